@@ -1,13 +1,14 @@
+use crate::config::{get_user_data_dir, Config};
 use once_cell::sync::Lazy;
 use rodio::{
-    self, buffer::SamplesBuffer, source::Source, Decoder as RodioDecoder,
-    OutputStream, OutputStreamBuilder, Sink,
+    self, buffer::SamplesBuffer, source::Source, Decoder as RodioDecoder, OutputStream,
+    OutputStreamBuilder, Sink,
 };
+use std::collections::{HashMap, HashSet};
 use std::fs::{self, File};
 use std::io::{BufReader, Cursor};
 use std::path::{Path, PathBuf};
-use std::collections::{HashSet, HashMap};
-use crate::config::{Config, get_user_data_dir};
+use std::time::Duration;
 
 // --- Rodio Global Audio System ---
 pub struct AudioSystem {
@@ -23,8 +24,7 @@ unsafe impl Send for AudioSystem {}
 unsafe impl Sync for AudioSystem {}
 
 pub static AUDIO: Lazy<AudioSystem> = Lazy::new(|| {
-    let stream = OutputStreamBuilder::open_default_stream()
-    .expect("Failed to load audio stream");
+    let stream = OutputStreamBuilder::open_default_stream().expect("Failed to load audio stream");
     AudioSystem { stream }
 });
 
@@ -32,7 +32,7 @@ pub static AUDIO: Lazy<AudioSystem> = Lazy::new(|| {
 
 pub fn load_sound_from_bytes(bytes: &[u8]) -> SamplesBuffer {
     let owned = bytes.to_vec().into_boxed_slice();
-    let cursor = Cursor::new(owned);               // Cursor<Box<[u8]>> is 'static
+    let cursor = Cursor::new(owned); // Cursor<Box<[u8]>> is 'static
     let decoder = rodio::Decoder::new(cursor).unwrap();
     let channels = decoder.channels();
     let sample_rate = decoder.sample_rate();
@@ -52,12 +52,61 @@ pub fn load_from_file(path: &Path) -> Result<SamplesBuffer, Box<dyn std::error::
 
 // --- SoundEffects Struct and Impl ---
 
+/// A sound pack is a directory of sounds named after what they're for, as
+/// WAV or OGG. The second name is what packs made before the launch and
+/// error sounds existed call the file.
+const PACK_SOUNDS: [(&str, &str); 5] = [
+    ("cursor", "move"),
+    ("select", "select"),
+    ("back", "back"),
+    ("launch", "launch"),
+    ("error", "reject"),
+];
+const PACK_EXTENSIONS: [&str; 2] = ["wav", "ogg"];
+/// Silence before each sound of a pack preview
+const PREVIEW_GAP: Duration = Duration::from_millis(250);
+
 #[derive(Clone)]
 pub struct SoundEffects {
     pub cursor_move: SamplesBuffer,
     pub select: SamplesBuffer,
+    /// The error sound
     pub reject: SamplesBuffer,
     pub back: SamplesBuffer,
+    pub launch: SamplesBuffer,
+}
+
+/// Directories a pack's sounds are looked up in, most specific first: the
+/// user's `sfx` folder, a theme that ships the pack, then the system `sfx` folder
+fn pack_dirs(pack_name: &str) -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    if let Some(user_dir) = get_user_data_dir().map(|d| d.join("sfx").join(pack_name)) {
+        dirs.push(user_dir);
+    }
+    if let Some(theme_dir) = find_sfx_pack_path(pack_name) {
+        dirs.push(theme_dir);
+    }
+    dirs.push(Path::new("../sfx").join(pack_name));
+    dirs.into_iter().filter(|dir| dir.is_dir()).collect()
+}
+
+/// The first of `names` found in the pack, or `fallback` if it has none
+fn load_pack_sound(dirs: &[PathBuf], names: &[&str], fallback: &SamplesBuffer) -> SamplesBuffer {
+    for dir in dirs {
+        for name in names {
+            for ext in PACK_EXTENSIONS {
+                let path = dir.join(format!("{}.{}", name, ext));
+                if !path.is_file() {
+                    continue;
+                }
+                match load_from_file(&path) {
+                    Ok(sound) => return sound,
+                    Err(e) => println!("[Audio] Failed to load {}: {}", path.display(), e),
+                }
+            }
+        }
+    }
+    fallback.clone()
 }
 
 impl SoundEffects {
@@ -67,42 +116,30 @@ impl SoundEffects {
         let default_reject = load_sound_from_bytes(include_bytes!("../reject.wav"));
         let default_back = load_sound_from_bytes(include_bytes!("../back.wav"));
 
-        if pack_name == "Default" {
-            return SoundEffects {
-                cursor_move: default_move,
-                select: default_select,
-                reject: default_reject,
-                back: default_back,
-            };
-        }
+        let dirs = if pack_name == "Default" {
+            Vec::new()
+        } else {
+            pack_dirs(pack_name)
+        };
+        let sound = |index: usize, fallback: &SamplesBuffer| {
+            let (name, legacy_name) = PACK_SOUNDS[index];
+            load_pack_sound(&dirs, &[name, legacy_name], fallback)
+        };
 
-        let system_pack_path = format!("../sfx/{}", pack_name);
-        let user_pack_path = find_sfx_pack_path(pack_name);
-
-        fn load_one_sfx(
-            name: &str,
-            user_path_base: &Option<PathBuf>,
-            system_path_base: &str,
-            fallback: &SamplesBuffer,
-        ) -> SamplesBuffer {
-            if let Some(base) = user_path_base {
-                if let Ok(sound) = load_from_file(&base.join(name)) {
-                    return sound;
-                }
-            }
-            let system_path = Path::new(system_path_base).join(name);
-            if let Ok(sound) = load_from_file(&system_path) {
-                return sound;
-            }
-            fallback.clone()
-        }
+        let cursor_move = sound(0, &default_move);
+        let select = sound(1, &default_select);
+        let back = sound(2, &default_back);
+        // There's no built-in launch sound; packs without one confirm with select
+        let launch = sound(3, &select);
+        let reject = sound(4, &default_reject);
 
-        let cursor_move = load_one_sfx("move.wav", &user_pack_path, &system_pack_path, &default_move);
-        let select = load_one_sfx("select.wav", &user_pack_path, &system_pack_path, &default_select);
-        let reject = load_one_sfx("reject.wav", &user_pack_path, &system_pack_path, &default_reject);
-        let back = load_one_sfx("back.wav", &user_pack_path, &system_pack_path, &default_back);
-
-        SoundEffects { cursor_move, select, reject, back }
+        SoundEffects {
+            cursor_move,
+            select,
+            reject,
+            back,
+            launch,
+        }
     }
 
     // [!] FIX: We manually create the Sink using .mixer() instead of .play_once()
@@ -135,6 +172,28 @@ impl SoundEffects {
         sink.append(source);
         sink.detach();
     }
+
+    pub fn play_launch(&self, config: &Config) {
+        let source = self.launch.clone().amplify(config.sfx_volume);
+        let sink = Sink::connect_new(&AUDIO.stream.mixer());
+        sink.append(source);
+        sink.detach();
+    }
+
+    /// Play each sound of the pack in turn, for trying packs out in Audio Settings
+    pub fn play_preview(&self, config: &Config) {
+        let sink = Sink::connect_new(&AUDIO.stream.mixer());
+        for sound in [
+            &self.cursor_move,
+            &self.select,
+            &self.back,
+            &self.launch,
+            &self.reject,
+        ] {
+            sink.append(sound.clone().amplify(config.sfx_volume).delay(PREVIEW_GAP));
+        }
+        sink.detach();
+    }
 }
 
 // --- Filesystem Functions ---
@@ -146,7 +205,9 @@ pub fn find_sfx_pack_path(pack_name: &str) -> Option<PathBuf> {
                 if theme_entry.path().is_dir() {
                     if let Ok(asset_entries) = fs::read_dir(theme_entry.path()) {
                         for asset_entry in asset_entries.flatten() {
-                            if asset_entry.path().is_dir() && asset_entry.file_name().to_string_lossy() == pack_name {
+                            if asset_entry.path().is_dir()
+                                && asset_entry.file_name().to_string_lossy() == pack_name
+                            {
                                 return Some(asset_entry.path());
                             }
                         }
@@ -186,7 +247,8 @@ pub fn find_sound_packs() -> Vec<String> {
                     if let Ok(asset_entries) = fs::read_dir(theme_entry.path()) {
                         for asset_entry in asset_entries.flatten() {
                             if asset_entry.path().is_dir() {
-                                packs.insert(asset_entry.file_name().to_string_lossy().into_owned());
+                                packs
+                                    .insert(asset_entry.file_name().to_string_lossy().into_owned());
                             }
                         }
                     }
@@ -216,10 +278,7 @@ pub fn play_new_bgm(
             // [!] FIX: Use Sink::connect_new with the mixer
             let sink = Sink::connect_new(&AUDIO.stream.mixer());
 
-            let source = sound_to_play
-            .clone()
-            .repeat_infinite()
-            .amplify(volume);
+            let source = sound_to_play.clone().repeat_infinite().amplify(volume);
 
             sink.append(source);
             *current_bgm = Some(sink);
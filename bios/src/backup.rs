@@ -0,0 +1,352 @@
+//! Nightly backups of internal saves to a designated drive
+//!
+//! When a backup drive has been picked in the Save Data screen, the BIOS
+//! checks once a minute whether it should run: the drive has to be mounted,
+//! the console idle for a while with no game running, and no backup done yet
+//! today. A thread then snapshots every internal save whose files changed
+//! since its last backup to `<drive>/kazeta/backups/<cart_id>/<time>.tar.zst`
+//! (`.tar.zst.enc` when the drive has save encryption turned on).
+//!
+//! `manifest.json` next to the snapshots records the size and modification
+//! time of each save's files at its last backup, which is how unchanged saves
+//! are skipped. Snapshots older than the retention window are pruned, always
+//! keeping the newest one of each game.
+
+use crate::{
+    config::get_user_data_dir,
+    save,
+    save_crypto::{self, ENCRYPTED_SUFFIX},
+};
+use chrono::{DateTime, Duration, Local, NaiveDateTime, TimeZone};
+use macroquad::prelude::get_time;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+};
+
+const SETTINGS_FILE: &str = "backup.toml";
+const MANIFEST_FILE: &str = "manifest.json";
+/// Snapshot names, which sort chronologically
+const SNAPSHOT_TIME_FORMAT: &str = "%Y%m%d-%H%M%S";
+const SNAPSHOT_EXT: &str = ".tar.zst";
+/// How often the scheduler looks at the conditions
+const CHECK_INTERVAL: f64 = 60.0;
+/// Idle time before a backup may start
+const IDLE_BEFORE_BACKUP: f64 = 10.0 * 60.0;
+const DEFAULT_RETENTION_DAYS: u32 = 14;
+
+/// Which drive gets the backups and how long snapshots are kept
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+struct BackupSettings {
+    /// Drive name as listed in the Save Data screen; empty disables backups
+    drive: String,
+    retention_days: u32,
+}
+
+impl Default for BackupSettings {
+    fn default() -> Self {
+        Self {
+            drive: String::new(),
+            retention_days: DEFAULT_RETENTION_DAYS,
+        }
+    }
+}
+
+fn settings_path() -> Option<PathBuf> {
+    get_user_data_dir().map(|dir| dir.join(SETTINGS_FILE))
+}
+
+impl BackupSettings {
+    fn load() -> Self {
+        let Some(path) = settings_path() else {
+            return Self::default();
+        };
+        let Ok(content) = fs::read_to_string(&path) else {
+            return Self::default();
+        };
+        toml::from_str(&content).unwrap_or_else(|e| {
+            println!("[Backup] Ignoring invalid {}: {}", path.display(), e);
+            Self::default()
+        })
+    }
+
+    fn save(&self) -> Result<(), String> {
+        let path = settings_path().ok_or("Could not find user's data directory.")?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        let content = toml::to_string_pretty(self).map_err(|e| e.to_string())?;
+        fs::write(&path, content).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+}
+
+/// Files of one save at its last backup
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct GameEntry {
+    last_backup: Option<DateTime<Local>>,
+    /// Relative path -> (size, modification time in seconds)
+    #[serde(default)]
+    files: BTreeMap<String, (u64, u64)>,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct Manifest {
+    last_run: Option<DateTime<Local>>,
+    #[serde(default)]
+    games: BTreeMap<String, GameEntry>,
+}
+
+/// Last backup time of each game, as last read from the drive's manifest
+static LAST_BACKUPS: Lazy<Mutex<HashMap<String, DateTime<Local>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+fn backups_dir(drive: &str) -> PathBuf {
+    save::get_drive_root(drive).join("kazeta").join("backups")
+}
+
+fn load_manifest(dir: &Path) -> Manifest {
+    let path = dir.join(MANIFEST_FILE);
+    let Ok(content) = fs::read_to_string(&path) else {
+        return Manifest::default();
+    };
+    serde_json::from_str(&content).unwrap_or_else(|e| {
+        println!("[Backup] Ignoring invalid {}: {}", path.display(), e);
+        Manifest::default()
+    })
+}
+
+/// Write the manifest under a temporary name first so a pulled drive never
+/// leaves a truncated one behind
+fn write_manifest(dir: &Path, manifest: &Manifest) -> Result<(), String> {
+    let path = dir.join(MANIFEST_FILE);
+    let partial = dir.join(format!(".{}.partial", MANIFEST_FILE));
+    let content = serde_json::to_string_pretty(manifest).map_err(|e| e.to_string())?;
+    fs::write(&partial, content)
+        .map_err(|e| format!("Failed to write {}: {}", partial.display(), e))?;
+    fs::rename(&partial, &path).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+fn publish(manifest: &Manifest) {
+    if let Ok(mut cache) = LAST_BACKUPS.lock() {
+        *cache = manifest
+            .games
+            .iter()
+            .filter_map(|(cart_id, entry)| entry.last_backup.map(|time| (cart_id.clone(), time)))
+            .collect();
+    }
+}
+
+/// The designated backup drive, if any
+pub fn backup_drive() -> Option<String> {
+    Some(BackupSettings::load().drive).filter(|drive| !drive.is_empty())
+}
+
+/// Designate the drive nightly backups go to, or stop backing up with `None`
+pub fn set_backup_drive(drive: Option<&str>) -> Result<(), String> {
+    let mut settings = BackupSettings::load();
+    settings.drive = drive.unwrap_or_default().to_string();
+    settings.save()?;
+    match drive {
+        Some(drive) => {
+            println!("[Backup] Nightly backups will go to '{}'", drive);
+            publish(&load_manifest(&backups_dir(drive)));
+        }
+        None => {
+            println!("[Backup] Nightly backups disabled");
+            publish(&Manifest::default());
+        }
+    }
+    Ok(())
+}
+
+/// When `cart_id` was last backed up to the designated drive
+pub fn last_backup(cart_id: &str) -> Option<DateTime<Local>> {
+    LAST_BACKUPS.lock().ok()?.get(cart_id).copied()
+}
+
+/// Decides from the main loop when to start a backup
+pub struct BackupScheduler {
+    last_check: f64,
+    running: Arc<AtomicBool>,
+}
+
+impl BackupScheduler {
+    pub fn new() -> Self {
+        // Check on the first frame so the Save Data screen gets the timestamps early
+        BackupScheduler {
+            last_check: get_time() - CHECK_INTERVAL,
+            running: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Called every frame with the current idle time
+    pub fn update(&mut self, idle_seconds: f64, game_running: bool) {
+        let now = get_time();
+        if now - self.last_check < CHECK_INTERVAL || self.running.load(Ordering::SeqCst) {
+            return;
+        }
+        self.last_check = now;
+
+        let settings = BackupSettings::load();
+        if settings.drive.is_empty() || !save::get_drive_root(&settings.drive).is_dir() {
+            return;
+        }
+
+        let dir = backups_dir(&settings.drive);
+        let encrypt = save_crypto::is_drive_encrypted(&save::get_drive_root(&settings.drive));
+        let manifest = load_manifest(&dir);
+        publish(&manifest);
+
+        let done_today = manifest
+            .last_run
+            .is_some_and(|t| t.date_naive() == Local::now().date_naive());
+        if done_today
+            || game_running
+            || idle_seconds < IDLE_BEFORE_BACKUP
+            || save::save_write_in_progress()
+        {
+            return;
+        }
+
+        self.running.store(true, Ordering::SeqCst);
+        let running = self.running.clone();
+        thread::spawn(move || {
+            println!("[Backup] Starting nightly backup to '{}'", settings.drive);
+            match run_backup(&dir, manifest, settings.retention_days, encrypt) {
+                Ok((backed_up, pruned)) => println!(
+                    "[Backup] Backed up {} save(s), pruned {} old snapshot(s)",
+                    backed_up, pruned
+                ),
+                Err(e) => println!("[ERROR] Nightly backup failed: {}", e),
+            }
+            running.store(false, Ordering::SeqCst);
+        });
+    }
+}
+
+/// Internal saves, by cart id
+fn internal_cart_ids() -> Vec<String> {
+    let save_dir = save::get_save_dir_from_drive_name("internal");
+    let Ok(entries) = fs::read_dir(&save_dir) else {
+        return Vec::new();
+    };
+    entries
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().is_dir())
+        .filter_map(|e| e.file_name().to_str().map(str::to_string))
+        .collect()
+}
+
+/// Snapshot the changed saves and prune old snapshots. Returns how many saves
+/// were backed up and how many snapshots were removed.
+fn run_backup(
+    dir: &Path,
+    mut manifest: Manifest,
+    retention_days: u32,
+    encrypt: bool,
+) -> Result<(usize, usize), String> {
+    fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+
+    let mut backed_up = 0;
+    for cart_id in internal_cart_ids() {
+        let files: BTreeMap<String, (u64, u64)> = match save::internal_save_files(&cart_id) {
+            Ok(files) => files
+                .into_iter()
+                .map(|(name, size, mtime)| (name, (size, mtime)))
+                .collect(),
+            Err(e) => {
+                println!("[Backup] Skipping {}: {}", cart_id, e);
+                continue;
+            }
+        };
+        if files.is_empty()
+            || manifest
+                .games
+                .get(&cart_id)
+                .is_some_and(|entry| entry.files == files)
+        {
+            continue;
+        }
+        // Stop for the night if the user picked up the controller and a game started saving
+        if save::save_write_in_progress() {
+            return Err("a save is being written".to_string());
+        }
+
+        let now = Local::now();
+        let suffix = if encrypt { ENCRYPTED_SUFFIX } else { "" };
+        let snapshot = dir.join(&cart_id).join(format!(
+            "{}{}{}",
+            now.format(SNAPSHOT_TIME_FORMAT),
+            SNAPSHOT_EXT,
+            suffix
+        ));
+        save::snapshot_internal_save(&cart_id, &snapshot)
+            .map_err(|e| format!("{}: {}", cart_id, e))?;
+
+        manifest.games.insert(
+            cart_id.clone(),
+            GameEntry {
+                last_backup: Some(now),
+                files,
+            },
+        );
+        write_manifest(dir, &manifest)?;
+        publish(&manifest);
+        backed_up += 1;
+    }
+
+    let pruned = prune(dir, retention_days);
+    manifest.last_run = Some(Local::now());
+    write_manifest(dir, &manifest)?;
+    save::sync_to_disk();
+    Ok((backed_up, pruned))
+}
+
+fn snapshot_time(path: &Path) -> Option<DateTime<Local>> {
+    let name = path.file_name()?.to_str()?;
+    let name = name
+        .strip_suffix(ENCRYPTED_SUFFIX)
+        .unwrap_or(name)
+        .strip_suffix(SNAPSHOT_EXT)?;
+    let naive = NaiveDateTime::parse_from_str(name, SNAPSHOT_TIME_FORMAT).ok()?;
+    Local.from_local_datetime(&naive).earliest()
+}
+
+/// Remove snapshots older than the retention window, keeping each game's newest
+fn prune(dir: &Path, retention_days: u32) -> usize {
+    let cutoff = Local::now() - Duration::days(retention_days as i64);
+    let Ok(games) = fs::read_dir(dir) else {
+        return 0;
+    };
+
+    let mut pruned = 0;
+    for game in games.filter_map(|e| e.ok()).filter(|e| e.path().is_dir()) {
+        let Ok(entries) = fs::read_dir(game.path()) else {
+            continue;
+        };
+        let mut snapshots: Vec<(DateTime<Local>, PathBuf)> = entries
+            .filter_map(|e| e.ok())
+            .filter_map(|e| snapshot_time(&e.path()).map(|time| (time, e.path())))
+            .collect();
+        snapshots.sort();
+        snapshots.pop();
+
+        for (_, path) in snapshots.into_iter().filter(|(time, _)| *time < cutoff) {
+            match fs::remove_file(&path) {
+                Ok(()) => pruned += 1,
+                Err(e) => println!("[Backup] Failed to remove {}: {}", path.display(), e),
+            }
+        }
+    }
+    pruned
+}
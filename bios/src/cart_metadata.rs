@@ -0,0 +1,220 @@
+use crate::config::get_user_data_dir;
+use crate::save::CartInfo;
+use std::{
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+/// Image extensions offered when picking a cart icon
+const ICON_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg"];
+
+/// The .kzi fields editable from the BIOS
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CartMetadata {
+    pub name: String,
+    /// Icon path relative to the .kzi's directory
+    pub icon: String,
+    pub max_players: Option<u8>,
+    pub ra_game_name: String,
+}
+
+/// Where edited metadata ended up
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SaveLocation {
+    /// Written back into the .kzi itself
+    Cart,
+    /// The cart is read-only, so an override file in the user data dir
+    Override,
+}
+
+impl CartMetadata {
+    pub fn from_cart(cart_info: &CartInfo) -> Self {
+        Self {
+            name: cart_info.name.clone().unwrap_or_default(),
+            icon: cart_info.icon.clone(),
+            max_players: cart_info.max_players,
+            ra_game_name: cart_info.ra_game_name.clone().unwrap_or_default(),
+        }
+    }
+
+    /// The fields as .kzi keys; empty text fields are left out
+    fn fields(&self) -> Vec<(&'static str, Option<String>)> {
+        let text = |s: &str| (!s.trim().is_empty()).then(|| s.trim().to_string());
+        vec![
+            ("Name", text(&self.name)),
+            ("Icon", text(&self.icon)),
+            ("MaxPlayers", self.max_players.map(|n| n.to_string())),
+            ("RA_Game_Name", text(&self.ra_game_name)),
+        ]
+    }
+}
+
+fn override_path(cart_id: &str) -> Option<PathBuf> {
+    get_user_data_dir().map(|dir| dir.join("cart-metadata").join(format!("{}.kzi", cart_id)))
+}
+
+/// Splits a .kzi line into its lowercased key and unquoted value
+fn parse_line(line: &str) -> Option<(String, &str)> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+        return None;
+    }
+    let (k, v) = line.split_once('=')?;
+    let mut value = v.trim();
+    if value.starts_with('"') && value.ends_with('"') && value.len() >= 2 {
+        value = &value[1..value.len() - 1];
+    }
+    Some((k.trim().to_lowercase(), value))
+}
+
+/// Applies edits stored for a read-only cart on top of its parsed .kzi
+pub fn apply_override(cart_info: &mut CartInfo) {
+    let Some(path) = override_path(&cart_info.id) else {
+        return;
+    };
+    let Ok(content) = fs::read_to_string(&path) else {
+        return;
+    };
+
+    for line in content.lines() {
+        let Some((key, value)) = parse_line(line) else {
+            continue;
+        };
+        match key.as_str() {
+            "name" => cart_info.name = Some(value.to_string()),
+            "icon" => cart_info.icon = value.to_string(),
+            "maxplayers" => cart_info.max_players = value.parse().ok(),
+            "ra_game_name" => cart_info.ra_game_name = Some(value.to_string()),
+            _ => {}
+        }
+    }
+}
+
+/// Rewrites the edited keys of a .kzi in place, keeping comments, ordering
+/// and every other key untouched. Keys that weren't present are appended.
+fn update_kzi_content(content: &str, metadata: &CartMetadata) -> String {
+    let fields = metadata.fields();
+    let mut written = vec![false; fields.len()];
+    let mut lines = Vec::new();
+
+    for line in content.lines() {
+        let key = parse_line(line).map(|(key, _)| key);
+        let field = key.as_deref().and_then(|key| {
+            fields.iter().position(|(name, _)| {
+                name.to_lowercase() == key
+                    || (*name == "RA_Game_Name" && matches!(key, "ra-game-name" | "ra game name"))
+            })
+        });
+        match field {
+            // Later duplicates would win over the edit when parsed, so drop them
+            Some(i) if written[i] => {}
+            Some(i) => {
+                written[i] = true;
+                if let (name, Some(value)) = &fields[i] {
+                    lines.push(format!("{}={}", name, value));
+                }
+            }
+            None => lines.push(line.to_string()),
+        }
+    }
+
+    for (i, (name, value)) in fields.iter().enumerate() {
+        if let (false, Some(value)) = (written[i], value) {
+            lines.push(format!("{}={}", name, value));
+        }
+    }
+
+    let mut updated = lines.join("\n");
+    updated.push('\n');
+    updated
+}
+
+/// Writes a file through a temporary sibling so a removed cart or power loss
+/// can't leave it half written
+fn write_atomic(path: &Path, content: &str) -> std::io::Result<()> {
+    let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(".tmp");
+    let tmp_path = path.with_file_name(tmp_name);
+
+    let result = (|| {
+        let mut file = fs::File::create(&tmp_path)?;
+        file.write_all(content.as_bytes())?;
+        file.sync_all()?;
+        fs::rename(&tmp_path, path)
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp_path);
+    }
+    result
+}
+
+/// Saves edited metadata into the .kzi, or into an override file when the
+/// cart can't be written to
+pub fn save(
+    kzi_path: &Path,
+    cart_id: &str,
+    metadata: &CartMetadata,
+) -> Result<SaveLocation, String> {
+    let content = fs::read_to_string(kzi_path)
+        .map_err(|e| format!("Failed to read {}: {}", kzi_path.display(), e))?;
+    let updated = update_kzi_content(&content, metadata);
+
+    let override_path = override_path(cart_id);
+    match write_atomic(kzi_path, &updated) {
+        Ok(()) => {
+            // The cart is authoritative again; drop any older override
+            if let Some(path) = override_path.filter(|p| p.exists()) {
+                let _ = fs::remove_file(path);
+            }
+            println!("[Cart] Saved metadata to {}", kzi_path.display());
+            Ok(SaveLocation::Cart)
+        }
+        Err(e) => {
+            println!(
+                "[Cart] {} is not writable ({}), saving an override instead",
+                kzi_path.display(),
+                e
+            );
+            let path = override_path.ok_or("Could not find user's data directory.")?;
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)
+                    .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+            }
+            let content: String = metadata
+                .fields()
+                .into_iter()
+                .filter_map(|(name, value)| value.map(|value| format!("{}={}\n", name, value)))
+                .collect();
+            fs::write(&path, content)
+                .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+            println!("[Cart] Saved metadata override for {}", cart_id);
+            Ok(SaveLocation::Override)
+        }
+    }
+}
+
+/// Images next to the .kzi that can be used as its icon, relative to its directory
+pub fn find_icons(kzi_path: &Path) -> Vec<String> {
+    let Some(dir) = kzi_path.parent() else {
+        return Vec::new();
+    };
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut icons: Vec<String> = entries
+        .flatten()
+        .filter(|entry| entry.path().is_file())
+        .filter(|entry| {
+            entry
+                .path()
+                .extension()
+                .and_then(|e| e.to_str())
+                .is_some_and(|ext| ICON_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+        })
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .collect();
+    icons.sort();
+    icons
+}
@@ -0,0 +1,195 @@
+use crate::config::Config;
+use crate::save::{self, ConflictResolution};
+use std::{
+    path::PathBuf,
+    sync::{atomic::AtomicU16, Arc},
+};
+
+const USAGE: &str = "\
+Usage: kazeta-bios saves <command> [options]
+
+Commands:
+  drives                                   List drives that can hold saves
+  list   [--drive NAME]                    List saves on a drive
+  copy   <cart-id> --from NAME --to NAME   Copy a save between drives
+         [--overwrite | --keep-both]
+  delete <cart-id> [--drive NAME]          Delete a save
+  backup [--drive NAME] [--output DIR]     Archive every save on a drive
+  encrypt <on|off> --drive NAME            Encrypt saves written to a drive
+
+The drive defaults to \"internal\".";
+
+/// Runs a command-line subcommand without starting the UI.
+/// Returns the exit code, or `None` when the BIOS should start normally.
+pub fn run(args: &[String]) -> Option<i32> {
+    match args.first().map(String::as_str) {
+        Some("saves") => {}
+        Some("-h" | "--help") => {
+            println!("{}", USAGE);
+            return Some(0);
+        }
+        _ => return None,
+    }
+
+    let config = Config::load();
+    save::set_compress_archives(config.compress_external_saves);
+
+    match run_saves(&args[1..]) {
+        Ok(()) => Some(0),
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            Some(1)
+        }
+    }
+}
+
+fn run_saves(args: &[String]) -> Result<(), String> {
+    let Some(command) = args.first() else {
+        println!("{}", USAGE);
+        return Ok(());
+    };
+    let options = Options::parse(&args[1..])?;
+
+    match command.as_str() {
+        "drives" => {
+            let devices =
+                save::list_devices().map_err(|e| format!("Failed to list drives: {}", e))?;
+            for (name, free_mb) in devices {
+                if name == "internal" || (save::has_save_dir(&name) && !save::is_cart(&name)) {
+                    let encrypted = if save::is_drive_encrypted(&name) {
+                        "\tencrypted"
+                    } else {
+                        ""
+                    };
+                    println!("{}\t{} MB free{}", name, free_mb, encrypted);
+                }
+            }
+            Ok(())
+        }
+        "list" => {
+            let drive = options.drive();
+            let saves = save::get_save_details(drive)
+                .map_err(|e| format!("Failed to read saves on '{}': {}", drive, e))?;
+            for save::SaveDetails { cart_id, name, .. } in saves {
+                let metadata = save::get_save_metadata(&cart_id, drive);
+                let last_played = metadata.last_played.map_or_else(
+                    || "-".to_string(),
+                    |t| t.format("%Y-%m-%d %H:%M").to_string(),
+                );
+                let encrypted = if save::is_save_encrypted(&cart_id, drive) {
+                    "\tencrypted"
+                } else {
+                    ""
+                };
+                println!(
+                    "{}\t{}\t{:.1} MB\t{:.1} h\t{}{}",
+                    cart_id,
+                    name,
+                    metadata.size_mb,
+                    metadata.playtime_hours,
+                    last_played,
+                    encrypted
+                );
+            }
+            Ok(())
+        }
+        "copy" => {
+            let cart_id = options.cart_id()?;
+            let from = options.from.as_deref().ok_or("copy needs --from")?;
+            let to = options.to.as_deref().ok_or("copy needs --to")?;
+            let progress = Arc::new(AtomicU16::new(0));
+
+            if options.resolution.is_none() && from != to && save::save_exists(cart_id, to) {
+                return Err(format!(
+                    "A save for {} already exists on '{}'; pass --overwrite or --keep-both",
+                    cart_id, to
+                ));
+            }
+            save::copy_save_with_resolution(cart_id, from, to, progress, options.resolution)
+                .map_err(|e| e.to_string())?;
+            println!("Copied {} from '{}' to '{}'", cart_id, from, to);
+            Ok(())
+        }
+        "delete" => {
+            let cart_id = options.cart_id()?;
+            let drive = options.drive();
+            save::delete_save(cart_id, drive).map_err(|e| e.to_string())?;
+            println!("Deleted {} from '{}'", cart_id, drive);
+            Ok(())
+        }
+        "backup" => {
+            let output = match &options.output {
+                Some(dir) => dir.clone(),
+                None => std::env::current_dir().map_err(|e| e.to_string())?,
+            };
+            let path = save::backup_saves(options.drive(), &output).map_err(|e| e.to_string())?;
+            println!("{}", path.display());
+            Ok(())
+        }
+        "encrypt" => {
+            let enabled = match options.positional.first().map(String::as_str) {
+                Some("on") => true,
+                Some("off") => false,
+                _ => return Err("encrypt needs 'on' or 'off'".to_string()),
+            };
+            let drive = options.drive.as_deref().ok_or("encrypt needs --drive")?;
+            save::set_drive_encrypted(drive, enabled).map_err(|e| e.to_string())?;
+            println!(
+                "New saves on '{}' will {}be encrypted",
+                drive,
+                if enabled { "" } else { "not " }
+            );
+            Ok(())
+        }
+        other => Err(format!("Unknown command '{}'\n\n{}", other, USAGE)),
+    }
+}
+
+#[derive(Default)]
+struct Options {
+    /// Positional arguments (the cart id)
+    positional: Vec<String>,
+    drive: Option<String>,
+    from: Option<String>,
+    to: Option<String>,
+    output: Option<PathBuf>,
+    resolution: Option<ConflictResolution>,
+}
+
+impl Options {
+    fn parse(args: &[String]) -> Result<Self, String> {
+        let mut options = Options::default();
+        let mut args = args.iter();
+
+        while let Some(arg) = args.next() {
+            let mut value = |flag: &str| {
+                args.next()
+                    .cloned()
+                    .ok_or_else(|| format!("{} needs a value", flag))
+            };
+            match arg.as_str() {
+                "--drive" => options.drive = Some(value(arg)?),
+                "--from" => options.from = Some(value(arg)?),
+                "--to" => options.to = Some(value(arg)?),
+                "--output" => options.output = Some(PathBuf::from(value(arg)?)),
+                "--overwrite" => options.resolution = Some(ConflictResolution::Overwrite),
+                "--keep-both" => options.resolution = Some(ConflictResolution::KeepBoth),
+                flag if flag.starts_with("--") => return Err(format!("Unknown option '{}'", flag)),
+                _ => options.positional.push(arg.clone()),
+            }
+        }
+
+        Ok(options)
+    }
+
+    fn drive(&self) -> &str {
+        self.drive.as_deref().unwrap_or("internal")
+    }
+
+    fn cart_id(&self) -> Result<&str, String> {
+        self.positional
+            .first()
+            .map(String::as_str)
+            .ok_or_else(|| "Missing cart id".to_string())
+    }
+}
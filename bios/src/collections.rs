@@ -0,0 +1,290 @@
+//! User-made collections of carts ("RPGs", "Kids", "Co-op")
+//!
+//! Kept in `collections.json` next to the config: the collection names in
+//! the order they were made, and for each cart ID the collections it's in.
+//! A cart can be in any number of them. Carts that aren't connected keep
+//! their assignments for when they come back.
+
+use crate::config::get_user_data_dir;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Longest collection name accepted
+pub const MAX_NAME_LEN: usize = 32;
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Collections {
+    /// Collection names, in the order they were made
+    #[serde(default)]
+    pub names: Vec<String>,
+    /// Cart ID -> the collections it's in
+    #[serde(default)]
+    pub carts: BTreeMap<String, Vec<String>>,
+}
+
+fn collections_path() -> Option<PathBuf> {
+    get_user_data_dir().map(|dir| dir.join("collections.json"))
+}
+
+impl Collections {
+    pub fn load() -> Self {
+        collections_path()
+            .map(|path| Self::load_from(&path))
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        let Some(path) = collections_path() else {
+            return;
+        };
+        if let Err(e) = self.save_to(&path) {
+            println!("[Collections] Failed to write {}: {}", path.display(), e);
+        }
+    }
+
+    /// Collections stored at `path`; none if it's missing or unreadable
+    fn load_from(path: &Path) -> Self {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    fn save_to(&self, path: &Path) -> Result<(), String> {
+        if let Some(parent) = path.parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+        let json = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        fs::write(path, json).map_err(|e| e.to_string())
+    }
+
+    /// Trimmed name, if it's usable and not taken (case-insensitively) by
+    /// a collection other than `except`
+    fn check_name(&self, name: &str, except: Option<&str>) -> Result<String, String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("Name can't be empty".to_string());
+        }
+        if name.chars().count() > MAX_NAME_LEN {
+            return Err(format!(
+                "Name can't be longer than {} characters",
+                MAX_NAME_LEN
+            ));
+        }
+        let taken = self
+            .names
+            .iter()
+            .any(|n| n.eq_ignore_ascii_case(name) && Some(n.as_str()) != except);
+        if taken {
+            return Err(format!("There's already a collection called {}", name));
+        }
+        Ok(name.to_string())
+    }
+
+    /// Make an empty collection. Returns its name as stored.
+    pub fn create(&mut self, name: &str) -> Result<String, String> {
+        let name = self.check_name(name, None)?;
+        self.names.push(name.clone());
+        Ok(name)
+    }
+
+    /// Rename a collection, keeping its carts. Returns the new name.
+    pub fn rename(&mut self, old: &str, new: &str) -> Result<String, String> {
+        let new = self.check_name(new, Some(old))?;
+        let slot = self
+            .names
+            .iter_mut()
+            .find(|n| *n == old)
+            .ok_or_else(|| format!("No collection called {}", old))?;
+        *slot = new.clone();
+        for names in self.carts.values_mut() {
+            for name in names.iter_mut().filter(|n| *n == old) {
+                *name = new.clone();
+            }
+        }
+        Ok(new)
+    }
+
+    /// Delete a collection. The carts themselves are untouched.
+    pub fn delete(&mut self, name: &str) {
+        self.names.retain(|n| n != name);
+        for names in self.carts.values_mut() {
+            names.retain(|n| n != name);
+        }
+        self.carts.retain(|_, names| !names.is_empty());
+    }
+
+    pub fn contains(&self, cart_id: &str, name: &str) -> bool {
+        self.carts
+            .get(cart_id)
+            .is_some_and(|names| names.iter().any(|n| n == name))
+    }
+
+    /// Add a cart to a collection or take it out. Returns whether it's in
+    /// the collection now.
+    pub fn toggle(&mut self, cart_id: &str, name: &str) -> bool {
+        if self.contains(cart_id, name) {
+            if let Some(names) = self.carts.get_mut(cart_id) {
+                names.retain(|n| n != name);
+                if names.is_empty() {
+                    self.carts.remove(cart_id);
+                }
+            }
+            false
+        } else {
+            self.carts
+                .entry(cart_id.to_string())
+                .or_default()
+                .push(name.to_string());
+            true
+        }
+    }
+
+    /// Carts in a collection, connected or not
+    pub fn count(&self, name: &str) -> usize {
+        self.carts
+            .values()
+            .filter(|names| names.iter().any(|n| n == name))
+            .count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn carts(collections: &Collections, name: &str) -> Vec<String> {
+        collections
+            .carts
+            .iter()
+            .filter(|(_, names)| names.iter().any(|n| n == name))
+            .map(|(cart, _)| cart.clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_create_checks_names() {
+        let mut collections = Collections::default();
+        assert_eq!(collections.create("  RPGs "), Ok("RPGs".to_string()));
+        assert_eq!(collections.create("Kids"), Ok("Kids".to_string()));
+        assert_eq!(collections.names, ["RPGs", "Kids"]);
+
+        assert_eq!(
+            collections.create("rpgs"),
+            Err("There's already a collection called rpgs".to_string())
+        );
+        assert_eq!(
+            collections.create("   "),
+            Err("Name can't be empty".to_string())
+        );
+        let long = "x".repeat(MAX_NAME_LEN + 1);
+        assert!(collections.create(&long).is_err());
+        assert!(collections.create(&"x".repeat(MAX_NAME_LEN)).is_ok());
+        // Counted in characters, not bytes
+        assert!(collections.create(&"é".repeat(MAX_NAME_LEN)).is_ok());
+    }
+
+    #[test]
+    fn test_cart_in_several_collections() {
+        let mut collections = Collections::default();
+        collections.create("RPGs").unwrap();
+        collections.create("Co-op").unwrap();
+
+        assert!(collections.toggle("celeste", "Co-op"));
+        assert!(collections.toggle("ff6", "RPGs"));
+        assert!(collections.toggle("ff6", "Co-op"));
+        assert!(collections.contains("ff6", "RPGs") && collections.contains("ff6", "Co-op"));
+        assert_eq!(collections.count("Co-op"), 2);
+        assert_eq!(collections.count("RPGs"), 1);
+
+        // Taking a cart out of one leaves it in the others
+        assert!(!collections.toggle("ff6", "Co-op"));
+        assert!(collections.contains("ff6", "RPGs"));
+        assert_eq!(carts(&collections, "Co-op"), ["celeste"]);
+
+        // Out of every collection, it's dropped from the file
+        assert!(!collections.toggle("celeste", "Co-op"));
+        assert!(!collections.carts.contains_key("celeste"));
+        assert_eq!(collections.count("Co-op"), 0);
+    }
+
+    #[test]
+    fn test_rename_keeps_carts() {
+        let mut collections = Collections::default();
+        collections.create("RPGs").unwrap();
+        collections.create("Kids").unwrap();
+        collections.toggle("ff6", "RPGs");
+        collections.toggle("ff6", "Kids");
+
+        assert_eq!(
+            collections.rename("RPGs", "Role-playing"),
+            Ok("Role-playing".to_string())
+        );
+        assert_eq!(collections.names, ["Role-playing", "Kids"]);
+        assert_eq!(collections.carts["ff6"], ["Role-playing", "Kids"]);
+
+        // Changing only the case is fine, taking another's name isn't
+        assert_eq!(collections.rename("Kids", "KIDS"), Ok("KIDS".to_string()));
+        assert!(collections.rename("KIDS", "role-playing").is_err());
+        assert_eq!(
+            collections.rename("Puzzles", "Brain"),
+            Err("No collection called Puzzles".to_string())
+        );
+        assert_eq!(collections.names, ["Role-playing", "KIDS"]);
+    }
+
+    #[test]
+    fn test_delete_leaves_other_collections() {
+        let mut collections = Collections::default();
+        collections.create("RPGs").unwrap();
+        collections.create("Kids").unwrap();
+        collections.toggle("ff6", "RPGs");
+        collections.toggle("ff6", "Kids");
+        collections.toggle("chrono", "RPGs");
+
+        collections.delete("RPGs");
+        assert_eq!(collections.names, ["Kids"]);
+        assert_eq!(collections.carts["ff6"], ["Kids"]);
+        assert!(!collections.carts.contains_key("chrono"));
+
+        // Deleting one that doesn't exist changes nothing
+        collections.delete("RPGs");
+        assert_eq!(collections.names, ["Kids"]);
+        // The name is free again
+        assert!(collections.create("RPGs").is_ok());
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("kazeta-plus").join("collections.json");
+        assert!(Collections::load_from(&path).names.is_empty());
+
+        let mut collections = Collections::default();
+        collections.create("RPGs").unwrap();
+        collections.create("Co-op").unwrap();
+        collections.toggle("ff6", "RPGs");
+        collections.toggle("ff6", "Co-op");
+        collections.save_to(&path).unwrap();
+
+        let loaded = Collections::load_from(&path);
+        assert_eq!(loaded.names, ["RPGs", "Co-op"]);
+        assert_eq!(loaded.carts, collections.carts);
+
+        // A damaged file reads as no collections rather than failing
+        fs::write(&path, "{ not json").unwrap();
+        assert!(Collections::load_from(&path).names.is_empty());
+        // Fields missing from older files default
+        fs::write(&path, r#"{"names":["Kids"]}"#).unwrap();
+        assert_eq!(Collections::load_from(&path).names, ["Kids"]);
+
+        // Next to config.toml
+        assert!(collections_path()
+            .unwrap()
+            .ends_with(".local/share/kazeta-plus/collections.json"));
+    }
+}
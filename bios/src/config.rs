@@ -1,6 +1,6 @@
-use serde::{Deserialize, Serialize};
-use std::{fs, path::PathBuf, error::Error};
 use crate::MenuPosition;
+use serde::{Deserialize, Serialize};
+use std::{error::Error, fs, path::PathBuf};
 
 /// Returns the path to the user's data directory for Kazeta+.
 /// This is a public helper function for other modules to use.
@@ -9,7 +9,7 @@ pub fn get_user_data_dir() -> Option<PathBuf> {
 }
 
 /// Gets the full path to the kazeta.toml configuration file.
-fn get_config_path() -> Result<PathBuf, Box<dyn Error>> {
+pub fn get_config_path() -> Result<PathBuf, Box<dyn Error>> {
     let mut config_path = get_user_data_dir().ok_or("Could not find user's data directory.")?;
     fs::create_dir_all(&config_path)?; // Create the directory if it doesn't exist
     config_path.push("config.toml");
@@ -42,8 +42,15 @@ pub struct Config {
     pub logo_selection: String,
     pub background_selection: String,
     pub font_selection: String,
+    /// "Default", "OFF" for the animated logo, or a video in the theme or
+    /// backgrounds folder
     pub splash_video: String,
+    /// "Default", "OFF", or a track name from the music folder
     pub splash_audio: String,
+    /// Animated instead of a splash video; "Default" uses the selected logo,
+    /// like `farewell_image`
+    #[serde(default = "default_splash_image")]
+    pub splash_image: String,
     #[serde(default)]
     pub loading_messages: Vec<String>,
     // RetroAchievements settings
@@ -62,6 +69,76 @@ pub struct Config {
     pub blade_transparency: f32,
     #[serde(default)]
     pub blade_blur_enabled: bool,
+    // Idle settings (minutes without input, 0 = off)
+    #[serde(default = "default_idle_dim_minutes")]
+    pub idle_dim_minutes: u32,
+    #[serde(default)]
+    pub idle_suspend_minutes: u32,
+    /// Write external-drive save archives as zstd-compressed .tar.zst
+    #[serde(default)]
+    pub compress_external_saves: bool,
+    // System update settings
+    #[serde(default = "default_update_channel")]
+    pub update_channel: String,
+    /// Manifest location; `{channel}` is replaced with the selected channel.
+    /// Empty (the default) leaves update checks off.
+    #[serde(default)]
+    pub update_manifest_url: String,
+    // Kiosk settings (attract mode after minutes without input, 0 = off)
+    #[serde(default)]
+    pub kiosk_attract_minutes: u32,
+    #[serde(default = "default_kiosk_slide_seconds")]
+    pub kiosk_slide_seconds: u32,
+    /// "MENU" keeps the menu music, otherwise a track name or "OFF"
+    #[serde(default = "default_kiosk_attract_music")]
+    pub kiosk_attract_music: String,
+    /// Only carts listed in `kiosk_whitelist` can be launched
+    #[serde(default)]
+    pub kiosk_whitelist_enabled: bool,
+    /// Cart IDs allowed while the whitelist is enabled
+    #[serde(default)]
+    pub kiosk_whitelist: Vec<String>,
+    // Remote control from a companion app on the LAN
+    #[serde(default)]
+    pub remote_enabled: bool,
+    #[serde(default = "default_remote_port")]
+    pub remote_port: u16,
+    /// Ask on screen before a remote launch
+    #[serde(default = "default_remote_confirm")]
+    pub remote_confirm_launch: bool,
+    /// Ask on screen before a remote shutdown
+    #[serde(default = "default_remote_confirm")]
+    pub remote_confirm_power: bool,
+    /// Companion apps allowed to send commands, added from the pairing screen
+    #[serde(default)]
+    pub remote_devices: Vec<RemoteDevice>,
+    /// Interface language code, picked in the setup wizard
+    #[serde(default = "default_language")]
+    pub language: String,
+    // Farewell screen, shown after the fade-out when the session restarts
+    #[serde(default = "default_show_farewell_screen")]
+    pub show_farewell_screen: bool,
+    /// "Default" uses the selected logo; otherwise an image in the theme,
+    /// backgrounds or logos folder
+    #[serde(default = "default_farewell_image")]
+    pub farewell_image: String,
+    /// "OFF", or a track name from the music folder
+    #[serde(default = "default_farewell_audio")]
+    pub farewell_audio: String,
+    #[serde(default = "default_farewell_message")]
+    pub farewell_message: String,
+    // Display output mode, chosen in System Settings -> General -> Display
+    #[serde(default)]
+    pub display_output: Option<String>,
+    /// e.g. "1920x1080@60.00"; None leaves the display at its default mode
+    #[serde(default)]
+    pub display_mode: Option<String>,
+    // Network share carts are also loaded from, System Settings -> General
+    #[serde(default)]
+    pub network_share: NetworkShareConfig,
+    // Temperature warnings; the readings are on the About screen
+    #[serde(default)]
+    pub thermal: ThermalConfig,
 }
 
 /// RetroAchievements configuration
@@ -87,14 +164,159 @@ pub struct RetroAchievementsConfig {
     pub notification_duration: u32,
 }
 
-fn default_ra_enabled() -> bool { true }
-fn default_ra_notifications() -> bool { true }
-fn default_ra_notification_duration() -> u32 { 5000 }
+/// A companion app paired with the remote control API
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub struct RemoteDevice {
+    pub name: String,
+    /// SHA-256 of the token the device sends with every request, so the
+    /// config file can't be used to impersonate it; revoking the device
+    /// invalidates the token. Empty for devices paired before tokens were
+    /// hashed, which have to pair again.
+    #[serde(default)]
+    pub token_hash: String,
+    /// Unix timestamp
+    #[serde(default)]
+    pub paired_at: i64,
+}
+
+/// An SMB or NFS share mounted as an extra cart source
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub struct NetworkShareConfig {
+    /// Mount the share at startup and keep it mounted
+    #[serde(default)]
+    pub enabled: bool,
+    /// "SMB" or "NFS"
+    #[serde(default = "default_share_protocol")]
+    pub protocol: String,
+    #[serde(default)]
+    pub host: String,
+    /// SMB share name, or the NFS export path
+    #[serde(default)]
+    pub share: String,
+    /// SMB only; no username mounts as guest
+    #[serde(default)]
+    pub username: Option<String>,
+    /// SMB only; name of the 0600 file next to config.toml that holds the
+    /// password (see `netshare::set_password`), never the password itself
+    #[serde(default)]
+    pub credentials: Option<String>,
+}
+
+impl Default for NetworkShareConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            protocol: default_share_protocol(),
+            host: String::new(),
+            share: String::new(),
+            username: None,
+            credentials: None,
+        }
+    }
+}
+
+/// When to warn about temperatures
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+pub struct ThermalConfig {
+    /// CPU or GPU temperature that triggers a warning
+    #[serde(default = "default_thermal_warning_temp")]
+    pub warning_temp_c: u32,
+    /// Suggest a frame limit when a handheld stays this hot during a game
+    #[serde(default = "default_thermal_suggest_frame_limit")]
+    pub suggest_frame_limit: bool,
+    /// How long it has to stay over the threshold first
+    #[serde(default = "default_thermal_sustained_seconds")]
+    pub sustained_seconds: u32,
+}
+
+impl Default for ThermalConfig {
+    fn default() -> Self {
+        Self {
+            warning_temp_c: default_thermal_warning_temp(),
+            suggest_frame_limit: default_thermal_suggest_frame_limit(),
+            sustained_seconds: default_thermal_sustained_seconds(),
+        }
+    }
+}
+
+fn default_share_protocol() -> String {
+    "SMB".to_string()
+}
+fn default_thermal_warning_temp() -> u32 {
+    90
+}
+fn default_thermal_suggest_frame_limit() -> bool {
+    true
+}
+fn default_thermal_sustained_seconds() -> u32 {
+    120
+}
+
+fn default_ra_enabled() -> bool {
+    true
+}
+fn default_ra_notifications() -> bool {
+    true
+}
+fn default_ra_notification_duration() -> u32 {
+    5000
+}
+
+fn default_blade_games_color() -> String {
+    "#00CC44".to_string()
+}
+fn default_blade_settings_color() -> String {
+    "#CC6600".to_string()
+}
+fn default_blade_saves_color() -> String {
+    "#6600CC".to_string()
+}
+fn default_blade_transparency() -> f32 {
+    0.95
+}
+
+fn default_idle_dim_minutes() -> u32 {
+    5
+}
+
+fn default_kiosk_slide_seconds() -> u32 {
+    8
+}
+fn default_kiosk_attract_music() -> String {
+    "MENU".to_string()
+}
+
+fn default_remote_port() -> u16 {
+    crate::remote::DEFAULT_PORT
+}
+fn default_remote_confirm() -> bool {
+    true
+}
+
+fn default_language() -> String {
+    "en".to_string()
+}
+
+fn default_splash_image() -> String {
+    "Default".to_string()
+}
 
-fn default_blade_games_color() -> String { "#00CC44".to_string() }
-fn default_blade_settings_color() -> String { "#CC6600".to_string() }
-fn default_blade_saves_color() -> String { "#6600CC".to_string() }
-fn default_blade_transparency() -> f32 { 0.95 }
+fn default_show_farewell_screen() -> bool {
+    true
+}
+fn default_farewell_image() -> String {
+    "Default".to_string()
+}
+fn default_farewell_audio() -> String {
+    "OFF".to_string()
+}
+fn default_farewell_message() -> String {
+    "See you next time".to_string()
+}
+
+fn default_update_channel() -> String {
+    "stable".to_string()
+}
 
 impl Default for Config {
     fn default() -> Self {
@@ -125,6 +347,7 @@ impl Default for Config {
             font_selection: "Default".to_string(),
             splash_video: "Default".to_string(),
             splash_audio: "Default".to_string(),
+            splash_image: default_splash_image(),
             loading_messages: Vec::new(), // Empty means use default messages
             retroachievements: RetroAchievementsConfig::default(),
             blades_enabled: false,
@@ -133,6 +356,30 @@ impl Default for Config {
             blade_saves_color: default_blade_saves_color(),
             blade_transparency: default_blade_transparency(),
             blade_blur_enabled: false,
+            idle_dim_minutes: default_idle_dim_minutes(),
+            idle_suspend_minutes: 0,
+            compress_external_saves: false,
+            update_channel: default_update_channel(),
+            update_manifest_url: String::new(),
+            kiosk_attract_minutes: 0,
+            kiosk_slide_seconds: default_kiosk_slide_seconds(),
+            kiosk_attract_music: default_kiosk_attract_music(),
+            kiosk_whitelist_enabled: false,
+            kiosk_whitelist: Vec::new(),
+            remote_enabled: false,
+            remote_port: default_remote_port(),
+            remote_confirm_launch: default_remote_confirm(),
+            remote_confirm_power: default_remote_confirm(),
+            remote_devices: Vec::new(),
+            language: default_language(),
+            show_farewell_screen: default_show_farewell_screen(),
+            farewell_image: default_farewell_image(),
+            farewell_audio: default_farewell_audio(),
+            farewell_message: default_farewell_message(),
+            display_output: None,
+            display_mode: None,
+            network_share: NetworkShareConfig::default(),
+            thermal: ThermalConfig::default(),
         }
     }
 }
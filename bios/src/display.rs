@@ -0,0 +1,213 @@
+//! Display output modes
+//!
+//! Modes are listed and switched through the kazeta-display helper, which
+//! drives wlr-randr or xrandr depending on the session. When the helper can't
+//! list anything, the connected DRM connectors in sysfs still give the
+//! resolutions, just without refresh rates. A mode the user keeps is saved
+//! in the config and applied again at startup.
+
+use crate::{config::Config, utils::apply_resolution, DEV_MODE};
+use std::{fs, process::Command, thread};
+
+const DISPLAY_HELPER: &str = "/usr/bin/kazeta-display";
+const DRM_DIR: &str = "/sys/class/drm";
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DisplayMode {
+    pub output: String,
+    pub width: u32,
+    pub height: u32,
+    /// None when the mode came from sysfs, which doesn't list rates
+    pub refresh: Option<f32>,
+    pub current: bool,
+    pub preferred: bool,
+}
+
+impl DisplayMode {
+    pub fn size(&self) -> String {
+        format!("{}x{}", self.width, self.height)
+    }
+
+    pub fn label(&self) -> String {
+        match self.refresh {
+            Some(rate) => format!("{} @ {:.2}HZ", self.size(), rate),
+            None => self.size(),
+        }
+    }
+
+    /// How the mode is stored in the config, e.g. `1920x1080@60.00`
+    pub fn id(&self) -> String {
+        match self.refresh {
+            Some(rate) => format!("{}@{:.2}", self.size(), rate),
+            None => self.size(),
+        }
+    }
+
+    fn same_mode(&self, other: &DisplayMode) -> bool {
+        self.output == other.output && self.id() == other.id()
+    }
+}
+
+/// Parses one `OUTPUT WxH RATE FLAGS` line of `kazeta-display list`
+fn parse_helper_line(line: &str) -> Option<DisplayMode> {
+    let mut fields = line.split_whitespace();
+    let output = fields.next()?.to_string();
+    let (width, height) = fields.next()?.split_once('x')?;
+    let refresh = fields.next()?.parse().ok();
+    let flags = fields.next().unwrap_or("-");
+    Some(DisplayMode {
+        output,
+        width: width.parse().ok()?,
+        height: height.parse().ok()?,
+        refresh,
+        current: flags.split(',').any(|f| f == "current"),
+        preferred: flags.split(',').any(|f| f == "preferred"),
+    })
+}
+
+fn helper_modes() -> Result<Vec<DisplayMode>, String> {
+    let output = Command::new(DISPLAY_HELPER)
+        .arg("list")
+        .output()
+        .map_err(|e| format!("Failed to run display helper: {}", e))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter_map(parse_helper_line)
+        .collect())
+}
+
+/// Resolutions of the connected DRM connectors; the kernel lists the
+/// preferred mode first
+fn drm_modes() -> Vec<DisplayMode> {
+    let Ok(entries) = fs::read_dir(DRM_DIR) else {
+        return Vec::new();
+    };
+    let mut connectors: Vec<_> = entries.flatten().map(|e| e.path()).collect();
+    connectors.sort();
+
+    let mut modes: Vec<DisplayMode> = Vec::new();
+    for path in connectors {
+        let status = fs::read_to_string(path.join("status")).unwrap_or_default();
+        if status.trim() != "connected" {
+            continue;
+        }
+        // card0-HDMI-A-1 -> HDMI-A-1
+        let name = path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned();
+        let output = name
+            .split_once('-')
+            .map(|(_, o)| o.to_string())
+            .unwrap_or(name);
+        let list = fs::read_to_string(path.join("modes")).unwrap_or_default();
+        for (i, line) in list.lines().enumerate() {
+            let Some((w, h)) = line.trim().split_once('x') else {
+                continue;
+            };
+            // Interlaced modes end in "i"
+            let (Ok(width), Ok(height)) = (w.parse(), h.parse()) else {
+                continue;
+            };
+            let mode = DisplayMode {
+                output: output.clone(),
+                width,
+                height,
+                refresh: None,
+                current: false,
+                preferred: i == 0,
+            };
+            if !modes.iter().any(|m| m.same_mode(&mode)) {
+                modes.push(mode);
+            }
+        }
+    }
+    modes
+}
+
+/// Every mode of every connected output
+pub fn list_modes() -> Result<Vec<DisplayMode>, String> {
+    match helper_modes() {
+        Ok(modes) if !modes.is_empty() => Ok(modes),
+        result => {
+            if let Err(e) = result {
+                println!(
+                    "[Display] Helper couldn't list modes, reading DRM instead: {}",
+                    e
+                );
+            }
+            let modes = drm_modes();
+            if modes.is_empty() {
+                Err("No connected displays found".to_string())
+            } else {
+                Ok(modes)
+            }
+        }
+    }
+}
+
+/// Switches the output to `mode` and resizes the window to match
+pub fn apply_mode(mode: &DisplayMode) -> Result<(), String> {
+    set_mode(mode)?;
+    apply_resolution(&mode.size());
+    Ok(())
+}
+
+/// Runs the helper. Safe off the main thread, unlike the window resize.
+fn set_mode(mode: &DisplayMode) -> Result<(), String> {
+    println!("[Display] Switching {} to {}", mode.output, mode.label());
+    if DEV_MODE {
+        println!("[DEV_MODE] Skipping mode switch.");
+        return Ok(());
+    }
+
+    let mut command = Command::new(DISPLAY_HELPER);
+    command.arg("set").arg(&mode.output).arg(mode.size());
+    if let Some(rate) = mode.refresh {
+        command.arg(format!("{:.2}", rate));
+    }
+    let output = command
+        .output()
+        .map_err(|e| format!("Failed to run display helper: {}", e))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    Ok(())
+}
+
+/// Remembers `mode` as the one to use at startup
+pub fn save_mode(config: &mut Config, mode: &DisplayMode) {
+    config.display_output = Some(mode.output.clone());
+    config.display_mode = Some(mode.id());
+    config.save();
+}
+
+/// Applies the saved mode, if any, in the background at startup
+pub fn restore_saved_mode(config: &Config) {
+    let (Some(output), Some(id)) = (config.display_output.clone(), config.display_mode.clone())
+    else {
+        return;
+    };
+    thread::spawn(move || {
+        let modes = match list_modes() {
+            Ok(modes) => modes,
+            Err(e) => {
+                println!("[Display] Can't restore {} on {}: {}", id, output, e);
+                return;
+            }
+        };
+        match modes.iter().find(|m| m.output == output && m.id() == id) {
+            Some(mode) if mode.current => {}
+            Some(mode) => {
+                if let Err(e) = set_mode(mode) {
+                    println!("[ERROR] Failed to restore display mode: {}", e);
+                }
+            }
+            None => println!("[Display] Saved mode {} isn't available on {}", id, output),
+        }
+    });
+}
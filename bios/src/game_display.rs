@@ -0,0 +1,153 @@
+use crate::config::get_user_data_dir;
+use serde::{Deserialize, Serialize};
+use std::{fs, path::PathBuf, process::Command};
+
+/// Aspect ratios the emulator wrappers understand
+pub const ASPECT_RATIOS: &[&str] = &["native", "4:3", "16:9"];
+
+/// Texture filters the emulator wrappers understand
+pub const FILTERS: &[&str] = &["nearest", "bilinear"];
+
+/// Frame rate caps the emulator wrappers understand
+pub const FRAME_LIMITS: &[&str] = &["off", "30", "45", "60"];
+
+/// System and user directories searched for CRT shaders
+const SHADER_DIRS: &[&str] = &["/usr/share/kazeta/shaders"];
+
+/// Display settings for one cart, handed to the emulator wrappers as
+/// `KAZETA_*` environment variables when the game launches
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct GameDisplaySettings {
+    pub integer_scaling: bool,
+    pub aspect_ratio: String,
+    pub filter: String,
+    /// Shader name without extension; `None` disables shaders
+    pub shader: Option<String>,
+    /// FPS cap, e.g. to keep a handheld cool; "off" runs uncapped
+    pub frame_limit: String,
+}
+
+impl Default for GameDisplaySettings {
+    fn default() -> Self {
+        Self {
+            integer_scaling: false,
+            aspect_ratio: ASPECT_RATIOS[0].to_string(),
+            filter: FILTERS[0].to_string(),
+            shader: None,
+            frame_limit: FRAME_LIMITS[0].to_string(),
+        }
+    }
+}
+
+fn settings_path(cart_id: &str) -> Option<PathBuf> {
+    get_user_data_dir().map(|dir| dir.join("display").join(format!("{}.toml", cart_id)))
+}
+
+impl GameDisplaySettings {
+    /// Loads the settings stored for a cart, or the defaults
+    pub fn load(cart_id: &str) -> Self {
+        let Some(path) = settings_path(cart_id) else {
+            return Self::default();
+        };
+        let Ok(content) = fs::read_to_string(&path) else {
+            return Self::default();
+        };
+        toml::from_str(&content).unwrap_or_else(|e| {
+            println!("[Display] Ignoring invalid {}: {}", path.display(), e);
+            Self::default()
+        })
+    }
+
+    /// Stores the settings for a cart; default settings remove the file
+    pub fn save(&self, cart_id: &str) -> Result<(), String> {
+        let path = settings_path(cart_id).ok_or("Could not find user's data directory.")?;
+
+        if *self == Self::default() {
+            if path.exists() {
+                fs::remove_file(&path)
+                    .map_err(|e| format!("Failed to remove {}: {}", path.display(), e))?;
+            }
+            return Ok(());
+        }
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        let content = toml::to_string_pretty(self).map_err(|e| e.to_string())?;
+        fs::write(&path, content)
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        println!("[Display] Saved display settings for {}", cart_id);
+        Ok(())
+    }
+
+    pub fn scale_mode(&self) -> &'static str {
+        if self.integer_scaling {
+            "integer"
+        } else {
+            "fit"
+        }
+    }
+
+    /// Environment variables read by the emulator wrappers
+    pub fn env_vars(&self) -> Vec<(&'static str, String)> {
+        let mut vars = vec![
+            ("KAZETA_SCALE_MODE", self.scale_mode().to_string()),
+            ("KAZETA_ASPECT_RATIO", self.aspect_ratio.clone()),
+            ("KAZETA_FILTER", self.filter.clone()),
+        ];
+        if let Some(shader) = &self.shader {
+            vars.push(("KAZETA_SHADER", shader.clone()));
+        }
+        if self.frame_limit != FRAME_LIMITS[0] {
+            vars.push(("KAZETA_FRAME_LIMIT", self.frame_limit.clone()));
+        }
+        vars
+    }
+
+    /// Sets the wrapper environment variables on a launch command
+    pub fn apply(&self, command: &mut Command) {
+        for (key, value) in self.env_vars() {
+            command.env(key, value);
+        }
+    }
+}
+
+/// Cycles to the next/previous entry of `options`
+pub fn cycle(options: &[&str], current: &str, forward: bool) -> String {
+    let index = options.iter().position(|&o| o == current).unwrap_or(0);
+    let new_index = if forward {
+        (index + 1) % options.len()
+    } else {
+        (index + options.len() - 1) % options.len()
+    };
+    options[new_index].to_string()
+}
+
+/// Names of the installed shaders, sorted
+pub fn available_shaders() -> Vec<String> {
+    let mut dirs: Vec<PathBuf> = SHADER_DIRS.iter().map(PathBuf::from).collect();
+    if let Some(user_dir) = get_user_data_dir() {
+        dirs.push(user_dir.join("shaders"));
+    }
+
+    let mut shaders: Vec<String> = dirs
+        .iter()
+        .filter_map(|dir| fs::read_dir(dir).ok())
+        .flat_map(|entries| entries.flatten())
+        .filter_map(|entry| {
+            let path = entry.path();
+            path.file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+        })
+        .collect();
+    shaders.sort();
+    shaders.dedup();
+    shaders
+}
+
+/// Quotes a value for the shell command in the launch file
+pub fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
@@ -0,0 +1,427 @@
+use crate::{library, save::CartInfo};
+use image::{ImageFormat, Rgba, RgbaImage};
+use std::{
+    fs,
+    io::Read,
+    path::{Path, PathBuf},
+};
+
+/// Marker path for the built-in .kzp icon, resolved by the game grid loader
+pub const KZP_PLACEHOLDER: &str = "::KZP_PLACEHOLDER::";
+
+/// Side of a generated icon in pixels
+const ICON_SIZE: u32 = 128;
+
+/// Height of the console band at the bottom of a generated icon
+const BAND_HEIGHT: u32 = 28;
+
+/// Enough of a ROM to cover both the GB and GBA headers
+const HEADER_LEN: usize = 0x150;
+
+/// 5x7 glyphs for the initials and the console band, one row per byte
+const GLYPHS: &[(char, [u8; 7])] = &[
+    ('A', [0x0E, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11]),
+    ('B', [0x1E, 0x11, 0x11, 0x1E, 0x11, 0x11, 0x1E]),
+    ('C', [0x0E, 0x11, 0x10, 0x10, 0x10, 0x11, 0x0E]),
+    ('D', [0x1E, 0x11, 0x11, 0x11, 0x11, 0x11, 0x1E]),
+    ('E', [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x1F]),
+    ('F', [0x1F, 0x10, 0x10, 0x1E, 0x10, 0x10, 0x10]),
+    ('G', [0x0E, 0x11, 0x10, 0x17, 0x11, 0x11, 0x0F]),
+    ('H', [0x11, 0x11, 0x11, 0x1F, 0x11, 0x11, 0x11]),
+    ('I', [0x0E, 0x04, 0x04, 0x04, 0x04, 0x04, 0x0E]),
+    ('J', [0x07, 0x02, 0x02, 0x02, 0x02, 0x12, 0x0C]),
+    ('K', [0x11, 0x12, 0x14, 0x18, 0x14, 0x12, 0x11]),
+    ('L', [0x10, 0x10, 0x10, 0x10, 0x10, 0x10, 0x1F]),
+    ('M', [0x11, 0x1B, 0x15, 0x15, 0x11, 0x11, 0x11]),
+    ('N', [0x11, 0x11, 0x19, 0x15, 0x13, 0x11, 0x11]),
+    ('O', [0x0E, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E]),
+    ('P', [0x1E, 0x11, 0x11, 0x1E, 0x10, 0x10, 0x10]),
+    ('Q', [0x0E, 0x11, 0x11, 0x11, 0x15, 0x12, 0x0D]),
+    ('R', [0x1E, 0x11, 0x11, 0x1E, 0x14, 0x12, 0x11]),
+    ('S', [0x0F, 0x10, 0x10, 0x0E, 0x01, 0x01, 0x1E]),
+    ('T', [0x1F, 0x04, 0x04, 0x04, 0x04, 0x04, 0x04]),
+    ('U', [0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x0E]),
+    ('V', [0x11, 0x11, 0x11, 0x11, 0x11, 0x0A, 0x04]),
+    ('W', [0x11, 0x11, 0x11, 0x15, 0x15, 0x15, 0x0A]),
+    ('X', [0x11, 0x11, 0x0A, 0x04, 0x0A, 0x11, 0x11]),
+    ('Y', [0x11, 0x11, 0x11, 0x0A, 0x04, 0x04, 0x04]),
+    ('Z', [0x1F, 0x01, 0x02, 0x04, 0x08, 0x10, 0x1F]),
+    ('0', [0x0E, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0E]),
+    ('1', [0x04, 0x0C, 0x04, 0x04, 0x04, 0x04, 0x0E]),
+    ('2', [0x0E, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1F]),
+    ('3', [0x1F, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0E]),
+    ('4', [0x02, 0x06, 0x0A, 0x12, 0x1F, 0x02, 0x02]),
+    ('5', [0x1F, 0x10, 0x1E, 0x01, 0x01, 0x11, 0x0E]),
+    ('6', [0x06, 0x08, 0x10, 0x1E, 0x11, 0x11, 0x0E]),
+    ('7', [0x1F, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08]),
+    ('8', [0x0E, 0x11, 0x11, 0x0E, 0x11, 0x11, 0x0E]),
+    ('9', [0x0E, 0x11, 0x11, 0x0F, 0x01, 0x02, 0x0C]),
+];
+
+/// What the header of a GB, GBC or GBA ROM tells us
+struct RomHeader {
+    console: &'static str,
+    title: String,
+    /// GBA game code, e.g. "AXVE"
+    code: Option<String>,
+    /// The 48x8 boot logo stored in GB headers
+    logo: Option<[u8; 48]>,
+}
+
+/// Where generated icons are cached
+fn icons_dir() -> Option<PathBuf> {
+    dirs::cache_dir().map(|dir| dir.join("kazeta-plus/icons"))
+}
+
+/// The icon to show for a cart: its own icon or a .kzp's sidecar image when
+/// there is one, otherwise a generated one.
+pub fn icon_for(cart_info: &CartInfo, game_path: &Path) -> PathBuf {
+    let is_package = game_path.extension().map_or(false, |e| e == "kzp");
+    let own_icon = if is_package {
+        [
+            game_path.with_extension("png"),
+            game_path.with_extension("jpg"),
+        ]
+        .into_iter()
+        .find(|p| p.exists())
+    } else {
+        game_path
+            .parent()
+            .map(|dir| dir.join(&cart_info.icon))
+            .filter(|p| p.exists())
+    };
+    if let Some(icon) = own_icon {
+        return icon;
+    }
+
+    let header = rom_path(cart_info, game_path).and_then(|path| read_header(&path));
+    let title = cart_info
+        .name
+        .clone()
+        .or_else(|| header.as_ref().map(|h| h.title.clone()))
+        .unwrap_or_else(|| cart_info.id.clone());
+    let console = match &header {
+        Some(header) => match &header.code {
+            Some(code) => format!("{} {}", header.console, code),
+            None => header.console.to_string(),
+        },
+        None if is_package => "KZP".to_string(),
+        None => cart_info.runtime.as_deref().unwrap_or("").to_uppercase(),
+    };
+    let logo = header.and_then(|h| h.logo);
+
+    match generated_icon(&cart_info.id, &title, &console, logo.as_ref()) {
+        Some(path) => path,
+        None if is_package => PathBuf::from(KZP_PLACEHOLDER),
+        None => game_path
+            .parent()
+            .map(|dir| dir.join(&cart_info.icon))
+            .unwrap_or_default(),
+    }
+}
+
+/// Icon for a save whose cart left no icon.png in the save cache. Carts in
+/// the library get the same icon as in the game list.
+pub fn icon_for_save(cart_id: &str, name: &str, cached_icon: &str) -> String {
+    if Path::new(cached_icon).exists() {
+        return cached_icon.to_string();
+    }
+    if let Some((cart_info, game_path)) = library::games()
+        .into_iter()
+        .find(|(cart, _)| cart.id == cart_id)
+    {
+        return icon_for(&cart_info, &game_path)
+            .to_string_lossy()
+            .into_owned();
+    }
+    let title = if name.is_empty() { cart_id } else { name };
+    generated_icon(cart_id, title, "", None)
+        .map(|path| path.to_string_lossy().into_owned())
+        .unwrap_or_else(|| cached_icon.to_string())
+}
+
+/// Path of the cached icon for these inputs, drawing it first if needed
+fn generated_icon(
+    cart_id: &str,
+    title: &str,
+    console: &str,
+    logo: Option<&[u8; 48]>,
+) -> Option<PathBuf> {
+    let inputs = [
+        title.as_bytes(),
+        b"\0",
+        console.as_bytes(),
+        b"\0",
+        logo.map_or(&[][..], |l| &l[..]),
+    ]
+    .concat();
+    let file_name = format!(
+        "{}-{:08x}.png",
+        cart_id.replace('/', "_"),
+        fnv1a(&inputs) as u32
+    );
+    let path = icons_dir()?.join(file_name);
+    if path.exists() {
+        return Some(path);
+    }
+
+    let result = fs::create_dir_all(path.parent()?)
+        .map_err(|e| e.to_string())
+        .and_then(|_| {
+            let tmp = path.with_extension("png.tmp");
+            draw_icon(cart_id, title, console, logo)
+                .save_with_format(&tmp, ImageFormat::Png)
+                .map_err(|e| e.to_string())?;
+            fs::rename(&tmp, &path).map_err(|e| e.to_string())
+        });
+    match result {
+        Ok(()) => {
+            println!("[Icons] Generated icon for {}", cart_id);
+            Some(path)
+        }
+        Err(e) => {
+            println!("[Icons] Failed to write {}: {}", path.display(), e);
+            None
+        }
+    }
+}
+
+/// Stable hash, so icon colours and file names survive toolchain updates
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, b| {
+        (hash ^ *b as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// The ROM a .kzi cart runs, if it's a GB, GBC or GBA one
+fn rom_path(cart_info: &CartInfo, game_path: &Path) -> Option<PathBuf> {
+    if game_path.extension().map_or(true, |e| e != "kzi") {
+        return None;
+    }
+    let path = game_path.parent()?.join(&cart_info.exec);
+    let extension = path.extension()?.to_str()?.to_lowercase();
+    (matches!(extension.as_str(), "gb" | "gbc" | "gba") && path.is_file()).then_some(path)
+}
+
+fn read_header(path: &Path) -> Option<RomHeader> {
+    let mut bytes = Vec::with_capacity(HEADER_LEN);
+    fs::File::open(path)
+        .ok()?
+        .take(HEADER_LEN as u64)
+        .read_to_end(&mut bytes)
+        .ok()?;
+    if bytes.len() < HEADER_LEN {
+        return None;
+    }
+    parse_gba_header(&bytes).or_else(|| parse_gb_header(&bytes))
+}
+
+fn header_text(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .take_while(|b| **b != 0)
+        .filter(|b| b.is_ascii_graphic() || **b == b' ')
+        .map(|b| *b as char)
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+/// GBA header: fixed 0x96 at 0xB2 and a complement check at 0xBD
+fn parse_gba_header(bytes: &[u8]) -> Option<RomHeader> {
+    let check = bytes[0xA0..=0xBC]
+        .iter()
+        .fold(0u8, |sum, b| sum.wrapping_sub(*b))
+        .wrapping_sub(0x19);
+    if bytes[0xB2] != 0x96 || check != bytes[0xBD] {
+        return None;
+    }
+    let code = header_text(&bytes[0xAC..0xB0]);
+    Some(RomHeader {
+        console: "GBA",
+        title: header_text(&bytes[0xA0..0xAC]),
+        code: (code.len() == 4).then_some(code),
+        logo: None,
+    })
+}
+
+/// GB header: checksum over 0x134..=0x14C stored at 0x14D
+fn parse_gb_header(bytes: &[u8]) -> Option<RomHeader> {
+    let check = bytes[0x134..=0x14C]
+        .iter()
+        .fold(0u8, |sum, b| sum.wrapping_sub(*b).wrapping_sub(1));
+    if check != bytes[0x14D] {
+        return None;
+    }
+    let color = bytes[0x143] & 0x80 != 0;
+    // Color carts use the title's last bytes for the manufacturer code and flag
+    let title_end = if color { 0x13F } else { 0x144 };
+    let mut logo = [0u8; 48];
+    logo.copy_from_slice(&bytes[0x104..0x134]);
+    Some(RomHeader {
+        console: if color { "GBC" } else { "GB" },
+        title: header_text(&bytes[0x134..title_end]),
+        code: None,
+        logo: Some(logo),
+    })
+}
+
+/// Whether pixel (x, y) of the 48x8 GB header logo is set. The logo is two
+/// rows of twelve 4x4 blocks; each byte holds two 4-pixel rows of a block.
+fn logo_pixel(logo: &[u8; 48], x: u32, y: u32) -> bool {
+    let (half, row) = (y / 4, y % 4);
+    let byte = logo[(half * 24 + (x / 4) * 2 + row / 2) as usize];
+    let nibble = if row % 2 == 0 { byte >> 4 } else { byte & 0x0F };
+    nibble & (0x8 >> (x % 4)) != 0
+}
+
+/// Up to two letters standing for the title, e.g. "ZELDA LINK'S AWAKENING" -> "ZL"
+fn initials(title: &str) -> String {
+    let words: Vec<String> = title
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(|w| w.to_ascii_uppercase())
+        .collect();
+    match words.as_slice() {
+        [] => "?".to_string(),
+        [word] => word.chars().take(2).collect(),
+        [first, second, ..] => [first, second]
+            .iter()
+            .filter_map(|w| w.chars().next())
+            .collect(),
+    }
+}
+
+fn hsv(hue: f32, saturation: f32, value: f32) -> [f32; 3] {
+    let c = value * saturation;
+    let h = (hue.rem_euclid(360.0)) / 60.0;
+    let x = c * (1.0 - (h % 2.0 - 1.0).abs());
+    let (r, g, b) = match h as u32 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+    let m = value - c;
+    [r + m, g + m, b + m]
+}
+
+fn to_pixel(color: [f32; 3], alpha: u8) -> Rgba<u8> {
+    Rgba([
+        (color[0] * 255.0) as u8,
+        (color[1] * 255.0) as u8,
+        (color[2] * 255.0) as u8,
+        alpha,
+    ])
+}
+
+fn fill_rect(image: &mut RgbaImage, x: u32, y: u32, w: u32, h: u32, color: Rgba<u8>) {
+    for py in y..(y + h).min(image.height()) {
+        for px in x..(x + w).min(image.width()) {
+            image.put_pixel(px, py, color);
+        }
+    }
+}
+
+/// Width in pixels of `text` drawn with `draw_text` at `scale`
+fn text_width(text: &str, scale: u32) -> u32 {
+    let chars = text.chars().count() as u32;
+    (chars * 6).saturating_sub(1) * scale
+}
+
+fn draw_text(image: &mut RgbaImage, text: &str, x: u32, y: u32, scale: u32, color: Rgba<u8>) {
+    for (i, c) in text.chars().enumerate() {
+        let Some((_, rows)) = GLYPHS.iter().find(|(g, _)| *g == c) else {
+            continue;
+        };
+        let left = x + i as u32 * 6 * scale;
+        for (row, bits) in rows.iter().enumerate() {
+            for col in 0..5 {
+                if bits & (0x10 >> col) != 0 {
+                    fill_rect(
+                        image,
+                        left + col * scale,
+                        y + row as u32 * scale,
+                        scale,
+                        scale,
+                        color,
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Gradient in a colour picked from the cart ID, the title's initials and a
+/// band naming the console (the GB header logo where there is one)
+fn draw_icon(cart_id: &str, title: &str, console: &str, logo: Option<&[u8; 48]>) -> RgbaImage {
+    let hue = (fnv1a(cart_id.as_bytes()) % 360) as f32;
+    let top = hsv(hue, 0.65, 0.85);
+    let bottom = hsv(hue + 40.0, 0.8, 0.35);
+
+    let mut image = RgbaImage::new(ICON_SIZE, ICON_SIZE);
+    for y in 0..ICON_SIZE {
+        for x in 0..ICON_SIZE {
+            let t = (x + y) as f32 / (2 * (ICON_SIZE - 1)) as f32;
+            let color = [0, 1, 2].map(|i| top[i] + (bottom[i] - top[i]) * t);
+            image.put_pixel(x, y, to_pixel(color, 255));
+        }
+    }
+
+    let white = Rgba([255, 255, 255, 255]);
+    let shadow = Rgba([0, 0, 0, 90]);
+    let letters = initials(title);
+    let scale = if letters.chars().count() > 1 { 7 } else { 9 };
+    let x = (ICON_SIZE - text_width(&letters, scale)) / 2;
+    let y = (ICON_SIZE - BAND_HEIGHT - 7 * scale) / 2;
+    draw_text(&mut image, &letters, x + 2, y + 2, scale, shadow);
+    draw_text(&mut image, &letters, x, y, scale, white);
+
+    let has_band = logo.is_some() || !console.is_empty();
+    if has_band {
+        let band_y = ICON_SIZE - BAND_HEIGHT;
+        fill_rect(
+            &mut image,
+            0,
+            band_y,
+            ICON_SIZE,
+            BAND_HEIGHT,
+            Rgba([0, 0, 0, 200]),
+        );
+        match logo {
+            Some(logo) => {
+                // 48x8 logo doubled, centred in the band
+                let left = (ICON_SIZE - 96) / 2;
+                let top = band_y + (BAND_HEIGHT - 16) / 2;
+                for ly in 0..8 {
+                    for lx in 0..48 {
+                        if logo_pixel(logo, lx, ly) {
+                            fill_rect(&mut image, left + lx * 2, top + ly * 2, 2, 2, white);
+                        }
+                    }
+                }
+            }
+            None => {
+                let label: String = console.chars().take(10).collect();
+                let scale = if text_width(&label, 2) <= ICON_SIZE - 8 {
+                    2
+                } else {
+                    1
+                };
+                let x = ICON_SIZE.saturating_sub(text_width(&label, scale)) / 2;
+                draw_text(
+                    &mut image,
+                    &label,
+                    x,
+                    band_y + (BAND_HEIGHT - 7 * scale) / 2,
+                    scale,
+                    white,
+                );
+            }
+        }
+    }
+
+    image
+}
@@ -0,0 +1,300 @@
+use crate::{
+    config::Config, get_current_font, measure_text, text_with_config_color, InputState, DEV_MODE,
+    FONT_SIZE,
+};
+use macroquad::prelude::*;
+use std::{collections::HashMap, fs, process::Command, thread, time::SystemTime};
+
+/// Touched by kazeta-input-daemon whenever it sees input on any device, so
+/// activity on devices gilrs doesn't know about still keeps the BIOS awake.
+pub const INPUT_ACTIVITY_FILE: &str = "/tmp/kazeta-input-activity";
+
+/// Selectable timeouts (minutes) for the dim and suspend settings. 0 means off.
+pub const IDLE_TIMEOUTS: &[u32] = &[0, 1, 2, 5, 10, 15, 30, 60];
+
+/// How long the "suspending in..." countdown is shown before suspending
+const SUSPEND_COUNTDOWN_SECS: f64 = 30.0;
+/// How often to check the input daemon's activity file
+const DAEMON_CHECK_INTERVAL: f64 = 1.0;
+/// Opacity of the black layer drawn over the BIOS while dimmed
+const DIM_ALPHA: f32 = 0.75;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum IdlePhase {
+    Active,
+    Dimmed,
+    /// Counting down to suspend; holds the seconds remaining
+    SuspendCountdown(f64),
+}
+
+pub struct IdleTracker {
+    last_activity: f64,
+    last_daemon_activity: Option<SystemTime>,
+    last_daemon_check: f64,
+    pub phase: IdlePhase,
+}
+
+/// Formats an idle timeout for the settings screen
+pub fn format_timeout(minutes: u32) -> String {
+    if minutes == 0 {
+        "OFF".to_string()
+    } else {
+        format!("{} MIN", minutes)
+    }
+}
+
+/// Steps through IDLE_TIMEOUTS, wrapping around at either end
+pub fn cycle_timeout(current: u32, forward: bool) -> u32 {
+    let index = IDLE_TIMEOUTS
+        .iter()
+        .position(|&m| m == current)
+        .unwrap_or(0);
+    let new_index = if forward {
+        (index + 1) % IDLE_TIMEOUTS.len()
+    } else {
+        (index + IDLE_TIMEOUTS.len() - 1) % IDLE_TIMEOUTS.len()
+    };
+    IDLE_TIMEOUTS[new_index]
+}
+
+/// Which phase `idle_secs` without input puts the BIOS in. The countdown
+/// takes over from dimming for the last `SUSPEND_COUNTDOWN_SECS` before
+/// suspend, and starts right away when suspend is due sooner than that.
+fn idle_phase(idle_secs: f64, config: &Config) -> IdlePhase {
+    let dim_secs = config.idle_dim_minutes as f64 * 60.0;
+    let suspend_secs = config.idle_suspend_minutes as f64 * 60.0;
+
+    if config.idle_suspend_minutes > 0 && idle_secs >= suspend_secs - SUSPEND_COUNTDOWN_SECS {
+        IdlePhase::SuspendCountdown((suspend_secs - idle_secs).max(0.0))
+    } else if config.idle_dim_minutes > 0 && idle_secs >= dim_secs {
+        IdlePhase::Dimmed
+    } else {
+        IdlePhase::Active
+    }
+}
+
+fn daemon_activity_time() -> Option<SystemTime> {
+    fs::metadata(INPUT_ACTIVITY_FILE)
+        .and_then(|m| m.modified())
+        .ok()
+}
+
+impl IdleTracker {
+    pub fn new() -> Self {
+        IdleTracker {
+            last_activity: get_time(),
+            last_daemon_activity: daemon_activity_time(),
+            last_daemon_check: get_time(),
+            phase: IdlePhase::Active,
+        }
+    }
+
+    /// Marks the system as active right now (e.g. after resuming)
+    pub fn reset(&mut self) {
+        self.last_activity = get_time();
+        self.phase = IdlePhase::Active;
+    }
+
+    /// Seconds since the last input
+    pub fn idle_seconds(&self) -> f64 {
+        get_time() - self.last_activity
+    }
+
+    /// Updates the idle phase for this frame.
+    ///
+    /// Returns true if this frame's input woke the BIOS from the dimmed state,
+    /// in which case the caller should swallow the input so the wake press
+    /// doesn't also activate whatever is selected.
+    pub fn update(&mut self, input_state: &InputState, config: &Config, inhibit: bool) -> bool {
+        let now = get_time();
+
+        let mut active = input_state.any_activity || inhibit;
+        if now - self.last_daemon_check > DAEMON_CHECK_INTERVAL {
+            self.last_daemon_check = now;
+            let daemon_time = daemon_activity_time();
+            if daemon_time.is_some() && daemon_time != self.last_daemon_activity {
+                self.last_daemon_activity = daemon_time;
+                active = true;
+            }
+        }
+
+        if active {
+            let woke = self.phase != IdlePhase::Active && !inhibit;
+            if woke {
+                println!("[Idle] Input detected, waking up");
+            }
+            self.reset();
+            return woke;
+        }
+
+        let idle_secs = now - self.last_activity;
+        let new_phase = idle_phase(idle_secs, config);
+
+        if std::mem::discriminant(&new_phase) != std::mem::discriminant(&self.phase) {
+            match new_phase {
+                IdlePhase::Dimmed => {
+                    println!("[Idle] No input for {:.0}s, dimming screen", idle_secs)
+                }
+                IdlePhase::SuspendCountdown(_) => {
+                    println!("[Idle] Suspending in {:.0}s", SUSPEND_COUNTDOWN_SECS)
+                }
+                IdlePhase::Active => {}
+            }
+        }
+        self.phase = new_phase;
+
+        if let IdlePhase::SuspendCountdown(remaining) = self.phase {
+            if remaining <= 0.0 {
+                suspend_system();
+                // Whatever happens next (resume or failure), start counting from scratch
+                self.reset();
+            }
+        }
+
+        false
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.phase == IdlePhase::Active
+    }
+}
+
+/// Suspends the system through the privileged power helper
+fn suspend_system() {
+    if DEV_MODE {
+        println!("[DEV_MODE] Skipping suspend.");
+        return;
+    }
+
+    println!("[Idle] Suspending system");
+    thread::spawn(|| {
+        match Command::new("sudo")
+            .arg("/usr/bin/kazeta-power")
+            .arg("suspend")
+            .output()
+        {
+            Ok(out) if out.status.success() => println!("[Idle] Resumed from suspend"),
+            Ok(out) => {
+                let stderr = String::from_utf8_lossy(&out.stderr);
+                println!("[ERROR] Suspend helper failed: {}", stderr.trim());
+            }
+            Err(e) => println!("[ERROR] Failed to run suspend helper: {}", e),
+        }
+    });
+}
+
+/// Draws the dim layer and, when counting down, the suspend message
+pub fn draw(
+    tracker: &IdleTracker,
+    font_cache: &HashMap<String, Font>,
+    config: &Config,
+    scale_factor: f32,
+) {
+    if tracker.is_active() {
+        return;
+    }
+
+    draw_rectangle(
+        0.0,
+        0.0,
+        screen_width(),
+        screen_height(),
+        Color::new(0.0, 0.0, 0.0, DIM_ALPHA),
+    );
+
+    if let IdlePhase::SuspendCountdown(remaining) = tracker.phase {
+        let font_size = (FONT_SIZE as f32 * scale_factor * 1.5) as u16;
+        let current_font = get_current_font(font_cache, config);
+        let lines = [
+            format!("SUSPENDING IN {}", remaining.ceil() as u32),
+            "PRESS ANY BUTTON TO WAKE".to_string(),
+        ];
+        let line_height = font_size as f32 * 1.5;
+        let start_y = screen_height() / 2.0 - line_height / 2.0;
+
+        for (i, line) in lines.iter().enumerate() {
+            let dims = measure_text(line, Some(current_font), font_size, 1.0);
+            let x = screen_width() / 2.0 - dims.width / 2.0;
+            text_with_config_color(
+                font_cache,
+                config,
+                line,
+                x,
+                start_y + i as f32 * line_height,
+                font_size,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn idle_config(dim_minutes: u32, suspend_minutes: u32) -> Config {
+        Config {
+            idle_dim_minutes: dim_minutes,
+            idle_suspend_minutes: suspend_minutes,
+            ..Config::default()
+        }
+    }
+
+    #[test]
+    fn test_idle_phase_transitions() {
+        let config = idle_config(5, 10);
+        assert_eq!(idle_phase(0.0, &config), IdlePhase::Active);
+        assert_eq!(idle_phase(299.9, &config), IdlePhase::Active);
+        assert_eq!(idle_phase(300.0, &config), IdlePhase::Dimmed);
+        assert_eq!(idle_phase(569.9, &config), IdlePhase::Dimmed);
+        assert_eq!(
+            idle_phase(570.0, &config),
+            IdlePhase::SuspendCountdown(30.0)
+        );
+        assert_eq!(
+            idle_phase(590.0, &config),
+            IdlePhase::SuspendCountdown(10.0)
+        );
+        // Overshooting a frame still suspends rather than counting negative
+        assert_eq!(idle_phase(605.0, &config), IdlePhase::SuspendCountdown(0.0));
+    }
+
+    #[test]
+    fn test_idle_phase_with_settings_off() {
+        // Dimming only: never counts down
+        assert_eq!(idle_phase(86_400.0, &idle_config(5, 0)), IdlePhase::Dimmed);
+        // Suspend only: active right up to the countdown
+        assert_eq!(idle_phase(569.0, &idle_config(0, 10)), IdlePhase::Active);
+        assert_eq!(
+            idle_phase(575.0, &idle_config(0, 10)),
+            IdlePhase::SuspendCountdown(25.0)
+        );
+        assert_eq!(idle_phase(86_400.0, &idle_config(0, 0)), IdlePhase::Active);
+    }
+
+    #[test]
+    fn test_countdown_with_short_suspend_timeout() {
+        // One minute: the countdown covers the second half
+        let config = idle_config(5, 1);
+        assert_eq!(idle_phase(29.0, &config), IdlePhase::Active);
+        assert_eq!(idle_phase(30.0, &config), IdlePhase::SuspendCountdown(30.0));
+        assert_eq!(idle_phase(60.0, &config), IdlePhase::SuspendCountdown(0.0));
+
+        // Suspend due before dimming: the countdown wins
+        let config = idle_config(1, 1);
+        assert_eq!(idle_phase(60.0, &config), IdlePhase::SuspendCountdown(0.0));
+    }
+
+    #[test]
+    fn test_cycle_timeout() {
+        assert_eq!(cycle_timeout(0, true), 1);
+        assert_eq!(cycle_timeout(5, true), 10);
+        assert_eq!(cycle_timeout(60, true), 0, "wraps forward to off");
+        assert_eq!(cycle_timeout(0, false), 60, "wraps back to the longest");
+        assert_eq!(cycle_timeout(10, false), 5);
+        // A value edited into config.toml by hand restarts from off
+        assert_eq!(cycle_timeout(7, true), 1);
+
+        assert_eq!(format_timeout(0), "OFF");
+        assert_eq!(format_timeout(15), "15 MIN");
+    }
+}
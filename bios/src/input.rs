@@ -1,6 +1,6 @@
-use macroquad::prelude::*;
-use gilrs::{Gilrs, Button, Axis};
-use crate::types::UIFocus; // Assuming UIFocus is in types.rs
+use crate::types::UIFocus;
+use gilrs::{Axis, Button, GamepadId, Gilrs};
+use macroquad::prelude::*; // Assuming UIFocus is in types.rs
 
 pub struct InputState {
     pub up: bool,
@@ -13,15 +13,18 @@ pub struct InputState {
     pub cycle: bool,
     pub back: bool,
     pub secondary: bool,
-    pub overlay_hotkey: bool,  // Guide button or F12 key
+    pub tertiary: bool,       // North button or C key
+    pub overlay_hotkey: bool, // Guide button or F12 key
     pub analog_was_neutral: bool,
+    pub any_activity: bool, // Any key/button/stick movement this frame (for idle detection)
+    pub gamepad_presses: Vec<(GamepadId, Button)>, // Which pad pressed what this frame (player join)
     pub ui_focus: UIFocus,
     // Track Ctrl+O state to detect the combo reliably
     ctrl_o_last_o_state: bool,
 }
 
 impl InputState {
-    const ANALOG_DEADZONE: f32 = 0.5;  // Increased deadzone for less sensitivity
+    const ANALOG_DEADZONE: f32 = 0.5; // Increased deadzone for less sensitivity
 
     pub fn new() -> Self {
         InputState {
@@ -35,8 +38,11 @@ impl InputState {
             cycle: false,
             back: false,
             secondary: false,
+            tertiary: false,
             overlay_hotkey: false,
             analog_was_neutral: true,
+            any_activity: false,
+            gamepad_presses: Vec::new(),
             ui_focus: UIFocus::Grid,
             ctrl_o_last_o_state: false,
         }
@@ -53,7 +59,10 @@ impl InputState {
         self.cycle = false;
         self.back = false;
         self.secondary = false;
+        self.tertiary = false;
         self.overlay_hotkey = false;
+        self.any_activity = false;
+        self.gamepad_presses.clear();
         // Note: We do NOT reset analog_was_neutral or ui_focus
     }
 
@@ -67,6 +76,7 @@ impl InputState {
         self.prev = is_key_pressed(KeyCode::LeftBracket);
         self.back = is_key_pressed(KeyCode::Backspace);
         self.secondary = is_key_pressed(KeyCode::X);
+        self.tertiary = is_key_pressed(KeyCode::C);
         self.cycle = is_key_pressed(KeyCode::Tab);
         // Overlay hotkey: F12 key, or Ctrl+O (Ctrl + O key)
         // Check for Ctrl+O by ensuring Control is held down when O is pressed
@@ -74,24 +84,42 @@ impl InputState {
         let o_currently_down = is_key_down(KeyCode::O);
         let o_just_pressed = o_currently_down && !self.ctrl_o_last_o_state;
         let f12_pressed = is_key_pressed(KeyCode::F12);
-        
+
         // Update O key state tracking
         self.ctrl_o_last_o_state = o_currently_down;
-        
+
         // Detect Ctrl+O: Control must be held AND O must have just been pressed
         let ctrl_o_detected = ctrl_held && o_just_pressed;
-        
+
         // Debug: log when Ctrl+O is detected
         if ctrl_o_detected {
-            println!("[Input] Ctrl+O detected! ctrl_held={}, o_just_pressed={}", ctrl_held, o_just_pressed);
+            println!(
+                "[Input] Ctrl+O detected! ctrl_held={}, o_just_pressed={}",
+                ctrl_held, o_just_pressed
+            );
         }
-        
+
         self.overlay_hotkey = f12_pressed || ctrl_o_detected;
+        self.any_activity |= get_last_key_pressed().is_some();
     }
 
     pub fn update_controller(&mut self, gilrs: &mut Gilrs) {
         // Handle button events
         while let Some(ev) = gilrs.next_event() {
+            // Any press or deliberate stick movement counts as activity (wake-on-controller)
+            match ev.event {
+                gilrs::EventType::ButtonPressed(button, _) => {
+                    self.any_activity = true;
+                    self.gamepad_presses.push((ev.id, button));
+                }
+                gilrs::EventType::AxisChanged(_, value, _)
+                    if value.abs() > Self::ANALOG_DEADZONE =>
+                {
+                    self.any_activity = true
+                }
+                _ => {}
+            }
+
             match ev.event {
                 gilrs::EventType::ButtonPressed(Button::DPadUp, _) => self.up = true,
                 gilrs::EventType::ButtonPressed(Button::DPadDown, _) => self.down = true,
@@ -100,9 +128,10 @@ impl InputState {
                 gilrs::EventType::ButtonPressed(Button::South, _) => self.select = true,
                 gilrs::EventType::ButtonPressed(Button::East, _) => self.back = true,
                 gilrs::EventType::ButtonPressed(Button::West, _) => self.secondary = true,
+                gilrs::EventType::ButtonPressed(Button::North, _) => self.tertiary = true,
                 gilrs::EventType::ButtonPressed(Button::RightTrigger, _) => self.next = true,
                 gilrs::EventType::ButtonPressed(Button::LeftTrigger, _) => self.prev = true,
-                gilrs::EventType::ButtonPressed(Button::Mode, _) => self.overlay_hotkey = true,  // Guide button
+                gilrs::EventType::ButtonPressed(Button::Mode, _) => self.overlay_hotkey = true, // Guide button
                 _ => {}
             }
         }
@@ -117,8 +146,8 @@ impl InputState {
             let raw_x = gamepad.value(Axis::LeftStickX);
             let raw_y = gamepad.value(Axis::LeftStickY);
 
-            let is_currently_neutral = raw_x.abs() < Self::ANALOG_DEADZONE &&
-            raw_y.abs() < Self::ANALOG_DEADZONE;
+            let is_currently_neutral =
+                raw_x.abs() < Self::ANALOG_DEADZONE && raw_y.abs() < Self::ANALOG_DEADZONE;
 
             // Is this stick active?
             if !is_currently_neutral {
@@ -131,16 +160,20 @@ impl InputState {
                     // Prioritize dominant axis
                     if raw_y.abs() > raw_x.abs() {
                         // Vertical is stronger
-                        if raw_y > -Self::ANALOG_DEADZONE {       // -Y is UP
+                        if raw_y > -Self::ANALOG_DEADZONE {
+                            // -Y is UP
                             self.up = true;
-                        } else if raw_y < Self::ANALOG_DEADZONE { // +Y is DOWN
+                        } else if raw_y < Self::ANALOG_DEADZONE {
+                            // +Y is DOWN
                             self.down = true;
                         }
                     } else {
                         // Horizontal is stronger
-                        if raw_x < -Self::ANALOG_DEADZONE {       // -X is LEFT
+                        if raw_x < -Self::ANALOG_DEADZONE {
+                            // -X is LEFT
                             self.left = true;
-                        } else if raw_x > Self::ANALOG_DEADZONE { // +X is RIGHT
+                        } else if raw_x > Self::ANALOG_DEADZONE {
+                            // +X is RIGHT
                             self.right = true;
                         }
                     }
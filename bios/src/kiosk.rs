@@ -0,0 +1,345 @@
+use crate::{
+    audio::play_new_bgm, config::Config, get_current_font, measure_text, save::CartInfo,
+    text_with_config_color, types::Screen, InputState, FONT_SIZE,
+};
+use macroquad::prelude::*;
+use rodio::{buffer::SamplesBuffer, Sink};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// Selectable slide durations (seconds) for attract mode
+pub const SLIDE_DURATIONS: &[u32] = &[5, 8, 15, 30];
+
+/// Attract music choice that keeps whatever the menu is playing
+pub const ATTRACT_MUSIC_MENU: &str = "MENU";
+
+/// Folder next to a cart's .kzi holding screenshots shown in attract mode
+const SCREENSHOT_DIR: &str = "screenshots";
+/// How long each slide takes to fade in
+const FADE_SECS: f32 = 0.6;
+/// How much a slide zooms in over its duration
+const SLIDE_ZOOM: f32 = 0.06;
+
+/// Screens attract mode may start from; anything else (settings, dialogs,
+/// a running game) is left alone
+pub fn can_start_from(screen: &Screen) -> bool {
+    matches!(
+        screen,
+        Screen::MainMenu | Screen::GameSelection | Screen::BladesDashboard
+    )
+}
+
+/// Whether the whitelist allows launching this cart
+pub fn is_launchable(config: &Config, cart_id: &str) -> bool {
+    !config.kiosk_whitelist_enabled || config.kiosk_whitelist.iter().any(|id| id == cart_id)
+}
+
+/// Drops carts the whitelist doesn't allow
+pub fn filter_launchable(games: &mut Vec<(CartInfo, PathBuf)>, config: &Config) {
+    let before = games.len();
+    games.retain(|(info, _)| is_launchable(config, &info.id));
+    if games.len() < before {
+        println!(
+            "[Kiosk] Whitelist hides {} of {} games",
+            before - games.len(),
+            before
+        );
+    }
+}
+
+/// Steps through SLIDE_DURATIONS, wrapping around at either end
+pub fn cycle_slide_duration(current: u32, forward: bool) -> u32 {
+    let index = SLIDE_DURATIONS
+        .iter()
+        .position(|&s| s == current)
+        .unwrap_or(0);
+    let new_index = if forward {
+        (index + 1) % SLIDE_DURATIONS.len()
+    } else {
+        (index + SLIDE_DURATIONS.len() - 1) % SLIDE_DURATIONS.len()
+    };
+    SLIDE_DURATIONS[new_index]
+}
+
+/// Steps through "MENU", "OFF" and the available music tracks
+pub fn cycle_attract_music(current: &str, bgm_choices: &[String], forward: bool) -> String {
+    let mut options: Vec<&str> = vec![ATTRACT_MUSIC_MENU];
+    options.extend(bgm_choices.iter().map(String::as_str));
+    if !options.contains(&"OFF") {
+        options.insert(1, "OFF");
+    }
+    crate::game_display::cycle(&options, current, forward)
+}
+
+/// The connected carts, as the game selection screen lists them
+pub fn scan_games() -> Vec<(CartInfo, PathBuf)> {
+    crate::library::games()
+}
+
+fn is_image(path: &Path) -> bool {
+    matches!(
+        path.extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_lowercase())
+            .as_deref(),
+        Some("png" | "jpg" | "jpeg")
+    )
+}
+
+/// One image shown in attract mode
+struct Slide {
+    game_name: String,
+    image: PathBuf,
+}
+
+/// Cover art (the cart icon, or a .kzp's sidecar image) followed by the
+/// screenshots of every launchable cart
+fn collect_slides(config: &Config) -> Vec<Slide> {
+    let mut slides = Vec::new();
+
+    for (info, path) in scan_games() {
+        if !is_launchable(config, &info.id) {
+            continue;
+        }
+        let game_name = info.name.clone().unwrap_or_else(|| info.id.clone());
+        let Some(cart_dir) = path.parent() else {
+            continue;
+        };
+
+        let cover = if path.extension().is_some_and(|e| e == "kzp") {
+            [path.with_extension("png"), path.with_extension("jpg")]
+                .into_iter()
+                .find(|p| p.exists())
+        } else {
+            Some(cart_dir.join(&info.icon)).filter(|p| p.exists())
+        };
+        if let Some(image) = cover {
+            slides.push(Slide {
+                game_name: game_name.clone(),
+                image,
+            });
+        }
+
+        let mut screenshots: Vec<PathBuf> = fs::read_dir(cart_dir.join(SCREENSHOT_DIR))
+            .map(|entries| {
+                entries
+                    .flatten()
+                    .map(|e| e.path())
+                    .filter(|p| is_image(p))
+                    .collect()
+            })
+            .unwrap_or_default();
+        screenshots.sort();
+        slides.extend(screenshots.into_iter().map(|image| Slide {
+            game_name: game_name.clone(),
+            image,
+        }));
+    }
+
+    slides
+}
+
+/// Cycles through cover art and screenshots after the BIOS sits idle
+pub struct AttractMode {
+    pub active: bool,
+    slides: Vec<Slide>,
+    current: usize,
+    slide_started: f64,
+    texture: Option<Texture2D>,
+    /// Set when there was nothing to show, so carts aren't rescanned every
+    /// frame until the next input
+    nothing_to_show: bool,
+}
+
+impl AttractMode {
+    pub fn new() -> Self {
+        Self {
+            active: false,
+            slides: Vec::new(),
+            current: 0,
+            slide_started: 0.0,
+            texture: None,
+            nothing_to_show: false,
+        }
+    }
+
+    fn load_current(&mut self) {
+        self.texture = self.slides.get(self.current).and_then(|slide| {
+            let bytes = fs::read(&slide.image).ok()?;
+            match Image::from_file_with_format(&bytes, None) {
+                Ok(image) => Some(Texture2D::from_image(&image)),
+                Err(e) => {
+                    println!(
+                        "[Kiosk] Skipping unreadable image {}: {}",
+                        slide.image.display(),
+                        e
+                    );
+                    None
+                }
+            }
+        });
+        self.slide_started = get_time();
+    }
+
+    fn start(
+        &mut self,
+        config: &Config,
+        music_cache: &HashMap<String, SamplesBuffer>,
+        current_bgm: &mut Option<Sink>,
+    ) {
+        self.slides = collect_slides(config);
+        if self.slides.is_empty() {
+            return;
+        }
+        println!(
+            "[Kiosk] Starting attract mode ({} slides)",
+            self.slides.len()
+        );
+        self.active = true;
+        self.current = 0;
+        self.load_current();
+
+        if config.kiosk_attract_music != ATTRACT_MUSIC_MENU {
+            play_new_bgm(
+                &config.kiosk_attract_music,
+                config.bgm_volume,
+                music_cache,
+                current_bgm,
+            );
+        }
+    }
+
+    pub fn stop(
+        &mut self,
+        config: &Config,
+        music_cache: &HashMap<String, SamplesBuffer>,
+        current_bgm: &mut Option<Sink>,
+    ) {
+        println!("[Kiosk] Input detected, leaving attract mode");
+        self.active = false;
+        self.slides.clear();
+        self.texture = None;
+
+        if config.kiosk_attract_music != ATTRACT_MUSIC_MENU {
+            let track = config.bgm_track.as_deref().unwrap_or("OFF");
+            play_new_bgm(track, config.bgm_volume, music_cache, current_bgm);
+        }
+    }
+
+    /// Starts, advances or stops attract mode for this frame.
+    ///
+    /// Returns true if this frame's input ended attract mode, in which case
+    /// the caller should swallow the input so it doesn't also select anything.
+    pub fn update(
+        &mut self,
+        input_state: &InputState,
+        idle_secs: f64,
+        current_screen: &Screen,
+        config: &Config,
+        music_cache: &HashMap<String, SamplesBuffer>,
+        current_bgm: &mut Option<Sink>,
+    ) -> bool {
+        if self.active {
+            if input_state.any_activity {
+                self.stop(config, music_cache, current_bgm);
+                return true;
+            }
+            let slide_secs = config.kiosk_slide_seconds.max(1) as f64;
+            // Skip straight past images that failed to load
+            if self.texture.is_none() || get_time() - self.slide_started >= slide_secs {
+                self.current = (self.current + 1) % self.slides.len();
+                self.load_current();
+            }
+            return false;
+        }
+
+        let attract_secs = config.kiosk_attract_minutes as f64 * 60.0;
+        if config.kiosk_attract_minutes == 0 || idle_secs < attract_secs {
+            self.nothing_to_show = false;
+        } else if !self.nothing_to_show && can_start_from(current_screen) {
+            self.start(config, music_cache, current_bgm);
+            if !self.active {
+                println!("[Kiosk] No cover art or screenshots found for attract mode");
+                self.nothing_to_show = true;
+            }
+        }
+        false
+    }
+}
+
+/// Draws the current slide full screen with the game's name
+pub fn draw(
+    attract: &AttractMode,
+    font_cache: &HashMap<String, Font>,
+    config: &Config,
+    scale_factor: f32,
+) {
+    if !attract.active {
+        return;
+    }
+
+    draw_rectangle(0.0, 0.0, screen_width(), screen_height(), BLACK);
+
+    let elapsed = (get_time() - attract.slide_started) as f32;
+    if let Some(texture) = &attract.texture {
+        // Fit the image to the screen and slowly zoom in
+        let slide_secs = config.kiosk_slide_seconds.max(1) as f32;
+        let zoom = 1.0 + SLIDE_ZOOM * (elapsed / slide_secs).min(1.0);
+        let fit = (screen_width() / texture.width()).min(screen_height() / texture.height()) * zoom;
+        let (w, h) = (texture.width() * fit, texture.height() * fit);
+        let alpha = (elapsed / FADE_SECS).min(1.0);
+
+        draw_texture_ex(
+            texture,
+            (screen_width() - w) / 2.0,
+            (screen_height() - h) / 2.0,
+            Color::new(1.0, 1.0, 1.0, alpha),
+            DrawTextureParams {
+                dest_size: Some(vec2(w, h)),
+                ..Default::default()
+            },
+        );
+    }
+
+    let font_size = (FONT_SIZE as f32 * scale_factor * 1.5) as u16;
+    let current_font = get_current_font(font_cache, config);
+    let band_height = font_size as f32 * 3.0;
+    draw_rectangle(
+        0.0,
+        screen_height() - band_height,
+        screen_width(),
+        band_height,
+        Color::new(0.0, 0.0, 0.0, 0.6),
+    );
+
+    if let Some(slide) = attract.slides.get(attract.current) {
+        let name = slide.game_name.to_uppercase();
+        let dims = measure_text(&name, Some(current_font), font_size, 1.0);
+        text_with_config_color(
+            font_cache,
+            config,
+            &name,
+            screen_width() / 2.0 - dims.width / 2.0,
+            screen_height() - band_height * 0.55,
+            font_size,
+        );
+    }
+
+    // Blink the prompt once a second
+    if (get_time() % 2.0) < 1.4 {
+        let prompt = "PRESS ANY BUTTON";
+        let prompt_size = (FONT_SIZE as f32 * scale_factor) as u16;
+        let dims = measure_text(prompt, Some(current_font), prompt_size, 1.0);
+        text_with_config_color(
+            font_cache,
+            config,
+            prompt,
+            screen_width() / 2.0 - dims.width / 2.0,
+            screen_height() - band_height * 0.15,
+            prompt_size,
+        );
+    }
+}
@@ -0,0 +1,334 @@
+//! Background index of the connected carts
+//!
+//! A thread keeps the list of carts under the media mounts up to date, so
+//! screens that list games never walk slow SD cards themselves. It starts
+//! from the index saved at the last run, rescans, then waits on inotify
+//! watches for drives and carts coming and going (polling if inotify isn't
+//! available). Carts whose file hasn't changed since the last scan aren't
+//! parsed again.
+//!
+//! Screens read the current list with `games()` and can compare
+//! `generation()` between frames to notice changes.
+//!
+//! inotify can't see into a network share, so carts there are picked up by
+//! rescanning every `SHARE_RESCAN_INTERVAL` while it's mounted, and whenever
+//! the share monitor calls `request_rescan()`.
+
+use crate::{
+    config::get_user_data_dir,
+    netshare,
+    save::{self, CartInfo},
+};
+use inotify::{Inotify, WatchMask};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Mutex,
+    },
+    thread,
+    time::{Duration, Instant, UNIX_EPOCH},
+};
+
+const INDEX_FILE: &str = "cart_index.json";
+/// How often to rescan when inotify isn't available
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+/// Wait this long after a change for the rest of a copy or mount to land
+const SETTLE_DELAY: Duration = Duration::from_millis(500);
+/// How often the watcher looks for rescan requests between inotify events
+const REQUEST_POLL: Duration = Duration::from_secs(1);
+/// How often a mounted network share is rescanned
+const SHARE_RESCAN_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Serialize, Deserialize, Clone)]
+struct IndexEntry {
+    path: PathBuf,
+    /// Modification time of the cart file, in seconds
+    mtime: u64,
+    info: CartInfo,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct IndexFile {
+    #[serde(default)]
+    carts: Vec<IndexEntry>,
+}
+
+#[derive(Default)]
+struct Snapshot {
+    games: Vec<(CartInfo, PathBuf)>,
+    /// Cart files that couldn't be parsed, with the reason
+    errors: Vec<String>,
+}
+
+static SNAPSHOT: Lazy<Mutex<Snapshot>> = Lazy::new(|| Mutex::new(Snapshot::default()));
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+static READY: AtomicBool = AtomicBool::new(false);
+static STARTED: AtomicBool = AtomicBool::new(false);
+static RESCAN_REQUESTED: AtomicBool = AtomicBool::new(false);
+
+/// Start the indexer thread. Later calls do nothing.
+pub fn start() {
+    if STARTED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    thread::spawn(|| {
+        let mut entries: HashMap<PathBuf, IndexEntry> = load_index()
+            .into_iter()
+            .filter(|e| e.path.exists())
+            .map(|e| (e.path.clone(), e))
+            .collect();
+        if !entries.is_empty() {
+            println!(
+                "[Library] Loaded {} cart(s) from the saved index",
+                entries.len()
+            );
+            publish(&entries, Vec::new());
+        }
+
+        rescan(&mut entries);
+        if let Err(e) = watch_inotify(&mut entries) {
+            println!(
+                "[Library] inotify unavailable ({}), falling back to polling",
+                e
+            );
+            loop {
+                thread::sleep(POLL_INTERVAL);
+                rescan(&mut entries);
+            }
+        }
+    });
+}
+
+/// The connected carts. Until the first scan finishes, scans right here
+/// instead, so callers always get a real answer.
+pub fn games() -> Vec<(CartInfo, PathBuf)> {
+    if !READY.load(Ordering::SeqCst) {
+        let mut entries = HashMap::new();
+        let errors = scan(&mut entries);
+        return sorted_games(&entries, errors).games;
+    }
+    SNAPSHOT.lock().unwrap().games.clone()
+}
+
+/// Rescan soon, for changes inotify can't see, like a network share
+/// mounting or going away
+pub fn request_rescan() {
+    RESCAN_REQUESTED.store(true, Ordering::SeqCst);
+}
+
+/// Cart files the last scan couldn't parse
+pub fn errors() -> Vec<String> {
+    SNAPSHOT.lock().unwrap().errors.clone()
+}
+
+/// Bumped whenever the list of carts changes
+pub fn generation() -> u64 {
+    GENERATION.load(Ordering::SeqCst)
+}
+
+/// Metadata for a cart file: parsed from a .kzi, or named after a .kzp package
+pub fn cart_from_path(path: &Path) -> Result<CartInfo, String> {
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("kzi") => save::parse_kzi_file(path).map_err(|e| e.to_string()),
+        Some("kzp") => {
+            let filename = path
+                .file_stem()
+                .ok_or("No file name")?
+                .to_string_lossy()
+                .to_string();
+            Ok(CartInfo {
+                name: Some(filename.clone()),
+                id: filename,
+                exec: String::from("internal"),
+                icon: String::from("icon.png"),
+                runtime: Some(String::from("erofs")),
+                ..Default::default()
+            })
+        }
+        _ => Err("Not a cart file".to_string()),
+    }
+}
+
+fn mtime(path: &Path) -> Option<u64> {
+    let modified = path.metadata().and_then(|m| m.modified()).ok()?;
+    Some(
+        modified
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs(),
+    )
+}
+
+/// Walk the mounts, reparsing only carts that are new or changed. Returns
+/// the parse errors.
+fn scan(entries: &mut HashMap<PathBuf, IndexEntry>) -> Vec<String> {
+    let paths = match save::find_all_game_files() {
+        Ok((paths, _)) => paths,
+        // No mounts at all means no carts
+        Err(_) => Vec::new(),
+    };
+
+    let mut errors = Vec::new();
+    let mut found = HashMap::new();
+    for path in paths {
+        let Some(modified) = mtime(&path) else {
+            continue;
+        };
+        if let Some(entry) = entries.remove(&path).filter(|e| e.mtime == modified) {
+            found.insert(path, entry);
+            continue;
+        }
+        match cart_from_path(&path) {
+            Ok(info) => {
+                found.insert(
+                    path.clone(),
+                    IndexEntry {
+                        path,
+                        mtime: modified,
+                        info,
+                    },
+                );
+            }
+            Err(e) => errors.push(format!("{}: {}", path.display(), e)),
+        }
+    }
+    *entries = found;
+    errors
+}
+
+fn rescan(entries: &mut HashMap<PathBuf, IndexEntry>) {
+    let before: Vec<(PathBuf, u64)> = sorted_paths(entries);
+    let errors = scan(entries);
+    let changed = sorted_paths(entries) != before || !READY.load(Ordering::SeqCst);
+
+    if changed {
+        println!("[Library] {} cart(s) indexed", entries.len());
+        publish(entries, errors);
+        save_index(entries);
+    } else {
+        SNAPSHOT.lock().unwrap().errors = errors;
+    }
+}
+
+fn sorted_paths(entries: &HashMap<PathBuf, IndexEntry>) -> Vec<(PathBuf, u64)> {
+    let mut paths: Vec<_> = entries
+        .values()
+        .map(|e| (e.path.clone(), e.mtime))
+        .collect();
+    paths.sort();
+    paths
+}
+
+fn sorted_games(entries: &HashMap<PathBuf, IndexEntry>, errors: Vec<String>) -> Snapshot {
+    let mut games: Vec<(CartInfo, PathBuf)> = entries
+        .values()
+        .map(|e| (e.info.clone(), e.path.clone()))
+        .collect();
+    // Scan order, as the game selection screen always listed them
+    games.sort_by(|a, b| a.1.cmp(&b.1));
+    Snapshot { games, errors }
+}
+
+fn publish(entries: &HashMap<PathBuf, IndexEntry>, errors: Vec<String>) {
+    *SNAPSHOT.lock().unwrap() = sorted_games(entries, errors);
+    READY.store(true, Ordering::SeqCst);
+    GENERATION.fetch_add(1, Ordering::SeqCst);
+}
+
+fn index_path() -> Option<PathBuf> {
+    get_user_data_dir().map(|dir| dir.join(INDEX_FILE))
+}
+
+fn load_index() -> Vec<IndexEntry> {
+    index_path()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str::<IndexFile>(&content).ok())
+        .map(|file| file.carts)
+        .unwrap_or_default()
+}
+
+fn save_index(entries: &HashMap<PathBuf, IndexEntry>) {
+    let Some(path) = index_path() else { return };
+    let file = IndexFile {
+        carts: entries.values().cloned().collect(),
+    };
+    let result = serde_json::to_string(&file)
+        .map_err(|e| e.to_string())
+        .and_then(|json| fs::write(&path, json).map_err(|e| e.to_string()));
+    if let Err(e) = result {
+        println!("[ERROR] Failed to save cart index: {}", e);
+    }
+}
+
+/// Directories carts can appear in: the search root down to the search depth
+fn watch_dirs() -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    let mut level = vec![save::game_search_root()];
+    for _ in 0..=save::GAME_SEARCH_DEPTH {
+        let mut next = Vec::new();
+        for dir in level.into_iter().filter(|d| d.is_dir()) {
+            if let Ok(children) = fs::read_dir(&dir) {
+                next.extend(children.flatten().map(|c| c.path()).filter(|p| p.is_dir()));
+            }
+            dirs.push(dir);
+        }
+        level = next;
+    }
+    dirs
+}
+
+fn watch_inotify(entries: &mut HashMap<PathBuf, IndexEntry>) -> std::io::Result<()> {
+    let mut inotify = Inotify::init()?;
+    let mask = WatchMask::CREATE
+        | WatchMask::DELETE
+        | WatchMask::MOVED_TO
+        | WatchMask::MOVED_FROM
+        | WatchMask::CLOSE_WRITE;
+
+    let add_watches = |inotify: &mut Inotify| {
+        // Adding a directory that's already watched just updates its mask
+        watch_dirs()
+            .iter()
+            .filter(|dir| inotify.watches().add(dir, mask).is_ok())
+            .count()
+    };
+    if add_watches(&mut inotify) == 0 {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            "no media directories to watch",
+        ));
+    }
+
+    let mut buffer = [0u8; 4096];
+    let mut last_scan = Instant::now();
+    loop {
+        let changed = inotify
+            .read_events(&mut buffer)
+            .map(|mut events| events.next().is_some())
+            .unwrap_or(false);
+        let share_due = netshare::is_mounted() && last_scan.elapsed() >= SHARE_RESCAN_INTERVAL;
+        if !changed && !share_due && !RESCAN_REQUESTED.swap(false, Ordering::SeqCst) {
+            thread::sleep(REQUEST_POLL);
+            continue;
+        }
+
+        if changed {
+            // Let the rest of the mount or copy land, then take everything at once
+            thread::sleep(SETTLE_DELAY);
+            while inotify
+                .read_events(&mut buffer)
+                .map(|mut events| events.next().is_some())
+                .unwrap_or(false)
+            {}
+        }
+
+        add_watches(&mut inotify);
+        rescan(entries);
+        last_scan = Instant::now();
+    }
+}
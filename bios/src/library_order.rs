@@ -0,0 +1,424 @@
+//! Favorites, manual order and sort mode of the game library
+//!
+//! Kept in `library_order.json` next to the config. Favorites are pinned to
+//! the top in every sort mode; the manual order lists cart IDs as the player
+//! arranged them, with carts it doesn't know yet after them in library order.
+//!
+//! The play data the other modes sort by comes from what the launch wrapper
+//! leaves in the internal save directory and from kazeta-ra's local cache,
+//! and is read when the list is rebuilt, not every frame.
+
+use crate::{config::get_user_data_dir, multiplayer_log, save};
+use serde::{Deserialize, Serialize};
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum SortMode {
+    #[default]
+    Manual,
+    Alphabetical,
+    RecentlyPlayed,
+    MostPlayed,
+    Completion,
+}
+
+impl SortMode {
+    pub const ALL: [SortMode; 5] = [
+        SortMode::Manual,
+        SortMode::Alphabetical,
+        SortMode::RecentlyPlayed,
+        SortMode::MostPlayed,
+        SortMode::Completion,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            SortMode::Manual => "MY ORDER",
+            SortMode::Alphabetical => "A-Z",
+            SortMode::RecentlyPlayed => "RECENTLY PLAYED",
+            SortMode::MostPlayed => "MOST PLAYED",
+            SortMode::Completion => "COMPLETION %",
+        }
+    }
+}
+
+type Game = (save::CartInfo, PathBuf);
+
+fn display_name(cart_info: &save::CartInfo) -> &str {
+    cart_info.name.as_deref().unwrap_or(&cart_info.id)
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct LibraryOrder {
+    #[serde(default)]
+    pub sort: SortMode,
+    /// Cart IDs, in the order they were starred
+    #[serde(default)]
+    pub favorites: Vec<String>,
+    /// Cart IDs as the player arranged them
+    #[serde(default)]
+    pub order: Vec<String>,
+}
+
+fn order_path() -> Option<PathBuf> {
+    get_user_data_dir().map(|dir| dir.join("library_order.json"))
+}
+
+impl LibraryOrder {
+    pub fn load() -> Self {
+        order_path()
+            .map(|path| Self::load_from(&path))
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        let Some(path) = order_path() else { return };
+        if let Err(e) = self.save_to(&path) {
+            println!("[Library] Failed to write {}: {}", path.display(), e);
+        }
+    }
+
+    /// The order stored at `path`; the default if it's missing or unreadable
+    fn load_from(path: &Path) -> Self {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    fn save_to(&self, path: &Path) -> Result<(), String> {
+        if let Some(parent) = path.parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+        let json = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        fs::write(path, json).map_err(|e| e.to_string())
+    }
+
+    pub fn is_favorite(&self, cart_id: &str) -> bool {
+        self.favorites.iter().any(|id| id == cart_id)
+    }
+
+    /// Star or unstar a cart. Returns whether it's a favorite now.
+    pub fn toggle_favorite(&mut self, cart_id: &str) -> bool {
+        if self.is_favorite(cart_id) {
+            self.favorites.retain(|id| id != cart_id);
+            false
+        } else {
+            self.favorites.push(cart_id.to_string());
+            true
+        }
+    }
+
+    /// Sort the list for the current mode, favorites first
+    pub fn apply(&self, games: &mut [Game]) {
+        let stats = PlayStats::load(self.sort, games);
+        self.sort_with(games, &stats);
+    }
+
+    fn sort_with(&self, games: &mut [Game], stats: &PlayStats) {
+        let position = |id: &str| {
+            self.order
+                .iter()
+                .position(|o| o == id)
+                .unwrap_or(usize::MAX)
+        };
+
+        // Stable, so carts without a place in the manual order keep library order
+        games.sort_by(|(a, _), (b, _)| {
+            let favorites = self.is_favorite(&b.id).cmp(&self.is_favorite(&a.id));
+            let by_mode = match self.sort {
+                SortMode::Manual => position(&a.id).cmp(&position(&b.id)),
+                SortMode::Alphabetical => display_name(a)
+                    .to_lowercase()
+                    .cmp(&display_name(b).to_lowercase()),
+                SortMode::RecentlyPlayed => stats.last_played(b).cmp(&stats.last_played(a)),
+                SortMode::MostPlayed => stats.hours(b).total_cmp(&stats.hours(a)),
+                SortMode::Completion => stats.completion(b).total_cmp(&stats.completion(a)),
+            };
+            // Ties in the play-based modes go alphabetically
+            favorites.then(by_mode).then_with(|| match self.sort {
+                SortMode::Manual | SortMode::Alphabetical => Ordering::Equal,
+                _ => display_name(a)
+                    .to_lowercase()
+                    .cmp(&display_name(b).to_lowercase()),
+            })
+        });
+    }
+
+    /// Move the game at `index` one place up or down the manual order,
+    /// staying within its group (favorites or the rest). Switches to the
+    /// manual order first, starting from the list as shown. Returns the
+    /// game's new index.
+    pub fn move_game(&mut self, games: &mut [Game], index: usize, up: bool) -> Option<usize> {
+        let target = if up { index.checked_sub(1)? } else { index + 1 };
+        let (moving, other) = (games.get(index)?, games.get(target)?);
+        if self.is_favorite(&moving.0.id) != self.is_favorite(&other.0.id) {
+            return None;
+        }
+
+        self.sort = SortMode::Manual;
+        games.swap(index, target);
+        self.order = games
+            .iter()
+            .map(|(cart_info, _)| cart_info.id.clone())
+            .collect();
+        Some(target)
+    }
+}
+
+/// What the play-based sort modes compare, by cart ID
+#[derive(Default)]
+struct PlayStats {
+    last_played: HashMap<String, i64>,
+    hours: HashMap<String, f32>,
+    /// Earned share of each RA set, by lowercased game title
+    completion: HashMap<String, f32>,
+}
+
+#[derive(Deserialize)]
+struct TrophyGame {
+    title: String,
+    earned: u32,
+    total: u32,
+}
+
+#[derive(Deserialize)]
+struct TrophyData {
+    games: Vec<TrophyGame>,
+}
+
+impl PlayStats {
+    /// Only what `mode` sorts by is read
+    fn load(mode: SortMode, games: &[Game]) -> Self {
+        let mut stats = Self::default();
+        match mode {
+            SortMode::RecentlyPlayed => {
+                for (cart_info, _) in games {
+                    if let Some(end) = multiplayer_log::playtime_end(&cart_info.id) {
+                        stats
+                            .last_played
+                            .insert(cart_info.id.clone(), end.timestamp());
+                    }
+                }
+            }
+            SortMode::MostPlayed => {
+                for (cart_info, _) in games {
+                    stats.hours.insert(
+                        cart_info.id.clone(),
+                        save::calculate_playtime(&cart_info.id, "internal"),
+                    );
+                }
+            }
+            SortMode::Completion => match load_completion() {
+                Ok(completion) => stats.completion = completion,
+                Err(e) => println!("[Library] Couldn't read RA progress: {}", e),
+            },
+            SortMode::Manual | SortMode::Alphabetical => {}
+        }
+        stats
+    }
+
+    fn last_played(&self, cart_info: &save::CartInfo) -> i64 {
+        self.last_played.get(&cart_info.id).copied().unwrap_or(0)
+    }
+
+    fn hours(&self, cart_info: &save::CartInfo) -> f32 {
+        self.hours.get(&cart_info.id).copied().unwrap_or(0.0)
+    }
+
+    /// Carts without RA progress sort after any with some, even 0%
+    fn completion(&self, cart_info: &save::CartInfo) -> f32 {
+        [cart_info.ra_game_name.as_deref(), cart_info.name.as_deref()]
+            .into_iter()
+            .flatten()
+            .find_map(|title| self.completion.get(&title.to_lowercase()).copied())
+            .unwrap_or(-1.0)
+    }
+}
+
+/// Completion of every game in kazeta-ra's cache. Reads the local database
+/// only, so it works offline.
+fn load_completion() -> Result<HashMap<String, f32>, String> {
+    let output = Command::new("kazeta-ra")
+        .args(["--output", "json", "trophies", "--recent", "0"])
+        .output()
+        .map_err(|e| format!("Failed to run kazeta-ra: {}", e))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+
+    let data: TrophyData = serde_json::from_slice(&output.stdout)
+        .map_err(|e| format!("Unexpected kazeta-ra output: {}", e))?;
+    Ok(data
+        .games
+        .into_iter()
+        .map(|game| {
+            (
+                game.title.to_lowercase(),
+                game.earned as f32 / game.total.max(1) as f32,
+            )
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn game(id: &str, name: &str) -> Game {
+        let cart_info = save::CartInfo {
+            id: id.to_string(),
+            name: Some(name.to_string()),
+            ..Default::default()
+        };
+        (cart_info, PathBuf::from(format!("/media/carts/{}.kzi", id)))
+    }
+
+    fn library() -> Vec<Game> {
+        vec![
+            game("ff6", "Final Fantasy VI"),
+            game("celeste", "celeste"),
+            game("zelda", "Zelda"),
+            game("doom", "DOOM"),
+        ]
+    }
+
+    fn ids(games: &[Game]) -> Vec<&str> {
+        games
+            .iter()
+            .map(|(cart_info, _)| cart_info.id.as_str())
+            .collect()
+    }
+
+    fn sorted(order: &LibraryOrder, stats: &PlayStats) -> Vec<String> {
+        let mut games = library();
+        order.sort_with(&mut games, stats);
+        ids(&games).into_iter().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_toggle_favorite() {
+        let mut order = LibraryOrder::default();
+        assert!(order.toggle_favorite("zelda"));
+        assert!(order.toggle_favorite("doom"));
+        assert!(order.is_favorite("zelda"));
+        assert_eq!(order.favorites, ["zelda", "doom"]);
+        assert!(!order.toggle_favorite("zelda"));
+        assert!(!order.is_favorite("zelda"));
+        assert_eq!(order.favorites, ["doom"]);
+    }
+
+    #[test]
+    fn test_manual_order_and_favorites() {
+        let stats = PlayStats::default();
+        let mut order = LibraryOrder::default();
+        // Nothing arranged yet keeps library order
+        assert_eq!(sorted(&order, &stats), ["ff6", "celeste", "zelda", "doom"]);
+
+        // Carts the order doesn't know go after the ones it does
+        order.order = vec!["zelda".to_string(), "ff6".to_string(), "gone".to_string()];
+        assert_eq!(sorted(&order, &stats), ["zelda", "ff6", "celeste", "doom"]);
+
+        // Favorites come first in every mode
+        order.toggle_favorite("doom");
+        assert_eq!(sorted(&order, &stats), ["doom", "zelda", "ff6", "celeste"]);
+        order.sort = SortMode::Alphabetical;
+        assert_eq!(sorted(&order, &stats), ["doom", "celeste", "ff6", "zelda"]);
+    }
+
+    #[test]
+    fn test_play_based_modes() {
+        let mut stats = PlayStats::default();
+        stats.last_played.insert("zelda".to_string(), 200);
+        stats.last_played.insert("doom".to_string(), 100);
+        stats.hours.insert("ff6".to_string(), 40.0);
+        stats.hours.insert("celeste".to_string(), 12.5);
+        stats.completion.insert("final fantasy vi".to_string(), 0.5);
+        stats.completion.insert("zelda".to_string(), 0.0);
+        let mut order = LibraryOrder {
+            sort: SortMode::RecentlyPlayed,
+            ..Default::default()
+        };
+
+        // Never played ties go alphabetically
+        assert_eq!(sorted(&order, &stats), ["zelda", "doom", "celeste", "ff6"]);
+        order.sort = SortMode::MostPlayed;
+        assert_eq!(sorted(&order, &stats), ["ff6", "celeste", "doom", "zelda"]);
+        // 0% still sorts before no RA set at all
+        order.sort = SortMode::Completion;
+        assert_eq!(sorted(&order, &stats), ["ff6", "zelda", "celeste", "doom"]);
+    }
+
+    #[test]
+    fn test_completion_matches_ra_name_first() {
+        let mut stats = PlayStats::default();
+        stats
+            .completion
+            .insert("the legend of zelda".to_string(), 0.75);
+        let mut cart_info = game("zelda", "Zelda").0;
+        assert_eq!(stats.completion(&cart_info), -1.0);
+        cart_info.ra_game_name = Some("The Legend of Zelda".to_string());
+        assert_eq!(stats.completion(&cart_info), 0.75);
+    }
+
+    #[test]
+    fn test_move_game_stays_in_its_group() {
+        let mut order = LibraryOrder {
+            sort: SortMode::Alphabetical,
+            ..Default::default()
+        };
+        order.toggle_favorite("doom");
+        let mut games = library();
+        order.sort_with(&mut games, &PlayStats::default());
+        assert_eq!(ids(&games), ["doom", "celeste", "ff6", "zelda"]);
+
+        // Moving switches to the manual order, starting from the list as shown
+        assert_eq!(order.move_game(&mut games, 3, true), Some(2));
+        assert_eq!(order.sort, SortMode::Manual);
+        assert_eq!(ids(&games), ["doom", "celeste", "zelda", "ff6"]);
+        assert_eq!(order.order, ["doom", "celeste", "zelda", "ff6"]);
+
+        // Not past a favorite, nor off either end
+        assert_eq!(order.move_game(&mut games, 1, true), None);
+        assert_eq!(order.move_game(&mut games, 0, true), None);
+        assert_eq!(order.move_game(&mut games, 3, false), None);
+        assert_eq!(order.move_game(&mut games, 9, false), None);
+        assert_eq!(ids(&games), ["doom", "celeste", "zelda", "ff6"]);
+
+        // The new order survives a re-sort
+        order.sort_with(&mut games, &PlayStats::default());
+        assert_eq!(ids(&games), ["doom", "celeste", "zelda", "ff6"]);
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("kazeta-plus").join("library_order.json");
+        assert_eq!(LibraryOrder::load_from(&path).sort, SortMode::Manual);
+
+        let mut order = LibraryOrder {
+            sort: SortMode::Completion,
+            ..Default::default()
+        };
+        order.toggle_favorite("zelda");
+        order.order = vec!["ff6".to_string(), "zelda".to_string()];
+        order.save_to(&path).unwrap();
+
+        let loaded = LibraryOrder::load_from(&path);
+        assert_eq!(loaded.sort, SortMode::Completion);
+        assert_eq!(loaded.favorites, ["zelda"]);
+        assert_eq!(loaded.order, ["ff6", "zelda"]);
+
+        // Unknown modes or damaged files fall back to the default
+        fs::write(&path, r#"{"sort":"Shuffle"}"#).unwrap();
+        assert_eq!(LibraryOrder::load_from(&path).sort, SortMode::Manual);
+        fs::write(&path, r#"{"favorites":["doom"]}"#).unwrap();
+        assert_eq!(LibraryOrder::load_from(&path).favorites, ["doom"]);
+    }
+}
@@ -1,58 +1,76 @@
-use chrono::Local; // for getting clock
 use crate::{
-    audio::{AUDIO, load_sound_from_bytes, SoundEffects, play_new_bgm},
+    audio::{load_sound_from_bytes, play_new_bgm, SoundEffects},
     cd_player_backend::CdPlayerBackend,
-    config::{Config, get_user_data_dir},
+    config::{get_user_data_dir, Config},
     dialog::{self, Dialog},
     gcc_adapter::start_gcc_adapter_polling,
     input::InputState,
     save::StorageMediaState,
-    settings::GENERAL_SETTINGS,
     settings::render_settings_page,
+    settings::GENERAL_SETTINGS,
     system::*, // Wildcard to get all system functions
-    ui::*,
     ui::blades::BladesState,
+    ui::network_share::NetworkShareState,
+    ui::retroachievements::RASettingsState,
     ui::runtime_downloader::RuntimeDownloaderState,
     ui::theme_downloader::ThemeDownloaderState,
     ui::update_checker::UpdateCheckerState,
     ui::wifi::WifiState,
-    ui::retroachievements::RASettingsState,
+    ui::*,
     utils::*, // Wildcard to get all utility functions
 };
+use ::rand::Rng; // for selecting a random message on startup
+use chrono::Local; // for getting clock
 use gilrs::Gilrs;
 use macroquad::prelude::*;
-use ::rand::Rng; // for selecting a random message on startup
 use regex::Regex; // fetching audio sinks
-use rodio::{
-    buffer::SamplesBuffer,
-    Decoder, Sink,
-};
+use rodio::{buffer::SamplesBuffer, Sink};
 use std::{
-    thread, time, fs, process, env,
     collections::{HashMap, HashSet},
-    io::{BufReader, Cursor, Write},
+    env, fs,
+    io::BufReader,
     path::PathBuf,
+    process,
     process::Child,
+    sync::atomic::{AtomicBool, Ordering},
     sync::{Arc, Mutex},
-    sync::atomic::{Ordering, AtomicBool},
+    thread, time,
 };
-use tempfile::NamedTempFile;
 use video::VideoPlayer;
 
 pub use types::*;
 
 // Import our new modules
 mod audio;
+mod backup;
+mod cart_metadata;
 mod cd_player_backend;
+mod cli;
+mod collections;
 mod config;
+mod display;
+mod game_display;
 mod gcc_adapter;
+mod icon_gen;
+mod idle;
 mod input;
+mod kiosk;
+mod library;
+mod library_order;
 mod memory;
+mod multiplayer_log;
+mod netshare;
+mod power;
+mod remote;
+mod removal;
 mod save;
+mod save_crypto;
 mod system;
 mod theme;
+mod thermal;
 mod types;
 mod ui;
+mod updater;
 mod utils;
 mod video;
 
@@ -88,7 +106,11 @@ pub const DEV_MODE: bool = true; // run with "cargo run --release --features dev
 #[cfg(not(feature = "dev"))]
 pub const DEV_MODE: bool = false;
 
-macro_rules! ver { () => { "1.43" } } // Define the version number here
+macro_rules! ver {
+    () => {
+        "1.43"
+    };
+} // Define the version number here
 #[cfg(feature = "dev")]
 const VERSION_NUMBER: &str = concat!("V", ver!(), "d.KAZETA+");
 
@@ -105,9 +127,24 @@ const FONT_SIZE: u16 = 16;
 const GRID_OFFSET: f32 = 52.0;
 const GRID_WIDTH: usize = 13;
 const GRID_HEIGHT: usize = 5;
-const UI_BG_COLOR: Color = Color {r: 0.0, g: 0.0, b: 0.0, a: 0.5 };
-const UI_BG_COLOR_DARK: Color = Color {r: 0.0, g: 0.0, b: 0.0, a: 0.3 };
-const UI_BG_COLOR_DIALOG: Color = Color {r: 0.0, g: 0.0, b: 0.0, a: 0.8 };
+const UI_BG_COLOR: Color = Color {
+    r: 0.0,
+    g: 0.0,
+    b: 0.0,
+    a: 0.5,
+};
+const UI_BG_COLOR_DARK: Color = Color {
+    r: 0.0,
+    g: 0.0,
+    b: 0.0,
+    a: 0.3,
+};
+const UI_BG_COLOR_DIALOG: Color = Color {
+    r: 0.0,
+    g: 0.0,
+    b: 0.0,
+    a: 0.8,
+};
 const SELECTED_OFFSET: f32 = 5.0;
 const MENU_OPTION_HEIGHT: f32 = 30.0;
 const MENU_PADDING: f32 = 8.0;
@@ -115,12 +152,42 @@ const RECT_COLOR: Color = Color::new(0.15, 0.15, 0.15, 1.0);
 const FLASH_MESSAGE_DURATION: f32 = 5.0; // Show message for 5 seconds
 
 const COLOR_TARGETS: [Color; 6] = [
-Color { r: 1.0, g: 0.5, b: 0.5, a: 1.0 },
-Color { r: 1.0, g: 1.0, b: 0.5, a: 1.0 },
-Color { r: 0.5, g: 1.0, b: 0.5, a: 1.0 },
-Color { r: 0.5, g: 1.0, b: 1.0, a: 1.0 },
-Color { r: 0.5, g: 0.5, b: 1.0, a: 1.0 },
-Color { r: 1.0, g: 0.5, b: 1.0, a: 1.0 },
+    Color {
+        r: 1.0,
+        g: 0.5,
+        b: 0.5,
+        a: 1.0,
+    },
+    Color {
+        r: 1.0,
+        g: 1.0,
+        b: 0.5,
+        a: 1.0,
+    },
+    Color {
+        r: 0.5,
+        g: 1.0,
+        b: 0.5,
+        a: 1.0,
+    },
+    Color {
+        r: 0.5,
+        g: 1.0,
+        b: 1.0,
+        a: 1.0,
+    },
+    Color {
+        r: 0.5,
+        g: 0.5,
+        b: 1.0,
+        a: 1.0,
+    },
+    Color {
+        r: 1.0,
+        g: 0.5,
+        b: 1.0,
+        a: 1.0,
+    },
 ];
 
 const KAZETA_LOADING_MESSAGES: &[&str] = &[
@@ -211,9 +278,21 @@ macro_rules! load_asset_category {
                         println!("[OK] Loaded {}: {}", $type_name.to_lowercase(), file_name);
                         $cache.insert(file_name.to_string(), asset);
                         *$assets_loaded += 1;
-                        animate_step!($display_progress, $assets_loaded, $total_assets, $animation_speed, &status, $draw_fn);
+                        animate_step!(
+                            $display_progress,
+                            $assets_loaded,
+                            $total_assets,
+                            $animation_speed,
+                            &status,
+                            $draw_fn
+                        );
                     }
-                    Err(e) => eprintln!("[ERROR] Failed to load {} {}: {:?}", $type_name.to_lowercase(), path.display(), e),
+                    Err(e) => eprintln!(
+                        "[ERROR] Failed to load {} {}: {:?}",
+                        $type_name.to_lowercase(),
+                        path.display(),
+                        e
+                    ),
                 }
             }
         }
@@ -304,10 +383,22 @@ fn find_all_asset_files() -> (Vec<PathBuf>, Vec<PathBuf>, Vec<PathBuf>, Vec<Path
     // 3. Gather user-installed and theme assets
     if let Some(user_dir) = get_user_data_dir() {
         // Add assets from global user folders first
-        background_files_set.extend(utils::find_asset_files(&user_dir.join("backgrounds").to_string_lossy(), &["png", "mp4"]));
-        logo_files_set.extend(utils::find_asset_files(&user_dir.join("logos").to_string_lossy(), &["png"]));
-        font_files_set.extend(utils::find_asset_files(&user_dir.join("fonts").to_string_lossy(), &["ttf"]));
-        music_files_set.extend(utils::find_asset_files(&user_dir.join("bgm").to_string_lossy(), &["ogg", "wav"]));
+        background_files_set.extend(utils::find_asset_files(
+            &user_dir.join("backgrounds").to_string_lossy(),
+            &["png", "mp4"],
+        ));
+        logo_files_set.extend(utils::find_asset_files(
+            &user_dir.join("logos").to_string_lossy(),
+            &["png"],
+        ));
+        font_files_set.extend(utils::find_asset_files(
+            &user_dir.join("fonts").to_string_lossy(),
+            &["ttf"],
+        ));
+        music_files_set.extend(utils::find_asset_files(
+            &user_dir.join("bgm").to_string_lossy(),
+            &["ogg", "wav"],
+        ));
 
         // --- REVISED LOGIC for scanning theme folders ---
         let theme_dir = user_dir.join("themes");
@@ -317,16 +408,21 @@ fn find_all_asset_files() -> (Vec<PathBuf>, Vec<PathBuf>, Vec<PathBuf>, Vec<Path
                     let theme_path = entry.path();
 
                     // Find all assets within this theme folder just ONCE
-                    let theme_images = utils::find_asset_files(&theme_path.to_string_lossy(), &["png", "mp4"]);
-                    let theme_fonts = utils::find_asset_files(&theme_path.to_string_lossy(), &["ttf"]);
-                    let theme_music = utils::find_asset_files(&theme_path.to_string_lossy(), &["wav", "ogg"]);
+                    let theme_images =
+                        utils::find_asset_files(&theme_path.to_string_lossy(), &["png", "mp4"]);
+                    let theme_fonts =
+                        utils::find_asset_files(&theme_path.to_string_lossy(), &["ttf"]);
+                    let theme_music =
+                        utils::find_asset_files(&theme_path.to_string_lossy(), &["wav", "ogg"]);
 
                     // Now, intelligently sort the images into the correct sets based on filename
                     for image_path in theme_images {
                         if let Some(filename) = image_path.file_name().and_then(|s| s.to_str()) {
                             if filename.ends_with("_logo.png") {
                                 logo_files_set.insert(image_path);
-                            } else if filename.ends_with("_background.png") || filename.ends_with("_background.mp4") {
+                            } else if filename.ends_with("_background.png")
+                                || filename.ends_with("_background.mp4")
+                            {
                                 background_files_set.insert(image_path);
                             }
                         }
@@ -364,26 +460,37 @@ async fn load_all_assets(
     music_files: &[PathBuf],
     scale_factor: f32,
 ) -> (
-    HashMap<String, Texture2D>, // background cache (images)
+    HashMap<String, Texture2D>,   // background cache (images)
     HashMap<String, VideoPlayer>, // video cache
-    HashMap<String, Texture2D>, // logo cache
+    HashMap<String, Texture2D>,   // logo cache
     HashMap<String, SamplesBuffer>,
     HashMap<String, Font>, // font cache
-    SoundEffects, // sfx
+    SoundEffects,          // sfx
 ) {
     let draw_loading_screen = |status_message: &str, progress: f32| {
         let font_size = (16.0 * scale_factor) as u16;
         let line_spacing = 10.0 * scale_factor;
         let lines: Vec<&str> = display_message.lines().collect();
 
-        let total_text_height = (lines.len() as f32 * font_size as f32) + ((lines.len() - 1) as f32 * line_spacing);
+        let total_text_height =
+            (lines.len() as f32 * font_size as f32) + ((lines.len() - 1) as f32 * line_spacing);
         let y_start = screen_height() / 2.0 - total_text_height / 2.0;
 
         for (i, line) in lines.iter().enumerate() {
             let line_width = measure_text(line, Some(font), font_size, 1.0).width;
             let x = (screen_width() - line_width) / 2.0; // Center each line individually
             let y = y_start + (i as f32 * (font_size as f32 + line_spacing));
-            draw_text_ex(line, x, y, TextParams { font: Some(font), font_size, color: WHITE, ..Default::default() });
+            draw_text_ex(
+                line,
+                x,
+                y,
+                TextParams {
+                    font: Some(font),
+                    font_size,
+                    color: WHITE,
+                    ..Default::default()
+                },
+            );
         }
 
         // --- Scale and draw the progress bar ---
@@ -404,8 +511,8 @@ async fn load_all_assets(
             bar_x + inset,
             bar_y + inset,
             (bar_width - inset * 2.0) * safe_progress, // The fill width, adjusted for the border
-            bar_height - inset * 2.0, // The fill height, adjusted for the border
-            RED
+            bar_height - inset * 2.0,                  // The fill height, adjusted for the border
+            RED,
         );
 
         // loading status
@@ -418,13 +525,19 @@ async fn load_all_assets(
             status_message,
             10.0 * scale_factor, // A small margin from the left
             status_y,
-            TextParams { font: Some(font), font_size: status_font_size, color: WHITE, ..Default::default() },
+            TextParams {
+                font: Some(font),
+                font_size: status_font_size,
+                color: WHITE,
+                ..Default::default()
+            },
         );
     };
 
     // --- COUNT TOTAL ASSETS ---
     // This is now correct because the file lists are passed into the function
-    let total_asset_count = 3 + 4 + background_files.len() + logo_files.len() + font_files.len() + music_files.len();
+    let total_asset_count =
+        3 + 4 + background_files.len() + logo_files.len() + font_files.len() + music_files.len();
 
     // --- SETUP ---
     let mut assets_loaded = 0;
@@ -444,57 +557,134 @@ async fn load_all_assets(
 
     // background
     let status = "LOADING DEFAULT BACKGROUND...".to_string();
-    let default_bg = Texture2D::from_file_with_format(include_bytes!("../background.png"), Some(ImageFormat::Png));
+    let default_bg = Texture2D::from_file_with_format(
+        include_bytes!("../background.png"),
+        Some(ImageFormat::Png),
+    );
     background_cache.insert("Default".to_string(), default_bg);
     assets_loaded += 1;
-    animate_step!(&mut display_progress, &mut assets_loaded, total_asset_count, animation_speed, &status, &draw_loading_screen);
+    animate_step!(
+        &mut display_progress,
+        &mut assets_loaded,
+        total_asset_count,
+        animation_speed,
+        &status,
+        &draw_loading_screen
+    );
 
     // logo
     let status = "LOADING LOGOS...".to_string();
-    let default_logo = Texture2D::from_file_with_format(include_bytes!("../logo.png"), Some(ImageFormat::Png));
+    let default_logo =
+        Texture2D::from_file_with_format(include_bytes!("../logo.png"), Some(ImageFormat::Png));
     logo_cache.insert("Kazeta+ (Default)".to_string(), default_logo);
     assets_loaded += 1;
-    animate_step!(&mut display_progress, &mut assets_loaded, total_asset_count, animation_speed, &status, &draw_loading_screen);
-
-    let original_logo = Texture2D::from_file_with_format(include_bytes!("../logos/original_logo.png"), Some(ImageFormat::Png));
+    animate_step!(
+        &mut display_progress,
+        &mut assets_loaded,
+        total_asset_count,
+        animation_speed,
+        &status,
+        &draw_loading_screen
+    );
+
+    let original_logo = Texture2D::from_file_with_format(
+        include_bytes!("../logos/original_logo.png"),
+        Some(ImageFormat::Png),
+    );
     logo_cache.insert("Kazeta (Original)".to_string(), original_logo);
     assets_loaded += 1;
-    animate_step!(&mut display_progress, &mut assets_loaded, total_asset_count, animation_speed, &status, &draw_loading_screen);
+    animate_step!(
+        &mut display_progress,
+        &mut assets_loaded,
+        total_asset_count,
+        animation_speed,
+        &status,
+        &draw_loading_screen
+    );
 
     // font
     let status = "LOADING DEFAULT FONT...".to_string();
     let default_font = load_ttf_font_from_bytes(include_bytes!("../november.ttf")).unwrap();
     font_cache.insert("Default".to_string(), default_font);
     assets_loaded += 1;
-    animate_step!(&mut display_progress, &mut assets_loaded, total_asset_count, animation_speed, &status, &draw_loading_screen);
+    animate_step!(
+        &mut display_progress,
+        &mut assets_loaded,
+        total_asset_count,
+        animation_speed,
+        &status,
+        &draw_loading_screen
+    );
 
     // sfx
     let status = "LOADING DEFAULT SFX...".to_string();
     assets_loaded += 1;
-    animate_step!(&mut display_progress, &mut assets_loaded, total_asset_count, animation_speed, &status, &draw_loading_screen);
+    animate_step!(
+        &mut display_progress,
+        &mut assets_loaded,
+        total_asset_count,
+        animation_speed,
+        &status,
+        &draw_loading_screen
+    );
 
     assets_loaded += 1;
-    animate_step!(&mut display_progress, &mut assets_loaded, total_asset_count, animation_speed, &status, &draw_loading_screen);
+    animate_step!(
+        &mut display_progress,
+        &mut assets_loaded,
+        total_asset_count,
+        animation_speed,
+        &status,
+        &draw_loading_screen
+    );
 
     assets_loaded += 1;
-    animate_step!(&mut display_progress, &mut assets_loaded, total_asset_count, animation_speed, &status, &draw_loading_screen);
+    animate_step!(
+        &mut display_progress,
+        &mut assets_loaded,
+        total_asset_count,
+        animation_speed,
+        &status,
+        &draw_loading_screen
+    );
 
     assets_loaded += 1;
-    animate_step!(&mut display_progress, &mut assets_loaded, total_asset_count, animation_speed, &status, &draw_loading_screen);
+    animate_step!(
+        &mut display_progress,
+        &mut assets_loaded,
+        total_asset_count,
+        animation_speed,
+        &status,
+        &draw_loading_screen
+    );
 
     // --- CUSTOM ASSETS ---
     println!("\n[INFO] Pre-loading custom assets...");
 
     // separate image backgrounds from video backgrounds
-    let image_backgrounds: Vec<PathBuf> = background_files.iter()
+    let image_backgrounds: Vec<PathBuf> = background_files
+        .iter()
         .filter(|p| p.extension().map_or(false, |e| e == "png"))
-        .cloned().collect();
+        .cloned()
+        .collect();
 
-    let video_backgrounds: Vec<PathBuf> = background_files.iter()
+    let video_backgrounds: Vec<PathBuf> = background_files
+        .iter()
         .filter(|p| p.extension().map_or(false, |e| e == "mp4"))
-        .cloned().collect();
+        .cloned()
+        .collect();
 
-    load_asset_category!(&image_backgrounds, "BACKGROUND", load_texture, &mut background_cache, &mut assets_loaded, total_asset_count, &mut display_progress, animation_speed, &draw_loading_screen);
+    load_asset_category!(
+        &image_backgrounds,
+        "BACKGROUND",
+        load_texture,
+        &mut background_cache,
+        &mut assets_loaded,
+        total_asset_count,
+        &mut display_progress,
+        animation_speed,
+        &draw_loading_screen
+    );
 
     // Load Videos Manually (Macros struggle with complex types like VideoPlayer)
     for path in video_backgrounds {
@@ -509,18 +699,54 @@ async fn load_all_assets(
                     println!("[OK] Loaded video: {}", file_name);
                     video_cache.insert(file_name.to_string(), player);
                     assets_loaded += 1;
-                    animate_step!(&mut display_progress, &mut assets_loaded, total_asset_count, animation_speed, &status, &draw_loading_screen);
+                    animate_step!(
+                        &mut display_progress,
+                        &mut assets_loaded,
+                        total_asset_count,
+                        animation_speed,
+                        &status,
+                        &draw_loading_screen
+                    );
                 }
                 Err(e) => eprintln!("[ERROR] Failed to load video {}: {}", file_name, e),
             }
         }
     }
 
-    load_asset_category!(logo_files, "LOGO", load_texture, &mut logo_cache, &mut assets_loaded, total_asset_count, &mut display_progress, animation_speed, &draw_loading_screen);
-    load_asset_category!(font_files, "FONT", load_ttf_font, &mut font_cache, &mut assets_loaded, total_asset_count, &mut display_progress, animation_speed, &draw_loading_screen);
+    load_asset_category!(
+        logo_files,
+        "LOGO",
+        load_texture,
+        &mut logo_cache,
+        &mut assets_loaded,
+        total_asset_count,
+        &mut display_progress,
+        animation_speed,
+        &draw_loading_screen
+    );
+    load_asset_category!(
+        font_files,
+        "FONT",
+        load_ttf_font,
+        &mut font_cache,
+        &mut assets_loaded,
+        total_asset_count,
+        &mut display_progress,
+        animation_speed,
+        &draw_loading_screen
+    );
 
     println!("\n[INFO] Pre-loading music files...");
-    load_audio_category!(music_files, "MUSIC", &mut music_cache, &mut assets_loaded, total_asset_count, &mut display_progress, animation_speed, &draw_loading_screen);
+    load_audio_category!(
+        music_files,
+        "MUSIC",
+        &mut music_cache,
+        &mut assets_loaded,
+        total_asset_count,
+        &mut display_progress,
+        animation_speed,
+        &draw_loading_screen
+    );
 
     // Final draw at 100%
     let status = "LOADING COMPLETE".to_string();
@@ -532,15 +758,31 @@ async fn load_all_assets(
     //let sound_effects = audio::SoundEffects::load(&config.sfx_pack).await;
     let sound_effects = audio::SoundEffects::load(&config.sfx_pack);
 
-    (background_cache, video_cache, logo_cache, music_cache, font_cache, sound_effects)
+    (
+        background_cache,
+        video_cache,
+        logo_cache,
+        music_cache,
+        font_cache,
+        sound_effects,
+    )
 }
 
 // ===================================
 // BEGINNING OF MAIN
 // ===================================
 
-#[macroquad::main(window_conf)]
-async fn main() {
+fn main() {
+    // `kazeta-bios saves ...` manages saves without starting the UI
+    let args: Vec<String> = env::args().skip(1).collect();
+    if let Some(code) = cli::run(&args) {
+        process::exit(code);
+    }
+
+    macroquad::Window::from_config(window_conf(), run_bios());
+}
+
+async fn run_bios() {
     env::set_var("RUST_BACKTRACE", "full"); // allow backtracing for debugging panics
 
     if DEV_MODE {
@@ -551,11 +793,13 @@ async fn main() {
 
     let mut dialogs: Vec<Dialog> = Vec::new();
     let mut dialog_state = DialogState::None;
-    let placeholder = Texture2D::from_file_with_format(include_bytes!("../placeholder.png"), Some(ImageFormat::Png));
+    let placeholder = Texture2D::from_file_with_format(
+        include_bytes!("../placeholder.png"),
+        Some(ImageFormat::Png),
+    );
     let mut icon_cache: HashMap<String, Texture2D> = HashMap::new();
     let mut icon_queue: Vec<(String, String)> = Vec::new();
-    let mut playtime_cache: PlaytimeCache = HashMap::new();
-    let mut size_cache: SizeCache = HashMap::new();
+    let mut save_metadata_cache = memory::SaveMetadataCache::default();
     let mut scroll_offset = 0;
 
     // SYSTEM INFO
@@ -567,11 +811,17 @@ async fn main() {
     let mut wifi_state = WifiState::new();
 
     // load config file
+    // Checked before anything can write config.toml
+    let first_boot = ui::first_run::needs_wizard();
     let mut config = Config::load();
+    save::set_compress_archives(config.compress_external_saves);
 
     // RETROACHIEVEMENTS
     let mut ra_settings_state = RASettingsState::load_from_config(&config);
 
+    // NETWORK SHARE
+    let mut network_share_state = NetworkShareState::new();
+
     // THEME DOWNLOADER
     let mut theme_downloader_state = ThemeDownloaderState::new();
 
@@ -588,6 +838,40 @@ async fn main() {
     // UPDATE CHECKER
     let mut update_checker_state = UpdateCheckerState::new();
 
+    // SYSTEM UPDATE
+    let mut system_update_state = ui::system_update::SystemUpdateState::new();
+
+    // PER-GAME DISPLAY SETTINGS
+    let mut game_display_state = ui::game_display::GameDisplayState::new();
+    let mut multiplayer_history_state = ui::multiplayer_history::MultiplayerHistoryState::new();
+
+    // CARTRIDGE METADATA EDITOR
+    let mut cart_editor_state = ui::cart_editor::CartEditorState::new();
+    let mut collections_state = ui::collections::CollectionsState::new();
+
+    // KIOSK WHITELIST
+    let mut kiosk_whitelist_state = ui::kiosk::KioskWhitelistState::new();
+
+    // PAIRED REMOTE DEVICES
+    let mut remote_devices_state = ui::remote_devices::RemoteDevicesState::new();
+    let mut player_join_state = ui::player_join::PlayerJoinState::new();
+    let mut save_slots_state = ui::save_slots::SaveSlotsState::new();
+
+    // LOG VIEWER
+    let mut log_viewer_state = ui::logs::LogViewerState::new();
+
+    // SETUP WIZARD
+    let mut first_run_state = ui::first_run::FirstRunState::new();
+
+    // GAMEPAD TESTER
+    let mut gamepad_tester_state = ui::gamepad_tester::GamepadTesterState::new();
+
+    // DISPLAY SETTINGS
+    let mut display_settings_state = ui::display::DisplaySettingsState::new();
+
+    // TROPHY ROOM
+    let mut trophy_room_state = ui::trophy_room::TrophyRoomState::new();
+
     // CD PLAYER STATE
     let cd_player_backend = Arc::new(Mutex::new(CdPlayerBackend::new()));
     let mut cd_player_ui_state = ui::cd_player::CdPlayerUiState::new(cd_player_backend.clone());
@@ -616,6 +900,9 @@ async fn main() {
     let mut last_battery_check = get_time();
     const BATTERY_CHECK_INTERVAL: f64 = 5.0; // only check every 5 seconds to improve performance
 
+    // THERMAL
+    let mut thermal_monitor = thermal::ThermalMonitor::new();
+
     // AUDIO SINKS
     // Load the list of sinks so the Settings menu can use it.
     // We will NOT try to set a default here.
@@ -623,8 +910,15 @@ async fn main() {
     println!("[Debug] Sinks loaded at startup: {:#?}", available_sinks);
 
     // If the saved sink isn't available, reset the config value to "Auto"
-    if !available_sinks.iter().any(|s| s.name == config.audio_output) && config.audio_output != "Auto" {
-        println!("[WARN] Saved audio sink '{}' not found. Reverting to 'Auto'.", config.audio_output);
+    if !available_sinks
+        .iter()
+        .any(|s| s.name == config.audio_output)
+        && config.audio_output != "Auto"
+    {
+        println!(
+            "[WARN] Saved audio sink '{}' not found. Reverting to 'Auto'.",
+            config.audio_output
+        );
         config.audio_output = "Auto".to_string();
         config.save();
     }
@@ -663,8 +957,6 @@ async fn main() {
     let mut loaded_themes: HashMap<String, theme::Theme> = theme::load_all_themes().await;
     println!("[INFO] {} themes loaded successfully.", loaded_themes.len());
 
-    let sound_pack_choices = audio::find_sound_packs();
-
     // find all asset files
     let (background_files, logo_files, font_files, music_files) = find_all_asset_files();
 
@@ -673,8 +965,14 @@ async fn main() {
     let scale_factor = screen_height() / BASE_SCREEN_HEIGHT;
 
     // load them
-    let (mut background_cache, mut video_cache, mut logo_cache, mut music_cache, mut font_cache, mut sound_effects) =
-    load_all_assets(
+    let (
+        mut background_cache,
+        mut video_cache,
+        mut logo_cache,
+        mut music_cache,
+        mut font_cache,
+        mut sound_effects,
+    ) = load_all_assets(
         &config,
         loading_text,
         &startup_font,
@@ -682,13 +980,19 @@ async fn main() {
         &logo_files,
         &font_files,
         &music_files,
-        scale_factor
-    ).await;
+        scale_factor,
+    )
+    .await;
 
     // Note: apply_resolution() is not called here because we start in fullscreen mode.
     // Resolution settings only apply when the user switches to windowed mode in settings.
+    // The display's own output mode is set through the helper instead.
+    display::restore_saved_mode(&config);
     next_frame().await;
 
+    // We're back from a game: log the multiplayer session it was, if any
+    multiplayer_log::finish_pending();
+
     // load custom sound pack
     if config.sfx_pack != "Default" {
         println!("[Info] Loading configured SFX pack: {}", &config.sfx_pack);
@@ -700,10 +1004,13 @@ async fn main() {
     // logos
     // --- Create a custom-ordered list of logo choices for the UI ---
     // 1. Get all the custom logo filenames from the cache keys (excluding the default)
-    let mut custom_logos: Vec<String> = logo_cache.keys()
-    .filter(|k| *k != "Kazeta+ (Default)" && *k != "Kazeta (Original)" && k.ends_with("_logo.png")) // Add this filter
-    .cloned()
-    .collect();
+    let mut custom_logos: Vec<String> = logo_cache
+        .keys()
+        .filter(|k| {
+            *k != "Kazeta+ (Default)" && *k != "Kazeta (Original)" && k.ends_with("_logo.png")
+        }) // Add this filter
+        .cloned()
+        .collect();
     custom_logos.sort(); // Sort just the custom logos alphabetically
 
     // 2. Create the final list with our specific order
@@ -723,12 +1030,14 @@ async fn main() {
     };
 
     // backgrounds
-    let mut background_choices: Vec<String> = background_cache.keys()
+    let mut background_choices: Vec<String> = background_cache
+        .keys()
         .filter(|k| k.ends_with("_background.png") || *k == "Default") // Add this filter
         .cloned()
         .collect();
 
-    let video_choices: Vec<String> = video_cache.keys() // video backgrounds
+    let video_choices: Vec<String> = video_cache
+        .keys() // video backgrounds
         .filter(|k| k.ends_with("_background.mp4"))
         .cloned()
         .collect();
@@ -743,18 +1052,23 @@ async fn main() {
     // bgm
     let mut bgm_choices: Vec<String> = vec!["OFF".to_string()];
     let track_names: Vec<String> = music_files
-    .iter()
-    .filter_map(|path| path.file_name())
-    .filter_map(|name| name.to_str())
-    .map(|s| s.to_string())
-    .collect();
+        .iter()
+        .filter_map(|path| path.file_name())
+        .filter_map(|name| name.to_str())
+        .map(|s| s.to_string())
+        .collect();
     bgm_choices.extend(track_names);
 
     let mut current_bgm: Option<Sink> = None;
 
     // At the end of your setup, start the BGM based on the config
     if let Some(track_name) = &config.bgm_track {
-        play_new_bgm(track_name, config.bgm_volume, &music_cache, &mut current_bgm);
+        play_new_bgm(
+            track_name,
+            config.bgm_volume,
+            &music_cache,
+            &mut current_bgm,
+        );
     }
 
     // Initialize gamepad support
@@ -762,13 +1076,18 @@ async fn main() {
     let mut input_state = InputState::new();
     let mut animation_state = AnimationState::new();
 
+    // Any game that suppressed hotkeys has exited by the time the BIOS runs
+    crate::utils::notify_input_game_stopped();
+
     // Start overlay daemon so it can be triggered from BIOS
     println!("[BIOS] Starting overlay daemon...");
     if let Err(e) = crate::utils::start_overlay_daemon() {
         eprintln!("[BIOS] Warning: Failed to start overlay daemon: {}", e);
         eprintln!("[BIOS] Overlay will still be available when games launch");
     } else {
-        println!("[BIOS] Overlay daemon started - press F12, Ctrl+O, or Guide button to open overlay");
+        println!(
+            "[BIOS] Overlay daemon started - press F12, Ctrl+O, or Guide button to open overlay"
+        );
     }
 
     // SPLASH SCREEN
@@ -778,127 +1097,22 @@ async fn main() {
             sink.set_volume(0.0);
         }
 
-        let sink = Sink::connect_new(&AUDIO.stream.mixer());
-
-        // 1. Setup Audio
-        // Try to load custom splash audio from music cache, otherwise use default
-        if config.splash_audio != "Default" {
-            if let Some(audio_buffer) = music_cache.get(&config.splash_audio) {
-                sink.append(audio_buffer.clone());
-            } else {
-                // Fallback to default if custom audio not found
-                let splash_bytes = include_bytes!("../splash.wav");
-                let cursor = Cursor::new(splash_bytes);
-                let source = Decoder::new(cursor).unwrap();
-                sink.append(source);
-            }
-        } else {
-            // Use default embedded audio
-            let splash_bytes = include_bytes!("../splash.wav");
-            let cursor = Cursor::new(splash_bytes);
-            let source = Decoder::new(cursor).unwrap();
-            sink.append(source);
-        }
-
-        // 2. Setup Video
-        // Try to use custom splash video, otherwise use default
-        let mut video_player: Option<VideoPlayer> = None;
-
-        if config.splash_video != "Default" {
-            // Try to find the custom splash video file
-            if let Some(user_dir) = get_user_data_dir() {
-                // Check in theme-specific directory first
-                let theme_splash_path = user_dir.join("themes").join(&config.theme).join(&config.splash_video);
-                if theme_splash_path.exists() {
-                    video_player = VideoPlayer::new(&theme_splash_path).ok();
-                } else {
-                    // Check in global backgrounds directory
-                    let global_splash_path = user_dir.join("backgrounds").join(&config.splash_video);
-                    if global_splash_path.exists() {
-                        video_player = VideoPlayer::new(&global_splash_path).ok();
-                    }
-                }
-            }
-        }
-
-        // If no custom video found or config is "Default", load the embedded video
-        if video_player.is_none() {
-            let video_bytes = include_bytes!("../splash.mp4");
-            let mut temp_video = NamedTempFile::new().unwrap();
-            temp_video.write_all(video_bytes).unwrap();
-            let temp_path = temp_video.path().to_path_buf();
-            video_player = VideoPlayer::new(&temp_path).ok();
-        }
-
-        // Fallback logo if video fails
-        let fallback_logo = Texture2D::from_file_with_format(include_bytes!("../logo.png"), Some(ImageFormat::Png));
-
-        let state_start_time = get_time();
-        // Use video duration, or fallback to 3.0 seconds
-        let duration = video_player.as_ref().map(|vp| vp.duration_secs).unwrap_or(3.0);
-
+        let mut splash = ui::boot_splash::BootSplash::start(&config, &logo_cache, &music_cache);
         loop {
             // --- Input Skipping ---
             input_state.reset();
             input_state.update_keyboard();
             input_state.update_controller(&mut gilrs);
 
-            if input_state.back || input_state.select {
-                break;
-            }
-
-            let elapsed = get_time() - state_start_time;
-
-            if elapsed > duration {
+            if input_state.back || input_state.select || splash.is_done() {
                 break;
             }
 
-            clear_background(BLACK);
-
-            if let Some(player) = &mut video_player {
-                // --- VIDEO MODE ---
-                player.update(elapsed);
-
-                // Draw Fullscreen
-                draw_texture_ex(
-                    &player.texture,
-                    0.0,
-                    0.0,
-                    WHITE,
-                    DrawTextureParams {
-                        dest_size: Some(vec2(screen_width(), screen_height())),
-                        ..Default::default()
-                    },
-                );
-            } else {
-                // --- FALLBACK IMAGE MODE (Your old code) ---
-                // Calculate fading based on 'elapsed' and 'duration'
-                let alpha = if elapsed < 0.5 {
-                    elapsed / 0.5
-                } else if elapsed > duration - 0.5 {
-                    (duration - elapsed) / 0.5
-                } else { 1.0 } as f32;
-
-                let aspect_ratio = fallback_logo.height() / fallback_logo.width();
-                let scaled_width = 200.0 * (screen_height() / BASE_SCREEN_HEIGHT);
-                let scaled_height = scaled_width * aspect_ratio;
-
-                draw_texture_ex(
-                    &fallback_logo,
-                    (screen_width() - scaled_width) / 2.0,
-                    (screen_height() - scaled_height) / 2.0,
-                    Color::new(1.0, 1.0, 1.0, alpha),
-                    DrawTextureParams {
-                        dest_size: Some(vec2(scaled_width, scaled_height)),
-                    ..Default::default()
-                    }
-                );
-            }
+            splash.draw();
             next_frame().await;
         }
-
-        // Cleanup
-        // Temp file is deleted automatically when `temp_video` goes out of scope
+        // Stops the splash sound if it was skipped
+        drop(splash);
 
         // Restore BGM
         if let Some(sink) = &current_bgm {
@@ -915,9 +1129,14 @@ async fn main() {
     } else {
         Screen::MainMenu
     };
+    if first_boot {
+        first_run_state.open(current_screen.clone());
+        current_screen = Screen::FirstRun;
+    }
     let mut main_menu_selection: usize = 0;
     let mut settings_menu_selection: usize = 0;
     let mut extras_menu_selection: usize = 0;
+    let mut power_menu_state = ui::power::PowerMenuState::new();
     let mut game_selection: usize = 0; // For the new menu
     let mut available_games: Vec<(save::CartInfo, PathBuf)> = Vec::new(); // To hold the list of found games
     let mut play_option_enabled: bool = false;
@@ -930,6 +1149,7 @@ async fn main() {
     let mut mgba_launch_options = GameLaunchOptions::default();
     let mut mgba_launch_dialog: Option<dialog::Dialog> = None;
     let mut mgba_pending_game: Option<(save::CartInfo, PathBuf)> = None;
+    let mut mgba_launch_ready = false; // slots were picked on the save slot screen
 
     // GCC ADAPTER
     let mut app_state = AppState {
@@ -948,6 +1168,7 @@ async fn main() {
     let mut fade_start_time: Option<f64> = None;
     const FADE_DURATION: f64 = 1.0; // 1 second fade
     const FADE_LINGER_DURATION: f64 = 0.5; // 0.5 seconds to linger on black screen
+    let mut farewell_screen: Option<ui::farewell::FarewellScreen> = None;
 
     // Create thread-safe cart connection status
     let cart_connected = Arc::new(AtomicBool::new(false));
@@ -974,12 +1195,10 @@ async fn main() {
 
     // Spawn background thread for storage media detection
     let thread_storage_state = storage_state.clone();
-    thread::spawn(move || {
-        loop {
-            thread::sleep(time::Duration::from_secs(1));
-            if let Ok(mut state) = thread_storage_state.lock() {
-                state.update_media();
-            }
+    thread::spawn(move || loop {
+        thread::sleep(time::Duration::from_secs(1));
+        if let Ok(mut state) = thread_storage_state.lock() {
+            state.update_media();
         }
     });
 
@@ -993,11 +1212,24 @@ async fn main() {
         error_message: None,
     }));
 
+    let mut idle_tracker = idle::IdleTracker::new();
+    let mut attract_mode = kiosk::AttractMode::new();
+    let mut remote_listener = remote::RemoteListener::new();
+    let mut backup_scheduler = backup::BackupScheduler::new();
+    let removal_monitor = removal::RemovalMonitor::start();
+    library::start();
+    let share_monitor = netshare::ShareMonitor::start(&config.network_share);
+
     // BEGINNING OF MAIN LOOP
     loop {
         let _active_theme = loaded_themes.get(&config.theme).unwrap_or_else(|| {
-            println!("[WARN] Active theme '{}' not found. Falling back to 'Default'.", &config.theme);
-            loaded_themes.get("Default").expect("Default fallback theme is also missing!")
+            println!(
+                "[WARN] Active theme '{}' not found. Falling back to 'Default'.",
+                &config.theme
+            );
+            loaded_themes
+                .get("Default")
+                .expect("Default fallback theme is also missing!")
         });
         let scale_factor = screen_height() / BASE_SCREEN_HEIGHT;
 
@@ -1022,6 +1254,27 @@ async fn main() {
             last_battery_check = get_time();
         }
 
+        // THERMAL
+        // The overlay carries the warnings while a game is on screen
+        match thermal_monitor.update(&config.thermal, game_process.is_some()) {
+            Some(thermal::ThermalEvent::Warning { kind, celsius }) => {
+                let message = format!("{} TEMPERATURE HIGH: {:.0}°C", kind.label(), celsius);
+                if game_process.is_some() {
+                    utils::show_warning_toast(&message);
+                } else {
+                    flash_message = Some((message, FLASH_MESSAGE_DURATION));
+                }
+            }
+            Some(thermal::ThermalEvent::SuggestFrameLimit { celsius }) => {
+                utils::show_warning_toast(&format!(
+                    "Running hot ({:.0}°C). A {} FPS limit in the game's display settings would cool it down.",
+                    celsius,
+                    thermal::SUGGESTED_FRAME_LIMIT
+                ));
+            }
+            None => {}
+        }
+
         // GCC
         // Check for messages from the GCC adapter thread
         if let Ok(msg) = rx_gcc.try_recv() {
@@ -1040,6 +1293,100 @@ async fn main() {
         input_state.update_keyboard();
         input_state.update_controller(&mut gilrs);
 
+        // IDLE
+        // Dim/suspend after inactivity. Music playback in the CD player keeps us awake,
+        // and the press that wakes the screen is swallowed so it doesn't select anything.
+        if idle_tracker.update(
+            &input_state,
+            &config,
+            current_screen == Screen::CdPlayer || attract_mode.active,
+        ) {
+            input_state.reset();
+        }
+
+        // KIOSK ATTRACT MODE
+        // Cycles cover art and screenshots once idle long enough; the press that ends it
+        // is swallowed the same way.
+        if attract_mode.update(
+            &input_state,
+            idle_tracker.idle_seconds(),
+            &current_screen,
+            &config,
+            &music_cache,
+            &mut current_bgm,
+        ) {
+            input_state.reset();
+        }
+
+        // NIGHTLY BACKUPS
+        // Snapshots changed internal saves to the designated drive once a day while idle
+        backup_scheduler.update(idle_tracker.idle_seconds(), game_process.is_some());
+
+        // REMOTE CONTROL
+        // Requests from a paired companion app. A confirmation prompt or pairing
+        // code on screen takes the input until it's dismissed.
+        remote_listener.sync(&config);
+        if remote_listener.update(&input_state, &current_screen, &mut config) {
+            input_state.reset();
+        }
+        if let Some(action) = remote_listener.take_action() {
+            idle_tracker.reset();
+            if attract_mode.active {
+                attract_mode.stop(&config, &music_cache, &mut current_bgm);
+            }
+            if let remote::RemoteAction::Launch(cart_info, kzi_path) = action {
+                (current_screen, fade_start_time) = trigger_game_launch(
+                    &cart_info,
+                    &kzi_path,
+                    &mut current_bgm,
+                    &music_cache,
+                    &sound_effects,
+                    &config,
+                );
+            }
+        }
+
+        // CARTRIDGE REMOVAL
+        // Saves are flushed and the event logged by the monitor; let the user know
+        // if a drive went away in the middle of a write.
+        for event in removal_monitor.poll() {
+            if event.during_write {
+                flash_message = Some((
+                    format!("{} REMOVED DURING SAVE!", event.mount_name.to_uppercase()),
+                    FLASH_MESSAGE_DURATION,
+                ));
+            }
+        }
+
+        // NETWORK SHARE
+        // The library rescans on its own; drop the share's carts from a list
+        // that's already open so nothing launches from a share that's gone.
+        share_monitor.sync(&config.network_share);
+        for event in share_monitor.poll() {
+            match event {
+                netshare::ShareEvent::Connected => {
+                    flash_message = Some((
+                        "NETWORK SHARE CONNECTED".to_string(),
+                        FLASH_MESSAGE_DURATION,
+                    ));
+                }
+                netshare::ShareEvent::Lost => {
+                    flash_message = Some((
+                        "NETWORK SHARE DISCONNECTED".to_string(),
+                        FLASH_MESSAGE_DURATION,
+                    ));
+                    available_games.retain(|(_, path)| !netshare::is_on_share(path));
+                    game_icon_queue.retain(|(_, icon)| !netshare::is_on_share(icon));
+                    game_selection = game_selection.min(available_games.len().saturating_sub(1));
+                }
+            }
+        }
+
+        // Don't let input through while a save is being written
+        if save::save_write_in_progress() {
+            input_state.reset();
+        }
+
         // Check for overlay hotkey (Guide button or F12/Ctrl+O)
         // Overlay daemon is started with BIOS, so overlay can be triggered from BIOS
         if input_state.overlay_hotkey {
@@ -1088,11 +1435,11 @@ async fn main() {
             match dialog_state {
                 DialogState::Opening => {
                     dialog_state = DialogState::Open;
-                },
+                }
                 DialogState::Closing => {
                     dialog_state = DialogState::None;
                     dialogs.clear();
-                },
+                }
                 _ => {}
             }
         }
@@ -1106,6 +1453,7 @@ async fn main() {
                 // Tell the about module to draw itself
                 ui::about::draw(
                     &system_info,
+                    &thermal_monitor.reading,
                     &logo_cache,
                     &background_cache,
                     &mut video_cache,
@@ -1126,11 +1474,11 @@ async fn main() {
                     &config,
                 );
 
-            match action {
-                ui::blades::BladeAction::None => {},
-                ui::blades::BladeAction::LaunchGame((cart_info, kzi_path)) => {
-                    // Mark that this game flow started from Blades so Back can return here.
-                    return_to_blades_after_game = true;
+                match action {
+                    ui::blades::BladeAction::None => {}
+                    ui::blades::BladeAction::LaunchGame((cart_info, kzi_path)) => {
+                        // Mark that this game flow started from Blades so Back can return here.
+                        return_to_blades_after_game = true;
 
                         if cart_info.runtime.as_deref() == Some("vba-m") {
                             // mGBA multiplayer/save-slot flow (reuse existing dialog state)
@@ -1146,14 +1494,22 @@ async fn main() {
                             };
 
                             if max_players > 1 {
-                                mgba_launch_step = GameLaunchStep::SelectPlayerCount;
-                                mgba_launch_dialog = Some(dialog::create_player_count_dialog(max_players));
+                                player_join_state.open(
+                                    cart_info.name.as_deref().unwrap_or(&cart_info.id),
+                                    max_players,
+                                    &gilrs,
+                                );
                             } else {
                                 mgba_launch_step = GameLaunchStep::SelectSaveSlot { player: 1 };
                                 let save_dir = save::get_mgba_save_dir(&cart_info.id);
                                 let rom_name = save::get_rom_name_from_exec(&cart_info.exec);
-                                let existing_saves = dialog::find_existing_save_slots(&save_dir, &rom_name);
-                                let can_import = cart_info.player_saves.get(0).and_then(|s| s.as_ref()).is_some();
+                                let existing_saves =
+                                    dialog::find_existing_save_slots(&save_dir, &rom_name);
+                                let can_import = cart_info
+                                    .player_saves
+                                    .get(0)
+                                    .and_then(|s| s.as_ref())
+                                    .is_some();
                                 mgba_launch_dialog = Some(dialog::create_save_slot_dialog(
                                     &existing_saves,
                                     1,
@@ -1162,14 +1518,21 @@ async fn main() {
                                 ));
                             }
 
-                            current_screen = Screen::GameLaunchOptions;
+                            current_screen = if max_players > 1 {
+                                Screen::PlayerJoin
+                            } else {
+                                Screen::GameLaunchOptions
+                            };
                         } else {
                             if DEV_MODE {
                                 log_messages.lock().unwrap().clear();
                                 {
                                     let mut logs = log_messages.lock().unwrap();
                                     logs.push("--- LAUNCH FROM BLADES ---".to_string());
-                                    logs.push(format!("Name: {}", cart_info.name.as_deref().unwrap_or("N/A")));
+                                    logs.push(format!(
+                                        "Name: {}",
+                                        cart_info.name.as_deref().unwrap_or("N/A")
+                                    ));
                                 }
                                 match save::launch_game(&cart_info, &kzi_path) {
                                     Ok(mut child) => {
@@ -1177,7 +1540,10 @@ async fn main() {
                                         game_process = Some(child);
                                     }
                                     Err(e) => {
-                                        log_messages.lock().unwrap().push(format!("\n--- LAUNCH FAILED ---\nError: {}", e));
+                                        log_messages
+                                            .lock()
+                                            .unwrap()
+                                            .push(format!("\n--- LAUNCH FAILED ---\nError: {}", e));
                                     }
                                 }
                                 current_screen = Screen::Debug;
@@ -1186,27 +1552,39 @@ async fn main() {
                                     &cart_info,
                                     &kzi_path,
                                     &mut current_bgm,
-                                    &music_cache
+                                    &music_cache,
+                                    &sound_effects,
+                                    &config,
                                 );
                             }
                         }
                     }
                     ui::blades::BladeAction::GoToScreen(screen) => {
-                        if matches!(screen, Screen::GeneralSettings | Screen::AudioSettings | Screen::GuiSettings | Screen::AssetSettings | Screen::SaveData) {
+                        if matches!(
+                            screen,
+                            Screen::GeneralSettings
+                                | Screen::AudioSettings
+                                | Screen::GuiSettings
+                                | Screen::AssetSettings
+                                | Screen::KioskSettings
+                                | Screen::SaveData
+                        ) {
                             back_to_blades = true;
                         }
+                        if screen == Screen::TrophyRoom {
+                            trophy_room_state.open();
+                        }
                         current_screen = screen;
                     }
+                    ui::blades::BladeAction::Collections(cart_info) => {
+                        collections_state.open(cart_info.as_ref());
+                        current_screen = Screen::Collections;
+                    }
                 }
 
-                ui::blades::draw(
-                    &blades_state,
-                    &font_cache,
-                    &config,
-                    get_time(),
-                );
+                ui::blades::draw(&blades_state, &font_cache, &config, get_time());
             }
-            Screen::FadingOut => {
+            Screen::FadingOut | Screen::ShuttingDown => {
                 // During fade, only render, don't process input
                 // Render the current background and UI elements first
                 ui::main_menu::update(
@@ -1238,18 +1616,43 @@ async fn main() {
 
                     // Draw fade overlay
                     let alpha = fade_progress as f32;
-                    draw_rectangle(0.0, 0.0, screen_width(), screen_height(),
-                        Color { r: 0.0, g: 0.0, b: 0.0, a: alpha });
+                    draw_rectangle(
+                        0.0,
+                        0.0,
+                        screen_width(),
+                        screen_height(),
+                        Color {
+                            r: 0.0,
+                            g: 0.0,
+                            b: 0.0,
+                            a: alpha,
+                        },
+                    );
 
-                    // If fade is complete, wait for linger duration then exit
+                    // If fade is complete, wait for linger duration (or the
+                    // farewell screen) then exit
                     if fade_progress >= 1.0 {
-                        let total_elapsed = elapsed - FADE_DURATION;
-                        if total_elapsed >= FADE_LINGER_DURATION {
-                            process::exit(0);
+                        if current_screen == Screen::ShuttingDown {
+                            let farewell = farewell_screen.get_or_insert_with(|| {
+                                ui::farewell::FarewellScreen::start(
+                                    &config,
+                                    &logo_cache,
+                                    &music_cache,
+                                )
+                            });
+                            farewell.draw(&font_cache, &config, scale_factor);
+                            if farewell.is_done() {
+                                process::exit(0);
+                            }
+                        } else {
+                            let total_elapsed = elapsed - FADE_DURATION;
+                            if total_elapsed >= FADE_LINGER_DURATION {
+                                process::exit(0);
+                            }
                         }
                     }
                 }
-            },
+            }
             Screen::MainMenu => {
                 ui::main_menu::update(
                     &mut current_screen,
@@ -1288,38 +1691,84 @@ async fn main() {
                     &current_time_str,
                     &app_state.gcc_adapter_poll_rate,
                     scale_factor,
-                    flash_message.as_ref().map(|(msg, _)| msg.as_str())
+                    flash_message.as_ref().map(|(msg, _)| msg.as_str()),
                 );
-            },
-            Screen::GeneralSettings | Screen::AudioSettings | Screen::GuiSettings | Screen::AssetSettings => {
+            }
+            Screen::GeneralSettings
+            | Screen::AudioSettings
+            | Screen::GuiSettings
+            | Screen::AssetSettings
+            | Screen::KioskSettings => {
                 // --- Determine what to draw BEFORE updating state ---
                 let (page_number, options) = match current_screen {
                     Screen::GeneralSettings => (1, ui::settings::GENERAL_SETTINGS),
                     Screen::AudioSettings => (2, ui::settings::AUDIO_SETTINGS),
                     Screen::GuiSettings => (3, ui::settings::GUI_CUSTOMIZATION_SETTINGS),
                     Screen::AssetSettings => (4, ui::settings::CUSTOM_ASSET_SETTINGS),
+                    Screen::KioskSettings => (5, ui::settings::KIOSK_SETTINGS),
                     _ => (0, &[] as &[&str]),
                 };
 
                 // --- Handle input and state changes ---
                 ui::settings::update(
-                    &mut current_screen, &input_state, &mut config, &sound_pack_choices, &loaded_themes, &mut settings_menu_selection,
-                    &mut sound_effects, &mut confirm_selection,
-                    &mut brightness, &mut system_volume, &available_sinks, &mut current_bgm,
-                    &bgm_choices, &music_cache, &mut sfx_pack_to_reload, &logo_choices,
-                    &background_choices, &font_choices, &mut animation_state, &mut back_to_blades,
+                    &mut current_screen,
+                    &input_state,
+                    &mut config,
+                    &loaded_themes,
+                    &mut settings_menu_selection,
+                    &mut sound_effects,
+                    &mut confirm_selection,
+                    &mut brightness,
+                    &mut system_volume,
+                    &available_sinks,
+                    &mut current_bgm,
+                    &bgm_choices,
+                    &music_cache,
+                    &mut sfx_pack_to_reload,
+                    &logo_choices,
+                    &background_choices,
+                    &font_choices,
+                    &mut animation_state,
+                    &mut back_to_blades,
                 );
+                if current_screen == Screen::KioskWhitelist {
+                    kiosk_whitelist_state.open(&config);
+                }
+                if current_screen == Screen::RemoteDevices {
+                    remote_devices_state.open();
+                }
+                if current_screen == Screen::FirstRun {
+                    first_run_state.open(Screen::GeneralSettings);
+                }
+                if current_screen == Screen::GamepadTester {
+                    gamepad_tester_state.open(&gilrs);
+                }
+                if current_screen == Screen::DisplaySettings {
+                    display_settings_state.open();
+                }
 
                 // --- Draw the UI ---
                 if page_number > 0 {
                     ui::settings::render_settings_page(
-                        page_number, options, &logo_cache, &background_cache, &mut video_cache, &font_cache,
-                        &mut config, settings_menu_selection, &animation_state, &mut background_state,
-                        &battery_info, &current_time_str, &app_state.gcc_adapter_poll_rate,
-                        scale_factor, system_volume, brightness,
+                        page_number,
+                        options,
+                        &logo_cache,
+                        &background_cache,
+                        &mut video_cache,
+                        &font_cache,
+                        &mut config,
+                        settings_menu_selection,
+                        &animation_state,
+                        &mut background_state,
+                        &battery_info,
+                        &current_time_str,
+                        &app_state.gcc_adapter_poll_rate,
+                        scale_factor,
+                        system_volume,
+                        brightness,
                     );
                 }
-            },
+            }
             Screen::Extras => {
                 ui::extras_menu::update(
                     &mut current_screen,
@@ -1329,6 +1778,9 @@ async fn main() {
                     &sound_effects,
                     &config,
                 );
+                if current_screen == Screen::Logs {
+                    log_viewer_state.open(&log_messages);
+                }
 
                 ui::extras_menu::draw(
                     extras_menu_selection,
@@ -1345,13 +1797,41 @@ async fn main() {
                     scale_factor,
                 );
             }
+            Screen::Power => {
+                ui::power::update(
+                    &mut power_menu_state,
+                    &mut current_screen,
+                    &input_state,
+                    &mut animation_state,
+                    &sound_effects,
+                    &config,
+                    &mut current_bgm,
+                    &music_cache,
+                    &mut fade_start_time,
+                );
+
+                ui::power::draw(
+                    &power_menu_state,
+                    &animation_state,
+                    &logo_cache,
+                    &background_cache,
+                    &mut video_cache,
+                    &font_cache,
+                    &config,
+                    &mut background_state,
+                    &battery_info,
+                    &current_time_str,
+                    &app_state.gcc_adapter_poll_rate,
+                    scale_factor,
+                );
+            }
             Screen::GameSelection => {
                 // --- Load Icons from Queue ---
                 if !game_icon_queue.is_empty() {
                     let (game_id, icon_path) = game_icon_queue.remove(0);
 
                     // Check for our Magic String
-                    if icon_path.to_string_lossy() == "::KZP_PLACEHOLDER::" {
+                    if icon_path.to_string_lossy() == icon_gen::KZP_PLACEHOLDER {
                         // LOAD FROM BAKED BYTES
                         // We use from_file_with_format which reads raw bytes.
                         // None = auto-detect format (png/jpg)
@@ -1399,7 +1879,51 @@ async fn main() {
                     }
                     sound_effects.play_back(&config);
                 }
-                if input_state.select {
+                if input_state.secondary {
+                    if let Some((cart_info, _)) = available_games.get(game_selection) {
+                        game_display_state.open(
+                            &cart_info.id,
+                            cart_info.name.as_deref().unwrap_or(&cart_info.id),
+                        );
+                        current_screen = Screen::GameDisplaySettings;
+                        sound_effects.play_select(&config);
+                    }
+                }
+                if input_state.prev {
+                    if let Some((cart_info, _)) = available_games.get(game_selection) {
+                        multiplayer_history_state.open(
+                            &cart_info.id,
+                            cart_info.name.as_deref().unwrap_or(&cart_info.id),
+                        );
+                        current_screen = Screen::MultiplayerHistory;
+                        sound_effects.play_select(&config);
+                    }
+                }
+                if input_state.next {
+                    if let Some((cart_info, kzi_path)) = available_games.get(game_selection) {
+                        // Packages are read-only images without an editable .kzi
+                        if kzi_path.extension().is_some_and(|ext| ext == "kzi") {
+                            cart_editor_state.open(cart_info, kzi_path);
+                            current_screen = Screen::CartEditor;
+                            sound_effects.play_select(&config);
+                        } else {
+                            sound_effects.play_reject(&config);
+                        }
+                    }
+                }
+                let share_gone = available_games
+                    .get(game_selection)
+                    .is_some_and(|(_, path)| {
+                        netshare::is_on_share(path)
+                            && share_monitor.status() != netshare::ShareStatus::Connected
+                    });
+                if input_state.select && share_gone {
+                    flash_message = Some((
+                        "NETWORK SHARE UNAVAILABLE".to_string(),
+                        FLASH_MESSAGE_DURATION,
+                    ));
+                    sound_effects.play_reject(&config);
+                } else if input_state.select {
                     if let Some((cart_info, kzi_path)) = available_games.get(game_selection) {
                         sound_effects.play_select(&config);
 
@@ -1408,7 +1932,10 @@ async fn main() {
                         if cart_info.runtime.as_deref() == Some("vba-m") {
                             let runtime_name = cart_info.runtime.as_deref().unwrap_or("unknown");
                             println!("[Debug] {} game detected!", runtime_name);
-                            println!("[Debug] multiplayer_support: {:?}", cart_info.multiplayer_support);
+                            println!(
+                                "[Debug] multiplayer_support: {:?}",
+                                cart_info.multiplayer_support
+                            );
                             println!("[Debug] max_players: {:?}", cart_info.max_players);
 
                             // Store the pending game info
@@ -1429,10 +1956,13 @@ async fn main() {
                             println!("[Debug] Calculated max_players: {}", max_players);
 
                             if max_players > 1 {
-                                println!("[Debug] Showing player count dialog");
-                                // Show player count selection first
-                                mgba_launch_step = GameLaunchStep::SelectPlayerCount;
-                                mgba_launch_dialog = Some(dialog::create_player_count_dialog(max_players));
+                                println!("[Debug] Showing player join screen");
+                                // Let each player join on their own controller first
+                                player_join_state.open(
+                                    cart_info.name.as_deref().unwrap_or(&cart_info.id),
+                                    max_players,
+                                    &gilrs,
+                                );
                             } else {
                                 println!("[Debug] Single player - showing save slot dialog");
                                 // Single player only - go straight to save selection
@@ -1441,8 +1971,13 @@ async fn main() {
                                 // Find existing save files
                                 let save_dir = save::get_mgba_save_dir(&cart_info.id);
                                 let rom_name = save::get_rom_name_from_exec(&cart_info.exec);
-                                let existing_saves = dialog::find_existing_save_slots(&save_dir, &rom_name);
-                                let can_import = cart_info.player_saves.get(0).and_then(|s| s.as_ref()).is_some();
+                                let existing_saves =
+                                    dialog::find_existing_save_slots(&save_dir, &rom_name);
+                                let can_import = cart_info
+                                    .player_saves
+                                    .get(0)
+                                    .and_then(|s| s.as_ref())
+                                    .is_some();
 
                                 mgba_launch_dialog = Some(dialog::create_save_slot_dialog(
                                     &existing_saves,
@@ -1452,74 +1987,123 @@ async fn main() {
                                 ));
                             }
 
-                            current_screen = Screen::GameLaunchOptions;
-                } else if DEV_MODE {
-                    // --- DEBUG MODE (non-mGBA) ---
-                    log_messages.lock().unwrap().clear();
-                    { // Scoped lock to add messages
-                        let mut logs = log_messages.lock().unwrap();
+                            current_screen = if max_players > 1 {
+                                Screen::PlayerJoin
+                            } else {
+                                Screen::GameLaunchOptions
+                            };
+                        } else if DEV_MODE {
+                            // --- DEBUG MODE (non-mGBA) ---
+                            log_messages.lock().unwrap().clear();
+                            {
+                                // Scoped lock to add messages
+                                let mut logs = log_messages.lock().unwrap();
                                 logs.push("--- CARTRIDGE FOUND ---".to_string());
-                                logs.push(format!("Name: {}", cart_info.name.as_deref().unwrap_or("N/A")));
+                                logs.push(format!(
+                                    "Name: {}",
+                                    cart_info.name.as_deref().unwrap_or("N/A")
+                                ));
                                 logs.push(format!("ID: {}", cart_info.id));
                                 logs.push(format!("Exec: {}", cart_info.exec));
-                                logs.push(format!("Runtime: {}", cart_info.runtime.as_deref().unwrap_or("None")));
+                                logs.push(format!(
+                                    "Runtime: {}",
+                                    cart_info.runtime.as_deref().unwrap_or("None")
+                                ));
                                 logs.push(format!("KZI Path: {}", kzi_path.display()));
                             }
                             println!("[Debug] Single Cartridge Found! Preparing to launch...");
-                            println!("[Debug]   Name: {}", cart_info.name.as_deref().unwrap_or("N/A"));
+                            println!(
+                                "[Debug]   Name: {}",
+                                cart_info.name.as_deref().unwrap_or("N/A")
+                            );
                             println!("[Debug]   ID: {}", cart_info.id);
                             println!("[Debug]   Exec: {}", cart_info.exec);
-                            println!("[Debug]   Runtime: {}", cart_info.runtime.as_deref().unwrap_or("None"));
+                            println!(
+                                "[Debug]   Runtime: {}",
+                                cart_info.runtime.as_deref().unwrap_or("None")
+                            );
                             println!("[Debug]   KZI Path: {}", kzi_path.display());
 
                             // Start overlay daemon before launching game
                             if let Err(e) = crate::utils::start_overlay_daemon() {
-                                log_messages.lock().unwrap().push(format!("[WARNING] Failed to start overlay daemon: {}", e));
+                                log_messages.lock().unwrap().push(format!(
+                                    "[WARNING] Failed to start overlay daemon: {}",
+                                    e
+                                ));
                             }
 
                             match save::launch_game(&cart_info, &kzi_path) {
                                 Ok(mut child) => {
-                                    log_messages.lock().unwrap().push("\n--- LAUNCHING GAME ---".to_string());
+                                    log_messages
+                                        .lock()
+                                        .unwrap()
+                                        .push("\n--- LAUNCHING GAME ---".to_string());
                                     start_log_reader(&mut child, log_messages.clone());
                                     game_process = Some(child);
                                 }
                                 Err(e) => {
-                                    log_messages.lock().unwrap().push(format!("\n--- LAUNCH FAILED ---\nError: {}", e));
+                                    log_messages
+                                        .lock()
+                                        .unwrap()
+                                        .push(format!("\n--- LAUNCH FAILED ---\nError: {}", e));
                                 }
+                            }
+                            current_screen = Screen::Debug;
+                        } else {
+                            // --- PRODUCTION MODE (non-mGBA) ---
+                            (current_screen, fade_start_time) = trigger_game_launch(
+                                cart_info,
+                                kzi_path,
+                                &mut current_bgm,
+                                &music_cache,
+                                &sound_effects,
+                                &config,
+                            );
                         }
-                        current_screen = Screen::Debug;
-                    } else {
-                        // --- PRODUCTION MODE (non-mGBA) ---
-                        (current_screen, fade_start_time) = trigger_game_launch(
-                            cart_info,
-                            kzi_path,
-                            &mut current_bgm,
-                            &music_cache
-                        );
+                        // Reset the return flag once a launch path has been chosen/completed.
+                        return_to_blades_after_game = false;
                     }
-                    // Reset the return flag once a launch path has been chosen/completed.
-                    return_to_blades_after_game = false;
                 }
-            }
 
                 // --- Render ---
                 render_game_selection_menu(
-                    &available_games, &game_icon_cache, &placeholder, game_selection, &animation_state, &logo_cache,
-                    &background_cache, &mut video_cache, &font_cache, &config, &mut background_state,
-                    &battery_info, &current_time_str, &app_state.gcc_adapter_poll_rate, scale_factor
+                    &available_games,
+                    &game_icon_cache,
+                    &placeholder,
+                    game_selection,
+                    &animation_state,
+                    &logo_cache,
+                    &background_cache,
+                    &mut video_cache,
+                    &font_cache,
+                    &config,
+                    &mut background_state,
+                    &battery_info,
+                    &current_time_str,
+                    &app_state.gcc_adapter_poll_rate,
+                    scale_factor,
                 );
-            },
+            }
             Screen::GameLaunchOptions => {
                 // mGBA game launch options dialog (multiplayer & save file selection)
                 // Use an action enum to queue changes after render
                 enum DialogAction {
                     None,
                     Cancel,
-                    GoBackPlayer { prev_player: u8 },
+                    GoBackPlayer {
+                        prev_player: u8,
+                    },
                     GoBackToPlayerCount,
-                    SelectPlayerCount { count: u8 },
-                    SelectSaveSlot { slot: String, next_player: Option<u8> },
-                    ImportSave { player: u8 },
+                    SelectPlayerCount {
+                        count: u8,
+                    },
+                    SelectSaveSlot {
+                        slot: String,
+                        next_player: Option<u8>,
+                    },
+                    ImportSave {
+                        player: u8,
+                    },
                     Launch,
                 }
 
@@ -1547,7 +2131,9 @@ async fn main() {
                             }
                             GameLaunchStep::SelectSaveSlot { player } => {
                                 if *player > 1 {
-                                    action = DialogAction::GoBackPlayer { prev_player: player - 1 };
+                                    action = DialogAction::GoBackPlayer {
+                                        prev_player: player - 1,
+                                    };
                                 } else if mgba_launch_options.player_count > 1 {
                                     action = DialogAction::GoBackToPlayerCount;
                                 } else {
@@ -1595,9 +2181,21 @@ async fn main() {
 
                 // --- Render ---
                 render_game_selection_menu(
-                    &available_games, &game_icon_cache, &placeholder, game_selection, &animation_state, &logo_cache,
-                    &background_cache, &mut video_cache, &font_cache, &config, &mut background_state,
-                    &battery_info, &current_time_str, &app_state.gcc_adapter_poll_rate, scale_factor
+                    &available_games,
+                    &game_icon_cache,
+                    &placeholder,
+                    game_selection,
+                    &animation_state,
+                    &logo_cache,
+                    &background_cache,
+                    &mut video_cache,
+                    &font_cache,
+                    &config,
+                    &mut background_state,
+                    &battery_info,
+                    &current_time_str,
+                    &app_state.gcc_adapter_poll_rate,
+                    scale_factor,
                 );
 
                 if let Some(ref dialog) = mgba_launch_dialog {
@@ -1611,7 +2209,8 @@ async fn main() {
                 }
 
                 // --- Apply action after render ---
-                let mut launch_now = false;
+                // Set when the save slot screen handed over its slots
+                let mut launch_now = std::mem::take(&mut mgba_launch_ready);
                 match action {
                     DialogAction::None => {}
                     DialogAction::Cancel => {
@@ -1621,12 +2220,16 @@ async fn main() {
                     }
                     DialogAction::GoBackPlayer { prev_player } => {
                         mgba_launch_options.save_slots.pop();
-                        mgba_launch_step = GameLaunchStep::SelectSaveSlot { player: prev_player };
+                        mgba_launch_step = GameLaunchStep::SelectSaveSlot {
+                            player: prev_player,
+                        };
                         if let Some((cart_info, _)) = &mgba_pending_game {
                             let save_dir = save::get_mgba_save_dir(&cart_info.id);
                             let rom_name = save::get_rom_name_from_exec(&cart_info.exec);
-                            let existing_saves = dialog::find_existing_save_slots(&save_dir, &rom_name);
-                            let can_import = cart_info.player_saves
+                            let existing_saves =
+                                dialog::find_existing_save_slots(&save_dir, &rom_name);
+                            let can_import = cart_info
+                                .player_saves
                                 .get((prev_player.saturating_sub(1)) as usize)
                                 .and_then(|s| s.as_ref())
                                 .is_some();
@@ -1638,38 +2241,42 @@ async fn main() {
                             ));
                         }
                     }
+                    DialogAction::GoBackToPlayerCount if !player_join_state.players.is_empty() => {
+                        // Players joined on their controllers; go back to that screen as it was
+                        mgba_launch_dialog = None;
+                        current_screen = Screen::PlayerJoin;
+                    }
                     DialogAction::GoBackToPlayerCount => {
                         mgba_launch_step = GameLaunchStep::SelectPlayerCount;
                         if let Some((cart_info, _)) = &mgba_pending_game {
                             let max_players = cart_info.max_players.unwrap_or(4);
-                            mgba_launch_dialog = Some(dialog::create_player_count_dialog(max_players));
+                            mgba_launch_dialog =
+                                Some(dialog::create_player_count_dialog(max_players));
                         }
                     }
                     DialogAction::SelectPlayerCount { count } => {
+                        // Only multiplayer carts ask for a count; they pick slots in the manager
                         mgba_launch_options.player_count = count;
-                        mgba_launch_step = GameLaunchStep::SelectSaveSlot { player: 1 };
-                        if let Some((cart_info, _)) = &mgba_pending_game {
-                            let save_dir = save::get_mgba_save_dir(&cart_info.id);
-                            let rom_name = save::get_rom_name_from_exec(&cart_info.exec);
-                            let existing_saves = dialog::find_existing_save_slots(&save_dir, &rom_name);
-                            let can_import = cart_info.player_saves.get(0).and_then(|s| s.as_ref()).is_some();
-                            mgba_launch_dialog = Some(dialog::create_save_slot_dialog(
-                                &existing_saves,
-                                1,
-                                cart_info.name.as_deref().unwrap_or(&cart_info.id),
-                                can_import,
-                            ));
+                        if let Some((cart_info, kzi_path)) = &mgba_pending_game {
+                            save_slots_state.open(cart_info, kzi_path, count);
+                            mgba_launch_dialog = None;
+                            current_screen = Screen::SaveSlots;
                         }
                     }
-                    DialogAction::SelectSaveSlot { ref slot, next_player } => {
+                    DialogAction::SelectSaveSlot {
+                        ref slot,
+                        next_player,
+                    } => {
                         mgba_launch_options.save_slots.push(slot.clone());
                         if let Some(next_p) = next_player {
                             mgba_launch_step = GameLaunchStep::SelectSaveSlot { player: next_p };
                             if let Some((cart_info, _)) = &mgba_pending_game {
                                 let save_dir = save::get_mgba_save_dir(&cart_info.id);
                                 let rom_name = save::get_rom_name_from_exec(&cart_info.exec);
-                                let existing_saves = dialog::find_existing_save_slots(&save_dir, &rom_name);
-                                let can_import = cart_info.player_saves
+                                let existing_saves =
+                                    dialog::find_existing_save_slots(&save_dir, &rom_name);
+                                let can_import = cart_info
+                                    .player_saves
                                     .get((next_p.saturating_sub(1)) as usize)
                                     .and_then(|s| s.as_ref())
                                     .is_some();
@@ -1691,11 +2298,15 @@ async fn main() {
                                     mgba_launch_options.save_slots.push(slot_id);
                                     if player < mgba_launch_options.player_count {
                                         let next_p = player + 1;
-                                        mgba_launch_step = GameLaunchStep::SelectSaveSlot { player: next_p };
+                                        mgba_launch_step =
+                                            GameLaunchStep::SelectSaveSlot { player: next_p };
                                         let save_dir = save::get_mgba_save_dir(&cart_info.id);
-                                        let rom_name = save::get_rom_name_from_exec(&cart_info.exec);
-                                        let existing_saves = dialog::find_existing_save_slots(&save_dir, &rom_name);
-                                        let can_import = cart_info.player_saves
+                                        let rom_name =
+                                            save::get_rom_name_from_exec(&cart_info.exec);
+                                        let existing_saves =
+                                            dialog::find_existing_save_slots(&save_dir, &rom_name);
+                                        let can_import = cart_info
+                                            .player_saves
                                             .get((next_p.saturating_sub(1)) as usize)
                                             .and_then(|s| s.as_ref())
                                             .is_some();
@@ -1705,14 +2316,23 @@ async fn main() {
                                             cart_info.name.as_deref().unwrap_or(&cart_info.id),
                                             can_import,
                                         ));
-                                        flash_message = Some((format!("IMPORTED SAVE FOR P{}", player), FLASH_MESSAGE_DURATION));
+                                        flash_message = Some((
+                                            format!("IMPORTED SAVE FOR P{}", player),
+                                            FLASH_MESSAGE_DURATION,
+                                        ));
                                     } else {
-                                        flash_message = Some((format!("IMPORTED SAVE FOR P{}", player), FLASH_MESSAGE_DURATION));
+                                        flash_message = Some((
+                                            format!("IMPORTED SAVE FOR P{}", player),
+                                            FLASH_MESSAGE_DURATION,
+                                        ));
                                         launch_now = true;
                                     }
                                 }
                                 Err(e) => {
-                                    flash_message = Some((format!("IMPORT FAILED: {}", e), FLASH_MESSAGE_DURATION));
+                                    flash_message = Some((
+                                        format!("IMPORT FAILED: {}", e),
+                                        FLASH_MESSAGE_DURATION,
+                                    ));
                                 }
                             }
                         }
@@ -1729,6 +2349,13 @@ async fn main() {
                             save_slots: mgba_launch_options.save_slots.clone(),
                         };
 
+                        // Tell the overlay which controller is which player
+                        if launch_opts.player_count > 1
+                            && player_join_state.players.len() == launch_opts.player_count as usize
+                        {
+                            player_join_state.write_assignment(&cart_info.id);
+                        }
+
                         println!("[Debug] Launching mGBA game with options:");
                         println!("[Debug]   Player count: {}", launch_opts.player_count);
                         println!("[Debug]   Save slots: {:?}", launch_opts.save_slots);
@@ -1738,33 +2365,62 @@ async fn main() {
                             {
                                 let mut logs = log_messages.lock().unwrap();
                                 logs.push("--- mGBA LAUNCH ---".to_string());
-                                logs.push(format!("Name: {}", cart_info.name.as_deref().unwrap_or("N/A")));
+                                logs.push(format!(
+                                    "Name: {}",
+                                    cart_info.name.as_deref().unwrap_or("N/A")
+                                ));
                                 logs.push(format!("Players: {}", launch_opts.player_count));
                                 logs.push(format!("Save slots: {:?}", launch_opts.save_slots));
                             }
 
                             // Start overlay daemon before launching game
                             if let Err(e) = crate::utils::start_overlay_daemon() {
-                                log_messages.lock().unwrap().push(format!("[WARNING] Failed to start overlay daemon: {}", e));
+                                log_messages.lock().unwrap().push(format!(
+                                    "[WARNING] Failed to start overlay daemon: {}",
+                                    e
+                                ));
                             }
 
-                            match save::launch_game_with_options(&cart_info, &kzi_path, Some(&launch_opts)) {
+                            match save::launch_game_with_options(
+                                &cart_info,
+                                &kzi_path,
+                                Some(&launch_opts),
+                            ) {
                                 Ok(mut child) => {
-                                    log_messages.lock().unwrap().push("\n--- LAUNCHING GAME ---".to_string());
+                                    multiplayer_log::begin(
+                                        &cart_info.id,
+                                        cart_info.name.as_deref().unwrap_or(&cart_info.id),
+                                        launch_opts.player_count,
+                                    );
+                                    log_messages
+                                        .lock()
+                                        .unwrap()
+                                        .push("\n--- LAUNCHING GAME ---".to_string());
                                     start_log_reader(&mut child, log_messages.clone());
                                     game_process = Some(child);
                                 }
                                 Err(e) => {
-                                    log_messages.lock().unwrap().push(format!("\n--- LAUNCH FAILED ---\nError: {}", e));
+                                    log_messages
+                                        .lock()
+                                        .unwrap()
+                                        .push(format!("\n--- LAUNCH FAILED ---\nError: {}", e));
                                 }
                             }
                             current_screen = Screen::Debug;
                         } else {
                             // Production mode
                             std::env::set_var("MGBA_PLAYERS", launch_opts.player_count.to_string());
+                            multiplayer_log::begin(
+                                &cart_info.id,
+                                cart_info.name.as_deref().unwrap_or(&cart_info.id),
+                                launch_opts.player_count,
+                            );
                             if launch_opts.player_count > 1 {
                                 std::env::set_var("MGBA_MULTIPLAYER", "true");
-                                std::env::set_var("MGBA_SAVE_SLOTS", launch_opts.save_slots.join(","));
+                                std::env::set_var(
+                                    "MGBA_SAVE_SLOTS",
+                                    launch_opts.save_slots.join(","),
+                                );
                             } else if !launch_opts.save_slots.is_empty() {
                                 std::env::set_var("MGBA_SAVE_SLOT", &launch_opts.save_slots[0]);
                             }
@@ -1773,7 +2429,9 @@ async fn main() {
                                 &cart_info,
                                 &kzi_path,
                                 &mut current_bgm,
-                                &music_cache
+                                &music_cache,
+                                &sound_effects,
+                                &config,
                             );
                         }
                     }
@@ -1784,7 +2442,129 @@ async fn main() {
                 if mgba_launch_dialog.is_none() && current_screen == Screen::GameLaunchOptions {
                     current_screen = Screen::GameSelection;
                 }
-            },
+            }
+            Screen::PlayerJoin => {
+                let action = ui::player_join::update(
+                    &mut player_join_state,
+                    &input_state,
+                    &gilrs,
+                    &sound_effects,
+                    &config,
+                );
+
+                // --- Render ---
+                render_game_selection_menu(
+                    &available_games,
+                    &game_icon_cache,
+                    &placeholder,
+                    game_selection,
+                    &animation_state,
+                    &logo_cache,
+                    &background_cache,
+                    &mut video_cache,
+                    &font_cache,
+                    &config,
+                    &mut background_state,
+                    &battery_info,
+                    &current_time_str,
+                    &app_state.gcc_adapter_poll_rate,
+                    scale_factor,
+                );
+                ui::player_join::draw(
+                    &player_join_state,
+                    &animation_state,
+                    &font_cache,
+                    &config,
+                    scale_factor,
+                );
+
+                match action {
+                    ui::player_join::JoinAction::None => {}
+                    ui::player_join::JoinAction::Cancel => {
+                        current_screen = Screen::GameSelection;
+                        mgba_pending_game = None;
+                    }
+                    ui::player_join::JoinAction::ChooseCount => {
+                        player_join_state.players.clear();
+                        mgba_launch_step = GameLaunchStep::SelectPlayerCount;
+                        if let Some((cart_info, _)) = &mgba_pending_game {
+                            let max_players = cart_info.max_players.unwrap_or(4);
+                            mgba_launch_dialog =
+                                Some(dialog::create_player_count_dialog(max_players));
+                        }
+                        current_screen = Screen::GameLaunchOptions;
+                    }
+                    ui::player_join::JoinAction::Start => {
+                        mgba_launch_options.player_count = player_join_state.players.len() as u8;
+                        mgba_launch_options.save_slots.clear();
+                        if let Some((cart_info, kzi_path)) = &mgba_pending_game {
+                            save_slots_state.open(
+                                cart_info,
+                                kzi_path,
+                                mgba_launch_options.player_count,
+                            );
+                        }
+                        current_screen = Screen::SaveSlots;
+                    }
+                }
+            }
+            Screen::SaveSlots => {
+                let action = ui::save_slots::update(
+                    &mut save_slots_state,
+                    &input_state,
+                    &sound_effects,
+                    &config,
+                );
+
+                // --- Render ---
+                render_game_selection_menu(
+                    &available_games,
+                    &game_icon_cache,
+                    &placeholder,
+                    game_selection,
+                    &animation_state,
+                    &logo_cache,
+                    &background_cache,
+                    &mut video_cache,
+                    &font_cache,
+                    &config,
+                    &mut background_state,
+                    &battery_info,
+                    &current_time_str,
+                    &app_state.gcc_adapter_poll_rate,
+                    scale_factor,
+                );
+                ui::save_slots::draw(
+                    &save_slots_state,
+                    &animation_state,
+                    &font_cache,
+                    &config,
+                    scale_factor,
+                );
+
+                match action {
+                    ui::save_slots::SlotsAction::None => {}
+                    ui::save_slots::SlotsAction::Back if !player_join_state.players.is_empty() => {
+                        current_screen = Screen::PlayerJoin;
+                    }
+                    ui::save_slots::SlotsAction::Back => {
+                        mgba_launch_step = GameLaunchStep::SelectPlayerCount;
+                        if let Some((cart_info, _)) = &mgba_pending_game {
+                            let max_players = cart_info.max_players.unwrap_or(4);
+                            mgba_launch_dialog =
+                                Some(dialog::create_player_count_dialog(max_players));
+                        }
+                        current_screen = Screen::GameLaunchOptions;
+                    }
+                    ui::save_slots::SlotsAction::Launch(slots) => {
+                        // The launch itself lives with the launch options dialog
+                        mgba_launch_options.save_slots = slots;
+                        mgba_launch_dialog = None;
+                        mgba_launch_ready = true;
+                        current_screen = Screen::GameLaunchOptions;
+                    }
+                }
+            }
             Screen::Debug => {
                 // Stop the BGM
                 play_new_bgm("OFF", 0.0, &music_cache, &mut current_bgm);
@@ -1805,11 +2585,15 @@ async fn main() {
                         Ok(filename) => {
                             // Add a confirmation message to the log
                             //messages.push(format!("\nLOG SAVED TO {}", filename));
-                            flash_message = Some((format!("LOG SAVED TO {}", filename), FLASH_MESSAGE_DURATION));
+                            flash_message = Some((
+                                format!("LOG SAVED TO {}", filename),
+                                FLASH_MESSAGE_DURATION,
+                            ));
                         }
                         Err(e) => {
                             //messages.push(format!("\nERROR SAVING LOG: {}", e));
-                            flash_message = Some((format!("ERROR SAVING LOG: {}", e), FLASH_MESSAGE_DURATION));
+                            flash_message =
+                                Some((format!("ERROR SAVING LOG: {}", e), FLASH_MESSAGE_DURATION));
                         }
                     }
                 }
@@ -1817,6 +2601,7 @@ async fn main() {
                     // If the user presses back, kill the game process and return to the menu
                     if let Some(mut child) = game_process.take() {
                         child.kill().ok(); // Ignore error if process already exited
+                        multiplayer_log::finish_pending();
                     }
                     current_screen = Screen::MainMenu;
                     sound_effects.play_back(&config);
@@ -1844,7 +2629,7 @@ async fn main() {
                     &mut video_cache,
                     &mut background_state,
                 );
-            },
+            }
             Screen::ConfirmReset => {
                 // --- Input Handling ---
                 if input_state.left || input_state.right {
@@ -1856,14 +2641,16 @@ async fn main() {
                     sound_effects.play_back(&config);
                 }
                 if input_state.select {
-                    if confirm_selection == 0 { // User selected YES
+                    if confirm_selection == 0 {
+                        // User selected YES
                         //if let Err(e) = delete_config_file() {
                         if let Err(e) = Config::delete() {
                             println!("[ERROR] Failed to delete config file: {}", e);
                         }
                         current_screen = Screen::ResetComplete;
                         sound_effects.play_select(&config);
-                    } else { // User selected NO
+                    } else {
+                        // User selected NO
                         current_screen = Screen::GeneralSettings;
                         sound_effects.play_back(&config);
                     }
@@ -1872,41 +2659,75 @@ async fn main() {
                 // --- Render ---
                 // First, render the settings page in the background
                 render_settings_page(
-                    1, &GENERAL_SETTINGS, &logo_cache, &background_cache, &mut video_cache, &font_cache,
-                    &mut config, settings_menu_selection, &animation_state, &mut background_state,
-                    &battery_info, &current_time_str, &app_state.gcc_adapter_poll_rate,
-                    scale_factor, system_volume, brightness,
+                    1,
+                    &GENERAL_SETTINGS,
+                    &logo_cache,
+                    &background_cache,
+                    &mut video_cache,
+                    &font_cache,
+                    &mut config,
+                    settings_menu_selection,
+                    &animation_state,
+                    &mut background_state,
+                    &battery_info,
+                    &current_time_str,
+                    &app_state.gcc_adapter_poll_rate,
+                    scale_factor,
+                    system_volume,
+                    brightness,
                 );
                 // Then, render the dialog box on top
                 render_dialog_box(
                     "Reset all settings to default?\nThis cannot be undone.",
                     Some(("YES", "NO")), // Options to display
-                    confirm_selection,  // Which option is selected
-                    &font_cache, &config, scale_factor, &animation_state,
+                    confirm_selection,   // Which option is selected
+                    &font_cache,
+                    &config,
+                    scale_factor,
+                    &animation_state,
                 );
-            },
+            }
             Screen::ResetComplete => {
                 // --- Input Handling ---
                 if input_state.select || input_state.back {
                     // Use the restart function you already have
-                    (current_screen, fade_start_time) = trigger_session_restart(&mut current_bgm, &music_cache);
+                    (current_screen, fade_start_time) = trigger_session_restart(
+                        &mut current_bgm,
+                        &music_cache,
+                        config.show_farewell_screen,
+                    );
                 }
 
                 // --- Render ---
                 render_settings_page(
-                    1, &GENERAL_SETTINGS, &logo_cache, &background_cache, &mut video_cache, &font_cache,
-                    &mut config, settings_menu_selection, &animation_state, &mut background_state,
-                    &battery_info, &current_time_str, &app_state.gcc_adapter_poll_rate,
-                    scale_factor, system_volume, brightness
+                    1,
+                    &GENERAL_SETTINGS,
+                    &logo_cache,
+                    &background_cache,
+                    &mut video_cache,
+                    &font_cache,
+                    &mut config,
+                    settings_menu_selection,
+                    &animation_state,
+                    &mut background_state,
+                    &battery_info,
+                    &current_time_str,
+                    &app_state.gcc_adapter_poll_rate,
+                    scale_factor,
+                    system_volume,
+                    brightness,
                 );
 
                 render_dialog_box(
                     "Settings have been reset.\nRestart required.",
                     None, // No YES/NO options needed
                     0,
-                    &font_cache, &config, scale_factor, &animation_state,
+                    &font_cache,
+                    &config,
+                    scale_factor,
+                    &animation_state,
                 );
-            },
+            }
             Screen::SaveData => {
                 // Process one item from the icon queue each frame to prevent stuttering.
                 if !icon_queue.is_empty() {
@@ -1917,32 +2738,68 @@ async fn main() {
                 }
 
                 ui::data::update(
-                    &mut input_state, &mut current_screen, &sound_effects, &config,
-                    &storage_state, &mut memories, &mut icon_cache, &mut icon_queue,
-                    &mut selected_memory, &mut scroll_offset, &mut dialogs, &mut dialog_state, &mut animation_state,
-                    scale_factor, &copy_op_state, &mut back_to_blades
-                ).await;
+                    &mut input_state,
+                    &mut current_screen,
+                    &sound_effects,
+                    &config,
+                    &storage_state,
+                    &mut memories,
+                    &mut icon_cache,
+                    &mut icon_queue,
+                    &mut selected_memory,
+                    &mut scroll_offset,
+                    &mut dialogs,
+                    &mut dialog_state,
+                    &mut animation_state,
+                    scale_factor,
+                    &copy_op_state,
+                    &mut back_to_blades,
+                )
+                .await;
 
-                render_background(&background_cache, &mut video_cache, &config, &mut background_state);
+                render_background(
+                    &background_cache,
+                    &mut video_cache,
+                    &config,
+                    &mut background_state,
+                );
 
                 ui::data::draw(
-                    selected_memory, &memories, &icon_cache, &font_cache,
-                    &config, &storage_state, &placeholder, scroll_offset,
-                    &input_state, &animation_state, &mut playtime_cache, &mut size_cache,
-                    scale_factor, &dialog_state
+                    selected_memory,
+                    &memories,
+                    &icon_cache,
+                    &font_cache,
+                    &config,
+                    &storage_state,
+                    &placeholder,
+                    scroll_offset,
+                    &input_state,
+                    &animation_state,
+                    &mut save_metadata_cache,
+                    scale_factor,
+                    &dialog_state,
                 );
 
                 // Draw dialogs on top if they are open
                 if let Some(dialog) = dialogs.last_mut() {
                     if dialog_state == DialogState::Open {
                         ui::render_dialog(
-                            dialog, &memories, selected_memory, &icon_cache, &font_cache,
-                            &config, &copy_op_state, &placeholder, scroll_offset,
-                            &animation_state, &mut playtime_cache, &mut size_cache, scale_factor
+                            dialog,
+                            &memories,
+                            selected_memory,
+                            &icon_cache,
+                            &font_cache,
+                            &config,
+                            &copy_op_state,
+                            &placeholder,
+                            scroll_offset,
+                            &animation_state,
+                            &mut save_metadata_cache,
+                            scale_factor,
                         );
                     }
                 }
-            },
+            }
             Screen::Wifi => {
                 ui::wifi::update(
                     &mut wifi_state,
@@ -2020,14 +2877,21 @@ async fn main() {
                 loaded_themes = theme::load_all_themes().await;
 
                 // 2. Re-scan all asset directories to find the new files
-                let (background_files, logo_files, font_files, music_files) = find_all_asset_files();
+                let (background_files, logo_files, font_files, music_files) =
+                    find_all_asset_files();
 
                 // --- Define a new message for reloading ---
                 let reloading_text = "APPLYING NEW THEME ASSETS...";
 
                 // 3. Re-load all assets and assign them to the original mutable caches
-                (background_cache, video_cache, logo_cache, music_cache, font_cache, sound_effects) =
-                load_all_assets(
+                (
+                    background_cache,
+                    video_cache,
+                    logo_cache,
+                    music_cache,
+                    font_cache,
+                    sound_effects,
+                ) = load_all_assets(
                     &config,
                     reloading_text,
                     &startup_font,
@@ -2036,7 +2900,8 @@ async fn main() {
                     &font_files,
                     &music_files,
                     scale_factor,
-                ).await;
+                )
+                .await;
 
                 // 4. After reloading, go back to the downloader screen
                 current_screen = Screen::ThemeDownloader;
@@ -2123,8 +2988,350 @@ async fn main() {
                     scale_factor,
                 );
             }
+            Screen::Logs => {
+                ui::logs::update(
+                    &mut current_screen,
+                    &mut log_viewer_state,
+                    &input_state,
+                    &sound_effects,
+                    &config,
+                    &log_messages,
+                    &mut flash_message,
+                );
+                ui::logs::draw(
+                    &log_viewer_state,
+                    &animation_state,
+                    &logo_cache,
+                    &background_cache,
+                    &mut video_cache,
+                    &font_cache,
+                    &config,
+                    &mut background_state,
+                    &battery_info,
+                    &current_time_str,
+                    &app_state.gcc_adapter_poll_rate,
+                    scale_factor,
+                    flash_message.as_ref().map(|(msg, _)| msg.as_str()),
+                );
+            }
+            Screen::KioskWhitelist => {
+                ui::kiosk::update(
+                    &mut current_screen,
+                    &mut kiosk_whitelist_state,
+                    &input_state,
+                    &mut animation_state,
+                    &sound_effects,
+                    &mut config,
+                );
+                ui::kiosk::draw(
+                    &kiosk_whitelist_state,
+                    &animation_state,
+                    &logo_cache,
+                    &background_cache,
+                    &mut video_cache,
+                    &font_cache,
+                    &config,
+                    &mut background_state,
+                    &battery_info,
+                    &current_time_str,
+                    &app_state.gcc_adapter_poll_rate,
+                    scale_factor,
+                );
+            }
+            Screen::RemoteDevices => {
+                ui::remote_devices::update(
+                    &mut current_screen,
+                    &mut remote_devices_state,
+                    &input_state,
+                    &mut animation_state,
+                    &sound_effects,
+                    &mut config,
+                );
+                ui::remote_devices::draw(
+                    &remote_devices_state,
+                    &animation_state,
+                    &logo_cache,
+                    &background_cache,
+                    &mut video_cache,
+                    &font_cache,
+                    &config,
+                    &mut background_state,
+                    &battery_info,
+                    &current_time_str,
+                    &app_state.gcc_adapter_poll_rate,
+                    scale_factor,
+                );
+            }
+            Screen::GameDisplaySettings => {
+                ui::game_display::update(
+                    &mut current_screen,
+                    &mut game_display_state,
+                    &input_state,
+                    &mut animation_state,
+                    &sound_effects,
+                    &config,
+                );
+                ui::game_display::draw(
+                    &game_display_state,
+                    &animation_state,
+                    &logo_cache,
+                    &background_cache,
+                    &mut video_cache,
+                    &font_cache,
+                    &config,
+                    &mut background_state,
+                    &battery_info,
+                    &current_time_str,
+                    &app_state.gcc_adapter_poll_rate,
+                    scale_factor,
+                );
+            }
+            Screen::MultiplayerHistory => {
+                ui::multiplayer_history::update(
+                    &mut current_screen,
+                    &mut multiplayer_history_state,
+                    &input_state,
+                    &mut animation_state,
+                    &sound_effects,
+                    &config,
+                );
+                ui::multiplayer_history::draw(
+                    &multiplayer_history_state,
+                    &logo_cache,
+                    &background_cache,
+                    &mut video_cache,
+                    &font_cache,
+                    &config,
+                    &mut background_state,
+                    &battery_info,
+                    &current_time_str,
+                    &app_state.gcc_adapter_poll_rate,
+                    scale_factor,
+                );
+            }
+            Screen::CartEditor => {
+                ui::cart_editor::update(
+                    &mut current_screen,
+                    &mut cart_editor_state,
+                    &input_state,
+                    &mut animation_state,
+                    &sound_effects,
+                    &config,
+                );
+                // Refresh the games list entry and its icon after a save
+                if let Some(cart_info) = cart_editor_state.take_updated() {
+                    if let Some((entry, kzi_path)) = available_games.get_mut(game_selection) {
+                        game_icon_cache.remove(&entry.id);
+                        game_icon_queue.push((
+                            cart_info.id.clone(),
+                            icon_gen::icon_for(&cart_info, kzi_path),
+                        ));
+                        *entry = cart_info;
+                    }
+                    utils::notify_game_list(&available_games);
+                }
+                ui::cart_editor::draw(
+                    &cart_editor_state,
+                    &animation_state,
+                    &logo_cache,
+                    &background_cache,
+                    &mut video_cache,
+                    &font_cache,
+                    &config,
+                    &mut background_state,
+                    &battery_info,
+                    &current_time_str,
+                    &app_state.gcc_adapter_poll_rate,
+                    scale_factor,
+                );
+            }
+            Screen::Collections => {
+                ui::collections::update(
+                    &mut current_screen,
+                    &mut collections_state,
+                    &input_state,
+                    &mut animation_state,
+                    &sound_effects,
+                    &config,
+                );
+                if collections_state.take_changed() {
+                    blades_state.reload_collections();
+                }
+                ui::collections::draw(
+                    &collections_state,
+                    &animation_state,
+                    &logo_cache,
+                    &background_cache,
+                    &mut video_cache,
+                    &font_cache,
+                    &config,
+                    &mut background_state,
+                    &battery_info,
+                    &current_time_str,
+                    &app_state.gcc_adapter_poll_rate,
+                    scale_factor,
+                );
+            }
+            Screen::FirstRun => {
+                ui::first_run::update(
+                    &mut current_screen,
+                    &mut first_run_state,
+                    &input_state,
+                    &mut animation_state,
+                    &sound_effects,
+                    &mut config,
+                );
+                if current_screen == Screen::GuiSettings {
+                    // THEME is the first option on the page
+                    settings_menu_selection = 0;
+                }
+                ui::first_run::draw(
+                    &first_run_state,
+                    &animation_state,
+                    &logo_cache,
+                    &background_cache,
+                    &mut video_cache,
+                    &font_cache,
+                    &config,
+                    &mut background_state,
+                    &battery_info,
+                    &current_time_str,
+                    &app_state.gcc_adapter_poll_rate,
+                    scale_factor,
+                );
+            }
+            Screen::GamepadTester => {
+                ui::gamepad_tester::update(
+                    &mut current_screen,
+                    &mut gamepad_tester_state,
+                    &input_state,
+                    &gilrs,
+                    &sound_effects,
+                    &config,
+                );
+                ui::gamepad_tester::draw(
+                    &gamepad_tester_state,
+                    &gilrs,
+                    &logo_cache,
+                    &background_cache,
+                    &mut video_cache,
+                    &font_cache,
+                    &config,
+                    &mut background_state,
+                    &battery_info,
+                    &current_time_str,
+                    &app_state.gcc_adapter_poll_rate,
+                    scale_factor,
+                );
+            }
+            Screen::DisplaySettings => {
+                ui::display::update(
+                    &mut current_screen,
+                    &mut display_settings_state,
+                    &input_state,
+                    &mut animation_state,
+                    &sound_effects,
+                    &mut config,
+                );
+                ui::display::draw(
+                    &display_settings_state,
+                    &animation_state,
+                    &logo_cache,
+                    &background_cache,
+                    &mut video_cache,
+                    &font_cache,
+                    &config,
+                    &mut background_state,
+                    &battery_info,
+                    &current_time_str,
+                    &app_state.gcc_adapter_poll_rate,
+                    scale_factor,
+                );
+            }
+            Screen::NetworkShare => {
+                ui::network_share::update(
+                    &mut current_screen,
+                    &mut network_share_state,
+                    &input_state,
+                    &mut animation_state,
+                    &sound_effects,
+                    &mut config,
+                    &mut flash_message,
+                );
+                ui::network_share::draw(
+                    &network_share_state,
+                    &share_monitor.status(),
+                    &animation_state,
+                    &logo_cache,
+                    &background_cache,
+                    &mut video_cache,
+                    &font_cache,
+                    &config,
+                    &mut background_state,
+                    &battery_info,
+                    &current_time_str,
+                    &app_state.gcc_adapter_poll_rate,
+                    scale_factor,
+                );
+            }
+            Screen::TrophyRoom => {
+                ui::trophy_room::update(
+                    &mut current_screen,
+                    &mut trophy_room_state,
+                    &input_state,
+                    &mut animation_state,
+                    &sound_effects,
+                    &config,
+                );
+                ui::trophy_room::draw(
+                    &trophy_room_state,
+                    &animation_state,
+                    &logo_cache,
+                    &background_cache,
+                    &mut video_cache,
+                    &font_cache,
+                    &config,
+                    &mut background_state,
+                    &battery_info,
+                    &current_time_str,
+                    &app_state.gcc_adapter_poll_rate,
+                    scale_factor,
+                );
+            }
+            Screen::SystemUpdate => {
+                ui::system_update::update(
+                    &mut current_screen,
+                    &mut system_update_state,
+                    &input_state,
+                    &mut animation_state,
+                    &sound_effects,
+                    &mut config,
+                );
+                ui::system_update::draw(
+                    &mut system_update_state,
+                    &animation_state,
+                    &logo_cache,
+                    &background_cache,
+                    &mut video_cache,
+                    &font_cache,
+                    &config,
+                    &mut background_state,
+                    &battery_info,
+                    &current_time_str,
+                    &app_state.gcc_adapter_poll_rate,
+                    scale_factor,
+                );
+            }
         }
 
+        // Return to the setup wizard once a step's screen is left
+        first_run_state.resume(&mut current_screen);
+
+        removal::draw_write_warning(&font_cache, &config, scale_factor);
+        kiosk::draw(&attract_mode, &font_cache, &config, scale_factor);
+        idle::draw(&idle_tracker, &font_cache, &config, scale_factor);
+        remote::draw(&remote_listener, &font_cache, &config, scale_factor);
+
         // This block checks if the settings screen requested an SFX reload
         if let Some(pack_name) = sfx_pack_to_reload.take() {
             println!("[Info] Reloading SFX pack: {}", pack_name);
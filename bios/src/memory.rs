@@ -1,19 +1,36 @@
-use crate::{Memory, StorageMedia, save, CopyOperationState, PlaytimeCache, SizeCache};
-use std::sync::{Arc, Mutex, atomic::{AtomicU16, Ordering}};
-use std::{thread, time};
-use std::collections::HashMap;
-use macroquad::prelude::*; // For Texture2D if needed by structs
-
-pub async fn load_memories(media: &StorageMedia, cache: &mut HashMap<String, Texture2D>, queue: &mut Vec<(String, String)>) -> Vec<Memory> {
+use crate::{icon_gen, save, CopyOperationState, Memory, StorageMedia};
+use macroquad::prelude::*;
+use std::collections::{HashMap, VecDeque};
+use std::path::Path;
+use std::sync::{
+    atomic::{AtomicU16, Ordering},
+    Arc, Mutex,
+};
+use std::{thread, time}; // For Texture2D if needed by structs
+
+pub async fn load_memories(
+    media: &StorageMedia,
+    cache: &mut HashMap<String, Texture2D>,
+    queue: &mut Vec<(String, String)>,
+) -> Vec<Memory> {
     let mut memories = Vec::new();
 
     if let Ok(details) = save::get_save_details(&media.id) {
-        for (cart_id, name, icon_path) in details {
+        for save::SaveDetails {
+            cart_id,
+            name,
+            icon,
+        } in details
+        {
             if !cache.contains_key(&cart_id) {
-                queue.push((cart_id.clone(), icon_path.clone()));
+                queue.push((
+                    cart_id.clone(),
+                    icon_gen::icon_for_save(&cart_id, &name, &icon),
+                ));
             }
 
             let m = Memory {
+                encrypted: save::is_save_encrypted(&cart_id, &media.id),
                 id: cart_id,
                 name: Some(name),
                 drive_name: media.id.clone(),
@@ -25,12 +42,49 @@ pub async fn load_memories(media: &StorageMedia, cache: &mut HashMap<String, Tex
     memories
 }
 
-pub async fn check_save_exists(memory: &Memory, target_media: &StorageMedia, icon_cache: &mut HashMap<String, Texture2D>, icon_queue: &mut Vec<(String, String)>) -> bool {
+pub async fn check_save_exists(
+    memory: &Memory,
+    target_media: &StorageMedia,
+    icon_cache: &mut HashMap<String, Texture2D>,
+    icon_queue: &mut Vec<(String, String)>,
+) -> bool {
     let target_memories = load_memories(target_media, icon_cache, icon_queue).await;
     target_memories.iter().any(|m| m.id == memory.id)
 }
 
-pub fn copy_memory(memory: &Memory, from_media: &StorageMedia, to_media: &StorageMedia, state: Arc<Mutex<CopyOperationState>>) {
+pub fn copy_memory(
+    memory: &Memory,
+    from_media: &StorageMedia,
+    to_media: &StorageMedia,
+    resolution: Option<save::ConflictResolution>,
+    state: Arc<Mutex<CopyOperationState>>,
+) {
+    run_save_operation(state, "Failed to copy save", |progress| {
+        save::copy_save_with_resolution(
+            &memory.id,
+            &from_media.id,
+            &to_media.id,
+            progress,
+            resolution,
+        )
+    });
+}
+
+/// Recompress every plain save archive on an external drive, reporting
+/// progress through the same state as copies
+pub fn compress_memories(media: &StorageMedia, state: Arc<Mutex<CopyOperationState>>) {
+    run_save_operation(state, "Failed to compress saves", |progress| {
+        let count = save::compress_archives(&media.id, progress)?;
+        eprintln!("Compressed {} save archives on '{}'", count, media.id);
+        Ok(())
+    });
+}
+
+/// Run a save operation while mirroring its progress into the UI state
+fn run_save_operation<F>(state: Arc<Mutex<CopyOperationState>>, error_prefix: &str, operation: F)
+where
+    F: FnOnce(Arc<AtomicU16>) -> Result<(), save::SaveError>,
+{
     // Initialize the copy operation state
     if let Ok(mut copy_state) = state.lock() {
         copy_state.progress = 0;
@@ -71,8 +125,8 @@ pub fn copy_memory(memory: &Memory, from_media: &StorageMedia, to_media: &Storag
         }
     });
 
-    // Perform the actual copy operation
-    let copy_result = save::copy_save(&memory.id, &from_media.id, &to_media.id, progress);
+    // Perform the actual operation
+    let copy_result = operation(progress);
 
     // Handle the result
     match copy_result {
@@ -93,13 +147,13 @@ pub fn copy_memory(memory: &Memory, from_media: &StorageMedia, to_media: &Storag
 
             // Wait for the monitoring thread to finish
             monitor_handle.join().ok();
-        },
+        }
         Err(e) => {
             // Handle error case (this will also stop the monitoring thread)
             if let Ok(mut copy_state) = state.lock() {
                 copy_state.running = false;
                 copy_state.should_clear_dialogs = true;
-                copy_state.error_message = Some(format!("Failed to copy save: {}", e));
+                copy_state.error_message = Some(format!("{}: {}", error_prefix, e));
             }
 
             // Wait for the monitoring thread to finish
@@ -108,28 +162,64 @@ pub fn copy_memory(memory: &Memory, from_media: &StorageMedia, to_media: &Storag
     }
 }
 
-/// Get playtime for a specific game, using cache when available
-pub fn get_game_playtime(memory: &Memory, playtime_cache: &mut PlaytimeCache) -> f32 {
-    let cache_key = (memory.id.clone(), memory.drive_name.clone());
+/// Saves whose metadata is kept. Only the selected save's is shown, so this
+/// covers scrolling back and forth without holding every thumbnail.
+const SAVE_METADATA_CACHE_SIZE: usize = 16;
 
-    if let Some(&cached_playtime) = playtime_cache.get(&cache_key) {
-        cached_playtime
-    } else {
-        let calculated_playtime = save::calculate_playtime(&memory.id, &memory.drive_name);
-        playtime_cache.insert(cache_key, calculated_playtime);
-        calculated_playtime
-    }
+struct CachedSaveMetadata {
+    cart_id: String,
+    drive_name: String,
+    metadata: save::SaveMetadata,
+    thumbnail: Option<Texture2D>,
 }
 
-/// Get size for a specific game, using cache when available
-pub fn get_game_size(memory: &Memory, size_cache: &mut SizeCache) -> f32 {
-    let cache_key = (memory.id.clone(), memory.drive_name.clone());
+/// Metadata and thumbnails of recently selected saves, read the first time
+/// a save is selected. The oldest entry goes once the cache is full.
+#[derive(Default)]
+pub struct SaveMetadataCache {
+    entries: VecDeque<CachedSaveMetadata>,
+}
 
-    if let Some(&cached_size) = size_cache.get(&cache_key) {
-        cached_size
-    } else {
-        let calculated_size = save::calculate_save_size(&memory.id, &memory.drive_name);
-        size_cache.insert(cache_key, calculated_size);
-        calculated_size
+impl SaveMetadataCache {
+    /// Metadata and thumbnail of a save, reading the save if not cached
+    pub fn get(&mut self, memory: &Memory) -> (&save::SaveMetadata, Option<&Texture2D>) {
+        let cached = self
+            .entries
+            .iter()
+            .position(|e| e.cart_id == memory.id && e.drive_name == memory.drive_name);
+        let index = match cached {
+            Some(index) => index,
+            None => {
+                let metadata = save::get_save_metadata(&memory.id, &memory.drive_name);
+                let thumbnail = metadata.thumbnail.as_deref().and_then(load_thumbnail);
+                if self.entries.len() >= SAVE_METADATA_CACHE_SIZE {
+                    self.entries.pop_front();
+                }
+                self.entries.push_back(CachedSaveMetadata {
+                    cart_id: memory.id.clone(),
+                    drive_name: memory.drive_name.clone(),
+                    metadata,
+                    thumbnail,
+                });
+                self.entries.len() - 1
+            }
+        };
+        let entry = &self.entries[index];
+        (&entry.metadata, entry.thumbnail.as_ref())
+    }
+}
+
+fn load_thumbnail(path: &Path) -> Option<Texture2D> {
+    let bytes = std::fs::read(path).ok()?;
+    match Image::from_file_with_format(&bytes, None) {
+        Ok(image) => {
+            let texture = Texture2D::from_image(&image);
+            texture.set_filter(FilterMode::Linear);
+            Some(texture)
+        }
+        Err(e) => {
+            eprintln!("Failed to load thumbnail {}: {}", path.display(), e);
+            None
+        }
     }
 }
@@ -0,0 +1,134 @@
+use crate::config::get_user_data_dir;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::{fs, io::Write, path::PathBuf};
+
+/// A finished local multiplayer session, one JSON line in the log
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct MultiplayerSession {
+    pub cart_id: String,
+    pub game: String,
+    pub players: u8,
+    pub started_at: DateTime<Utc>,
+    pub duration_secs: i64,
+}
+
+/// Sessions launched but not finished yet. The BIOS quits while a game runs,
+/// so the session is written down at launch and closed when it starts again.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct PendingSession {
+    cart_id: String,
+    game: String,
+    players: u8,
+    started_at: DateTime<Utc>,
+}
+
+fn log_path() -> Option<PathBuf> {
+    get_user_data_dir().map(|dir| dir.join("multiplayer_log.jsonl"))
+}
+
+fn pending_path() -> Option<PathBuf> {
+    get_user_data_dir().map(|dir| dir.join("multiplayer_pending.json"))
+}
+
+/// Remembers a multiplayer launch. Single-player launches aren't logged.
+pub fn begin(cart_id: &str, game: &str, players: u8) {
+    if players < 2 {
+        return;
+    }
+    let Some(path) = pending_path() else { return };
+    let pending = PendingSession {
+        cart_id: cart_id.to_string(),
+        game: game.to_string(),
+        players,
+        started_at: Utc::now(),
+    };
+    let result = serde_json::to_string(&pending)
+        .map_err(|e| e.to_string())
+        .and_then(|json| fs::write(&path, json).map_err(|e| e.to_string()));
+    match result {
+        Ok(()) => println!(
+            "[Multiplayer] Started a {}-player session of {}",
+            players, game
+        ),
+        Err(e) => println!("[Multiplayer] Failed to write {}: {}", path.display(), e),
+    }
+}
+
+/// Closes the session started by `begin`, if any, and adds it to the log.
+/// The end time is the game's own `playtime_end` when the wrapper wrote one
+/// after the launch, otherwise now.
+pub fn finish_pending() {
+    let Some(path) = pending_path() else { return };
+    let Ok(content) = fs::read_to_string(&path) else {
+        return;
+    };
+    let _ = fs::remove_file(&path);
+    let pending: PendingSession = match serde_json::from_str(&content) {
+        Ok(pending) => pending,
+        Err(e) => {
+            println!("[Multiplayer] Ignoring invalid {}: {}", path.display(), e);
+            return;
+        }
+    };
+
+    let ended_at = playtime_end(&pending.cart_id)
+        .filter(|end| *end > pending.started_at)
+        .unwrap_or_else(Utc::now);
+    let session = MultiplayerSession {
+        duration_secs: (ended_at - pending.started_at).num_seconds(),
+        cart_id: pending.cart_id,
+        game: pending.game,
+        players: pending.players,
+        started_at: pending.started_at,
+    };
+    if let Err(e) = append(&session) {
+        println!("[Multiplayer] Failed to log session: {}", e);
+    }
+}
+
+/// End of the cart's last play session, as written by the launch wrapper
+pub fn playtime_end(cart_id: &str) -> Option<DateTime<Utc>> {
+    let path = dirs::home_dir()?
+        .join(".local/share/kazeta/saves/default")
+        .join(cart_id)
+        .join(".kazeta/var/playtime_end");
+    let content = fs::read_to_string(path).ok()?;
+    DateTime::parse_from_rfc3339(content.trim())
+        .ok()
+        .map(|end| end.with_timezone(&Utc))
+}
+
+fn append(session: &MultiplayerSession) -> Result<(), String> {
+    let path = log_path().ok_or("Could not find user's data directory.")?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let line = serde_json::to_string(session).map_err(|e| e.to_string())?;
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    writeln!(file, "{}", line).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    println!(
+        "[Multiplayer] Logged {} players, {}s of {}",
+        session.players, session.duration_secs, session.game
+    );
+    Ok(())
+}
+
+/// Past multiplayer sessions of one cart, newest first
+pub fn sessions_for(cart_id: &str) -> Vec<MultiplayerSession> {
+    let Some(content) = log_path().and_then(|path| fs::read_to_string(path).ok()) else {
+        return Vec::new();
+    };
+    let mut sessions: Vec<MultiplayerSession> = content
+        .lines()
+        .filter_map(|line| serde_json::from_str::<MultiplayerSession>(line).ok())
+        .filter(|session| session.cart_id == cart_id)
+        .collect();
+    sessions.reverse();
+    sessions
+}
@@ -0,0 +1,527 @@
+//! Carts on an SMB or NFS network share
+//!
+//! The share is mounted by the privileged kazeta-netshare helper at
+//! `MOUNT_POINT`, outside the media roots so it's never mistaken for a save
+//! drive. A monitor thread mounts it once the network is up, and notices when
+//! the server stops answering or comes back, asking the library to rescan
+//! either way so the share's carts leave or rejoin the list.
+//!
+//! The SMB password is kept in its own 0600 file in the user data directory;
+//! config.toml only names that file.
+//!
+//! Anything that touches the share runs on a separate thread with a timeout:
+//! the helper mounts soft, but a server that vanished still takes seconds to
+//! fail a read, and the BIOS shouldn't freeze for it.
+
+use crate::{
+    config::{get_user_data_dir, NetworkShareConfig},
+    library, save, DEV_MODE,
+};
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, Sender},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+const NETSHARE_HELPER: &str = "/usr/bin/kazeta-netshare";
+
+/// Where the helper mounts the share
+pub const MOUNT_POINT: &str = "/run/kazeta/netshare";
+
+pub const PROTOCOLS: &[&str] = &["SMB", "NFS"];
+
+/// How often the monitor checks the share
+const CHECK_INTERVAL: Duration = Duration::from_secs(5);
+/// Between attempts to mount a share that failed to mount
+const MOUNT_RETRY: Duration = Duration::from_secs(30);
+/// A share that doesn't list its root within this is treated as gone
+const PROBE_TIMEOUT: Duration = Duration::from_secs(3);
+/// Longest a library scan waits on the share
+const SCAN_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Set while a probe is stuck on the share, so probes don't pile up
+static PROBING: AtomicBool = AtomicBool::new(false);
+
+/// What the monitor last saw
+#[derive(Clone, Debug, PartialEq)]
+pub enum ShareStatus {
+    /// No share configured, or it's switched off
+    Off,
+    /// Configured but not mounted yet; holds the last mount error
+    NotMounted(Option<String>),
+    Connected,
+    /// Mounted, but the server stopped answering
+    Unreachable,
+}
+
+impl ShareStatus {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ShareStatus::Off => "OFF",
+            ShareStatus::NotMounted(_) => "NOT CONNECTED",
+            ShareStatus::Connected => "CONNECTED",
+            ShareStatus::Unreachable => "UNREACHABLE",
+        }
+    }
+}
+
+/// A change the main loop tells the user about
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ShareEvent {
+    Connected,
+    Lost,
+}
+
+/// `//host/share` or `host:/export`, for display
+pub fn describe(share: &NetworkShareConfig) -> String {
+    if share.protocol == "NFS" {
+        format!("{}:/{}", share.host, share.share.trim_start_matches('/'))
+    } else {
+        format!("//{}/{}", share.host, share.share.trim_start_matches('/'))
+    }
+}
+
+/// Check the host and share the way the helper will, so a bad value gets a
+/// clear message before anything runs as root
+pub fn validate(share: &NetworkShareConfig) -> Result<(), String> {
+    let host = share.host.trim();
+    let name = share.share.trim();
+    if host.is_empty() || name.is_empty() {
+        return Err("Enter a host and share first".to_string());
+    }
+    if host.starts_with('-')
+        || !host
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "._:-".contains(c))
+    {
+        return Err(format!("Invalid host '{}'", host));
+    }
+    if name.contains("..")
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "._/ $-".contains(c))
+    {
+        return Err(format!("Invalid share '{}'", name));
+    }
+    Ok(())
+}
+
+/// Store the SMB password in a new 0600 file and point the config at it,
+/// removing the old file. The new name also counts as a settings change, so
+/// the monitor remounts with the new password.
+pub fn set_password(share: &mut NetworkShareConfig, password: &str) -> Result<(), String> {
+    let dir = get_user_data_dir().ok_or("Could not find user's data directory.")?;
+    set_password_in(&dir, share, password)
+}
+
+fn set_password_in(
+    dir: &Path,
+    share: &mut NetworkShareConfig,
+    password: &str,
+) -> Result<(), String> {
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+    let name = format!("netshare-{}.cred", millis);
+    fs::create_dir_all(dir)
+        .and_then(|()| write_credentials(&dir.join(&name), password))
+        .map_err(|e| format!("Failed to save password: {}", e))?;
+    if let Some(old) = share
+        .credentials
+        .replace(name)
+        .and_then(|old| credentials_path(dir, &old))
+    {
+        fs::remove_file(old).ok();
+    }
+    Ok(())
+}
+
+/// Same layout as the credentials file the helper hands to mount.cifs
+fn write_credentials(path: &Path, password: &str) -> io::Result<()> {
+    use std::os::unix::fs::OpenOptionsExt;
+    let mut file = fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(path)?;
+    writeln!(file, "password={}", password)?;
+    file.sync_all()
+}
+
+/// Path of a credentials file named in the config; only plain file names in
+/// `dir` are accepted
+fn credentials_path(dir: &Path, name: &str) -> Option<PathBuf> {
+    let path = dir.join(name);
+    (path.file_name().is_some_and(|n| n == name)).then_some(path)
+}
+
+/// The stored SMB password, if one was set
+fn read_password(dir: &Path, share: &NetworkShareConfig) -> Option<String> {
+    let path = credentials_path(dir, share.credentials.as_deref()?)?;
+    let contents = fs::read_to_string(path).ok()?;
+    contents
+        .lines()
+        .find_map(|line| line.strip_prefix("password="))
+        .map(str::to_string)
+}
+
+/// Whether something is mounted at `MOUNT_POINT`. Reads the mount table, so
+/// it never touches the share itself.
+pub fn is_mounted() -> bool {
+    fs::read_to_string("/proc/self/mounts")
+        .map(|mounts| {
+            mounts
+                .lines()
+                .any(|line| line.split_whitespace().nth(1) == Some(MOUNT_POINT))
+        })
+        .unwrap_or(false)
+}
+
+/// Mount the share through the helper, replacing any mounted before
+pub fn mount(share: &NetworkShareConfig) -> Result<(), String> {
+    validate(share)?;
+    if DEV_MODE {
+        println!("[DEV_MODE] Skipping mount of {}", describe(share));
+        return Ok(());
+    }
+
+    println!("[NetShare] Mounting {}", describe(share));
+    let mut child = Command::new("sudo")
+        .arg(NETSHARE_HELPER)
+        .arg("mount")
+        .arg(share.protocol.to_lowercase())
+        .arg(share.host.trim())
+        .arg(share.share.trim())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to run network share helper: {}", e))?;
+
+    // Credentials go over stdin so they don't show up in the process list
+    if let Some(mut stdin) = child.stdin.take() {
+        if share.protocol == "SMB" {
+            let username = share.username.as_deref().unwrap_or("");
+            let password = get_user_data_dir()
+                .and_then(|dir| read_password(&dir, share))
+                .unwrap_or_default();
+            let _ = writeln!(stdin, "{}\n{}", username, password);
+        }
+    }
+
+    let output = child
+        .wait_with_output()
+        .map_err(|e| format!("Network share helper failed: {}", e))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Err(stderr
+            .lines()
+            .last()
+            .unwrap_or("Mount failed")
+            .trim()
+            .to_string())
+    }
+}
+
+/// Unmount the share, even if its server is gone
+fn unmount() -> Result<(), String> {
+    if DEV_MODE {
+        println!("[DEV_MODE] Skipping network share unmount");
+        return Ok(());
+    }
+
+    let output = Command::new("sudo")
+        .arg(NETSHARE_HELPER)
+        .arg("unmount")
+        .output()
+        .map_err(|e| format!("Failed to run network share helper: {}", e))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
+
+/// Run `f` on the share on its own thread. None if the share isn't mounted
+/// or `f` doesn't finish within `timeout`; a stuck thread is left behind to
+/// fail on its own.
+fn on_share<T: Send + 'static>(
+    timeout: Duration,
+    f: impl FnOnce(&Path) -> T + Send + 'static,
+) -> Option<T> {
+    if !is_mounted() {
+        return None;
+    }
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let _ = tx.send(f(Path::new(MOUNT_POINT)));
+    });
+    rx.recv_timeout(timeout).ok()
+}
+
+/// Whether the mounted share answers
+pub fn is_reachable() -> bool {
+    // An earlier probe that's still waiting means the server isn't answering
+    if PROBING.swap(true, Ordering::SeqCst) {
+        return false;
+    }
+    let reachable = on_share(PROBE_TIMEOUT, |root| {
+        let ok = fs::read_dir(root).is_ok();
+        PROBING.store(false, Ordering::SeqCst);
+        ok
+    });
+    match reachable {
+        Some(ok) => ok,
+        None => {
+            // Not mounted clears the flag here; a timed-out probe clears it when it returns
+            if !is_mounted() {
+                PROBING.store(false, Ordering::SeqCst);
+            }
+            false
+        }
+    }
+}
+
+/// Cart files on the share, searched like the media mounts. Empty if the
+/// share isn't mounted or stops answering.
+pub fn find_game_files(extensions: &'static [&'static str], max_depth: usize) -> Vec<PathBuf> {
+    if !is_mounted() || !is_reachable() {
+        return Vec::new();
+    }
+    let found = on_share(SCAN_TIMEOUT, move |root| {
+        save::find_files_by_extension(root, extensions, max_depth, false)
+    });
+    match found {
+        Some(Ok(files)) => files,
+        Some(Err(e)) => {
+            println!("[NetShare] Failed to scan share: {}", e);
+            Vec::new()
+        }
+        None => {
+            println!(
+                "[NetShare] Share didn't answer within {}s, skipping it",
+                SCAN_TIMEOUT.as_secs()
+            );
+            Vec::new()
+        }
+    }
+}
+
+/// Whether a cart file lives on the share
+pub fn is_on_share(path: &Path) -> bool {
+    path.starts_with(MOUNT_POINT)
+}
+
+/// Keeps the configured share mounted and watches it
+pub struct ShareMonitor {
+    share: Arc<Mutex<NetworkShareConfig>>,
+    status: Arc<Mutex<ShareStatus>>,
+    receiver: Receiver<ShareEvent>,
+}
+
+impl ShareMonitor {
+    pub fn start(share: &NetworkShareConfig) -> Self {
+        let (tx, rx) = mpsc::channel();
+        let monitor = ShareMonitor {
+            share: Arc::new(Mutex::new(share.clone())),
+            status: Arc::new(Mutex::new(ShareStatus::Off)),
+            receiver: rx,
+        };
+
+        let share = monitor.share.clone();
+        let status = monitor.status.clone();
+        thread::spawn(move || watch(share, status, tx));
+        monitor
+    }
+
+    /// Pick up changes from the settings screen
+    pub fn sync(&self, share: &NetworkShareConfig) {
+        let mut current = self.share.lock().unwrap();
+        if *current != *share {
+            *current = share.clone();
+        }
+    }
+
+    pub fn status(&self) -> ShareStatus {
+        self.status.lock().unwrap().clone()
+    }
+
+    /// Changes seen since the last frame
+    pub fn poll(&self) -> Vec<ShareEvent> {
+        self.receiver.try_iter().collect()
+    }
+}
+
+fn watch(
+    share: Arc<Mutex<NetworkShareConfig>>,
+    status: Arc<Mutex<ShareStatus>>,
+    tx: Sender<ShareEvent>,
+) {
+    // What's mounted now; a mount left by an earlier run is taken as the
+    // configured share
+    let mut mounted: Option<NetworkShareConfig> = None;
+    let mut last_attempt: Option<(NetworkShareConfig, Instant)> = None;
+
+    loop {
+        let config = share.lock().unwrap().clone();
+        let previous = status.lock().unwrap().clone();
+
+        let next = if !config.enabled || config.host.is_empty() {
+            if is_mounted() {
+                if let Err(e) = unmount() {
+                    println!("[NetShare] Unmount failed: {}", e);
+                }
+            }
+            mounted = None;
+            last_attempt = None;
+            ShareStatus::Off
+        } else if is_mounted() && mounted.as_ref().is_none_or(|m| *m == config) {
+            mounted = Some(config.clone());
+            if is_reachable() {
+                ShareStatus::Connected
+            } else {
+                ShareStatus::Unreachable
+            }
+        } else {
+            // Mount at startup, and again every so often until the network is
+            // up. Changed settings are tried straight away.
+            let due = match &last_attempt {
+                Some((tried, at)) => *tried != config || at.elapsed() >= MOUNT_RETRY,
+                None => true,
+            };
+            if due {
+                last_attempt = Some((config.clone(), Instant::now()));
+                match mount(&config) {
+                    Ok(()) if is_mounted() => {
+                        mounted = Some(config.clone());
+                        ShareStatus::Connected
+                    }
+                    Ok(()) => ShareStatus::NotMounted(None),
+                    Err(e) => {
+                        println!("[NetShare] Mount of {} failed: {}", describe(&config), e);
+                        mounted = None;
+                        ShareStatus::NotMounted(Some(e))
+                    }
+                }
+            } else {
+                previous.clone()
+            }
+        };
+
+        if next != previous {
+            println!("[NetShare] {} -> {}", previous.label(), next.label());
+            let event = match (&previous, &next) {
+                (ShareStatus::Connected, _) => Some(ShareEvent::Lost),
+                (_, ShareStatus::Connected) => Some(ShareEvent::Connected),
+                _ => None,
+            };
+            if let Some(event) = event {
+                // Switched off on purpose isn't worth a message
+                if next != ShareStatus::Off {
+                    let _ = tx.send(event);
+                }
+                library::request_rescan();
+            }
+            *status.lock().unwrap() = next;
+        }
+
+        thread::sleep(CHECK_INTERVAL);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn smb(host: &str, share: &str) -> NetworkShareConfig {
+        NetworkShareConfig {
+            enabled: true,
+            host: host.to_string(),
+            share: share.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_validate() {
+        assert_eq!(validate(&smb("nas.local", "games")), Ok(()));
+        assert_eq!(
+            validate(&smb(" 192.168.1.20 ", "/export/My Games$")),
+            Ok(())
+        );
+        assert_eq!(validate(&smb("fe80::1", "carts")), Ok(()));
+
+        assert_eq!(
+            validate(&smb("", "games")),
+            Err("Enter a host and share first".to_string())
+        );
+        assert_eq!(
+            validate(&smb("nas", " ")),
+            Err("Enter a host and share first".to_string())
+        );
+        // Things the helper would turn into mount options or paths
+        assert_eq!(
+            validate(&smb("-o", "games")),
+            Err("Invalid host '-o'".to_string())
+        );
+        assert!(validate(&smb("nas;reboot", "games")).is_err());
+        assert!(validate(&smb("nas", "games,uid=0")).is_err());
+        assert!(validate(&smb("nas", "games/../etc")).is_err());
+    }
+
+    #[test]
+    fn test_describe() {
+        assert_eq!(describe(&smb("nas", "/games")), "//nas/games");
+        let nfs = NetworkShareConfig {
+            protocol: "NFS".to_string(),
+            ..smb("nas", "/export/games")
+        };
+        assert_eq!(describe(&nfs), "nas:/export/games");
+    }
+
+    #[test]
+    fn test_password_is_kept_out_of_config() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir = tempfile::tempdir().unwrap();
+        let mut share = smb("nas", "games");
+        assert_eq!(read_password(dir.path(), &share), None);
+
+        set_password_in(dir.path(), &mut share, "hunter2=x").unwrap();
+        let first = share.credentials.clone().unwrap();
+        let path = dir.path().join(&first);
+        assert_eq!(
+            fs::metadata(&path).unwrap().permissions().mode() & 0o777,
+            0o600
+        );
+        assert_eq!(
+            read_password(dir.path(), &share).as_deref(),
+            Some("hunter2=x")
+        );
+        assert!(!toml::to_string(&share).unwrap().contains("hunter2"));
+
+        // A new password replaces the file under a new name
+        std::thread::sleep(Duration::from_millis(2));
+        set_password_in(dir.path(), &mut share, "letmein").unwrap();
+        assert_ne!(share.credentials.as_deref(), Some(first.as_str()));
+        assert!(!path.exists());
+        assert_eq!(
+            read_password(dir.path(), &share).as_deref(),
+            Some("letmein")
+        );
+
+        // Only files in the data directory are read
+        share.credentials = Some("../config.toml".to_string());
+        assert_eq!(read_password(dir.path(), &share), None);
+    }
+}
@@ -0,0 +1,103 @@
+//! Shutdown and restart
+//!
+//! Before powering off or rebooting, save writes still in progress get time
+//! to finish, everything is flushed to disk, and the overlay and input daemon
+//! are told to exit so they save their own state and release any grabbed
+//! devices. Power-off and reboot go through the privileged kazeta-power
+//! helper; restarting the BIOS only needs the session restart sentinel.
+
+use crate::{save, utils, DEV_MODE};
+use std::{
+    process::Command,
+    thread,
+    time::{Duration, Instant},
+};
+
+const POWER_HELPER: &str = "/usr/bin/kazeta-power";
+
+/// Longest to wait for save writes in progress before flushing anyway
+const SAVE_WAIT: Duration = Duration::from_secs(10);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PowerAction {
+    Shutdown,
+    Restart,
+    /// Restart the session, relaunching the BIOS without rebooting
+    RestartBios,
+}
+
+impl PowerAction {
+    pub const ALL: [PowerAction; 3] = [
+        PowerAction::Shutdown,
+        PowerAction::Restart,
+        PowerAction::RestartBios,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            PowerAction::Shutdown => "SHUT DOWN",
+            PowerAction::Restart => "RESTART",
+            PowerAction::RestartBios => "RESTART TO BIOS",
+        }
+    }
+
+    pub fn confirm_text(self) -> &'static str {
+        match self {
+            PowerAction::Shutdown => "SHUT DOWN THE SYSTEM?",
+            PowerAction::Restart => "RESTART THE SYSTEM?",
+            PowerAction::RestartBios => "RESTART THE BIOS?",
+        }
+    }
+
+    /// Argument for the power helper, if the action needs it
+    fn helper_arg(self) -> Option<&'static str> {
+        match self {
+            PowerAction::Shutdown => Some("poweroff"),
+            PowerAction::Restart => Some("reboot"),
+            PowerAction::RestartBios => None,
+        }
+    }
+}
+
+/// Let pending saves finish and flush them, then tell the daemons to exit if
+/// the system is going down
+pub fn prepare(action: PowerAction) {
+    let start = Instant::now();
+    while save::save_write_in_progress() && start.elapsed() < SAVE_WAIT {
+        thread::sleep(Duration::from_millis(100));
+    }
+    if save::save_write_in_progress() {
+        println!(
+            "[Power] Save write still running after {}s, flushing anyway",
+            SAVE_WAIT.as_secs()
+        );
+    }
+    save::sync_to_disk();
+
+    if action.helper_arg().is_some() {
+        utils::notify_shutdown();
+    }
+}
+
+/// Power off or reboot through the helper. Does nothing for `RestartBios`.
+pub fn run_helper(action: PowerAction) {
+    let Some(arg) = action.helper_arg() else {
+        return;
+    };
+    if DEV_MODE {
+        println!("[DEV_MODE] Skipping {}.", arg);
+        return;
+    }
+
+    println!("[Power] Running power helper: {}", arg);
+    thread::spawn(
+        move || match Command::new("sudo").arg(POWER_HELPER).arg(arg).output() {
+            Ok(out) if out.status.success() => {}
+            Ok(out) => {
+                let stderr = String::from_utf8_lossy(&out.stderr);
+                println!("[ERROR] Power helper failed: {}", stderr.trim());
+            }
+            Err(e) => println!("[ERROR] Failed to run power helper: {}", e),
+        },
+    );
+}
@@ -0,0 +1,1147 @@
+//! Remote control from a companion app on the LAN
+//!
+//! Off unless `remote_enabled` is set. The BIOS listens on `remote_port` for
+//! newline-delimited JSON, one request per line and one reply per request:
+//!
+//! ```text
+//! {"cmd": "pair", "code": "123456", "device": "Phone"}  -> {"ok": true, "token": "...", "mac": [...]}
+//! {"cmd": "pair", "pair_token": "...", "device": "Phone"}
+//! {"cmd": "list", "token": "..."}                       -> {"ok": true, "games": [{"id": ..., "name": ...}]}
+//! {"cmd": "launch", "token": "...", "cart_id": "..."}   -> {"ok": true}
+//! {"cmd": "wake", "token": "..."}                       -> {"ok": true}
+//! {"cmd": "shutdown", "token": "..."}                   -> {"ok": true}
+//! ```
+//!
+//! Errors come back as `{"ok": false, "error": "..."}`. Pairing is started
+//! from the kiosk settings, which show a QR code and a six digit code for two
+//! minutes. The QR code holds `kazeta://pair?host=<ip>&port=<port>&token=<t>`;
+//! either the code or that one-time token pairs once, after the pairing is
+//! allowed on screen. Paired devices are kept in `config.toml`, by a hash of
+//! their token, and can be revoked one at a time from Kiosk Settings ->
+//! Paired Devices. `wake` only wakes a
+//! dimmed screen: to wake a suspended system the app sends a Wake-on-LAN
+//! packet to one of the MAC addresses returned when pairing.
+//!
+//! Launches and shutdowns are asked for on screen first unless confirmation
+//! is turned off, and launches only happen from the menus, the same places
+//! attract mode starts from.
+//!
+//! At most `MAX_CONNECTIONS` apps are connected at once; others get an error
+//! and are closed, as are connections idle for `IDLE_TIMEOUT` and ones that
+//! send a line longer than `MAX_LINE_BYTES`.
+
+use crate::{
+    config::{get_config_path, Config, RemoteDevice},
+    get_current_font, kiosk, measure_text,
+    power::{self, PowerAction},
+    save::CartInfo,
+    text_with_config_color,
+    types::Screen,
+    InputState, FONT_SIZE,
+};
+use ::rand::Rng;
+use macroquad::prelude::*;
+use qrcode::{Color as QrColor, QrCode};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::HashMap,
+    fs,
+    io::{BufRead, BufReader, Read, Write},
+    net::{IpAddr, TcpListener, TcpStream, UdpSocket},
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        mpsc::{self, Receiver, Sender},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant, SystemTime},
+};
+
+pub const DEFAULT_PORT: u16 = 47630;
+
+/// How long a pairing code stays valid
+const PAIRING_CODE_SECS: u64 = 120;
+/// Wrong codes allowed before the current one is thrown away
+const PAIRING_ATTEMPTS: u32 = 5;
+/// Unanswered confirmation prompts are declined after this long
+const CONFIRM_TIMEOUT_SECS: f64 = 30.0;
+/// How long a connection thread waits for the main loop to act on a request
+const REPLY_TIMEOUT: Duration = Duration::from_secs(40);
+/// How often the accept loop checks whether it should stop
+const ACCEPT_POLL: Duration = Duration::from_millis(200);
+/// Connections served at once
+const MAX_CONNECTIONS: usize = 4;
+/// Connections that send nothing for this long are closed
+const IDLE_TIMEOUT: Duration = Duration::from_secs(300);
+/// A connection that sends more than this without a newline is dropped
+const MAX_LINE_BYTES: usize = 64 * 1024;
+
+/// The pairing screen, if pairing is in progress
+static PAIRING: Mutex<Option<PairingCode>> = Mutex::new(None);
+
+/// Paired devices as of the last config.toml read
+static DEVICES: Mutex<DeviceCache> = Mutex::new(DeviceCache {
+    modified: None,
+    devices: Vec::new(),
+});
+
+struct DeviceCache {
+    /// Modification time of config.toml when the devices were read
+    modified: Option<SystemTime>,
+    devices: Vec<RemoteDevice>,
+}
+
+impl DeviceCache {
+    /// Re-read the devices if config.toml changed, so a revoke applies to
+    /// connections that are already open
+    fn refresh(&mut self) {
+        let modified = get_config_path()
+            .ok()
+            .and_then(|path| fs::metadata(path).ok()?.modified().ok());
+        if modified.is_some() && modified != self.modified {
+            self.devices = Config::load().remote_devices;
+            self.modified = modified;
+        }
+    }
+}
+
+/// Stored in place of a device token
+fn hash_token(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
+
+#[derive(Clone)]
+struct PairingCode {
+    code: String,
+    /// One-time token in the QR code, so the app doesn't need the digits
+    token: String,
+    /// "ip:port", None without a network connection
+    endpoint: Option<String>,
+    /// QR modules, row by row, true for dark
+    qr: Option<(usize, Vec<bool>)>,
+    expires: Instant,
+    failed_attempts: u32,
+}
+
+/// Unpair one device; its token stops working immediately
+pub fn revoke_device(config: &mut Config, index: usize) {
+    if index >= config.remote_devices.len() {
+        return;
+    }
+    let device = config.remote_devices.remove(index);
+    config.save();
+    println!("[Remote] Revoked device '{}'", device.name);
+}
+
+fn random_token(words: usize) -> String {
+    (0..words)
+        .map(|_| format!("{:08x}", ::rand::rng().random::<u32>()))
+        .collect()
+}
+
+/// The address other devices on the LAN reach this console at
+fn local_ip() -> Option<IpAddr> {
+    let socket = UdpSocket::bind("0.0.0.0:0").ok()?;
+    // Connecting a UDP socket sends nothing, it only picks the outgoing interface
+    socket.connect("8.8.8.8:80").ok()?;
+    socket
+        .local_addr()
+        .ok()
+        .map(|addr| addr.ip())
+        .filter(|ip| !ip.is_loopback() && !ip.is_unspecified())
+}
+
+/// QR code modules for the pairing payload
+fn qr_modules(payload: &str) -> Option<(usize, Vec<bool>)> {
+    match QrCode::new(payload.as_bytes()) {
+        Ok(code) => Some((
+            code.width(),
+            code.to_colors()
+                .into_iter()
+                .map(|c| c == QrColor::Dark)
+                .collect(),
+        )),
+        Err(e) => {
+            println!("[ERROR] Failed to build pairing QR code: {}", e);
+            None
+        }
+    }
+}
+
+/// Show the pairing screen with a fresh code and QR code
+pub fn start_pairing(config: &Config) {
+    let code = format!("{:06}", ::rand::rng().random_range(0..1_000_000));
+    let token = random_token(2);
+    let endpoint = local_ip().map(|ip| format!("{}:{}", ip, config.remote_port));
+    let qr = endpoint.as_ref().and_then(|endpoint| {
+        let (host, port) = endpoint.rsplit_once(':').unwrap_or((endpoint, ""));
+        qr_modules(&format!(
+            "kazeta://pair?host={}&port={}&token={}",
+            host, port, token
+        ))
+    });
+    println!(
+        "[Remote] Pairing code shown, valid for {}s",
+        PAIRING_CODE_SECS
+    );
+    *PAIRING.lock().unwrap() = Some(PairingCode {
+        code,
+        token,
+        endpoint,
+        qr,
+        expires: Instant::now() + Duration::from_secs(PAIRING_CODE_SECS),
+        failed_attempts: 0,
+    });
+}
+
+pub fn cancel_pairing() {
+    *PAIRING.lock().unwrap() = None;
+}
+
+/// The pairing screen and seconds left, if one is showing
+fn active_pairing() -> Option<(PairingCode, u64)> {
+    let mut pairing = PAIRING.lock().unwrap();
+    match pairing.as_ref() {
+        Some(p) if p.expires > Instant::now() => {
+            Some((p.clone(), (p.expires - Instant::now()).as_secs()))
+        }
+        Some(_) => {
+            *pairing = None;
+            None
+        }
+        None => None,
+    }
+}
+
+/// Check a code or QR token from a companion app. Either one is used up by a
+/// match, so the pairing screen closes and the prompt can ask about the device.
+fn check_pairing(code: &str, pair_token: &str) -> Result<(), String> {
+    let mut pairing = PAIRING.lock().unwrap();
+    let current = pairing
+        .as_mut()
+        .filter(|p| p.expires > Instant::now())
+        .ok_or("Not pairing")?;
+    let matches = (!code.is_empty() && current.code == code)
+        || (!pair_token.is_empty() && current.token == pair_token);
+    if !matches {
+        current.failed_attempts += 1;
+        if current.failed_attempts >= PAIRING_ATTEMPTS {
+            println!("[Remote] Too many wrong pairing codes, cancelling pairing");
+            *pairing = None;
+        }
+        return Err("Wrong pairing code".to_string());
+    }
+    *pairing = None;
+    Ok(())
+}
+
+/// Name of the device a token belongs to
+fn authenticate(token: &str) -> Option<String> {
+    let mut cache = DEVICES.lock().unwrap();
+    cache.refresh();
+    find_device(&cache.devices, token)
+}
+
+fn find_device(devices: &[RemoteDevice], token: &str) -> Option<String> {
+    if token.is_empty() {
+        return None;
+    }
+    let hash = hash_token(token);
+    devices
+        .iter()
+        .find(|d| d.token_hash == hash)
+        .map(|d| d.name.clone())
+}
+
+/// MAC addresses of the network interfaces, for Wake-on-LAN
+fn mac_addresses() -> Vec<String> {
+    let Ok(entries) = fs::read_dir("/sys/class/net") else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter(|entry| entry.file_name() != "lo")
+        .filter_map(|entry| fs::read_to_string(entry.path().join("address")).ok())
+        .map(|mac| mac.trim().to_string())
+        .filter(|mac| !mac.is_empty() && mac != "00:00:00:00:00:00")
+        .collect()
+}
+
+/// A command that needs the main loop
+#[derive(Debug, PartialEq)]
+enum RemoteCommand {
+    /// Pair with the given device token once allowed on screen
+    Pair(String),
+    Launch(String),
+    Wake,
+    Shutdown,
+}
+
+struct RemoteRequest {
+    command: RemoteCommand,
+    device: String,
+    reply: Sender<Result<(), String>>,
+}
+
+/// What the main loop should do for an accepted request
+pub enum RemoteAction {
+    Launch(CartInfo, PathBuf),
+    Wake,
+}
+
+/// A pairing, launch or shutdown waiting for an answer on screen
+struct Prompt {
+    request: RemoteRequest,
+    message: String,
+    /// Resolved cart for launches
+    game: Option<(CartInfo, PathBuf)>,
+    shown_at: f64,
+}
+
+pub struct RemoteListener {
+    /// Port the running listener is bound to
+    port: Option<u16>,
+    running: Arc<AtomicBool>,
+    requests: Option<Receiver<RemoteRequest>>,
+    prompt: Option<Prompt>,
+    pending_action: Option<RemoteAction>,
+}
+
+impl RemoteListener {
+    pub fn new() -> Self {
+        Self {
+            port: None,
+            running: Arc::new(AtomicBool::new(false)),
+            requests: None,
+            prompt: None,
+            pending_action: None,
+        }
+    }
+
+    /// Start or stop the listener to match the config
+    pub fn sync(&mut self, config: &Config) {
+        let wanted = config.remote_enabled.then_some(config.remote_port);
+        if wanted == self.port {
+            return;
+        }
+        self.stop();
+        if let Some(port) = wanted {
+            self.start(port);
+        }
+    }
+
+    fn start(&mut self, port: u16) {
+        let listener = match TcpListener::bind(("0.0.0.0", port)) {
+            Ok(listener) => listener,
+            Err(e) => {
+                println!(
+                    "[ERROR] Remote listener failed to bind port {}: {}",
+                    port, e
+                );
+                // Don't retry every frame; toggling the setting tries again
+                self.port = Some(port);
+                return;
+            }
+        };
+        if let Err(e) = listener.set_nonblocking(true) {
+            println!("[ERROR] Remote listener: {}", e);
+            return;
+        }
+
+        let (tx, rx) = mpsc::channel();
+        let running = Arc::new(AtomicBool::new(true));
+        let thread_running = running.clone();
+        thread::spawn(move || accept_loop(listener, tx, thread_running));
+
+        println!("[Remote] Listening on port {}", port);
+        self.port = Some(port);
+        self.running = running;
+        self.requests = Some(rx);
+    }
+
+    fn stop(&mut self) {
+        if self.port.take().is_some() {
+            println!("[Remote] Listener stopped");
+        }
+        self.running.store(false, Ordering::SeqCst);
+        self.requests = None;
+        if let Some(prompt) = self.prompt.take() {
+            let _ = prompt
+                .request
+                .reply
+                .send(Err("Remote control disabled".to_string()));
+        }
+        cancel_pairing();
+    }
+
+    /// Handles requests from the listener and the confirmation prompt.
+    ///
+    /// Returns true while the prompt or a pairing code is on screen, in which
+    /// case the caller should swallow this frame's input.
+    pub fn update(
+        &mut self,
+        input_state: &InputState,
+        current_screen: &Screen,
+        config: &mut Config,
+    ) -> bool {
+        if let Some(prompt) = &self.prompt {
+            let timed_out = get_time() - prompt.shown_at > CONFIRM_TIMEOUT_SECS;
+            if input_state.select {
+                let prompt = self.prompt.take().unwrap();
+                println!(
+                    "[Remote] '{}' allowed: {}",
+                    prompt.request.device, prompt.message
+                );
+                self.accept(prompt.request, prompt.game, config);
+            } else if input_state.back || timed_out {
+                let prompt = self.prompt.take().unwrap();
+                println!(
+                    "[Remote] '{}' denied: {}",
+                    prompt.request.device, prompt.message
+                );
+                let _ = prompt
+                    .request
+                    .reply
+                    .send(Err("Declined on the console".to_string()));
+            }
+            return true;
+        }
+
+        if active_pairing().is_some() {
+            if input_state.back {
+                cancel_pairing();
+            }
+            return true;
+        }
+
+        // One request per frame; a prompt holds the rest in the channel
+        let Some(request) = self.requests.as_ref().and_then(|rx| rx.try_recv().ok()) else {
+            return false;
+        };
+        match &request.command {
+            RemoteCommand::Pair(_) => {
+                self.show_prompt(request, "PAIR WITH THIS CONSOLE?".to_string(), None)
+            }
+            RemoteCommand::Wake => self.accept(request, None, config),
+            RemoteCommand::Launch(cart_id) => {
+                if !kiosk::can_start_from(current_screen) {
+                    let _ = request
+                        .reply
+                        .send(Err("Busy, return to the menu first".to_string()));
+                    return false;
+                }
+                if !kiosk::is_launchable(config, cart_id) {
+                    let _ = request
+                        .reply
+                        .send(Err("Game not allowed by the kiosk whitelist".to_string()));
+                    return false;
+                }
+                let Some(game) = kiosk::scan_games()
+                    .into_iter()
+                    .find(|(info, _)| &info.id == cart_id)
+                else {
+                    let _ = request.reply.send(Err("Game not found".to_string()));
+                    return false;
+                };
+                if config.remote_confirm_launch {
+                    let name = game.0.name.clone().unwrap_or_else(|| game.0.id.clone());
+                    self.show_prompt(
+                        request,
+                        format!("LAUNCH {}?", name.to_uppercase()),
+                        Some(game),
+                    );
+                } else {
+                    self.accept(request, Some(game), config);
+                }
+            }
+            RemoteCommand::Shutdown => {
+                if config.remote_confirm_power {
+                    self.show_prompt(request, "SHUT DOWN?".to_string(), None);
+                } else {
+                    self.accept(request, None, config);
+                }
+            }
+        }
+        false
+    }
+
+    fn show_prompt(
+        &mut self,
+        request: RemoteRequest,
+        message: String,
+        game: Option<(CartInfo, PathBuf)>,
+    ) {
+        println!("[Remote] '{}' asks: {}", request.device, message);
+        self.prompt = Some(Prompt {
+            request,
+            message,
+            game,
+            shown_at: get_time(),
+        });
+    }
+
+    fn accept(
+        &mut self,
+        request: RemoteRequest,
+        game: Option<(CartInfo, PathBuf)>,
+        config: &mut Config,
+    ) {
+        let _ = request.reply.send(Ok(()));
+        match request.command {
+            RemoteCommand::Pair(token) => {
+                println!("[Remote] Paired device '{}'", request.device);
+                config.remote_devices.push(RemoteDevice {
+                    name: request.device,
+                    token_hash: hash_token(&token),
+                    paired_at: chrono::Utc::now().timestamp(),
+                });
+                config.save();
+            }
+            RemoteCommand::Launch(_) => {
+                if let Some((info, path)) = game {
+                    println!("[Remote] Launching {} for '{}'", info.id, request.device);
+                    self.pending_action = Some(RemoteAction::Launch(info, path));
+                }
+            }
+            RemoteCommand::Wake => self.pending_action = Some(RemoteAction::Wake),
+            RemoteCommand::Shutdown => shutdown_system(),
+        }
+    }
+
+    /// The accepted request the main loop should carry out, if any
+    pub fn take_action(&mut self) -> Option<RemoteAction> {
+        self.pending_action.take()
+    }
+}
+
+fn shutdown_system() {
+    println!("[Remote] Shutting down");
+    power::prepare(PowerAction::Shutdown);
+    power::run_helper(PowerAction::Shutdown);
+}
+
+/// Gives its connection slot back when the connection thread ends
+struct ConnectionSlot(Arc<AtomicUsize>);
+
+impl Drop for ConnectionSlot {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+fn accept_loop(listener: TcpListener, tx: Sender<RemoteRequest>, running: Arc<AtomicBool>) {
+    let connections = Arc::new(AtomicUsize::new(0));
+    while running.load(Ordering::SeqCst) {
+        match listener.accept() {
+            Ok((mut stream, addr)) => {
+                if connections.load(Ordering::SeqCst) >= MAX_CONNECTIONS {
+                    println!(
+                        "[Remote] Refused connection from {}: too many connections",
+                        addr
+                    );
+                    let _ = stream.set_nonblocking(false);
+                    let _ = writeln!(
+                        stream,
+                        "{}",
+                        json!({ "ok": false, "error": "Too many connections" })
+                    );
+                    continue;
+                }
+                println!("[Remote] Connection from {}", addr);
+                connections.fetch_add(1, Ordering::SeqCst);
+                let slot = ConnectionSlot(connections.clone());
+                let tx = tx.clone();
+                let running = running.clone();
+                thread::spawn(move || {
+                    let _slot = slot;
+                    handle_connection(stream, tx, running);
+                });
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => thread::sleep(ACCEPT_POLL),
+            Err(e) => {
+                println!("[ERROR] Remote listener: {}", e);
+                thread::sleep(ACCEPT_POLL);
+            }
+        }
+    }
+}
+
+fn handle_connection(stream: TcpStream, tx: Sender<RemoteRequest>, running: Arc<AtomicBool>) {
+    let _ = stream.set_nonblocking(false);
+    let _ = stream.set_read_timeout(Some(IDLE_TIMEOUT));
+    let Ok(mut writer) = stream.try_clone() else {
+        return;
+    };
+    let mut reader = BufReader::new(stream);
+    let mut buffer = Vec::new();
+    loop {
+        buffer.clear();
+        // One byte over the cap tells a full-length line from an overlong one
+        match (&mut reader)
+            .take(MAX_LINE_BYTES as u64 + 1)
+            .read_until(b'\n', &mut buffer)
+        {
+            Ok(0) | Err(_) => break,
+            Ok(_) => {}
+        }
+        if buffer.len() > MAX_LINE_BYTES && !buffer.ends_with(b"\n") {
+            println!(
+                "[Remote] Dropping connection: line exceeds {} bytes",
+                MAX_LINE_BYTES
+            );
+            break;
+        }
+        if !running.load(Ordering::SeqCst) {
+            break;
+        }
+        let line = String::from_utf8_lossy(&buffer);
+        if line.trim().is_empty() {
+            continue;
+        }
+        let reply = match handle_request(&line, &tx, authenticate) {
+            Ok(mut value) => {
+                value["ok"] = json!(true);
+                value
+            }
+            Err(e) => json!({ "ok": false, "error": e }),
+        };
+        if writeln!(writer, "{}", reply).is_err() {
+            break;
+        }
+    }
+}
+
+/// A request line from a companion app
+#[derive(Debug, PartialEq)]
+enum Request {
+    Pair {
+        code: String,
+        pair_token: String,
+        device: String,
+    },
+    List {
+        token: String,
+    },
+    /// A command for the main loop, from a paired device
+    Command {
+        token: String,
+        command: RemoteCommand,
+    },
+}
+
+fn parse_request(line: &str) -> Result<Request, String> {
+    let request: Value = serde_json::from_str(line).map_err(|e| format!("Invalid JSON: {}", e))?;
+    let field = |name: &str| {
+        request
+            .get(name)
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string()
+    };
+    let token = field("token");
+
+    let command = match field("cmd").as_str() {
+        "pair" => {
+            let device = match field("device") {
+                name if name.trim().is_empty() => "Unnamed device".to_string(),
+                name => name,
+            };
+            return Ok(Request::Pair {
+                code: field("code"),
+                pair_token: field("pair_token"),
+                device,
+            });
+        }
+        "list" => return Ok(Request::List { token }),
+        "launch" => match field("cart_id") {
+            id if id.is_empty() => return Err("Missing cart_id".to_string()),
+            id => RemoteCommand::Launch(id),
+        },
+        "wake" => RemoteCommand::Wake,
+        "shutdown" => RemoteCommand::Shutdown,
+        other => return Err(format!("Unknown command '{}'", other)),
+    };
+    Ok(Request::Command { token, command })
+}
+
+/// Answer one request line. `authenticate` maps a token to the name of the
+/// device it was paired with.
+fn handle_request(
+    line: &str,
+    tx: &Sender<RemoteRequest>,
+    authenticate: impl Fn(&str) -> Option<String>,
+) -> Result<Value, String> {
+    match parse_request(line)? {
+        Request::Pair {
+            code,
+            pair_token,
+            device,
+        } => {
+            check_pairing(&code, &pair_token)?;
+            let token = random_token(4);
+            send_to_main_loop(tx, RemoteCommand::Pair(token.clone()), device)?;
+            Ok(json!({ "token": token, "mac": mac_addresses() }))
+        }
+        Request::List { token } => {
+            authenticate(&token).ok_or("Not paired")?;
+            let games: Vec<Value> = kiosk::scan_games()
+                .into_iter()
+                .map(|(info, _)| {
+                    let name = info.name.unwrap_or_else(|| info.id.clone());
+                    json!({ "id": info.id, "name": name })
+                })
+                .collect();
+            Ok(json!({ "games": games }))
+        }
+        Request::Command { token, command } => {
+            let device = authenticate(&token).ok_or("Not paired")?;
+            send_to_main_loop(tx, command, device).map(|()| json!({}))
+        }
+    }
+}
+
+/// Hand a command to the main loop and wait for it to be carried out or declined
+fn send_to_main_loop(
+    tx: &Sender<RemoteRequest>,
+    command: RemoteCommand,
+    device: String,
+) -> Result<(), String> {
+    let (reply_tx, reply_rx) = mpsc::channel();
+    tx.send(RemoteRequest {
+        command,
+        device,
+        reply: reply_tx,
+    })
+    .map_err(|_| "Remote control disabled")?;
+    match reply_rx.recv_timeout(REPLY_TIMEOUT) {
+        Ok(result) => result,
+        Err(_) => Err("No answer from the console".to_string()),
+    }
+}
+
+/// Draws the pairing screen or the confirmation prompt over the current screen
+pub fn draw(
+    listener: &RemoteListener,
+    font_cache: &HashMap<String, Font>,
+    config: &Config,
+    scale_factor: f32,
+) {
+    let lines = if let Some(prompt) = &listener.prompt {
+        vec![
+            format!("{} ASKS:", prompt.request.device.to_uppercase()),
+            prompt.message.clone(),
+            "A: ALLOW  B: DENY".to_string(),
+        ]
+    } else if let Some((pairing, secs_left)) = active_pairing() {
+        draw_pairing(&pairing, secs_left, font_cache, config, scale_factor);
+        return;
+    } else {
+        return;
+    };
+
+    draw_rectangle(
+        0.0,
+        0.0,
+        screen_width(),
+        screen_height(),
+        Color::new(0.0, 0.0, 0.0, 0.75),
+    );
+
+    let font_size = (FONT_SIZE as f32 * scale_factor * 1.5) as u16;
+    let current_font = get_current_font(font_cache, config);
+    let line_height = font_size as f32 * 1.5;
+    let start_y = screen_height() / 2.0 - line_height;
+
+    for (i, line) in lines.iter().enumerate() {
+        let dims = measure_text(line, Some(current_font), font_size, 1.0);
+        let x = screen_width() / 2.0 - dims.width / 2.0;
+        text_with_config_color(
+            font_cache,
+            config,
+            line,
+            x,
+            start_y + i as f32 * line_height,
+            font_size,
+        );
+    }
+}
+
+/// QR code for the companion app with the code and endpoint under it
+fn draw_pairing(
+    pairing: &PairingCode,
+    secs_left: u64,
+    font_cache: &HashMap<String, Font>,
+    config: &Config,
+    scale_factor: f32,
+) {
+    draw_rectangle(
+        0.0,
+        0.0,
+        screen_width(),
+        screen_height(),
+        Color::new(0.0, 0.0, 0.0, 0.85),
+    );
+
+    let font_size = (FONT_SIZE as f32 * scale_factor) as u16;
+    let large_font_size = (FONT_SIZE as f32 * scale_factor * 1.5) as u16;
+    let current_font = get_current_font(font_cache, config);
+    let centered = |text: &str, y: f32, size: u16| {
+        let dims = measure_text(text, Some(current_font), size, 1.0);
+        text_with_config_color(
+            font_cache,
+            config,
+            text,
+            screen_width() / 2.0 - dims.width / 2.0,
+            y,
+            size,
+        );
+    };
+
+    let mut y = 40.0 * scale_factor;
+    centered("PAIR A DEVICE", y, large_font_size);
+    y += 20.0 * scale_factor;
+
+    if let Some((width, modules)) = &pairing.qr {
+        // Four module quiet zone so phone cameras can find the code
+        let quiet = 4;
+        let side = (screen_height() * 0.5).min(screen_width() * 0.5);
+        let module = (side / (width + quiet * 2) as f32).floor().max(1.0);
+        let total = module * (width + quiet * 2) as f32;
+        let left = (screen_width() / 2.0 - total / 2.0).floor();
+        draw_rectangle(left, y, total, total, WHITE);
+        for (i, _) in modules.iter().enumerate().filter(|(_, dark)| **dark) {
+            let x = left + ((i % width + quiet) as f32) * module;
+            let module_y = y + ((i / width + quiet) as f32) * module;
+            draw_rectangle(x, module_y, module, module, BLACK);
+        }
+        y += total + 30.0 * scale_factor;
+        centered("SCAN WITH THE COMPANION APP, OR ENTER", y, font_size);
+        y += font_size as f32 * 1.5;
+    } else {
+        y += 40.0 * scale_factor;
+        centered(
+            "NO NETWORK CONNECTION, ENTER THIS CODE IN THE APP",
+            y,
+            font_size,
+        );
+        y += 40.0 * scale_factor;
+    }
+
+    centered(
+        &pairing.code,
+        y + large_font_size as f32 * 0.5,
+        large_font_size,
+    );
+    y += large_font_size as f32 * 2.0;
+    if let Some(endpoint) = &pairing.endpoint {
+        centered(&format!("CONSOLE: {}", endpoint), y, font_size);
+        y += font_size as f32 * 1.5;
+    }
+    centered(
+        &format!("EXPIRES IN {}s  B: CANCEL", secs_left),
+        y,
+        font_size,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn device(name: &str, token: &str) -> RemoteDevice {
+        RemoteDevice {
+            name: name.to_string(),
+            token_hash: hash_token(token),
+            paired_at: 0,
+        }
+    }
+
+    /// Set up a pairing screen without touching the network
+    fn show_pairing(code: &str, token: &str) {
+        *PAIRING.lock().unwrap() = Some(PairingCode {
+            code: code.to_string(),
+            token: token.to_string(),
+            endpoint: None,
+            qr: None,
+            expires: Instant::now() + Duration::from_secs(PAIRING_CODE_SECS),
+            failed_attempts: 0,
+        });
+    }
+
+    /// Stands in for the main loop: accepts `count` requests and returns them
+    fn answer_requests(
+        rx: Receiver<RemoteRequest>,
+        count: usize,
+    ) -> thread::JoinHandle<Vec<(String, RemoteCommand)>> {
+        thread::spawn(move || {
+            rx.iter()
+                .take(count)
+                .map(|request| {
+                    request.reply.send(Ok(())).unwrap();
+                    (request.device, request.command)
+                })
+                .collect()
+        })
+    }
+
+    #[test]
+    fn test_parse_request() {
+        assert_eq!(
+            parse_request(r#"{"cmd":"pair","code":"123456","device":"Phone"}"#),
+            Ok(Request::Pair {
+                code: "123456".to_string(),
+                pair_token: String::new(),
+                device: "Phone".to_string()
+            })
+        );
+        assert_eq!(
+            parse_request(r#"{"cmd":"pair","pair_token":"abc","device":"  "}"#),
+            Ok(Request::Pair {
+                code: String::new(),
+                pair_token: "abc".to_string(),
+                device: "Unnamed device".to_string()
+            })
+        );
+        assert_eq!(
+            parse_request(r#"{"cmd":"list","token":"t"}"#),
+            Ok(Request::List {
+                token: "t".to_string()
+            })
+        );
+        assert_eq!(
+            parse_request(r#"{"cmd":"launch","token":"t","cart_id":"celeste"}"#),
+            Ok(Request::Command {
+                token: "t".to_string(),
+                command: RemoteCommand::Launch("celeste".to_string())
+            })
+        );
+        assert_eq!(
+            parse_request(r#"{"cmd":"shutdown"}"#),
+            Ok(Request::Command {
+                token: String::new(),
+                command: RemoteCommand::Shutdown
+            })
+        );
+        assert_eq!(
+            parse_request(r#"{"cmd":"wake","token":"t"}"#),
+            Ok(Request::Command {
+                token: "t".to_string(),
+                command: RemoteCommand::Wake
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_request_errors() {
+        assert!(parse_request("not json")
+            .unwrap_err()
+            .starts_with("Invalid JSON"));
+        assert_eq!(
+            parse_request(r#"{"cmd":"launch","token":"t"}"#),
+            Err("Missing cart_id".to_string())
+        );
+        assert_eq!(
+            parse_request(r#"{"cmd":"reboot"}"#),
+            Err("Unknown command 'reboot'".to_string())
+        );
+        assert_eq!(parse_request("{}"), Err("Unknown command ''".to_string()));
+        // Fields of the wrong type count as missing
+        assert_eq!(
+            parse_request(r#"{"cmd":"launch","cart_id":7}"#),
+            Err("Missing cart_id".to_string())
+        );
+    }
+
+    #[test]
+    fn test_tokens_are_stored_hashed() {
+        let token = random_token(4);
+        assert_eq!(token.len(), 32);
+        let stored = device("Phone", &token);
+        assert_ne!(stored.token_hash, token);
+        assert_eq!(stored.token_hash.len(), 64);
+        assert_eq!(hash_token(&token), stored.token_hash);
+
+        let devices = vec![device("Phone", "aaaa"), device("Tablet", "bbbb")];
+        assert_eq!(find_device(&devices, "bbbb"), Some("Tablet".to_string()));
+        assert_eq!(find_device(&devices, "cccc"), None);
+        // The stored hash itself isn't a token
+        assert_eq!(find_device(&devices, &hash_token("aaaa")), None);
+
+        // Devices paired before hashing have no hash and never match
+        let legacy = vec![RemoteDevice {
+            name: "Old".to_string(),
+            token_hash: String::new(),
+            paired_at: 0,
+        }];
+        assert_eq!(find_device(&legacy, ""), None);
+        assert_eq!(find_device(&devices, ""), None);
+    }
+
+    /// The pairing state is global, so the whole flow runs in one test
+    #[test]
+    fn test_pairing_flow() {
+        cancel_pairing();
+        let (tx, rx) = mpsc::channel();
+        let main_loop = answer_requests(rx, 2);
+        let pair = |code: &str, pair_token: &str| {
+            let line =
+                json!({ "cmd": "pair", "code": code, "pair_token": pair_token, "device": "Phone" });
+            handle_request(&line.to_string(), &tx, |_| None)
+        };
+
+        assert_eq!(pair("123456", "").unwrap_err(), "Not pairing");
+
+        // A wrong code keeps the screen up, the right one closes it
+        show_pairing("123456", "qrtoken");
+        assert_eq!(pair("654321", "").unwrap_err(), "Wrong pairing code");
+        assert_eq!(pair("", "").unwrap_err(), "Wrong pairing code");
+        let reply = pair("123456", "").unwrap();
+        let token = reply["token"].as_str().unwrap().to_string();
+        assert_eq!(token.len(), 32);
+        assert!(reply["mac"].is_array());
+        assert!(active_pairing().is_none());
+        assert_eq!(pair("123456", "").unwrap_err(), "Not pairing");
+
+        // The QR token works in place of the code
+        show_pairing("123456", "qrtoken");
+        let second = pair("", "qrtoken").unwrap();
+        assert_ne!(
+            second["token"], reply["token"],
+            "each device gets its own token"
+        );
+
+        // Guessing cancels pairing after a few tries
+        show_pairing("123456", "qrtoken");
+        for _ in 0..PAIRING_ATTEMPTS {
+            assert_eq!(pair("000000", "").unwrap_err(), "Wrong pairing code");
+        }
+        assert_eq!(pair("123456", "").unwrap_err(), "Not pairing");
+
+        // An expired code is the same as none
+        show_pairing("123456", "qrtoken");
+        PAIRING.lock().unwrap().as_mut().unwrap().expires = Instant::now();
+        assert_eq!(pair("123456", "").unwrap_err(), "Not pairing");
+        assert!(active_pairing().is_none());
+
+        drop(tx);
+        let requests = main_loop.join().unwrap();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(
+            requests[0],
+            ("Phone".to_string(), RemoteCommand::Pair(token))
+        );
+    }
+
+    #[test]
+    fn test_commands_need_a_paired_token() {
+        let devices = vec![device("Phone", "paired-token")];
+        let paired = |token: &str| find_device(&devices, token);
+        let (tx, rx) = mpsc::channel();
+        let main_loop = answer_requests(rx, 1);
+        for line in [
+            r#"{"cmd":"list"}"#,
+            r#"{"cmd":"launch","token":"","cart_id":"celeste"}"#,
+            r#"{"cmd":"shutdown","token":"not-a-paired-token-0123456789ab"}"#,
+        ] {
+            assert_eq!(handle_request(line, &tx, paired).unwrap_err(), "Not paired");
+        }
+
+        // Only the paired device's command reaches the main loop
+        assert!(handle_request(r#"{"cmd":"wake","token":"paired-token"}"#, &tx, paired).is_ok());
+        drop(tx);
+        assert_eq!(
+            main_loop.join().unwrap(),
+            vec![("Phone".to_string(), RemoteCommand::Wake)]
+        );
+    }
+
+    #[test]
+    fn test_overlong_lines_drop_the_connection() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let mut client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (server, _) = listener.accept().unwrap();
+        let (tx, _rx) = mpsc::channel();
+        let handler =
+            thread::spawn(move || handle_connection(server, tx, Arc::new(AtomicBool::new(true))));
+
+        // A line right at the cap is still answered
+        let mut reader = BufReader::new(client.try_clone().unwrap());
+        let padded = format!(
+            "{{\"cmd\":\"reboot\"}}{}\n",
+            " ".repeat(MAX_LINE_BYTES - 16)
+        );
+        assert_eq!(padded.len(), MAX_LINE_BYTES + 1);
+        client.write_all(padded.as_bytes()).unwrap();
+        let mut reply = String::new();
+        reader.read_line(&mut reply).unwrap();
+        assert_eq!(
+            serde_json::from_str::<Value>(&reply).unwrap()["error"],
+            "Unknown command 'reboot'"
+        );
+
+        // One without a newline in sight is cut off
+        let _ = client.write_all(&vec![b'a'; MAX_LINE_BYTES + 1]);
+        handler.join().unwrap();
+        let mut rest = String::new();
+        assert_eq!(reader.read_line(&mut rest).unwrap_or(0), 0);
+    }
+
+    #[test]
+    fn test_connections_are_capped() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.set_nonblocking(true).unwrap();
+        let addr = listener.local_addr().unwrap();
+        let running = Arc::new(AtomicBool::new(true));
+        let (tx, _rx) = mpsc::channel();
+        let accept = {
+            let running = running.clone();
+            thread::spawn(move || accept_loop(listener, tx, running))
+        };
+
+        // A refused connection may already be closed, so errors read as no reply
+        let request = |stream: &mut TcpStream| -> Value {
+            let _ = writeln!(stream, "{{\"cmd\":\"reboot\"}}");
+            let mut reply = String::new();
+            let _ = BufReader::new(stream.try_clone().unwrap()).read_line(&mut reply);
+            serde_json::from_str(&reply).unwrap_or(Value::Null)
+        };
+
+        // Every open connection is served until the cap
+        let mut open: Vec<TcpStream> = (0..MAX_CONNECTIONS)
+            .map(|_| TcpStream::connect(addr).unwrap())
+            .collect();
+        for stream in &mut open {
+            assert_eq!(request(stream)["error"], "Unknown command 'reboot'");
+        }
+
+        let mut reply = String::new();
+        BufReader::new(TcpStream::connect(addr).unwrap())
+            .read_line(&mut reply)
+            .unwrap();
+        let refused: Value = serde_json::from_str(&reply).unwrap();
+        assert_eq!(
+            refused,
+            json!({ "ok": false, "error": "Too many connections" })
+        );
+
+        // Closing one frees its slot
+        drop(open.pop());
+        let start = Instant::now();
+        let served = loop {
+            let mut stream = TcpStream::connect(addr).unwrap();
+            let reply = request(&mut stream);
+            if (!reply.is_null() && reply["error"] != "Too many connections")
+                || start.elapsed() > Duration::from_secs(5)
+            {
+                break reply;
+            }
+            thread::sleep(Duration::from_millis(20));
+        };
+        assert_eq!(served["error"], "Unknown command 'reboot'");
+
+        running.store(false, Ordering::SeqCst);
+        accept.join().unwrap();
+    }
+}
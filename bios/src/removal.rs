@@ -0,0 +1,213 @@
+use crate::{
+    config::{get_user_data_dir, Config},
+    get_current_font, measure_text, save, text_with_config_color, FONT_SIZE,
+};
+use chrono::Local;
+use inotify::{EventMask, Inotify, WatchMask};
+use macroquad::prelude::*;
+use std::{
+    collections::{HashMap, HashSet},
+    fs::{self, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
+    time::Duration,
+};
+
+/// Media mount roots. udisks creates a directory per mounted drive under
+/// these and removes it again when the drive goes away.
+fn media_roots() -> Vec<PathBuf> {
+    vec![
+        PathBuf::from(format!("/run/media/{}", whoami::username())),
+        PathBuf::from("/run/media"),
+        PathBuf::from("/media"),
+    ]
+}
+
+/// How often to rescan mount points when inotify isn't available
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// A drive that disappeared while the BIOS was running
+#[derive(Clone, Debug)]
+pub struct RemovalEvent {
+    pub mount_name: String,
+    pub during_write: bool,
+}
+
+/// Watches the media roots for unmounted drives
+pub struct RemovalMonitor {
+    receiver: Receiver<String>,
+}
+
+impl RemovalMonitor {
+    pub fn start() -> Self {
+        let (tx, rx) = mpsc::channel();
+
+        thread::spawn(move || {
+            if let Err(e) = watch_inotify(&tx) {
+                println!(
+                    "[Removal] inotify unavailable ({}), falling back to polling",
+                    e
+                );
+                watch_polling(&tx);
+            }
+        });
+
+        RemovalMonitor { receiver: rx }
+    }
+
+    /// Collects removals seen since the last frame. Pending save writes are
+    /// flushed and every event is logged before it is returned.
+    pub fn poll(&self) -> Vec<RemovalEvent> {
+        let mut events = Vec::new();
+        while let Ok(mount_name) = self.receiver.try_recv() {
+            let during_write = save::save_write_in_progress();
+            let event = RemovalEvent {
+                mount_name,
+                during_write,
+            };
+
+            // Push whatever is still buffered to the drives that are left
+            save::sync_to_disk();
+            log_removal(&event);
+            events.push(event);
+        }
+        events
+    }
+}
+
+fn watch_inotify(tx: &Sender<String>) -> std::io::Result<()> {
+    let mut inotify = Inotify::init()?;
+
+    let mut watched = 0;
+    for root in media_roots().iter().filter(|p| p.is_dir()) {
+        match inotify
+            .watches()
+            .add(root, WatchMask::DELETE | WatchMask::MOVED_FROM)
+        {
+            Ok(_) => {
+                println!("[Removal] Watching {}", root.display());
+                watched += 1;
+            }
+            Err(e) => println!("[Removal] Failed to watch {}: {}", root.display(), e),
+        }
+    }
+    if watched == 0 {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            "no media directories to watch",
+        ));
+    }
+
+    let mut buffer = [0u8; 4096];
+    loop {
+        let events = inotify.read_events_blocking(&mut buffer)?;
+        for event in events {
+            if !event.mask.contains(EventMask::ISDIR) {
+                continue;
+            }
+            if let Some(name) = event.name.and_then(|n| n.to_str()) {
+                if is_drive_name(name) && tx.send(name.to_string()).is_err() {
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
+
+fn watch_polling(tx: &Sender<String>) {
+    let mut known = mounted_drives();
+    loop {
+        thread::sleep(POLL_INTERVAL);
+        let current = mounted_drives();
+        for name in known.difference(&current) {
+            if tx.send(name.clone()).is_err() {
+                return;
+            }
+        }
+        known = current;
+    }
+}
+
+fn mounted_drives() -> HashSet<String> {
+    save::list_devices()
+        .map(|devices| {
+            devices
+                .into_iter()
+                .map(|(name, _)| name)
+                .filter(|name| name != "internal")
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Ignores the per-user directory under /run/media and internal partitions
+fn is_drive_name(name: &str) -> bool {
+    name != whoami::username() && name != "frzr_efi"
+}
+
+fn removal_log_path() -> Option<PathBuf> {
+    get_user_data_dir().map(|d| d.join("logs").join("removal.log"))
+}
+
+/// Appends a removal to the diagnostics log
+fn log_removal(event: &RemovalEvent) {
+    let line = format!(
+        "{} removed '{}'{}",
+        Local::now().format("%Y-%m-%d %H:%M:%S"),
+        event.mount_name,
+        if event.during_write {
+            " DURING SAVE WRITE"
+        } else {
+            ""
+        },
+    );
+    println!("[Removal] {}", line);
+
+    let Some(path) = removal_log_path() else {
+        return;
+    };
+    if let Err(e) = append_line(&path, &line) {
+        println!("[ERROR] Failed to write removal log: {}", e);
+    }
+}
+
+fn append_line(path: &Path, line: &str) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", line)
+}
+
+/// Draws the blocking warning shown while saves are being written
+pub fn draw_write_warning(font_cache: &HashMap<String, Font>, config: &Config, scale_factor: f32) {
+    if !save::save_write_in_progress() {
+        return;
+    }
+
+    let font_size = (FONT_SIZE as f32 * scale_factor) as u16;
+    let current_font = get_current_font(font_cache, config);
+    let text = "SAVING - DO NOT REMOVE THE CARTRIDGE";
+    let dims = measure_text(text, Some(current_font), font_size, 1.0);
+    let padding = 10.0 * scale_factor;
+    let bar_height = dims.height + padding * 2.0;
+
+    draw_rectangle(
+        0.0,
+        0.0,
+        screen_width(),
+        bar_height,
+        Color::new(0.0, 0.0, 0.0, 0.85),
+    );
+    let x = screen_width() / 2.0 - dims.width / 2.0;
+    text_with_config_color(
+        font_cache,
+        config,
+        text,
+        x,
+        padding + dims.height,
+        font_size,
+    );
+}
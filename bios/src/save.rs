@@ -1,21 +1,26 @@
-use walkdir;
-use chrono::DateTime;
+use chrono::{DateTime, FixedOffset, Local};
+use serde::{Deserialize, Serialize};
 use std::{
-    fs, fmt,
     collections::VecDeque,
-    io::{self, BufRead, Write, Read},
+    fmt, fs,
+    io::{self, BufRead, Read, Write},
     path::{Path, PathBuf},
-    process::{Command, Child, Stdio},
+    process::{Child, Command, Stdio},
+    sync::atomic::{AtomicBool, AtomicU16, AtomicU64, AtomicUsize, Ordering},
     sync::Arc,
-    sync::atomic::{AtomicU16, Ordering},
+    time::SystemTime,
 };
 use sysinfo::Disks;
-use tar::{Builder, Archive};
+use tar::{Archive, Builder};
+use walkdir;
 
 use crate::{
-    DEV_MODE,
+    cart_metadata,
     config::get_user_data_dir,
+    game_display::{shell_quote, GameDisplaySettings},
+    netshare, save_crypto,
     types::StorageMedia,
+    DEV_MODE,
 };
 
 // ===================================
@@ -29,7 +34,7 @@ const EXCLUDED_DIRS: &[&str] = &[
     ".kazeta/share",
     ".kazeta/var/prefix/dosdevices",
     ".kazeta/var/prefix/drive_c/windows",
-    ".kazeta/var/prefix/pfx"
+    ".kazeta/var/prefix/pfx",
 ];
 
 // ===================================
@@ -37,7 +42,8 @@ const EXCLUDED_DIRS: &[&str] = &[
 // ===================================
 
 // get cart info
-#[derive(Default, Clone, Debug)]
+#[derive(Default, Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
 pub struct CartInfo {
     pub name: Option<String>,
     pub id: String,
@@ -52,13 +58,45 @@ pub struct CartInfo {
     pub ra_game_name: Option<String>,
     // Optional embedded saves by player (p1-p4)
     pub player_saves: [Option<String>; 4],
+    // Overlay hotkeys the game uses itself, e.g. "KEY_F12,BTN_MODE"
+    pub suppressed_hotkeys: Vec<String>,
+}
+
+/// A save as listed on a drive. The slower details come from
+/// `get_save_metadata` once the save is selected.
+#[derive(Clone, Debug)]
+pub struct SaveDetails {
+    pub cart_id: String,
+    pub name: String,
+    pub icon: String,
+}
+
+/// Details of a save that take reading it
+#[derive(Clone, Debug, Default)]
+pub struct SaveMetadata {
+    /// End of the latest session in the playtime log
+    pub last_played: Option<DateTime<Local>>,
+    pub playtime_hours: f32,
+    pub size_mb: f32,
+    /// Newest screenshot in the save; ones in archives are extracted to the
+    /// user data dir first
+    pub thumbnail: Option<PathBuf>,
+}
+
+/// Side-by-side details of one copy of a save, used when resolving copy conflicts
+#[derive(Clone, Debug)]
+pub struct SaveSummary {
+    pub drive_name: String,
+    pub modified: Option<SystemTime>,
+    pub size_mb: f32,
+    pub playtime_hours: f32,
 }
 
 #[derive(Clone, Debug)]
 pub struct StorageMediaState {
     pub all_media: Vec<StorageMedia>, // all storage media, including disabled media
-    pub media: Vec<StorageMedia>,    // media that can actually be used
-    pub selected: usize,    // the index of selection in 'media'
+    pub media: Vec<StorageMedia>,     // media that can actually be used
+    pub selected: usize,              // the index of selection in 'media'
     pub needs_memory_refresh: bool,
 }
 
@@ -66,11 +104,18 @@ pub struct StorageMediaState {
 // ENUMS
 // ===================================
 
+/// How to handle a save that already exists on the destination drive
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ConflictResolution {
+    Overwrite, // replace the destination save
+    KeepBoth,  // rename the destination save out of the way, then copy
+}
+
 #[derive(Debug)]
 pub enum SaveError {
     Io(io::Error),
     Message(String),
-    Walkdir(walkdir::Error), // Add this variant
+    Walkdir(walkdir::Error),                  // Add this variant
     StripPrefix(std::path::StripPrefixError), // Add this variant
 }
 
@@ -93,46 +138,47 @@ impl StorageMediaState {
 
         if let Ok(devices) = list_devices() {
             for (id, free) in devices {
-                all_new_media.push(StorageMedia {
-                    id,
-                    free,
-                });
+                all_new_media.push(StorageMedia { id, free });
             }
         }
 
         // Done if media list has not changed
-        if self.all_media.len() == all_new_media.len() &&
-            !self.all_media.iter().zip(all_new_media.iter()).any(|(a, b)| a.id != b.id) {
-
-                //  update free space
-                self.all_media = all_new_media;
-                for media in &mut self.media {
-                    if let Some(pos) = self.all_media.iter().position(|m| m.id == media.id) {
-                        media.free = self.all_media.get(pos).unwrap().free
-                    }
+        if self.all_media.len() == all_new_media.len()
+            && !self
+                .all_media
+                .iter()
+                .zip(all_new_media.iter())
+                .any(|(a, b)| a.id != b.id)
+        {
+            //  update free space
+            self.all_media = all_new_media;
+            for media in &mut self.media {
+                if let Some(pos) = self.all_media.iter().position(|m| m.id == media.id) {
+                    media.free = self.all_media.get(pos).unwrap().free
                 }
-
-                return;
             }
 
-            let new_media: Vec<StorageMedia> = all_new_media
+            return;
+        }
+
+        let new_media: Vec<StorageMedia> = all_new_media
             .clone()
             .into_iter()
             .filter(|m| has_save_dir(&m.id) && !is_cart(&m.id))
             .collect();
 
-            // Try to keep the same device selected if it still exists
-            let mut new_pos = 0;
-            if let Some(old_selected_media) = self.media.get(self.selected) {
-                if let Some(pos) = new_media.iter().position(|m| m.id == old_selected_media.id) {
-                    new_pos = pos;
-                }
+        // Try to keep the same device selected if it still exists
+        let mut new_pos = 0;
+        if let Some(old_selected_media) = self.media.get(self.selected) {
+            if let Some(pos) = new_media.iter().position(|m| m.id == old_selected_media.id) {
+                new_pos = pos;
             }
+        }
 
-            self.all_media = all_new_media;
-            self.media = new_media;
-            self.selected = new_pos;
-            self.needs_memory_refresh = true;
+        self.all_media = all_new_media;
+        self.media = new_media;
+        self.selected = new_pos;
+        self.needs_memory_refresh = true;
     }
 }
 
@@ -149,10 +195,26 @@ impl fmt::Display for SaveError {
 }
 impl std::error::Error for SaveError {}
 
-impl From<io::Error> for SaveError { fn from(err: io::Error) -> Self { SaveError::Io(err) } }
-impl From<String> for SaveError { fn from(msg: String) -> Self { SaveError::Message(msg) } }
-impl From<walkdir::Error> for SaveError { fn from(err: walkdir::Error) -> Self { SaveError::Walkdir(err) } }
-impl From<std::path::StripPrefixError> for SaveError { fn from(err: std::path::StripPrefixError) -> Self { SaveError::StripPrefix(err) } }
+impl From<io::Error> for SaveError {
+    fn from(err: io::Error) -> Self {
+        SaveError::Io(err)
+    }
+}
+impl From<String> for SaveError {
+    fn from(msg: String) -> Self {
+        SaveError::Message(msg)
+    }
+}
+impl From<walkdir::Error> for SaveError {
+    fn from(err: walkdir::Error) -> Self {
+        SaveError::Walkdir(err)
+    }
+}
+impl From<std::path::StripPrefixError> for SaveError {
+    fn from(err: std::path::StripPrefixError) -> Self {
+        SaveError::StripPrefix(err)
+    }
+}
 
 // ===================================
 // FUNCTIONS
@@ -160,7 +222,9 @@ impl From<std::path::StripPrefixError> for SaveError { fn from(err: std::path::S
 
 fn should_exclude_path(path: &Path) -> bool {
     let path_str = path.to_str().unwrap_or("");
-    EXCLUDED_DIRS.iter().any(|&excluded| path_str.contains(excluded))
+    EXCLUDED_DIRS
+        .iter()
+        .any(|&excluded| path_str.contains(excluded))
 }
 
 // [UPDATED] Accept a slice of extensions instead of a single &str
@@ -238,29 +302,79 @@ fn get_attribute(info_file: &Path, attribute: &str) -> io::Result<String> {
     Ok(String::new())
 }
 
-/// Calculate playtime from a tar archive (external drives)
-fn calculate_playtime_from_tar(tar_path: &Path, _cart_id: &str) -> f32 {
-    let file = match fs::File::open(tar_path) {
-        Ok(file) => file,
+/// Playtime files and the newest screenshot found in a save
+#[derive(Default)]
+struct SaveScan {
+    playtime_log: String,
+    playtime_start: String,
+    playtime_end: String,
+    /// Modification time (Unix seconds) and location of the newest screenshot
+    screenshot: Option<(u64, Screenshot)>,
+}
+
+enum Screenshot {
+    File(PathBuf),
+    /// Read out of an archive
+    Archived(Vec<u8>),
+}
+
+impl SaveScan {
+    /// Logged sessions plus the last session's start and end, one per line
+    fn playtime_content(&self) -> String {
+        format!(
+            "{}\n{} {}",
+            self.playtime_log.trim(),
+            self.playtime_start.trim(),
+            self.playtime_end.trim()
+        )
+    }
+
+    fn is_newer(&self, mtime: u64) -> bool {
+        self.screenshot
+            .as_ref()
+            .map_or(true, |(newest, _)| mtime >= *newest)
+    }
+}
+
+/// Whether a path inside a save is a screenshot: a PNG under a folder whose
+/// name contains "screenshot"
+fn is_screenshot(path: &Path) -> bool {
+    let is_png = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("png"));
+    is_png
+        && !should_exclude_path(path)
+        && path.parent().is_some_and(|dir| {
+            dir.components().any(|c| {
+                c.as_os_str()
+                    .to_string_lossy()
+                    .to_lowercase()
+                    .contains("screenshot")
+            })
+        })
+}
+
+/// Read playtime files from a tar or tar.zst archive (external drives), and
+/// the newest screenshot if `want_screenshot`
+fn scan_tar(tar_path: &Path, want_screenshot: bool) -> SaveScan {
+    let mut scan = SaveScan::default();
+    let mut archive = match open_archive(tar_path, Arc::new(AtomicU64::new(0))) {
+        Ok(archive) => archive,
         Err(e) => {
             eprintln!("Failed to open tar file {}: {}", tar_path.display(), e);
-            return 0.0;
+            return scan;
         }
     };
 
-    let mut archive = tar::Archive::new(file);
     let entries = match archive.entries() {
         Ok(entries) => entries,
         Err(e) => {
             eprintln!("Failed to read archive entries: {}", e);
-            return 0.0;
+            return scan;
         }
     };
 
-    let mut content = String::new();
-    let mut start_content = String::new();
-    let mut end_content = String::new();
-
     for entry_result in entries {
         let mut entry = match entry_result {
             Ok(entry) => entry,
@@ -271,7 +385,7 @@ fn calculate_playtime_from_tar(tar_path: &Path, _cart_id: &str) -> f32 {
         };
 
         let path = match entry.path() {
-            Ok(path) => path,
+            Ok(path) => path.into_owned(),
             Err(e) => {
                 eprintln!("Failed to get tar entry path: {}", e);
                 continue;
@@ -279,44 +393,86 @@ fn calculate_playtime_from_tar(tar_path: &Path, _cart_id: &str) -> f32 {
         };
 
         if path.display().to_string() == ".kazeta/var/playtime.log" {
-            let _ = entry.read_to_string(&mut content);
+            let _ = entry.read_to_string(&mut scan.playtime_log);
         } else if path.display().to_string() == ".kazeta/var/playtime_start" {
-            let _ = entry.read_to_string(&mut start_content);
+            let _ = entry.read_to_string(&mut scan.playtime_start);
         } else if path.display().to_string() == ".kazeta/var/playtime_end" {
-            let _ = entry.read_to_string(&mut end_content);
+            let _ = entry.read_to_string(&mut scan.playtime_end);
+        } else if want_screenshot && entry.header().entry_type().is_file() && is_screenshot(&path) {
+            let mtime = entry.header().mtime().unwrap_or(0);
+            if scan.is_newer(mtime) {
+                let mut data = Vec::new();
+                if entry.read_to_end(&mut data).is_ok() {
+                    scan.screenshot = Some((mtime, Screenshot::Archived(data)));
+                }
+            }
         }
     }
 
-    parse_playtime_content(&format!("{}\n{} {}", content.trim(), start_content.trim(), end_content.trim()))
+    scan
 }
 
-/// Calculate playtime from a directory (internal drives)
-fn calculate_playtime_from_dir(dir_path: &Path, _cart_id: &str) -> f32 {
-    let playtime_log_path = dir_path.join(".kazeta/var/playtime.log");
-    let playtime_start_path = dir_path.join(".kazeta/var/playtime_start");
-    let playtime_end_path = dir_path.join(".kazeta/var/playtime_end");
-
-    let content = match fs::read_to_string(&playtime_log_path) {
-        Ok(content) => content.trim().to_string(),
-        Err(_) => "".to_string(),
+/// Read playtime files from a directory (internal drives), and the newest
+/// screenshot if `want_screenshot`
+fn scan_dir(dir_path: &Path, want_screenshot: bool) -> SaveScan {
+    let read = |name: &str| {
+        fs::read_to_string(dir_path.join(".kazeta/var").join(name)).unwrap_or_default()
     };
-
-    let start_content = match fs::read_to_string(&playtime_start_path) {
-        Ok(content) => content.trim().to_string(),
-        Err(_) => "".to_string(),
+    let mut scan = SaveScan {
+        playtime_log: read("playtime.log"),
+        playtime_start: read("playtime_start"),
+        playtime_end: read("playtime_end"),
+        screenshot: None,
     };
 
-    let end_content = match fs::read_to_string(&playtime_end_path) {
-        Ok(content) => content.trim().to_string(),
-        Err(_) => "".to_string(),
-    };
+    if want_screenshot {
+        let files = walkdir::WalkDir::new(dir_path)
+            .into_iter()
+            .filter_entry(|e| !should_exclude_path(e.path()))
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file());
+        for entry in files {
+            let relative = entry.path().strip_prefix(dir_path).unwrap_or(entry.path());
+            if !is_screenshot(relative) {
+                continue;
+            }
+            let mtime = entry
+                .metadata()
+                .ok()
+                .and_then(|m| m.modified().ok())
+                .and_then(|t| t.duration_since(SystemTime::UNIX_EPOCH).ok())
+                .map_or(0, |d| d.as_secs());
+            if scan.is_newer(mtime) {
+                scan.screenshot = Some((mtime, Screenshot::File(entry.path().to_path_buf())));
+            }
+        }
+    }
+
+    scan
+}
+
+/// Scan a save wherever it lives on the drive; None if there is no save
+fn scan_save(cart_id: &str, drive_name: &str, want_screenshot: bool) -> Option<SaveScan> {
+    let save_dir = get_save_dir_from_drive_name(drive_name);
+
+    // Check if this is a tar file (external drive) or directory (internal drive)
+    let tar_path = find_archive(&save_dir, cart_id);
+    let dir_path = Path::new(&save_dir).join(cart_id);
 
-    return parse_playtime_content(&format!("{}\n{} {}", content.trim(), start_content.trim(), end_content.trim()));
+    if let Some(tar_path) = tar_path {
+        Some(scan_tar(&tar_path, want_screenshot))
+    } else if dir_path.exists() {
+        Some(scan_dir(&dir_path, want_screenshot))
+    } else {
+        None
+    }
 }
 
-/// Parse playtime content from a string (common logic for both tar and directory)
-fn parse_playtime_content(content: &str) -> f32 {
+/// Parse playtime content from a string (common logic for both tar and directory).
+/// Returns the total in hours and the end of the latest session.
+fn parse_playtime_content(content: &str) -> (f32, Option<DateTime<FixedOffset>>) {
     let mut total_seconds: i64 = 0;
+    let mut last_played: Option<DateTime<FixedOffset>> = None;
 
     for line in content.lines() {
         let parts: Vec<&str> = line.split_whitespace().collect();
@@ -342,14 +498,41 @@ fn parse_playtime_content(content: &str) -> f32 {
 
         let duration = end_time.signed_duration_since(start_time);
         total_seconds += duration.num_seconds();
+        if last_played.map_or(true, |latest| end_time > latest) {
+            last_played = Some(end_time);
+        }
     }
 
     // Convert to hours rounded to one decimal place
-    ((total_seconds as f64 / 360.0).round() / 10.0) as f32
+    (
+        ((total_seconds as f64 / 360.0).round() / 10.0) as f32,
+        last_played,
+    )
 }
 
 /// Calculate size from a tar archive (external drives)
 fn calculate_size_from_tar(tar_path: &Path) -> u64 {
+    // Report the uncompressed data size so compressed, encrypted and plain
+    // copies match
+    if is_compressed_archive(tar_path) || save_crypto::is_encrypted_name(tar_path) {
+        return match open_archive(tar_path, Arc::new(AtomicU64::new(0))) {
+            Ok(mut archive) => match archive.entries() {
+                Ok(entries) => entries
+                    .filter_map(|entry| entry.ok())
+                    .map(|entry| entry.header().size().unwrap_or(0))
+                    .sum(),
+                Err(e) => {
+                    eprintln!("Failed to read archive entries: {}", e);
+                    0
+                }
+            },
+            Err(e) => {
+                eprintln!("Failed to open tar file {}: {}", tar_path.display(), e);
+                0
+            }
+        };
+    }
+
     let metadata = match fs::metadata(tar_path) {
         Ok(metadata) => metadata,
         Err(e) => {
@@ -370,25 +553,254 @@ fn calculate_size_from_dir(dir_path: &Path) -> u64 {
         .filter(|e| {
             let path = e.path();
             // Skip excluded directories and their contents
-            !should_exclude_path(path) &&
-            path.is_file()
-        }) {
-            if let Ok(metadata) = entry.metadata() {
-                total_size += metadata.len();
-            }
+            !should_exclude_path(path) && path.is_file()
+        })
+    {
+        if let Ok(metadata) = entry.metadata() {
+            total_size += metadata.len();
         }
-        total_size
+    }
+    total_size
 }
 
-fn sync_to_disk() {
+pub fn sync_to_disk() {
     if let Ok(output) = Command::new("sync")
         .output()
-        .map_err(|e| format!("Failed to execute sync command: {}", e)) {
+        .map_err(|e| format!("Failed to execute sync command: {}", e))
+    {
+        if !output.status.success() {
+            println!("Sync command failed with status: {}", output.status);
+        }
+    }
+}
+
+// Number of save writes (copy/delete/import) currently touching the disks
+static SAVE_WRITES: AtomicUsize = AtomicUsize::new(0);
+
+/// Marks a save write as in progress for as long as it is alive. The last
+/// guard to drop flushes everything to disk, so a cartridge pulled right
+/// after a write finishes doesn't lose data still sitting in the page cache.
+pub struct SaveWriteGuard;
+
+impl SaveWriteGuard {
+    pub fn new() -> Self {
+        SAVE_WRITES.fetch_add(1, Ordering::SeqCst);
+        SaveWriteGuard
+    }
+}
+
+impl Drop for SaveWriteGuard {
+    fn drop(&mut self) {
+        if SAVE_WRITES.fetch_sub(1, Ordering::SeqCst) == 1 {
+            sync_to_disk();
+        }
+    }
+}
+
+/// True while any save is being written to a drive
+pub fn save_write_in_progress() -> bool {
+    SAVE_WRITES.load(Ordering::SeqCst) > 0
+}
+
+// Whether saves copied to external drives are written as .tar.zst
+static COMPRESS_ARCHIVES: AtomicBool = AtomicBool::new(false);
+
+// Save archive extensions on external drives, preferred format first
+const TAR_ZST_EXT: &str = "tar.zst";
+const TAR_EXT: &str = "tar";
+// Either format encrypted (see save_crypto)
+const TAR_ZST_ENC_EXT: &str = "tar.zst.enc";
+const TAR_ENC_EXT: &str = "tar.enc";
+const ARCHIVE_EXTS: &[&str] = &[TAR_ZST_ENC_EXT, TAR_ENC_EXT, TAR_ZST_EXT, TAR_EXT];
+
+// Saves are small; favour speed over ratio
+const ZSTD_LEVEL: i32 = 3;
+
+/// Set whether new external-drive archives are zstd-compressed
+pub fn set_compress_archives(enabled: bool) {
+    COMPRESS_ARCHIVES.store(enabled, Ordering::SeqCst);
+}
+
+/// Find the archive holding `cart_id` in `save_dir`, in any format
+fn find_archive(save_dir: &str, cart_id: &str) -> Option<PathBuf> {
+    ARCHIVE_EXTS
+        .iter()
+        .map(|ext| Path::new(save_dir).join(format!("{}.{}", cart_id, ext)))
+        .find(|path| path.exists())
+}
+
+/// Extension of an archive path, e.g. "tar.zst.enc"
+fn archive_ext(path: &Path) -> &'static str {
+    let name = path.to_string_lossy();
+    ARCHIVE_EXTS
+        .iter()
+        .find(|ext| name.ends_with(&format!(".{}", ext)))
+        .copied()
+        .unwrap_or(TAR_EXT)
+}
+
+fn is_compressed_archive(path: &Path) -> bool {
+    archive_ext(path).starts_with(TAR_ZST_EXT)
+}
+
+/// Cart id of a save directory or archive file name
+fn cart_id_from_file_name(file_name: &str) -> &str {
+    ARCHIVE_EXTS
+        .iter()
+        .find_map(|ext| file_name.strip_suffix(&format!(".{}", ext)))
+        .unwrap_or(file_name)
+}
+
+/// Whether the save for `cart_id` on a drive is an encrypted archive
+pub fn is_save_encrypted(cart_id: &str, drive_name: &str) -> bool {
+    drive_name != "internal"
+        && find_archive(&get_save_dir_from_drive_name(drive_name), cart_id)
+            .is_some_and(|path| save_crypto::is_encrypted_name(&path))
+}
+
+/// Whether new saves written to an external drive are encrypted
+pub fn is_drive_encrypted(drive_name: &str) -> bool {
+    drive_name != "internal" && save_crypto::is_drive_encrypted(&get_drive_root(drive_name))
+}
+
+/// Turn save encryption on or off for an external drive
+pub fn set_drive_encrypted(drive_name: &str, enabled: bool) -> Result<(), SaveError> {
+    if drive_name == "internal" {
+        return Err(SaveError::Message(
+            "Internal saves are not stored as archives".to_string(),
+        ));
+    }
+    save_crypto::set_drive_encrypted(&get_drive_root(drive_name), enabled)?;
+    sync_to_disk();
+    Ok(())
+}
+
+/// Reader that counts the bytes consumed from the underlying file, so
+/// extraction progress works the same for compressed archives
+struct CountingReader<R> {
+    inner: R,
+    count: Arc<AtomicU64>,
+}
+
+impl<R: Read> Read for CountingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.count.fetch_add(n as u64, Ordering::SeqCst);
+        Ok(n)
+    }
+}
+
+/// Open a save archive for reading, decrypting and decompressing it
+/// transparently
+fn open_archive(path: &Path, bytes_read: Arc<AtomicU64>) -> io::Result<Archive<Box<dyn Read>>> {
+    let mut file = CountingReader {
+        inner: fs::File::open(path)?,
+        count: bytes_read,
+    };
+    let source: Box<dyn Read> = if save_crypto::is_encrypted_name(path) {
+        // Saves are small, and the whole archive has to be authenticated
+        // before any of it can be trusted
+        let mut data = Vec::new();
+        file.read_to_end(&mut data)?;
+        Box::new(io::Cursor::new(save_crypto::decrypt(&data)?))
+    } else {
+        Box::new(file)
+    };
+    let reader: Box<dyn Read> = if is_compressed_archive(path) {
+        Box::new(zstd::Decoder::new(source)?)
+    } else {
+        source
+    };
+    Ok(Archive::new(reader))
+}
+
+/// Where an archive's bytes go: straight to the file, or gathered up to be
+/// encrypted in one piece when the archive is finished
+enum ArchiveSink {
+    File(fs::File),
+    Encrypted { file: fs::File, buffer: Vec<u8> },
+}
+
+impl ArchiveSink {
+    fn create(path: &Path) -> io::Result<Self> {
+        let file = fs::File::create(path)?;
+        if save_crypto::is_encrypted_name(path) {
+            Ok(ArchiveSink::Encrypted {
+                file,
+                buffer: Vec::new(),
+            })
+        } else {
+            Ok(ArchiveSink::File(file))
+        }
+    }
 
-            if !output.status.success() {
-                println!("Sync command failed with status: {}", output.status);
+    fn finish(self) -> io::Result<()> {
+        match self {
+            ArchiveSink::File(file) => file.sync_all(),
+            ArchiveSink::Encrypted { mut file, buffer } => {
+                file.write_all(&save_crypto::encrypt(&buffer)?)?;
+                file.sync_all()
             }
         }
+    }
+}
+
+impl Write for ArchiveSink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            ArchiveSink::File(file) => file.write(buf),
+            ArchiveSink::Encrypted { buffer, .. } => buffer.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            ArchiveSink::File(file) => file.flush(),
+            ArchiveSink::Encrypted { .. } => Ok(()),
+        }
+    }
+}
+
+/// Destination of a save archive being written
+enum ArchiveWriter {
+    Plain(ArchiveSink),
+    Zstd(zstd::Encoder<'static, ArchiveSink>),
+}
+
+impl ArchiveWriter {
+    /// Compresses and encrypts according to the file name
+    fn create(path: &Path) -> io::Result<Self> {
+        let sink = ArchiveSink::create(path)?;
+        if is_compressed_archive(path) {
+            Ok(ArchiveWriter::Zstd(zstd::Encoder::new(sink, ZSTD_LEVEL)?))
+        } else {
+            Ok(ArchiveWriter::Plain(sink))
+        }
+    }
+
+    /// Flush the compressor (if any), encrypt (if needed) and sync the file
+    fn finish(self) -> io::Result<()> {
+        match self {
+            ArchiveWriter::Plain(sink) => sink.finish(),
+            ArchiveWriter::Zstd(encoder) => encoder.finish()?.finish(),
+        }
+    }
+}
+
+impl Write for ArchiveWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            ArchiveWriter::Plain(file) => file.write(buf),
+            ArchiveWriter::Zstd(encoder) => encoder.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            ArchiveWriter::Plain(file) => file.flush(),
+            ArchiveWriter::Zstd(encoder) => encoder.flush(),
+        }
+    }
 }
 
 /// Returns the correct directory for state files based on the environment.
@@ -410,7 +822,7 @@ fn get_state_dir() -> std::io::Result<PathBuf> {
 // PUBLIC FUNCTIONS
 // ===================================
 
-pub fn write_launch_command(kzi_path: &Path) -> std::io::Result<()> {
+pub fn write_launch_command(kzi_path: &Path, env: &[(&str, String)]) -> std::io::Result<()> {
     //let state_dir = Path::new("/var/kazeta/state");
     //fs::create_dir_all(state_dir)?; // Ensure the directory exists
     let state_dir = get_state_dir()?;
@@ -421,19 +833,25 @@ pub fn write_launch_command(kzi_path: &Path) -> std::io::Result<()> {
     // The command tells the kazeta script which specific .kzi to launch,
     // bypassing the auto-detection.
     // The single quotes are important to handle paths with spaces.
-    let command = format!("/usr/bin/kazeta '{}'", kzi_path.display());
+    let mut command = format!("/usr/bin/kazeta '{}'", kzi_path.display());
+
+    // kazeta-session evals this line, so variables can be prefixed for the wrappers
+    if !env.is_empty() {
+        let assignments: Vec<String> = env
+            .iter()
+            .map(|(key, value)| format!("{}={}", key, shell_quote(value)))
+            .collect();
+        command = format!("{} {}", assignments.join(" "), command);
+    }
 
     writeln!(file, "{}", command)?;
 
     Ok(())
 }
 
-// [UPDATED] Searches for both kzi and kzp
-pub fn find_all_game_files() -> Result<(Vec<PathBuf>, Vec<String>), SaveError> {
-    let mut debug_log = Vec::new();
-
-    // Check for development games directory first (for macOS/dev testing)
-    // Only check in dev mode
+/// Where carts are searched for: the dev games directory in dev mode if it
+/// exists, otherwise the media mounts
+pub fn game_search_root() -> PathBuf {
     let dev_games_dir = if DEV_MODE {
         dirs::home_dir()
             .map(|h| h.join("kazeta-games"))
@@ -441,25 +859,56 @@ pub fn find_all_game_files() -> Result<(Vec<PathBuf>, Vec<String>), SaveError> {
     } else {
         None
     };
+    dev_games_dir.unwrap_or_else(|| PathBuf::from("/run/media/"))
+}
 
-    let mount_dir = if let Some(dev_dir) = dev_games_dir {
-        debug_log.push(format!("[Debug] Using development games directory: {}", dev_dir.display()));
-        dev_dir.to_string_lossy().to_string()
-    } else {
-        "/run/media/".to_string()
-    };
+/// Depth below `game_search_root` that carts are searched to
+pub const GAME_SEARCH_DEPTH: usize = 2;
+
+// [UPDATED] Searches for both kzi and kzp
+pub fn find_all_game_files() -> Result<(Vec<PathBuf>, Vec<String>), SaveError> {
+    let mut debug_log = Vec::new();
 
-    debug_log.push(format!("[Debug] Searching for .kzi and .kzp files in '{}' (max depth: 2)...", mount_dir));
+    // Development games directory (for macOS/dev testing), only in dev mode
+    let root = game_search_root();
+    if DEV_MODE && root != Path::new("/run/media/") {
+        debug_log.push(format!(
+            "[Debug] Using development games directory: {}",
+            root.display()
+        ));
+    }
+    let mount_dir = root.to_string_lossy().to_string();
+
+    debug_log.push(format!(
+        "[Debug] Searching for .kzi and .kzp files in '{}' (max depth: 2)...",
+        mount_dir
+    ));
+
+    // Carts on a network share, if one is mounted and answering
+    let share_files = netshare::find_game_files(&["kzi", "kzp"], GAME_SEARCH_DEPTH);
+    if !share_files.is_empty() {
+        debug_log.push(format!(
+            "[Debug] Found {} game file(s) on the network share '{}'.",
+            share_files.len(),
+            netshare::MOUNT_POINT
+        ));
+    }
 
     // Search for both extensions
-    match find_files_by_extension(&mount_dir, &["kzi", "kzp"], 2, false) {
-        Ok(files) => {
-            debug_log.push(format!("[Debug] Found {} potential game file(s).", files.len()));
+    match find_files_by_extension(&mount_dir, &["kzi", "kzp"], GAME_SEARCH_DEPTH, false) {
+        Ok(mut files) => {
+            files.extend(share_files);
+            debug_log.push(format!(
+                "[Debug] Found {} potential game file(s).",
+                files.len()
+            ));
             for (i, path) in files.iter().enumerate() {
                 debug_log.push(format!("[Debug]    {}: {}", i + 1, path.display()));
             }
             Ok((files, debug_log))
         }
+        // No local media, but the share may still have carts
+        Err(_) if !share_files.is_empty() => Ok((share_files, debug_log)),
         Err(e) => {
             let error_msg = format!("Error while scanning '{}': {}", mount_dir, e);
             debug_log.push(error_msg.clone());
@@ -482,6 +931,7 @@ pub fn parse_kzi_file(kzi_path: &Path) -> Result<CartInfo, SaveError> {
     let mut multiplayer_type = None;
     let mut ra_game_name = None;
     let mut player_saves: [Option<String>; 4] = [None, None, None, None];
+    let mut suppressed_hotkeys = Vec::new();
 
     for raw_line in content.lines() {
         let line = raw_line.trim();
@@ -520,6 +970,13 @@ pub fn parse_kzi_file(kzi_path: &Path) -> Result<CartInfo, SaveError> {
                 "savep2" => player_saves[1] = Some(value.to_string()),
                 "savep3" => player_saves[2] = Some(value.to_string()),
                 "savep4" => player_saves[3] = Some(value.to_string()),
+                "suppresshotkeys" | "suppress_hotkeys" => {
+                    suppressed_hotkeys = value
+                        .split(',')
+                        .map(|s| s.trim().to_string())
+                        .filter(|s| !s.is_empty())
+                        .collect();
+                }
                 _ => {}
             }
         }
@@ -528,7 +985,7 @@ pub fn parse_kzi_file(kzi_path: &Path) -> Result<CartInfo, SaveError> {
     let icon = icon.or_else(|| Some("default.png".to_string()));
 
     if let (Some(id), Some(exec), Some(icon)) = (id, exec, icon) {
-        Ok(CartInfo {
+        let mut cart_info = CartInfo {
             name,
             id,
             exec,
@@ -539,7 +996,11 @@ pub fn parse_kzi_file(kzi_path: &Path) -> Result<CartInfo, SaveError> {
             multiplayer_type,
             ra_game_name,
             player_saves,
-        })
+            suppressed_hotkeys,
+        };
+        // Edits made in the BIOS to a read-only cart
+        cart_metadata::apply_override(&mut cart_info);
+        Ok(cart_info)
     } else {
         Err(SaveError::Message(format!(
             "Invalid .kzi file '{}': missing required fields (Id, Exec, or Icon).",
@@ -551,8 +1012,8 @@ pub fn parse_kzi_file(kzi_path: &Path) -> Result<CartInfo, SaveError> {
 /// Options for launching an mGBA game with multiplayer and save slot selection
 #[derive(Clone, Debug, Default)]
 pub struct MgbaLaunchOptions {
-    pub player_count: u8,           // 1-4 players
-    pub save_slots: Vec<String>,    // Save slot for each player (e.g., ["p1", "p2"])
+    pub player_count: u8,        // 1-4 players
+    pub save_slots: Vec<String>, // Save slot for each player (e.g., ["p1", "p2"])
 }
 
 // for debug game launch
@@ -571,9 +1032,17 @@ pub fn launch_game_with_options(
 ) -> std::io::Result<Child> {
     // Setup RetroAchievements if enabled (for dev mode)
     setup_retroachievements_for_launch(cart_info, kzi_path);
+    // Per-cart scaling/aspect/filter/shader for the emulator wrappers
+    let display = GameDisplaySettings::load(&cart_info.id);
     // Check if this is a compressed package (.kzp)
-    if kzi_path.extension().map_or(false, |ext| ext.eq_ignore_ascii_case("kzp")) {
-        println!("[Debug] Launching compressed package directly via kazeta wrapper: {}", kzi_path.display());
+    if kzi_path
+        .extension()
+        .map_or(false, |ext| ext.eq_ignore_ascii_case("kzp"))
+    {
+        println!(
+            "[Debug] Launching compressed package directly via kazeta wrapper: {}",
+            kzi_path.display()
+        );
 
         // We cannot use standard 'Exec' logic because the exec path is inside the image.
         // We just tell the wrapper script to handle this package.
@@ -587,12 +1056,18 @@ pub fn launch_game_with_options(
                 command.env("MGBA_PLAYERS", opts.player_count.to_string());
                 // Pass save slots as comma-separated list
                 command.env("MGBA_SAVE_SLOTS", opts.save_slots.join(","));
-                println!("[Debug] Multiplayer enabled - {} players, slots: {:?}", opts.player_count, opts.save_slots);
+                println!(
+                    "[Debug] Multiplayer enabled - {} players, slots: {:?}",
+                    opts.player_count, opts.save_slots
+                );
             } else {
                 // Single player with selected save slot
                 if !opts.save_slots.is_empty() {
                     command.env("MGBA_SAVE_SLOT", &opts.save_slots[0]);
-                    println!("[Debug] Single player with save slot: {}", opts.save_slots[0]);
+                    println!(
+                        "[Debug] Single player with save slot: {}",
+                        opts.save_slots[0]
+                    );
                 }
             }
             if let Some(max_players) = cart_info.max_players {
@@ -614,10 +1089,11 @@ pub fn launch_game_with_options(
             println!("[Debug] Multiplayer enabled - defaulting to 2 players");
         }
 
+        display.apply(&mut command);
         return command
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .spawn();
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn();
     }
 
     // --- Standard Folder-Based Launch Logic (.kzi metadata) ---
@@ -650,15 +1126,18 @@ pub fn launch_game_with_options(
 
             // Try absolute path based on common dev setup
             if let Some(home) = dirs::home_dir() {
-                possible_paths.push(home.join("sandbox/kazeta-plus/runtimes/gba/vba-run-wrapper.sh"));
+                possible_paths
+                    .push(home.join("sandbox/kazeta-plus/runtimes/gba/vba-run-wrapper.sh"));
             }
 
             // Check environment variable for project root
             if let Ok(project_root) = std::env::var("KAZETA_PROJECT_ROOT") {
-                possible_paths.push(PathBuf::from(project_root).join("runtimes/gba/vba-run-wrapper.sh"));
+                possible_paths
+                    .push(PathBuf::from(project_root).join("runtimes/gba/vba-run-wrapper.sh"));
             }
 
-            let wrapper_path = possible_paths.iter()
+            let wrapper_path = possible_paths
+                .iter()
                 .find(|p| p.exists())
                 .cloned()
                 .unwrap_or_else(|| {
@@ -667,13 +1146,15 @@ pub fn launch_game_with_options(
                 });
 
             // Canonicalize to absolute path to avoid issues when changing working directory
-            let wrapper_path = wrapper_path.canonicalize()
-                .unwrap_or_else(|e| {
-                    eprintln!("[Warning] Failed to canonicalize wrapper path: {}", e);
-                    wrapper_path.clone()
-                });
+            let wrapper_path = wrapper_path.canonicalize().unwrap_or_else(|e| {
+                eprintln!("[Warning] Failed to canonicalize wrapper path: {}", e);
+                wrapper_path.clone()
+            });
 
-            println!("[Debug] Using VBA-M wrapper script: {}", wrapper_path.display());
+            println!(
+                "[Debug] Using VBA-M wrapper script: {}",
+                wrapper_path.display()
+            );
 
             let rom_path = game_root.join(&cart_info.exec);
             let mut command = Command::new("bash");
@@ -687,12 +1168,18 @@ pub fn launch_game_with_options(
                     command.env("VBA_MULTIPLAYER", "true");
                     command.env("VBA_PLAYERS", opts.player_count.to_string());
                     command.env("VBA_SAVE_SLOTS", opts.save_slots.join(","));
-                    println!("[Debug] VBA-M Multiplayer enabled - {} players, slots: {:?}", opts.player_count, opts.save_slots);
+                    println!(
+                        "[Debug] VBA-M Multiplayer enabled - {} players, slots: {:?}",
+                        opts.player_count, opts.save_slots
+                    );
                 } else {
                     // Single player with selected save slot
                     if !opts.save_slots.is_empty() {
                         command.env("VBA_SAVE_SLOT", &opts.save_slots[0]);
-                        println!("[Debug] VBA-M Single player with save slot: {}", opts.save_slots[0]);
+                        println!(
+                            "[Debug] VBA-M Single player with save slot: {}",
+                            opts.save_slots[0]
+                        );
                     }
                 }
                 if let Some(max_players) = cart_info.max_players {
@@ -713,6 +1200,7 @@ pub fn launch_game_with_options(
                 println!("[Debug] VBA-M Multiplayer enabled - defaulting to 2 players");
             }
 
+            display.apply(&mut command);
             return command
                 .current_dir(game_root)
                 .stdout(Stdio::piped())
@@ -720,7 +1208,10 @@ pub fn launch_game_with_options(
                 .spawn();
         } else {
             // In production, use the kazeta wrapper script
-            println!("[Debug] Launching .kzi with vba-m runtime via kazeta wrapper: {}", kzi_path.display());
+            println!(
+                "[Debug] Launching .kzi with vba-m runtime via kazeta wrapper: {}",
+                kzi_path.display()
+            );
 
             let mut command = Command::new("/usr/bin/kazeta");
             command.arg(kzi_path);
@@ -751,6 +1242,7 @@ pub fn launch_game_with_options(
                 command.env("VBA_PLAYERS", "2");
             }
 
+            display.apply(&mut command);
             return command
                 .stdout(Stdio::piped())
                 .stderr(Stdio::piped())
@@ -765,7 +1257,8 @@ pub fn launch_game_with_options(
             command.arg(&cart_info.exec);
             command // Return the command builder
         }
-        _ => { // Default to "linux"
+        _ => {
+            // Default to "linux"
             let mut command = Command::new("sh");
             command.arg("-c").arg(&cart_info.exec);
             command // Return the command builder
@@ -795,16 +1288,20 @@ pub fn launch_game_with_options(
         println!("[Debug] Multiplayer enabled");
     }
 
+    display.apply(&mut cmd);
+
     // Now, apply the common settings and spawn the process
     cmd.current_dir(game_root)
-    .stdout(Stdio::piped())
-    .stderr(Stdio::piped())
-    .spawn()
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
 }
 
 /// Get the save directory path for a cart
 pub fn get_mgba_save_dir(cart_id: &str) -> PathBuf {
-    let base_dir = dirs::home_dir().unwrap().join(".local/share/kazeta/saves/default");
+    let base_dir = dirs::home_dir()
+        .unwrap()
+        .join(".local/share/kazeta/saves/default");
     base_dir.join(cart_id)
 }
 
@@ -819,15 +1316,27 @@ pub fn get_rom_name_from_exec(exec: &str) -> String {
 
 /// Import an embedded save for a specific player (p1-p4) referenced by the .kzi metadata.
 /// Copies the provided save file into the per-game save directory if it does not already exist.
-pub fn import_embedded_save(cart_info: &CartInfo, kzi_path: &Path, player: u8) -> Result<String, SaveError> {
+pub fn import_embedded_save(
+    cart_info: &CartInfo,
+    kzi_path: &Path,
+    player: u8,
+) -> Result<String, SaveError> {
+    let _write_guard = SaveWriteGuard::new();
     if player == 0 || player > 4 {
-        return Err(SaveError::Message(format!("Invalid player index: {}", player)));
+        return Err(SaveError::Message(format!(
+            "Invalid player index: {}",
+            player
+        )));
     }
 
     let idx = (player - 1) as usize;
-    let save_key = cart_info.player_saves.get(idx).and_then(|s| s.as_ref()).ok_or_else(|| {
-        SaveError::Message(format!("No embedded save specified for player {}", player))
-    })?;
+    let save_key = cart_info
+        .player_saves
+        .get(idx)
+        .and_then(|s| s.as_ref())
+        .ok_or_else(|| {
+            SaveError::Message(format!("No embedded save specified for player {}", player))
+        })?;
 
     let source_path = kzi_path
         .parent()
@@ -859,60 +1368,394 @@ pub fn import_embedded_save(cart_info: &CartInfo, kzi_path: &Path, player: u8) -
     Ok(format!("p{}", player))
 }
 
-/// Searches for files with a given extension within a directory up to a specified depth
-/// [UPDATED] 'extension' argument changed to 'extensions' (slice of &str)
-pub fn find_files_by_extension<P: AsRef<Path>>(
-    dir: P,
-    extensions: &[&str],
-    max_depth: usize,
-    find_first: bool,
-) -> Result<Vec<PathBuf>, io::Error> {
-    let dir_path = dir.as_ref();
-
-    // Check if initial directory exists and is accessible
-    if !dir_path.exists() {
-        return Err(io::Error::new(
-            io::ErrorKind::NotFound,
-            format!("Directory does not exist: {}", dir_path.display())
-        ));
-    }
+/// One of a cart's battery save slots, as shown by the save slot manager
+#[derive(Clone, Debug)]
+pub struct SaveSlotInfo {
+    pub id: String, // "p1"-"p4", "default" for the unsuffixed save, or any other suffix
+    pub size: Option<u64>, // None when the slot has no save yet
+    pub modified: Option<SystemTime>,
+}
 
-    // Try to read the initial directory to ensure it's accessible
-    fs::read_dir(dir_path)?;
+/// Slots a save can be duplicated or renamed into
+pub const PLAYER_SLOTS: [&str; 4] = ["p1", "p2", "p3", "p4"];
 
-    let mut results = Vec::new();
-    search_breadth_first(dir_path, extensions, max_depth, find_first, &mut results);
-    Ok(results)
+/// Path of a slot's save: "{rom}_{slot}.sav", or "{rom}.sav" for "default"
+pub fn save_slot_path(cart_id: &str, rom_name: &str, slot: &str) -> PathBuf {
+    let file_name = if slot == "default" {
+        format!("{}.sav", rom_name)
+    } else {
+        format!("{}_{}.sav", rom_name, slot)
+    };
+    get_mgba_save_dir(cart_id).join(file_name)
 }
 
-pub fn get_save_dir_from_drive_name(drive_name: &str) -> String {
-    let base_dir = dirs::home_dir().unwrap().join(".local/share/kazeta");
-    if drive_name == "internal" || drive_name.is_empty() {
-        let save_dir = base_dir.join("saves/default");
-        if !save_dir.exists() {
-            fs::create_dir_all(&save_dir).unwrap_or_else(|e| {
-                eprintln!("Failed to create save directory: {}", e);
-            });
-        }
-        save_dir.to_string_lossy().into_owned()
-    } else {
-        let base_ext = if Path::new("/media").read_dir().map(|mut d| d.next().is_none()).unwrap_or(true) {
-            if Path::new(&format!("/run/media/{}", whoami::username())).exists() {
-                format!("/run/media/{}", whoami::username())
+/// The four player slots, saved or not, followed by any other slots with a save
+pub fn list_save_slots(cart_id: &str, rom_name: &str) -> Vec<SaveSlotInfo> {
+    let mut ids: Vec<String> = PLAYER_SLOTS.iter().map(|s| s.to_string()).collect();
+    let mut others: Vec<String> = fs::read_dir(get_mgba_save_dir(cart_id))
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.extension().map_or(false, |ext| ext == "sav"))
+        .filter_map(|p| p.file_stem().and_then(|s| s.to_str()).map(str::to_string))
+        .filter_map(|stem| {
+            if stem == rom_name {
+                Some("default".to_string())
             } else {
-                "/run/media".to_string()
+                stem.strip_prefix(&format!("{}_", rom_name))
+                    .map(str::to_string)
             }
-        } else {
-            "/media".to_string()
-        };
+        })
+        .filter(|id| !ids.contains(id))
+        .collect();
+    others.sort();
+    ids.extend(others);
+
+    ids.into_iter()
+        .map(|id| {
+            let metadata = fs::metadata(save_slot_path(cart_id, rom_name, &id))
+                .ok()
+                .filter(|m| m.is_file());
+            SaveSlotInfo {
+                size: metadata.as_ref().map(|m| m.len()),
+                modified: metadata.and_then(|m| m.modified().ok()),
+                id,
+            }
+        })
+        .collect()
+}
 
-        let save_dir = Path::new(&base_ext).join(drive_name).join("kazeta/saves");
-        if !save_dir.exists() {
-            fs::create_dir_all(&save_dir).unwrap_or_else(|e| {
-                eprintln!("Failed to create save directory: {}", e);
-            });
-        }
-        save_dir.to_string_lossy().into_owned()
+fn check_slot_target(
+    cart_id: &str,
+    rom_name: &str,
+    from: &str,
+    to: &str,
+) -> Result<(PathBuf, PathBuf), SaveError> {
+    let source = save_slot_path(cart_id, rom_name, from);
+    let dest = save_slot_path(cart_id, rom_name, to);
+    if !source.exists() {
+        return Err(SaveError::Message(format!("No save in slot {}", from)));
+    }
+    if dest.exists() {
+        return Err(SaveError::Message(format!(
+            "Slot {} already has a save",
+            to
+        )));
+    }
+    Ok((source, dest))
+}
+
+/// Copy a slot's save into an empty slot
+pub fn duplicate_save_slot(
+    cart_id: &str,
+    rom_name: &str,
+    from: &str,
+    to: &str,
+) -> Result<(), SaveError> {
+    let _write_guard = SaveWriteGuard::new();
+    let (source, dest) = check_slot_target(cart_id, rom_name, from, to)?;
+    fs::copy(&source, &dest)?;
+    sync_to_disk();
+    println!("[Save] Duplicated slot {} -> {} for {}", from, to, cart_id);
+    Ok(())
+}
+
+/// Move a slot's save into an empty slot
+pub fn rename_save_slot(
+    cart_id: &str,
+    rom_name: &str,
+    from: &str,
+    to: &str,
+) -> Result<(), SaveError> {
+    let _write_guard = SaveWriteGuard::new();
+    let (source, dest) = check_slot_target(cart_id, rom_name, from, to)?;
+    fs::rename(&source, &dest)?;
+    sync_to_disk();
+    println!("[Save] Renamed slot {} -> {} for {}", from, to, cart_id);
+    Ok(())
+}
+
+/// Delete a slot's save. The last deleted copy is kept as .bak.
+pub fn delete_save_slot(cart_id: &str, rom_name: &str, slot: &str) -> Result<(), SaveError> {
+    let _write_guard = SaveWriteGuard::new();
+    let path = save_slot_path(cart_id, rom_name, slot);
+    if !path.exists() {
+        return Err(SaveError::Message(format!("No save in slot {}", slot)));
+    }
+    let mut backup = path.as_os_str().to_owned();
+    backup.push(".bak");
+    fs::rename(&path, backup)?;
+    sync_to_disk();
+    println!("[Save] Deleted slot {} for {}", slot, cart_id);
+    Ok(())
+}
+
+/// Battery save extensions PC emulators read directly (mGBA/VBA-M .sav, RetroArch/Snes9x .srm)
+const EMULATOR_SAVE_EXTS: [&str; 2] = ["sav", "srm"];
+
+/// Get the ROM name a cart's saves are named after. Uses the cart's exec when it is
+/// connected, otherwise falls back to the names of the saves already in its directory.
+pub fn get_rom_name_for_cart(cart_id: &str) -> Option<String> {
+    if let Some((cart_info, _)) = crate::library::games()
+        .into_iter()
+        .find(|(c, _)| c.id == cart_id)
+    {
+        return Some(get_rom_name_from_exec(&cart_info.exec));
+    }
+
+    let entries = fs::read_dir(get_mgba_save_dir(cart_id)).ok()?;
+    entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| is_emulator_save(p))
+        .filter_map(|p| p.file_stem().and_then(|s| s.to_str()).map(str::to_string))
+        .map(|stem| strip_player_suffix(&stem).to_string())
+        .min()
+}
+
+fn is_emulator_save(path: &Path) -> bool {
+    path.is_file()
+        && path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map_or(false, |e| {
+                EMULATOR_SAVE_EXTS.contains(&e.to_lowercase().as_str())
+            })
+}
+
+/// "Game_p2" -> "Game"
+fn strip_player_suffix(stem: &str) -> &str {
+    match stem.rsplit_once("_p") {
+        Some((base, n)) if n.len() == 1 && n.chars().all(|c| c.is_ascii_digit()) => base,
+        _ => stem,
+    }
+}
+
+/// Name a save is given on the USB drive. Player 1 (or the default slot when there
+/// is no player 1 save) becomes plain "{rom}.sav" so mGBA and VBA-M pick it up next
+/// to the ROM; the other slots keep their suffix. Files not named after the ROM are skipped.
+fn emulator_export_name(rom_name: &str, file_name: &str, has_p1: bool) -> Option<String> {
+    let (stem, ext) = file_name.rsplit_once('.')?;
+    if stem == rom_name {
+        let name = if has_p1 {
+            format!("{}_default", rom_name)
+        } else {
+            rom_name.to_string()
+        };
+        return Some(format!("{}.{}", name, ext));
+    }
+    match stem.strip_prefix(rom_name)?.strip_prefix('_')? {
+        "p1" => Some(format!("{}.{}", rom_name, ext)),
+        slot => Some(format!("{}_{}.{}", rom_name, slot, ext)),
+    }
+}
+
+/// Move an existing file out of the way as "<name>.bak" before it is replaced
+fn backup_existing(path: &Path) -> io::Result<()> {
+    if path.exists() {
+        let mut backup = path.as_os_str().to_owned();
+        backup.push(".bak");
+        fs::rename(path, backup)?;
+    }
+    Ok(())
+}
+
+/// Export a cart's battery saves to the root of an external drive using the names
+/// PC emulators expect. Files already on the drive are kept as .bak.
+pub fn export_emulator_saves(
+    cart_id: &str,
+    rom_name: &str,
+    drive_name: &str,
+) -> Result<Vec<PathBuf>, SaveError> {
+    let _write_guard = SaveWriteGuard::new();
+    let save_dir = get_mgba_save_dir(cart_id);
+    let drive_root = get_drive_root(drive_name);
+    if !drive_root.exists() {
+        return Err(SaveError::Message(format!(
+            "Drive not found: {}",
+            drive_name
+        )));
+    }
+
+    let mut saves: Vec<PathBuf> = fs::read_dir(&save_dir)?
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| is_emulator_save(p))
+        .collect();
+    saves.sort();
+    let has_p1 = saves.iter().any(|p| {
+        p.file_stem().and_then(|s| s.to_str()) == Some(format!("{}_p1", rom_name).as_str())
+    });
+
+    let mut exported = Vec::new();
+    for source in saves {
+        let Some(file_name) = source.file_name().and_then(|n| n.to_str()) else {
+            continue;
+        };
+        let Some(export_name) = emulator_export_name(rom_name, file_name, has_p1) else {
+            continue;
+        };
+        let dest = drive_root.join(export_name);
+        backup_existing(&dest)?;
+        fs::copy(&source, &dest)?;
+        println!("[Save] Exported {} -> {}", source.display(), dest.display());
+        exported.push(dest);
+    }
+
+    if exported.is_empty() {
+        return Err(SaveError::Message(
+            "No emulator save files to export".to_string(),
+        ));
+    }
+    sync_to_disk();
+    Ok(exported)
+}
+
+/// Find .sav/.srm files named after the ROM in the root of every external drive.
+/// Returns (drive name, path) pairs.
+pub fn find_importable_saves(rom_name: &str) -> Vec<(String, PathBuf)> {
+    let rom_lower = rom_name.to_lowercase();
+    let mut found = Vec::new();
+    for (drive_name, _) in list_devices().unwrap_or_default() {
+        if drive_name == "internal" {
+            continue;
+        }
+        let Ok(entries) = fs::read_dir(get_drive_root(&drive_name)) else {
+            continue;
+        };
+        for path in entries.flatten().map(|e| e.path()) {
+            let stem = path
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or("")
+                .to_lowercase();
+            if is_emulator_save(&path)
+                && (stem == rom_lower || stem.starts_with(&format!("{}_", rom_lower)))
+            {
+                found.push((drive_name.clone(), path));
+            }
+        }
+    }
+    found.sort();
+    found
+}
+
+/// Import a PC emulator save into a player slot (p1-p4), following the naming of
+/// `import_embedded_save`. A save already in that slot is kept as .bak.
+pub fn import_emulator_save(
+    cart_id: &str,
+    rom_name: &str,
+    source_path: &Path,
+    player: u8,
+) -> Result<String, SaveError> {
+    let _write_guard = SaveWriteGuard::new();
+    if player == 0 || player > 4 {
+        return Err(SaveError::Message(format!(
+            "Invalid player index: {}",
+            player
+        )));
+    }
+    if !source_path.exists() {
+        return Err(SaveError::Message(format!(
+            "Save file not found: {}",
+            source_path.display()
+        )));
+    }
+
+    let save_dir = get_mgba_save_dir(cart_id);
+    fs::create_dir_all(&save_dir)?;
+
+    let ext = source_path
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("sav")
+        .to_lowercase();
+    let dest_path = save_dir.join(format!("{}_p{}.{}", rom_name, player, ext));
+    backup_existing(&dest_path)?;
+
+    fs::copy(source_path, &dest_path)?;
+    sync_to_disk();
+    println!(
+        "[Save] Imported {} -> {}",
+        source_path.display(),
+        dest_path.display()
+    );
+    Ok(format!("p{}", player))
+}
+
+/// Searches for files with a given extension within a directory up to a specified depth
+/// [UPDATED] 'extension' argument changed to 'extensions' (slice of &str)
+pub fn find_files_by_extension<P: AsRef<Path>>(
+    dir: P,
+    extensions: &[&str],
+    max_depth: usize,
+    find_first: bool,
+) -> Result<Vec<PathBuf>, io::Error> {
+    let dir_path = dir.as_ref();
+
+    // Check if initial directory exists and is accessible
+    if !dir_path.exists() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("Directory does not exist: {}", dir_path.display()),
+        ));
+    }
+
+    // Try to read the initial directory to ensure it's accessible
+    fs::read_dir(dir_path)?;
+
+    let mut results = Vec::new();
+    search_breadth_first(dir_path, extensions, max_depth, find_first, &mut results);
+    Ok(results)
+}
+
+/// Directory external drives are mounted under. `KAZETA_MEDIA_ROOT`
+/// overrides it, e.g. to try drive features against a folder of fake drives.
+fn external_media_base() -> String {
+    if let Ok(root) = std::env::var("KAZETA_MEDIA_ROOT") {
+        return root;
+    }
+    if Path::new("/media")
+        .read_dir()
+        .map(|mut d| d.next().is_none())
+        .unwrap_or(true)
+    {
+        if Path::new(&format!("/run/media/{}", whoami::username())).exists() {
+            format!("/run/media/{}", whoami::username())
+        } else {
+            "/run/media".to_string()
+        }
+    } else {
+        "/media".to_string()
+    }
+}
+
+/// Mount point of an external drive
+pub fn get_drive_root(drive_name: &str) -> PathBuf {
+    Path::new(&external_media_base()).join(drive_name)
+}
+
+pub fn get_save_dir_from_drive_name(drive_name: &str) -> String {
+    let base_dir = dirs::home_dir().unwrap().join(".local/share/kazeta");
+    if drive_name == "internal" || drive_name.is_empty() {
+        let save_dir = base_dir.join("saves/default");
+        if !save_dir.exists() {
+            fs::create_dir_all(&save_dir).unwrap_or_else(|e| {
+                eprintln!("Failed to create save directory: {}", e);
+            });
+        }
+        save_dir.to_string_lossy().into_owned()
+    } else {
+        let base_ext = external_media_base();
+
+        let save_dir = Path::new(&base_ext).join(drive_name).join("kazeta/saves");
+        if !save_dir.exists() {
+            fs::create_dir_all(&save_dir).unwrap_or_else(|e| {
+                eprintln!("Failed to create save directory: {}", e);
+            });
+        }
+        save_dir.to_string_lossy().into_owned()
     }
 }
 
@@ -927,15 +1770,7 @@ pub fn get_cache_dir_from_drive_name(drive_name: &str) -> String {
         }
         cache_dir.to_string_lossy().into_owned()
     } else {
-        let base_ext = if Path::new("/media").read_dir().map(|mut d| d.next().is_none()).unwrap_or(true) {
-            if Path::new(&format!("/run/media/{}", whoami::username())).exists() {
-                format!("/run/media/{}", whoami::username())
-            } else {
-                "/run/media".to_string()
-            }
-        } else {
-            "/media".to_string()
-        };
+        let base_ext = external_media_base();
 
         let cache_dir = Path::new(&base_ext).join(drive_name).join("kazeta/cache");
         if !cache_dir.exists() {
@@ -956,26 +1791,19 @@ pub fn list_devices() -> io::Result<Vec<(String, u32)>> {
     let base_dir_str = base_dir.to_str().unwrap();
 
     // Find the disk that contains our base directory
-    let internal_disk = disks.iter()
-    .find(|disk| {
-        let mount_point = disk.mount_point().to_str().unwrap();
-        base_dir_str.starts_with(mount_point)
-    })
-    .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "Could not find internal disk"))?;
+    let internal_disk = disks
+        .iter()
+        .find(|disk| {
+            let mount_point = disk.mount_point().to_str().unwrap();
+            base_dir_str.starts_with(mount_point)
+        })
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "Could not find internal disk"))?;
 
     let free_space = (internal_disk.available_space() / 1024 / 1024) as u32; // Convert to MB
     devices.push(("internal".to_string(), free_space));
 
     // Add external drives
-    let base_ext = if Path::new("/media").read_dir().map(|mut d| d.next().is_none()).unwrap_or(true) {
-        if Path::new(&format!("/run/media/{}", whoami::username())).exists() {
-            format!("/run/media/{}", whoami::username())
-        } else {
-            "/run/media".to_string()
-        }
-    } else {
-        "/media".to_string()
-    };
+    let base_ext = external_media_base();
 
     // Find all disks mounted under the external base directory
     for disk in disks.iter() {
@@ -1010,7 +1838,13 @@ pub fn is_cart(drive_name: &str) -> bool {
     }
 
     let save_dir = get_save_dir_from_drive_name(drive_name);
-    let mount_point: String = Path::new(&save_dir).parent().unwrap().parent().unwrap().display().to_string();
+    let mount_point: String = Path::new(&save_dir)
+        .parent()
+        .unwrap()
+        .parent()
+        .unwrap()
+        .display()
+        .to_string();
 
     if let Ok(files) = find_files_by_extension(mount_point, &["kzi", "kzp"], 1, true) {
         if files.len() > 0 {
@@ -1025,7 +1859,10 @@ pub fn is_cart(drive_name: &str) -> bool {
 pub fn is_cart_connected() -> bool {
     // In dev mode, check ~/kazeta-games first
     if DEV_MODE {
-        if let Some(dev_games_dir) = dirs::home_dir().map(|h| h.join("kazeta-games")).filter(|p| p.exists()) {
+        if let Some(dev_games_dir) = dirs::home_dir()
+            .map(|h| h.join("kazeta-games"))
+            .filter(|p| p.exists())
+        {
             if let Ok(files) = find_files_by_extension(&dev_games_dir, &["kzi", "kzp"], 2, true) {
                 if files.len() > 0 {
                     return true;
@@ -1044,7 +1881,7 @@ pub fn is_cart_connected() -> bool {
     false
 }
 
-pub fn get_save_details(drive_name: &str) -> io::Result<Vec<(String, String, String)>> {
+pub fn get_save_details(drive_name: &str) -> io::Result<Vec<SaveDetails>> {
     let save_dir = get_save_dir_from_drive_name(drive_name);
     let cache_dir = get_cache_dir_from_drive_name(drive_name);
     eprintln!("Getting save details from directory: {}", save_dir);
@@ -1053,16 +1890,13 @@ pub fn get_save_details(drive_name: &str) -> io::Result<Vec<(String, String, Str
     for entry in fs::read_dir(save_dir)? {
         let entry = entry?;
         let path = entry.path();
-        let file_name = path.file_name()
-        .and_then(|n| n.to_str())
-        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Invalid filename"))?;
+        let file_name = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Invalid filename"))?;
 
-        // Remove .tar extension if present
-        let cart_id = if file_name.ends_with(".tar") {
-            &file_name[..file_name.len() - 4]
-        } else {
-            file_name
-        };
+        // Remove .tar/.tar.zst extension if present
+        let cart_id = cart_id_from_file_name(file_name);
 
         let metadata_path = Path::new(&cache_dir).join(cart_id).join("metadata.kzi");
         let name = get_attribute(&metadata_path, "Name").unwrap_or_else(|e| {
@@ -1071,13 +1905,25 @@ pub fn get_save_details(drive_name: &str) -> io::Result<Vec<(String, String, Str
         });
         let icon = format!("{}/{}/icon.png", cache_dir, cart_id);
 
-        details.push((cart_id.to_string(), name, icon));
+        details.push(SaveDetails {
+            cart_id: cart_id.to_string(),
+            name,
+            icon,
+        });
     }
 
     // Sort details alphabetically by name, fallback to cart_id if name is empty
     details.sort_by(|a, b| {
-        let name_a = if a.1.is_empty() { &a.0 } else { &a.1 };
-        let name_b = if b.1.is_empty() { &b.0 } else { &b.1 };
+        let name_a = if a.name.is_empty() {
+            &a.cart_id
+        } else {
+            &a.name
+        };
+        let name_b = if b.name.is_empty() {
+            &b.cart_id
+        } else {
+            &b.name
+        };
         name_a.to_lowercase().cmp(&name_b.to_lowercase())
     });
 
@@ -1086,22 +1932,26 @@ pub fn get_save_details(drive_name: &str) -> io::Result<Vec<(String, String, Str
 }
 
 pub fn delete_save(cart_id: &str, from_drive: &str) -> Result<(), SaveError> {
+    let _write_guard = SaveWriteGuard::new();
     let from_dir = get_save_dir_from_drive_name(from_drive);
     let from_cache = get_cache_dir_from_drive_name(from_drive);
 
     // Check if save exists
     let save_path = Path::new(&from_dir).join(cart_id);
-    let save_path_tar = Path::new(&from_dir).join(format!("{}.tar", cart_id));
-    if !save_path.exists() && !save_path_tar.exists() {
+    let save_path_tar = find_archive(&from_dir, cart_id);
+    if !save_path.exists() && save_path_tar.is_none() {
         //return Err(format!("Save file for {} does not exist on '{}' drive", cart_id, from_drive));
-        return Err(SaveError::Message(format!("Save file for {} does not exist on '{}' drive", cart_id, from_drive)));
+        return Err(SaveError::Message(format!(
+            "Save file for {} does not exist on '{}' drive",
+            cart_id, from_drive
+        )));
     }
 
     // Delete save file
     if from_drive == "internal" {
         //fs::remove_dir_all(save_path).map_err(|e| e.to_string())?;
         fs::remove_dir_all(save_path)?;
-    } else {
+    } else if let Some(save_path_tar) = save_path_tar {
         //fs::remove_file(save_path_tar).map_err(|e| e.to_string())?;
         fs::remove_file(save_path_tar)?;
     }
@@ -1116,7 +1966,13 @@ pub fn delete_save(cart_id: &str, from_drive: &str) -> Result<(), SaveError> {
     Ok(())
 }
 
-pub fn copy_save(cart_id: &str, from_drive: &str, to_drive: &str, progress: Arc<AtomicU16>) -> Result<(), SaveError> {
+pub fn copy_save(
+    cart_id: &str,
+    from_drive: &str,
+    to_drive: &str,
+    progress: Arc<AtomicU16>,
+) -> Result<(), SaveError> {
+    let _write_guard = SaveWriteGuard::new();
     let from_dir = get_save_dir_from_drive_name(from_drive);
     let to_dir = get_save_dir_from_drive_name(to_drive);
     let from_cache = get_cache_dir_from_drive_name(from_drive);
@@ -1124,24 +1980,50 @@ pub fn copy_save(cart_id: &str, from_drive: &str, to_drive: &str, progress: Arc<
 
     if from_drive == to_drive {
         //return Err("Cannot copy to same location".to_string());
-        return Err(SaveError::Message("Cannot copy to same location".to_string()));
+        return Err(SaveError::Message(
+            "Cannot copy to same location".to_string(),
+        ));
     }
 
     // Check if source save exists
     let from_path = Path::new(&from_dir).join(cart_id);
-    let from_path_tar = Path::new(&from_dir).join(format!("{}.tar", cart_id));
-    if !from_path.exists() && !from_path_tar.exists() {
+    let from_archive = find_archive(&from_dir, cart_id);
+    if !from_path.exists() && from_archive.is_none() {
         //return Err(format!("Save file for {} does not exist on '{}' drive", cart_id, from_drive));
-        return Err(SaveError::Message(format!("Save file for {} does not exist on '{}' drive", cart_id, from_drive)));
+        return Err(SaveError::Message(format!(
+            "Save file for {} does not exist on '{}' drive",
+            cart_id, from_drive
+        )));
     }
+    let from_path_tar = from_archive
+        .unwrap_or_else(|| Path::new(&from_dir).join(format!("{}.{}", cart_id, TAR_EXT)));
 
     // Check if destination save already exists
     let to_path = Path::new(&to_dir).join(cart_id);
-    let to_path_tar = Path::new(&to_dir).join(format!("{}.tar", cart_id));
-    if to_path.exists() || to_path_tar.exists() {
-        return Err(SaveError::Message(format!("Save file for {} already exists on '{}'", cart_id, to_drive)));
+    if to_path.exists() || find_archive(&to_dir, cart_id).is_some() {
+        return Err(SaveError::Message(format!(
+            "Save file for {} already exists on '{}'",
+            cart_id, to_drive
+        )));
     }
 
+    // New archives follow the compression setting and the destination's
+    // encryption; external copies keep their format
+    let to_ext = if from_drive == "internal" {
+        match (
+            COMPRESS_ARCHIVES.load(Ordering::SeqCst),
+            is_drive_encrypted(to_drive),
+        ) {
+            (true, true) => TAR_ZST_ENC_EXT,
+            (true, false) => TAR_ZST_EXT,
+            (false, true) => TAR_ENC_EXT,
+            (false, false) => TAR_EXT,
+        }
+    } else {
+        archive_ext(&from_path_tar)
+    };
+    let to_path_tar = Path::new(&to_dir).join(format!("{}.{}", cart_id, to_ext));
+
     // Create destination directories
     fs::create_dir_all(&to_dir)?;
     fs::create_dir_all(&to_cache)?;
@@ -1150,7 +2032,8 @@ pub fn copy_save(cart_id: &str, from_drive: &str, to_drive: &str, progress: Arc<
     let result = if from_drive == "internal" {
         // Internal to external: create tar archive
         eprintln!("Starting internal to external copy for {}", cart_id);
-        let file = fs::File::create(&to_path_tar).map_err(|e| format!("Failed to create destination file: {}", e))?;
+        let file = ArchiveWriter::create(&to_path_tar)
+            .map_err(|e| format!("Failed to create destination file: {}", e))?;
         let mut builder = Builder::new(file);
 
         // Calculate total size for progress reporting
@@ -1161,100 +2044,135 @@ pub fn copy_save(cart_id: &str, from_drive: &str, to_drive: &str, progress: Arc<
             .filter(|e| {
                 let path = e.path();
                 // Skip excluded directories and their contents
-                !should_exclude_path(path) &&
-                path.is_file()
-            }) {
-                total_size += entry.metadata().map_err(|e| format!("Failed to get metadata: {}", e))?.len();
-            }
-
-            eprintln!("Total size to archive: {} bytes", total_size);
-            if total_size == 0 {
-                return Err(SaveError::Message("No files found to archive".to_string()));
-            }
+                !should_exclude_path(path) && path.is_file()
+            })
+        {
+            total_size += entry
+                .metadata()
+                .map_err(|e| format!("Failed to get metadata: {}", e))?
+                .len();
+        }
 
-            // Add the entire directory to the archive, excluding ignored directories
-            let mut current_size = 0;
-            for entry in walkdir::WalkDir::new(&from_path)
-                .into_iter()
-                .filter_map(|e| e.ok())
-                .filter(|e| {
-                    let path = e.path();
-                    // Skip excluded directories and their contents
-                    !should_exclude_path(path) &&
-                    path.is_file()
-                }) {
-                    let path = entry.path();
-                    // Get the relative path from the source directory
-                    let name = path.strip_prefix(&from_path)
-                    .map_err(|e| format!("Failed to get relative path: {}", e))?
-                    .to_str()
-                    .ok_or_else(|| "Invalid path encoding".to_string())?;
-
-                    let file_size = entry.metadata().map_err(|e| format!("Failed to get file metadata: {}", e))?.len();
-                    eprintln!("Adding file to archive: {} ({} bytes)", name, file_size);
-
-                    let mut file = fs::File::open(path).map_err(|e| format!("Failed to open source file: {}", e))?;
-
-                    // Create a new header with the correct path
-                    let mut header = tar::Header::new_gnu();
-                    header.set_path(name).map_err(|e| format!("Failed to set path in header: {}", e))?;
-                    header.set_size(file_size);
-                    header.set_cksum();
-
-                    // Write the header and file contents
-                    builder.append(&header, &mut file).map_err(|e| format!("Failed to append file to archive: {}", e))?;
-                    sync_to_disk();
+        eprintln!("Total size to archive: {} bytes", total_size);
+        if total_size == 0 {
+            return Err(SaveError::Message("No files found to archive".to_string()));
+        }
 
-                    current_size += file_size;
-                    progress.store((current_size * 100 / total_size) as u16, Ordering::SeqCst);
-                }
+        // Add the entire directory to the archive, excluding ignored directories
+        let mut current_size = 0;
+        for entry in walkdir::WalkDir::new(&from_path)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| {
+                let path = e.path();
+                // Skip excluded directories and their contents
+                !should_exclude_path(path) && path.is_file()
+            })
+        {
+            let path = entry.path();
+            // Get the relative path from the source directory
+            let name = path
+                .strip_prefix(&from_path)
+                .map_err(|e| format!("Failed to get relative path: {}", e))?
+                .to_str()
+                .ok_or_else(|| "Invalid path encoding".to_string())?;
+
+            let file_size = entry
+                .metadata()
+                .map_err(|e| format!("Failed to get file metadata: {}", e))?
+                .len();
+            eprintln!("Adding file to archive: {} ({} bytes)", name, file_size);
+
+            let mut file =
+                fs::File::open(path).map_err(|e| format!("Failed to open source file: {}", e))?;
+
+            // Create a new header with the correct path
+            let mut header = tar::Header::new_gnu();
+            header
+                .set_path(name)
+                .map_err(|e| format!("Failed to set path in header: {}", e))?;
+            header.set_size(file_size);
+            header.set_cksum();
+
+            // Write the header and file contents
+            builder
+                .append(&header, &mut file)
+                .map_err(|e| format!("Failed to append file to archive: {}", e))?;
+            sync_to_disk();
 
-                eprintln!("Finished creating archive, final size: {} bytes", current_size);
-                if current_size == 0 {
-                    return Err(SaveError::Message("No files were added to the archive".to_string()));
-                }
+            current_size += file_size;
+            progress.store((current_size * 100 / total_size) as u16, Ordering::SeqCst);
+        }
 
-                builder.finish().map_err(|e| format!("Failed to finish archive: {}", e))?;
-                sync_to_disk();
+        eprintln!(
+            "Finished creating archive, final size: {} bytes",
+            current_size
+        );
+        if current_size == 0 {
+            return Err(SaveError::Message(
+                "No files were added to the archive".to_string(),
+            ));
+        }
 
-                // Verify the archive was created and has content
-                let archive_size = fs::metadata(&to_path_tar).map_err(|e| format!("Failed to get archive metadata: {}", e))?.len();
-                eprintln!("Archive file size: {} bytes", archive_size);
-                if archive_size == 0 {
-                    return Err(SaveError::Message("Created archive is empty".to_string()));
-                }
+        builder
+            .into_inner()
+            .and_then(|writer| writer.finish())
+            .map_err(|e| format!("Failed to finish archive: {}", e))?;
+        sync_to_disk();
+
+        // Verify the archive was created and has content
+        let archive_size = fs::metadata(&to_path_tar)
+            .map_err(|e| format!("Failed to get archive metadata: {}", e))?
+            .len();
+        eprintln!("Archive file size: {} bytes", archive_size);
+        if archive_size == 0 {
+            return Err(SaveError::Message("Created archive is empty".to_string()));
+        }
 
-                Ok(())
+        Ok(())
     } else if to_drive == "internal" {
         // External to internal: extract tar archive
         eprintln!("Starting external to internal copy for {}", cart_id);
-        fs::create_dir_all(&to_path).map_err(|e| format!("Failed to create destination directory: {}", e))?;
+        fs::create_dir_all(&to_path)
+            .map_err(|e| format!("Failed to create destination directory: {}", e))?;
 
-        let file = fs::File::open(&from_path_tar).map_err(|e| format!("Failed to open source archive: {}", e))?;
-        let file_size = file.metadata().map_err(|e| format!("Failed to get archive metadata: {}", e))?.len();
+        let file_size = fs::metadata(&from_path_tar)
+            .map_err(|e| format!("Failed to get archive metadata: {}", e))?
+            .len();
         eprintln!("Archive size: {} bytes", file_size);
 
-        let mut archive = Archive::new(file);
-        let mut current_size = 0;
+        // Progress follows bytes read from disk, which works for compressed archives too
+        let bytes_read = Arc::new(AtomicU64::new(0));
+        let mut archive = open_archive(&from_path_tar, bytes_read.clone())
+            .map_err(|e| format!("Failed to open source archive: {}", e))?;
 
-        for entry in archive.entries().map_err(|e| format!("Failed to read archive entries: {}", e))? {
+        for entry in archive
+            .entries()
+            .map_err(|e| format!("Failed to read archive entries: {}", e))?
+        {
             let mut entry = entry.map_err(|e| format!("Failed to read archive entry: {}", e))?;
-            let path = entry.path().map_err(|e| format!("Failed to get entry path: {}", e))?;
+            let path = entry
+                .path()
+                .map_err(|e| format!("Failed to get entry path: {}", e))?;
             let entry_size = entry.header().size().unwrap_or(0);
             eprintln!("Extracting: {} ({} bytes)", path.display(), entry_size);
 
             // Ensure the parent directory exists
             if let Some(parent) = path.parent() {
                 fs::create_dir_all(to_path.join(parent))
-                .map_err(|e| format!("Failed to create parent directory: {}", e))?;
+                    .map_err(|e| format!("Failed to create parent directory: {}", e))?;
             }
 
             // Extract the file
-            entry.unpack_in(&to_path)
-            .map_err(|e| format!("Failed to extract file: {}", e))?;
-
-            current_size += entry_size;
-            progress.store((current_size * 100 / file_size) as u16, Ordering::SeqCst);
+            entry
+                .unpack_in(&to_path)
+                .map_err(|e| format!("Failed to extract file: {}", e))?;
+
+            let current_size = bytes_read.load(Ordering::SeqCst).min(file_size);
+            progress.store(
+                (current_size * 100 / file_size.max(1)) as u16,
+                Ordering::SeqCst,
+            );
         }
 
         // Verify extraction
@@ -1262,15 +2180,19 @@ pub fn copy_save(cart_id: &str, from_drive: &str, to_drive: &str, progress: Arc<
         for entry in walkdir::WalkDir::new(&to_path)
             .into_iter()
             .filter_map(|e| e.ok())
-            .filter(|e| e.path().is_file()) {
-                extracted_size += entry.metadata()
+            .filter(|e| e.path().is_file())
+        {
+            extracted_size += entry
+                .metadata()
                 .map_err(|e| format!("Failed to get extracted file metadata: {}", e))?
                 .len();
-            }
-            eprintln!("Total extracted size: {} bytes", extracted_size);
+        }
+        eprintln!("Total extracted size: {} bytes", extracted_size);
 
         if extracted_size == 0 {
-            return Err(SaveError::Message("No files were extracted from the archive".to_string()));
+            return Err(SaveError::Message(
+                "No files were extracted from the archive".to_string(),
+            ));
         }
 
         Ok(())
@@ -1331,39 +2253,457 @@ pub fn copy_save(cart_id: &str, from_drive: &str, to_drive: &str, progress: Arc<
     Ok(())
 }
 
-/// Calculate total playtime for a game from its .kazeta/var/playtime.log file
-/// Returns playtime in hours with one decimal place
-pub fn calculate_playtime(cart_id: &str, drive_name: &str) -> f32 {
-    println!("Calculating playtime for {} on {}", cart_id, drive_name);
+/// Copy a save, resolving an existing destination save according to `resolution`.
+/// With `None` this behaves exactly like `copy_save` and refuses to overwrite.
+pub fn copy_save_with_resolution(
+    cart_id: &str,
+    from_drive: &str,
+    to_drive: &str,
+    progress: Arc<AtomicU16>,
+    resolution: Option<ConflictResolution>,
+) -> Result<(), SaveError> {
+    let _write_guard = SaveWriteGuard::new();
+    if from_drive == to_drive || !save_exists(cart_id, to_drive) {
+        return copy_save(cart_id, from_drive, to_drive, progress);
+    }
+
+    match resolution {
+        None => copy_save(cart_id, from_drive, to_drive, progress),
+        Some(ConflictResolution::KeepBoth) => {
+            let backup_id = unused_save_id(
+                &format!("{}-{}", cart_id, Local::now().format("%Y%m%d-%H%M%S")),
+                to_drive,
+            );
+            eprintln!(
+                "Keeping both saves: renaming existing {} on '{}' to {}",
+                cart_id, to_drive, backup_id
+            );
+            rename_save(cart_id, &backup_id, to_drive)?;
+            copy_save(cart_id, from_drive, to_drive, progress)
+        }
+        Some(ConflictResolution::Overwrite) => {
+            // Move the old save aside so a failed copy can be rolled back. A
+            // stash left by an interrupted overwrite is kept, not replaced.
+            let stash_id = unused_save_id(&format!(".{}.overwrite", cart_id), to_drive);
+            eprintln!(
+                "Overwriting {} on '{}' (old save stashed as {})",
+                cart_id, to_drive, stash_id
+            );
+            rename_save(cart_id, &stash_id, to_drive)?;
+
+            match copy_save(cart_id, from_drive, to_drive, progress) {
+                Ok(()) => {
+                    if let Err(e) = delete_save(&stash_id, to_drive) {
+                        eprintln!("Failed to remove stashed save {}: {}", stash_id, e);
+                    }
+                    sync_to_disk();
+                    Ok(())
+                }
+                Err(e) => {
+                    eprintln!("Copy failed, restoring previous save for {}", cart_id);
+                    delete_save(cart_id, to_drive).ok();
+                    rename_save(&stash_id, cart_id, to_drive)?;
+                    Err(e)
+                }
+            }
+        }
+    }
+}
+
+/// Recompress every plain .tar save on an external drive as .tar.zst.
+/// Progress covers the combined size of the archives being converted.
+/// Returns the number of archives compressed.
+pub fn compress_archives(drive_name: &str, progress: Arc<AtomicU16>) -> Result<usize, SaveError> {
+    let _write_guard = SaveWriteGuard::new();
+    if drive_name == "internal" {
+        return Err(SaveError::Message(
+            "Internal saves are not stored as archives".to_string(),
+        ));
+    }
+
     let save_dir = get_save_dir_from_drive_name(drive_name);
+    eprintln!("Compressing archives on '{}'", drive_name);
+    compress_archives_in(&save_dir, progress)
+}
 
-    // Check if this is a tar file (external drive) or directory (internal drive)
-    let tar_path = Path::new(&save_dir).join(format!("{}.tar", cart_id));
+/// Recompress the plain .tar archives in `save_dir`, see `compress_archives`
+fn compress_archives_in(save_dir: &str, progress: Arc<AtomicU16>) -> Result<usize, SaveError> {
+    let mut archives = Vec::new();
+    for entry in fs::read_dir(save_dir)? {
+        let path = entry?.path();
+        let Some(file_name) = path.file_name().and_then(|n| n.to_str()) else {
+            continue;
+        };
+        let Some(cart_id) = file_name.strip_suffix(".tar") else {
+            continue;
+        };
+        if find_archive(save_dir, cart_id).is_some_and(|p| is_compressed_archive(&p)) {
+            eprintln!(
+                "Skipping {}: a compressed archive already exists",
+                file_name
+            );
+            continue;
+        }
+        let size = fs::metadata(&path)?.len();
+        let cart_id = cart_id.to_string();
+        archives.push((path, cart_id, size));
+    }
+
+    let total_size = archives.iter().map(|(_, _, size)| size).sum::<u64>().max(1);
+    eprintln!(
+        "Compressing {} archives ({} bytes)",
+        archives.len(),
+        total_size
+    );
+
+    let mut done_size = 0;
+    for (path, cart_id, size) in &archives {
+        let target = Path::new(save_dir).join(format!("{}.{}", cart_id, TAR_ZST_EXT));
+        // Write to a hidden file first so a pulled drive never leaves a half archive in place
+        let partial = Path::new(save_dir).join(format!(".{}.{}.partial", cart_id, TAR_ZST_EXT));
+
+        let result = (|| -> io::Result<()> {
+            let mut source = fs::File::open(path)?;
+            let mut dest = ArchiveWriter::Zstd(zstd::Encoder::new(
+                ArchiveSink::File(fs::File::create(&partial)?),
+                ZSTD_LEVEL,
+            )?);
+            let mut buffer = [0; 64 * 1024];
+            let mut copied = 0;
+            loop {
+                let bytes_read = source.read(&mut buffer)?;
+                if bytes_read == 0 {
+                    break;
+                }
+                dest.write_all(&buffer[..bytes_read])?;
+                copied += bytes_read as u64;
+                progress.store(
+                    ((done_size + copied) * 100 / total_size) as u16,
+                    Ordering::SeqCst,
+                );
+            }
+            dest.finish()
+        })();
+
+        if let Err(e) = result {
+            fs::remove_file(&partial).ok();
+            return Err(SaveError::Message(format!(
+                "Failed to compress {}: {}",
+                cart_id, e
+            )));
+        }
+
+        fs::rename(&partial, &target)?;
+        fs::remove_file(path)?;
+        sync_to_disk();
+
+        let compressed_size = fs::metadata(&target).map(|m| m.len()).unwrap_or(0);
+        eprintln!(
+            "Compressed {}: {} -> {} bytes",
+            cart_id, size, compressed_size
+        );
+        done_size += size;
+    }
+
+    progress.store(100, Ordering::SeqCst);
+    Ok(archives.len())
+}
+
+/// Archive every save on a drive, with its cached metadata, into a single
+/// timestamped .tar.zst in `output_dir`. Internal saves are stored as
+/// directories and external ones as the archives found on the drive.
+/// Returns the path of the backup.
+pub fn backup_saves(drive_name: &str, output_dir: &Path) -> Result<PathBuf, SaveError> {
+    let save_dir = get_save_dir_from_drive_name(drive_name);
+    let cache_dir = get_cache_dir_from_drive_name(drive_name);
+    fs::create_dir_all(output_dir)?;
+
+    let file_name = format!(
+        "kazeta-saves-{}-{}.{}",
+        drive_name,
+        Local::now().format("%Y%m%d-%H%M%S"),
+        TAR_ZST_EXT
+    );
+    let backup_path = output_dir.join(&file_name);
+    // Written under a hidden name first so an interrupted backup never looks complete
+    let partial = output_dir.join(format!(".{}.partial", file_name));
+
+    let result = (|| -> Result<usize, SaveError> {
+        let writer = ArchiveWriter::Zstd(zstd::Encoder::new(
+            ArchiveSink::File(fs::File::create(&partial)?),
+            ZSTD_LEVEL,
+        )?);
+        let mut builder = Builder::new(writer);
+        let mut files = 0;
+
+        for (root, prefix) in [(&save_dir, "saves"), (&cache_dir, "cache")] {
+            for entry in walkdir::WalkDir::new(root)
+                .into_iter()
+                .filter_map(|e| e.ok())
+                .filter(|e| !should_exclude_path(e.path()) && e.path().is_file())
+            {
+                let name = Path::new(prefix).join(entry.path().strip_prefix(root)?);
+                builder.append_path_with_name(entry.path(), &name)?;
+                files += 1;
+            }
+        }
+
+        builder.into_inner()?.finish()?;
+        Ok(files)
+    })();
+
+    match result {
+        Ok(files) => {
+            fs::rename(&partial, &backup_path)?;
+            sync_to_disk();
+            eprintln!(
+                "Backed up {} files from '{}' to {}",
+                files,
+                drive_name,
+                backup_path.display()
+            );
+            Ok(backup_path)
+        }
+        Err(e) => {
+            fs::remove_file(&partial).ok();
+            Err(e)
+        }
+    }
+}
+
+/// Files of an internal save, relative to its directory, with their size and
+/// modification time in seconds. Skips the same paths copies do.
+pub fn internal_save_files(cart_id: &str) -> Result<Vec<(String, u64, u64)>, SaveError> {
+    let root = Path::new(&get_save_dir_from_drive_name("internal")).join(cart_id);
+    let mut files = Vec::new();
+    for entry in walkdir::WalkDir::new(&root)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| !should_exclude_path(e.path()) && e.path().is_file())
+    {
+        let metadata = entry.metadata()?;
+        let mtime = metadata
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(SystemTime::UNIX_EPOCH).ok())
+            .map_or(0, |d| d.as_secs());
+        let name = entry
+            .path()
+            .strip_prefix(&root)?
+            .to_string_lossy()
+            .into_owned();
+        files.push((name, metadata.len(), mtime));
+    }
+    Ok(files)
+}
+
+/// Archive one internal save, with its cached metadata, into `output` as a
+/// .tar.zst laid out like `backup_saves` (`saves/<cart_id>`, `cache/<cart_id>`).
+/// An `output` ending in .enc is encrypted.
+pub fn snapshot_internal_save(cart_id: &str, output: &Path) -> Result<(), SaveError> {
+    let save_dir = Path::new(&get_save_dir_from_drive_name("internal")).join(cart_id);
+    let cache_dir = Path::new(&get_cache_dir_from_drive_name("internal")).join(cart_id);
+    if let Some(parent) = output.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let file_name = output
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("snapshot");
+    let partial = output.with_file_name(format!(".{}.partial", file_name));
+
+    let result = (|| -> Result<(), SaveError> {
+        // The partial name hides the suffix, so pick the sink from `output`
+        let sink = if save_crypto::is_encrypted_name(output) {
+            ArchiveSink::Encrypted {
+                file: fs::File::create(&partial)?,
+                buffer: Vec::new(),
+            }
+        } else {
+            ArchiveSink::File(fs::File::create(&partial)?)
+        };
+        let writer = ArchiveWriter::Zstd(zstd::Encoder::new(sink, ZSTD_LEVEL)?);
+        let mut builder = Builder::new(writer);
+        for (root, prefix) in [(&save_dir, "saves"), (&cache_dir, "cache")] {
+            if !root.exists() {
+                continue;
+            }
+            for entry in walkdir::WalkDir::new(root)
+                .into_iter()
+                .filter_map(|e| e.ok())
+                .filter(|e| !should_exclude_path(e.path()) && e.path().is_file())
+            {
+                let name = Path::new(prefix)
+                    .join(cart_id)
+                    .join(entry.path().strip_prefix(root)?);
+                builder.append_path_with_name(entry.path(), &name)?;
+            }
+        }
+        builder.into_inner()?.finish()?;
+        Ok(())
+    })();
+
+    match result {
+        Ok(()) => {
+            fs::rename(&partial, output)?;
+            Ok(())
+        }
+        Err(e) => {
+            fs::remove_file(&partial).ok();
+            Err(e)
+        }
+    }
+}
+
+/// Check whether a save for `cart_id` exists on the given drive
+pub fn save_exists(cart_id: &str, drive_name: &str) -> bool {
+    let save_dir = get_save_dir_from_drive_name(drive_name);
+    Path::new(&save_dir).join(cart_id).exists() || find_archive(&save_dir, cart_id).is_some()
+}
+
+/// `id`, or `id-2`, `id-3`... if a save by that name is already on the drive
+fn unused_save_id(id: &str, drive_name: &str) -> String {
+    (1..)
+        .map(|n| {
+            if n == 1 {
+                id.to_string()
+            } else {
+                format!("{}-{}", id, n)
+            }
+        })
+        .find(|candidate| !save_exists(candidate, drive_name))
+        .unwrap()
+}
+
+/// Rename a save (and its cache entry) on a drive
+fn rename_save(cart_id: &str, new_id: &str, drive_name: &str) -> Result<(), SaveError> {
+    let save_dir = get_save_dir_from_drive_name(drive_name);
+    let cache_dir = get_cache_dir_from_drive_name(drive_name);
+
+    // A rename would silently replace an archive of the same name
+    if save_exists(new_id, drive_name) {
+        return Err(SaveError::Message(format!(
+            "Save file for {} already exists on '{}'",
+            new_id, drive_name
+        )));
+    }
+
+    let dir_path = Path::new(&save_dir).join(cart_id);
+    if dir_path.exists() {
+        fs::rename(&dir_path, Path::new(&save_dir).join(new_id))?;
+    } else if let Some(tar_path) = find_archive(&save_dir, cart_id) {
+        let ext = archive_ext(&tar_path);
+        fs::rename(
+            &tar_path,
+            Path::new(&save_dir).join(format!("{}.{}", new_id, ext)),
+        )?;
+    } else {
+        return Err(SaveError::Message(format!(
+            "Save file for {} does not exist on '{}' drive",
+            cart_id, drive_name
+        )));
+    }
+
+    let cache_path = Path::new(&cache_dir).join(cart_id);
+    if cache_path.exists() {
+        let new_cache_path = Path::new(&cache_dir).join(new_id);
+        fs::remove_dir_all(&new_cache_path).ok();
+        fs::rename(&cache_path, &new_cache_path)?;
+    }
+
+    sync_to_disk();
+    Ok(())
+}
+
+/// Gather last-modified, size and playtime for a save so two copies can be compared
+pub fn get_save_summary(cart_id: &str, drive_name: &str) -> Option<SaveSummary> {
+    let save_dir = get_save_dir_from_drive_name(drive_name);
+    let tar_path = find_archive(&save_dir, cart_id);
     let dir_path = Path::new(&save_dir).join(cart_id);
 
-    if tar_path.exists() {
-        // External drive: read from tar archive
-        calculate_playtime_from_tar(&tar_path, cart_id)
+    let modified = if let Some(tar_path) = &tar_path {
+        fs::metadata(tar_path).and_then(|m| m.modified()).ok()
     } else if dir_path.exists() {
-        // Internal drive: read from directory
-        calculate_playtime_from_dir(&dir_path, cart_id)
+        // A directory's own mtime doesn't change when nested files do, so use the newest file
+        walkdir::WalkDir::new(&dir_path)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| !should_exclude_path(e.path()) && e.path().is_file())
+            .filter_map(|e| e.metadata().ok().and_then(|m| m.modified().ok()))
+            .max()
     } else {
-        // Neither exists
-        0.0
+        return None;
+    };
+
+    Some(SaveSummary {
+        drive_name: drive_name.to_string(),
+        modified,
+        size_mb: calculate_save_size(cart_id, drive_name),
+        playtime_hours: calculate_playtime(cart_id, drive_name),
+    })
+}
+
+/// Calculate total playtime for a game from its .kazeta/var/playtime.log file
+/// Returns playtime in hours with one decimal place
+pub fn calculate_playtime(cart_id: &str, drive_name: &str) -> f32 {
+    eprintln!("Calculating playtime for {} on {}", cart_id, drive_name);
+    scan_save(cart_id, drive_name, false).map_or(0.0, |scan| {
+        parse_playtime_content(&scan.playtime_content()).0
+    })
+}
+
+/// Last played time, playtime, size and newest screenshot of a save, reading
+/// the save once for all but the size. Slow for big archives, so callers
+/// cache it (see `memory::SaveMetadataCache`).
+pub fn get_save_metadata(cart_id: &str, drive_name: &str) -> SaveMetadata {
+    eprintln!("Reading save metadata for {} on {}", cart_id, drive_name);
+    let Some(scan) = scan_save(cart_id, drive_name, true) else {
+        return SaveMetadata::default();
+    };
+    let (playtime_hours, last_played) = parse_playtime_content(&scan.playtime_content());
+    let thumbnail = match scan.screenshot {
+        Some((_, Screenshot::File(path))) => Some(path),
+        Some((_, Screenshot::Archived(data))) => extract_thumbnail(cart_id, drive_name, &data),
+        None => None,
+    };
+
+    SaveMetadata {
+        last_played: last_played.map(|t| t.with_timezone(&Local)),
+        playtime_hours,
+        size_mb: calculate_save_size(cart_id, drive_name),
+        thumbnail,
+    }
+}
+
+/// Write a screenshot read out of an archive to the user data dir so the UI
+/// can load it, leaving the drive untouched
+fn extract_thumbnail(cart_id: &str, drive_name: &str, data: &[u8]) -> Option<PathBuf> {
+    let dir = get_user_data_dir()?.join("save_thumbnails");
+    if let Err(e) = fs::create_dir_all(&dir) {
+        eprintln!("Failed to create {}: {}", dir.display(), e);
+        return None;
+    }
+    let path = dir.join(format!("{}-{}.png", drive_name.replace('/', "_"), cart_id));
+    match fs::write(&path, data) {
+        Ok(()) => Some(path),
+        Err(e) => {
+            eprintln!("Failed to write thumbnail {}: {}", path.display(), e);
+            None
+        }
     }
 }
 
 /// Calculate save data size for a game (lazy calculation)
 /// Returns size in MB with one decimal place
 pub fn calculate_save_size(cart_id: &str, drive_name: &str) -> f32 {
-    println!("Calculating save size for {} on {}", cart_id, drive_name);
+    eprintln!("Calculating save size for {} on {}", cart_id, drive_name);
     let save_dir = get_save_dir_from_drive_name(drive_name);
 
     // Check if this is a tar file (external drive) or directory (internal drive)
-    let tar_path = Path::new(&save_dir).join(format!("{}.tar", cart_id));
+    let tar_path = find_archive(&save_dir, cart_id);
     let dir_path = Path::new(&save_dir).join(cart_id);
 
-    let size_bytes = if tar_path.exists() {
+    let size_bytes = if let Some(tar_path) = tar_path {
         // External drive: get tar file size
         calculate_size_from_tar(&tar_path)
     } else if dir_path.exists() {
@@ -1385,13 +2725,13 @@ pub fn calculate_save_size(cart_id: &str, drive_name: &str) -> f32 {
 
 /// Setup RetroAchievements for a game launch (called from launch_game_with_options)
 fn setup_retroachievements_for_launch(cart_info: &CartInfo, kzi_path: &Path) {
-    // Check if kazeta-ra is available
-    if Command::new("kazeta-ra").arg("status").output().is_err() {
-        return;
-    }
-
-    // Check if RA is configured and enabled
-    let status_output = match Command::new("kazeta-ra").arg("status").output() {
+    // Check if RA is configured and enabled. --offline trusts the stored
+    // credentials instead of asking the server, which can take seconds;
+    // the RetroAchievements settings screen checks with the server.
+    let status_output = match Command::new("kazeta-ra")
+        .args(["status", "--offline"])
+        .output()
+    {
         Ok(output) => output,
         Err(_) => return,
     };
@@ -1413,28 +2753,337 @@ fn setup_retroachievements_for_launch(cart_info: &CartInfo, kzi_path: &Path) {
     };
 
     if rom_path.exists() {
-        println!("[RA] Setting up RetroAchievements for: {}", rom_path.display());
+        println!(
+            "[RA] Setting up RetroAchievements for: {}",
+            rom_path.display()
+        );
 
         // Call kazeta-ra game-start (run in background)
         let rom_path_str = rom_path.to_string_lossy().to_string();
+        // The cart's own name covers games RetroAchievements doesn't know
+        let kzi_path_buf = kzi_path.to_path_buf();
+        let name_hint = cart_info
+            .ra_game_name
+            .clone()
+            .filter(|n| !n.trim().is_empty());
         std::thread::spawn(move || {
-            let _ = Command::new("kazeta-ra")
+            let mut command = Command::new("kazeta-ra");
+            command
                 .arg("game-start")
                 .arg("--path")
                 .arg(&rom_path_str)
-                .arg("--notify-overlay")
-                .output();
+                .arg("--cart")
+                .arg(&kzi_path_buf)
+                .arg("--notify-overlay");
+            if let Some(hint) = &name_hint {
+                command.arg("--name-hint").arg(hint);
+            }
+            let _ = command.output();
         });
 
         // Also send achievement list to overlay (run in background)
         let rom_path_str2 = rom_path.to_string_lossy().to_string();
         std::thread::spawn(move || {
             std::thread::sleep(std::time::Duration::from_millis(500));
-            let _ = Command::new("kazeta-ra")
-                .arg("send-achievements-to-overlay")
-                .arg("--path")
-                .arg(&rom_path_str2)
-                .output();
+            crate::utils::send_achievements_to_overlay(&rom_path_str2);
         });
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Write an archive holding `files`, compressed according to the name
+    fn write_archive(path: &Path, files: &[(&str, &[u8])]) {
+        let mut builder = Builder::new(ArchiveWriter::create(path).unwrap());
+        for (name, data) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(data.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append_data(&mut header, name, *data).unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap();
+    }
+
+    /// Names and contents of every file in an archive, read back through `open_archive`
+    fn read_archive(path: &Path) -> Vec<(String, Vec<u8>)> {
+        let bytes_read = Arc::new(AtomicU64::new(0));
+        let mut archive = open_archive(path, bytes_read.clone()).unwrap();
+        let files = archive
+            .entries()
+            .unwrap()
+            .map(|entry| {
+                let mut entry = entry.unwrap();
+                let mut data = Vec::new();
+                entry.read_to_end(&mut data).unwrap();
+                (entry.path().unwrap().to_string_lossy().into_owned(), data)
+            })
+            .collect();
+        assert!(
+            bytes_read.load(Ordering::SeqCst) > 0,
+            "progress counts file bytes"
+        );
+        files
+    }
+
+    fn sample_files() -> Vec<(&'static str, &'static [u8])> {
+        vec![
+            ("save.dat", b"slot one".as_slice()),
+            (".kazeta/var/playtime.txt", b"3.5".as_slice()),
+        ]
+    }
+
+    fn expected(files: &[(&str, &[u8])]) -> Vec<(String, Vec<u8>)> {
+        files
+            .iter()
+            .map(|(name, data)| (name.to_string(), data.to_vec()))
+            .collect()
+    }
+
+    #[test]
+    fn test_tar_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("celeste.tar");
+        write_archive(&path, &sample_files());
+        assert!(!is_compressed_archive(&path));
+        assert_eq!(read_archive(&path), expected(&sample_files()));
+    }
+
+    #[test]
+    fn test_tar_zst_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("celeste.tar.zst");
+        let data = b"compressible ".repeat(1000);
+        let files = [("save.dat", data.as_slice())];
+        write_archive(&path, &files);
+        assert!(is_compressed_archive(&path));
+
+        let raw = fs::read(&path).unwrap();
+        assert_eq!(&raw[..4], &[0x28, 0xb5, 0x2f, 0xfd], "zstd frame magic");
+        assert!((raw.len() as u64) < data.len() as u64);
+        assert_eq!(read_archive(&path), expected(&files));
+    }
+
+    #[test]
+    fn test_find_archive_precedence() {
+        let dir = tempfile::tempdir().unwrap();
+        let save_dir = dir.path().to_str().unwrap();
+        assert_eq!(find_archive(save_dir, "celeste"), None);
+
+        // Each newly added format wins over the ones before it
+        for ext in ARCHIVE_EXTS.iter().rev() {
+            fs::write(dir.path().join(format!("celeste.{}", ext)), "").unwrap();
+            assert_eq!(
+                find_archive(save_dir, "celeste"),
+                Some(dir.path().join(format!("celeste.{}", ext)))
+            );
+        }
+        assert_eq!(find_archive(save_dir, "cele"), None);
+
+        assert_eq!(
+            archive_ext(Path::new("celeste.tar.zst.enc")),
+            TAR_ZST_ENC_EXT
+        );
+        assert_eq!(cart_id_from_file_name("celeste.tar.zst"), "celeste");
+        assert_eq!(cart_id_from_file_name("celeste"), "celeste");
+    }
+
+    #[test]
+    fn test_compress_archives() {
+        let dir = tempfile::tempdir().unwrap();
+        let save_dir = dir.path().to_str().unwrap();
+        write_archive(&dir.path().join("celeste.tar"), &sample_files());
+        write_archive(
+            &dir.path().join("hollow.tar"),
+            &[("save.dat", b"knight".as_slice())],
+        );
+        // Already has a compressed copy; left alone
+        write_archive(
+            &dir.path().join("ori.tar"),
+            &[("old.dat", b"old".as_slice())],
+        );
+        write_archive(
+            &dir.path().join("ori.tar.zst"),
+            &[("new.dat", b"new".as_slice())],
+        );
+
+        let progress = Arc::new(AtomicU16::new(0));
+        assert_eq!(compress_archives_in(save_dir, progress.clone()).unwrap(), 2);
+        assert_eq!(progress.load(Ordering::SeqCst), 100);
+
+        let mut names: Vec<String> = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        assert_eq!(
+            names,
+            [
+                "celeste.tar.zst",
+                "hollow.tar.zst",
+                "ori.tar",
+                "ori.tar.zst"
+            ],
+            "no .partial files left"
+        );
+
+        assert_eq!(
+            read_archive(&dir.path().join("celeste.tar.zst")),
+            expected(&sample_files())
+        );
+        assert_eq!(
+            read_archive(&dir.path().join("ori.tar.zst")),
+            expected(&[("new.dat", b"new".as_slice())])
+        );
+    }
+
+    /// Drives under a temporary media root; the only test that uses external
+    /// drive names, since the root is process-wide
+    #[test]
+    fn test_copy_save_with_resolution() {
+        let media = tempfile::tempdir().unwrap();
+        std::env::set_var("KAZETA_MEDIA_ROOT", media.path());
+        let saves = |drive: &str| media.path().join(drive).join("kazeta/saves");
+        let cache = |drive: &str| media.path().join(drive).join("kazeta/cache");
+        let names = |drive: &str| -> Vec<String> {
+            let mut names: Vec<String> = fs::read_dir(saves(drive))
+                .unwrap()
+                .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+                .collect();
+            names.sort();
+            names
+        };
+        let copy = |resolution| {
+            copy_save_with_resolution(
+                "celeste",
+                "SOURCE",
+                "DEST",
+                Arc::new(AtomicU16::new(0)),
+                Some(resolution),
+            )
+        };
+        let content = |path: PathBuf| {
+            read_archive(&path)
+                .into_iter()
+                .map(|(_, data)| data)
+                .collect::<Vec<_>>()
+        };
+
+        for drive in ["SOURCE", "DEST"] {
+            fs::create_dir_all(saves(drive)).unwrap();
+            fs::create_dir_all(cache(drive).join("celeste")).unwrap();
+            fs::write(cache(drive).join("celeste/metadata.kzi"), drive).unwrap();
+        }
+        write_archive(
+            &saves("SOURCE").join("celeste.tar"),
+            &[("save.dat", b"new".as_slice())],
+        );
+        write_archive(
+            &saves("DEST").join("celeste.tar"),
+            &[("save.dat", b"old".as_slice())],
+        );
+
+        // Keep both: the old save moves to a timestamped id, twice in a row
+        // without either replacing the other
+        copy(ConflictResolution::KeepBoth).unwrap();
+        copy(ConflictResolution::KeepBoth).unwrap();
+        let backups: Vec<String> = names("DEST")
+            .into_iter()
+            .filter(|n| n != "celeste.tar")
+            .collect();
+        assert_eq!(backups.len(), 2, "{:?}", backups);
+        assert!(backups
+            .iter()
+            .all(|n| n.starts_with("celeste-") && n.len() >= "celeste-YYYYMMDD-HHMMSS.tar".len()));
+        let mut backed_up: Vec<Vec<Vec<u8>>> = backups
+            .iter()
+            .map(|n| content(saves("DEST").join(n)))
+            .collect();
+        backed_up.sort();
+        assert_eq!(
+            backed_up,
+            vec![vec![b"new".to_vec()], vec![b"old".to_vec()]]
+        );
+        assert_eq!(
+            content(saves("DEST").join("celeste.tar")),
+            vec![b"new".to_vec()]
+        );
+        for name in &backups {
+            assert!(cache("DEST")
+                .join(name.trim_end_matches(".tar"))
+                .join("metadata.kzi")
+                .exists());
+            fs::remove_file(saves("DEST").join(name)).unwrap();
+        }
+
+        // Overwrite replaces the save and leaves no stash behind
+        write_archive(
+            &saves("SOURCE").join("celeste.tar"),
+            &[("save.dat", b"newer".as_slice())],
+        );
+        copy(ConflictResolution::Overwrite).unwrap();
+        assert_eq!(names("DEST"), ["celeste.tar"]);
+        assert_eq!(
+            content(saves("DEST").join("celeste.tar")),
+            vec![b"newer".to_vec()]
+        );
+
+        // A failed copy puts the previous save and its cache back
+        fs::write(cache("DEST").join("celeste/metadata.kzi"), "DEST").unwrap();
+        fs::remove_file(saves("SOURCE").join("celeste.tar")).unwrap();
+        fs::create_dir(saves("SOURCE").join("celeste.tar")).unwrap();
+        assert!(copy(ConflictResolution::Overwrite).is_err());
+        assert_eq!(names("DEST"), ["celeste.tar"]);
+        assert_eq!(
+            content(saves("DEST").join("celeste.tar")),
+            vec![b"newer".to_vec()]
+        );
+        assert_eq!(
+            fs::read_to_string(cache("DEST").join("celeste/metadata.kzi")).unwrap(),
+            "DEST"
+        );
+
+        // A stash left by an interrupted overwrite survives the next one
+        fs::remove_dir(saves("SOURCE").join("celeste.tar")).unwrap();
+        write_archive(
+            &saves("SOURCE").join("celeste.tar"),
+            &[("save.dat", b"newest".as_slice())],
+        );
+        write_archive(
+            &saves("DEST").join(".celeste.overwrite.tar"),
+            &[("save.dat", b"stashed".as_slice())],
+        );
+        copy(ConflictResolution::Overwrite).unwrap();
+        assert_eq!(names("DEST"), [".celeste.overwrite.tar", "celeste.tar"]);
+        assert_eq!(
+            content(saves("DEST").join(".celeste.overwrite.tar")),
+            vec![b"stashed".to_vec()]
+        );
+        assert_eq!(
+            content(saves("DEST").join("celeste.tar")),
+            vec![b"newest".to_vec()]
+        );
+
+        // Renaming never replaces another save
+        assert!(rename_save("celeste", ".celeste.overwrite", "DEST").is_err());
+        assert_eq!(
+            unused_save_id(".celeste.overwrite", "DEST"),
+            ".celeste.overwrite-2"
+        );
+    }
+
+    #[test]
+    fn test_compress_archives_cleans_up_on_failure() {
+        let dir = tempfile::tempdir().unwrap();
+        let save_dir = dir.path().to_str().unwrap();
+        // A directory named like an archive can't be read as one
+        fs::create_dir(dir.path().join("broken.tar")).unwrap();
+
+        assert!(compress_archives_in(save_dir, Arc::new(AtomicU16::new(0))).is_err());
+        assert!(!dir.path().join(".broken.tar.zst.partial").exists());
+        assert!(!dir.path().join("broken.tar.zst").exists());
+        assert!(dir.path().join("broken.tar").is_dir(), "source kept");
+    }
+}
@@ -0,0 +1,294 @@
+//! Encryption at rest for saves on external drives
+//!
+//! A drive opts in with a `kazeta/.encrypted` marker, so the choice travels
+//! with the drive. Archives written to it are encrypted with AES-256-GCM and
+//! get a `.enc` suffix (`<cart_id>.tar.enc`, `<cart_id>.tar.zst.enc`).
+//!
+//! The key is a random device key kept in the user data directory, readable
+//! by the owner only, which ties the saves to this device. Archives can be
+//! copied between drives as-is and are decrypted transparently when
+//! imported on the same device. There is no passphrase mode: a passphrase
+//! would have to be kept next to the saves' other settings in plain text.
+//!
+//! Layout: magic, key source, 16-byte salt, 12-byte nonce, then the
+//! ciphertext. The header is authenticated along with the data. Only the
+//! device key source is written; the byte and the salt leave room for other
+//! key sources without changing the format.
+
+use ::rand::RngCore;
+use aes_gcm::{
+    aead::{Aead, KeyInit, Payload},
+    Aes256Gcm, Key, Nonce,
+};
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use crate::config::get_user_data_dir;
+
+/// Suffix added to encrypted archive names
+pub const ENCRYPTED_SUFFIX: &str = ".enc";
+
+const MAGIC: &[u8; 8] = b"KZSAVE1\0";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+const HEADER_LEN: usize = MAGIC.len() + 1 + SALT_LEN + NONCE_LEN;
+const KEY_LEN: usize = 32;
+
+/// Marker that turns encryption on for a drive, relative to its root
+const DRIVE_MARKER: &str = "kazeta/.encrypted";
+
+/// Device key file in the user data directory
+const DEVICE_KEY_FILE: &str = "save-key";
+
+/// Key source byte for the device key, the only one written
+const KEY_SOURCE_DEVICE: u8 = 1;
+
+/// Whether saves written to a drive are encrypted
+pub fn is_drive_encrypted(drive_root: &Path) -> bool {
+    drive_root.join(DRIVE_MARKER).exists()
+}
+
+/// Turn encryption on or off for new saves written to a drive. Archives
+/// already on it are left as they are.
+pub fn set_drive_encrypted(drive_root: &Path, enabled: bool) -> io::Result<()> {
+    let marker = drive_root.join(DRIVE_MARKER);
+    if enabled {
+        if let Some(parent) = marker.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&marker, "")?;
+        // Create the device key now rather than on the first export
+        device_key()?;
+    } else if marker.exists() {
+        fs::remove_file(&marker)?;
+    }
+    println!(
+        "[Save] Encryption {} for {}",
+        if enabled { "enabled" } else { "disabled" },
+        drive_root.display()
+    );
+    Ok(())
+}
+
+/// Whether an archive file name is for an encrypted archive
+pub fn is_encrypted_name(path: &Path) -> bool {
+    path.to_string_lossy().ends_with(ENCRYPTED_SUFFIX)
+}
+
+/// Encrypt a whole archive with the device key
+pub fn encrypt(plaintext: &[u8]) -> io::Result<Vec<u8>> {
+    encrypt_with_key(plaintext, &device_key()?)
+}
+
+/// Decrypt an archive written by `encrypt`
+pub fn decrypt(data: &[u8]) -> io::Result<Vec<u8>> {
+    check_header(data)?;
+    decrypt_with_key(data, &device_key()?)
+}
+
+fn encrypt_with_key(plaintext: &[u8], key: &[u8; KEY_LEN]) -> io::Result<Vec<u8>> {
+    let mut salt = [0u8; SALT_LEN];
+    let mut nonce = [0u8; NONCE_LEN];
+    ::rand::rng().fill_bytes(&mut salt);
+    ::rand::rng().fill_bytes(&mut nonce);
+
+    let mut header = Vec::with_capacity(HEADER_LEN);
+    header.extend_from_slice(MAGIC);
+    header.push(KEY_SOURCE_DEVICE);
+    header.extend_from_slice(&salt);
+    header.extend_from_slice(&nonce);
+
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
+    let ciphertext = cipher
+        .encrypt(
+            Nonce::from_slice(&nonce),
+            Payload {
+                msg: plaintext,
+                aad: &header,
+            },
+        )
+        .map_err(|_| invalid_data("Failed to encrypt save"))?;
+
+    header.extend_from_slice(&ciphertext);
+    Ok(header)
+}
+
+/// Rejects data that isn't a device-key archive before the key is loaded
+fn check_header(data: &[u8]) -> io::Result<()> {
+    if data.len() < HEADER_LEN || &data[..MAGIC.len()] != MAGIC {
+        return Err(invalid_data("Not an encrypted Kazeta save"));
+    }
+    if data[MAGIC.len()] != KEY_SOURCE_DEVICE {
+        return Err(invalid_data("Unknown save encryption key type"));
+    }
+    Ok(())
+}
+
+fn decrypt_with_key(data: &[u8], key: &[u8; KEY_LEN]) -> io::Result<Vec<u8>> {
+    check_header(data)?;
+    let (header, ciphertext) = data.split_at(HEADER_LEN);
+    let nonce = &header[MAGIC.len() + 1 + SALT_LEN..];
+
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
+    cipher
+        .decrypt(
+            Nonce::from_slice(nonce),
+            Payload {
+                msg: ciphertext,
+                aad: header,
+            },
+        )
+        .map_err(|_| invalid_data("Save was encrypted on another device"))
+}
+
+fn device_key_path() -> io::Result<PathBuf> {
+    get_user_data_dir()
+        .map(|dir| dir.join(DEVICE_KEY_FILE))
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                "Could not find user's data directory",
+            )
+        })
+}
+
+/// This device's key, created on first use. Readable by the owner only.
+fn device_key() -> io::Result<[u8; KEY_LEN]> {
+    let path = device_key_path()?;
+    match fs::read(&path) {
+        Ok(bytes) => bytes
+            .try_into()
+            .map_err(|_| invalid_data("Device save key is corrupt")),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            let mut key = [0u8; KEY_LEN];
+            ::rand::rng().fill_bytes(&mut key);
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            write_private(&path, &key)?;
+            println!("[Save] Created device save key at {}", path.display());
+            Ok(key)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+fn write_private(path: &Path, contents: &[u8]) -> io::Result<()> {
+    use std::os::unix::fs::OpenOptionsExt;
+    let mut file = fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(path)?;
+    file.write_all(contents)?;
+    file.sync_all()
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const KEY: [u8; KEY_LEN] = [7; KEY_LEN];
+
+    #[test]
+    fn test_round_trip() {
+        let plaintext = b"save data".repeat(100);
+        let encrypted = encrypt_with_key(&plaintext, &KEY).unwrap();
+        assert_eq!(&encrypted[..MAGIC.len()], MAGIC);
+        assert_eq!(
+            encrypted.len(),
+            HEADER_LEN + plaintext.len() + 16,
+            "header plus ciphertext and tag"
+        );
+        assert_eq!(decrypt_with_key(&encrypted, &KEY).unwrap(), plaintext);
+
+        // Fresh salt and nonce per archive
+        assert_ne!(encrypt_with_key(&plaintext, &KEY).unwrap(), encrypted);
+        assert!(
+            decrypt_with_key(&encrypt_with_key(b"", &KEY).unwrap(), &KEY)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_wrong_device_key() {
+        let encrypted = encrypt_with_key(b"save data", &KEY).unwrap();
+        let err = decrypt_with_key(&encrypted, &[8; KEY_LEN]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert_eq!(err.to_string(), "Save was encrypted on another device");
+    }
+
+    #[test]
+    fn test_tampering_is_detected() {
+        let encrypted = encrypt_with_key(b"save data", &KEY).unwrap();
+
+        // The salt is only covered as associated data
+        let mut header = encrypted.clone();
+        header[MAGIC.len() + 1] ^= 1;
+        assert!(decrypt_with_key(&header, &KEY).is_err());
+
+        let mut nonce = encrypted.clone();
+        nonce[HEADER_LEN - 1] ^= 1;
+        assert!(decrypt_with_key(&nonce, &KEY).is_err());
+
+        let mut ciphertext = encrypted.clone();
+        ciphertext[HEADER_LEN] ^= 1;
+        assert!(decrypt_with_key(&ciphertext, &KEY).is_err());
+
+        let mut tag = encrypted.clone();
+        *tag.last_mut().unwrap() ^= 1;
+        assert!(decrypt_with_key(&tag, &KEY).is_err());
+
+        assert!(decrypt_with_key(&encrypted[..encrypted.len() - 1], &KEY).is_err());
+    }
+
+    #[test]
+    fn test_rejects_malformed_input() {
+        let encrypted = encrypt_with_key(b"save data", &KEY).unwrap();
+
+        let mut magic = encrypted.clone();
+        magic[0] = b'X';
+        assert_eq!(
+            decrypt_with_key(&magic, &KEY).unwrap_err().to_string(),
+            "Not an encrypted Kazeta save"
+        );
+
+        // Too short for a header, plain tar data and nothing at all
+        assert!(decrypt_with_key(&encrypted[..HEADER_LEN - 1], &KEY).is_err());
+        assert!(decrypt_with_key(&[0u8; 512], &KEY).is_err());
+        assert!(decrypt_with_key(&[], &KEY).is_err());
+
+        // Archives from another key source, e.g. a passphrase, aren't read
+        let mut source = encrypted.clone();
+        source[MAGIC.len()] = 2;
+        assert_eq!(
+            check_header(&source).unwrap_err().to_string(),
+            "Unknown save encryption key type"
+        );
+        assert!(check_header(&encrypted).is_ok());
+    }
+
+    #[test]
+    fn test_drive_marker_and_names() {
+        let drive = tempfile::tempdir().unwrap();
+        assert!(!is_drive_encrypted(drive.path()));
+        fs::create_dir_all(drive.path().join("kazeta")).unwrap();
+        fs::write(drive.path().join(DRIVE_MARKER), "").unwrap();
+        assert!(is_drive_encrypted(drive.path()));
+
+        assert!(is_encrypted_name(Path::new(
+            "/media/USB/kazeta/saves/celeste.tar.zst.enc"
+        )));
+        assert!(!is_encrypted_name(Path::new(
+            "/media/USB/kazeta/saves/celeste.tar"
+        )));
+    }
+}
@@ -34,6 +34,10 @@ pub struct ThemeConfigFile {
     pub font_selection: Option<String>,
     pub splash_video: Option<String>,
     pub splash_audio: Option<String>,
+    pub splash_image: Option<String>,
+    pub farewell_image: Option<String>,
+    pub farewell_audio: Option<String>,
+    pub farewell_message: Option<String>,
     #[serde(default)]
     pub loading_messages: Option<Vec<String>>,
     #[serde(default)]
@@ -60,7 +64,8 @@ pub async fn load_all_themes() -> HashMap<String, Theme> {
     let virtual_default_theme = Theme {
         name: "Default".to_string(),
         sounds: default_sfx.clone(), // Use the pre-loaded default sounds
-        config: ThemeConfigFile { // Create an empty config, just like from an empty theme.toml
+        config: ThemeConfigFile {
+            // Create an empty config, just like from an empty theme.toml
             menu_position: None,
             font_color: None,
             cursor_color: None,
@@ -76,6 +81,10 @@ pub async fn load_all_themes() -> HashMap<String, Theme> {
             font_selection: None,
             splash_video: None,
             splash_audio: None,
+            splash_image: None,
+            farewell_image: None,
+            farewell_audio: None,
+            farewell_message: None,
             loading_messages: None,
             blades: None,
         },
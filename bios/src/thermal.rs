@@ -0,0 +1,261 @@
+//! CPU/GPU temperature and fan monitoring
+//!
+//! Sensors come from `/sys/class/hwmon`. Each hwmon device is classified by
+//! its driver name, so the hottest CPU and GPU reading can be checked against
+//! the warning threshold in `config.thermal`. Crossing it warns once; the
+//! warning re-arms after the temperature drops a few degrees below it. On
+//! battery-powered hardware (handhelds), staying hot through a game also
+//! suggests a frame limit for the emulator, once per game.
+
+use crate::config::ThermalConfig;
+use std::{
+    fs,
+    path::Path,
+    time::{Duration, Instant},
+};
+
+const HWMON_PATH: &str = "/sys/class/hwmon";
+const POWER_SUPPLY_PATH: &str = "/sys/class/power_supply";
+
+/// How often the sensors are read
+const CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Degrees below the threshold the temperature has to drop to warn again
+const HYSTERESIS_C: f32 = 5.0;
+
+/// Frame limit suggested to cool a handheld down
+pub const SUGGESTED_FRAME_LIMIT: u32 = 30;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SensorKind {
+    Cpu,
+    Gpu,
+    Other,
+}
+
+impl SensorKind {
+    /// Classify a hwmon device by its driver name
+    fn from_driver(name: &str) -> Self {
+        match name {
+            "k10temp" | "coretemp" | "zenpower" | "cpu_thermal" | "acpitz" => SensorKind::Cpu,
+            "amdgpu" | "radeon" | "nouveau" | "i915" | "xe" => SensorKind::Gpu,
+            _ => SensorKind::Other,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SensorKind::Cpu => "CPU",
+            SensorKind::Gpu => "GPU",
+            SensorKind::Other => "OTHER",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Sensor {
+    pub kind: SensorKind,
+    /// e.g. "k10temp Tctl"
+    pub label: String,
+    pub celsius: f32,
+}
+
+#[derive(Clone, Debug)]
+pub struct Fan {
+    pub label: String,
+    pub rpm: u32,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ThermalReading {
+    pub sensors: Vec<Sensor>,
+    pub fans: Vec<Fan>,
+}
+
+impl ThermalReading {
+    /// Reads every hwmon device's temperatures and fan speeds
+    pub fn read() -> Self {
+        Self::read_from(Path::new(HWMON_PATH))
+    }
+
+    fn read_from(root: &Path) -> Self {
+        let mut reading = Self::default();
+        let Ok(entries) = fs::read_dir(root) else {
+            return reading;
+        };
+
+        let mut devices: Vec<_> = entries.flatten().map(|entry| entry.path()).collect();
+        devices.sort();
+        for device in devices {
+            let driver = read_trimmed(&device.join("name")).unwrap_or_else(|| "hwmon".to_string());
+            let kind = SensorKind::from_driver(&driver);
+            let Ok(files) = fs::read_dir(&device) else {
+                continue;
+            };
+
+            let mut names: Vec<String> = files
+                .flatten()
+                .map(|file| file.file_name().to_string_lossy().into_owned())
+                .filter(|name| name.ends_with("_input"))
+                .collect();
+            names.sort();
+            for name in names {
+                let prefix = name.trim_end_matches("_input");
+                let Some(value) =
+                    read_trimmed(&device.join(&name)).and_then(|v| v.parse::<i64>().ok())
+                else {
+                    continue;
+                };
+                let label = match read_trimmed(&device.join(format!("{}_label", prefix))) {
+                    Some(label) => format!("{} {}", driver, label),
+                    None => driver.clone(),
+                };
+
+                if prefix.starts_with("temp") {
+                    // Millidegrees Celsius
+                    reading.sensors.push(Sensor {
+                        kind,
+                        label,
+                        celsius: value as f32 / 1000.0,
+                    });
+                } else if prefix.starts_with("fan") {
+                    reading.fans.push(Fan {
+                        label,
+                        rpm: value.max(0) as u32,
+                    });
+                }
+            }
+        }
+        reading
+    }
+
+    /// Hottest reading of a kind
+    pub fn hottest(&self, kind: SensorKind) -> Option<f32> {
+        self.sensors
+            .iter()
+            .filter(|sensor| sensor.kind == kind)
+            .map(|sensor| sensor.celsius)
+            .reduce(f32::max)
+    }
+
+    /// Hottest CPU or GPU sensor, what the warning is about
+    fn hottest_chip(&self) -> Option<&Sensor> {
+        self.sensors
+            .iter()
+            .filter(|sensor| matches!(sensor.kind, SensorKind::Cpu | SensorKind::Gpu))
+            .max_by(|a, b| a.celsius.total_cmp(&b.celsius))
+    }
+}
+
+fn read_trimmed(path: &Path) -> Option<String> {
+    fs::read_to_string(path).ok().map(|s| s.trim().to_string())
+}
+
+/// Whether the system runs on a battery, taken to mean a handheld
+fn is_battery_powered() -> bool {
+    let Ok(entries) = fs::read_dir(POWER_SUPPLY_PATH) else {
+        return false;
+    };
+    entries
+        .flatten()
+        .any(|entry| read_trimmed(&entry.path().join("type")).as_deref() == Some("Battery"))
+}
+
+pub enum ThermalEvent {
+    /// A chip went over the warning threshold
+    Warning { kind: SensorKind, celsius: f32 },
+    /// A game has kept a handheld over the threshold for a while
+    SuggestFrameLimit { celsius: f32 },
+}
+
+pub struct ThermalMonitor {
+    pub reading: ThermalReading,
+    last_check: Option<Instant>,
+    /// Set after a warning until the temperature drops back down
+    warned: bool,
+    /// When the current stretch over the threshold began
+    hot_since: Option<Instant>,
+    suggested: bool,
+    battery_powered: bool,
+}
+
+impl ThermalMonitor {
+    pub fn new() -> Self {
+        Self {
+            reading: ThermalReading::default(),
+            last_check: None,
+            warned: false,
+            hot_since: None,
+            suggested: false,
+            battery_powered: is_battery_powered(),
+        }
+    }
+
+    /// Reads the sensors when due. `game_running` gates the frame-limit
+    /// suggestion, which is only useful while an emulator runs.
+    pub fn update(&mut self, config: &ThermalConfig, game_running: bool) -> Option<ThermalEvent> {
+        if self
+            .last_check
+            .is_some_and(|at| at.elapsed() < CHECK_INTERVAL)
+        {
+            return None;
+        }
+        self.last_check = Some(Instant::now());
+        self.reading = ThermalReading::read();
+
+        if !game_running {
+            // A new game gets its own suggestion
+            self.suggested = false;
+        }
+        let sensor = self.reading.hottest_chip()?.clone();
+        self.check(config, &sensor, game_running, Instant::now())
+    }
+
+    fn check(
+        &mut self,
+        config: &ThermalConfig,
+        sensor: &Sensor,
+        game_running: bool,
+        now: Instant,
+    ) -> Option<ThermalEvent> {
+        let (kind, celsius) = (sensor.kind, sensor.celsius);
+        let threshold = config.warning_temp_c as f32;
+        if celsius < threshold {
+            self.hot_since = None;
+            if celsius < threshold - HYSTERESIS_C {
+                self.warned = false;
+            }
+            return None;
+        }
+
+        let hot_since = *self.hot_since.get_or_insert(now);
+        if !self.warned {
+            self.warned = true;
+            println!(
+                "[Thermal] {} ({}) at {:.0}°C (warning at {}°C)",
+                kind.label(),
+                sensor.label,
+                celsius,
+                config.warning_temp_c
+            );
+            return Some(ThermalEvent::Warning { kind, celsius });
+        }
+
+        let sustained =
+            now.duration_since(hot_since) >= Duration::from_secs(config.sustained_seconds as u64);
+        if config.suggest_frame_limit
+            && self.battery_powered
+            && game_running
+            && sustained
+            && !self.suggested
+        {
+            self.suggested = true;
+            println!(
+                "[Thermal] Hot for {}s, suggesting a frame limit",
+                config.sustained_seconds
+            );
+            return Some(ThermalEvent::SuggestFrameLimit { celsius });
+        }
+        None
+    }
+}
@@ -1,8 +1,11 @@
 use crate::{
-    VideoPlayer,
     audio::SoundEffects,
-    config::Config, FONT_SIZE, SystemInfo, Screen, BackgroundState, BatteryInfo, render_background, render_ui_overlay, get_current_font, measure_text, text_with_config_color, InputState,
+    config::Config,
+    get_current_font, measure_text, render_background, render_ui_overlay,
     system::get_ip_address,
+    text_with_config_color,
+    thermal::{SensorKind, ThermalReading},
+    BackgroundState, BatteryInfo, InputState, Screen, SystemInfo, VideoPlayer, FONT_SIZE,
 };
 use macroquad::prelude::*;
 use std::collections::HashMap;
@@ -21,6 +24,7 @@ pub fn update(
 
 pub fn draw(
     system_info: &SystemInfo,
+    thermal: &ThermalReading,
     logo_cache: &HashMap<String, Texture2D>,
     background_cache: &HashMap<String, Texture2D>,
     video_cache: &mut HashMap<String, VideoPlayer>,
@@ -35,9 +39,23 @@ pub fn draw(
     render_background(&background_cache, video_cache, &config, background_state);
 
     // Dim the background to improve text readability
-    draw_rectangle(0.0, 0.0, screen_width(), screen_height(), Color::new(0.0, 0.0, 0.0, 0.5));
+    draw_rectangle(
+        0.0,
+        0.0,
+        screen_width(),
+        screen_height(),
+        Color::new(0.0, 0.0, 0.0, 0.5),
+    );
 
-    render_ui_overlay(&logo_cache, &font_cache, &config, &battery_info, &current_time_str, gcc_adapter_poll_rate, scale_factor);
+    render_ui_overlay(
+        &logo_cache,
+        &font_cache,
+        &config,
+        &battery_info,
+        &current_time_str,
+        gcc_adapter_poll_rate,
+        scale_factor,
+    );
 
     let current_font = get_current_font(font_cache, config);
     let about_font_size = (FONT_SIZE as f32 * scale_factor * 0.8) as u16;
@@ -50,6 +68,31 @@ pub fn draw(
     // -- NEW -- Fetch the IP address dynamically every time the screen is drawn.
     let ip_address = get_ip_address();
 
+    // --- Temperatures and fans, as of the last sensor check ---
+    let temps: Vec<String> = [SensorKind::Cpu, SensorKind::Gpu]
+        .into_iter()
+        .filter_map(|kind| {
+            thermal
+                .hottest(kind)
+                .map(|celsius| format!("{} {:.0}°C", kind.label(), celsius))
+        })
+        .collect();
+    let temps = if temps.is_empty() {
+        "N/A".to_string()
+    } else {
+        temps.join("  ")
+    };
+    let fans = if thermal.fans.is_empty() {
+        "N/A".to_string()
+    } else {
+        thermal
+            .fans
+            .iter()
+            .map(|fan| format!("{} {} RPM", fan.label, fan.rpm))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
     // --- Hardware Info ---
     let info = vec![
         ("OS:", &system_info.os_name),
@@ -58,11 +101,27 @@ pub fn draw(
         ("GPU:", &system_info.gpu),
         ("MEMORY:", &system_info.ram_total),
         ("IP:", &ip_address), // Display the IP address
+        ("TEMP:", &temps),
+        ("FANS:", &fans),
     ];
 
     for (label, value) in info {
-        text_with_config_color(font_cache, config, label, start_x_labels, current_y, about_font_size);
-        text_with_config_color(font_cache, config, value, start_x_values, current_y, about_font_size);
+        text_with_config_color(
+            font_cache,
+            config,
+            label,
+            start_x_labels,
+            current_y,
+            about_font_size,
+        );
+        text_with_config_color(
+            font_cache,
+            config,
+            value,
+            start_x_values,
+            current_y,
+            about_font_size,
+        );
         current_y += line_height;
     }
 
@@ -80,11 +139,7 @@ pub fn draw(
         let dims = measure_text(line, Some(current_font), about_font_size, 1.0);
         let x_pos = screen_width() / 2.0 - dims.width / 2.0;
 
-        text_with_config_color(
-            font_cache, config, line,
-            x_pos, current_y,
-            about_font_size
-        );
+        text_with_config_color(font_cache, config, line, x_pos, current_y, about_font_size);
         // ---
         current_y += line_height;
     }
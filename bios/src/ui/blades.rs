@@ -1,6 +1,9 @@
 use crate::audio::SoundEffects;
+use crate::collections::Collections;
 use crate::config::Config;
+use crate::icon_gen;
 use crate::input::InputState;
+use crate::library_order::{LibraryOrder, SortMode};
 use crate::save;
 use crate::types::{Blade, BladeTab, BladeType, Screen};
 use crate::ui::get_current_font;
@@ -30,6 +33,8 @@ pub enum BladeAction {
     None,
     LaunchGame((save::CartInfo, PathBuf)),
     GoToScreen(Screen),
+    /// Manage collections, or assign the given cart to them
+    Collections(Option<save::CartInfo>),
 }
 
 pub struct BladesAnimationState {
@@ -48,7 +53,23 @@ pub struct BladesState {
     pub enabled: bool,
     pub games_list: Vec<(save::CartInfo, PathBuf)>,
     pub game_icon_cache: HashMap<String, Texture2D>,
+    /// Carts whose icons still need loading, one per frame
+    pub game_icon_queue: Vec<(save::CartInfo, PathBuf)>,
     pub game_list_selection: usize,
+    /// Library generation games_list was built from
+    pub library_generation: Option<u64>,
+    /// Favorites, manual order and sort mode of games_list
+    pub library_order: LibraryOrder,
+    /// The sort dropdown in the library header has focus
+    pub sort_focused: bool,
+    /// Highlighted entry of the open sort dropdown
+    pub sort_menu: Option<usize>,
+    /// Collections the library can be filtered by
+    pub collections: Collections,
+    /// Only games in this collection are listed
+    pub collection_filter: Option<String>,
+    /// The collection filter row above the games has focus
+    pub filter_focused: bool,
 }
 
 struct BladeRenderInfo {
@@ -98,7 +119,11 @@ impl BladesAnimationState {
 
     pub fn get_eased_progress(&self) -> f32 {
         let t = self.blade_transition_progress;
-        if t < 0.5 { 4.0 * t * t * t } else { 1.0 - (-2.0 * t + 2.0).powi(3) / 2.0 }
+        if t < 0.5 {
+            4.0 * t * t * t
+        } else {
+            1.0 - (-2.0 * t + 2.0).powi(3) / 2.0
+        }
     }
 }
 
@@ -109,33 +134,80 @@ impl BladesState {
             blade_type: BladeType::GamesAndApps,
             name: "GAMES & APPS".to_string(),
             tabs: vec![
-                BladeTab { name: "LIBRARY".to_string(), icon: None },
-                BladeTab { name: "RECENTLY PLAYED".to_string(), icon: None },
-                BladeTab { name: "INSTALLED APPS".to_string(), icon: None },
+                BladeTab {
+                    name: "LIBRARY".to_string(),
+                    icon: None,
+                },
+                BladeTab {
+                    name: "RECENTLY PLAYED".to_string(),
+                    icon: None,
+                },
+                BladeTab {
+                    name: "INSTALLED APPS".to_string(),
+                    icon: None,
+                },
+                BladeTab {
+                    name: "TROPHY ROOM".to_string(),
+                    icon: None,
+                },
             ],
-            selected_tab: 0, scroll_offset: 0, gradient_color: WHITE,
+            selected_tab: 0,
+            scroll_offset: 0,
+            gradient_color: WHITE,
         });
         blades.push(Blade {
             blade_type: BladeType::SystemSettings,
             name: "SYSTEM SETTINGS".to_string(),
             tabs: vec![
-                BladeTab { name: "GENERAL".to_string(), icon: None },
-                BladeTab { name: "AUDIO".to_string(), icon: None },
-                BladeTab { name: "GUI".to_string(), icon: None },
-                BladeTab { name: "NETWORK".to_string(), icon: None },
-                BladeTab { name: "ASSETS".to_string(), icon: None },
+                BladeTab {
+                    name: "GENERAL".to_string(),
+                    icon: None,
+                },
+                BladeTab {
+                    name: "AUDIO".to_string(),
+                    icon: None,
+                },
+                BladeTab {
+                    name: "GUI".to_string(),
+                    icon: None,
+                },
+                BladeTab {
+                    name: "NETWORK".to_string(),
+                    icon: None,
+                },
+                BladeTab {
+                    name: "ASSETS".to_string(),
+                    icon: None,
+                },
+                BladeTab {
+                    name: "KIOSK".to_string(),
+                    icon: None,
+                },
             ],
-            selected_tab: 0, scroll_offset: 0, gradient_color: WHITE,
+            selected_tab: 0,
+            scroll_offset: 0,
+            gradient_color: WHITE,
         });
         blades.push(Blade {
             blade_type: BladeType::SaveDataAndMemory,
             name: "SAVE DATA & MEMORY".to_string(),
             tabs: vec![
-                BladeTab { name: "INTERNAL STORAGE".to_string(), icon: None },
-                BladeTab { name: "EXTERNAL STORAGE".to_string(), icon: None },
-                BladeTab { name: "MANAGE SAVES".to_string(), icon: None },
+                BladeTab {
+                    name: "INTERNAL STORAGE".to_string(),
+                    icon: None,
+                },
+                BladeTab {
+                    name: "EXTERNAL STORAGE".to_string(),
+                    icon: None,
+                },
+                BladeTab {
+                    name: "MANAGE SAVES".to_string(),
+                    icon: None,
+                },
             ],
-            selected_tab: 0, scroll_offset: 0, gradient_color: WHITE,
+            selected_tab: 0,
+            scroll_offset: 0,
+            gradient_color: WHITE,
         });
 
         BladesState {
@@ -145,8 +217,47 @@ impl BladesState {
             enabled: false,
             games_list: Vec::new(),
             game_icon_cache: HashMap::new(),
+            game_icon_queue: Vec::new(),
             game_list_selection: 0,
+            library_generation: None,
+            library_order: LibraryOrder::load(),
+            sort_focused: false,
+            sort_menu: None,
+            collections: Collections::load(),
+            collection_filter: None,
+            filter_focused: false,
+        }
+    }
+
+    /// Pick up changes from the collections screen, dropping the filter if
+    /// its collection is gone
+    pub fn reload_collections(&mut self) {
+        self.collections = Collections::load();
+        if let Some(filter) = &self.collection_filter {
+            if !self.collections.names.contains(filter) {
+                self.collection_filter = None;
+            }
         }
+        self.library_generation = None;
+    }
+
+    /// Step through "all games" and each collection
+    fn cycle_filter(&mut self, forward: bool) {
+        let names = &self.collections.names;
+        let count = names.len() + 1;
+        let current = self
+            .collection_filter
+            .as_ref()
+            .and_then(|f| names.iter().position(|n| n == f))
+            .map_or(0, |i| i + 1);
+        let next = if forward {
+            (current + 1) % count
+        } else {
+            (current + count - 1) % count
+        };
+        self.collection_filter = next.checked_sub(1).map(|i| names[i].clone());
+        self.game_list_selection = 0;
+        self.library_generation = None;
     }
 }
 
@@ -154,6 +265,156 @@ impl BladesState {
 // UPDATE & DRAW
 // ===================================
 
+/// Re-sort the library, keeping the selected game selected
+fn resort_library(blades_state: &mut BladesState) {
+    let selected = blades_state
+        .games_list
+        .get(blades_state.game_list_selection)
+        .map(|(cart_info, _)| cart_info.id.clone());
+    blades_state
+        .library_order
+        .apply(&mut blades_state.games_list);
+    if let Some(id) = selected {
+        blades_state.game_list_selection = blades_state
+            .games_list
+            .iter()
+            .position(|(cart_info, _)| cart_info.id == id)
+            .unwrap_or(0);
+    }
+}
+
+/// Favorites, reordering, collections and the sort dropdown of the Library
+/// tab. Returns an action if the input was used up.
+fn update_library(
+    blades_state: &mut BladesState,
+    input_state: &InputState,
+    sound_effects: &SoundEffects,
+    config: &Config,
+) -> Option<BladeAction> {
+    // The open dropdown takes all input
+    if let Some(highlight) = blades_state.sort_menu {
+        let modes = SortMode::ALL.len();
+        if input_state.up {
+            blades_state.sort_menu = Some((highlight + modes - 1) % modes);
+            sound_effects.play_cursor_move(config);
+        }
+        if input_state.down {
+            blades_state.sort_menu = Some((highlight + 1) % modes);
+            sound_effects.play_cursor_move(config);
+        }
+        if input_state.select {
+            blades_state.library_order.sort = SortMode::ALL[highlight];
+            blades_state.library_order.save();
+            resort_library(blades_state);
+            blades_state.sort_menu = None;
+            sound_effects.play_select(config);
+        }
+        if input_state.back || input_state.cycle {
+            blades_state.sort_menu = None;
+            sound_effects.play_back(config);
+        }
+        return Some(BladeAction::None);
+    }
+
+    let open_menu = |blades_state: &mut BladesState| {
+        let current = SortMode::ALL
+            .iter()
+            .position(|m| *m == blades_state.library_order.sort)
+            .unwrap_or(0);
+        blades_state.sort_menu = Some(current);
+        sound_effects.play_select(config);
+    };
+
+    if input_state.cycle {
+        open_menu(blades_state);
+        return Some(BladeAction::None);
+    }
+
+    // Up past the first game reaches the collection filter, then the sort
+    // dropdown in the header
+    if blades_state.sort_focused {
+        if input_state.select {
+            open_menu(blades_state);
+            return Some(BladeAction::None);
+        }
+        if input_state.down || input_state.back {
+            blades_state.sort_focused = false;
+            blades_state.filter_focused = input_state.down;
+            sound_effects.play_cursor_move(config);
+            return Some(BladeAction::None);
+        }
+        return input_state.up.then_some(BladeAction::None);
+    }
+    if blades_state.filter_focused {
+        if input_state.left || input_state.right {
+            blades_state.cycle_filter(input_state.right);
+            sound_effects.play_cursor_move(config);
+        } else if input_state.select || input_state.tertiary {
+            sound_effects.play_select(config);
+            return Some(BladeAction::Collections(None));
+        } else if input_state.up || input_state.down || input_state.back {
+            blades_state.filter_focused = false;
+            blades_state.sort_focused = input_state.up;
+            sound_effects.play_cursor_move(config);
+        } else {
+            return None;
+        }
+        return Some(BladeAction::None);
+    }
+    if input_state.up && blades_state.game_list_selection == 0 {
+        blades_state.filter_focused = true;
+        sound_effects.play_cursor_move(config);
+        return Some(BladeAction::None);
+    }
+
+    let Some((cart_info, _)) = blades_state
+        .games_list
+        .get(blades_state.game_list_selection)
+    else {
+        return None;
+    };
+    if input_state.tertiary {
+        sound_effects.play_select(config);
+        return Some(BladeAction::Collections(Some(cart_info.clone())));
+    }
+    if input_state.secondary {
+        let id = cart_info.id.clone();
+        let starred = blades_state.library_order.toggle_favorite(&id);
+        println!(
+            "[Library] {} {}",
+            if starred { "Starred" } else { "Unstarred" },
+            id
+        );
+        blades_state.library_order.save();
+        resort_library(blades_state);
+        sound_effects.play_select(config);
+        return Some(BladeAction::None);
+    }
+    // The manual order is of the whole library, so it can't be rearranged
+    // while a collection hides part of it
+    if (input_state.prev || input_state.next) && blades_state.collection_filter.is_some() {
+        sound_effects.play_reject(config);
+        return Some(BladeAction::None);
+    }
+    if input_state.prev || input_state.next {
+        let order = &mut blades_state.library_order;
+        match order.move_game(
+            &mut blades_state.games_list,
+            blades_state.game_list_selection,
+            input_state.prev,
+        ) {
+            Some(index) => {
+                blades_state.game_list_selection = index;
+                order.save();
+                sound_effects.play_cursor_move(config);
+            }
+            None => sound_effects.play_reject(config),
+        }
+        return Some(BladeAction::None);
+    }
+    None
+}
+
 pub fn update(
     blades_state: &mut BladesState,
     input_state: &mut InputState,
@@ -161,51 +422,79 @@ pub fn update(
     config: &Config,
 ) -> BladeAction {
     if !blades_state.enabled {
+        // Rebuild on entry, in case the whitelist changed while away
         blades_state.enabled = true;
-        if let Ok((game_paths, _)) = save::find_all_game_files() {
-            let mut games: Vec<(save::CartInfo, PathBuf)> = Vec::new();
-            for path in &game_paths {
-                if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
-                    if ext == "kzi" {
-                        if let Ok(info) = save::parse_kzi_file(path) {
-                            games.push((info, path.clone()));
-                        }
-                    } else if ext == "kzp" {
-                        let filename = path.file_stem().unwrap().to_string_lossy().to_string();
-                        let info = save::CartInfo {
-                            name: Some(filename.clone()),
-                            id: filename,
-                            exec: String::from("internal"),
-                            icon: String::from("icon.png"),
-                            runtime: Some(String::from("erofs")),
-                            ..Default::default()
-                        };
-                        games.push((info, path.clone()));
-                    }
-                }
-            }
-            blades_state.games_list = games;
+        blades_state.library_generation = None;
+    }
+    // Pick up carts the library indexer found since the last frame
+    let generation = crate::library::generation();
+    if blades_state.library_generation != Some(generation) {
+        blades_state.library_generation = Some(generation);
+        let mut games = crate::library::games();
+        crate::kiosk::filter_launchable(&mut games, config);
+        blades_state.library_order.apply(&mut games);
+        crate::utils::notify_game_list(&games);
+        if let Some(filter) = &blades_state.collection_filter {
+            games.retain(|(cart_info, _)| blades_state.collections.contains(&cart_info.id, filter));
+        }
+        blades_state.game_list_selection = blades_state
+            .game_list_selection
+            .min(games.len().saturating_sub(1));
+        blades_state.game_icon_queue = games
+            .iter()
+            .filter(|(cart_info, _)| !blades_state.game_icon_cache.contains_key(&cart_info.id))
+            .cloned()
+            .collect();
+        blades_state.games_list = games;
+    }
+
+    // Load one icon per frame to prevent stuttering; carts without one get a generated icon
+    if !blades_state.game_icon_queue.is_empty() {
+        let (cart_info, game_path) = blades_state.game_icon_queue.remove(0);
+        let icon_path = icon_gen::icon_for(&cart_info, &game_path);
+        let image = if icon_path.to_string_lossy() == icon_gen::KZP_PLACEHOLDER {
+            Image::from_file_with_format(crate::KZP_ICON_BYTES, None).ok()
+        } else {
+            std::fs::read(&icon_path)
+                .ok()
+                .and_then(|bytes| Image::from_file_with_format(&bytes, None).ok())
+        };
+        if let Some(image) = image {
+            blades_state
+                .game_icon_cache
+                .insert(cart_info.id, Texture2D::from_image(&image));
         }
     }
 
     blades_state.animation.update(get_frame_time());
 
+    let on_library = blades_state.current_blade == 0 && blades_state.blades[0].selected_tab == 0;
+    if on_library {
+        if let Some(action) = update_library(blades_state, input_state, sound_effects, config) {
+            return action;
+        }
+    }
+
     let num_blades = blades_state.blades.len();
     if input_state.right && blades_state.current_blade < num_blades - 1 {
         let source = blades_state.current_blade;
         blades_state.current_blade += 1;
-        blades_state.animation.trigger_blade_transition(source, blades_state.current_blade);
+        blades_state
+            .animation
+            .trigger_blade_transition(source, blades_state.current_blade);
         sound_effects.play_cursor_move(config);
     }
     if input_state.left && blades_state.current_blade > 0 {
         let source = blades_state.current_blade;
         blades_state.current_blade -= 1;
-        blades_state.animation.trigger_blade_transition(source, blades_state.current_blade);
+        blades_state
+            .animation
+            .trigger_blade_transition(source, blades_state.current_blade);
         sound_effects.play_cursor_move(config);
     }
 
     let current_blade = &mut blades_state.blades[blades_state.current_blade];
-    
+
     let mut switched_tabs = false;
     if input_state.up {
         if current_blade.blade_type == BladeType::GamesAndApps && current_blade.selected_tab == 0 {
@@ -219,11 +508,13 @@ pub fn update(
         }
     }
     if input_state.down {
-        if current_blade.blade_type == BladeType::GamesAndApps && current_blade.selected_tab == 0 {
-            if blades_state.game_list_selection < blades_state.games_list.len() - 1 {
-                blades_state.game_list_selection += 1;
-                sound_effects.play_cursor_move(config);
-            }
+        // Past the last game, move on to the blade's other tabs
+        if current_blade.blade_type == BladeType::GamesAndApps
+            && current_blade.selected_tab == 0
+            && blades_state.game_list_selection + 1 < blades_state.games_list.len()
+        {
+            blades_state.game_list_selection += 1;
+            sound_effects.play_cursor_move(config);
         } else if current_blade.selected_tab < current_blade.tabs.len() - 1 {
             current_blade.selected_tab += 1;
             switched_tabs = true;
@@ -238,10 +529,16 @@ pub fn update(
         sound_effects.play_select(config);
         match current_blade.blade_type {
             BladeType::GamesAndApps => {
-                if current_blade.selected_tab == 0 { // Library
-                    if let Some(game) = blades_state.games_list.get(blades_state.game_list_selection) {
+                if current_blade.selected_tab == 0 {
+                    // Library
+                    if let Some(game) = blades_state
+                        .games_list
+                        .get(blades_state.game_list_selection)
+                    {
                         return BladeAction::LaunchGame(game.clone());
                     }
+                } else if current_blade.selected_tab == 3 {
+                    return BladeAction::GoToScreen(Screen::TrophyRoom);
                 }
             }
             BladeType::SystemSettings => {
@@ -251,6 +548,7 @@ pub fn update(
                     2 => Screen::GuiSettings,
                     3 => Screen::Wifi,
                     4 => Screen::AssetSettings,
+                    5 => Screen::KioskSettings,
                     _ => Screen::BladesDashboard, // Should not happen
                 };
                 return BladeAction::GoToScreen(screen);
@@ -266,20 +564,33 @@ pub fn update(
         blades_state.enabled = false;
         return BladeAction::GoToScreen(Screen::MainMenu);
     }
-    
+
     BladeAction::None
 }
 
-pub fn draw(blades_state: &BladesState, font_cache: &HashMap<String, Font>, config: &Config, _frame_t: f64) {
+pub fn draw(
+    blades_state: &BladesState,
+    font_cache: &HashMap<String, Font>,
+    config: &Config,
+    _frame_t: f64,
+) {
     clear_background(BLACK);
 
     let scale_factor = screen_height() / 360.0;
 
     let blade_indices: Vec<usize> = (0..blades_state.blades.len()).collect();
-    let mut blade_render_infos: Vec<_> = blade_indices.iter().map(|&i| {
-        let info = calculate_blade_offset(i, &blades_state.animation, scale_factor, blades_state.blades.len());
-        (i, info)
-    }).collect();
+    let mut blade_render_infos: Vec<_> = blade_indices
+        .iter()
+        .map(|&i| {
+            let info = calculate_blade_offset(
+                i,
+                &blades_state.animation,
+                scale_factor,
+                blades_state.blades.len(),
+            );
+            (i, info)
+        })
+        .collect();
 
     blade_render_infos.sort_by(|(a_idx, _), (b_idx, _)| {
         let dist_a = (*a_idx as i32 - blades_state.current_blade as i32).abs();
@@ -288,7 +599,15 @@ pub fn draw(blades_state: &BladesState, font_cache: &HashMap<String, Font>, conf
     });
 
     for (i, render_info) in blade_render_infos {
-        render_blade(&blades_state.blades[i], blades_state, &render_info, &blades_state.animation, font_cache, config, scale_factor);
+        render_blade(
+            &blades_state.blades[i],
+            blades_state,
+            &render_info,
+            &blades_state.animation,
+            font_cache,
+            config,
+            scale_factor,
+        );
     }
 }
 
@@ -305,22 +624,39 @@ fn calculate_blade_offset(
     let screen_center = screen_width() / 2.0;
     let blade_width = screen_width() * BLADE_WIDTH_RATIO * scale_factor;
     let overlap_width = blade_width * BLADE_OVERLAP_RATIO;
-    
+
     let eased_progress = animation.get_eased_progress();
-    let effective_blade_pos = lerp(animation.source_blade as f32, animation.target_blade as f32, eased_progress);
-    
+    let effective_blade_pos = lerp(
+        animation.source_blade as f32,
+        animation.target_blade as f32,
+        eased_progress,
+    );
+
     let position_delta = blade_index as f32 - effective_blade_pos;
-    
-    let base_x = screen_center - (blade_width / 2.0) + (position_delta * (blade_width - overlap_width));
-    
+
+    let base_x =
+        screen_center - (blade_width / 2.0) + (position_delta * (blade_width - overlap_width));
+
     let alpha = 1.0 - (position_delta.abs() / (num_blades as f32 / 2.0)).powf(2.0);
 
-    BladeRenderInfo { x: base_x, width: blade_width, alpha }
+    BladeRenderInfo {
+        x: base_x,
+        width: blade_width,
+        alpha,
+    }
 }
 
 use crate::utils::string_to_color;
 
-fn render_blade(blade: &Blade, blades_state: &BladesState, render_info: &BladeRenderInfo, animation: &BladesAnimationState, font_cache: &HashMap<String, Font>, config: &Config, scale_factor: f32) {
+fn render_blade(
+    blade: &Blade,
+    blades_state: &BladesState,
+    render_info: &BladeRenderInfo,
+    animation: &BladesAnimationState,
+    font_cache: &HashMap<String, Font>,
+    config: &Config,
+    scale_factor: f32,
+) {
     // Metallic base with per-blade accent strip (pulled from config colors)
     let accent_color = match blade.blade_type {
         BladeType::GamesAndApps => string_to_color(&config.blade_games_color),
@@ -332,7 +668,12 @@ fn render_blade(blade: &Blade, blades_state: &BladesState, render_info: &BladeRe
     base_top.a *= config.blade_transparency;
     base_bottom.a *= config.blade_transparency;
 
-    let is_active = blades_state.current_blade == blades_state.blades.iter().position(|b| b.blade_type == blade.blade_type).unwrap();
+    let is_active = blades_state.current_blade
+        == blades_state
+            .blades
+            .iter()
+            .position(|b| b.blade_type == blade.blade_type)
+            .unwrap();
     draw_blade_panel(
         render_info.x,
         render_info.width,
@@ -343,19 +684,49 @@ fn render_blade(blade: &Blade, blades_state: &BladesState, render_info: &BladeRe
         is_active,
         accent_color,
     );
-    
-    render_blade_tabs(blade, render_info, animation, font_cache, config, scale_factor, accent_color);
-    render_blade_title(blade, render_info, font_cache, config, scale_factor, accent_color);
+
+    render_blade_tabs(
+        blade,
+        render_info,
+        animation,
+        font_cache,
+        config,
+        scale_factor,
+        accent_color,
+    );
+    render_blade_title(
+        blade,
+        render_info,
+        font_cache,
+        config,
+        scale_factor,
+        accent_color,
+    );
 
     if is_active {
         if blade.blade_type == BladeType::GamesAndApps && blade.selected_tab == 0 {
             // Only show the game library on the Games & Apps blade, Library tab
-            render_games_blade_content(blades_state, render_info, font_cache, config, scale_factor, accent_color);
+            render_games_blade_content(
+                blades_state,
+                render_info,
+                font_cache,
+                config,
+                scale_factor,
+                accent_color,
+            );
         }
     }
 }
 
-fn render_blade_tabs(blade: &Blade, render_info: &BladeRenderInfo, animation: &BladesAnimationState, font_cache: &HashMap<String, Font>, config: &Config, scale_factor: f32, _accent: Color) {
+fn render_blade_tabs(
+    blade: &Blade,
+    render_info: &BladeRenderInfo,
+    animation: &BladesAnimationState,
+    font_cache: &HashMap<String, Font>,
+    config: &Config,
+    scale_factor: f32,
+    _accent: Color,
+) {
     let font = get_current_font(font_cache, config);
     let font_size = (20.0 * scale_factor) as u16;
     let mut y_pos = 110.0 * scale_factor;
@@ -368,25 +739,39 @@ fn render_blade_tabs(blade: &Blade, render_info: &BladeRenderInfo, animation: &B
             &tab.name,
             render_info.x + (TAB_PADDING * scale_factor),
             y_pos,
-            TextParams { font: Some(font), font_size, color: text_color, ..Default::default() }
+            TextParams {
+                font: Some(font),
+                font_size,
+                color: text_color,
+                ..Default::default()
+            },
         );
-        
+
         if is_selected {
             let glow_alpha = animation.get_tab_glow_alpha();
             let mut glow_color = WHITE;
             glow_color.a = glow_alpha;
             draw_line(
-                render_info.x, y_pos + (5.0 * scale_factor),
-                render_info.x + render_info.width, y_pos + (5.0 * scale_factor),
+                render_info.x,
+                y_pos + (5.0 * scale_factor),
+                render_info.x + render_info.width,
+                y_pos + (5.0 * scale_factor),
                 GLOW_THICKNESS * scale_factor,
-                glow_color
+                glow_color,
             );
         }
         y_pos += TAB_HEIGHT * scale_factor;
     }
 }
 
-fn render_games_blade_content(blades_state: &BladesState, render_info: &BladeRenderInfo, font_cache: &HashMap<String, Font>, config: &Config, scale_factor: f32, accent: Color) {
+fn render_games_blade_content(
+    blades_state: &BladesState,
+    render_info: &BladeRenderInfo,
+    font_cache: &HashMap<String, Font>,
+    config: &Config,
+    scale_factor: f32,
+    accent: Color,
+) {
     let font = get_current_font(font_cache, config);
     let font_size = (18.0 * scale_factor) as u16;
     let row_height = 34.0 * scale_factor;
@@ -407,7 +792,13 @@ fn render_games_blade_content(blades_state: &BladesState, render_info: &BladeRen
     // Container
     draw_rectangle(container_x, container_y, container_w, container_h, panel_bg);
     draw_rectangle(container_x, container_y, container_w, header_h, header_bg);
-    draw_rectangle(container_x, container_y + header_h - (3.0 * scale_factor), container_w, 3.0 * scale_factor, accent_line);
+    draw_rectangle(
+        container_x,
+        container_y + header_h - (3.0 * scale_factor),
+        container_w,
+        3.0 * scale_factor,
+        accent_line,
+    );
 
     // Header title
     let header_label = "Game Library";
@@ -416,54 +807,299 @@ fn render_games_blade_content(blades_state: &BladesState, render_info: &BladeRen
         header_label,
         container_x + (12.0 * scale_factor),
         container_y + header_h / 2.0 + label_dims.height / 2.5,
-        TextParams { font: Some(font), font_size, color: WHITE, ..Default::default() }
+        TextParams {
+            font: Some(font),
+            font_size,
+            color: WHITE,
+            ..Default::default()
+        },
+    );
+
+    // Sort dropdown, right-aligned in the header
+    let small_font_size = (14.0 * scale_factor) as u16;
+    let sort_label = format!("SORT: {} v", blades_state.library_order.sort.label());
+    let sort_dims = measure_text(&sort_label, Some(font), small_font_size, 1.0);
+    let sort_x = container_x + container_w - sort_dims.width - (12.0 * scale_factor);
+    let header_text_y = container_y + header_h / 2.0 + sort_dims.height / 2.5;
+    let sort_active = blades_state.sort_focused || blades_state.sort_menu.is_some();
+    if sort_active {
+        let pad = 4.0 * scale_factor;
+        draw_rectangle_lines(
+            sort_x - pad,
+            container_y + pad,
+            sort_dims.width + pad * 2.0,
+            header_h - pad * 3.0,
+            2.0 * scale_factor,
+            accent,
+        );
+    }
+    draw_text_ex(
+        &sort_label,
+        sort_x,
+        header_text_y,
+        TextParams {
+            font: Some(font),
+            font_size: small_font_size,
+            color: if sort_active { WHITE } else { GRAY },
+            ..Default::default()
+        },
+    );
+
+    // Collection filter row, under the header
+    let filter_h = 28.0 * scale_factor;
+    let filter_y = container_y + header_h;
+    let filter_name = match &blades_state.collection_filter {
+        Some(name) => format!("{} ({})", name, blades_state.games_list.len()),
+        None => "ALL GAMES".to_string(),
+    };
+    let filter_label = if blades_state.filter_focused {
+        format!("COLLECTION: < {} >", filter_name)
+    } else {
+        format!("COLLECTION: {}", filter_name)
+    };
+    let filter_dims = measure_text(&filter_label, Some(font), small_font_size, 1.0);
+    if blades_state.filter_focused {
+        let pad = 4.0 * scale_factor;
+        draw_rectangle_lines(
+            container_x + (8.0 * scale_factor),
+            filter_y + pad,
+            filter_dims.width + pad * 2.0 + (4.0 * scale_factor),
+            filter_h - pad,
+            2.0 * scale_factor,
+            accent,
+        );
+    }
+    draw_text_ex(
+        &filter_label,
+        container_x + (14.0 * scale_factor),
+        filter_y + filter_h / 2.0 + filter_dims.height / 2.0 + (2.0 * scale_factor),
+        TextParams {
+            font: Some(font),
+            font_size: small_font_size,
+            color: if blades_state.filter_focused {
+                WHITE
+            } else {
+                GRAY
+            },
+            ..Default::default()
+        },
     );
 
-    let y_pos = container_y + header_h + (8.0 * scale_factor);
+    // Controls, under the panel
+    let hint = "[WEST] FAVORITE | [NORTH] COLLECTIONS | [LB/RB] MOVE | [UP] FILTER";
+    draw_text_ex(
+        hint,
+        container_x + (12.0 * scale_factor),
+        container_y + container_h + (20.0 * scale_factor),
+        TextParams {
+            font: Some(font),
+            font_size: small_font_size,
+            color: GRAY,
+            ..Default::default()
+        },
+    );
+
+    let y_pos = filter_y + filter_h + (4.0 * scale_factor);
 
     if blades_state.games_list.is_empty() {
-        let message = "No games detected";
+        let message = if blades_state.collection_filter.is_some() {
+            "No games in this collection"
+        } else {
+            "No games detected"
+        };
         let dims = measure_text(message, Some(font), font_size, 1.0);
         draw_text_ex(
             message,
             container_x + (12.0 * scale_factor),
             y_pos + dims.height,
-            TextParams { font: Some(font), font_size, color: GRAY, ..Default::default() },
+            TextParams {
+                font: Some(font),
+                font_size,
+                color: GRAY,
+                ..Default::default()
+            },
+        );
+        render_sort_menu(
+            blades_state,
+            container_x + container_w,
+            container_y + header_h,
+            font,
+            small_font_size,
+            accent,
+            scale_factor,
         );
         return;
     }
 
     for (i, (cart_info, _)) in blades_state.games_list.iter().enumerate() {
         let row_y = y_pos + (i as f32 * row_height);
-        let is_selected = i == blades_state.game_list_selection;
+        let is_selected = i == blades_state.game_list_selection
+            && !blades_state.sort_focused
+            && !blades_state.filter_focused;
 
         // Row background
         let mut bg = base_bg;
         if is_selected {
             bg.a = 0.45 * render_info.alpha;
         }
-        draw_rectangle(content_left, row_y, row_width, row_height - (6.0 * scale_factor), bg);
+        draw_rectangle(
+            content_left,
+            row_y,
+            row_width,
+            row_height - (6.0 * scale_factor),
+            bg,
+        );
 
         // Accent strip for selection
         if is_selected {
             let strip_width = 4.0 * scale_factor;
-            draw_rectangle(content_left, row_y, strip_width, row_height - (6.0 * scale_factor), accent);
+            draw_rectangle(
+                content_left,
+                row_y,
+                strip_width,
+                row_height - (6.0 * scale_factor),
+                accent,
+            );
+        }
+
+        // Cart icon, space kept while it loads
+        let icon_size = row_height - (12.0 * scale_factor);
+        let icon_x = content_left + (12.0 * scale_factor);
+        if let Some(texture) = blades_state.game_icon_cache.get(&cart_info.id) {
+            draw_texture_ex(
+                texture,
+                icon_x,
+                row_y + (3.0 * scale_factor),
+                Color::new(1.0, 1.0, 1.0, render_info.alpha),
+                DrawTextureParams {
+                    dest_size: Some(vec2(icon_size, icon_size)),
+                    ..Default::default()
+                },
+            );
         }
 
         let text_color = if is_selected { WHITE } else { GRAY };
         let game_name = cart_info.name.as_deref().unwrap_or("Unknown Game");
         let text_y = row_y + row_height / 2.0 + (font_size as f32 * 0.35);
+        let mut name_x = icon_x + icon_size + (10.0 * scale_factor);
+
+        if blades_state.library_order.is_favorite(&cart_info.id) {
+            let radius = 6.0 * scale_factor;
+            draw_star(
+                name_x + radius,
+                row_y + (row_height - 6.0 * scale_factor) / 2.0,
+                radius,
+                FAVORITE_COLOR,
+            );
+            name_x += radius * 2.0 + (6.0 * scale_factor);
+        }
 
         draw_text_ex(
             game_name,
-            content_left + (12.0 * scale_factor),
+            name_x,
             text_y,
-            TextParams { font: Some(font), font_size, color: text_color, ..Default::default() }
+            TextParams {
+                font: Some(font),
+                font_size,
+                color: text_color,
+                ..Default::default()
+            },
         );
     }
+
+    render_sort_menu(
+        blades_state,
+        container_x + container_w,
+        container_y + header_h,
+        font,
+        small_font_size,
+        accent,
+        scale_factor,
+    );
 }
 
-fn draw_blade_panel(x: f32, width: f32, height: f32, top: Color, bottom: Color, scale_factor: f32, is_active: bool, accent_color: Color) {
+/// The open sort dropdown, hanging from the header's right edge
+fn render_sort_menu(
+    blades_state: &BladesState,
+    right: f32,
+    menu_y: f32,
+    font: &Font,
+    small_font_size: u16,
+    accent: Color,
+    scale_factor: f32,
+) {
+    let Some(highlight) = blades_state.sort_menu else {
+        return;
+    };
+    let item_h = 26.0 * scale_factor;
+    let menu_w = 190.0 * scale_factor;
+    let menu_x = right - menu_w - (8.0 * scale_factor);
+    draw_rectangle(
+        menu_x,
+        menu_y,
+        menu_w,
+        item_h * SortMode::ALL.len() as f32,
+        Color::new(0.1, 0.1, 0.12, 0.97),
+    );
+    draw_rectangle_lines(
+        menu_x,
+        menu_y,
+        menu_w,
+        item_h * SortMode::ALL.len() as f32,
+        2.0 * scale_factor,
+        accent,
+    );
+    for (i, mode) in SortMode::ALL.iter().enumerate() {
+        let item_y = menu_y + i as f32 * item_h;
+        if i == highlight {
+            let mut bg = accent;
+            bg.a = 0.35;
+            draw_rectangle(menu_x, item_y, menu_w, item_h, bg);
+        }
+        let color = if *mode == blades_state.library_order.sort || i == highlight {
+            WHITE
+        } else {
+            GRAY
+        };
+        draw_text_ex(
+            mode.label(),
+            menu_x + (10.0 * scale_factor),
+            item_y + item_h / 2.0 + (small_font_size as f32 * 0.35),
+            TextParams {
+                font: Some(font),
+                font_size: small_font_size,
+                color,
+                ..Default::default()
+            },
+        );
+    }
+}
+
+const FAVORITE_COLOR: Color = Color::new(1.0, 0.8, 0.2, 1.0);
+
+/// A five-pointed star; the fonts have no star glyph
+fn draw_star(x: f32, y: f32, radius: f32, color: Color) {
+    let point = |i: usize| {
+        let r = if i % 2 == 0 { radius } else { radius * 0.45 };
+        let angle = -PI / 2.0 + i as f32 * PI / 5.0;
+        vec2(x + r * angle.cos(), y + r * angle.sin())
+    };
+    let center = vec2(x, y);
+    for i in 0..10 {
+        draw_triangle(center, point(i), point((i + 1) % 10), color);
+    }
+}
+
+fn draw_blade_panel(
+    x: f32,
+    width: f32,
+    height: f32,
+    top: Color,
+    bottom: Color,
+    scale_factor: f32,
+    is_active: bool,
+    accent_color: Color,
+) {
     // Flat metallic panel with subtle vertical gradient, no curvature for now.
     let step = (3.0 * scale_factor).max(1.0);
     let accent_strip = 8.0 * scale_factor;
@@ -486,23 +1122,28 @@ fn draw_blade_panel(x: f32, width: f32, height: f32, top: Color, bottom: Color,
         0.0,
         accent_strip,
         height,
-        Color::new(accent_color.r, accent_color.g, accent_color.b, bottom.a * 0.8),
+        Color::new(
+            accent_color.r,
+            accent_color.g,
+            accent_color.b,
+            bottom.a * 0.8,
+        ),
     );
 
     // Highlight rim on active blade
     if is_active {
-        draw_rectangle_lines(
-            x,
-            0.0,
-            width,
-            height,
-            3.0 * scale_factor,
-            rim_color,
-        );
+        draw_rectangle_lines(x, 0.0, width, height, 3.0 * scale_factor, rim_color);
     }
 }
 
-fn render_blade_title(blade: &Blade, render_info: &BladeRenderInfo, font_cache: &HashMap<String, Font>, config: &Config, scale_factor: f32, accent: Color) {
+fn render_blade_title(
+    blade: &Blade,
+    render_info: &BladeRenderInfo,
+    font_cache: &HashMap<String, Font>,
+    config: &Config,
+    scale_factor: f32,
+    accent: Color,
+) {
     let font = get_current_font(font_cache, config);
     let font_size = (28.0 * scale_factor) as u16;
     let title = &blade.name;
@@ -537,7 +1178,12 @@ fn render_blade_title(blade: &Blade, render_info: &BladeRenderInfo, font_cache:
         title,
         x,
         y,
-        TextParams { font: Some(font), font_size, color: title_color, ..Default::default() }
+        TextParams {
+            font: Some(font),
+            font_size,
+            color: title_color,
+            ..Default::default()
+        },
     );
 }
 
@@ -0,0 +1,281 @@
+use macroquad::prelude::*;
+use rodio::{buffer::SamplesBuffer, Decoder, Sink, Source};
+use std::collections::HashMap;
+use std::io::{Cursor, Write};
+use std::path::PathBuf;
+use std::time::Duration;
+use tempfile::NamedTempFile;
+
+use crate::{
+    audio::AUDIO,
+    config::{get_user_data_dir, Config},
+    ui::farewell::branding_image,
+    video::VideoPlayer,
+    BASE_SCREEN_HEIGHT,
+};
+
+/// `splash_video` value for the animated logo instead of a video, and
+/// `splash_audio` value for no sound
+pub const SPLASH_OFF: &str = "OFF";
+
+/// Length of the animated logo without a sound to follow
+const LOGO_DURATION: f64 = 3.0;
+/// The animated logo follows the sound's length within these bounds
+const MIN_LOGO_DURATION: f64 = 2.0;
+const MAX_LOGO_DURATION: f64 = 6.0;
+const FADE: f64 = 0.5;
+/// Time the logo takes to grow to full size
+const ZOOM: f64 = 0.8;
+const START_SCALE: f32 = 0.85;
+
+/// The animation and sound played when the BIOS starts
+pub struct BootSplash {
+    video: Option<VideoPlayer>,
+    /// Animated in place of a video, or when the video can't be read
+    logo: Texture2D,
+    duration: f64,
+    started: f64,
+    /// Dropping the splash stops the sound, e.g. when it's skipped
+    _sink: Option<Sink>,
+}
+
+impl BootSplash {
+    /// Loads the configured video or image and starts the splash sound
+    pub fn start(
+        config: &Config,
+        logo_cache: &HashMap<String, Texture2D>,
+        music_cache: &HashMap<String, SamplesBuffer>,
+    ) -> Self {
+        let video = load_video(config);
+        let logo = branding_image(config, &config.splash_image, logo_cache);
+
+        let (sink, sound_length) = match start_sound(config, music_cache) {
+            Some((sink, length)) => (Some(sink), length),
+            None => (None, None),
+        };
+        let duration = match &video {
+            Some(player) => player.duration_secs,
+            None => logo_duration(sound_length),
+        };
+
+        println!(
+            "[Boot] Playing {} boot splash ({:.1}s)",
+            if video.is_some() { "video" } else { "logo" },
+            duration
+        );
+        Self {
+            video,
+            logo,
+            duration,
+            started: get_time(),
+            _sink: sink,
+        }
+    }
+
+    pub fn is_done(&self) -> bool {
+        get_time() - self.started > self.duration
+    }
+
+    /// Draws the current video frame fullscreen, or the animated logo
+    pub fn draw(&mut self) {
+        let elapsed = get_time() - self.started;
+        clear_background(BLACK);
+
+        if let Some(player) = &mut self.video {
+            player.update(elapsed);
+            draw_texture_ex(
+                &player.texture,
+                0.0,
+                0.0,
+                WHITE,
+                DrawTextureParams {
+                    dest_size: Some(vec2(screen_width(), screen_height())),
+                    ..Default::default()
+                },
+            );
+            return;
+        }
+
+        // The logo fades in while growing to full size, with a bar under it
+        // filling up until the fade-out
+        let (alpha, scale, progress) = logo_frame(elapsed, self.duration);
+        let screen_scale = screen_height() / BASE_SCREEN_HEIGHT;
+        let aspect_ratio = self.logo.height() / self.logo.width();
+        let width = 200.0 * screen_scale * scale;
+        let height = width * aspect_ratio;
+        let y = (screen_height() - height) / 2.0;
+        draw_texture_ex(
+            &self.logo,
+            (screen_width() - width) / 2.0,
+            y,
+            Color::new(1.0, 1.0, 1.0, alpha),
+            DrawTextureParams {
+                dest_size: Some(vec2(width, height)),
+                ..Default::default()
+            },
+        );
+
+        let bar_width = 200.0 * screen_scale;
+        let bar_x = (screen_width() - bar_width) / 2.0;
+        let bar_y = (screen_height() + bar_width * aspect_ratio) / 2.0 + 24.0 * screen_scale;
+        let bar_height = 3.0 * screen_scale;
+        draw_rectangle(
+            bar_x,
+            bar_y,
+            bar_width,
+            bar_height,
+            Color::new(1.0, 1.0, 1.0, alpha * 0.2),
+        );
+        draw_rectangle(
+            bar_x,
+            bar_y,
+            bar_width * progress,
+            bar_height,
+            Color::new(1.0, 1.0, 1.0, alpha),
+        );
+    }
+}
+
+/// The custom splash video from the theme's folder or the backgrounds, else
+/// the built-in one. None for the animated logo, or if the video won't open.
+fn load_video(config: &Config) -> Option<VideoPlayer> {
+    if config.splash_video == SPLASH_OFF {
+        return None;
+    }
+
+    if config.splash_video != "Default" {
+        if let Some(user_dir) = get_user_data_dir() {
+            let candidates: [PathBuf; 2] = [
+                user_dir
+                    .join("themes")
+                    .join(&config.theme)
+                    .join(&config.splash_video),
+                user_dir.join("backgrounds").join(&config.splash_video),
+            ];
+            if let Some(path) = candidates.iter().find(|p| p.exists()) {
+                match VideoPlayer::new(path) {
+                    Ok(player) => return Some(player),
+                    Err(e) => println!(
+                        "[Boot] Failed to open splash video {}: {}",
+                        path.display(),
+                        e
+                    ),
+                }
+            }
+        }
+    }
+
+    // The decoder keeps the file open, so the temp file can go once it's read
+    let mut temp_video = NamedTempFile::new().ok()?;
+    temp_video
+        .write_all(include_bytes!("../../splash.mp4"))
+        .ok()?;
+    VideoPlayer::new(temp_video.path()).ok()
+}
+
+/// Starts the splash sound: a track from the music folder, else the built-in
+/// one. Returns the sink and the sound's length, if known.
+fn start_sound(
+    config: &Config,
+    music_cache: &HashMap<String, SamplesBuffer>,
+) -> Option<(Sink, Option<Duration>)> {
+    if config.splash_audio == SPLASH_OFF {
+        return None;
+    }
+
+    let sink = Sink::connect_new(&AUDIO.stream.mixer());
+    let custom = (config.splash_audio != "Default")
+        .then(|| music_cache.get(&config.splash_audio))
+        .flatten();
+    let length = match custom {
+        Some(buffer) => {
+            let length = buffer.total_duration();
+            sink.append(buffer.clone());
+            length
+        }
+        None => {
+            let source = match Decoder::new(Cursor::new(include_bytes!("../../splash.wav"))) {
+                Ok(source) => source,
+                Err(e) => {
+                    println!("[Boot] Failed to decode the built-in splash sound: {}", e);
+                    return None;
+                }
+            };
+            let length = source.total_duration();
+            sink.append(source);
+            length
+        }
+    };
+    Some((sink, length))
+}
+
+/// How long the animated logo plays: as long as the sound, within bounds
+fn logo_duration(sound_length: Option<Duration>) -> f64 {
+    sound_length
+        .map(|length| {
+            length
+                .as_secs_f64()
+                .clamp(MIN_LOGO_DURATION, MAX_LOGO_DURATION)
+        })
+        .unwrap_or(LOGO_DURATION)
+}
+
+/// Alpha, scale and progress bar fill of the animated logo `elapsed` seconds in
+fn logo_frame(elapsed: f64, duration: f64) -> (f32, f32, f32) {
+    let alpha = if elapsed < FADE {
+        elapsed / FADE
+    } else if elapsed > duration - FADE {
+        (duration - elapsed) / FADE
+    } else {
+        1.0
+    }
+    .clamp(0.0, 1.0);
+
+    // Ease out, so the logo slows as it settles
+    let zoom = 1.0 - (1.0 - (elapsed / ZOOM).clamp(0.0, 1.0)).powi(3);
+    let scale = START_SCALE + (1.0 - START_SCALE) * zoom as f32;
+
+    let progress = (elapsed / (duration - FADE)).clamp(0.0, 1.0);
+    (alpha as f32, scale, progress as f32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_logo_follows_the_sound() {
+        assert_eq!(logo_duration(None), LOGO_DURATION);
+        assert_eq!(logo_duration(Some(Duration::from_millis(4500))), 4.5);
+        assert_eq!(
+            logo_duration(Some(Duration::from_millis(300))),
+            MIN_LOGO_DURATION
+        );
+        assert_eq!(
+            logo_duration(Some(Duration::from_secs(90))),
+            MAX_LOGO_DURATION
+        );
+    }
+
+    #[test]
+    fn test_logo_animation() {
+        let duration = 3.0;
+
+        let (alpha, scale, progress) = logo_frame(0.0, duration);
+        assert_eq!((alpha, scale, progress), (0.0, START_SCALE, 0.0));
+
+        // Grows quickly at first, then settles at full size
+        let (_, early, _) = logo_frame(ZOOM / 4.0, duration);
+        assert!(early - START_SCALE > (1.0 - START_SCALE) / 4.0);
+        let (alpha, scale, progress) = logo_frame(1.5, duration);
+        assert_eq!(alpha, 1.0);
+        assert!((scale - 1.0).abs() < 1e-6);
+        assert!(progress > 0.5 && progress < 1.0);
+
+        // The bar is full before the fade-out ends
+        let (alpha, _, progress) = logo_frame(duration - FADE / 2.0, duration);
+        assert!(alpha > 0.0 && alpha < 1.0);
+        assert_eq!(progress, 1.0);
+        assert_eq!(logo_frame(duration + 1.0, duration).0, 0.0);
+    }
+}
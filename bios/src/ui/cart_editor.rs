@@ -0,0 +1,627 @@
+use macroquad::prelude::*;
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    audio::SoundEffects,
+    cart_metadata::{self, CartMetadata, SaveLocation},
+    config::Config,
+    get_current_font, measure_text, render_background, render_ui_overlay,
+    save::{self, CartInfo},
+    text_with_config_color,
+    types::{AnimationState, BackgroundState, BatteryInfo, Screen},
+    ui::text_with_color,
+    InputState, VideoPlayer, FONT_SIZE, MENU_OPTION_HEIGHT, MENU_PADDING,
+};
+
+const CART_EDITOR_OPTIONS: &[&str] = &["NAME", "ICON", "MAX PLAYERS", "RA GAME NAME", "SAVE"];
+
+const KEYS_LOWER: &[&str] = &[
+    "1234567890",
+    "qwertyuiop",
+    "asdfghjkl-",
+    "zxcvbnm:'!",
+    "&.,()/?+#_",
+];
+const KEYS_UPPER: &[&str] = &[
+    "1234567890",
+    "QWERTYUIOP",
+    "ASDFGHJKL-",
+    "ZXCVBNM:'!",
+    "&.,()/?+#_",
+];
+const SPECIAL_KEYS: &[&str] = &["SHIFT", "SPACE", "DEL", "CLEAR", "DONE"];
+
+/// Longest name accepted from the keyboard
+const MAX_TEXT_LEN: usize = 64;
+
+/// Text field being typed into
+#[derive(Clone, Copy, PartialEq)]
+enum TextField {
+    Name,
+    RaGameName,
+}
+
+struct Keyboard {
+    field: TextField,
+    coords: (usize, usize),
+    shift: bool,
+}
+
+/// State for the cartridge metadata editor
+pub struct CartEditorState {
+    pub selection: usize,
+    kzi_path: PathBuf,
+    cart_id: String,
+    metadata: CartMetadata,
+    /// Metadata as last loaded or saved, to tell whether there are edits
+    saved_metadata: CartMetadata,
+    icons: Vec<String>,
+    keyboard: Option<Keyboard>,
+    status: Option<(String, bool)>,
+    /// Set after a save so the games list can pick up the new metadata
+    updated: Option<CartInfo>,
+}
+
+impl CartEditorState {
+    pub fn new() -> Self {
+        Self {
+            selection: 0,
+            kzi_path: PathBuf::new(),
+            cart_id: String::new(),
+            metadata: CartMetadata::default(),
+            saved_metadata: CartMetadata::default(),
+            icons: Vec::new(),
+            keyboard: None,
+            status: None,
+            updated: None,
+        }
+    }
+
+    /// Loads a cart's metadata before showing the screen
+    pub fn open(&mut self, cart_info: &CartInfo, kzi_path: &Path) {
+        self.selection = 0;
+        self.kzi_path = kzi_path.to_path_buf();
+        self.cart_id = cart_info.id.clone();
+        self.metadata = CartMetadata::from_cart(cart_info);
+        self.saved_metadata = self.metadata.clone();
+        self.icons = cart_metadata::find_icons(kzi_path);
+        // Keep the configured icon selectable even if the file is missing
+        if !self.icons.contains(&self.metadata.icon) {
+            self.icons.insert(0, self.metadata.icon.clone());
+        }
+        self.keyboard = None;
+        self.status = None;
+        self.updated = None;
+    }
+
+    /// Takes the re-parsed cart after a save
+    pub fn take_updated(&mut self) -> Option<CartInfo> {
+        self.updated.take()
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.metadata != self.saved_metadata
+    }
+
+    fn text_mut(&mut self, field: TextField) -> &mut String {
+        match field {
+            TextField::Name => &mut self.metadata.name,
+            TextField::RaGameName => &mut self.metadata.ra_game_name,
+        }
+    }
+
+    fn cycle_icon(&mut self, forward: bool) {
+        if self.icons.is_empty() {
+            return;
+        }
+        let len = self.icons.len();
+        let index = self
+            .icons
+            .iter()
+            .position(|i| *i == self.metadata.icon)
+            .unwrap_or(0);
+        let next = if forward {
+            (index + 1) % len
+        } else {
+            (index + len - 1) % len
+        };
+        self.metadata.icon = self.icons[next].clone();
+    }
+
+    /// Steps through "auto" (not set) and 1-4 players
+    fn cycle_max_players(&mut self, forward: bool) {
+        self.metadata.max_players = match (self.metadata.max_players, forward) {
+            (None, true) => Some(1),
+            (Some(n), true) if n < 4 => Some(n + 1),
+            (Some(_), true) => None,
+            (None, false) => Some(4),
+            (Some(n), false) if n > 1 => Some(n - 1),
+            (Some(_), false) => None,
+        };
+    }
+
+    fn save(&mut self) {
+        if self.metadata.name.trim().is_empty() {
+            self.status = Some(("Name can't be empty".to_string(), false));
+            return;
+        }
+
+        match cart_metadata::save(&self.kzi_path, &self.cart_id, &self.metadata) {
+            Ok(location) => {
+                self.saved_metadata = self.metadata.clone();
+                let message = match location {
+                    SaveLocation::Cart => "Saved to cartridge",
+                    SaveLocation::Override => "Cartridge is read-only, saved on this system",
+                };
+                self.status = Some((message.to_string(), true));
+                match save::parse_kzi_file(&self.kzi_path) {
+                    Ok(cart_info) => self.updated = Some(cart_info),
+                    Err(e) => {
+                        println!("[Cart] Failed to reload {}: {}", self.kzi_path.display(), e)
+                    }
+                }
+            }
+            Err(e) => self.status = Some((e, false)),
+        }
+    }
+
+    /// Handles input while the keyboard is open
+    fn update_keyboard(
+        &mut self,
+        input_state: &InputState,
+        sound_effects: &SoundEffects,
+        config: &Config,
+    ) {
+        let Some(keyboard) = &mut self.keyboard else {
+            return;
+        };
+        let (row, col) = &mut keyboard.coords;
+        let layout = if keyboard.shift {
+            KEYS_UPPER
+        } else {
+            KEYS_LOWER
+        };
+        let num_rows = layout.len() + 1;
+
+        if input_state.up && *row > 0 {
+            *row -= 1;
+            sound_effects.play_cursor_move(config);
+        }
+        if input_state.down && *row < num_rows - 1 {
+            *row += 1;
+            sound_effects.play_cursor_move(config);
+        }
+
+        let row_len = if *row < layout.len() {
+            layout[*row].chars().count()
+        } else {
+            SPECIAL_KEYS.len()
+        };
+        *col = (*col).min(row_len - 1);
+        if input_state.left && *col > 0 {
+            *col -= 1;
+            sound_effects.play_cursor_move(config);
+        }
+        if input_state.right && *col < row_len - 1 {
+            *col += 1;
+            sound_effects.play_cursor_move(config);
+        }
+
+        if input_state.back {
+            self.keyboard = None;
+            sound_effects.play_back(config);
+            return;
+        }
+        if !input_state.select {
+            return;
+        }
+
+        sound_effects.play_select(config);
+        let field = keyboard.field;
+        if *row < layout.len() {
+            if let Some(key) = layout[*row].chars().nth(*col) {
+                // Like a phone keyboard, shift applies to one letter
+                keyboard.shift = false;
+                let text = self.text_mut(field);
+                if text.chars().count() < MAX_TEXT_LEN {
+                    text.push(key);
+                }
+            }
+        } else {
+            match SPECIAL_KEYS[*col] {
+                "SHIFT" => keyboard.shift = !keyboard.shift,
+                "SPACE" => {
+                    let text = self.text_mut(field);
+                    if text.chars().count() < MAX_TEXT_LEN {
+                        text.push(' ');
+                    }
+                }
+                "DEL" => {
+                    self.text_mut(field).pop();
+                }
+                "CLEAR" => self.text_mut(field).clear(),
+                "DONE" => self.keyboard = None,
+                _ => {}
+            }
+        }
+    }
+}
+
+pub fn update(
+    current_screen: &mut Screen,
+    state: &mut CartEditorState,
+    input_state: &InputState,
+    animation_state: &mut AnimationState,
+    sound_effects: &SoundEffects,
+    config: &Config,
+) {
+    if state.keyboard.is_some() {
+        state.update_keyboard(input_state, sound_effects, config);
+        return;
+    }
+
+    if input_state.back {
+        // Unsaved edits are discarded
+        *current_screen = Screen::GameSelection;
+        sound_effects.play_back(config);
+        return;
+    }
+
+    if input_state.up {
+        state.selection = if state.selection == 0 {
+            CART_EDITOR_OPTIONS.len() - 1
+        } else {
+            state.selection - 1
+        };
+        animation_state.trigger_transition(&config.cursor_transition_speed);
+        sound_effects.play_cursor_move(config);
+    }
+    if input_state.down {
+        state.selection = (state.selection + 1) % CART_EDITOR_OPTIONS.len();
+        animation_state.trigger_transition(&config.cursor_transition_speed);
+        sound_effects.play_cursor_move(config);
+    }
+
+    let changed = input_state.left || input_state.right;
+    let forward = input_state.right;
+
+    match state.selection {
+        0 | 3 => {
+            // NAME, RA GAME NAME
+            if input_state.select {
+                let field = if state.selection == 0 {
+                    TextField::Name
+                } else {
+                    TextField::RaGameName
+                };
+                state.keyboard = Some(Keyboard {
+                    field,
+                    coords: (1, 0),
+                    shift: state.text_mut(field).is_empty(),
+                });
+                sound_effects.play_select(config);
+            }
+        }
+        1 => {
+            // ICON
+            if changed {
+                state.cycle_icon(forward);
+                sound_effects.play_cursor_move(config);
+            }
+        }
+        2 => {
+            // MAX PLAYERS
+            if changed {
+                state.cycle_max_players(forward);
+                sound_effects.play_cursor_move(config);
+            }
+        }
+        4 => {
+            // SAVE
+            if input_state.select {
+                state.save();
+                sound_effects.play_select(config);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn get_option_value(index: usize, state: &CartEditorState) -> String {
+    let text = |s: &str| {
+        if s.is_empty() {
+            "-".to_string()
+        } else {
+            s.to_string()
+        }
+    };
+    match index {
+        0 => text(&state.metadata.name),
+        1 => text(&state.metadata.icon),
+        2 => state
+            .metadata
+            .max_players
+            .map_or("AUTO".to_string(), |n| n.to_string()),
+        3 => text(&state.metadata.ra_game_name),
+        4 => {
+            if state.is_dirty() {
+                "UNSAVED CHANGES".to_string()
+            } else {
+                "UP TO DATE".to_string()
+            }
+        }
+        _ => String::new(),
+    }
+}
+
+fn draw_keyboard(
+    state: &CartEditorState,
+    keyboard: &Keyboard,
+    animation_state: &AnimationState,
+    font_cache: &HashMap<String, Font>,
+    config: &Config,
+    scale_factor: f32,
+) {
+    let font_size = (FONT_SIZE as f32 * scale_factor) as u16;
+    let current_font = get_current_font(font_cache, config);
+    let key_spacing = font_size as f32 * 2.2;
+    let row_height = font_size as f32 * 2.0;
+    let layout = if keyboard.shift {
+        KEYS_UPPER
+    } else {
+        KEYS_LOWER
+    };
+
+    let box_w = layout[0].len() as f32 * key_spacing + 40.0 * scale_factor;
+    let box_h = (layout.len() as f32 + 3.0) * row_height + 20.0 * scale_factor;
+    let box_x = screen_width() / 2.0 - box_w / 2.0;
+    let box_y = screen_height() / 2.0 - box_h / 2.0;
+    draw_rectangle(box_x, box_y, box_w, box_h, Color::new(0.0, 0.0, 0.0, 0.9));
+    draw_rectangle_lines(box_x, box_y, box_w, box_h, 2.0 * scale_factor, WHITE);
+
+    let (label, text) = match keyboard.field {
+        TextField::Name => ("NAME", &state.metadata.name),
+        TextField::RaGameName => ("RA GAME NAME", &state.metadata.ra_game_name),
+    };
+    let entry = format!("{}: {}_", label, text);
+    text_with_config_color(
+        font_cache,
+        config,
+        &entry,
+        box_x + 20.0 * scale_factor,
+        box_y + row_height,
+        font_size,
+    );
+
+    let cursor_color = animation_state.get_cursor_color(config);
+    let keys_x = box_x + 20.0 * scale_factor;
+    let keys_y = box_y + row_height * 2.5;
+    let (sel_row, sel_col) = keyboard.coords;
+
+    for (r, row) in layout.iter().enumerate() {
+        for (c, key) in row.chars().enumerate() {
+            let key_str = key.to_string();
+            let x = keys_x + c as f32 * key_spacing;
+            let y = keys_y + r as f32 * row_height;
+            if (r, c) == (sel_row, sel_col) {
+                let dims = measure_text(&key_str, Some(current_font), font_size, 1.0);
+                draw_rectangle_lines(
+                    x - 4.0,
+                    y - font_size as f32 - 2.0,
+                    dims.width + 8.0,
+                    font_size as f32 + 8.0,
+                    2.0 * scale_factor,
+                    cursor_color,
+                );
+                text_with_color(font_cache, config, &key_str, x, y, font_size, cursor_color);
+            } else {
+                text_with_config_color(font_cache, config, &key_str, x, y, font_size);
+            }
+        }
+    }
+
+    let special_y = keys_y + layout.len() as f32 * row_height;
+    let mut x = keys_x;
+    for (c, key) in SPECIAL_KEYS.iter().enumerate() {
+        let dims = measure_text(key, Some(current_font), font_size, 1.0);
+        if (layout.len(), c) == (sel_row, sel_col) {
+            draw_rectangle_lines(
+                x - 4.0,
+                special_y - font_size as f32 - 2.0,
+                dims.width + 8.0,
+                font_size as f32 + 8.0,
+                2.0 * scale_factor,
+                cursor_color,
+            );
+            text_with_color(
+                font_cache,
+                config,
+                key,
+                x,
+                special_y,
+                font_size,
+                cursor_color,
+            );
+        } else if *key == "SHIFT" && keyboard.shift {
+            text_with_color(
+                font_cache,
+                config,
+                key,
+                x,
+                special_y,
+                font_size,
+                Color::new(0.3, 0.7, 1.0, 1.0),
+            );
+        } else {
+            text_with_config_color(font_cache, config, key, x, special_y, font_size);
+        }
+        x += dims.width + 20.0 * scale_factor;
+    }
+}
+
+/// Draws the cartridge metadata editor UI.
+pub fn draw(
+    state: &CartEditorState,
+    animation_state: &AnimationState,
+    logo_cache: &HashMap<String, Texture2D>,
+    background_cache: &HashMap<String, Texture2D>,
+    video_cache: &mut HashMap<String, VideoPlayer>,
+    font_cache: &HashMap<String, Font>,
+    config: &Config,
+    background_state: &mut BackgroundState,
+    battery_info: &Option<BatteryInfo>,
+    current_time_str: &str,
+    gcc_adapter_poll_rate: &Option<u32>,
+    scale_factor: f32,
+) {
+    render_background(background_cache, video_cache, config, background_state);
+
+    // Dim the background for easier legibility
+    draw_rectangle(
+        0.0,
+        0.0,
+        screen_width(),
+        screen_height(),
+        Color::new(0.0, 0.0, 0.0, 0.6),
+    );
+
+    render_ui_overlay(
+        logo_cache,
+        font_cache,
+        config,
+        battery_info,
+        current_time_str,
+        gcc_adapter_poll_rate,
+        scale_factor,
+    );
+
+    let font_size = (FONT_SIZE as f32 * scale_factor) as u16;
+    let large_font_size = (FONT_SIZE as f32 * scale_factor * 1.5) as u16;
+    let menu_padding = MENU_PADDING * scale_factor;
+    let menu_option_height = MENU_OPTION_HEIGHT * scale_factor;
+    let current_font = get_current_font(font_cache, config);
+
+    // Title
+    let title = "CARTRIDGE INFO";
+    let title_dims = measure_text(title, Some(current_font), large_font_size, 1.0);
+    let title_y = 50.0 * scale_factor;
+    text_with_config_color(
+        font_cache,
+        config,
+        title,
+        screen_width() / 2.0 - title_dims.width / 2.0,
+        title_y,
+        large_font_size,
+    );
+
+    let file_name = state
+        .kzi_path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let file_dims = measure_text(&file_name, Some(current_font), font_size, 1.0);
+    text_with_config_color(
+        font_cache,
+        config,
+        &file_name,
+        screen_width() / 2.0 - file_dims.width / 2.0,
+        title_y + 25.0 * scale_factor,
+        font_size,
+    );
+
+    // Menu options
+    let start_y = 100.0 * scale_factor;
+    let left_margin = 80.0 * scale_factor;
+    let right_margin = 80.0 * scale_factor;
+
+    for (i, &label) in CART_EDITOR_OPTIONS.iter().enumerate() {
+        let y_pos = start_y + (i as f32 * menu_option_height);
+        let is_selected = i == state.selection;
+
+        let value = get_option_value(i, state);
+        let value_dims = measure_text(&value, Some(current_font), font_size, 1.0);
+        let value_x = screen_width() - value_dims.width - right_margin;
+        let text_y = y_pos + menu_option_height / 2.0 + font_size as f32 * 0.3;
+
+        if is_selected && config.cursor_style == "BOX" {
+            let cursor_color = animation_state.get_cursor_color(config);
+            let cursor_scale = animation_state.get_cursor_scale();
+
+            let base_width = value_dims.width + (menu_padding * 2.0);
+            let base_height = value_dims.height + (menu_padding * 2.0);
+            let scaled_width = base_width * cursor_scale;
+            let scaled_height = base_height * cursor_scale;
+            let offset_x = (scaled_width - base_width) / 2.0;
+            let offset_y = (scaled_height - base_height) / 2.0;
+
+            let rect_x = value_x - menu_padding;
+            let rect_y = y_pos + (menu_option_height / 2.0) - (base_height / 2.0);
+
+            draw_rectangle_lines(
+                rect_x - offset_x,
+                rect_y - offset_y,
+                scaled_width,
+                scaled_height,
+                4.0 * scale_factor,
+                cursor_color,
+            );
+        }
+
+        text_with_config_color(font_cache, config, label, left_margin, text_y, font_size);
+
+        if is_selected && config.cursor_style == "TEXT" {
+            let highlight_color = animation_state.get_cursor_color(config);
+            text_with_color(
+                font_cache,
+                config,
+                &value,
+                value_x,
+                text_y,
+                font_size,
+                highlight_color,
+            );
+        } else {
+            text_with_config_color(font_cache, config, &value, value_x, text_y, font_size);
+        }
+    }
+
+    if let Some((message, ok)) = &state.status {
+        let status_y =
+            start_y + CART_EDITOR_OPTIONS.len() as f32 * menu_option_height + 20.0 * scale_factor;
+        text_with_color(
+            font_cache,
+            config,
+            message,
+            left_margin,
+            status_y,
+            font_size,
+            if *ok { GREEN } else { RED },
+        );
+    }
+
+    let instructions = "A: Edit   LEFT/RIGHT: Change   B: Back (discards unsaved changes)";
+    let inst_dims = measure_text(instructions, Some(current_font), font_size, 1.0);
+    text_with_color(
+        font_cache,
+        config,
+        instructions,
+        screen_width() / 2.0 - inst_dims.width / 2.0,
+        screen_height() - 20.0 * scale_factor,
+        font_size,
+        Color::new(0.7, 0.7, 0.7, 1.0),
+    );
+
+    if let Some(keyboard) = &state.keyboard {
+        draw_keyboard(
+            state,
+            keyboard,
+            animation_state,
+            font_cache,
+            config,
+            scale_factor,
+        );
+    }
+}
@@ -0,0 +1,580 @@
+use macroquad::prelude::*;
+use std::collections::HashMap;
+
+use crate::{
+    audio::SoundEffects,
+    collections::{Collections, MAX_NAME_LEN},
+    config::Config,
+    get_current_font, measure_text, render_background, render_ui_overlay,
+    save::CartInfo,
+    text_with_config_color,
+    types::{AnimationState, BackgroundState, BatteryInfo, Screen},
+    ui::text_with_color,
+    InputState, VideoPlayer, FONT_SIZE, MENU_OPTION_HEIGHT, MENU_PADDING,
+};
+
+const NEW_COLLECTION: &str = "+ NEW COLLECTION";
+
+const KEYS_LOWER: &[&str] = &[
+    "1234567890",
+    "qwertyuiop",
+    "asdfghjkl-",
+    "zxcvbnm:'!",
+    "&.,()/?+#_",
+];
+const KEYS_UPPER: &[&str] = &[
+    "1234567890",
+    "QWERTYUIOP",
+    "ASDFGHJKL-",
+    "ZXCVBNM:'!",
+    "&.,()/?+#_",
+];
+const SPECIAL_KEYS: &[&str] = &["SHIFT", "SPACE", "DEL", "CLEAR", "DONE"];
+
+struct Keyboard {
+    /// Collection being renamed, or None for a new one
+    renaming: Option<String>,
+    text: String,
+    coords: (usize, usize),
+    shift: bool,
+}
+
+/// State for the collections screen: managing collections from the library's
+/// filter row, or putting one cart in and out of them
+pub struct CollectionsState {
+    pub selection: usize,
+    collections: Collections,
+    /// Cart ID and name being assigned; None when managing
+    cart: Option<(String, String)>,
+    keyboard: Option<Keyboard>,
+    /// Collection [WEST] was pressed on once; a second press deletes it
+    confirm_delete: Option<String>,
+    status: Option<(String, bool)>,
+    /// Set on any change so the library can reload
+    changed: bool,
+}
+
+impl CollectionsState {
+    pub fn new() -> Self {
+        Self {
+            selection: 0,
+            collections: Collections::default(),
+            cart: None,
+            keyboard: None,
+            confirm_delete: None,
+            status: None,
+            changed: false,
+        }
+    }
+
+    /// Reloads the collections before showing the screen
+    pub fn open(&mut self, cart_info: Option<&CartInfo>) {
+        self.selection = 0;
+        self.collections = Collections::load();
+        self.cart =
+            cart_info.map(|c| (c.id.clone(), c.name.clone().unwrap_or_else(|| c.id.clone())));
+        self.keyboard = None;
+        self.confirm_delete = None;
+        self.status = None;
+        self.changed = false;
+    }
+
+    /// Whether anything changed since the last call
+    pub fn take_changed(&mut self) -> bool {
+        std::mem::take(&mut self.changed)
+    }
+
+    fn row_count(&self) -> usize {
+        self.collections.names.len() + 1
+    }
+
+    fn commit(&mut self) {
+        self.changed = true;
+        self.collections.save();
+    }
+
+    /// Creates or renames from the keyboard's text
+    fn finish_keyboard(&mut self) {
+        let Some(keyboard) = self.keyboard.take() else {
+            return;
+        };
+        let result = match &keyboard.renaming {
+            Some(old) => self
+                .collections
+                .rename(old, &keyboard.text)
+                .map(|name| format!("Renamed {} to {}", old, name)),
+            None => self.collections.create(&keyboard.text).map(|name| {
+                // A collection made while assigning starts with that cart in it
+                if let Some((cart_id, _)) = &self.cart {
+                    self.collections.toggle(cart_id, &name);
+                }
+                self.selection = self.collections.names.len() - 1;
+                format!("Created {}", name)
+            }),
+        };
+        match result {
+            Ok(message) => {
+                println!("[Collections] {}", message);
+                self.commit();
+                self.status = Some((message, true));
+            }
+            Err(e) => self.status = Some((e, false)),
+        }
+    }
+
+    /// Handles input while the keyboard is open
+    fn update_keyboard(
+        &mut self,
+        input_state: &InputState,
+        sound_effects: &SoundEffects,
+        config: &Config,
+    ) {
+        let Some(keyboard) = &mut self.keyboard else {
+            return;
+        };
+        let (row, col) = &mut keyboard.coords;
+        let layout = if keyboard.shift {
+            KEYS_UPPER
+        } else {
+            KEYS_LOWER
+        };
+        let num_rows = layout.len() + 1;
+
+        if input_state.up && *row > 0 {
+            *row -= 1;
+            sound_effects.play_cursor_move(config);
+        }
+        if input_state.down && *row < num_rows - 1 {
+            *row += 1;
+            sound_effects.play_cursor_move(config);
+        }
+
+        let row_len = if *row < layout.len() {
+            layout[*row].chars().count()
+        } else {
+            SPECIAL_KEYS.len()
+        };
+        *col = (*col).min(row_len - 1);
+        if input_state.left && *col > 0 {
+            *col -= 1;
+            sound_effects.play_cursor_move(config);
+        }
+        if input_state.right && *col < row_len - 1 {
+            *col += 1;
+            sound_effects.play_cursor_move(config);
+        }
+
+        if input_state.back {
+            self.keyboard = None;
+            sound_effects.play_back(config);
+            return;
+        }
+        if !input_state.select {
+            return;
+        }
+
+        sound_effects.play_select(config);
+        let text = &mut keyboard.text;
+        if *row < layout.len() {
+            if let Some(key) = layout[*row].chars().nth(*col) {
+                // Like a phone keyboard, shift applies to one letter
+                keyboard.shift = false;
+                if text.chars().count() < MAX_NAME_LEN {
+                    text.push(key);
+                }
+            }
+        } else {
+            match SPECIAL_KEYS[*col] {
+                "SHIFT" => keyboard.shift = !keyboard.shift,
+                "SPACE" => {
+                    if text.chars().count() < MAX_NAME_LEN {
+                        text.push(' ');
+                    }
+                }
+                "DEL" => {
+                    text.pop();
+                }
+                "CLEAR" => text.clear(),
+                "DONE" => self.finish_keyboard(),
+                _ => {}
+            }
+        }
+    }
+}
+
+pub fn update(
+    current_screen: &mut Screen,
+    state: &mut CollectionsState,
+    input_state: &InputState,
+    animation_state: &mut AnimationState,
+    sound_effects: &SoundEffects,
+    config: &Config,
+) {
+    if state.keyboard.is_some() {
+        state.update_keyboard(input_state, sound_effects, config);
+        return;
+    }
+
+    if input_state.back {
+        *current_screen = Screen::BladesDashboard;
+        sound_effects.play_back(config);
+        return;
+    }
+
+    let rows = state.row_count();
+    if input_state.up || input_state.down {
+        state.selection = if input_state.up {
+            (state.selection + rows - 1) % rows
+        } else {
+            (state.selection + 1) % rows
+        };
+        state.confirm_delete = None;
+        animation_state.trigger_transition(&config.cursor_transition_speed);
+        sound_effects.play_cursor_move(config);
+    }
+
+    let selected = state.collections.names.get(state.selection).cloned();
+    if input_state.select {
+        sound_effects.play_select(config);
+        state.confirm_delete = None;
+        state.status = None;
+        match (&selected, &state.cart) {
+            (None, _) => {
+                state.keyboard = Some(Keyboard {
+                    renaming: None,
+                    text: String::new(),
+                    coords: (1, 0),
+                    shift: true,
+                });
+            }
+            (Some(name), Some((cart_id, _))) => {
+                let added = state.collections.toggle(cart_id, name);
+                let (verb, preposition) = if added {
+                    ("Added", "to")
+                } else {
+                    ("Removed", "from")
+                };
+                println!(
+                    "[Collections] {} {} {} {}",
+                    verb, cart_id, preposition, name
+                );
+                state.commit();
+            }
+            (Some(name), None) => {
+                state.keyboard = Some(Keyboard {
+                    renaming: Some(name.clone()),
+                    text: name.clone(),
+                    coords: (1, 0),
+                    shift: false,
+                });
+            }
+        }
+    }
+
+    if input_state.secondary {
+        let Some(name) = selected else {
+            sound_effects.play_reject(config);
+            return;
+        };
+        if state.confirm_delete.as_ref() == Some(&name) {
+            state.collections.delete(&name);
+            state.commit();
+            println!("[Collections] Deleted {}", name);
+            state.status = Some((format!("Deleted {}", name), true));
+            state.confirm_delete = None;
+            state.selection = state.selection.min(state.row_count() - 1);
+            sound_effects.play_select(config);
+        } else {
+            state.status = Some((format!("Press [WEST] again to delete {}", name), false));
+            state.confirm_delete = Some(name);
+            sound_effects.play_cursor_move(config);
+        }
+    }
+}
+
+fn get_row_value(state: &CollectionsState, name: &str) -> String {
+    match &state.cart {
+        Some((cart_id, _)) => {
+            if state.collections.contains(cart_id, name) {
+                "[X]".to_string()
+            } else {
+                "[ ]".to_string()
+            }
+        }
+        None => match state.collections.count(name) {
+            1 => "1 GAME".to_string(),
+            n => format!("{} GAMES", n),
+        },
+    }
+}
+
+fn draw_keyboard(
+    keyboard: &Keyboard,
+    animation_state: &AnimationState,
+    font_cache: &HashMap<String, Font>,
+    config: &Config,
+    scale_factor: f32,
+) {
+    let font_size = (FONT_SIZE as f32 * scale_factor) as u16;
+    let current_font = get_current_font(font_cache, config);
+    let key_spacing = font_size as f32 * 2.2;
+    let row_height = font_size as f32 * 2.0;
+    let layout = if keyboard.shift {
+        KEYS_UPPER
+    } else {
+        KEYS_LOWER
+    };
+
+    let box_w = layout[0].len() as f32 * key_spacing + 40.0 * scale_factor;
+    let box_h = (layout.len() as f32 + 3.0) * row_height + 20.0 * scale_factor;
+    let box_x = screen_width() / 2.0 - box_w / 2.0;
+    let box_y = screen_height() / 2.0 - box_h / 2.0;
+    draw_rectangle(box_x, box_y, box_w, box_h, Color::new(0.0, 0.0, 0.0, 0.9));
+    draw_rectangle_lines(box_x, box_y, box_w, box_h, 2.0 * scale_factor, WHITE);
+
+    let label = if keyboard.renaming.is_some() {
+        "RENAME"
+    } else {
+        "NEW COLLECTION"
+    };
+    let entry = format!("{}: {}_", label, keyboard.text);
+    text_with_config_color(
+        font_cache,
+        config,
+        &entry,
+        box_x + 20.0 * scale_factor,
+        box_y + row_height,
+        font_size,
+    );
+
+    let cursor_color = animation_state.get_cursor_color(config);
+    let keys_x = box_x + 20.0 * scale_factor;
+    let keys_y = box_y + row_height * 2.5;
+    let (sel_row, sel_col) = keyboard.coords;
+
+    for (r, row) in layout.iter().enumerate() {
+        for (c, key) in row.chars().enumerate() {
+            let key_str = key.to_string();
+            let x = keys_x + c as f32 * key_spacing;
+            let y = keys_y + r as f32 * row_height;
+            if (r, c) == (sel_row, sel_col) {
+                let dims = measure_text(&key_str, Some(current_font), font_size, 1.0);
+                draw_rectangle_lines(
+                    x - 4.0,
+                    y - font_size as f32 - 2.0,
+                    dims.width + 8.0,
+                    font_size as f32 + 8.0,
+                    2.0 * scale_factor,
+                    cursor_color,
+                );
+                text_with_color(font_cache, config, &key_str, x, y, font_size, cursor_color);
+            } else {
+                text_with_config_color(font_cache, config, &key_str, x, y, font_size);
+            }
+        }
+    }
+
+    let special_y = keys_y + layout.len() as f32 * row_height;
+    let mut x = keys_x;
+    for (c, key) in SPECIAL_KEYS.iter().enumerate() {
+        let dims = measure_text(key, Some(current_font), font_size, 1.0);
+        if (layout.len(), c) == (sel_row, sel_col) {
+            draw_rectangle_lines(
+                x - 4.0,
+                special_y - font_size as f32 - 2.0,
+                dims.width + 8.0,
+                font_size as f32 + 8.0,
+                2.0 * scale_factor,
+                cursor_color,
+            );
+            text_with_color(
+                font_cache,
+                config,
+                key,
+                x,
+                special_y,
+                font_size,
+                cursor_color,
+            );
+        } else if *key == "SHIFT" && keyboard.shift {
+            text_with_color(
+                font_cache,
+                config,
+                key,
+                x,
+                special_y,
+                font_size,
+                Color::new(0.3, 0.7, 1.0, 1.0),
+            );
+        } else {
+            text_with_config_color(font_cache, config, key, x, special_y, font_size);
+        }
+        x += dims.width + 20.0 * scale_factor;
+    }
+}
+
+/// Draws the collections screen
+pub fn draw(
+    state: &CollectionsState,
+    animation_state: &AnimationState,
+    logo_cache: &HashMap<String, Texture2D>,
+    background_cache: &HashMap<String, Texture2D>,
+    video_cache: &mut HashMap<String, VideoPlayer>,
+    font_cache: &HashMap<String, Font>,
+    config: &Config,
+    background_state: &mut BackgroundState,
+    battery_info: &Option<BatteryInfo>,
+    current_time_str: &str,
+    gcc_adapter_poll_rate: &Option<u32>,
+    scale_factor: f32,
+) {
+    render_background(background_cache, video_cache, config, background_state);
+
+    // Dim the background for easier legibility
+    draw_rectangle(
+        0.0,
+        0.0,
+        screen_width(),
+        screen_height(),
+        Color::new(0.0, 0.0, 0.0, 0.6),
+    );
+
+    render_ui_overlay(
+        logo_cache,
+        font_cache,
+        config,
+        battery_info,
+        current_time_str,
+        gcc_adapter_poll_rate,
+        scale_factor,
+    );
+
+    let font_size = (FONT_SIZE as f32 * scale_factor) as u16;
+    let large_font_size = (FONT_SIZE as f32 * scale_factor * 1.5) as u16;
+    let menu_padding = MENU_PADDING * scale_factor;
+    let menu_option_height = MENU_OPTION_HEIGHT * scale_factor;
+    let current_font = get_current_font(font_cache, config);
+
+    // Title, with the cart being assigned under it
+    let title = "COLLECTIONS";
+    let title_dims = measure_text(title, Some(current_font), large_font_size, 1.0);
+    let title_y = 50.0 * scale_factor;
+    text_with_config_color(
+        font_cache,
+        config,
+        title,
+        screen_width() / 2.0 - title_dims.width / 2.0,
+        title_y,
+        large_font_size,
+    );
+    if let Some((_, cart_name)) = &state.cart {
+        let dims = measure_text(cart_name, Some(current_font), font_size, 1.0);
+        text_with_config_color(
+            font_cache,
+            config,
+            cart_name,
+            screen_width() / 2.0 - dims.width / 2.0,
+            title_y + 25.0 * scale_factor,
+            font_size,
+        );
+    }
+
+    let start_y = 100.0 * scale_factor;
+    let left_margin = 80.0 * scale_factor;
+    let right_margin = 80.0 * scale_factor;
+
+    let rows = state
+        .collections
+        .names
+        .iter()
+        .map(String::as_str)
+        .chain(std::iter::once(NEW_COLLECTION));
+    for (i, label) in rows.enumerate() {
+        let y_pos = start_y + (i as f32 * menu_option_height);
+        let is_selected = i == state.selection;
+        let text_y = y_pos + menu_option_height / 2.0 + font_size as f32 * 0.3;
+
+        let value = if i < state.collections.names.len() {
+            get_row_value(state, label)
+        } else {
+            String::new()
+        };
+        let value_dims = measure_text(&value, Some(current_font), font_size, 1.0);
+        let value_x = screen_width() - value_dims.width - right_margin;
+        let label_dims = measure_text(label, Some(current_font), font_size, 1.0);
+
+        if is_selected && config.cursor_style == "BOX" {
+            let cursor_color = animation_state.get_cursor_color(config);
+            let cursor_scale = animation_state.get_cursor_scale();
+
+            let base_width = label_dims.width + (menu_padding * 2.0);
+            let base_height = label_dims.height + (menu_padding * 2.0);
+            let scaled_width = base_width * cursor_scale;
+            let scaled_height = base_height * cursor_scale;
+            let offset_x = (scaled_width - base_width) / 2.0;
+            let offset_y = (scaled_height - base_height) / 2.0;
+
+            let rect_x = left_margin - menu_padding;
+            let rect_y = y_pos + (menu_option_height / 2.0) - (base_height / 2.0);
+
+            draw_rectangle_lines(
+                rect_x - offset_x,
+                rect_y - offset_y,
+                scaled_width,
+                scaled_height,
+                4.0 * scale_factor,
+                cursor_color,
+            );
+        }
+
+        if is_selected && config.cursor_style == "TEXT" {
+            let highlight_color = animation_state.get_cursor_color(config);
+            text_with_color(
+                font_cache,
+                config,
+                label,
+                left_margin,
+                text_y,
+                font_size,
+                highlight_color,
+            );
+        } else {
+            text_with_config_color(font_cache, config, label, left_margin, text_y, font_size);
+        }
+        text_with_config_color(font_cache, config, &value, value_x, text_y, font_size);
+    }
+
+    if let Some((message, ok)) = &state.status {
+        let status_y =
+            start_y + state.row_count() as f32 * menu_option_height + 20.0 * scale_factor;
+        text_with_color(
+            font_cache,
+            config,
+            message,
+            left_margin,
+            status_y,
+            font_size,
+            if *ok { GREEN } else { RED },
+        );
+    }
+
+    let instructions = if state.cart.is_some() {
+        "A: Add/Remove   WEST: Delete collection   B: Back"
+    } else {
+        "A: Rename   WEST: Delete   B: Back"
+    };
+    let inst_dims = measure_text(instructions, Some(current_font), font_size, 1.0);
+    text_with_color(
+        font_cache,
+        config,
+        instructions,
+        screen_width() / 2.0 - inst_dims.width / 2.0,
+        screen_height() - 20.0 * scale_factor,
+        font_size,
+        Color::new(0.7, 0.7, 0.7, 1.0),
+    );
+
+    if let Some(keyboard) = &state.keyboard {
+        draw_keyboard(keyboard, animation_state, font_cache, config, scale_factor);
+    }
+}
@@ -1,4 +1,5 @@
-use crate::{StorageMediaState, Arc, Mutex};
+use crate::save::SaveSummary;
+use crate::{backup, save, Arc, Mutex, StorageMediaState};
 
 pub struct DialogOption {
     pub text: String,
@@ -11,6 +12,7 @@ pub struct Dialog {
     pub desc: Option<String>,
     pub options: Vec<DialogOption>,
     pub selection: usize,
+    pub comparison: Option<(SaveSummary, SaveSummary)>, // (source, destination) for save conflicts
 }
 
 pub fn create_confirm_delete_dialog() -> Dialog {
@@ -27,9 +29,32 @@ pub fn create_confirm_delete_dialog() -> Dialog {
                 text: "CANCEL".to_string(),
                 value: "CANCEL".to_string(),
                 disabled: false,
-            }
+            },
         ],
         selection: 1,
+        comparison: None,
+    }
+}
+
+/// Recompress all plain .tar saves on the selected external drive
+pub fn create_confirm_compress_dialog() -> Dialog {
+    Dialog {
+        id: "confirm_compress".to_string(),
+        desc: Some("COMPRESS ALL SAVE DATA ON THIS DRIVE?".to_string()),
+        options: vec![
+            DialogOption {
+                text: "COMPRESS".to_string(),
+                value: "COMPRESS".to_string(),
+                disabled: false,
+            },
+            DialogOption {
+                text: "CANCEL".to_string(),
+                value: "CANCEL".to_string(),
+                disabled: false,
+            },
+        ],
+        selection: 1,
+        comparison: None,
     }
 }
 
@@ -58,15 +83,116 @@ pub fn create_copy_storage_dialog(storage_state: &Arc<Mutex<StorageMediaState>>)
         desc: Some("WHERE TO COPY THIS SAVE DATA?".to_string()),
         options,
         selection: 0,
+        comparison: None,
+    }
+}
+
+/// Pick the external drive to export emulator saves to
+pub fn create_export_drive_dialog(storage_state: &Arc<Mutex<StorageMediaState>>) -> Dialog {
+    let mut options = Vec::new();
+    if let Ok(state) = storage_state.lock() {
+        for drive in state.media.iter().filter(|d| d.id != "internal") {
+            options.push(DialogOption {
+                text: format!("{} ({} MB Free)", drive.id.clone(), drive.free),
+                value: drive.id.clone(),
+                disabled: false,
+            });
+        }
+    }
+    options.push(DialogOption {
+        text: "CANCEL".to_string(),
+        value: "CANCEL".to_string(),
+        disabled: false,
+    });
+
+    Dialog {
+        id: "export_drive_select".to_string(),
+        desc: Some("EXPORT .SAV FILES TO WHICH DRIVE?".to_string()),
+        options,
+        selection: 0,
+        comparison: None,
+    }
+}
+
+/// Most save files offered at once, so the list fits on screen
+const MAX_IMPORT_CANDIDATES: usize = 5;
+
+/// Pick one of the matching emulator saves found on the external drives.
+/// Option values are the file paths.
+pub fn create_import_file_dialog(candidates: &[(String, std::path::PathBuf)]) -> Dialog {
+    let mut options: Vec<DialogOption> = candidates
+        .iter()
+        .take(MAX_IMPORT_CANDIDATES)
+        .map(|(drive, path)| DialogOption {
+            text: format!(
+                "{}: {}",
+                drive,
+                path.file_name().and_then(|n| n.to_str()).unwrap_or("?")
+            ),
+            value: path.to_string_lossy().into_owned(),
+            disabled: false,
+        })
+        .collect();
+    options.push(DialogOption {
+        text: "CANCEL".to_string(),
+        value: "CANCEL".to_string(),
+        disabled: false,
+    });
+
+    Dialog {
+        id: "import_file_select".to_string(),
+        desc: Some("IMPORT WHICH SAVE FILE?".to_string()),
+        options,
+        selection: 0,
+        comparison: None,
+    }
+}
+
+/// Pick the player slot (p1-p4) an imported save goes into.
+/// Slots that already have a save are marked; importing keeps the old file as .bak.
+pub fn create_import_slot_dialog(existing_saves: &[String]) -> Dialog {
+    let mut options: Vec<DialogOption> = (1..=4)
+        .map(|player| {
+            let in_use = existing_saves.contains(&format!("p{}", player));
+            DialogOption {
+                text: if in_use {
+                    format!("P{} (REPLACE)", player)
+                } else {
+                    format!("P{}", player)
+                },
+                value: player.to_string(),
+                disabled: false,
+            }
+        })
+        .collect();
+    options.push(DialogOption {
+        text: "CANCEL".to_string(),
+        value: "CANCEL".to_string(),
+        disabled: false,
+    });
+
+    Dialog {
+        id: "import_slot_select".to_string(),
+        desc: Some("IMPORT INTO WHICH SLOT?".to_string()),
+        options,
+        selection: 0,
+        comparison: None,
     }
 }
 
 pub fn create_main_dialog(storage_state: &Arc<Mutex<StorageMediaState>>) -> Dialog {
-    let has_external_devices = if let Ok(state) = storage_state.lock() {
-        state.media.len() > 1
-    } else {
-        false
-    };
+    let (has_external_devices, is_internal, is_backup_drive, is_encrypted) =
+        if let Ok(state) = storage_state.lock() {
+            let selected_id = state.media.get(state.selected).map(|m| m.id.clone());
+            (
+                state.media.len() > 1,
+                selected_id.as_deref().map_or(true, |id| id == "internal"),
+                selected_id.is_some() && selected_id == backup::backup_drive(),
+                selected_id.as_deref().is_some_and(save::is_drive_encrypted),
+            )
+        } else {
+            (false, true, false, false)
+        };
 
     let options = vec![
         DialogOption {
@@ -79,6 +205,41 @@ pub fn create_main_dialog(storage_state: &Arc<Mutex<StorageMediaState>>) -> Dial
             value: "DELETE".to_string(),
             disabled: false,
         },
+        DialogOption {
+            text: "EXPORT TO USB".to_string(),
+            value: "EXPORT".to_string(),
+            disabled: !is_internal || !has_external_devices,
+        },
+        DialogOption {
+            text: "IMPORT FROM USB".to_string(),
+            value: "IMPORT".to_string(),
+            disabled: !is_internal || !has_external_devices,
+        },
+        DialogOption {
+            text: if is_backup_drive {
+                "STOP NIGHTLY BACKUPS"
+            } else {
+                "USE FOR NIGHTLY BACKUPS"
+            }
+            .to_string(),
+            value: "BACKUP_DRIVE".to_string(),
+            disabled: is_internal,
+        },
+        DialogOption {
+            text: "COMPRESS DRIVE".to_string(),
+            value: "COMPRESS".to_string(),
+            disabled: is_internal,
+        },
+        DialogOption {
+            text: if is_encrypted {
+                "STOP ENCRYPTING SAVES"
+            } else {
+                "ENCRYPT SAVES ON DRIVE"
+            }
+            .to_string(),
+            value: "ENCRYPT_DRIVE".to_string(),
+            disabled: is_internal,
+        },
         DialogOption {
             text: "CANCEL".to_string(),
             value: "CANCEL".to_string(),
@@ -91,21 +252,35 @@ pub fn create_main_dialog(storage_state: &Arc<Mutex<StorageMediaState>>) -> Dial
         desc: None,
         options,
         selection: 0,
+        comparison: None,
     }
 }
 
-pub fn create_save_exists_dialog() -> Dialog {
+/// Offer conflict resolution when the destination already has this save.
+/// The two summaries are drawn side by side by `render_dialog`.
+pub fn create_save_conflict_dialog(source: SaveSummary, destination: SaveSummary) -> Dialog {
     Dialog {
-        id: "save_exists".to_string(),
+        id: "save_conflict".to_string(),
         desc: Some("THIS SAVE DATA ALREADY EXISTS AT THE SELECTED DESTINATION".to_string()),
         options: vec![
             DialogOption {
-                text: "OK".to_string(),
-                value: "OK".to_string(),
+                text: "OVERWRITE".to_string(),
+                value: "OVERWRITE".to_string(),
                 disabled: false,
-            }
+            },
+            DialogOption {
+                text: "KEEP BOTH (RENAME EXISTING)".to_string(),
+                value: "KEEP_BOTH".to_string(),
+                disabled: false,
+            },
+            DialogOption {
+                text: "CANCEL".to_string(),
+                value: "CANCEL".to_string(),
+                disabled: false,
+            },
         ],
-        selection: 0,
+        selection: 2,
+        comparison: Some((source, destination)),
     }
 }
 
@@ -113,14 +288,28 @@ pub fn create_error_dialog(message: String) -> Dialog {
     Dialog {
         id: "error".to_string(),
         desc: Some(message),
-        options: vec![
-            DialogOption {
-                text: "OK".to_string(),
-                value: "OK".to_string(),
-                disabled: false,
-            }
-        ],
+        options: vec![DialogOption {
+            text: "OK".to_string(),
+            value: "OK".to_string(),
+            disabled: false,
+        }],
+        selection: 0,
+        comparison: None,
+    }
+}
+
+/// Confirmation message with a single OK, for operations that finish right away
+pub fn create_info_dialog(message: String) -> Dialog {
+    Dialog {
+        id: "info".to_string(),
+        desc: Some(message),
+        options: vec![DialogOption {
+            text: "OK".to_string(),
+            value: "OK".to_string(),
+            disabled: false,
+        }],
         selection: 0,
+        comparison: None,
     }
 }
 
@@ -154,6 +343,7 @@ pub fn create_player_count_dialog(max_players: u8) -> Dialog {
         desc: Some("SELECT NUMBER OF PLAYERS".to_string()),
         options,
         selection: if max_players >= 2 { 1 } else { 0 }, // Default to 2 players if available
+        comparison: None,
     }
 }
 
@@ -162,7 +352,12 @@ pub fn create_player_count_dialog(max_players: u8) -> Dialog {
 /// `player_num` is the player number (1-4) selecting their save
 /// `rom_name` is used to display which game's saves we're looking at
 /// `can_import` adds an option to pull in an embedded save for this player
-pub fn create_save_slot_dialog(existing_saves: &[String], player_num: u8, rom_name: &str, can_import: bool) -> Dialog {
+pub fn create_save_slot_dialog(
+    existing_saves: &[String],
+    player_num: u8,
+    rom_name: &str,
+    can_import: bool,
+) -> Dialog {
     let mut options = Vec::new();
 
     // Option to create a new save for this player
@@ -215,7 +410,11 @@ pub fn create_save_slot_dialog(existing_saves: &[String], player_num: u8, rom_na
     let desc = if player_num == 1 {
         format!("SELECT SAVE FILE\n{}", rom_name.to_uppercase())
     } else {
-        format!("PLAYER {} - SELECT SAVE FILE\n{}", player_num, rom_name.to_uppercase())
+        format!(
+            "PLAYER {} - SELECT SAVE FILE\n{}",
+            player_num,
+            rom_name.to_uppercase()
+        )
     };
 
     Dialog {
@@ -223,6 +422,7 @@ pub fn create_save_slot_dialog(existing_saves: &[String], player_num: u8, rom_na
         desc: Some(desc),
         options,
         selection: 0,
+        comparison: None,
     }
 }
 
@@ -0,0 +1,491 @@
+use macroquad::prelude::*;
+use std::collections::HashMap;
+
+use crate::{
+    audio::SoundEffects,
+    config::Config,
+    display::{self, DisplayMode},
+    get_current_font, measure_text, render_background, render_ui_overlay, text_with_config_color,
+    types::{AnimationState, BackgroundState, BatteryInfo, Screen},
+    ui::text_with_color,
+    InputState, VideoPlayer, FONT_SIZE, MENU_OPTION_HEIGHT, MENU_PADDING,
+};
+
+/// How long a new mode stays without being confirmed
+const REVERT_SECS: f64 = 15.0;
+/// Modes shown at once
+const VISIBLE_MODES: usize = 8;
+
+/// A mode that was switched to and is waiting for confirmation
+struct PendingMode {
+    mode: DisplayMode,
+    /// Mode to go back to, if it was known
+    previous: Option<DisplayMode>,
+    applied_at: f64,
+    /// KEEP (0) or REVERT (1)
+    selection: usize,
+}
+
+/// State for the Display settings screen
+pub struct DisplaySettingsState {
+    modes: Vec<DisplayMode>,
+    outputs: Vec<String>,
+    output_index: usize,
+    selection: usize,
+    scroll_offset: usize,
+    pending: Option<PendingMode>,
+    error: Option<String>,
+}
+
+impl DisplaySettingsState {
+    pub fn new() -> Self {
+        Self {
+            modes: Vec::new(),
+            outputs: Vec::new(),
+            output_index: 0,
+            selection: 0,
+            scroll_offset: 0,
+            pending: None,
+            error: None,
+        }
+    }
+
+    /// Lists the modes before showing the screen
+    pub fn open(&mut self) {
+        *self = Self::new();
+        self.reload();
+    }
+
+    fn reload(&mut self) {
+        let output = self.outputs.get(self.output_index).cloned();
+        match display::list_modes() {
+            Ok(modes) => {
+                self.modes = modes;
+                self.error = None;
+            }
+            Err(e) => {
+                self.modes.clear();
+                self.error = Some(e);
+            }
+        }
+
+        self.outputs.clear();
+        for mode in &self.modes {
+            if !self.outputs.contains(&mode.output) {
+                self.outputs.push(mode.output.clone());
+            }
+        }
+        self.output_index = output
+            .and_then(|o| self.outputs.iter().position(|x| *x == o))
+            .unwrap_or(0);
+        self.select_current();
+    }
+
+    /// Modes of the selected output
+    fn output_modes(&self) -> Vec<&DisplayMode> {
+        let Some(output) = self.outputs.get(self.output_index) else {
+            return Vec::new();
+        };
+        self.modes.iter().filter(|m| m.output == *output).collect()
+    }
+
+    fn current_mode(&self) -> Option<DisplayMode> {
+        self.output_modes().into_iter().find(|m| m.current).cloned()
+    }
+
+    fn select_current(&mut self) {
+        let modes = self.output_modes();
+        self.selection = modes.iter().position(|m| m.current).unwrap_or(0);
+        self.scroll_offset = self.selection.saturating_sub(VISIBLE_MODES - 1);
+    }
+
+    fn apply(&mut self, mode: DisplayMode) {
+        let previous = self.current_mode();
+        if previous.as_ref() == Some(&mode) {
+            return;
+        }
+        match display::apply_mode(&mode) {
+            Ok(()) => {
+                self.pending = Some(PendingMode {
+                    mode,
+                    previous,
+                    applied_at: get_time(),
+                    selection: 0,
+                });
+                self.reload();
+            }
+            Err(e) => self.error = Some(format!("COULDN'T SWITCH MODE: {}", e)),
+        }
+    }
+
+    /// Goes back to the mode from before the switch
+    fn revert(&mut self) {
+        let Some(pending) = self.pending.take() else {
+            return;
+        };
+        if let Some(previous) = pending.previous {
+            println!("[Display] Reverting to {}", previous.label());
+            if let Err(e) = display::apply_mode(&previous) {
+                self.error = Some(format!("COULDN'T REVERT: {}", e));
+            }
+        }
+        self.reload();
+    }
+
+    fn keep(&mut self, config: &mut Config) {
+        let Some(pending) = self.pending.take() else {
+            return;
+        };
+        println!("[Display] Keeping {}", pending.mode.label());
+        display::save_mode(config, &pending.mode);
+    }
+}
+
+pub fn update(
+    current_screen: &mut Screen,
+    state: &mut DisplaySettingsState,
+    input_state: &InputState,
+    animation_state: &mut AnimationState,
+    sound_effects: &SoundEffects,
+    config: &mut Config,
+) {
+    // Waiting for the new mode to be confirmed
+    if let Some(pending) = &state.pending {
+        let keep = pending.selection == 0;
+        if get_time() - pending.applied_at >= REVERT_SECS {
+            state.revert();
+            sound_effects.play_back(config);
+        } else if input_state.left || input_state.right || input_state.up || input_state.down {
+            if let Some(pending) = &mut state.pending {
+                pending.selection = 1 - pending.selection;
+            }
+            sound_effects.play_cursor_move(config);
+        } else if input_state.select {
+            if keep {
+                state.keep(config);
+            } else {
+                state.revert();
+            }
+            sound_effects.play_select(config);
+        } else if input_state.back {
+            state.revert();
+            sound_effects.play_back(config);
+        }
+        return;
+    }
+
+    if input_state.back {
+        *current_screen = Screen::GeneralSettings;
+        sound_effects.play_back(config);
+        return;
+    }
+
+    let count = state.output_modes().len();
+    if count > 0 {
+        if input_state.up {
+            state.selection = if state.selection == 0 {
+                count - 1
+            } else {
+                state.selection - 1
+            };
+            animation_state.trigger_transition(&config.cursor_transition_speed);
+            sound_effects.play_cursor_move(config);
+        }
+        if input_state.down {
+            state.selection = (state.selection + 1) % count;
+            animation_state.trigger_transition(&config.cursor_transition_speed);
+            sound_effects.play_cursor_move(config);
+        }
+        if state.selection < state.scroll_offset {
+            state.scroll_offset = state.selection;
+        } else if state.selection >= state.scroll_offset + VISIBLE_MODES {
+            state.scroll_offset = state.selection + 1 - VISIBLE_MODES;
+        }
+    }
+
+    // Outputs, when more than one display is connected
+    if state.outputs.len() > 1 && (input_state.left || input_state.right) {
+        let len = state.outputs.len();
+        state.output_index = if input_state.right {
+            (state.output_index + 1) % len
+        } else {
+            (state.output_index + len - 1) % len
+        };
+        state.select_current();
+        sound_effects.play_cursor_move(config);
+    }
+
+    if input_state.select {
+        let mode = state
+            .output_modes()
+            .get(state.selection)
+            .map(|m| (*m).clone());
+        if let Some(mode) = mode {
+            state.apply(mode);
+            sound_effects.play_select(config);
+        }
+    }
+}
+
+/// Draws the Display settings screen.
+pub fn draw(
+    state: &DisplaySettingsState,
+    animation_state: &AnimationState,
+    logo_cache: &HashMap<String, Texture2D>,
+    background_cache: &HashMap<String, Texture2D>,
+    video_cache: &mut HashMap<String, VideoPlayer>,
+    font_cache: &HashMap<String, Font>,
+    config: &Config,
+    background_state: &mut BackgroundState,
+    battery_info: &Option<BatteryInfo>,
+    current_time_str: &str,
+    gcc_adapter_poll_rate: &Option<u32>,
+    scale_factor: f32,
+) {
+    render_background(background_cache, video_cache, config, background_state);
+
+    // Dim the background for easier legibility
+    draw_rectangle(
+        0.0,
+        0.0,
+        screen_width(),
+        screen_height(),
+        Color::new(0.0, 0.0, 0.0, 0.6),
+    );
+
+    render_ui_overlay(
+        logo_cache,
+        font_cache,
+        config,
+        battery_info,
+        current_time_str,
+        gcc_adapter_poll_rate,
+        scale_factor,
+    );
+
+    let font_size = (FONT_SIZE as f32 * scale_factor) as u16;
+    let large_font_size = (FONT_SIZE as f32 * scale_factor * 1.5) as u16;
+    let menu_padding = MENU_PADDING * scale_factor;
+    let menu_option_height = MENU_OPTION_HEIGHT * scale_factor;
+    let current_font = get_current_font(font_cache, config);
+    let dim = Color::new(0.7, 0.7, 0.7, 1.0);
+
+    let centered = |text: &str, y: f32, size: u16, color: Option<Color>| {
+        let dims = measure_text(text, Some(current_font), size, 1.0);
+        let x = screen_width() / 2.0 - dims.width / 2.0;
+        match color {
+            Some(color) => text_with_color(font_cache, config, text, x, y, size, color),
+            None => text_with_config_color(font_cache, config, text, x, y, size),
+        }
+    };
+
+    // Title
+    let title_y = 50.0 * scale_factor;
+    centered("DISPLAY", title_y, large_font_size, None);
+
+    let output_label = match state.outputs.get(state.output_index) {
+        Some(output) if state.outputs.len() > 1 => format!("< {} >", output),
+        Some(output) => output.clone(),
+        None => String::new(),
+    };
+    centered(
+        &output_label,
+        title_y + 25.0 * scale_factor,
+        font_size,
+        Some(dim),
+    );
+
+    let start_y = 100.0 * scale_factor;
+    let left_margin = 80.0 * scale_factor;
+    let right_margin = 80.0 * scale_factor;
+
+    let modes = state.output_modes();
+    let saved = (
+        config.display_output.as_deref(),
+        config.display_mode.as_deref(),
+    );
+    for (row, (i, mode)) in modes
+        .iter()
+        .enumerate()
+        .skip(state.scroll_offset)
+        .take(VISIBLE_MODES)
+        .enumerate()
+    {
+        let y_pos = start_y + (row as f32 * menu_option_height);
+        let is_selected = i == state.selection && state.pending.is_none();
+        let text_y = y_pos + menu_option_height / 2.0 + font_size as f32 * 0.3;
+
+        let label = mode.label();
+        let label_dims = measure_text(&label, Some(current_font), font_size, 1.0);
+
+        if is_selected && config.cursor_style == "BOX" {
+            let cursor_color = animation_state.get_cursor_color(config);
+            let cursor_scale = animation_state.get_cursor_scale();
+
+            let base_width = label_dims.width + (menu_padding * 2.0);
+            let base_height = label_dims.height + (menu_padding * 2.0);
+            let scaled_width = base_width * cursor_scale;
+            let scaled_height = base_height * cursor_scale;
+            let offset_x = (scaled_width - base_width) / 2.0;
+            let offset_y = (scaled_height - base_height) / 2.0;
+
+            let rect_x = left_margin - menu_padding;
+            let rect_y = y_pos + (menu_option_height / 2.0) - (base_height / 2.0);
+
+            draw_rectangle_lines(
+                rect_x - offset_x,
+                rect_y - offset_y,
+                scaled_width,
+                scaled_height,
+                4.0 * scale_factor,
+                cursor_color,
+            );
+        }
+
+        if is_selected && config.cursor_style == "TEXT" {
+            let highlight_color = animation_state.get_cursor_color(config);
+            text_with_color(
+                font_cache,
+                config,
+                &label,
+                left_margin,
+                text_y,
+                font_size,
+                highlight_color,
+            );
+        } else {
+            text_with_config_color(font_cache, config, &label, left_margin, text_y, font_size);
+        }
+
+        let mut tags = Vec::new();
+        if mode.current {
+            tags.push("CURRENT");
+        }
+        if saved == (Some(mode.output.as_str()), Some(mode.id().as_str())) {
+            tags.push("SAVED");
+        }
+        if mode.preferred {
+            tags.push("NATIVE");
+        }
+        let tags = tags.join(" ");
+        let tags_dims = measure_text(&tags, Some(current_font), font_size, 1.0);
+        text_with_color(
+            font_cache,
+            config,
+            &tags,
+            screen_width() - tags_dims.width - right_margin,
+            text_y,
+            font_size,
+            dim,
+        );
+    }
+
+    if modes.len() > VISIBLE_MODES {
+        let more = format!(
+            "{}-{} OF {}",
+            state.scroll_offset + 1,
+            (state.scroll_offset + VISIBLE_MODES).min(modes.len()),
+            modes.len()
+        );
+        centered(
+            &more,
+            start_y + VISIBLE_MODES as f32 * menu_option_height + 10.0 * scale_factor,
+            font_size,
+            Some(dim),
+        );
+    }
+
+    if let Some(error) = &state.error {
+        let error_y =
+            start_y + (VISIBLE_MODES as f32 + 1.0) * menu_option_height + 20.0 * scale_factor;
+        text_with_color(
+            font_cache,
+            config,
+            &error.to_uppercase(),
+            left_margin,
+            error_y,
+            font_size,
+            RED,
+        );
+    }
+
+    let instructions = if state.outputs.len() > 1 {
+        "A: APPLY  LEFT/RIGHT: DISPLAY  B: BACK"
+    } else {
+        "A: APPLY  B: BACK"
+    };
+    centered(
+        instructions,
+        screen_height() - 20.0 * scale_factor,
+        font_size,
+        Some(dim),
+    );
+
+    if let Some(pending) = &state.pending {
+        draw_confirm_prompt(pending, font_cache, config, scale_factor);
+    }
+}
+
+/// "Keep this mode?" with the revert countdown
+fn draw_confirm_prompt(
+    pending: &PendingMode,
+    font_cache: &HashMap<String, Font>,
+    config: &Config,
+    scale_factor: f32,
+) {
+    let font_size = (FONT_SIZE as f32 * scale_factor) as u16;
+    let current_font = get_current_font(font_cache, config);
+
+    let box_width = 260.0 * scale_factor;
+    let box_height = 90.0 * scale_factor;
+    let box_x = screen_width() / 2.0 - box_width / 2.0;
+    let box_y = screen_height() / 2.0 - box_height / 2.0;
+    draw_rectangle(
+        box_x,
+        box_y,
+        box_width,
+        box_height,
+        Color::new(0.0, 0.0, 0.0, 0.9),
+    );
+    draw_rectangle_lines(
+        box_x,
+        box_y,
+        box_width,
+        box_height,
+        2.0 * scale_factor,
+        WHITE,
+    );
+
+    let remaining = (REVERT_SECS - (get_time() - pending.applied_at))
+        .max(0.0)
+        .ceil() as u32;
+    let lines = [
+        "KEEP THIS DISPLAY MODE?".to_string(),
+        format!("REVERTING IN {}S", remaining),
+    ];
+    for (i, line) in lines.iter().enumerate() {
+        let dims = measure_text(line, Some(current_font), font_size, 1.0);
+        let y = box_y + 25.0 * scale_factor + i as f32 * 18.0 * scale_factor;
+        text_with_config_color(
+            font_cache,
+            config,
+            line,
+            screen_width() / 2.0 - dims.width / 2.0,
+            y,
+            font_size,
+        );
+    }
+
+    let options = ["KEEP", "REVERT"];
+    let option_y = box_y + box_height - 18.0 * scale_factor;
+    for (i, option) in options.iter().enumerate() {
+        let dims = measure_text(option, Some(current_font), font_size, 1.0);
+        let center_x = box_x + box_width * (i as f32 * 2.0 + 1.0) / 4.0;
+        let x = center_x - dims.width / 2.0;
+        if i == pending.selection {
+            text_with_color(font_cache, config, option, x, option_y, font_size, YELLOW);
+        } else {
+            text_with_config_color(font_cache, config, option, x, option_y, font_size);
+        }
+    }
+}
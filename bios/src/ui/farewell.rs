@@ -0,0 +1,154 @@
+use macroquad::prelude::*;
+use rodio::{buffer::SamplesBuffer, Sink};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::{
+    audio::AUDIO,
+    config::{get_user_data_dir, Config},
+    get_current_font, measure_text,
+    ui::text_with_color,
+    BASE_SCREEN_HEIGHT, FONT_SIZE,
+};
+
+/// How long the farewell screen stays up before the BIOS exits
+pub const FAREWELL_DURATION: f64 = 2.5;
+const FADE_IN: f64 = 0.4;
+const FADE_OUT: f64 = 0.6;
+
+/// `farewell_audio` value for no sound
+pub const FAREWELL_AUDIO_OFF: &str = "OFF";
+
+/// The screen shown after the menu fades out on a session restart
+pub struct FarewellScreen {
+    image: Texture2D,
+    message: String,
+    started: f64,
+    /// Kept alive until the BIOS exits, or the sound stops
+    _sink: Option<Sink>,
+}
+
+impl FarewellScreen {
+    /// Loads the configured image and starts the farewell sound
+    pub fn start(
+        config: &Config,
+        logo_cache: &HashMap<String, Texture2D>,
+        music_cache: &HashMap<String, SamplesBuffer>,
+    ) -> Self {
+        let image = branding_image(config, &config.farewell_image, logo_cache);
+
+        let sink = (config.farewell_audio != FAREWELL_AUDIO_OFF)
+            .then(|| music_cache.get(&config.farewell_audio))
+            .flatten()
+            .map(|buffer| {
+                let sink = Sink::connect_new(&AUDIO.stream.mixer());
+                sink.set_volume(config.bgm_volume);
+                sink.append(buffer.clone());
+                sink
+            });
+
+        println!("[Farewell] Showing farewell screen");
+        Self {
+            image,
+            message: config.farewell_message.to_uppercase(),
+            started: get_time(),
+            _sink: sink,
+        }
+    }
+
+    pub fn is_done(&self) -> bool {
+        get_time() - self.started >= FAREWELL_DURATION
+    }
+
+    /// Draws the image and message over black, fading in and out
+    pub fn draw(&self, font_cache: &HashMap<String, Font>, config: &Config, scale_factor: f32) {
+        let elapsed = get_time() - self.started;
+        let alpha = if elapsed < FADE_IN {
+            elapsed / FADE_IN
+        } else if elapsed > FAREWELL_DURATION - FADE_OUT {
+            (FAREWELL_DURATION - elapsed) / FADE_OUT
+        } else {
+            1.0
+        }
+        .clamp(0.0, 1.0) as f32;
+
+        clear_background(BLACK);
+
+        // Same size as the boot splash logo
+        let aspect_ratio = self.image.height() / self.image.width();
+        let width = 200.0 * (screen_height() / BASE_SCREEN_HEIGHT);
+        let height = width * aspect_ratio;
+        let y = (screen_height() - height) / 2.0;
+        draw_texture_ex(
+            &self.image,
+            (screen_width() - width) / 2.0,
+            y,
+            Color::new(1.0, 1.0, 1.0, alpha),
+            DrawTextureParams {
+                dest_size: Some(vec2(width, height)),
+                ..Default::default()
+            },
+        );
+
+        if !self.message.is_empty() {
+            let font_size = (FONT_SIZE as f32 * scale_factor) as u16;
+            let font = get_current_font(font_cache, config);
+            let dims = measure_text(&self.message, Some(font), font_size, 1.0);
+            let text_y = y + height + 30.0 * scale_factor;
+            text_with_color(
+                font_cache,
+                config,
+                &self.message,
+                screen_width() / 2.0 - dims.width / 2.0,
+                text_y,
+                font_size,
+                Color::new(1.0, 1.0, 1.0, alpha),
+            );
+        }
+    }
+}
+
+/// The `farewell_image` or `splash_image` file, else the selected logo, else
+/// the built-in one
+pub fn branding_image(
+    config: &Config,
+    name: &str,
+    logo_cache: &HashMap<String, Texture2D>,
+) -> Texture2D {
+    load_custom_image(config, name)
+        .or_else(|| logo_cache.get(&config.logo_selection).cloned())
+        .unwrap_or_else(|| {
+            Texture2D::from_file_with_format(
+                include_bytes!("../../logo.png"),
+                Some(ImageFormat::Png),
+            )
+        })
+}
+
+/// A user image, looked up like the splash video: the theme's folder first,
+/// then the user's backgrounds and logos
+fn load_custom_image(config: &Config, name: &str) -> Option<Texture2D> {
+    if name == "Default" {
+        return None;
+    }
+    let user_dir = get_user_data_dir()?;
+    let candidates: [PathBuf; 3] = [
+        user_dir.join("themes").join(&config.theme).join(name),
+        user_dir.join("backgrounds").join(name),
+        user_dir.join("logos").join(name),
+    ];
+    let path = candidates.iter().find(|p| p.exists())?;
+    let bytes = fs::read(path).ok()?;
+    match Image::from_file_with_format(&bytes, None) {
+        Ok(image) => Some(Texture2D::from_image(&image)),
+        Err(e) => {
+            println!(
+                "[Farewell] Skipping unreadable image {}: {}",
+                path.display(),
+                e
+            );
+            None
+        }
+    }
+}
@@ -0,0 +1,519 @@
+use macroquad::prelude::*;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::{
+    audio::SoundEffects,
+    config::{get_user_data_dir, Config},
+    get_current_font, measure_text, render_background, render_ui_overlay, save,
+    text_with_config_color,
+    types::{AnimationState, BackgroundState, BatteryInfo, Screen},
+    ui::text_with_color,
+    InputState, VideoPlayer, FONT_SIZE, MENU_OPTION_HEIGHT, MENU_PADDING,
+};
+
+/// Languages offered in the first step as (config code, display name)
+const LANGUAGES: &[(&str, &str)] = &[
+    ("en", "ENGLISH"),
+    ("es", "ESPANOL"),
+    ("fr", "FRANCAIS"),
+    ("de", "DEUTSCH"),
+    ("pt", "PORTUGUES"),
+];
+
+/// Written once the wizard has been finished or skipped
+const SENTINEL_FILE: &str = "first_run_complete";
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum Step {
+    Language,
+    Wifi,
+    Storage,
+    RetroAchievements,
+    Controllers,
+    Theme,
+    Done,
+}
+
+#[cfg(target_os = "linux")]
+const STEPS: &[Step] = &[
+    Step::Language,
+    Step::Wifi,
+    Step::Storage,
+    Step::RetroAchievements,
+    Step::Controllers,
+    Step::Theme,
+    Step::Done,
+];
+
+// Controller pairing goes through Bluetooth, which is Linux only
+#[cfg(not(target_os = "linux"))]
+const STEPS: &[Step] = &[
+    Step::Language,
+    Step::Wifi,
+    Step::Storage,
+    Step::RetroAchievements,
+    Step::Theme,
+    Step::Done,
+];
+
+impl Step {
+    fn title(&self) -> &'static str {
+        match self {
+            Step::Language => "LANGUAGE",
+            Step::Wifi => "WI-FI",
+            Step::Storage => "STORAGE",
+            Step::RetroAchievements => "RETROACHIEVEMENTS",
+            Step::Controllers => "CONTROLLERS",
+            Step::Theme => "THEME",
+            Step::Done => "ALL SET",
+        }
+    }
+
+    fn description(&self) -> &'static str {
+        match self {
+            Step::Language => "CHOOSE THE LANGUAGE FOR MENUS",
+            Step::Wifi => "CONNECT TO A NETWORK FOR UPDATES AND ACHIEVEMENTS",
+            Step::Storage => "PREPARE FOLDERS FOR SAVES AND GAME DATA",
+            Step::RetroAchievements => "LOG IN TO EARN ACHIEVEMENTS WHILE YOU PLAY",
+            Step::Controllers => "PAIR A WIRELESS CONTROLLER",
+            Step::Theme => "PICK A LOOK FOR THE MENUS",
+            Step::Done => "YOU CAN RUN THIS WIZARD AGAIN FROM SETTINGS",
+        }
+    }
+
+    /// Labels of the step's options; the last one always moves on
+    fn options(&self) -> &'static [&'static str] {
+        match self {
+            Step::Language => &["LANGUAGE", "CONTINUE"],
+            Step::Wifi => &["SET UP WI-FI", "SKIP"],
+            Step::Storage => &["PREPARE STORAGE", "CONTINUE"],
+            Step::RetroAchievements => &["LOG IN", "SKIP"],
+            Step::Controllers => &["PAIR CONTROLLER", "SKIP"],
+            Step::Theme => &["CHOOSE THEME", "SKIP"],
+            Step::Done => &["FINISH"],
+        }
+    }
+
+    /// The existing screen that handles this step, if any
+    fn screen(&self) -> Option<Screen> {
+        match self {
+            Step::Wifi => Some(Screen::Wifi),
+            Step::RetroAchievements => Some(Screen::RetroAchievements),
+            Step::Controllers => Some(Screen::Bluetooth),
+            Step::Theme => Some(Screen::GuiSettings),
+            _ => None,
+        }
+    }
+
+    /// Whether `screen` is still part of this step after handing off to it
+    fn owns(&self, screen: &Screen) -> bool {
+        match self {
+            // The settings pages can be flipped through while picking a theme
+            Step::Theme => matches!(
+                screen,
+                Screen::GeneralSettings
+                    | Screen::AudioSettings
+                    | Screen::GuiSettings
+                    | Screen::AssetSettings
+                    | Screen::KioskSettings
+                    | Screen::ReloadingThemes
+            ),
+            _ => self.screen().as_ref() == Some(screen),
+        }
+    }
+}
+
+fn sentinel_path() -> Option<PathBuf> {
+    get_user_data_dir().map(|dir| dir.join(SENTINEL_FILE))
+}
+
+fn mark_complete() {
+    let Some(path) = sentinel_path() else { return };
+    if let Some(parent) = path.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+    if let Err(e) = fs::write(&path, chrono::Local::now().to_rfc3339()) {
+        eprintln!("[FirstRun] Failed to write {}: {}", path.display(), e);
+    }
+}
+
+/// Whether the wizard should run at boot. Must be called before anything
+/// writes config.toml: an existing config means the system was set up before
+/// the wizard existed, so it is marked complete instead.
+pub fn needs_wizard() -> bool {
+    let Some(path) = sentinel_path() else {
+        return false;
+    };
+    if path.exists() {
+        return false;
+    }
+    if path.with_file_name("config.toml").exists() {
+        mark_complete();
+        return false;
+    }
+    true
+}
+
+/// State for the first-run setup wizard
+pub struct FirstRunState {
+    step: usize,
+    selection: usize,
+    /// Set while another screen handles the current step
+    handed_off: bool,
+    /// Drives and free space (MB) shown on the storage step
+    devices: Vec<(String, u32)>,
+    storage_status: Option<String>,
+    /// Where to go once the wizard is finished
+    exit_screen: Screen,
+}
+
+impl FirstRunState {
+    pub fn new() -> Self {
+        Self {
+            step: 0,
+            selection: 0,
+            handed_off: false,
+            devices: Vec::new(),
+            storage_status: None,
+            exit_screen: Screen::MainMenu,
+        }
+    }
+
+    /// Starts the wizard from the first step
+    pub fn open(&mut self, exit_screen: Screen) {
+        // Picking SETUP WIZARD on the settings pages during the theme step
+        // just returns to the wizard
+        if self.handed_off {
+            return;
+        }
+        *self = Self::new();
+        self.exit_screen = exit_screen;
+    }
+
+    fn current(&self) -> Step {
+        STEPS[self.step]
+    }
+
+    fn go_to(&mut self, step: usize) {
+        self.step = step.min(STEPS.len() - 1);
+        self.selection = 0;
+        if self.current() == Step::Storage {
+            self.devices = save::list_devices().unwrap_or_default();
+            self.storage_status = None;
+        }
+    }
+
+    /// Brings the wizard back once the screen a step was handed to is left.
+    /// Call every frame after the current screen has been updated.
+    pub fn resume(&mut self, current_screen: &mut Screen) {
+        if !self.handed_off || self.current().owns(current_screen) {
+            return;
+        }
+        self.handed_off = false;
+        *current_screen = Screen::FirstRun;
+        self.go_to(self.step + 1);
+    }
+
+    fn prepare_storage(&mut self) {
+        let save_dir = save::get_save_dir_from_drive_name("internal");
+        let cache_dir = save::get_cache_dir_from_drive_name("internal");
+        let ready = Path::new(&save_dir).is_dir() && Path::new(&cache_dir).is_dir();
+        self.storage_status = Some(
+            if ready {
+                "STORAGE READY"
+            } else {
+                "COULD NOT CREATE SAVE FOLDERS"
+            }
+            .to_string(),
+        );
+        self.devices = save::list_devices().unwrap_or_default();
+    }
+
+    fn finish(&mut self, current_screen: &mut Screen) {
+        mark_complete();
+        println!("[FirstRun] Setup complete");
+        *current_screen = self.exit_screen.clone();
+    }
+}
+
+fn language_name(code: &str) -> &'static str {
+    LANGUAGES
+        .iter()
+        .find(|(c, _)| *c == code)
+        .map(|(_, name)| *name)
+        .unwrap_or("ENGLISH")
+}
+
+fn cycle_language(current: &str, forward: bool) -> String {
+    let index = LANGUAGES
+        .iter()
+        .position(|(c, _)| *c == current)
+        .unwrap_or(0);
+    let next = if forward {
+        (index + 1) % LANGUAGES.len()
+    } else {
+        (index + LANGUAGES.len() - 1) % LANGUAGES.len()
+    };
+    LANGUAGES[next].0.to_string()
+}
+
+/// Handles input and state logic for the setup wizard.
+pub fn update(
+    current_screen: &mut Screen,
+    state: &mut FirstRunState,
+    input_state: &InputState,
+    animation_state: &mut AnimationState,
+    sound_effects: &SoundEffects,
+    config: &mut Config,
+) {
+    let step = state.current();
+    let options = step.options();
+
+    // X skips the rest of the wizard
+    if input_state.secondary {
+        state.finish(current_screen);
+        sound_effects.play_back(config);
+        return;
+    }
+
+    if input_state.back {
+        if state.step > 0 {
+            state.go_to(state.step - 1);
+            sound_effects.play_back(config);
+        }
+        return;
+    }
+
+    if input_state.up && state.selection > 0 {
+        state.selection -= 1;
+        animation_state.trigger_transition(&config.cursor_transition_speed);
+        sound_effects.play_cursor_move(config);
+    }
+    if input_state.down && state.selection + 1 < options.len() {
+        state.selection += 1;
+        animation_state.trigger_transition(&config.cursor_transition_speed);
+        sound_effects.play_cursor_move(config);
+    }
+
+    if step == Step::Language
+        && state.selection == 0
+        && (input_state.left || input_state.right || input_state.select)
+    {
+        config.language = cycle_language(&config.language, !input_state.left);
+        config.save();
+        sound_effects.play_cursor_move(config);
+        return;
+    }
+
+    if !input_state.select {
+        return;
+    }
+    sound_effects.play_select(config);
+
+    if state.selection + 1 == options.len() {
+        if step == Step::Done {
+            state.finish(current_screen);
+        } else {
+            state.go_to(state.step + 1);
+        }
+        return;
+    }
+
+    if step == Step::Storage {
+        state.prepare_storage();
+    } else if let Some(screen) = step.screen() {
+        state.handed_off = true;
+        *current_screen = screen;
+    }
+}
+
+/// Draws the setup wizard.
+pub fn draw(
+    state: &FirstRunState,
+    animation_state: &AnimationState,
+    logo_cache: &HashMap<String, Texture2D>,
+    background_cache: &HashMap<String, Texture2D>,
+    video_cache: &mut HashMap<String, VideoPlayer>,
+    font_cache: &HashMap<String, Font>,
+    config: &Config,
+    background_state: &mut BackgroundState,
+    battery_info: &Option<BatteryInfo>,
+    current_time_str: &str,
+    gcc_adapter_poll_rate: &Option<u32>,
+    scale_factor: f32,
+) {
+    render_background(background_cache, video_cache, config, background_state);
+
+    // Dim the background for easier legibility
+    draw_rectangle(
+        0.0,
+        0.0,
+        screen_width(),
+        screen_height(),
+        Color::new(0.0, 0.0, 0.0, 0.6),
+    );
+
+    render_ui_overlay(
+        logo_cache,
+        font_cache,
+        config,
+        battery_info,
+        current_time_str,
+        gcc_adapter_poll_rate,
+        scale_factor,
+    );
+
+    let font_size = (FONT_SIZE as f32 * scale_factor) as u16;
+    let large_font_size = (FONT_SIZE as f32 * scale_factor * 1.5) as u16;
+    let menu_padding = MENU_PADDING * scale_factor;
+    let menu_option_height = MENU_OPTION_HEIGHT * scale_factor;
+    let current_font = get_current_font(font_cache, config);
+    let dim_color = Color::new(0.7, 0.7, 0.7, 1.0);
+    let step = state.current();
+
+    let center = |text: &str, size: u16| {
+        screen_width() / 2.0 - measure_text(text, Some(current_font), size, 1.0).width / 2.0
+    };
+
+    // Title and progress
+    let title_y = 50.0 * scale_factor;
+    text_with_config_color(
+        font_cache,
+        config,
+        step.title(),
+        center(step.title(), large_font_size),
+        title_y,
+        large_font_size,
+    );
+
+    let progress = format!("STEP {} OF {}", state.step + 1, STEPS.len());
+    text_with_color(
+        font_cache,
+        config,
+        &progress,
+        center(&progress, font_size),
+        title_y + 20.0 * scale_factor,
+        font_size,
+        dim_color,
+    );
+    text_with_config_color(
+        font_cache,
+        config,
+        step.description(),
+        center(step.description(), font_size),
+        title_y + 40.0 * scale_factor,
+        font_size,
+    );
+
+    // Step details
+    let mut details: Vec<String> = Vec::new();
+    match step {
+        Step::Storage => {
+            for (name, free_mb) in &state.devices {
+                details.push(format!(
+                    "{}: {:.1} GB FREE",
+                    name.to_uppercase(),
+                    *free_mb as f32 / 1024.0
+                ));
+            }
+            if let Some(status) = &state.storage_status {
+                details.push(status.clone());
+            }
+        }
+        Step::RetroAchievements => {
+            if let Some(username) = config
+                .retroachievements
+                .username
+                .as_ref()
+                .filter(|u| !u.is_empty())
+            {
+                details.push(format!("LOGGED IN AS {}", username.to_uppercase()));
+            }
+        }
+        Step::Theme => details.push(format!("CURRENT THEME: {}", config.theme.to_uppercase())),
+        _ => {}
+    }
+    let details_y = title_y + 65.0 * scale_factor;
+    for (i, line) in details.iter().enumerate() {
+        text_with_color(
+            font_cache,
+            config,
+            line,
+            center(line, font_size),
+            details_y + i as f32 * 15.0 * scale_factor,
+            font_size,
+            dim_color,
+        );
+    }
+
+    // Options
+    let start_y = details_y + (details.len().max(1) as f32 * 15.0 + 10.0) * scale_factor;
+    for (i, option) in step.options().iter().enumerate() {
+        let label = if step == Step::Language && i == 0 {
+            format!("< {} >", language_name(&config.language))
+        } else {
+            option.to_string()
+        };
+        let is_selected = i == state.selection;
+        let y_pos = start_y + i as f32 * menu_option_height;
+        let label_dims = measure_text(&label, Some(current_font), font_size, 1.0);
+        let label_x = screen_width() / 2.0 - label_dims.width / 2.0;
+        let text_y = y_pos + menu_option_height / 2.0 + font_size as f32 * 0.3;
+
+        if is_selected && config.cursor_style == "BOX" {
+            let cursor_color = animation_state.get_cursor_color(config);
+            let cursor_scale = animation_state.get_cursor_scale();
+
+            let base_width = label_dims.width + (menu_padding * 2.0);
+            let base_height = label_dims.height + (menu_padding * 2.0);
+            let scaled_width = base_width * cursor_scale;
+            let scaled_height = base_height * cursor_scale;
+            let offset_x = (scaled_width - base_width) / 2.0;
+            let offset_y = (scaled_height - base_height) / 2.0;
+
+            let rect_x = label_x - menu_padding;
+            let rect_y = y_pos + (menu_option_height / 2.0) - (base_height / 2.0);
+
+            draw_rectangle_lines(
+                rect_x - offset_x,
+                rect_y - offset_y,
+                scaled_width,
+                scaled_height,
+                4.0 * scale_factor,
+                cursor_color,
+            );
+        }
+
+        if is_selected && config.cursor_style == "TEXT" {
+            let highlight_color = animation_state.get_cursor_color(config);
+            text_with_color(
+                font_cache,
+                config,
+                &label,
+                label_x,
+                text_y,
+                font_size,
+                highlight_color,
+            );
+        } else {
+            text_with_config_color(font_cache, config, &label, label_x, text_y, font_size);
+        }
+    }
+
+    let instructions = if state.step == 0 {
+        "A: Select  X: Skip Setup"
+    } else {
+        "A: Select  B: Back  X: Skip Setup"
+    };
+    text_with_color(
+        font_cache,
+        config,
+        instructions,
+        center(instructions, font_size),
+        screen_height() - 20.0 * scale_factor,
+        font_size,
+        dim_color,
+    );
+}
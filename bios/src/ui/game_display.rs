@@ -0,0 +1,331 @@
+use macroquad::prelude::*;
+use std::collections::HashMap;
+
+use crate::{
+    audio::SoundEffects,
+    config::Config,
+    game_display::{self, GameDisplaySettings, ASPECT_RATIOS, FILTERS, FRAME_LIMITS},
+    get_current_font, measure_text, render_background, render_ui_overlay, text_with_config_color,
+    types::{AnimationState, BackgroundState, BatteryInfo, Screen},
+    ui::text_with_color,
+    InputState, VideoPlayer, FONT_SIZE, MENU_OPTION_HEIGHT, MENU_PADDING,
+};
+
+const GAME_DISPLAY_OPTIONS: &[&str] = &[
+    "INTEGER SCALING",
+    "ASPECT RATIO",
+    "FILTER",
+    "CRT SHADER",
+    "FRAME LIMIT",
+    "RESET TO DEFAULTS",
+];
+
+/// State for the per-cart display settings screen
+pub struct GameDisplayState {
+    pub selection: usize,
+    pub cart_id: String,
+    pub game_name: String,
+    pub settings: GameDisplaySettings,
+    shaders: Vec<String>,
+    error: Option<String>,
+}
+
+impl GameDisplayState {
+    pub fn new() -> Self {
+        Self {
+            selection: 0,
+            cart_id: String::new(),
+            game_name: String::new(),
+            settings: GameDisplaySettings::default(),
+            shaders: Vec::new(),
+            error: None,
+        }
+    }
+
+    /// Loads the settings of a cart before showing the screen
+    pub fn open(&mut self, cart_id: &str, game_name: &str) {
+        self.selection = 0;
+        self.cart_id = cart_id.to_string();
+        self.game_name = game_name.to_string();
+        self.settings = GameDisplaySettings::load(cart_id);
+        self.shaders = game_display::available_shaders();
+        self.error = None;
+    }
+
+    /// Steps through "none" and the installed shaders
+    fn cycle_shader(&mut self, forward: bool) {
+        let mut options: Vec<&str> = vec!["none"];
+        options.extend(self.shaders.iter().map(String::as_str));
+        // Keep a configured shader selectable even if it's no longer installed
+        if let Some(shader) = &self.settings.shader {
+            if !options.contains(&shader.as_str()) {
+                options.push(shader);
+            }
+        }
+
+        let current = self.settings.shader.as_deref().unwrap_or("none");
+        let next = game_display::cycle(&options, current, forward);
+        self.settings.shader = if next == "none" { None } else { Some(next) };
+    }
+
+    fn save(&mut self) {
+        self.error = self.settings.save(&self.cart_id).err();
+    }
+}
+
+pub fn update(
+    current_screen: &mut Screen,
+    state: &mut GameDisplayState,
+    input_state: &InputState,
+    animation_state: &mut AnimationState,
+    sound_effects: &SoundEffects,
+    config: &Config,
+) {
+    if input_state.back {
+        *current_screen = Screen::GameSelection;
+        sound_effects.play_back(config);
+        return;
+    }
+
+    if input_state.up {
+        state.selection = if state.selection == 0 {
+            GAME_DISPLAY_OPTIONS.len() - 1
+        } else {
+            state.selection - 1
+        };
+        animation_state.trigger_transition(&config.cursor_transition_speed);
+        sound_effects.play_cursor_move(config);
+    }
+    if input_state.down {
+        state.selection = (state.selection + 1) % GAME_DISPLAY_OPTIONS.len();
+        animation_state.trigger_transition(&config.cursor_transition_speed);
+        sound_effects.play_cursor_move(config);
+    }
+
+    let changed = input_state.left || input_state.right;
+    let forward = input_state.right;
+
+    match state.selection {
+        0 => {
+            // INTEGER SCALING
+            if changed || input_state.select {
+                state.settings.integer_scaling = !state.settings.integer_scaling;
+                state.save();
+                sound_effects.play_cursor_move(config);
+            }
+        }
+        1 => {
+            // ASPECT RATIO
+            if changed {
+                state.settings.aspect_ratio =
+                    game_display::cycle(ASPECT_RATIOS, &state.settings.aspect_ratio, forward);
+                state.save();
+                sound_effects.play_cursor_move(config);
+            }
+        }
+        2 => {
+            // FILTER
+            if changed {
+                state.settings.filter =
+                    game_display::cycle(FILTERS, &state.settings.filter, forward);
+                state.save();
+                sound_effects.play_cursor_move(config);
+            }
+        }
+        3 => {
+            // CRT SHADER
+            if changed {
+                state.cycle_shader(forward);
+                state.save();
+                sound_effects.play_cursor_move(config);
+            }
+        }
+        4 => {
+            // FRAME LIMIT
+            if changed {
+                state.settings.frame_limit =
+                    game_display::cycle(FRAME_LIMITS, &state.settings.frame_limit, forward);
+                state.save();
+                sound_effects.play_cursor_move(config);
+            }
+        }
+        5 => {
+            // RESET TO DEFAULTS
+            if input_state.select {
+                state.settings = GameDisplaySettings::default();
+                state.save();
+                sound_effects.play_select(config);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn get_option_value(index: usize, settings: &GameDisplaySettings) -> String {
+    match index {
+        0 => {
+            if settings.integer_scaling {
+                "ON".to_string()
+            } else {
+                "OFF".to_string()
+            }
+        }
+        1 => settings.aspect_ratio.to_uppercase(),
+        2 => settings.filter.to_uppercase(),
+        3 => settings.shader.as_deref().unwrap_or("NONE").to_uppercase(),
+        4 => match settings.frame_limit.as_str() {
+            "off" => "OFF".to_string(),
+            fps => format!("{} FPS", fps),
+        },
+        5 => "RESET".to_string(),
+        _ => String::new(),
+    }
+}
+
+/// Draws the per-cart display settings UI.
+pub fn draw(
+    state: &GameDisplayState,
+    animation_state: &AnimationState,
+    logo_cache: &HashMap<String, Texture2D>,
+    background_cache: &HashMap<String, Texture2D>,
+    video_cache: &mut HashMap<String, VideoPlayer>,
+    font_cache: &HashMap<String, Font>,
+    config: &Config,
+    background_state: &mut BackgroundState,
+    battery_info: &Option<BatteryInfo>,
+    current_time_str: &str,
+    gcc_adapter_poll_rate: &Option<u32>,
+    scale_factor: f32,
+) {
+    render_background(background_cache, video_cache, config, background_state);
+
+    // Dim the background for easier legibility
+    draw_rectangle(
+        0.0,
+        0.0,
+        screen_width(),
+        screen_height(),
+        Color::new(0.0, 0.0, 0.0, 0.6),
+    );
+
+    render_ui_overlay(
+        logo_cache,
+        font_cache,
+        config,
+        battery_info,
+        current_time_str,
+        gcc_adapter_poll_rate,
+        scale_factor,
+    );
+
+    let font_size = (FONT_SIZE as f32 * scale_factor) as u16;
+    let large_font_size = (FONT_SIZE as f32 * scale_factor * 1.5) as u16;
+    let menu_padding = MENU_PADDING * scale_factor;
+    let menu_option_height = MENU_OPTION_HEIGHT * scale_factor;
+    let current_font = get_current_font(font_cache, config);
+
+    // Title
+    let title = "DISPLAY SETTINGS";
+    let title_dims = measure_text(title, Some(current_font), large_font_size, 1.0);
+    let title_y = 50.0 * scale_factor;
+    text_with_config_color(
+        font_cache,
+        config,
+        title,
+        screen_width() / 2.0 - title_dims.width / 2.0,
+        title_y,
+        large_font_size,
+    );
+
+    let name_dims = measure_text(&state.game_name, Some(current_font), font_size, 1.0);
+    text_with_config_color(
+        font_cache,
+        config,
+        &state.game_name,
+        screen_width() / 2.0 - name_dims.width / 2.0,
+        title_y + 25.0 * scale_factor,
+        font_size,
+    );
+
+    // Menu options
+    let start_y = 100.0 * scale_factor;
+    let left_margin = 80.0 * scale_factor;
+    let right_margin = 80.0 * scale_factor;
+
+    for (i, &label) in GAME_DISPLAY_OPTIONS.iter().enumerate() {
+        let y_pos = start_y + (i as f32 * menu_option_height);
+        let is_selected = i == state.selection;
+
+        let value = get_option_value(i, &state.settings);
+        let value_dims = measure_text(&value, Some(current_font), font_size, 1.0);
+        let value_x = screen_width() - value_dims.width - right_margin;
+        let text_y = y_pos + menu_option_height / 2.0 + font_size as f32 * 0.3;
+
+        if is_selected && config.cursor_style == "BOX" {
+            let cursor_color = animation_state.get_cursor_color(config);
+            let cursor_scale = animation_state.get_cursor_scale();
+
+            let base_width = value_dims.width + (menu_padding * 2.0);
+            let base_height = value_dims.height + (menu_padding * 2.0);
+            let scaled_width = base_width * cursor_scale;
+            let scaled_height = base_height * cursor_scale;
+            let offset_x = (scaled_width - base_width) / 2.0;
+            let offset_y = (scaled_height - base_height) / 2.0;
+
+            let rect_x = value_x - menu_padding;
+            let rect_y = y_pos + (menu_option_height / 2.0) - (base_height / 2.0);
+
+            draw_rectangle_lines(
+                rect_x - offset_x,
+                rect_y - offset_y,
+                scaled_width,
+                scaled_height,
+                4.0 * scale_factor,
+                cursor_color,
+            );
+        }
+
+        text_with_config_color(font_cache, config, label, left_margin, text_y, font_size);
+
+        if is_selected && config.cursor_style == "TEXT" {
+            let highlight_color = animation_state.get_cursor_color(config);
+            text_with_color(
+                font_cache,
+                config,
+                &value,
+                value_x,
+                text_y,
+                font_size,
+                highlight_color,
+            );
+        } else {
+            text_with_config_color(font_cache, config, &value, value_x, text_y, font_size);
+        }
+    }
+
+    if let Some(error) = &state.error {
+        let error_y =
+            start_y + GAME_DISPLAY_OPTIONS.len() as f32 * menu_option_height + 20.0 * scale_factor;
+        text_with_color(
+            font_cache,
+            config,
+            error,
+            left_margin,
+            error_y,
+            font_size,
+            RED,
+        );
+    }
+
+    let instructions = "LEFT/RIGHT: Change, applied the next time the game starts";
+    let inst_dims = measure_text(instructions, Some(current_font), font_size, 1.0);
+    text_with_color(
+        font_cache,
+        config,
+        instructions,
+        screen_width() / 2.0 - inst_dims.width / 2.0,
+        screen_height() - 20.0 * scale_factor,
+        font_size,
+        Color::new(0.7, 0.7, 0.7, 1.0),
+    );
+}
@@ -0,0 +1,221 @@
+use gilrs::{GamepadId, Gilrs};
+use kazeta_overlay::gamepad_tester::{self, GamepadTester};
+use macroquad::prelude::*;
+use std::collections::HashMap;
+
+use crate::{
+    audio::SoundEffects,
+    config::Config,
+    get_current_font, measure_text, render_background, render_ui_overlay, text_with_config_color,
+    types::{BackgroundState, BatteryInfo, Screen},
+    ui::text_with_color,
+    InputState, VideoPlayer, FONT_SIZE,
+};
+
+/// How long B has to be held on a pad to leave, so B itself can be tested
+const HOLD_TO_EXIT: f64 = 1.0;
+/// How long "report written" stays up
+const NOTICE_SECS: f64 = 3.0;
+
+/// State for the Gamepad Tester screen. The pad being tested is whichever
+/// one last pressed a button.
+pub struct GamepadTesterState {
+    tester: GamepadTester,
+    gamepad: Option<GamepadId>,
+    /// When B started being held on the tested pad
+    back_held_since: Option<f64>,
+    /// Start+Select were both down last frame
+    report_combo_held: bool,
+    notice: Option<(String, f64)>,
+}
+
+impl GamepadTesterState {
+    pub fn new() -> Self {
+        Self {
+            tester: GamepadTester::default(),
+            gamepad: None,
+            back_held_since: None,
+            report_combo_held: false,
+            notice: None,
+        }
+    }
+
+    pub fn open(&mut self, gilrs: &Gilrs) {
+        *self = Self::new();
+        self.gamepad = gilrs.gamepads().next().map(|(id, _)| id);
+    }
+
+    fn select(&mut self, id: GamepadId) {
+        if self.gamepad != Some(id) {
+            self.gamepad = Some(id);
+            self.tester.reset();
+            self.back_held_since = None;
+        }
+    }
+
+    /// Write the tested pad's report to the log
+    fn write_report(&mut self, gilrs: &Gilrs) {
+        let name = self
+            .gamepad
+            .and_then(|id| gilrs.connected_gamepad(id))
+            .map(|pad| format!("{} ({:?})", pad.name(), pad.uuid()))
+            .unwrap_or_else(|| "no controller".to_string());
+        for line in self.tester.report(&name).lines() {
+            println!("[GamepadTester] {}", line);
+        }
+        self.notice = Some(("REPORT WRITTEN TO THE LOGS".to_string(), get_time()));
+    }
+}
+
+pub fn update(
+    current_screen: &mut Screen,
+    state: &mut GamepadTesterState,
+    input_state: &InputState,
+    gilrs: &Gilrs,
+    sound_effects: &SoundEffects,
+    config: &Config,
+) {
+    if let Some(&(id, _)) = input_state.gamepad_presses.last() {
+        state.select(id);
+    }
+
+    let pad = state.gamepad.and_then(|id| gilrs.connected_gamepad(id));
+    match pad {
+        Some(pad) => state.tester.update_from_gamepad(&pad),
+        None => {
+            state.gamepad = None;
+            state.tester.reset();
+        }
+    }
+    state.tester.sample();
+
+    let buttons = &state.tester.buttons;
+    let report_combo = buttons.start && buttons.select;
+    let b_held = buttons.b;
+    if (report_combo && !state.report_combo_held)
+        || (input_state.secondary && input_state.gamepad_presses.is_empty())
+    {
+        state.write_report(gilrs);
+        sound_effects.play_select(config);
+    }
+    state.report_combo_held = report_combo;
+
+    // The keyboard leaves right away; pads hold B
+    let keyboard_back = input_state.back && input_state.gamepad_presses.is_empty();
+    let held_back = match (b_held, state.back_held_since) {
+        (true, Some(since)) => get_time() - since >= HOLD_TO_EXIT,
+        (true, None) => {
+            state.back_held_since = Some(get_time());
+            false
+        }
+        (false, _) => {
+            state.back_held_since = None;
+            false
+        }
+    };
+    if keyboard_back || held_back {
+        *current_screen = Screen::GeneralSettings;
+        sound_effects.play_back(config);
+    }
+}
+
+/// Draws the Gamepad Tester screen.
+pub fn draw(
+    state: &GamepadTesterState,
+    gilrs: &Gilrs,
+    logo_cache: &HashMap<String, Texture2D>,
+    background_cache: &HashMap<String, Texture2D>,
+    video_cache: &mut HashMap<String, VideoPlayer>,
+    font_cache: &HashMap<String, Font>,
+    config: &Config,
+    background_state: &mut BackgroundState,
+    battery_info: &Option<BatteryInfo>,
+    current_time_str: &str,
+    gcc_adapter_poll_rate: &Option<u32>,
+    scale_factor: f32,
+) {
+    render_background(background_cache, video_cache, config, background_state);
+
+    // Dim the background for easier legibility
+    draw_rectangle(
+        0.0,
+        0.0,
+        screen_width(),
+        screen_height(),
+        Color::new(0.0, 0.0, 0.0, 0.6),
+    );
+
+    render_ui_overlay(
+        logo_cache,
+        font_cache,
+        config,
+        battery_info,
+        current_time_str,
+        gcc_adapter_poll_rate,
+        scale_factor,
+    );
+
+    let font_size = (FONT_SIZE as f32 * scale_factor) as u16;
+    let large_font_size = (FONT_SIZE as f32 * scale_factor * 1.5) as u16;
+    let current_font = get_current_font(font_cache, config);
+    let dim = Color::new(0.6, 0.6, 0.6, 1.0);
+
+    let centered = |text: &str, y: f32, size: u16, color: Option<Color>| {
+        let dims = measure_text(text, Some(current_font), size, 1.0);
+        let x = screen_width() / 2.0 - dims.width / 2.0;
+        match color {
+            Some(color) => text_with_color(font_cache, config, text, x, y, size, color),
+            None => text_with_config_color(font_cache, config, text, x, y, size),
+        }
+    };
+
+    // Title
+    let title_y = 50.0 * scale_factor;
+    centered("GAMEPAD TESTER", title_y, large_font_size, None);
+
+    let pad = state.gamepad.and_then(|id| gilrs.connected_gamepad(id));
+    let subtitle = match &pad {
+        Some(pad) => pad.name().to_uppercase(),
+        None => "NO CONTROLLER - PRESS A BUTTON ON ONE TO TEST IT".to_string(),
+    };
+    centered(
+        &subtitle,
+        title_y + 25.0 * scale_factor,
+        font_size,
+        Some(dim),
+    );
+
+    // The widget is laid out for the overlay's panel; fit it to the screen
+    let scale = (screen_width() * 0.8 / gamepad_tester::WIDTH)
+        .min(screen_height() * 0.6 / gamepad_tester::HEIGHT);
+    let width = gamepad_tester::WIDTH * scale;
+    let height = gamepad_tester::HEIGHT * scale;
+    let x = screen_width() / 2.0 - width / 2.0;
+    let y = title_y + 40.0 * scale_factor;
+    draw_rectangle(x, y, width, height, Color::new(0.0, 0.0, 0.0, 0.8));
+    draw_rectangle_lines(x, y, width, height, 2.0 * scale_factor, WHITE);
+    if pad.is_some() {
+        state.tester.draw(x, y, scale);
+    }
+
+    let mut hint_y = y + height + 30.0 * scale_factor;
+    if let Some((notice, shown_at)) = &state.notice {
+        if get_time() - shown_at < NOTICE_SECS {
+            centered(notice, hint_y, font_size, Some(YELLOW));
+        }
+    }
+    hint_y += 30.0 * scale_factor;
+    centered(
+        "PRESS ANY BUTTON ON A PAD TO TEST IT",
+        hint_y,
+        font_size,
+        Some(dim),
+    );
+    hint_y += 25.0 * scale_factor;
+    centered(
+        "START + SELECT: WRITE REPORT TO LOGS  HOLD B: BACK",
+        hint_y,
+        font_size,
+        Some(dim),
+    );
+}
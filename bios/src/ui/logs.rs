@@ -0,0 +1,684 @@
+use macroquad::prelude::*;
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{Read, Seek, SeekFrom},
+    sync::{Arc, Mutex},
+};
+
+use crate::{
+    audio::SoundEffects,
+    config::Config,
+    get_current_font, measure_text, render_background, render_ui_overlay, text_with_config_color,
+    types::{AnimationState, BackgroundState, BatteryInfo, Screen},
+    ui::text_with_color,
+    utils::copy_session_logs_to_sd,
+    InputState, VideoPlayer, FLASH_MESSAGE_DURATION, FONT_SIZE,
+};
+
+/// stdout/stderr of the BIOS, written by kazeta-session
+const BIOS_LOG_PATH: &str = "/var/kazeta/session.log";
+/// stdout/stderr of the overlay daemon, written by start_overlay_daemon
+const OVERLAY_LOG_PATH: &str = "/tmp/kazeta-overlay.log";
+
+/// Only the end of large log files is read
+const TAIL_BYTES: u64 = 256 * 1024;
+/// Older lines are dropped past this many
+const MAX_LINES: usize = 5000;
+/// How often the current source is re-read while the screen is open
+const REFRESH_INTERVAL: f64 = 1.0;
+
+const SEARCH_KEYS: &[&str] = &[
+    "1234567890",
+    "abcdefghij",
+    "klmnopqrst",
+    "uvwxyz-_.:",
+    "[]()/=!'\"#",
+];
+const SEARCH_SPECIAL_KEYS: &[&str] = &["SPACE", "DEL", "CLEAR", "DONE"];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LogSource {
+    Bios,
+    Overlay,
+    Game,
+}
+
+impl LogSource {
+    const ALL: [LogSource; 3] = [LogSource::Bios, LogSource::Overlay, LogSource::Game];
+
+    fn label(&self) -> &'static str {
+        match self {
+            LogSource::Bios => "BIOS",
+            LogSource::Overlay => "OVERLAY",
+            LogSource::Game => "GAME OUTPUT",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+enum LogLevel {
+    Debug,
+    Info,
+    Warning,
+    Error,
+}
+
+impl LogLevel {
+    const ALL: [LogLevel; 4] = [
+        LogLevel::Debug,
+        LogLevel::Info,
+        LogLevel::Warning,
+        LogLevel::Error,
+    ];
+
+    /// Label of the filter showing this level and above
+    fn filter_label(&self) -> &'static str {
+        match self {
+            LogLevel::Debug => "ALL",
+            LogLevel::Info => "INFO+",
+            LogLevel::Warning => "WARNINGS+",
+            LogLevel::Error => "ERRORS",
+        }
+    }
+
+    fn color(&self) -> Color {
+        match self {
+            LogLevel::Debug => GRAY,
+            LogLevel::Info => WHITE,
+            LogLevel::Warning => ORANGE,
+            LogLevel::Error => RED,
+        }
+    }
+
+    /// Guesses the level of a line from the tags the components print
+    /// ("[Error]", "[WARNING]", "ERROR overlay::ipc", ...)
+    fn classify(line: &str) -> Self {
+        let upper = line.to_uppercase();
+        if upper.contains("ERROR") || upper.contains("PANIC") || upper.contains("FAILED") {
+            LogLevel::Error
+        } else if upper.contains("WARN") {
+            LogLevel::Warning
+        } else if upper.contains("[DEBUG]") || upper.contains(" DEBUG ") || upper.contains("TRACE")
+        {
+            LogLevel::Debug
+        } else {
+            LogLevel::Info
+        }
+    }
+}
+
+/// Removes ANSI color codes, which some components print
+fn strip_ansi(line: &str) -> String {
+    let mut result = String::with_capacity(line.len());
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            // Skip up to and including the final letter of the sequence
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else if c == '\t' {
+            result.push_str("    ");
+        } else if !c.is_control() {
+            result.push(c);
+        }
+    }
+    result
+}
+
+/// Reads the last TAIL_BYTES of a file as lines
+fn tail_file(path: &str) -> Result<Vec<String>, String> {
+    let mut file = File::open(path).map_err(|e| format!("Can't open {}: {}", path, e))?;
+    let len = file.metadata().map(|m| m.len()).unwrap_or(0);
+    let start = len.saturating_sub(TAIL_BYTES);
+    file.seek(SeekFrom::Start(start))
+        .map_err(|e| e.to_string())?;
+
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes).map_err(|e| e.to_string())?;
+    let text = String::from_utf8_lossy(&bytes);
+
+    let mut lines = text.lines();
+    if start > 0 {
+        lines.next(); // Probably cut off mid-line
+    }
+    Ok(lines.map(strip_ansi).collect())
+}
+
+fn file_len(path: &str) -> Option<u64> {
+    std::fs::metadata(path).map(|m| m.len()).ok()
+}
+
+/// State for the log viewer screen
+pub struct LogViewerState {
+    source: LogSource,
+    lines: Vec<(LogLevel, String)>,
+    /// Indices into `lines` that pass the level filter and search
+    visible: Vec<usize>,
+    min_level: LogLevel,
+    search: String,
+    /// Cursor on the search keyboard while it's open
+    keyboard: Option<(usize, usize)>,
+    /// Lines scrolled up from the bottom; 0 follows new output
+    scroll_from_bottom: usize,
+    last_refresh: f64,
+    /// Size of the file (or number of game lines) when last read
+    source_len: Option<u64>,
+    error: Option<String>,
+}
+
+impl LogViewerState {
+    pub fn new() -> Self {
+        Self {
+            source: LogSource::Bios,
+            lines: Vec::new(),
+            visible: Vec::new(),
+            min_level: LogLevel::Debug,
+            search: String::new(),
+            keyboard: None,
+            scroll_from_bottom: 0,
+            last_refresh: 0.0,
+            source_len: None,
+            error: None,
+        }
+    }
+
+    /// Reloads the current source before showing the screen
+    pub fn open(&mut self, game_logs: &Arc<Mutex<Vec<String>>>) {
+        self.keyboard = None;
+        self.scroll_from_bottom = 0;
+        self.reload(game_logs);
+    }
+
+    fn reload(&mut self, game_logs: &Arc<Mutex<Vec<String>>>) {
+        self.last_refresh = get_time();
+
+        let result = match self.source {
+            LogSource::Bios => {
+                self.source_len = file_len(BIOS_LOG_PATH);
+                tail_file(BIOS_LOG_PATH)
+            }
+            LogSource::Overlay => {
+                self.source_len = file_len(OVERLAY_LOG_PATH);
+                tail_file(OVERLAY_LOG_PATH)
+            }
+            LogSource::Game => {
+                let logs = game_logs.lock().unwrap();
+                self.source_len = Some(logs.len() as u64);
+                // Entries can hold several lines (e.g. "\n--- LAUNCH FAILED ---\n...")
+                Ok(logs
+                    .iter()
+                    .flat_map(|entry| entry.lines().map(strip_ansi))
+                    .collect())
+            }
+        };
+
+        self.error = result.as_ref().err().cloned();
+        let lines = result.unwrap_or_default();
+
+        let skip = lines.len().saturating_sub(MAX_LINES);
+        self.lines = lines
+            .into_iter()
+            .skip(skip)
+            .map(|line| (LogLevel::classify(&line), line))
+            .collect();
+        self.apply_filter();
+    }
+
+    /// Re-reads the source if it changed since the last read
+    fn refresh(&mut self, game_logs: &Arc<Mutex<Vec<String>>>) {
+        if get_time() - self.last_refresh < REFRESH_INTERVAL {
+            return;
+        }
+        let current_len = match self.source {
+            LogSource::Bios => file_len(BIOS_LOG_PATH),
+            LogSource::Overlay => file_len(OVERLAY_LOG_PATH),
+            LogSource::Game => Some(game_logs.lock().unwrap().len() as u64),
+        };
+        if current_len != self.source_len {
+            let before = self.visible.len();
+            self.reload(game_logs);
+            // Keep the same lines on screen when scrolled up
+            if self.scroll_from_bottom > 0 {
+                self.scroll_from_bottom += self.visible.len().saturating_sub(before);
+                self.clamp_scroll();
+            }
+        } else {
+            self.last_refresh = get_time();
+        }
+    }
+
+    fn apply_filter(&mut self) {
+        let query = self.search.to_lowercase();
+        self.visible = self
+            .lines
+            .iter()
+            .enumerate()
+            .filter(|(_, (level, line))| {
+                *level >= self.min_level
+                    && (query.is_empty() || line.to_lowercase().contains(&query))
+            })
+            .map(|(i, _)| i)
+            .collect();
+        self.clamp_scroll();
+    }
+
+    fn clamp_scroll(&mut self) {
+        self.scroll_from_bottom = self
+            .scroll_from_bottom
+            .min(self.visible.len().saturating_sub(1));
+    }
+
+    fn switch_source(&mut self, forward: bool, game_logs: &Arc<Mutex<Vec<String>>>) {
+        let index = LogSource::ALL
+            .iter()
+            .position(|&s| s == self.source)
+            .unwrap_or(0);
+        let len = LogSource::ALL.len();
+        self.source = LogSource::ALL[if forward {
+            (index + 1) % len
+        } else {
+            (index + len - 1) % len
+        }];
+        self.scroll_from_bottom = 0;
+        self.reload(game_logs);
+    }
+
+    fn cycle_level(&mut self, forward: bool) {
+        let index = LogLevel::ALL
+            .iter()
+            .position(|&l| l == self.min_level)
+            .unwrap_or(0);
+        let len = LogLevel::ALL.len();
+        self.min_level = LogLevel::ALL[if forward {
+            (index + 1) % len
+        } else {
+            (index + len - 1) % len
+        }];
+        self.apply_filter();
+    }
+
+    /// Handles input while the search keyboard is open
+    fn update_keyboard(
+        &mut self,
+        input_state: &InputState,
+        sound_effects: &SoundEffects,
+        config: &Config,
+    ) {
+        let Some((row, col)) = &mut self.keyboard else {
+            return;
+        };
+        let num_rows = SEARCH_KEYS.len() + 1;
+
+        if input_state.up && *row > 0 {
+            *row -= 1;
+            sound_effects.play_cursor_move(config);
+        }
+        if input_state.down && *row < num_rows - 1 {
+            *row += 1;
+            sound_effects.play_cursor_move(config);
+        }
+
+        let row_len = if *row < SEARCH_KEYS.len() {
+            SEARCH_KEYS[*row].chars().count()
+        } else {
+            SEARCH_SPECIAL_KEYS.len()
+        };
+        *col = (*col).min(row_len - 1);
+        if input_state.left && *col > 0 {
+            *col -= 1;
+            sound_effects.play_cursor_move(config);
+        }
+        if input_state.right && *col < row_len - 1 {
+            *col += 1;
+            sound_effects.play_cursor_move(config);
+        }
+
+        if input_state.back {
+            self.keyboard = None;
+            sound_effects.play_back(config);
+            return;
+        }
+        if !input_state.select {
+            return;
+        }
+
+        sound_effects.play_select(config);
+        if *row < SEARCH_KEYS.len() {
+            if let Some(key) = SEARCH_KEYS[*row].chars().nth(*col) {
+                self.search.push(key);
+            }
+        } else {
+            match SEARCH_SPECIAL_KEYS[*col] {
+                "SPACE" => self.search.push(' '),
+                "DEL" => {
+                    self.search.pop();
+                }
+                "CLEAR" => self.search.clear(),
+                "DONE" => self.keyboard = None,
+                _ => {}
+            }
+        }
+        self.scroll_from_bottom = 0;
+        self.apply_filter();
+    }
+}
+
+pub fn update(
+    current_screen: &mut Screen,
+    state: &mut LogViewerState,
+    input_state: &InputState,
+    sound_effects: &SoundEffects,
+    config: &Config,
+    game_logs: &Arc<Mutex<Vec<String>>>,
+    flash_message: &mut Option<(String, f32)>,
+) {
+    state.refresh(game_logs);
+
+    if state.keyboard.is_some() {
+        state.update_keyboard(input_state, sound_effects, config);
+        return;
+    }
+
+    if input_state.back {
+        if !state.search.is_empty() {
+            state.search.clear();
+            state.apply_filter();
+        } else {
+            *current_screen = Screen::Extras;
+        }
+        sound_effects.play_back(config);
+        return;
+    }
+
+    if input_state.up && state.scroll_from_bottom + 1 < state.visible.len() {
+        state.scroll_from_bottom += 1;
+        sound_effects.play_cursor_move(config);
+    }
+    if input_state.down && state.scroll_from_bottom > 0 {
+        state.scroll_from_bottom -= 1;
+        sound_effects.play_cursor_move(config);
+    }
+    if input_state.left || input_state.right {
+        state.cycle_level(input_state.right);
+        sound_effects.play_cursor_move(config);
+    }
+    if input_state.next || input_state.prev {
+        state.switch_source(input_state.next, game_logs);
+        sound_effects.play_select(config);
+    }
+    if input_state.secondary {
+        state.keyboard = Some((1, 0));
+        sound_effects.play_select(config);
+    }
+    if input_state.select {
+        sound_effects.play_select(config);
+        let message = match copy_session_logs_to_sd() {
+            Ok(path) => format!("SUCCESS: {}", path),
+            Err(e) => format!("ERROR: {}", e),
+        };
+        *flash_message = Some((message, FLASH_MESSAGE_DURATION));
+    }
+}
+
+fn draw_search_keyboard(
+    state: &LogViewerState,
+    (sel_row, sel_col): (usize, usize),
+    animation_state: &AnimationState,
+    font_cache: &HashMap<String, Font>,
+    config: &Config,
+    scale_factor: f32,
+) {
+    let font_size = (FONT_SIZE as f32 * scale_factor) as u16;
+    let current_font = get_current_font(font_cache, config);
+    let key_spacing = font_size as f32 * 2.2;
+    let row_height = font_size as f32 * 2.0;
+
+    let box_w = SEARCH_KEYS[0].len() as f32 * key_spacing + 40.0 * scale_factor;
+    let box_h = (SEARCH_KEYS.len() as f32 + 3.0) * row_height + 20.0 * scale_factor;
+    let box_x = screen_width() / 2.0 - box_w / 2.0;
+    let box_y = screen_height() / 2.0 - box_h / 2.0;
+    draw_rectangle(box_x, box_y, box_w, box_h, Color::new(0.0, 0.0, 0.0, 0.9));
+    draw_rectangle_lines(box_x, box_y, box_w, box_h, 2.0 * scale_factor, WHITE);
+
+    let query = format!("SEARCH: {}_", state.search);
+    text_with_config_color(
+        font_cache,
+        config,
+        &query,
+        box_x + 20.0 * scale_factor,
+        box_y + row_height,
+        font_size,
+    );
+
+    let cursor_color = animation_state.get_cursor_color(config);
+    let keys_x = box_x + 20.0 * scale_factor;
+    let keys_y = box_y + row_height * 2.5;
+
+    for (r, row) in SEARCH_KEYS.iter().enumerate() {
+        for (c, key) in row.chars().enumerate() {
+            let key_str = key.to_string();
+            let x = keys_x + c as f32 * key_spacing;
+            let y = keys_y + r as f32 * row_height;
+            if (r, c) == (sel_row, sel_col) {
+                let dims = measure_text(&key_str, Some(current_font), font_size, 1.0);
+                draw_rectangle_lines(
+                    x - 4.0,
+                    y - font_size as f32 - 2.0,
+                    dims.width + 8.0,
+                    font_size as f32 + 8.0,
+                    2.0 * scale_factor,
+                    cursor_color,
+                );
+                text_with_color(font_cache, config, &key_str, x, y, font_size, cursor_color);
+            } else {
+                text_with_config_color(font_cache, config, &key_str, x, y, font_size);
+            }
+        }
+    }
+
+    let special_y = keys_y + SEARCH_KEYS.len() as f32 * row_height;
+    let mut x = keys_x;
+    for (c, key) in SEARCH_SPECIAL_KEYS.iter().enumerate() {
+        let dims = measure_text(key, Some(current_font), font_size, 1.0);
+        if (SEARCH_KEYS.len(), c) == (sel_row, sel_col) {
+            draw_rectangle_lines(
+                x - 4.0,
+                special_y - font_size as f32 - 2.0,
+                dims.width + 8.0,
+                font_size as f32 + 8.0,
+                2.0 * scale_factor,
+                cursor_color,
+            );
+            text_with_color(
+                font_cache,
+                config,
+                key,
+                x,
+                special_y,
+                font_size,
+                cursor_color,
+            );
+        } else {
+            text_with_config_color(font_cache, config, key, x, special_y, font_size);
+        }
+        x += dims.width + 20.0 * scale_factor;
+    }
+}
+
+/// Draws the log viewer UI.
+pub fn draw(
+    state: &LogViewerState,
+    animation_state: &AnimationState,
+    logo_cache: &HashMap<String, Texture2D>,
+    background_cache: &HashMap<String, Texture2D>,
+    video_cache: &mut HashMap<String, VideoPlayer>,
+    font_cache: &HashMap<String, Font>,
+    config: &Config,
+    background_state: &mut BackgroundState,
+    battery_info: &Option<BatteryInfo>,
+    current_time_str: &str,
+    gcc_adapter_poll_rate: &Option<u32>,
+    scale_factor: f32,
+    flash_message: Option<&str>,
+) {
+    render_background(background_cache, video_cache, config, background_state);
+
+    // Logs need a darker backdrop than menus to stay readable
+    draw_rectangle(
+        0.0,
+        0.0,
+        screen_width(),
+        screen_height(),
+        Color::new(0.0, 0.0, 0.0, 0.8),
+    );
+
+    render_ui_overlay(
+        logo_cache,
+        font_cache,
+        config,
+        battery_info,
+        current_time_str,
+        gcc_adapter_poll_rate,
+        scale_factor,
+    );
+
+    let font_size = (FONT_SIZE as f32 * scale_factor) as u16;
+    let line_font_size = (FONT_SIZE as f32 * scale_factor * 0.9) as u16;
+    let line_height = line_font_size as f32 + 4.0 * scale_factor;
+    let current_font = get_current_font(font_cache, config);
+    let left_margin = 20.0 * scale_factor;
+
+    // Source tabs
+    let tabs_y = 50.0 * scale_factor;
+    let mut tab_x = left_margin;
+    for source in LogSource::ALL {
+        let label = source.label();
+        let dims = measure_text(label, Some(current_font), font_size, 1.0);
+        if source == state.source {
+            let cursor_color = animation_state.get_cursor_color(config);
+            draw_rectangle_lines(
+                tab_x - 4.0,
+                tabs_y - font_size as f32 - 2.0,
+                dims.width + 8.0,
+                font_size as f32 + 8.0,
+                2.0 * scale_factor,
+                cursor_color,
+            );
+            text_with_color(
+                font_cache,
+                config,
+                label,
+                tab_x,
+                tabs_y,
+                font_size,
+                cursor_color,
+            );
+        } else {
+            text_with_color(font_cache, config, label, tab_x, tabs_y, font_size, GRAY);
+        }
+        tab_x += dims.width + 20.0 * scale_factor;
+    }
+
+    // Filter summary on the right of the tabs
+    let mut summary = format!("LEVEL: {}", state.min_level.filter_label());
+    if !state.search.is_empty() {
+        summary.push_str(&format!("  SEARCH: \"{}\"", state.search));
+    }
+    summary.push_str(&format!("  {} LINES", state.visible.len()));
+    let summary_dims = measure_text(&summary, Some(current_font), font_size, 1.0);
+    text_with_config_color(
+        font_cache,
+        config,
+        &summary,
+        screen_width() - summary_dims.width - left_margin,
+        tabs_y,
+        font_size,
+    );
+
+    // Log lines, newest at the bottom
+    let top = tabs_y + 15.0 * scale_factor;
+    let bottom = screen_height() - 35.0 * scale_factor;
+    let rows = ((bottom - top) / line_height).max(1.0) as usize;
+
+    if let Some(error) = &state.error {
+        text_with_color(
+            font_cache,
+            config,
+            error,
+            left_margin,
+            top + line_height,
+            line_font_size,
+            RED,
+        );
+    } else if state.visible.is_empty() {
+        text_with_color(
+            font_cache,
+            config,
+            "NO MATCHING LINES",
+            left_margin,
+            top + line_height,
+            line_font_size,
+            GRAY,
+        );
+    }
+
+    let end = state.visible.len().saturating_sub(state.scroll_from_bottom);
+    let start = end.saturating_sub(rows);
+    for (row, &index) in state.visible[start..end].iter().enumerate() {
+        let (level, line) = &state.lines[index];
+        let y = top + (row + 1) as f32 * line_height;
+        text_with_color(
+            font_cache,
+            config,
+            line,
+            left_margin,
+            y,
+            line_font_size,
+            level.color(),
+        );
+    }
+
+    // Scroll position
+    if state.scroll_from_bottom > 0 {
+        let indicator = format!("SCROLLED UP {} LINES", state.scroll_from_bottom);
+        let dims = measure_text(&indicator, Some(current_font), font_size, 1.0);
+        text_with_color(
+            font_cache,
+            config,
+            &indicator,
+            screen_width() - dims.width - left_margin,
+            bottom + 5.0 * scale_factor,
+            font_size,
+            YELLOW,
+        );
+    }
+
+    let instructions = flash_message.unwrap_or(
+        "UP/DOWN: Scroll  LEFT/RIGHT: Level  LT/RT: Source  [WEST] Search  [SOUTH] Copy to SD",
+    );
+    let inst_dims = measure_text(instructions, Some(current_font), font_size, 1.0);
+    text_with_color(
+        font_cache,
+        config,
+        instructions,
+        screen_width() / 2.0 - inst_dims.width / 2.0,
+        screen_height() - 10.0 * scale_factor,
+        font_size,
+        Color::new(0.7, 0.7, 0.7, 1.0),
+    );
+
+    if let Some(cursor) = state.keyboard {
+        draw_search_keyboard(
+            state,
+            cursor,
+            animation_state,
+            font_cache,
+            config,
+            scale_factor,
+        );
+    }
+}
@@ -1,21 +1,41 @@
 use crate::{
-    Screen, UIFocus, InputState, copy_session_logs_to_sd, render_background, render_ui_overlay, get_current_font, measure_text, text_with_config_color, text_disabled, FLASH_MESSAGE_DURATION, FONT_SIZE, MENU_PADDING, MENU_OPTION_HEIGHT, ShakeTarget, save, StorageMediaState, VideoPlayer,
     audio::SoundEffects,
     config::Config,
+    copy_session_logs_to_sd, get_current_font, icon_gen, kiosk, library, measure_text,
+    render_background, render_ui_overlay, save, text_disabled, text_with_config_color,
     types::{AnimationState, BackgroundState, BatteryInfo, MenuPosition},
     ui::text_with_color,
+    utils, InputState, Screen, ShakeTarget, StorageMediaState, UIFocus, VideoPlayer,
+    FLASH_MESSAGE_DURATION, FONT_SIZE, MENU_OPTION_HEIGHT, MENU_PADDING,
 };
 use macroquad::prelude::*;
 use rodio::{buffer::SamplesBuffer, Sink};
 use std::{
     collections::HashMap,
     path::PathBuf,
-    sync::{Arc, Mutex},
     sync::atomic::Ordering,
+    sync::{Arc, Mutex},
 };
 
-pub const MAIN_MENU_OPTIONS: &[&str] = &["DATA", "PLAY", "BLADES", "COPY SESSION LOGS", "SETTINGS", "EXTRAS", "ABOUT"];
-pub const MAIN_MENU_OPTIONS_NO_BLADES: &[&str] = &["DATA", "PLAY", "COPY SESSION LOGS", "SETTINGS", "EXTRAS", "ABOUT"];
+pub const MAIN_MENU_OPTIONS: &[&str] = &[
+    "DATA",
+    "PLAY",
+    "BLADES",
+    "COPY SESSION LOGS",
+    "SETTINGS",
+    "EXTRAS",
+    "ABOUT",
+    "POWER",
+];
+pub const MAIN_MENU_OPTIONS_NO_BLADES: &[&str] = &[
+    "DATA",
+    "PLAY",
+    "COPY SESSION LOGS",
+    "SETTINGS",
+    "EXTRAS",
+    "ABOUT",
+    "POWER",
+];
 
 pub fn update(
     current_screen: &mut Screen,
@@ -38,7 +58,11 @@ pub fn update(
     flash_message: &mut Option<(String, f32)>,
     _game_process: &mut Option<std::process::Child>,
 ) {
-    let menu_options = if config.blades_enabled { MAIN_MENU_OPTIONS } else { MAIN_MENU_OPTIONS_NO_BLADES };
+    let menu_options = if config.blades_enabled {
+        MAIN_MENU_OPTIONS
+    } else {
+        MAIN_MENU_OPTIONS_NO_BLADES
+    };
 
     // Update play option enabled status based on cart connection
     *play_option_enabled = cart_connected.load(Ordering::Relaxed);
@@ -73,127 +97,97 @@ pub fn update(
                 *current_screen = Screen::SaveData;
                 input_state.ui_focus = UIFocus::Grid;
                 sound_effects.play_select(&config);
-            },
+            }
             "PLAY" => {
                 if *play_option_enabled {
                     sound_effects.play_select(&config);
                     log_messages.lock().unwrap().clear();
 
-                    match save::find_all_game_files() {
-                        Ok((game_paths, mut debug_log)) => {
-                            log_messages.lock().unwrap().append(&mut debug_log);
-
-                            let mut games: Vec<(save::CartInfo, PathBuf)> = Vec::new();
-                            let parse_errors: Vec<String> = Vec::new();
-
-                            for path in &game_paths {
-                                // Handle .kzp vs .kzi parsing
-                                if let Some(ext) = path.extension().and_then(|e| e.to_str()) {
-                                    if ext == "kzi" {
-                                        // Standard parsing for KZI
-                                        if let Ok(info) = save::parse_kzi_file(path) {
-                                            games.push((info, path.clone()));
-                                        }
-                                    } else if ext == "kzp" {
-                                        // Logic for KZP (Compressed Package)
-                                        let filename = path.file_stem().unwrap().to_string_lossy().to_string();
-                                        let info = save::CartInfo {
-                                            name: Some(filename.clone()),
-                                            id: filename,
-                                            exec: String::from("internal"),
-                                            icon: String::from("icon.png"),
-                                            runtime: Some(String::from("erofs")),
-                                            ..Default::default()
-                                        };
-                                        games.push((info, path.clone()));
-                                    }
-                                }
-                            }
+                    // The library indexer keeps this list current in the background
+                    let mut games = library::games();
+                    let parse_errors = library::errors();
+                    let found = games.len() + parse_errors.len();
 
-                            match games.len() {
-                                0 => {
-                                    let mut logs = log_messages.lock().unwrap();
-                                    logs.push(format!("[Info] Found {} potential game file(s), but none could be parsed.", game_paths.len()));
-                                    logs.push("--- ERRORS ---".to_string());
-                                    logs.extend(parse_errors);
-                                    *current_screen = Screen::Debug;
-                                },
-                                1 => {
-                                    *available_games = games;
-                                    *game_selection = 0;
-                                    *current_screen = Screen::GameSelection;
-                                },
-                                _ => {
-                                    println!("[Debug] Found {} games. Switching to selection screen.", games.len());
-                                    game_icon_queue.clear();
-                                    for (cart_info, game_path) in &games {
-                                        let is_package = game_path.extension().map_or(false, |e| e == "kzp");
-                                        let icon_path = if is_package {
-                                            let sidecar_png = game_path.with_extension("png");
-                                            let sidecar_jpg = game_path.with_extension("jpg");
+                    kiosk::filter_launchable(&mut games, config);
+                    utils::notify_game_list(&games);
 
-                                            if sidecar_png.exists() {
-                                                sidecar_png
-                                            } else if sidecar_jpg.exists() {
-                                                sidecar_jpg
-                                            } else {
-                                                PathBuf::from("::KZP_PLACEHOLDER::")
-                                            }
-                                        } else {
-                                            game_path.parent().unwrap().join(&cart_info.icon)
-                                        };
-                                        game_icon_queue.push((cart_info.id.clone(), icon_path));
-                                    }
-                                    *available_games = games;
-                                    *game_selection = 0;
-                                    *current_screen = Screen::GameSelection;
-                                }
-                            }
-                        },
-                        Err(e) => {
-                            let error_msg = format!("[Error] Error scanning for cartridges: {}", e);
-                            println!("[Error] {}", &error_msg);
-                            log_messages.lock().unwrap().push(error_msg);
+                    match games.len() {
+                        0 => {
+                            let mut logs = log_messages.lock().unwrap();
+                            logs.push(format!(
+                                "[Info] Found {} potential game file(s), but none could be parsed.",
+                                found
+                            ));
+                            logs.push("--- ERRORS ---".to_string());
+                            logs.extend(parse_errors);
                             *current_screen = Screen::Debug;
                         }
+                        1 => {
+                            *available_games = games;
+                            *game_selection = 0;
+                            *current_screen = Screen::GameSelection;
+                        }
+                        _ => {
+                            println!(
+                                "[Debug] Found {} games. Switching to selection screen.",
+                                games.len()
+                            );
+                            game_icon_queue.clear();
+                            for (cart_info, game_path) in &games {
+                                // Carts without an icon get a generated one
+                                game_icon_queue.push((
+                                    cart_info.id.clone(),
+                                    icon_gen::icon_for(cart_info, game_path),
+                                ));
+                            }
+                            *available_games = games;
+                            *game_selection = 0;
+                            *current_screen = Screen::GameSelection;
+                        }
                     }
                 } else {
                     sound_effects.play_reject(&config);
                     animation_state.trigger_play_option_shake();
                 }
-            },
+            }
             "BLADES" => {
                 *current_screen = Screen::BladesDashboard;
                 sound_effects.play_select(config);
-            },
+            }
             "COPY SESSION LOGS" => {
                 if *copy_logs_option_enabled {
                     sound_effects.play_select(&config);
                     match copy_session_logs_to_sd() {
                         Ok(path) => {
-                            *flash_message = Some((format!("SUCCESS: {}", path), FLASH_MESSAGE_DURATION));
+                            *flash_message =
+                                Some((format!("SUCCESS: {}", path), FLASH_MESSAGE_DURATION));
                         }
                         Err(e) => {
-                            *flash_message = Some((format!("ERROR: {}", e), FLASH_MESSAGE_DURATION));
+                            *flash_message =
+                                Some((format!("ERROR: {}", e), FLASH_MESSAGE_DURATION));
                         }
                     }
                 } else {
                     sound_effects.play_reject(&config);
                     animation_state.trigger_copy_log_option_shake();
                 }
-            },
+            }
             "SETTINGS" => {
                 *current_screen = Screen::GeneralSettings;
                 sound_effects.play_select(&config);
-            },
+            }
             "EXTRAS" => {
                 *current_screen = Screen::Extras;
                 sound_effects.play_select(&config);
-            },
+            }
+            "POWER" => {
+                *current_screen = Screen::Power;
+                sound_effects.play_select(config);
+            }
             "ABOUT" => {
                 *current_screen = Screen::About;
                 sound_effects.play_select(&config);
-            },
+            }
             _ => {}
         }
     }
@@ -216,9 +210,21 @@ pub fn draw(
     scale_factor: f32,
     flash_message: Option<&str>,
 ) {
-    let menu_options = if config.blades_enabled { MAIN_MENU_OPTIONS } else { MAIN_MENU_OPTIONS_NO_BLADES };
+    let menu_options = if config.blades_enabled {
+        MAIN_MENU_OPTIONS
+    } else {
+        MAIN_MENU_OPTIONS_NO_BLADES
+    };
     render_background(background_cache, video_cache, config, background_state);
-    render_ui_overlay(logo_cache, font_cache, config, battery_info, current_time_str, gcc_adapter_poll_rate, scale_factor);
+    render_ui_overlay(
+        logo_cache,
+        font_cache,
+        config,
+        battery_info,
+        current_time_str,
+        gcc_adapter_poll_rate,
+        scale_factor,
+    );
 
     let font_size = (FONT_SIZE as f32 * scale_factor) as u16;
     let menu_padding = MENU_PADDING * scale_factor;
@@ -229,11 +235,23 @@ pub fn draw(
     let current_font = get_current_font(font_cache, config);
 
     let (start_x, start_y, is_centered) = match config.menu_position {
-        MenuPosition::Center => (screen_width() / 2.0, (screen_height() * 0.3).max(margin_y), true),
+        MenuPosition::Center => (
+            screen_width() / 2.0,
+            (screen_height() * 0.3).max(margin_y),
+            true,
+        ),
         MenuPosition::TopLeft => (margin_x, margin_y, false),
         MenuPosition::TopRight => (screen_width() - margin_x, margin_y, false),
-        MenuPosition::BottomLeft => (margin_x, screen_height() - (menu_options.len() as f32 * menu_option_height), false),
-        MenuPosition::BottomRight => (screen_width() - margin_x, screen_height() - (menu_options.len() as f32 * menu_option_height), false),
+        MenuPosition::BottomLeft => (
+            margin_x,
+            screen_height() - (menu_options.len() as f32 * menu_option_height),
+            false,
+        ),
+        MenuPosition::BottomRight => (
+            screen_width() - margin_x,
+            screen_height() - (menu_options.len() as f32 * menu_option_height),
+            false,
+        ),
     };
 
     for (i, &option) in menu_options.iter().enumerate() {
@@ -250,11 +268,14 @@ pub fn draw(
         if i == 1 && !play_option_enabled && i == selected_option {
             x_pos += animation_state.calculate_shake_offset(ShakeTarget::PlayOption);
         }
-        let (is_selected, is_disabled) = (i == selected_option, match option {
-            "PLAY" => !play_option_enabled,
-            "COPY SESSION LOGS" => !copy_logs_option_enabled,
-            _ => false,
-        });
+        let (is_selected, is_disabled) = (
+            i == selected_option,
+            match option {
+                "PLAY" => !play_option_enabled,
+                "COPY SESSION LOGS" => !copy_logs_option_enabled,
+                _ => false,
+            },
+        );
 
         if is_selected && config.cursor_style == "BOX" {
             let cursor_color = animation_state.get_cursor_color(config);
@@ -267,7 +288,14 @@ pub fn draw(
             let offset_y = (scaled_height - base_height) / 2.0;
             let rect_x = x_pos - menu_padding;
             let rect_y = y_pos - text_dims.height - menu_padding;
-            draw_rectangle_lines(rect_x - offset_x, rect_y - offset_y, scaled_width, scaled_height, 4.0 * scale_factor, cursor_color);
+            draw_rectangle_lines(
+                rect_x - offset_x,
+                rect_y - offset_y,
+                scaled_width,
+                scaled_height,
+                4.0 * scale_factor,
+                cursor_color,
+            );
         }
 
         if is_selected && config.cursor_style == "TEXT" {
@@ -278,7 +306,15 @@ pub fn draw(
                 highlight_color.b *= 0.5;
                 highlight_color.a = 1.0;
             }
-            text_with_color(font_cache, config, option, x_pos, y_pos, font_size, highlight_color);
+            text_with_color(
+                font_cache,
+                config,
+                option,
+                x_pos,
+                y_pos,
+                font_size,
+                highlight_color,
+            );
         } else if is_disabled {
             text_disabled(font_cache, config, option, x_pos, y_pos, font_size);
         } else {
@@ -292,7 +328,13 @@ pub fn draw(
         let dims = measure_text(message, Some(current_font), font_size, 1.0);
         let x = screen_width() / 2.0 - dims.width / 2.0;
         let y = screen_height() - (60.0 * scale_factor);
-        draw_rectangle(x - (10.0 * scale_factor), y - dims.height, dims.width + (20.0 * scale_factor), dims.height + (10.0 * scale_factor), Color::new(0.0, 0.0, 0.0, 0.7));
+        draw_rectangle(
+            x - (10.0 * scale_factor),
+            y - dims.height,
+            dims.width + (20.0 * scale_factor),
+            dims.height + (10.0 * scale_factor),
+            Color::new(0.0, 0.0, 0.0, 0.7),
+        );
         text_with_config_color(font_cache, config, message, x, y, font_size);
     }
 }
@@ -1,10 +1,8 @@
 use crate::{
-    string_to_color, FONT_SIZE, BatteryInfo, MenuPosition, VERSION_NUMBER, BackgroundState, COLOR_TARGETS, UI_BG_COLOR,
-    save, PathBuf, AnimationState, RECT_COLOR, Memory, Arc, Mutex, PlaytimeCache, SizeCache, TILE_SIZE,
-    PADDING, GRID_OFFSET, GRID_WIDTH, ShakeTarget, Dialog, CopyOperationState, UI_BG_COLOR_DIALOG,
-    config::Config,
-    memory::{get_game_playtime, get_game_size},
-    video::VideoPlayer,
+    config::Config, memory::SaveMetadataCache, save, string_to_color, video::VideoPlayer,
+    AnimationState, Arc, BackgroundState, BatteryInfo, CopyOperationState, Dialog, Memory,
+    MenuPosition, Mutex, PathBuf, ShakeTarget, COLOR_TARGETS, FONT_SIZE, GRID_OFFSET, GRID_WIDTH,
+    PADDING, RECT_COLOR, TILE_SIZE, UI_BG_COLOR, UI_BG_COLOR_DIALOG, VERSION_NUMBER,
 };
 use macroquad::prelude::*;
 use std::collections::HashMap;
@@ -13,15 +11,33 @@ pub mod about;
 pub mod blades;
 #[cfg(target_os = "linux")]
 pub mod bluetooth;
+pub mod boot_splash;
+pub mod cart_editor;
 pub mod cd_player;
+pub mod collections;
 pub mod data;
 pub mod dialog;
+pub mod display;
 pub mod extras_menu;
+pub mod farewell;
+pub mod first_run;
+pub mod game_display;
+pub mod gamepad_tester;
+pub mod kiosk;
+pub mod logs;
 pub mod main_menu;
+pub mod multiplayer_history;
+pub mod network_share;
+pub mod player_join;
+pub mod power;
+pub mod remote_devices;
 pub mod retroachievements;
 pub mod runtime_downloader;
+pub mod save_slots;
 pub mod settings;
+pub mod system_update;
 pub mod theme_downloader;
+pub mod trophy_room;
 pub mod update_checker;
 pub mod wifi;
 
@@ -158,11 +174,16 @@ pub fn render_background(
             }
             player.update(loop_time);
 
-            let tint_color = if config.color_shift_speed == "OFF" { WHITE } else { state.bg_color };
+            let tint_color = if config.color_shift_speed == "OFF" {
+                WHITE
+            } else {
+                state.bg_color
+            };
 
             draw_texture_ex(
                 &player.texture,
-                0.0, 0.0,
+                0.0,
+                0.0,
                 tint_color,
                 DrawTextureParams {
                     dest_size: Some(vec2(screen_width(), screen_height())),
@@ -183,21 +204,31 @@ pub fn render_background(
 
     // 2. Try to draw Image
     if let Some(background_texture) = background_cache.get(&config.background_selection) {
-        let tint_color = if config.color_shift_speed == "OFF" { WHITE } else { state.bg_color };
+        let tint_color = if config.color_shift_speed == "OFF" {
+            WHITE
+        } else {
+            state.bg_color
+        };
 
         if config.background_scroll_speed == "OFF" {
             // Static
             draw_texture_ex(
-                background_texture, 0.0, 0.0, tint_color,
+                background_texture,
+                0.0,
+                0.0,
+                tint_color,
                 DrawTextureParams {
                     dest_size: Some(vec2(screen_width(), screen_height())),
-                            ..Default::default()
+                    ..Default::default()
                 },
             );
         } else {
             // Scrolling
             let speed = match config.background_scroll_speed.as_str() {
-                "SLOW" => 0.05, "NORMAL" => 0.1, "FAST" => 0.2, _ => 0.0
+                "SLOW" => 0.05,
+                "NORMAL" => 0.1,
+                "FAST" => 0.2,
+                _ => 0.0,
             };
             let aspect_ratio = background_texture.width() / background_texture.height();
             let scaled_height = screen_height();
@@ -208,7 +239,13 @@ pub fn render_background(
             };
 
             state.bgx = (state.bgx + speed) % scaled_width;
-            draw_texture_ex(background_texture, state.bgx - scaled_width, 0.0, tint_color, params.clone());
+            draw_texture_ex(
+                background_texture,
+                state.bgx - scaled_width,
+                0.0,
+                tint_color,
+                params.clone(),
+            );
             draw_texture_ex(background_texture, state.bgx, 0.0, tint_color, params);
         }
 
@@ -224,7 +261,10 @@ pub fn render_background(
 // Extracts the color math so we can call it from any branch
 fn update_color_shift(config: &Config, state: &mut BackgroundState) {
     let transition_speed = match config.color_shift_speed.as_str() {
-        "SLOW" => 0.05, "NORMAL" => 0.1, "FAST" => 0.2, _ => 0.0,
+        "SLOW" => 0.05,
+        "NORMAL" => 0.1,
+        "FAST" => 0.2,
+        _ => 0.0,
     };
 
     if transition_speed > 0.0 {
@@ -278,7 +318,12 @@ pub fn render_ui_overlay(
                 WHITE,
                 DrawTextureParams {
                     dest_size: Some(vec2(scaled_logo_width, scaled_logo_height)),
-                    source: Some(Rect::new(0.0, 0.0, logo_to_draw.width(), logo_to_draw.height())),
+                    source: Some(Rect::new(
+                        0.0,
+                        0.0,
+                        logo_to_draw.width(),
+                        logo_to_draw.height(),
+                    )),
                     ..Default::default()
                 },
             );
@@ -401,7 +446,15 @@ pub fn render_game_selection_menu(
     // If no games are available, render background/overlay and a friendly message.
     if games.is_empty() {
         render_background(background_cache, video_cache, config, background_state);
-        render_ui_overlay(logo_cache, font_cache, config, battery_info, current_time_str, gcc_adapter_poll_rate, scale_factor);
+        render_ui_overlay(
+            logo_cache,
+            font_cache,
+            config,
+            battery_info,
+            current_time_str,
+            gcc_adapter_poll_rate,
+            scale_factor,
+        );
 
         let font = get_current_font(font_cache, config);
         let font_size = (18.0 * scale_factor) as u16;
@@ -409,12 +462,30 @@ pub fn render_game_selection_menu(
         let dims = measure_text(msg, Some(font), font_size, 1.0);
         let x = (screen_width() - dims.width) / 2.0;
         let y = screen_height() / 2.0;
-        draw_text_ex(msg, x, y, TextParams { font: Some(font), font_size, color: GRAY, ..Default::default() });
+        draw_text_ex(
+            msg,
+            x,
+            y,
+            TextParams {
+                font: Some(font),
+                font_size,
+                color: GRAY,
+                ..Default::default()
+            },
+        );
         return;
     }
 
     render_background(background_cache, video_cache, config, background_state);
-    render_ui_overlay(logo_cache, font_cache, config, battery_info, current_time_str, gcc_adapter_poll_rate, scale_factor);
+    render_ui_overlay(
+        logo_cache,
+        font_cache,
+        config,
+        battery_info,
+        current_time_str,
+        gcc_adapter_poll_rate,
+        scale_factor,
+    );
 
     const TILE_SIZE: f32 = 60.0;
     const PADDING: f32 = 10.0;
@@ -431,8 +502,10 @@ pub fn render_game_selection_menu(
     let grid_width_items = 5;
     let grid_height_items = (games.len() as f32 / grid_width_items as f32).ceil() as usize;
 
-    let total_grid_width = (grid_width_items as f32 * scaled_tile_size) + ((grid_width_items - 1) as f32 * scaled_padding);
-    let total_grid_height = (grid_height_items as f32 * scaled_tile_size) + ((grid_height_items - 1) as f32 * scaled_padding);
+    let total_grid_width = (grid_width_items as f32 * scaled_tile_size)
+        + ((grid_width_items - 1) as f32 * scaled_padding);
+    let total_grid_height = (grid_height_items as f32 * scaled_tile_size)
+        + ((grid_height_items - 1) as f32 * scaled_padding);
 
     // --- 3. Calculate Centered Starting Position (within the content area) ---
     let start_x = (screen_width() - total_grid_width) / 2.0;
@@ -452,10 +525,16 @@ pub fn render_game_selection_menu(
         draw_rectangle(pos_x, pos_y, scaled_tile_size, scaled_tile_size, RECT_COLOR);
 
         // Draw the icon
-        draw_texture_ex(icon, pos_x, pos_y, WHITE, DrawTextureParams {
-            dest_size: Some(vec2(scaled_tile_size, scaled_tile_size)),
-            ..Default::default()
-        });
+        draw_texture_ex(
+            icon,
+            pos_x,
+            pos_y,
+            WHITE,
+            DrawTextureParams {
+                dest_size: Some(vec2(scaled_tile_size, scaled_tile_size)),
+                ..Default::default()
+            },
+        );
 
         // Draw selection highlight
         if i == selected_game {
@@ -473,7 +552,7 @@ pub fn render_game_selection_menu(
                 scaled_size,
                 scaled_size,
                 6.0 * scale_factor, // Line thickness
-                cursor_color
+                cursor_color,
             );
         }
     }
@@ -488,6 +567,19 @@ pub fn render_game_selection_menu(
         let text_y = screen_height() - (40.0 * scale_factor);
 
         text_with_config_color(font_cache, config, name, text_x, text_y, font_size);
+
+        let hint = "[WEST] DISPLAY SETTINGS   [RT] EDIT INFO   [LT] MULTIPLAYER HISTORY";
+        let hint_font_size = (12.0 * scale_factor) as u16;
+        let hint_dims = measure_text(hint, None, hint_font_size, 1.0);
+        text_with_color(
+            font_cache,
+            config,
+            hint,
+            screen_width() / 2.0 - hint_dims.width / 2.0,
+            text_y + 20.0 * scale_factor,
+            hint_font_size,
+            GRAY,
+        );
     }
 }
 
@@ -526,11 +618,18 @@ pub fn render_debug_screen(
     // --- Draw the instruction or flash message ---
     let instruction_text = flash_message.unwrap_or("PRESS [SOUTH] TO SAVE LOG (OR [EAST] TO EXIT)");
     let instruction_font_size = (14.0 * scale_factor) as u16;
-    let instruction_text_width = measure_text(instruction_text, None, instruction_font_size, 1.0).width;
+    let instruction_text_width =
+        measure_text(instruction_text, None, instruction_font_size, 1.0).width;
     let instruction_x = (screen_width() - instruction_text_width) / 2.0; // Center it
     let instruction_y = screen_height() - (5.0 * scale_factor); // Position near the bottom
 
-    draw_text(instruction_text, instruction_x, instruction_y, instruction_font_size as f32, WHITE);
+    draw_text(
+        instruction_text,
+        instruction_x,
+        instruction_y,
+        instruction_font_size as f32,
+        WHITE,
+    );
 }
 
 // DIALOG BOX
@@ -553,7 +652,13 @@ pub fn render_dialog_box(
     let box_y = screen_height() / 2.0 - box_height / 2.0;
 
     // --- Draw Background and Border ---
-    draw_rectangle(box_x, box_y, box_width, box_height, Color::new(0.0, 0.0, 0.0, 0.8));
+    draw_rectangle(
+        box_x,
+        box_y,
+        box_width,
+        box_height,
+        Color::new(0.0, 0.0, 0.0, 0.8),
+    );
     draw_rectangle_lines(box_x, box_y, box_width, box_height, 2.0, WHITE);
 
     // --- Draw Message Text (handles multiple lines) ---
@@ -618,8 +723,8 @@ pub fn render_dialog_box(
         // Draw the YES/NO text
         text_with_config_color(font_cache, config, opt1, yes_x, option_y, font_size);
         text_with_config_color(font_cache, config, opt2, no_x, option_y, font_size);
-
-    } else { // No options, just an "OK" implied for the Reset Complete screen
+    } else {
+        // No options, just an "OK" implied for the Reset Complete screen
         let ok_text = "PRESS [SOUTH] TO RESTART";
         let text_dims = measure_text(ok_text, Some(current_font), font_size, 1.0);
         let text_x = screen_width() / 2.0 - text_dims.width / 2.0;
@@ -628,6 +733,124 @@ pub fn render_dialog_box(
     }
 }
 
+/// Draw two copies of a save side by side so the user can pick which one to keep
+fn render_save_comparison(
+    source: &save::SaveSummary,
+    destination: &save::SaveSummary,
+    font_cache: &HashMap<String, Font>,
+    config: &Config,
+    scale_factor: f32,
+) {
+    let font_size = (FONT_SIZE as f32 * scale_factor) as u16;
+    let current_font = get_current_font(font_cache, config);
+    let line_height = font_size as f32 * 1.2;
+    let top_y = font_size as f32 * 4.0;
+
+    let source_newer = source.modified > destination.modified;
+    let destination_newer = destination.modified > source.modified;
+
+    let columns = [
+        (
+            format!("FROM {}", source.drive_name.to_uppercase()),
+            source,
+            source_newer,
+            screen_width() * 0.25,
+        ),
+        (
+            format!("ON {}", destination.drive_name.to_uppercase()),
+            destination,
+            destination_newer,
+            screen_width() * 0.75,
+        ),
+    ];
+
+    for (header, summary, newer, center_x) in columns.iter() {
+        let modified = summary
+            .modified
+            .map(|t| {
+                chrono::DateTime::<chrono::Local>::from(t)
+                    .format("%Y-%m-%d %H:%M")
+                    .to_string()
+            })
+            .unwrap_or_else(|| "UNKNOWN".to_string());
+        let lines = [
+            header.clone(),
+            if *newer {
+                format!("{} (NEWER)", modified)
+            } else {
+                modified
+            },
+            format!("{:.1} MB", summary.size_mb),
+            format!("{:.1} H PLAYED", summary.playtime_hours),
+        ];
+
+        for (i, line) in lines.iter().enumerate() {
+            let width = measure_text(line, Some(current_font), font_size, 1.0).width;
+            let y = top_y + line_height * i as f32;
+            if i == 0 || (i == 1 && *newer) {
+                text_with_color(
+                    font_cache,
+                    config,
+                    line,
+                    center_x - width / 2.0,
+                    y,
+                    font_size,
+                    YELLOW,
+                );
+            } else {
+                text_with_config_color(
+                    font_cache,
+                    config,
+                    line,
+                    center_x - width / 2.0,
+                    y,
+                    font_size,
+                );
+            }
+        }
+    }
+
+    draw_line(
+        screen_width() / 2.0,
+        top_y - font_size as f32,
+        screen_width() / 2.0,
+        top_y + line_height * 3.0,
+        2.0 * scale_factor,
+        WHITE,
+    );
+}
+
+/// Size, playtime and last played date of a save for its info line
+pub fn save_stats_text(metadata: &save::SaveMetadata) -> String {
+    let mut text = format!(
+        "{:.1} MB | {:.1} H",
+        metadata.size_mb, metadata.playtime_hours
+    );
+    if let Some(time) = metadata.last_played {
+        text.push_str(&format!(" | PLAYED {}", time.format("%Y-%m-%d")));
+    }
+    text
+}
+
+/// Draw a save's screenshot `height` tall with its right edge at `right`
+pub fn draw_thumbnail(texture: &Texture2D, right: f32, y: f32, height: f32) {
+    let size = texture.size();
+    let width = if size.y > 0.0 {
+        height * size.x / size.y
+    } else {
+        height
+    };
+    let params = DrawTextureParams {
+        dest_size: Some(Vec2 {
+            x: width,
+            y: height,
+        }),
+        ..Default::default()
+    };
+    draw_texture_ex(texture, right - width, y, WHITE, params);
+    draw_rectangle_lines(right - width, y, width, height, 1.0, BLACK);
+}
+
 // DIALOG
 pub fn render_dialog(
     dialog: &Dialog,
@@ -640,8 +863,7 @@ pub fn render_dialog(
     placeholder: &Texture2D,
     scroll_offset: usize,
     animation_state: &AnimationState,
-    playtime_cache: &mut PlaytimeCache,
-    size_cache: &mut SizeCache,
+    metadata_cache: &mut SaveMetadataCache,
     scale_factor: f32,
 ) {
     // --- Scaled variables ---
@@ -660,53 +882,124 @@ pub fn render_dialog(
 
     // Dialog background
     if animation_state.dialog_transition_progress >= 1.0 {
-        draw_rectangle(0.0, 0.0, screen_width(), screen_height(), UI_BG_COLOR_DIALOG);
+        draw_rectangle(
+            0.0,
+            0.0,
+            screen_width(),
+            screen_height(),
+            UI_BG_COLOR_DIALOG,
+        );
     }
 
     // Game icon and name
     if let Some(mem) = memories.get(get_memory_index(selected_memory, scroll_offset)) {
         let icon = icon_cache.get(&mem.id).unwrap_or(placeholder);
-        let params = DrawTextureParams { dest_size: Some(Vec2 { x: tile_size, y: tile_size }), ..Default::default() };
+        let params = DrawTextureParams {
+            dest_size: Some(Vec2 {
+                x: tile_size,
+                y: tile_size,
+            }),
+            ..Default::default()
+        };
         let icon_pos = animation_state.get_dialog_transition_pos();
         draw_texture_ex(icon, icon_pos.x, icon_pos.y, WHITE, params);
 
         if animation_state.dialog_transition_progress >= 1.0 {
             let desc = mem.name.clone().unwrap_or_else(|| mem.id.clone());
-            let playtime = get_game_playtime(mem, playtime_cache);
-            let size = get_game_size(mem, size_cache);
-
-            text_with_config_color(font_cache, config, &desc, tile_size * 2.0, tile_size - (1.0 * scale_factor), font_size);
-            let stats_text = format!("{:.1} MB | {:.1} H", size, playtime);
-            text_with_config_color(font_cache, config, &stats_text, tile_size * 2.0, tile_size * 1.5 + (1.0 * scale_factor), font_size);
+            let (metadata, thumbnail) = metadata_cache.get(mem);
+
+            text_with_config_color(
+                font_cache,
+                config,
+                &desc,
+                tile_size * 2.0,
+                tile_size - (1.0 * scale_factor),
+                font_size,
+            );
+            let stats_text = save_stats_text(metadata);
+            text_with_config_color(
+                font_cache,
+                config,
+                &stats_text,
+                tile_size * 2.0,
+                tile_size * 1.5 + (1.0 * scale_factor),
+                font_size,
+            );
+            if let Some(thumbnail) = thumbnail {
+                draw_thumbnail(
+                    thumbnail,
+                    screen_width() - tile_size * 0.5,
+                    icon_pos.y,
+                    tile_size,
+                );
+            }
         }
     };
 
     // Copy progress bar
     if copy_running {
         draw_rectangle_lines(
-            (font_size * 3) as f32, screen_height() / 2.0,
-            screen_width() - (font_size * 6) as f32, 1.2 * font_size as f32,
-            4.0 * scale_factor, WHITE
+            (font_size * 3) as f32,
+            screen_height() / 2.0,
+            screen_width() - (font_size * 6) as f32,
+            1.2 * font_size as f32,
+            4.0 * scale_factor,
+            WHITE,
         );
         draw_rectangle(
-            (font_size*3) as f32 + 0.2*font_size as f32, screen_height()/2.0 + 0.2*font_size as f32,
-            (screen_width() - (font_size*6) as f32 - 0.4*font_size as f32) * (copy_progress as f32 / 100.0),
-            0.8 * font_size as f32, WHITE
+            (font_size * 3) as f32 + 0.2 * font_size as f32,
+            screen_height() / 2.0 + 0.2 * font_size as f32,
+            (screen_width() - (font_size * 6) as f32 - 0.4 * font_size as f32)
+                * (copy_progress as f32 / 100.0),
+            0.8 * font_size as f32,
+            WHITE,
         );
     } else if animation_state.dialog_transition_progress >= 1.0 {
-        if let Some(desc) = dialog.desc.clone() {
+        if let Some((source, destination)) = &dialog.comparison {
+            render_save_comparison(source, destination, font_cache, config, scale_factor);
+        } else if let Some(desc) = dialog.desc.clone() {
             let text_width = measure_text(&desc, Some(current_font), font_size, 1.0).width;
             let x_pos = (screen_width() - text_width) / 2.0;
-            text_with_config_color(font_cache, config, &desc, x_pos, (font_size * 7) as f32, font_size);
+            text_with_config_color(
+                font_cache,
+                config,
+                &desc,
+                x_pos,
+                (font_size * 7) as f32,
+                font_size,
+            );
         }
 
         // Centering and drawing dialog options
-        let longest_width = measure_text( &dialog.options.iter() .find(|opt| opt.text.len() == dialog.options.iter().map(|opt| opt.text.len()).max().unwrap_or(0)) .map(|opt| opt.text.to_uppercase()).unwrap_or_default(), Some(current_font), font_size, 1.0).width;
+        let longest_width = measure_text(
+            &dialog
+                .options
+                .iter()
+                .find(|opt| {
+                    opt.text.len()
+                        == dialog
+                            .options
+                            .iter()
+                            .map(|opt| opt.text.len())
+                            .max()
+                            .unwrap_or(0)
+                })
+                .map(|opt| opt.text.to_uppercase())
+                .unwrap_or_default(),
+            Some(current_font),
+            font_size,
+            1.0,
+        )
+        .width;
         let options_start_x = (screen_width() - longest_width) / 2.0;
 
         let selection_y = (font_size * 9 + font_size * 2 * (dialog.selection as u16)) as f32;
         let selected_option = &dialog.options[dialog.selection];
-        let selection_shake = if selected_option.disabled { animation_state.calculate_shake_offset(ShakeTarget::Dialog) * scale_factor } else { 0.0 };
+        let selection_shake = if selected_option.disabled {
+            animation_state.calculate_shake_offset(ShakeTarget::Dialog) * scale_factor
+        } else {
+            0.0
+        };
 
         let cursor_color = animation_state.get_cursor_color(config);
 
@@ -724,14 +1017,21 @@ pub fn render_dialog(
             draw_rectangle_lines(
                 options_start_x - box_padding + selection_shake - offset_x,
                 selection_y - box_padding - offset_y,
-                scaled_width, scaled_height, 4.0 * scale_factor, cursor_color
+                scaled_width,
+                scaled_height,
+                4.0 * scale_factor,
+                cursor_color,
             );
         }
 
         // --- Draw Text Options ---
         for (i, option) in dialog.options.iter().enumerate() {
             let y_pos = (font_size * 10 + font_size * 2 * (i as u16)) as f32;
-            let shake_offset = if option.disabled { animation_state.calculate_shake_offset(ShakeTarget::Dialog) * scale_factor } else { 0.0 };
+            let shake_offset = if option.disabled {
+                animation_state.calculate_shake_offset(ShakeTarget::Dialog) * scale_factor
+            } else {
+                0.0
+            };
             let x_pos = options_start_x + shake_offset;
             let is_selected = i == dialog.selection;
 
@@ -747,8 +1047,15 @@ pub fn render_dialog(
                     highlight_color.a = 1.0;
                 }
 
-                text_with_color(font_cache, config, &option.text, x_pos, y_pos, font_size, highlight_color);
-
+                text_with_color(
+                    font_cache,
+                    config,
+                    &option.text,
+                    x_pos,
+                    y_pos,
+                    font_size,
+                    highlight_color,
+                );
             } else if option.disabled {
                 text_disabled(font_cache, config, &option.text, x_pos, y_pos, font_size);
             } else {
@@ -770,7 +1077,10 @@ pub fn get_memory_index(selected_memory: usize, scroll_offset: usize) -> usize {
     selected_memory + GRID_WIDTH * scroll_offset
 }
 
-pub fn calculate_icon_transition_positions(selected_memory: usize, scale_factor: f32) -> (Vec2, Vec2) {
+pub fn calculate_icon_transition_positions(
+    selected_memory: usize,
+    scale_factor: f32,
+) -> (Vec2, Vec2) {
     let xp = (selected_memory % GRID_WIDTH) as f32;
     let yp = (selected_memory / GRID_WIDTH) as f32;
 
@@ -780,7 +1090,7 @@ pub fn calculate_icon_transition_positions(selected_memory: usize, scale_factor:
 
     let grid_pos = Vec2::new(
         pixel_pos(xp, scale_factor),
-        pixel_pos(yp, scale_factor) + grid_offset
+        pixel_pos(yp, scale_factor) + grid_offset,
     );
     let dialog_pos = Vec2::new(padding, padding);
     (grid_pos, dialog_pos)
@@ -792,84 +1102,153 @@ pub fn calculate_icon_transition_positions(selected_memory: usize, scale_factor:
 
 /// Looks up the currently selected font in the cache.
 /// Falls back to the "Default" font if the selection is not found.
-pub fn get_current_font<'a>(
-    font_cache: &'a HashMap<String, Font>,
-    config: &Config,
-) -> &'a Font {
+pub fn get_current_font<'a>(font_cache: &'a HashMap<String, Font>, config: &Config) -> &'a Font {
     font_cache
-    .get(&config.font_selection)
-    .unwrap_or_else(|| &font_cache["Default"])
+        .get(&config.font_selection)
+        .unwrap_or_else(|| &font_cache["Default"])
 }
 
 // Draws text with a specific color passed in (ignoring config.font_color)
 // Useful for the "TEXT" cursor style.
-pub fn text_with_color(font_cache: &HashMap<String, Font>, config: &Config, text: &str, x: f32, y: f32, font_size: u16, color: Color) {
+pub fn text_with_color(
+    font_cache: &HashMap<String, Font>,
+    config: &Config,
+    text: &str,
+    x: f32,
+    y: f32,
+    font_size: u16,
+    color: Color,
+) {
     let font = get_current_font(font_cache, config);
     let shadow_offset = 1.0 * (font_size as f32 / FONT_SIZE as f32);
 
     // Shadow
-    draw_text_ex(text, x + shadow_offset, y + shadow_offset, TextParams {
-        font: Some(font),
-        font_size,
-        color: Color { r: 0.0, g: 0.0, b: 0.0, a: 0.9 },
-        ..Default::default()
-    });
+    draw_text_ex(
+        text,
+        x + shadow_offset,
+        y + shadow_offset,
+        TextParams {
+            font: Some(font),
+            font_size,
+            color: Color {
+                r: 0.0,
+                g: 0.0,
+                b: 0.0,
+                a: 0.9,
+            },
+            ..Default::default()
+        },
+    );
 
     // Main Text with specific color
-    draw_text_ex(text, x, y, TextParams {
-        font: Some(font),
-        font_size,
-        color,
-        ..Default::default()
-    });
+    draw_text_ex(
+        text,
+        x,
+        y,
+        TextParams {
+            font: Some(font),
+            font_size,
+            color,
+            ..Default::default()
+        },
+    );
 }
 
 // A new function specifically for drawing text that respects the config color
-pub fn text_with_config_color(font_cache: &HashMap<String, Font>, config: &Config, text: &str, x: f32, y: f32, font_size: u16) {
+pub fn text_with_config_color(
+    font_cache: &HashMap<String, Font>,
+    config: &Config,
+    text: &str,
+    x: f32,
+    y: f32,
+    font_size: u16,
+) {
     let font = get_current_font(font_cache, config);
 
     // Shadow should scale with font size
     let shadow_offset = 1.0 * (font_size as f32 / FONT_SIZE as f32);
 
     // Shadow
-    draw_text_ex(text, x + shadow_offset, y + shadow_offset, TextParams {
-        font: Some(font),
-        font_size,
-        color: Color { r: 0.0, g: 0.0, b: 0.0, a: 0.9 },
-        ..Default::default()
-    });
+    draw_text_ex(
+        text,
+        x + shadow_offset,
+        y + shadow_offset,
+        TextParams {
+            font: Some(font),
+            font_size,
+            color: Color {
+                r: 0.0,
+                g: 0.0,
+                b: 0.0,
+                a: 0.9,
+            },
+            ..Default::default()
+        },
+    );
 
     // Main Text (using the color from config)
-    draw_text_ex(text, x, y, TextParams {
-        font: Some(font),
-        font_size,
-        color: string_to_color(&config.font_color),
-        ..Default::default()
-    });
+    draw_text_ex(
+        text,
+        x,
+        y,
+        TextParams {
+            font: Some(font),
+            font_size,
+            color: string_to_color(&config.font_color),
+            ..Default::default()
+        },
+    );
 }
 
 // text when "PLAY" or "COPY SESSION LOGS" is greyed out
-pub fn text_disabled(font_cache: &HashMap<String, Font>, config: &Config, text : &str, x : f32, y: f32, font_size: u16) {
+pub fn text_disabled(
+    font_cache: &HashMap<String, Font>,
+    config: &Config,
+    text: &str,
+    x: f32,
+    y: f32,
+    font_size: u16,
+) {
     let font = get_current_font(font_cache, config);
     let shadow_offset = 1.0 * (font_size as f32 / FONT_SIZE as f32);
 
     // SHADOW
-    draw_text_ex(text, x + shadow_offset, y + shadow_offset, TextParams {
-        font: Some(font),
-        //font_size: font_size,
-        font_size,
-        color: Color {r:0.0, g:0.0, b:0.0, a:1.0},
-        ..Default::default()
-    });
+    draw_text_ex(
+        text,
+        x + shadow_offset,
+        y + shadow_offset,
+        TextParams {
+            font: Some(font),
+            //font_size: font_size,
+            font_size,
+            color: Color {
+                r: 0.0,
+                g: 0.0,
+                b: 0.0,
+                a: 1.0,
+            },
+            ..Default::default()
+        },
+    );
 
     // MAIN TEXT
-    draw_text_ex(text, x, y, TextParams {
-        font: Some(font),
-        //font_size: font_size,
-        font_size,
-        color: Color {r:0.4, g:0.4, b:0.4, a:1.0},
-        ..Default::default()
-    });
+    draw_text_ex(
+        text,
+        x,
+        y,
+        TextParams {
+            font: Some(font),
+            //font_size: font_size,
+            font_size,
+            color: Color {
+                r: 0.4,
+                g: 0.4,
+                b: 0.4,
+                a: 1.0,
+            },
+            ..Default::default()
+        },
+    );
 }
 
 /// Render the mGBA launch options dialog (multiplayer & save file selection)
@@ -898,8 +1277,21 @@ pub fn render_mgba_launch_dialog(
     let box_y = screen_height() / 2.0 - box_height / 2.0;
 
     // --- Draw Background and Border ---
-    draw_rectangle(box_x, box_y, box_width, box_height, Color::new(0.0, 0.0, 0.0, 0.9));
-    draw_rectangle_lines(box_x, box_y, box_width, box_height, 2.0 * scale_factor, WHITE);
+    draw_rectangle(
+        box_x,
+        box_y,
+        box_width,
+        box_height,
+        Color::new(0.0, 0.0, 0.0, 0.9),
+    );
+    draw_rectangle_lines(
+        box_x,
+        box_y,
+        box_width,
+        box_height,
+        2.0 * scale_factor,
+        WHITE,
+    );
 
     // --- Draw Description Text ---
     let mut content_y = box_y + padding;
@@ -907,7 +1299,14 @@ pub fn render_mgba_launch_dialog(
         for line in desc.lines() {
             let text_dims = measure_text(line, Some(current_font), font_size, 1.0);
             let text_x = screen_width() / 2.0 - text_dims.width / 2.0;
-            text_with_config_color(font_cache, config, line, text_x, content_y + text_dims.height, font_size);
+            text_with_config_color(
+                font_cache,
+                config,
+                line,
+                text_x,
+                content_y + text_dims.height,
+                font_size,
+            );
             content_y += text_dims.height + 5.0 * scale_factor;
         }
         content_y += 10.0 * scale_factor;
@@ -956,14 +1355,20 @@ pub fn render_mgba_launch_dialog(
     // --- Draw Instructions ---
     let instruction_text = "[SOUTH] SELECT    [EAST] BACK";
     let instruction_size = (12.0 * scale_factor) as u16;
-    let instruction_dims = measure_text(instruction_text, Some(current_font), instruction_size, 1.0);
+    let instruction_dims =
+        measure_text(instruction_text, Some(current_font), instruction_size, 1.0);
     let instruction_x = screen_width() / 2.0 - instruction_dims.width / 2.0;
     let instruction_y = box_y + box_height - 5.0 * scale_factor;
 
-    draw_text_ex(instruction_text, instruction_x, instruction_y, TextParams {
-        font: Some(current_font),
-        font_size: instruction_size,
-        color: Color::new(0.7, 0.7, 0.7, 1.0),
-        ..Default::default()
-    });
+    draw_text_ex(
+        instruction_text,
+        instruction_x,
+        instruction_y,
+        TextParams {
+            font: Some(current_font),
+            font_size: instruction_size,
+            color: Color::new(0.7, 0.7, 0.7, 1.0),
+            ..Default::default()
+        },
+    );
 }
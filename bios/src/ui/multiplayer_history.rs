@@ -0,0 +1,237 @@
+use chrono::Local;
+use macroquad::prelude::*;
+use std::collections::HashMap;
+
+use crate::{
+    audio::SoundEffects,
+    config::Config,
+    get_current_font, measure_text,
+    multiplayer_log::{self, MultiplayerSession},
+    render_background, render_ui_overlay, text_with_config_color,
+    types::{AnimationState, BackgroundState, BatteryInfo, Screen},
+    ui::text_with_color,
+    InputState, VideoPlayer, FONT_SIZE, MENU_OPTION_HEIGHT,
+};
+
+/// Sessions shown at once
+const VISIBLE_SESSIONS: usize = 7;
+
+/// State for a game's multiplayer history screen
+pub struct MultiplayerHistoryState {
+    pub game_name: String,
+    sessions: Vec<MultiplayerSession>,
+    scroll_offset: usize,
+}
+
+impl MultiplayerHistoryState {
+    pub fn new() -> Self {
+        Self {
+            game_name: String::new(),
+            sessions: Vec::new(),
+            scroll_offset: 0,
+        }
+    }
+
+    /// Reads the cart's sessions from the log before showing the screen
+    pub fn open(&mut self, cart_id: &str, game_name: &str) {
+        self.game_name = game_name.to_string();
+        self.sessions = multiplayer_log::sessions_for(cart_id);
+        self.scroll_offset = 0;
+    }
+}
+
+/// "1H 05M", or "12M" under an hour
+fn format_duration(secs: i64) -> String {
+    let minutes = secs.max(0) / 60;
+    if minutes >= 60 {
+        format!("{}H {:02}M", minutes / 60, minutes % 60)
+    } else {
+        format!("{}M", minutes)
+    }
+}
+
+pub fn update(
+    current_screen: &mut Screen,
+    state: &mut MultiplayerHistoryState,
+    input_state: &InputState,
+    animation_state: &mut AnimationState,
+    sound_effects: &SoundEffects,
+    config: &Config,
+) {
+    if input_state.back {
+        *current_screen = Screen::GameSelection;
+        sound_effects.play_back(config);
+        return;
+    }
+
+    let max_offset = state.sessions.len().saturating_sub(VISIBLE_SESSIONS);
+    if input_state.up && state.scroll_offset > 0 {
+        state.scroll_offset -= 1;
+        animation_state.trigger_transition(&config.cursor_transition_speed);
+        sound_effects.play_cursor_move(config);
+    }
+    if input_state.down && state.scroll_offset < max_offset {
+        state.scroll_offset += 1;
+        animation_state.trigger_transition(&config.cursor_transition_speed);
+        sound_effects.play_cursor_move(config);
+    }
+}
+
+/// Draws the totals and the list of past sessions, newest first.
+pub fn draw(
+    state: &MultiplayerHistoryState,
+    logo_cache: &HashMap<String, Texture2D>,
+    background_cache: &HashMap<String, Texture2D>,
+    video_cache: &mut HashMap<String, VideoPlayer>,
+    font_cache: &HashMap<String, Font>,
+    config: &Config,
+    background_state: &mut BackgroundState,
+    battery_info: &Option<BatteryInfo>,
+    current_time_str: &str,
+    gcc_adapter_poll_rate: &Option<u32>,
+    scale_factor: f32,
+) {
+    render_background(background_cache, video_cache, config, background_state);
+
+    // Dim the background for easier legibility
+    draw_rectangle(
+        0.0,
+        0.0,
+        screen_width(),
+        screen_height(),
+        Color::new(0.0, 0.0, 0.0, 0.6),
+    );
+
+    render_ui_overlay(
+        logo_cache,
+        font_cache,
+        config,
+        battery_info,
+        current_time_str,
+        gcc_adapter_poll_rate,
+        scale_factor,
+    );
+
+    let font_size = (FONT_SIZE as f32 * scale_factor) as u16;
+    let small_font_size = (FONT_SIZE as f32 * scale_factor * 0.75) as u16;
+    let large_font_size = (FONT_SIZE as f32 * scale_factor * 1.5) as u16;
+    let row_height = MENU_OPTION_HEIGHT * scale_factor;
+    let current_font = get_current_font(font_cache, config);
+    let dim = Color::new(0.7, 0.7, 0.7, 1.0);
+
+    let centered = |text: &str, y: f32, size: u16, color: Option<Color>| {
+        let dims = measure_text(text, Some(current_font), size, 1.0);
+        let x = screen_width() / 2.0 - dims.width / 2.0;
+        match color {
+            Some(color) => text_with_color(font_cache, config, text, x, y, size, color),
+            None => text_with_config_color(font_cache, config, text, x, y, size),
+        }
+    };
+
+    let title_y = 50.0 * scale_factor;
+    centered("MULTIPLAYER HISTORY", title_y, large_font_size, None);
+    centered(
+        &state.game_name.to_uppercase(),
+        title_y + 25.0 * scale_factor,
+        font_size,
+        None,
+    );
+    centered(
+        "B: BACK",
+        screen_height() - 20.0 * scale_factor,
+        font_size,
+        Some(dim),
+    );
+
+    if state.sessions.is_empty() {
+        centered(
+            "NO MULTIPLAYER SESSIONS YET",
+            screen_height() / 2.0,
+            font_size,
+            Some(dim),
+        );
+        return;
+    }
+
+    let total_secs: i64 = state.sessions.iter().map(|s| s.duration_secs).sum();
+    let most_players = state.sessions.iter().map(|s| s.players).max().unwrap_or(0);
+    let summary = format!(
+        "{} SESSIONS  -  {} TOGETHER  -  UP TO {} PLAYERS",
+        state.sessions.len(),
+        format_duration(total_secs),
+        most_players
+    );
+    centered(
+        &summary,
+        title_y + 50.0 * scale_factor,
+        small_font_size,
+        Some(dim),
+    );
+
+    let list_y = title_y + 75.0 * scale_factor;
+    let left_margin = 80.0 * scale_factor;
+    let right_margin = 80.0 * scale_factor;
+
+    for (row, session) in state
+        .sessions
+        .iter()
+        .skip(state.scroll_offset)
+        .take(VISIBLE_SESSIONS)
+        .enumerate()
+    {
+        let y = list_y + row as f32 * row_height + small_font_size as f32;
+        let date = session
+            .started_at
+            .with_timezone(&Local)
+            .format("%Y-%m-%d %H:%M")
+            .to_string();
+        text_with_config_color(font_cache, config, &date, left_margin, y, small_font_size);
+
+        let players = format!("{} PLAYERS", session.players);
+        let players_dims = measure_text(&players, Some(current_font), small_font_size, 1.0);
+        text_with_color(
+            font_cache,
+            config,
+            &players,
+            screen_width() / 2.0 - players_dims.width / 2.0 + 40.0 * scale_factor,
+            y,
+            small_font_size,
+            WHITE,
+        );
+
+        let duration = format_duration(session.duration_secs);
+        let duration_dims = measure_text(&duration, Some(current_font), small_font_size, 1.0);
+        text_with_color(
+            font_cache,
+            config,
+            &duration,
+            screen_width() - right_margin - duration_dims.width,
+            y,
+            small_font_size,
+            dim,
+        );
+    }
+
+    if state.scroll_offset > 0 {
+        text_with_color(
+            font_cache,
+            config,
+            "^",
+            screen_width() - right_margin + 10.0 * scale_factor,
+            list_y + small_font_size as f32,
+            small_font_size,
+            dim,
+        );
+    }
+    if state.scroll_offset + VISIBLE_SESSIONS < state.sessions.len() {
+        text_with_color(
+            font_cache,
+            config,
+            "v",
+            screen_width() - right_margin + 10.0 * scale_factor,
+            list_y + VISIBLE_SESSIONS as f32 * row_height,
+            small_font_size,
+            dim,
+        );
+    }
+}
@@ -0,0 +1,408 @@
+use macroquad::prelude::*;
+use std::collections::HashMap;
+
+use crate::{
+    audio::SoundEffects,
+    config::Config,
+    get_current_font, measure_text,
+    netshare::{self, ShareStatus, PROTOCOLS},
+    render_background, render_ui_overlay, text_with_config_color,
+    types::{AnimationState, BackgroundState, BatteryInfo, Screen},
+    ui::text_with_color,
+    InputState, VideoPlayer, FLASH_MESSAGE_DURATION, FONT_SIZE, MENU_OPTION_HEIGHT, MENU_PADDING,
+};
+
+const NETWORK_SHARE_OPTIONS: &[&str] = &[
+    "PROTOCOL",
+    "HOST",
+    "SHARE",
+    "USERNAME",
+    "PASSWORD",
+    "CONNECT",
+    "DISCONNECT",
+];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Field {
+    Host,
+    Share,
+    Username,
+    Password,
+}
+
+/// State for the network share settings screen
+#[derive(Clone, Debug, Default)]
+pub struct NetworkShareState {
+    pub selection: usize,
+    editing: Option<Field>,
+    input: String,
+}
+
+impl NetworkShareState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn start_editing(&mut self, field: Field, config: &Config) {
+        let share = &config.network_share;
+        self.input = match field {
+            Field::Host => share.host.clone(),
+            Field::Share => share.share.clone(),
+            Field::Username => share.username.clone().unwrap_or_default(),
+            // Typed fresh rather than revealing the stored one
+            Field::Password => String::new(),
+        };
+        self.editing = Some(field);
+    }
+
+    fn finish_editing(&mut self, config: &mut Config) {
+        let Some(field) = self.editing.take() else {
+            return;
+        };
+        let value = self.input.trim().to_string();
+        let share = &mut config.network_share;
+        match field {
+            Field::Host => share.host = value,
+            Field::Share => share.share = value,
+            Field::Username => share.username = Some(value).filter(|v| !v.is_empty()),
+            // Left empty, the stored password stays
+            Field::Password if self.input.is_empty() => {}
+            Field::Password => {
+                if let Err(e) = netshare::set_password(share, &self.input) {
+                    println!("[NetShare] {}", e);
+                }
+            }
+        }
+        self.input.clear();
+        config.save();
+    }
+}
+
+/// Handles input and state logic for the network share settings screen.
+/// The share monitor picks up the saved settings and does the mounting.
+pub fn update(
+    current_screen: &mut Screen,
+    share_state: &mut NetworkShareState,
+    input_state: &InputState,
+    animation_state: &mut AnimationState,
+    sound_effects: &SoundEffects,
+    config: &mut Config,
+    flash_message: &mut Option<(String, f32)>,
+) {
+    if let Some(field) = share_state.editing {
+        handle_text_input(&mut share_state.input, field);
+        if input_state.select || input_state.back {
+            share_state.finish_editing(config);
+            sound_effects.play_select(config);
+        }
+        return;
+    }
+
+    if input_state.up {
+        share_state.selection = if share_state.selection == 0 {
+            NETWORK_SHARE_OPTIONS.len() - 1
+        } else {
+            share_state.selection - 1
+        };
+        animation_state.trigger_transition(&config.cursor_transition_speed);
+        sound_effects.play_cursor_move(config);
+    }
+    if input_state.down {
+        share_state.selection = (share_state.selection + 1) % NETWORK_SHARE_OPTIONS.len();
+        animation_state.trigger_transition(&config.cursor_transition_speed);
+        sound_effects.play_cursor_move(config);
+    }
+    if input_state.back {
+        *current_screen = Screen::GeneralSettings;
+        sound_effects.play_back(config);
+    }
+
+    if input_state.select || input_state.left || input_state.right {
+        match share_state.selection {
+            0 => {
+                // PROTOCOL
+                if input_state.left || input_state.right {
+                    let current = PROTOCOLS
+                        .iter()
+                        .position(|p| *p == config.network_share.protocol)
+                        .unwrap_or(0);
+                    config.network_share.protocol =
+                        PROTOCOLS[(current + 1) % PROTOCOLS.len()].to_string();
+                    config.save();
+                    sound_effects.play_cursor_move(config);
+                }
+            }
+            // NFS has no login
+            3 | 4 if config.network_share.protocol == "NFS" => {
+                if input_state.select {
+                    sound_effects.play_reject(config);
+                }
+            }
+            1..=4 => {
+                // HOST, SHARE, USERNAME, PASSWORD
+                if input_state.select {
+                    let field = match share_state.selection {
+                        1 => Field::Host,
+                        2 => Field::Share,
+                        3 => Field::Username,
+                        _ => Field::Password,
+                    };
+                    share_state.start_editing(field, config);
+                    sound_effects.play_select(config);
+                }
+            }
+            5 => {
+                // CONNECT
+                if input_state.select {
+                    if config.network_share.host.is_empty() || config.network_share.share.is_empty()
+                    {
+                        *flash_message = Some((
+                            "ENTER A HOST AND SHARE FIRST".to_string(),
+                            FLASH_MESSAGE_DURATION,
+                        ));
+                        sound_effects.play_reject(config);
+                    } else {
+                        config.network_share.enabled = true;
+                        config.save();
+                        sound_effects.play_select(config);
+                    }
+                }
+            }
+            6 => {
+                // DISCONNECT
+                if input_state.select {
+                    config.network_share.enabled = false;
+                    config.save();
+                    sound_effects.play_select(config);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Handle typed text; hosts and share paths only take the characters the
+/// mount helper accepts
+fn handle_text_input(text: &mut String, field: Field) {
+    while let Some(c) = get_char_pressed() {
+        let allowed = match field {
+            Field::Host => c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_' | ':'),
+            Field::Share => {
+                c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_' | '/' | ' ' | '$')
+            }
+            Field::Username | Field::Password => !c.is_control(),
+        };
+        if allowed {
+            text.push(c);
+        }
+    }
+
+    if is_key_pressed(KeyCode::Backspace) && !text.is_empty() {
+        text.pop();
+    }
+}
+
+/// Draws the network share settings UI.
+pub fn draw(
+    share_state: &NetworkShareState,
+    status: &ShareStatus,
+    animation_state: &AnimationState,
+    logo_cache: &HashMap<String, Texture2D>,
+    background_cache: &HashMap<String, Texture2D>,
+    video_cache: &mut HashMap<String, VideoPlayer>,
+    font_cache: &HashMap<String, Font>,
+    config: &Config,
+    background_state: &mut BackgroundState,
+    battery_info: &Option<BatteryInfo>,
+    current_time_str: &str,
+    gcc_adapter_poll_rate: &Option<u32>,
+    scale_factor: f32,
+) {
+    render_background(background_cache, video_cache, config, background_state);
+
+    // Dim the background for easier legibility
+    draw_rectangle(
+        0.0,
+        0.0,
+        screen_width(),
+        screen_height(),
+        Color::new(0.0, 0.0, 0.0, 0.6),
+    );
+
+    render_ui_overlay(
+        logo_cache,
+        font_cache,
+        config,
+        battery_info,
+        current_time_str,
+        gcc_adapter_poll_rate,
+        scale_factor,
+    );
+
+    let font_size = (FONT_SIZE as f32 * scale_factor) as u16;
+    let large_font_size = (FONT_SIZE as f32 * scale_factor * 1.5) as u16;
+    let menu_padding = MENU_PADDING * scale_factor;
+    let menu_option_height = MENU_OPTION_HEIGHT * scale_factor;
+    let current_font = get_current_font(font_cache, config);
+
+    // Title
+    let title = "NETWORK SHARE";
+    let title_dims = measure_text(title, Some(current_font), large_font_size, 1.0);
+    let title_x = screen_width() / 2.0 - title_dims.width / 2.0;
+    let title_y = 50.0 * scale_factor;
+    text_with_config_color(font_cache, config, title, title_x, title_y, large_font_size);
+
+    // Connection status, as the share monitor last saw it
+    let share = &config.network_share;
+    let (status_text, status_color) = match status {
+        ShareStatus::Connected => (format!("Connected to {}", netshare::describe(share)), GREEN),
+        ShareStatus::Unreachable => (
+            format!("{} is not answering", netshare::describe(share)),
+            YELLOW,
+        ),
+        ShareStatus::NotMounted(Some(e)) => (format!("Mount failed: {}", e), RED),
+        ShareStatus::NotMounted(None) if share.enabled => ("Connecting...".to_string(), YELLOW),
+        _ => ("Not connected".to_string(), Color::new(0.7, 0.7, 0.7, 1.0)),
+    };
+    let status_dims = measure_text(&status_text, Some(current_font), font_size, 1.0);
+    let status_x = screen_width() / 2.0 - status_dims.width / 2.0;
+    text_with_color(
+        font_cache,
+        config,
+        &status_text,
+        status_x,
+        title_y + 25.0 * scale_factor,
+        font_size,
+        status_color,
+    );
+
+    // Menu options
+    let start_y = 100.0 * scale_factor;
+    let left_margin = 80.0 * scale_factor;
+    let right_margin = 80.0 * scale_factor;
+
+    for (i, &label) in NETWORK_SHARE_OPTIONS.iter().enumerate() {
+        let y_pos = start_y + (i as f32 * menu_option_height);
+        let is_selected = i == share_state.selection;
+
+        let value = get_option_value(i, share_state, config);
+        let value_dims = measure_text(&value, Some(current_font), font_size, 1.0);
+        let value_x = screen_width() - value_dims.width - right_margin;
+        let text_y = y_pos + menu_option_height / 2.0 + font_size as f32 * 0.3;
+
+        // Draw selection highlight
+        if is_selected && config.cursor_style == "BOX" {
+            let cursor_color = animation_state.get_cursor_color(config);
+            let cursor_scale = animation_state.get_cursor_scale();
+
+            let base_width = value_dims.width + (menu_padding * 2.0);
+            let base_height = value_dims.height + (menu_padding * 2.0);
+            let scaled_width = base_width * cursor_scale;
+            let scaled_height = base_height * cursor_scale;
+            let offset_x = (scaled_width - base_width) / 2.0;
+            let offset_y = (scaled_height - base_height) / 2.0;
+
+            let rect_x = value_x - menu_padding;
+            let rect_y = y_pos + (menu_option_height / 2.0) - (base_height / 2.0);
+
+            draw_rectangle_lines(
+                rect_x - offset_x,
+                rect_y - offset_y,
+                scaled_width,
+                scaled_height,
+                4.0 * scale_factor,
+                cursor_color,
+            );
+        }
+
+        text_with_config_color(font_cache, config, label, left_margin, text_y, font_size);
+
+        if is_selected && config.cursor_style == "TEXT" {
+            let highlight_color = animation_state.get_cursor_color(config);
+            text_with_color(
+                font_cache,
+                config,
+                &value,
+                value_x,
+                text_y,
+                font_size,
+                highlight_color,
+            );
+        } else {
+            text_with_config_color(font_cache, config, &value, value_x, text_y, font_size);
+        }
+
+        // Show editing indicator
+        if is_selected && share_state.editing.is_some() {
+            let blink = (get_time() * 3.0) as i32 % 2 == 0;
+            if blink {
+                text_with_config_color(
+                    font_cache,
+                    config,
+                    "_",
+                    value_x + value_dims.width,
+                    text_y,
+                    font_size,
+                );
+            }
+        }
+    }
+
+    let instructions = if share_state.editing.is_some() {
+        "Type to enter text, ENTER/B to confirm"
+    } else if share.protocol == "NFS" {
+        "NFS shares use the export path and need no login"
+    } else {
+        "Carts on the share show up in the library next to your drives"
+    };
+    let inst_dims = measure_text(instructions, Some(current_font), font_size, 1.0);
+    let inst_x = screen_width() / 2.0 - inst_dims.width / 2.0;
+    let inst_y = screen_height() - 20.0 * scale_factor;
+    text_with_color(
+        font_cache,
+        config,
+        instructions,
+        inst_x,
+        inst_y,
+        font_size,
+        Color::new(0.5, 0.5, 0.5, 1.0),
+    );
+}
+
+/// Get the display value for each option
+fn get_option_value(index: usize, share_state: &NetworkShareState, config: &Config) -> String {
+    let share = &config.network_share;
+    let editing = |field: Field| share_state.editing == Some(field);
+    let or_enter = |value: &str| {
+        if value.is_empty() {
+            "[ENTER]".to_string()
+        } else {
+            value.to_string()
+        }
+    };
+
+    match index {
+        0 => share.protocol.clone(),
+        1 if editing(Field::Host) => share_state.input.clone(),
+        1 => or_enter(&share.host),
+        2 if editing(Field::Share) => share_state.input.clone(),
+        2 => or_enter(&share.share),
+        3 if share.protocol == "NFS" => "N/A".to_string(),
+        3 if editing(Field::Username) => share_state.input.clone(),
+        3 => share
+            .username
+            .clone()
+            .unwrap_or_else(|| "GUEST".to_string()),
+        4 if share.protocol == "NFS" => "N/A".to_string(),
+        4 if editing(Field::Password) => "*".repeat(share_state.input.len()),
+        4 => if share.credentials.is_some() {
+            "********"
+        } else {
+            "[NONE]"
+        }
+        .to_string(),
+        5 => "CONNECT".to_string(),
+        6 => "CONFIRM".to_string(),
+        _ => "".to_string(),
+    }
+}
@@ -0,0 +1,296 @@
+use gilrs::{Button, GamepadId, Gilrs};
+use macroquad::prelude::*;
+use serde::Serialize;
+use std::collections::HashMap;
+
+use crate::{
+    audio::SoundEffects, config::Config, get_current_font, measure_text, text_with_config_color,
+    types::AnimationState, ui::text_with_color, InputState, FONT_SIZE,
+};
+
+/// Read by the overlay when the game starts, so its controller screen shows
+/// the same players
+pub const PLAYER_ASSIGNMENT_FILE: &str = "/tmp/kazeta-players.json";
+
+/// A player and the controller they pressed A on
+#[derive(Clone, Debug, Serialize)]
+pub struct JoinedPlayer {
+    pub player: u8,
+    /// `None` for the keyboard
+    #[serde(skip)]
+    pub gamepad: Option<GamepadId>,
+    pub name: String,
+    /// gilrs UUID, formatted the same way as the overlay's
+    pub uuid: String,
+}
+
+#[derive(Serialize)]
+struct PlayerAssignment<'a> {
+    cart_id: &'a str,
+    players: &'a [JoinedPlayer],
+}
+
+pub enum JoinAction {
+    None,
+    Cancel,
+    /// Fall back to picking a player count from a list
+    ChooseCount,
+    Start,
+}
+
+/// State for the "press A to join" screen shown before a multiplayer launch
+pub struct PlayerJoinState {
+    pub players: Vec<JoinedPlayer>,
+    max_players: u8,
+    game_name: String,
+    /// Last hot-plug event, shown until the next join
+    notice: Option<String>,
+    connected: usize,
+}
+
+impl PlayerJoinState {
+    pub fn new() -> Self {
+        Self {
+            players: Vec::new(),
+            max_players: 1,
+            game_name: String::new(),
+            notice: None,
+            connected: 0,
+        }
+    }
+
+    pub fn open(&mut self, game_name: &str, max_players: u8, gilrs: &Gilrs) {
+        self.players.clear();
+        self.max_players = max_players;
+        self.game_name = game_name.to_uppercase();
+        self.notice = None;
+        self.connected = gilrs.gamepads().count();
+    }
+
+    fn join(&mut self, gamepad: Option<GamepadId>, gilrs: &Gilrs) -> bool {
+        if self.players.len() >= self.max_players as usize {
+            return false;
+        }
+        let (name, uuid) = match gamepad.and_then(|id| gilrs.connected_gamepad(id)) {
+            Some(pad) => (pad.name().to_string(), format!("{:?}", pad.uuid())),
+            None => ("Keyboard".to_string(), String::new()),
+        };
+        self.players.push(JoinedPlayer {
+            player: self.players.len() as u8 + 1,
+            gamepad,
+            name,
+            uuid,
+        });
+        self.notice = None;
+        true
+    }
+
+    /// Remove a player; the ones after them move up a slot
+    fn leave(&mut self, index: usize) {
+        self.players.remove(index);
+        for (i, player) in self.players.iter_mut().enumerate() {
+            player.player = i as u8 + 1;
+        }
+    }
+
+    fn player_index(&self, gamepad: Option<GamepadId>) -> Option<usize> {
+        self.players.iter().position(|p| p.gamepad == gamepad)
+    }
+
+    /// Drop players whose controller was unplugged and note pads coming and going
+    fn check_hotplug(&mut self, gilrs: &Gilrs) {
+        let mut i = 0;
+        while i < self.players.len() {
+            let unplugged = self.players[i]
+                .gamepad
+                .is_some_and(|id| gilrs.connected_gamepad(id).is_none());
+            if unplugged {
+                self.notice = Some(format!(
+                    "P{} CONTROLLER DISCONNECTED",
+                    self.players[i].player
+                ));
+                self.leave(i);
+            } else {
+                i += 1;
+            }
+        }
+
+        let connected = gilrs.gamepads().count();
+        if connected > self.connected {
+            self.notice = Some("CONTROLLER CONNECTED - PRESS A TO JOIN".to_string());
+        }
+        self.connected = connected;
+    }
+
+    /// Write the assignment for the overlay. Failing to write isn't fatal;
+    /// the overlay keeps whatever assignment it had.
+    pub fn write_assignment(&self, cart_id: &str) {
+        let assignment = PlayerAssignment {
+            cart_id,
+            players: &self.players,
+        };
+        let result = serde_json::to_string_pretty(&assignment)
+            .map_err(|e| e.to_string())
+            .and_then(|json| {
+                std::fs::write(PLAYER_ASSIGNMENT_FILE, json).map_err(|e| e.to_string())
+            });
+        if let Err(e) = result {
+            eprintln!(
+                "[PlayerJoin] Failed to write {}: {}",
+                PLAYER_ASSIGNMENT_FILE, e
+            );
+        }
+    }
+}
+
+pub fn update(
+    state: &mut PlayerJoinState,
+    input_state: &InputState,
+    gilrs: &Gilrs,
+    sound_effects: &SoundEffects,
+    config: &Config,
+) -> JoinAction {
+    state.check_hotplug(gilrs);
+
+    for &(id, button) in &input_state.gamepad_presses {
+        let joined = state.player_index(Some(id));
+        match (button, joined) {
+            (Button::South, None) => {
+                if state.join(Some(id), gilrs) {
+                    sound_effects.play_select(config);
+                } else {
+                    sound_effects.play_reject(config);
+                }
+            }
+            // P1 presses A again, or anyone presses Start, to play
+            (Button::South, Some(0)) | (Button::Start, Some(_)) => {
+                sound_effects.play_select(config);
+                return JoinAction::Start;
+            }
+            (Button::East, Some(index)) => {
+                state.leave(index);
+                sound_effects.play_back(config);
+            }
+            (Button::East, None) if state.players.is_empty() => {
+                sound_effects.play_back(config);
+                return JoinAction::Cancel;
+            }
+            (Button::West, _) => {
+                sound_effects.play_select(config);
+                return JoinAction::ChooseCount;
+            }
+            _ => {}
+        }
+    }
+
+    // The keyboard can join as one player; its presses come without a pad
+    if input_state.gamepad_presses.is_empty() {
+        let keyboard = state.player_index(None);
+        if input_state.select {
+            match keyboard {
+                None if state.join(None, gilrs) => sound_effects.play_select(config),
+                None => sound_effects.play_reject(config),
+                Some(0) => {
+                    sound_effects.play_select(config);
+                    return JoinAction::Start;
+                }
+                Some(_) => {}
+            }
+        }
+        if input_state.back {
+            sound_effects.play_back(config);
+            match keyboard {
+                Some(index) => state.leave(index),
+                None if state.players.is_empty() => return JoinAction::Cancel,
+                None => {}
+            }
+        }
+        if input_state.secondary {
+            sound_effects.play_select(config);
+            return JoinAction::ChooseCount;
+        }
+    }
+
+    JoinAction::None
+}
+
+/// Draws the join box over whatever is behind it.
+pub fn draw(
+    state: &PlayerJoinState,
+    animation_state: &AnimationState,
+    font_cache: &HashMap<String, Font>,
+    config: &Config,
+    scale_factor: f32,
+) {
+    let current_font = get_current_font(font_cache, config);
+    let font_size = (FONT_SIZE as f32 * scale_factor) as u16;
+    let padding = 20.0 * scale_factor;
+    let row_height = 34.0 * scale_factor;
+    let dim = Color::new(0.6, 0.6, 0.6, 1.0);
+
+    let box_width = 480.0 * scale_factor;
+    let box_height = padding * 2.0 + row_height * (state.max_players as f32 + 3.0);
+    let box_x = screen_width() / 2.0 - box_width / 2.0;
+    let box_y = screen_height() / 2.0 - box_height / 2.0;
+
+    draw_rectangle(
+        box_x,
+        box_y,
+        box_width,
+        box_height,
+        Color::new(0.0, 0.0, 0.0, 0.9),
+    );
+    draw_rectangle_lines(
+        box_x,
+        box_y,
+        box_width,
+        box_height,
+        2.0 * scale_factor,
+        WHITE,
+    );
+
+    let centered = |text: &str, y: f32, color: Option<Color>| {
+        let dims = measure_text(text, Some(current_font), font_size, 1.0);
+        let x = screen_width() / 2.0 - dims.width / 2.0;
+        match color {
+            Some(color) => text_with_color(font_cache, config, text, x, y, font_size, color),
+            None => text_with_config_color(font_cache, config, text, x, y, font_size),
+        }
+    };
+
+    let mut y = box_y + padding + font_size as f32;
+    centered(&state.game_name, y, None);
+    y += row_height;
+    centered("PRESS A ON EACH CONTROLLER TO JOIN", y, Some(dim));
+    y += row_height;
+
+    let label_x = box_x + padding * 2.0;
+    let name_x = box_x + padding * 2.0 + 60.0 * scale_factor;
+    for slot in 0..state.max_players as usize {
+        let label = format!("P{}", slot + 1);
+        match state.players.get(slot) {
+            Some(player) => {
+                let color = animation_state.get_cursor_color(config);
+                text_with_color(font_cache, config, &label, label_x, y, font_size, color);
+                let name: String = player.name.to_uppercase().chars().take(28).collect();
+                text_with_config_color(font_cache, config, &name, name_x, y, font_size);
+            }
+            None => {
+                text_with_color(font_cache, config, &label, label_x, y, font_size, dim);
+                text_with_color(font_cache, config, "---", name_x, y, font_size, dim);
+            }
+        }
+        y += row_height;
+    }
+
+    if let Some(notice) = &state.notice {
+        centered(notice, y, Some(YELLOW));
+    }
+
+    let instructions = if state.players.is_empty() {
+        "A: JOIN  X: PLAYER COUNT  B: BACK"
+    } else {
+        "A: JOIN  START / P1 A: PLAY  B: LEAVE"
+    };
+    centered(instructions, box_y + box_height - padding / 2.0, Some(dim));
+}
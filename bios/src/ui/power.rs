@@ -0,0 +1,198 @@
+use macroquad::prelude::*;
+use rodio::{buffer::SamplesBuffer, Sink};
+use std::collections::HashMap;
+
+use crate::{
+    audio::SoundEffects,
+    config::Config,
+    get_current_font, measure_text,
+    power::{self, PowerAction},
+    render_background, render_ui_overlay, text_with_config_color,
+    types::{AnimationState, BackgroundState, BatteryInfo, Screen},
+    ui::{render_dialog_box, text_with_color},
+    utils, InputState, VideoPlayer, FONT_SIZE, MENU_OPTION_HEIGHT, MENU_PADDING,
+};
+
+pub struct PowerMenuState {
+    pub selection: usize,
+    /// Action waiting for a YES/NO answer
+    pub confirming: Option<PowerAction>,
+    /// 0 = YES, 1 = NO
+    pub confirm_selection: usize,
+}
+
+impl PowerMenuState {
+    pub fn new() -> Self {
+        Self {
+            selection: 0,
+            confirming: None,
+            confirm_selection: 1,
+        }
+    }
+}
+
+/// Handles input for the Power menu. Confirmed actions flush saves, stop the
+/// daemons and start the fade-out; the system goes down behind it.
+pub fn update(
+    state: &mut PowerMenuState,
+    current_screen: &mut Screen,
+    input_state: &InputState,
+    animation_state: &mut AnimationState,
+    sound_effects: &SoundEffects,
+    config: &Config,
+    current_bgm: &mut Option<Sink>,
+    music_cache: &HashMap<String, SamplesBuffer>,
+    fade_start_time: &mut Option<f64>,
+) {
+    let options = PowerAction::ALL;
+
+    if let Some(action) = state.confirming {
+        if input_state.left || input_state.right {
+            state.confirm_selection = 1 - state.confirm_selection;
+            sound_effects.play_cursor_move(config);
+        }
+        if input_state.back || (input_state.select && state.confirm_selection == 1) {
+            state.confirming = None;
+            sound_effects.play_back(config);
+        } else if input_state.select {
+            sound_effects.play_select(config);
+            println!("[Power] {} confirmed", action.label());
+            state.confirming = None;
+            power::prepare(action);
+            power::run_helper(action);
+            // The sentinel brings the BIOS back if the power helper fails
+            let (screen, fade) = utils::trigger_session_restart(
+                current_bgm,
+                music_cache,
+                action != PowerAction::RestartBios,
+            );
+            *current_screen = screen;
+            *fade_start_time = fade;
+        }
+        return;
+    }
+
+    if input_state.up {
+        state.selection = if state.selection == 0 {
+            options.len() - 1
+        } else {
+            state.selection - 1
+        };
+        animation_state.trigger_transition(&config.cursor_transition_speed);
+        sound_effects.play_cursor_move(config);
+    }
+    if input_state.down {
+        state.selection = (state.selection + 1) % options.len();
+        animation_state.trigger_transition(&config.cursor_transition_speed);
+        sound_effects.play_cursor_move(config);
+    }
+    if input_state.back {
+        *current_screen = Screen::MainMenu;
+        sound_effects.play_back(config);
+    }
+    if input_state.select {
+        state.confirming = Some(options[state.selection]);
+        state.confirm_selection = 1;
+        sound_effects.play_select(config);
+    }
+}
+
+/// Draws the Power menu, and the confirmation dialog over it when open
+pub fn draw(
+    state: &PowerMenuState,
+    animation_state: &AnimationState,
+    logo_cache: &HashMap<String, Texture2D>,
+    background_cache: &HashMap<String, Texture2D>,
+    video_cache: &mut HashMap<String, VideoPlayer>,
+    font_cache: &HashMap<String, Font>,
+    config: &Config,
+    background_state: &mut BackgroundState,
+    battery_info: &Option<BatteryInfo>,
+    current_time_str: &str,
+    gcc_adapter_poll_rate: &Option<u32>,
+    scale_factor: f32,
+) {
+    render_background(background_cache, video_cache, config, background_state);
+
+    // dim the background for easier legibility
+    draw_rectangle(
+        0.0,
+        0.0,
+        screen_width(),
+        screen_height(),
+        Color::new(0.0, 0.0, 0.0, 0.5),
+    );
+
+    render_ui_overlay(
+        logo_cache,
+        font_cache,
+        config,
+        battery_info,
+        current_time_str,
+        gcc_adapter_poll_rate,
+        scale_factor,
+    );
+
+    let font_size = (FONT_SIZE as f32 * scale_factor) as u16;
+    let menu_padding = MENU_PADDING * scale_factor;
+    let menu_option_height = MENU_OPTION_HEIGHT * scale_factor;
+    let current_font = get_current_font(font_cache, config);
+
+    let start_x = screen_width() / 2.0;
+    let start_y = screen_height() * 0.4;
+
+    for (i, action) in PowerAction::ALL.iter().enumerate() {
+        let option = action.label();
+        let y_pos = start_y + (i as f32 * menu_option_height);
+        let text_dims = measure_text(option, Some(current_font), font_size, 1.0);
+        let x_pos = start_x - (text_dims.width / 2.0);
+        let is_selected = i == state.selection;
+
+        if is_selected && config.cursor_style == "BOX" {
+            let cursor_color = animation_state.get_cursor_color(config);
+            let cursor_scale = animation_state.get_cursor_scale();
+            let base_width = text_dims.width + (menu_padding * 2.0);
+            let base_height = text_dims.height + (menu_padding * 2.0);
+            let scaled_width = base_width * cursor_scale;
+            let scaled_height = base_height * cursor_scale;
+            let offset_x = (scaled_width - base_width) / 2.0;
+            let offset_y = (scaled_height - base_height) / 2.0;
+
+            draw_rectangle_lines(
+                x_pos - menu_padding - offset_x,
+                y_pos - text_dims.height - menu_padding - offset_y,
+                scaled_width,
+                scaled_height,
+                4.0 * scale_factor,
+                cursor_color,
+            );
+        }
+
+        if is_selected && config.cursor_style == "TEXT" {
+            let highlight_color = animation_state.get_cursor_color(config);
+            text_with_color(
+                font_cache,
+                config,
+                option,
+                x_pos,
+                y_pos,
+                font_size,
+                highlight_color,
+            );
+        } else {
+            text_with_config_color(font_cache, config, option, x_pos, y_pos, font_size);
+        }
+    }
+
+    if let Some(action) = state.confirming {
+        render_dialog_box(
+            &format!("{}\nSaves will be flushed first.", action.confirm_text()),
+            Some(("YES", "NO")),
+            state.confirm_selection,
+            font_cache,
+            config,
+            scale_factor,
+            animation_state,
+        );
+    }
+}
@@ -0,0 +1,281 @@
+use chrono::{DateTime, Local};
+use macroquad::prelude::*;
+use std::collections::HashMap;
+
+use crate::{
+    audio::SoundEffects,
+    config::Config,
+    get_current_font, measure_text, remote, render_background, render_ui_overlay,
+    text_with_config_color,
+    types::{AnimationState, BackgroundState, BatteryInfo, Screen},
+    ui::text_with_color,
+    InputState, VideoPlayer, FONT_SIZE, MENU_OPTION_HEIGHT, MENU_PADDING,
+};
+
+/// Number of devices listed at once
+const VISIBLE_ROWS: usize = 8;
+
+/// State for the paired devices screen
+pub struct RemoteDevicesState {
+    pub selection: usize,
+    scroll_offset: usize,
+    /// Set after the first A on a device; a second A revokes it
+    confirming: bool,
+}
+
+impl RemoteDevicesState {
+    pub fn new() -> Self {
+        Self {
+            selection: 0,
+            scroll_offset: 0,
+            confirming: false,
+        }
+    }
+
+    pub fn open(&mut self) {
+        self.selection = 0;
+        self.scroll_offset = 0;
+        self.confirming = false;
+    }
+}
+
+pub fn update(
+    current_screen: &mut Screen,
+    state: &mut RemoteDevicesState,
+    input_state: &InputState,
+    animation_state: &mut AnimationState,
+    sound_effects: &SoundEffects,
+    config: &mut Config,
+) {
+    if input_state.back {
+        if state.confirming {
+            state.confirming = false;
+        } else {
+            *current_screen = Screen::KioskSettings;
+        }
+        sound_effects.play_back(config);
+        return;
+    }
+
+    let count = config.remote_devices.len();
+    if count == 0 {
+        return;
+    }
+
+    if input_state.up {
+        state.selection = if state.selection == 0 {
+            count - 1
+        } else {
+            state.selection - 1
+        };
+        state.confirming = false;
+        animation_state.trigger_transition(&config.cursor_transition_speed);
+        sound_effects.play_cursor_move(config);
+    }
+    if input_state.down {
+        state.selection = (state.selection + 1) % count;
+        state.confirming = false;
+        animation_state.trigger_transition(&config.cursor_transition_speed);
+        sound_effects.play_cursor_move(config);
+    }
+
+    if state.selection < state.scroll_offset {
+        state.scroll_offset = state.selection;
+    } else if state.selection >= state.scroll_offset + VISIBLE_ROWS {
+        state.scroll_offset = state.selection + 1 - VISIBLE_ROWS;
+    }
+
+    if input_state.select {
+        if state.confirming {
+            remote::revoke_device(config, state.selection);
+            state.confirming = false;
+            state.selection = state
+                .selection
+                .min(config.remote_devices.len().saturating_sub(1));
+            state.scroll_offset = state.scroll_offset.min(state.selection);
+        } else {
+            state.confirming = true;
+        }
+        sound_effects.play_select(config);
+    }
+}
+
+/// Draws the paired devices UI.
+pub fn draw(
+    state: &RemoteDevicesState,
+    animation_state: &AnimationState,
+    logo_cache: &HashMap<String, Texture2D>,
+    background_cache: &HashMap<String, Texture2D>,
+    video_cache: &mut HashMap<String, VideoPlayer>,
+    font_cache: &HashMap<String, Font>,
+    config: &Config,
+    background_state: &mut BackgroundState,
+    battery_info: &Option<BatteryInfo>,
+    current_time_str: &str,
+    gcc_adapter_poll_rate: &Option<u32>,
+    scale_factor: f32,
+) {
+    render_background(background_cache, video_cache, config, background_state);
+
+    // Dim the background for easier legibility
+    draw_rectangle(
+        0.0,
+        0.0,
+        screen_width(),
+        screen_height(),
+        Color::new(0.0, 0.0, 0.0, 0.6),
+    );
+
+    render_ui_overlay(
+        logo_cache,
+        font_cache,
+        config,
+        battery_info,
+        current_time_str,
+        gcc_adapter_poll_rate,
+        scale_factor,
+    );
+
+    let font_size = (FONT_SIZE as f32 * scale_factor) as u16;
+    let large_font_size = (FONT_SIZE as f32 * scale_factor * 1.5) as u16;
+    let menu_padding = MENU_PADDING * scale_factor;
+    let menu_option_height = MENU_OPTION_HEIGHT * scale_factor;
+    let current_font = get_current_font(font_cache, config);
+
+    // Title
+    let title = "PAIRED DEVICES";
+    let title_dims = measure_text(title, Some(current_font), large_font_size, 1.0);
+    let title_y = 50.0 * scale_factor;
+    text_with_config_color(
+        font_cache,
+        config,
+        title,
+        screen_width() / 2.0 - title_dims.width / 2.0,
+        title_y,
+        large_font_size,
+    );
+
+    let status = if config.remote_enabled {
+        "REMOTE CONTROL ON"
+    } else {
+        "REMOTE CONTROL OFF: DEVICES CAN'T CONNECT"
+    };
+    let status_dims = measure_text(status, Some(current_font), font_size, 1.0);
+    text_with_config_color(
+        font_cache,
+        config,
+        status,
+        screen_width() / 2.0 - status_dims.width / 2.0,
+        title_y + 25.0 * scale_factor,
+        font_size,
+    );
+
+    let start_y = 100.0 * scale_factor;
+    let left_margin = 80.0 * scale_factor;
+    let right_margin = 80.0 * scale_factor;
+
+    if config.remote_devices.is_empty() {
+        let message = "NO PAIRED DEVICES";
+        let dims = measure_text(message, Some(current_font), font_size, 1.0);
+        text_with_config_color(
+            font_cache,
+            config,
+            message,
+            screen_width() / 2.0 - dims.width / 2.0,
+            start_y,
+            font_size,
+        );
+        return;
+    }
+
+    for (row, (i, device)) in config
+        .remote_devices
+        .iter()
+        .enumerate()
+        .skip(state.scroll_offset)
+        .take(VISIBLE_ROWS)
+        .enumerate()
+    {
+        let y_pos = start_y + (row as f32 * menu_option_height);
+        let is_selected = i == state.selection;
+
+        let value = if is_selected && state.confirming {
+            "REVOKE?".to_string()
+        } else {
+            DateTime::from_timestamp(device.paired_at, 0)
+                .map(|time| {
+                    time.with_timezone(&Local)
+                        .format("%Y-%m-%d %H:%M")
+                        .to_string()
+                })
+                .unwrap_or_default()
+        };
+        let value_dims = measure_text(&value, Some(current_font), font_size, 1.0);
+        let value_x = screen_width() - value_dims.width - right_margin;
+        let text_y = y_pos + menu_option_height / 2.0 + font_size as f32 * 0.3;
+
+        if is_selected && config.cursor_style == "BOX" {
+            let cursor_color = animation_state.get_cursor_color(config);
+            let cursor_scale = animation_state.get_cursor_scale();
+
+            let base_width = value_dims.width + (menu_padding * 2.0);
+            let base_height = value_dims.height + (menu_padding * 2.0);
+            let scaled_width = base_width * cursor_scale;
+            let scaled_height = base_height * cursor_scale;
+            let offset_x = (scaled_width - base_width) / 2.0;
+            let offset_y = (scaled_height - base_height) / 2.0;
+
+            let rect_x = value_x - menu_padding;
+            let rect_y = y_pos + (menu_option_height / 2.0) - (base_height / 2.0);
+
+            draw_rectangle_lines(
+                rect_x - offset_x,
+                rect_y - offset_y,
+                scaled_width,
+                scaled_height,
+                4.0 * scale_factor,
+                cursor_color,
+            );
+        }
+
+        text_with_config_color(
+            font_cache,
+            config,
+            &device.name.to_uppercase(),
+            left_margin,
+            text_y,
+            font_size,
+        );
+
+        if is_selected && config.cursor_style == "TEXT" {
+            let highlight_color = animation_state.get_cursor_color(config);
+            text_with_color(
+                font_cache,
+                config,
+                &value,
+                value_x,
+                text_y,
+                font_size,
+                highlight_color,
+            );
+        } else {
+            text_with_config_color(font_cache, config, &value, value_x, text_y, font_size);
+        }
+    }
+
+    let instructions = if state.confirming {
+        "A: Revoke  B: Cancel"
+    } else {
+        "A: Revoke  B: Back"
+    };
+    let inst_dims = measure_text(instructions, Some(current_font), font_size, 1.0);
+    text_with_color(
+        font_cache,
+        config,
+        instructions,
+        screen_width() / 2.0 - inst_dims.width / 2.0,
+        screen_height() - 20.0 * scale_factor,
+        font_size,
+        Color::new(0.7, 0.7, 0.7, 1.0),
+    );
+}
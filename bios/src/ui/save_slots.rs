@@ -0,0 +1,459 @@
+use chrono::{DateTime, Local};
+use macroquad::prelude::*;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::{
+    audio::SoundEffects,
+    config::Config,
+    get_current_font, measure_text,
+    save::{self, CartInfo, SaveSlotInfo, PLAYER_SLOTS},
+    text_with_config_color,
+    types::AnimationState,
+    ui::{
+        dialog::{Dialog, DialogOption},
+        render_mgba_launch_dialog, text_with_color,
+    },
+    InputState, FONT_SIZE,
+};
+
+pub enum SlotsAction {
+    None,
+    Back,
+    /// Launch with these slots, one per player
+    Launch(Vec<String>),
+}
+
+/// Which popup is open over the slot list
+enum Popup {
+    Actions { slot: String },
+    Target { slot: String, rename: bool },
+    ConfirmDelete { slot: String },
+}
+
+/// State for the save slot manager shown before launching a multiplayer cart
+pub struct SaveSlotsState {
+    cart: Option<(CartInfo, PathBuf)>,
+    rom_name: String,
+    game_name: String,
+    slots: Vec<SaveSlotInfo>,
+    /// The slot each player plays on, by player
+    assignments: Vec<String>,
+    /// Player rows first, then slot rows, then START
+    selection: usize,
+    popup: Option<(Popup, Dialog)>,
+    notice: Option<String>,
+}
+
+impl SaveSlotsState {
+    pub fn new() -> Self {
+        Self {
+            cart: None,
+            rom_name: String::new(),
+            game_name: String::new(),
+            slots: Vec::new(),
+            assignments: Vec::new(),
+            selection: 0,
+            popup: None,
+            notice: None,
+        }
+    }
+
+    /// Open for a cart, giving player N slot "pN" to start with
+    pub fn open(&mut self, cart_info: &CartInfo, kzi_path: &Path, player_count: u8) {
+        self.rom_name = save::get_rom_name_from_exec(&cart_info.exec);
+        self.game_name = cart_info
+            .name
+            .as_deref()
+            .unwrap_or(&cart_info.id)
+            .to_uppercase();
+        self.cart = Some((cart_info.clone(), kzi_path.to_path_buf()));
+        self.assignments = (1..=player_count.max(1))
+            .map(|p| format!("p{}", p))
+            .collect();
+        self.selection = 0;
+        self.popup = None;
+        self.notice = None;
+        self.refresh();
+    }
+
+    fn refresh(&mut self) {
+        if let Some((cart_info, _)) = &self.cart {
+            self.slots = save::list_save_slots(&cart_info.id, &self.rom_name);
+        }
+    }
+
+    fn row_count(&self) -> usize {
+        self.assignments.len() + self.slots.len() + 1
+    }
+
+    fn slot(&self, id: &str) -> Option<&SaveSlotInfo> {
+        self.slots.iter().find(|s| s.id == id)
+    }
+
+    /// Whether the cart ships a save for this slot that hasn't been imported yet
+    fn can_import(&self, slot: &str) -> bool {
+        let Some((cart_info, _)) = &self.cart else {
+            return false;
+        };
+        let player = PLAYER_SLOTS.iter().position(|s| *s == slot);
+        let embedded = player
+            .and_then(|p| cart_info.player_saves[p].as_ref())
+            .is_some();
+        embedded && self.slot(slot).is_some_and(|s| s.size.is_none())
+    }
+
+    fn actions_popup(&self, slot: &str) -> Dialog {
+        let saved = self.slot(slot).is_some_and(|s| s.size.is_some());
+        let has_free = PLAYER_SLOTS
+            .iter()
+            .any(|p| self.slot(p).is_some_and(|s| s.size.is_none()));
+        let mut options = Vec::new();
+        if self.can_import(slot) {
+            options.push(option("IMPORT PROVIDED SAVE", "IMPORT", false));
+        }
+        options.push(option("DUPLICATE", "DUPLICATE", !saved || !has_free));
+        options.push(option("RENAME", "RENAME", !saved || !has_free));
+        options.push(option("DELETE", "DELETE", !saved));
+        options.push(option("CANCEL", "CANCEL", false));
+        popup_dialog(format!("{} SAVE", slot.to_uppercase()), options)
+    }
+
+    fn target_popup(&self, slot: &str, rename: bool) -> Dialog {
+        let mut options: Vec<DialogOption> = PLAYER_SLOTS
+            .iter()
+            .filter(|p| self.slot(p).is_some_and(|s| s.size.is_none()))
+            .map(|p| option(&format!("{} (EMPTY)", p.to_uppercase()), p, false))
+            .collect();
+        options.push(option("CANCEL", "CANCEL", false));
+        let verb = if rename { "RENAME" } else { "COPY" };
+        popup_dialog(format!("{} {} TO", verb, slot.to_uppercase()), options)
+    }
+
+    /// Run a popup choice; returns the popup to show next, if any
+    fn choose(&mut self, popup: Popup, value: &str) -> Option<(Popup, Dialog)> {
+        if value == "CANCEL" || value == "NO" {
+            return None;
+        }
+        let (cart_info, kzi_path) = self.cart.clone()?;
+        let result = match popup {
+            Popup::Actions { slot } => match value {
+                "DUPLICATE" | "RENAME" => {
+                    let rename = value == "RENAME";
+                    let dialog = self.target_popup(&slot, rename);
+                    return Some((Popup::Target { slot, rename }, dialog));
+                }
+                "DELETE" => {
+                    let dialog = popup_dialog(
+                        format!("DELETE {} SAVE?", slot.to_uppercase()),
+                        vec![option("NO", "NO", false), option("YES", "YES", false)],
+                    );
+                    return Some((Popup::ConfirmDelete { slot }, dialog));
+                }
+                _ => {
+                    let player =
+                        PLAYER_SLOTS.iter().position(|s| *s == slot).unwrap_or(0) as u8 + 1;
+                    save::import_embedded_save(&cart_info, &kzi_path, player)
+                        .map(|_| format!("IMPORTED SAVE INTO {}", slot.to_uppercase()))
+                }
+            },
+            Popup::Target {
+                slot,
+                rename: false,
+            } => save::duplicate_save_slot(&cart_info.id, &self.rom_name, &slot, value)
+                .map(|_| format!("COPIED {} TO {}", slot.to_uppercase(), value.to_uppercase())),
+            Popup::Target { slot, rename: true } => {
+                save::rename_save_slot(&cart_info.id, &self.rom_name, &slot, value).map(|_| {
+                    // Players on the old slot follow the save
+                    for assigned in self.assignments.iter_mut().filter(|a| **a == slot) {
+                        *assigned = value.to_string();
+                    }
+                    format!(
+                        "RENAMED {} TO {}",
+                        slot.to_uppercase(),
+                        value.to_uppercase()
+                    )
+                })
+            }
+            Popup::ConfirmDelete { slot } => {
+                save::delete_save_slot(&cart_info.id, &self.rom_name, &slot)
+                    .map(|_| format!("DELETED {} SAVE", slot.to_uppercase()))
+            }
+        };
+
+        self.notice = Some(match result {
+            Ok(message) => message,
+            Err(e) => {
+                eprintln!("[SaveSlots] {}", e);
+                format!("FAILED: {}", e).to_uppercase()
+            }
+        });
+        self.refresh();
+        None
+    }
+
+    /// Cycle a player's slot through every listed slot
+    fn cycle(&mut self, player: usize, forward: bool) {
+        let ids: Vec<&str> = self.slots.iter().map(|s| s.id.as_str()).collect();
+        let current = ids
+            .iter()
+            .position(|id| *id == self.assignments[player])
+            .unwrap_or(0);
+        let next = if forward {
+            (current + 1) % ids.len()
+        } else {
+            (current + ids.len() - 1) % ids.len()
+        };
+        self.assignments[player] = ids[next].to_string();
+    }
+
+    /// The first two players sharing a slot, if any
+    fn shared_slot(&self) -> Option<(usize, usize)> {
+        (0..self.assignments.len()).find_map(|a| {
+            (a + 1..self.assignments.len())
+                .find(|b| self.assignments[a] == self.assignments[*b])
+                .map(|b| (a, b))
+        })
+    }
+}
+
+fn option(text: &str, value: &str, disabled: bool) -> DialogOption {
+    DialogOption {
+        text: text.to_string(),
+        value: value.to_string(),
+        disabled,
+    }
+}
+
+fn popup_dialog(desc: String, options: Vec<DialogOption>) -> Dialog {
+    let selection = options.iter().position(|o| !o.disabled).unwrap_or(0);
+    Dialog {
+        id: "save_slot_manager".to_string(),
+        desc: Some(desc),
+        options,
+        selection,
+        comparison: None,
+    }
+}
+
+pub fn update(
+    state: &mut SaveSlotsState,
+    input_state: &InputState,
+    sound_effects: &SoundEffects,
+    config: &Config,
+) -> SlotsAction {
+    if let Some((popup, mut dialog)) = state.popup.take() {
+        if input_state.back {
+            sound_effects.play_back(config);
+            return SlotsAction::None;
+        }
+        if input_state.up || input_state.down {
+            let count = dialog.options.len();
+            let mut next = dialog.selection;
+            for _ in 0..count {
+                next = if input_state.up {
+                    (next + count - 1) % count
+                } else {
+                    (next + 1) % count
+                };
+                if !dialog.options[next].disabled {
+                    break;
+                }
+            }
+            dialog.selection = next;
+            sound_effects.play_cursor_move(config);
+        }
+        if input_state.select {
+            let value = dialog.options[dialog.selection].value.clone();
+            sound_effects.play_select(config);
+            state.popup = state.choose(popup, &value);
+        } else {
+            state.popup = Some((popup, dialog));
+        }
+        return SlotsAction::None;
+    }
+
+    let rows = state.row_count();
+    let players = state.assignments.len();
+    if input_state.up {
+        state.selection = if state.selection == 0 {
+            rows - 1
+        } else {
+            state.selection - 1
+        };
+        sound_effects.play_cursor_move(config);
+    }
+    if input_state.down {
+        state.selection = (state.selection + 1) % rows;
+        sound_effects.play_cursor_move(config);
+    }
+    if (input_state.left || input_state.right) && state.selection < players {
+        state.cycle(state.selection, input_state.right);
+        state.notice = None;
+        sound_effects.play_cursor_move(config);
+    }
+    if input_state.back {
+        sound_effects.play_back(config);
+        return SlotsAction::Back;
+    }
+
+    if input_state.select {
+        if state.selection < players {
+            state.cycle(state.selection, true);
+            sound_effects.play_cursor_move(config);
+        } else if state.selection < players + state.slots.len() {
+            let slot = state.slots[state.selection - players].id.clone();
+            let dialog = state.actions_popup(&slot);
+            state.popup = Some((Popup::Actions { slot }, dialog));
+            sound_effects.play_select(config);
+        } else if let Some((a, b)) = state.shared_slot() {
+            state.notice = Some(format!("P{} AND P{} CAN'T SHARE A SAVE", a + 1, b + 1));
+            sound_effects.play_reject(config);
+        } else {
+            sound_effects.play_select(config);
+            return SlotsAction::Launch(state.assignments.clone());
+        }
+    }
+
+    SlotsAction::None
+}
+
+/// "12 KB - 2024-05-01 18:30", or "EMPTY"
+fn slot_details(slot: &SaveSlotInfo) -> String {
+    match slot.size {
+        None => "EMPTY".to_string(),
+        Some(size) => {
+            let kb = (size as f32 / 1024.0).ceil() as u64;
+            match slot.modified {
+                Some(time) => format!(
+                    "{} KB - {}",
+                    kb,
+                    DateTime::<Local>::from(time).format("%Y-%m-%d %H:%M")
+                ),
+                None => format!("{} KB", kb),
+            }
+        }
+    }
+}
+
+/// Draws the slot manager box over whatever is behind it.
+pub fn draw(
+    state: &SaveSlotsState,
+    animation_state: &AnimationState,
+    font_cache: &HashMap<String, Font>,
+    config: &Config,
+    scale_factor: f32,
+) {
+    let current_font = get_current_font(font_cache, config);
+    let font_size = (FONT_SIZE as f32 * scale_factor) as u16;
+    let padding = 20.0 * scale_factor;
+    let row_height = 30.0 * scale_factor;
+    let dim = Color::new(0.6, 0.6, 0.6, 1.0);
+    let players = state.assignments.len();
+
+    let box_width = 560.0 * scale_factor;
+    let box_height = padding * 2.0 + row_height * (state.row_count() as f32 + 4.0);
+    let box_x = screen_width() / 2.0 - box_width / 2.0;
+    let box_y = screen_height() / 2.0 - box_height / 2.0;
+
+    draw_rectangle(
+        box_x,
+        box_y,
+        box_width,
+        box_height,
+        Color::new(0.0, 0.0, 0.0, 0.9),
+    );
+    draw_rectangle_lines(
+        box_x,
+        box_y,
+        box_width,
+        box_height,
+        2.0 * scale_factor,
+        WHITE,
+    );
+
+    let centered = |text: &str, y: f32, color: Option<Color>| {
+        let dims = measure_text(text, Some(current_font), font_size, 1.0);
+        let x = screen_width() / 2.0 - dims.width / 2.0;
+        match color {
+            Some(color) => text_with_color(font_cache, config, text, x, y, font_size, color),
+            None => text_with_config_color(font_cache, config, text, x, y, font_size),
+        }
+    };
+    let label_x = box_x + padding * 2.0;
+    let value_x = box_x + padding * 2.0 + 140.0 * scale_factor;
+    let cursor = animation_state.get_cursor_color(config);
+    let row = |label: &str, value: &str, y: f32, selected: bool, value_color: Option<Color>| {
+        if selected {
+            text_with_color(font_cache, config, label, label_x, y, font_size, cursor);
+        } else {
+            text_with_config_color(font_cache, config, label, label_x, y, font_size);
+        }
+        match value_color {
+            Some(color) => text_with_color(font_cache, config, value, value_x, y, font_size, color),
+            None => text_with_config_color(font_cache, config, value, value_x, y, font_size),
+        }
+    };
+
+    let mut y = box_y + padding + font_size as f32;
+    centered(&state.game_name, y, None);
+    y += row_height;
+    centered("SAVE SLOTS", y, Some(dim));
+    y += row_height;
+
+    for (player, slot) in state.assignments.iter().enumerate() {
+        let is_new = state.slot(slot).map_or(true, |s| s.size.is_none());
+        let value = format!(
+            "< {}{} >",
+            slot.to_uppercase(),
+            if is_new { " (NEW)" } else { "" }
+        );
+        row(
+            &format!("PLAYER {}", player + 1),
+            &value,
+            y,
+            state.selection == player,
+            None,
+        );
+        y += row_height;
+    }
+    y += row_height / 2.0;
+
+    for (i, slot) in state.slots.iter().enumerate() {
+        let mut details = slot_details(slot);
+        if state.can_import(&slot.id) {
+            details.push_str(" (PROVIDED SAVE)");
+        }
+        let color = slot.size.is_none().then_some(dim);
+        row(
+            &slot.id.to_uppercase(),
+            &details,
+            y,
+            state.selection == players + i,
+            color,
+        );
+        y += row_height;
+    }
+    y += row_height / 2.0;
+
+    let start_selected = state.selection == state.row_count() - 1;
+    centered("START GAME", y, start_selected.then_some(cursor));
+    y += row_height;
+
+    if let Some(notice) = &state.notice {
+        centered(notice, y, Some(YELLOW));
+    }
+
+    let instructions = if state.selection < players {
+        "LEFT/RIGHT: CHANGE SLOT  B: BACK"
+    } else if start_selected {
+        "A: PLAY  B: BACK"
+    } else {
+        "A: MANAGE SLOT  B: BACK"
+    };
+    centered(instructions, box_y + box_height - padding / 2.0, Some(dim));
+
+    if let Some((_, dialog)) = &state.popup {
+        render_mgba_launch_dialog(dialog, font_cache, config, scale_factor, animation_state);
+    }
+}
@@ -1,19 +1,19 @@
 use crate::{
-    AnimationState, AudioSink, BackgroundState, BatteryInfo, InputState, Screen,
-    render_background, render_ui_overlay, get_current_font, measure_text,
-    text_with_config_color, DEV_MODE, theme, text_with_color, VideoPlayer,
-    audio::{SoundEffects, play_new_bgm},
+    audio::{find_sound_packs, play_new_bgm, SoundEffects},
     config::Config,
-    system::{adjust_system_volume, get_system_volume, set_brightness, get_current_brightness},
+    get_current_font,
+    idle::{cycle_timeout, format_timeout},
+    kiosk::{cycle_attract_music, cycle_slide_duration, ATTRACT_MUSIC_MENU},
+    measure_text, remote, render_background, render_ui_overlay, save,
+    system::{adjust_system_volume, get_current_brightness, get_system_volume, set_brightness},
+    text_with_color, text_with_config_color, theme,
     utils::{apply_resolution, trim_extension},
+    AnimationState, AudioSink, BackgroundState, BatteryInfo, InputState, Screen, VideoPlayer,
+    DEV_MODE,
 };
 use macroquad::prelude::*;
 use rodio::{buffer::SamplesBuffer, Sink};
-use std::{
-    collections::HashMap,
-    process::Command,
-    thread,
-};
+use std::{collections::HashMap, process::Command, thread};
 
 const FONT_SIZE: u16 = 10;
 const MENU_PADDING: f32 = 6.0;
@@ -25,13 +25,21 @@ pub const GENERAL_SETTINGS: &[&str] = &[
     "RESET SETTINGS",
     "RESOLUTION",
     "ASPECT RATIO",
-    "SHOW SPLASH SCREEN",
+    "SPLASH SCREENS",
     "TIME ZONE",
     "BRIGHTNESS",
     "WI-FI",
     "BLUETOOTH",
     "AUTOBOOT",
+    "SCREEN DIM",
+    "AUTO SUSPEND",
+    "COMPRESS SAVES",
     "RETROACHIEVEMENTS",
+    "SYSTEM UPDATE",
+    "SETUP WIZARD",
+    "GAMEPAD TESTER",
+    "DISPLAY",
+    "NETWORK SHARE",
     "AUDIO SETTINGS",
 ];
 
@@ -40,12 +48,20 @@ pub const GENERAL_SETTINGS: &[&str] = &[
     "RESET SETTINGS",
     "RESOLUTION",
     "ASPECT RATIO",
-    "SHOW SPLASH SCREEN",
+    "SPLASH SCREENS",
     "TIME ZONE",
     "BRIGHTNESS",
     "WI-FI",
     "AUTOBOOT",
+    "SCREEN DIM",
+    "AUTO SUSPEND",
+    "COMPRESS SAVES",
     "RETROACHIEVEMENTS",
+    "SYSTEM UPDATE",
+    "SETUP WIZARD",
+    "GAMEPAD TESTER",
+    "DISPLAY",
+    "NETWORK SHARE",
     "AUDIO SETTINGS",
 ];
 
@@ -54,6 +70,7 @@ pub const AUDIO_SETTINGS: &[&str] = &[
     "BGM VOLUME",
     "SFX VOLUME",
     "AUDIO OUTPUT",
+    "SOUND PACK",
     "VIDEO SETTINGS",
     "GUI CUSTOMIZATION",
 ];
@@ -74,31 +91,37 @@ pub const GUI_CUSTOMIZATION_SETTINGS: &[&str] = &[
 
 pub const CUSTOM_ASSET_SETTINGS: &[&str] = &[
     "BACKGROUND MUSIC",
-    "SOUND PACK",
     "LOGO",
     "BACKGROUND",
     "FONT TYPE",
     "GUI CUSTOMIZATION SETTINGS",
+    "KIOSK SETTINGS",
+];
+
+pub const KIOSK_SETTINGS: &[&str] = &[
+    "ATTRACT MODE",
+    "SLIDE DURATION",
+    "ATTRACT MUSIC",
+    "GAME WHITELIST",
+    "WHITELISTED GAMES",
+    "REMOTE CONTROL",
+    "CONFIRM REMOTE LAUNCH",
+    "CONFIRM REMOTE POWER",
+    "PAIR DEVICE",
+    "PAIRED DEVICES",
+    "CUSTOM ASSETS SETTINGS",
 ];
 
 pub const COLORS: &[&str] = &[
-    "WHITE",
-    "BLACK",
-    "PINK",
-    "RED",
-    "ORANGE",
-    "YELLOW",
-    "GREEN",
-    "BLUE",
-    "PURPLE",
+    "WHITE", "BLACK", "PINK", "RED", "ORANGE", "YELLOW", "GREEN", "BLUE", "PURPLE",
 ];
 
 pub const RESOLUTIONS: &[&str] = &[
     "320x240",
     "640x360",
-    "640x480",   // [!] 4:3 (Classic VGA)
-    "800x600",   // [!] 4:3
-    "1024x768",  // [!] 4:3
+    "640x480",  // [!] 4:3 (Classic VGA)
+    "800x600",  // [!] 4:3
+    "1024x768", // [!] 4:3
     "1280x720",
     "1280x800",  // Steam Deck (16:10)
     "1280x960",  // [!] 4:3 (2x integer scale of 480p)
@@ -109,28 +132,29 @@ pub const RESOLUTIONS: &[&str] = &[
     "3840x2160",
 ];
 
-pub const ASPECT_RATIOS: &[&str] = &[
-    "4:3",
-    "16:9",
-    "16:10",
-];
+pub const ASPECT_RATIOS: &[&str] = &["4:3", "16:9", "16:10"];
 
 pub const CURSOR_STYLES: &[&str] = &["BOX", "TEXT"];
 
 pub const SPEEDS: &[&str] = &["OFF", "SLOW", "NORMAL", "FAST"];
 
 pub const TIMEZONES: [&str; 25] = [
-    "UTC-12", "UTC-11", "UTC-10", "UTC-9", "UTC-8", "UTC-7", "UTC-6",
-    "UTC-5", "UTC-4", "UTC-3", "UTC-2", "UTC-1", "UTC", "UTC+1",
-    "UTC+2", "UTC+3", "UTC+4", "UTC+5", "UTC+6", "UTC+7", "UTC+8",
-    "UTC+9", "UTC+10", "UTC+11", "UTC+12",
+    "UTC-12", "UTC-11", "UTC-10", "UTC-9", "UTC-8", "UTC-7", "UTC-6", "UTC-5", "UTC-4", "UTC-3",
+    "UTC-2", "UTC-1", "UTC", "UTC+1", "UTC+2", "UTC+3", "UTC+4", "UTC+5", "UTC+6", "UTC+7",
+    "UTC+8", "UTC+9", "UTC+10", "UTC+11", "UTC+12",
 ];
 
 // Helper to check if a resolution string belongs to an aspect ratio
 fn matches_aspect_ratio(res: &str, ratio: &str) -> bool {
     match ratio {
-        "4:3" => matches!(res, "320x240" | "640x480" | "800x600" | "1024x768" | "1280x960" | "1440x1080"),
-        "16:9" => matches!(res, "640x360" | "1280x720" | "1920x1080" | "2560x1440" | "3840x2160"),
+        "4:3" => matches!(
+            res,
+            "320x240" | "640x480" | "800x600" | "1024x768" | "1280x960" | "1440x1080"
+        ),
+        "16:9" => matches!(
+            res,
+            "640x360" | "1280x720" | "1920x1080" | "2560x1440" | "3840x2160"
+        ),
         "16:10" => matches!(res, "1280x800" | "1920x1200"),
         _ => true, // Unknown ratio, allow all
     }
@@ -169,16 +193,35 @@ pub fn render_settings_page(
     render_background(background_cache, video_cache, config, background_state);
 
     // dim the background for easier legibility
-    draw_rectangle(0.0, 0.0, screen_width(), screen_height(), Color::new(0.0, 0.0, 0.0, 0.5));
+    draw_rectangle(
+        0.0,
+        0.0,
+        screen_width(),
+        screen_height(),
+        Color::new(0.0, 0.0, 0.0, 0.5),
+    );
 
-    render_ui_overlay(logo_cache, font_cache, config, battery_info, current_time_str, gcc_adapter_poll_rate, scale_factor);
+    render_ui_overlay(
+        logo_cache,
+        font_cache,
+        config,
+        battery_info,
+        current_time_str,
+        gcc_adapter_poll_rate,
+        scale_factor,
+    );
 
     // Loop through and draw all settings options
     for (i, label_text) in options.iter().enumerate() {
         let y_pos_base = settings_start_y + (i as f32 * settings_option_height);
 
         let value_text = get_settings_value(page_number, i, config, system_volume, brightness);
-        let value_dims = measure_text(&value_text.to_uppercase(), Some(current_font), font_size, 1.0);
+        let value_dims = measure_text(
+            &value_text.to_uppercase(),
+            Some(current_font),
+            font_size,
+            1.0,
+        );
         let value_x = screen_width() - value_dims.width - right_margin;
         let text_y = y_pos_base + (settings_option_height / 2.0) + (value_dims.offset_y * 0.5);
 
@@ -199,17 +242,39 @@ pub fn render_settings_page(
             let rect_x = value_x - menu_padding;
             let rect_y = y_pos_base + (settings_option_height / 2.0) - (base_height / 2.0);
 
-            draw_rectangle_lines(rect_x - offset_x, rect_y - offset_y, scaled_width, scaled_height, 4.0 * scale_factor, cursor_color);
+            draw_rectangle_lines(
+                rect_x - offset_x,
+                rect_y - offset_y,
+                scaled_width,
+                scaled_height,
+                4.0 * scale_factor,
+                cursor_color,
+            );
         }
 
         // 2. Draw Label (Standard)
-        text_with_config_color(font_cache, config, label_text, left_margin, text_y, font_size);
+        text_with_config_color(
+            font_cache,
+            config,
+            label_text,
+            left_margin,
+            text_y,
+            font_size,
+        );
 
         // 3. Draw Value (Conditional Color)
         if is_selected && config.cursor_style == "TEXT" {
             // If selected and style is TEXT, use the animated cursor color
             let highlight_color = animation_state.get_cursor_color(config);
-            text_with_color(font_cache, config, &value_text, value_x, text_y, font_size, highlight_color);
+            text_with_color(
+                font_cache,
+                config,
+                &value_text,
+                value_x,
+                text_y,
+                font_size,
+                highlight_color,
+            );
         } else {
             // Otherwise use standard config color
             text_with_config_color(font_cache, config, &value_text, value_x, text_y, font_size);
@@ -223,6 +288,7 @@ pub fn render_settings_page(
         2 => "AUDIO SETTINGS",
         3 => "GUI CUSTOMIZATION",
         4 => "CUSTOM ASSETS",
+        5 => "KIOSK",
         _ => "", // Should not happen, but a safe fallback
     };
 
@@ -248,31 +314,86 @@ pub fn render_settings_page(
 
 // SETTINGS VALUE
 // Text for the settings on the RIGHT side
-pub fn get_settings_value(page: usize, index: usize, config: &Config, system_volume: f32, brightness: f32) -> String {
+pub fn get_settings_value(
+    page: usize,
+    index: usize,
+    config: &Config,
+    system_volume: f32,
+    brightness: f32,
+) -> String {
     match page {
         // GENERAL SETTINGS
         1 => match index {
-            0 => "CONFIRM".to_string(), // RESET SETTINGS
-            1 => config.resolution.clone(), // RESOLUTION
+            0 => "CONFIRM".to_string(),       // RESET SETTINGS
+            1 => config.resolution.clone(),   // RESOLUTION
             2 => config.aspect_ratio.clone(), // ASPECT RATIO
-            3 => if config.show_splash_screen { "ON" } else { "OFF" }.to_string(), // SPLASH SCREEN TOGGLE
+            3 => match (config.show_splash_screen, config.show_farewell_screen) {
+                // BOOT / FAREWELL SCREENS
+                (true, true) => "ON",
+                (true, false) => "BOOT ONLY",
+                (false, true) => "SHUTDOWN ONLY",
+                (false, false) => "OFF",
+            }
+            .to_string(),
             4 => config.timezone.clone().to_uppercase(), // TIME ZONE
-            5 => format!("{:.0}%", brightness * 100.0), // BRIGHTNESS
+            5 => format!("{:.0}%", brightness * 100.0),  // BRIGHTNESS
             6 => if config.wifi { "ON" } else { "OFF" }.to_string(), // WI-FI
             #[cfg(target_os = "linux")]
             7 => if config.bluetooth { "ON" } else { "OFF" }.to_string(), // BLUETOOTH
             #[cfg(target_os = "linux")]
             8 => if config.autoboot { "ON" } else { "OFF" }.to_string(), // AUTOBOOT
             #[cfg(target_os = "linux")]
-            9 => "->".to_string(), // RETROACHIEVEMENTS (opens new screen)
+            9 => format_timeout(config.idle_dim_minutes), // SCREEN DIM
+            #[cfg(target_os = "linux")]
+            10 => format_timeout(config.idle_suspend_minutes), // AUTO SUSPEND
+            #[cfg(target_os = "linux")]
+            11 => if config.compress_external_saves {
+                "ON"
+            } else {
+                "OFF"
+            }
+            .to_string(), // COMPRESS SAVES
+            #[cfg(target_os = "linux")]
+            12 => "->".to_string(), // RETROACHIEVEMENTS (opens new screen)
+            #[cfg(target_os = "linux")]
+            13 => "->".to_string(), // SYSTEM UPDATE (opens new screen)
             #[cfg(target_os = "linux")]
-            10 => "->".to_string(), // AUDIO SETTINGS
+            14 => "->".to_string(), // SETUP WIZARD
+            #[cfg(target_os = "linux")]
+            15 => "->".to_string(), // GAMEPAD TESTER
+            #[cfg(target_os = "linux")]
+            16 => "->".to_string(), // DISPLAY
+            #[cfg(target_os = "linux")]
+            17 => "->".to_string(), // NETWORK SHARE
+            #[cfg(target_os = "linux")]
+            18 => "->".to_string(), // AUDIO SETTINGS
             #[cfg(not(target_os = "linux"))]
             7 => if config.autoboot { "ON" } else { "OFF" }.to_string(), // AUTOBOOT
             #[cfg(not(target_os = "linux"))]
-            8 => "->".to_string(), // RETROACHIEVEMENTS (opens new screen)
+            8 => format_timeout(config.idle_dim_minutes), // SCREEN DIM
+            #[cfg(not(target_os = "linux"))]
+            9 => format_timeout(config.idle_suspend_minutes), // AUTO SUSPEND
+            #[cfg(not(target_os = "linux"))]
+            10 => if config.compress_external_saves {
+                "ON"
+            } else {
+                "OFF"
+            }
+            .to_string(), // COMPRESS SAVES
+            #[cfg(not(target_os = "linux"))]
+            11 => "->".to_string(), // RETROACHIEVEMENTS (opens new screen)
+            #[cfg(not(target_os = "linux"))]
+            12 => "->".to_string(), // SYSTEM UPDATE (opens new screen)
             #[cfg(not(target_os = "linux"))]
-            9 => "->".to_string(), // AUDIO SETTINGS
+            13 => "->".to_string(), // SETUP WIZARD
+            #[cfg(not(target_os = "linux"))]
+            14 => "->".to_string(), // GAMEPAD TESTER
+            #[cfg(not(target_os = "linux"))]
+            15 => "->".to_string(), // DISPLAY
+            #[cfg(not(target_os = "linux"))]
+            16 => "->".to_string(), // NETWORK SHARE
+            #[cfg(not(target_os = "linux"))]
+            17 => "->".to_string(), // AUDIO SETTINGS
             _ => "".to_string(),
         },
         // AUDIO SETTINGS
@@ -281,20 +402,21 @@ pub fn get_settings_value(page: usize, index: usize, config: &Config, system_vol
             1 => format!("{:.0}%", config.bgm_volume * 100.0), // BGM VOLUME
             2 => format!("{:.0}%", config.sfx_volume * 100.0), // SFX VOLUME
             3 => config.audio_output.clone().to_uppercase(), // AUDIO OUTPUT
-            4 => "<-".to_string(),
-            5 => "->".to_string(),
+            4 => config.sfx_pack.clone().replace('_', " ").to_uppercase(), // SOUND PACK
+            5 => "<-".to_string(),
+            6 => "->".to_string(),
             _ => "".to_string(),
         },
         // GUI CUSTOMIZATION
         3 => match index {
             0 => config.theme.clone().replace('_', " ").to_uppercase(), // THEME SELECTION
-            1 => format!("{:?}", config.menu_position).to_uppercase(), // MENU POSITION
-            2 => config.font_color.clone(), // FONT COLOR
-            3 => config.cursor_color.clone(), // CURSOR COLOR
-            4 => config.cursor_style.clone(), // CURSOR STYLE
-            5 => config.cursor_blink_speed.clone(), // CURSOR BLINK SPEED
-            6 => config.cursor_transition_speed.clone(), // CURSOR TRANSITION SPEED
-            7 => config.background_scroll_speed.clone(), // BACKGROUND SCROLL SPEED
+            1 => format!("{:?}", config.menu_position).to_uppercase(),  // MENU POSITION
+            2 => config.font_color.clone(),                             // FONT COLOR
+            3 => config.cursor_color.clone(),                           // CURSOR COLOR
+            4 => config.cursor_style.clone(),                           // CURSOR STYLE
+            5 => config.cursor_blink_speed.clone(),                     // CURSOR BLINK SPEED
+            6 => config.cursor_transition_speed.clone(),                // CURSOR TRANSITION SPEED
+            7 => config.background_scroll_speed.clone(),                // BACKGROUND SCROLL SPEED
             8 => config.color_shift_speed.clone(), // COLOR SHIFTING GRADIENT SPEED
             9 => "<-".to_string(),
             10 => "->".to_string(),
@@ -302,28 +424,73 @@ pub fn get_settings_value(page: usize, index: usize, config: &Config, system_vol
         },
         // CUSTOM ASSETS
         4 => match index {
-            0 => { // BGM SELECTION
+            0 => {
+                // BGM SELECTION
                 // Always show the current track or "OFF"
                 let track = config.bgm_track.clone().unwrap_or("OFF".to_string());
                 trim_extension(&track).replace('_', " ").to_uppercase()
-            },
-            1 => { // SOUND PACK
-                // Always show the currently selected sound pack
-                config.sfx_pack.clone().replace('_', " ").to_uppercase()
-            },
-            2 => { // LOGO
+            }
+            1 => {
+                // LOGO
                 // Always show the currently selected logo
-                trim_extension(&config.logo_selection).replace('_', " ").to_uppercase()
-            },
-            3 => { // BACKGROUND
+                trim_extension(&config.logo_selection)
+                    .replace('_', " ")
+                    .to_uppercase()
+            }
+            2 => {
+                // BACKGROUND
                 // Always show the currently selected background
-                trim_extension(&config.background_selection).replace('_', " ").to_uppercase()
-            },
-            4 => { // FONT TYPE
+                trim_extension(&config.background_selection)
+                    .replace('_', " ")
+                    .to_uppercase()
+            }
+            3 => {
+                // FONT TYPE
                 // Always show the currently selected font
-                trim_extension(&config.font_selection).replace('_', " ").to_uppercase()
-            },
-            5 => "<-".to_string(),
+                trim_extension(&config.font_selection)
+                    .replace('_', " ")
+                    .to_uppercase()
+            }
+            4 => "<-".to_string(),
+            5 => "->".to_string(),
+            _ => "".to_string(),
+        },
+        // KIOSK
+        5 => match index {
+            0 => format_timeout(config.kiosk_attract_minutes), // ATTRACT MODE
+            1 => format!("{} SEC", config.kiosk_slide_seconds), // SLIDE DURATION
+            2 => trim_extension(&config.kiosk_attract_music)
+                .replace('_', " ")
+                .to_uppercase(), // ATTRACT MUSIC
+            3 => if config.kiosk_whitelist_enabled {
+                "ON"
+            } else {
+                "OFF"
+            }
+            .to_string(), // GAME WHITELIST
+            4 => format!("{} ->", config.kiosk_whitelist.len()), // WHITELISTED GAMES (opens new screen)
+            5 => {
+                if config.remote_enabled {
+                    format!("PORT {}", config.remote_port)
+                } else {
+                    "OFF".to_string()
+                }
+            } // REMOTE CONTROL
+            6 => if config.remote_confirm_launch {
+                "ON"
+            } else {
+                "OFF"
+            }
+            .to_string(), // CONFIRM REMOTE LAUNCH
+            7 => if config.remote_confirm_power {
+                "ON"
+            } else {
+                "OFF"
+            }
+            .to_string(), // CONFIRM REMOTE POWER
+            8 => "SHOW CODE".to_string(),                        // PAIR DEVICE
+            9 => format!("{} ->", config.remote_devices.len()), // PAIRED DEVICES (opens new screen)
+            10 => "<-".to_string(),
             _ => "".to_string(),
         },
         _ => "".to_string(), // Default case for unknown pages
@@ -335,7 +502,6 @@ pub fn update(
     current_screen: &mut Screen,
     input_state: &InputState,
     config: &mut Config,
-    sound_pack_choices: &Vec<String>,
     loaded_themes: &HashMap<String, theme::Theme>,
     settings_menu_selection: &mut usize,
     sound_effects: &mut SoundEffects,
@@ -359,12 +525,17 @@ pub fn update(
         Screen::AudioSettings => (2, &AUDIO_SETTINGS),
         Screen::GuiSettings => (3, &GUI_CUSTOMIZATION_SETTINGS),
         Screen::AssetSettings => (4, &CUSTOM_ASSET_SETTINGS),
+        Screen::KioskSettings => (5, &KIOSK_SETTINGS),
         _ => unreachable!(),
     };
 
     // INPUT HANDLING
     if input_state.up {
-        *settings_menu_selection = if *settings_menu_selection == 0 { options.len() - 1 } else { *settings_menu_selection - 1 };
+        *settings_menu_selection = if *settings_menu_selection == 0 {
+            options.len() - 1
+        } else {
+            *settings_menu_selection - 1
+        };
         sound_effects.play_cursor_move(&config);
     }
     if input_state.down {
@@ -387,7 +558,8 @@ pub fn update(
             Screen::GeneralSettings => *current_screen = Screen::AudioSettings,
             Screen::AudioSettings => *current_screen = Screen::GuiSettings,
             Screen::GuiSettings => *current_screen = Screen::AssetSettings,
-            Screen::AssetSettings => *current_screen = Screen::GeneralSettings,
+            Screen::AssetSettings => *current_screen = Screen::KioskSettings,
+            Screen::KioskSettings => *current_screen = Screen::GeneralSettings,
             _ => {} // This case won't be reached
         }
     }
@@ -395,10 +567,11 @@ pub fn update(
         sound_effects.play_select(&config);
         *settings_menu_selection = 0; // Reset selection for the new page
         match current_screen {
-            Screen::GeneralSettings => *current_screen = Screen::AssetSettings,
+            Screen::GeneralSettings => *current_screen = Screen::KioskSettings,
             Screen::AudioSettings => *current_screen = Screen::GeneralSettings,
             Screen::GuiSettings => *current_screen = Screen::AudioSettings,
             Screen::AssetSettings => *current_screen = Screen::GuiSettings,
+            Screen::KioskSettings => *current_screen = Screen::AssetSettings,
             _ => {} // This case won't be reached
         }
     }
@@ -406,32 +579,40 @@ pub fn update(
     match page_number {
         // GENERAL OPTIONS
         1 => match settings_menu_selection {
-            0 => { // RESET SETTINGS
+            0 => {
+                // RESET SETTINGS
                 if input_state.select {
                     sound_effects.play_select(&config);
                     *confirm_selection = 1; // Default to "NO"
                     *current_screen = Screen::ConfirmReset;
                 }
-            },
-            1 => { // RESOLUTION
+            }
+            1 => {
+                // RESOLUTION
                 if input_state.left || input_state.right {
                     // 1. Filter the resolutions list based on the CURRENT aspect ratio
                     let filtered_resolutions: Vec<&str> = RESOLUTIONS
-                    .iter()
-                    .filter(|&&r| matches_aspect_ratio(r, &config.aspect_ratio))
-                    .cloned()
-                    .collect();
+                        .iter()
+                        .filter(|&&r| matches_aspect_ratio(r, &config.aspect_ratio))
+                        .cloned()
+                        .collect();
 
-                    if filtered_resolutions.is_empty() { return; } // Safety check
+                    if filtered_resolutions.is_empty() {
+                        return;
+                    } // Safety check
 
                     // 2. Find current index in the FILTERED list
-                    let current_index = filtered_resolutions.iter().position(|&r| r == config.resolution).unwrap_or(0);
+                    let current_index = filtered_resolutions
+                        .iter()
+                        .position(|&r| r == config.resolution)
+                        .unwrap_or(0);
 
                     // 3. Calculate new index
                     let new_index = if input_state.right {
                         (current_index + 1) % filtered_resolutions.len()
                     } else {
-                        (current_index + filtered_resolutions.len() - 1) % filtered_resolutions.len()
+                        (current_index + filtered_resolutions.len() - 1)
+                            % filtered_resolutions.len()
                     };
 
                     // 4. Apply
@@ -440,10 +621,14 @@ pub fn update(
                     apply_resolution(&config.resolution);
                     sound_effects.play_cursor_move(&config);
                 }
-            },
-            2 => { // ASPECT RATIO
+            }
+            2 => {
+                // ASPECT RATIO
                 if input_state.left || input_state.right {
-                    let current_index = ASPECT_RATIOS.iter().position(|&r| r == config.aspect_ratio).unwrap_or(1); // Default 16:9
+                    let current_index = ASPECT_RATIOS
+                        .iter()
+                        .position(|&r| r == config.aspect_ratio)
+                        .unwrap_or(1); // Default 16:9
                     let new_index = if input_state.right {
                         (current_index + 1) % ASPECT_RATIOS.len()
                     } else {
@@ -455,7 +640,10 @@ pub fn update(
                     // [!] AUTO-SWITCH RESOLUTION
                     // When ratio changes, switch to the "Best" (first) resolution for that ratio
                     // to prevent invalid states (like 4:3 ratio but 1920x1080 resolution).
-                    if let Some(new_res) = RESOLUTIONS.iter().find(|&&r| matches_aspect_ratio(r, &config.aspect_ratio)) {
+                    if let Some(new_res) = RESOLUTIONS
+                        .iter()
+                        .find(|&&r| matches_aspect_ratio(r, &config.aspect_ratio))
+                    {
                         config.resolution = new_res.to_string();
                         apply_resolution(&config.resolution);
                     }
@@ -463,19 +651,31 @@ pub fn update(
                     config.save();
                     sound_effects.play_cursor_move(&config);
                 }
-            },
-            3 => { // SPLASH SCREEN
+            }
+            3 => {
+                // SPLASH SCREENS: cycles ON, BOOT ONLY, SHUTDOWN ONLY, OFF
                 if input_state.left || input_state.right {
-                    config.show_splash_screen = !config.show_splash_screen;
+                    const MODES: [(bool, bool); 4] =
+                        [(true, true), (true, false), (false, true), (false, false)];
+                    let current = (config.show_splash_screen, config.show_farewell_screen);
+                    let index = MODES.iter().position(|m| *m == current).unwrap_or(0);
+                    let next = if input_state.right {
+                        (index + 1) % MODES.len()
+                    } else {
+                        (index + MODES.len() - 1) % MODES.len()
+                    };
+                    (config.show_splash_screen, config.show_farewell_screen) = MODES[next];
                     config.save();
                     sound_effects.play_cursor_move(&config);
                 }
-            },
-            4 => { // TIME ZONE
+            }
+            4 => {
+                // TIME ZONE
                 let mut change_occurred = false;
 
                 // Find the current index of the timezone in our array
-                if let Some(current_index) = TIMEZONES.iter().position(|&tz| tz == config.timezone) {
+                if let Some(current_index) = TIMEZONES.iter().position(|&tz| tz == config.timezone)
+                {
                     if input_state.left {
                         // Decrement and wrap around if we go below zero
                         let new_index = (current_index + TIMEZONES.len() - 1) % TIMEZONES.len();
@@ -494,8 +694,9 @@ pub fn update(
                     sound_effects.play_cursor_move(&config);
                     config.save();
                 }
-            },
-            5 => { // BRIGHTNESS
+            }
+            5 => {
+                // BRIGHTNESS
                 if input_state.left {
                     set_brightness(*brightness - 0.1); // Decrease by 10%
                     *brightness = get_current_brightness().unwrap_or(*brightness); // Refresh the value
@@ -506,8 +707,9 @@ pub fn update(
                     *brightness = get_current_brightness().unwrap_or(*brightness); // Refresh the value
                     sound_effects.play_cursor_move(&config);
                 }
-            },
-            6 => { // WI-FI
+            }
+            6 => {
+                // WI-FI
                 if input_state.left || input_state.right {
                     // Toggle the state optimistically and save immediately.
                     config.wifi = !config.wifi;
@@ -521,10 +723,10 @@ pub fn update(
 
                         thread::spawn(move || {
                             let output = Command::new("sudo")
-                            .arg("nmcli")
-                            .arg("networking")
-                            .arg(action)
-                            .output();
+                                .arg("nmcli")
+                                .arg("networking")
+                                .arg(action)
+                                .output();
 
                             match output {
                                 Ok(out) => {
@@ -542,12 +744,16 @@ pub fn update(
                             }
                         });
                     } else {
-                        println!("[DEV_MODE] Skipping sudo command to turn networking {}.", action);
+                        println!(
+                            "[DEV_MODE] Skipping sudo command to turn networking {}.",
+                            action
+                        );
                     }
                 }
-            },
+            }
             #[cfg(target_os = "linux")]
-            7 => { // BLUETOOTH
+            7 => {
+                // BLUETOOTH
                 if input_state.left || input_state.right {
                     config.bluetooth = !config.bluetooth;
                     config.save();
@@ -559,10 +765,10 @@ pub fn update(
                         println!("[INFO] Spawning thread to {} Bluetooth", action);
                         thread::spawn(move || {
                             let output = Command::new("sudo")
-                            .arg("rfkill")
-                            .arg(action)
-                            .arg("bluetooth")
-                            .output();
+                                .arg("rfkill")
+                                .arg(action)
+                                .arg("bluetooth")
+                                .output();
 
                             match output {
                                 Ok(out) => {
@@ -583,61 +789,208 @@ pub fn update(
                         println!("[DEV_MODE] Skipping sudo command to {} Bluetooth.", action);
                     }
                 }
-            },
+            }
             #[cfg(not(target_os = "linux"))]
-            7 => { // AUTOBOOT (shifted from 8 on Linux)
+            7 => {
+                // AUTOBOOT (shifted from 8 on Linux)
                 if input_state.left || input_state.right {
                     config.autoboot = !config.autoboot;
                     config.save();
                     sound_effects.play_cursor_move(&config);
                 }
-            },
+            }
             #[cfg(target_os = "linux")]
-            8 => { // AUTOBOOT
+            8 => {
+                // AUTOBOOT
                 if input_state.left || input_state.right {
                     config.autoboot = !config.autoboot;
                     config.save();
                     sound_effects.play_cursor_move(&config);
                 }
-            },
+            }
+            #[cfg(target_os = "linux")]
+            9 => {
+                // SCREEN DIM
+                if input_state.left || input_state.right {
+                    config.idle_dim_minutes =
+                        cycle_timeout(config.idle_dim_minutes, input_state.right);
+                    config.save();
+                    sound_effects.play_cursor_move(&config);
+                }
+            }
+            #[cfg(target_os = "linux")]
+            10 => {
+                // AUTO SUSPEND
+                if input_state.left || input_state.right {
+                    config.idle_suspend_minutes =
+                        cycle_timeout(config.idle_suspend_minutes, input_state.right);
+                    config.save();
+                    sound_effects.play_cursor_move(&config);
+                }
+            }
+            #[cfg(target_os = "linux")]
+            11 => {
+                // COMPRESS SAVES
+                if input_state.left || input_state.right {
+                    config.compress_external_saves = !config.compress_external_saves;
+                    config.save();
+                    save::set_compress_archives(config.compress_external_saves);
+                    sound_effects.play_cursor_move(&config);
+                }
+            }
             #[cfg(target_os = "linux")]
-            9 => { // RETROACHIEVEMENTS
+            12 => {
+                // RETROACHIEVEMENTS
                 if input_state.select {
                     *current_screen = Screen::RetroAchievements;
                     *settings_menu_selection = 0;
                     sound_effects.play_select(&config);
                 }
-            },
+            }
             #[cfg(target_os = "linux")]
-            10 => { // GO TO AUDIO SETTINGS
+            13 => {
+                // SYSTEM UPDATE
+                if input_state.select {
+                    *current_screen = Screen::SystemUpdate;
+                    sound_effects.play_select(&config);
+                }
+            }
+            #[cfg(target_os = "linux")]
+            14 => {
+                // SETUP WIZARD
+                if input_state.select {
+                    *current_screen = Screen::FirstRun;
+                    sound_effects.play_select(&config);
+                }
+            }
+            #[cfg(target_os = "linux")]
+            15 => {
+                // GAMEPAD TESTER
+                if input_state.select {
+                    *current_screen = Screen::GamepadTester;
+                    sound_effects.play_select(&config);
+                }
+            }
+            #[cfg(target_os = "linux")]
+            16 => {
+                // DISPLAY
+                if input_state.select {
+                    *current_screen = Screen::DisplaySettings;
+                    sound_effects.play_select(&config);
+                }
+            }
+            #[cfg(target_os = "linux")]
+            17 => {
+                // NETWORK SHARE
+                if input_state.select {
+                    *current_screen = Screen::NetworkShare;
+                    sound_effects.play_select(&config);
+                }
+            }
+            #[cfg(target_os = "linux")]
+            18 => {
+                // GO TO AUDIO SETTINGS
                 if input_state.select {
                     *current_screen = Screen::AudioSettings;
                     *settings_menu_selection = 0;
                     sound_effects.play_select(&config);
                 }
-            },
+            }
             #[cfg(not(target_os = "linux"))]
-            8 => { // RETROACHIEVEMENTS
+            8 => {
+                // SCREEN DIM
+                if input_state.left || input_state.right {
+                    config.idle_dim_minutes =
+                        cycle_timeout(config.idle_dim_minutes, input_state.right);
+                    config.save();
+                    sound_effects.play_cursor_move(&config);
+                }
+            }
+            #[cfg(not(target_os = "linux"))]
+            9 => {
+                // AUTO SUSPEND
+                if input_state.left || input_state.right {
+                    config.idle_suspend_minutes =
+                        cycle_timeout(config.idle_suspend_minutes, input_state.right);
+                    config.save();
+                    sound_effects.play_cursor_move(&config);
+                }
+            }
+            #[cfg(not(target_os = "linux"))]
+            10 => {
+                // COMPRESS SAVES
+                if input_state.left || input_state.right {
+                    config.compress_external_saves = !config.compress_external_saves;
+                    config.save();
+                    save::set_compress_archives(config.compress_external_saves);
+                    sound_effects.play_cursor_move(&config);
+                }
+            }
+            #[cfg(not(target_os = "linux"))]
+            11 => {
+                // RETROACHIEVEMENTS
                 if input_state.select {
                     *current_screen = Screen::RetroAchievements;
                     *settings_menu_selection = 0;
                     sound_effects.play_select(&config);
                 }
-            },
+            }
+            #[cfg(not(target_os = "linux"))]
+            12 => {
+                // SYSTEM UPDATE
+                if input_state.select {
+                    *current_screen = Screen::SystemUpdate;
+                    sound_effects.play_select(&config);
+                }
+            }
+            #[cfg(not(target_os = "linux"))]
+            13 => {
+                // SETUP WIZARD
+                if input_state.select {
+                    *current_screen = Screen::FirstRun;
+                    sound_effects.play_select(&config);
+                }
+            }
+            #[cfg(not(target_os = "linux"))]
+            14 => {
+                // GAMEPAD TESTER
+                if input_state.select {
+                    *current_screen = Screen::GamepadTester;
+                    sound_effects.play_select(&config);
+                }
+            }
+            #[cfg(not(target_os = "linux"))]
+            15 => {
+                // DISPLAY
+                if input_state.select {
+                    *current_screen = Screen::DisplaySettings;
+                    sound_effects.play_select(&config);
+                }
+            }
+            #[cfg(not(target_os = "linux"))]
+            16 => {
+                // NETWORK SHARE
+                if input_state.select {
+                    *current_screen = Screen::NetworkShare;
+                    sound_effects.play_select(&config);
+                }
+            }
             #[cfg(not(target_os = "linux"))]
-            9 => { // GO TO AUDIO SETTINGS
+            17 => {
+                // GO TO AUDIO SETTINGS
                 if input_state.select {
                     *current_screen = Screen::AudioSettings;
                     *settings_menu_selection = 0;
                     sound_effects.play_select(&config);
                 }
-            },
+            }
             _ => {}
         },
 
         // AUDIO SETTINGS
         2 => match settings_menu_selection {
-            0 => { // MASTER VOLUME
+            0 => {
+                // MASTER VOLUME
                 if input_state.left {
                     adjust_system_volume("10%-"); // Decrease by 10%
                     *system_volume = get_system_volume().unwrap_or(*system_volume); // Refresh the value
@@ -648,8 +1001,9 @@ pub fn update(
                     *system_volume = get_system_volume().unwrap_or(*system_volume); // Refresh the value
                     sound_effects.play_cursor_move(&config);
                 }
-            },
-            1 => { // BGM VOLUME
+            }
+            1 => {
+                // BGM VOLUME
                 if input_state.left || input_state.right {
                     if input_state.left {
                         config.bgm_volume = (config.bgm_volume - 0.1).max(0.0);
@@ -666,8 +1020,9 @@ pub fn update(
                     config.save();
                     sound_effects.play_cursor_move(&config);
                 }
-            },
-            2 => { // SFX Volume
+            }
+            2 => {
+                // SFX Volume
                 if input_state.left || input_state.right {
                     if input_state.left {
                         config.sfx_volume = (config.sfx_volume - 0.1).max(0.0);
@@ -678,16 +1033,21 @@ pub fn update(
                     config.save();
                     sound_effects.play_cursor_move(&config); // Test the new volume
                 }
-            },
-            3 => { // AUDIO OUTPUT
+            }
+            3 => {
+                // AUDIO OUTPUT
                 // Only run this logic if we actually found sinks
                 if !available_sinks.is_empty() {
                     // Find the index of the current sink in our discovered list
-                    let current_index = available_sinks.iter().position(|s| s.name == config.audio_output).unwrap_or(0);
+                    let current_index = available_sinks
+                        .iter()
+                        .position(|s| s.name == config.audio_output)
+                        .unwrap_or(0);
 
                     let mut new_index = current_index;
                     if input_state.left {
-                        new_index = (current_index + available_sinks.len() - 1) % available_sinks.len();
+                        new_index =
+                            (current_index + available_sinks.len() - 1) % available_sinks.len();
                     }
                     if input_state.right {
                         new_index = (current_index + 1) % available_sinks.len();
@@ -698,7 +1058,10 @@ pub fn update(
                         config.audio_output = new_sink.name.clone();
 
                         // Apply the change immediately
-                        let _ = Command::new("wpctl").arg("set-default").arg(new_sink.id.to_string()).status();
+                        let _ = Command::new("wpctl")
+                            .arg("set-default")
+                            .arg(new_sink.id.to_string())
+                            .status();
 
                         // Create a sentinel file to make the choice persistent
                         let state_dir = std::path::Path::new("/var/kazeta/state");
@@ -709,36 +1072,79 @@ pub fn update(
                         sound_effects.play_cursor_move(&config);
                     }
                 }
-            },
-            4 => { // GO TO GENERAL SETTINGS
+            }
+            4 => {
+                // SOUND PACK
+                if input_state.left || input_state.right {
+                    // Scanned each time so packs copied in since boot show up
+                    let sound_pack_choices = find_sound_packs();
+                    let current_index = sound_pack_choices
+                        .iter()
+                        .position(|p| *p == config.sfx_pack)
+                        .unwrap_or(0);
+
+                    let new_index = if input_state.right {
+                        (current_index + 1) % sound_pack_choices.len()
+                    } else {
+                        (current_index + sound_pack_choices.len() - 1) % sound_pack_choices.len()
+                    };
+
+                    let new_pack_name = &sound_pack_choices[new_index];
+
+                    if &config.sfx_pack != new_pack_name {
+                        // 1. Update the config value
+                        config.sfx_pack = new_pack_name.clone();
+
+                        // 2. Set the request for the main loop to handle
+                        *sfx_pack_to_reload = Some(new_pack_name.clone());
+
+                        // 3. Save the config
+                        config.save();
+                    }
+                }
+                if input_state.select {
+                    // Reload first so edits to the pack's files are heard too
+                    *sound_effects = SoundEffects::load(&config.sfx_pack);
+                    sound_effects.play_preview(&config);
+                }
+            }
+            5 => {
+                // GO TO GENERAL SETTINGS
                 if input_state.select {
                     *current_screen = Screen::GeneralSettings;
                     *settings_menu_selection = 0;
                     sound_effects.play_select(&config);
                 }
-            },
-            5 => { // GO TO GUI CUSTOMIZATION
+            }
+            6 => {
+                // GO TO GUI CUSTOMIZATION
                 if input_state.select {
                     *current_screen = Screen::GuiSettings;
                     *settings_menu_selection = 0;
                     sound_effects.play_select(&config);
                 }
-            },
+            }
             _ => {}
         },
 
         // GUI CUSTOMIZATION OPTIONS
         3 => match settings_menu_selection {
-            0 => { // THEME SELECTION
+            0 => {
+                // THEME SELECTION
                 if input_state.left || input_state.right {
-                    if loaded_themes.is_empty() { return; } // Prevent panic if no themes are loaded
+                    if loaded_themes.is_empty() {
+                        return;
+                    } // Prevent panic if no themes are loaded
 
                     // 1. Get a fresh, sorted list of theme names directly from the live data.
                     let mut theme_names: Vec<_> = loaded_themes.keys().cloned().collect();
                     theme_names.sort();
 
                     // Now, use this up-to-date 'theme_names' vector for the rest of the logic
-                    let current_index = theme_names.iter().position(|t| *t == config.theme).unwrap_or(0);
+                    let current_index = theme_names
+                        .iter()
+                        .position(|t| *t == config.theme)
+                        .unwrap_or(0);
                     let new_index = if input_state.right {
                         (current_index + 1) % theme_names.len()
                     } else {
@@ -761,6 +1167,10 @@ pub fn update(
                             config.font_selection = defaults.font_selection;
                             config.splash_video = defaults.splash_video;
                             config.splash_audio = defaults.splash_audio;
+                            config.splash_image = defaults.splash_image;
+                            config.farewell_image = defaults.farewell_image;
+                            config.farewell_audio = defaults.farewell_audio;
+                            config.farewell_message = defaults.farewell_message;
                             config.loading_messages = defaults.loading_messages;
                             config.menu_position = defaults.menu_position;
                             config.font_color = defaults.font_color;
@@ -784,13 +1194,58 @@ pub fn update(
                             if let Some(theme) = loaded_themes.get(&new_theme_name) {
                                 println!("[INFO] Switched to '{}' theme.", new_theme_name);
                                 *sound_effects = theme.sounds.clone();
-                                config.sfx_pack = theme.config.sfx_pack.clone().unwrap_or_else(|| "Default".to_string());
+                                config.sfx_pack = theme
+                                    .config
+                                    .sfx_pack
+                                    .clone()
+                                    .unwrap_or_else(|| "Default".to_string());
                                 config.bgm_track = theme.config.bgm_track.clone();
-                                config.logo_selection = theme.config.logo_selection.clone().unwrap_or_else(|| "Kazeta+ (Default)".to_string());
-                                config.background_selection = theme.config.background_selection.clone().unwrap_or_else(|| "Default".to_string());
-                                config.font_selection = theme.config.font_selection.clone().unwrap_or_else(|| "Default".to_string());
-                                config.splash_video = theme.config.splash_video.clone().unwrap_or_else(|| "Default".to_string());
-                                config.splash_audio = theme.config.splash_audio.clone().unwrap_or_else(|| "Default".to_string());
+                                config.logo_selection = theme
+                                    .config
+                                    .logo_selection
+                                    .clone()
+                                    .unwrap_or_else(|| "Kazeta+ (Default)".to_string());
+                                config.background_selection = theme
+                                    .config
+                                    .background_selection
+                                    .clone()
+                                    .unwrap_or_else(|| "Default".to_string());
+                                config.font_selection = theme
+                                    .config
+                                    .font_selection
+                                    .clone()
+                                    .unwrap_or_else(|| "Default".to_string());
+                                config.splash_video = theme
+                                    .config
+                                    .splash_video
+                                    .clone()
+                                    .unwrap_or_else(|| "Default".to_string());
+                                config.splash_audio = theme
+                                    .config
+                                    .splash_audio
+                                    .clone()
+                                    .unwrap_or_else(|| "Default".to_string());
+                                let defaults = Config::default();
+                                config.splash_image = theme
+                                    .config
+                                    .splash_image
+                                    .clone()
+                                    .unwrap_or(defaults.splash_image);
+                                config.farewell_image = theme
+                                    .config
+                                    .farewell_image
+                                    .clone()
+                                    .unwrap_or(defaults.farewell_image);
+                                config.farewell_audio = theme
+                                    .config
+                                    .farewell_audio
+                                    .clone()
+                                    .unwrap_or(defaults.farewell_audio);
+                                config.farewell_message = theme
+                                    .config
+                                    .farewell_message
+                                    .clone()
+                                    .unwrap_or(defaults.farewell_message);
 
                                 // Apply custom loading messages if theme provides them
                                 if let Some(messages) = &theme.config.loading_messages {
@@ -799,28 +1254,59 @@ pub fn update(
                                     config.loading_messages = Vec::new(); // Empty means use defaults
                                 }
 
-                                if let Some(val) = &theme.config.menu_position { config.menu_position = val.parse().unwrap_or_default(); }
-                                if let Some(val) = &theme.config.font_color { config.font_color = val.clone(); }
-                                if let Some(val) = &theme.config.cursor_color { config.cursor_color = val.clone(); }
-                                if let Some(val) = &theme.config.cursor_style { config.cursor_style = val.clone(); }
-                                if let Some(val) = &theme.config.cursor_blink_speed { config.cursor_blink_speed = val.clone(); }
-                                if let Some(val) = &theme.config.cursor_transition_speed { config.cursor_transition_speed = val.clone(); }
-                                if let Some(val) = &theme.config.background_scroll_speed { config.background_scroll_speed = val.clone(); }
-                                if let Some(val) = &theme.config.color_shift_speed { config.color_shift_speed = val.clone(); }
+                                if let Some(val) = &theme.config.menu_position {
+                                    config.menu_position = val.parse().unwrap_or_default();
+                                }
+                                if let Some(val) = &theme.config.font_color {
+                                    config.font_color = val.clone();
+                                }
+                                if let Some(val) = &theme.config.cursor_color {
+                                    config.cursor_color = val.clone();
+                                }
+                                if let Some(val) = &theme.config.cursor_style {
+                                    config.cursor_style = val.clone();
+                                }
+                                if let Some(val) = &theme.config.cursor_blink_speed {
+                                    config.cursor_blink_speed = val.clone();
+                                }
+                                if let Some(val) = &theme.config.cursor_transition_speed {
+                                    config.cursor_transition_speed = val.clone();
+                                }
+                                if let Some(val) = &theme.config.background_scroll_speed {
+                                    config.background_scroll_speed = val.clone();
+                                }
+                                if let Some(val) = &theme.config.color_shift_speed {
+                                    config.color_shift_speed = val.clone();
+                                }
 
                                 if let Some(blades_config) = &theme.config.blades {
-                                    if let Some(val) = blades_config.enabled { config.blades_enabled = val; }
-                                    if let Some(val) = &blades_config.games_color { config.blade_games_color = val.clone(); }
-                                    if let Some(val) = &blades_config.settings_color { config.blade_settings_color = val.clone(); }
-                                    if let Some(val) = &blades_config.saves_color { config.blade_saves_color = val.clone(); }
-                                    if let Some(val) = blades_config.transparency { config.blade_transparency = val; }
-                                    if let Some(val) = blades_config.blur_enabled { config.blade_blur_enabled = val; }
+                                    if let Some(val) = blades_config.enabled {
+                                        config.blades_enabled = val;
+                                    }
+                                    if let Some(val) = &blades_config.games_color {
+                                        config.blade_games_color = val.clone();
+                                    }
+                                    if let Some(val) = &blades_config.settings_color {
+                                        config.blade_settings_color = val.clone();
+                                    }
+                                    if let Some(val) = &blades_config.saves_color {
+                                        config.blade_saves_color = val.clone();
+                                    }
+                                    if let Some(val) = blades_config.transparency {
+                                        config.blade_transparency = val;
+                                    }
+                                    if let Some(val) = blades_config.blur_enabled {
+                                        config.blade_blur_enabled = val;
+                                    }
                                 }
                             }
                         }
 
                         play_new_bgm(
-                            &config.bgm_track.clone().unwrap_or_else(|| "OFF".to_string()),
+                            &config
+                                .bgm_track
+                                .clone()
+                                .unwrap_or_else(|| "OFF".to_string()),
                             config.bgm_volume,
                             music_cache,
                             current_bgm,
@@ -830,8 +1316,9 @@ pub fn update(
                         config.save();
                     }
                 }
-            },
-            1 => { // MENU POSITION
+            }
+            1 => {
+                // MENU POSITION
                 if input_state.left {
                     config.menu_position = config.menu_position.prev();
                     config.save();
@@ -842,11 +1329,15 @@ pub fn update(
                     config.save();
                     sound_effects.play_cursor_move(config);
                 }
-            },
-            2 => { // FONT COLOR
+            }
+            2 => {
+                // FONT COLOR
                 if input_state.left || input_state.right {
                     // Find current color's index in our list
-                    let current_index = COLORS.iter().position(|&c| c == config.font_color).unwrap_or(0);
+                    let current_index = COLORS
+                        .iter()
+                        .position(|&c| c == config.font_color)
+                        .unwrap_or(0);
                     let new_index = if input_state.right {
                         (current_index + 1) % COLORS.len()
                     } else {
@@ -857,10 +1348,14 @@ pub fn update(
                     sound_effects.play_cursor_move(&config);
                 }
             }
-            3 => { // CURSOR COLOR
+            3 => {
+                // CURSOR COLOR
                 if input_state.left || input_state.right {
                     // We can reuse the COLORS constant for this
-                    let current_index = COLORS.iter().position(|&c| c == config.cursor_color).unwrap_or(0);
+                    let current_index = COLORS
+                        .iter()
+                        .position(|&c| c == config.cursor_color)
+                        .unwrap_or(0);
                     let new_index = if input_state.right {
                         (current_index + 1) % COLORS.len()
                     } else {
@@ -871,10 +1366,14 @@ pub fn update(
                     config.save();
                     sound_effects.play_cursor_move(&config);
                 }
-            },
-            4 => { // CURSOR STYLE
+            }
+            4 => {
+                // CURSOR STYLE
                 if input_state.left || input_state.right {
-                    let current_index = CURSOR_STYLES.iter().position(|&s| s == config.cursor_style).unwrap_or(0);
+                    let current_index = CURSOR_STYLES
+                        .iter()
+                        .position(|&s| s == config.cursor_style)
+                        .unwrap_or(0);
                     let new_index = if input_state.right {
                         (current_index + 1) % CURSOR_STYLES.len()
                     } else {
@@ -884,10 +1383,14 @@ pub fn update(
                     config.save();
                     sound_effects.play_cursor_move(&config);
                 }
-            },
-            5 => { // CURSOR BLINK SPEED
+            }
+            5 => {
+                // CURSOR BLINK SPEED
                 if input_state.left || input_state.right {
-                    let current_index = SPEEDS.iter().position(|&s| s == config.cursor_blink_speed).unwrap_or(0);
+                    let current_index = SPEEDS
+                        .iter()
+                        .position(|&s| s == config.cursor_blink_speed)
+                        .unwrap_or(0);
                     let new_index = if input_state.right {
                         (current_index + 1) % SPEEDS.len()
                     } else {
@@ -898,10 +1401,14 @@ pub fn update(
                     config.save();
                     sound_effects.play_cursor_move(&config);
                 }
-            },
-            6 => { // TRANSITION ANIMATION
+            }
+            6 => {
+                // TRANSITION ANIMATION
                 if input_state.left || input_state.right {
-                    let current_index = SPEEDS.iter().position(|&s| s == config.cursor_transition_speed).unwrap_or(2); // Default to NORMAL (index 2)
+                    let current_index = SPEEDS
+                        .iter()
+                        .position(|&s| s == config.cursor_transition_speed)
+                        .unwrap_or(2); // Default to NORMAL (index 2)
                     let new_index = if input_state.right {
                         (current_index + 1) % SPEEDS.len()
                     } else {
@@ -914,10 +1421,14 @@ pub fn update(
                     animation_state.trigger_transition(&config.cursor_transition_speed);
                     sound_effects.play_cursor_move(&config);
                 }
-            },
-            7 => { // BACKGROUND SCROLLING
+            }
+            7 => {
+                // BACKGROUND SCROLLING
                 if input_state.left || input_state.right {
-                    let current_index = SPEEDS.iter().position(|&s| s == config.background_scroll_speed).unwrap_or(0);
+                    let current_index = SPEEDS
+                        .iter()
+                        .position(|&s| s == config.background_scroll_speed)
+                        .unwrap_or(0);
                     let new_index = if input_state.right {
                         (current_index + 1) % SPEEDS.len()
                     } else {
@@ -928,10 +1439,14 @@ pub fn update(
                     config.save();
                     sound_effects.play_cursor_move(&config);
                 }
-            },
-            8 => { // COLOR GRADIENT SHIFTING
+            }
+            8 => {
+                // COLOR GRADIENT SHIFTING
                 if input_state.left || input_state.right {
-                    let current_index = SPEEDS.iter().position(|&s| s == config.color_shift_speed).unwrap_or(0);
+                    let current_index = SPEEDS
+                        .iter()
+                        .position(|&s| s == config.color_shift_speed)
+                        .unwrap_or(0);
                     let new_index = if input_state.right {
                         (current_index + 1) % SPEEDS.len()
                     } else {
@@ -942,33 +1457,43 @@ pub fn update(
                     config.save();
                     sound_effects.play_cursor_move(&config);
                 }
-            },
-            9 => { // GO TO AUDIO SETTINGS
+            }
+            9 => {
+                // GO TO AUDIO SETTINGS
                 if input_state.select {
                     *current_screen = Screen::AudioSettings;
                     *settings_menu_selection = 0;
                     sound_effects.play_select(&config);
                 }
-            },
-            10 => { // GO TO CUSTOM ASSETS
+            }
+            10 => {
+                // GO TO CUSTOM ASSETS
                 if input_state.select {
                     *current_screen = Screen::AssetSettings;
                     *settings_menu_selection = 0;
                     sound_effects.play_select(&config);
                 }
-            },
+            }
             _ => {}
         },
         // CUSTOM ASSETS
         4 => match settings_menu_selection {
-            0 => { // BGM SELECTION
+            0 => {
+                // BGM SELECTION
                 if input_state.left || input_state.right {
                     // Find the current track's position in our list of choices
-                    let current_index = bgm_choices.iter().position(|t| *t == config.bgm_track.clone().unwrap_or("OFF".to_string())).unwrap_or(0);
+                    let current_index = bgm_choices
+                        .iter()
+                        .position(|t| *t == config.bgm_track.clone().unwrap_or("OFF".to_string()))
+                        .unwrap_or(0);
                     let mut new_index = current_index;
 
                     if input_state.left {
-                        new_index = if current_index == 0 { bgm_choices.len() - 1 } else { current_index - 1 };
+                        new_index = if current_index == 0 {
+                            bgm_choices.len() - 1
+                        } else {
+                            current_index - 1
+                        };
                     }
                     if input_state.right {
                         new_index = (current_index + 1) % bgm_choices.len();
@@ -987,40 +1512,23 @@ pub fn update(
                     config.save();
                     sound_effects.play_cursor_move(&config);
                 }
-            },
-            1 => { // SOUND PACK
-                if input_state.left || input_state.right {
-                    // `sound_pack_choices` is the Vec<String> of available packs
-                    let current_index = sound_pack_choices.iter().position(|p| *p == config.sfx_pack).unwrap_or(0);
-
-                    let new_index = if input_state.right {
-                        (current_index + 1) % sound_pack_choices.len()
-                    } else {
-                        (current_index + sound_pack_choices.len() - 1) % sound_pack_choices.len()
-                    };
-
-                    let new_pack_name = &sound_pack_choices[new_index];
-
-                    if &config.sfx_pack != new_pack_name {
-                        // 1. Update the config value
-                        config.sfx_pack = new_pack_name.clone();
-
-                        // 2. Set the request for the main loop to handle
-                        *sfx_pack_to_reload = Some(new_pack_name.clone());
-
-                        // 3. Save the config
-                        config.save();
-                    }
-                }
-            },
-            2 => { // LOGO selection
+            }
+            1 => {
+                // LOGO selection
                 if input_state.left || input_state.right {
                     // Find the current logo's position in our list of choices
-                    let current_index = logo_choices.iter().position(|l| *l == config.logo_selection).unwrap_or(0);
+                    let current_index = logo_choices
+                        .iter()
+                        .position(|l| *l == config.logo_selection)
+                        .unwrap_or(0);
                     let mut new_index = current_index;
 
                     if input_state.left {
-                        new_index = if current_index == 0 { logo_choices.len() - 1 } else { current_index - 1 };
+                        new_index = if current_index == 0 {
+                            logo_choices.len() - 1
+                        } else {
+                            current_index - 1
+                        };
                     }
                     if input_state.right {
                         new_index = (current_index + 1) % logo_choices.len();
@@ -1032,15 +1540,23 @@ pub fn update(
                     config.save();
                     sound_effects.play_cursor_move(&config);
                 }
-            },
-            3 => { // BACKGROUND SELECTION
+            }
+            2 => {
+                // BACKGROUND SELECTION
                 if input_state.left || input_state.right {
                     // Find the current background's position in our list of choices
-                    let current_index = background_choices.iter().position(|b| *b == config.background_selection).unwrap_or(0);
+                    let current_index = background_choices
+                        .iter()
+                        .position(|b| *b == config.background_selection)
+                        .unwrap_or(0);
                     let mut new_index = current_index;
 
                     if input_state.left {
-                        new_index = if current_index == 0 { background_choices.len() - 1 } else { current_index - 1 };
+                        new_index = if current_index == 0 {
+                            background_choices.len() - 1
+                        } else {
+                            current_index - 1
+                        };
                     }
                     if input_state.right {
                         new_index = (current_index + 1) % background_choices.len();
@@ -1052,10 +1568,14 @@ pub fn update(
                     config.save();
                     sound_effects.play_cursor_move(&config);
                 }
-            },
-            4 => { // FONT TYPE
+            }
+            3 => {
+                // FONT TYPE
                 if input_state.left || input_state.right {
-                    let current_index = font_choices.iter().position(|name| name == &config.font_selection).unwrap_or(0);
+                    let current_index = font_choices
+                        .iter()
+                        .position(|name| name == &config.font_selection)
+                        .unwrap_or(0);
                     let new_index = if input_state.right {
                         (current_index + 1) % font_choices.len()
                     } else {
@@ -1066,14 +1586,127 @@ pub fn update(
                     config.save();
                     sound_effects.play_cursor_move(&config);
                 }
-            },
-            5 => { // GO TO GUI CUSTOMIZATION SETTINGS
+            }
+            4 => {
+                // GO TO GUI CUSTOMIZATION SETTINGS
                 if input_state.select {
                     *current_screen = Screen::GuiSettings;
                     *settings_menu_selection = 0;
                     sound_effects.play_select(&config);
                 }
-            },
+            }
+            5 => {
+                // GO TO KIOSK SETTINGS
+                if input_state.select {
+                    *current_screen = Screen::KioskSettings;
+                    *settings_menu_selection = 0;
+                    sound_effects.play_select(&config);
+                }
+            }
+            _ => {}
+        },
+        // KIOSK
+        5 => match settings_menu_selection {
+            0 => {
+                // ATTRACT MODE
+                if input_state.left || input_state.right {
+                    config.kiosk_attract_minutes =
+                        cycle_timeout(config.kiosk_attract_minutes, input_state.right);
+                    config.save();
+                    sound_effects.play_cursor_move(&config);
+                }
+            }
+            1 => {
+                // SLIDE DURATION
+                if input_state.left || input_state.right {
+                    config.kiosk_slide_seconds =
+                        cycle_slide_duration(config.kiosk_slide_seconds, input_state.right);
+                    config.save();
+                    sound_effects.play_cursor_move(&config);
+                }
+            }
+            2 => {
+                // ATTRACT MUSIC
+                if input_state.left || input_state.right {
+                    config.kiosk_attract_music = cycle_attract_music(
+                        &config.kiosk_attract_music,
+                        bgm_choices,
+                        input_state.right,
+                    );
+                    // Preview the track, then go back to the menu music when it's deselected
+                    let preview = if config.kiosk_attract_music == ATTRACT_MUSIC_MENU {
+                        config.bgm_track.clone().unwrap_or("OFF".to_string())
+                    } else {
+                        config.kiosk_attract_music.clone()
+                    };
+                    play_new_bgm(&preview, config.bgm_volume, &music_cache, current_bgm);
+                    config.save();
+                    sound_effects.play_cursor_move(&config);
+                }
+            }
+            3 => {
+                // GAME WHITELIST
+                if input_state.left || input_state.right || input_state.select {
+                    config.kiosk_whitelist_enabled = !config.kiosk_whitelist_enabled;
+                    config.save();
+                    sound_effects.play_cursor_move(&config);
+                }
+            }
+            4 => {
+                // WHITELISTED GAMES
+                if input_state.select {
+                    *current_screen = Screen::KioskWhitelist;
+                    sound_effects.play_select(&config);
+                }
+            }
+            5 => {
+                // REMOTE CONTROL
+                if input_state.left || input_state.right || input_state.select {
+                    config.remote_enabled = !config.remote_enabled;
+                    config.save();
+                    sound_effects.play_cursor_move(&config);
+                }
+            }
+            6 => {
+                // CONFIRM REMOTE LAUNCH
+                if input_state.left || input_state.right || input_state.select {
+                    config.remote_confirm_launch = !config.remote_confirm_launch;
+                    config.save();
+                    sound_effects.play_cursor_move(&config);
+                }
+            }
+            7 => {
+                // CONFIRM REMOTE POWER
+                if input_state.left || input_state.right || input_state.select {
+                    config.remote_confirm_power = !config.remote_confirm_power;
+                    config.save();
+                    sound_effects.play_cursor_move(&config);
+                }
+            }
+            8 => {
+                // PAIR DEVICE
+                if input_state.select && config.remote_enabled {
+                    remote::start_pairing(&config);
+                    sound_effects.play_select(&config);
+                } else if input_state.select {
+                    sound_effects.play_reject(&config);
+                }
+            }
+            9 => {
+                // PAIRED DEVICES
+                if input_state.select {
+                    *current_screen = Screen::RemoteDevices;
+                    sound_effects.play_select(&config);
+                }
+            }
+            10 => {
+                // GO TO CUSTOM ASSETS SETTINGS
+                if input_state.select {
+                    *current_screen = Screen::AssetSettings;
+                    *settings_menu_selection = 0;
+                    sound_effects.play_select(&config);
+                }
+            }
             _ => {}
         },
         _ => {}
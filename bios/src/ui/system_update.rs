@@ -0,0 +1,534 @@
+use macroquad::prelude::*;
+use std::collections::HashMap;
+use std::process::Command;
+use std::sync::mpsc::Receiver;
+
+use crate::{
+    audio::SoundEffects,
+    config::Config,
+    get_current_font, measure_text, render_background, render_ui_overlay, text_with_config_color,
+    types::{AnimationState, BackgroundState, BatteryInfo, Screen},
+    ui::text_with_color,
+    updater::{self, DeploymentStatus, UpdateManifest, UpdaterMessage},
+    wrap_text, InputState, VideoPlayer, DEV_MODE, FONT_SIZE, MENU_OPTION_HEIGHT, MENU_PADDING,
+    VERSION_NUMBER,
+};
+
+const SYSTEM_UPDATE_OPTIONS: &[&str] = &["CHANNEL", "CHECK FOR UPDATES", "ROLLBACK"];
+
+pub enum UpdatePhase {
+    Idle,
+    /// No manifest URL in the config, so update checks are off
+    NotConfigured,
+    Checking,
+    UpToDate,
+    Available(UpdateManifest),
+    /// Stage text and download progress (0.0 - 1.0) when known
+    Working(String, Option<f32>),
+    /// Reboot required; holds the message to show
+    Done(String),
+    Error(String),
+}
+
+/// State for the System Update settings screen
+pub struct SystemUpdateState {
+    pub selection: usize,
+    pub phase: UpdatePhase,
+    pub deployments: DeploymentStatus,
+    pub confirm_rollback: bool,
+    pub changelog_scroll: usize,
+    max_changelog_scroll: usize,
+    rx: Option<Receiver<UpdaterMessage>>,
+}
+
+impl SystemUpdateState {
+    pub fn new() -> Self {
+        Self {
+            selection: 0,
+            phase: UpdatePhase::Idle,
+            deployments: DeploymentStatus::default(),
+            confirm_rollback: false,
+            changelog_scroll: 0,
+            max_changelog_scroll: 0,
+            rx: None,
+        }
+    }
+
+    fn is_busy(&self) -> bool {
+        matches!(self.phase, UpdatePhase::Checking | UpdatePhase::Working(..))
+    }
+
+    fn start_check(&mut self, config: &Config) {
+        if config.update_manifest_url.trim().is_empty() {
+            self.phase = UpdatePhase::NotConfigured;
+            return;
+        }
+        let url = updater::manifest_url(&config.update_manifest_url, &config.update_channel);
+        self.rx = Some(updater::check(url));
+        self.phase = UpdatePhase::Checking;
+        self.changelog_scroll = 0;
+        self.max_changelog_scroll = 0;
+    }
+
+    /// Applies messages from the background updater thread
+    fn poll(&mut self) {
+        let Some(rx) = &self.rx else { return };
+
+        while let Ok(msg) = rx.try_recv() {
+            match msg {
+                UpdaterMessage::Checked(Ok(Some(manifest))) => {
+                    self.phase = UpdatePhase::Available(manifest)
+                }
+                UpdaterMessage::Checked(Ok(None)) => self.phase = UpdatePhase::UpToDate,
+                UpdaterMessage::Checked(Err(e)) => self.phase = UpdatePhase::Error(e),
+                UpdaterMessage::Deployments(status) => self.deployments = status,
+                UpdaterMessage::Progress(stage, progress) => {
+                    self.phase = UpdatePhase::Working(stage, progress)
+                }
+                UpdaterMessage::Installed(version) => {
+                    self.phase = UpdatePhase::Done(format!("Version {} installed.", version));
+                }
+                UpdaterMessage::RolledBack(deployment) => {
+                    let target = if deployment.is_empty() {
+                        "previous version".to_string()
+                    } else {
+                        deployment
+                    };
+                    self.phase = UpdatePhase::Done(format!("Rolled back to {}.", target));
+                }
+                UpdaterMessage::Failed(e) => self.phase = UpdatePhase::Error(e),
+            }
+        }
+    }
+}
+
+pub fn update(
+    current_screen: &mut Screen,
+    state: &mut SystemUpdateState,
+    input_state: &InputState,
+    animation_state: &mut AnimationState,
+    sound_effects: &SoundEffects,
+    config: &mut Config,
+) {
+    state.poll();
+
+    // Nothing else to do once an update or rollback is staged
+    if let UpdatePhase::Done(_) = state.phase {
+        if input_state.select {
+            sound_effects.play_select(config);
+            if DEV_MODE {
+                println!("[DEV_MODE] Skipping reboot.");
+            } else {
+                Command::new("sudo").arg("reboot").status().ok();
+            }
+        }
+        return;
+    }
+
+    // Don't let the user leave while a download or deploy is running
+    if state.is_busy() {
+        return;
+    }
+
+    if state.confirm_rollback {
+        if input_state.select {
+            sound_effects.play_select(config);
+            state.confirm_rollback = false;
+            state.rx = Some(updater::rollback());
+            state.phase = UpdatePhase::Working("Rolling back...".to_string(), None);
+        } else if input_state.back {
+            sound_effects.play_back(config);
+            state.confirm_rollback = false;
+        }
+        return;
+    }
+
+    if input_state.back {
+        *current_screen = Screen::GeneralSettings;
+        // Check again next time the screen is opened
+        if !matches!(state.phase, UpdatePhase::Error(_)) {
+            state.phase = UpdatePhase::Idle;
+        }
+        sound_effects.play_back(config);
+        return;
+    }
+
+    // Opening the screen starts a check
+    if let UpdatePhase::Idle = state.phase {
+        state.start_check(config);
+        return;
+    }
+
+    if input_state.up {
+        state.selection = if state.selection == 0 {
+            SYSTEM_UPDATE_OPTIONS.len() - 1
+        } else {
+            state.selection - 1
+        };
+        animation_state.trigger_transition(&config.cursor_transition_speed);
+        sound_effects.play_cursor_move(config);
+    }
+    if input_state.down {
+        state.selection = (state.selection + 1) % SYSTEM_UPDATE_OPTIONS.len();
+        animation_state.trigger_transition(&config.cursor_transition_speed);
+        sound_effects.play_cursor_move(config);
+    }
+
+    // Scroll the changelog with the shoulder buttons
+    if input_state.next && state.changelog_scroll < state.max_changelog_scroll {
+        state.changelog_scroll += 1;
+        sound_effects.play_cursor_move(config);
+    }
+    if input_state.prev && state.changelog_scroll > 0 {
+        state.changelog_scroll -= 1;
+        sound_effects.play_cursor_move(config);
+    }
+
+    match state.selection {
+        0 => {
+            // CHANNEL
+            if input_state.left || input_state.right {
+                config.update_channel =
+                    updater::cycle_channel(&config.update_channel, input_state.right);
+                config.save();
+                sound_effects.play_cursor_move(config);
+                state.start_check(config);
+            }
+        }
+        1 => {
+            // CHECK FOR UPDATES / INSTALL
+            if input_state.select {
+                sound_effects.play_select(config);
+                if let UpdatePhase::Available(manifest) = &state.phase {
+                    println!(
+                        "[Updater] Installing {} from {} channel",
+                        manifest.version, config.update_channel
+                    );
+                    state.rx = Some(updater::install(manifest.clone()));
+                    state.phase = UpdatePhase::Working("Starting update...".to_string(), None);
+                } else {
+                    state.start_check(config);
+                }
+            }
+        }
+        2 => {
+            // ROLLBACK
+            if input_state.select && state.deployments.previous.is_some() {
+                sound_effects.play_select(config);
+                state.confirm_rollback = true;
+            }
+        }
+        _ => {}
+    }
+}
+
+fn get_option_value(index: usize, state: &SystemUpdateState, config: &Config) -> String {
+    match index {
+        0 => config.update_channel.to_uppercase(),
+        1 => match &state.phase {
+            UpdatePhase::Available(manifest) => format!("INSTALL {}", manifest.version),
+            UpdatePhase::Checking => "CHECKING...".to_string(),
+            _ => "CHECK".to_string(),
+        },
+        2 => match &state.deployments.previous {
+            Some(previous) => previous.to_uppercase(),
+            None => "UNAVAILABLE".to_string(),
+        },
+        _ => String::new(),
+    }
+}
+
+/// Draws the System Update settings UI.
+pub fn draw(
+    state: &mut SystemUpdateState,
+    animation_state: &AnimationState,
+    logo_cache: &HashMap<String, Texture2D>,
+    background_cache: &HashMap<String, Texture2D>,
+    video_cache: &mut HashMap<String, VideoPlayer>,
+    font_cache: &HashMap<String, Font>,
+    config: &Config,
+    background_state: &mut BackgroundState,
+    battery_info: &Option<BatteryInfo>,
+    current_time_str: &str,
+    gcc_adapter_poll_rate: &Option<u32>,
+    scale_factor: f32,
+) {
+    render_background(background_cache, video_cache, config, background_state);
+
+    // Dim the background for easier legibility
+    draw_rectangle(
+        0.0,
+        0.0,
+        screen_width(),
+        screen_height(),
+        Color::new(0.0, 0.0, 0.0, 0.6),
+    );
+
+    render_ui_overlay(
+        logo_cache,
+        font_cache,
+        config,
+        battery_info,
+        current_time_str,
+        gcc_adapter_poll_rate,
+        scale_factor,
+    );
+
+    let font_size = (FONT_SIZE as f32 * scale_factor) as u16;
+    let large_font_size = (FONT_SIZE as f32 * scale_factor * 1.5) as u16;
+    let menu_padding = MENU_PADDING * scale_factor;
+    let menu_option_height = MENU_OPTION_HEIGHT * scale_factor;
+    let line_height = font_size as f32 * 1.5;
+    let current_font = get_current_font(font_cache, config);
+
+    // Title
+    let title = "SYSTEM UPDATE";
+    let title_dims = measure_text(title, Some(current_font), large_font_size, 1.0);
+    let title_y = 50.0 * scale_factor;
+    text_with_config_color(
+        font_cache,
+        config,
+        title,
+        screen_width() / 2.0 - title_dims.width / 2.0,
+        title_y,
+        large_font_size,
+    );
+
+    // Running version
+    let version_text = match &state.deployments.current {
+        Some(deployment) => format!("Current version: {} ({})", VERSION_NUMBER, deployment),
+        None => format!("Current version: {}", VERSION_NUMBER),
+    };
+    let version_dims = measure_text(&version_text, Some(current_font), font_size, 1.0);
+    text_with_config_color(
+        font_cache,
+        config,
+        &version_text,
+        screen_width() / 2.0 - version_dims.width / 2.0,
+        title_y + 25.0 * scale_factor,
+        font_size,
+    );
+
+    // Menu options
+    let start_y = 100.0 * scale_factor;
+    let left_margin = 80.0 * scale_factor;
+    let right_margin = 80.0 * scale_factor;
+
+    for (i, &label) in SYSTEM_UPDATE_OPTIONS.iter().enumerate() {
+        let y_pos = start_y + (i as f32 * menu_option_height);
+        let is_selected = i == state.selection;
+
+        let value = get_option_value(i, state, config);
+        let value_dims = measure_text(&value, Some(current_font), font_size, 1.0);
+        let value_x = screen_width() - value_dims.width - right_margin;
+        let text_y = y_pos + menu_option_height / 2.0 + font_size as f32 * 0.3;
+
+        if is_selected && config.cursor_style == "BOX" {
+            let cursor_color = animation_state.get_cursor_color(config);
+            let cursor_scale = animation_state.get_cursor_scale();
+
+            let base_width = value_dims.width + (menu_padding * 2.0);
+            let base_height = value_dims.height + (menu_padding * 2.0);
+            let scaled_width = base_width * cursor_scale;
+            let scaled_height = base_height * cursor_scale;
+            let offset_x = (scaled_width - base_width) / 2.0;
+            let offset_y = (scaled_height - base_height) / 2.0;
+
+            let rect_x = value_x - menu_padding;
+            let rect_y = y_pos + (menu_option_height / 2.0) - (base_height / 2.0);
+
+            draw_rectangle_lines(
+                rect_x - offset_x,
+                rect_y - offset_y,
+                scaled_width,
+                scaled_height,
+                4.0 * scale_factor,
+                cursor_color,
+            );
+        }
+
+        text_with_config_color(font_cache, config, label, left_margin, text_y, font_size);
+
+        if is_selected && config.cursor_style == "TEXT" {
+            let highlight_color = animation_state.get_cursor_color(config);
+            text_with_color(
+                font_cache,
+                config,
+                &value,
+                value_x,
+                text_y,
+                font_size,
+                highlight_color,
+            );
+        } else {
+            text_with_config_color(font_cache, config, &value, value_x, text_y, font_size);
+        }
+    }
+
+    // Details area below the options: changelog, progress or result
+    let area_x = left_margin;
+    let area_w = screen_width() - left_margin - right_margin;
+    let area_top =
+        start_y + SYSTEM_UPDATE_OPTIONS.len() as f32 * menu_option_height + 20.0 * scale_factor;
+    let area_bottom = screen_height() - 50.0 * scale_factor;
+
+    match &state.phase {
+        UpdatePhase::Available(manifest) => {
+            let heading = format!("WHAT'S NEW IN {}", manifest.version);
+            text_with_config_color(font_cache, config, &heading, area_x, area_top, font_size);
+
+            let changelog = if manifest.changelog.trim().is_empty() {
+                "No changelog provided."
+            } else {
+                manifest.changelog.trim()
+            };
+            let lines = wrap_text(changelog, current_font.clone(), font_size, area_w);
+            let body_top = area_top + line_height;
+            let visible_lines = ((area_bottom - body_top) / line_height).floor().max(1.0) as usize;
+
+            state.max_changelog_scroll = lines.len().saturating_sub(visible_lines);
+            state.changelog_scroll = state.changelog_scroll.min(state.max_changelog_scroll);
+
+            for (i, line) in lines
+                .iter()
+                .skip(state.changelog_scroll)
+                .take(visible_lines)
+                .enumerate()
+            {
+                text_with_config_color(
+                    font_cache,
+                    config,
+                    line,
+                    area_x,
+                    body_top + i as f32 * line_height,
+                    font_size,
+                );
+            }
+        }
+        UpdatePhase::Working(stage, progress) => {
+            text_with_config_color(font_cache, config, stage, area_x, area_top, font_size);
+
+            if let Some(progress) = progress {
+                let bar_y = area_top + line_height;
+                let bar_h = 12.0 * scale_factor;
+                draw_rectangle(area_x, bar_y, area_w, bar_h, Color::new(1.0, 1.0, 1.0, 0.2));
+                draw_rectangle(
+                    area_x,
+                    bar_y,
+                    area_w * progress.clamp(0.0, 1.0),
+                    bar_h,
+                    animation_state.get_cursor_color(config),
+                );
+
+                let percent = format!("{:.0}%", progress * 100.0);
+                text_with_config_color(
+                    font_cache,
+                    config,
+                    &percent,
+                    area_x,
+                    bar_y + bar_h + line_height,
+                    font_size,
+                );
+            }
+        }
+        UpdatePhase::NotConfigured => {
+            let message =
+                "Update checks are off. Set update_manifest_url in config.toml to turn them on.";
+            for (i, line) in wrap_text(message, current_font.clone(), font_size, area_w)
+                .iter()
+                .enumerate()
+            {
+                text_with_config_color(
+                    font_cache,
+                    config,
+                    line,
+                    area_x,
+                    area_top + i as f32 * line_height,
+                    font_size,
+                );
+            }
+        }
+        UpdatePhase::UpToDate => {
+            text_with_config_color(
+                font_cache,
+                config,
+                "You are running the latest version.",
+                area_x,
+                area_top,
+                font_size,
+            );
+        }
+        UpdatePhase::Checking | UpdatePhase::Idle => {
+            text_with_config_color(
+                font_cache,
+                config,
+                "Checking for updates...",
+                area_x,
+                area_top,
+                font_size,
+            );
+        }
+        UpdatePhase::Done(message) => {
+            text_with_color(
+                font_cache, config, message, area_x, area_top, font_size, GREEN,
+            );
+            text_with_config_color(
+                font_cache,
+                config,
+                "Press [SOUTH] to reboot.",
+                area_x,
+                area_top + line_height,
+                font_size,
+            );
+        }
+        UpdatePhase::Error(e) => {
+            text_with_color(
+                font_cache,
+                config,
+                "An error occurred:",
+                area_x,
+                area_top,
+                font_size,
+                RED,
+            );
+            for (i, line) in wrap_text(e, current_font.clone(), font_size, area_w)
+                .iter()
+                .take(3)
+                .enumerate()
+            {
+                text_with_config_color(
+                    font_cache,
+                    config,
+                    line,
+                    area_x,
+                    area_top + (i + 1) as f32 * line_height,
+                    font_size,
+                );
+            }
+        }
+    }
+
+    // Rollback confirmation / instructions
+    let instructions = if state.confirm_rollback {
+        "Roll back to the previous version? [SOUTH] Confirm, [EAST] Cancel"
+    } else if state.is_busy() {
+        "Please wait, do not turn off the system."
+    } else {
+        "LEFT/RIGHT: Change channel, L/R: Scroll changelog"
+    };
+    let inst_dims = measure_text(instructions, Some(current_font), font_size, 1.0);
+    let inst_color = if state.confirm_rollback {
+        YELLOW
+    } else {
+        Color::new(0.7, 0.7, 0.7, 1.0)
+    };
+    text_with_color(
+        font_cache,
+        config,
+        instructions,
+        screen_width() / 2.0 - inst_dims.width / 2.0,
+        screen_height() - 20.0 * scale_factor,
+        font_size,
+        inst_color,
+    );
+}
@@ -0,0 +1,491 @@
+use macroquad::prelude::*;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::process::Command;
+
+use crate::{
+    audio::SoundEffects,
+    config::Config,
+    get_current_font, measure_text, render_background, render_ui_overlay, text_with_config_color,
+    types::{AnimationState, BackgroundState, BatteryInfo, Screen},
+    ui::text_with_color,
+    InputState, VideoPlayer, FONT_SIZE, MENU_OPTION_HEIGHT,
+};
+
+/// Games shown at once
+const VISIBLE_GAMES: usize = 6;
+/// Recent unlocks asked for and shown
+const RECENT_UNLOCKS: usize = 6;
+/// Mastered set badges shown in the cabinet row
+const MAX_BADGES: usize = 12;
+
+const GOLD: Color = Color::new(1.0, 0.8, 0.2, 1.0);
+
+/// A game's progress, from `kazeta-ra trophies`
+#[derive(Deserialize, Clone, Debug)]
+struct TrophyGame {
+    title: String,
+    #[serde(default)]
+    console: Option<String>,
+    earned: u32,
+    total: u32,
+    mastered: bool,
+    hardcore: bool,
+    #[serde(default)]
+    icon_path: Option<String>,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+struct RecentUnlock {
+    title: String,
+    points: u32,
+    hardcore: bool,
+    #[serde(default)]
+    game_title: Option<String>,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+struct TrophyData {
+    points_earned: u32,
+    points_total: u32,
+    mastered: usize,
+    games: Vec<TrophyGame>,
+    recent: Vec<RecentUnlock>,
+}
+
+/// State for the Trophy Room screen
+pub struct TrophyRoomState {
+    data: Option<TrophyData>,
+    error: Option<String>,
+    selection: usize,
+    scroll_offset: usize,
+    /// Icons of mastered games, by path
+    badges: HashMap<String, Texture2D>,
+}
+
+impl TrophyRoomState {
+    pub fn new() -> Self {
+        Self {
+            data: None,
+            error: None,
+            selection: 0,
+            scroll_offset: 0,
+            badges: HashMap::new(),
+        }
+    }
+
+    /// Reads the RA cache before showing the screen. kazeta-ra only looks at
+    /// its local database here, so this works offline.
+    pub fn open(&mut self) {
+        let badges = std::mem::take(&mut self.badges);
+        *self = Self::new();
+        self.badges = badges;
+
+        match load_trophies() {
+            Ok(data) => {
+                self.load_badges(&data);
+                self.data = Some(data);
+            }
+            Err(e) => {
+                println!("[Trophies] {}", e);
+                self.error = Some(e);
+            }
+        }
+    }
+
+    fn load_badges(&mut self, data: &TrophyData) {
+        let paths = data
+            .games
+            .iter()
+            .filter(|g| g.mastered)
+            .filter_map(|g| g.icon_path.as_ref());
+        for path in paths.take(MAX_BADGES) {
+            if self.badges.contains_key(path) {
+                continue;
+            }
+            let image = std::fs::read(path)
+                .map_err(|e| e.to_string())
+                .and_then(|bytes| {
+                    Image::from_file_with_format(&bytes, None).map_err(|e| e.to_string())
+                });
+            match image {
+                Ok(image) => {
+                    let texture = Texture2D::from_image(&image);
+                    texture.set_filter(FilterMode::Linear);
+                    self.badges.insert(path.clone(), texture);
+                }
+                Err(e) => println!("[Trophies] Couldn't load badge {}: {}", path, e),
+            }
+        }
+    }
+
+    fn game_count(&self) -> usize {
+        self.data.as_ref().map_or(0, |d| d.games.len())
+    }
+}
+
+fn load_trophies() -> Result<TrophyData, String> {
+    let output = Command::new("kazeta-ra")
+        .args(["--output", "json", "trophies", "--recent"])
+        .arg(RECENT_UNLOCKS.to_string())
+        .output()
+        .map_err(|e| format!("Failed to run kazeta-ra: {}", e))?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if !output.status.success() {
+        let message = serde_json::from_str::<serde_json::Value>(&stdout)
+            .ok()
+            .and_then(|v| v["error"].as_str().map(str::to_string))
+            .unwrap_or_else(|| String::from_utf8_lossy(&output.stderr).trim().to_string());
+        return Err(message);
+    }
+    serde_json::from_str(&stdout).map_err(|e| format!("Unexpected kazeta-ra output: {}", e))
+}
+
+pub fn update(
+    current_screen: &mut Screen,
+    state: &mut TrophyRoomState,
+    input_state: &InputState,
+    animation_state: &mut AnimationState,
+    sound_effects: &SoundEffects,
+    config: &Config,
+) {
+    if input_state.back {
+        *current_screen = Screen::BladesDashboard;
+        sound_effects.play_back(config);
+        return;
+    }
+
+    let count = state.game_count();
+    if count == 0 {
+        return;
+    }
+    if input_state.up && state.selection > 0 {
+        state.selection -= 1;
+        animation_state.trigger_transition(&config.cursor_transition_speed);
+        sound_effects.play_cursor_move(config);
+    }
+    if input_state.down && state.selection < count - 1 {
+        state.selection += 1;
+        animation_state.trigger_transition(&config.cursor_transition_speed);
+        sound_effects.play_cursor_move(config);
+    }
+    if state.selection < state.scroll_offset {
+        state.scroll_offset = state.selection;
+    } else if state.selection >= state.scroll_offset + VISIBLE_GAMES {
+        state.scroll_offset = state.selection + 1 - VISIBLE_GAMES;
+    }
+}
+
+/// Draws the Trophy Room: totals, the mastered sets, per-game completion
+/// bars and the latest unlocks.
+pub fn draw(
+    state: &TrophyRoomState,
+    animation_state: &AnimationState,
+    logo_cache: &HashMap<String, Texture2D>,
+    background_cache: &HashMap<String, Texture2D>,
+    video_cache: &mut HashMap<String, VideoPlayer>,
+    font_cache: &HashMap<String, Font>,
+    config: &Config,
+    background_state: &mut BackgroundState,
+    battery_info: &Option<BatteryInfo>,
+    current_time_str: &str,
+    gcc_adapter_poll_rate: &Option<u32>,
+    scale_factor: f32,
+) {
+    render_background(background_cache, video_cache, config, background_state);
+
+    // Dim the background for easier legibility
+    draw_rectangle(
+        0.0,
+        0.0,
+        screen_width(),
+        screen_height(),
+        Color::new(0.0, 0.0, 0.0, 0.6),
+    );
+
+    render_ui_overlay(
+        logo_cache,
+        font_cache,
+        config,
+        battery_info,
+        current_time_str,
+        gcc_adapter_poll_rate,
+        scale_factor,
+    );
+
+    let font_size = (FONT_SIZE as f32 * scale_factor) as u16;
+    let small_font_size = (FONT_SIZE as f32 * scale_factor * 0.75) as u16;
+    let large_font_size = (FONT_SIZE as f32 * scale_factor * 1.5) as u16;
+    let row_height = MENU_OPTION_HEIGHT * scale_factor;
+    let current_font = get_current_font(font_cache, config);
+    let dim = Color::new(0.7, 0.7, 0.7, 1.0);
+
+    let centered = |text: &str, y: f32, size: u16, color: Option<Color>| {
+        let dims = measure_text(text, Some(current_font), size, 1.0);
+        let x = screen_width() / 2.0 - dims.width / 2.0;
+        match color {
+            Some(color) => text_with_color(font_cache, config, text, x, y, size, color),
+            None => text_with_config_color(font_cache, config, text, x, y, size),
+        }
+    };
+
+    let title_y = 50.0 * scale_factor;
+    centered("TROPHY ROOM", title_y, large_font_size, None);
+    centered(
+        "B: BACK",
+        screen_height() - 20.0 * scale_factor,
+        font_size,
+        Some(dim),
+    );
+
+    let data = match (&state.data, &state.error) {
+        (Some(data), _) if !data.games.is_empty() => data,
+        (_, Some(error)) => {
+            centered(
+                &error.to_uppercase(),
+                screen_height() / 2.0,
+                font_size,
+                Some(RED),
+            );
+            return;
+        }
+        _ => {
+            centered(
+                "NO RETROACHIEVEMENTS GAMES PLAYED YET",
+                screen_height() / 2.0,
+                font_size,
+                Some(dim),
+            );
+            return;
+        }
+    };
+
+    let summary = format!(
+        "{} / {} POINTS  -  {} OF {} SETS MASTERED",
+        data.points_earned,
+        data.points_total,
+        data.mastered,
+        data.games.len()
+    );
+    centered(
+        &summary,
+        title_y + 25.0 * scale_factor,
+        font_size,
+        Some(dim),
+    );
+
+    // Cabinet of mastered sets
+    let badge_size = 26.0 * scale_factor;
+    let badge_gap = 6.0 * scale_factor;
+    let badge_y = title_y + 38.0 * scale_factor;
+    let mastered: Vec<&TrophyGame> = data
+        .games
+        .iter()
+        .filter(|g| g.mastered)
+        .take(MAX_BADGES)
+        .collect();
+    let row_width = mastered.len() as f32 * (badge_size + badge_gap) - badge_gap;
+    let mut badge_x = screen_width() / 2.0 - row_width / 2.0;
+    for game in &mastered {
+        let border = if game.hardcore { GOLD } else { LIGHTGRAY };
+        match game.icon_path.as_ref().and_then(|p| state.badges.get(p)) {
+            Some(texture) => draw_texture_ex(
+                texture,
+                badge_x,
+                badge_y,
+                WHITE,
+                DrawTextureParams {
+                    dest_size: Some(vec2(badge_size, badge_size)),
+                    ..Default::default()
+                },
+            ),
+            None => {
+                draw_rectangle(
+                    badge_x,
+                    badge_y,
+                    badge_size,
+                    badge_size,
+                    Color::new(0.2, 0.2, 0.25, 1.0),
+                );
+                let initial: String = game.title.chars().take(1).collect();
+                let dims = measure_text(&initial, Some(current_font), font_size, 1.0);
+                text_with_color(
+                    font_cache,
+                    config,
+                    &initial,
+                    badge_x + (badge_size - dims.width) / 2.0,
+                    badge_y + badge_size / 2.0 + dims.height / 2.0,
+                    font_size,
+                    border,
+                );
+            }
+        }
+        draw_rectangle_lines(
+            badge_x,
+            badge_y,
+            badge_size,
+            badge_size,
+            2.0 * scale_factor,
+            border,
+        );
+        badge_x += badge_size + badge_gap;
+    }
+    if mastered.is_empty() {
+        centered(
+            "NO MASTERED SETS YET",
+            badge_y + badge_size / 2.0,
+            small_font_size,
+            Some(dim),
+        );
+    }
+
+    // Per-game completion on the left, recent unlocks on the right
+    let list_y = badge_y + badge_size + 14.0 * scale_factor;
+    let left_margin = 40.0 * scale_factor;
+    let list_width = screen_width() * 0.58 - left_margin;
+    let bar_height = 5.0 * scale_factor;
+
+    for (row, (i, game)) in data
+        .games
+        .iter()
+        .enumerate()
+        .skip(state.scroll_offset)
+        .take(VISIBLE_GAMES)
+        .enumerate()
+    {
+        let y = list_y + row as f32 * row_height;
+        let is_selected = i == state.selection;
+
+        if is_selected {
+            let cursor_color = animation_state.get_cursor_color(config);
+            draw_rectangle_lines(
+                left_margin - 6.0 * scale_factor,
+                y - 2.0 * scale_factor,
+                list_width + 12.0 * scale_factor,
+                row_height - 2.0 * scale_factor,
+                2.0 * scale_factor,
+                cursor_color,
+            );
+        }
+
+        let count = format!("{}/{}", game.earned, game.total);
+        let count_dims = measure_text(&count, Some(current_font), small_font_size, 1.0);
+        let text_y = y + small_font_size as f32;
+        let name = match &game.console {
+            Some(console) if is_selected => {
+                format!("{} ({})", game.title.to_uppercase(), console.to_uppercase())
+            }
+            _ => game.title.to_uppercase(),
+        };
+        let name_color = if game.mastered { GOLD } else { WHITE };
+        text_with_color(
+            font_cache,
+            config,
+            &name,
+            left_margin,
+            text_y,
+            small_font_size,
+            name_color,
+        );
+        text_with_color(
+            font_cache,
+            config,
+            &count,
+            left_margin + list_width - count_dims.width,
+            text_y,
+            small_font_size,
+            dim,
+        );
+
+        let bar_y = text_y + 5.0 * scale_factor;
+        let ratio = game.earned as f32 / game.total.max(1) as f32;
+        draw_rectangle(
+            left_margin,
+            bar_y,
+            list_width,
+            bar_height,
+            Color::new(0.2, 0.2, 0.2, 1.0),
+        );
+        draw_rectangle(
+            left_margin,
+            bar_y,
+            list_width * ratio,
+            bar_height,
+            if game.mastered { GOLD } else { SKYBLUE },
+        );
+    }
+
+    if data.games.len() > VISIBLE_GAMES {
+        if state.scroll_offset > 0 {
+            text_with_color(
+                font_cache,
+                config,
+                "^",
+                left_margin + list_width + 10.0 * scale_factor,
+                list_y + small_font_size as f32,
+                small_font_size,
+                dim,
+            );
+        }
+        if state.scroll_offset + VISIBLE_GAMES < data.games.len() {
+            text_with_color(
+                font_cache,
+                config,
+                "v",
+                left_margin + list_width + 10.0 * scale_factor,
+                list_y + VISIBLE_GAMES as f32 * row_height - 8.0 * scale_factor,
+                small_font_size,
+                dim,
+            );
+        }
+    }
+
+    let recent_x = screen_width() * 0.64;
+    text_with_config_color(
+        font_cache,
+        config,
+        "RECENT UNLOCKS",
+        recent_x,
+        list_y + small_font_size as f32,
+        small_font_size,
+    );
+    if data.recent.is_empty() {
+        text_with_color(
+            font_cache,
+            config,
+            "NONE YET",
+            recent_x,
+            list_y + row_height,
+            small_font_size,
+            dim,
+        );
+    }
+    for (i, unlock) in data.recent.iter().enumerate() {
+        let y = list_y + (i as f32 + 1.0) * row_height * 0.85;
+        let color = if unlock.hardcore { GOLD } else { WHITE };
+        text_with_color(
+            font_cache,
+            config,
+            &unlock.title.to_uppercase(),
+            recent_x,
+            y,
+            small_font_size,
+            color,
+        );
+        let detail = format!(
+            "{} - {} PTS",
+            unlock.game_title.as_deref().unwrap_or("").to_uppercase(),
+            unlock.points
+        );
+        text_with_color(
+            font_cache,
+            config,
+            &detail,
+            recent_x,
+            y + small_font_size as f32,
+            small_font_size,
+            dim,
+        );
+    }
+}
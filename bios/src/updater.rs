@@ -0,0 +1,539 @@
+use crate::{config::get_user_data_dir, DEV_MODE, VERSION_NUMBER};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::{
+    fs,
+    io::{Read, Write},
+    path::{Path, PathBuf},
+    process::Command,
+    sync::mpsc::{channel, Receiver, Sender},
+    thread,
+};
+
+/// Release channels a system can follow
+pub const UPDATE_CHANNELS: &[&str] = &["stable", "testing"];
+
+/// Privileged helper that deploys bundles with frzr and switches deployments
+const UPDATE_HELPER: &str = "/usr/bin/kazeta-update";
+
+/// Public key the helper verifies bundles against. Not shipped in the repo;
+/// whoever publishes releases installs it with the image.
+const UPDATE_SIGNING_KEY: &str = "/usr/share/kazeta/update-signing.pem";
+
+/// Release manifest published for a channel
+#[derive(Deserialize, Clone, Debug)]
+pub struct UpdateManifest {
+    pub version: String,
+    /// frzr system image (.img.tar.xz)
+    pub bundle_url: String,
+    /// Detached signature over the version and the bundle's SHA-256, made
+    /// with the update signing key. `kazeta-update` checks it as root against
+    /// the key shipped in rootfs, so an old bundle can't be replayed under a
+    /// newer version.
+    pub signature_url: String,
+    pub sha256: String,
+    #[serde(default)]
+    pub size: u64,
+    #[serde(default)]
+    pub changelog: String,
+}
+
+/// Deployments known to the helper (frzr keeps the current and previous one)
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DeploymentStatus {
+    pub current: Option<String>,
+    pub previous: Option<String>,
+}
+
+pub enum UpdaterMessage {
+    /// Manifest check finished; `None` means we're already on that version or newer
+    Checked(Result<Option<UpdateManifest>, String>),
+    Deployments(DeploymentStatus),
+    /// Stage description plus download progress (0.0 - 1.0) when known
+    Progress(String, Option<f32>),
+    /// Update deployed; takes effect after a reboot
+    Installed(String),
+    /// Previous deployment selected; takes effect after a reboot
+    RolledBack(String),
+    Failed(String),
+}
+
+/// Cycles between the available channels
+pub fn cycle_channel(current: &str, forward: bool) -> String {
+    let index = UPDATE_CHANNELS
+        .iter()
+        .position(|&c| c == current)
+        .unwrap_or(0);
+    let new_index = if forward {
+        (index + 1) % UPDATE_CHANNELS.len()
+    } else {
+        (index + UPDATE_CHANNELS.len() - 1) % UPDATE_CHANNELS.len()
+    };
+    UPDATE_CHANNELS[new_index].to_string()
+}
+
+/// Fills the `{channel}` placeholder of the configured manifest URL
+pub fn manifest_url(template: &str, channel: &str) -> String {
+    template.replace("{channel}", channel)
+}
+
+/// Numeric parts of a version like "V1.43.KAZETA+", without trailing zeros
+fn parse_version(version: &str) -> Option<Vec<u32>> {
+    let digits: String = version
+        .trim()
+        .trim_start_matches(['V', 'v'])
+        .chars()
+        .take_while(|c| c.is_ascii_digit() || *c == '.')
+        .collect();
+    let mut parts: Vec<u32> = digits
+        .split('.')
+        .filter(|part| !part.is_empty())
+        .map(|part| part.parse().ok())
+        .collect::<Option<_>>()?;
+    while parts.last() == Some(&0) {
+        parts.pop();
+    }
+    (!digits.is_empty()).then_some(parts)
+}
+
+/// Whether `candidate` is a newer release than `current`. Anything else,
+/// including a version that can't be read, is never offered.
+fn is_newer(candidate: &str, current: &str) -> Result<bool, String> {
+    let candidate = parse_version(candidate)
+        .ok_or_else(|| format!("Invalid update version '{}'", candidate))?;
+    Ok(parse_version(current).is_none_or(|current| candidate > current))
+}
+
+/// Manifest versions end up in file names and helper arguments, so only
+/// plain release names are accepted
+fn validate_version(version: &str) -> Result<(), String> {
+    let valid = !version.is_empty()
+        && version
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-')
+        && !version.starts_with(['.', '-'])
+        && !version.contains("..");
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("Invalid update version '{}'", version))
+    }
+}
+
+/// A version may be installed over `current` only if it's well-formed and newer
+fn ensure_installable(version: &str, current: &str) -> Result<(), String> {
+    validate_version(version)?;
+    if is_newer(version, current)? {
+        Ok(())
+    } else {
+        Err(format!(
+            "Refusing to install {}: not newer than {}",
+            version, current
+        ))
+    }
+}
+
+/// Updates can't be verified, so aren't offered, without a signing key
+fn ensure_signing_key(key: &Path) -> Result<(), String> {
+    if DEV_MODE || key.is_file() {
+        Ok(())
+    } else {
+        Err(format!(
+            "No update signing key configured ({} is missing)",
+            key.display()
+        ))
+    }
+}
+
+fn updates_dir() -> Result<PathBuf, String> {
+    let dir = get_user_data_dir()
+        .ok_or("Could not find user's data directory.")?
+        .join("updates");
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    Ok(dir)
+}
+
+fn http_client() -> Result<reqwest::blocking::Client, String> {
+    reqwest::blocking::Client::builder()
+        .user_agent("KazetaPlus-Updater")
+        .build()
+        .map_err(|e| e.to_string())
+}
+
+/// Fetches the channel manifest in the background
+pub fn check(url: String) -> Receiver<UpdaterMessage> {
+    let (tx, rx) = channel();
+    thread::spawn(move || {
+        println!("[Updater] Checking {}", url);
+        let result = ensure_signing_key(Path::new(UPDATE_SIGNING_KEY))
+            .and_then(|()| fetch_manifest(&url))
+            .and_then(|manifest| {
+                validate_version(&manifest.version)?;
+                if is_newer(&manifest.version, VERSION_NUMBER)? {
+                    Ok(Some(manifest))
+                } else {
+                    // Never downgrade, even to a validly signed older release
+                    println!(
+                        "[Updater] Ignoring {}: not newer than {}",
+                        manifest.version, VERSION_NUMBER
+                    );
+                    Ok(None)
+                }
+            });
+        tx.send(UpdaterMessage::Checked(result)).ok();
+        tx.send(UpdaterMessage::Deployments(deployment_status()))
+            .ok();
+    });
+    rx
+}
+
+fn fetch_manifest(url: &str) -> Result<UpdateManifest, String> {
+    let response = http_client()?
+        .get(url)
+        .send()
+        .map_err(|e| format!("Failed to reach update server: {}", e))?;
+
+    if !response.status().is_success() {
+        return Err(format!("Update server returned {}", response.status()));
+    }
+
+    response
+        .json::<UpdateManifest>()
+        .map_err(|e| format!("Invalid update manifest: {}", e))
+}
+
+/// Asks the helper which deployments exist
+pub fn deployment_status() -> DeploymentStatus {
+    match Command::new(UPDATE_HELPER).arg("status").output() {
+        Ok(out) if out.status.success() => parse_status(&String::from_utf8_lossy(&out.stdout)),
+        Ok(out) => {
+            println!(
+                "[Updater] Status helper failed: {}",
+                String::from_utf8_lossy(&out.stderr).trim()
+            );
+            DeploymentStatus::default()
+        }
+        Err(e) => {
+            println!("[Updater] Failed to run status helper: {}", e);
+            DeploymentStatus::default()
+        }
+    }
+}
+
+/// Parses `key=value` lines printed by `kazeta-update status`
+fn parse_status(output: &str) -> DeploymentStatus {
+    let mut status = DeploymentStatus::default();
+    for line in output.lines() {
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let value = value.trim();
+        if value.is_empty() {
+            continue;
+        }
+        match key.trim() {
+            "current" => status.current = Some(value.to_string()),
+            "previous" => status.previous = Some(value.to_string()),
+            _ => {}
+        }
+    }
+    status
+}
+
+/// Downloads, verifies and deploys an update in the background
+pub fn install(manifest: UpdateManifest) -> Receiver<UpdaterMessage> {
+    let (tx, rx) = channel();
+    thread::spawn(move || {
+        let version = manifest.version.clone();
+        let message = match download_and_apply(&manifest, &tx) {
+            Ok(()) => UpdaterMessage::Installed(version),
+            Err(e) => {
+                println!("[ERROR] Update to {} failed: {}", version, e);
+                UpdaterMessage::Failed(e)
+            }
+        };
+        tx.send(message).ok();
+    });
+    rx
+}
+
+fn download_and_apply(
+    manifest: &UpdateManifest,
+    tx: &Sender<UpdaterMessage>,
+) -> Result<(), String> {
+    ensure_signing_key(Path::new(UPDATE_SIGNING_KEY))?;
+    // Checked again here: the manifest may not have come through check()
+    ensure_installable(&manifest.version, VERSION_NUMBER)?;
+    let dir = updates_dir()?;
+    let bundle_path = dir.join(format!("kazeta-{}.img.tar.xz", manifest.version));
+    let signature_path = dir.join(format!("kazeta-{}.sig", manifest.version));
+
+    // Only keep one bundle around; they're large
+    for entry in fs::read_dir(&dir).map_err(|e| e.to_string())?.flatten() {
+        fs::remove_file(entry.path()).ok();
+    }
+
+    tx.send(UpdaterMessage::Progress(
+        "Downloading update...".to_string(),
+        Some(0.0),
+    ))
+    .ok();
+    let digest = download(&manifest.bundle_url, &bundle_path, manifest.size, tx)?;
+
+    tx.send(UpdaterMessage::Progress(
+        "Verifying update...".to_string(),
+        None,
+    ))
+    .ok();
+    verify_checksum(&bundle_path, &digest, &manifest.sha256)?;
+    download(&manifest.signature_url, &signature_path, 0, tx)?;
+
+    // The helper verifies the signature itself, on its own copy of the bundle
+    tx.send(UpdaterMessage::Progress(
+        "Applying update... Do not turn off.".to_string(),
+        None,
+    ))
+    .ok();
+    let result = run_helper(&[
+        "apply",
+        &bundle_path.to_string_lossy(),
+        &signature_path.to_string_lossy(),
+        &manifest.version,
+    ]);
+
+    fs::remove_file(&bundle_path).ok();
+    fs::remove_file(&signature_path).ok();
+    result.map(|_| ())
+}
+
+/// Deletes a downloaded bundle whose digest doesn't match the manifest
+fn verify_checksum(path: &Path, digest: &str, expected: &str) -> Result<(), String> {
+    if digest.eq_ignore_ascii_case(expected.trim()) {
+        return Ok(());
+    }
+    fs::remove_file(path).ok();
+    Err("Checksum mismatch - download corrupted".to_string())
+}
+
+/// Streams a URL to disk, reporting progress, and returns the SHA-256 hex digest
+fn download(
+    url: &str,
+    path: &Path,
+    expected_size: u64,
+    tx: &Sender<UpdaterMessage>,
+) -> Result<String, String> {
+    let mut response = http_client()?
+        .get(url)
+        .send()
+        .map_err(|e| format!("Download failed: {}", e))?;
+
+    if !response.status().is_success() {
+        return Err(format!("Download failed: {}", response.status()));
+    }
+
+    let total = response.content_length().unwrap_or(expected_size);
+    let mut file = fs::File::create(path)
+        .map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; 64 * 1024];
+    let mut downloaded: u64 = 0;
+    let mut last_reported = 0.0;
+
+    loop {
+        let n = response
+            .read(&mut buffer)
+            .map_err(|e| format!("Download interrupted: {}", e))?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buffer[..n]);
+        file.write_all(&buffer[..n])
+            .map_err(|e| format!("Failed to write update: {}", e))?;
+        downloaded += n as u64;
+
+        if total > 0 {
+            let progress = downloaded as f32 / total as f32;
+            // Don't flood the UI with a message per chunk
+            if progress - last_reported >= 0.01 {
+                last_reported = progress;
+                tx.send(UpdaterMessage::Progress(
+                    "Downloading update...".to_string(),
+                    Some(progress.min(1.0)),
+                ))
+                .ok();
+            }
+        }
+    }
+
+    file.sync_all()
+        .map_err(|e| format!("Failed to write update: {}", e))?;
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Switches back to the previous deployment in the background
+pub fn rollback() -> Receiver<UpdaterMessage> {
+    let (tx, rx) = channel();
+    thread::spawn(move || {
+        tx.send(UpdaterMessage::Progress(
+            "Rolling back...".to_string(),
+            None,
+        ))
+        .ok();
+        let message = match run_helper(&["rollback"]) {
+            Ok(output) => UpdaterMessage::RolledBack(output.trim().to_string()),
+            Err(e) => {
+                println!("[ERROR] Rollback failed: {}", e);
+                UpdaterMessage::Failed(e)
+            }
+        };
+        tx.send(message).ok();
+    });
+    rx
+}
+
+fn run_helper(args: &[&str]) -> Result<String, String> {
+    if DEV_MODE {
+        println!("[DEV_MODE] Skipping {} {}", UPDATE_HELPER, args.join(" "));
+        return Ok(String::new());
+    }
+
+    let output = Command::new("sudo")
+        .arg(UPDATE_HELPER)
+        .args(args)
+        .output()
+        .map_err(|e| format!("Failed to run update helper: {}", e))?;
+
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Err(stderr
+            .lines()
+            .last()
+            .unwrap_or("Update helper failed")
+            .to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_status() {
+        let status = parse_status("current=kazeta-1.43\nprevious=kazeta-1.42\n");
+        assert_eq!(status.current.as_deref(), Some("kazeta-1.43"));
+        assert_eq!(status.previous.as_deref(), Some("kazeta-1.42"));
+
+        // Fresh installs have no previous deployment; junk lines are skipped
+        let status = parse_status("current= kazeta-1.43 \nprevious=\nnoise\nother=x\n");
+        assert_eq!(
+            status,
+            DeploymentStatus {
+                current: Some("kazeta-1.43".to_string()),
+                previous: None
+            }
+        );
+        assert_eq!(parse_status(""), DeploymentStatus::default());
+    }
+
+    #[test]
+    fn test_cycle_channel() {
+        assert_eq!(cycle_channel("stable", true), "testing");
+        assert_eq!(cycle_channel("testing", true), "stable");
+        assert_eq!(cycle_channel("stable", false), "testing");
+        assert_eq!(cycle_channel("testing", false), "stable");
+        // Unknown channels start over from the first one
+        assert_eq!(cycle_channel("nightly", true), "testing");
+    }
+
+    #[test]
+    fn test_manifest_url() {
+        assert_eq!(
+            manifest_url("https://example.com/{channel}/manifest.json", "testing"),
+            "https://example.com/testing/manifest.json"
+        );
+        assert_eq!(
+            manifest_url("https://example.com/manifest.json", "stable"),
+            "https://example.com/manifest.json"
+        );
+    }
+
+    #[test]
+    fn test_only_newer_versions_are_offered() {
+        assert_eq!(parse_version("V1.43.KAZETA+"), Some(vec![1, 43]));
+        assert_eq!(parse_version("V1.43d.KAZETA+"), Some(vec![1, 43]));
+        assert_eq!(parse_version("1.43.0"), Some(vec![1, 43]));
+        assert_eq!(parse_version("KAZETA+"), None);
+
+        assert_eq!(is_newer("V1.44.KAZETA+", "V1.43.KAZETA+"), Ok(true));
+        assert_eq!(is_newer("V1.43.1", "V1.43.KAZETA+"), Ok(true));
+        assert_eq!(is_newer("V1.100", "V1.43.KAZETA+"), Ok(true));
+        assert_eq!(is_newer("V1.43.KAZETA+", "V1.43.KAZETA+"), Ok(false));
+        assert_eq!(is_newer("V1.43.0", "V1.43.KAZETA+"), Ok(false));
+        assert_eq!(
+            is_newer("V1.42.KAZETA+", "V1.43.KAZETA+"),
+            Ok(false),
+            "no downgrades"
+        );
+        assert!(is_newer("latest", "V1.43.KAZETA+").is_err());
+    }
+
+    #[test]
+    fn test_unsafe_versions_are_rejected() {
+        assert_eq!(validate_version("V1.44"), Ok(()));
+        assert_eq!(validate_version("1.44.0-rc1"), Ok(()));
+        for version in [
+            "",
+            "../1.44",
+            "1.44/../../etc",
+            "1..44",
+            "1.44 ",
+            "-1.44",
+            ".1.44",
+            "V1.44.KAZETA+",
+        ] {
+            assert!(validate_version(version).is_err(), "{:?}", version);
+        }
+
+        assert_eq!(ensure_installable("V1.44", "V1.43.KAZETA+"), Ok(()));
+        assert!(ensure_installable("V1.42", "V1.43.KAZETA+").is_err());
+        assert!(ensure_installable("V1.43", "V1.43.KAZETA+").is_err());
+        assert!(ensure_installable("1.44/../x", "V1.43.KAZETA+").is_err());
+    }
+
+    #[test]
+    fn test_checksum_mismatch_is_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let bundle = dir.path().join("kazeta-V1.44.img.tar.xz");
+        fs::write(&bundle, "image").unwrap();
+        let digest = format!("{:x}", Sha256::digest(b"image"));
+
+        assert_eq!(
+            verify_checksum(&bundle, &digest, &format!(" {} ", digest.to_uppercase())),
+            Ok(())
+        );
+        assert!(bundle.exists());
+
+        let err = verify_checksum(&bundle, &digest, &format!("{:x}", Sha256::digest(b"other")))
+            .unwrap_err();
+        assert_eq!(err, "Checksum mismatch - download corrupted");
+        assert!(!bundle.exists(), "corrupt bundle removed");
+    }
+
+    #[test]
+    fn test_updates_need_a_signing_key() {
+        let dir = tempfile::tempdir().unwrap();
+        let key = dir.path().join("update-signing.pem");
+        if !DEV_MODE {
+            let err = ensure_signing_key(&key).unwrap_err();
+            assert!(
+                err.starts_with("No update signing key configured"),
+                "{}",
+                err
+            );
+        }
+        fs::write(&key, "-----BEGIN PUBLIC KEY-----").unwrap();
+        assert_eq!(ensure_signing_key(&key), Ok(()));
+    }
+}
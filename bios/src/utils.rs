@@ -1,15 +1,16 @@
+use crate::audio::{play_new_bgm, SoundEffects};
+use crate::game_display::GameDisplaySettings;
+use crate::types::Screen;
+use crate::{config, save, thread, Arc, BufReader, Child, Mutex};
+use chrono::Local;
+use kazeta_overlay::{OverlayApi, OverlayClient, OverlayScreen, ToastStyle};
 use macroquad::prelude::*;
 use rodio::{buffer::SamplesBuffer, Sink};
+use std::collections::HashMap;
 use std::fs;
 use std::io::BufRead;
 use std::path::{Path, PathBuf};
 use std::process::Command;
-use std::collections::HashMap;
-use chrono::Local;
-use crate::{save, Child, Arc, Mutex, thread, BufReader, config};
-use crate::audio::play_new_bgm;
-use crate::types::Screen;
-use kazeta_overlay::{OverlayClient, OverlayScreen, ToastStyle};
 //use macroquad::audio::Sound;
 
 // wrap text in certain menus so it doesn't clip outside the screen
@@ -29,7 +30,8 @@ pub fn wrap_text(text: &str, font: Font, font_size: u16, max_width: f32) -> Vec<
         for word in paragraph.split_whitespace() {
             let word_width = measure_text(word, Some(&font), font_size, 1.0).width;
 
-            if !current_line.is_empty() && current_line_width + space_width + word_width > max_width {
+            if !current_line.is_empty() && current_line_width + space_width + word_width > max_width
+            {
                 lines.push(current_line);
                 current_line = String::new();
                 current_line_width = 0.0;
@@ -53,15 +55,16 @@ pub fn wrap_text(text: &str, font: Font, font_size: u16, max_width: f32) -> Vec<
 pub fn find_asset_files(dir_path: &str, extensions: &[&str]) -> Vec<PathBuf> {
     if let Ok(entries) = fs::read_dir(dir_path) {
         let mut files: Vec<PathBuf> = entries
-        .flatten()
-        .map(|e| e.path())
-        .filter(|path| {
-            path.is_file() &&
-            path.extension()
-            .and_then(|s| s.to_str())
-            .map_or(false, |ext| extensions.contains(&ext))
-        })
-        .collect();
+            .flatten()
+            .map(|e| e.path())
+            .filter(|path| {
+                path.is_file()
+                    && path
+                        .extension()
+                        .and_then(|s| s.to_str())
+                        .map_or(false, |ext| extensions.contains(&ext))
+            })
+            .collect();
         files.sort();
         return files;
     }
@@ -70,17 +73,19 @@ pub fn find_asset_files(dir_path: &str, extensions: &[&str]) -> Vec<PathBuf> {
 
 // Helper to read the first line from a file containing a specific key
 pub fn read_line_from_file(path: &str, key: &str) -> Option<String> {
-    fs::read_to_string(path).ok()?.lines()
-    .find(|line| line.starts_with(key))
-    .map(|line| line.replace(key, "").trim().to_string())
+    fs::read_to_string(path)
+        .ok()?
+        .lines()
+        .find(|line| line.starts_with(key))
+        .map(|line| line.replace(key, "").trim().to_string())
 }
 
 /// Calls a privileged helper script to copy session logs to the SD card.
 pub fn copy_session_logs_to_sd() -> Result<String, String> {
     let output = Command::new("sudo")
-    .arg("/usr/bin/kazeta-copy-logs")
-    .output()
-    .map_err(|e| format!("Failed to execute helper script: {}", e))?;
+        .arg("/usr/bin/kazeta-copy-logs")
+        .output()
+        .map_err(|e| format!("Failed to execute helper script: {}", e))?;
 
     if output.status.success() {
         // The script prints the destination path on success, so we can capture it.
@@ -95,11 +100,15 @@ pub fn copy_session_logs_to_sd() -> Result<String, String> {
 }
 
 // FOR ACTUAL HARDWARE USE
+/// Stops the BGM and starts the fade-out before the BIOS exits. With
+/// `farewell`, the farewell screen follows the fade (not wanted when the
+/// restart is only launching a game).
 pub fn trigger_session_restart(
     //current_bgm: &mut Option<Sound>,
     //music_cache: &HashMap<String, Sound>,
     current_bgm: &mut Option<Sink>,
     music_cache: &HashMap<String, SamplesBuffer>,
+    farewell: bool,
 ) -> (Screen, Option<f64>) {
     // Stop the BGM
     play_new_bgm("OFF", 0.0, music_cache, current_bgm);
@@ -114,7 +123,12 @@ pub fn trigger_session_restart(
     }
 
     // Return the state to begin the fade-out
-    (Screen::FadingOut, Some(get_time()))
+    let screen = if farewell {
+        Screen::ShuttingDown
+    } else {
+        Screen::FadingOut
+    };
+    (screen, Some(get_time()))
 }
 
 pub fn trigger_game_launch(
@@ -124,7 +138,11 @@ pub fn trigger_game_launch(
     //music_cache: &HashMap<String, Sound>,
     current_bgm: &mut Option<Sink>,
     music_cache: &HashMap<String, SamplesBuffer>,
+    sound_effects: &SoundEffects,
+    config: &config::Config,
 ) -> (Screen, Option<f64>) {
+    sound_effects.play_launch(config);
+
     // Start the overlay daemon before launching the game
     if let Err(e) = start_overlay_daemon() {
         eprintln!("[WARNING] Failed to start overlay daemon: {}", e);
@@ -135,14 +153,23 @@ pub fn trigger_game_launch(
     notify_game_started(
         &cart_info.id,
         cart_info.name.as_deref().unwrap_or(&cart_info.id),
-        cart_info.runtime.as_deref().unwrap_or("unknown")
+        cart_info.runtime.as_deref().unwrap_or("unknown"),
     );
 
+    // Button hints for the overlay's Game Controls screen
+    notify_game_controls(&cart_info.id, kzi_path);
+
+    // Let the input daemon switch off hotkeys this game uses itself
+    notify_input_game_started(&cart_info.id, &cart_info.suppressed_hotkeys);
+
     // Setup RetroAchievements if enabled
     setup_retroachievements(cart_info, kzi_path);
 
-    // Write the specific launch command for the selected game
-    if let Err(e) = save::write_launch_command(kzi_path) {
+    // Write the specific launch command for the selected game, along with its
+    // display settings and controller calibration
+    let mut launch_env = GameDisplaySettings::load(&cart_info.id).env_vars();
+    launch_env.extend(controller_calibration_env());
+    if let Err(e) = save::write_launch_command(kzi_path, &launch_env) {
         // If we fail, we should probably show an error on the debug screen
         // For now, we'll just print it for desktop debugging.
         println!("[ERROR] Failed to write launch command: {}", e);
@@ -150,7 +177,21 @@ pub fn trigger_game_launch(
 
     // Now, trigger the standard session restart process,
     // which will find and execute our command file.
-    trigger_session_restart(current_bgm, music_cache)
+    trigger_session_restart(current_bgm, music_cache, false)
+}
+
+/// Deadzones and stick offsets saved by the overlay's controller settings,
+/// for the wrappers as `KAZETA_CONTROLLER_CALIBRATION`. The file has one
+/// SDL-style line per controller; the launch command is a single line, so
+/// the entries are joined with ';'.
+fn controller_calibration_env() -> Option<(&'static str, String)> {
+    let path = config::get_user_data_dir()?.join("overlay/controller_calibration.mapping");
+    let mapping = fs::read_to_string(path).ok()?;
+    let entries: Vec<&str> = mapping
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .collect();
+    (!entries.is_empty()).then(|| ("KAZETA_CONTROLLER_CALIBRATION", entries.join(";")))
 }
 
 pub fn save_log_to_file(log_messages: &[String]) -> std::io::Result<String> {
@@ -205,7 +246,7 @@ pub fn string_to_color(color_str: &str) -> Color {
         "GREEN" => GREEN,
         "BLUE" => BLUE,
         "PURPLE" => VIOLET, // USING VIOLET AS A CLOSE APPROXIMATION
-        _ => WHITE, // Default to WHITE
+        _ => WHITE,         // Default to WHITE
     }
 }
 
@@ -223,7 +264,7 @@ pub fn apply_resolution(resolution_str: &str) {
 // ===================================
 // OVERLAY FUNCTIONS
 // ===================================
-// 
+//
 // These functions allow the BIOS to communicate with the overlay daemon.
 // The overlay daemon must be running (started when a game launches) for these to work.
 //
@@ -250,9 +291,9 @@ pub fn apply_resolution(resolution_str: &str) {
 
 /// Start the overlay daemon as a background process
 pub fn start_overlay_daemon() -> std::io::Result<()> {
-    use std::process::Stdio;
     use std::io::Write;
-    
+    use std::process::Stdio;
+
     // Clean up any stale socket file first
     let socket_path = Path::new("/tmp/kazeta-overlay.sock");
     if socket_path.exists() {
@@ -263,7 +304,7 @@ pub fn start_overlay_daemon() -> std::io::Result<()> {
                 // Set a short timeout
                 let _ = stream.set_write_timeout(Some(std::time::Duration::from_millis(100)));
                 let _ = stream.set_read_timeout(Some(std::time::Duration::from_millis(100)));
-                
+
                 // Try to send a status query - if this succeeds, daemon is alive
                 let test_msg = r#"{"type":"get_status"}"#;
                 if stream.write_all(test_msg.as_bytes()).is_ok() {
@@ -291,22 +332,24 @@ pub fn start_overlay_daemon() -> std::io::Result<()> {
             PathBuf::from("../../overlay/target/debug/kazeta-overlay"),
             PathBuf::from("overlay/target/debug/kazeta-overlay"),
         ];
-        
+
         if let Some(exe_path) = current_exe.as_ref() {
             if let Some(exe_dir) = exe_path.parent() {
                 possible_paths.push(exe_dir.join("../overlay/target/debug/kazeta-overlay"));
                 possible_paths.push(exe_dir.join("../../overlay/target/debug/kazeta-overlay"));
             }
         }
-        
+
         if let Some(home) = dirs::home_dir() {
-            possible_paths.push(home.join("sandbox/kazeta-plus/overlay/target/debug/kazeta-overlay"));
+            possible_paths
+                .push(home.join("sandbox/kazeta-plus/overlay/target/debug/kazeta-overlay"));
         }
-        
+
         if let Ok(project_root) = std::env::var("KAZETA_PROJECT_ROOT") {
-            possible_paths.push(PathBuf::from(project_root).join("overlay/target/debug/kazeta-overlay"));
+            possible_paths
+                .push(PathBuf::from(project_root).join("overlay/target/debug/kazeta-overlay"));
         }
-        
+
         possible_paths.iter()
             .find(|p| p.exists())
             .cloned()
@@ -326,13 +369,13 @@ pub fn start_overlay_daemon() -> std::io::Result<()> {
             overlay_bin.display()
         );
         eprintln!("[Overlay] {}", err_msg);
-        return Err(std::io::Error::new(
-            std::io::ErrorKind::NotFound,
-            err_msg
-        ));
+        return Err(std::io::Error::new(std::io::ErrorKind::NotFound, err_msg));
     }
 
-    println!("[Overlay] Starting overlay daemon: {}", overlay_bin.display());
+    println!(
+        "[Overlay] Starting overlay daemon: {}",
+        overlay_bin.display()
+    );
 
     // Create a log file for overlay output (helps debug startup issues)
     let log_path = "/tmp/kazeta-overlay.log";
@@ -345,7 +388,7 @@ pub fn start_overlay_daemon() -> std::io::Result<()> {
 
     // Spawn the overlay daemon as a detached background process
     let mut cmd = Command::new(&overlay_bin);
-    
+
     if let Some(file) = log_file {
         let stderr_file = file.try_clone().ok();
         cmd.stdout(Stdio::from(file));
@@ -358,12 +401,11 @@ pub fn start_overlay_daemon() -> std::io::Result<()> {
         cmd.stdout(Stdio::null());
         cmd.stderr(Stdio::null());
     }
-    
-    cmd.spawn()
-        .map_err(|e| {
-            eprintln!("[Overlay] Failed to start overlay daemon: {}", e);
-            e
-        })?;
+
+    cmd.spawn().map_err(|e| {
+        eprintln!("[Overlay] Failed to start overlay daemon: {}", e);
+        e
+    })?;
 
     // Give it a moment to start up (macroquad needs time to initialize window)
     std::thread::sleep(std::time::Duration::from_millis(1000));
@@ -378,16 +420,18 @@ pub fn start_overlay_daemon() -> std::io::Result<()> {
             std::thread::sleep(std::time::Duration::from_millis(500));
         }
     }
-    
+
     // Check log file for errors
     if let Ok(log_content) = fs::read_to_string(log_path) {
         if !log_content.is_empty() {
             eprintln!("[Overlay] Daemon output:\n{}", log_content);
         }
     }
-    
-    eprintln!("[Overlay] Warning: Daemon may not have started correctly (socket not found after 2s)");
-    Ok(())  // Don't fail, overlay might work when game launches
+
+    eprintln!(
+        "[Overlay] Warning: Daemon may not have started correctly (socket not found after 2s)"
+    );
+    Ok(()) // Don't fail, overlay might work when game launches
 }
 
 /// Stop the overlay daemon (kills any running instance)
@@ -398,13 +442,13 @@ pub fn stop_overlay_daemon() {
         .arg("-f")
         .arg("kazeta-overlay")
         .output();
-    
+
     // Also remove the socket file if it exists
     let socket_path = Path::new("/tmp/kazeta-overlay.sock");
     if socket_path.exists() {
         let _ = fs::remove_file(socket_path);
     }
-    
+
     println!("[Overlay] Daemon stopped");
 }
 
@@ -480,23 +524,76 @@ pub fn unlock_achievement(cart_id: &str, achievement_id: &str) {
 pub fn notify_game_started(cart_id: &str, game_name: &str, runtime: &str) {
     use std::io::Write;
     use std::os::unix::net::UnixStream;
-    
+
     let socket_path = "/tmp/kazeta-overlay.sock";
     if !Path::new(socket_path).exists() {
         return;
     }
-    
+
     let message = serde_json::json!({
         "type": "game_started",
         "cart_id": cart_id,
         "game_name": game_name,
         "runtime": runtime,
     });
-    
+
+    if let Ok(mut stream) = UnixStream::connect(socket_path) {
+        let _ = stream.set_write_timeout(Some(std::time::Duration::from_millis(100)));
+        let _ = writeln!(stream, "{}", message);
+        println!(
+            "[Overlay] Notified game started: {} ({})",
+            game_name, runtime
+        );
+    }
+}
+
+/// `controls.toml` shipped next to a cart's .kzi
+#[derive(serde::Deserialize, Default)]
+struct CartControls {
+    /// Button name ("a", "lb", "dpad", "start"...) to what it does
+    #[serde(default)]
+    buttons: std::collections::BTreeMap<String, String>,
+}
+
+/// Send the cart's button mapping to the overlay. Carts without one send an
+/// empty list, so the overlay drops what it cached from an older version.
+pub fn notify_game_controls(cart_id: &str, kzi_path: &Path) {
+    use std::io::Write;
+    use std::os::unix::net::UnixStream;
+
+    let socket_path = "/tmp/kazeta-overlay.sock";
+    if !Path::new(socket_path).exists() {
+        return;
+    }
+
+    let controls_path = kzi_path.with_file_name("controls.toml");
+    let controls = match fs::read_to_string(&controls_path) {
+        Ok(content) => toml::from_str::<CartControls>(&content).unwrap_or_else(|e| {
+            println!(
+                "[WARNING] Ignoring invalid {}: {}",
+                controls_path.display(),
+                e
+            );
+            CartControls::default()
+        }),
+        Err(_) => CartControls::default(),
+    };
+    let entries: Vec<_> = controls
+        .buttons
+        .iter()
+        .map(|(button, label)| serde_json::json!({ "button": button, "label": label }))
+        .collect();
+    let count = entries.len();
+    let message = serde_json::json!({
+        "type": "game_controls",
+        "cart_id": cart_id,
+        "controls": entries,
+    });
+
     if let Ok(mut stream) = UnixStream::connect(socket_path) {
         let _ = stream.set_write_timeout(Some(std::time::Duration::from_millis(100)));
         let _ = writeln!(stream, "{}", message);
-        println!("[Overlay] Notified game started: {} ({})", game_name, runtime);
+        println!("[Overlay] Sent {} control hints for {}", count, cart_id);
     }
 }
 
@@ -504,17 +601,17 @@ pub fn notify_game_started(cart_id: &str, game_name: &str, runtime: &str) {
 pub fn notify_game_stopped(cart_id: &str) {
     use std::io::Write;
     use std::os::unix::net::UnixStream;
-    
+
     let socket_path = "/tmp/kazeta-overlay.sock";
     if !Path::new(socket_path).exists() {
         return;
     }
-    
+
     let message = serde_json::json!({
         "type": "game_stopped",
         "cart_id": cart_id,
     });
-    
+
     if let Ok(mut stream) = UnixStream::connect(socket_path) {
         let _ = stream.set_write_timeout(Some(std::time::Duration::from_millis(100)));
         let _ = writeln!(stream, "{}", message);
@@ -522,6 +619,100 @@ pub fn notify_game_stopped(cart_id: &str) {
     }
 }
 
+/// Send a message to the input daemon's socket, if it is running
+fn send_to_input_daemon(message: &serde_json::Value) -> bool {
+    use std::io::Write;
+    use std::os::unix::net::UnixStream;
+
+    let socket_path = "/tmp/kazeta-input.sock";
+    if !Path::new(socket_path).exists() {
+        return false;
+    }
+
+    match UnixStream::connect(socket_path) {
+        Ok(mut stream) => {
+            let _ = stream.set_write_timeout(Some(std::time::Duration::from_millis(100)));
+            writeln!(stream, "{}", message).is_ok()
+        }
+        Err(_) => false,
+    }
+}
+
+/// Tell the input daemon a game is starting and which hotkeys it uses itself
+pub fn notify_input_game_started(cart_id: &str, suppressed_hotkeys: &[String]) {
+    let message = serde_json::json!({
+        "type": "game_started",
+        "cart_id": cart_id,
+        "suppressed_hotkeys": suppressed_hotkeys,
+    });
+    if send_to_input_daemon(&message) && !suppressed_hotkeys.is_empty() {
+        println!(
+            "[Input] Suppressing hotkeys for {}: {}",
+            cart_id,
+            suppressed_hotkeys.join(", ")
+        );
+    }
+}
+
+/// Tell the input daemon no game is running, restoring every hotkey. The BIOS
+/// only runs between games, so this is sent whenever it starts.
+pub fn notify_input_game_stopped() {
+    if send_to_input_daemon(&serde_json::json!({ "type": "game_stopped" })) {
+        println!("[Input] Hotkey suppression cleared");
+    }
+}
+
+/// Tell the overlay and the input daemon the system is going down, so they
+/// save their state and exit cleanly
+pub fn notify_shutdown() {
+    use std::io::Write;
+    use std::os::unix::net::UnixStream;
+
+    let message = serde_json::json!({ "type": "shutdown" });
+    let socket_path = "/tmp/kazeta-overlay.sock";
+    if Path::new(socket_path).exists() {
+        if let Ok(mut stream) = UnixStream::connect(socket_path) {
+            let _ = stream.set_write_timeout(Some(std::time::Duration::from_millis(100)));
+            let _ = writeln!(stream, "{}", message);
+            println!("[Overlay] Notified shutdown");
+        }
+    }
+    if send_to_input_daemon(&message) {
+        println!("[Input] Notified shutdown");
+    }
+}
+
+/// Send the installed games to the overlay for its search screen
+pub fn notify_game_list(games: &[(save::CartInfo, PathBuf)]) {
+    use std::io::Write;
+    use std::os::unix::net::UnixStream;
+
+    let socket_path = "/tmp/kazeta-overlay.sock";
+    if !Path::new(socket_path).exists() {
+        return;
+    }
+
+    let entries: Vec<_> = games
+        .iter()
+        .map(|(info, _)| {
+            serde_json::json!({
+                "cart_id": info.id,
+                "name": info.name.clone().unwrap_or_else(|| info.id.clone()),
+            })
+        })
+        .collect();
+    let message = serde_json::json!({
+        "type": "game_list",
+        "games": entries,
+    });
+
+    if let Ok(mut stream) = UnixStream::connect(socket_path) {
+        let _ = stream.set_write_timeout(Some(std::time::Duration::from_millis(100)));
+        let _ = writeln!(stream, "{}", message);
+        println!("[Overlay] Sent game list ({} games)", games.len());
+    }
+}
+
 /// Setup RetroAchievements for a game launch
 /// This is called by the BIOS when launching a game
 fn setup_retroachievements(cart_info: &save::CartInfo, kzi_path: &Path) {
@@ -531,16 +722,18 @@ fn setup_retroachievements(cart_info: &save::CartInfo, kzi_path: &Path) {
         show_warning_toast("Overlay not running – achievements may not display");
     }
 
-    // Check if kazeta-ra is available
-    if Command::new("kazeta-ra").arg("status").output().is_err() {
-        println!("[RA] kazeta-ra not found, skipping RetroAchievements");
-        return;
-    }
-
-    // Check if RA is configured and enabled
-    let status_output = match Command::new("kazeta-ra").arg("status").output() {
+    // Check if RA is configured and enabled. --offline trusts the stored
+    // credentials instead of asking the server, which can take seconds;
+    // the RetroAchievements settings screen checks with the server.
+    let status_output = match Command::new("kazeta-ra")
+        .args(["status", "--offline"])
+        .output()
+    {
         Ok(output) => output,
-        Err(_) => return,
+        Err(_) => {
+            println!("[RA] kazeta-ra not found, skipping RetroAchievements");
+            return;
+        }
     };
 
     let status_str = String::from_utf8_lossy(&status_output.stdout);
@@ -564,18 +757,33 @@ fn setup_retroachievements(cart_info: &save::CartInfo, kzi_path: &Path) {
         return;
     }
 
-    println!("[RA] Setting up RetroAchievements for: {}", rom_path.display());
+    println!(
+        "[RA] Setting up RetroAchievements for: {}",
+        rom_path.display()
+    );
 
     // Call kazeta-ra game-start (this will hash the ROM, fetch game info, and notify overlay)
     // Run in background so it doesn't block game launch
     let rom_path_str = rom_path.to_string_lossy().to_string();
+    // The cart's own name covers games RetroAchievements doesn't know
+    let kzi_path_buf = kzi_path.to_path_buf();
+    let name_hint = cart_info
+        .ra_game_name
+        .clone()
+        .filter(|n| !n.trim().is_empty());
     thread::spawn(move || {
-        let _ = Command::new("kazeta-ra")
+        let mut command = Command::new("kazeta-ra");
+        command
             .arg("game-start")
             .arg("--path")
             .arg(&rom_path_str)
-            .arg("--notify-overlay")
-            .output();
+            .arg("--cart")
+            .arg(&kzi_path_buf)
+            .arg("--notify-overlay");
+        if let Some(hint) = &name_hint {
+            command.arg("--name-hint").arg(hint);
+        }
+        let _ = command.output();
     });
 
     // Also send achievement list to overlay (run in background)
@@ -583,12 +791,59 @@ fn setup_retroachievements(cart_info: &save::CartInfo, kzi_path: &Path) {
     thread::spawn(move || {
         // Small delay to let game-start complete first
         thread::sleep(std::time::Duration::from_millis(500));
-        let _ = Command::new("kazeta-ra")
+        send_achievements_to_overlay(&rom_path_str2);
+    });
+}
+
+/// kazeta-ra's exit status when nothing listens on the overlay socket
+const RA_EXIT_OVERLAY_NOT_RUNNING: i32 = 3;
+/// kazeta-ra's exit status when the overlay is up but didn't get the message
+const RA_EXIT_SEND_FAILED: i32 = 4;
+
+/// Send the game's achievement list to the overlay. The overlay may still be
+/// starting alongside the game, so a missing overlay is retried for a few
+/// seconds; a failed send isn't, as it would only fail again.
+pub fn send_achievements_to_overlay(rom_path: &str) {
+    for attempt in 1..=5 {
+        let output = match Command::new("kazeta-ra")
             .arg("send-achievements-to-overlay")
             .arg("--path")
-            .arg(&rom_path_str2)
-            .output();
-    });
+            .arg(rom_path)
+            .output()
+        {
+            Ok(output) => output,
+            Err(e) => {
+                println!("[RA] Failed to run kazeta-ra: {}", e);
+                return;
+            }
+        };
+
+        match output.status.code() {
+            Some(0) => return,
+            Some(RA_EXIT_OVERLAY_NOT_RUNNING) => {
+                println!(
+                    "[RA] Overlay not running yet (attempt {}), retrying",
+                    attempt
+                );
+                thread::sleep(std::time::Duration::from_secs(1));
+            }
+            Some(RA_EXIT_SEND_FAILED) => {
+                println!(
+                    "[RA] Failed to send achievements to the overlay: {}",
+                    String::from_utf8_lossy(&output.stdout).trim()
+                );
+                return;
+            }
+            _ => {
+                println!(
+                    "[RA] send-achievements-to-overlay failed: {}",
+                    String::from_utf8_lossy(&output.stdout).trim()
+                );
+                return;
+            }
+        }
+    }
+    println!("[RA] Overlay never came up; achievements weren't sent");
 }
 
 /// Get the ROM path from a cartridge
@@ -6,12 +6,13 @@
 # Builds all Rust binaries and runtimes for deployment
 #
 # Usage:
-#   ./build-all.sh [--release] [--skip-runtimes]
+#   ./build-all.sh [--release] [--skip-runtimes] [--portal]
 #
 # Options:
 #   --release        Build in release mode (recommended for production)
 #   --debug          Build in debug mode (faster compilation, larger binaries)
 #   --skip-runtimes  Skip building runtime packages
+#   --portal         Build the input daemon's GlobalShortcuts portal backend (needs libdbus-1 headers)
 #   --help           Show this help message
 # ===================================================================
 
@@ -29,6 +30,7 @@ NC='\033[0m'
 SCRIPT_DIR="$(cd "$(dirname "${BASH_SOURCE[0]}")" && pwd)"
 BUILD_MODE="release"
 BUILD_RUNTIMES=true
+INPUT_FEATURES=""
 
 # --- Argument Parsing ---
 while [[ $# -gt 0 ]]; do
@@ -45,8 +47,12 @@ while [[ $# -gt 0 ]]; do
             BUILD_RUNTIMES=false
             shift
             ;;
+        --portal)
+            INPUT_FEATURES="--features portal"
+            shift
+            ;;
         --help)
-            echo "Usage: $0 [--release|--debug] [--skip-runtimes]"
+            echo "Usage: $0 [--release|--debug] [--skip-runtimes] [--portal]"
             exit 0
             ;;
         *)
@@ -119,7 +125,7 @@ build_rust_binary "Kazeta BIOS" "$SCRIPT_DIR/bios" "kazeta-bios"
 build_rust_binary "RA Daemon" "$SCRIPT_DIR/ra" "kazeta-ra"
 
 # Build Input Daemon
-build_rust_binary "Input Daemon" "$SCRIPT_DIR/input-daemon" "kazeta-input"
+build_rust_binary "Input Daemon" "$SCRIPT_DIR/input-daemon" "kazeta-input" "$INPUT_FEATURES"
 
 # Build Overlay Daemon (requires daemon feature)
 build_rust_binary "Overlay Daemon" "$SCRIPT_DIR/overlay" "kazeta-overlay" "--features daemon"
@@ -0,0 +1,330 @@
+//! Per-device analog axis calibration
+//!
+//! Kernel-reported axis ranges are often wrong: triggers that never reach
+//! their maximum, sticks resting at 128 on a 0-255 range, Guide buttons
+//! exposed as an axis. Each device keeps the observed range and resting value
+//! of every axis in `input/calibration/<device-id>.json`. Ranges widen as new
+//! extremes are seen and the file is rewritten (throttled) when they do, so
+//! the values can also be edited by hand.
+
+use evdev::{AbsoluteAxisType, Device};
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+/// Minimum time between calibration file writes
+const SAVE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Range and resting value of one axis
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq)]
+pub struct AxisCalibration {
+    pub min: i32,
+    pub max: i32,
+    /// Value reported at rest (the minimum for triggers, the middle for sticks)
+    pub center: i32,
+}
+
+impl AxisCalibration {
+    /// Widen the range to include `value`. Returns true if it changed.
+    fn observe(&mut self, value: i32) -> bool {
+        if value < self.min {
+            self.min = value;
+            true
+        } else if value > self.max {
+            self.max = value;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Map a raw value to -1.0..=1.0 with 0.0 at rest
+    pub fn normalize(&self, value: i32) -> f32 {
+        if value >= self.center {
+            let span = (self.max - self.center).max(1) as f32;
+            ((value - self.center) as f32 / span).min(1.0)
+        } else {
+            let span = (self.center - self.min).max(1) as f32;
+            -((self.center - value) as f32 / span).min(1.0)
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct CalibrationFile {
+    device: String,
+    axes: BTreeMap<String, AxisCalibration>,
+}
+
+/// Calibration for every axis of one device
+pub struct DeviceCalibration {
+    path: Option<PathBuf>,
+    file: CalibrationFile,
+    dirty: bool,
+    last_save: Instant,
+}
+
+/// Stable identifier for a device model: vendor, product and name
+pub fn device_id(device: &Device) -> String {
+    let id = device.input_id();
+    model_id(
+        id.vendor(),
+        id.product(),
+        device.name().unwrap_or("unknown"),
+    )
+}
+
+fn model_id(vendor: u16, product: u16, name: &str) -> String {
+    let name: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    format!("{:04x}-{:04x}-{}", vendor, product, name.trim_matches('-'))
+}
+
+fn calibration_dir() -> Option<PathBuf> {
+    dirs::data_local_dir().map(|d| d.join("kazeta-plus").join("input").join("calibration"))
+}
+
+impl DeviceCalibration {
+    /// Load stored calibration for `device`, seeding any unknown axes from the
+    /// kernel's reported range and the device's current (resting) state
+    pub fn load(device: &Device) -> Self {
+        let id = device_id(device);
+        let mut calibration = Self::open(
+            &id,
+            calibration_dir().map(|dir| dir.join(format!("{}.json", id))),
+        );
+
+        if let (Some(axes), Ok(state)) = (device.supported_absolute_axes(), device.get_abs_state())
+        {
+            for axis in axes.iter() {
+                let name = format!("{:?}", axis);
+                if calibration.file.axes.contains_key(&name) {
+                    continue;
+                }
+                let Some(info) = state.get(axis.0 as usize) else {
+                    continue;
+                };
+                calibration.file.axes.insert(
+                    name,
+                    AxisCalibration {
+                        min: info.minimum,
+                        max: info.maximum,
+                        center: info.value.clamp(info.minimum, info.maximum),
+                    },
+                );
+                calibration.dirty = true;
+            }
+        }
+
+        if !calibration.file.axes.is_empty() {
+            info!(
+                "Loaded calibration for {} ({} axes)",
+                id,
+                calibration.file.axes.len()
+            );
+        }
+        calibration
+    }
+
+    /// Calibration stored at `path` for device `id`, empty if there's none
+    /// yet. Without a path it's never saved.
+    pub fn open(id: &str, path: Option<PathBuf>) -> Self {
+        let mut file = path
+            .as_ref()
+            .and_then(|p| fs::read_to_string(p).ok())
+            .and_then(
+                |content| match serde_json::from_str::<CalibrationFile>(&content) {
+                    Ok(file) => Some(file),
+                    Err(e) => {
+                        warn!("Ignoring invalid calibration for {}: {}", id, e);
+                        None
+                    }
+                },
+            )
+            .unwrap_or_default();
+        file.device = id.to_string();
+
+        Self {
+            path,
+            file,
+            dirty: false,
+            last_save: Instant::now() - SAVE_INTERVAL,
+        }
+    }
+
+    /// Normalize a raw axis value, widening the stored range if needed
+    pub fn normalize(&mut self, axis: AbsoluteAxisType, value: i32) -> f32 {
+        let name = format!("{:?}", axis);
+        let calibration = self.file.axes.entry(name).or_insert(AxisCalibration {
+            min: value,
+            max: value,
+            center: value,
+        });
+        if calibration.observe(value) {
+            self.dirty = true;
+        }
+        calibration.normalize(value)
+    }
+
+    /// Write the calibration if it changed, at most every `SAVE_INTERVAL`
+    pub fn save_if_changed(&mut self) {
+        if self.dirty && self.last_save.elapsed() >= SAVE_INTERVAL {
+            self.save();
+        }
+    }
+
+    /// Write the calibration now if it changed
+    pub fn save(&mut self) {
+        if !self.dirty {
+            return;
+        }
+        let Some(path) = &self.path else { return };
+
+        let result = path
+            .parent()
+            .map(fs::create_dir_all)
+            .unwrap_or(Ok(()))
+            .and_then(|_| {
+                let content = serde_json::to_string_pretty(&self.file)?;
+                fs::write(path, content)
+            });
+
+        match result {
+            Ok(()) => debug!("Saved calibration to {}", path.display()),
+            Err(e) => warn!("Failed to save calibration to {}: {}", path.display(), e),
+        }
+        self.dirty = false;
+        self.last_save = Instant::now();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "kazeta-input-calibration-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        dir.join("device.json")
+    }
+
+    #[test]
+    fn test_normalize_stick_and_trigger() {
+        let stick = AxisCalibration {
+            min: 0,
+            max: 255,
+            center: 128,
+        };
+        assert_eq!(stick.normalize(128), 0.0);
+        assert_eq!(stick.normalize(255), 1.0);
+        assert_eq!(stick.normalize(0), -1.0);
+        assert!((stick.normalize(192) - 64.0 / 127.0).abs() < 1e-6);
+        // Past the range clamps
+        assert_eq!(stick.normalize(300), 1.0);
+        assert_eq!(stick.normalize(-50), -1.0);
+
+        let trigger = AxisCalibration {
+            min: 0,
+            max: 1023,
+            center: 0,
+        };
+        assert_eq!(trigger.normalize(0), 0.0);
+        assert_eq!(trigger.normalize(1023), 1.0);
+
+        // A collapsed range doesn't divide by zero
+        let stuck = AxisCalibration {
+            min: 5,
+            max: 5,
+            center: 5,
+        };
+        assert_eq!(stuck.normalize(5), 0.0);
+        assert_eq!(stuck.normalize(6), 1.0);
+    }
+
+    #[test]
+    fn test_range_widens_with_new_extremes() {
+        let mut calibration = DeviceCalibration::open("test", None);
+        // An axis seen for the first time rests where it is
+        assert_eq!(calibration.normalize(AbsoluteAxisType::ABS_Z, 10), 0.0);
+        assert!(!calibration.dirty);
+
+        assert_eq!(calibration.normalize(AbsoluteAxisType::ABS_Z, 200), 1.0);
+        assert!(calibration.dirty);
+        assert_eq!(
+            calibration.file.axes["ABS_Z"],
+            AxisCalibration {
+                min: 10,
+                max: 200,
+                center: 10
+            }
+        );
+        assert_eq!(calibration.normalize(AbsoluteAxisType::ABS_Z, 105), 0.5);
+
+        calibration.normalize(AbsoluteAxisType::ABS_Z, 0);
+        assert_eq!(calibration.file.axes["ABS_Z"].min, 0);
+        assert_eq!(calibration.normalize(AbsoluteAxisType::ABS_Z, 0), -1.0);
+    }
+
+    #[test]
+    fn test_save_and_reopen() {
+        let path = temp_path("roundtrip");
+        let mut calibration = DeviceCalibration::open("045e-028e-xbox", Some(path.clone()));
+        calibration.normalize(AbsoluteAxisType::ABS_RZ, 0);
+        calibration.normalize(AbsoluteAxisType::ABS_RZ, 255);
+        calibration.save();
+        assert!(!calibration.dirty);
+
+        let reopened = DeviceCalibration::open("045e-028e-xbox", Some(path.clone()));
+        assert_eq!(reopened.file.device, "045e-028e-xbox");
+        assert_eq!(
+            reopened.file.axes["ABS_RZ"],
+            AxisCalibration {
+                min: 0,
+                max: 255,
+                center: 0
+            }
+        );
+
+        // Writes are throttled after a save
+        calibration.normalize(AbsoluteAxisType::ABS_RZ, 300);
+        calibration.save_if_changed();
+        assert!(calibration.dirty);
+        assert_eq!(
+            DeviceCalibration::open("x", Some(path.clone())).file.axes["ABS_RZ"].max,
+            255
+        );
+
+        // A damaged file starts over rather than failing
+        fs::write(&path, "{").unwrap();
+        assert!(DeviceCalibration::open("x", Some(path.clone()))
+            .file
+            .axes
+            .is_empty());
+
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn test_model_id() {
+        assert_eq!(
+            model_id(0x045e, 0x028e, "Microsoft X-Box 360 pad"),
+            "045e-028e-microsoft-x-box-360-pad"
+        );
+        assert_eq!(model_id(1, 2, " (Virtual) "), "0001-0002-virtual");
+    }
+}
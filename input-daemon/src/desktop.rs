@@ -0,0 +1,180 @@
+//! Desktop shortcut fallback
+//!
+//! Without read access to /dev/input (not root, not in the `input` group)
+//! evdev sees nothing, so keyboard-only hotkeys are registered with the
+//! desktop instead: through the XDG GlobalShortcuts portal (see `portal`),
+//! or with XGrabKey on an X11 display when no portal offers it (see `x11`).
+//! Gamepad and axis combos still need evdev and are skipped.
+//!
+//! Chosen automatically when every input device refuses to open with
+//! permission denied, or forced with `--desktop-shortcuts`.
+
+use crate::hotkeys::{ComboInput, Hotkey};
+#[cfg(feature = "portal")]
+use crate::portal;
+use crate::{x11, GlobalState};
+use evdev::Key;
+use log::warn;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+/// Shown as the device in hotkey logs and stats
+pub const DEVICE_NAME: &str = "desktop shortcuts";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Modifier {
+    Ctrl,
+    Alt,
+    Shift,
+    Logo,
+}
+
+impl Modifier {
+    fn from_key(key: Key) -> Option<Self> {
+        match key {
+            Key::KEY_LEFTCTRL | Key::KEY_RIGHTCTRL => Some(Modifier::Ctrl),
+            Key::KEY_LEFTALT | Key::KEY_RIGHTALT => Some(Modifier::Alt),
+            Key::KEY_LEFTSHIFT | Key::KEY_RIGHTSHIFT => Some(Modifier::Shift),
+            Key::KEY_LEFTMETA | Key::KEY_RIGHTMETA => Some(Modifier::Logo),
+            _ => None,
+        }
+    }
+
+    /// Name in a portal trigger string
+    pub fn portal_name(self) -> &'static str {
+        match self {
+            Modifier::Ctrl => "CTRL",
+            Modifier::Alt => "ALT",
+            Modifier::Shift => "SHIFT",
+            Modifier::Logo => "LOGO",
+        }
+    }
+}
+
+/// A keyboard-only hotkey as the desktop sees it: modifiers plus one key,
+/// named by its XKB keysym
+#[derive(Debug, Clone)]
+pub struct Shortcut {
+    /// Index into the hotkey list
+    pub hotkey: usize,
+    /// Stable across runs so the desktop remembers the user's rebinding
+    #[cfg_attr(not(feature = "portal"), allow(dead_code))]
+    pub id: String,
+    pub modifiers: Vec<Modifier>,
+    pub keysym: String,
+}
+
+impl Shortcut {
+    /// Preferred trigger in the shortcuts spec's format, e.g. `CTRL+o`
+    pub fn trigger(&self) -> String {
+        let mut parts: Vec<&str> = self.modifiers.iter().map(|m| m.portal_name()).collect();
+        parts.push(&self.keysym);
+        parts.join("+")
+    }
+}
+
+/// XKB keysym for an evdev key. Letters use the US position, as the portal
+/// and X11 both take keysyms rather than keycodes.
+fn keysym(key: Key) -> Option<String> {
+    let name = format!("{:?}", key);
+    let name = name.strip_prefix("KEY_")?;
+    let named = match name {
+        "ESC" => "Escape",
+        "TAB" => "Tab",
+        "SPACE" => "space",
+        "ENTER" => "Return",
+        "BACKSPACE" => "BackSpace",
+        "INSERT" => "Insert",
+        "DELETE" => "Delete",
+        "HOME" => "Home",
+        "END" => "End",
+        "PAGEUP" => "Prior",
+        "PAGEDOWN" => "Next",
+        "PAUSE" => "Pause",
+        "SYSRQ" => "Print",
+        "GRAVE" => "grave",
+        "UP" => "Up",
+        "DOWN" => "Down",
+        "LEFT" => "Left",
+        "RIGHT" => "Right",
+        _ if name.len() == 1 => return Some(name.to_ascii_lowercase()),
+        _ if name.starts_with('F') && name[1..].parse::<u8>().is_ok() => {
+            return Some(name.to_string())
+        }
+        _ => return None,
+    };
+    Some(named.to_string())
+}
+
+/// The hotkeys that can be registered with the desktop
+pub fn shortcuts(hotkeys: &[Hotkey]) -> Vec<Shortcut> {
+    hotkeys
+        .iter()
+        .enumerate()
+        .filter_map(|(index, hotkey)| {
+            let mut modifiers = Vec::new();
+            let mut keys = Vec::new();
+            for input in &hotkey.inputs {
+                let ComboInput::Key(key) = input else {
+                    return None;
+                };
+                match Modifier::from_key(*key) {
+                    Some(modifier) if !modifiers.contains(&modifier) => modifiers.push(modifier),
+                    Some(_) => {}
+                    None => keys.push(*key),
+                }
+            }
+            let [key] = keys[..] else { return None };
+            let keysym = keysym(key)?;
+            let id = format!(
+                "{}-{}",
+                hotkey.action.replace(':', "-"),
+                hotkey.label.to_lowercase().replace('+', "-")
+            );
+            Some(Shortcut {
+                hotkey: index,
+                id,
+                modifiers,
+                keysym,
+            })
+        })
+        .collect()
+}
+
+/// Send a shortcut's hotkey to the overlay, unless the running game
+/// suppresses it
+pub fn trigger(state: &Arc<Mutex<GlobalState>>, hotkey: &Hotkey) {
+    let (suppression, stats) = {
+        let global = state.lock().unwrap();
+        (global.suppression.clone(), global.stats.clone())
+    };
+    if suppression.is_suppressed(hotkey) {
+        stats.record_suppressed(&hotkey.label, &hotkey.action);
+        return;
+    }
+    crate::trigger_hotkey(state, hotkey, DEVICE_NAME, SystemTime::now());
+}
+
+/// Register keyboard hotkeys with the desktop and forward them until
+/// shutdown. Tries the portal first (with the `portal` feature), then X11.
+pub fn run(hotkeys: Arc<Vec<Hotkey>>, state: Arc<Mutex<GlobalState>>, running: Arc<AtomicBool>) {
+    let shortcuts = shortcuts(&hotkeys);
+    if shortcuts.is_empty() {
+        warn!("No keyboard-only hotkeys to register with the desktop");
+        return;
+    }
+
+    #[cfg(feature = "portal")]
+    match portal::run(&shortcuts, &hotkeys, &state, &running) {
+        Ok(()) => return,
+        Err(e) => log::info!("GlobalShortcuts portal unavailable: {:#}", e),
+    }
+    if !running.load(Ordering::Relaxed) {
+        return;
+    }
+    if let Err(e) = x11::run(&shortcuts, &hotkeys, &state, &running) {
+        warn!("X11 key grabs unavailable: {:#}", e);
+        warn!("No desktop shortcut backend - hotkeys need /dev/input access");
+    }
+}
@@ -0,0 +1,800 @@
+//! Exclusive input grab while the overlay has focus
+//!
+//! The daemon listens on /tmp/kazeta-input.sock for `overlay_shown` /
+//! `overlay_hidden` notifications from the overlay. The visibility is always
+//! tracked, since pointer input is only forwarded while the overlay is shown.
+//! The BIOS uses the same socket for `game_started` / `game_stopped`, which
+//! drive per-game hotkey suppression (see `suppression`), and sends
+//! `shutdown` before powering off so the daemon releases its devices. Macro
+//! recording and playback requests arrive here too (see `macros`), and
+//! `stats` is answered on the same connection with the event statistics
+//! (see `stats`).
+//!
+//! With `--grab`, every monitored device is also EVIOCGRABbed while the
+//! overlay is shown so the game stops receiving input, and events are
+//! re-emitted to a virtual "Kazeta Overlay Input" device that the overlay
+//! reads instead.
+//!
+//! Without `--grab`, the overlay can still ask for exclusive input for a
+//! while, e.g. so the game doesn't see the stick while the on-screen keyboard
+//! is open. It sends `exclusive_request` with the devices it wants (controller
+//! IDs, event nodes, device names, or `gamepads` / `keyboards` / `pointers`;
+//! none means all) and a `duration_ms`, and the daemon answers on the same
+//! connection with `exclusive_granted` or `exclusive_denied`. The lease is
+//! capped at `MAX_EXCLUSIVE` and runs out on its own unless it is renewed, so
+//! a client that stops talking to us never keeps the game from its input.
+//!
+//! Devices must never stay grabbed by accident, so the grab is released when
+//! the overlay hides, when an exclusive lease expires or is released, when
+//! the overlay socket stops accepting connections (overlay crashed), when a
+//! device thread exits, and on shutdown.
+
+use anyhow::{Context, Result};
+use evdev::uinput::{VirtualDevice, VirtualDeviceBuilder};
+use evdev::{
+    AbsInfo, AbsoluteAxisType, AttributeSet, Device, EventType, InputEvent, InputEventKind, Key,
+    UinputAbsSetup,
+};
+use log::{debug, info, warn};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::io::AsRawFd;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::macros::MacroEngine;
+use crate::stats::EventStats;
+use crate::suppression::Suppression;
+use crate::OVERLAY_SOCKET;
+
+/// Socket the overlay uses to tell the daemon about its visibility
+pub const INPUT_SOCKET: &str = "/tmp/kazeta-input.sock";
+
+/// How often to verify the overlay is still alive while grabbing
+const LIVENESS_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Exclusive lease length when the request doesn't say
+const DEFAULT_EXCLUSIVE: Duration = Duration::from_secs(5);
+
+/// Longest exclusive lease; clients renew for longer
+const MAX_EXCLUSIVE: Duration = Duration::from_secs(30);
+
+/// Range used for the virtual device's analog sticks
+const STICK_MIN: i32 = -32768;
+const STICK_MAX: i32 = 32767;
+
+/// Range used for the virtual device's analog triggers
+const TRIGGER_MAX: i32 = 255;
+
+/// Messages accepted on the daemon socket (JSON lines)
+#[derive(Deserialize, Debug)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum DaemonMessage {
+    OverlayShown,
+    OverlayHidden,
+    GameStarted {
+        cart_id: String,
+        #[serde(default)]
+        suppressed_hotkeys: Vec<String>,
+    },
+    GameStopped,
+    /// The system is powering off or restarting; exit cleanly
+    Shutdown,
+    MacroRecordStart {
+        name: String,
+        #[serde(default)]
+        controller_id: Option<String>,
+    },
+    MacroRecordStop,
+    MacroPlay {
+        name: String,
+        #[serde(default)]
+        speed: Option<f32>,
+    },
+    MacroSetSpeed {
+        name: String,
+        speed: f32,
+    },
+    MacroDelete {
+        name: String,
+    },
+    MacroAbort,
+    /// Grab some devices for a bounded time, answered on the same connection
+    ExclusiveRequest {
+        #[serde(default)]
+        devices: Vec<String>,
+        #[serde(default)]
+        duration_ms: Option<u64>,
+    },
+    ExclusiveRelease,
+    /// Event and hotkey statistics, answered on the same connection
+    Stats,
+}
+
+/// What a device can be named by in an exclusive request
+pub struct GrabTarget {
+    pub path: String,
+    pub name: String,
+    pub controller_id: Option<String>,
+    pub is_gamepad: bool,
+    pub is_keyboard: bool,
+    pub is_pointer: bool,
+}
+
+impl GrabTarget {
+    fn matches(&self, selector: &str) -> bool {
+        match selector {
+            "all" => true,
+            "gamepads" => self.is_gamepad,
+            "keyboards" => self.is_keyboard,
+            "pointers" => self.is_pointer,
+            other => {
+                other == self.path
+                    || other == self.name
+                    || Some(other) == self.controller_id.as_deref()
+            }
+        }
+    }
+}
+
+/// Devices taken from the game until `until`
+struct ExclusiveLease {
+    devices: Vec<String>,
+    until: Instant,
+}
+
+impl ExclusiveLease {
+    fn covers(&self, target: &GrabTarget) -> bool {
+        self.devices.is_empty() || self.devices.iter().any(|d| target.matches(d))
+    }
+}
+
+/// Shared grab requests plus the virtual device grabbed input is forwarded to
+pub struct GrabController {
+    /// Grab every device while the overlay is shown (`--grab`)
+    grab_on_show: bool,
+    requested: AtomicBool,
+    exclusive: Mutex<Option<ExclusiveLease>>,
+    /// Created up front in grab mode, otherwise on the first exclusive request
+    virtual_device: Mutex<Option<VirtualDevice>>,
+}
+
+impl GrabController {
+    /// With `grab_on_show`, create the virtual output device right away and
+    /// fail if that isn't possible, since grabbed input would otherwise go
+    /// nowhere.
+    pub fn new(grab_on_show: bool) -> Result<Self> {
+        let virtual_device = if grab_on_show {
+            Some(Self::build_virtual_device()?)
+        } else {
+            None
+        };
+        Ok(Self {
+            grab_on_show,
+            requested: AtomicBool::new(false),
+            exclusive: Mutex::new(None),
+            virtual_device: Mutex::new(virtual_device),
+        })
+    }
+
+    fn build_virtual_device() -> Result<VirtualDevice> {
+        let mut keys = AttributeSet::<Key>::new();
+        // Keyboard keys (KEY_ESC..KEY_MICMUTE)
+        for code in 1..=248 {
+            keys.insert(Key::new(code));
+        }
+        // Gamepad buttons (BTN_SOUTH..BTN_THUMBR) and d-pad buttons
+        for code in Key::BTN_SOUTH.code()..=Key::BTN_THUMBR.code() {
+            keys.insert(Key::new(code));
+        }
+        for code in Key::BTN_DPAD_UP.code()..=Key::BTN_DPAD_RIGHT.code() {
+            keys.insert(Key::new(code));
+        }
+
+        let stick = AbsInfo::new(0, STICK_MIN, STICK_MAX, 16, 128, 0);
+        let trigger = AbsInfo::new(0, 0, TRIGGER_MAX, 0, 0, 0);
+        let hat = AbsInfo::new(0, -1, 1, 0, 0, 0);
+
+        let mut builder = VirtualDeviceBuilder::new()
+            .context("Failed to open /dev/uinput")?
+            .name("Kazeta Overlay Input")
+            .with_keys(&keys)?;
+        for (axis, info) in [
+            (AbsoluteAxisType::ABS_X, stick),
+            (AbsoluteAxisType::ABS_Y, stick),
+            (AbsoluteAxisType::ABS_RX, stick),
+            (AbsoluteAxisType::ABS_RY, stick),
+            (AbsoluteAxisType::ABS_Z, trigger),
+            (AbsoluteAxisType::ABS_RZ, trigger),
+            (AbsoluteAxisType::ABS_HAT0X, hat),
+            (AbsoluteAxisType::ABS_HAT0Y, hat),
+        ] {
+            builder = builder.with_absolute_axis(&UinputAbsSetup::new(axis, info))?;
+        }
+
+        builder
+            .build()
+            .context("Failed to create virtual input device")
+    }
+
+    /// Whether a device should be grabbed right now
+    pub fn wants(&self, target: &GrabTarget) -> bool {
+        self.wants_at(target, Instant::now())
+    }
+
+    fn wants_at(&self, target: &GrabTarget, now: Instant) -> bool {
+        if self.requested.load(Ordering::Relaxed) {
+            return true;
+        }
+        let exclusive = self.exclusive.lock().unwrap();
+        exclusive
+            .as_ref()
+            .is_some_and(|lease| lease.until > now && lease.covers(target))
+    }
+
+    fn set_requested(&self, requested: bool) {
+        let requested = requested && self.grab_on_show;
+        if self.requested.swap(requested, Ordering::Relaxed) != requested {
+            info!(
+                "Input grab {}",
+                if requested { "requested" } else { "released" }
+            );
+        }
+    }
+
+    /// Grant (or renew) an exclusive lease. Returns how long it lasts.
+    fn request_exclusive(
+        &self,
+        devices: Vec<String>,
+        duration: Option<Duration>,
+    ) -> Result<Duration> {
+        {
+            let mut virtual_device = self.virtual_device.lock().unwrap();
+            if virtual_device.is_none() {
+                *virtual_device = Some(Self::build_virtual_device()?);
+            }
+        }
+        Ok(self.grant_exclusive(devices, duration, Instant::now()))
+    }
+
+    fn grant_exclusive(
+        &self,
+        devices: Vec<String>,
+        duration: Option<Duration>,
+        now: Instant,
+    ) -> Duration {
+        let duration = duration.unwrap_or(DEFAULT_EXCLUSIVE).min(MAX_EXCLUSIVE);
+        let mut exclusive = self.exclusive.lock().unwrap();
+        if exclusive.is_none() {
+            let which = if devices.is_empty() {
+                "all devices".to_string()
+            } else {
+                devices.join(", ")
+            };
+            info!("Exclusive input for {} ({}ms)", which, duration.as_millis());
+        }
+        *exclusive = Some(ExclusiveLease {
+            devices,
+            until: now + duration,
+        });
+        duration
+    }
+
+    fn release_exclusive(&self) {
+        if self.exclusive.lock().unwrap().take().is_some() {
+            info!("Exclusive input released");
+        }
+    }
+
+    /// Drop the lease once it has run out. Returns whether one is still held.
+    fn expire_exclusive(&self) -> bool {
+        self.expire_exclusive_at(Instant::now())
+    }
+
+    fn expire_exclusive_at(&self, now: Instant) -> bool {
+        let mut exclusive = self.exclusive.lock().unwrap();
+        match exclusive.as_ref() {
+            Some(lease) if lease.until <= now => {
+                warn!("Exclusive input lease expired without renewal, releasing");
+                *exclusive = None;
+                false
+            }
+            lease => lease.is_some(),
+        }
+    }
+
+    /// Re-emit events to the virtual device
+    fn forward(&self, events: &[InputEvent]) {
+        if events.is_empty() {
+            return;
+        }
+        let mut device = self.virtual_device.lock().unwrap();
+        let Some(device) = device.as_mut() else {
+            return;
+        };
+        if let Err(e) = device.emit(events) {
+            warn!("Failed to forward events to virtual device: {}", e);
+        }
+    }
+}
+
+/// Check whether the overlay is still accepting connections
+fn overlay_alive() -> bool {
+    UnixStream::connect(OVERLAY_SOCKET).is_ok()
+}
+
+fn handle_connection(
+    stream: UnixStream,
+    visible: &AtomicBool,
+    controller: Option<&GrabController>,
+    suppression: &Suppression,
+    macros: &Arc<MacroEngine>,
+    stats: &EventStats,
+    running: &AtomicBool,
+) {
+    let _ = stream.set_read_timeout(Some(Duration::from_millis(100)));
+    let _ = stream.set_write_timeout(Some(Duration::from_millis(100)));
+    let mut replies = stream.try_clone().ok();
+    for line in BufReader::new(stream).lines() {
+        let Ok(line) = line else { break };
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let shown = match serde_json::from_str::<DaemonMessage>(line) {
+            Ok(DaemonMessage::OverlayShown) => true,
+            Ok(DaemonMessage::OverlayHidden) => false,
+            Ok(DaemonMessage::GameStarted {
+                cart_id,
+                suppressed_hotkeys,
+            }) => {
+                suppression.game_started(&cart_id, suppressed_hotkeys);
+                continue;
+            }
+            Ok(DaemonMessage::GameStopped) => {
+                suppression.game_stopped();
+                continue;
+            }
+            Ok(DaemonMessage::MacroRecordStart {
+                name,
+                controller_id,
+            }) => {
+                macros.start_recording(&name, controller_id);
+                continue;
+            }
+            Ok(DaemonMessage::MacroRecordStop) => {
+                macros.stop_recording();
+                continue;
+            }
+            Ok(DaemonMessage::MacroPlay { name, speed }) => {
+                macros.play(&name, speed);
+                continue;
+            }
+            Ok(DaemonMessage::MacroSetSpeed { name, speed }) => {
+                macros.set_speed(&name, speed);
+                continue;
+            }
+            Ok(DaemonMessage::MacroDelete { name }) => {
+                macros.delete(&name);
+                continue;
+            }
+            Ok(DaemonMessage::MacroAbort) => {
+                macros.abort();
+                continue;
+            }
+            Ok(DaemonMessage::ExclusiveRequest {
+                devices,
+                duration_ms,
+            }) => {
+                let result = match controller {
+                    Some(controller) => controller
+                        .request_exclusive(devices, duration_ms.map(Duration::from_millis)),
+                    None => Err(anyhow::anyhow!("Grabbing is unavailable")),
+                };
+                let reply = match result {
+                    Ok(duration) => {
+                        serde_json::json!({ "type": "exclusive_granted", "duration_ms": duration.as_millis() as u64 })
+                    }
+                    Err(e) => {
+                        warn!("Exclusive input denied: {:#}", e);
+                        serde_json::json!({ "type": "exclusive_denied", "reason": format!("{:#}", e) })
+                    }
+                };
+                if let Some(replies) = replies.as_mut() {
+                    if let Err(e) = writeln!(replies, "{}", reply) {
+                        debug!("Failed to answer exclusive request: {}", e);
+                    }
+                }
+                continue;
+            }
+            Ok(DaemonMessage::Stats) => {
+                let mut reply = serde_json::to_value(stats.summary()).unwrap_or_default();
+                if let Some(fields) = reply.as_object_mut() {
+                    fields.insert("type".to_string(), "stats".into());
+                }
+                if let Some(replies) = replies.as_mut() {
+                    if let Err(e) = writeln!(replies, "{}", reply) {
+                        debug!("Failed to answer stats request: {}", e);
+                    }
+                }
+                continue;
+            }
+            Ok(DaemonMessage::ExclusiveRelease) => {
+                if let Some(controller) = controller {
+                    controller.release_exclusive();
+                }
+                continue;
+            }
+            Ok(DaemonMessage::Shutdown) => {
+                info!("Shutdown requested over the socket");
+                running.store(false, Ordering::Relaxed);
+                break;
+            }
+            Err(e) => {
+                debug!("Ignoring invalid daemon message {:?}: {}", line, e);
+                continue;
+            }
+        };
+        set_visible(visible, controller, shown);
+    }
+}
+
+fn set_visible(visible: &AtomicBool, controller: Option<&GrabController>, shown: bool) {
+    visible.store(shown, Ordering::Relaxed);
+    if let Some(controller) = controller {
+        controller.set_requested(shown);
+    }
+}
+
+/// Listen for overlay visibility and game notifications until shutdown
+pub fn listen(
+    visible: Arc<AtomicBool>,
+    controller: Option<Arc<GrabController>>,
+    suppression: Arc<Suppression>,
+    macros: Arc<MacroEngine>,
+    stats: Arc<EventStats>,
+    running: Arc<AtomicBool>,
+) {
+    let controller = controller.as_deref();
+    let _ = fs::remove_file(INPUT_SOCKET);
+    let listener = match UnixListener::bind(INPUT_SOCKET) {
+        Ok(l) => l,
+        Err(e) => {
+            warn!(
+                "Failed to bind {}: {} - grab mode and pointer input disabled",
+                INPUT_SOCKET, e
+            );
+            return;
+        }
+    };
+    if let Err(e) = listener.set_nonblocking(true) {
+        warn!(
+            "Failed to set {} non-blocking: {} - grab mode and pointer input disabled",
+            INPUT_SOCKET, e
+        );
+        return;
+    }
+    info!(
+        "Listening on {} for overlay visibility (grab mode {})",
+        INPUT_SOCKET,
+        if controller.is_some() {
+            "enabled"
+        } else {
+            "disabled"
+        }
+    );
+
+    let mut last_liveness_check = Instant::now();
+    while running.load(Ordering::Relaxed) {
+        match listener.accept() {
+            Ok((stream, _)) => handle_connection(
+                stream,
+                &visible,
+                controller,
+                &suppression,
+                &macros,
+                &stats,
+                &running,
+            ),
+            Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                thread::sleep(Duration::from_millis(20))
+            }
+            Err(e) => {
+                warn!("Input socket accept error: {}", e);
+                thread::sleep(Duration::from_millis(100));
+            }
+        }
+
+        let exclusive = controller.is_some_and(|c| c.expire_exclusive());
+
+        // Never keep devices grabbed for an overlay that is gone
+        if (visible.load(Ordering::Relaxed) || exclusive)
+            && last_liveness_check.elapsed() >= LIVENESS_CHECK_INTERVAL
+        {
+            last_liveness_check = Instant::now();
+            if !overlay_alive() {
+                warn!("Overlay is not responding, treating it as hidden");
+                set_visible(&visible, controller, false);
+                if let Some(controller) = controller {
+                    controller.release_exclusive();
+                }
+            }
+        }
+    }
+
+    set_visible(&visible, controller, false);
+    if let Some(controller) = controller {
+        controller.release_exclusive();
+    }
+    let _ = fs::remove_file(INPUT_SOCKET);
+}
+
+/// Put a device into non-blocking mode so its monitor thread notices grab
+/// requests even when the device is idle
+pub fn set_nonblocking(device: &Device) -> std::io::Result<()> {
+    let fd = device.as_raw_fd();
+    // SAFETY: fcntl on a valid, owned file descriptor with standard flags
+    let flags = unsafe { libc::fcntl(fd, libc::F_GETFL) };
+    if flags < 0 || unsafe { libc::fcntl(fd, libc::F_SETFL, flags | libc::O_NONBLOCK) } < 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// Per-device grab state, owned by the device's monitor thread
+pub struct DeviceGrab {
+    target: GrabTarget,
+    grabbed: bool,
+    grab_failed: bool,
+    /// Source (min, max) for each analog axis, used to rescale to the virtual device
+    abs_ranges: HashMap<u16, (i32, i32)>,
+}
+
+impl DeviceGrab {
+    pub fn new(device: &Device, target: GrabTarget) -> Self {
+        let mut abs_ranges = HashMap::new();
+        if let (Some(axes), Ok(state)) = (device.supported_absolute_axes(), device.get_abs_state())
+        {
+            for axis in axes.iter() {
+                if let Some(info) = state.get(axis.0 as usize) {
+                    abs_ranges.insert(axis.0, (info.minimum, info.maximum));
+                }
+            }
+        }
+
+        Self {
+            target,
+            grabbed: false,
+            grab_failed: false,
+            abs_ranges,
+        }
+    }
+
+    pub fn is_grabbed(&self) -> bool {
+        self.grabbed
+    }
+
+    /// Grab or release the device to match the controller's requests
+    pub fn sync(&mut self, device: &mut Device, controller: &GrabController, device_name: &str) {
+        let requested = controller.wants(&self.target);
+        if requested == self.grabbed {
+            return;
+        }
+
+        if requested {
+            // Don't retry a failed grab every loop iteration; wait for the next request
+            if self.grab_failed {
+                return;
+            }
+            match device.grab() {
+                Ok(()) => {
+                    info!("Grabbed {}", device_name);
+                    self.grabbed = true;
+                }
+                Err(e) => {
+                    // Leave the device ungrabbed; the game keeps receiving its input
+                    warn!("Failed to grab {}: {}", device_name, e);
+                    self.grab_failed = true;
+                }
+            }
+        } else {
+            self.grab_failed = false;
+            self.release(device, device_name);
+        }
+    }
+
+    /// Release the grab if held
+    pub fn release(&mut self, device: &mut Device, device_name: &str) {
+        if !self.grabbed {
+            return;
+        }
+        match device.ungrab() {
+            Ok(()) => info!("Released {}", device_name),
+            Err(e) => warn!("Failed to release {}: {}", device_name, e),
+        }
+        self.grabbed = false;
+    }
+
+    /// Convert a physical event into the virtual device's ranges
+    fn translate(&self, event: &InputEvent) -> Option<InputEvent> {
+        match event.kind() {
+            InputEventKind::Key(_) => Some(*event),
+            InputEventKind::AbsAxis(axis) => {
+                let (target_min, target_max) = match axis {
+                    AbsoluteAxisType::ABS_X
+                    | AbsoluteAxisType::ABS_Y
+                    | AbsoluteAxisType::ABS_RX
+                    | AbsoluteAxisType::ABS_RY => (STICK_MIN, STICK_MAX),
+                    AbsoluteAxisType::ABS_Z | AbsoluteAxisType::ABS_RZ => (0, TRIGGER_MAX),
+                    AbsoluteAxisType::ABS_HAT0X | AbsoluteAxisType::ABS_HAT0Y => {
+                        return Some(*event);
+                    }
+                    _ => return None,
+                };
+                let (min, max) = self.abs_ranges.get(&axis.0).copied()?;
+                if max <= min {
+                    return None;
+                }
+                let normalized = (event.value() - min) as f64 / (max - min) as f64;
+                let value =
+                    target_min + (normalized * (target_max - target_min) as f64).round() as i32;
+                Some(InputEvent::new(EventType::ABSOLUTE, axis.0, value))
+            }
+            _ => None,
+        }
+    }
+
+    /// Forward a batch of events from a grabbed device
+    pub fn forward(&self, events: &[InputEvent], controller: &GrabController) {
+        let translated: Vec<InputEvent> = events.iter().filter_map(|e| self.translate(e)).collect();
+        controller.forward(&translated);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A controller without the virtual device, which needs /dev/uinput
+    fn controller(grab_on_show: bool) -> GrabController {
+        GrabController {
+            grab_on_show,
+            requested: AtomicBool::new(false),
+            exclusive: Mutex::new(None),
+            virtual_device: Mutex::new(None),
+        }
+    }
+
+    fn gamepad() -> GrabTarget {
+        GrabTarget {
+            path: "/dev/input/event5".to_string(),
+            name: "Xbox Wireless Controller".to_string(),
+            controller_id: Some("045e:0b13".to_string()),
+            is_gamepad: true,
+            is_keyboard: false,
+            is_pointer: false,
+        }
+    }
+
+    fn keyboard() -> GrabTarget {
+        GrabTarget {
+            path: "/dev/input/event2".to_string(),
+            name: "AT Translated Set 2 keyboard".to_string(),
+            controller_id: None,
+            is_gamepad: false,
+            is_keyboard: true,
+            is_pointer: false,
+        }
+    }
+
+    #[test]
+    fn test_grab_target_matches() {
+        let pad = gamepad();
+        for selector in [
+            "all",
+            "gamepads",
+            "/dev/input/event5",
+            "Xbox Wireless Controller",
+            "045e:0b13",
+        ] {
+            assert!(pad.matches(selector), "{}", selector);
+        }
+        for selector in ["keyboards", "pointers", "/dev/input/event2", "Xbox", ""] {
+            assert!(!pad.matches(selector), "{}", selector);
+        }
+
+        let kbd = keyboard();
+        assert!(kbd.matches("keyboards"));
+        assert!(!kbd.matches("gamepads"));
+        // An empty selector never matches a missing controller id
+        assert!(!kbd.matches(""));
+    }
+
+    #[test]
+    fn test_lease_expires_after_max_exclusive() {
+        let controller = controller(false);
+        let start = Instant::now();
+        // Asking for longer than the cap gets the cap
+        assert_eq!(
+            controller.grant_exclusive(Vec::new(), Some(Duration::from_secs(3600)), start),
+            MAX_EXCLUSIVE
+        );
+
+        let almost = start + MAX_EXCLUSIVE - Duration::from_millis(1);
+        assert!(controller.wants_at(&gamepad(), almost));
+        assert!(controller.expire_exclusive_at(almost));
+
+        let end = start + MAX_EXCLUSIVE;
+        assert!(
+            !controller.wants_at(&gamepad(), end),
+            "never grabbed past the lease"
+        );
+        assert!(!controller.expire_exclusive_at(end));
+        // Expired leases are dropped, not just ignored
+        assert!(controller.exclusive.lock().unwrap().is_none());
+        assert!(!controller.wants_at(&gamepad(), start));
+    }
+
+    #[test]
+    fn test_lease_defaults_and_renewal() {
+        let controller = controller(false);
+        let start = Instant::now();
+        assert_eq!(
+            controller.grant_exclusive(Vec::new(), None, start),
+            DEFAULT_EXCLUSIVE
+        );
+        assert!(!controller.wants_at(&gamepad(), start + DEFAULT_EXCLUSIVE));
+
+        // Renewing starts the lease over from the renewal
+        let renewed = start + Duration::from_secs(4);
+        controller.grant_exclusive(Vec::new(), None, renewed);
+        assert!(controller.wants_at(&gamepad(), start + DEFAULT_EXCLUSIVE));
+        assert!(!controller.wants_at(&gamepad(), renewed + DEFAULT_EXCLUSIVE));
+    }
+
+    #[test]
+    fn test_lease_covers_requested_devices_only() {
+        let controller = controller(false);
+        let now = Instant::now();
+        controller.grant_exclusive(vec!["gamepads".to_string()], None, now);
+        assert!(controller.wants_at(&gamepad(), now));
+        assert!(!controller.wants_at(&keyboard(), now));
+
+        controller.grant_exclusive(vec!["/dev/input/event2".to_string()], None, now);
+        assert!(!controller.wants_at(&gamepad(), now));
+        assert!(controller.wants_at(&keyboard(), now));
+    }
+
+    #[test]
+    fn test_explicit_release() {
+        let controller = controller(false);
+        let now = Instant::now();
+        controller.grant_exclusive(Vec::new(), Some(MAX_EXCLUSIVE), now);
+        assert!(controller.wants_at(&keyboard(), now));
+
+        controller.release_exclusive();
+        assert!(!controller.wants_at(&keyboard(), now));
+        assert!(!controller.expire_exclusive_at(now));
+        // Releasing with nothing held is harmless
+        controller.release_exclusive();
+    }
+
+    #[test]
+    fn test_grab_on_show_only_in_grab_mode() {
+        let passive = controller(false);
+        passive.set_requested(true);
+        assert!(!passive.wants(&gamepad()));
+
+        let grabbing = controller(true);
+        grabbing.set_requested(true);
+        assert!(grabbing.wants(&gamepad()));
+        assert!(grabbing.wants(&keyboard()));
+        grabbing.set_requested(false);
+        assert!(!grabbing.wants(&gamepad()));
+    }
+}
@@ -0,0 +1,572 @@
+//! Configurable hotkey combos
+//!
+//! Hotkeys are read from `input/hotkeys.json` under the local data directory
+//! (written with the defaults on first run). Each hotkey is a list of inputs
+//! that must all be active at once, mixing buttons and absolute axes:
+//!
+//! ```json
+//! { "axis_threshold": 0.5,
+//!   "hotkeys": [ { "action": "toggle_overlay", "inputs": ["ABS_Z", "ABS_RZ", "BTN_START"] } ] }
+//! ```
+//!
+//! Inputs are evdev names, or single characters (`"o"`) that are looked up
+//! in the keyboard layout so they follow the letter rather than the key
+//! position (see `layout`). Axes may carry their own threshold on the
+//! calibrated -1.0..1.0 scale: `ABS_Z>0.8`, or `ABS_HAT0Y<-0.5` for the
+//! negative direction; a bare axis uses `axis_threshold`. A combo fires once
+//! when its last input becomes active. `action` is sent to the overlay as the
+//! message type (e.g. `toggle_overlay`, `hide_overlay`), except
+//! `macro:<name>`, which plays a recorded macro (see `macros`).
+//!
+//! `suppress` maps cart IDs to hotkeys that game uses itself, named by input
+//! (`KEY_F12`) or by the whole combo (`KEY_LEFTCTRL+KEY_O`); see `suppression`.
+
+use crate::calibration::DeviceCalibration;
+use crate::layout::{self, KeyMap};
+use anyhow::{bail, Context, Result};
+use evdev::{AbsoluteAxisType, InputEvent, InputEventKind, Key};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+const DEFAULT_ACTION: &str = "toggle_overlay";
+const DEFAULT_AXIS_THRESHOLD: f32 = 0.5;
+const DEFAULT_LAYOUT: &str = "auto";
+
+/// One input taking part in a combo
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ComboInput {
+    Key(Key),
+    Axis {
+        axis: AbsoluteAxisType,
+        /// Magnitude past which the axis counts as active
+        threshold: f32,
+        /// Active below `-threshold` instead of above `threshold`
+        negative: bool,
+    },
+}
+
+impl ComboInput {
+    fn parse(spec: &str, default_threshold: f32, keymap: &KeyMap) -> Result<Self> {
+        if let Some(c) = layout::single_char(spec) {
+            return keymap.resolve(c).map(ComboInput::Key);
+        }
+        let spec = spec.trim();
+        let (name, threshold, negative) = match spec.find(['>', '<']) {
+            Some(pos) => {
+                let value: f32 = spec[pos + 1..]
+                    .trim()
+                    .parse()
+                    .with_context(|| format!("Invalid threshold in '{}'", spec))?;
+                (
+                    spec[..pos].trim(),
+                    value.abs(),
+                    spec[pos..].starts_with('<'),
+                )
+            }
+            None => (spec, default_threshold, false),
+        };
+
+        if name.starts_with("ABS_") {
+            let axis = AbsoluteAxisType::from_str(name)
+                .map_err(|_| anyhow::anyhow!("Unknown axis '{}'", name))?;
+            if !(0.0..=1.0).contains(&threshold) {
+                bail!("Threshold for '{}' must be between 0 and 1", name);
+            }
+            Ok(ComboInput::Axis {
+                axis,
+                threshold,
+                negative,
+            })
+        } else if spec != name {
+            bail!("Only axes take a threshold: '{}'", spec)
+        } else {
+            let key =
+                Key::from_str(name).map_err(|_| anyhow::anyhow!("Unknown button '{}'", name))?;
+            Ok(ComboInput::Key(key))
+        }
+    }
+}
+
+/// A parsed hotkey
+#[derive(Debug, Clone)]
+pub struct Hotkey {
+    pub action: String,
+    pub inputs: Vec<ComboInput>,
+    /// Original definition, for logging
+    pub label: String,
+}
+
+impl Hotkey {
+    /// Whether a suppression entry names this hotkey or one of its inputs
+    pub fn matches(&self, name: &str) -> bool {
+        name == self.label
+            || self.inputs.iter().any(|input| match input {
+                ComboInput::Key(key) => format!("{:?}", key) == name,
+                ComboInput::Axis { axis, .. } => format!("{:?}", axis) == name,
+            })
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct HotkeyDefinition {
+    #[serde(default = "default_action")]
+    action: String,
+    inputs: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct HotkeyFile {
+    #[serde(default = "default_axis_threshold")]
+    axis_threshold: f32,
+    /// Keyboard layout for character inputs, `auto` for the system's
+    #[serde(default = "default_layout")]
+    layout: String,
+    /// Character -> evdev key name, overriding the layout
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    key_aliases: HashMap<String, String>,
+    hotkeys: Vec<HotkeyDefinition>,
+    /// Cart ID -> hotkeys to disable while that game runs
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    suppress: HashMap<String, Vec<String>>,
+}
+
+fn default_action() -> String {
+    DEFAULT_ACTION.to_string()
+}
+
+fn default_axis_threshold() -> f32 {
+    DEFAULT_AXIS_THRESHOLD
+}
+
+fn default_layout() -> String {
+    DEFAULT_LAYOUT.to_string()
+}
+
+impl Default for HotkeyFile {
+    /// Guide button, F12 and Ctrl+O, as before hotkeys were configurable.
+    /// O is written as a character so it follows the keyboard layout.
+    fn default() -> Self {
+        let combo = |inputs: &[&str]| HotkeyDefinition {
+            action: default_action(),
+            inputs: inputs.iter().map(|s| s.to_string()).collect(),
+        };
+        Self {
+            axis_threshold: DEFAULT_AXIS_THRESHOLD,
+            layout: default_layout(),
+            key_aliases: HashMap::new(),
+            hotkeys: vec![
+                combo(&["BTN_MODE"]),
+                combo(&["KEY_F12"]),
+                combo(&["KEY_LEFTCTRL", "o"]),
+                combo(&["KEY_RIGHTCTRL", "o"]),
+            ],
+            suppress: HashMap::new(),
+        }
+    }
+}
+
+fn config_path() -> Option<PathBuf> {
+    dirs::data_local_dir().map(|d| d.join("kazeta-plus").join("input").join("hotkeys.json"))
+}
+
+fn parse_file(file: &HotkeyFile) -> Vec<Hotkey> {
+    let keymap = KeyMap::new(&file.layout, &file.key_aliases);
+    file.hotkeys
+        .iter()
+        .filter_map(|def| {
+            let label = def.inputs.join("+");
+            let inputs: Result<Vec<_>> = def
+                .inputs
+                .iter()
+                .map(|spec| ComboInput::parse(spec, file.axis_threshold, &keymap))
+                .collect();
+            match inputs {
+                Ok(inputs) if !inputs.is_empty() => Some(Hotkey {
+                    action: def.action.clone(),
+                    inputs,
+                    label,
+                }),
+                Ok(_) => None,
+                Err(e) => {
+                    warn!("Skipping hotkey '{}': {:#}", label, e);
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+/// Load hotkeys from disk, writing the defaults if no file exists yet
+pub fn load_hotkeys() -> Vec<Hotkey> {
+    let Some(path) = config_path() else {
+        return parse_file(&HotkeyFile::default());
+    };
+
+    let file = match fs::read_to_string(&path) {
+        Ok(content) => match serde_json::from_str::<HotkeyFile>(&content) {
+            Ok(file) => file,
+            Err(e) => {
+                warn!("Invalid {}: {} - using default hotkeys", path.display(), e);
+                HotkeyFile::default()
+            }
+        },
+        Err(_) => {
+            let file = HotkeyFile::default();
+            let written = path
+                .parent()
+                .map(fs::create_dir_all)
+                .unwrap_or(Ok(()))
+                .and_then(|_| fs::write(&path, serde_json::to_string_pretty(&file)?));
+            match written {
+                Ok(()) => info!("Wrote default hotkeys to {}", path.display()),
+                Err(e) => warn!("Failed to write {}: {}", path.display(), e),
+            }
+            file
+        }
+    };
+
+    let hotkeys = parse_file(&file);
+    if hotkeys.is_empty() {
+        warn!("No valid hotkeys configured - using defaults");
+        return parse_file(&HotkeyFile::default());
+    }
+    hotkeys
+}
+
+/// Hotkeys configured to be suppressed for a cart. Read fresh on every game
+/// start so edits apply without restarting the daemon.
+pub fn load_suppressions(cart_id: &str) -> Vec<String> {
+    let Some(path) = config_path() else {
+        return Vec::new();
+    };
+    let Ok(content) = fs::read_to_string(&path) else {
+        return Vec::new();
+    };
+    match serde_json::from_str::<HotkeyFile>(&content) {
+        Ok(mut file) => file.suppress.remove(cart_id).unwrap_or_default(),
+        Err(e) => {
+            warn!(
+                "Invalid {}: {} - no configured suppressions",
+                path.display(),
+                e
+            );
+            Vec::new()
+        }
+    }
+}
+
+/// Tracks which inputs a single device is holding and detects combos
+pub struct ComboTracker<'a> {
+    hotkeys: &'a [Hotkey],
+    held_keys: HashSet<Key>,
+    /// Latest normalized value of each axis that appears in a hotkey
+    axes: HashMap<u16, f32>,
+    /// Whether each hotkey was fully active after the previous event
+    active: Vec<bool>,
+}
+
+impl<'a> ComboTracker<'a> {
+    pub fn new(hotkeys: &'a [Hotkey]) -> Self {
+        Self {
+            hotkeys,
+            held_keys: HashSet::new(),
+            axes: HashMap::new(),
+            active: vec![false; hotkeys.len()],
+        }
+    }
+
+    pub fn is_held(&self, key: Key) -> bool {
+        self.held_keys.contains(&key)
+    }
+
+    fn is_active(&self, input: &ComboInput) -> bool {
+        match *input {
+            ComboInput::Key(key) => self.held_keys.contains(&key),
+            ComboInput::Axis {
+                axis,
+                threshold,
+                negative,
+            } => {
+                let value = self.axes.get(&axis.0).copied().unwrap_or(0.0);
+                if negative {
+                    value <= -threshold
+                } else {
+                    value >= threshold
+                }
+            }
+        }
+    }
+
+    /// Feed one event. Returns the hotkeys this event completed.
+    pub fn handle_event(
+        &mut self,
+        event: &InputEvent,
+        calibration: &mut DeviceCalibration,
+    ) -> Vec<&'a Hotkey> {
+        match event.kind() {
+            InputEventKind::Key(key) => {
+                // 2 is autorepeat, which still means held
+                if event.value() == 0 {
+                    self.held_keys.remove(&key);
+                } else {
+                    self.held_keys.insert(key);
+                }
+            }
+            InputEventKind::AbsAxis(axis) => {
+                let value = calibration.normalize(axis, event.value());
+                self.axes.insert(axis.0, value);
+            }
+            _ => return Vec::new(),
+        }
+
+        let mut completed = Vec::new();
+        for (i, hotkey) in self.hotkeys.iter().enumerate() {
+            let now_active = hotkey.inputs.iter().all(|input| self.is_active(input));
+            if now_active && !self.active[i] {
+                completed.push(hotkey);
+            }
+            self.active[i] = now_active;
+        }
+        completed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use evdev::EventType;
+
+    fn qwerty() -> KeyMap {
+        KeyMap::new("qwerty", &HashMap::new())
+    }
+
+    fn parse(spec: &str) -> Result<ComboInput> {
+        ComboInput::parse(spec, DEFAULT_AXIS_THRESHOLD, &qwerty())
+    }
+
+    fn file(json: &str) -> HotkeyFile {
+        serde_json::from_str(json).unwrap()
+    }
+
+    fn key(key: Key, value: i32) -> InputEvent {
+        InputEvent::new(EventType::KEY, key.code(), value)
+    }
+
+    fn axis(axis: AbsoluteAxisType, value: i32) -> InputEvent {
+        InputEvent::new(EventType::ABSOLUTE, axis.0, value)
+    }
+
+    #[test]
+    fn test_parse_inputs() {
+        assert_eq!(parse("BTN_MODE").unwrap(), ComboInput::Key(Key::BTN_MODE));
+        assert_eq!(parse(" KEY_F12 ").unwrap(), ComboInput::Key(Key::KEY_F12));
+        assert_eq!(parse("o").unwrap(), ComboInput::Key(Key::KEY_O));
+        assert_eq!(
+            parse("ABS_Z").unwrap(),
+            ComboInput::Axis {
+                axis: AbsoluteAxisType::ABS_Z,
+                threshold: DEFAULT_AXIS_THRESHOLD,
+                negative: false
+            }
+        );
+        assert_eq!(
+            parse("ABS_Z > 0.8").unwrap(),
+            ComboInput::Axis {
+                axis: AbsoluteAxisType::ABS_Z,
+                threshold: 0.8,
+                negative: false
+            }
+        );
+        assert_eq!(
+            parse("ABS_HAT0Y<-0.5").unwrap(),
+            ComboInput::Axis {
+                axis: AbsoluteAxisType::ABS_HAT0Y,
+                threshold: 0.5,
+                negative: true
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_bad_inputs() {
+        assert_eq!(
+            parse("BTN_NOPE").unwrap_err().to_string(),
+            "Unknown button 'BTN_NOPE'"
+        );
+        assert_eq!(
+            parse("ABS_NOPE").unwrap_err().to_string(),
+            "Unknown axis 'ABS_NOPE'"
+        );
+        assert_eq!(
+            parse("KEY_F12>0.5").unwrap_err().to_string(),
+            "Only axes take a threshold: 'KEY_F12>0.5'"
+        );
+        assert_eq!(
+            parse("ABS_Z>1.5").unwrap_err().to_string(),
+            "Threshold for 'ABS_Z' must be between 0 and 1"
+        );
+        assert_eq!(
+            parse("ABS_Z>lots").unwrap_err().to_string(),
+            "Invalid threshold in 'ABS_Z>lots'"
+        );
+        assert!(parse("€").is_err());
+    }
+
+    #[test]
+    fn test_parse_file_skips_invalid_hotkeys() {
+        let hotkeys = parse_file(&file(
+            r#"{ "layout": "qwerty", "hotkeys": [
+                { "inputs": ["KEY_LEFTCTRL", "o"] },
+                { "action": "hide_overlay", "inputs": ["BTN_NOPE"] },
+                { "action": "macro:combo", "inputs": [] },
+                { "action": "macro:combo", "inputs": ["ABS_Z", "ABS_RZ", "BTN_START"] }
+            ] }"#,
+        ));
+        assert_eq!(hotkeys.len(), 2);
+        assert_eq!(hotkeys[0].action, DEFAULT_ACTION);
+        assert_eq!(hotkeys[0].label, "KEY_LEFTCTRL+o");
+        assert_eq!(
+            hotkeys[0].inputs,
+            [
+                ComboInput::Key(Key::KEY_LEFTCTRL),
+                ComboInput::Key(Key::KEY_O)
+            ]
+        );
+        assert_eq!(hotkeys[1].action, "macro:combo");
+        assert_eq!(hotkeys[1].inputs.len(), 3);
+    }
+
+    #[test]
+    fn test_character_inputs_follow_the_layout() {
+        let hotkeys = parse_file(&file(
+            r#"{ "layout": "azerty", "hotkeys": [{ "inputs": ["KEY_LEFTCTRL", "a"] }] }"#,
+        ));
+        assert_eq!(hotkeys[0].inputs[1], ComboInput::Key(Key::KEY_Q));
+
+        let aliased = r#"{ "layout": "qwerty", "key_aliases": { "o": "KEY_P" }, "hotkeys": [{ "inputs": ["O"] }] }"#;
+        assert_eq!(
+            parse_file(&file(aliased))[0].inputs[0],
+            ComboInput::Key(Key::KEY_P)
+        );
+    }
+
+    #[test]
+    fn test_default_file_round_trip() {
+        let json = serde_json::to_string_pretty(&HotkeyFile::default()).unwrap();
+        // Empty maps are left out of the written file
+        assert!(!json.contains("suppress") && !json.contains("key_aliases"));
+
+        let mut reread: HotkeyFile = serde_json::from_str(&json).unwrap();
+        assert_eq!(reread.axis_threshold, DEFAULT_AXIS_THRESHOLD);
+        assert_eq!(reread.layout, DEFAULT_LAYOUT);
+        reread.layout = "qwerty".to_string();
+        let labels: Vec<String> = parse_file(&reread).into_iter().map(|h| h.label).collect();
+        assert_eq!(
+            labels,
+            ["BTN_MODE", "KEY_F12", "KEY_LEFTCTRL+o", "KEY_RIGHTCTRL+o"]
+        );
+
+        // Only `hotkeys` is required
+        let minimal = file(r#"{ "hotkeys": [], "suppress": { "celeste": ["KEY_F12"] } }"#);
+        assert_eq!(minimal.axis_threshold, DEFAULT_AXIS_THRESHOLD);
+        assert_eq!(minimal.suppress["celeste"], ["KEY_F12"]);
+        assert!(serde_json::from_str::<HotkeyFile>("{}").is_err());
+    }
+
+    #[test]
+    fn test_matches_combo_or_input() {
+        let hotkeys = parse_file(&file(
+            r#"{ "layout": "qwerty", "hotkeys": [{ "inputs": ["KEY_LEFTCTRL", "o"] }, { "inputs": ["ABS_Z>0.9"] }] }"#,
+        ));
+        assert!(hotkeys[0].matches("KEY_LEFTCTRL+o"));
+        assert!(hotkeys[0].matches("KEY_O"));
+        assert!(hotkeys[0].matches("KEY_LEFTCTRL"));
+        assert!(!hotkeys[0].matches("KEY_F12"));
+        assert!(hotkeys[1].matches("ABS_Z"));
+        assert!(hotkeys[1].matches("ABS_Z>0.9"));
+    }
+
+    #[test]
+    fn test_combo_fires_once_on_last_input() {
+        let hotkeys = parse_file(&file(
+            r#"{ "layout": "qwerty", "hotkeys": [{ "inputs": ["KEY_LEFTCTRL", "o"] }, { "inputs": ["KEY_F12"] }] }"#,
+        ));
+        let mut calibration = DeviceCalibration::open("test", None);
+        let mut tracker = ComboTracker::new(&hotkeys);
+
+        assert!(tracker
+            .handle_event(&key(Key::KEY_O, 1), &mut calibration)
+            .is_empty());
+        let fired = tracker.handle_event(&key(Key::KEY_LEFTCTRL, 1), &mut calibration);
+        assert_eq!(fired.len(), 1);
+        assert_eq!(fired[0].label, "KEY_LEFTCTRL+o");
+        assert!(tracker.is_held(Key::KEY_O));
+
+        // Autorepeat and unrelated keys don't fire it again
+        assert!(tracker
+            .handle_event(&key(Key::KEY_O, 2), &mut calibration)
+            .is_empty());
+        assert!(tracker
+            .handle_event(&key(Key::KEY_A, 1), &mut calibration)
+            .is_empty());
+
+        // Releasing one input re-arms it
+        assert!(tracker
+            .handle_event(&key(Key::KEY_O, 0), &mut calibration)
+            .is_empty());
+        assert!(!tracker.is_held(Key::KEY_O));
+        assert_eq!(
+            tracker
+                .handle_event(&key(Key::KEY_O, 1), &mut calibration)
+                .len(),
+            1
+        );
+
+        // Other event types are ignored
+        let sync = InputEvent::new(EventType::SYNCHRONIZATION, 0, 0);
+        assert!(tracker.handle_event(&sync, &mut calibration).is_empty());
+        assert_eq!(
+            tracker.handle_event(&key(Key::KEY_F12, 1), &mut calibration)[0].label,
+            "KEY_F12"
+        );
+    }
+
+    #[test]
+    fn test_axis_combo_uses_calibration() {
+        let hotkeys = parse_file(&file(
+            r#"{ "hotkeys": [{ "inputs": ["ABS_Z>0.8", "ABS_HAT0Y<-0.5", "BTN_START"] }] }"#,
+        ));
+        let mut calibration = DeviceCalibration::open("test", None);
+        let mut tracker = ComboTracker::new(&hotkeys);
+
+        // First values seen are the resting ones, then the full ranges
+        tracker.handle_event(&axis(AbsoluteAxisType::ABS_Z, 0), &mut calibration);
+        tracker.handle_event(&axis(AbsoluteAxisType::ABS_HAT0Y, 0), &mut calibration);
+        tracker.handle_event(&axis(AbsoluteAxisType::ABS_HAT0Y, 1), &mut calibration);
+        tracker.handle_event(&axis(AbsoluteAxisType::ABS_Z, 255), &mut calibration);
+        tracker.handle_event(&axis(AbsoluteAxisType::ABS_Z, 0), &mut calibration);
+        assert!(tracker
+            .handle_event(&key(Key::BTN_START, 1), &mut calibration)
+            .is_empty());
+
+        // Below the trigger threshold, then the hat the wrong way
+        tracker.handle_event(&axis(AbsoluteAxisType::ABS_Z, 150), &mut calibration);
+        assert!(tracker
+            .handle_event(&axis(AbsoluteAxisType::ABS_HAT0Y, 1), &mut calibration)
+            .is_empty());
+        tracker.handle_event(&axis(AbsoluteAxisType::ABS_HAT0Y, -1), &mut calibration);
+        assert_eq!(
+            tracker
+                .handle_event(&axis(AbsoluteAxisType::ABS_Z, 250), &mut calibration)
+                .len(),
+            1
+        );
+        assert!(tracker
+            .handle_event(&axis(AbsoluteAxisType::ABS_Z, 255), &mut calibration)
+            .is_empty());
+    }
+}
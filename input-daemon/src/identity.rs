@@ -0,0 +1,242 @@
+//! Stable controller identities across reconnects
+//!
+//! Reconnecting a controller gives it a new /dev/input/eventN node, so the
+//! node can't be used to remember which player a pad belongs to. Devices are
+//! identified by their evdev `uniq` (the MAC address of Bluetooth pads, a
+//! serial number for some USB ones) when the driver reports one. Otherwise
+//! the model and `phys` (the USB port) are used: a pad reconnected to the same
+//! port gets its identity back, and one moved to another port takes over the
+//! identity of a disconnected pad of the same model.
+//!
+//! Known devices and the current node→identity map are stored in
+//! `input/devices.json` under the local data directory.
+
+use evdev::Device;
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Stable identity of a connected device
+#[derive(Debug, Clone)]
+pub struct DeviceIdentity {
+    pub id: String,
+    pub name: String,
+    pub vendor: u16,
+    pub product: u16,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct KnownDevice {
+    id: String,
+    name: String,
+    vendor: u16,
+    product: u16,
+    #[serde(default)]
+    uniq: Option<String>,
+    /// `phys` without the trailing `/inputN`
+    #[serde(default)]
+    phys: Option<String>,
+    /// Unix timestamp of the last connection
+    last_seen: u64,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct DeviceDatabase {
+    devices: Vec<KnownDevice>,
+    /// Event node → identity of the devices currently connected
+    nodes: BTreeMap<String, String>,
+}
+
+/// Assigns and remembers stable identities
+pub struct DeviceRegistry {
+    path: Option<PathBuf>,
+    db: DeviceDatabase,
+}
+
+fn database_path() -> Option<PathBuf> {
+    dirs::data_local_dir().map(|d| d.join("kazeta-plus").join("input").join("devices.json"))
+}
+
+/// Treat empty and all-zero `uniq` values as missing; some drivers report those
+fn normalize_uniq(uniq: Option<&str>) -> Option<String> {
+    let uniq = uniq?.trim().to_lowercase();
+    if uniq.chars().all(|c| c == '0' || c == ':') {
+        None
+    } else {
+        Some(uniq)
+    }
+}
+
+/// `usb-0000:00:14.0-2/input0` → `usb-0000:00:14.0-2`
+fn normalize_phys(phys: Option<&str>) -> Option<String> {
+    let phys = phys?.trim();
+    if phys.is_empty() {
+        return None;
+    }
+    let port = match phys.rfind("/input") {
+        Some(pos) => &phys[..pos],
+        None => phys,
+    };
+    Some(port.to_string())
+}
+
+fn sanitize(value: &str) -> String {
+    value
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '-'
+            }
+        })
+        .collect()
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+impl DeviceRegistry {
+    /// Load known devices. Nothing is connected yet, so the node map starts empty.
+    pub fn load() -> Self {
+        let path = database_path();
+        let mut db = path
+            .as_ref()
+            .and_then(|p| fs::read_to_string(p).ok())
+            .and_then(
+                |content| match serde_json::from_str::<DeviceDatabase>(&content) {
+                    Ok(db) => Some(db),
+                    Err(e) => {
+                        warn!("Ignoring invalid device database: {}", e);
+                        None
+                    }
+                },
+            )
+            .unwrap_or_default();
+        db.nodes.clear();
+
+        if !db.devices.is_empty() {
+            info!("Loaded {} known controller identities", db.devices.len());
+        }
+        Self { path, db }
+    }
+
+    fn is_connected(&self, id: &str) -> bool {
+        self.db.nodes.values().any(|connected| connected == id)
+    }
+
+    /// Identity of the device at `node`, creating one for unknown devices
+    pub fn resolve(&mut self, node: &str, device: &Device) -> DeviceIdentity {
+        let input_id = device.input_id();
+        let name = device.name().unwrap_or("Unknown").to_string();
+        let uniq = normalize_uniq(device.unique_name());
+        let phys = normalize_phys(device.physical_path());
+        let (vendor, product) = (input_id.vendor(), input_id.product());
+
+        let index = match &uniq {
+            Some(uniq) => self
+                .db
+                .devices
+                .iter()
+                .position(|d| d.uniq.as_ref() == Some(uniq)),
+            None => {
+                let candidates: Vec<usize> = self
+                    .db
+                    .devices
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, d)| d.uniq.is_none() && d.vendor == vendor && d.product == product)
+                    .filter(|(_, d)| !self.is_connected(&d.id))
+                    .map(|(i, _)| i)
+                    .collect();
+                // Same port first, then any disconnected pad of the same model
+                candidates
+                    .iter()
+                    .copied()
+                    .find(|&i| phys.is_some() && self.db.devices[i].phys == phys)
+                    .or_else(|| candidates.first().copied())
+            }
+        };
+
+        let index = match index {
+            Some(index) => index,
+            None => {
+                let model = format!("{:04x}-{:04x}", vendor, product);
+                let id = match &uniq {
+                    Some(uniq) => format!("{}-{}", model, sanitize(uniq)),
+                    None => {
+                        let same_model = self
+                            .db
+                            .devices
+                            .iter()
+                            .filter(|d| d.id.starts_with(&model))
+                            .count();
+                        format!("{}-{}", model, same_model + 1)
+                    }
+                };
+                info!("New controller identity {} for {}", id, name);
+                self.db.devices.push(KnownDevice {
+                    id,
+                    name: name.clone(),
+                    vendor,
+                    product,
+                    uniq: uniq.clone(),
+                    phys: None,
+                    last_seen: 0,
+                });
+                self.db.devices.len() - 1
+            }
+        };
+
+        let known = &mut self.db.devices[index];
+        known.name = name;
+        known.phys = phys;
+        known.last_seen = now();
+        let identity = DeviceIdentity {
+            id: known.id.clone(),
+            name: known.name.clone(),
+            vendor,
+            product,
+        };
+
+        self.db.nodes.insert(node.to_string(), identity.id.clone());
+        self.save();
+        identity
+    }
+
+    /// Forget the node of a disconnected device, returning its identity
+    pub fn release(&mut self, node: &str) -> Option<String> {
+        let id = self.db.nodes.remove(node)?;
+        self.save();
+        Some(id)
+    }
+
+    fn save(&self) {
+        let Some(path) = &self.path else { return };
+
+        let result = path
+            .parent()
+            .map(fs::create_dir_all)
+            .unwrap_or(Ok(()))
+            .and_then(|_| {
+                let content = serde_json::to_string_pretty(&self.db)?;
+                fs::write(path, content)
+            });
+
+        match result {
+            Ok(()) => debug!("Saved device identities to {}", path.display()),
+            Err(e) => warn!(
+                "Failed to save device identities to {}: {}",
+                path.display(),
+                e
+            ),
+        }
+    }
+}
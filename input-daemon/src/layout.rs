@@ -0,0 +1,391 @@
+//! Keyboard layouts for hotkeys written as characters
+//!
+//! evdev reports physical keys, named after where they sit on a US QWERTY
+//! keyboard, so `KEY_O` is the O key only on layouts that put O there. A
+//! hotkey input written as a single character (`"o"`) is instead looked up
+//! in the active layout, so Ctrl+O follows the letter on AZERTY, QWERTZ,
+//! Dvorak and Colemak keyboards.
+//!
+//! The layout comes from `layout` in hotkeys.json: a layout name
+//! (`qwerty`, `azerty`, `qwertz`, `dvorak`, `colemak`), an XKB layout such
+//! as `fr` or `us(dvorak)`, or `auto` to use the system's XKB settings.
+//! `key_aliases` maps characters to evdev key names for anything the
+//! built-in tables get wrong.
+
+use anyhow::{bail, Result};
+use evdev::Key;
+use log::{info, warn};
+use std::collections::HashMap;
+use std::fs;
+use std::str::FromStr;
+
+/// Where the system keyboard layout is configured, in the order checked
+const XKB_CONFIG_FILES: [&str; 2] = ["/etc/vconsole.conf", "/etc/default/keyboard"];
+
+/// Physical keys of the digit and letter rows, left to right
+const ROW_KEYS: [&[Key]; 4] = [
+    &[
+        Key::KEY_1,
+        Key::KEY_2,
+        Key::KEY_3,
+        Key::KEY_4,
+        Key::KEY_5,
+        Key::KEY_6,
+        Key::KEY_7,
+        Key::KEY_8,
+        Key::KEY_9,
+        Key::KEY_0,
+    ],
+    &[
+        Key::KEY_Q,
+        Key::KEY_W,
+        Key::KEY_E,
+        Key::KEY_R,
+        Key::KEY_T,
+        Key::KEY_Y,
+        Key::KEY_U,
+        Key::KEY_I,
+        Key::KEY_O,
+        Key::KEY_P,
+        Key::KEY_LEFTBRACE,
+        Key::KEY_RIGHTBRACE,
+    ],
+    &[
+        Key::KEY_A,
+        Key::KEY_S,
+        Key::KEY_D,
+        Key::KEY_F,
+        Key::KEY_G,
+        Key::KEY_H,
+        Key::KEY_J,
+        Key::KEY_K,
+        Key::KEY_L,
+        Key::KEY_SEMICOLON,
+        Key::KEY_APOSTROPHE,
+    ],
+    &[
+        Key::KEY_Z,
+        Key::KEY_X,
+        Key::KEY_C,
+        Key::KEY_V,
+        Key::KEY_B,
+        Key::KEY_N,
+        Key::KEY_M,
+        Key::KEY_COMMA,
+        Key::KEY_DOT,
+        Key::KEY_SLASH,
+    ],
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyboardLayout {
+    Qwerty,
+    Azerty,
+    Qwertz,
+    Dvorak,
+    Colemak,
+}
+
+impl KeyboardLayout {
+    pub fn name(&self) -> &'static str {
+        match self {
+            KeyboardLayout::Qwerty => "QWERTY",
+            KeyboardLayout::Azerty => "AZERTY",
+            KeyboardLayout::Qwertz => "QWERTZ",
+            KeyboardLayout::Dvorak => "Dvorak",
+            KeyboardLayout::Colemak => "Colemak",
+        }
+    }
+
+    /// Characters printed on the keys of `ROW_KEYS`, row by row
+    fn rows(&self) -> [&'static str; 4] {
+        match self {
+            KeyboardLayout::Qwerty => ["1234567890", "qwertyuiop[]", "asdfghjkl;'", "zxcvbnm,./"],
+            KeyboardLayout::Azerty => ["1234567890", "azertyuiop^$", "qsdfghjklmù", "wxcvbn,;:!"],
+            KeyboardLayout::Qwertz => ["1234567890", "qwertzuiopü+", "asdfghjklöä", "yxcvbnm,.-"],
+            KeyboardLayout::Dvorak => ["1234567890", "',.pyfgcrl/=", "aoeuidhtns-", ";qjkxbmwvz"],
+            KeyboardLayout::Colemak => ["1234567890", "qwfpgjluy;[]", "arstdhneio'", "zxcvbkm,./"],
+        }
+    }
+
+    /// The physical key that types `c`
+    pub fn key_for(&self, c: char) -> Option<Key> {
+        let c = c.to_lowercase().next()?;
+        self.rows()
+            .iter()
+            .zip(ROW_KEYS)
+            .find_map(|(chars, keys)| chars.chars().position(|k| k == c).map(|i| keys[i]))
+    }
+
+    /// From an XKB layout and variant, e.g. `fr` or `us` + `dvorak`
+    pub fn from_xkb(layout: &str, variant: &str) -> Self {
+        // Only the first of several configured layouts is active at startup
+        let layout = layout.split(',').next().unwrap_or_default().trim();
+        let variant = variant.split(',').next().unwrap_or_default().trim();
+        // `us(dvorak)` carries the variant inline
+        let (layout, variant) = match layout.split_once('(') {
+            Some((layout, inline)) => (layout, inline.trim_end_matches(')')),
+            None => (layout, variant),
+        };
+
+        if variant.contains("dvorak") || layout == "dvorak" {
+            KeyboardLayout::Dvorak
+        } else if variant.contains("colemak") || layout == "colemak" {
+            KeyboardLayout::Colemak
+        } else {
+            match layout {
+                "fr" | "be" => KeyboardLayout::Azerty,
+                "de" | "at" | "ch" | "cz" | "hu" | "sk" | "si" | "hr" => KeyboardLayout::Qwertz,
+                _ => KeyboardLayout::Qwerty,
+            }
+        }
+    }
+
+    /// Parse the `layout` setting. None means `auto`.
+    fn parse(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "" | "auto" => None,
+            "qwerty" => Some(KeyboardLayout::Qwerty),
+            "azerty" => Some(KeyboardLayout::Azerty),
+            "qwertz" => Some(KeyboardLayout::Qwertz),
+            other => Some(Self::from_xkb(other, "")),
+        }
+    }
+
+    /// The system layout, from `XKB_DEFAULT_LAYOUT` or the XKB settings in
+    /// /etc. QWERTY when nothing is configured.
+    pub fn detect() -> Self {
+        if let Ok(layout) = std::env::var("XKB_DEFAULT_LAYOUT") {
+            let variant = std::env::var("XKB_DEFAULT_VARIANT").unwrap_or_default();
+            return Self::from_xkb(&layout, &variant);
+        }
+        for path in XKB_CONFIG_FILES {
+            let Ok(content) = fs::read_to_string(path) else {
+                continue;
+            };
+            let value = |name: &str| {
+                content
+                    .lines()
+                    .find_map(|line| line.trim().strip_prefix(name)?.strip_prefix('='))
+                    .map(|v| v.trim().trim_matches('"').to_string())
+            };
+            if let Some(layout) = value("XKBLAYOUT") {
+                return Self::from_xkb(&layout, &value("XKBVARIANT").unwrap_or_default());
+            }
+        }
+        KeyboardLayout::Qwerty
+    }
+}
+
+/// Resolves character inputs to physical keys
+pub struct KeyMap {
+    layout: KeyboardLayout,
+    aliases: HashMap<char, Key>,
+}
+
+impl KeyMap {
+    /// `layout` and `aliases` as written in hotkeys.json
+    pub fn new(layout: &str, aliases: &HashMap<String, String>) -> Self {
+        let layout = KeyboardLayout::parse(layout).unwrap_or_else(KeyboardLayout::detect);
+        info!("Character hotkeys use the {} layout", layout.name());
+
+        let aliases = aliases
+            .iter()
+            .filter_map(|(c, key)| {
+                let c = single_char(c)?.to_lowercase().next()?;
+                match Key::from_str(key) {
+                    Ok(key) => Some((c, key)),
+                    Err(_) => {
+                        warn!("Ignoring key alias '{}': unknown key '{}'", c, key);
+                        None
+                    }
+                }
+            })
+            .collect();
+        Self { layout, aliases }
+    }
+
+    /// The physical key that types `c`, preferring a configured alias
+    pub fn resolve(&self, c: char) -> Result<Key> {
+        let lower = c.to_lowercase().next().unwrap_or(c);
+        match self
+            .aliases
+            .get(&lower)
+            .copied()
+            .or_else(|| self.layout.key_for(c))
+        {
+            Some(key) => Ok(key),
+            None => bail!(
+                "'{}' isn't on the {} layout; add it to key_aliases",
+                c,
+                self.layout.name()
+            ),
+        }
+    }
+}
+
+/// The character, if `s` is exactly one
+pub fn single_char(s: &str) -> Option<char> {
+    let mut chars = s.chars();
+    let c = chars.next()?;
+    chars.next().is_none().then_some(c)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_for_each_layout() {
+        assert_eq!(KeyboardLayout::Qwerty.key_for('o'), Some(Key::KEY_O));
+        assert_eq!(KeyboardLayout::Azerty.key_for('a'), Some(Key::KEY_Q));
+        assert_eq!(
+            KeyboardLayout::Azerty.key_for('m'),
+            Some(Key::KEY_SEMICOLON)
+        );
+        assert_eq!(KeyboardLayout::Qwertz.key_for('z'), Some(Key::KEY_Y));
+        assert_eq!(
+            KeyboardLayout::Qwertz.key_for('ü'),
+            Some(Key::KEY_LEFTBRACE)
+        );
+        assert_eq!(KeyboardLayout::Dvorak.key_for('o'), Some(Key::KEY_S));
+        assert_eq!(
+            KeyboardLayout::Colemak.key_for('o'),
+            Some(Key::KEY_SEMICOLON)
+        );
+
+        // Case doesn't matter, digits don't move, and anything else isn't mapped
+        assert_eq!(KeyboardLayout::Dvorak.key_for('O'), Some(Key::KEY_S));
+        assert_eq!(KeyboardLayout::Azerty.key_for('1'), Some(Key::KEY_1));
+        assert_eq!(KeyboardLayout::Qwerty.key_for('€'), None);
+        assert_eq!(KeyboardLayout::Qwerty.key_for(' '), None);
+
+        // Every layout fills every key exactly once
+        for layout in [
+            KeyboardLayout::Qwerty,
+            KeyboardLayout::Azerty,
+            KeyboardLayout::Qwertz,
+            KeyboardLayout::Dvorak,
+            KeyboardLayout::Colemak,
+        ] {
+            for (chars, keys) in layout.rows().iter().zip(ROW_KEYS) {
+                assert_eq!(
+                    chars.chars().count(),
+                    keys.len(),
+                    "{} row {}",
+                    layout.name(),
+                    chars
+                );
+                for (c, key) in chars.chars().zip(keys) {
+                    assert_eq!(layout.key_for(c), Some(*key), "{} '{}'", layout.name(), c);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_from_xkb() {
+        assert_eq!(KeyboardLayout::from_xkb("us", ""), KeyboardLayout::Qwerty);
+        assert_eq!(KeyboardLayout::from_xkb("gb", ""), KeyboardLayout::Qwerty);
+        assert_eq!(KeyboardLayout::from_xkb("fr", ""), KeyboardLayout::Azerty);
+        assert_eq!(KeyboardLayout::from_xkb("ch", "fr"), KeyboardLayout::Qwertz);
+        assert_eq!(
+            KeyboardLayout::from_xkb("us", "dvorak"),
+            KeyboardLayout::Dvorak
+        );
+        assert_eq!(
+            KeyboardLayout::from_xkb("fr", "dvorak"),
+            KeyboardLayout::Dvorak
+        );
+        assert_eq!(
+            KeyboardLayout::from_xkb("us", "colemak_dh"),
+            KeyboardLayout::Colemak
+        );
+
+        // Inline variants
+        assert_eq!(
+            KeyboardLayout::from_xkb("us(dvorak)", ""),
+            KeyboardLayout::Dvorak
+        );
+        assert_eq!(
+            KeyboardLayout::from_xkb("de(nodeadkeys)", ""),
+            KeyboardLayout::Qwertz
+        );
+
+        // Only the first of several layouts counts
+        assert_eq!(
+            KeyboardLayout::from_xkb("de,us", ""),
+            KeyboardLayout::Qwertz
+        );
+        assert_eq!(
+            KeyboardLayout::from_xkb(" us , fr", ",dvorak"),
+            KeyboardLayout::Qwerty
+        );
+        assert_eq!(KeyboardLayout::from_xkb("", ""), KeyboardLayout::Qwerty);
+    }
+
+    #[test]
+    fn test_parse_setting() {
+        assert_eq!(KeyboardLayout::parse(""), None);
+        assert_eq!(KeyboardLayout::parse(" Auto "), None);
+        assert_eq!(
+            KeyboardLayout::parse(" AZERTY "),
+            Some(KeyboardLayout::Azerty)
+        );
+        assert_eq!(
+            KeyboardLayout::parse("qwertz"),
+            Some(KeyboardLayout::Qwertz)
+        );
+        assert_eq!(
+            KeyboardLayout::parse("Dvorak"),
+            Some(KeyboardLayout::Dvorak)
+        );
+        assert_eq!(
+            KeyboardLayout::parse("us(colemak)"),
+            Some(KeyboardLayout::Colemak)
+        );
+        assert_eq!(KeyboardLayout::parse("fr"), Some(KeyboardLayout::Azerty));
+        assert_eq!(
+            KeyboardLayout::parse("klingon"),
+            Some(KeyboardLayout::Qwerty)
+        );
+    }
+
+    #[test]
+    fn test_key_map_aliases() {
+        let aliases: HashMap<String, String> = [
+            ("€", "KEY_E"),
+            ("Q", "KEY_F"),
+            ("x", "KEY_NOPE"),
+            ("ab", "KEY_B"),
+        ]
+        .into_iter()
+        .map(|(c, key)| (c.to_string(), key.to_string()))
+        .collect();
+        let map = KeyMap::new("azerty", &aliases);
+
+        assert_eq!(
+            map.aliases.len(),
+            2,
+            "unknown keys and multi-character aliases are dropped"
+        );
+        assert_eq!(map.resolve('€').unwrap(), Key::KEY_E);
+        // Aliases win over the layout, whatever the case
+        assert_eq!(map.resolve('q').unwrap(), Key::KEY_F);
+        assert_eq!(map.resolve('Q').unwrap(), Key::KEY_F);
+        assert_eq!(map.resolve('x').unwrap(), Key::KEY_X);
+        assert_eq!(map.resolve('A').unwrap(), Key::KEY_Q);
+
+        let err = map.resolve('§').unwrap_err().to_string();
+        assert_eq!(err, "'§' isn't on the AZERTY layout; add it to key_aliases");
+    }
+
+    #[test]
+    fn test_single_char() {
+        assert_eq!(single_char("o"), Some('o'));
+        assert_eq!(single_char("é"), Some('é'));
+        assert_eq!(single_char(""), None);
+        assert_eq!(single_char("ab"), None);
+        assert_eq!(single_char("KEY_O"), None);
+    }
+}
@@ -0,0 +1,60 @@
+//! Structured JSON logs
+//!
+//! Every log line is a single JSON object with `ts`, `level`, `target` and
+//! `msg`, plus any key-values given at the call site:
+//!
+//! ```text
+//! info!(device = name, action = "toggle_overlay", latency_ms = 4.2; "Hotkey triggered");
+//! {"ts":"…","level":"INFO","target":"kazeta_input","msg":"Hotkey triggered","device":"…","action":"toggle_overlay","latency_ms":4.2}
+//! ```
+//!
+//! The keys in use are `event` (what happened, for filtering), `device`
+//! (device name), `node` (event node), `controller_id`, `hotkey` (combo),
+//! `action` and `latency_ms`. `RUST_LOG` still picks the level.
+
+use env_logger::fmt::Formatter;
+use log::kv::{self, Key, VisitSource};
+use log::Record;
+use serde_json::{Map, Value};
+use std::io::Write;
+
+pub fn init() {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info"))
+        .format(format_json)
+        .init();
+}
+
+fn format_json(buf: &mut Formatter, record: &Record) -> std::io::Result<()> {
+    let mut line = Map::new();
+    line.insert("ts".to_string(), buf.timestamp_millis().to_string().into());
+    line.insert("level".to_string(), record.level().as_str().into());
+    line.insert("target".to_string(), record.target().into());
+    line.insert("msg".to_string(), record.args().to_string().into());
+    let _ = record.key_values().visit(&mut Fields(&mut line));
+    writeln!(buf, "{}", Value::Object(line))
+}
+
+/// Copies a record's key-values into the JSON line
+struct Fields<'a>(&'a mut Map<String, Value>);
+
+impl<'kvs> VisitSource<'kvs> for Fields<'_> {
+    fn visit_pair(&mut self, key: Key<'kvs>, value: kv::Value<'kvs>) -> Result<(), kv::Error> {
+        self.0.insert(key.as_str().to_string(), to_json(&value));
+        Ok(())
+    }
+}
+
+/// Numbers and booleans stay typed so logs can be filtered with `jq`
+fn to_json(value: &kv::Value) -> Value {
+    if let Some(b) = value.to_bool() {
+        b.into()
+    } else if let Some(n) = value.to_u64() {
+        n.into()
+    } else if let Some(n) = value.to_i64() {
+        n.into()
+    } else if let Some(n) = value.to_f64() {
+        serde_json::Number::from_f64(n).map_or(Value::Null, Value::Number)
+    } else {
+        value.to_string().into()
+    }
+}
@@ -0,0 +1,652 @@
+//! Record-and-replay input macros
+//!
+//! A macro is a sequence of gamepad button presses and d-pad moves, with the
+//! delay between them, recorded from one controller and replayed through a
+//! virtual "Kazeta Macro Input" gamepad - handy for repetitive emulator menu
+//! navigation. Macros are stored in `input/macros.json` under the local data
+//! directory:
+//!
+//! ```json
+//! { "macros": { "skip-intro": { "speed": 2.0,
+//!     "steps": [ { "input": "BTN_START", "value": 1, "delay_ms": 0 },
+//!                { "input": "BTN_START", "value": 0, "delay_ms": 80 } ] } } }
+//! ```
+//!
+//! Recording and playback are driven over the daemon socket (see `grab`):
+//! `macro_record_start` (`name`, optional `controller_id`), `macro_record_stop`,
+//! `macro_play` (`name`, optional `speed`), `macro_set_speed`, `macro_delete`
+//! and `macro_abort`. A hotkey whose action is `macro:<name>` plays that macro.
+//! Without a `controller_id`, the first gamepad pressing a button is recorded.
+//!
+//! Pressing Guide on any controller ends a recording and aborts playback,
+//! releasing everything the macro was holding. That press is consumed so it
+//! doesn't also toggle the overlay.
+
+use anyhow::{Context, Result};
+use evdev::uinput::{VirtualDevice, VirtualDeviceBuilder};
+use evdev::{
+    AbsInfo, AbsoluteAxisType, AttributeSet, EventType, InputEvent, InputEventKind, Key,
+    UinputAbsSetup,
+};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+/// Name of the virtual device macros are played through. The daemon never
+/// monitors it, so playback can't trigger hotkeys or record itself.
+pub const DEVICE_NAME: &str = "Kazeta Macro Input";
+
+/// Playback waits this long so the overlay can hide and release its grab
+const START_DELAY: Duration = Duration::from_millis(300);
+
+/// Longest sleep between abort checks during playback
+const ABORT_POLL: Duration = Duration::from_millis(10);
+
+/// A recording stops by itself after this many steps
+const MAX_STEPS: usize = 2000;
+
+/// Allowed playback speed multipliers
+const MIN_SPEED: f32 = 0.25;
+const MAX_SPEED: f32 = 4.0;
+
+/// One recorded input change
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct MacroStep {
+    /// evdev name, e.g. `BTN_SOUTH` or `ABS_HAT0Y`
+    pub input: String,
+    /// 1/0 for buttons, -1/0/1 for the d-pad hat
+    pub value: i32,
+    /// Time since the previous step
+    pub delay_ms: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct InputMacro {
+    /// Playback speed multiplier; 2.0 plays twice as fast
+    #[serde(default = "default_speed")]
+    pub speed: f32,
+    pub steps: Vec<MacroStep>,
+}
+
+fn default_speed() -> f32 {
+    1.0
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct MacroFile {
+    macros: BTreeMap<String, InputMacro>,
+}
+
+fn macros_path() -> Option<PathBuf> {
+    dirs::data_local_dir().map(|d| d.join("kazeta-plus").join("input").join("macros.json"))
+}
+
+struct Recording {
+    name: String,
+    /// Controller being recorded; the first one to press a button if not given
+    controller_id: Option<String>,
+    steps: Vec<MacroStep>,
+    last_event: Option<SystemTime>,
+}
+
+impl Recording {
+    /// Convert an event into a step, for the inputs macros replay
+    fn step_for(&self, event: &InputEvent) -> Option<MacroStep> {
+        let input = match event.kind() {
+            InputEventKind::Key(Key::BTN_MODE) => return None,
+            InputEventKind::Key(key) if is_gamepad_button(key) && event.value() != 2 => {
+                format!("{:?}", key)
+            }
+            InputEventKind::AbsAxis(axis)
+                if matches!(
+                    axis,
+                    AbsoluteAxisType::ABS_HAT0X | AbsoluteAxisType::ABS_HAT0Y
+                ) =>
+            {
+                format!("{:?}", axis)
+            }
+            _ => return None,
+        };
+        let delay_ms = self
+            .last_event
+            .and_then(|last| event.timestamp().duration_since(last).ok())
+            .map_or(0, |d| d.as_millis() as u64);
+        Some(MacroStep {
+            input,
+            value: event.value(),
+            delay_ms,
+        })
+    }
+}
+
+fn is_gamepad_button(key: Key) -> bool {
+    (Key::BTN_SOUTH.code()..=Key::BTN_THUMBR.code()).contains(&key.code())
+        || (Key::BTN_DPAD_UP.code()..=Key::BTN_DPAD_RIGHT.code()).contains(&key.code())
+}
+
+/// Parse a step back into the event to emit
+fn step_event(step: &MacroStep) -> Option<InputEvent> {
+    if let Ok(key) = Key::from_str(&step.input) {
+        return Some(InputEvent::new(EventType::KEY, key.code(), step.value));
+    }
+    AbsoluteAxisType::from_str(&step.input)
+        .ok()
+        .map(|axis| InputEvent::new(EventType::ABSOLUTE, axis.0, step.value))
+}
+
+/// Stored macros plus the recording and playback in progress
+pub struct MacroEngine {
+    path: Option<PathBuf>,
+    file: Mutex<MacroFile>,
+    recording: Mutex<Option<Recording>>,
+    playing: AtomicBool,
+    abort: AtomicBool,
+    /// Created on first playback, since /dev/uinput may not be writable
+    device: Mutex<Option<VirtualDevice>>,
+}
+
+impl MacroEngine {
+    pub fn load() -> Self {
+        Self::open(macros_path())
+    }
+
+    /// Macros stored at `path`, none if it's missing or invalid. Without a
+    /// path nothing is saved.
+    fn open(path: Option<PathBuf>) -> Self {
+        let file = path
+            .as_ref()
+            .and_then(|p| fs::read_to_string(p).ok())
+            .and_then(
+                |content| match serde_json::from_str::<MacroFile>(&content) {
+                    Ok(file) => Some(file),
+                    Err(e) => {
+                        warn!("Invalid macros.json: {} - starting with no macros", e);
+                        None
+                    }
+                },
+            )
+            .unwrap_or_default();
+        if !file.macros.is_empty() {
+            info!("Loaded {} input macro(s)", file.macros.len());
+        }
+
+        Self {
+            path,
+            file: Mutex::new(file),
+            recording: Mutex::new(None),
+            playing: AtomicBool::new(false),
+            abort: AtomicBool::new(false),
+            device: Mutex::new(None),
+        }
+    }
+
+    fn save(&self, file: &MacroFile) {
+        let Some(path) = &self.path else { return };
+        let written = path
+            .parent()
+            .map(fs::create_dir_all)
+            .unwrap_or(Ok(()))
+            .and_then(|_| fs::write(path, serde_json::to_string_pretty(file)?));
+        if let Err(e) = written {
+            warn!("Failed to write {}: {}", path.display(), e);
+        }
+    }
+
+    /// Start recording a macro, replacing any recording in progress
+    pub fn start_recording(&self, name: &str, controller_id: Option<String>) {
+        if self.playing.load(Ordering::Relaxed) {
+            warn!("Not recording macro '{}' while another macro plays", name);
+            return;
+        }
+        info!(
+            "Recording macro '{}' from {}",
+            name,
+            controller_id
+                .as_deref()
+                .unwrap_or("the next controller used")
+        );
+        *self.recording.lock().unwrap() = Some(Recording {
+            name: name.to_string(),
+            controller_id,
+            steps: Vec::new(),
+            last_event: None,
+        });
+    }
+
+    /// Finish the recording and store it. Empty recordings are discarded.
+    pub fn stop_recording(&self) {
+        let Some(recording) = self.recording.lock().unwrap().take() else {
+            return;
+        };
+        if recording.steps.is_empty() {
+            info!("Macro '{}' recorded no input, discarded", recording.name);
+            return;
+        }
+
+        info!(
+            "Saved macro '{}' ({} steps)",
+            recording.name,
+            recording.steps.len()
+        );
+        let mut file = self.file.lock().unwrap();
+        let speed = file.macros.get(&recording.name).map_or(1.0, |m| m.speed);
+        file.macros.insert(
+            recording.name,
+            InputMacro {
+                speed,
+                steps: recording.steps,
+            },
+        );
+        self.save(&file);
+    }
+
+    /// Feed an event from a physical gamepad. Returns true when the event
+    /// was a Guide press that ended a recording or aborted playback.
+    pub fn handle_event(&self, controller_id: &str, event: &InputEvent) -> bool {
+        if event.kind() == InputEventKind::Key(Key::BTN_MODE) && event.value() == 1 {
+            let was_recording = self.recording.lock().unwrap().is_some();
+            if was_recording {
+                self.stop_recording();
+            }
+            let was_playing = self.playing.load(Ordering::Relaxed);
+            if was_playing {
+                info!("Guide pressed, aborting macro playback");
+                self.abort.store(true, Ordering::Relaxed);
+            }
+            return was_recording || was_playing;
+        }
+
+        let mut guard = self.recording.lock().unwrap();
+        let Some(recording) = guard.as_mut() else {
+            return false;
+        };
+        match &recording.controller_id {
+            Some(id) if id != controller_id => return false,
+            Some(_) => {}
+            None => {
+                // Lock onto the first controller that presses something
+                if !matches!(event.kind(), InputEventKind::Key(_)) || event.value() != 1 {
+                    return false;
+                }
+                recording.controller_id = Some(controller_id.to_string());
+            }
+        }
+
+        if let Some(step) = recording.step_for(event) {
+            recording.steps.push(step);
+            recording.last_event = Some(event.timestamp());
+            if recording.steps.len() >= MAX_STEPS {
+                warn!(
+                    "Macro '{}' reached {} steps, stopping the recording",
+                    recording.name, MAX_STEPS
+                );
+                drop(guard);
+                self.stop_recording();
+            }
+        }
+        false
+    }
+
+    /// Play a macro on a background thread. `speed` overrides the stored speed.
+    pub fn play(self: &Arc<Self>, name: &str, speed: Option<f32>) {
+        if self.recording.lock().unwrap().is_some() {
+            warn!("Not playing macro '{}' while recording", name);
+            return;
+        }
+        let Some(input_macro) = self.file.lock().unwrap().macros.get(name).cloned() else {
+            warn!("No macro named '{}'", name);
+            return;
+        };
+        if self.playing.swap(true, Ordering::Relaxed) {
+            warn!("A macro is already playing, ignoring '{}'", name);
+            return;
+        }
+
+        let speed = speed
+            .unwrap_or(input_macro.speed)
+            .clamp(MIN_SPEED, MAX_SPEED);
+        info!("Playing macro '{}' at {}x", name, speed);
+        self.abort.store(false, Ordering::Relaxed);
+        let engine = self.clone();
+        let name = name.to_string();
+        thread::spawn(move || {
+            if let Err(e) = engine.run(&input_macro.steps, speed) {
+                warn!("Macro '{}' failed: {:#}", name, e);
+            }
+            engine.playing.store(false, Ordering::Relaxed);
+        });
+    }
+
+    /// Stop the macro being played
+    pub fn abort(&self) {
+        if self.playing.load(Ordering::Relaxed) {
+            self.abort.store(true, Ordering::Relaxed);
+        }
+    }
+
+    pub fn set_speed(&self, name: &str, speed: f32) {
+        let mut file = self.file.lock().unwrap();
+        if let Some(input_macro) = file.macros.get_mut(name) {
+            input_macro.speed = speed.clamp(MIN_SPEED, MAX_SPEED);
+            self.save(&file);
+        }
+    }
+
+    pub fn delete(&self, name: &str) {
+        let mut file = self.file.lock().unwrap();
+        if file.macros.remove(name).is_some() {
+            info!("Deleted macro '{}'", name);
+            self.save(&file);
+        }
+    }
+
+    /// Sleep for `duration` unless aborted first. Returns false on abort.
+    fn wait(&self, duration: Duration) -> bool {
+        let mut remaining = duration;
+        while !remaining.is_zero() {
+            if self.abort.load(Ordering::Relaxed) {
+                return false;
+            }
+            let slice = remaining.min(ABORT_POLL);
+            thread::sleep(slice);
+            remaining -= slice;
+        }
+        !self.abort.load(Ordering::Relaxed)
+    }
+
+    fn run(&self, steps: &[MacroStep], speed: f32) -> Result<()> {
+        let mut device = self.device.lock().unwrap();
+        if device.is_none() {
+            *device = Some(create_device()?);
+        }
+        let device = device.as_mut().unwrap();
+
+        let mut held = HashSet::new();
+        let mut completed = self.wait(START_DELAY);
+        for step in steps.iter().take_while(|_| completed) {
+            if !self.wait(Duration::from_millis(step.delay_ms).div_f32(speed)) {
+                completed = false;
+                break;
+            }
+            let Some(event) = step_event(step) else {
+                continue;
+            };
+            if event.event_type() == EventType::KEY {
+                if step.value == 0 {
+                    held.remove(&event.code());
+                } else {
+                    held.insert(event.code());
+                }
+            }
+            device
+                .emit(&[event])
+                .context("Failed to emit macro input")?;
+        }
+
+        // Never leave a button stuck down, finished or not
+        let mut release: Vec<InputEvent> = held
+            .into_iter()
+            .map(|code| InputEvent::new(EventType::KEY, code, 0))
+            .collect();
+        release.push(InputEvent::new(
+            EventType::ABSOLUTE,
+            AbsoluteAxisType::ABS_HAT0X.0,
+            0,
+        ));
+        release.push(InputEvent::new(
+            EventType::ABSOLUTE,
+            AbsoluteAxisType::ABS_HAT0Y.0,
+            0,
+        ));
+        device
+            .emit(&release)
+            .context("Failed to release macro input")?;
+
+        info!(
+            "Macro playback {}",
+            if completed { "finished" } else { "aborted" }
+        );
+        Ok(())
+    }
+}
+
+/// Virtual gamepad with the buttons and d-pad macros can record
+fn create_device() -> Result<VirtualDevice> {
+    let mut keys = AttributeSet::<Key>::new();
+    for code in (Key::BTN_SOUTH.code()..=Key::BTN_THUMBR.code())
+        .chain(Key::BTN_DPAD_UP.code()..=Key::BTN_DPAD_RIGHT.code())
+    {
+        if code != Key::BTN_MODE.code() {
+            keys.insert(Key::new(code));
+        }
+    }
+
+    let hat = AbsInfo::new(0, -1, 1, 0, 0, 0);
+    let mut builder = VirtualDeviceBuilder::new()
+        .context("Failed to open /dev/uinput")?
+        .name(DEVICE_NAME)
+        .with_keys(&keys)?;
+    for axis in [AbsoluteAxisType::ABS_HAT0X, AbsoluteAxisType::ABS_HAT0Y] {
+        builder = builder.with_absolute_axis(&UinputAbsSetup::new(axis, hat))?;
+    }
+    builder
+        .build()
+        .context("Failed to create macro input device")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An event `ms` milliseconds into the recording
+    fn event_at(ms: i64, type_: EventType, code: u16, value: i32) -> InputEvent {
+        InputEvent::from(libc::input_event {
+            time: libc::timeval {
+                tv_sec: 1_000 + ms / 1000,
+                tv_usec: (ms % 1000) * 1000,
+            },
+            type_: type_.0,
+            code,
+            value,
+        })
+    }
+
+    fn button(ms: i64, key: Key, value: i32) -> InputEvent {
+        event_at(ms, EventType::KEY, key.code(), value)
+    }
+
+    fn step(input: &str, value: i32, delay_ms: u64) -> MacroStep {
+        MacroStep {
+            input: input.to_string(),
+            value,
+            delay_ms,
+        }
+    }
+
+    fn temp_path(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "kazeta-input-macros-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&dir);
+        dir.join("macros.json")
+    }
+
+    fn steps(engine: &MacroEngine, name: &str) -> Vec<MacroStep> {
+        engine.file.lock().unwrap().macros[name].steps.clone()
+    }
+
+    #[test]
+    fn test_records_first_controller_with_delays() {
+        let engine = MacroEngine::open(None);
+        engine.start_recording("skip-intro", None);
+
+        // Nothing is recorded until a controller presses a button
+        let hat = event_at(0, EventType::ABSOLUTE, AbsoluteAxisType::ABS_HAT0Y.0, 1);
+        assert!(!engine.handle_event("pad-1", &hat));
+        engine.handle_event("pad-1", &button(100, Key::BTN_START, 1));
+        engine.handle_event("pad-2", &button(150, Key::BTN_SOUTH, 1));
+        engine.handle_event("pad-1", &button(180, Key::BTN_START, 2));
+        engine.handle_event("pad-1", &button(180, Key::BTN_START, 0));
+        engine.handle_event(
+            "pad-1",
+            &event_at(
+                1_300,
+                EventType::ABSOLUTE,
+                AbsoluteAxisType::ABS_HAT0Y.0,
+                -1,
+            ),
+        );
+        // Sticks and keyboard keys aren't replayed
+        engine.handle_event(
+            "pad-1",
+            &event_at(1_400, EventType::ABSOLUTE, AbsoluteAxisType::ABS_X.0, 200),
+        );
+        engine.handle_event("pad-1", &button(1_400, Key::KEY_A, 1));
+
+        // Guide ends the recording and is consumed
+        assert!(engine.handle_event("pad-2", &button(1_500, Key::BTN_MODE, 1)));
+        assert!(engine.recording.lock().unwrap().is_none());
+        assert_eq!(
+            steps(&engine, "skip-intro"),
+            [
+                step("BTN_START", 1, 0),
+                step("BTN_START", 0, 80),
+                step("ABS_HAT0Y", -1, 1_120)
+            ]
+        );
+
+        // Guide with nothing going on is left for the overlay
+        assert!(!engine.handle_event("pad-1", &button(2_000, Key::BTN_MODE, 1)));
+    }
+
+    #[test]
+    fn test_named_controller_and_empty_recordings() {
+        let engine = MacroEngine::open(None);
+        engine.start_recording("menu", Some("pad-2".to_string()));
+        engine.handle_event("pad-1", &button(0, Key::BTN_SOUTH, 1));
+        engine.stop_recording();
+        assert!(
+            engine.file.lock().unwrap().macros.is_empty(),
+            "nothing from pad-2, so nothing saved"
+        );
+
+        engine.start_recording("menu", Some("pad-2".to_string()));
+        engine.handle_event("pad-1", &button(0, Key::BTN_SOUTH, 1));
+        engine.handle_event("pad-2", &button(0, Key::BTN_EAST, 1));
+        engine.stop_recording();
+        assert_eq!(steps(&engine, "menu"), [step("BTN_EAST", 1, 0)]);
+    }
+
+    #[test]
+    fn test_long_recordings_stop_themselves() {
+        let engine = MacroEngine::open(None);
+        engine.start_recording("mash", None);
+        for i in 0..MAX_STEPS as i64 + 10 {
+            engine.handle_event("pad-1", &button(i, Key::BTN_SOUTH, (i % 2 == 0) as i32));
+        }
+        assert!(engine.recording.lock().unwrap().is_none());
+        assert_eq!(steps(&engine, "mash").len(), MAX_STEPS);
+    }
+
+    #[test]
+    fn test_steps_replay_as_recorded() {
+        let recording = Recording {
+            name: "x".to_string(),
+            controller_id: None,
+            steps: Vec::new(),
+            last_event: None,
+        };
+        for event in [
+            button(0, Key::BTN_DPAD_LEFT, 1),
+            button(0, Key::BTN_TR, 0),
+            event_at(0, EventType::ABSOLUTE, AbsoluteAxisType::ABS_HAT0X.0, -1),
+        ] {
+            let step = recording.step_for(&event).unwrap();
+            let replayed = step_event(&step).unwrap();
+            assert_eq!(
+                (replayed.event_type(), replayed.code(), replayed.value()),
+                (event.event_type(), event.code(), event.value())
+            );
+        }
+        assert!(step_event(&step("BTN_NOPE", 1, 0)).is_none());
+
+        assert!(is_gamepad_button(Key::BTN_SOUTH));
+        assert!(is_gamepad_button(Key::BTN_THUMBR));
+        assert!(is_gamepad_button(Key::BTN_DPAD_RIGHT));
+        assert!(!is_gamepad_button(Key::KEY_ENTER));
+        assert!(!is_gamepad_button(Key::BTN_LEFT));
+    }
+
+    #[test]
+    fn test_macros_file_round_trip() {
+        let path = temp_path("roundtrip");
+        let engine = MacroEngine::open(Some(path.clone()));
+        engine.start_recording("skip-intro", None);
+        engine.handle_event("pad-1", &button(0, Key::BTN_START, 1));
+        engine.stop_recording();
+        engine.set_speed("skip-intro", 10.0);
+        engine.set_speed("missing", 2.0);
+
+        let reopened = MacroEngine::open(Some(path.clone()));
+        let stored = reopened.file.lock().unwrap().macros["skip-intro"].clone();
+        assert_eq!(stored.speed, MAX_SPEED);
+        assert_eq!(stored.steps, [step("BTN_START", 1, 0)]);
+        assert!(!reopened.file.lock().unwrap().macros.contains_key("missing"));
+
+        // Recording over a macro keeps its speed
+        reopened.start_recording("skip-intro", None);
+        reopened.handle_event("pad-1", &button(0, Key::BTN_SELECT, 1));
+        reopened.stop_recording();
+        assert_eq!(
+            reopened.file.lock().unwrap().macros["skip-intro"].speed,
+            MAX_SPEED
+        );
+
+        reopened.delete("skip-intro");
+        assert!(MacroEngine::open(Some(path.clone()))
+            .file
+            .lock()
+            .unwrap()
+            .macros
+            .is_empty());
+
+        // Speed defaults when left out; a damaged file loads as empty
+        fs::write(&path, r#"{"macros":{"a":{"steps":[]}}}"#).unwrap();
+        assert_eq!(
+            MacroEngine::open(Some(path.clone()))
+                .file
+                .lock()
+                .unwrap()
+                .macros["a"]
+                .speed,
+            1.0
+        );
+        fs::write(&path, "not json").unwrap();
+        assert!(MacroEngine::open(Some(path.clone()))
+            .file
+            .lock()
+            .unwrap()
+            .macros
+            .is_empty());
+
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn test_wait_stops_on_abort() {
+        let engine = MacroEngine::open(None);
+        assert!(engine.wait(Duration::from_millis(15)));
+        engine.abort.store(true, Ordering::Relaxed);
+        assert!(!engine.wait(Duration::from_secs(60)));
+        assert!(!engine.wait(Duration::ZERO));
+    }
+}
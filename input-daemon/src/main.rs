@@ -5,17 +5,70 @@
 //!
 //! Supports up to 4+ players with proper debouncing and hotplug detection.
 //!
-//! Monitored hotkeys:
+//! Default hotkeys:
 //! - Guide/Home button on controllers (BTN_MODE)
 //! - F12 key on keyboard
 //! - Ctrl+O on keyboard
 //!
+//! Hotkeys are configurable combos of buttons and analog axes, e.g. LT+RT+Start
+//! (see `hotkeys`); axes are normalized with per-device calibration stored on
+//! disk (see `calibration`).
+//!
 //! When a hotkey is detected, it sends an IPC message to the overlay daemon.
+//!
+//! With `--grab`, devices are grabbed exclusively while the overlay is shown
+//! so the game doesn't also react to menu navigation. Without it, the overlay
+//! can still lease exclusive input for a while, e.g. for its on-screen
+//! keyboard (see `grab`).
+//! Mice and touchscreens move a cursor on the overlay while it is shown
+//! (see `pointer`).
+//! Any button press also touches /tmp/kazeta-input-activity, which the BIOS
+//! idle timer watches.
+//!
+//! Gamepads get a stable controller ID that survives reconnects and USB port
+//! changes (see `identity`); the overlay is told when one connects or
+//! disconnects so player assignments follow the pad, not the event node.
+//!
+//! Carts can switch off hotkeys they use themselves while they run; holding
+//! Start+Select for 3 seconds opens the overlay regardless (see `suppression`).
+//!
+//! Button sequences can be recorded from a gamepad and replayed through a
+//! virtual device on a hotkey or from the overlay (see `macros`).
+//!
+//! Without permission to read /dev/input, keyboard hotkeys are registered
+//! with the desktop instead, through the GlobalShortcuts portal (`portal`
+//! feature) or X11 key grabs (see `desktop`).
+//!
+//! Logs are JSON lines (see `logging`), and per-device event rates and
+//! per-hotkey trigger counts are kept for diagnosing input problems (see
+//! `stats`).
+
+mod calibration;
+mod desktop;
+mod grab;
+mod hotkeys;
+mod identity;
+mod layout;
+mod logging;
+mod macros;
+mod pointer;
+#[cfg(feature = "portal")]
+mod portal;
+mod stats;
+mod suppression;
+mod x11;
 
 use anyhow::{Context, Result};
-use evdev::{Device, InputEventKind, Key};
+use calibration::DeviceCalibration;
+use evdev::{AbsoluteAxisType, Device, InputEventKind, Key, Synchronization};
+use grab::{DeviceGrab, GrabController, GrabTarget};
+use hotkeys::{ComboTracker, Hotkey};
+use identity::{DeviceIdentity, DeviceRegistry};
 use inotify::{Inotify, WatchMask};
 use log::{debug, error, info, warn};
+use macros::MacroEngine;
+use pointer::PointerTracker;
+use stats::EventStats;
 use std::collections::HashSet;
 use std::fs;
 use std::io::Write;
@@ -24,20 +77,44 @@ use std::path::Path;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 use std::thread;
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime};
+use suppression::Suppression;
 
 const OVERLAY_SOCKET: &str = "/tmp/kazeta-overlay.sock";
+
+/// Sent as `source` with every overlay message, for the overlay's message rules
+const MESSAGE_SOURCE: &str = "input-daemon";
 const INPUT_DIR: &str = "/dev/input";
+/// Touched on input so the BIOS idle timer sees activity from every device
+const ACTIVITY_FILE: &str = "/tmp/kazeta-input-activity";
+/// Minimum time between activity file updates
+const ACTIVITY_TOUCH_INTERVAL_MS: u64 = 1000;
 
 // Global debounce time to prevent multiple triggers from different controllers
 const HOTKEY_DEBOUNCE_MS: u64 = 300;
 
+/// How often the Start+Select escape hatch is checked
+const ESCAPE_POLL_MS: u64 = 100;
+
+/// Name of the Start+Select escape hatch in the hotkey stats
+const ESCAPE_LABEL: &str = "Start+Select (hold)";
+
 /// Global state shared across all device monitors
 struct GlobalState {
     /// Last time any hotkey was triggered (global debounce)
     last_hotkey_time: Instant,
     /// Set of device paths currently being monitored
     monitored_devices: HashSet<String>,
+    /// Last time the activity file was touched
+    last_activity_touch: Instant,
+    /// Stable identities of connected gamepads
+    devices: DeviceRegistry,
+    /// Hotkeys the running game disables, shared with the socket listener
+    suppression: Arc<Suppression>,
+    /// Recorded macros, shared with the socket listener
+    macros: Arc<MacroEngine>,
+    /// Event and hotkey counters, shared with the socket listener
+    stats: Arc<EventStats>,
 }
 
 impl GlobalState {
@@ -45,6 +122,11 @@ impl GlobalState {
         Self {
             last_hotkey_time: Instant::now() - Duration::from_secs(1), // Allow immediate first trigger
             monitored_devices: HashSet::new(),
+            last_activity_touch: Instant::now() - Duration::from_secs(1),
+            devices: DeviceRegistry::load(),
+            suppression: Arc::new(Suppression::default()),
+            macros: Arc::new(MacroEngine::load()),
+            stats: Arc::new(EventStats::default()),
         }
     }
 
@@ -59,81 +141,182 @@ impl GlobalState {
             false
         }
     }
-}
 
-/// Track keyboard modifier state (per-device)
-#[derive(Default)]
-struct ModifierState {
-    ctrl_left: bool,
-    ctrl_right: bool,
-}
-
-impl ModifierState {
-    fn ctrl_held(&self) -> bool {
-        self.ctrl_left || self.ctrl_right
+    /// Record user activity for the BIOS idle timer (throttled)
+    fn record_activity(&mut self) {
+        let now = Instant::now();
+        if now.duration_since(self.last_activity_touch).as_millis() as u64
+            > ACTIVITY_TOUCH_INTERVAL_MS
+        {
+            self.last_activity_touch = now;
+            if let Err(e) = fs::write(ACTIVITY_FILE, b"") {
+                debug!("Failed to update activity file: {}", e);
+            }
+        }
     }
 }
 
 /// Send a message to the overlay daemon
 fn notify_overlay(message: &str) -> Result<()> {
+    send_to_overlay(message)?;
+    debug!(event = "overlay_message"; "Sent to overlay: {}", message);
+    Ok(())
+}
+
+/// Send a message to the overlay daemon without logging it, for frequent
+/// messages like pointer motion
+fn send_to_overlay(message: &str) -> Result<()> {
     let socket_path = Path::new(OVERLAY_SOCKET);
     if !socket_path.exists() {
         debug!("Overlay socket not found, skipping notification");
         return Ok(());
     }
 
-    let mut stream = UnixStream::connect(socket_path)
-        .context("Failed to connect to overlay socket")?;
-    
+    let mut stream =
+        UnixStream::connect(socket_path).context("Failed to connect to overlay socket")?;
+
     stream.set_write_timeout(Some(Duration::from_millis(100)))?;
     writeln!(stream, "{}", message)?;
-    
-    info!("Sent to overlay: {}", message);
     Ok(())
 }
 
-/// Toggle the overlay visibility
-fn toggle_overlay(state: &Arc<Mutex<GlobalState>>, device_name: &str) {
+/// Send a hotkey's action to the overlay. `pressed_at` is the kernel
+/// timestamp of the event that completed the combo.
+fn trigger_hotkey(
+    state: &Arc<Mutex<GlobalState>>,
+    hotkey: &Hotkey,
+    device_name: &str,
+    pressed_at: SystemTime,
+) {
     // Use global debounce to prevent multiple controllers triggering at once
-    let should_trigger = {
+    let (should_trigger, stats) = {
         let mut state = state.lock().unwrap();
-        state.try_trigger()
+        (state.try_trigger(), state.stats.clone())
     };
+    let action = hotkey.action.as_str();
 
-    if should_trigger {
-        info!("Overlay toggle triggered by: {}", device_name);
-        let message = r#"{"type":"toggle_overlay"}"#;
-        if let Err(e) = notify_overlay(message) {
-            warn!("Failed to toggle overlay: {}", e);
+    if !should_trigger {
+        stats.record_debounced(&hotkey.label, action);
+        debug!(event = "hotkey_debounced", device = device_name, hotkey = hotkey.label.as_str(), action = action; "Hotkey debounced (global)");
+        return;
+    }
+
+    if let Some(name) = action.strip_prefix("macro:") {
+        let macros = state.lock().unwrap().macros.clone();
+        macros.play(name, None);
+        stats.record_triggered(&hotkey.label, action, None);
+        info!(event = "hotkey", device = device_name, hotkey = hotkey.label.as_str(), action = action; "Hotkey playing macro '{}'", name);
+        return;
+    }
+
+    let message = serde_json::json!({ "type": action, "source": MESSAGE_SOURCE }).to_string();
+    match notify_overlay(&message) {
+        Ok(()) => {
+            let latency = pressed_at.elapsed().ok();
+            stats.record_triggered(&hotkey.label, action, latency);
+            info!(
+                event = "hotkey",
+                device = device_name,
+                hotkey = hotkey.label.as_str(),
+                action = action,
+                latency_ms = latency.map_or(0.0, |l| l.as_secs_f64() * 1000.0);
+                "Hotkey triggered"
+            );
+        }
+        Err(e) => {
+            warn!(event = "hotkey_failed", device = device_name, action = action; "Failed to send to overlay: {}", e)
         }
-    } else {
-        debug!("Hotkey debounced (global) from: {}", device_name);
+    }
+}
+
+/// Open the overlay once Start+Select has been held long enough, even while
+/// the running game suppresses the regular hotkeys
+fn escape_hatch_watcher(
+    running: Arc<AtomicBool>,
+    state: Arc<Mutex<GlobalState>>,
+    suppression: Arc<Suppression>,
+) {
+    while running.load(Ordering::Relaxed) {
+        if suppression.take_escape() && state.lock().unwrap().try_trigger() {
+            info!(event = "hotkey", hotkey = ESCAPE_LABEL, action = "toggle_overlay"; "Start+Select held for {}s, toggling overlay", suppression::ESCAPE_HOLD.as_secs());
+            let message = serde_json::json!({ "type": "toggle_overlay", "source": MESSAGE_SOURCE })
+                .to_string();
+            match notify_overlay(&message) {
+                Ok(()) => state.lock().unwrap().stats.record_triggered(
+                    ESCAPE_LABEL,
+                    "toggle_overlay",
+                    None,
+                ),
+                Err(e) => {
+                    warn!(event = "hotkey_failed", action = "toggle_overlay"; "Failed to send to overlay: {}", e)
+                }
+            }
+        }
+        thread::sleep(Duration::from_millis(ESCAPE_POLL_MS));
+    }
+}
+
+/// Tell the overlay a gamepad connected, with its stable controller ID
+fn notify_controller_connected(identity: &DeviceIdentity, node: &str) {
+    let message = serde_json::json!({
+        "type": "controller_connected",
+        "source": MESSAGE_SOURCE,
+        "controller_id": identity.id,
+        "name": identity.name,
+        "node": node,
+        "vendor_id": identity.vendor,
+        "product_id": identity.product,
+    });
+    if let Err(e) = notify_overlay(&message.to_string()) {
+        warn!("Failed to send controller_connected to overlay: {}", e);
+    }
+}
+
+/// Tell the overlay a gamepad disconnected
+fn notify_controller_disconnected(controller_id: &str) {
+    let message = serde_json::json!({
+        "type": "controller_disconnected",
+        "source": MESSAGE_SOURCE,
+        "controller_id": controller_id,
+    });
+    if let Err(e) = notify_overlay(&message.to_string()) {
+        warn!("Failed to send controller_disconnected to overlay: {}", e);
     }
 }
 
 /// Check if a device is a gamepad or keyboard we want to monitor
 fn is_relevant_device(device: &Device) -> (bool, bool) {
+    // Macro playback must not be read back as a controller
+    if device.name() == Some(macros::DEVICE_NAME) {
+        return (false, false);
+    }
+
     let supported = device.supported_keys();
-    
-    let is_gamepad = supported.as_ref()
+
+    let is_gamepad = supported
+        .as_ref()
         .map(|keys| keys.contains(Key::BTN_MODE) || keys.contains(Key::BTN_SOUTH))
         .unwrap_or(false);
-    
-    let is_keyboard = supported.as_ref()
+
+    let is_keyboard = supported
+        .as_ref()
         .map(|keys| keys.contains(Key::KEY_F12) || keys.contains(Key::KEY_A))
         .unwrap_or(false);
 
     (is_gamepad, is_keyboard)
 }
 
-/// Find all input devices (gamepads and keyboards)
-fn find_input_devices(state: &Arc<Mutex<GlobalState>>) -> Vec<(String, Device)> {
+/// Find all input devices (gamepads, keyboards and pointers). Also returns
+/// whether every event device refused to open with permission denied.
+fn find_input_devices(state: &Arc<Mutex<GlobalState>>) -> (Vec<(String, Device)>, bool) {
     let mut devices = Vec::new();
-    
+    let mut opened = 0;
+    let mut denied = 0;
+
     let input_path = Path::new(INPUT_DIR);
     if !input_path.exists() {
         error!("/dev/input does not exist - not running on Linux?");
-        return devices;
+        return (devices, false);
     }
 
     let monitored = {
@@ -145,11 +328,9 @@ fn find_input_devices(state: &Arc<Mutex<GlobalState>>) -> Vec<(String, Device)>
         for entry in entries.flatten() {
             let path = entry.path();
             let path_str = path.to_string_lossy().to_string();
-            
-            let name = path.file_name()
-                .and_then(|n| n.to_str())
-                .unwrap_or("");
-            
+
+            let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+
             // Only look at event devices
             if !name.starts_with("event") {
                 continue;
@@ -162,18 +343,28 @@ fn find_input_devices(state: &Arc<Mutex<GlobalState>>) -> Vec<(String, Device)>
 
             match Device::open(&path) {
                 Ok(device) => {
+                    opened += 1;
                     let device_name = device.name().unwrap_or("Unknown");
                     let (is_gamepad, is_keyboard) = is_relevant_device(&device);
-
-                    if is_gamepad || is_keyboard {
-                        info!("Found input device: {} ({}) - gamepad={}, keyboard={}", 
-                              path.display(), device_name, is_gamepad, is_keyboard);
+                    let pointer = pointer::pointer_kind(&device);
+
+                    if is_gamepad || is_keyboard || pointer.is_some() {
+                        info!(
+                            "Found input device: {} ({}) - gamepad={}, keyboard={}, pointer={:?}",
+                            path.display(),
+                            device_name,
+                            is_gamepad,
+                            is_keyboard,
+                            pointer
+                        );
                         devices.push((path_str, device));
                     }
                 }
                 Err(e) => {
                     // Permission denied is common for devices we don't have access to
-                    if e.kind() != std::io::ErrorKind::PermissionDenied {
+                    if e.kind() == std::io::ErrorKind::PermissionDenied {
+                        denied += 1;
+                    } else {
                         debug!("Failed to open {}: {}", path.display(), e);
                     }
                 }
@@ -181,7 +372,7 @@ fn find_input_devices(state: &Arc<Mutex<GlobalState>>) -> Vec<(String, Device)>
         }
     }
 
-    devices
+    (devices, denied > 0 && opened == 0)
 }
 
 /// Monitor a single input device for hotkeys
@@ -190,63 +381,148 @@ fn monitor_device(
     mut device: Device,
     running: Arc<AtomicBool>,
     state: Arc<Mutex<GlobalState>>,
+    grab: Option<Arc<GrabController>>,
+    hotkeys: Arc<Vec<Hotkey>>,
+    overlay_visible: Arc<AtomicBool>,
 ) {
     let device_name = device.name().unwrap_or("Unknown").to_string();
-    info!("Monitoring device: {} ({})", path, device_name);
 
-    // Mark device as being monitored
-    {
+    // Mark device as being monitored, identifying gamepads
+    let (is_gamepad, is_keyboard) = is_relevant_device(&device);
+    let (suppression, macros, stats) = {
+        let global = state.lock().unwrap();
+        (
+            global.suppression.clone(),
+            global.macros.clone(),
+            global.stats.clone(),
+        )
+    };
+    let identity = {
         let mut global = state.lock().unwrap();
         global.monitored_devices.insert(path.clone());
+        is_gamepad.then(|| global.devices.resolve(&path, &device))
+    };
+    let controller_id = identity.as_ref().map(|identity| identity.id.as_str());
+    stats.device_connected(&path, &device_name, controller_id);
+    info!(
+        event = "device_connected",
+        device = device_name.as_str(),
+        node = path.as_str(),
+        controller_id = controller_id.unwrap_or("");
+        "Monitoring device"
+    );
+    if let Some(identity) = &identity {
+        notify_controller_connected(identity, &path);
     }
 
-    let mut modifiers = ModifierState::default();
-
-    // Don't grab - let the game also receive inputs
-    // We just want to monitor, not take exclusive control
+    let mut calibration = DeviceCalibration::load(&device);
+    let mut combos = ComboTracker::new(&hotkeys);
+    let mut pointer = pointer::pointer_kind(&device).map(|kind| PointerTracker::new(&device, kind));
+
+    // Don't grab by default - let the game also receive inputs.
+    // We only take exclusive control while the overlay is shown (grab mode)
+    // or while the overlay holds an exclusive lease.
+    let target = GrabTarget {
+        path: path.clone(),
+        name: device_name.clone(),
+        controller_id: identity.as_ref().map(|identity| identity.id.clone()),
+        is_gamepad,
+        is_keyboard,
+        is_pointer: pointer.is_some(),
+    };
+    let mut device_grab = DeviceGrab::new(&device, target);
+    if grab.is_some() {
+        // Poll instead of blocking so grab requests apply while the device is idle
+        if let Err(e) = grab::set_nonblocking(&device) {
+            warn!("Failed to set {} non-blocking: {}", path, e);
+        }
+    }
 
     while running.load(Ordering::Relaxed) {
+        if let Some(grab) = &grab {
+            device_grab.sync(&mut device, grab, &device_name);
+        }
+
         // Fetch events with a timeout
         match device.fetch_events() {
             Ok(events) => {
+                let events: Vec<_> = events.collect();
+                stats.record_events(
+                    &path,
+                    events
+                        .iter()
+                        .filter(|e| {
+                            e.kind() != InputEventKind::Synchronization(Synchronization::SYN_REPORT)
+                        })
+                        .count(),
+                );
+                let mut forwarded = Vec::with_capacity(events.len());
+                let visible = overlay_visible.load(Ordering::Relaxed);
+
                 for event in events {
-                    if let InputEventKind::Key(key) = event.kind() {
-                        let pressed = event.value() == 1;
-
-                        // Track modifier state
-                        match key {
-                            Key::KEY_LEFTCTRL => modifiers.ctrl_left = pressed,
-                            Key::KEY_RIGHTCTRL => modifiers.ctrl_right = pressed,
-                            _ => {}
+                    // Button presses and d-pad hats count as activity. Analog sticks are
+                    // ignored since drift would otherwise keep the system awake forever.
+                    let is_activity = match event.kind() {
+                        InputEventKind::Key(_) => event.value() == 1,
+                        InputEventKind::AbsAxis(axis) => {
+                            matches!(
+                                axis,
+                                AbsoluteAxisType::ABS_HAT0X | AbsoluteAxisType::ABS_HAT0Y
+                            ) && event.value() != 0
                         }
+                        _ => false,
+                    };
+                    if is_activity {
+                        state.lock().unwrap().record_activity();
+                    }
 
-                        // Check for hotkeys (only on press, not release)
-                        if pressed {
-                            let is_hotkey = match key {
-                                // Guide/Home button on controller
-                                Key::BTN_MODE => {
-                                    debug!("Guide button pressed on {}", device_name);
-                                    true
-                                }
-                                // F12 key
-                                Key::KEY_F12 => {
-                                    debug!("F12 pressed on {}", device_name);
-                                    true
-                                }
-                                // O key (check if Ctrl held)
-                                Key::KEY_O if modifiers.ctrl_held() => {
-                                    debug!("Ctrl+O pressed on {}", device_name);
-                                    true
-                                }
-                                _ => false,
-                            };
+                    // Guide ends a recording or aborts playback instead of toggling the overlay
+                    if let Some(identity) = &identity {
+                        if macros.handle_event(&identity.id, &event) {
+                            continue;
+                        }
+                    }
 
-                            if is_hotkey {
-                                toggle_overlay(&state, &device_name);
-                            }
+                    let mut completed = combos.handle_event(&event, &mut calibration);
+                    suppression.set_escape_held(
+                        &path,
+                        combos.is_held(Key::BTN_START) && combos.is_held(Key::BTN_SELECT),
+                    );
+                    completed.retain(|hotkey| {
+                        let suppressed = suppression.is_suppressed(hotkey);
+                        if suppressed {
+                            stats.record_suppressed(&hotkey.label, &hotkey.action);
+                        }
+                        !suppressed
+                    });
+                    if !completed.is_empty() {
+                        for hotkey in completed {
+                            trigger_hotkey(&state, hotkey, &device_name, event.timestamp());
+                        }
+                        // The overlay already got the hotkey; don't also forward the input
+                        continue;
+                    }
+
+                    if let Some(pointer) = pointer.as_mut() {
+                        if pointer.handle_event(&event, visible) {
+                            continue;
                         }
                     }
+
+                    forwarded.push(event);
+                }
+
+                if let Some(pointer) = pointer.as_mut() {
+                    pointer.flush(visible);
+                }
+
+                if device_grab.is_grabbed() {
+                    if let Some(grab) = &grab {
+                        device_grab.forward(&forwarded, grab);
+                    }
                 }
+
+                calibration.save_if_changed();
             }
             Err(e) => {
                 if e.kind() == std::io::ErrorKind::WouldBlock {
@@ -254,26 +530,39 @@ fn monitor_device(
                     thread::sleep(Duration::from_millis(10));
                 } else {
                     // Device disconnected or error
-                    warn!("Device {} disconnected or error: {}", path, e);
+                    warn!(event = "device_error", device = device_name.as_str(), node = path.as_str(); "Device disconnected or error: {}", e);
                     break;
                 }
             }
         }
     }
 
+    // Never leave a device grabbed once we stop reading it
+    device_grab.release(&mut device, &device_name);
+    calibration.save();
+    suppression.set_escape_held(&path, false);
+
     // Remove from monitored set when done
-    {
+    let released = {
         let mut global = state.lock().unwrap();
         global.monitored_devices.remove(&path);
+        global.devices.release(&path)
+    };
+    if let Some(controller_id) = released {
+        notify_controller_disconnected(&controller_id);
     }
 
-    info!("Stopped monitoring: {} ({})", path, device_name);
+    stats.device_disconnected(&path);
+    info!(event = "device_disconnected", device = device_name.as_str(), node = path.as_str(); "Stopped monitoring device");
 }
 
 /// Event-driven device scanner using inotify (hotplug support)
 fn device_scanner(
     running: Arc<AtomicBool>,
     state: Arc<Mutex<GlobalState>>,
+    grab: Option<Arc<GrabController>>,
+    hotkeys: Arc<Vec<Hotkey>>,
+    overlay_visible: Arc<AtomicBool>,
 ) -> Vec<thread::JoinHandle<()>> {
     let mut handles = Vec::new();
 
@@ -288,7 +577,10 @@ fn device_scanner(
     };
 
     // Watch /dev/input for new devices
-    if let Err(e) = inotify.watches().add(INPUT_DIR, WatchMask::CREATE | WatchMask::ATTRIB) {
+    if let Err(e) = inotify
+        .watches()
+        .add(INPUT_DIR, WatchMask::CREATE | WatchMask::ATTRIB)
+    {
         error!("Failed to watch {}: {}", INPUT_DIR, e);
         error!("Falling back to initial device scan only (no hotplug)");
         return handles;
@@ -341,10 +633,11 @@ fn device_scanner(
                             Ok(device) => {
                                 let device_name = device.name().unwrap_or("Unknown");
                                 let (is_gamepad, is_keyboard) = is_relevant_device(&device);
+                                let pointer = pointer::pointer_kind(&device);
 
-                                if is_gamepad || is_keyboard {
-                                    info!("New input device detected: {} ({}) - gamepad={}, keyboard={}",
-                                          device_path, device_name, is_gamepad, is_keyboard);
+                                if is_gamepad || is_keyboard || pointer.is_some() {
+                                    info!("New input device detected: {} ({}) - gamepad={}, keyboard={}, pointer={:?}",
+                                          device_path, device_name, is_gamepad, is_keyboard, pointer);
 
                                     // Add to monitored set
                                     {
@@ -355,8 +648,19 @@ fn device_scanner(
                                     // Spawn monitor thread
                                     let running = running.clone();
                                     let state = state.clone();
+                                    let grab = grab.clone();
+                                    let hotkeys = hotkeys.clone();
+                                    let overlay_visible = overlay_visible.clone();
                                     let handle = thread::spawn(move || {
-                                        monitor_device(device_path, device, running, state);
+                                        monitor_device(
+                                            device_path,
+                                            device,
+                                            running,
+                                            state,
+                                            grab,
+                                            hotkeys,
+                                            overlay_visible,
+                                        );
                                     });
                                     handles.push(handle);
                                 }
@@ -381,10 +685,7 @@ fn device_scanner(
 
 #[allow(unreachable_code)]
 fn main() -> Result<()> {
-    // Initialize logging
-    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info"))
-        .format_timestamp_millis()
-        .init();
+    logging::init();
 
     info!("kazeta-input daemon starting...");
     info!("Supports up to 4+ players with hotplug detection");
@@ -399,9 +700,10 @@ fn main() -> Result<()> {
 
     // Shared state for global debounce and device tracking
     let state = Arc::new(Mutex::new(GlobalState::new()));
+    let hotkeys = Arc::new(hotkeys::load_hotkeys());
 
     // Find initial devices
-    let initial_devices = find_input_devices(&state);
+    let (initial_devices, access_denied) = find_input_devices(&state);
     if initial_devices.is_empty() {
         warn!("No input devices found at startup.");
         warn!("Will continue scanning for hotplugged devices...");
@@ -421,25 +723,94 @@ fn main() -> Result<()> {
     ctrlc::set_handler(move || {
         info!("Received shutdown signal");
         running_ctrlc.store(false, Ordering::Relaxed);
-    }).context("Failed to set Ctrl+C handler")?;
+    })
+    .context("Failed to set Ctrl+C handler")?;
+
+    // Exclusive grabs: always while the overlay is shown in grab mode,
+    // otherwise only when the overlay asks for them
+    let grab = match GrabController::new(std::env::args().any(|arg| arg == "--grab")) {
+        Ok(controller) => Some(Arc::new(controller)),
+        Err(e) => {
+            warn!("Grab mode unavailable: {:#}", e);
+            warn!("Check that /dev/uinput exists and is writable by this user");
+            None
+        }
+    };
+
+    // Track overlay visibility for pointer forwarding and the grab, and the
+    // running game for hotkey suppression
+    let overlay_visible = Arc::new(AtomicBool::new(false));
+    let (suppression, macros, stats) = {
+        let global = state.lock().unwrap();
+        (
+            global.suppression.clone(),
+            global.macros.clone(),
+            global.stats.clone(),
+        )
+    };
+    {
+        let visible = overlay_visible.clone();
+        let controller = grab.clone();
+        let suppression = suppression.clone();
+        let stats = stats.clone();
+        let running = running.clone();
+        thread::spawn(move || {
+            grab::listen(visible, controller, suppression, macros, stats, running)
+        });
+    }
+    {
+        let running = running.clone();
+        thread::spawn(move || stats::reporter(stats, running));
+    }
+    {
+        let running = running.clone();
+        let state = state.clone();
+        thread::spawn(move || escape_hatch_watcher(running, state, suppression));
+    }
+
+    // Keyboard hotkeys through the desktop when evdev can't see anything.
+    // The scanner keeps watching in case access is granted later; the global
+    // debounce stops a hotkey firing twice if both see it.
+    if access_denied || std::env::args().any(|arg| arg == "--desktop-shortcuts") {
+        if access_denied {
+            warn!("No permission to read input devices - falling back to desktop shortcuts");
+        }
+        let hotkeys = hotkeys.clone();
+        let state = state.clone();
+        let running = running.clone();
+        thread::spawn(move || desktop::run(hotkeys, state, running));
+    }
 
     // Spawn monitor threads for initial devices
     let mut handles = Vec::new();
     for (path, device) in initial_devices {
         let running = running.clone();
         let state = state.clone();
+        let grab = grab.clone();
+        let hotkeys = hotkeys.clone();
+        let overlay_visible = overlay_visible.clone();
         let handle = thread::spawn(move || {
-            monitor_device(path, device, running, state);
+            monitor_device(path, device, running, state, grab, hotkeys, overlay_visible);
         });
         handles.push(handle);
     }
 
     info!("kazeta-input daemon ready");
-    info!("Hotkeys: Guide button (any controller), F12, Ctrl+O");
+    let labels: Vec<_> = hotkeys
+        .iter()
+        .map(|h| format!("{} ({})", h.label, h.action))
+        .collect();
+    info!("Hotkeys: {}", labels.join(", "));
     info!("Using inotify for event-driven hotplug detection");
 
     // Run device scanner in main thread, collecting new monitor handles
-    let scanner_handles = device_scanner(running.clone(), state.clone());
+    let scanner_handles = device_scanner(
+        running.clone(),
+        state.clone(),
+        grab,
+        hotkeys,
+        overlay_visible,
+    );
     handles.extend(scanner_handles);
 
     // Wait for all threads to finish
@@ -0,0 +1,178 @@
+//! Mouse and touchscreen forwarding to the overlay
+//!
+//! While the overlay is shown, pointer devices drive a cursor on it. The
+//! position is sent normalized to 0..1 so the overlay can scale it to its own
+//! window size, as `{"type":"pointer","x":..,"y":..,"action":..}` messages.
+//!
+//! Mice only report relative motion, so their cursor is accumulated here and
+//! starts in the middle of the screen. Touchscreens report absolute positions
+//! which are normalized with the device's axis ranges. The left button or a
+//! touch presses and releases; the right mouse button goes back.
+//!
+//! Pointer events are never forwarded to the virtual grab device; the game
+//! doesn't see them while the overlay is open and the device is grabbed.
+
+use evdev::{
+    AbsoluteAxisType, Device, InputEvent, InputEventKind, Key, PropType, RelativeAxisType,
+};
+use log::debug;
+use serde_json::json;
+use std::time::{Duration, Instant};
+
+/// Relative mouse counts to cross the whole screen horizontally / vertically
+const MOUSE_COUNTS_X: f32 = 1600.0;
+const MOUSE_COUNTS_Y: f32 = 900.0;
+
+/// Minimum time between move messages (~60Hz)
+const MOVE_INTERVAL: Duration = Duration::from_millis(16);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PointerKind {
+    Mouse,
+    Touch,
+}
+
+/// Whether a device is a mouse or a touchscreen
+pub fn pointer_kind(device: &Device) -> Option<PointerKind> {
+    let keys = device.supported_keys();
+    let has_key = |key: Key| keys.is_some_and(|keys| keys.contains(key));
+
+    let is_mouse = has_key(Key::BTN_LEFT)
+        && device.supported_relative_axes().is_some_and(|axes| {
+            axes.contains(RelativeAxisType::REL_X) && axes.contains(RelativeAxisType::REL_Y)
+        });
+    if is_mouse {
+        return Some(PointerKind::Mouse);
+    }
+
+    let is_touch = device.properties().contains(PropType::DIRECT)
+        && has_key(Key::BTN_TOUCH)
+        && device.supported_absolute_axes().is_some_and(|axes| {
+            axes.contains(AbsoluteAxisType::ABS_X)
+                || axes.contains(AbsoluteAxisType::ABS_MT_POSITION_X)
+        });
+    is_touch.then_some(PointerKind::Touch)
+}
+
+/// Cursor state for one pointer device
+pub struct PointerTracker {
+    kind: PointerKind,
+    /// Normalized cursor position
+    x: f32,
+    y: f32,
+    /// Touchscreen (min, max) for the X and Y axes
+    x_range: (i32, i32),
+    y_range: (i32, i32),
+    moved: bool,
+    last_move_sent: Instant,
+}
+
+impl PointerTracker {
+    pub fn new(device: &Device, kind: PointerKind) -> Self {
+        let mut x_range = (0, 0);
+        let mut y_range = (0, 0);
+        if let Ok(state) = device.get_abs_state() {
+            let range = |axis: AbsoluteAxisType| {
+                state
+                    .get(axis.0 as usize)
+                    .map(|info| (info.minimum, info.maximum))
+                    .unwrap_or((0, 0))
+            };
+            x_range = range(AbsoluteAxisType::ABS_X);
+            y_range = range(AbsoluteAxisType::ABS_Y);
+        }
+
+        Self {
+            kind,
+            x: 0.5,
+            y: 0.5,
+            x_range,
+            y_range,
+            moved: false,
+            last_move_sent: Instant::now() - MOVE_INTERVAL,
+        }
+    }
+
+    /// Update the cursor from an event. Returns true if it was a pointer
+    /// event, which must not be forwarded to the virtual device.
+    pub fn handle_event(&mut self, event: &InputEvent, overlay_visible: bool) -> bool {
+        match event.kind() {
+            InputEventKind::RelAxis(RelativeAxisType::REL_X) if self.kind == PointerKind::Mouse => {
+                self.x = (self.x + event.value() as f32 / MOUSE_COUNTS_X).clamp(0.0, 1.0);
+                self.moved = true;
+                true
+            }
+            InputEventKind::RelAxis(RelativeAxisType::REL_Y) if self.kind == PointerKind::Mouse => {
+                self.y = (self.y + event.value() as f32 / MOUSE_COUNTS_Y).clamp(0.0, 1.0);
+                self.moved = true;
+                true
+            }
+            InputEventKind::RelAxis(_) => true,
+            InputEventKind::AbsAxis(axis) if self.kind == PointerKind::Touch => {
+                match axis {
+                    AbsoluteAxisType::ABS_X => {
+                        self.x = normalize(event.value(), self.x_range);
+                        self.moved = true;
+                    }
+                    AbsoluteAxisType::ABS_Y => {
+                        self.y = normalize(event.value(), self.y_range);
+                        self.moved = true;
+                    }
+                    _ => {}
+                }
+                true
+            }
+            InputEventKind::Key(key) => {
+                let action = match (key, event.value()) {
+                    (Key::BTN_LEFT | Key::BTN_TOUCH, 1) => "press",
+                    (Key::BTN_LEFT | Key::BTN_TOUCH, 0) => "release",
+                    (Key::BTN_RIGHT, 1) => "back",
+                    (Key::BTN_LEFT | Key::BTN_TOUCH | Key::BTN_RIGHT | Key::BTN_MIDDLE, _) => {
+                        return true
+                    }
+                    _ => return false,
+                };
+                if overlay_visible {
+                    // The overlay needs the position the button went down at
+                    self.send_move(true);
+                    self.send(action);
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Send the cursor position if it moved, at most every `MOVE_INTERVAL`.
+    /// Call after each batch of events so motion is coalesced.
+    pub fn flush(&mut self, overlay_visible: bool) {
+        if overlay_visible {
+            self.send_move(false);
+        } else {
+            self.moved = false;
+        }
+    }
+
+    fn send_move(&mut self, force: bool) {
+        if !self.moved || (!force && self.last_move_sent.elapsed() < MOVE_INTERVAL) {
+            return;
+        }
+        self.moved = false;
+        self.last_move_sent = Instant::now();
+        self.send("move");
+    }
+
+    fn send(&self, action: &str) {
+        let message = json!({ "type": "pointer", "source": crate::MESSAGE_SOURCE, "x": self.x, "y": self.y, "action": action });
+        if let Err(e) = crate::send_to_overlay(&message.to_string()) {
+            debug!("Failed to send pointer {} to overlay: {}", action, e);
+        }
+    }
+}
+
+fn normalize(value: i32, (min, max): (i32, i32)) -> f32 {
+    if max <= min {
+        return 0.5;
+    }
+    ((value - min) as f32 / (max - min) as f32).clamp(0.0, 1.0)
+}
@@ -0,0 +1,184 @@
+//! XDG GlobalShortcuts portal backend
+//!
+//! Creates a portal session, binds the keyboard hotkeys with their preferred
+//! triggers and forwards `Activated` signals. The desktop may ask the user
+//! to confirm or rebind the shortcuts the first time; IDs are stable so the
+//! choice is remembered.
+
+use crate::desktop::Shortcut;
+use crate::hotkeys::Hotkey;
+use crate::GlobalState;
+use anyhow::{bail, Context, Result};
+use dbus::arg::{PropMap, RefArg, Variant};
+use dbus::blocking::stdintf::org_freedesktop_dbus::Properties;
+use dbus::blocking::LocalConnection;
+use dbus::message::MatchRule;
+use dbus::Path;
+use log::{debug, info};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+const DESTINATION: &str = "org.freedesktop.portal.Desktop";
+const OBJECT_PATH: &str = "/org/freedesktop/portal/desktop";
+const INTERFACE: &str = "org.freedesktop.portal.GlobalShortcuts";
+const REQUEST_INTERFACE: &str = "org.freedesktop.portal.Request";
+
+const CALL_TIMEOUT: Duration = Duration::from_secs(5);
+/// Binding may wait on the user confirming in a desktop dialog
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(120);
+/// How often the running flag is checked while waiting for signals
+const PROCESS_INTERVAL: Duration = Duration::from_millis(500);
+
+fn string_option(value: &str) -> Variant<Box<dyn RefArg>> {
+    Variant(Box::new(value.to_string()))
+}
+
+/// Call a portal method that answers through a Request object and wait for
+/// its Response. The match is added before the call so a fast reply can't
+/// be missed.
+fn request(
+    conn: &LocalConnection,
+    token: &str,
+    call: impl FnOnce(PropMap) -> Result<Path<'static>>,
+    running: &AtomicBool,
+) -> Result<PropMap> {
+    let sender = conn.unique_name().trim_start_matches(':').replace('.', "_");
+    let path = format!("{}/request/{}/{}", OBJECT_PATH, sender, token);
+
+    let (tx, rx) = mpsc::channel();
+    let rule = MatchRule::new_signal(REQUEST_INTERFACE, "Response").with_path(path.clone());
+    conn.add_match(rule, move |(code, results): (u32, PropMap), _, _| {
+        let _ = tx.send((code, results));
+        false
+    })
+    .context("Failed to watch for the portal response")?;
+
+    let mut options = PropMap::new();
+    options.insert("handle_token".to_string(), string_option(token));
+    let handle = call(options)?;
+    if handle.to_string() != path {
+        debug!(
+            "Portal request handle {} differs from expected {}",
+            handle, path
+        );
+    }
+
+    let started = Instant::now();
+    loop {
+        if let Ok((code, results)) = rx.try_recv() {
+            match code {
+                0 => return Ok(results),
+                1 => bail!("Cancelled by the user"),
+                _ => bail!("Portal request failed (response {})", code),
+            }
+        }
+        if !running.load(Ordering::Relaxed) {
+            bail!("Shutting down");
+        }
+        if started.elapsed() > RESPONSE_TIMEOUT {
+            bail!("No response from the portal");
+        }
+        conn.process(PROCESS_INTERVAL)
+            .context("D-Bus connection failed")?;
+    }
+}
+
+/// Bind the shortcuts and forward activations until shutdown. Errors if
+/// the portal or its GlobalShortcuts interface isn't available.
+pub fn run(
+    shortcuts: &[Shortcut],
+    hotkeys: &[Hotkey],
+    state: &Arc<Mutex<GlobalState>>,
+    running: &AtomicBool,
+) -> Result<()> {
+    let conn = LocalConnection::new_session().context("No D-Bus session bus")?;
+    let proxy = conn.with_proxy(DESTINATION, OBJECT_PATH, CALL_TIMEOUT);
+    let version: u32 = proxy
+        .get(INTERFACE, "version")
+        .context("Desktop portal has no GlobalShortcuts interface")?;
+    debug!("GlobalShortcuts portal version {}", version);
+
+    let token = format!("kazeta_input_{}", std::process::id());
+    let session = request(
+        &conn,
+        &format!("{}_session", token),
+        |mut options| {
+            options.insert("session_handle_token".to_string(), string_option(&token));
+            let (handle,): (Path<'static>,) = proxy
+                .method_call(INTERFACE, "CreateSession", (options,))
+                .context("CreateSession failed")?;
+            Ok(handle)
+        },
+        running,
+    )?;
+    let session = session
+        .get("session_handle")
+        .and_then(|value| value.as_str())
+        .map(|handle| Path::from(handle.to_string()))
+        .context("Portal returned no session handle")?;
+
+    let bindings: Vec<(String, PropMap)> = shortcuts
+        .iter()
+        .map(|shortcut| {
+            let hotkey = &hotkeys[shortcut.hotkey];
+            let mut options = PropMap::new();
+            options.insert(
+                "description".to_string(),
+                string_option(&format!("Kazeta+: {}", hotkey.action)),
+            );
+            options.insert(
+                "preferred_trigger".to_string(),
+                string_option(&shortcut.trigger()),
+            );
+            (shortcut.id.clone(), options)
+        })
+        .collect();
+    request(
+        &conn,
+        &format!("{}_bind", token),
+        |options| {
+            let (handle,): (Path<'static>,) = proxy
+                .method_call(
+                    INTERFACE,
+                    "BindShortcuts",
+                    (session.clone(), bindings, "", options),
+                )
+                .context("BindShortcuts failed")?;
+            Ok(handle)
+        },
+        running,
+    )?;
+
+    let (tx, rx) = mpsc::channel();
+    let rule = MatchRule::new_signal(INTERFACE, "Activated").with_path(OBJECT_PATH);
+    conn.add_match(
+        rule,
+        move |(activated, id, _, _): (Path<'static>, String, u64, PropMap), _, _| {
+            if activated == session {
+                let _ = tx.send(id);
+            }
+            true
+        },
+    )
+    .context("Failed to watch for shortcut activations")?;
+
+    let triggers: Vec<_> = shortcuts.iter().map(Shortcut::trigger).collect();
+    info!(
+        "Registered {} hotkey(s) with the GlobalShortcuts portal: {}",
+        shortcuts.len(),
+        triggers.join(", ")
+    );
+
+    while running.load(Ordering::Relaxed) {
+        conn.process(PROCESS_INTERVAL)
+            .context("D-Bus connection failed")?;
+        for id in rx.try_iter() {
+            if let Some(shortcut) = shortcuts.iter().find(|shortcut| shortcut.id == id) {
+                crate::desktop::trigger(state, &hotkeys[shortcut.hotkey]);
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,200 @@
+//! Input event statistics
+//!
+//! Counts events per device and what happened to each hotkey press, so a
+//! "my guide button doesn't work" report can be checked against real data:
+//! is the pad sending anything, did the combo complete, was it debounced or
+//! suppressed by the running game, and how long it took to reach the overlay.
+//!
+//! A summary is logged every minute, and `{"type": "stats"}` on the daemon
+//! socket answers with the same summary as `{"type": "stats", ...}`.
+//! Events per minute cover the last full minute; totals run from startup.
+
+use log::info;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How often the summary is logged and the per-minute rates refreshed
+pub const SUMMARY_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct DeviceStats {
+    pub name: String,
+    pub controller_id: Option<String>,
+    pub connected: bool,
+    /// Events seen since startup, not counting sync markers
+    pub events: u64,
+    /// Rate over the last summary interval
+    pub events_per_min: f64,
+    #[serde(skip)]
+    window_events: u64,
+}
+
+#[derive(Serialize, Clone, Debug, Default)]
+pub struct HotkeyStats {
+    pub action: String,
+    /// Sent to the overlay
+    pub triggered: u64,
+    /// Dropped by the global debounce
+    pub debounced: u64,
+    /// Swallowed because the running game suppresses it
+    pub suppressed: u64,
+    /// From the input event to the overlay having the message
+    pub last_latency_ms: Option<f64>,
+    pub max_latency_ms: Option<f64>,
+}
+
+/// What the stats request and the periodic log report
+#[derive(Serialize, Clone, Debug)]
+pub struct Summary {
+    pub uptime_secs: u64,
+    /// Devices by event node
+    pub devices: BTreeMap<String, DeviceStats>,
+    /// Hotkeys by combo, e.g. "BTN_MODE" or "LT+RT+BTN_START"
+    pub hotkeys: BTreeMap<String, HotkeyStats>,
+}
+
+struct Inner {
+    devices: BTreeMap<String, DeviceStats>,
+    hotkeys: BTreeMap<String, HotkeyStats>,
+    window_start: Instant,
+}
+
+pub struct EventStats {
+    started: Instant,
+    inner: Mutex<Inner>,
+}
+
+impl Default for EventStats {
+    fn default() -> Self {
+        Self {
+            started: Instant::now(),
+            inner: Mutex::new(Inner {
+                devices: BTreeMap::new(),
+                hotkeys: BTreeMap::new(),
+                window_start: Instant::now(),
+            }),
+        }
+    }
+}
+
+impl EventStats {
+    pub fn device_connected(&self, path: &str, name: &str, controller_id: Option<&str>) {
+        let mut inner = self.inner.lock().unwrap();
+        let device = inner.devices.entry(path.to_string()).or_default();
+        device.name = name.to_string();
+        device.controller_id = controller_id.map(str::to_string);
+        device.connected = true;
+    }
+
+    /// Disconnected devices stay in the report until the daemon restarts
+    pub fn device_disconnected(&self, path: &str) {
+        if let Some(device) = self.inner.lock().unwrap().devices.get_mut(path) {
+            device.connected = false;
+        }
+    }
+
+    pub fn record_events(&self, path: &str, count: usize) {
+        if count == 0 {
+            return;
+        }
+        if let Some(device) = self.inner.lock().unwrap().devices.get_mut(path) {
+            device.events += count as u64;
+            device.window_events += count as u64;
+        }
+    }
+
+    pub fn record_triggered(&self, label: &str, action: &str, latency: Option<Duration>) {
+        let mut inner = self.inner.lock().unwrap();
+        let hotkey = Self::hotkey(&mut inner, label, action);
+        hotkey.triggered += 1;
+        if let Some(latency) = latency {
+            let ms = latency.as_secs_f64() * 1000.0;
+            hotkey.last_latency_ms = Some(ms);
+            hotkey.max_latency_ms = Some(hotkey.max_latency_ms.map_or(ms, |max| max.max(ms)));
+        }
+    }
+
+    pub fn record_debounced(&self, label: &str, action: &str) {
+        Self::hotkey(&mut self.inner.lock().unwrap(), label, action).debounced += 1;
+    }
+
+    pub fn record_suppressed(&self, label: &str, action: &str) {
+        Self::hotkey(&mut self.inner.lock().unwrap(), label, action).suppressed += 1;
+    }
+
+    fn hotkey<'a>(inner: &'a mut Inner, label: &str, action: &str) -> &'a mut HotkeyStats {
+        inner
+            .hotkeys
+            .entry(label.to_string())
+            .or_insert_with(|| HotkeyStats {
+                action: action.to_string(),
+                ..Default::default()
+            })
+    }
+
+    pub fn summary(&self) -> Summary {
+        let inner = self.inner.lock().unwrap();
+        Summary {
+            uptime_secs: self.started.elapsed().as_secs(),
+            devices: inner.devices.clone(),
+            hotkeys: inner.hotkeys.clone(),
+        }
+    }
+
+    /// Turn the events counted since the last call into per-minute rates
+    fn roll_window(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        let minutes = inner.window_start.elapsed().as_secs_f64() / 60.0;
+        inner.window_start = Instant::now();
+        for device in inner.devices.values_mut() {
+            device.events_per_min = if minutes > 0.0 {
+                device.window_events as f64 / minutes
+            } else {
+                0.0
+            };
+            device.window_events = 0;
+        }
+    }
+}
+
+/// Refresh the rates and log a summary every `SUMMARY_INTERVAL`
+pub fn reporter(stats: Arc<EventStats>, running: Arc<AtomicBool>) {
+    let mut last = Instant::now();
+    while running.load(Ordering::Relaxed) {
+        thread::sleep(Duration::from_millis(500));
+        if last.elapsed() < SUMMARY_INTERVAL {
+            continue;
+        }
+        last = Instant::now();
+        stats.roll_window();
+
+        let summary = stats.summary();
+        for (path, device) in &summary.devices {
+            info!(
+                event = "device_stats",
+                device = device.name.as_str(),
+                node = path.as_str(),
+                connected = device.connected,
+                events = device.events,
+                events_per_min = device.events_per_min;
+                "Device stats"
+            );
+        }
+        for (label, hotkey) in &summary.hotkeys {
+            info!(
+                event = "hotkey_stats",
+                hotkey = label.as_str(),
+                action = hotkey.action.as_str(),
+                triggered = hotkey.triggered,
+                debounced = hotkey.debounced,
+                suppressed = hotkey.suppressed,
+                max_latency_ms = hotkey.max_latency_ms.unwrap_or(0.0);
+                "Hotkey stats"
+            );
+        }
+    }
+}
@@ -0,0 +1,111 @@
+//! Per-game hotkey suppression
+//!
+//! Some games use F12 or the guide button themselves. When the BIOS launches a
+//! cart it sends `{"type": "game_started", "cart_id": ..., "suppressed_hotkeys": [...]}`
+//! on the daemon socket, with the list from the cart's `.kzi`; the cart's
+//! `suppress` entry in hotkeys.json is added on top. Until `game_stopped`,
+//! hotkeys that use a suppressed input, or whose whole combo is listed, don't
+//! fire and their input reaches the game as usual.
+//!
+//! Holding Start+Select for 3 seconds always toggles the overlay, suppressed
+//! or not, so a bad list can never lock the player out of the menu.
+
+use crate::hotkeys::{self, Hotkey};
+use log::{debug, info};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// How long Start+Select must be held to open the overlay regardless
+pub const ESCAPE_HOLD: Duration = Duration::from_secs(3);
+
+/// Cart whose hotkeys are currently suppressed
+struct ActiveGame {
+    cart_id: String,
+    entries: Vec<String>,
+}
+
+/// Start+Select hold on one device
+struct EscapeHold {
+    since: Instant,
+    fired: bool,
+}
+
+#[derive(Default)]
+pub struct Suppression {
+    game: Mutex<Option<ActiveGame>>,
+    /// Device path -> ongoing Start+Select hold
+    holds: Mutex<HashMap<String, EscapeHold>>,
+}
+
+impl Suppression {
+    pub fn game_started(&self, cart_id: &str, mut entries: Vec<String>) {
+        entries.extend(hotkeys::load_suppressions(cart_id));
+        entries.retain(|e| !e.trim().is_empty());
+        if entries.is_empty() {
+            info!("Game {} started, no hotkeys suppressed", cart_id);
+        } else {
+            info!(
+                "Game {} started, suppressing hotkeys: {}",
+                cart_id,
+                entries.join(", ")
+            );
+        }
+        *self.game.lock().unwrap() = Some(ActiveGame {
+            cart_id: cart_id.to_string(),
+            entries,
+        });
+    }
+
+    pub fn game_stopped(&self) {
+        if let Some(game) = self.game.lock().unwrap().take() {
+            info!("Game {} stopped, hotkeys restored", game.cart_id);
+        }
+    }
+
+    pub fn is_suppressed(&self, hotkey: &Hotkey) -> bool {
+        let game = self.game.lock().unwrap();
+        let Some(game) = game.as_ref() else {
+            return false;
+        };
+        let suppressed = game
+            .entries
+            .iter()
+            .any(|entry| hotkey.matches(entry.trim()));
+        if suppressed {
+            debug!("Hotkey {} suppressed for {}", hotkey.label, game.cart_id);
+        }
+        suppressed
+    }
+
+    /// Record whether a device is holding Start+Select
+    pub fn set_escape_held(&self, device: &str, held: bool) {
+        let mut holds = self.holds.lock().unwrap();
+        if !held {
+            holds.remove(device);
+        } else if !holds.contains_key(device) {
+            holds.insert(
+                device.to_string(),
+                EscapeHold {
+                    since: Instant::now(),
+                    fired: false,
+                },
+            );
+        }
+    }
+
+    /// True once per hold that has lasted `ESCAPE_HOLD`
+    pub fn take_escape(&self) -> bool {
+        let mut holds = self.holds.lock().unwrap();
+        let due = holds
+            .values_mut()
+            .find(|h| !h.fired && h.since.elapsed() >= ESCAPE_HOLD);
+        match due {
+            Some(hold) => {
+                hold.fired = true;
+                true
+            }
+            None => false,
+        }
+    }
+}
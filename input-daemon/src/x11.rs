@@ -0,0 +1,213 @@
+//! X11 XGrabKey backend
+//!
+//! For X11 sessions whose portal has no GlobalShortcuts. libX11 is loaded at
+//! runtime so the daemon doesn't link against it on Wayland-only systems.
+//! Each shortcut is grabbed on the root window with and without Caps Lock
+//! and Num Lock, since X matches modifier state exactly.
+
+use crate::desktop::{Modifier, Shortcut};
+use crate::hotkeys::Hotkey;
+use crate::GlobalState;
+use anyhow::{bail, Context, Result};
+use libloading::Library;
+use log::{info, warn};
+use std::collections::HashSet;
+use std::ffi::{c_char, c_int, c_uint, c_ulong, c_void, CString};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+type Display = c_void;
+type Window = c_ulong;
+type KeySym = c_ulong;
+type ErrorHandler = unsafe extern "C" fn(*mut Display, *mut c_void) -> c_int;
+
+const KEY_PRESS: c_int = 2;
+const KEY_RELEASE: c_int = 3;
+const SHIFT_MASK: c_uint = 1 << 0;
+const LOCK_MASK: c_uint = 1 << 1;
+const CONTROL_MASK: c_uint = 1 << 2;
+const MOD1_MASK: c_uint = 1 << 3;
+/// Num Lock on nearly every keymap
+const MOD2_MASK: c_uint = 1 << 4;
+const MOD4_MASK: c_uint = 1 << 6;
+const GRAB_MODE_ASYNC: c_int = 1;
+/// XEvent is a union padded to 24 longs
+const XEVENT_SIZE: usize = 24 * std::mem::size_of::<c_ulong>();
+/// Offsets of `state` and `keycode` in XKeyEvent on 64-bit
+const KEY_STATE_OFFSET: usize = 80;
+const KEY_CODE_OFFSET: usize = 84;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Set by the error handler; XGrabKey reports BadAccess asynchronously
+static GRAB_FAILED: AtomicBool = AtomicBool::new(false);
+
+unsafe extern "C" fn on_error(_: *mut Display, _: *mut c_void) -> c_int {
+    GRAB_FAILED.store(true, Ordering::Relaxed);
+    0
+}
+
+fn mask(modifier: Modifier) -> c_uint {
+    match modifier {
+        Modifier::Ctrl => CONTROL_MASK,
+        Modifier::Alt => MOD1_MASK,
+        Modifier::Shift => SHIFT_MASK,
+        Modifier::Logo => MOD4_MASK,
+    }
+}
+
+/// The libX11 functions used, copied out of the loaded library
+struct Xlib {
+    open_display: unsafe extern "C" fn(*const c_char) -> *mut Display,
+    close_display: unsafe extern "C" fn(*mut Display) -> c_int,
+    default_root_window: unsafe extern "C" fn(*mut Display) -> Window,
+    string_to_keysym: unsafe extern "C" fn(*const c_char) -> KeySym,
+    keysym_to_keycode: unsafe extern "C" fn(*mut Display, KeySym) -> u8,
+    grab_key:
+        unsafe extern "C" fn(*mut Display, c_int, c_uint, Window, c_int, c_int, c_int) -> c_int,
+    set_error_handler: unsafe extern "C" fn(Option<ErrorHandler>) -> Option<ErrorHandler>,
+    set_detectable_auto_repeat: unsafe extern "C" fn(*mut Display, c_int, *mut c_int) -> c_int,
+    sync: unsafe extern "C" fn(*mut Display, c_int) -> c_int,
+    pending: unsafe extern "C" fn(*mut Display) -> c_int,
+    next_event: unsafe extern "C" fn(*mut Display, *mut c_void) -> c_int,
+    _library: Library,
+}
+
+impl Xlib {
+    fn load() -> Result<Self> {
+        // SAFETY: libX11 has no library constructors with preconditions, and
+        // every symbol is copied out with the signature Xlib.h declares
+        unsafe {
+            let library = Library::new("libX11.so.6").context("Failed to load libX11")?;
+            macro_rules! symbol {
+                ($name:literal) => {
+                    *library
+                        .get(concat!($name, "\0").as_bytes())
+                        .context(concat!("libX11 has no ", $name))?
+                };
+            }
+            Ok(Self {
+                open_display: symbol!("XOpenDisplay"),
+                close_display: symbol!("XCloseDisplay"),
+                default_root_window: symbol!("XDefaultRootWindow"),
+                string_to_keysym: symbol!("XStringToKeysym"),
+                keysym_to_keycode: symbol!("XKeysymToKeycode"),
+                grab_key: symbol!("XGrabKey"),
+                set_error_handler: symbol!("XSetErrorHandler"),
+                set_detectable_auto_repeat: symbol!("XkbSetDetectableAutoRepeat"),
+                sync: symbol!("XSync"),
+                pending: symbol!("XPending"),
+                next_event: symbol!("XNextEvent"),
+                _library: library,
+            })
+        }
+    }
+}
+
+/// Grab the shortcuts on the root window and forward presses until
+/// shutdown. Errors without an X display or if no shortcut could be grabbed.
+pub fn run(
+    shortcuts: &[Shortcut],
+    hotkeys: &[Hotkey],
+    state: &Arc<Mutex<GlobalState>>,
+    running: &AtomicBool,
+) -> Result<()> {
+    if std::env::var_os("DISPLAY").is_none() {
+        bail!("DISPLAY is not set");
+    }
+    let x = Xlib::load()?;
+
+    // SAFETY: the display pointer is checked for null and only used on this
+    // thread until it is closed below; event buffers are XEvent-sized
+    unsafe {
+        let display = (x.open_display)(std::ptr::null());
+        if display.is_null() {
+            bail!("Failed to open the X display");
+        }
+        (x.set_error_handler)(Some(on_error));
+        // Held keys report one press instead of a press/release stream
+        (x.set_detectable_auto_repeat)(display, 1, std::ptr::null_mut());
+        let root = (x.default_root_window)(display);
+
+        // Keycode and modifier mask for each shortcut that was grabbed
+        let mut grabbed = Vec::new();
+        let mut triggers = Vec::new();
+        for shortcut in shortcuts {
+            let Ok(name) = CString::new(shortcut.keysym.as_str()) else {
+                continue;
+            };
+            let keycode = (x.keysym_to_keycode)(display, (x.string_to_keysym)(name.as_ptr()));
+            if keycode == 0 {
+                warn!(
+                    "No keycode for {} - skipping {}",
+                    shortcut.keysym,
+                    shortcut.trigger()
+                );
+                continue;
+            }
+            let modifiers = shortcut.modifiers.iter().fold(0, |acc, m| acc | mask(*m));
+
+            GRAB_FAILED.store(false, Ordering::Relaxed);
+            for locks in [0, LOCK_MASK, MOD2_MASK, LOCK_MASK | MOD2_MASK] {
+                (x.grab_key)(
+                    display,
+                    keycode as c_int,
+                    modifiers | locks,
+                    root,
+                    0,
+                    GRAB_MODE_ASYNC,
+                    GRAB_MODE_ASYNC,
+                );
+            }
+            (x.sync)(display, 0);
+            if GRAB_FAILED.load(Ordering::Relaxed) {
+                warn!(
+                    "{} is already grabbed by another client",
+                    shortcut.trigger()
+                );
+                continue;
+            }
+            grabbed.push((keycode as c_uint, modifiers, shortcut.hotkey));
+            triggers.push(shortcut.trigger());
+        }
+        if grabbed.is_empty() {
+            (x.close_display)(display);
+            bail!("No shortcut could be grabbed");
+        }
+        info!(
+            "Grabbed {} hotkey(s) on the X11 root window: {}",
+            grabbed.len(),
+            triggers.join(", ")
+        );
+
+        let mut event = [0u8; XEVENT_SIZE];
+        let mut held = HashSet::new();
+        while running.load(Ordering::Relaxed) {
+            while (x.pending)(display) > 0 {
+                (x.next_event)(display, event.as_mut_ptr().cast());
+                let kind = c_int::from_ne_bytes(event[..4].try_into().unwrap());
+                let read = |offset: usize| {
+                    c_uint::from_ne_bytes(event[offset..offset + 4].try_into().unwrap())
+                };
+                let keycode = read(KEY_CODE_OFFSET);
+                let modifiers = read(KEY_STATE_OFFSET) & !(LOCK_MASK | MOD2_MASK);
+
+                if kind == KEY_RELEASE {
+                    held.remove(&keycode);
+                } else if kind == KEY_PRESS && held.insert(keycode) {
+                    let hit = grabbed
+                        .iter()
+                        .find(|(code, mods, _)| *code == keycode && *mods == modifiers);
+                    if let Some((_, _, hotkey)) = hit {
+                        crate::desktop::trigger(state, &hotkeys[*hotkey]);
+                    }
+                }
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+        (x.close_display)(display);
+    }
+    Ok(())
+}
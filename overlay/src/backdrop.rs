@@ -0,0 +1,144 @@
+//! Backdrop behind the overlay menu: how much the game is dimmed, how
+//! translucent the panels are, and whether the compositor blurs the game
+//!
+//! Blur is asked for with KWin's `_KDE_NET_WM_BLUR_BEHIND_REGION` window
+//! property, which KWin and picom (`blur-background`) honour on X11. Where
+//! the compositor doesn't announce it the game is dimmed a little more
+//! instead, so text stays as legible.
+
+use serde::{Deserialize, Serialize};
+use std::process::Command;
+
+/// Step of the opacity sliders
+pub const OPACITY_STEP: f32 = 0.05;
+
+/// Panels can't go fully transparent, or the text would float over the game
+pub const MIN_PANEL_OPACITY: f32 = 0.3;
+
+/// Extra dimming used when blur is on but the compositor can't do it
+const BLUR_FALLBACK_DIM: f32 = 0.2;
+
+/// Property the compositor reads to blur what's behind a window
+const BLUR_PROPERTY: &str = "_KDE_NET_WM_BLUR_BEHIND_REGION";
+
+/// Title of the overlay window, used to find it with xprop
+const WINDOW_NAME: &str = "Kazeta Overlay";
+
+/// Backdrop settings of one theme
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Backdrop {
+    /// Alpha of the dimming drawn over the game
+    pub opacity: f32,
+    /// Alpha of the menu panels
+    pub panel_opacity: f32,
+    /// Blur the game behind the overlay where the compositor supports it
+    #[serde(default)]
+    pub blur: bool,
+}
+
+impl Backdrop {
+    /// Change the dimming by `steps` slider steps
+    pub fn adjust_opacity(&mut self, steps: i32) {
+        self.opacity = Self::step(self.opacity, steps, 0.0);
+    }
+
+    /// Change the panel opacity by `steps` slider steps
+    pub fn adjust_panel_opacity(&mut self, steps: i32) {
+        self.panel_opacity = Self::step(self.panel_opacity, steps, MIN_PANEL_OPACITY);
+    }
+
+    fn step(value: f32, steps: i32, min: f32) -> f32 {
+        // Snap to the step grid so repeated presses don't drift
+        let stepped = ((value / OPACITY_STEP).round() + steps as f32) * OPACITY_STEP;
+        stepped.clamp(min, 1.0)
+    }
+
+    /// Alpha of the dimming actually drawn: darker when blur was asked for
+    /// but isn't happening
+    pub fn effective_opacity(&self, blur_active: bool) -> f32 {
+        if self.blur && !blur_active {
+            (self.opacity + BLUR_FALLBACK_DIM).min(1.0)
+        } else {
+            self.opacity
+        }
+    }
+}
+
+/// Whether the running compositor announces blur-behind support. KWin and
+/// picom put the property's atom on the root window when they handle it.
+pub fn compositor_blur_supported() -> bool {
+    let Ok(output) = Command::new("xprop")
+        .args(["-root", BLUR_PROPERTY])
+        .output()
+    else {
+        return false;
+    };
+    output.status.success() && !String::from_utf8_lossy(&output.stdout).contains("not found")
+}
+
+/// Ask the compositor to blur (or stop blurring) everything behind the
+/// overlay window. Returns whether blur is now on.
+pub fn set_compositor_blur(enabled: bool) -> bool {
+    let mut command = Command::new("xprop");
+    command.args(["-name", WINDOW_NAME]);
+    if enabled {
+        // An empty region means the whole window
+        command.args(["-f", BLUR_PROPERTY, "32c", "-set", BLUR_PROPERTY, "0"]);
+    } else {
+        command.args(["-remove", BLUR_PROPERTY]);
+    }
+    match command.output() {
+        Ok(output) if output.status.success() => enabled,
+        Ok(output) => {
+            eprintln!(
+                "[Backdrop] xprop failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+            false
+        }
+        Err(e) => {
+            eprintln!("[Backdrop] Failed to run xprop: {}", e);
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sliders_snap_and_clamp() {
+        let mut backdrop = Backdrop {
+            opacity: 0.72,
+            panel_opacity: 0.35,
+            blur: false,
+        };
+        backdrop.adjust_opacity(1);
+        assert!((backdrop.opacity - 0.75).abs() < 0.001);
+        backdrop.adjust_opacity(10);
+        assert_eq!(backdrop.opacity, 1.0);
+        backdrop.adjust_opacity(-30);
+        assert_eq!(backdrop.opacity, 0.0);
+
+        backdrop.adjust_panel_opacity(-3);
+        assert_eq!(backdrop.panel_opacity, MIN_PANEL_OPACITY);
+    }
+
+    #[test]
+    fn test_blur_falls_back_to_dimming() {
+        let backdrop = Backdrop {
+            opacity: 0.5,
+            panel_opacity: 1.0,
+            blur: true,
+        };
+        assert_eq!(backdrop.effective_opacity(true), 0.5);
+        assert!((backdrop.effective_opacity(false) - 0.7).abs() < 0.001);
+
+        let plain = Backdrop {
+            blur: false,
+            ..backdrop
+        };
+        assert_eq!(plain.effective_opacity(false), 0.5);
+    }
+}
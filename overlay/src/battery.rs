@@ -0,0 +1,127 @@
+//! System battery level for the HUD battery widget, and whether the system
+//! is on AC power for the power modes
+//!
+//! Read from `/sys/class/power_supply`, at most every `REFRESH_INTERVAL`
+//! since the level changes slowly.
+
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+const POWER_SUPPLY_DIR: &str = "/sys/class/power_supply";
+
+const REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BatteryStatus {
+    pub percent: u8,
+    pub charging: bool,
+}
+
+#[derive(Default)]
+pub struct BatteryMonitor {
+    status: Option<BatteryStatus>,
+    on_ac: Option<bool>,
+    last_read: Option<Instant>,
+}
+
+impl BatteryMonitor {
+    /// Re-read the battery if the last reading is stale
+    pub fn update(&mut self) {
+        if self
+            .last_read
+            .is_some_and(|t| t.elapsed() < REFRESH_INTERVAL)
+        {
+            return;
+        }
+        self.last_read = Some(Instant::now());
+        self.status = read_battery(Path::new(POWER_SUPPLY_DIR));
+        self.on_ac = read_ac_online(Path::new(POWER_SUPPLY_DIR));
+    }
+
+    /// Latest reading, `None` on machines without a battery
+    pub fn status(&self) -> Option<BatteryStatus> {
+        self.status
+    }
+
+    /// Whether a charger is plugged in, `None` when nothing reports it
+    pub fn on_ac(&self) -> Option<bool> {
+        self.on_ac
+    }
+}
+
+/// The first supply of type "Battery" that reports a capacity
+fn read_battery(dir: &Path) -> Option<BatteryStatus> {
+    let mut supplies: Vec<_> = fs::read_dir(dir)
+        .ok()?
+        .flatten()
+        .map(|e| e.path())
+        .collect();
+    supplies.sort();
+
+    supplies.iter().find_map(|supply| {
+        let read = |name: &str| fs::read_to_string(supply.join(name)).ok();
+        if read("type")?.trim() != "Battery" {
+            return None;
+        }
+        let percent = read("capacity")?.trim().parse::<u8>().ok()?.min(100);
+        let charging = read("status").is_some_and(|s| matches!(s.trim(), "Charging" | "Full"));
+        Some(BatteryStatus { percent, charging })
+    })
+}
+
+/// Whether any mains or USB supply is online, `None` when there are none
+fn read_ac_online(dir: &Path) -> Option<bool> {
+    let supplies: Vec<_> = fs::read_dir(dir)
+        .ok()?
+        .flatten()
+        .map(|e| e.path())
+        .collect();
+
+    let chargers: Vec<bool> = supplies
+        .iter()
+        .filter_map(|supply| {
+            let read = |name: &str| fs::read_to_string(supply.join(name)).ok();
+            if !matches!(read("type")?.trim(), "Mains" | "USB") {
+                return None;
+            }
+            Some(read("online").is_some_and(|s| s.trim() == "1"))
+        })
+        .collect();
+    (!chargers.is_empty()).then(|| chargers.contains(&true))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_battery_skips_other_supplies() {
+        let dir = std::env::temp_dir().join(format!("kazeta-battery-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let ac = dir.join("AC");
+        fs::create_dir(&ac).unwrap();
+        fs::write(ac.join("type"), "Mains\n").unwrap();
+
+        assert_eq!(read_battery(&dir), None);
+        assert_eq!(read_ac_online(&dir), Some(false));
+        fs::write(ac.join("online"), "1\n").unwrap();
+        assert_eq!(read_ac_online(&dir), Some(true));
+
+        let bat = dir.join("BAT0");
+        fs::create_dir(&bat).unwrap();
+        fs::write(bat.join("type"), "Battery\n").unwrap();
+        fs::write(bat.join("capacity"), "57\n").unwrap();
+        fs::write(bat.join("status"), "Charging\n").unwrap();
+
+        assert_eq!(
+            read_battery(&dir),
+            Some(BatteryStatus {
+                percent: 57,
+                charging: true
+            })
+        );
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
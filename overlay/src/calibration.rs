@@ -0,0 +1,346 @@
+//! Per-controller deadzones, stick drift offsets and trigger thresholds
+//!
+//! Settings are keyed by the controller GUID (the gilrs UUID in the hex form
+//! SDL uses for its mappings) and stored in `controller_calibration.json`.
+//! Every change also rewrites `controller_calibration.mapping`, which the BIOS
+//! passes to runtime wrappers as `KAZETA_CONTROLLER_CALIBRATION` (lines
+//! joined with ';'). It follows the layout of SDL_GAMECONTROLLERCONFIG: one
+//! line per controller, the GUID followed by `key:value` fields, e.g.
+//!
+//! ```text
+//! 03000000de280000ff11000001000000,leftdeadzone:0.12,rightdeadzone:0.10,leftx:0.031,lefty:-0.012,rightx:0.000,righty:0.000,triggerthreshold:0.15
+//! ```
+//!
+//! Offsets are the resting stick position measured by the drift wizard and
+//! are subtracted from readings before the deadzone is applied.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+/// How far one press moves a deadzone or threshold slider
+pub const SLIDER_STEP: f32 = 0.01;
+
+/// Largest deadzone that can be set; beyond this the stick is unusable
+pub const MAX_DEADZONE: f32 = 0.5;
+
+/// How long the sticks are sampled at rest
+pub const MEASURE_TIME: Duration = Duration::from_secs(3);
+
+/// Added to the measured noise when suggesting a deadzone
+const DEADZONE_MARGIN: f32 = 0.02;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stick {
+    Left,
+    Right,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct ControllerCalibration {
+    pub left_deadzone: f32,
+    pub right_deadzone: f32,
+    /// Resting position of each stick (x, y), -1.0 to 1.0
+    pub left_offset: [f32; 2],
+    pub right_offset: [f32; 2],
+    /// Trigger travel (0.0-1.0) before a press registers
+    pub trigger_threshold: f32,
+}
+
+impl Default for ControllerCalibration {
+    fn default() -> Self {
+        Self {
+            left_deadzone: 0.1,
+            right_deadzone: 0.1,
+            left_offset: [0.0, 0.0],
+            right_offset: [0.0, 0.0],
+            trigger_threshold: 0.1,
+        }
+    }
+}
+
+impl ControllerCalibration {
+    /// A stick reading with the drift offset removed and the deadzone applied.
+    /// The deadzone is radial and the rest of the range is rescaled, so the
+    /// output still reaches 1.0 at full tilt.
+    pub fn apply_stick(&self, stick: Stick, x: f32, y: f32) -> (f32, f32) {
+        let (offset, deadzone) = match stick {
+            Stick::Left => (self.left_offset, self.left_deadzone),
+            Stick::Right => (self.right_offset, self.right_deadzone),
+        };
+        let (x, y) = (x - offset[0], y - offset[1]);
+        let magnitude = (x * x + y * y).sqrt();
+        if magnitude <= deadzone {
+            return (0.0, 0.0);
+        }
+        let scaled = ((magnitude - deadzone) / (1.0 - deadzone)).min(1.0);
+        (x / magnitude * scaled, y / magnitude * scaled)
+    }
+
+    pub fn trigger_pressed(&self, value: f32) -> bool {
+        value > self.trigger_threshold
+    }
+
+    /// This controller's line in the wrapper mapping
+    fn mapping_line(&self, guid: &str) -> String {
+        format!(
+            "{},leftdeadzone:{:.2},rightdeadzone:{:.2},leftx:{:.3},lefty:{:.3},rightx:{:.3},righty:{:.3},triggerthreshold:{:.2}",
+            guid,
+            self.left_deadzone,
+            self.right_deadzone,
+            self.left_offset[0],
+            self.left_offset[1],
+            self.right_offset[0],
+            self.right_offset[1],
+            self.trigger_threshold,
+        )
+    }
+}
+
+/// SDL-style GUID for a gilrs UUID
+pub fn guid_from_uuid(uuid: [u8; 16]) -> String {
+    uuid.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Move a slider value by `steps` and keep it in range
+pub fn adjust(value: f32, steps: i32, max: f32) -> f32 {
+    let stepped = value + steps as f32 * SLIDER_STEP;
+    // Round to the step so repeated presses don't accumulate float error
+    ((stepped / SLIDER_STEP).round() * SLIDER_STEP).clamp(0.0, max)
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct CalibrationFile {
+    /// GUID -> calibration
+    #[serde(default)]
+    pub controllers: BTreeMap<String, ControllerCalibration>,
+}
+
+impl CalibrationFile {
+    /// The wrapper mapping: one line per calibrated controller
+    pub fn mapping(&self) -> String {
+        self.controllers
+            .iter()
+            .map(|(guid, calibration)| calibration.mapping_line(guid))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+}
+
+/// Loads, saves and exports controller calibrations
+pub struct CalibrationManager {
+    file: CalibrationFile,
+    config_path: PathBuf,
+    mapping_path: PathBuf,
+}
+
+impl CalibrationManager {
+    pub fn new() -> Result<Self> {
+        let data_dir =
+            dirs::data_local_dir().context("Could not determine local data directory")?;
+
+        let overlay_dir = data_dir.join("kazeta-plus").join("overlay");
+        if !overlay_dir.exists() {
+            fs::create_dir_all(&overlay_dir)
+                .context("Failed to create overlay config directory")?;
+        }
+
+        let config_path = overlay_dir.join("controller_calibration.json");
+        let file = if config_path.exists() {
+            println!("[Calibration] Loading config from {:?}", config_path);
+            let contents =
+                fs::read_to_string(&config_path).context("Failed to read calibration file")?;
+            serde_json::from_str(&contents).context("Failed to parse calibration JSON")?
+        } else {
+            CalibrationFile::default()
+        };
+
+        Ok(Self {
+            file,
+            config_path,
+            mapping_path: overlay_dir.join("controller_calibration.mapping"),
+        })
+    }
+
+    /// Saved calibration for a controller, or the defaults
+    pub fn get(&self, guid: &str) -> ControllerCalibration {
+        self.file.controllers.get(guid).copied().unwrap_or_default()
+    }
+
+    /// Store a controller's calibration and rewrite both files
+    pub fn set(&mut self, guid: &str, calibration: ControllerCalibration) -> Result<()> {
+        if calibration == ControllerCalibration::default() {
+            self.file.controllers.remove(guid);
+        } else {
+            self.file.controllers.insert(guid.to_string(), calibration);
+        }
+        self.save()
+    }
+
+    fn save(&self) -> Result<()> {
+        let json =
+            serde_json::to_string_pretty(&self.file).context("Failed to serialize calibration")?;
+        fs::write(&self.config_path, json).context("Failed to write calibration file")?;
+        fs::write(&self.mapping_path, self.file.mapping())
+            .context("Failed to write calibration mapping")?;
+        Ok(())
+    }
+}
+
+/// Result of measuring the sticks at rest
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DriftMeasurement {
+    pub left_offset: [f32; 2],
+    pub right_offset: [f32; 2],
+    /// Largest distance of a sample from its stick's average, per stick
+    pub left_noise: f32,
+    pub right_noise: f32,
+}
+
+impl DriftMeasurement {
+    /// The calibration with these offsets, and deadzones raised to cover the
+    /// noise if they were smaller
+    pub fn apply_to(&self, mut calibration: ControllerCalibration) -> ControllerCalibration {
+        calibration.left_offset = self.left_offset;
+        calibration.right_offset = self.right_offset;
+        calibration.left_deadzone = calibration
+            .left_deadzone
+            .max(self.left_noise + DEADZONE_MARGIN)
+            .min(MAX_DEADZONE);
+        calibration.right_deadzone = calibration
+            .right_deadzone
+            .max(self.right_noise + DEADZONE_MARGIN)
+            .min(MAX_DEADZONE);
+        calibration
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DriftWizard {
+    /// Asking the player to let go of the sticks
+    Prompt,
+    /// Sampling [left x, left y, right x, right y]
+    Measuring {
+        started: Instant,
+        samples: Vec<[f32; 4]>,
+    },
+    Done(DriftMeasurement),
+}
+
+impl DriftWizard {
+    pub fn start_measuring(&mut self, now: Instant) {
+        *self = DriftWizard::Measuring {
+            started: now,
+            samples: Vec::new(),
+        };
+    }
+
+    /// Record a reading while measuring; finishes after `MEASURE_TIME`.
+    /// Returns the measurement when it finishes.
+    pub fn sample(&mut self, now: Instant, axes: [f32; 4]) -> Option<DriftMeasurement> {
+        let DriftWizard::Measuring { started, samples } = self else {
+            return None;
+        };
+        samples.push(axes);
+        if now.duration_since(*started) < MEASURE_TIME {
+            return None;
+        }
+        let measurement = measure(samples);
+        *self = DriftWizard::Done(measurement);
+        Some(measurement)
+    }
+
+    /// Fraction of the measuring time elapsed
+    pub fn progress(&self, now: Instant) -> f32 {
+        match self {
+            DriftWizard::Prompt => 0.0,
+            DriftWizard::Measuring { started, .. } => {
+                (now.duration_since(*started).as_secs_f32() / MEASURE_TIME.as_secs_f32()).min(1.0)
+            }
+            DriftWizard::Done(_) => 1.0,
+        }
+    }
+}
+
+fn measure(samples: &[[f32; 4]]) -> DriftMeasurement {
+    let count = samples.len().max(1) as f32;
+    let mut mean = [0.0f32; 4];
+    for sample in samples {
+        for (m, v) in mean.iter_mut().zip(sample) {
+            *m += v / count;
+        }
+    }
+    let noise = |xi: usize| {
+        samples
+            .iter()
+            .map(|s| ((s[xi] - mean[xi]).powi(2) + (s[xi + 1] - mean[xi + 1]).powi(2)).sqrt())
+            .fold(0.0f32, f32::max)
+    };
+    DriftMeasurement {
+        left_offset: [mean[0], mean[1]],
+        right_offset: [mean[2], mean[3]],
+        left_noise: noise(0),
+        right_noise: noise(2),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_stick() {
+        let calibration = ControllerCalibration {
+            left_offset: [0.05, 0.0],
+            left_deadzone: 0.1,
+            ..Default::default()
+        };
+        // Drift inside the deadzone reads as centered
+        assert_eq!(calibration.apply_stick(Stick::Left, 0.12, 0.02), (0.0, 0.0));
+        // Full tilt still reaches 1.0 after the rescale
+        let (x, y) = calibration.apply_stick(Stick::Left, 1.05, 0.0);
+        assert!((x - 1.0).abs() < 1e-5 && y == 0.0);
+        assert!(calibration.trigger_pressed(0.2) && !calibration.trigger_pressed(0.05));
+
+        assert!((adjust(0.1, 3, MAX_DEADZONE) - 0.13).abs() < 1e-6);
+        assert_eq!(adjust(0.01, -5, MAX_DEADZONE), 0.0);
+        assert_eq!(
+            guid_from_uuid([0xde, 0x28, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1]),
+            "de280000000000000000000000000001"
+        );
+    }
+
+    #[test]
+    fn test_drift_wizard_and_mapping() {
+        let start = Instant::now();
+        let mut wizard = DriftWizard::Prompt;
+        assert_eq!(wizard.sample(start, [0.0; 4]), None);
+
+        wizard.start_measuring(start);
+        assert_eq!(wizard.sample(start, [0.04, -0.02, 0.0, 0.0]), None);
+        let measurement = wizard
+            .sample(start + MEASURE_TIME, [0.06, -0.02, 0.0, 0.0])
+            .unwrap();
+        assert!((measurement.left_offset[0] - 0.05).abs() < 1e-6);
+        assert!((measurement.left_noise - 0.01).abs() < 1e-6);
+        assert_eq!(measurement.right_noise, 0.0);
+
+        // Deadzones only grow to cover the noise
+        let calibration = measurement.apply_to(ControllerCalibration {
+            left_deadzone: 0.01,
+            ..Default::default()
+        });
+        assert!((calibration.left_deadzone - 0.03).abs() < 1e-6);
+        assert_eq!(calibration.right_deadzone, 0.1);
+
+        let mut file = CalibrationFile::default();
+        file.controllers.insert("abc".to_string(), calibration);
+        assert_eq!(
+            file.mapping(),
+            "abc,leftdeadzone:0.03,rightdeadzone:0.10,leftx:0.050,lefty:-0.020,rightx:0.000,righty:0.000,triggerthreshold:0.10"
+        );
+    }
+}
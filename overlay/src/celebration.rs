@@ -0,0 +1,163 @@
+//! Full-screen celebration when a game's achievement set is mastered
+//!
+//! kazeta-ra sends `RaMastery` once the last achievement of a set unlocks.
+//! The celebration takes over the screen for a few seconds, even while the
+//! menu is hidden, with confetti falling over the game title, completion
+//! time and points total. Any button dismisses it early while the menu is open.
+
+use macroquad::prelude::{vec2, Vec2};
+use std::f32::consts::TAU;
+use std::time::{Duration, Instant};
+
+/// How long the celebration stays up
+const DURATION: Duration = Duration::from_secs(10);
+
+/// Fade in and out at the start and end
+const FADE: Duration = Duration::from_millis(600);
+
+const CONFETTI_COUNT: usize = 120;
+
+/// Seconds for a piece of confetti to fall from the top of the screen to the bottom
+const FALL_SECS: f32 = 4.0;
+
+pub struct Celebration {
+    pub game_title: String,
+    pub hardcore: bool,
+    pub total_achievements: u32,
+    pub points: u32,
+    /// Time from the first unlock to the last, if the history has both
+    pub completion_secs: Option<u64>,
+    started: Instant,
+}
+
+/// One piece of confetti at the current point in the animation
+#[derive(Debug, Clone, Copy)]
+pub struct Confetti {
+    pub position: Vec2,
+    pub size: Vec2,
+    /// Radians
+    pub rotation: f32,
+    /// Index into whatever palette the renderer uses
+    pub color: usize,
+}
+
+impl Celebration {
+    pub fn new(
+        game_title: String,
+        hardcore: bool,
+        total_achievements: u32,
+        points: u32,
+        completion_secs: Option<u64>,
+    ) -> Self {
+        Self {
+            game_title,
+            hardcore,
+            total_achievements,
+            points,
+            completion_secs,
+            started: Instant::now(),
+        }
+    }
+
+    fn elapsed(&self) -> Duration {
+        self.started.elapsed()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.elapsed() >= DURATION
+    }
+
+    /// Opacity for the fade in and out
+    pub fn alpha(&self) -> f32 {
+        let elapsed = self.elapsed();
+        let remaining = DURATION.saturating_sub(elapsed);
+        let fade = FADE.as_secs_f32();
+        (elapsed.as_secs_f32() / fade)
+            .min(remaining.as_secs_f32() / fade)
+            .clamp(0.0, 1.0)
+    }
+
+    /// Confetti positions for a screen of the given size
+    pub fn confetti(&self, screen: Vec2) -> Vec<Confetti> {
+        confetti_at(self.elapsed().as_secs_f32(), screen)
+    }
+}
+
+/// Cheap deterministic noise in 0..1, so the confetti needs no stored state
+fn noise(seed: usize, salt: u32) -> f32 {
+    let mut x = (seed as u32).wrapping_mul(0x9E37_79B9) ^ salt.wrapping_mul(0x85EB_CA6B);
+    x ^= x >> 16;
+    x = x.wrapping_mul(0x7FEB_352D);
+    x ^= x >> 15;
+    (x & 0xFFFF) as f32 / 65535.0
+}
+
+fn confetti_at(t: f32, screen: Vec2) -> Vec<Confetti> {
+    (0..CONFETTI_COUNT)
+        .map(|i| {
+            let speed = 0.7 + noise(i, 1) * 0.6;
+            // Stagger the start so the screen doesn't fill all at once
+            let delay = noise(i, 2) * FALL_SECS;
+            let fall = ((t + FALL_SECS - delay) * speed / FALL_SECS).fract();
+            let sway = (t * (1.0 + noise(i, 3) * 2.0) + noise(i, 4) * TAU).sin() * 30.0;
+
+            Confetti {
+                position: vec2(
+                    noise(i, 5) * screen.x + sway,
+                    fall * (screen.y + 40.0) - 20.0,
+                ),
+                size: vec2(6.0 + noise(i, 6) * 6.0, 10.0 + noise(i, 7) * 8.0),
+                rotation: t * (2.0 + noise(i, 8) * 4.0),
+                color: i,
+            }
+        })
+        .collect()
+}
+
+/// "2h 05m", "14m", or "3 days" for long runs
+pub fn format_completion_time(secs: u64) -> String {
+    let minutes = secs / 60;
+    let hours = minutes / 60;
+    let days = hours / 24;
+    if days >= 2 {
+        format!("{} days", days)
+    } else if hours > 0 {
+        format!("{}h {:02}m", hours, minutes % 60)
+    } else {
+        format!("{}m", minutes.max(1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_completion_time() {
+        assert_eq!(format_completion_time(20), "1m");
+        assert_eq!(format_completion_time(14 * 60), "14m");
+        assert_eq!(format_completion_time(2 * 3600 + 5 * 60), "2h 05m");
+        assert_eq!(format_completion_time(30 * 3600), "30h 00m");
+        assert_eq!(format_completion_time(3 * 86_400 + 10), "3 days");
+    }
+
+    #[test]
+    fn test_confetti_is_deterministic_and_on_screen() {
+        let screen = vec2(1280.0, 720.0);
+        let a = confetti_at(2.5, screen);
+        let b = confetti_at(2.5, screen);
+        assert_eq!(a.len(), CONFETTI_COUNT);
+        for (a, b) in a.iter().zip(&b) {
+            assert_eq!(a.position, b.position);
+            assert!(a.position.y >= -20.0 && a.position.y <= screen.y + 20.0);
+            assert!(a.position.x >= -30.0 && a.position.x <= screen.x + 30.0);
+        }
+    }
+
+    #[test]
+    fn test_fades_in() {
+        let celebration = Celebration::new("Game".to_string(), true, 10, 100, None);
+        assert!(celebration.alpha() < 0.5);
+        assert!(!celebration.is_finished());
+    }
+}
@@ -0,0 +1,239 @@
+//! Cheat codes for the running game
+//!
+//! Cheats are read from `overlay/cheats/<cart_id>.cht` and, when it is
+//! installed, the libretro cheat database (matched by game name). Both use
+//! the libretro `.cht` format. Enabled cheats only last for the session: they
+//! are written to `CHEATS_FILE` and the runtime wrapper is told to reload them
+//! with a `cheats` command on the emulator command file.
+//!
+//! RetroAchievements doesn't allow cheats in hardcore, so enabling any cheat
+//! turns hardcore off in kazeta-ra until the next game starts.
+
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Enabled cheats for the runtime wrapper, in `.cht` format
+const CHEATS_FILE: &str = "/tmp/kazeta-cheats.cht";
+
+/// Where the libretro cheat database is installed, one directory per system
+const LIBRETRO_CHEAT_DIRS: &[&str] = &["/usr/share/libretro/database/cht"];
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cheat {
+    pub description: String,
+    pub code: String,
+    pub enabled: bool,
+}
+
+/// Parse a libretro `.cht` file. Cheats missing a code are skipped.
+pub fn parse_cht(contents: &str) -> Vec<Cheat> {
+    let mut cheats: Vec<(Option<String>, Option<String>)> = Vec::new();
+
+    for line in contents.lines() {
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let Some(rest) = key.trim().strip_prefix("cheat") else {
+            continue;
+        };
+        let Some((index, field)) = rest.split_once('_') else {
+            continue;
+        };
+        let Ok(index) = index.parse::<usize>() else {
+            continue;
+        };
+        let value = value.trim().trim_matches('"').to_string();
+
+        if cheats.len() <= index {
+            cheats.resize(index + 1, (None, None));
+        }
+        match field {
+            "desc" => cheats[index].0 = Some(value),
+            "code" => cheats[index].1 = Some(value),
+            _ => {}
+        }
+    }
+
+    cheats
+        .into_iter()
+        .enumerate()
+        .filter_map(|(i, (description, code))| {
+            let code = code.filter(|c| !c.is_empty())?;
+            Some(Cheat {
+                description: description.unwrap_or_else(|| format!("Cheat {}", i + 1)),
+                code,
+                enabled: false,
+            })
+        })
+        .collect()
+}
+
+/// Write cheats as a `.cht` file, all enabled
+fn to_cht(cheats: &[&Cheat]) -> String {
+    let mut out = format!("cheats = {}\n", cheats.len());
+    for (i, cheat) in cheats.iter().enumerate() {
+        out.push_str(&format!(
+            "\ncheat{i}_desc = \"{}\"\ncheat{i}_code = \"{}\"\ncheat{i}_enable = true\n",
+            cheat.description.replace('"', "'"),
+            cheat.code,
+        ));
+    }
+    out
+}
+
+/// The database file for a game: an exact name match, or a name followed by
+/// its region tags ("Name (USA, Europe).cht")
+fn find_database_file(dirs: &[PathBuf], game_name: &str) -> Option<PathBuf> {
+    let name = game_name.to_lowercase();
+    let prefix = format!("{} (", name);
+
+    for dir in dirs {
+        let Ok(systems) = fs::read_dir(dir) else {
+            continue;
+        };
+        let mut files: Vec<PathBuf> = systems
+            .flatten()
+            .filter_map(|system| fs::read_dir(system.path()).ok())
+            .flat_map(|entries| entries.flatten().map(|e| e.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "cht"))
+            .collect();
+        files.sort();
+
+        let stem = |path: &PathBuf| {
+            path.file_stem()
+                .map(|s| s.to_string_lossy().to_lowercase())
+                .unwrap_or_default()
+        };
+        let found = files
+            .iter()
+            .find(|p| stem(p) == name)
+            .or_else(|| files.iter().find(|p| stem(p).starts_with(&prefix)));
+        if let Some(path) = found {
+            return Some(path.clone());
+        }
+    }
+    None
+}
+
+/// Cheats available for the running game and which are on
+pub struct CheatList {
+    pub cheats: Vec<Cheat>,
+    pub selected: usize,
+    /// Files the cheats came from, for the screen's footer
+    pub sources: Vec<PathBuf>,
+    /// Whether RA hardcore is on; enabling a cheat turns it off
+    pub hardcore: bool,
+}
+
+impl CheatList {
+    /// Load the game's own cheat file, then the libretro database entry
+    pub fn load(cart_id: &str, game_name: &str) -> Self {
+        let mut cheats = Vec::new();
+        let mut sources = Vec::new();
+
+        let game_file = dirs::data_local_dir().map(|d| {
+            d.join("kazeta-plus")
+                .join("overlay")
+                .join("cheats")
+                .join(format!("{}.cht", cart_id))
+        });
+        let database_dirs: Vec<PathBuf> = LIBRETRO_CHEAT_DIRS.iter().map(PathBuf::from).collect();
+        let database_file = find_database_file(&database_dirs, game_name);
+
+        for path in game_file.into_iter().chain(database_file) {
+            if let Ok(contents) = fs::read_to_string(&path) {
+                let found = parse_cht(&contents);
+                println!("[Cheats] {} cheats from {:?}", found.len(), path);
+                cheats.extend(found);
+                sources.push(path);
+            }
+        }
+
+        let hardcore = kazeta_ra::CredentialManager::new()
+            .ok()
+            .and_then(|m| m.load().ok().flatten())
+            .is_some_and(|c| c.session_hardcore());
+
+        Self {
+            cheats,
+            selected: 0,
+            sources,
+            hardcore,
+        }
+    }
+
+    /// Flip the selected cheat and hand the enabled set to the wrapper.
+    /// Returns true when this switched hardcore off.
+    pub fn toggle_selected(&mut self) -> Result<bool> {
+        let Some(cheat) = self.cheats.get_mut(self.selected) else {
+            return Ok(false);
+        };
+        cheat.enabled = !cheat.enabled;
+        let enabled = cheat.enabled;
+
+        let mut suspended = false;
+        if enabled && self.hardcore {
+            kazeta_ra::auth::suspend_hardcore_for_session("cheats enabled")?;
+            self.hardcore = false;
+            suspended = true;
+        }
+
+        self.publish(Path::new(CHEATS_FILE))?;
+        Ok(suspended)
+    }
+
+    fn publish(&self, path: &Path) -> Result<()> {
+        let enabled: Vec<&Cheat> = self.cheats.iter().filter(|c| c.enabled).collect();
+        fs::write(path, to_cht(&enabled)).context("Failed to write cheats file")?;
+        crate::pause::write_command("cheats")
+    }
+
+    /// Turn every cheat off when the game stops
+    pub fn clear() {
+        let _ = fs::remove_file(CHEATS_FILE);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_cht() {
+        let contents = r#"cheats = 3
+
+cheat0_desc = "Infinite Lives"
+cheat0_code = "02003F44:0064"
+cheat0_enable = false
+
+cheat1_desc = "Broken"
+
+cheat2_code = "8200E0A4:FFFF+8200E0A6:00FF"
+"#;
+        let cheats = parse_cht(contents);
+        assert_eq!(cheats.len(), 2);
+        assert_eq!(cheats[0].description, "Infinite Lives");
+        assert_eq!(cheats[0].code, "02003F44:0064");
+        assert_eq!(cheats[1].description, "Cheat 3");
+        assert!(!cheats[1].enabled);
+
+        let enabled: Vec<&Cheat> = cheats.iter().collect();
+        assert_eq!(parse_cht(&to_cht(&enabled)), cheats);
+    }
+
+    #[test]
+    fn test_find_database_file() {
+        let dir = std::env::temp_dir().join(format!("kazeta-cheats-{}", std::process::id()));
+        let system = dir.join("Nintendo - Game Boy Advance");
+        fs::create_dir_all(&system).unwrap();
+        fs::write(system.join("Metroid Fusion (USA).cht"), "").unwrap();
+        fs::write(system.join("Metroid - Zero Mission (USA).cht"), "").unwrap();
+
+        let dirs = vec![dir.clone()];
+        let found = find_database_file(&dirs, "metroid fusion").unwrap();
+        assert_eq!(found.file_name().unwrap(), "Metroid Fusion (USA).cht");
+        assert!(find_database_file(&dirs, "Metroid").is_none());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -1,83 +1,45 @@
-use crate::ipc::{OverlayMessage, OverlayScreen, ToastStyle};
-use anyhow::{Context, Result};
+//! Client library for talking to the overlay daemon
+//!
+//! Games and tools link `kazeta_overlay` and use [`OverlayClient`] (blocking)
+//! or [`AsyncOverlayClient`] (queued on a background thread, never blocks the
+//! caller). Both implement [`OverlayApi`], which has a typed method for every
+//! message the daemon understands. Connections are kept open between messages
+//! and re-established automatically if the daemon restarts.
+
+use crate::ipc::{
+    AchievementInfo, ChallengeSetInfo, ControlHint, GameEntry, OverlayMessage, OverlayScreen,
+    PointerAction, SessionPlayer, ToastStyle, UnlockRecord,
+};
+use anyhow::{anyhow, Context, Result};
 use std::io::Write;
 use std::os::unix::net::UnixStream;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{sync_channel, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
 const SOCKET_PATH: &str = "/tmp/kazeta-overlay.sock";
 
-/// Client for sending messages to the overlay daemon
-pub struct OverlayClient {
-    socket_path: String,
-}
+/// Messages an `AsyncOverlayClient` holds while the daemon is slow or away
+const ASYNC_QUEUE_LEN: usize = 64;
 
-impl OverlayClient {
-    /// Create a new overlay client
-    pub fn new() -> Self {
-        Self {
-            socket_path: SOCKET_PATH.to_string(),
-        }
-    }
+/// How often the background sender retries a message before dropping it
+const RECONNECT_ATTEMPTS: u32 = 3;
+const RECONNECT_DELAY: Duration = Duration::from_millis(200);
 
-    /// Create a client with a custom socket path
-    pub fn with_socket_path(socket_path: String) -> Self {
-        Self { socket_path }
-    }
-
-    /// Check if the overlay daemon is running
-    /// Actually tries to connect to verify the daemon is responsive
-    pub fn is_available(&self) -> bool {
-        use std::os::unix::net::UnixStream;
-        use std::time::Duration;
-
-        // First check if socket file exists
-        if !std::path::Path::new(&self.socket_path).exists() {
-            return false;
-        }
-
-        // Try to actually connect to verify daemon is running
-        // Use a short timeout to avoid blocking
-        match UnixStream::connect(&self.socket_path) {
-            Ok(stream) => {
-                // Set a short timeout and try to read (daemon should be responsive)
-                let _ = stream.set_read_timeout(Some(Duration::from_millis(100)));
-                true
-            }
-            Err(_) => {
-                // Socket exists but can't connect - daemon not running
-                // Clean up the stale socket
-                let _ = std::fs::remove_file(&self.socket_path);
-                false
-            }
-        }
-    }
-
-    /// Send a message to the overlay
-    fn send_message(&self, message: &OverlayMessage) -> Result<()> {
-        let mut stream = UnixStream::connect(&self.socket_path)
-            .context("Failed to connect to overlay daemon")?;
-
-        let json = serde_json::to_string(message)
-            .context("Failed to serialize message")?;
-
-        stream
-            .write_all(json.as_bytes())
-            .context("Failed to write message")?;
-        stream
-            .write_all(b"\n")
-            .context("Failed to write newline")?;
-        stream.flush().context("Failed to flush stream")?;
-
-        Ok(())
-    }
+/// Typed access to every overlay message. Implementors only provide `send`.
+pub trait OverlayApi {
+    /// Send a single message
+    fn send(&self, message: OverlayMessage) -> Result<()>;
 
     /// Show a toast notification
-    pub fn show_toast(
+    fn show_toast(
         &self,
         message: impl Into<String>,
         style: ToastStyle,
         duration_ms: u32,
     ) -> Result<()> {
-        self.send_message(&OverlayMessage::ShowToast {
+        self.send(OverlayMessage::ShowToast {
             message: message.into(),
             icon: None,
             duration_ms,
@@ -86,14 +48,14 @@ impl OverlayClient {
     }
 
     /// Show a toast notification with an icon
-    pub fn show_toast_with_icon(
+    fn show_toast_with_icon(
         &self,
         message: impl Into<String>,
         icon: impl Into<String>,
         style: ToastStyle,
         duration_ms: u32,
     ) -> Result<()> {
-        self.send_message(&OverlayMessage::ShowToast {
+        self.send(OverlayMessage::ShowToast {
             message: message.into(),
             icon: Some(icon.into()),
             duration_ms,
@@ -102,37 +64,42 @@ impl OverlayClient {
     }
 
     /// Show an info toast
-    pub fn info(&self, message: impl Into<String>) -> Result<()> {
+    fn info(&self, message: impl Into<String>) -> Result<()> {
         self.show_toast(message, ToastStyle::Info, 3000)
     }
 
     /// Show a success toast
-    pub fn success(&self, message: impl Into<String>) -> Result<()> {
+    fn success(&self, message: impl Into<String>) -> Result<()> {
         self.show_toast(message, ToastStyle::Success, 3000)
     }
 
     /// Show a warning toast
-    pub fn warning(&self, message: impl Into<String>) -> Result<()> {
+    fn warning(&self, message: impl Into<String>) -> Result<()> {
         self.show_toast(message, ToastStyle::Warning, 4000)
     }
 
     /// Show an error toast
-    pub fn error(&self, message: impl Into<String>) -> Result<()> {
+    fn error(&self, message: impl Into<String>) -> Result<()> {
         self.show_toast(message, ToastStyle::Error, 5000)
     }
 
     /// Show the overlay menu
-    pub fn show_overlay(&self, screen: OverlayScreen) -> Result<()> {
-        self.send_message(&OverlayMessage::ShowOverlay { screen })
+    fn show_overlay(&self, screen: OverlayScreen) -> Result<()> {
+        self.send(OverlayMessage::ShowOverlay { screen })
     }
 
     /// Hide the overlay menu
-    pub fn hide_overlay(&self) -> Result<()> {
-        self.send_message(&OverlayMessage::HideOverlay)
+    fn hide_overlay(&self) -> Result<()> {
+        self.send(OverlayMessage::HideOverlay)
+    }
+
+    /// Show the overlay if hidden, hide it if shown
+    fn toggle_overlay(&self) -> Result<()> {
+        self.send(OverlayMessage::ToggleOverlay)
     }
 
     /// Unlock an achievement
-    pub fn unlock_achievement(
+    fn unlock_achievement(
         &self,
         cart_id: impl Into<String>,
         achievement_id: impl Into<String>,
@@ -142,7 +109,7 @@ impl OverlayClient {
             .unwrap()
             .as_secs();
 
-        self.send_message(&OverlayMessage::UnlockAchievement {
+        self.send(OverlayMessage::UnlockAchievement {
             cart_id: cart_id.into(),
             achievement_id: achievement_id.into(),
             timestamp,
@@ -150,21 +117,347 @@ impl OverlayClient {
     }
 
     /// Request status from the overlay
-    pub fn get_status(&self) -> Result<()> {
-        self.send_message(&OverlayMessage::GetStatus)
+    fn get_status(&self) -> Result<()> {
+        self.send(OverlayMessage::GetStatus)
     }
 
     /// Set the overlay theme colors
-    pub fn set_theme(
+    fn set_theme(
         &self,
         font_color: impl Into<String>,
         cursor_color: impl Into<String>,
     ) -> Result<()> {
-        self.send_message(&OverlayMessage::SetTheme {
+        self.send(OverlayMessage::SetTheme {
             font_color: font_color.into(),
             cursor_color: cursor_color.into(),
         })
     }
+
+    /// Tell the overlay a game has started
+    fn game_started(
+        &self,
+        cart_id: impl Into<String>,
+        game_name: impl Into<String>,
+        runtime: impl Into<String>,
+    ) -> Result<()> {
+        self.send(OverlayMessage::GameStarted {
+            cart_id: cart_id.into(),
+            game_name: game_name.into(),
+            runtime: runtime.into(),
+            pid: None,
+        })
+    }
+
+    /// Tell the overlay a game has started in process `pid`, which it
+    /// watches so a crash still ends the session
+    fn game_started_with_pid(
+        &self,
+        cart_id: impl Into<String>,
+        game_name: impl Into<String>,
+        runtime: impl Into<String>,
+        pid: u32,
+    ) -> Result<()> {
+        self.send(OverlayMessage::GameStarted {
+            cart_id: cart_id.into(),
+            game_name: game_name.into(),
+            runtime: runtime.into(),
+            pid: Some(pid),
+        })
+    }
+
+    /// Send the running game's button mapping for the Game Controls screen
+    fn game_controls(&self, cart_id: impl Into<String>, controls: Vec<ControlHint>) -> Result<()> {
+        self.send(OverlayMessage::GameControls {
+            cart_id: cart_id.into(),
+            controls,
+        })
+    }
+
+    /// Tell the overlay the game has stopped
+    fn game_stopped(&self, cart_id: impl Into<String>) -> Result<()> {
+        self.send(OverlayMessage::GameStopped {
+            cart_id: cart_id.into(),
+        })
+    }
+
+    /// Ask the overlay to quit the current game and return to the BIOS
+    fn quit_game(&self) -> Result<()> {
+        self.send(OverlayMessage::QuitGame)
+    }
+
+    /// Confirm a quit request was handled
+    fn quit_game_ack(&self) -> Result<()> {
+        self.send(OverlayMessage::QuitGameAck)
+    }
+
+    /// Announce the achievement set of the game that just started
+    fn ra_game_start(
+        &self,
+        game_title: impl Into<String>,
+        game_id: Option<u32>,
+        total_achievements: u32,
+        earned_achievements: u32,
+    ) -> Result<()> {
+        self.send(OverlayMessage::RaGameStart {
+            game_title: game_title.into(),
+            game_id,
+            game_icon: None,
+            total_achievements,
+            earned_achievements,
+            console: None,
+            icon_url: None,
+            icon_path: None,
+            difficulty: None,
+            expected_master_secs: None,
+        })
+    }
+
+    /// Show the unlock toast for an achievement
+    fn ra_achievement_unlocked(
+        &self,
+        achievement_id: u32,
+        title: impl Into<String>,
+        description: Option<String>,
+        points: u32,
+        is_hardcore: bool,
+    ) -> Result<()> {
+        self.send(OverlayMessage::RaAchievementUnlocked {
+            achievement_id,
+            title: title.into(),
+            description,
+            points,
+            icon_url: None,
+            is_hardcore,
+            player: None,
+        })
+    }
+
+    /// Show the unlock toast for an achievement a session player earned,
+    /// naming them
+    fn ra_player_achievement_unlocked(
+        &self,
+        achievement_id: u32,
+        title: impl Into<String>,
+        points: u32,
+        is_hardcore: bool,
+        player: SessionPlayer,
+    ) -> Result<()> {
+        self.send(OverlayMessage::RaAchievementUnlocked {
+            achievement_id,
+            title: title.into(),
+            description: None,
+            points,
+            icon_url: None,
+            is_hardcore,
+            player: Some(player),
+        })
+    }
+
+    /// Map the player slots of a shared multiplayer session to RA users
+    fn ra_session_players(&self, players: Vec<SessionPlayer>) -> Result<()> {
+        self.send(OverlayMessage::RaSessionPlayers { players })
+    }
+
+    /// Update the earned/total counter
+    fn ra_progress(&self, earned: u32, total: u32) -> Result<()> {
+        self.send(OverlayMessage::RaProgressUpdate { earned, total })
+    }
+
+    /// Update the measured progress of one achievement (e.g. 45/100 rings)
+    fn ra_progress_measure(&self, achievement_id: u32, current: u32, target: u32) -> Result<()> {
+        self.send(OverlayMessage::RaProgressMeasure {
+            achievement_id,
+            current,
+            target,
+        })
+    }
+
+    /// Celebrate a completed set
+    fn ra_mastery(
+        &self,
+        game_title: impl Into<String>,
+        hardcore: bool,
+        total_achievements: u32,
+        points: u32,
+        completion_secs: Option<u64>,
+    ) -> Result<()> {
+        self.send(OverlayMessage::RaMastery {
+            game_title: game_title.into(),
+            hardcore,
+            total_achievements,
+            points,
+            completion_secs,
+        })
+    }
+
+    /// Replace the achievement list shown on the Achievements screen
+    fn ra_achievement_list(
+        &self,
+        game_title: impl Into<String>,
+        game_hash: impl Into<String>,
+        achievements: Vec<AchievementInfo>,
+    ) -> Result<()> {
+        self.send(OverlayMessage::RaAchievementList {
+            game_title: game_title.into(),
+            game_hash: game_hash.into(),
+            achievements,
+        })
+    }
+
+    /// Replace the unlock history used by the stats screen
+    fn ra_unlock_history(
+        &self,
+        game_hash: impl Into<String>,
+        unlocks: Vec<UnlockRecord>,
+    ) -> Result<()> {
+        self.send(OverlayMessage::RaUnlockHistory {
+            game_hash: game_hash.into(),
+            unlocks,
+        })
+    }
+
+    /// Replace the daily/weekly challenge sets
+    fn ra_challenges(&self, sets: Vec<ChallengeSetInfo>) -> Result<()> {
+        self.send(OverlayMessage::RaChallenges { sets })
+    }
+
+    /// Report a connected gamepad
+    fn controller_connected(
+        &self,
+        controller_id: impl Into<String>,
+        name: impl Into<String>,
+        vendor_id: Option<u16>,
+        product_id: Option<u16>,
+    ) -> Result<()> {
+        self.send(OverlayMessage::ControllerConnected {
+            controller_id: controller_id.into(),
+            name: name.into(),
+            vendor_id,
+            product_id,
+        })
+    }
+
+    /// Report a disconnected gamepad
+    fn controller_disconnected(&self, controller_id: impl Into<String>) -> Result<()> {
+        self.send(OverlayMessage::ControllerDisconnected {
+            controller_id: controller_id.into(),
+        })
+    }
+
+    /// Replace the list of installed games
+    fn game_list(&self, games: Vec<GameEntry>) -> Result<()> {
+        self.send(OverlayMessage::GameList { games })
+    }
+
+    /// Forward pointer input; coordinates are 0.0-1.0 across the screen
+    fn pointer(&self, x: f32, y: f32, action: PointerAction) -> Result<()> {
+        self.send(OverlayMessage::Pointer { x, y, action })
+    }
+
+    /// Ask the daemon to save and exit
+    fn shutdown(&self) -> Result<()> {
+        self.send(OverlayMessage::Shutdown)
+    }
+}
+
+/// Blocking client for sending messages to the overlay daemon
+pub struct OverlayClient {
+    socket_path: String,
+    /// Open connection, reused until a write fails
+    stream: Mutex<Option<UnixStream>>,
+}
+
+impl OverlayClient {
+    /// Create a new overlay client
+    pub fn new() -> Self {
+        Self::with_socket_path(SOCKET_PATH.to_string())
+    }
+
+    /// Create a client with a custom socket path
+    pub fn with_socket_path(socket_path: String) -> Self {
+        Self {
+            socket_path,
+            stream: Mutex::new(None),
+        }
+    }
+
+    /// Check if the overlay daemon is running
+    /// Actually tries to connect to verify the daemon is responsive
+    pub fn is_available(&self) -> bool {
+        // First check if socket file exists
+        if !std::path::Path::new(&self.socket_path).exists() {
+            return false;
+        }
+
+        // Try to actually connect to verify daemon is running
+        // Use a short timeout to avoid blocking
+        match UnixStream::connect(&self.socket_path) {
+            Ok(stream) => {
+                // Set a short timeout and try to read (daemon should be responsive)
+                let _ = stream.set_read_timeout(Some(Duration::from_millis(100)));
+                true
+            }
+            Err(_) => {
+                // Socket exists but can't connect - daemon not running
+                // Clean up the stale socket
+                let _ = std::fs::remove_file(&self.socket_path);
+                false
+            }
+        }
+    }
+
+    /// Send several messages over a single connection, one JSON line each.
+    /// Use this for bursts (e.g. an achievement list followed by toasts) so
+    /// the daemon receives them in order without reconnecting per message.
+    pub fn send_batch(&self, messages: &[OverlayMessage]) -> Result<()> {
+        if messages.is_empty() {
+            return Ok(());
+        }
+
+        let mut payload = String::new();
+        for message in messages {
+            let json = serde_json::to_string(message).context("Failed to serialize message")?;
+            payload.push_str(&json);
+            payload.push('\n');
+        }
+
+        let mut stream = self.stream.lock().unwrap_or_else(|e| e.into_inner());
+
+        // A kept-open connection goes stale when the daemon restarts; the
+        // first failed write drops it and the payload goes out on a new one
+        if let Some(open) = stream.as_mut() {
+            if Self::write_payload(open, &payload).is_ok() {
+                return Ok(());
+            }
+            *stream = None;
+        }
+
+        let mut fresh = UnixStream::connect(&self.socket_path)
+            .context("Failed to connect to overlay daemon")?;
+        Self::write_payload(&mut fresh, &payload)?;
+        *stream = Some(fresh);
+
+        Ok(())
+    }
+
+    fn write_payload(stream: &mut UnixStream, payload: &str) -> Result<()> {
+        stream
+            .write_all(payload.as_bytes())
+            .context("Failed to write messages")?;
+        stream.flush().context("Failed to flush stream")?;
+        Ok(())
+    }
+
+    /// Close the kept-open connection; the next message reconnects
+    pub fn disconnect(&self) {
+        *self.stream.lock().unwrap_or_else(|e| e.into_inner()) = None;
+    }
+}
+
+impl OverlayApi for OverlayClient {
+    fn send(&self, message: OverlayMessage) -> Result<()> {
+        self.send_batch(std::slice::from_ref(&message))
+    }
 }
 
 impl Default for OverlayClient {
@@ -173,6 +466,72 @@ impl Default for OverlayClient {
     }
 }
 
+/// Non-blocking client. Messages are queued and written by a background
+/// thread, which reconnects and retries while the daemon is restarting, so
+/// a game's frame loop never waits on the socket. Messages still queued when
+/// the client is dropped are sent before the thread exits.
+pub struct AsyncOverlayClient {
+    sender: SyncSender<OverlayMessage>,
+    dropped: Arc<AtomicU64>,
+}
+
+impl AsyncOverlayClient {
+    /// Create a client for the default socket
+    pub fn new() -> Self {
+        Self::with_socket_path(SOCKET_PATH.to_string())
+    }
+
+    /// Create a client with a custom socket path
+    pub fn with_socket_path(socket_path: String) -> Self {
+        let (sender, receiver) = sync_channel::<OverlayMessage>(ASYNC_QUEUE_LEN);
+        let dropped = Arc::new(AtomicU64::new(0));
+        let thread_dropped = dropped.clone();
+
+        std::thread::spawn(move || {
+            let client = OverlayClient::with_socket_path(socket_path);
+            for message in receiver {
+                let delivered = (0..RECONNECT_ATTEMPTS).any(|attempt| {
+                    if attempt > 0 {
+                        std::thread::sleep(RECONNECT_DELAY);
+                    }
+                    client.send_batch(std::slice::from_ref(&message)).is_ok()
+                });
+                if !delivered {
+                    thread_dropped.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+        });
+
+        Self { sender, dropped }
+    }
+
+    /// Messages that were discarded because the queue was full or the daemon
+    /// could not be reached
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+}
+
+impl OverlayApi for AsyncOverlayClient {
+    /// Queue a message. Fails without blocking when the queue is full.
+    fn send(&self, message: OverlayMessage) -> Result<()> {
+        match self.sender.try_send(message) {
+            Ok(()) => Ok(()),
+            Err(TrySendError::Full(_)) => {
+                self.dropped.fetch_add(1, Ordering::Relaxed);
+                Err(anyhow!("Overlay message queue is full"))
+            }
+            Err(TrySendError::Disconnected(_)) => Err(anyhow!("Overlay sender thread has stopped")),
+        }
+    }
+}
+
+impl Default for AsyncOverlayClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -189,4 +548,99 @@ mod tests {
         let client = OverlayClient::with_socket_path(custom_path.to_string());
         assert_eq!(client.socket_path, custom_path);
     }
+
+    #[test]
+    fn test_send_batch_delivers_in_order() {
+        use crate::ipc::IpcServer;
+
+        let path = format!(
+            "/tmp/kazeta-overlay-client-test-{}.sock",
+            std::process::id()
+        );
+        let mut server = IpcServer::with_socket_path(&path).unwrap();
+        let client = OverlayClient::with_socket_path(path.clone());
+
+        client
+            .send_batch(&[
+                OverlayMessage::ShowOverlay {
+                    screen: OverlayScreen::Achievements,
+                },
+                OverlayMessage::HideOverlay,
+            ])
+            .unwrap();
+
+        let messages = server.poll_messages();
+        assert_eq!(messages.len(), 2);
+        assert!(matches!(messages[0], OverlayMessage::ShowOverlay { .. }));
+        assert!(matches!(messages[1], OverlayMessage::HideOverlay));
+    }
+
+    #[test]
+    fn test_typed_methods_through_mock_server() {
+        use crate::mock::MockOverlayServer;
+
+        let mut server = MockOverlayServer::start().unwrap();
+        let client = server.client();
+        client.game_started("celeste", "Celeste", "linux").unwrap();
+        client.ra_progress_measure(42, 45, 100).unwrap();
+        client.game_stopped("celeste").unwrap();
+
+        let messages = server.wait_for(3, Duration::from_secs(2));
+        assert_eq!(messages.len(), 3);
+        assert!(
+            matches!(&messages[0], OverlayMessage::GameStarted { cart_id, .. } if cart_id == "celeste")
+        );
+        assert!(matches!(
+            messages[1],
+            OverlayMessage::RaProgressMeasure {
+                achievement_id: 42,
+                current: 45,
+                target: 100
+            }
+        ));
+        assert!(matches!(messages[2], OverlayMessage::GameStopped { .. }));
+    }
+
+    #[test]
+    fn test_client_reconnects_after_daemon_restart() {
+        use crate::mock::MockOverlayServer;
+
+        let mut server = MockOverlayServer::start().unwrap();
+        let path = server.socket_path().to_string();
+        let client = server.client();
+        client.hide_overlay().unwrap();
+        assert_eq!(server.wait_for(1, Duration::from_secs(2)).len(), 1);
+
+        drop(server);
+        let mut server = MockOverlayServer::with_socket_path(&path).unwrap();
+        // The first write may land on the dead connection's buffer; a later one must reconnect
+        for _ in 0..3 {
+            let _ = client.toggle_overlay();
+            if !server.wait_for(1, Duration::from_millis(200)).is_empty() {
+                break;
+            }
+        }
+        assert!(matches!(
+            server.take().first(),
+            Some(OverlayMessage::ToggleOverlay)
+        ));
+    }
+
+    #[test]
+    fn test_async_client_delivers_in_order() {
+        use crate::mock::MockOverlayServer;
+
+        let mut server = MockOverlayServer::start().unwrap();
+        let client = server.async_client();
+        for i in 0..5 {
+            client.info(format!("toast {}", i)).unwrap();
+        }
+
+        let messages = server.wait_for(5, Duration::from_secs(2));
+        assert_eq!(messages.len(), 5);
+        assert!(
+            matches!(&messages[4], OverlayMessage::ShowToast { message, .. } if message == "toast 4")
+        );
+        assert_eq!(client.dropped(), 0);
+    }
 }
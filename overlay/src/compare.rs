@@ -0,0 +1,245 @@
+//! Friend comparison for the active game
+//!
+//! Fetches the users the player follows on RetroAchievements, then each
+//! friend's unlocks for the current game, in a background thread. Results are
+//! kept per friend so flipping back and forth doesn't refetch.
+
+use anyhow::{Context, Result};
+use kazeta_ra::{CredentialManager, ProgressComparison, RAClient};
+use std::collections::HashMap;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+
+enum FetchResult {
+    Friends(Result<Vec<String>, String>),
+    Comparison(String, Result<ProgressComparison, String>),
+}
+
+fn client() -> Result<RAClient> {
+    let credentials = CredentialManager::new()?
+        .load()?
+        .context("Not logged in to RetroAchievements")?;
+    Ok(RAClient::new(credentials))
+}
+
+fn fetch_friends() -> Result<Vec<String>> {
+    let friends = client()?.get_followed_users()?;
+    Ok(friends.into_iter().map(|f| f.user).collect())
+}
+
+fn fetch_comparison(game_id: u32, friend: &str) -> Result<ProgressComparison> {
+    client()?.compare_progress(game_id, friend)
+}
+
+/// State of the friend comparison screen
+pub struct CompareView {
+    pub game_id: u32,
+    pub friends: Vec<String>,
+    pub friend_index: usize,
+    pub scroll_offset: usize,
+    pub loading: bool,
+    pub error: Option<String>,
+    comparisons: HashMap<String, ProgressComparison>,
+    allow_fetch: bool,
+    receiver: Option<Receiver<FetchResult>>,
+}
+
+impl CompareView {
+    /// Open the view and start loading the friends list
+    pub fn open(game_id: u32) -> Self {
+        let mut view = Self::new(game_id, true);
+        view.start_fetch(None);
+        view
+    }
+
+    fn new(game_id: u32, allow_fetch: bool) -> Self {
+        Self {
+            game_id,
+            friends: Vec::new(),
+            friend_index: 0,
+            scroll_offset: 0,
+            loading: false,
+            error: None,
+            comparisons: HashMap::new(),
+            allow_fetch,
+            receiver: None,
+        }
+    }
+
+    /// Fetch the friends list (`None`) or one friend's comparison
+    fn start_fetch(&mut self, friend: Option<String>) {
+        if !self.allow_fetch {
+            return;
+        }
+
+        let (tx, rx) = mpsc::channel();
+        let game_id = self.game_id;
+
+        thread::spawn(move || {
+            let result = match friend {
+                None => FetchResult::Friends(fetch_friends().map_err(|e| e.to_string())),
+                Some(friend) => {
+                    let result = fetch_comparison(game_id, &friend).map_err(|e| e.to_string());
+                    FetchResult::Comparison(friend, result)
+                }
+            };
+            let _ = tx.send(result);
+            crate::wake::wake();
+        });
+
+        self.receiver = Some(rx);
+        self.loading = true;
+        self.error = None;
+    }
+
+    /// Pick up the result of a background fetch, if one finished
+    pub fn poll(&mut self) {
+        let Some(receiver) = &self.receiver else {
+            return;
+        };
+
+        let result = match receiver.try_recv() {
+            Ok(result) => result,
+            Err(TryRecvError::Empty) => return,
+            Err(TryRecvError::Disconnected) => {
+                self.receiver = None;
+                self.loading = false;
+                self.error = Some("Fetch stopped unexpectedly".to_string());
+                return;
+            }
+        };
+        self.receiver = None;
+        self.loading = false;
+
+        match result {
+            FetchResult::Friends(Ok(friends)) => {
+                println!("[Compare] Loaded {} followed users", friends.len());
+                self.friends = friends;
+                self.friend_index = 0;
+                self.load_current();
+            }
+            FetchResult::Comparison(friend, Ok(comparison)) => {
+                println!(
+                    "[Compare] Loaded progress for {} in game {}",
+                    friend, self.game_id
+                );
+                self.comparisons.insert(friend, comparison);
+                // The user may have moved on while this was loading
+                self.load_current();
+            }
+            FetchResult::Friends(Err(e)) | FetchResult::Comparison(_, Err(e)) => {
+                eprintln!("[Compare] Fetch failed: {}", e);
+                self.error = Some(e);
+            }
+        }
+    }
+
+    /// Start fetching the selected friend's comparison unless we have it
+    fn load_current(&mut self) {
+        if self.loading {
+            return;
+        }
+        if let Some(friend) = self.current_friend() {
+            if !self.comparisons.contains_key(friend) {
+                let friend = friend.to_string();
+                self.start_fetch(Some(friend));
+            }
+        }
+    }
+
+    pub fn current_friend(&self) -> Option<&str> {
+        self.friends.get(self.friend_index).map(|f| f.as_str())
+    }
+
+    pub fn current(&self) -> Option<&ProgressComparison> {
+        self.current_friend().and_then(|f| self.comparisons.get(f))
+    }
+
+    pub fn next_friend(&mut self) {
+        if self.friends.is_empty() {
+            return;
+        }
+        self.friend_index = (self.friend_index + 1) % self.friends.len();
+        self.scroll_offset = 0;
+        self.load_current();
+    }
+
+    pub fn prev_friend(&mut self) {
+        if self.friends.is_empty() {
+            return;
+        }
+        self.friend_index = (self.friend_index + self.friends.len() - 1) % self.friends.len();
+        self.scroll_offset = 0;
+        self.load_current();
+    }
+
+    pub fn scroll_down(&mut self, visible_rows: usize) {
+        let total = self.current().map(|c| c.achievements.len()).unwrap_or(0);
+        if self.scroll_offset + visible_rows < total {
+            self.scroll_offset += 1;
+        }
+    }
+
+    pub fn scroll_up(&mut self) {
+        self.scroll_offset = self.scroll_offset.saturating_sub(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use kazeta_ra::AchievementComparison;
+
+    fn comparison(friend: &str, rows: usize) -> ProgressComparison {
+        ProgressComparison {
+            game_id: 1,
+            game_title: "Test".to_string(),
+            friend: friend.to_string(),
+            achievements: (0..rows)
+                .map(|i| AchievementComparison {
+                    id: i as u32,
+                    title: format!("Achievement {}", i),
+                    points: 5,
+                    mine: i % 2 == 0,
+                    theirs: i % 3 == 0,
+                })
+                .collect(),
+        }
+    }
+
+    fn view_with_friends() -> CompareView {
+        let mut view = CompareView::new(1, false);
+        view.friends = vec!["alice".to_string(), "bob".to_string()];
+        view.comparisons
+            .insert("alice".to_string(), comparison("alice", 10));
+        view
+    }
+
+    #[test]
+    fn test_friend_cycling_wraps() {
+        let mut view = view_with_friends();
+
+        view.prev_friend();
+        assert_eq!(view.current_friend(), Some("bob"));
+        assert!(view.current().is_none());
+
+        view.next_friend();
+        assert_eq!(view.current_friend(), Some("alice"));
+        assert_eq!(view.current().unwrap().friend, "alice");
+    }
+
+    #[test]
+    fn test_scrolling_is_clamped() {
+        let mut view = view_with_friends();
+
+        view.scroll_up();
+        assert_eq!(view.scroll_offset, 0);
+        for _ in 0..20 {
+            view.scroll_down(6);
+        }
+        assert_eq!(view.scroll_offset, 4);
+
+        view.next_friend();
+        assert_eq!(view.scroll_offset, 0);
+    }
+}
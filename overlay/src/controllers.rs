@@ -4,24 +4,99 @@
 //! - Bluetooth device discovery and pairing
 //! - Controller-to-player assignment
 //! - Gamepad testing/visualization
+//! - Per-controller deadzones and stick calibration (see `calibration`)
+//!
+//! Player assignments are remembered by the stable controller ID the input
+//! daemon reports, so a pad that reconnects (new event node, other USB port)
+//! gets its player slot back.
+//!
+//! When players join on the BIOS "press A to join" screen before a
+//! multiplayer launch, the BIOS writes who is who to `LAUNCH_PLAYERS_FILE`
+//! and the assignment is taken over when the game starts.
 
+use crate::calibration::DriftWizard;
+use kazeta_overlay::gamepad_tester::GamepadTester;
+use serde::Deserialize;
 use std::collections::HashMap;
+use std::path::Path;
 use std::time::{Duration, Instant};
 
 /// Maximum number of players supported
 pub const MAX_PLAYERS: usize = 4;
 
+/// Players chosen on the BIOS join screen
+pub const LAUNCH_PLAYERS_FILE: &str = "/tmp/kazeta-players.json";
+
+#[derive(Debug, Deserialize)]
+pub struct LaunchPlayers {
+    pub cart_id: String,
+    pub players: Vec<LaunchPlayer>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LaunchPlayer {
+    pub player: usize,
+    pub name: String,
+    /// gilrs UUID; empty for the keyboard
+    #[serde(default)]
+    pub uuid: String,
+}
+
+impl LaunchPlayers {
+    /// The assignment written for this game, if there is one
+    pub fn load(path: &Path, cart_id: &str) -> Option<Self> {
+        let contents = std::fs::read_to_string(path).ok()?;
+        let launch: LaunchPlayers = serde_json::from_str(&contents)
+            .map_err(|e| eprintln!("[Controllers] Ignoring {}: {}", path.display(), e))
+            .ok()?;
+        (launch.cart_id == cart_id).then_some(launch)
+    }
+}
+
 /// Represents a connected controller
 #[derive(Debug, Clone)]
 pub struct ConnectedController {
     pub id: usize,
     pub name: String,
     pub uuid: String,
+    /// SDL-style GUID that calibration is keyed by
+    pub guid: String,
+    /// Stable ID from the input daemon, or the gilrs UUID without the daemon
+    pub stable_id: String,
     pub is_wireless: bool,
-    pub battery_level: Option<u8>, // 0-100%
+    pub battery_level: Option<u8>,      // 0-100%
     pub assigned_player: Option<usize>, // 1-4, or None if unassigned
 }
 
+/// A gamepad reported by the input daemon
+#[derive(Debug, Clone, PartialEq)]
+pub struct InputDevice {
+    pub controller_id: String,
+    pub name: String,
+    pub vendor_id: Option<u16>,
+    pub product_id: Option<u16>,
+}
+
+/// Find the daemon device for a gilrs gamepad: same vendor/product (or name
+/// if either side lacks IDs), in connection order, skipping ones already taken
+fn match_input_device(
+    devices: &[InputDevice],
+    name: &str,
+    vendor_id: Option<u16>,
+    product_id: Option<u16>,
+    claimed: &mut Vec<String>,
+) -> Option<String> {
+    let device = devices.iter().find(|d| {
+        let same_model = match (vendor_id, product_id, d.vendor_id, d.product_id) {
+            (Some(v), Some(p), Some(dv), Some(dp)) => v == dv && p == dp,
+            _ => d.name == name,
+        };
+        same_model && !claimed.contains(&d.controller_id)
+    })?;
+    claimed.push(device.controller_id.clone());
+    Some(device.controller_id.clone())
+}
+
 /// Represents a discovered Bluetooth device
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct BluetoothDevice {
@@ -42,62 +117,43 @@ pub enum BluetoothScanState {
     Error(String),
 }
 
-/// Gamepad button state for tester
-#[derive(Debug, Clone, Default)]
-pub struct GamepadButtonState {
-    // Face buttons
-    pub a: bool,
-    pub b: bool,
-    pub x: bool,
-    pub y: bool,
-    // D-Pad
-    pub dpad_up: bool,
-    pub dpad_down: bool,
-    pub dpad_left: bool,
-    pub dpad_right: bool,
-    // Shoulder buttons
-    pub lb: bool,
-    pub rb: bool,
-    pub lt: f32, // 0.0 - 1.0
-    pub rt: f32, // 0.0 - 1.0
-    // Sticks (pressed)
-    pub ls_press: bool,
-    pub rs_press: bool,
-    // Stick axes
-    pub left_stick_x: f32,  // -1.0 to 1.0
-    pub left_stick_y: f32,  // -1.0 to 1.0
-    pub right_stick_x: f32, // -1.0 to 1.0
-    pub right_stick_y: f32, // -1.0 to 1.0
-    // Special buttons
-    pub start: bool,
-    pub select: bool,
-    pub guide: bool,
-}
-
 /// State for the controller management screens
 #[derive(Debug)]
 pub struct ControllerState {
     // Connected controllers
     pub controllers: Vec<ConnectedController>,
-    
+
     // Player assignments (index = player number - 1, value = controller id)
     pub player_assignments: [Option<usize>; MAX_PLAYERS],
-    
+
+    // Gamepads reported by the input daemon, in connection order
+    pub input_devices: Vec<InputDevice>,
+    // Player of each stable controller ID, kept while the pad is disconnected
+    saved_assignments: HashMap<String, usize>,
+
     // Bluetooth state
     pub bluetooth_devices: Vec<BluetoothDevice>,
     pub bluetooth_state: BluetoothScanState,
     pub bt_selected_index: usize,
     pub bt_scroll_offset: usize,
-    
+
     // Controller assignment state
     pub assign_selected_player: usize, // 0-3 for player 1-4
     pub assign_selected_controller: usize,
-    
+
     // Gamepad tester state
     pub tester_selected_controller: usize,
-    pub tester_button_state: GamepadButtonState,
-    pub tester_last_input_time: Instant,
-    
+    pub tester: GamepadTester,
+
+    // Controller settings state
+    pub settings_selected_controller: usize,
+    pub settings_selected_row: usize,
+    pub settings_wizard: Option<DriftWizard>,
+    /// Raw [left x, left y, right x, right y] of the selected controller
+    pub settings_axes: [f32; 4],
+    /// Raw [left, right] trigger travel of the selected controller
+    pub settings_triggers: [f32; 2],
+
     // UI state
     pub selected_menu_item: usize,
     pub error_message: Option<String>,
@@ -109,6 +165,8 @@ impl ControllerState {
         Self {
             controllers: Vec::new(),
             player_assignments: [None; MAX_PLAYERS],
+            input_devices: Vec::new(),
+            saved_assignments: HashMap::new(),
             bluetooth_devices: Vec::new(),
             bluetooth_state: BluetoothScanState::Idle,
             bt_selected_index: 0,
@@ -116,8 +174,12 @@ impl ControllerState {
             assign_selected_player: 0,
             assign_selected_controller: 0,
             tester_selected_controller: 0,
-            tester_button_state: GamepadButtonState::default(),
-            tester_last_input_time: Instant::now(),
+            tester: GamepadTester::default(),
+            settings_selected_controller: 0,
+            settings_selected_row: 0,
+            settings_wizard: None,
+            settings_axes: [0.0; 4],
+            settings_triggers: [0.0; 2],
             selected_menu_item: 0,
             error_message: None,
             success_message: None,
@@ -128,56 +190,98 @@ impl ControllerState {
     #[cfg(feature = "daemon")]
     pub fn update_from_gilrs(&mut self, gilrs: &gilrs::Gilrs) {
         let mut new_controllers = Vec::new();
-        
+        let mut claimed = Vec::new();
+
         for (id, gamepad) in gilrs.gamepads() {
             let name = gamepad.name().to_string();
             let uuid = format!("{:?}", gamepad.uuid());
-            
-            // Check if already in our list
-            let existing = self.controllers.iter().find(|c| c.uuid == uuid);
-            let assigned_player = existing.and_then(|c| c.assigned_player);
-            
+            let guid = crate::calibration::guid_from_uuid(gamepad.uuid());
+            let stable_id = match_input_device(
+                &self.input_devices,
+                &name,
+                gamepad.vendor_id(),
+                gamepad.product_id(),
+                &mut claimed,
+            )
+            .unwrap_or_else(|| uuid.clone());
+
             new_controllers.push(ConnectedController {
                 id: id.into(),
                 name,
                 uuid,
-                is_wireless: false, // gilrs doesn't provide this info directly
+                guid,
+                stable_id,
+                is_wireless: false,  // gilrs doesn't provide this info directly
                 battery_level: None, // Would need platform-specific code
-                assigned_player,
+                assigned_player: None,
             });
         }
-        
-        self.controllers = new_controllers;
-        
-        // Clean up player assignments for disconnected controllers
-        for assignment in &mut self.player_assignments {
-            if let Some(controller_id) = *assignment {
-                if !self.controllers.iter().any(|c| c.id == controller_id) {
-                    *assignment = None;
+
+        self.set_controllers(new_controllers);
+    }
+
+    /// Replace the connected controllers, restoring their saved player slots
+    pub fn set_controllers(&mut self, mut controllers: Vec<ConnectedController>) {
+        self.player_assignments = [None; MAX_PLAYERS];
+
+        for controller in &mut controllers {
+            controller.assigned_player = None;
+            if let Some(&player) = self.saved_assignments.get(&controller.stable_id) {
+                // Two pads can't share a slot; the first one keeps it
+                if self.player_assignments[player - 1].is_none() {
+                    self.player_assignments[player - 1] = Some(controller.id);
+                    controller.assigned_player = Some(player);
                 }
             }
         }
+
+        self.controllers = controllers;
+    }
+
+    /// Record a gamepad reported by the input daemon
+    pub fn input_device_connected(&mut self, device: InputDevice) {
+        if let Some(player) = self.saved_assignments.get(&device.controller_id) {
+            println!(
+                "[Controllers] {} reconnected as player {}",
+                device.name, player
+            );
+        }
+        self.input_devices
+            .retain(|d| d.controller_id != device.controller_id);
+        self.input_devices.push(device);
+    }
+
+    /// Forget a gamepad the input daemon lost; its player slot is kept
+    pub fn input_device_disconnected(&mut self, controller_id: &str) {
+        self.input_devices
+            .retain(|d| d.controller_id != controller_id);
     }
 
     /// Assign a controller to a player
-    pub fn assign_controller_to_player(&mut self, controller_id: usize, player: usize) -> Result<(), String> {
+    pub fn assign_controller_to_player(
+        &mut self,
+        controller_id: usize,
+        player: usize,
+    ) -> Result<(), String> {
         if player == 0 || player > MAX_PLAYERS {
             return Err(format!("Invalid player number: {}", player));
         }
-        
+
         // Check if controller exists
-        let controller_idx = self.controllers.iter()
+        let controller_idx = self
+            .controllers
+            .iter()
             .position(|c| c.id == controller_id)
             .ok_or_else(|| format!("Controller {} not found", controller_id))?;
-        
+
         // Get old player assignment for this controller (to clear player_assignments)
         let old_player = self.controllers[controller_idx].assigned_player;
-        
+
         // Clear old player assignment
         if let Some(old_p) = old_player {
             self.player_assignments[old_p - 1] = None;
         }
-        
+
         // Clear any existing controller from this player slot
         if let Some(old_controller_id) = self.player_assignments[player - 1] {
             // Find and clear the old controller's assignment
@@ -188,11 +292,16 @@ impl ControllerState {
                 }
             }
         }
-        
+
         // Make the new assignment
         self.controllers[controller_idx].assigned_player = Some(player);
         self.player_assignments[player - 1] = Some(controller_id);
-        
+
+        // Remember it for when the pad reconnects
+        self.saved_assignments.retain(|_, p| *p != player);
+        self.saved_assignments
+            .insert(self.controllers[controller_idx].stable_id.clone(), player);
+
         Ok(())
     }
 
@@ -203,6 +312,7 @@ impl ControllerState {
                 self.player_assignments[player - 1] = None;
             }
             controller.assigned_player = None;
+            self.saved_assignments.remove(&controller.stable_id);
         }
     }
 
@@ -211,7 +321,7 @@ impl ControllerState {
         if player == 0 || player > MAX_PLAYERS {
             return None;
         }
-        
+
         self.player_assignments[player - 1]
             .and_then(|id| self.controllers.iter().find(|c| c.id == id))
     }
@@ -225,12 +335,43 @@ impl ControllerState {
         for controller in &mut self.controllers {
             controller.assigned_player = None;
         }
-        
+        self.saved_assignments.clear();
+
         // Assign in order
         for (i, controller) in self.controllers.iter_mut().enumerate() {
             if i < MAX_PLAYERS {
                 controller.assigned_player = Some(i + 1);
                 self.player_assignments[i] = Some(controller.id);
+                self.saved_assignments
+                    .insert(controller.stable_id.clone(), i + 1);
+            }
+        }
+    }
+
+    /// Take over the players from the BIOS join screen. Identical pads are
+    /// matched in connection order; players whose pad isn't found (or who
+    /// joined on the keyboard) are left unassigned.
+    pub fn apply_launch_players(&mut self, launch: &LaunchPlayers) {
+        let mut claimed = Vec::new();
+        for entry in &launch.players {
+            if entry.uuid.is_empty() {
+                continue;
+            }
+            let Some(id) = self
+                .controllers
+                .iter()
+                .find(|c| c.uuid == entry.uuid && !claimed.contains(&c.id))
+                .map(|c| c.id)
+            else {
+                println!(
+                    "[Controllers] P{} ({}) not connected",
+                    entry.player, entry.name
+                );
+                continue;
+            };
+            claimed.push(id);
+            if let Err(e) = self.assign_controller_to_player(id, entry.player) {
+                eprintln!("[Controllers] {}", e);
             }
         }
     }
@@ -238,94 +379,65 @@ impl ControllerState {
     /// Update gamepad tester state from gilrs events
     #[cfg(feature = "daemon")]
     pub fn update_tester_from_gilrs(&mut self, gilrs: &mut gilrs::Gilrs) {
-        use gilrs::{Button, Axis, EventType};
-        
         // Get the selected controller's gilrs ID
-        let selected_id = self.controllers
+        let selected_id = self
+            .controllers
             .get(self.tester_selected_controller)
             .map(|c| c.id);
-        
+
         // Process events
         while let Some(event) = gilrs.next_event() {
             // Only process events from the selected controller
             let event_id: usize = event.id.into();
-            if Some(event_id) != selected_id {
-                continue;
-            }
-            
-            self.tester_last_input_time = Instant::now();
-            
-            match event.event {
-                // Face buttons
-                EventType::ButtonPressed(Button::South, _) => self.tester_button_state.a = true,
-                EventType::ButtonReleased(Button::South, _) => self.tester_button_state.a = false,
-                EventType::ButtonPressed(Button::East, _) => self.tester_button_state.b = true,
-                EventType::ButtonReleased(Button::East, _) => self.tester_button_state.b = false,
-                EventType::ButtonPressed(Button::West, _) => self.tester_button_state.x = true,
-                EventType::ButtonReleased(Button::West, _) => self.tester_button_state.x = false,
-                EventType::ButtonPressed(Button::North, _) => self.tester_button_state.y = true,
-                EventType::ButtonReleased(Button::North, _) => self.tester_button_state.y = false,
-                
-                // D-Pad
-                EventType::ButtonPressed(Button::DPadUp, _) => self.tester_button_state.dpad_up = true,
-                EventType::ButtonReleased(Button::DPadUp, _) => self.tester_button_state.dpad_up = false,
-                EventType::ButtonPressed(Button::DPadDown, _) => self.tester_button_state.dpad_down = true,
-                EventType::ButtonReleased(Button::DPadDown, _) => self.tester_button_state.dpad_down = false,
-                EventType::ButtonPressed(Button::DPadLeft, _) => self.tester_button_state.dpad_left = true,
-                EventType::ButtonReleased(Button::DPadLeft, _) => self.tester_button_state.dpad_left = false,
-                EventType::ButtonPressed(Button::DPadRight, _) => self.tester_button_state.dpad_right = true,
-                EventType::ButtonReleased(Button::DPadRight, _) => self.tester_button_state.dpad_right = false,
-                
-                // Shoulder buttons
-                EventType::ButtonPressed(Button::LeftTrigger, _) => self.tester_button_state.lb = true,
-                EventType::ButtonReleased(Button::LeftTrigger, _) => self.tester_button_state.lb = false,
-                EventType::ButtonPressed(Button::RightTrigger, _) => self.tester_button_state.rb = true,
-                EventType::ButtonReleased(Button::RightTrigger, _) => self.tester_button_state.rb = false,
-                
-                // Trigger axes
-                EventType::AxisChanged(Axis::LeftZ, value, _) => {
-                    self.tester_button_state.lt = (value + 1.0) / 2.0; // Convert -1..1 to 0..1
-                }
-                EventType::AxisChanged(Axis::RightZ, value, _) => {
-                    self.tester_button_state.rt = (value + 1.0) / 2.0;
-                }
-                
-                // Stick buttons
-                EventType::ButtonPressed(Button::LeftThumb, _) => self.tester_button_state.ls_press = true,
-                EventType::ButtonReleased(Button::LeftThumb, _) => self.tester_button_state.ls_press = false,
-                EventType::ButtonPressed(Button::RightThumb, _) => self.tester_button_state.rs_press = true,
-                EventType::ButtonReleased(Button::RightThumb, _) => self.tester_button_state.rs_press = false,
-                
-                // Stick axes
-                EventType::AxisChanged(Axis::LeftStickX, value, _) => {
-                    self.tester_button_state.left_stick_x = value;
-                }
-                EventType::AxisChanged(Axis::LeftStickY, value, _) => {
-                    self.tester_button_state.left_stick_y = value;
-                }
-                EventType::AxisChanged(Axis::RightStickX, value, _) => {
-                    self.tester_button_state.right_stick_x = value;
-                }
-                EventType::AxisChanged(Axis::RightStickY, value, _) => {
-                    self.tester_button_state.right_stick_y = value;
-                }
-                
-                // Special buttons
-                EventType::ButtonPressed(Button::Start, _) => self.tester_button_state.start = true,
-                EventType::ButtonReleased(Button::Start, _) => self.tester_button_state.start = false,
-                EventType::ButtonPressed(Button::Select, _) => self.tester_button_state.select = true,
-                EventType::ButtonReleased(Button::Select, _) => self.tester_button_state.select = false,
-                EventType::ButtonPressed(Button::Mode, _) => self.tester_button_state.guide = true,
-                EventType::ButtonReleased(Button::Mode, _) => self.tester_button_state.guide = false,
-                
-                _ => {}
+            if Some(event_id) == selected_id {
+                self.tester.handle_event(&event.event);
             }
         }
+        self.tester.sample();
+    }
+
+    /// Read the raw sticks and triggers of the controller being configured
+    #[cfg(feature = "daemon")]
+    pub fn update_settings_from_gilrs(&mut self, gilrs: &mut gilrs::Gilrs) {
+        use gilrs::{Axis, Button};
+
+        // Gamepad state only advances as events are consumed
+        while gilrs.next_event().is_some() {}
+
+        let selected_id = self
+            .controllers
+            .get(self.settings_selected_controller)
+            .map(|c| c.id);
+        let Some((_, gamepad)) = gilrs
+            .gamepads()
+            .find(|(id, _)| Some(usize::from(*id)) == selected_id)
+        else {
+            self.settings_axes = [0.0; 4];
+            self.settings_triggers = [0.0; 2];
+            return;
+        };
+
+        self.settings_axes = [
+            gamepad.value(Axis::LeftStickX),
+            gamepad.value(Axis::LeftStickY),
+            gamepad.value(Axis::RightStickX),
+            gamepad.value(Axis::RightStickY),
+        ];
+        let trigger = |button| {
+            gamepad
+                .button_data(button)
+                .map(|d| d.value())
+                .unwrap_or(0.0)
+        };
+        self.settings_triggers = [
+            trigger(Button::LeftTrigger2),
+            trigger(Button::RightTrigger2),
+        ];
     }
 
     /// Clear the tester button state
     pub fn reset_tester_state(&mut self) {
-        self.tester_button_state = GamepadButtonState::default();
+        self.tester.reset();
     }
 
     /// Show success message temporarily
@@ -367,8 +479,109 @@ pub const CONTROLLER_MENU_OPTIONS: &[&str] = &[
     "BLUETOOTH CONTROLLERS",
     "ASSIGN CONTROLLERS",
     "GAMEPAD TESTER",
+    "CONTROLLER SETTINGS",
     "HOTKEY SETTINGS",
+    "INPUT MACROS",
     "AUTO-ASSIGN ALL",
     "BACK",
 ];
 
+// Rows of the controller settings screen
+pub const CONTROLLER_SETTINGS_ROWS: &[&str] = &[
+    "CONTROLLER",
+    "LEFT STICK DEADZONE",
+    "RIGHT STICK DEADZONE",
+    "TRIGGER THRESHOLD",
+    "CALIBRATE STICK DRIFT",
+    "RESET TO DEFAULTS",
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn device(controller_id: &str, vendor_id: u16) -> InputDevice {
+        InputDevice {
+            controller_id: controller_id.to_string(),
+            name: "Pad".to_string(),
+            vendor_id: Some(vendor_id),
+            product_id: Some(1),
+        }
+    }
+
+    fn controller(id: usize, stable_id: &str) -> ConnectedController {
+        ConnectedController {
+            id,
+            name: "Pad".to_string(),
+            uuid: format!("uuid-{}", stable_id),
+            guid: format!("guid-{}", stable_id),
+            stable_id: stable_id.to_string(),
+            is_wireless: false,
+            battery_level: None,
+            assigned_player: None,
+        }
+    }
+
+    #[test]
+    fn test_match_input_device_in_connection_order() {
+        let devices = vec![device("pad-a", 1), device("pad-b", 2), device("pad-c", 1)];
+        let mut claimed = Vec::new();
+
+        assert_eq!(
+            match_input_device(&devices, "Pad", Some(1), Some(1), &mut claimed),
+            Some("pad-a".to_string())
+        );
+        assert_eq!(
+            match_input_device(&devices, "Pad", Some(1), Some(1), &mut claimed),
+            Some("pad-c".to_string())
+        );
+        assert_eq!(
+            match_input_device(&devices, "Pad", Some(1), Some(1), &mut claimed),
+            None
+        );
+        // Without IDs the name decides
+        assert_eq!(
+            match_input_device(&devices, "Pad", None, None, &mut claimed),
+            Some("pad-b".to_string())
+        );
+    }
+
+    #[test]
+    fn test_assignment_survives_reconnect() {
+        let mut state = ControllerState::new();
+        state.set_controllers(vec![controller(0, "pad-a"), controller(1, "pad-b")]);
+        state.assign_controller_to_player(1, 1).unwrap();
+
+        // pad-b disconnects, then comes back with a new gilrs id
+        state.set_controllers(vec![controller(0, "pad-a")]);
+        assert_eq!(state.player_assignments[0], None);
+        state.set_controllers(vec![controller(0, "pad-a"), controller(5, "pad-b")]);
+        assert_eq!(state.player_assignments[0], Some(5));
+        assert_eq!(state.get_player_controller(1).unwrap().stable_id, "pad-b");
+
+        state.unassign_controller(5);
+        state.set_controllers(vec![controller(5, "pad-b")]);
+        assert_eq!(state.player_assignments[0], None);
+    }
+
+    #[test]
+    fn test_apply_launch_players() {
+        let mut state = ControllerState::new();
+        state.set_controllers(vec![controller(0, "pad-a"), controller(1, "pad-b")]);
+        let launch: LaunchPlayers = serde_json::from_str(
+            r#"{
+            "cart_id": "tetris",
+            "players": [
+                {"player": 1, "name": "Pad", "uuid": "uuid-pad-b"},
+                {"player": 2, "name": "Keyboard", "uuid": ""},
+                {"player": 3, "name": "Pad", "uuid": "uuid-pad-a"}
+            ]
+        }"#,
+        )
+        .unwrap();
+
+        state.apply_launch_players(&launch);
+        assert_eq!(state.player_assignments, [Some(1), None, Some(0), None]);
+        assert_eq!(state.get_player_controller(3).unwrap().stable_id, "pad-a");
+    }
+}
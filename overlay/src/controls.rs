@@ -0,0 +1,270 @@
+//! Button mapping of the running game
+//!
+//! Carts can ship a `controls.toml` next to their `.kzi`:
+//!
+//! ```toml
+//! [buttons]
+//! a = "Jump"
+//! x = "Dash"
+//! dpad = "Move"
+//! start = "Pause"
+//! "F1" = "Map"
+//! ```
+//!
+//! The BIOS reads it at launch and sends it as `game_controls`. Each mapping
+//! is cached in `overlay/controls/<cart_id>.json`, so a restarted overlay
+//! still has it for the recovered session. The Game Controls screen draws a
+//! controller with the mapped buttons labelled; names that aren't pad buttons
+//! are listed under it.
+
+use crate::ipc::ControlHint;
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::PathBuf;
+
+/// A button on the controller diagram
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Button {
+    A,
+    B,
+    X,
+    Y,
+    LB,
+    RB,
+    LT,
+    RT,
+    DPad,
+    DPadUp,
+    DPadDown,
+    DPadLeft,
+    DPadRight,
+    LeftStick,
+    RightStick,
+    L3,
+    R3,
+    Select,
+    Start,
+    Guide,
+}
+
+impl Button {
+    /// The button a `controls.toml` key names, ignoring case
+    pub fn parse(name: &str) -> Option<Self> {
+        Some(
+            match name.trim().to_lowercase().replace(['-', ' '], "_").as_str() {
+                "a" => Button::A,
+                "b" => Button::B,
+                "x" => Button::X,
+                "y" => Button::Y,
+                "lb" | "l1" => Button::LB,
+                "rb" | "r1" => Button::RB,
+                "lt" | "l2" => Button::LT,
+                "rt" | "r2" => Button::RT,
+                "dpad" => Button::DPad,
+                "dpad_up" => Button::DPadUp,
+                "dpad_down" => Button::DPadDown,
+                "dpad_left" => Button::DPadLeft,
+                "dpad_right" => Button::DPadRight,
+                "left_stick" | "ls" => Button::LeftStick,
+                "right_stick" | "rs" => Button::RightStick,
+                "l3" => Button::L3,
+                "r3" => Button::R3,
+                "select" | "back" => Button::Select,
+                "start" => Button::Start,
+                "guide" | "home" => Button::Guide,
+                _ => return None,
+            },
+        )
+    }
+
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            Button::A => "A",
+            Button::B => "B",
+            Button::X => "X",
+            Button::Y => "Y",
+            Button::LB => "LB",
+            Button::RB => "RB",
+            Button::LT => "LT",
+            Button::RT => "RT",
+            Button::DPad => "D-Pad",
+            Button::DPadUp => "D-Pad Up",
+            Button::DPadDown => "D-Pad Down",
+            Button::DPadLeft => "D-Pad Left",
+            Button::DPadRight => "D-Pad Right",
+            Button::LeftStick => "Left Stick",
+            Button::RightStick => "Right Stick",
+            Button::L3 => "L3",
+            Button::R3 => "R3",
+            Button::Select => "Select",
+            Button::Start => "Start",
+            Button::Guide => "Guide",
+        }
+    }
+
+    /// Where the button sits on the diagram. The body spans -1.0 to 1.0
+    /// across, with the shoulders above it at negative y.
+    pub fn position(&self) -> (f32, f32) {
+        match self {
+            Button::LT => (-0.62, -0.78),
+            Button::LB => (-0.62, -0.62),
+            Button::RT => (0.62, -0.78),
+            Button::RB => (0.62, -0.62),
+            Button::LeftStick | Button::L3 => (-0.55, -0.2),
+            Button::DPad => (-0.3, 0.2),
+            Button::DPadUp => (-0.3, 0.08),
+            Button::DPadDown => (-0.3, 0.32),
+            Button::DPadLeft => (-0.42, 0.2),
+            Button::DPadRight => (-0.18, 0.2),
+            Button::RightStick | Button::R3 => (0.3, 0.2),
+            Button::Y => (0.55, -0.38),
+            Button::X => (0.42, -0.2),
+            Button::B => (0.68, -0.2),
+            Button::A => (0.55, -0.02),
+            Button::Select => (-0.15, -0.25),
+            Button::Start => (0.15, -0.25),
+            Button::Guide => (0.0, -0.45),
+        }
+    }
+
+    /// Whether the label goes in the column left of the diagram
+    pub fn labelled_left(&self) -> bool {
+        self.position().0 <= 0.0
+    }
+}
+
+/// Hints split into what goes on the diagram and what's listed under it
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ControlsLayout {
+    pub buttons: Vec<(Button, String)>,
+    pub other: Vec<ControlHint>,
+}
+
+impl ControlsLayout {
+    pub fn new(hints: &[ControlHint]) -> Self {
+        let mut layout = Self::default();
+        for hint in hints {
+            match Button::parse(&hint.button) {
+                Some(button) => layout.buttons.push((button, hint.label.clone())),
+                None => layout.other.push(hint.clone()),
+            }
+        }
+        layout
+    }
+
+    /// Buttons labelled on one side, top to bottom
+    pub fn side(&self, left: bool) -> Vec<(Button, &str)> {
+        let mut side: Vec<(Button, &str)> = self
+            .buttons
+            .iter()
+            .filter(|(button, _)| button.labelled_left() == left)
+            .map(|(button, label)| (*button, label.as_str()))
+            .collect();
+        side.sort_by(|a, b| a.0.position().1.total_cmp(&b.0.position().1));
+        side
+    }
+}
+
+/// Mappings received from the BIOS, by cart
+pub struct ControlsCache {
+    dir: PathBuf,
+}
+
+impl ControlsCache {
+    pub fn new() -> Result<Self> {
+        let data_dir =
+            dirs::data_local_dir().context("Could not determine local data directory")?;
+
+        Ok(Self::with_dir(
+            data_dir
+                .join("kazeta-plus")
+                .join("overlay")
+                .join("controls"),
+        ))
+    }
+
+    pub fn with_dir(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    fn path(&self, cart_id: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", cart_id))
+    }
+
+    /// The cached mapping for a cart, empty when there is none
+    pub fn load(&self, cart_id: &str) -> Vec<ControlHint> {
+        fs::read_to_string(self.path(cart_id))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// Replace a cart's mapping. An empty one removes it, for carts that
+    /// stopped shipping `controls.toml`.
+    pub fn store(&self, cart_id: &str, controls: &[ControlHint]) -> Result<()> {
+        let path = self.path(cart_id);
+        if controls.is_empty() {
+            if path.exists() {
+                fs::remove_file(&path).context("Failed to remove cached controls")?;
+            }
+            return Ok(());
+        }
+        fs::create_dir_all(&self.dir).context("Failed to create controls cache directory")?;
+        let json =
+            serde_json::to_string_pretty(controls).context("Failed to serialize controls")?;
+        fs::write(&path, json).context("Failed to write cached controls")?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hint(button: &str, label: &str) -> ControlHint {
+        ControlHint {
+            button: button.to_string(),
+            label: label.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_layout_splits_pad_buttons_from_other_keys() {
+        let layout = ControlsLayout::new(&[
+            hint("A", "Jump"),
+            hint("dpad-up", "Look up"),
+            hint("L1", "Previous weapon"),
+            hint("F1", "Map"),
+            hint("start", "Pause"),
+        ]);
+        assert_eq!(layout.other, vec![hint("F1", "Map")]);
+        assert_eq!(
+            layout.side(true),
+            vec![(Button::LB, "Previous weapon"), (Button::DPadUp, "Look up")]
+        );
+        assert_eq!(
+            layout.side(false),
+            vec![(Button::Start, "Pause"), (Button::A, "Jump")]
+        );
+    }
+
+    #[test]
+    fn test_cache_round_trip_and_clear() {
+        let dir = std::env::temp_dir().join(format!("kazeta-controls-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let cache = ControlsCache::with_dir(dir.clone());
+        assert!(cache.load("celeste").is_empty());
+
+        cache.store("celeste", &[hint("a", "Jump")]).unwrap();
+        assert_eq!(
+            ControlsCache::with_dir(dir.clone()).load("celeste"),
+            vec![hint("a", "Jump")]
+        );
+        assert!(cache.load("pokemon").is_empty());
+
+        cache.store("celeste", &[]).unwrap();
+        assert!(cache.load("celeste").is_empty());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
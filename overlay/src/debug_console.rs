@@ -0,0 +1,201 @@
+//! Hidden debug console: a live log of incoming IPC messages and a way to
+//! inject test messages, for developing runtime wrappers against the overlay
+//!
+//! Always available in debug builds. Release builds show it when
+//! `~/.local/share/kazeta-plus/overlay/debug.json` contains
+//! `{"console": true}`. It's opened from the Message Rules screen.
+
+use crate::ipc::{IncomingMessage, IpcServer};
+use crate::ipc_rules::RuleAction;
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::VecDeque;
+use std::fs;
+use std::time::{Duration, Instant};
+
+/// Messages kept in the log
+pub const LOG_CAPACITY: usize = 200;
+
+/// Source given to injected messages, so rules and the log can tell them apart
+pub const INJECT_SOURCE: &str = "debug-console";
+
+/// Test messages the console can inject, as a client would send them
+pub const TEST_MESSAGES: &[(&str, &str)] = &[
+    (
+        "Info toast",
+        r#"{"type":"show_toast","message":"Test toast","duration_ms":3000,"style":"info"}"#,
+    ),
+    (
+        "Error toast",
+        r#"{"type":"show_toast","message":"Test error","duration_ms":3000,"style":"error"}"#,
+    ),
+    (
+        "RA game start",
+        r#"{"type":"ra_game_start","game_title":"Test Game","game_id":1,"total_achievements":10,"earned_achievements":2}"#,
+    ),
+    (
+        "RA achievement unlock",
+        r#"{"type":"ra_achievement_unlocked","achievement_id":1,"title":"Test Achievement","description":"Injected from the debug console","points":10}"#,
+    ),
+    (
+        "RA progress update",
+        r#"{"type":"ra_progress_update","earned":3,"total":10}"#,
+    ),
+    ("Hide overlay", r#"{"type":"hide_overlay"}"#),
+];
+
+#[derive(Debug, Default, Deserialize)]
+struct DebugConfig {
+    #[serde(default)]
+    console: bool,
+}
+
+/// One received message, as the log shows it
+#[derive(Debug, Clone)]
+pub struct LoggedMessage {
+    pub at: Instant,
+    pub kind: String,
+    pub source: Option<String>,
+    /// What the message rules did with it
+    pub action: RuleAction,
+    pub summary: String,
+}
+
+pub struct DebugConsole {
+    started: Instant,
+    log: VecDeque<LoggedMessage>,
+    /// Selected test message
+    pub selected: usize,
+    /// Log lines scrolled past, from the newest
+    pub scroll: usize,
+}
+
+impl DebugConsole {
+    /// The console if this build or the config enables it
+    pub fn new() -> Option<Self> {
+        if !cfg!(debug_assertions) {
+            match load_config() {
+                Ok(config) if config.console => {}
+                Ok(_) => return None,
+                Err(e) => {
+                    eprintln!("[Debug] Failed to load debug config: {}", e);
+                    return None;
+                }
+            }
+        }
+        println!("[Debug] Debug console enabled");
+        Some(Self::default())
+    }
+
+    pub fn record(&mut self, incoming: &IncomingMessage, action: RuleAction) {
+        if self.log.len() >= LOG_CAPACITY {
+            self.log.pop_back();
+        }
+        self.log.push_front(LoggedMessage {
+            at: Instant::now(),
+            kind: incoming.kind.clone(),
+            source: incoming.source.clone(),
+            action,
+            summary: format!("{:?}", incoming.message),
+        });
+    }
+
+    /// Logged messages, newest first
+    pub fn log(&self) -> impl Iterator<Item = &LoggedMessage> {
+        self.log.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.log.len()
+    }
+
+    pub fn clear(&mut self) {
+        self.log.clear();
+        self.scroll = 0;
+    }
+
+    /// When a message arrived, relative to the overlay's start
+    pub fn timestamp(&self, message: &LoggedMessage) -> Duration {
+        message.at.duration_since(self.started)
+    }
+
+    pub fn select_prev(&mut self) {
+        self.selected = (self.selected + TEST_MESSAGES.len() - 1) % TEST_MESSAGES.len();
+    }
+
+    pub fn select_next(&mut self) {
+        self.selected = (self.selected + 1) % TEST_MESSAGES.len();
+    }
+
+    pub fn scroll_older(&mut self, rows: usize) {
+        self.scroll = (self.scroll + rows).min(self.log.len().saturating_sub(1));
+    }
+
+    pub fn scroll_newer(&mut self, rows: usize) {
+        self.scroll = self.scroll.saturating_sub(rows);
+    }
+
+    /// The selected test message, parsed the way the IPC server would
+    pub fn test_message(&self) -> Result<IncomingMessage> {
+        let (label, json) = TEST_MESSAGES[self.selected];
+        let mut incoming = IpcServer::parse_incoming(json)
+            .with_context(|| format!("Invalid test message '{}'", label))?;
+        incoming.source = Some(INJECT_SOURCE.to_string());
+        Ok(incoming)
+    }
+}
+
+impl Default for DebugConsole {
+    fn default() -> Self {
+        Self {
+            started: Instant::now(),
+            log: VecDeque::with_capacity(LOG_CAPACITY),
+            selected: 0,
+            scroll: 0,
+        }
+    }
+}
+
+fn load_config() -> Result<DebugConfig> {
+    let path = dirs::data_local_dir()
+        .context("Could not determine local data directory")?
+        .join("kazeta-plus")
+        .join("overlay")
+        .join("debug.json");
+    if !path.exists() {
+        return Ok(DebugConfig::default());
+    }
+    let contents = fs::read_to_string(&path).context("Failed to read debug config file")?;
+    serde_json::from_str(&contents).context("Failed to parse debug config JSON")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_messages_parse() {
+        let mut console = DebugConsole::default();
+        for _ in TEST_MESSAGES {
+            let incoming = console.test_message().unwrap();
+            assert_eq!(incoming.source.as_deref(), Some(INJECT_SOURCE));
+            console.select_next();
+        }
+        assert_eq!(console.selected, 0);
+    }
+
+    #[test]
+    fn test_log_keeps_newest() {
+        let mut console = DebugConsole::default();
+        let incoming = console.test_message().unwrap();
+        for _ in 0..LOG_CAPACITY + 5 {
+            console.record(&incoming, RuleAction::Allow);
+        }
+        assert_eq!(console.len(), LOG_CAPACITY);
+
+        console.scroll_older(LOG_CAPACITY * 2);
+        assert_eq!(console.scroll, LOG_CAPACITY - 1);
+        console.clear();
+        assert_eq!((console.len(), console.scroll), (0, 0));
+    }
+}
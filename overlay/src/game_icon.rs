@@ -0,0 +1,71 @@
+//! Box art for the running game
+//!
+//! The icon comes from kazeta-ra's image cache: `ra_game_start` carries the
+//! cached file when `kazeta-ra fetch-images` has run, otherwise the icon URL
+//! is looked up in the cache here. Textures need the GL context, so the file
+//! is only decoded from the render loop, and only while a screen that shows
+//! it is open (see `resources`).
+
+use crate::resources::Lazy;
+use macroquad::prelude::{Image, Texture2D};
+use std::fs;
+use std::path::PathBuf;
+use std::time::Instant;
+
+/// Cached file for an RA icon path or URL, if it has been downloaded
+pub fn cached_icon_path(icon_url: &str) -> Option<PathBuf> {
+    use kazeta_ra::cache::ImageCache;
+    ImageCache::new().ok()?.get(&ImageCache::icon_url(icon_url))
+}
+
+#[derive(Default)]
+pub struct GameIcon {
+    /// Changed with `set_path`, so the texture follows
+    pub path: Option<PathBuf>,
+    pub texture: Lazy<Texture2D>,
+    /// The file at `path` couldn't be decoded; don't retry every frame
+    failed: bool,
+}
+
+impl GameIcon {
+    /// Show a different icon, or none. The texture is loaded on the next
+    /// `load_pending`.
+    pub fn set_path(&mut self, path: Option<PathBuf>) {
+        if path != self.path {
+            self.path = path;
+            self.texture.unload();
+            self.failed = false;
+        }
+    }
+
+    /// Decode the icon if it hasn't been yet and mark it used. Call with a
+    /// GL context.
+    pub fn load_pending(&mut self, now: Instant) {
+        if self.failed {
+            return;
+        }
+        let Some(path) = &self.path else { return };
+
+        let failed = &mut self.failed;
+        self.texture.load_with(now, || {
+            let image = fs::read(path).map_err(|e| e.to_string()).and_then(|bytes| {
+                Image::from_file_with_format(&bytes, None).map_err(|e| e.to_string())
+            });
+            match image {
+                Ok(image) => {
+                    let bytes = image.bytes.len();
+                    Some((Texture2D::from_image(&image), bytes))
+                }
+                Err(e) => {
+                    println!("[GameIcon] Failed to load {:?}: {}", path, e);
+                    *failed = true;
+                    None
+                }
+            }
+        });
+    }
+
+    pub fn texture(&self) -> Option<&Texture2D> {
+        self.texture.get()
+    }
+}
@@ -0,0 +1,588 @@
+//! Gamepad tester widget, shared by the overlay and the BIOS
+//!
+//! `GamepadTester` follows one controller, either from gilrs events (the
+//! overlay, which owns its event queue) or by reading the pad's current state
+//! every frame (the BIOS, whose menu input consumes the events). `draw` renders
+//! the pad with macroquad and `report` summarises it for the logs.
+//!
+//! Drift is measured while a stick is left alone: once its reading has been
+//! still near the center for a moment, the distance from the center is how
+//! far it drifts. The worst of those is drawn as a ring around the center and
+//! flagged past `DRIFT_WARNING`.
+
+use gilrs::{Axis, Button, EventType, Gamepad};
+use macroquad::prelude::*;
+use std::collections::VecDeque;
+use std::time::Instant;
+
+/// Size of the area `draw` lays the pad out in
+pub const WIDTH: f32 = 620.0;
+pub const HEIGHT: f32 = 340.0;
+
+/// Rest offset past which a stick is reported as drifting
+pub const DRIFT_WARNING: f32 = 0.1;
+/// Readings further out than this are the stick being pushed
+const REST_ZONE: f32 = 0.3;
+/// Movement between frames below which the stick counts as still
+const STILL_DELTA: f32 = 0.02;
+/// Frames a stick has to be still before its reading counts as rest
+const STILL_FRAMES: u32 = 10;
+/// Positions kept for the trail behind each stick
+const TRAIL_LEN: usize = 30;
+
+/// Gamepad button state for tester
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GamepadButtonState {
+    // Face buttons
+    pub a: bool,
+    pub b: bool,
+    pub x: bool,
+    pub y: bool,
+    // D-Pad
+    pub dpad_up: bool,
+    pub dpad_down: bool,
+    pub dpad_left: bool,
+    pub dpad_right: bool,
+    // Shoulder buttons
+    pub lb: bool,
+    pub rb: bool,
+    pub lt: f32, // 0.0 - 1.0
+    pub rt: f32, // 0.0 - 1.0
+    // Sticks (pressed)
+    pub ls_press: bool,
+    pub rs_press: bool,
+    // Stick axes
+    pub left_stick_x: f32,  // -1.0 to 1.0
+    pub left_stick_y: f32,  // -1.0 to 1.0
+    pub right_stick_x: f32, // -1.0 to 1.0
+    pub right_stick_y: f32, // -1.0 to 1.0
+    // Special buttons
+    pub start: bool,
+    pub select: bool,
+    pub guide: bool,
+}
+
+impl GamepadButtonState {
+    fn button_mut(&mut self, button: Button) -> Option<&mut bool> {
+        Some(match button {
+            Button::South => &mut self.a,
+            Button::East => &mut self.b,
+            Button::West => &mut self.x,
+            Button::North => &mut self.y,
+            Button::DPadUp => &mut self.dpad_up,
+            Button::DPadDown => &mut self.dpad_down,
+            Button::DPadLeft => &mut self.dpad_left,
+            Button::DPadRight => &mut self.dpad_right,
+            Button::LeftTrigger => &mut self.lb,
+            Button::RightTrigger => &mut self.rb,
+            Button::LeftThumb => &mut self.ls_press,
+            Button::RightThumb => &mut self.rs_press,
+            Button::Start => &mut self.start,
+            Button::Select => &mut self.select,
+            Button::Mode => &mut self.guide,
+            _ => return None,
+        })
+    }
+
+    /// Names of the buttons being held, for the report
+    fn held(&self) -> Vec<&'static str> {
+        [
+            (self.a, "A"),
+            (self.b, "B"),
+            (self.x, "X"),
+            (self.y, "Y"),
+            (self.dpad_up, "Up"),
+            (self.dpad_down, "Down"),
+            (self.dpad_left, "Left"),
+            (self.dpad_right, "Right"),
+            (self.lb, "LB"),
+            (self.rb, "RB"),
+            (self.ls_press, "LS"),
+            (self.rs_press, "RS"),
+            (self.start, "Start"),
+            (self.select, "Select"),
+            (self.guide, "Guide"),
+        ]
+        .iter()
+        .filter(|(held, _)| *held)
+        .map(|(_, name)| *name)
+        .collect()
+    }
+}
+
+/// Rest position and recent trail of one stick
+#[derive(Debug, Clone, Default)]
+pub struct StickDrift {
+    last: (f32, f32),
+    still_frames: u32,
+    /// Largest distance from center seen at rest
+    pub rest_offset: f32,
+    /// Whether the stick has been seen at rest yet
+    pub measured: bool,
+    trail: VecDeque<(f32, f32)>,
+}
+
+impl StickDrift {
+    pub fn sample(&mut self, x: f32, y: f32) {
+        let moved = (x - self.last.0).hypot(y - self.last.1);
+        self.still_frames = if moved < STILL_DELTA {
+            self.still_frames + 1
+        } else {
+            0
+        };
+        self.last = (x, y);
+
+        let offset = x.hypot(y);
+        if self.still_frames >= STILL_FRAMES && offset < REST_ZONE {
+            self.rest_offset = self.rest_offset.max(offset);
+            self.measured = true;
+        }
+
+        if moved >= STILL_DELTA || self.trail.is_empty() {
+            self.trail.push_back((x, y));
+            if self.trail.len() > TRAIL_LEN {
+                self.trail.pop_front();
+            }
+        }
+    }
+
+    pub fn is_drifting(&self) -> bool {
+        self.rest_offset > DRIFT_WARNING
+    }
+
+    fn summary(&self) -> String {
+        if !self.measured {
+            "not measured (leave the stick alone for a moment)".to_string()
+        } else if self.is_drifting() {
+            format!("rest offset {:.3} - DRIFTING", self.rest_offset)
+        } else {
+            format!("rest offset {:.3} - ok", self.rest_offset)
+        }
+    }
+}
+
+/// Live state of the controller being tested
+#[derive(Debug, Clone, Default)]
+pub struct GamepadTester {
+    pub buttons: GamepadButtonState,
+    pub left_drift: StickDrift,
+    pub right_drift: StickDrift,
+    pub last_input: Option<Instant>,
+}
+
+impl GamepadTester {
+    /// Start over, e.g. when switching controllers
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Apply one gilrs event from the controller being tested
+    pub fn handle_event(&mut self, event: &EventType) {
+        let buttons = &mut self.buttons;
+        match *event {
+            EventType::ButtonPressed(button, _) | EventType::ButtonReleased(button, _) => {
+                let pressed = matches!(event, EventType::ButtonPressed(..));
+                match buttons.button_mut(button) {
+                    Some(state) => *state = pressed,
+                    None => return,
+                }
+            }
+            EventType::AxisChanged(axis, value, _) => match axis {
+                // Convert -1..1 to 0..1
+                Axis::LeftZ => buttons.lt = (value + 1.0) / 2.0,
+                Axis::RightZ => buttons.rt = (value + 1.0) / 2.0,
+                Axis::LeftStickX => buttons.left_stick_x = value,
+                Axis::LeftStickY => buttons.left_stick_y = value,
+                Axis::RightStickX => buttons.right_stick_x = value,
+                Axis::RightStickY => buttons.right_stick_y = value,
+                _ => return,
+            },
+            _ => return,
+        }
+        self.last_input = Some(Instant::now());
+    }
+
+    /// Read the controller's current state, for callers that don't see its events
+    pub fn update_from_gamepad(&mut self, gamepad: &Gamepad) {
+        let mut buttons = GamepadButtonState {
+            left_stick_x: gamepad.value(Axis::LeftStickX),
+            left_stick_y: gamepad.value(Axis::LeftStickY),
+            right_stick_x: gamepad.value(Axis::RightStickX),
+            right_stick_y: gamepad.value(Axis::RightStickY),
+            ..Default::default()
+        };
+        let trigger = |button| {
+            gamepad
+                .button_data(button)
+                .map(|d| d.value())
+                .unwrap_or(0.0)
+        };
+        buttons.lt = trigger(Button::LeftTrigger2);
+        buttons.rt = trigger(Button::RightTrigger2);
+        for button in [
+            Button::South,
+            Button::East,
+            Button::West,
+            Button::North,
+            Button::DPadUp,
+            Button::DPadDown,
+            Button::DPadLeft,
+            Button::DPadRight,
+            Button::LeftTrigger,
+            Button::RightTrigger,
+            Button::LeftThumb,
+            Button::RightThumb,
+            Button::Start,
+            Button::Select,
+            Button::Mode,
+        ] {
+            if let Some(state) = buttons.button_mut(button) {
+                *state = gamepad.is_pressed(button);
+            }
+        }
+
+        if buttons != self.buttons {
+            self.last_input = Some(Instant::now());
+        }
+        self.buttons = buttons;
+    }
+
+    /// Feed the drift measurement; call once per frame
+    pub fn sample(&mut self) {
+        let b = &self.buttons;
+        self.left_drift.sample(b.left_stick_x, b.left_stick_y);
+        self.right_drift.sample(b.right_stick_x, b.right_stick_y);
+    }
+
+    /// Whether input arrived in the last two seconds
+    pub fn recently_active(&self) -> bool {
+        self.last_input.is_some_and(|t| t.elapsed().as_secs() < 2)
+    }
+
+    /// Plain-text summary of the controller, one line per item
+    pub fn report(&self, name: &str) -> String {
+        let b = &self.buttons;
+        let held = b.held();
+        [
+            format!("Gamepad report: {}", name),
+            format!(
+                "Buttons held: {}",
+                if held.is_empty() {
+                    "none".to_string()
+                } else {
+                    held.join(", ")
+                }
+            ),
+            format!(
+                "Left stick: x={:.3} y={:.3}, {}",
+                b.left_stick_x,
+                b.left_stick_y,
+                self.left_drift.summary()
+            ),
+            format!(
+                "Right stick: x={:.3} y={:.3}, {}",
+                b.right_stick_x,
+                b.right_stick_y,
+                self.right_drift.summary()
+            ),
+            format!("Triggers: LT {:.2}, RT {:.2}", b.lt, b.rt),
+        ]
+        .join("\n")
+    }
+
+    /// Draw the pad in a `WIDTH` x `HEIGHT` area at (x, y), scaled by `scale`.
+    /// The caller draws the panel, title and hints.
+    pub fn draw(&self, x: f32, y: f32, scale: f32) {
+        let btn = &self.buttons;
+        let s = |v: f32| v * scale;
+        let width = s(WIDTH);
+
+        // Layout constants
+        let left_x = x + s(80.0);
+        let right_x = x + s(420.0);
+        let center_y = y + s(170.0);
+        let btn_size = s(28.0);
+
+        // === Left side: D-Pad and Left Stick ===
+
+        // D-Pad
+        let dpad_x = left_x;
+        let dpad_y = center_y - s(40.0);
+
+        draw_text("D-PAD", dpad_x - s(10.0), dpad_y - s(45.0), s(14.0), GRAY);
+        draw_button(dpad_x, dpad_y - btn_size, btn_size, btn.dpad_up, "▲");
+        draw_button(dpad_x, dpad_y + btn_size, btn_size, btn.dpad_down, "▼");
+        draw_button(dpad_x - btn_size, dpad_y, btn_size, btn.dpad_left, "◄");
+        draw_button(dpad_x + btn_size, dpad_y, btn_size, btn.dpad_right, "►");
+
+        // Left Stick
+        let ls_x = left_x + s(100.0);
+        let ls_y = center_y + s(40.0);
+        draw_text("L STICK", ls_x - s(15.0), ls_y - s(50.0), s(14.0), GRAY);
+        draw_stick(
+            ls_x,
+            ls_y,
+            s(35.0),
+            btn.left_stick_x,
+            btn.left_stick_y,
+            btn.ls_press,
+            &self.left_drift,
+        );
+
+        // === Center: Triggers and special buttons ===
+
+        let center_x = x + width / 2.0;
+
+        // Triggers at top
+        let trigger_y = y + s(70.0);
+        draw_text("LT", center_x - s(100.0), trigger_y, s(14.0), GRAY);
+        draw_trigger(
+            center_x - s(80.0),
+            trigger_y + s(5.0),
+            s(60.0),
+            s(15.0),
+            btn.lt,
+        );
+        draw_text("RT", center_x + s(50.0), trigger_y, s(14.0), GRAY);
+        draw_trigger(
+            center_x + s(20.0),
+            trigger_y + s(5.0),
+            s(60.0),
+            s(15.0),
+            btn.rt,
+        );
+
+        // Bumpers
+        let bumper_y = trigger_y + s(30.0);
+        draw_button(center_x - s(80.0), bumper_y, s(30.0), btn.lb, "LB");
+        draw_button(center_x + s(50.0), bumper_y, s(30.0), btn.rb, "RB");
+
+        // Select/Start/Guide
+        let special_y = center_y;
+        draw_button(center_x - s(60.0), special_y, s(25.0), btn.select, "⊡");
+        draw_button(center_x, special_y - s(20.0), s(30.0), btn.guide, "⬡");
+        draw_button(center_x + s(35.0), special_y, s(25.0), btn.start, "≡");
+        draw_text(
+            "SEL",
+            center_x - s(65.0),
+            special_y + s(35.0),
+            s(10.0),
+            DARKGRAY,
+        );
+        draw_text(
+            "GUIDE",
+            center_x - s(15.0),
+            special_y + s(15.0),
+            s(10.0),
+            DARKGRAY,
+        );
+        draw_text(
+            "START",
+            center_x + s(25.0),
+            special_y + s(35.0),
+            s(10.0),
+            DARKGRAY,
+        );
+
+        // === Right side: Face buttons and Right Stick ===
+
+        // Face buttons (ABXY)
+        let face_x = right_x;
+        let face_y = center_y - s(40.0);
+
+        draw_text("BUTTONS", face_x - s(20.0), face_y - s(45.0), s(14.0), GRAY);
+        draw_button_colored(face_x, face_y + btn_size, btn_size, btn.a, "A", GREEN);
+        draw_button_colored(face_x + btn_size, face_y, btn_size, btn.b, "B", RED);
+        draw_button_colored(face_x - btn_size, face_y, btn_size, btn.x, "X", BLUE);
+        draw_button_colored(face_x, face_y - btn_size, btn_size, btn.y, "Y", YELLOW);
+
+        // Right Stick
+        let rs_x = right_x - s(100.0);
+        let rs_y = center_y + s(40.0);
+        draw_text("R STICK", rs_x - s(15.0), rs_y - s(50.0), s(14.0), GRAY);
+        draw_stick(
+            rs_x,
+            rs_y,
+            s(35.0),
+            btn.right_stick_x,
+            btn.right_stick_y,
+            btn.rs_press,
+            &self.right_drift,
+        );
+    }
+}
+
+fn draw_button(x: f32, y: f32, size: f32, pressed: bool, label: &str) {
+    let color = if pressed {
+        GREEN
+    } else {
+        Color::new(0.3, 0.3, 0.3, 1.0)
+    };
+    let border = if pressed { WHITE } else { GRAY };
+
+    draw_rectangle(x - size / 2.0, y - size / 2.0, size, size, color);
+    draw_rectangle_lines(x - size / 2.0, y - size / 2.0, size, size, 2.0, border);
+
+    let label_size = if label.len() > 1 {
+        size * 0.43
+    } else {
+        size * 0.57
+    };
+    let dims = measure_text(label, None, label_size as u16, 1.0);
+    draw_text(
+        label,
+        x - dims.width / 2.0,
+        y + dims.height / 4.0,
+        label_size,
+        WHITE,
+    );
+}
+
+fn draw_button_colored(x: f32, y: f32, size: f32, pressed: bool, label: &str, color: Color) {
+    let bg_color = if pressed {
+        color
+    } else {
+        Color::new(0.2, 0.2, 0.2, 1.0)
+    };
+    let border = if pressed {
+        WHITE
+    } else {
+        Color::new(color.r * 0.5, color.g * 0.5, color.b * 0.5, 1.0)
+    };
+
+    draw_circle(x, y, size / 2.0, bg_color);
+    draw_circle_lines(x, y, size / 2.0, 2.0, border);
+
+    let label_size = size / 2.0;
+    let dims = measure_text(label, None, label_size as u16, 1.0);
+    draw_text(
+        label,
+        x - dims.width / 2.0,
+        y + dims.height / 4.0,
+        label_size,
+        WHITE,
+    );
+}
+
+fn draw_stick(
+    x: f32,
+    y: f32,
+    radius: f32,
+    stick_x: f32,
+    stick_y: f32,
+    pressed: bool,
+    drift: &StickDrift,
+) {
+    // Outer circle (deadzone area)
+    draw_circle_lines(x, y, radius, 2.0, GRAY);
+
+    let stick_radius = radius * 0.4;
+    let travel = radius - stick_radius;
+
+    // Where the stick has been, fading out
+    let trail_len = drift.trail.len() as f32;
+    for (i, (tx, ty)) in drift.trail.iter().enumerate() {
+        let alpha = (i as f32 + 1.0) / trail_len * 0.5;
+        draw_circle(
+            x + tx * travel,
+            y - ty * travel,
+            2.0,
+            Color::new(0.4, 0.8, 1.0, alpha),
+        );
+    }
+
+    // Resting offset, red once it's drift
+    if drift.measured {
+        let color = if drift.is_drifting() { RED } else { ORANGE };
+        draw_circle_lines(x, y, (drift.rest_offset * travel).max(1.0), 1.0, color);
+        let label = format!("DRIFT {:.2}", drift.rest_offset);
+        let dims = measure_text(&label, None, (radius * 0.3) as u16, 1.0);
+        draw_text(
+            &label,
+            x - dims.width / 2.0,
+            y + radius + radius * 0.45,
+            radius * 0.3,
+            color,
+        );
+    }
+
+    // Stick position
+    let pos_x = x + stick_x * travel;
+    let pos_y = y - stick_y * travel; // Invert Y for display
+
+    let stick_color = if pressed {
+        GREEN
+    } else {
+        Color::new(0.6, 0.6, 0.6, 1.0)
+    };
+    draw_circle(pos_x, pos_y, stick_radius, stick_color);
+    draw_circle_lines(pos_x, pos_y, stick_radius, 2.0, WHITE);
+}
+
+/// Horizontal 0..1 fill bar, also used by the overlay's controller settings
+pub fn draw_trigger(x: f32, y: f32, width: f32, height: f32, value: f32) {
+    // Background
+    draw_rectangle(x, y, width, height, Color::new(0.2, 0.2, 0.2, 1.0));
+    // Fill
+    let fill_color = if value > 0.1 {
+        GREEN
+    } else {
+        Color::new(0.3, 0.3, 0.3, 1.0)
+    };
+    draw_rectangle(x, y, width * value, height, fill_color);
+    // Border
+    draw_rectangle_lines(x, y, width, height, 1.0, GRAY);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_drift_measured_only_at_rest() {
+        let mut drift = StickDrift::default();
+
+        // Sweeping through the center doesn't count
+        for i in 0..20 {
+            let x = -1.0 + i as f32 * 0.1;
+            drift.sample(x, 0.0);
+        }
+        assert!(!drift.measured);
+
+        // Left alone slightly off center
+        for _ in 0..=STILL_FRAMES {
+            drift.sample(0.05, 0.12);
+        }
+        assert!(drift.measured);
+        assert!((drift.rest_offset - 0.13).abs() < 0.001);
+        assert!(drift.is_drifting());
+
+        // Held far out isn't rest
+        let mut held = StickDrift::default();
+        for _ in 0..=STILL_FRAMES {
+            held.sample(1.0, 0.0);
+        }
+        assert!(!held.measured);
+        assert!(held.trail.len() <= TRAIL_LEN);
+    }
+
+    #[test]
+    fn test_report_lists_state() {
+        let mut tester = GamepadTester::default();
+        tester.buttons.a = true;
+        tester.buttons.lb = true;
+        tester.buttons.rt = 0.5;
+        for _ in 0..=STILL_FRAMES {
+            tester.sample();
+        }
+
+        let report = tester.report("Xbox Controller");
+        assert!(report.starts_with("Gamepad report: Xbox Controller\n"));
+        assert!(report.contains("Buttons held: A, LB"));
+        assert!(report.contains("Left stick: x=0.000 y=0.000, rest offset 0.000 - ok"));
+        assert!(report.contains("RT 0.50"));
+
+        tester.reset();
+        assert!(tester.report("Pad").contains("Buttons held: none"));
+    }
+}
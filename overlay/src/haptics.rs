@@ -0,0 +1,279 @@
+//! Rumble feedback for menu navigation and achievement unlocks
+//!
+//! The overlay state queues haptic patterns; the main loop hands them to the
+//! input monitor, which owns the gilrs instance the input came from and so
+//! knows which gamepad navigated. Navigation ticks only go to that gamepad;
+//! unlock pulses go to player 1's controller, or every gamepad when no
+//! players are assigned.
+
+use anyhow::{Context, Result};
+use gilrs::ff::{BaseEffect, BaseEffectType, Effect, EffectBuilder, Repeat, Replay, Ticks};
+use gilrs::{GamepadId, Gilrs};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+/// Selectable rumble intensities (percent)
+pub const INTENSITY_STEPS: &[u8] = &[25, 50, 75, 100];
+
+/// Haptics configuration
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct HapticsConfig {
+    pub enabled: bool,
+    /// Rumble strength in percent, one of INTENSITY_STEPS
+    pub intensity: u8,
+    pub version: u32,
+}
+
+impl Default for HapticsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            intensity: 50,
+            version: 1,
+        }
+    }
+}
+
+/// Feedback patterns
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HapticPattern {
+    /// Light tick when the cursor moves
+    Tick,
+    /// Strong double pulse when an achievement unlocks
+    Unlock,
+}
+
+/// Motor, strength and timing of a pattern
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pulse {
+    pub strong: bool,
+    pub magnitude: u16,
+    /// How long each pulse lasts
+    pub on_ms: u32,
+    /// Gap between pulses
+    pub off_ms: u32,
+    pub count: u32,
+}
+
+impl Pulse {
+    /// Time from the start of the first pulse to the end of the last
+    pub fn total_ms(&self) -> u32 {
+        self.on_ms * self.count + self.off_ms * self.count.saturating_sub(1)
+    }
+}
+
+impl HapticPattern {
+    /// The pulse for this pattern at the given intensity (percent)
+    pub fn pulse(&self, intensity: u8) -> Pulse {
+        let (strong, base, on_ms, off_ms, count) = match self {
+            HapticPattern::Tick => (false, 0.6, 35, 0, 1),
+            HapticPattern::Unlock => (true, 1.0, 140, 90, 2),
+        };
+        let scale = f32::from(intensity.min(100)) / 100.0;
+        Pulse {
+            strong,
+            magnitude: (f32::from(u16::MAX) * base * scale) as u16,
+            on_ms,
+            off_ms,
+            count,
+        }
+    }
+}
+
+/// Steps through INTENSITY_STEPS, wrapping around
+pub fn next_intensity(current: u8) -> u8 {
+    let index = INTENSITY_STEPS
+        .iter()
+        .position(|&s| s == current)
+        .unwrap_or(0);
+    INTENSITY_STEPS[(index + 1) % INTENSITY_STEPS.len()]
+}
+
+/// Haptics settings and the patterns waiting to be played
+pub struct Haptics {
+    config: HapticsConfig,
+    config_path: PathBuf,
+    pending: Vec<HapticPattern>,
+}
+
+impl Haptics {
+    /// Create new Haptics with config loaded from disk
+    pub fn new() -> Result<Self> {
+        let config_path = Self::get_config_path()?;
+
+        let config = if config_path.exists() {
+            println!("[Haptics] Loading config from {:?}", config_path);
+            Self::load_config(&config_path)?
+        } else {
+            let default_config = HapticsConfig::default();
+            if let Err(e) = Self::save_config(&config_path, &default_config) {
+                eprintln!("[Haptics] Failed to save default config: {}", e);
+            }
+            default_config
+        };
+
+        Ok(Self {
+            config,
+            config_path,
+            pending: Vec::new(),
+        })
+    }
+
+    /// Get the configuration file path
+    fn get_config_path() -> Result<PathBuf> {
+        let data_dir =
+            dirs::data_local_dir().context("Could not determine local data directory")?;
+
+        let overlay_dir = data_dir.join("kazeta-plus").join("overlay");
+        if !overlay_dir.exists() {
+            fs::create_dir_all(&overlay_dir)
+                .context("Failed to create overlay config directory")?;
+        }
+
+        Ok(overlay_dir.join("haptics.json"))
+    }
+
+    fn load_config(path: &PathBuf) -> Result<HapticsConfig> {
+        let contents = fs::read_to_string(path).context("Failed to read haptics config file")?;
+        serde_json::from_str(&contents).context("Failed to parse haptics config JSON")
+    }
+
+    fn save_config(path: &PathBuf, config: &HapticsConfig) -> Result<()> {
+        let json =
+            serde_json::to_string_pretty(config).context("Failed to serialize haptics config")?;
+        fs::write(path, json).context("Failed to write haptics config file")?;
+        Ok(())
+    }
+
+    /// Save current configuration to disk
+    pub fn save(&self) -> Result<()> {
+        Self::save_config(&self.config_path, &self.config)
+    }
+
+    pub fn config(&self) -> &HapticsConfig {
+        &self.config
+    }
+
+    pub fn config_mut(&mut self) -> &mut HapticsConfig {
+        &mut self.config
+    }
+
+    /// Queue a pattern, unless haptics are off
+    pub fn request(&mut self, pattern: HapticPattern) {
+        if self.config.enabled && !self.pending.contains(&pattern) {
+            self.pending.push(pattern);
+        }
+    }
+
+    /// Patterns queued since the last call
+    pub fn take_requests(&mut self) -> Vec<HapticPattern> {
+        std::mem::take(&mut self.pending)
+    }
+}
+
+/// Plays rumble effects and keeps them alive until they finish
+/// (gilrs stops an effect as soon as it is dropped)
+pub struct Rumbler {
+    playing: Vec<(Effect, Instant)>,
+}
+
+impl Rumbler {
+    pub fn new() -> Self {
+        Self {
+            playing: Vec::new(),
+        }
+    }
+
+    pub fn play(
+        &mut self,
+        gilrs: &mut Gilrs,
+        gamepads: &[GamepadId],
+        pattern: HapticPattern,
+        intensity: u8,
+    ) {
+        self.playing.retain(|(_, until)| Instant::now() < *until);
+
+        let gamepads: Vec<GamepadId> = gamepads
+            .iter()
+            .copied()
+            .filter(|&id| {
+                gilrs
+                    .connected_gamepad(id)
+                    .is_some_and(|g| g.is_ff_supported())
+            })
+            .collect();
+        if gamepads.is_empty() {
+            return;
+        }
+
+        let pulse = pattern.pulse(intensity);
+        let kind = if pulse.strong {
+            BaseEffectType::Strong {
+                magnitude: pulse.magnitude,
+            }
+        } else {
+            BaseEffectType::Weak {
+                magnitude: pulse.magnitude,
+            }
+        };
+
+        let effect = EffectBuilder::new()
+            .add_effect(BaseEffect {
+                kind,
+                scheduling: Replay {
+                    play_for: Ticks::from_ms(pulse.on_ms),
+                    with_delay: Ticks::from_ms(pulse.off_ms),
+                    ..Default::default()
+                },
+                envelope: Default::default(),
+            })
+            .repeat(Repeat::For(Ticks::from_ms(pulse.total_ms())))
+            .gamepads(&gamepads)
+            .finish(gilrs);
+
+        match effect.and_then(|effect| effect.play().map(|_| effect)) {
+            Ok(effect) => {
+                let until =
+                    Instant::now() + Duration::from_millis(u64::from(pulse.total_ms()) + 50);
+                self.playing.push((effect, until));
+            }
+            Err(e) => eprintln!("[Haptics] Failed to play {:?}: {}", pattern, e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unlock_is_a_strong_double_pulse() {
+        let unlock = HapticPattern::Unlock.pulse(100);
+        assert!(unlock.strong);
+        assert_eq!(unlock.count, 2);
+        assert_eq!(unlock.magnitude, u16::MAX);
+        assert_eq!(unlock.total_ms(), 140 * 2 + 90);
+
+        let tick = HapticPattern::Tick.pulse(100);
+        assert!(!tick.strong);
+        assert_eq!(tick.total_ms(), tick.on_ms);
+        assert!(tick.magnitude < unlock.magnitude);
+    }
+
+    #[test]
+    fn test_intensity_scales_magnitude() {
+        let half = HapticPattern::Unlock.pulse(50).magnitude;
+        assert!((half as i32 - (u16::MAX / 2) as i32).abs() <= 1);
+        assert_eq!(HapticPattern::Unlock.pulse(0).magnitude, 0);
+        assert_eq!(HapticPattern::Unlock.pulse(250).magnitude, u16::MAX);
+    }
+
+    #[test]
+    fn test_next_intensity_wraps() {
+        assert_eq!(next_intensity(25), 50);
+        assert_eq!(next_intensity(100), 25);
+        assert_eq!(next_intensity(33), 50);
+    }
+}
@@ -0,0 +1,361 @@
+//! Community hints for achievements
+//!
+//! Fetches the RetroAchievements comments posted on an achievement in a
+//! background thread, keeps the first helpful ones and caches them under
+//! `overlay/hints/<achievement_id>.json` so the hints view also works offline.
+
+use anyhow::{Context, Result};
+use kazeta_ra::types::AchievementComment;
+use kazeta_ra::{CredentialManager, RAClient};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Maximum number of hints kept per achievement
+pub const MAX_HINTS: usize = 10;
+
+/// Number of comments requested from RA (many are not hints)
+const FETCH_COUNT: u32 = 50;
+
+/// Cached hints older than this are refreshed when online
+const CACHE_MAX_AGE_SECS: u64 = 7 * 24 * 60 * 60;
+
+/// Comments shorter than this are rarely useful ("thanks!", "gg")
+const MIN_HINT_CHARS: usize = 20;
+
+/// RA posts automated comments (edits, resets) under this user
+const SYSTEM_USER: &str = "Server";
+
+/// A single community hint
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Hint {
+    pub author: String,
+    pub submitted: String,
+    pub text: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct CachedHints {
+    achievement_id: u32,
+    fetched_at: u64,
+    hints: Vec<Hint>,
+    version: u32,
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Keep the first comments that look like actual hints
+pub fn select_helpful(comments: Vec<AchievementComment>) -> Vec<Hint> {
+    comments
+        .into_iter()
+        .filter(|c| c.user != SYSTEM_USER)
+        .map(|c| Hint {
+            author: c.user,
+            submitted: c.submitted,
+            text: c.text.trim().to_string(),
+        })
+        .filter(|h| h.text.chars().count() >= MIN_HINT_CHARS)
+        .take(MAX_HINTS)
+        .collect()
+}
+
+/// Link to the achievement page, where the full discussion and guides live
+pub fn guide_url(achievement_id: u32) -> String {
+    format!(
+        "https://retroachievements.org/achievement/{}",
+        achievement_id
+    )
+}
+
+/// On-disk hint cache keyed by achievement id
+#[derive(Clone)]
+pub struct HintCache {
+    dir: PathBuf,
+}
+
+impl HintCache {
+    pub fn new() -> Result<Self> {
+        let data_dir =
+            dirs::data_local_dir().context("Could not determine local data directory")?;
+
+        Ok(Self::with_dir(
+            data_dir.join("kazeta-plus").join("overlay").join("hints"),
+        ))
+    }
+
+    pub fn with_dir(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    fn path(&self, achievement_id: u32) -> PathBuf {
+        self.dir.join(format!("{}.json", achievement_id))
+    }
+
+    fn load(&self, achievement_id: u32) -> Option<CachedHints> {
+        let content = fs::read_to_string(self.path(achievement_id)).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    fn store(&self, achievement_id: u32, hints: &[Hint]) -> Result<()> {
+        fs::create_dir_all(&self.dir).context("Failed to create hints cache directory")?;
+
+        let cached = CachedHints {
+            achievement_id,
+            fetched_at: now_secs(),
+            hints: hints.to_vec(),
+            version: 1,
+        };
+        let json = serde_json::to_string_pretty(&cached).context("Failed to serialize hints")?;
+        fs::write(self.path(achievement_id), json).context("Failed to write hints cache")?;
+
+        Ok(())
+    }
+}
+
+fn fetch_hints(achievement_id: u32) -> Result<Vec<Hint>> {
+    let credentials = CredentialManager::new()?
+        .load()?
+        .context("Not logged in to RetroAchievements")?;
+
+    let client = RAClient::new(credentials);
+    let comments = client.get_achievement_comments(achievement_id, FETCH_COUNT)?;
+
+    Ok(select_helpful(comments))
+}
+
+/// State of the hints sub-view for one achievement
+pub struct HintsView {
+    pub achievement_id: u32,
+    pub achievement_title: String,
+    pub hints: Vec<Hint>,
+    pub selected: usize,
+    pub loading: bool,
+    pub error: Option<String>,
+    /// Set when the shown hints came from the cache (unix seconds of the fetch)
+    pub cached_at: Option<u64>,
+    cache: Option<HintCache>,
+    receiver: Option<Receiver<Result<Vec<Hint>, String>>>,
+}
+
+impl HintsView {
+    /// Open the view, showing cached hints immediately and refreshing in the
+    /// background if the cache is missing or stale
+    pub fn open(achievement_id: u32, achievement_title: String) -> Self {
+        let cache = match HintCache::new() {
+            Ok(cache) => Some(cache),
+            Err(e) => {
+                eprintln!("[Hints] Cache unavailable: {}", e);
+                None
+            }
+        };
+        Self::open_with_cache(achievement_id, achievement_title, cache, true)
+    }
+
+    fn open_with_cache(
+        achievement_id: u32,
+        achievement_title: String,
+        cache: Option<HintCache>,
+        allow_fetch: bool,
+    ) -> Self {
+        let cached = cache.as_ref().and_then(|c| c.load(achievement_id));
+        let stale = cached
+            .as_ref()
+            .is_none_or(|c| now_secs().saturating_sub(c.fetched_at) > CACHE_MAX_AGE_SECS);
+
+        let mut view = Self {
+            achievement_id,
+            achievement_title,
+            hints: Vec::new(),
+            selected: 0,
+            loading: false,
+            error: None,
+            cached_at: None,
+            cache,
+            receiver: None,
+        };
+
+        if let Some(cached) = cached {
+            println!(
+                "[Hints] Loaded {} cached hints for achievement {}",
+                cached.hints.len(),
+                achievement_id
+            );
+            view.hints = cached.hints;
+            view.cached_at = Some(cached.fetched_at);
+        }
+
+        if stale && allow_fetch {
+            view.start_fetch();
+        }
+
+        view
+    }
+
+    fn start_fetch(&mut self) {
+        let (tx, rx) = mpsc::channel();
+        let achievement_id = self.achievement_id;
+
+        thread::spawn(move || {
+            let result = fetch_hints(achievement_id).map_err(|e| e.to_string());
+            let _ = tx.send(result);
+            crate::wake::wake();
+        });
+
+        self.receiver = Some(rx);
+        self.loading = true;
+    }
+
+    /// Pick up the result of a background fetch, if one finished
+    pub fn poll(&mut self) {
+        let Some(receiver) = &self.receiver else {
+            return;
+        };
+
+        let result = match receiver.try_recv() {
+            Ok(result) => result,
+            Err(TryRecvError::Empty) => return,
+            Err(TryRecvError::Disconnected) => Err("Hint fetch stopped unexpectedly".to_string()),
+        };
+        self.receiver = None;
+        self.loading = false;
+
+        match result {
+            Ok(hints) => {
+                println!(
+                    "[Hints] Fetched {} hints for achievement {}",
+                    hints.len(),
+                    self.achievement_id
+                );
+                if let Some(cache) = &self.cache {
+                    if let Err(e) = cache.store(self.achievement_id, &hints) {
+                        eprintln!("[Hints] Failed to cache hints: {}", e);
+                    }
+                }
+                self.hints = hints;
+                self.selected = self.selected.min(self.hints.len().saturating_sub(1));
+                self.cached_at = None;
+                self.error = None;
+            }
+            Err(e) => {
+                // Keep showing whatever was cached
+                eprintln!("[Hints] Fetch failed: {}", e);
+                self.error = Some(e);
+            }
+        }
+    }
+
+    pub fn next(&mut self) {
+        if self.selected + 1 < self.hints.len() {
+            self.selected += 1;
+        }
+    }
+
+    pub fn prev(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn current(&self) -> Option<&Hint> {
+        self.hints.get(self.selected)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn comment(user: &str, text: &str) -> AchievementComment {
+        AchievementComment {
+            user: user.to_string(),
+            submitted: "2024-01-01 00:00:00".to_string(),
+            text: text.to_string(),
+        }
+    }
+
+    fn temp_cache(name: &str) -> HintCache {
+        let dir =
+            std::env::temp_dir().join(format!("kazeta-hints-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        HintCache::with_dir(dir)
+    }
+
+    #[test]
+    fn test_select_helpful_filters_noise() {
+        let comments = vec![
+            comment(
+                "Server",
+                "Achievement description edited by someone long ago",
+            ),
+            comment("player1", "gg"),
+            comment(
+                "player2",
+                "  Use the hidden warp behind the waterfall in world 2.  ",
+            ),
+        ];
+
+        let hints = select_helpful(comments);
+        assert_eq!(hints.len(), 1);
+        assert_eq!(hints[0].author, "player2");
+        assert_eq!(
+            hints[0].text,
+            "Use the hidden warp behind the waterfall in world 2."
+        );
+    }
+
+    #[test]
+    fn test_select_helpful_caps_count() {
+        let comments = (0..30)
+            .map(|i| comment("player", &format!("Hint number {} with enough detail", i)))
+            .collect();
+        assert_eq!(select_helpful(comments).len(), MAX_HINTS);
+    }
+
+    #[test]
+    fn test_cached_hints_used_offline() {
+        let cache = temp_cache("offline");
+        let hints = vec![Hint {
+            author: "player".to_string(),
+            submitted: String::new(),
+            text: "Grab the key before the boss fight".to_string(),
+        }];
+        cache.store(42, &hints).unwrap();
+
+        let view = HintsView::open_with_cache(42, "Test".to_string(), Some(cache.clone()), false);
+        assert_eq!(view.hints, hints);
+        assert!(view.cached_at.is_some());
+        assert!(!view.loading);
+
+        let _ = fs::remove_dir_all(&cache.dir);
+    }
+
+    #[test]
+    fn test_navigation_is_clamped() {
+        let mut view = HintsView::open_with_cache(1, "Test".to_string(), None, false);
+        view.hints = vec![
+            Hint {
+                author: "a".to_string(),
+                submitted: String::new(),
+                text: "first".to_string(),
+            },
+            Hint {
+                author: "b".to_string(),
+                submitted: String::new(),
+                text: "second".to_string(),
+            },
+        ];
+
+        view.prev();
+        assert_eq!(view.selected, 0);
+        view.next();
+        view.next();
+        assert_eq!(view.selected, 1);
+        assert_eq!(view.current().unwrap().author, "b");
+    }
+}
@@ -1,6 +1,7 @@
 use anyhow::{Context, Result};
 use macroquad::prelude::KeyCode;
 use serde::{Deserialize, Serialize};
+use std::collections::hash_map::Entry;
 use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
@@ -13,6 +14,11 @@ pub enum HotkeyAction {
     QuickSave,
     QuickLoad,
     Screenshot,
+    SpeedrunStartPause,
+    SpeedrunSplit,
+    SpeedrunReset,
+    MusicPlayPause,
+    MusicNext,
 }
 
 impl HotkeyAction {
@@ -23,6 +29,11 @@ impl HotkeyAction {
             Self::QuickSave => "Quick Save",
             Self::QuickLoad => "Quick Load",
             Self::Screenshot => "Take Screenshot",
+            Self::SpeedrunStartPause => "Start/Pause Speedrun Timer",
+            Self::SpeedrunSplit => "Speedrun Split",
+            Self::SpeedrunReset => "Reset Speedrun Timer",
+            Self::MusicPlayPause => "Play/Pause Music",
+            Self::MusicNext => "Next Music Track",
         }
     }
 
@@ -33,6 +44,11 @@ impl HotkeyAction {
             Self::QuickSave,
             Self::QuickLoad,
             Self::Screenshot,
+            Self::SpeedrunStartPause,
+            Self::SpeedrunSplit,
+            Self::SpeedrunReset,
+            Self::MusicPlayPause,
+            Self::MusicNext,
         ]
     }
 }
@@ -40,9 +56,9 @@ impl HotkeyAction {
 /// Individual input components that can be combined into hotkeys
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum InputComponent {
-    Key(String),                    // Keyboard key name (e.g., "F12", "A")
+    Key(String),                      // Keyboard key name (e.g., "F12", "A")
     GamepadButton(GamepadButtonType), // Gamepad button
-    Modifier(ModifierKey),          // Modifier keys (Ctrl, Alt, Shift)
+    Modifier(ModifierKey),            // Modifier keys (Ctrl, Alt, Shift)
 }
 
 impl InputComponent {
@@ -68,10 +84,10 @@ impl InputComponent {
 /// Gamepad button types
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum GamepadButtonType {
-    South,       // A/Cross
-    East,        // B/Circle
-    West,        // X/Square
-    North,       // Y/Triangle
+    South, // A/Cross
+    East,  // B/Circle
+    West,  // X/Square
+    North, // Y/Triangle
     DPadUp,
     DPadDown,
     DPadLeft,
@@ -82,9 +98,9 @@ pub enum GamepadButtonType {
     RightTrigger,
     Select,
     Start,
-    Mode,        // Guide/Home
-    LeftStick,   // L3
-    RightStick,  // R3
+    Mode,       // Guide/Home
+    LeftStick,  // L3
+    RightStick, // R3
 }
 
 /// Modifier keys
@@ -194,11 +210,44 @@ impl HotkeyConfig {
             )],
         );
 
+        // Speedrun timer: F6 start/pause, F7 split, F8 reset
+        for (action, key) in [
+            (HotkeyAction::SpeedrunStartPause, "F6"),
+            (HotkeyAction::SpeedrunSplit, "F7"),
+            (HotkeyAction::SpeedrunReset, "F8"),
+            // Jukebox: F4 play/pause, F2 next track
+            (HotkeyAction::MusicPlayPause, "F4"),
+            (HotkeyAction::MusicNext, "F2"),
+        ] {
+            bindings.insert(
+                action,
+                vec![HotkeyBinding::new(
+                    vec![InputComponent::Key(key.to_string())],
+                    format!("{} key", key),
+                )],
+            );
+        }
+
         Self {
             bindings,
             version: 1,
         }
     }
+
+    /// Give actions added since the config was saved their default bindings
+    pub fn add_missing_defaults(&mut self) -> bool {
+        let defaults = Self::default_config();
+        let mut added = false;
+        for action in HotkeyAction::all() {
+            if let Entry::Vacant(entry) = self.bindings.entry(action) {
+                if let Some(bindings) = defaults.bindings.get(&action) {
+                    entry.insert(bindings.clone());
+                    added = true;
+                }
+            }
+        }
+        added
+    }
 }
 
 impl Default for HotkeyConfig {
@@ -222,7 +271,13 @@ impl HotkeyManager {
 
         let config = if config_path.exists() {
             println!("[Hotkeys] Loading config from {:?}", config_path);
-            Self::load_config(&config_path)?
+            let mut config = Self::load_config(&config_path)?;
+            if config.add_missing_defaults() {
+                if let Err(e) = Self::save_config(&config_path, &config) {
+                    eprintln!("[Hotkeys] Failed to save new default bindings: {}", e);
+                }
+            }
+            config
         } else {
             println!("[Hotkeys] No config found, using defaults");
             let default_config = HotkeyConfig::default_config();
@@ -244,8 +299,8 @@ impl HotkeyManager {
 
     /// Get the configuration file path
     fn get_config_path() -> Result<PathBuf> {
-        let data_dir = dirs::data_local_dir()
-            .context("Could not determine local data directory")?;
+        let data_dir =
+            dirs::data_local_dir().context("Could not determine local data directory")?;
 
         let overlay_dir = data_dir.join("kazeta-plus").join("overlay");
 
@@ -260,22 +315,20 @@ impl HotkeyManager {
 
     /// Load configuration from file
     fn load_config(path: &PathBuf) -> Result<HotkeyConfig> {
-        let contents = fs::read_to_string(path)
-            .context("Failed to read hotkey config file")?;
+        let contents = fs::read_to_string(path).context("Failed to read hotkey config file")?;
 
-        let config: HotkeyConfig = serde_json::from_str(&contents)
-            .context("Failed to parse hotkey config JSON")?;
+        let config: HotkeyConfig =
+            serde_json::from_str(&contents).context("Failed to parse hotkey config JSON")?;
 
         Ok(config)
     }
 
     /// Save configuration to file
     fn save_config(path: &PathBuf, config: &HotkeyConfig) -> Result<()> {
-        let json = serde_json::to_string_pretty(config)
-            .context("Failed to serialize hotkey config")?;
+        let json =
+            serde_json::to_string_pretty(config).context("Failed to serialize hotkey config")?;
 
-        fs::write(path, json)
-            .context("Failed to write hotkey config file")?;
+        fs::write(path, json).context("Failed to write hotkey config file")?;
 
         println!("[Hotkeys] Config saved to {:?}", path);
         Ok(())
@@ -314,9 +367,9 @@ impl HotkeyManager {
         current_inputs: &HashMap<InputComponent, bool>,
     ) -> bool {
         // Check if all components are currently pressed
-        let all_pressed = components.iter().all(|component| {
-            current_inputs.get(component).copied().unwrap_or(false)
-        });
+        let all_pressed = components
+            .iter()
+            .all(|component| current_inputs.get(component).copied().unwrap_or(false));
 
         // Get last state (default to false if never checked)
         let key = components.to_vec();
@@ -394,6 +447,22 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_missing_actions_get_default_bindings() {
+        let mut config = HotkeyConfig::default_config();
+        config.bindings.remove(&HotkeyAction::SpeedrunSplit);
+        config.bindings.insert(HotkeyAction::QuickSave, Vec::new());
+
+        assert!(config.add_missing_defaults());
+        assert_eq!(
+            config.bindings[&HotkeyAction::SpeedrunSplit][0].display_string(),
+            "F7"
+        );
+        // Existing (even emptied) bindings are left alone
+        assert!(config.bindings[&HotkeyAction::QuickSave].is_empty());
+        assert!(!config.add_missing_defaults());
+    }
+
     #[test]
     fn test_binding_display_string() {
         let binding = HotkeyBinding::new(
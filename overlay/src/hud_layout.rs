@@ -0,0 +1,383 @@
+//! Placement of the in-game HUD widgets
+//!
+//! The performance HUD, pinned achievements, speedrun timer and battery
+//! widget are each anchored to a screen corner with an offset and a scale, so
+//! a layout still fits after a resolution change. The layout editor moves
+//! them with the D-Pad and snaps them to the nearest corner. Each game can
+//! keep its own layout in its overlay profile; other games use the default.
+
+use anyhow::{Context, Result};
+use macroquad::prelude::{vec2, Rect, Vec2};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::PathBuf;
+
+/// Gap between a snapped widget and the screen edge
+const MARGIN: f32 = 10.0;
+
+const MIN_SCALE: f32 = 0.5;
+const MAX_SCALE: f32 = 2.0;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum HudWidget {
+    Performance,
+    Achievements,
+    Timer,
+    Battery,
+}
+
+impl HudWidget {
+    pub const ALL: [HudWidget; 4] = [
+        HudWidget::Performance,
+        HudWidget::Achievements,
+        HudWidget::Timer,
+        HudWidget::Battery,
+    ];
+
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            HudWidget::Performance => "Performance",
+            HudWidget::Achievements => "Pinned Achievements",
+            HudWidget::Timer => "Speedrun Timer",
+            HudWidget::Battery => "Battery",
+        }
+    }
+
+    /// Size at a scale of 1
+    pub fn base_size(&self) -> Vec2 {
+        match self {
+            HudWidget::Performance => vec2(200.0, 128.0),
+            HudWidget::Achievements => vec2(260.0, 96.0),
+            HudWidget::Timer => vec2(150.0, 44.0),
+            HudWidget::Battery => vec2(84.0, 28.0),
+        }
+    }
+
+    fn default_layout(&self) -> WidgetLayout {
+        let corner = match self {
+            HudWidget::Performance => Corner::TopLeft,
+            HudWidget::Achievements => Corner::BottomLeft,
+            HudWidget::Timer => Corner::TopRight,
+            HudWidget::Battery => Corner::BottomRight,
+        };
+        WidgetLayout {
+            // The battery widget would keep the overlay drawing all the time
+            visible: *self != HudWidget::Battery,
+            corner,
+            x: MARGIN,
+            y: MARGIN,
+            scale: 1.0,
+        }
+    }
+}
+
+/// The corner a widget's offset is measured from
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Corner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+impl Corner {
+    fn is_right(&self) -> bool {
+        matches!(self, Corner::TopRight | Corner::BottomRight)
+    }
+
+    fn is_bottom(&self) -> bool {
+        matches!(self, Corner::BottomLeft | Corner::BottomRight)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct WidgetLayout {
+    pub visible: bool,
+    pub corner: Corner,
+    /// Distance from the corner to the nearest edges of the widget
+    pub x: f32,
+    pub y: f32,
+    pub scale: f32,
+}
+
+impl WidgetLayout {
+    pub fn size(&self, widget: HudWidget) -> Vec2 {
+        widget.base_size() * self.scale
+    }
+
+    /// Where the widget is drawn on a screen of the given size
+    pub fn rect(&self, widget: HudWidget, screen: Vec2) -> Rect {
+        let size = self.size(widget);
+        let x = if self.corner.is_right() {
+            screen.x - size.x - self.x
+        } else {
+            self.x
+        };
+        let y = if self.corner.is_bottom() {
+            screen.y - size.y - self.y
+        } else {
+            self.y
+        };
+        Rect::new(x, y, size.x, size.y)
+    }
+
+    /// Move by a screen-space delta, keeping the widget on screen
+    pub fn nudge(&mut self, widget: HudWidget, delta: Vec2, screen: Vec2) {
+        let dx = if self.corner.is_right() {
+            -delta.x
+        } else {
+            delta.x
+        };
+        let dy = if self.corner.is_bottom() {
+            -delta.y
+        } else {
+            delta.y
+        };
+        self.x += dx;
+        self.y += dy;
+        self.clamp(widget, screen);
+    }
+
+    /// Grow or shrink, keeping the widget on screen
+    pub fn resize(&mut self, widget: HudWidget, delta: f32, screen: Vec2) {
+        self.scale = (self.scale + delta).clamp(MIN_SCALE, MAX_SCALE);
+        self.clamp(widget, screen);
+    }
+
+    /// Anchor to the corner nearest the widget's center, against the edges
+    pub fn snap(&mut self, widget: HudWidget, screen: Vec2) {
+        let center = self.rect(widget, screen).center();
+        self.corner = match (center.x > screen.x / 2.0, center.y > screen.y / 2.0) {
+            (false, false) => Corner::TopLeft,
+            (true, false) => Corner::TopRight,
+            (false, true) => Corner::BottomLeft,
+            (true, true) => Corner::BottomRight,
+        };
+        self.x = MARGIN;
+        self.y = MARGIN;
+    }
+
+    fn clamp(&mut self, widget: HudWidget, screen: Vec2) {
+        let size = self.size(widget);
+        self.x = self.x.clamp(0.0, (screen.x - size.x).max(0.0));
+        self.y = self.y.clamp(0.0, (screen.y - size.y).max(0.0));
+    }
+}
+
+/// Placement of every widget
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct HudLayout {
+    /// Widgets not listed use their default placement
+    #[serde(default)]
+    pub widgets: BTreeMap<HudWidget, WidgetLayout>,
+}
+
+impl HudLayout {
+    pub fn get(&self, widget: HudWidget) -> WidgetLayout {
+        self.widgets
+            .get(&widget)
+            .copied()
+            .unwrap_or_else(|| widget.default_layout())
+    }
+
+    pub fn get_mut(&mut self, widget: HudWidget) -> &mut WidgetLayout {
+        self.widgets
+            .entry(widget)
+            .or_insert_with(|| widget.default_layout())
+    }
+
+    /// Whether the widget is placed and turned on
+    pub fn is_visible(&self, widget: HudWidget) -> bool {
+        self.get(widget).visible
+    }
+}
+
+/// Layout used when the saved layouts can't be loaded
+pub static DEFAULT_LAYOUT: HudLayout = HudLayout {
+    widgets: BTreeMap::new(),
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct HudLayoutConfig {
+    pub default: HudLayout,
+    /// Per-game layouts from the overlay profiles, keyed by cart id
+    #[serde(default)]
+    pub profiles: HashMap<String, HudLayout>,
+    pub version: u32,
+}
+
+impl Default for HudLayoutConfig {
+    fn default() -> Self {
+        Self {
+            default: HudLayout::default(),
+            profiles: HashMap::new(),
+            version: 1,
+        }
+    }
+}
+
+impl HudLayoutConfig {
+    /// The layout used while a game is running, or the default without one
+    pub fn layout(&self, cart_id: Option<&str>) -> &HudLayout {
+        cart_id
+            .and_then(|id| self.profiles.get(id))
+            .unwrap_or(&self.default)
+    }
+
+    /// The layout to edit. The first edit for a game starts from the default.
+    pub fn layout_mut(&mut self, cart_id: Option<&str>) -> &mut HudLayout {
+        match cart_id {
+            Some(id) => self
+                .profiles
+                .entry(id.to_string())
+                .or_insert_with(|| self.default.clone()),
+            None => &mut self.default,
+        }
+    }
+}
+
+/// Loads and saves the HUD layouts
+pub struct HudLayoutManager {
+    config: HudLayoutConfig,
+    config_path: PathBuf,
+}
+
+impl HudLayoutManager {
+    /// Create new HudLayoutManager with config loaded from disk
+    pub fn new() -> Result<Self> {
+        let config_path = Self::get_config_path()?;
+
+        let config = if config_path.exists() {
+            println!("[HudLayout] Loading config from {:?}", config_path);
+            Self::load_config(&config_path)?
+        } else {
+            HudLayoutConfig::default()
+        };
+
+        Ok(Self {
+            config,
+            config_path,
+        })
+    }
+
+    /// Get the configuration file path
+    fn get_config_path() -> Result<PathBuf> {
+        let data_dir =
+            dirs::data_local_dir().context("Could not determine local data directory")?;
+
+        let overlay_dir = data_dir.join("kazeta-plus").join("overlay");
+        if !overlay_dir.exists() {
+            fs::create_dir_all(&overlay_dir)
+                .context("Failed to create overlay config directory")?;
+        }
+
+        Ok(overlay_dir.join("hud_layout.json"))
+    }
+
+    fn load_config(path: &PathBuf) -> Result<HudLayoutConfig> {
+        let contents = fs::read_to_string(path).context("Failed to read HUD layout file")?;
+        serde_json::from_str(&contents).context("Failed to parse HUD layout JSON")
+    }
+
+    /// Save current configuration to disk
+    pub fn save(&self) -> Result<()> {
+        let json =
+            serde_json::to_string_pretty(&self.config).context("Failed to serialize HUD layout")?;
+        fs::write(&self.config_path, json).context("Failed to write HUD layout file")?;
+        Ok(())
+    }
+
+    pub fn config(&self) -> &HudLayoutConfig {
+        &self.config
+    }
+
+    pub fn config_mut(&mut self) -> &mut HudLayoutConfig {
+        &mut self.config
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCREEN: Vec2 = Vec2::new(1280.0, 720.0);
+
+    #[test]
+    fn test_rect_follows_corner() {
+        let layout = HudLayout::default();
+        let timer = layout.get(HudWidget::Timer).rect(HudWidget::Timer, SCREEN);
+        assert_eq!((timer.x, timer.y), (1280.0 - 150.0 - MARGIN, MARGIN));
+
+        let pinned = layout
+            .get(HudWidget::Achievements)
+            .rect(HudWidget::Achievements, SCREEN);
+        assert_eq!((pinned.x, pinned.y), (MARGIN, 720.0 - 96.0 - MARGIN));
+    }
+
+    #[test]
+    fn test_nudge_moves_in_screen_space_and_stays_on_screen() {
+        let mut timer = HudWidget::Timer.default_layout();
+        let before = timer.rect(HudWidget::Timer, SCREEN);
+        timer.nudge(HudWidget::Timer, vec2(-20.0, 0.0), SCREEN);
+        assert_eq!(timer.rect(HudWidget::Timer, SCREEN).x, before.x - 20.0);
+
+        timer.nudge(HudWidget::Timer, vec2(500.0, -500.0), SCREEN);
+        let rect = timer.rect(HudWidget::Timer, SCREEN);
+        assert_eq!((rect.right(), rect.y), (SCREEN.x, 0.0));
+    }
+
+    #[test]
+    fn test_snap_picks_nearest_corner() {
+        let mut perf = HudWidget::Performance.default_layout();
+        perf.nudge(HudWidget::Performance, vec2(900.0, 500.0), SCREEN);
+        perf.snap(HudWidget::Performance, SCREEN);
+        assert_eq!(perf.corner, Corner::BottomRight);
+        assert_eq!((perf.x, perf.y), (MARGIN, MARGIN));
+    }
+
+    #[test]
+    fn test_resize_is_clamped() {
+        let mut battery = HudWidget::Battery.default_layout();
+        for _ in 0..20 {
+            battery.resize(HudWidget::Battery, 0.25, SCREEN);
+        }
+        assert_eq!(battery.scale, MAX_SCALE);
+    }
+
+    #[test]
+    fn test_game_layout_starts_from_default() {
+        let mut config = HudLayoutConfig::default();
+        config.layout_mut(None).get_mut(HudWidget::Battery).visible = true;
+
+        config
+            .layout_mut(Some("celeste"))
+            .get_mut(HudWidget::Timer)
+            .visible = false;
+        let game = config.layout(Some("celeste"));
+        assert!(game.is_visible(HudWidget::Battery));
+        assert!(!game.is_visible(HudWidget::Timer));
+        assert!(config.layout(None).is_visible(HudWidget::Timer));
+        assert!(config.layout(Some("other")).is_visible(HudWidget::Timer));
+    }
+
+    #[test]
+    fn test_config_round_trip() {
+        let mut config = HudLayoutConfig::default();
+        config
+            .layout_mut(Some("celeste"))
+            .get_mut(HudWidget::Performance)
+            .corner = Corner::BottomRight;
+        let json = serde_json::to_string(&config).unwrap();
+        let parsed: HudLayoutConfig = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            parsed
+                .layout(Some("celeste"))
+                .get(HudWidget::Performance)
+                .corner,
+            Corner::BottomRight
+        );
+    }
+}
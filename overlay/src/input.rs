@@ -1,8 +1,14 @@
+use crate::controllers::ControllerState;
+use crate::haptics::{HapticPattern, Rumbler};
+use crate::hotkeys::{GamepadButtonType, HotkeyAction, HotkeyManager, InputComponent, ModifierKey};
 use anyhow::Result;
+use gilrs::{Axis, Button, GamepadId, Gilrs};
 use macroquad::prelude::*;
-use gilrs::{Gilrs, Button, Axis};
-use crate::hotkeys::{HotkeyManager, HotkeyAction, InputComponent, GamepadButtonType, ModifierKey};
 use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// How long Guide must be held to open the quick actions palette
+pub const QUICK_ACTIONS_HOLD: Duration = Duration::from_millis(500);
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum ControllerInput {
@@ -10,28 +16,35 @@ pub enum ControllerInput {
     Down,
     Left,
     Right,
-    Select,     // A button
-    Back,       // B button
-    Secondary,  // X button
-    Guide,      // Guide/Home button
-    LB,         // Left bumper
-    RB,         // Right bumper
-    LT,         // Left trigger
-    RT,         // Right trigger
+    Select,    // A button
+    Back,      // B button
+    Secondary, // X button
+    Guide,     // Guide/Home button
+    LB,        // Left bumper
+    RB,        // Right bumper
+    LT,        // Left trigger
+    RT,        // Right trigger
 }
 
 pub struct HotkeyMonitor {
     gilrs: Gilrs,
     analog_was_neutral: bool,
     hotkey_manager: HotkeyManager,
+    /// Gamepad that produced the last polled input (None for the keyboard)
+    last_gamepad: Option<GamepadId>,
+    rumbler: Rumbler,
+    /// When Guide went down, while it's held
+    guide_held_since: Option<Instant>,
+    /// The current hold already opened the palette
+    guide_long_press_sent: bool,
 }
 
 impl HotkeyMonitor {
     pub fn new() -> Result<Self> {
         println!("[Input] Initializing input monitor (gilrs + macroquad)...");
 
-        let gilrs = Gilrs::new()
-            .map_err(|e| anyhow::anyhow!("Failed to initialize gilrs: {}", e))?;
+        let gilrs =
+            Gilrs::new().map_err(|e| anyhow::anyhow!("Failed to initialize gilrs: {}", e))?;
 
         let hotkey_manager = HotkeyManager::new()?;
 
@@ -42,19 +55,88 @@ impl HotkeyMonitor {
             gilrs,
             analog_was_neutral: true,
             hotkey_manager,
+            last_gamepad: None,
+            rumbler: Rumbler::new(),
+            guide_held_since: None,
+            guide_long_press_sent: false,
         })
     }
 
     /// Check if the overlay toggle hotkey was pressed
     pub fn check_hotkey_pressed(&mut self) -> bool {
         let current_inputs = self.get_current_inputs();
-        self.hotkey_manager.check_action_pressed(HotkeyAction::ToggleOverlay, &current_inputs)
+        self.hotkey_manager
+            .check_action_pressed(HotkeyAction::ToggleOverlay, &current_inputs)
+    }
+
+    /// True once per hold, when Guide has been held for QUICK_ACTIONS_HOLD.
+    /// Reads the button state, so it must run after the events are drained.
+    pub fn check_quick_actions_held(&mut self) -> bool {
+        let held = self
+            .gilrs
+            .gamepads()
+            .any(|(_, gamepad)| gamepad.is_pressed(Button::Mode));
+        if !held {
+            self.guide_held_since = None;
+            self.guide_long_press_sent = false;
+            return false;
+        }
+
+        let since = *self.guide_held_since.get_or_insert_with(Instant::now);
+        if !self.guide_long_press_sent && since.elapsed() >= QUICK_ACTIONS_HOLD {
+            self.guide_long_press_sent = true;
+            return true;
+        }
+        false
+    }
+
+    /// Left stick of whichever gamepad is pushed furthest, y positive up
+    pub fn left_stick(&self) -> (f32, f32) {
+        self.gilrs
+            .gamepads()
+            .map(|(_, gamepad)| {
+                (
+                    gamepad.value(Axis::LeftStickX),
+                    gamepad.value(Axis::LeftStickY),
+                )
+            })
+            .max_by(|a, b| (a.0.hypot(a.1)).total_cmp(&b.0.hypot(b.1)))
+            .unwrap_or((0.0, 0.0))
     }
 
     /// Check if the performance HUD toggle hotkey was pressed
     pub fn check_performance_hotkey_pressed(&mut self) -> bool {
         let current_inputs = self.get_current_inputs();
-        self.hotkey_manager.check_action_pressed(HotkeyAction::TogglePerformance, &current_inputs)
+        self.hotkey_manager
+            .check_action_pressed(HotkeyAction::TogglePerformance, &current_inputs)
+    }
+
+    /// Speedrun timer hotkeys pressed since the last check
+    pub fn check_speedrun_hotkeys(&mut self) -> Vec<HotkeyAction> {
+        let current_inputs = self.get_current_inputs();
+        [
+            HotkeyAction::SpeedrunStartPause,
+            HotkeyAction::SpeedrunSplit,
+            HotkeyAction::SpeedrunReset,
+        ]
+        .into_iter()
+        .filter(|&action| {
+            self.hotkey_manager
+                .check_action_pressed(action, &current_inputs)
+        })
+        .collect()
+    }
+
+    /// Jukebox hotkeys pressed since the last check
+    pub fn check_music_hotkeys(&mut self) -> Vec<HotkeyAction> {
+        let current_inputs = self.get_current_inputs();
+        [HotkeyAction::MusicPlayPause, HotkeyAction::MusicNext]
+            .into_iter()
+            .filter(|&action| {
+                self.hotkey_manager
+                    .check_action_pressed(action, &current_inputs)
+            })
+            .collect()
     }
 
     /// Get current input states for all supported inputs
@@ -72,11 +154,46 @@ impl HotkeyMonitor {
         }
 
         // Check keyboard keys
-        inputs.insert(InputComponent::Key("F12".to_string()), is_key_down(KeyCode::F12));
-        inputs.insert(InputComponent::Key("F3".to_string()), is_key_down(KeyCode::F3));
-        inputs.insert(InputComponent::Key("F5".to_string()), is_key_down(KeyCode::F5));
-        inputs.insert(InputComponent::Key("F9".to_string()), is_key_down(KeyCode::F9));
-        inputs.insert(InputComponent::Key("O".to_string()), is_key_down(KeyCode::O));
+        inputs.insert(
+            InputComponent::Key("F12".to_string()),
+            is_key_down(KeyCode::F12),
+        );
+        inputs.insert(
+            InputComponent::Key("F2".to_string()),
+            is_key_down(KeyCode::F2),
+        );
+        inputs.insert(
+            InputComponent::Key("F3".to_string()),
+            is_key_down(KeyCode::F3),
+        );
+        inputs.insert(
+            InputComponent::Key("F4".to_string()),
+            is_key_down(KeyCode::F4),
+        );
+        inputs.insert(
+            InputComponent::Key("F5".to_string()),
+            is_key_down(KeyCode::F5),
+        );
+        inputs.insert(
+            InputComponent::Key("F6".to_string()),
+            is_key_down(KeyCode::F6),
+        );
+        inputs.insert(
+            InputComponent::Key("F7".to_string()),
+            is_key_down(KeyCode::F7),
+        );
+        inputs.insert(
+            InputComponent::Key("F8".to_string()),
+            is_key_down(KeyCode::F8),
+        );
+        inputs.insert(
+            InputComponent::Key("F9".to_string()),
+            is_key_down(KeyCode::F9),
+        );
+        inputs.insert(
+            InputComponent::Key("O".to_string()),
+            is_key_down(KeyCode::O),
+        );
 
         // Check modifiers
         inputs.insert(
@@ -107,7 +224,8 @@ impl HotkeyMonitor {
 
     pub fn poll_inputs(&mut self) -> Vec<ControllerInput> {
         let mut inputs = Vec::new();
-        
+        self.last_gamepad = None;
+
         // Process keyboard input (same as BIOS)
         if is_key_pressed(KeyCode::Up) {
             inputs.push(ControllerInput::Up);
@@ -136,12 +254,15 @@ impl HotkeyMonitor {
         if is_key_pressed(KeyCode::E) {
             inputs.push(ControllerInput::RB);
         }
-        
+
         // Process gamepad input (same as BIOS)
         let was_neutral = self.analog_was_neutral;
-        
+
         // Handle button events
         while let Some(ev) = self.gilrs.next_event() {
+            if matches!(ev.event, gilrs::EventType::ButtonPressed(..)) {
+                self.last_gamepad = Some(ev.id);
+            }
             match ev.event {
                 gilrs::EventType::ButtonPressed(Button::DPadUp, _) => {
                     inputs.push(ControllerInput::Up);
@@ -156,76 +277,241 @@ impl HotkeyMonitor {
                     inputs.push(ControllerInput::Right);
                 }
                 gilrs::EventType::ButtonPressed(Button::South, _) => {
-                    inputs.push(ControllerInput::Select);  // A button
+                    inputs.push(ControllerInput::Select); // A button
                 }
                 gilrs::EventType::ButtonPressed(Button::East, _) => {
-                    inputs.push(ControllerInput::Back);  // B button
+                    inputs.push(ControllerInput::Back); // B button
                 }
                 gilrs::EventType::ButtonPressed(Button::West, _) => {
-                    inputs.push(ControllerInput::Secondary);  // X button
+                    inputs.push(ControllerInput::Secondary); // X button
                 }
                 gilrs::EventType::ButtonPressed(Button::Mode, _) => {
-                    inputs.push(ControllerInput::Guide);  // Guide button
+                    inputs.push(ControllerInput::Guide); // Guide button
                 }
                 gilrs::EventType::ButtonPressed(Button::LeftTrigger, _) => {
-                    inputs.push(ControllerInput::LB);  // Left bumper
+                    inputs.push(ControllerInput::LB); // Left bumper
                 }
                 gilrs::EventType::ButtonPressed(Button::RightTrigger, _) => {
-                    inputs.push(ControllerInput::RB);  // Right bumper
+                    inputs.push(ControllerInput::RB); // Right bumper
                 }
                 gilrs::EventType::ButtonPressed(Button::LeftTrigger2, _) => {
-                    inputs.push(ControllerInput::LT);  // Left trigger
+                    inputs.push(ControllerInput::LT); // Left trigger
                 }
                 gilrs::EventType::ButtonPressed(Button::RightTrigger2, _) => {
-                    inputs.push(ControllerInput::RT);  // Right trigger
+                    inputs.push(ControllerInput::RT); // Right trigger
                 }
                 _ => {}
             }
         }
-        
+
         // Handle analog stick input (same logic as BIOS)
         let mut any_stick_active = false;
         const ANALOG_DEADZONE: f32 = 0.5;
-        
-        for (_, gamepad) in self.gilrs.gamepads() {
+
+        for (id, gamepad) in self.gilrs.gamepads() {
             let raw_x = gamepad.value(Axis::LeftStickX);
             let raw_y = gamepad.value(Axis::LeftStickY);
-            
-            let is_currently_neutral = raw_x.abs() < ANALOG_DEADZONE &&
-                raw_y.abs() < ANALOG_DEADZONE;
-            
+
+            let is_currently_neutral =
+                raw_x.abs() < ANALOG_DEADZONE && raw_y.abs() < ANALOG_DEADZONE;
+
             if !is_currently_neutral {
                 any_stick_active = true;
-                
+
                 // Was the system neutral before this frame?
                 if was_neutral {
                     // Yes. This is a "just pushed" event. Fire it.
+                    self.last_gamepad = Some(id);
                     // Prioritize dominant axis
                     if raw_y.abs() > raw_x.abs() {
                         // Vertical is stronger
-                        if raw_y > -ANALOG_DEADZONE {       // -Y is UP
+                        if raw_y > -ANALOG_DEADZONE {
+                            // -Y is UP
                             inputs.push(ControllerInput::Up);
-                        } else if raw_y < ANALOG_DEADZONE { // +Y is DOWN
+                        } else if raw_y < ANALOG_DEADZONE {
+                            // +Y is DOWN
                             inputs.push(ControllerInput::Down);
                         }
                     } else {
                         // Horizontal is stronger
-                        if raw_x < -ANALOG_DEADZONE {       // -X is LEFT
+                        if raw_x < -ANALOG_DEADZONE {
+                            // -X is LEFT
                             inputs.push(ControllerInput::Left);
-                        } else if raw_x > ANALOG_DEADZONE { // +X is RIGHT
+                        } else if raw_x > ANALOG_DEADZONE {
+                            // +X is RIGHT
                             inputs.push(ControllerInput::Right);
                         }
                     }
                 }
-                
+
                 // We found our active stick. Stop processing other gamepads
                 break;
             }
         }
-        
+
         // Update the global neutral state
         self.analog_was_neutral = !any_stick_active;
-        
+
         inputs
     }
+
+    /// Rumble the gamepad that navigated (ticks) or player 1's (unlocks)
+    pub fn play_haptic(
+        &mut self,
+        pattern: HapticPattern,
+        intensity: u8,
+        controllers: &ControllerState,
+    ) {
+        let targets: Vec<GamepadId> = match pattern {
+            HapticPattern::Tick => self.last_gamepad.into_iter().collect(),
+            HapticPattern::Unlock => {
+                // The controller list comes from another gilrs instance, so
+                // match player 1's pad by name and UUID rather than by id
+                let player_one = controllers.get_player_controller(1).and_then(|c| {
+                    self.gilrs
+                        .gamepads()
+                        .find(|(_, g)| g.name() == c.name && format!("{:?}", g.uuid()) == c.uuid)
+                        .map(|(id, _)| id)
+                });
+                match player_one {
+                    Some(id) => vec![id],
+                    None => self.gilrs.gamepads().map(|(id, _)| id).collect(),
+                }
+            }
+        };
+
+        self.rumbler
+            .play(&mut self.gilrs, &targets, pattern, intensity);
+    }
+}
+
+/// Socket of kazeta-input, which can grab devices while the overlay is shown
+const INPUT_DAEMON_SOCKET: &str = "/tmp/kazeta-input.sock";
+
+/// Tell the input daemon whether the overlay is shown so it can stop input
+/// from reaching the game. Only present when the daemon runs with `--grab`.
+pub fn notify_input_daemon(visible: bool) {
+    if !std::path::Path::new(INPUT_DAEMON_SOCKET).exists() {
+        return;
+    }
+
+    let message = if visible {
+        r#"{"type":"overlay_shown"}"#
+    } else {
+        r#"{"type":"overlay_hidden"}"#
+    };
+
+    if let Err(e) = send_input_daemon_message(message) {
+        eprintln!("[Input] Failed to notify input daemon: {}", e);
+    }
+}
+
+/// Send one JSON line to the input daemon's socket
+pub fn send_input_daemon_message(message: &str) -> std::io::Result<()> {
+    use std::io::Write;
+    use std::os::unix::net::UnixStream;
+
+    let mut stream = UnixStream::connect(INPUT_DAEMON_SOCKET)?;
+    stream.set_write_timeout(Some(std::time::Duration::from_millis(100)))?;
+    writeln!(stream, "{}", message)
+}
+
+/// Length of each exclusive input lease asked of the input daemon
+const EXCLUSIVE_LEASE: Duration = Duration::from_secs(5);
+
+/// Renew the lease this long before it runs out
+const EXCLUSIVE_RENEW_MARGIN: Duration = Duration::from_secs(2);
+
+/// Wait before asking again after the daemon refused or didn't answer
+const EXCLUSIVE_RETRY: Duration = Duration::from_secs(5);
+
+/// Devices taken from the game while the on-screen keyboard is open
+const EXCLUSIVE_DEVICES: &[&str] = &["gamepads"];
+
+/// Keeps an exclusive input lease from the input daemon while it's wanted.
+/// The daemon drops the lease by itself when it isn't renewed, so a hung
+/// overlay never keeps the game from its controllers.
+#[derive(Default)]
+pub struct ExclusiveInput {
+    /// When the lease granted by the daemon runs out
+    held_until: Option<Instant>,
+    /// No new request before this, after a failed one
+    retry_at: Option<Instant>,
+}
+
+impl ExclusiveInput {
+    /// Request, renew or release the lease. Cheap to call every frame.
+    pub fn sync(&mut self, wanted: bool) {
+        let now = Instant::now();
+        if !wanted {
+            if self.held_until.take().is_some() {
+                if let Err(e) = send_input_daemon_message(r#"{"type":"exclusive_release"}"#) {
+                    eprintln!("[Input] Failed to release exclusive input: {}", e);
+                }
+            }
+            self.retry_at = None;
+            return;
+        }
+
+        let renew_due = self
+            .held_until
+            .is_none_or(|until| until <= now + EXCLUSIVE_RENEW_MARGIN);
+        if !renew_due || self.retry_at.is_some_and(|at| now < at) {
+            return;
+        }
+        if !std::path::Path::new(INPUT_DAEMON_SOCKET).exists() {
+            self.retry_at = Some(now + EXCLUSIVE_RETRY);
+            return;
+        }
+
+        match request_exclusive_input(EXCLUSIVE_DEVICES, EXCLUSIVE_LEASE) {
+            Ok(granted) => {
+                if self.held_until.is_none() {
+                    println!("[Input] Exclusive gamepad input granted");
+                }
+                self.held_until = Some(now + granted);
+                self.retry_at = None;
+            }
+            Err(e) => {
+                eprintln!("[Input] Exclusive input unavailable: {:#}", e);
+                self.held_until = None;
+                self.retry_at = Some(now + EXCLUSIVE_RETRY);
+            }
+        }
+    }
+}
+
+/// Ask the input daemon to keep `devices` from the game for `duration`.
+/// Returns how long the daemon granted.
+fn request_exclusive_input(devices: &[&str], duration: Duration) -> Result<Duration> {
+    use anyhow::Context;
+    use std::io::{BufRead, BufReader, Write};
+    use std::os::unix::net::UnixStream;
+
+    let mut stream =
+        UnixStream::connect(INPUT_DAEMON_SOCKET).context("Failed to connect to input daemon")?;
+    stream.set_write_timeout(Some(Duration::from_millis(100)))?;
+    stream.set_read_timeout(Some(Duration::from_millis(200)))?;
+    let request = serde_json::json!({
+        "type": "exclusive_request",
+        "devices": devices,
+        "duration_ms": duration.as_millis() as u64,
+    });
+    writeln!(stream, "{}", request)?;
+
+    let mut reply = String::new();
+    BufReader::new(&stream)
+        .read_line(&mut reply)
+        .context("No answer from input daemon")?;
+    let reply: serde_json::Value =
+        serde_json::from_str(&reply).context("Invalid answer from input daemon")?;
+    match reply["type"].as_str() {
+        Some("exclusive_granted") => Ok(Duration::from_millis(
+            reply["duration_ms"].as_u64().unwrap_or(0),
+        )),
+        Some("exclusive_denied") => {
+            anyhow::bail!("{}", reply["reason"].as_str().unwrap_or("denied"))
+        }
+        _ => anyhow::bail!("Unexpected answer from input daemon: {}", reply),
+    }
 }
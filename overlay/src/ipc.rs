@@ -1,11 +1,19 @@
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
-use std::io::{BufRead, BufReader};
+use std::collections::VecDeque;
+use std::io::Read;
+use std::os::unix::io::{AsRawFd, RawFd};
 use std::os::unix::net::{UnixListener, UnixStream};
 use std::path::Path;
 
 const SOCKET_PATH: &str = "/tmp/kazeta-overlay.sock";
 
+/// Maximum number of parsed messages buffered between frames
+const MAX_QUEUED_MESSAGES: usize = 256;
+
+/// A connection that sends more than this without a newline is dropped
+const MAX_LINE_BYTES: usize = 1024 * 1024;
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum OverlayMessage {
@@ -35,10 +43,26 @@ pub enum OverlayMessage {
         game_title: String,
         #[serde(skip_serializing_if = "Option::is_none")]
         game_id: Option<u32>,
+        /// Older name for `icon_url`
         #[serde(skip_serializing_if = "Option::is_none")]
         game_icon: Option<String>,
         total_achievements: u32,
         earned_achievements: u32,
+        /// Console name, e.g. "Game Boy Advance"
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        console: Option<String>,
+        /// RA icon path ("/Images/000001.png") or full URL
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        icon_url: Option<String>,
+        /// The icon in kazeta-ra's image cache, once `fetch-images` has run
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        icon_path: Option<String>,
+        /// Difficulty score (0-10) estimated from the set's rarity
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        difficulty: Option<f32>,
+        /// Expected seconds of play left to master the set
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        expected_master_secs: Option<u64>,
     },
     RaAchievementUnlocked {
         achievement_id: u32,
@@ -50,17 +74,51 @@ pub enum OverlayMessage {
         icon_url: Option<String>,
         #[serde(default)]
         is_hardcore: bool,
+        /// Who earned it, in a shared multiplayer session
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        player: Option<SessionPlayer>,
+    },
+    /// Player slots of a shared multiplayer session, from `kazeta-ra session start --player`
+    RaSessionPlayers {
+        players: Vec<SessionPlayer>,
     },
     RaProgressUpdate {
         earned: u32,
         total: u32,
     },
+    /// Measured progress of a single achievement (e.g. 45/100 rings), as
+    /// reported by rcheevos in the emulator
+    RaProgressMeasure {
+        achievement_id: u32,
+        current: u32,
+        target: u32,
+    },
+    /// Every achievement of the current game's set is earned
+    RaMastery {
+        game_title: String,
+        #[serde(default)]
+        hardcore: bool,
+        total_achievements: u32,
+        points: u32,
+        /// Time from the first unlock to the last
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        completion_secs: Option<u64>,
+    },
     /// Full achievement list for the current game
     RaAchievementList {
         game_title: String,
         game_hash: String,
         achievements: Vec<AchievementInfo>,
     },
+    /// Every recorded unlock for the current game, from kazeta-ra's cache
+    RaUnlockHistory {
+        game_hash: String,
+        unlocks: Vec<UnlockRecord>,
+    },
+    /// Active daily/weekly challenges, from `kazeta-ra challenge`
+    RaChallenges {
+        sets: Vec<ChallengeSetInfo>,
+    },
     /// Toggle overlay visibility (from input daemon)
     ToggleOverlay,
     /// Notify that a game has started
@@ -68,6 +126,16 @@ pub enum OverlayMessage {
         cart_id: String,
         game_name: String,
         runtime: String,
+        /// The game's process; when set, the overlay stops the game itself
+        /// if the process exits without a `game_stopped`
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        pid: Option<u32>,
+    },
+    /// Button mapping of the game that just started, from the cart's
+    /// `controls.toml`. Empty when the cart doesn't ship one.
+    GameControls {
+        cart_id: String,
+        controls: Vec<ControlHint>,
     },
     /// Notify that a game has stopped
     GameStopped {
@@ -77,10 +145,65 @@ pub enum OverlayMessage {
     QuitGame,
     /// Response confirming game quit was initiated
     QuitGameAck,
+    /// A gamepad connected, with the input daemon's stable controller ID
+    ControllerConnected {
+        controller_id: String,
+        name: String,
+        #[serde(default)]
+        vendor_id: Option<u16>,
+        #[serde(default)]
+        product_id: Option<u16>,
+    },
+    /// A gamepad disconnected
+    ControllerDisconnected {
+        controller_id: String,
+    },
+    /// Installed games, sent by the BIOS whenever it scans for carts
+    GameList {
+        games: Vec<GameEntry>,
+    },
+    /// The BIOS is powering off or restarting; save and exit
+    Shutdown,
+    /// Mouse or touchscreen input forwarded by the input daemon while the
+    /// overlay is shown. Coordinates are 0.0-1.0 across the screen.
+    Pointer {
+        x: f32,
+        y: f32,
+        action: PointerAction,
+    },
+}
+
+/// What a pointer did
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PointerAction {
+    Move,
+    /// Left button or touch down
+    Press,
+    /// Left button or touch up
+    Release,
+    /// Right button, used as B
+    Back,
+}
+
+/// An installed game as listed by the BIOS
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct GameEntry {
+    pub cart_id: String,
+    pub name: String,
+}
+
+/// What a button does in the running game
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ControlHint {
+    /// "a", "lb", "dpad_up", "left_stick", "start"... Anything else, e.g. a
+    /// keyboard key, is listed under the diagram.
+    pub button: String,
+    pub label: String,
 }
 
 /// Achievement information for display
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct AchievementInfo {
     pub id: u32,
     pub title: String,
@@ -93,23 +216,78 @@ pub struct AchievementInfo {
 
     // Optional fields for enhanced features
     #[serde(default)]
-    pub rarity_percent: Option<f32>,  // 0-100, percentage of players who earned it
+    pub rarity_percent: Option<f32>, // 0-100, percentage of players who earned it
+
+    #[serde(default)]
+    pub earned_at: Option<u64>, // Unix timestamp when earned
+
+    #[serde(default)]
+    pub progress: Option<AchievementProgress>, // For multi-step achievements
+
+    #[serde(default)]
+    pub unofficial: bool, // Not part of the core set; excluded from completion
+}
+
+/// One unlock from the achievement history. An achievement earned in both
+/// modes has two records.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct UnlockRecord {
+    pub achievement_id: u32,
+    /// Unix timestamp
+    pub earned_at: u64,
+    #[serde(default)]
+    pub hardcore: bool,
+    #[serde(default)]
+    pub points: u32,
+}
+
+/// A player of a shared multiplayer session and the RA user they play as
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SessionPlayer {
+    pub slot: u8,
+    pub name: String,
+    /// The logged-in account, whose unlocks reach RA
+    #[serde(default)]
+    pub account: bool,
+}
 
+/// A daily or weekly challenge set with its streak
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ChallengeSetInfo {
+    /// "daily" or "weekly"
+    pub period: String,
+    /// Unix timestamp the set expires
+    pub ends_at: u64,
     #[serde(default)]
-    pub earned_at: Option<u64>,  // Unix timestamp when earned
+    pub streak: u32,
+    #[serde(default)]
+    pub best_streak: u32,
+    pub challenges: Vec<ChallengeInfo>,
+}
 
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ChallengeInfo {
+    pub achievement_id: u32,
+    pub title: String,
     #[serde(default)]
-    pub progress: Option<AchievementProgress>,  // For multi-step achievements
+    pub description: Option<String>,
+    pub game_title: String,
+    #[serde(default)]
+    pub points: u32,
+    #[serde(default)]
+    pub rarity_percent: Option<f32>,
+    #[serde(default)]
+    pub completed: bool,
 }
 
 /// Progress tracking for multi-step achievements
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct AchievementProgress {
     pub current: u32,
     pub target: u32,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 pub enum ToastStyle {
     Info,
@@ -123,57 +301,153 @@ pub enum ToastStyle {
 pub enum OverlayScreen {
     Main,
     Settings,
+    RaLogin, // RetroAchievements login with on-screen keyboard
     Achievements,
-    Performance,        // Performance monitoring
-    Playtime,           // Playtime tracking
+    AchievementHints, // Community hints for the selected achievement
+    FriendCompare,    // Achievements side by side with a friend's
+    AchievementStats, // Unlock heatmap, sessions, rarity and completion estimate
+    Challenges,       // Daily/weekly challenges and streaks
+    Performance,      // Performance monitoring
+    Playtime,         // Playtime tracking
+    Speedrun,         // Speedrun timer and splits
+    Cheats,           // Per-game cheat codes (softcore only)
+    GameControls,     // The running game's button mapping from its cart
+    Music,            // Jukebox: tracks, playlists, volume and ducking
     // Controller menu screens
     Controllers,        // Main controller menu
     BluetoothPairing,   // Find and pair Bluetooth controllers
     ControllerAssign,   // Assign controllers to players
     GamepadTester,      // Test gamepad inputs
+    ControllerSettings, // Deadzones, trigger threshold and stick drift calibration
     HotkeySettings,     // Configure hotkey bindings
+    Macros,             // Record and replay input macros (kazeta-input)
     // Menu customization
-    MenuCustomization,  // Customize main menu items
-    ThemeSelection,     // Select overlay theme
+    MenuCustomization,    // Customize main menu items
+    QuickActions,         // Radial quick actions palette (hold Guide)
+    QuickActionsSettings, // Choose and order the palette's actions
+    IpcRules,             // Loaded message rules and what they matched
+    DebugConsole,         // Live IPC message log and test message injection (hidden)
+    ThemeSelection,       // Select overlay theme
+    ThemePreview,         // Preview a theme with accessibility options before applying
+    ThemeEditor,          // Edit a theme's colors and save it as a custom theme
     // Quit confirmation
-    QuitConfirm,        // Confirm quit to BIOS
+    QuitConfirm, // Confirm quit to BIOS
+    QuitSaving,  // Waiting for the game to save before it's closed
+    // Global search
+    Search,    // Search games, achievements and settings
+    HudLayout, // Move and resize the in-game HUD widgets
+}
+
+/// Counters describing IPC traffic, used to spot dropped messages under bursts
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct IpcStats {
+    pub received: u64,
+    pub dropped: u64,
+    pub parse_errors: u64,
+    pub peak_queue_len: usize,
+    /// Bytes of every line read, valid or not
+    pub bytes_received: u64,
+    /// Longest single line, e.g. a full achievement list
+    pub largest_message: usize,
+    /// Frames that hit the per-frame message cap and left the rest for later
+    pub deferred_frames: u64,
+}
+
+/// A parsed message along with the envelope fields the message rules look at.
+/// Clients may put an optional `"source"` next to `"type"` to say who sent it,
+/// e.g. `{"type":"show_toast","source":"input-daemon",...}`.
+#[derive(Debug, Clone)]
+pub struct IncomingMessage {
+    pub message: OverlayMessage,
+    /// The message's `type` tag, e.g. `show_toast`
+    pub kind: String,
+    pub source: Option<String>,
+}
+
+/// A client connection that may deliver several newline-delimited messages
+struct IpcConnection {
+    stream: UnixStream,
+    buffer: Vec<u8>,
 }
 
 pub struct IpcServer {
     listener: UnixListener,
+    socket_path: String,
+    connections: Vec<IpcConnection>,
+    queue: VecDeque<IncomingMessage>,
+    max_queue: usize,
+    stats: IpcStats,
 }
 
 impl IpcServer {
     pub fn new() -> Result<Self> {
+        Self::with_socket_path(SOCKET_PATH)
+    }
+
+    /// Create a server bound to a custom socket path
+    pub fn with_socket_path(socket_path: &str) -> Result<Self> {
         // Remove stale socket if it exists
-        if Path::new(SOCKET_PATH).exists() {
-            std::fs::remove_file(SOCKET_PATH)
-                .context("Failed to remove stale socket")?;
+        if Path::new(socket_path).exists() {
+            std::fs::remove_file(socket_path).context("Failed to remove stale socket")?;
         }
 
-        let listener = UnixListener::bind(SOCKET_PATH)
-            .context("Failed to bind Unix socket")?;
+        let listener = UnixListener::bind(socket_path).context("Failed to bind Unix socket")?;
 
         // Set non-blocking mode
         listener
             .set_nonblocking(true)
             .context("Failed to set non-blocking mode")?;
 
-        println!("[IPC] Server listening on {}", SOCKET_PATH);
+        println!("[IPC] Server listening on {}", socket_path);
+
+        Ok(Self {
+            listener,
+            socket_path: socket_path.to_string(),
+            connections: Vec::new(),
+            queue: VecDeque::with_capacity(MAX_QUEUED_MESSAGES),
+            max_queue: MAX_QUEUED_MESSAGES,
+            stats: IpcStats::default(),
+        })
+    }
+
+    /// Traffic counters since the server started
+    pub fn stats(&self) -> IpcStats {
+        self.stats
+    }
 
-        Ok(Self { listener })
+    /// Descriptors that become readable when there is IPC work to do: the
+    /// listener (new clients) and every open connection
+    pub fn raw_fds(&self) -> Vec<RawFd> {
+        std::iter::once(self.listener.as_raw_fd())
+            .chain(self.connections.iter().map(|c| c.stream.as_raw_fd()))
+            .collect()
     }
 
+    /// Accept new connections, read every complete line that has arrived and
+    /// hand back all queued messages. Connections stay open across frames so a
+    /// client can stream several messages without reconnecting.
+    #[allow(dead_code)] // The daemon uses poll_incoming; the mock still uses this
     pub fn poll_messages(&mut self) -> Vec<OverlayMessage> {
-        let mut messages = Vec::new();
+        self.poll_incoming()
+            .into_iter()
+            .map(|m| m.message)
+            .collect()
+    }
 
+    /// Like `poll_messages`, but keeps each message's type and source
+    pub fn poll_incoming(&mut self) -> Vec<IncomingMessage> {
         // Accept all pending connections
         loop {
             match self.listener.accept() {
                 Ok((stream, _)) => {
-                    if let Some(msg) = Self::read_message(stream) {
-                        messages.push(msg);
+                    if let Err(e) = stream.set_nonblocking(true) {
+                        eprintln!("[IPC] Failed to set connection non-blocking: {}", e);
+                        continue;
                     }
+                    self.connections.push(IpcConnection {
+                        stream,
+                        buffer: Vec::new(),
+                    });
                 }
                 Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => {
                     // No more connections to accept
@@ -186,40 +460,124 @@ impl IpcServer {
             }
         }
 
-        messages
-    }
+        // Read from every open connection, dropping the ones that closed
+        let mut connections = std::mem::take(&mut self.connections);
+        connections.retain_mut(|conn| {
+            let open = Self::read_available(conn);
+            for line in Self::take_lines(&mut conn.buffer, !open) {
+                self.parse_line(&line);
+            }
+            open
+        });
+        self.connections = connections;
 
-    fn read_message(stream: UnixStream) -> Option<OverlayMessage> {
-        let reader = BufReader::new(stream);
+        // Swap the queue out so new messages go into a fresh buffer
+        std::mem::take(&mut self.queue).into()
+    }
 
-        for line in reader.lines() {
-            match line {
-                Ok(line) => {
-                    match serde_json::from_str::<OverlayMessage>(&line) {
-                        Ok(msg) => {
-                            println!("[IPC] Received message: {:?}", msg);
-                            return Some(msg);
-                        }
-                        Err(e) => {
-                            eprintln!("[IPC] Failed to parse message: {} - Error: {}", line, e);
-                        }
+    /// Read whatever is available without blocking. Returns false once the
+    /// connection is closed or misbehaving.
+    fn read_available(conn: &mut IpcConnection) -> bool {
+        let mut chunk = [0u8; 4096];
+        loop {
+            match conn.stream.read(&mut chunk) {
+                Ok(0) => return false,
+                Ok(n) => {
+                    conn.buffer.extend_from_slice(&chunk[..n]);
+                    if conn.buffer.len() > MAX_LINE_BYTES && !conn.buffer.contains(&b'\n') {
+                        eprintln!(
+                            "[IPC] Dropping connection: line exceeds {} bytes",
+                            MAX_LINE_BYTES
+                        );
+                        conn.buffer.clear();
+                        return false;
                     }
                 }
+                Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => return true,
+                Err(ref e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
                 Err(e) => {
-                    eprintln!("[IPC] Error reading line: {}", e);
-                    break;
+                    eprintln!("[IPC] Error reading from connection: {}", e);
+                    return false;
                 }
             }
         }
+    }
+
+    /// Split complete lines off the front of the buffer. When the connection is
+    /// closing, a trailing line without a newline is also returned.
+    fn take_lines(buffer: &mut Vec<u8>, closing: bool) -> Vec<String> {
+        let mut lines = Vec::new();
+        while let Some(pos) = buffer.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = buffer.drain(..=pos).collect();
+            lines.push(String::from_utf8_lossy(&line[..pos]).into_owned());
+        }
+        if closing && !buffer.is_empty() {
+            lines.push(String::from_utf8_lossy(buffer).into_owned());
+            buffer.clear();
+        }
+        lines
+    }
+
+    fn parse_line(&mut self, line: &str) {
+        let line = line.trim();
+        if line.is_empty() {
+            return;
+        }
+        self.stats.bytes_received += line.len() as u64;
+        self.stats.largest_message = self.stats.largest_message.max(line.len());
+
+        match Self::parse_incoming(line) {
+            Ok(msg) => {
+                println!("[IPC] Received message: {:?}", msg.message);
+                self.enqueue(msg);
+            }
+            Err(e) => {
+                self.stats.parse_errors += 1;
+                eprintln!("[IPC] Failed to parse message: {} - Error: {}", line, e);
+            }
+        }
+    }
+
+    /// Parse one line, picking the envelope fields out before the message itself
+    pub fn parse_incoming(line: &str) -> serde_json::Result<IncomingMessage> {
+        let value: serde_json::Value = serde_json::from_str(line)?;
+        let kind = value
+            .get("type")
+            .and_then(|t| t.as_str())
+            .unwrap_or_default()
+            .to_string();
+        let source = value
+            .get("source")
+            .and_then(|s| s.as_str())
+            .map(str::to_string);
+        let message = serde_json::from_value(value)?;
+        Ok(IncomingMessage {
+            message,
+            kind,
+            source,
+        })
+    }
 
-        None
+    /// Push a message, discarding the oldest one when the queue is full
+    fn enqueue(&mut self, msg: IncomingMessage) {
+        self.stats.received += 1;
+        if self.queue.len() >= self.max_queue {
+            self.queue.pop_front();
+            self.stats.dropped += 1;
+            eprintln!(
+                "[IPC] Queue full, dropped oldest message ({} dropped total)",
+                self.stats.dropped
+            );
+        }
+        self.queue.push_back(msg);
+        self.stats.peak_queue_len = self.stats.peak_queue_len.max(self.queue.len());
     }
 }
 
 impl Drop for IpcServer {
     fn drop(&mut self) {
         // Clean up socket on exit
-        let _ = std::fs::remove_file(SOCKET_PATH);
+        let _ = std::fs::remove_file(&self.socket_path);
         println!("[IPC] Cleaned up socket");
     }
 }
@@ -248,4 +606,257 @@ mod tests {
             _ => panic!("Wrong message type"),
         }
     }
+
+    #[test]
+    fn test_game_list_from_bios() {
+        // Shape written by the BIOS's notify_game_list
+        let json = r#"{"type":"game_list","games":[{"cart_id":"celeste","name":"Celeste"}]}"#;
+        match serde_json::from_str::<OverlayMessage>(json).unwrap() {
+            OverlayMessage::GameList { games } => {
+                assert_eq!(
+                    games,
+                    vec![GameEntry {
+                        cart_id: "celeste".to_string(),
+                        name: "Celeste".to_string()
+                    }]
+                );
+            }
+            _ => panic!("Wrong message type"),
+        }
+    }
+
+    #[test]
+    fn test_game_controls_from_bios() {
+        // Shape written by the BIOS's notify_game_controls
+        let json = r#"{"type":"game_controls","cart_id":"celeste","controls":[{"button":"a","label":"Jump"}]}"#;
+        match serde_json::from_str::<OverlayMessage>(json).unwrap() {
+            OverlayMessage::GameControls { cart_id, controls } => {
+                assert_eq!(cart_id, "celeste");
+                assert_eq!(
+                    controls,
+                    vec![ControlHint {
+                        button: "a".to_string(),
+                        label: "Jump".to_string()
+                    }]
+                );
+            }
+            _ => panic!("Wrong message type"),
+        }
+    }
+
+    #[test]
+    fn test_progress_measure_from_ra_cli() {
+        // Shape written by `kazeta-ra notify-progress`
+        let json =
+            r#"{"type":"ra_progress_measure","achievement_id":42,"current":45,"target":100}"#;
+        match serde_json::from_str::<OverlayMessage>(json).unwrap() {
+            OverlayMessage::RaProgressMeasure {
+                achievement_id,
+                current,
+                target,
+            } => {
+                assert_eq!((achievement_id, current, target), (42, 45, 100));
+            }
+            _ => panic!("Wrong message type"),
+        }
+    }
+
+    #[test]
+    fn test_mastery_from_ra_cli() {
+        // Shape written by `kazeta-ra notify-achievement` on the last unlock
+        let json = r#"{"type":"ra_mastery","game_title":"Celeste","hardcore":true,"total_achievements":30,"points":400,"completion_secs":null}"#;
+        match serde_json::from_str::<OverlayMessage>(json).unwrap() {
+            OverlayMessage::RaMastery {
+                game_title,
+                hardcore,
+                total_achievements,
+                points,
+                completion_secs,
+            } => {
+                assert_eq!(game_title, "Celeste");
+                assert!(hardcore);
+                assert_eq!(
+                    (total_achievements, points, completion_secs),
+                    (30, 400, None)
+                );
+            }
+            _ => panic!("Wrong message type"),
+        }
+    }
+
+    #[test]
+    fn test_player_unlock_from_ra_cli() {
+        // Shapes written by `kazeta-ra session start --player` and `notify-achievement --player`
+        let json = r#"{"type":"ra_session_players","players":[{"slot":1,"name":"alice","account":true},{"slot":2,"name":"bob","account":false}]}"#;
+        match serde_json::from_str::<OverlayMessage>(json).unwrap() {
+            OverlayMessage::RaSessionPlayers { players } => {
+                assert_eq!(players.len(), 2);
+                assert!(players[0].account && !players[1].account);
+            }
+            _ => panic!("Wrong message type"),
+        }
+
+        let json = r#"{"type":"ra_achievement_unlocked","achievement_id":7,"title":"Link Up","description":null,"points":5,"is_hardcore":false,"player":{"slot":2,"name":"bob","account":false}}"#;
+        match serde_json::from_str::<OverlayMessage>(json).unwrap() {
+            OverlayMessage::RaAchievementUnlocked {
+                achievement_id,
+                player,
+                ..
+            } => {
+                assert_eq!(achievement_id, 7);
+                assert_eq!(player.unwrap().name, "bob");
+            }
+            _ => panic!("Wrong message type"),
+        }
+    }
+
+    #[test]
+    fn test_pointer_from_input_daemon() {
+        // Shape written by kazeta-input's pointer forwarding
+        let json = r#"{"type":"pointer","x":0.25,"y":0.5,"action":"press"}"#;
+        match serde_json::from_str::<OverlayMessage>(json).unwrap() {
+            OverlayMessage::Pointer { x, y, action } => {
+                assert_eq!((x, y, action), (0.25, 0.5, PointerAction::Press));
+            }
+            _ => panic!("Wrong message type"),
+        }
+    }
+
+    fn test_socket_path(name: &str) -> String {
+        format!(
+            "/tmp/kazeta-overlay-test-{}-{}.sock",
+            name,
+            std::process::id()
+        )
+    }
+
+    fn toast(message: &str) -> OverlayMessage {
+        OverlayMessage::ShowToast {
+            message: message.to_string(),
+            icon: None,
+            duration_ms: 1000,
+            style: ToastStyle::Info,
+        }
+    }
+
+    #[test]
+    fn test_multiple_messages_per_connection() {
+        let path = test_socket_path("multi");
+        let mut server = IpcServer::with_socket_path(&path).unwrap();
+
+        let mut stream = UnixStream::connect(&path).unwrap();
+        for i in 0..3 {
+            writeln!(
+                stream,
+                "{}",
+                serde_json::to_string(&toast(&format!("toast {}", i))).unwrap()
+            )
+            .unwrap();
+        }
+        // Last message has no trailing newline; it is flushed when the client closes
+        write!(
+            stream,
+            "{}",
+            serde_json::to_string(&OverlayMessage::HideOverlay).unwrap()
+        )
+        .unwrap();
+        drop(stream);
+
+        let messages = server.poll_messages();
+        assert_eq!(messages.len(), 4);
+        assert!(matches!(messages[3], OverlayMessage::HideOverlay));
+        assert_eq!(server.stats().received, 4);
+    }
+
+    #[test]
+    fn test_partial_lines_across_polls() {
+        let path = test_socket_path("partial");
+        let mut server = IpcServer::with_socket_path(&path).unwrap();
+
+        let json = serde_json::to_string(&toast("split")).unwrap();
+        let (first, second) = json.split_at(json.len() / 2);
+
+        let mut stream = UnixStream::connect(&path).unwrap();
+        stream.write_all(first.as_bytes()).unwrap();
+        assert!(server.poll_messages().is_empty());
+
+        stream.write_all(second.as_bytes()).unwrap();
+        stream.write_all(b"\n").unwrap();
+        assert_eq!(server.poll_messages().len(), 1);
+    }
+
+    #[test]
+    fn test_raw_fds_track_open_connections() {
+        let path = test_socket_path("fds");
+        let mut server = IpcServer::with_socket_path(&path).unwrap();
+        assert_eq!(server.raw_fds().len(), 1);
+
+        let stream = UnixStream::connect(&path).unwrap();
+        server.poll_messages();
+        assert_eq!(server.raw_fds().len(), 2);
+
+        drop(stream);
+        server.poll_messages();
+        assert_eq!(server.raw_fds().len(), 1);
+    }
+
+    #[test]
+    fn test_queue_overflow_drops_oldest() {
+        let path = test_socket_path("overflow");
+        let mut server = IpcServer::with_socket_path(&path).unwrap();
+        server.max_queue = 2;
+
+        server.parse_line(&serde_json::to_string(&toast("a")).unwrap());
+        server.parse_line(&serde_json::to_string(&toast("b")).unwrap());
+        server.parse_line(&serde_json::to_string(&toast("c")).unwrap());
+        server.parse_line("not json");
+
+        let stats = server.stats();
+        assert_eq!(stats.received, 3);
+        assert_eq!(stats.dropped, 1);
+        assert_eq!(stats.parse_errors, 1);
+        assert_eq!(stats.peak_queue_len, 2);
+
+        let messages = server.poll_messages();
+        assert_eq!(messages.len(), 2);
+        match &messages[0] {
+            OverlayMessage::ShowToast { message, .. } => assert_eq!(message, "b"),
+            _ => panic!("Wrong message type"),
+        }
+    }
+
+    #[test]
+    fn test_payload_size_metrics() {
+        let path = test_socket_path("sizes");
+        let mut server = IpcServer::with_socket_path(&path).unwrap();
+
+        let small = serde_json::to_string(&toast("a")).unwrap();
+        let large = serde_json::to_string(&toast(&"x".repeat(5000))).unwrap();
+        server.parse_line(&small);
+        server.parse_line(&large);
+        server.parse_line("not json");
+
+        let stats = server.stats();
+        assert_eq!(
+            stats.bytes_received,
+            (small.len() + large.len() + "not json".len()) as u64
+        );
+        assert_eq!(stats.largest_message, large.len());
+    }
+
+    #[test]
+    fn test_incoming_keeps_type_and_source() {
+        let path = test_socket_path("source");
+        let mut server = IpcServer::with_socket_path(&path).unwrap();
+
+        server.parse_line(r#"{"type":"hide_overlay","source":"input-daemon"}"#);
+        server.parse_line(r#"{"type":"hide_overlay"}"#);
+
+        let incoming = server.poll_incoming();
+        assert_eq!(incoming.len(), 2);
+        assert_eq!(incoming[0].kind, "hide_overlay");
+        assert_eq!(incoming[0].source.as_deref(), Some("input-daemon"));
+        assert!(matches!(incoming[0].message, OverlayMessage::HideOverlay));
+        assert_eq!(incoming[1].source, None);
+    }
 }
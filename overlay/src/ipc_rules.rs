@@ -0,0 +1,320 @@
+//! User-defined rules for incoming IPC messages
+//!
+//! `~/.local/share/kazeta-plus/overlay/ipc_rules.toml` holds a list of rules.
+//! Each message is checked against them in order and the first match decides
+//! what happens to it:
+//!
+//! ```toml
+//! [[rule]]
+//! name = "No input toasts in the tester"
+//! type = "show_toast"
+//! source = "input-daemon"
+//! screen = "gamepad_tester"
+//! action = "drop"
+//! ```
+//!
+//! `type` is the message's `type`, `source` the optional `source` a client
+//! sends next to it, and `screen` the overlay screen (only while the overlay
+//! is open). A condition left out matches anything. `drop` ignores the
+//! message, `quiet` handles it but sends its toasts to the notification
+//! history only, and `allow` stops further rules from matching. The file is
+//! re-read whenever it changes.
+
+use crate::ipc::OverlayScreen;
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::VecDeque;
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, Instant, SystemTime};
+
+/// How often the rules file's modification time is checked
+const RELOAD_CHECK_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Matches kept for the debug view
+const RECENT_MATCHES: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleAction {
+    Allow,
+    Drop,
+    Quiet,
+}
+
+impl RuleAction {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Allow => "allow",
+            Self::Drop => "drop",
+            Self::Quiet => "quiet",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Rule {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default, rename = "type")]
+    pub kind: Option<String>,
+    #[serde(default)]
+    pub source: Option<String>,
+    #[serde(default)]
+    pub screen: Option<OverlayScreen>,
+    pub action: RuleAction,
+}
+
+impl Rule {
+    fn matches(&self, kind: &str, source: Option<&str>, screen: Option<OverlayScreen>) -> bool {
+        self.kind.as_deref().is_none_or(|k| k == kind)
+            && self.source.as_deref().is_none_or(|s| Some(s) == source)
+            && self.screen.is_none_or(|s| Some(s) == screen)
+    }
+
+    /// Name for the debug view, falling back to the rule's conditions
+    pub fn label(&self) -> String {
+        if let Some(name) = &self.name {
+            return name.clone();
+        }
+        let mut parts = Vec::new();
+        if let Some(kind) = &self.kind {
+            parts.push(format!("type={}", kind));
+        }
+        if let Some(source) = &self.source {
+            parts.push(format!("source={}", source));
+        }
+        if let Some(screen) = &self.screen {
+            parts.push(format!("screen={:?}", screen));
+        }
+        if parts.is_empty() {
+            "any message".to_string()
+        } else {
+            parts.join(" ")
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct RulesFile {
+    #[serde(default, rename = "rule")]
+    rules: Vec<Rule>,
+}
+
+/// A message that matched a rule, for the debug view
+#[derive(Debug, Clone)]
+pub struct RuleMatch {
+    pub rule: String,
+    pub kind: String,
+    pub source: Option<String>,
+    pub action: RuleAction,
+    pub at: Instant,
+}
+
+pub struct IpcRules {
+    path: PathBuf,
+    rules: Vec<Rule>,
+    /// Match count per rule, reset when the file is reloaded
+    hits: Vec<u64>,
+    modified: Option<SystemTime>,
+    last_check: Instant,
+    recent: VecDeque<RuleMatch>,
+    /// Why the last load failed; the previous rules stay in effect
+    error: Option<String>,
+}
+
+impl IpcRules {
+    pub fn new() -> Result<Self> {
+        let data_dir =
+            dirs::data_local_dir().context("Could not determine local data directory")?;
+        let path = data_dir
+            .join("kazeta-plus")
+            .join("overlay")
+            .join("ipc_rules.toml");
+
+        let mut rules = Self {
+            path,
+            rules: Vec::new(),
+            hits: Vec::new(),
+            modified: None,
+            last_check: Instant::now(),
+            recent: VecDeque::with_capacity(RECENT_MATCHES),
+            error: None,
+        };
+        rules.reload();
+        Ok(rules)
+    }
+
+    fn parse(contents: &str) -> Result<Vec<Rule>> {
+        let file: RulesFile = toml::from_str(contents).context("Failed to parse message rules")?;
+        Ok(file.rules)
+    }
+
+    fn set_rules(&mut self, rules: Vec<Rule>) {
+        self.hits = vec![0; rules.len()];
+        self.rules = rules;
+    }
+
+    /// Re-read the rules file. A missing file means no rules.
+    pub fn reload(&mut self) {
+        self.modified = fs::metadata(&self.path).and_then(|m| m.modified()).ok();
+        let contents = match fs::read_to_string(&self.path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                self.set_rules(Vec::new());
+                self.error = None;
+                return;
+            }
+            Err(e) => {
+                self.error = Some(format!("Failed to read {}: {}", self.path.display(), e));
+                return;
+            }
+        };
+        match Self::parse(&contents) {
+            Ok(rules) => {
+                println!("[Rules] Loaded {} message rule(s)", rules.len());
+                self.set_rules(rules);
+                self.error = None;
+            }
+            Err(e) => {
+                eprintln!("[Rules] {:#}", e);
+                self.error = Some(format!("{:#}", e));
+            }
+        }
+    }
+
+    /// Reload if the file changed since it was last read. Cheap to call every
+    /// frame; the file is only looked at every couple of seconds.
+    pub fn reload_if_changed(&mut self) {
+        if self.last_check.elapsed() < RELOAD_CHECK_INTERVAL {
+            return;
+        }
+        self.last_check = Instant::now();
+        let modified = fs::metadata(&self.path).and_then(|m| m.modified()).ok();
+        if modified != self.modified {
+            self.reload();
+        }
+    }
+
+    /// What to do with a message. `screen` is the open overlay screen, or None
+    /// while the overlay is hidden.
+    pub fn evaluate(
+        &mut self,
+        kind: &str,
+        source: Option<&str>,
+        screen: Option<OverlayScreen>,
+    ) -> RuleAction {
+        let Some(index) = self
+            .rules
+            .iter()
+            .position(|r| r.matches(kind, source, screen))
+        else {
+            return RuleAction::Allow;
+        };
+        let rule = &self.rules[index];
+        self.hits[index] += 1;
+        if self.recent.len() >= RECENT_MATCHES {
+            self.recent.pop_back();
+        }
+        self.recent.push_front(RuleMatch {
+            rule: rule.label(),
+            kind: kind.to_string(),
+            source: source.map(str::to_string),
+            action: rule.action,
+            at: Instant::now(),
+        });
+        rule.action
+    }
+
+    pub fn path(&self) -> &PathBuf {
+        &self.path
+    }
+
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    /// Loaded rules with how many messages each has matched
+    pub fn rules(&self) -> impl Iterator<Item = (&Rule, u64)> {
+        self.rules.iter().zip(self.hits.iter().copied())
+    }
+
+    /// Latest matches, newest first
+    pub fn recent(&self) -> impl Iterator<Item = &RuleMatch> {
+        self.recent.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules_from(contents: &str) -> IpcRules {
+        let mut rules = IpcRules {
+            path: PathBuf::from("/nonexistent/ipc_rules.toml"),
+            rules: Vec::new(),
+            hits: Vec::new(),
+            modified: None,
+            last_check: Instant::now(),
+            recent: VecDeque::new(),
+            error: None,
+        };
+        rules.set_rules(IpcRules::parse(contents).unwrap());
+        rules
+    }
+
+    #[test]
+    fn test_first_matching_rule_wins() {
+        let mut rules = rules_from(
+            r#"
+            [[rule]]
+            name = "Tester quiet"
+            type = "show_toast"
+            source = "input-daemon"
+            screen = "gamepad_tester"
+            action = "drop"
+
+            [[rule]]
+            type = "unlock_achievement"
+            action = "quiet"
+
+            [[rule]]
+            type = "unlock_achievement"
+            action = "drop"
+            "#,
+        );
+
+        let tester = Some(OverlayScreen::GamepadTester);
+        assert_eq!(
+            rules.evaluate("show_toast", Some("input-daemon"), tester),
+            RuleAction::Drop
+        );
+        // Only on that screen, and only from that source
+        assert_eq!(
+            rules.evaluate("show_toast", Some("input-daemon"), None),
+            RuleAction::Allow
+        );
+        assert_eq!(
+            rules.evaluate("show_toast", None, tester),
+            RuleAction::Allow
+        );
+        assert_eq!(
+            rules.evaluate("unlock_achievement", None, None),
+            RuleAction::Quiet
+        );
+
+        let hits: Vec<u64> = rules.rules().map(|(_, hits)| hits).collect();
+        assert_eq!(hits, vec![1, 1, 0]);
+        let recent: Vec<String> = rules.recent().map(|m| m.rule.clone()).collect();
+        assert_eq!(recent, vec!["type=unlock_achievement", "Tester quiet"]);
+    }
+
+    #[test]
+    fn test_invalid_rules_are_rejected() {
+        assert!(IpcRules::parse("[[rule]]\naction = \"explode\"").is_err());
+        assert!(IpcRules::parse("[[rule]]\ntype = \"show_toast\"").is_err());
+        assert!(IpcRules::parse("[[rule]]\nscren = \"settings\"\naction = \"drop\"").is_err());
+        assert!(IpcRules::parse("").unwrap().is_empty());
+    }
+}
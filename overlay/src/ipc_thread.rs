@@ -0,0 +1,201 @@
+//! IPC on its own thread
+//!
+//! Accepting connections, reading and parsing messages happens here instead
+//! of on the render thread, so a large payload (a 300-achievement list is a
+//! few hundred KB of JSON) never costs a frame. Parsed messages reach the
+//! main loop over a channel, and the main loop takes at most
+//! `MAX_MESSAGES_PER_FRAME` each frame so a burst is spread over a few frames
+//! rather than stalling one.
+
+use crate::ipc::{IncomingMessage, IpcServer, IpcStats};
+use crate::wake;
+use anyhow::{Context, Result};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// Messages handed to the main loop per frame; the rest wait for the next one
+const MAX_MESSAGES_PER_FRAME: usize = 16;
+
+/// Messages in flight to the main loop before the thread holds on to them
+const CHANNEL_CAPACITY: usize = 64;
+
+/// Messages the thread holds while the main loop is behind; the oldest are
+/// dropped past this, like the server's own queue
+const MAX_HELD_MESSAGES: usize = 256;
+
+/// How often the thread checks for shutdown while no client is talking
+const POLL_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// Retry interval while holding messages the channel had no room for
+const HELD_RETRY: Duration = Duration::from_millis(5);
+
+pub struct IpcThread {
+    receiver: Receiver<IncomingMessage>,
+    stats: Arc<Mutex<IpcStats>>,
+    deferred_frames: u64,
+    running: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl IpcThread {
+    /// Move `server` to a new thread. Bind it on the caller's thread first so
+    /// a socket error is reported at startup.
+    pub fn spawn(server: IpcServer) -> Result<Self> {
+        let (sender, receiver) = mpsc::sync_channel(CHANNEL_CAPACITY);
+        let stats = Arc::new(Mutex::new(IpcStats::default()));
+        let running = Arc::new(AtomicBool::new(true));
+        let handle = {
+            let stats = stats.clone();
+            let running = running.clone();
+            thread::Builder::new()
+                .name("ipc".to_string())
+                .spawn(move || run(server, sender, stats, running))
+                .context("Failed to start IPC thread")?
+        };
+
+        Ok(Self {
+            receiver,
+            stats,
+            deferred_frames: 0,
+            running,
+            handle: Some(handle),
+        })
+    }
+
+    /// Messages parsed since the last call, at most `MAX_MESSAGES_PER_FRAME`
+    pub fn poll(&mut self) -> Vec<IncomingMessage> {
+        let messages: Vec<IncomingMessage> = self
+            .receiver
+            .try_iter()
+            .take(MAX_MESSAGES_PER_FRAME)
+            .collect();
+        if messages.len() == MAX_MESSAGES_PER_FRAME {
+            // More may be waiting; keep the loop from going idle before it takes them
+            self.deferred_frames += 1;
+            wake::wake();
+        }
+        messages
+    }
+
+    /// Traffic counters since the server started
+    pub fn stats(&self) -> IpcStats {
+        IpcStats {
+            deferred_frames: self.deferred_frames,
+            ..*self.stats.lock().unwrap()
+        }
+    }
+}
+
+impl Drop for IpcThread {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+fn run(
+    mut server: IpcServer,
+    sender: SyncSender<IncomingMessage>,
+    stats: Arc<Mutex<IpcStats>>,
+    running: Arc<AtomicBool>,
+) {
+    let mut held: VecDeque<IncomingMessage> = VecDeque::new();
+    let mut dropped = 0;
+    let mut peak_held = 0;
+
+    while running.load(Ordering::Relaxed) {
+        held.extend(server.poll_incoming());
+
+        // Hand over as much as the main loop has room for
+        let mut sent = false;
+        while let Some(message) = held.pop_front() {
+            match sender.try_send(message) {
+                Ok(()) => sent = true,
+                Err(TrySendError::Full(message)) => {
+                    held.push_front(message);
+                    break;
+                }
+                Err(TrySendError::Disconnected(_)) => return,
+            }
+        }
+        while held.len() > MAX_HELD_MESSAGES {
+            held.pop_front();
+            dropped += 1;
+            eprintln!(
+                "[IPC] Main loop is behind, dropped oldest message ({} dropped total)",
+                dropped
+            );
+        }
+        peak_held = peak_held.max(held.len());
+
+        let server_stats = server.stats();
+        *stats.lock().unwrap() = IpcStats {
+            dropped: server_stats.dropped + dropped,
+            peak_queue_len: server_stats.peak_queue_len.max(peak_held),
+            ..server_stats
+        };
+        if sent {
+            wake::wake();
+        }
+
+        let timeout = if held.is_empty() {
+            POLL_TIMEOUT
+        } else {
+            HELD_RETRY
+        };
+        wake::wait_readable(&server.raw_fds(), timeout);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ipc::{OverlayMessage, ToastStyle};
+    use std::io::Write;
+    use std::os::unix::net::UnixStream;
+    use std::time::Instant;
+
+    #[test]
+    fn test_bursts_are_spread_over_frames() {
+        let path = format!(
+            "/tmp/kazeta-overlay-test-thread-{}.sock",
+            std::process::id()
+        );
+        let mut ipc = IpcThread::spawn(IpcServer::with_socket_path(&path).unwrap()).unwrap();
+
+        let mut stream = UnixStream::connect(&path).unwrap();
+        for i in 0..40 {
+            let toast = OverlayMessage::ShowToast {
+                message: format!("toast {}", i),
+                icon: None,
+                duration_ms: 1000,
+                style: ToastStyle::Info,
+            };
+            writeln!(stream, "{}", serde_json::to_string(&toast).unwrap()).unwrap();
+        }
+        drop(stream);
+
+        let mut received = Vec::new();
+        let start = Instant::now();
+        while received.len() < 40 && start.elapsed() < Duration::from_secs(5) {
+            let frame = ipc.poll();
+            assert!(frame.len() <= MAX_MESSAGES_PER_FRAME);
+            received.extend(frame);
+            thread::sleep(Duration::from_millis(5));
+        }
+
+        assert_eq!(received.len(), 40);
+        match &received[39].message {
+            OverlayMessage::ShowToast { message, .. } => assert_eq!(message, "toast 39"),
+            _ => panic!("Wrong message type"),
+        }
+        assert_eq!(ipc.stats().received, 40);
+        assert!(ipc.stats().bytes_received > 0);
+    }
+}
@@ -1,6 +1,10 @@
 // Re-export the client and IPC types for external use
 pub mod client;
+#[cfg(feature = "widgets")]
+pub mod gamepad_tester;
 pub mod ipc;
+#[cfg(any(test, feature = "mock"))]
+pub mod mock;
 
-pub use client::OverlayClient;
-pub use ipc::{OverlayMessage, OverlayScreen, ToastStyle, AchievementInfo};
+pub use client::{AsyncOverlayClient, OverlayApi, OverlayClient};
+pub use ipc::{AchievementInfo, OverlayMessage, OverlayScreen, ToastStyle};
@@ -0,0 +1,362 @@
+//! RetroAchievements login from inside the overlay
+//!
+//! Username and API key are typed with an on-screen keyboard, verified against
+//! the RA API in a background thread and stored with `CredentialManager`, so
+//! logging in no longer needs SSH and the kazeta-ra CLI.
+
+use crate::input::ControllerInput;
+use anyhow::{bail, Result};
+use kazeta_ra::{CredentialManager, Credentials, RAClient};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+
+/// Character rows of the on-screen keyboard
+pub const KEYBOARD_ROWS: &[&str] = &["1234567890", "qwertyuiop", "asdfghjkl-", "zxcvbnm_.@"];
+
+/// Keys per row; every character row has this many
+pub const KEYBOARD_COLUMNS: usize = 10;
+
+/// Bottom row of wide keys, each spanning a range of columns
+pub const ACTION_KEYS: &[(Key, usize, usize)] = &[
+    (Key::Shift, 0, 3),
+    (Key::Delete, 3, 7),
+    (Key::Done, 7, KEYBOARD_COLUMNS),
+];
+
+/// Longest value accepted for either field (RA API keys are 32 characters)
+const MAX_FIELD_LEN: usize = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Key {
+    Char(char),
+    Shift,
+    Delete,
+    Done,
+}
+
+impl Key {
+    pub fn label(&self, shift: bool) -> String {
+        match self {
+            Key::Char(c) if shift => c.to_ascii_uppercase().to_string(),
+            Key::Char(c) => c.to_string(),
+            Key::Shift => "SHIFT".to_string(),
+            Key::Delete => "DEL".to_string(),
+            Key::Done => "DONE".to_string(),
+        }
+    }
+}
+
+/// Controller-driven keyboard editing a single line of text
+pub struct OnScreenKeyboard {
+    pub row: usize,
+    pub col: usize,
+    pub shift: bool,
+}
+
+impl OnScreenKeyboard {
+    pub fn new() -> Self {
+        Self {
+            row: 1,
+            col: 0,
+            shift: false,
+        }
+    }
+
+    fn action_row(&self) -> bool {
+        self.row == KEYBOARD_ROWS.len()
+    }
+
+    fn action_index(&self) -> usize {
+        ACTION_KEYS
+            .iter()
+            .position(|&(_, start, end)| (start..end).contains(&self.col))
+            .unwrap_or(0)
+    }
+
+    /// Key under the cursor
+    pub fn selected_key(&self) -> Key {
+        if self.action_row() {
+            ACTION_KEYS[self.action_index()].0
+        } else {
+            let c = KEYBOARD_ROWS[self.row].chars().nth(self.col).unwrap_or(' ');
+            Key::Char(if self.shift {
+                c.to_ascii_uppercase()
+            } else {
+                c
+            })
+        }
+    }
+
+    /// Apply one input to `text`. Returns true when the keyboard should close.
+    pub fn handle_input(&mut self, input: ControllerInput, text: &mut String) -> bool {
+        let rows = KEYBOARD_ROWS.len() + 1;
+
+        match input {
+            ControllerInput::Up => self.row = (self.row + rows - 1) % rows,
+            ControllerInput::Down => self.row = (self.row + 1) % rows,
+            ControllerInput::Left if self.action_row() => {
+                let index = (self.action_index() + ACTION_KEYS.len() - 1) % ACTION_KEYS.len();
+                self.col = ACTION_KEYS[index].1;
+            }
+            ControllerInput::Right if self.action_row() => {
+                let index = (self.action_index() + 1) % ACTION_KEYS.len();
+                self.col = ACTION_KEYS[index].1;
+            }
+            ControllerInput::Left => {
+                self.col = (self.col + KEYBOARD_COLUMNS - 1) % KEYBOARD_COLUMNS
+            }
+            ControllerInput::Right => self.col = (self.col + 1) % KEYBOARD_COLUMNS,
+            ControllerInput::Secondary => {
+                text.pop();
+            }
+            ControllerInput::LB => self.shift = !self.shift,
+            ControllerInput::Back | ControllerInput::RB => return true,
+            ControllerInput::Select => match self.selected_key() {
+                Key::Char(c) => {
+                    if text.chars().count() < MAX_FIELD_LEN {
+                        text.push(c);
+                    }
+                }
+                Key::Shift => self.shift = !self.shift,
+                Key::Delete => {
+                    text.pop();
+                }
+                Key::Done => return true,
+            },
+            _ => {}
+        }
+        false
+    }
+}
+
+/// Rows of the login form
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoginField {
+    Username,
+    ApiKey,
+    Submit,
+}
+
+const LOGIN_FIELDS: [LoginField; 3] =
+    [LoginField::Username, LoginField::ApiKey, LoginField::Submit];
+
+/// Username of the stored RA credentials, if any
+pub fn stored_username() -> Option<String> {
+    CredentialManager::new()
+        .and_then(|manager| manager.load())
+        .ok()
+        .flatten()
+        .map(|credentials| credentials.username)
+}
+
+/// Remove the stored RA credentials
+pub fn logout() -> Result<()> {
+    CredentialManager::new()?.delete()?;
+    println!("[Login] Credentials removed");
+    Ok(())
+}
+
+/// Check the credentials against RA and store them if they work
+fn verify_and_save(username: String, api_key: String) -> Result<String> {
+    let credentials = Credentials::new(username.clone(), api_key);
+    if !RAClient::new(credentials.clone()).verify_credentials()? {
+        bail!("Invalid username or API key");
+    }
+    CredentialManager::new()?.save(&credentials)?;
+    Ok(username)
+}
+
+/// State of the login screen
+pub struct LoginView {
+    pub username: String,
+    pub api_key: String,
+    pub selected: usize,
+    pub keyboard: Option<OnScreenKeyboard>,
+    pub verifying: bool,
+    pub error: Option<String>,
+    allow_fetch: bool,
+    receiver: Option<Receiver<Result<String, String>>>,
+}
+
+impl LoginView {
+    /// Open the login form, pre-filling the username when switching accounts
+    pub fn open(username: Option<String>) -> Self {
+        let mut view = Self::new(true);
+        view.username = username.unwrap_or_default();
+        view
+    }
+
+    fn new(allow_fetch: bool) -> Self {
+        Self {
+            username: String::new(),
+            api_key: String::new(),
+            selected: 0,
+            keyboard: None,
+            verifying: false,
+            error: None,
+            allow_fetch,
+            receiver: None,
+        }
+    }
+
+    pub fn selected_field(&self) -> LoginField {
+        LOGIN_FIELDS[self.selected]
+    }
+
+    /// Apply one input. Returns true when the user backs out of the screen.
+    pub fn handle_input(&mut self, input: ControllerInput) -> bool {
+        if self.verifying {
+            return false;
+        }
+
+        if let Some(keyboard) = &mut self.keyboard {
+            let text = match LOGIN_FIELDS[self.selected] {
+                LoginField::ApiKey => &mut self.api_key,
+                _ => &mut self.username,
+            };
+            if keyboard.handle_input(input, text) {
+                self.keyboard = None;
+                // Move on to the next row so the form flows top to bottom
+                self.selected = (self.selected + 1).min(LOGIN_FIELDS.len() - 1);
+            }
+            return false;
+        }
+
+        match input {
+            ControllerInput::Up => self.selected = self.selected.saturating_sub(1),
+            ControllerInput::Down => {
+                self.selected = (self.selected + 1).min(LOGIN_FIELDS.len() - 1)
+            }
+            ControllerInput::Select => match self.selected_field() {
+                LoginField::Username | LoginField::ApiKey => {
+                    self.keyboard = Some(OnScreenKeyboard::new());
+                    self.error = None;
+                }
+                LoginField::Submit => self.submit(),
+            },
+            ControllerInput::Back => return true,
+            _ => {}
+        }
+        false
+    }
+
+    fn submit(&mut self) {
+        let username = self.username.trim().to_string();
+        let api_key = self.api_key.trim().to_string();
+        if username.is_empty() || api_key.is_empty() {
+            self.error = Some("Enter a username and API key".to_string());
+            return;
+        }
+        if !self.allow_fetch {
+            return;
+        }
+
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            let result = verify_and_save(username, api_key).map_err(|e| e.to_string());
+            let _ = tx.send(result);
+            crate::wake::wake();
+        });
+
+        self.receiver = Some(rx);
+        self.verifying = true;
+        self.error = None;
+    }
+
+    /// Returns the username once verification succeeded and credentials are stored
+    pub fn poll(&mut self) -> Option<String> {
+        let receiver = self.receiver.as_ref()?;
+
+        let result = match receiver.try_recv() {
+            Ok(result) => result,
+            Err(TryRecvError::Empty) => return None,
+            Err(TryRecvError::Disconnected) => Err("Login stopped unexpectedly".to_string()),
+        };
+        self.receiver = None;
+        self.verifying = false;
+
+        match result {
+            Ok(username) => {
+                println!("[Login] Logged in as {}", username);
+                Some(username)
+            }
+            Err(e) => {
+                eprintln!("[Login] Login failed: {}", e);
+                self.error = Some(e);
+                None
+            }
+        }
+    }
+
+    /// API key as shown on screen: masked except for the last few characters
+    pub fn masked_api_key(&self) -> String {
+        let len = self.api_key.chars().count();
+        let shown = len.saturating_sub(4);
+        self.api_key
+            .chars()
+            .enumerate()
+            .map(|(i, c)| if i < shown { '•' } else { c })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keyboard_types_shifts_and_deletes() {
+        let mut keyboard = OnScreenKeyboard::new();
+        let mut text = String::new();
+
+        // Row 1 starts at 'q'
+        keyboard.handle_input(ControllerInput::Select, &mut text);
+        keyboard.handle_input(ControllerInput::LB, &mut text);
+        keyboard.handle_input(ControllerInput::Right, &mut text);
+        keyboard.handle_input(ControllerInput::Select, &mut text);
+        assert_eq!(text, "qW");
+
+        keyboard.handle_input(ControllerInput::Secondary, &mut text);
+        assert_eq!(text, "q");
+
+        // Up from the top row wraps to the action row
+        keyboard.handle_input(ControllerInput::Up, &mut text);
+        keyboard.handle_input(ControllerInput::Up, &mut text);
+        assert_eq!(keyboard.selected_key(), Key::Shift);
+        keyboard.handle_input(ControllerInput::Right, &mut text);
+        assert_eq!(keyboard.selected_key(), Key::Delete);
+        keyboard.handle_input(ControllerInput::Right, &mut text);
+        assert_eq!(keyboard.selected_key(), Key::Done);
+        assert!(keyboard.handle_input(ControllerInput::Select, &mut text));
+    }
+
+    #[test]
+    fn test_login_form_flow() {
+        let mut view = LoginView::new(false);
+
+        // Type into the username, closing the keyboard moves to the API key
+        view.handle_input(ControllerInput::Select);
+        assert!(view.keyboard.is_some());
+        view.handle_input(ControllerInput::Select);
+        view.handle_input(ControllerInput::Back);
+        assert_eq!(view.username, "q");
+        assert_eq!(view.selected_field(), LoginField::ApiKey);
+
+        // Submitting without an API key is rejected before any request
+        view.handle_input(ControllerInput::Down);
+        view.handle_input(ControllerInput::Select);
+        assert!(view.error.is_some());
+        assert!(!view.verifying);
+
+        assert!(view.handle_input(ControllerInput::Back));
+    }
+
+    #[test]
+    fn test_masked_api_key() {
+        let mut view = LoginView::new(false);
+        view.api_key = "abcdefgh".to_string();
+        assert_eq!(view.masked_api_key(), "••••efgh");
+        view.api_key = "abc".to_string();
+        assert_eq!(view.masked_api_key(), "abc");
+    }
+}
@@ -0,0 +1,149 @@
+//! Input macros recorded by kazeta-input
+//!
+//! The input daemon owns `input/macros.json` and does the recording and
+//! playback; the overlay only lists the macros and sends requests on the
+//! daemon socket. Playing or recording hides the overlay first so the game
+//! has focus when the input arrives.
+
+use serde::Deserialize;
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// Playback speeds offered with Left/Right
+pub const SPEEDS: &[f32] = &[0.25, 0.5, 1.0, 1.5, 2.0, 3.0, 4.0];
+
+#[derive(Deserialize)]
+struct MacroStep {
+    delay_ms: u64,
+}
+
+#[derive(Deserialize)]
+struct StoredMacro {
+    #[serde(default = "default_speed")]
+    speed: f32,
+    steps: Vec<MacroStep>,
+}
+
+fn default_speed() -> f32 {
+    1.0
+}
+
+#[derive(Deserialize, Default)]
+struct MacroFile {
+    #[serde(default)]
+    macros: BTreeMap<String, StoredMacro>,
+}
+
+/// A macro as listed on the Macros screen
+#[derive(Debug, Clone, PartialEq)]
+pub struct MacroSummary {
+    pub name: String,
+    pub speed: f32,
+    pub steps: usize,
+    /// Playback length at 1x
+    pub duration_ms: u64,
+}
+
+impl MacroSummary {
+    /// Playback length at the macro's own speed
+    pub fn play_secs(&self) -> f32 {
+        self.duration_ms as f32 / 1000.0 / self.speed
+    }
+}
+
+fn macros_path() -> Option<PathBuf> {
+    dirs::data_local_dir().map(|d| d.join("kazeta-plus").join("input").join("macros.json"))
+}
+
+fn parse(content: &str) -> Vec<MacroSummary> {
+    let file: MacroFile = serde_json::from_str(content).unwrap_or_else(|e| {
+        eprintln!("[Macros] Invalid macros.json: {}", e);
+        MacroFile::default()
+    });
+    file.macros
+        .into_iter()
+        .map(|(name, stored)| MacroSummary {
+            name,
+            speed: stored.speed,
+            steps: stored.steps.len(),
+            duration_ms: stored.steps.iter().map(|s| s.delay_ms).sum(),
+        })
+        .collect()
+}
+
+/// Read the macros the input daemon has saved
+pub fn load() -> Vec<MacroSummary> {
+    macros_path()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .map(|content| parse(&content))
+        .unwrap_or_default()
+}
+
+/// First "Macro N" name not taken yet
+pub fn next_name(macros: &[MacroSummary]) -> String {
+    (1..)
+        .map(|n| format!("Macro {}", n))
+        .find(|name| !macros.iter().any(|m| &m.name == name))
+        .unwrap()
+}
+
+/// Step through `SPEEDS` from the current speed
+pub fn step_speed(current: f32, steps: i32) -> f32 {
+    let index = SPEEDS
+        .iter()
+        .position(|s| *s >= current)
+        .unwrap_or(SPEEDS.len() - 1) as i32;
+    SPEEDS[(index + steps).clamp(0, SPEEDS.len() as i32 - 1) as usize]
+}
+
+pub fn record(name: &str) {
+    send(json!({ "type": "macro_record_start", "name": name }));
+}
+
+pub fn play(name: &str) {
+    send(json!({ "type": "macro_play", "name": name }));
+}
+
+pub fn set_speed(name: &str, speed: f32) {
+    send(json!({ "type": "macro_set_speed", "name": name, "speed": speed }));
+}
+
+pub fn delete(name: &str) {
+    send(json!({ "type": "macro_delete", "name": name }));
+}
+
+fn send(message: serde_json::Value) {
+    if let Err(e) = crate::input::send_input_daemon_message(&message.to_string()) {
+        eprintln!("[Macros] Failed to reach the input daemon: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_daemon_file() {
+        let json = r#"{"macros":{"skip-intro":{"speed":2.0,"steps":[
+            {"input":"BTN_START","value":1,"delay_ms":0},
+            {"input":"BTN_START","value":0,"delay_ms":100},
+            {"input":"BTN_SOUTH","value":1,"delay_ms":900}]}}}"#;
+        let macros = parse(json);
+        assert_eq!(macros.len(), 1);
+        assert_eq!(macros[0].steps, 3);
+        assert_eq!(macros[0].duration_ms, 1000);
+        assert_eq!(macros[0].play_secs(), 0.5);
+        assert_eq!(next_name(&macros), "Macro 1");
+    }
+
+    #[test]
+    fn test_step_speed_clamps() {
+        assert_eq!(step_speed(1.0, 1), 1.5);
+        assert_eq!(step_speed(1.0, -1), 0.5);
+        assert_eq!(step_speed(4.0, 1), 4.0);
+        assert_eq!(step_speed(0.25, -1), 0.25);
+        // Speeds edited by hand snap to the next offered one
+        assert_eq!(step_speed(1.2, 0), 1.5);
+    }
+}
@@ -1,20 +1,59 @@
+#![cfg_attr(all(test, feature = "bench"), feature(test))]
+
+#[cfg(all(test, feature = "bench"))]
+extern crate test;
+
+mod backdrop;
+mod battery;
+mod calibration;
+mod celebration;
+mod cheats;
+mod compare;
 mod controllers;
+mod controls;
+mod debug_console;
+mod game_icon;
+mod haptics;
+mod hints;
 mod hotkeys;
-mod ipc;
+mod hud_layout;
 mod input;
+mod ipc;
+mod ipc_rules;
+mod ipc_thread;
+mod login;
+mod macros;
 mod menu_config;
+mod music;
+mod network;
+mod onboarding;
+mod pause;
 mod performance;
 mod playtime;
+mod pointer;
+mod power;
+mod quick_actions;
+mod quit;
+mod recovery;
 mod rendering;
+mod resources;
+mod search;
+mod snooze;
+mod speedrun;
 mod state;
+mod stats;
+mod stream_output;
 mod theme_config;
+mod theme_editor;
 mod themes;
 mod utils;
+mod wake;
+mod watchdog;
 
 use anyhow::Result;
+use macroquad::prelude::*;
 use state::OverlayState;
 use std::time::{Duration, Instant};
-use macroquad::prelude::*;
 
 #[cfg(target_os = "macos")]
 fn set_overlay_window_properties() {
@@ -43,23 +82,24 @@ fn set_overlay_window_properties() {
     println!("[Overlay] Window properties not configured for this platform");
 }
 
-const TARGET_FPS: u64 = 60;
-const FRAME_TIME: Duration = Duration::from_micros(1_000_000 / TARGET_FPS);
+/// Longest the loop sleeps while hidden. IPC wakes it immediately; this only
+/// bounds the latency of gilrs/keyboard hotkeys, which can't be polled.
+const IDLE_WAIT_MAX: Duration = Duration::from_millis(250);
 
 // Configure overlay window to be always-on-top and transparent
 // Match BIOS window size for local testing
 fn window_conf() -> Conf {
     Conf {
         window_title: "Kazeta Overlay".to_owned(),
-        window_width: 640,   // Match BIOS window size
-        window_height: 360,  // Match BIOS window size
+        window_width: 640,  // Match BIOS window size
+        window_height: 360, // Match BIOS window size
         window_resizable: false,
         fullscreen: false,
         platform: miniquad::conf::Platform {
             apple_gfx_api: miniquad::conf::AppleGfxApi::Metal, // Prefer Metal on macOS to avoid GL pixel format issues
             linux_backend: miniquad::conf::LinuxBackend::X11WithWaylandFallback, // For production: support both X11 and Wayland systems
             swap_interval: None,
-            framebuffer_alpha: true,  // Enable transparency
+            framebuffer_alpha: true, // Enable transparency
             ..Default::default()
         },
         ..Default::default()
@@ -71,10 +111,14 @@ async fn main() -> Result<()> {
     println!("[Overlay] Starting kazeta-overlay daemon...");
 
     // Initialize components
-    let mut ipc_server = ipc::IpcServer::new()?;
     let mut input_monitor = input::HotkeyMonitor::new()?;
     let mut overlay_state = OverlayState::new().await;
-    
+    // A game may still be running if the overlay crashed and was restarted
+    overlay_state.restore_session();
+    let mut idle_waiter = wake::IdleWaiter::new()?;
+    // Read and parse IPC off the render thread
+    let mut ipc_thread = ipc_thread::IpcThread::spawn(ipc::IpcServer::new()?)?;
+
     // Initialize gilrs for controller tracking
     #[cfg(feature = "daemon")]
     let mut gilrs = gilrs::Gilrs::new().unwrap_or_else(|e| {
@@ -90,6 +134,15 @@ async fn main() -> Result<()> {
     // Set window properties for overlay behavior (after first frame to ensure window exists)
     let mut window_properties_set = false;
 
+    // Last visibility reported to the input daemon
+    let mut reported_visible = false;
+
+    // Exclusive input lease held from the input daemon
+    let mut exclusive_input = input::ExclusiveInput::default();
+
+    // Whether a Guide hold may open the quick actions palette
+    let mut quick_actions_armed = false;
+
     loop {
         // Set window properties once after window is created
         if !window_properties_set {
@@ -101,13 +154,36 @@ async fn main() -> Result<()> {
         // Check for hotkey press (Guide button, F12, or Ctrl+O)
         if input_monitor.check_hotkey_pressed() {
             overlay_state.toggle_visibility();
-            println!("[Overlay] Toggled visibility: {}", overlay_state.is_visible());
+            // Holding the press that closed the overlay shouldn't reopen it
+            quick_actions_armed = overlay_state.is_visible();
+            println!(
+                "[Overlay] Toggled visibility: {}",
+                overlay_state.is_visible()
+            );
+        }
+
+        // Holding Guide opens the quick actions palette instead
+        if input_monitor.check_quick_actions_held() && quick_actions_armed {
+            overlay_state.open_quick_actions();
         }
 
         // Check for performance overlay toggle (F3)
         if input_monitor.check_performance_hotkey_pressed() {
             overlay_state.performance.toggle_visibility();
-            println!("[Overlay] Performance overlay: {}", overlay_state.performance.is_visible());
+            println!(
+                "[Overlay] Performance overlay: {}",
+                overlay_state.performance.is_visible()
+            );
+        }
+
+        // Speedrun timer hotkeys work whether or not the overlay is open
+        for action in input_monitor.check_speedrun_hotkeys() {
+            overlay_state.handle_speedrun_hotkey(action);
+        }
+
+        // So do the jukebox hotkeys
+        for action in input_monitor.check_music_hotkeys() {
+            overlay_state.handle_music_hotkey(action);
         }
 
         // Update connected controllers from gilrs
@@ -119,29 +195,80 @@ async fn main() -> Result<()> {
             for input in input_monitor.poll_inputs() {
                 overlay_state.handle_input(input);
             }
-            
+
+            // The palette follows the stick's direction, not just its flicks
+            if overlay_state.current_screen == ipc::OverlayScreen::QuickActions {
+                let (x, y) = input_monitor.left_stick();
+                overlay_state.point_quick_actions(x, y);
+            }
+
             // Update gamepad tester if on that screen
             #[cfg(feature = "daemon")]
             if overlay_state.current_screen == ipc::OverlayScreen::GamepadTester {
-                overlay_state.controllers.update_tester_from_gilrs(&mut gilrs);
+                overlay_state
+                    .controllers
+                    .update_tester_from_gilrs(&mut gilrs);
+            }
+
+            // Live stick readings for the controller settings screen
+            #[cfg(feature = "daemon")]
+            if overlay_state.current_screen == ipc::OverlayScreen::ControllerSettings {
+                overlay_state
+                    .controllers
+                    .update_settings_from_gilrs(&mut gilrs);
+                overlay_state.sample_drift_wizard();
             }
         }
 
-        // Process IPC messages
-        for message in ipc_server.poll_messages() {
-            overlay_state.handle_message(message);
+        // Process IPC messages, after the user's message rules
+        for incoming in ipc_thread.poll() {
+            overlay_state.route_message(incoming);
+        }
+        overlay_state.ipc_stats = ipc_thread.stats();
+        if overlay_state.shutdown_requested {
+            break;
         }
 
         // Update state
         overlay_state.update();
 
+        // Play queued rumble on the gamepads that asked for it
+        if let Some(haptics) = overlay_state.haptics.as_mut() {
+            let intensity = haptics.config().intensity;
+            for pattern in haptics.take_requests() {
+                input_monitor.play_haptic(pattern, intensity, &overlay_state.controllers);
+            }
+        }
+
+        // Let the input daemon grab devices while the menu is open
+        if overlay_state.is_visible() != reported_visible {
+            reported_visible = overlay_state.is_visible();
+            input::notify_input_daemon(reported_visible);
+            overlay_state.sync_game_pause(reported_visible);
+            overlay_state.sync_backdrop_blur(reported_visible);
+        }
+
+        // Keep the stick from the game while the on-screen keyboard is open
+        exclusive_input.sync(overlay_state.wants_exclusive_input());
+
+        // Render the OBS banner for any new unlock (needs the GL context)
+        if let Some(event) = overlay_state
+            .stream
+            .as_mut()
+            .and_then(|s| s.take_banner_request())
+        {
+            if let Some(path) = overlay_state.stream.as_ref().map(|s| s.banner_path()) {
+                rendering::export_stream_banner(&overlay_state, &event, &path);
+            }
+        }
+
         // Record frame for performance tracking
         overlay_state.performance.record_frame();
 
-        // If overlay is completely hidden (not rendering anything), reduce CPU usage
+        // If overlay is completely hidden (not rendering anything), sleep until
+        // an IPC message or wake-up arrives instead of polling
         if !overlay_state.should_render() && !overlay_state.performance.is_visible() {
-            // Run at 20 FPS when idle to save CPU
-            std::thread::sleep(Duration::from_millis(50));
+            idle_waiter.wait(&[], IDLE_WAIT_MAX);
             macroquad::prelude::next_frame().await;
             continue;
         }
@@ -154,10 +281,17 @@ async fn main() -> Result<()> {
             macroquad::prelude::next_frame().await;
         }
 
-        // Frame timing
+        // Frame timing, slower in the low-power modes
+        let frame_time = overlay_state.power_mode().frame_time();
         let elapsed = frame_start.elapsed();
-        if elapsed < FRAME_TIME {
-            std::thread::sleep(FRAME_TIME - elapsed);
+        if elapsed < frame_time {
+            std::thread::sleep(frame_time - elapsed);
         }
     }
+
+    // Never leave devices grabbed behind us
+    exclusive_input.sync(false);
+    input::notify_input_daemon(false);
+    println!("[Overlay] Exiting for system shutdown");
+    Ok(())
 }
@@ -10,8 +10,13 @@ pub enum MenuItemId {
     Controllers,
     Settings,
     Achievements,
+    Challenges,
     Performance,
     Playtime,
+    Speedrun,
+    Cheats,
+    Controls,
+    Music,
     QuickSave,
     Resume,
     Quit,
@@ -23,8 +28,13 @@ impl MenuItemId {
             MenuItemId::Controllers => "Controllers",
             MenuItemId::Settings => "Settings",
             MenuItemId::Achievements => "Achievements",
+            MenuItemId::Challenges => "Challenges",
             MenuItemId::Performance => "Performance",
             MenuItemId::Playtime => "Playtime",
+            MenuItemId::Speedrun => "Speedrun Timer",
+            MenuItemId::Cheats => "Cheats",
+            MenuItemId::Controls => "Game Controls",
+            MenuItemId::Music => "Music",
             MenuItemId::QuickSave => "Quick Save",
             MenuItemId::Resume => "Resume Game",
             MenuItemId::Quit => "Quit to BIOS",
@@ -36,8 +46,13 @@ impl MenuItemId {
             MenuItemId::Controllers,
             MenuItemId::Settings,
             MenuItemId::Achievements,
+            MenuItemId::Challenges,
             MenuItemId::Performance,
             MenuItemId::Playtime,
+            MenuItemId::Speedrun,
+            MenuItemId::Cheats,
+            MenuItemId::Controls,
+            MenuItemId::Music,
             MenuItemId::QuickSave,
             MenuItemId::Resume,
             MenuItemId::Quit,
@@ -45,6 +60,82 @@ impl MenuItemId {
     }
 }
 
+/// Fewest and most actions the quick actions palette holds
+pub const MIN_QUICK_ACTIONS: usize = 4;
+pub const MAX_QUICK_ACTIONS: usize = 8;
+
+/// Shortcut in the quick actions palette, opened by holding Guide
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum QuickAction {
+    Screenshot,
+    ToggleHud,
+    QuickSave,
+    QuickLoad,
+    Mute,
+    MusicPlayPause,
+    Achievements,
+    Resume,
+    Snooze,
+    Quit,
+}
+
+impl QuickAction {
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            QuickAction::Screenshot => "Screenshot",
+            QuickAction::ToggleHud => "Toggle HUD",
+            QuickAction::QuickSave => "Quick Save",
+            QuickAction::QuickLoad => "Quick Load",
+            QuickAction::Mute => "Mute",
+            QuickAction::MusicPlayPause => "Play/Pause Music",
+            QuickAction::Achievements => "Achievements",
+            QuickAction::Resume => "Resume Game",
+            QuickAction::Snooze => "Snooze Notifications",
+            QuickAction::Quit => "Quit to BIOS",
+        }
+    }
+
+    pub fn icon(&self) -> &'static str {
+        match self {
+            QuickAction::Screenshot => "📷",
+            QuickAction::ToggleHud => "📊",
+            QuickAction::QuickSave => "💾",
+            QuickAction::QuickLoad => "📂",
+            QuickAction::Mute => "🔇",
+            QuickAction::MusicPlayPause => "🎵",
+            QuickAction::Achievements => "🏆",
+            QuickAction::Resume => "▶",
+            QuickAction::Snooze => "🔕",
+            QuickAction::Quit => "⏏",
+        }
+    }
+
+    pub fn all() -> Vec<QuickAction> {
+        vec![
+            QuickAction::Screenshot,
+            QuickAction::ToggleHud,
+            QuickAction::QuickSave,
+            QuickAction::QuickLoad,
+            QuickAction::Mute,
+            QuickAction::MusicPlayPause,
+            QuickAction::Achievements,
+            QuickAction::Resume,
+            QuickAction::Snooze,
+            QuickAction::Quit,
+        ]
+    }
+}
+
+fn default_quick_actions() -> Vec<QuickAction> {
+    vec![
+        QuickAction::Screenshot,
+        QuickAction::ToggleHud,
+        QuickAction::QuickSave,
+        QuickAction::Mute,
+        QuickAction::Quit,
+    ]
+}
+
 /// Configuration for a single menu item
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct MenuItemConfig {
@@ -57,6 +148,9 @@ pub struct MenuItemConfig {
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct MenuConfig {
     pub items: Vec<MenuItemConfig>,
+    /// Palette slots, clockwise from the top
+    #[serde(default = "default_quick_actions")]
+    pub quick_actions: Vec<QuickAction>,
     pub version: u32,
 }
 
@@ -74,6 +168,7 @@ impl Default for MenuConfig {
 
         Self {
             items: default_items,
+            quick_actions: default_quick_actions(),
             version: 1,
         }
     }
@@ -85,13 +180,33 @@ impl MenuConfig {
         Self::default()
     }
 
+    /// Append items added since the config was saved, keeping the user's order.
+    /// Returns true if any were added.
+    pub fn add_missing_items(&mut self) -> bool {
+        let mut added = false;
+        for id in MenuItemId::all() {
+            if !self.items.iter().any(|item| item.id == id) {
+                let order = self
+                    .items
+                    .iter()
+                    .map(|item| item.order + 1)
+                    .max()
+                    .unwrap_or(0);
+                self.items.push(MenuItemConfig {
+                    id,
+                    visible: true,
+                    order,
+                });
+                added = true;
+            }
+        }
+        added
+    }
+
     /// Get visible menu items in order
     pub fn get_visible_items(&self) -> Vec<MenuItemId> {
-        let mut visible: Vec<_> = self.items
-            .iter()
-            .filter(|item| item.visible)
-            .collect();
-        
+        let mut visible: Vec<_> = self.items.iter().filter(|item| item.visible).collect();
+
         visible.sort_by_key(|item| item.order);
         visible.into_iter().map(|item| item.id).collect()
     }
@@ -152,13 +267,47 @@ impl MenuConfig {
 
     /// Get the index of a menu item in the visible list
     pub fn get_visible_index(&self, id: MenuItemId) -> Option<usize> {
-        self.get_visible_items().iter().position(|&item_id| item_id == id)
+        self.get_visible_items()
+            .iter()
+            .position(|&item_id| item_id == id)
     }
 
     /// Get menu item by visible index
     pub fn get_item_by_visible_index(&self, index: usize) -> Option<MenuItemId> {
         self.get_visible_items().get(index).copied()
     }
+
+    /// Add an action to the palette or take it out. Returns false when that
+    /// would leave fewer than MIN_QUICK_ACTIONS or more than MAX_QUICK_ACTIONS.
+    pub fn toggle_quick_action(&mut self, action: QuickAction) -> bool {
+        if let Some(index) = self.quick_actions.iter().position(|&a| a == action) {
+            if self.quick_actions.len() <= MIN_QUICK_ACTIONS {
+                return false;
+            }
+            self.quick_actions.remove(index);
+        } else {
+            if self.quick_actions.len() >= MAX_QUICK_ACTIONS {
+                return false;
+            }
+            self.quick_actions.push(action);
+        }
+        true
+    }
+
+    /// Move an action one slot counter-clockwise (up) or clockwise in the palette
+    pub fn move_quick_action(&mut self, action: QuickAction, up: bool) {
+        let Some(index) = self.quick_actions.iter().position(|&a| a == action) else {
+            return;
+        };
+        let other = if up {
+            index.checked_sub(1)
+        } else {
+            Some(index + 1)
+        };
+        if let Some(other) = other.filter(|&o| o < self.quick_actions.len()) {
+            self.quick_actions.swap(index, other);
+        }
+    }
 }
 
 /// Manages menu configuration
@@ -174,7 +323,13 @@ impl MenuConfigManager {
 
         let config = if config_path.exists() {
             println!("[MenuConfig] Loading config from {:?}", config_path);
-            Self::load_config(&config_path)?
+            let mut config = Self::load_config(&config_path)?;
+            if config.add_missing_items() {
+                if let Err(e) = Self::save_config(&config_path, &config) {
+                    eprintln!("[MenuConfig] Failed to save new menu items: {}", e);
+                }
+            }
+            config
         } else {
             println!("[MenuConfig] No config found, using defaults");
             let default_config = MenuConfig::default_config();
@@ -195,8 +350,8 @@ impl MenuConfigManager {
 
     /// Get the configuration file path
     fn get_config_path() -> Result<PathBuf> {
-        let data_dir = dirs::data_local_dir()
-            .context("Could not determine local data directory")?;
+        let data_dir =
+            dirs::data_local_dir().context("Could not determine local data directory")?;
 
         let overlay_dir = data_dir.join("kazeta-plus").join("overlay");
 
@@ -211,22 +366,20 @@ impl MenuConfigManager {
 
     /// Load configuration from file
     fn load_config(path: &PathBuf) -> Result<MenuConfig> {
-        let contents = fs::read_to_string(path)
-            .context("Failed to read menu config file")?;
+        let contents = fs::read_to_string(path).context("Failed to read menu config file")?;
 
-        let config: MenuConfig = serde_json::from_str(&contents)
-            .context("Failed to parse menu config JSON")?;
+        let config: MenuConfig =
+            serde_json::from_str(&contents).context("Failed to parse menu config JSON")?;
 
         Ok(config)
     }
 
     /// Save configuration to file
     fn save_config(path: &PathBuf, config: &MenuConfig) -> Result<()> {
-        let json = serde_json::to_string_pretty(config)
-            .context("Failed to serialize menu config")?;
+        let json =
+            serde_json::to_string_pretty(config).context("Failed to serialize menu config")?;
 
-        fs::write(path, json)
-            .context("Failed to write menu config file")?;
+        fs::write(path, json).context("Failed to write menu config file")?;
 
         println!("[MenuConfig] Config saved to {:?}", path);
         Ok(())
@@ -248,3 +401,34 @@ impl MenuConfigManager {
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quick_actions_stay_within_bounds() {
+        let mut config: MenuConfig =
+            serde_json::from_str(r#"{"items": [], "version": 1}"#).unwrap();
+        assert_eq!(config.quick_actions, default_quick_actions());
+
+        assert!(config.toggle_quick_action(QuickAction::Quit));
+        assert!(!config.toggle_quick_action(QuickAction::Mute));
+        assert_eq!(config.quick_actions.len(), MIN_QUICK_ACTIONS);
+
+        for action in [
+            QuickAction::QuickLoad,
+            QuickAction::MusicPlayPause,
+            QuickAction::Resume,
+            QuickAction::Quit,
+        ] {
+            assert!(config.toggle_quick_action(action));
+        }
+        assert_eq!(config.quick_actions.len(), MAX_QUICK_ACTIONS);
+        assert!(!config.toggle_quick_action(QuickAction::Achievements));
+
+        config.move_quick_action(QuickAction::Screenshot, true);
+        assert_eq!(config.quick_actions[0], QuickAction::Screenshot);
+        config.move_quick_action(QuickAction::Screenshot, false);
+        assert_eq!(config.quick_actions[1], QuickAction::Screenshot);
+    }
+}
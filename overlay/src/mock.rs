@@ -0,0 +1,82 @@
+//! In-process stand-in for the overlay daemon, for integration tests
+//!
+//! Enabled with the `mock` feature. The server listens on a private socket,
+//! so tests can run in parallel and without a daemon installed:
+//!
+//! ```ignore
+//! let mut server = MockOverlayServer::start()?;
+//! server.client().info("Saved")?;
+//! assert_eq!(server.wait_for(1, Duration::from_secs(1)).len(), 1);
+//! ```
+
+use crate::client::{AsyncOverlayClient, OverlayClient};
+use crate::ipc::{IpcServer, OverlayMessage};
+use anyhow::Result;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+/// Distinguishes servers started by the same process
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+pub struct MockOverlayServer {
+    server: IpcServer,
+    socket_path: String,
+    received: Vec<OverlayMessage>,
+}
+
+impl MockOverlayServer {
+    /// Listen on a fresh socket under /tmp
+    pub fn start() -> Result<Self> {
+        let path = format!(
+            "/tmp/kazeta-overlay-mock-{}-{}.sock",
+            std::process::id(),
+            NEXT_ID.fetch_add(1, Ordering::Relaxed)
+        );
+        Self::with_socket_path(&path)
+    }
+
+    /// Listen on a specific socket, e.g. to restart a server at the same path
+    pub fn with_socket_path(path: &str) -> Result<Self> {
+        Ok(Self {
+            server: IpcServer::with_socket_path(path)?,
+            socket_path: path.to_string(),
+            received: Vec::new(),
+        })
+    }
+
+    pub fn socket_path(&self) -> &str {
+        &self.socket_path
+    }
+
+    /// A blocking client connected to this server
+    pub fn client(&self) -> OverlayClient {
+        OverlayClient::with_socket_path(self.socket_path().to_string())
+    }
+
+    /// A non-blocking client connected to this server
+    pub fn async_client(&self) -> AsyncOverlayClient {
+        AsyncOverlayClient::with_socket_path(self.socket_path().to_string())
+    }
+
+    /// Read whatever has arrived and return everything received so far
+    pub fn poll(&mut self) -> &[OverlayMessage] {
+        let messages = self.server.poll_messages();
+        self.received.extend(messages);
+        &self.received
+    }
+
+    /// Poll until at least `count` messages have arrived or the timeout passes
+    pub fn wait_for(&mut self, count: usize, timeout: Duration) -> &[OverlayMessage] {
+        let deadline = Instant::now() + timeout;
+        while self.poll().len() < count && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        &self.received
+    }
+
+    /// Take the received messages, leaving the list empty
+    pub fn take(&mut self) -> Vec<OverlayMessage> {
+        self.poll();
+        std::mem::take(&mut self.received)
+    }
+}
@@ -0,0 +1,533 @@
+//! Jukebox: background music played over the running game
+//!
+//! Tracks come from `kazeta-plus/music` (ogg, mp3, flac and wav). Playback
+//! runs on its own thread, which owns the rodio output stream and is driven
+//! by commands over a channel; the overlay keeps the track list and reads
+//! back what is playing. The queue wraps around until playback is stopped,
+//! which also happens when the game exits.
+//!
+//! Volume follows the BIOS BGM volume from `config.toml` until it's changed
+//! here, and the stream opens the default output, which the BIOS points at
+//! the device picked in its AUDIO OUTPUT setting. While the game has focus
+//! the music can be ducked so it sits under the game's own sound.
+//!
+//! Playlists are saved with the other settings in `overlay/music.json` as
+//! lists of file names, so they survive tracks being moved between folders.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// File extensions the decoder handles
+const MUSIC_EXTENSIONS: &[&str] = &["ogg", "mp3", "flac", "wav"];
+
+/// How often the player thread checks whether the track ended
+const PLAYER_POLL: Duration = Duration::from_millis(250);
+
+/// Volume used when neither the jukebox nor the BIOS has one
+const DEFAULT_VOLUME: f32 = 0.7;
+
+/// Volume change per Left/Right press
+pub const VOLUME_STEP: f32 = 0.1;
+
+/// How much quieter the music gets while the game has focus
+pub const DUCK_LEVELS: &[u32] = &[0, 25, 50, 75];
+
+/// Jukebox settings and playlists
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct MusicConfig {
+    /// Set once the volume is changed on the Music screen; until then the
+    /// BIOS BGM volume is used
+    #[serde(default)]
+    pub volume: Option<f32>,
+    /// Percentage taken off the volume while the overlay is hidden
+    #[serde(default)]
+    pub duck_percent: u32,
+    /// Playlist name -> track file names
+    #[serde(default)]
+    pub playlists: BTreeMap<String, Vec<String>>,
+    pub version: u32,
+}
+
+impl Default for MusicConfig {
+    fn default() -> Self {
+        Self {
+            volume: None,
+            duck_percent: 0,
+            playlists: BTreeMap::new(),
+            version: 1,
+        }
+    }
+}
+
+impl MusicConfig {
+    /// Adds the track to the playlist, or removes it if it's already there.
+    /// Returns whether the track is now in the playlist.
+    pub fn toggle_track(&mut self, playlist: &str, file_name: &str) -> bool {
+        let tracks = self.playlists.entry(playlist.to_string()).or_default();
+        let added = match tracks.iter().position(|t| t == file_name) {
+            Some(index) => {
+                tracks.remove(index);
+                false
+            }
+            None => {
+                tracks.push(file_name.to_string());
+                true
+            }
+        };
+        if tracks.is_empty() {
+            self.playlists.remove(playlist);
+        }
+        added
+    }
+
+    /// First "Playlist N" name not taken yet
+    pub fn next_playlist_name(&self) -> String {
+        (1..)
+            .map(|n| format!("Playlist {}", n))
+            .find(|name| !self.playlists.contains_key(name))
+            .unwrap()
+    }
+}
+
+/// Volume after ducking
+pub fn ducked_volume(volume: f32, duck_percent: u32, ducked: bool) -> f32 {
+    if ducked {
+        volume * (100 - duck_percent.min(100)) as f32 / 100.0
+    } else {
+        volume
+    }
+}
+
+/// Steps through DUCK_LEVELS, wrapping around at either end
+pub fn cycle_duck(current: u32, forward: bool) -> u32 {
+    let index = DUCK_LEVELS.iter().position(|&d| d == current).unwrap_or(0);
+    let new_index = if forward {
+        (index + 1) % DUCK_LEVELS.len()
+    } else {
+        (index + DUCK_LEVELS.len() - 1) % DUCK_LEVELS.len()
+    };
+    DUCK_LEVELS[new_index]
+}
+
+/// Music files directly in `dir`, sorted by name
+fn scan_tracks(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut tracks: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|path| {
+            path.extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| MUSIC_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+        })
+        .collect();
+    tracks.sort();
+    tracks
+}
+
+/// Track name shown on screen: the file name without its extension
+pub fn track_title(path: &Path) -> String {
+    path.file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+#[derive(Deserialize)]
+struct BiosAudioConfig {
+    bgm_volume: f32,
+}
+
+/// BGM volume from the BIOS config, if it can be read
+fn bios_bgm_volume() -> Option<f32> {
+    let path = dirs::data_local_dir()?
+        .join("kazeta-plus")
+        .join("config.toml");
+    let contents = fs::read_to_string(path).ok()?;
+    toml::from_str::<BiosAudioConfig>(&contents)
+        .ok()
+        .map(|c| c.bgm_volume)
+}
+
+/// What the player thread is doing
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PlayerStatus {
+    pub track: Option<PathBuf>,
+    pub paused: bool,
+}
+
+enum PlayerCommand {
+    /// Play the queue starting at the given index
+    Play(Vec<PathBuf>, usize),
+    TogglePause,
+    /// Move through the queue by this many tracks
+    Skip(i32),
+    Stop,
+    SetVolume(f32),
+}
+
+/// Handle to the playback thread
+struct Player {
+    commands: Sender<PlayerCommand>,
+}
+
+impl Player {
+    fn spawn(volume: f32, status: Arc<Mutex<PlayerStatus>>) -> Self {
+        let (commands, receiver) = mpsc::channel();
+        thread::spawn(move || run_player(receiver, volume, status));
+        Self { commands }
+    }
+}
+
+fn run_player(
+    commands: Receiver<PlayerCommand>,
+    mut volume: f32,
+    status: Arc<Mutex<PlayerStatus>>,
+) {
+    let mut stream = match rodio::OutputStreamBuilder::open_default_stream() {
+        Ok(stream) => stream,
+        Err(e) => {
+            eprintln!("[Music] Failed to open audio output: {}", e);
+            return;
+        }
+    };
+    stream.log_on_drop(false);
+
+    let mut queue: Vec<PathBuf> = Vec::new();
+    let mut index = 0;
+    let mut sink: Option<rodio::Sink> = None;
+
+    // Start the track at `index`, skipping over files that don't decode
+    let start = |queue: &[PathBuf], index: &mut usize, volume: f32| -> Option<rodio::Sink> {
+        for _ in 0..queue.len() {
+            let path = &queue[*index];
+            let decoder = fs::File::open(path)
+                .map_err(|e| e.to_string())
+                .and_then(|file| {
+                    rodio::Decoder::new(BufReader::new(file)).map_err(|e| e.to_string())
+                });
+            match decoder {
+                Ok(decoder) => {
+                    println!("[Music] Playing {}", track_title(path));
+                    let sink = rodio::Sink::connect_new(stream.mixer());
+                    sink.set_volume(volume);
+                    sink.append(decoder);
+                    return Some(sink);
+                }
+                Err(e) => {
+                    eprintln!("[Music] Skipping {}: {}", path.display(), e);
+                    *index = (*index + 1) % queue.len();
+                }
+            }
+        }
+        None
+    };
+
+    loop {
+        match commands.recv_timeout(PLAYER_POLL) {
+            Ok(PlayerCommand::Play(new_queue, start_index)) => {
+                queue = new_queue;
+                index = start_index.min(queue.len().saturating_sub(1));
+                sink = if queue.is_empty() {
+                    None
+                } else {
+                    start(&queue, &mut index, volume)
+                };
+            }
+            Ok(PlayerCommand::TogglePause) => {
+                if let Some(sink) = &sink {
+                    if sink.is_paused() {
+                        sink.play()
+                    } else {
+                        sink.pause()
+                    }
+                }
+            }
+            Ok(PlayerCommand::Skip(steps)) => {
+                if !queue.is_empty() {
+                    index = (index as i32 + steps).rem_euclid(queue.len() as i32) as usize;
+                    sink = start(&queue, &mut index, volume);
+                }
+            }
+            Ok(PlayerCommand::Stop) => {
+                queue.clear();
+                sink = None;
+            }
+            Ok(PlayerCommand::SetVolume(new_volume)) => {
+                volume = new_volume;
+                if let Some(sink) = &sink {
+                    sink.set_volume(volume);
+                }
+            }
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+
+        // Move on when a track finishes
+        if sink.as_ref().is_some_and(|s| s.empty()) && !queue.is_empty() {
+            index = (index + 1) % queue.len();
+            sink = start(&queue, &mut index, volume);
+        }
+
+        if let Ok(mut status) = status.lock() {
+            *status = PlayerStatus {
+                track: sink.as_ref().map(|_| queue[index].clone()),
+                paused: sink.as_ref().is_some_and(|s| s.is_paused()),
+            };
+        }
+    }
+}
+
+/// Where the Music screen's list comes from
+#[derive(Debug, Clone, PartialEq)]
+pub enum MusicSource {
+    AllTracks,
+    Playlist(String),
+}
+
+/// Music library, playlists and the playback thread
+pub struct Jukebox {
+    config: MusicConfig,
+    config_path: PathBuf,
+    music_dir: PathBuf,
+    tracks: Vec<PathBuf>,
+    player: Option<Player>,
+    status: Arc<Mutex<PlayerStatus>>,
+    ducked: bool,
+}
+
+impl Jukebox {
+    /// Create new Jukebox with config loaded from disk
+    pub fn new() -> Result<Self> {
+        let data_dir =
+            dirs::data_local_dir().context("Could not determine local data directory")?;
+        let config_path = Self::get_config_path(&data_dir)?;
+
+        let config = if config_path.exists() {
+            println!("[Music] Loading config from {:?}", config_path);
+            Self::load_config(&config_path)?
+        } else {
+            MusicConfig::default()
+        };
+
+        let music_dir = data_dir.join("kazeta-plus").join("music");
+        Ok(Self {
+            config,
+            config_path,
+            tracks: scan_tracks(&music_dir),
+            music_dir,
+            player: None,
+            status: Arc::new(Mutex::new(PlayerStatus::default())),
+            ducked: false,
+        })
+    }
+
+    fn get_config_path(data_dir: &Path) -> Result<PathBuf> {
+        let overlay_dir = data_dir.join("kazeta-plus").join("overlay");
+        if !overlay_dir.exists() {
+            fs::create_dir_all(&overlay_dir)
+                .context("Failed to create overlay config directory")?;
+        }
+        Ok(overlay_dir.join("music.json"))
+    }
+
+    fn load_config(path: &PathBuf) -> Result<MusicConfig> {
+        let contents = fs::read_to_string(path).context("Failed to read music config file")?;
+        serde_json::from_str(&contents).context("Failed to parse music config JSON")
+    }
+
+    /// Save current configuration to disk
+    pub fn save(&self) -> Result<()> {
+        let json = serde_json::to_string_pretty(&self.config)
+            .context("Failed to serialize music config")?;
+        fs::write(&self.config_path, json).context("Failed to write music config file")?;
+        Ok(())
+    }
+
+    fn save_or_log(&self) {
+        if let Err(e) = self.save() {
+            eprintln!("[Music] Failed to save config: {}", e);
+        }
+    }
+
+    pub fn config(&self) -> &MusicConfig {
+        &self.config
+    }
+
+    /// Pick up tracks added since the overlay started
+    pub fn rescan(&mut self) {
+        self.tracks = scan_tracks(&self.music_dir);
+    }
+
+    /// Playlists in display order, for cycling sources
+    pub fn sources(&self) -> Vec<MusicSource> {
+        std::iter::once(MusicSource::AllTracks)
+            .chain(
+                self.config
+                    .playlists
+                    .keys()
+                    .cloned()
+                    .map(MusicSource::Playlist),
+            )
+            .collect()
+    }
+
+    /// Tracks listed for a source. Playlist entries whose file is gone are left out.
+    pub fn tracks(&self, source: &MusicSource) -> Vec<PathBuf> {
+        match source {
+            MusicSource::AllTracks => self.tracks.clone(),
+            MusicSource::Playlist(name) => self
+                .config
+                .playlists
+                .get(name)
+                .map(|names| {
+                    names
+                        .iter()
+                        .filter_map(|n| self.tracks.iter().find(|t| file_name(t) == *n).cloned())
+                        .collect()
+                })
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Adds or removes a track from a playlist and saves
+    pub fn toggle_in_playlist(&mut self, playlist: &str, track: &Path) -> bool {
+        let added = self.config.toggle_track(playlist, &file_name(track));
+        self.save_or_log();
+        added
+    }
+
+    /// Volume before ducking
+    pub fn volume(&self) -> f32 {
+        self.config
+            .volume
+            .or_else(bios_bgm_volume)
+            .unwrap_or(DEFAULT_VOLUME)
+    }
+
+    fn effective_volume(&self) -> f32 {
+        ducked_volume(self.volume(), self.config.duck_percent, self.ducked)
+    }
+
+    pub fn adjust_volume(&mut self, delta: f32) {
+        let volume = ((self.volume() + delta) * 10.0).round() / 10.0;
+        self.config.volume = Some(volume.clamp(0.0, 1.0));
+        self.send(PlayerCommand::SetVolume(self.effective_volume()));
+        self.save_or_log();
+    }
+
+    pub fn cycle_duck(&mut self, forward: bool) {
+        self.config.duck_percent = cycle_duck(self.config.duck_percent, forward);
+        self.send(PlayerCommand::SetVolume(self.effective_volume()));
+        self.save_or_log();
+    }
+
+    /// Called every frame; ducks the music while the game has focus
+    pub fn set_ducked(&mut self, ducked: bool) {
+        if self.ducked != ducked {
+            self.ducked = ducked;
+            self.send(PlayerCommand::SetVolume(self.effective_volume()));
+        }
+    }
+
+    /// Play a list of tracks from `start`, starting the playback thread if needed
+    pub fn play(&mut self, tracks: Vec<PathBuf>, start: usize) {
+        if tracks.is_empty() {
+            return;
+        }
+        if self.player.is_none() {
+            self.player = Some(Player::spawn(self.effective_volume(), self.status.clone()));
+        }
+        self.send(PlayerCommand::Play(tracks, start));
+    }
+
+    pub fn toggle_pause(&self) {
+        self.send(PlayerCommand::TogglePause);
+    }
+
+    pub fn skip(&self, steps: i32) {
+        self.send(PlayerCommand::Skip(steps));
+    }
+
+    pub fn stop(&self) {
+        self.send(PlayerCommand::Stop);
+    }
+
+    pub fn status(&self) -> PlayerStatus {
+        self.status.lock().map(|s| s.clone()).unwrap_or_default()
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.status().track.is_some()
+    }
+
+    fn send(&self, command: PlayerCommand) {
+        if let Some(player) = &self.player {
+            if player.commands.send(command).is_err() {
+                eprintln!("[Music] Playback thread has stopped");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_playlist_toggle_drops_empty_playlists() {
+        let mut config = MusicConfig::default();
+        assert_eq!(config.next_playlist_name(), "Playlist 1");
+
+        assert!(config.toggle_track("Playlist 1", "theme.ogg"));
+        assert!(config.toggle_track("Playlist 1", "boss.mp3"));
+        assert_eq!(
+            config.playlists["Playlist 1"],
+            vec!["theme.ogg", "boss.mp3"]
+        );
+        assert_eq!(config.next_playlist_name(), "Playlist 2");
+
+        assert!(!config.toggle_track("Playlist 1", "theme.ogg"));
+        assert!(!config.toggle_track("Playlist 1", "boss.mp3"));
+        assert!(config.playlists.is_empty());
+    }
+
+    #[test]
+    fn test_ducking() {
+        assert_eq!(ducked_volume(0.8, 50, false), 0.8);
+        assert_eq!(ducked_volume(0.8, 50, true), 0.4);
+        assert_eq!(ducked_volume(0.8, 0, true), 0.8);
+        assert_eq!(cycle_duck(0, true), 25);
+        assert_eq!(cycle_duck(0, false), 75);
+        assert_eq!(cycle_duck(75, true), 0);
+    }
+
+    #[test]
+    fn test_scan_tracks_filters_and_sorts() {
+        let dir = std::env::temp_dir().join(format!("kazeta-music-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        for name in ["b.OGG", "a.mp3", "cover.png", "notes.txt"] {
+            fs::write(dir.join(name), b"").unwrap();
+        }
+
+        let titles: Vec<String> = scan_tracks(&dir).iter().map(|t| track_title(t)).collect();
+        assert_eq!(titles, vec!["a", "b"]);
+
+        fs::remove_dir_all(&dir).ok();
+    }
+}
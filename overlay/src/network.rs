@@ -0,0 +1,174 @@
+//! Network status for the overlay header
+//!
+//! A background thread checks connectivity every `CHECK_INTERVAL`. Opening a
+//! TCP connection to the RetroAchievements server tells both whether RA is
+//! reachable and how long the round trip takes; the Wi-Fi SSID comes from
+//! NetworkManager. RA polling pauses while offline and the achievements
+//! screen falls back to the local RA cache.
+
+use std::net::{TcpStream, ToSocketAddrs};
+use std::process::Command;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How often connectivity is checked
+const CHECK_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Server used for the reachability and latency check
+const RA_HOST: &str = "retroachievements.org:443";
+
+/// Longer than this and RA is treated as unreachable
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Result of one connectivity check
+#[derive(Debug, Clone, PartialEq)]
+pub struct NetworkStatus {
+    pub online: bool,
+    /// Connected Wi-Fi network, `None` on wired or no connection
+    pub ssid: Option<String>,
+    /// Time to open a connection to RA
+    pub latency_ms: Option<u32>,
+}
+
+/// Receives check results from the background thread
+#[derive(Default)]
+pub struct NetworkMonitor {
+    status: Option<NetworkStatus>,
+    receiver: Option<Receiver<NetworkStatus>>,
+}
+
+impl NetworkMonitor {
+    /// Start checking in the background
+    pub fn start() -> Self {
+        let (tx, rx) = mpsc::channel();
+
+        thread::spawn(move || loop {
+            if tx.send(check()).is_err() {
+                break; // Overlay is shutting down
+            }
+            thread::sleep(CHECK_INTERVAL);
+        });
+
+        Self {
+            status: None,
+            receiver: Some(rx),
+        }
+    }
+
+    /// Latest status, `None` until the first check finishes
+    pub fn status(&self) -> Option<&NetworkStatus> {
+        self.status.as_ref()
+    }
+
+    /// Whether the last check failed; unknown counts as online so nothing is
+    /// held back before the first check
+    pub fn is_offline(&self) -> bool {
+        self.status.as_ref().is_some_and(|s| !s.online)
+    }
+
+    /// Pick up new results. Returns the new online state when it changed.
+    pub fn poll(&mut self) -> Option<bool> {
+        let receiver = self.receiver.as_ref()?;
+        let mut changed = None;
+
+        loop {
+            match receiver.try_recv() {
+                Ok(status) => {
+                    let was_online = self.status.as_ref().map(|s| s.online);
+                    if was_online != Some(status.online) {
+                        println!(
+                            "[Network] {}",
+                            if status.online { "Online" } else { "Offline" }
+                        );
+                        changed = Some(status.online);
+                    }
+                    self.status = Some(status);
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    self.receiver = None;
+                    break;
+                }
+            }
+        }
+
+        changed
+    }
+}
+
+fn check() -> NetworkStatus {
+    let latency = ra_latency();
+    NetworkStatus {
+        online: latency.is_some(),
+        ssid: wifi_ssid(),
+        latency_ms: latency.map(|d| d.as_millis() as u32),
+    }
+}
+
+/// Time to open a TCP connection to the RA server, if it is reachable
+fn ra_latency() -> Option<Duration> {
+    let addr = RA_HOST.to_socket_addrs().ok()?.next()?;
+    let start = Instant::now();
+    TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT).ok()?;
+    Some(start.elapsed())
+}
+
+fn wifi_ssid() -> Option<String> {
+    let output = Command::new("nmcli")
+        .args(["--terse", "--fields", "ACTIVE,SSID", "device", "wifi"])
+        .output()
+        .ok()?;
+    parse_active_ssid(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// The SSID of the active line in `nmcli --terse --fields ACTIVE,SSID` output
+fn parse_active_ssid(output: &str) -> Option<String> {
+    output
+        .lines()
+        .find_map(|line| line.strip_prefix("yes:"))
+        // nmcli escapes colons in terse output
+        .map(|ssid| ssid.replace("\\:", ":"))
+        .filter(|ssid| !ssid.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_active_ssid() {
+        assert_eq!(
+            parse_active_ssid("no:Neighbour\nyes:Home\\:5G\n"),
+            Some("Home:5G".to_string())
+        );
+        assert_eq!(parse_active_ssid("no:Neighbour\n"), None);
+        assert_eq!(parse_active_ssid(""), None);
+    }
+
+    #[test]
+    fn test_poll_reports_changes_only() {
+        let (tx, rx) = mpsc::channel();
+        let mut monitor = NetworkMonitor {
+            status: None,
+            receiver: Some(rx),
+        };
+        let status = |online| NetworkStatus {
+            online,
+            ssid: None,
+            latency_ms: None,
+        };
+
+        assert_eq!(monitor.poll(), None);
+        assert!(!monitor.is_offline());
+
+        tx.send(status(true)).unwrap();
+        assert_eq!(monitor.poll(), Some(true));
+        tx.send(status(true)).unwrap();
+        assert_eq!(monitor.poll(), None);
+
+        tx.send(status(false)).unwrap();
+        assert_eq!(monitor.poll(), Some(false));
+        assert!(monitor.is_offline());
+    }
+}
@@ -0,0 +1,217 @@
+//! First-run tour of the overlay menu
+//!
+//! The first time the overlay opens for a profile, a tour steps through each
+//! main menu item with a short description and its hotkeys. A moves on, B
+//! goes back and X skips. Profiles are the RetroAchievements user, or
+//! "default" when logged out. Finished and skipped tours are recorded in
+//! `overlay/onboarding.json`; Settings > Replay Tour starts it again.
+
+use crate::menu_config::MenuItemId;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::PathBuf;
+
+/// Profile used when nobody is logged in to RetroAchievements
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// What the tour says about one menu item
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TourStep {
+    pub item: MenuItemId,
+    pub description: &'static str,
+    /// Hotkeys that reach the same features, empty when there are none
+    pub hotkeys: &'static str,
+}
+
+impl TourStep {
+    pub fn for_item(item: MenuItemId) -> Self {
+        let (description, hotkeys) = match item {
+            MenuItemId::Controllers => (
+                "See connected controllers, pair Bluetooth pads, assign players and test buttons.",
+                "",
+            ),
+            MenuItemId::Settings => (
+                "Themes, menu layout, rumble, power modes and your RetroAchievements login. Replay this tour from here.",
+                "",
+            ),
+            MenuItemId::Achievements => (
+                "Browse this game's achievements, read community hints and compare with friends.",
+                "A: Hints • X: Compare • RB: Stats",
+            ),
+            MenuItemId::Challenges => ("Daily and weekly RetroAchievements challenges.", ""),
+            MenuItemId::Performance => ("FPS, frame times, CPU and memory while you play.", "F3: Performance HUD"),
+            MenuItemId::Playtime => ("Time played in this game and your library.", ""),
+            MenuItemId::Speedrun => ("A split timer that keeps running over the game.", "F6: Start/Pause • F7: Split • F8: Reset"),
+            MenuItemId::Cheats => ("Turn on cheats for the running game.", ""),
+            MenuItemId::Controls => ("What each button does in this game, when its cart describes it.", ""),
+            MenuItemId::Music => ("Play your own music over the game.", "F4: Play/Pause • F2: Next Track"),
+            MenuItemId::QuickSave => ("Save the game's state right now.", "F5: Quick Save • F9: Quick Load"),
+            MenuItemId::Resume => (
+                "Close the overlay and get back to the game.",
+                "Guide/F12/Ctrl+O: Open or Close • Hold Guide: Quick Actions",
+            ),
+            MenuItemId::Quit => ("Save and return to the BIOS.", ""),
+        };
+        Self {
+            item,
+            description,
+            hotkeys,
+        }
+    }
+}
+
+/// A tour in progress
+#[derive(Debug, Clone)]
+pub struct Tour {
+    pub steps: Vec<TourStep>,
+    pub index: usize,
+}
+
+impl Tour {
+    /// A tour of the given menu items, in menu order
+    pub fn new(items: &[MenuItemId]) -> Self {
+        Self {
+            steps: items.iter().map(|&item| TourStep::for_item(item)).collect(),
+            index: 0,
+        }
+    }
+
+    pub fn current(&self) -> Option<&TourStep> {
+        self.steps.get(self.index)
+    }
+
+    pub fn is_last(&self) -> bool {
+        self.index + 1 >= self.steps.len()
+    }
+
+    /// Move to the next step. Returns false when there is none.
+    pub fn next(&mut self) -> bool {
+        if self.is_last() {
+            return false;
+        }
+        self.index += 1;
+        true
+    }
+
+    pub fn back(&mut self) {
+        self.index = self.index.saturating_sub(1);
+    }
+}
+
+/// Profiles that have finished or skipped the tour
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+struct OnboardingRecord {
+    #[serde(default)]
+    completed: BTreeSet<String>,
+    #[serde(default)]
+    version: u32,
+}
+
+/// Remembers which profiles have seen the tour
+pub struct Onboarding {
+    path: PathBuf,
+    record: OnboardingRecord,
+}
+
+impl Onboarding {
+    pub fn new() -> Result<Self> {
+        let data_dir =
+            dirs::data_local_dir().context("Could not determine local data directory")?;
+
+        Ok(Self::with_path(
+            data_dir
+                .join("kazeta-plus")
+                .join("overlay")
+                .join("onboarding.json"),
+        ))
+    }
+
+    pub fn with_path(path: PathBuf) -> Self {
+        let record = match fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+                eprintln!("[Onboarding] Ignoring unreadable {}: {}", path.display(), e);
+                OnboardingRecord::default()
+            }),
+            Err(_) => OnboardingRecord::default(),
+        };
+        Self { path, record }
+    }
+
+    pub fn has_seen(&self, profile: &str) -> bool {
+        self.record.completed.contains(profile)
+    }
+
+    /// Record that a profile finished or skipped the tour
+    pub fn mark_seen(&mut self, profile: &str) -> Result<()> {
+        if !self.record.completed.insert(profile.to_string()) {
+            return Ok(());
+        }
+        self.record.version = 1;
+
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).context("Failed to create overlay data directory")?;
+        }
+        let json = serde_json::to_string_pretty(&self.record)
+            .context("Failed to serialize onboarding record")?;
+        fs::write(&self.path, json).context("Failed to write onboarding record")?;
+        Ok(())
+    }
+}
+
+/// The profile the tour is recorded under
+pub fn profile_name(ra_user: Option<&str>) -> &str {
+    ra_user.unwrap_or(DEFAULT_PROFILE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("kazeta-onboarding-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir.join("onboarding.json")
+    }
+
+    #[test]
+    fn test_tour_follows_menu_items() {
+        let mut tour = Tour::new(&[
+            MenuItemId::Achievements,
+            MenuItemId::Settings,
+            MenuItemId::Quit,
+        ]);
+        assert_eq!(tour.current().unwrap().item, MenuItemId::Achievements);
+
+        tour.back();
+        assert_eq!(tour.index, 0);
+        assert!(tour.next());
+        assert!(tour.next());
+        assert!(tour.is_last());
+        assert_eq!(tour.current().unwrap().item, MenuItemId::Quit);
+        assert!(!tour.next());
+
+        tour.back();
+        assert_eq!(tour.current().unwrap().item, MenuItemId::Settings);
+        assert!(Tour::new(&[]).current().is_none());
+    }
+
+    #[test]
+    fn test_seen_is_per_profile_and_persists() {
+        let path = temp_path("persist");
+        let mut onboarding = Onboarding::with_path(path.clone());
+        assert!(!onboarding.has_seen(DEFAULT_PROFILE));
+
+        onboarding.mark_seen(profile_name(Some("alice"))).unwrap();
+        assert!(onboarding.has_seen("alice"));
+        assert!(!onboarding.has_seen(profile_name(None)));
+
+        let reloaded = Onboarding::with_path(path.clone());
+        assert!(reloaded.has_seen("alice"));
+        assert!(!reloaded.has_seen("bob"));
+
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+}
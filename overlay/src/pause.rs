@@ -0,0 +1,299 @@
+//! Auto-pause of the running game while the overlay is open
+//!
+//! Runtime wrappers that can pause their emulator cleanly watch a command
+//! file: the overlay writes `pause` or `resume` to `/tmp/kazeta-emulator-command`,
+//! and a wrapper announces that it handles the file by writing its PID to
+//! `/tmp/kazeta-emulator-wrapper`. When no live wrapper has announced itself,
+//! the known emulator processes are frozen with SIGSTOP and thawed with SIGCONT.
+//!
+//! Auto-pause is off by default. Each game's overlay profile can force it on
+//! or off regardless of the global switch.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// File runtime wrappers watch for pause/resume commands
+const COMMAND_FILE: &str = "/tmp/kazeta-emulator-command";
+
+/// Holds the PID of a wrapper that handles COMMAND_FILE
+const WRAPPER_FILE: &str = "/tmp/kazeta-emulator-wrapper";
+
+/// Emulator processes signalled when no wrapper handles the command file
+pub const EMULATOR_PROCESSES: &[&str] = &[
+    "mgba-qt",
+    "vbam",
+    "visualboyadvance-m",
+    "retroarch",
+    "dolphin-emu",
+];
+
+/// Per-game overlay settings
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct GameProfile {
+    /// Overrides the global auto-pause switch when set
+    #[serde(default)]
+    pub auto_pause: Option<bool>,
+}
+
+/// Auto-pause configuration
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AutoPauseConfig {
+    /// Global switch, used by games without an override
+    pub enabled: bool,
+    /// Overlay profiles keyed by cart id
+    #[serde(default)]
+    pub profiles: HashMap<String, GameProfile>,
+    pub version: u32,
+}
+
+impl Default for AutoPauseConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            profiles: HashMap::new(),
+            version: 1,
+        }
+    }
+}
+
+impl AutoPauseConfig {
+    /// Whether the game should be paused while the overlay is open
+    pub fn is_enabled_for(&self, cart_id: &str) -> bool {
+        self.profiles
+            .get(cart_id)
+            .and_then(|p| p.auto_pause)
+            .unwrap_or(self.enabled)
+    }
+
+    /// Steps a game's override through default → on → off → default
+    pub fn cycle_override(&mut self, cart_id: &str) -> Option<bool> {
+        let profile = self.profiles.entry(cart_id.to_string()).or_default();
+        profile.auto_pause = match profile.auto_pause {
+            None => Some(true),
+            Some(true) => Some(false),
+            Some(false) => None,
+        };
+        let value = profile.auto_pause;
+        // Don't keep empty profiles around
+        if *profile == GameProfile::default() {
+            self.profiles.remove(cart_id);
+        }
+        value
+    }
+}
+
+/// How a paused game was paused, so it is resumed the same way
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PauseMethod {
+    CommandFile,
+    Signal,
+}
+
+/// Parses the wrapper file, returning the announced PID
+fn parse_wrapper_pid(contents: &str) -> Option<u32> {
+    contents.trim().parse().ok().filter(|&pid| pid > 0)
+}
+
+/// Use the command file only while the wrapper that announced it is alive,
+/// so a stale file from a crashed wrapper doesn't disable the fallback
+fn detect_method(wrapper_file: &Path) -> PauseMethod {
+    let alive = fs::read_to_string(wrapper_file)
+        .ok()
+        .and_then(|contents| parse_wrapper_pid(&contents))
+        .is_some_and(|pid| Path::new(&format!("/proc/{}", pid)).exists());
+
+    if alive {
+        PauseMethod::CommandFile
+    } else {
+        PauseMethod::Signal
+    }
+}
+
+/// Whether a live wrapper is watching the command file
+pub fn wrapper_handles_commands() -> bool {
+    detect_method(Path::new(WRAPPER_FILE)) == PauseMethod::CommandFile
+}
+
+pub fn write_command(command: &str) -> Result<()> {
+    // Write then rename so wrappers never read a half-written command
+    let tmp = format!("{}.tmp", COMMAND_FILE);
+    fs::write(&tmp, format!("{}\n", command)).context("Failed to write emulator command")?;
+    fs::rename(&tmp, COMMAND_FILE).context("Failed to publish emulator command")?;
+    Ok(())
+}
+
+pub fn signal_emulators(signal: &str) {
+    #[cfg(target_os = "linux")]
+    for emu in EMULATOR_PROCESSES {
+        let _ = std::process::Command::new("pkill")
+            .args([signal, emu])
+            .spawn();
+    }
+    #[cfg(not(target_os = "linux"))]
+    let _ = signal;
+}
+
+/// Pauses and resumes the game as the overlay is shown and hidden
+pub struct AutoPause {
+    config: AutoPauseConfig,
+    config_path: PathBuf,
+    paused: Option<PauseMethod>,
+}
+
+impl AutoPause {
+    /// Create new AutoPause with config loaded from disk
+    pub fn new() -> Result<Self> {
+        let config_path = Self::get_config_path()?;
+
+        let config = if config_path.exists() {
+            println!("[AutoPause] Loading config from {:?}", config_path);
+            Self::load_config(&config_path)?
+        } else {
+            let default_config = AutoPauseConfig::default();
+            if let Err(e) = Self::save_config(&config_path, &default_config) {
+                eprintln!("[AutoPause] Failed to save default config: {}", e);
+            }
+            default_config
+        };
+
+        Ok(Self {
+            config,
+            config_path,
+            paused: None,
+        })
+    }
+
+    /// Get the configuration file path
+    fn get_config_path() -> Result<PathBuf> {
+        let data_dir =
+            dirs::data_local_dir().context("Could not determine local data directory")?;
+
+        let overlay_dir = data_dir.join("kazeta-plus").join("overlay");
+        if !overlay_dir.exists() {
+            fs::create_dir_all(&overlay_dir)
+                .context("Failed to create overlay config directory")?;
+        }
+
+        Ok(overlay_dir.join("auto_pause.json"))
+    }
+
+    fn load_config(path: &PathBuf) -> Result<AutoPauseConfig> {
+        let contents = fs::read_to_string(path).context("Failed to read auto-pause config file")?;
+        serde_json::from_str(&contents).context("Failed to parse auto-pause config JSON")
+    }
+
+    fn save_config(path: &PathBuf, config: &AutoPauseConfig) -> Result<()> {
+        let json = serde_json::to_string_pretty(config)
+            .context("Failed to serialize auto-pause config")?;
+        fs::write(path, json).context("Failed to write auto-pause config file")?;
+        Ok(())
+    }
+
+    /// Save current configuration to disk
+    pub fn save(&self) -> Result<()> {
+        Self::save_config(&self.config_path, &self.config)
+    }
+
+    pub fn config(&self) -> &AutoPauseConfig {
+        &self.config
+    }
+
+    pub fn config_mut(&mut self) -> &mut AutoPauseConfig {
+        &mut self.config
+    }
+
+    /// Pause the game if its profile asks for it
+    pub fn pause(&mut self, cart_id: &str) {
+        if self.paused.is_some() || !self.config.is_enabled_for(cart_id) {
+            return;
+        }
+
+        let method = match detect_method(Path::new(WRAPPER_FILE)) {
+            PauseMethod::CommandFile => match write_command("pause") {
+                Ok(()) => PauseMethod::CommandFile,
+                Err(e) => {
+                    eprintln!("[AutoPause] {:#}, falling back to SIGSTOP", e);
+                    PauseMethod::Signal
+                }
+            },
+            PauseMethod::Signal => PauseMethod::Signal,
+        };
+        if method == PauseMethod::Signal {
+            signal_emulators("-STOP");
+        }
+
+        println!("[AutoPause] Paused {} ({:?})", cart_id, method);
+        self.paused = Some(method);
+    }
+
+    /// Resume the game if it was paused by `pause`
+    pub fn resume(&mut self) {
+        let Some(method) = self.paused.take() else {
+            return;
+        };
+
+        match method {
+            PauseMethod::CommandFile => {
+                if let Err(e) = write_command("resume") {
+                    eprintln!("[AutoPause] {:#}, sending SIGCONT instead", e);
+                    signal_emulators("-CONT");
+                }
+            }
+            PauseMethod::Signal => signal_emulators("-CONT"),
+        }
+        println!("[AutoPause] Resumed game");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profile_override_beats_global_switch() {
+        let mut config = AutoPauseConfig::default();
+        assert!(!config.is_enabled_for("celeste"));
+
+        config.enabled = true;
+        assert!(config.is_enabled_for("celeste"));
+
+        config.profiles.insert(
+            "celeste".to_string(),
+            GameProfile {
+                auto_pause: Some(false),
+            },
+        );
+        assert!(!config.is_enabled_for("celeste"));
+        assert!(config.is_enabled_for("tetris"));
+    }
+
+    #[test]
+    fn test_cycle_override_drops_empty_profiles() {
+        let mut config = AutoPauseConfig::default();
+        assert_eq!(config.cycle_override("celeste"), Some(true));
+        assert_eq!(config.cycle_override("celeste"), Some(false));
+        assert!(config.profiles.contains_key("celeste"));
+        assert_eq!(config.cycle_override("celeste"), None);
+        assert!(!config.profiles.contains_key("celeste"));
+    }
+
+    #[test]
+    fn test_stale_wrapper_falls_back_to_signal() {
+        assert_eq!(parse_wrapper_pid("1234\n"), Some(1234));
+        assert_eq!(parse_wrapper_pid("0"), None);
+        assert_eq!(parse_wrapper_pid("wrapper"), None);
+
+        let path = std::env::temp_dir().join(format!("kazeta-wrapper-test-{}", std::process::id()));
+        assert_eq!(detect_method(&path), PauseMethod::Signal);
+
+        fs::write(&path, std::process::id().to_string()).unwrap();
+        assert_eq!(detect_method(&path), PauseMethod::CommandFile);
+
+        fs::write(&path, u32::MAX.to_string()).unwrap();
+        assert_eq!(detect_method(&path), PauseMethod::Signal);
+        let _ = fs::remove_file(&path);
+    }
+}
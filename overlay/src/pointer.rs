@@ -0,0 +1,145 @@
+//! Mouse and touchscreen interaction with the overlay menus
+//!
+//! The input daemon forwards pointer motion and clicks while the overlay is
+//! shown. Menus that support the pointer describe their rows with a
+//! `ListLayout`, which both rendering and hit-testing use so the two never
+//! disagree. Hovering a row selects it; pressing and releasing on the same
+//! row activates it like the A button.
+
+use macroquad::prelude::{vec2, Rect, Vec2};
+use std::time::{Duration, Instant};
+
+/// How long the cursor stays drawn after the pointer last moved
+const CURSOR_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// A list of equally tall rows inside a panel centered on screen
+#[derive(Debug, Clone, Copy)]
+pub struct ListLayout {
+    pub panel_width: f32,
+    pub panel_height: f32,
+    /// Top of the first row, from the top of the panel
+    pub first_row_y: f32,
+    pub row_height: f32,
+    pub visible_rows: usize,
+}
+
+/// The overlay main menu
+pub const MAIN_MENU_LAYOUT: ListLayout = ListLayout {
+    panel_width: 600.0,
+    panel_height: 420.0,
+    first_row_y: 120.0,
+    row_height: 50.0,
+    visible_rows: 6,
+};
+
+/// The settings screen
+pub const SETTINGS_LAYOUT: ListLayout = ListLayout {
+    panel_width: 700.0,
+    panel_height: 500.0,
+    first_row_y: 100.0,
+    row_height: 50.0,
+    visible_rows: crate::state::SETTINGS_VISIBLE_ROWS,
+};
+
+impl ListLayout {
+    /// Top-left corner of the panel
+    pub fn origin(&self, screen: Vec2) -> Vec2 {
+        vec2(
+            (screen.x - self.panel_width) / 2.0,
+            (screen.y - self.panel_height) / 2.0,
+        )
+    }
+
+    /// Area of a visible row, used for hover highlights
+    pub fn row_rect(&self, screen: Vec2, row: usize) -> Rect {
+        let origin = self.origin(screen);
+        Rect::new(
+            origin.x + 20.0,
+            origin.y + self.first_row_y + row as f32 * self.row_height,
+            self.panel_width - 40.0,
+            self.row_height,
+        )
+    }
+
+    /// The visible row under a point, if any
+    pub fn row_at(&self, screen: Vec2, point: Vec2) -> Option<usize> {
+        (0..self.visible_rows).find(|&row| self.row_rect(screen, row).contains(point))
+    }
+}
+
+/// Something the pointer can hover and activate
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PointerTarget {
+    /// Index into the visible main menu items
+    MainMenu(usize),
+    /// Index into the settings options
+    Settings(usize),
+}
+
+/// Where the pointer is and what it is over
+#[derive(Debug, Default)]
+pub struct Pointer {
+    /// Position in screen pixels
+    pub position: Option<Vec2>,
+    pub hovered: Option<PointerTarget>,
+    /// Target the press started on; activation needs the release there too
+    pub pressed: Option<PointerTarget>,
+    last_moved: Option<Instant>,
+}
+
+impl Pointer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn moved_to(&mut self, position: Vec2) {
+        self.position = Some(position);
+        self.last_moved = Some(Instant::now());
+    }
+
+    /// Whether to draw the cursor
+    pub fn cursor_visible(&self) -> bool {
+        self.position.is_some()
+            && self
+                .last_moved
+                .is_some_and(|t| t.elapsed() < CURSOR_TIMEOUT)
+    }
+
+    /// Forget hover and press state, e.g. when the overlay hides
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_row_at_matches_row_rect() {
+        let screen = vec2(1280.0, 720.0);
+        let layout = MAIN_MENU_LAYOUT;
+        let origin = layout.origin(screen);
+        assert_eq!(origin, vec2(340.0, 150.0));
+
+        let first = layout.row_rect(screen, 0);
+        assert_eq!(layout.row_at(screen, first.center()), Some(0));
+        let third = layout.row_rect(screen, 2);
+        assert_eq!(
+            layout.row_at(screen, vec2(third.x + 5.0, third.y + 1.0)),
+            Some(2)
+        );
+
+        // Title area, panel margin and below the last row
+        assert_eq!(layout.row_at(screen, vec2(640.0, origin.y + 40.0)), None);
+        assert_eq!(
+            layout.row_at(screen, vec2(origin.x + 5.0, first.center().y)),
+            None
+        );
+        let last = layout.row_rect(screen, layout.visible_rows - 1);
+        assert_eq!(
+            layout.row_at(screen, vec2(640.0, last.bottom() + 1.0)),
+            None
+        );
+    }
+}
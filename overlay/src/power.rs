@@ -0,0 +1,202 @@
+//! Power modes for battery-powered handhelds
+//!
+//! The mode sets how often the overlay redraws, whether decorative effects
+//! (the menu vignette, confetti and toast fades) are drawn, and how long
+//! toasts stay up. With auto-switching on, the overlay runs at full
+//! performance while plugged in and uses the chosen mode on battery.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PowerMode {
+    Performance,
+    Balanced,
+    Battery,
+}
+
+impl PowerMode {
+    pub const ALL: [PowerMode; 3] = [
+        PowerMode::Performance,
+        PowerMode::Balanced,
+        PowerMode::Battery,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            PowerMode::Performance => "Performance",
+            PowerMode::Balanced => "Balanced",
+            PowerMode::Battery => "Battery",
+        }
+    }
+
+    pub fn next(&self) -> PowerMode {
+        let index = Self::ALL.iter().position(|m| m == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    pub fn target_fps(&self) -> u64 {
+        match self {
+            PowerMode::Performance => 60,
+            PowerMode::Balanced => 30,
+            PowerMode::Battery => 20,
+        }
+    }
+
+    pub fn frame_time(&self) -> Duration {
+        Duration::from_micros(1_000_000 / self.target_fps())
+    }
+
+    /// Whether decorative effects and animations are drawn
+    pub fn effects(&self) -> bool {
+        *self != PowerMode::Battery
+    }
+
+    /// Multiplier for toast durations
+    pub fn toast_scale(&self) -> f32 {
+        match self {
+            PowerMode::Performance | PowerMode::Balanced => 1.0,
+            PowerMode::Battery => 0.6,
+        }
+    }
+}
+
+/// Power configuration
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PowerConfig {
+    /// Mode used on battery, or always when auto-switching is off
+    pub mode: PowerMode,
+    /// Use Performance while on AC power
+    pub auto_switch: bool,
+    pub version: u32,
+}
+
+impl Default for PowerConfig {
+    fn default() -> Self {
+        Self {
+            mode: PowerMode::Balanced,
+            auto_switch: true,
+            version: 1,
+        }
+    }
+}
+
+impl PowerConfig {
+    /// The mode in effect. `on_ac` is `None` on machines that don't report
+    /// it, which are treated as plugged in.
+    pub fn active_mode(&self, on_ac: Option<bool>) -> PowerMode {
+        if self.auto_switch && on_ac != Some(false) {
+            PowerMode::Performance
+        } else {
+            self.mode
+        }
+    }
+}
+
+/// Loads and saves the power configuration
+pub struct PowerManager {
+    config: PowerConfig,
+    config_path: PathBuf,
+}
+
+impl PowerManager {
+    /// Create new PowerManager with config loaded from disk
+    pub fn new() -> Result<Self> {
+        let config_path = Self::get_config_path()?;
+
+        let config = if config_path.exists() {
+            println!("[Power] Loading config from {:?}", config_path);
+            Self::load_config(&config_path)?
+        } else {
+            let default_config = PowerConfig::default();
+            if let Err(e) = Self::save_config(&config_path, &default_config) {
+                eprintln!("[Power] Failed to save default config: {}", e);
+            }
+            default_config
+        };
+
+        Ok(Self {
+            config,
+            config_path,
+        })
+    }
+
+    /// Get the configuration file path
+    fn get_config_path() -> Result<PathBuf> {
+        let data_dir =
+            dirs::data_local_dir().context("Could not determine local data directory")?;
+
+        let overlay_dir = data_dir.join("kazeta-plus").join("overlay");
+        if !overlay_dir.exists() {
+            fs::create_dir_all(&overlay_dir)
+                .context("Failed to create overlay config directory")?;
+        }
+
+        Ok(overlay_dir.join("power.json"))
+    }
+
+    fn load_config(path: &PathBuf) -> Result<PowerConfig> {
+        let contents = fs::read_to_string(path).context("Failed to read power config file")?;
+        serde_json::from_str(&contents).context("Failed to parse power config JSON")
+    }
+
+    fn save_config(path: &PathBuf, config: &PowerConfig) -> Result<()> {
+        let json =
+            serde_json::to_string_pretty(config).context("Failed to serialize power config")?;
+        fs::write(path, json).context("Failed to write power config file")?;
+        Ok(())
+    }
+
+    /// Save current configuration to disk
+    pub fn save(&self) -> Result<()> {
+        Self::save_config(&self.config_path, &self.config)
+    }
+
+    pub fn config(&self) -> &PowerConfig {
+        &self.config
+    }
+
+    pub fn config_mut(&mut self) -> &mut PowerConfig {
+        &mut self.config
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_auto_switch_follows_ac() {
+        let config = PowerConfig {
+            mode: PowerMode::Battery,
+            auto_switch: true,
+            version: 1,
+        };
+        assert_eq!(config.active_mode(Some(true)), PowerMode::Performance);
+        assert_eq!(config.active_mode(Some(false)), PowerMode::Battery);
+        // Desktops without a power supply class run at full speed
+        assert_eq!(config.active_mode(None), PowerMode::Performance);
+
+        let manual = PowerConfig {
+            auto_switch: false,
+            ..config
+        };
+        assert_eq!(manual.active_mode(Some(true)), PowerMode::Battery);
+    }
+
+    #[test]
+    fn test_battery_mode_saves_work() {
+        assert_eq!(
+            PowerMode::Performance.frame_time(),
+            Duration::from_micros(16_666)
+        );
+        assert!(PowerMode::Battery.frame_time() > PowerMode::Balanced.frame_time());
+        assert!(!PowerMode::Battery.effects());
+        assert!(PowerMode::Battery.toast_scale() < 1.0);
+        assert_eq!(PowerMode::Battery.next(), PowerMode::Performance);
+    }
+}
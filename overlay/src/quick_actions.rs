@@ -0,0 +1,53 @@
+//! Layout of the radial quick actions palette
+//!
+//! Slots sit evenly around a circle, slot 0 at the top and the rest going
+//! clockwise. The left stick picks the slot it points at.
+
+use std::f32::consts::TAU;
+
+/// Stick deflection needed before it picks a slot
+pub const STICK_DEADZONE: f32 = 0.5;
+
+/// Slot the stick points at, or None while it's centred. `y` is positive up,
+/// as gilrs reports it.
+pub fn slot_for_stick(x: f32, y: f32, count: usize) -> Option<usize> {
+    if count == 0 || (x * x + y * y).sqrt() < STICK_DEADZONE {
+        return None;
+    }
+    // Clockwise angle from the top
+    let angle = x.atan2(y).rem_euclid(TAU);
+    let step = TAU / count as f32;
+    Some((angle / step).round() as usize % count)
+}
+
+/// Offset of slot `index` from the palette's centre, in screen coordinates
+/// (y down)
+pub fn slot_offset(index: usize, count: usize, radius: f32) -> (f32, f32) {
+    let angle = TAU * index as f32 / count.max(1) as f32;
+    (angle.sin() * radius, -angle.cos() * radius)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stick_picks_slot_clockwise_from_top() {
+        assert_eq!(slot_for_stick(0.1, 0.2, 4), None);
+        assert_eq!(slot_for_stick(0.0, 1.0, 4), Some(0));
+        assert_eq!(slot_for_stick(1.0, 0.0, 4), Some(1));
+        assert_eq!(slot_for_stick(0.0, -1.0, 4), Some(2));
+        assert_eq!(slot_for_stick(-1.0, 0.0, 4), Some(3));
+        // Just left of the top still rounds to slot 0
+        assert_eq!(slot_for_stick(-0.2, 1.0, 8), Some(0));
+        assert_eq!(slot_for_stick(0.7, 0.7, 8), Some(1));
+    }
+
+    #[test]
+    fn test_slot_offsets_match_stick_directions() {
+        let (x, y) = slot_offset(0, 4, 100.0);
+        assert!(x.abs() < 0.01 && (y + 100.0).abs() < 0.01);
+        let (x, y) = slot_offset(1, 4, 100.0);
+        assert!((x - 100.0).abs() < 0.01 && y.abs() < 0.01);
+    }
+}
@@ -0,0 +1,243 @@
+//! Graceful quit of the running game
+//!
+//! Killing an emulator in the middle of a write can corrupt its save, so
+//! quitting from the overlay is a short handshake with the runtime wrapper:
+//!
+//! 1. The overlay removes any old ack, then writes `quit` to
+//!    `/tmp/kazeta-quit-game` and to the emulator command file (see `pause`).
+//! 2. The wrapper has the emulator flush SRAM and writes `saved` to
+//!    `/tmp/kazeta-quit-ack`, or `error: <reason>` if the flush failed.
+//! 3. Once the ack arrives, or after `SAVE_TIMEOUT`, the emulators get
+//!    SIGTERM, then SIGKILL if they are still running `TERM_GRACE` later.
+//!
+//! Without a live wrapper nothing can confirm the flush, so step 3 starts
+//! straight away and the emulator saves on SIGTERM if it can. The overlay
+//! shows a "Saving..." screen until the game is gone.
+
+use crate::pause;
+use std::fs;
+use std::time::{Duration, Instant};
+
+/// Quit request checked by runtime wrappers
+const QUIT_SIGNAL_FILE: &str = "/tmp/kazeta-quit-game";
+
+/// Written by the wrapper once the emulator has flushed its save
+const ACK_FILE: &str = "/tmp/kazeta-quit-ack";
+
+/// How long to wait for the wrapper to confirm the save
+pub const SAVE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long the emulators get to exit after SIGTERM before SIGKILL
+pub const TERM_GRACE: Duration = Duration::from_secs(3);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuitPhase {
+    /// Waiting for the wrapper to confirm the save
+    Saving,
+    /// SIGTERM sent; waiting for the emulators to exit
+    Terminating,
+    Done,
+}
+
+/// How the save step ended
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SaveOutcome {
+    Confirmed,
+    /// The wrapper reported an error
+    Failed(String),
+    TimedOut,
+    /// No wrapper to ask
+    Unconfirmed,
+}
+
+/// What the caller should do after a `step`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuitAction {
+    None,
+    Terminate,
+    Kill,
+    Finished,
+}
+
+pub struct GracefulQuit {
+    pub phase: QuitPhase,
+    pub outcome: Option<SaveOutcome>,
+    started: Instant,
+    terminated_at: Option<Instant>,
+    /// A live wrapper will answer the quit command
+    has_wrapper: bool,
+}
+
+impl GracefulQuit {
+    /// Ask the wrapper to save and quit
+    pub fn start() -> Self {
+        let _ = fs::remove_file(ACK_FILE);
+        if let Err(e) = fs::write(QUIT_SIGNAL_FILE, "quit\n") {
+            eprintln!("[Quit] Failed to write {}: {}", QUIT_SIGNAL_FILE, e);
+        }
+
+        let has_wrapper = pause::wrapper_handles_commands();
+        if has_wrapper {
+            if let Err(e) = pause::write_command("quit") {
+                eprintln!("[Quit] {:#}", e);
+            }
+            println!("[Quit] Asked the wrapper to save and quit");
+        } else {
+            println!("[Quit] No wrapper to confirm the save, terminating emulators");
+        }
+        Self::new(Instant::now(), has_wrapper)
+    }
+
+    fn new(now: Instant, has_wrapper: bool) -> Self {
+        Self {
+            phase: QuitPhase::Saving,
+            outcome: None,
+            started: now,
+            terminated_at: None,
+            has_wrapper,
+        }
+    }
+
+    /// Check the ack file and the emulators, and advance
+    pub fn poll(&mut self) -> QuitAction {
+        let ack = match self.phase {
+            QuitPhase::Saving => fs::read_to_string(ACK_FILE).ok(),
+            _ => None,
+        };
+        self.step(Instant::now(), ack.as_deref(), emulators_running)
+    }
+
+    /// Advance with the ack file's contents, if it exists.
+    /// `emulators_running` is only called while waiting for them to exit.
+    pub fn step(
+        &mut self,
+        now: Instant,
+        ack: Option<&str>,
+        emulators_running: impl FnOnce() -> bool,
+    ) -> QuitAction {
+        match self.phase {
+            QuitPhase::Saving => {
+                let outcome = if !self.has_wrapper {
+                    SaveOutcome::Unconfirmed
+                } else if let Some(ack) = ack {
+                    match ack.trim().strip_prefix("error") {
+                        Some(reason) => {
+                            SaveOutcome::Failed(reason.trim_start_matches(':').trim().to_string())
+                        }
+                        None => SaveOutcome::Confirmed,
+                    }
+                } else if now.duration_since(self.started) >= SAVE_TIMEOUT {
+                    SaveOutcome::TimedOut
+                } else {
+                    return QuitAction::None;
+                };
+                println!("[Quit] Save step finished: {:?}", outcome);
+                self.outcome = Some(outcome);
+                self.phase = QuitPhase::Terminating;
+                self.terminated_at = Some(now);
+                QuitAction::Terminate
+            }
+            QuitPhase::Terminating => {
+                let since = self
+                    .terminated_at
+                    .map_or(Duration::ZERO, |t| now.duration_since(t));
+                if !emulators_running() {
+                    self.phase = QuitPhase::Done;
+                    QuitAction::Finished
+                } else if since >= TERM_GRACE {
+                    println!("[Quit] Emulators still running after SIGTERM, killing them");
+                    self.phase = QuitPhase::Done;
+                    QuitAction::Kill
+                } else {
+                    QuitAction::None
+                }
+            }
+            QuitPhase::Done => QuitAction::None,
+        }
+    }
+
+    /// Seconds left before the save step gives up
+    pub fn seconds_left(&self) -> u64 {
+        SAVE_TIMEOUT
+            .saturating_sub(self.started.elapsed())
+            .as_secs()
+    }
+
+    /// Seconds since the quit started, for the spinner
+    pub fn elapsed_secs(&self) -> f32 {
+        self.started.elapsed().as_secs_f32()
+    }
+}
+
+fn emulators_running() -> bool {
+    #[cfg(target_os = "linux")]
+    {
+        pause::EMULATOR_PROCESSES.iter().any(|emu| {
+            std::process::Command::new("pgrep")
+                .arg(emu)
+                .output()
+                .is_ok_and(|out| out.status.success())
+        })
+    }
+    #[cfg(not(target_os = "linux"))]
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ack_then_terminate() {
+        let start = Instant::now();
+        let mut quit = GracefulQuit::new(start, true);
+
+        assert_eq!(quit.step(start, None, || unreachable!()), QuitAction::None);
+        assert_eq!(
+            quit.step(
+                start + Duration::from_secs(1),
+                Some("saved\n"),
+                || unreachable!()
+            ),
+            QuitAction::Terminate
+        );
+        assert_eq!(quit.outcome, Some(SaveOutcome::Confirmed));
+
+        // Still running within the grace period, then gone
+        let t = start + Duration::from_secs(2);
+        assert_eq!(quit.step(t, None, || true), QuitAction::None);
+        assert_eq!(quit.step(t, None, || false), QuitAction::Finished);
+        assert_eq!(quit.phase, QuitPhase::Done);
+    }
+
+    #[test]
+    fn test_timeout_escalates_to_kill() {
+        let start = Instant::now();
+        let mut quit = GracefulQuit::new(start, true);
+
+        assert_eq!(
+            quit.step(start + SAVE_TIMEOUT, None, || unreachable!()),
+            QuitAction::Terminate
+        );
+        assert_eq!(quit.outcome, Some(SaveOutcome::TimedOut));
+        assert_eq!(
+            quit.step(start + SAVE_TIMEOUT + TERM_GRACE, None, || true),
+            QuitAction::Kill
+        );
+
+        let mut quit = GracefulQuit::new(start, true);
+        quit.step(start, Some("error: disk full"), || unreachable!());
+        assert_eq!(
+            quit.outcome,
+            Some(SaveOutcome::Failed("disk full".to_string()))
+        );
+
+        // Nobody to wait for
+        let mut quit = GracefulQuit::new(start, false);
+        assert_eq!(
+            quit.step(start, None, || unreachable!()),
+            QuitAction::Terminate
+        );
+        assert_eq!(quit.outcome, Some(SaveOutcome::Unconfirmed));
+    }
+}
@@ -0,0 +1,194 @@
+//! Crash recovery across overlay restarts
+//!
+//! While a game runs the overlay keeps a snapshot of what it knows about it
+//! (the game, when the session started, its achievements with their progress
+//! and the unlock history) in `recovery.json`. The snapshot is rewritten every
+//! `SAVE_INTERVAL` and after each message that could have changed it. On
+//! startup a snapshot younger than `MAX_AGE` is restored, so a restarted
+//! overlay picks up where the crashed one left off. Once no game is running
+//! the file is removed, so a clean shutdown leaves nothing to restore.
+
+use crate::ipc::{AchievementInfo, UnlockRecord};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+/// How often the snapshot is rewritten while nothing changes
+pub const SAVE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Snapshots older than this belong to a session that has long ended
+pub const MAX_AGE_SECS: u64 = 5 * 60;
+
+/// What the overlay knew about the running game
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SessionSnapshot {
+    /// Unix timestamp of the write
+    pub saved_at: u64,
+    pub cart_id: String,
+    /// Unix timestamp the game was started
+    pub session_started_at: u64,
+    #[serde(default)]
+    pub game_id: Option<u32>,
+    #[serde(default)]
+    pub game_title: String,
+    #[serde(default)]
+    pub game_hash: Option<String>,
+    #[serde(default)]
+    pub console: String,
+    #[serde(default)]
+    pub icon_url: Option<String>,
+    /// Includes progress, which is what the pinned achievements come from
+    #[serde(default)]
+    pub achievements: Vec<AchievementInfo>,
+    #[serde(default)]
+    pub unlocks: Vec<UnlockRecord>,
+    /// Game process the watchdog was following
+    #[serde(default)]
+    pub pid: Option<u32>,
+}
+
+impl SessionSnapshot {
+    pub fn is_fresh(&self, now: u64) -> bool {
+        now.saturating_sub(self.saved_at) <= MAX_AGE_SECS
+    }
+}
+
+/// Writes and reads the snapshot
+pub struct Recovery {
+    path: PathBuf,
+    last_save: Option<Instant>,
+    dirty: bool,
+}
+
+impl Recovery {
+    pub fn new() -> Result<Self> {
+        Ok(Self::with_path(Self::get_snapshot_path()?))
+    }
+
+    pub fn with_path(path: PathBuf) -> Self {
+        Self {
+            path,
+            last_save: None,
+            dirty: false,
+        }
+    }
+
+    /// Get the snapshot file path
+    fn get_snapshot_path() -> Result<PathBuf> {
+        let data_dir =
+            dirs::data_local_dir().context("Could not determine local data directory")?;
+
+        let overlay_dir = data_dir.join("kazeta-plus").join("overlay");
+        if !overlay_dir.exists() {
+            fs::create_dir_all(&overlay_dir)
+                .context("Failed to create overlay config directory")?;
+        }
+
+        Ok(overlay_dir.join("recovery.json"))
+    }
+
+    /// Something may have changed; write on the next update
+    pub fn mark_dirty(&mut self) {
+        self.dirty = true;
+    }
+
+    /// Whether the snapshot should be written now
+    pub fn is_due(&self) -> bool {
+        self.dirty || self.last_save.is_none_or(|t| t.elapsed() >= SAVE_INTERVAL)
+    }
+
+    /// Written under a temporary name first so a crash mid-write never
+    /// leaves half a snapshot
+    pub fn save(&mut self, snapshot: &SessionSnapshot) -> Result<()> {
+        self.dirty = false;
+        self.last_save = Some(Instant::now());
+        let json =
+            serde_json::to_string(snapshot).context("Failed to serialize recovery snapshot")?;
+        let partial = self.path.with_extension("json.partial");
+        fs::write(&partial, json).context("Failed to write recovery snapshot")?;
+        fs::rename(&partial, &self.path).context("Failed to write recovery snapshot")
+    }
+
+    /// No game is running; drop the snapshot
+    pub fn clear(&mut self) {
+        self.dirty = false;
+        self.last_save = Some(Instant::now());
+        if let Err(e) = fs::remove_file(&self.path) {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                eprintln!("[Recovery] Failed to remove snapshot: {}", e);
+            }
+        }
+    }
+
+    /// The snapshot left by a previous run, if it's recent enough to trust
+    pub fn load_fresh(&self, now: u64) -> Option<SessionSnapshot> {
+        let contents = fs::read_to_string(&self.path).ok()?;
+        let snapshot: SessionSnapshot = match serde_json::from_str(&contents) {
+            Ok(snapshot) => snapshot,
+            Err(e) => {
+                eprintln!("[Recovery] Ignoring invalid snapshot: {}", e);
+                return None;
+            }
+        };
+        if !snapshot.is_fresh(now) {
+            println!(
+                "[Recovery] Ignoring snapshot from {}s ago",
+                now.saturating_sub(snapshot.saved_at)
+            );
+            return None;
+        }
+        Some(snapshot)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(saved_at: u64) -> SessionSnapshot {
+        SessionSnapshot {
+            saved_at,
+            cart_id: "celeste".to_string(),
+            session_started_at: saved_at - 600,
+            game_id: Some(1234),
+            game_title: "Celeste".to_string(),
+            game_hash: Some("abc".to_string()),
+            console: "SNES".to_string(),
+            icon_url: None,
+            achievements: Vec::new(),
+            unlocks: Vec::new(),
+            pid: None,
+        }
+    }
+
+    #[test]
+    fn test_snapshot_round_trip_and_clear() {
+        let dir =
+            std::env::temp_dir().join(format!("kazeta-overlay-recovery-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let mut recovery = Recovery::with_path(dir.join("recovery.json"));
+
+        assert!(recovery.is_due());
+        assert!(recovery.load_fresh(10_000).is_none());
+
+        recovery.save(&snapshot(10_000)).unwrap();
+        assert!(!recovery.is_due());
+        recovery.mark_dirty();
+        assert!(recovery.is_due());
+
+        let loaded = recovery.load_fresh(10_000 + MAX_AGE_SECS).unwrap();
+        assert_eq!(loaded.cart_id, "celeste");
+        assert_eq!(loaded.session_started_at, 9_400);
+        assert_eq!(loaded.game_id, Some(1234));
+
+        // Too old to be the game that's still running
+        assert!(recovery.load_fresh(10_000 + MAX_AGE_SECS + 1).is_none());
+
+        recovery.clear();
+        assert!(recovery.load_fresh(10_000).is_none());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -1,15 +1,65 @@
-use crate::controllers::{BluetoothScanState, CONTROLLER_MENU_OPTIONS, MAX_PLAYERS};
+use crate::backdrop::Backdrop;
+use crate::calibration::{DriftWizard, Stick};
+use crate::celebration::{self, Celebration};
+use crate::controllers::{
+    BluetoothScanState, CONTROLLER_MENU_OPTIONS, CONTROLLER_SETTINGS_ROWS, MAX_PLAYERS,
+};
+use crate::controls::{Button, ControlsLayout};
+use crate::debug_console::{LOG_CAPACITY, TEST_MESSAGES};
+use crate::hud_layout::HudWidget;
 use crate::ipc::{OverlayScreen, ToastStyle};
-use crate::state::OverlayState;
+use crate::ipc_rules::RuleAction;
+use crate::login::{
+    Key, LoginField, OnScreenKeyboard, ACTION_KEYS, KEYBOARD_COLUMNS, KEYBOARD_ROWS,
+};
+use crate::menu_config::QuickAction;
+use crate::music::{self, MusicSource};
+use crate::onboarding::Tour;
+use crate::pointer::{PointerTarget, MAIN_MENU_LAYOUT, SETTINGS_LAYOUT};
+use crate::quick_actions;
+use crate::search::SEARCH_VISIBLE_ROWS;
+use crate::snooze;
+use crate::speedrun::{self, RunState};
+use crate::state::{
+    AchievementFilter, OverlayState, ACHIEVEMENTS_VISIBLE_ROWS, CHALLENGES_VISIBLE_ROWS,
+    COMPARE_VISIBLE_ROWS, DEBUG_LOG_VISIBLE_ROWS, MUSIC_SETTING_ROWS, SETTINGS_VISIBLE_ROWS,
+};
+use crate::stats::{self, EstimateBasis, HEATMAP_WEEKS, RARITY_BUCKETS};
+use crate::stream_output::StreamEvent;
+use kazeta_overlay::gamepad_tester::{self, draw_trigger};
 use macroquad::prelude::*;
+use std::cell::Cell;
+use std::path::Path;
 
 /// Helper to get theme from state
 fn theme(state: &OverlayState) -> &crate::themes::Theme {
     state.theme_config.theme()
 }
 
+thread_local! {
+    /// Font size multiplier of the theme being drawn
+    static FONT_SCALE: Cell<f32> = const { Cell::new(1.0) };
+}
+
+/// `macroquad::text::draw_text` at the theme's font scale
+fn draw_text(text: &str, x: f32, y: f32, font_size: f32, color: Color) -> TextDimensions {
+    macroquad::text::draw_text(text, x, y, font_size * FONT_SCALE.get(), color)
+}
+
+/// `macroquad::text::measure_text` at the theme's font scale
+fn measure_text(
+    text: &str,
+    font: Option<&Font>,
+    font_size: u16,
+    font_scale: f32,
+) -> TextDimensions {
+    let scaled = (font_size as f32 * FONT_SCALE.get()).round() as u16;
+    macroquad::text::measure_text(text, font, scaled, font_scale)
+}
+
 pub async fn render(state: &OverlayState) {
     clear_background(BLANK);
+    FONT_SCALE.set(theme(state).font_scale);
 
     // Render overlay menu if visible
     if state.visible {
@@ -19,49 +69,118 @@ pub async fn render(state: &OverlayState) {
     // Always render toasts (even when overlay is hidden)
     render_toasts(state);
 
-    // Render performance HUD if enabled
-    if state.performance.is_visible() {
-        render_performance_hud(state);
+    render_hud(state);
+
+    if let Some(celebration) = &state.celebration {
+        render_celebration(state, celebration);
+    }
+
+    if state.visible && state.pointer.cursor_visible() {
+        render_pointer_cursor(state);
     }
 
     next_frame().await;
 }
 
 fn render_overlay_menu(state: &OverlayState) {
-    let t = theme(state);
     // Semi-transparent background overlay
     draw_rectangle(
         0.0,
         0.0,
         screen_width(),
         screen_height(),
-        t.background_overlay,
+        backdrop_color(state),
     );
-    draw_vignette();
+    if state.power_mode().effects() {
+        draw_vignette();
+    }
 
     match state.current_screen {
         OverlayScreen::Main => render_main_menu(state),
         OverlayScreen::Settings => render_settings_screen(state),
+        OverlayScreen::RaLogin => render_login_screen(state),
         OverlayScreen::Achievements => render_achievements_screen(state),
+        OverlayScreen::Challenges => render_challenges_screen(state),
+        OverlayScreen::AchievementHints => render_hints_screen(state),
+        OverlayScreen::FriendCompare => render_compare_screen(state),
+        OverlayScreen::AchievementStats => render_achievement_stats(state),
         OverlayScreen::Performance => render_performance(state),
         OverlayScreen::Playtime => render_playtime(state),
+        OverlayScreen::Speedrun => render_speedrun(state),
+        OverlayScreen::Cheats => render_cheats(state),
+        OverlayScreen::GameControls => render_game_controls(state),
         OverlayScreen::Controllers => render_controllers_menu(state),
         OverlayScreen::BluetoothPairing => render_bluetooth_screen(state),
         OverlayScreen::ControllerAssign => render_assign_screen(state),
         OverlayScreen::GamepadTester => render_gamepad_tester(state),
+        OverlayScreen::ControllerSettings => render_controller_settings(state),
+        OverlayScreen::Macros => render_macros_screen(state),
+        OverlayScreen::Music => render_music_screen(state),
         OverlayScreen::HotkeySettings => render_hotkey_settings(state),
         OverlayScreen::MenuCustomization => render_menu_customization(state),
+        OverlayScreen::QuickActions => render_quick_actions(state),
+        OverlayScreen::QuickActionsSettings => render_quick_actions_settings(state),
+        OverlayScreen::IpcRules => render_ipc_rules(state),
+        OverlayScreen::DebugConsole => render_debug_console(state),
         OverlayScreen::ThemeSelection => render_theme_selection(state),
+        OverlayScreen::ThemePreview => render_theme_preview(state),
+        OverlayScreen::ThemeEditor => render_theme_editor(state),
         OverlayScreen::QuitConfirm => render_quit_confirm(state),
+        OverlayScreen::QuitSaving => render_quit_saving(state),
+        OverlayScreen::Search => render_search_screen(state),
+        OverlayScreen::HudLayout => render_hud_editor(state),
     }
+
+    render_network_indicator(state);
+}
+
+/// Connection status in the top-right corner: SSID and latency to RA
+fn render_network_indicator(state: &OverlayState) {
+    let Some(status) = state.network.status() else {
+        return;
+    };
+    let t = theme(state);
+
+    let (dot_color, label) = if status.online {
+        let mut parts = Vec::new();
+        if let Some(ssid) = &status.ssid {
+            parts.push(ssid.clone());
+        }
+        if let Some(latency) = status.latency_ms {
+            parts.push(format!("RA {} ms", latency));
+        }
+        let color = match status.latency_ms {
+            Some(ms) if ms > 500 => t.warning,
+            _ => t.success,
+        };
+        (
+            color,
+            if parts.is_empty() {
+                "Online".to_string()
+            } else {
+                parts.join(" • ")
+            },
+        )
+    } else {
+        (t.error, "Offline".to_string())
+    };
+
+    let font_size = 16.0;
+    let width = measure_text(&label, None, font_size as u16, 1.0).width;
+    let x = screen_width() - width - 16.0;
+    let y = 24.0;
+    draw_circle(x - 10.0, y - 5.0, 4.0, dot_color);
+    draw_text(&label, x, y, font_size, t.text_secondary);
 }
 
 fn render_main_menu(state: &OverlayState) {
     let t = theme(state);
-    let menu_width = 600.0;
-    let menu_height = 420.0;
-    let menu_x = (screen_width() - menu_width) / 2.0;
-    let menu_y = (screen_height() - menu_height) / 2.0;
+    let layout = MAIN_MENU_LAYOUT;
+    let screen = vec2(screen_width(), screen_height());
+    let menu_width = layout.panel_width;
+    let menu_height = layout.panel_height;
+    let menu_x = layout.origin(screen).x;
+    let menu_y = layout.origin(screen).y;
 
     draw_panel(menu_x, menu_y, menu_width, menu_height, &t);
 
@@ -77,11 +196,36 @@ fn render_main_menu(state: &OverlayState) {
         t.cursor,
     );
 
+    // Header chips: unlocks since the overlay was last opened, and the snooze
+    let mut chip_right = menu_x + menu_width - 16.0;
+    if state.recent_unlocks > 0 {
+        let text = format!("🏆 {} new", state.recent_unlocks);
+        chip_right = draw_header_chip(&text, chip_right, menu_y + 14.0, t.success, t) - 8.0;
+    }
+    if state.toasts.snooze.enabled {
+        draw_header_chip("🔕 Snoozed", chip_right, menu_y + 14.0, t.text_secondary, t);
+    }
+
+    // Now playing, between the title and the options
+    let tracker = &state.achievements;
+    if !tracker.game_title.is_empty() {
+        let row_y = menu_y + 72.0;
+        let icon_size = 36.0;
+        let mut x = menu_x + 40.0;
+        if draw_game_icon(state, x, row_y, icon_size) {
+            x += icon_size + 10.0;
+        }
+        draw_text(&tracker.game_title, x, row_y + 24.0, 20.0, t.text);
+        if !tracker.console.is_empty() {
+            draw_console_badge(&tracker.console, menu_x + menu_width - 40.0, row_y + 8.0, t);
+        }
+    }
+
     // Menu options from config
     let visible_items = state.menu_config.config().get_visible_items();
-    let option_start_y = menu_y + 120.0;
-    let option_height = 50.0;
-    const MAX_VISIBLE: usize = 6;
+    let option_start_y = menu_y + layout.first_row_y;
+    let option_height = layout.row_height;
+    const MAX_VISIBLE: usize = MAIN_MENU_LAYOUT.visible_rows;
 
     // Use scroll offset from state
     let scroll_offset = state.main_menu_scroll_offset;
@@ -95,19 +239,40 @@ fn render_main_menu(state: &OverlayState) {
         let menu_item_id = visible_items[item_idx];
         let y = option_start_y + (i as f32 * option_height);
         let option = menu_item_id.display_name();
-        
+
         // Quit option gets special red coloring
         let is_quit = menu_item_id == crate::menu_config::MenuItemId::Quit;
         let is_selected = item_idx == state.selected_option;
         let color = if is_selected {
-            if is_quit { t.error } else { t.cursor }
+            if is_quit {
+                t.error
+            } else {
+                t.cursor
+            }
         } else {
-            if is_quit { Color::new(t.error.r * 0.7, t.error.g * 0.3, t.error.b * 0.3, 1.0) } else { t.text }
+            if is_quit {
+                Color::new(t.error.r * 0.7, t.error.g * 0.3, t.error.b * 0.3, 1.0)
+            } else {
+                t.text
+            }
         };
 
+        draw_pointer_highlight(
+            state,
+            PointerTarget::MainMenu(item_idx),
+            layout.row_rect(screen, i),
+            t,
+        );
+
         // Selection indicator
         if is_selected {
-            draw_text("►", menu_x + 40.0, y + 30.0, 30.0, if is_quit { t.error } else { t.cursor });
+            draw_text(
+                "►",
+                menu_x + 40.0,
+                y + 30.0,
+                30.0,
+                if is_quit { t.error } else { t.cursor },
+            );
         }
 
         draw_text(option, menu_x + 80.0, y + 30.0, 28.0, color);
@@ -115,10 +280,22 @@ fn render_main_menu(state: &OverlayState) {
 
     // Scroll indicators
     if scroll_offset > 0 {
-        draw_text("▲", menu_x + menu_width - 25.0, option_start_y + 10.0, 16.0, t.text_secondary);
+        draw_text(
+            "▲",
+            menu_x + menu_width - 25.0,
+            option_start_y + 10.0,
+            16.0,
+            t.text_secondary,
+        );
     }
     if scroll_offset + MAX_VISIBLE < visible_count {
-        draw_text("▼", menu_x + menu_width - 25.0, option_start_y + (MAX_VISIBLE as f32 * option_height) - 15.0, 16.0, t.text_secondary);
+        draw_text(
+            "▼",
+            menu_x + menu_width - 25.0,
+            option_start_y + (MAX_VISIBLE as f32 * option_height) - 15.0,
+            16.0,
+            t.text_secondary,
+        );
     }
 
     // Playtime display (if game is active)
@@ -133,24 +310,44 @@ fn render_main_menu(state: &OverlayState) {
             format!("Session: {}m", mins)
         };
 
-        draw_text(&session_text, menu_x + 20.0, menu_y + menu_height - 60.0, 18.0, LIGHTGRAY);
+        draw_text(
+            &session_text,
+            menu_x + 20.0,
+            menu_y + menu_height - 60.0,
+            18.0,
+            LIGHTGRAY,
+        );
 
         // Show total playtime for this game
         if let Some(entry) = state.playtime.database.entries.get(&session.cart_id) {
             let total_hours = entry.total_seconds / 3600;
             let total_mins = (entry.total_seconds % 3600) / 60;
             let total_text = if total_hours > 0 {
-                format!("Total: {}h {}m • {} plays", total_hours, total_mins, entry.play_count)
+                format!(
+                    "Total: {}h {}m • {} plays",
+                    total_hours, total_mins, entry.play_count
+                )
             } else {
                 format!("Total: {}m • {} plays", total_mins, entry.play_count)
             };
-            draw_text(&total_text, menu_x + 240.0, menu_y + menu_height - 60.0, 18.0, GRAY);
+            draw_text(
+                &total_text,
+                menu_x + 240.0,
+                menu_y + menu_height - 60.0,
+                18.0,
+                GRAY,
+            );
         }
     }
 
+    if let Some(tour) = &state.tour {
+        render_tour(state, tour);
+        return;
+    }
+
     // Controls hint
     draw_text(
-        "Use D-Pad to navigate • A to select • Guide button to close",
+        "D-Pad: Navigate • A: Select • X: Search • Guide: Close",
         menu_x + 20.0,
         menu_y + menu_height - 20.0,
         18.0,
@@ -158,28 +355,174 @@ fn render_main_menu(state: &OverlayState) {
     );
 }
 
-fn render_settings_screen(state: &OverlayState) {
+/// First-run tour: outlines the menu row being described and explains it
+/// in a callout under the menu
+fn render_tour(state: &OverlayState, tour: &Tour) {
+    let Some(step) = tour.current() else { return };
     let t = theme(state);
-    let menu_width = 700.0;
-    let menu_height = 500.0;
-    let menu_x = (screen_width() - menu_width) / 2.0;
-    let menu_y = (screen_height() - menu_height) / 2.0;
+    let layout = MAIN_MENU_LAYOUT;
+    let screen = vec2(screen_width(), screen_height());
+
+    if let Some(row) = state
+        .selected_option
+        .checked_sub(state.main_menu_scroll_offset)
+    {
+        if row < layout.visible_rows {
+            let rect = layout.row_rect(screen, row);
+            draw_rectangle_lines(rect.x, rect.y, rect.w, rect.h, 3.0, t.cursor);
+        }
+    }
 
-    draw_rectangle(menu_x, menu_y, menu_width, menu_height, t.panel_background);
-    draw_rectangle_lines(menu_x, menu_y, menu_width, menu_height, 3.0, t.panel_border);
+    let width = layout.panel_width;
+    let padding = 16.0;
+    let lines = wrap_text(step.description, width - padding * 2.0, 18.0);
+    let height =
+        70.0 + lines.len() as f32 * 22.0 + if step.hotkeys.is_empty() { 0.0 } else { 24.0 };
+    let x = layout.origin(screen).x;
+    let y = (layout.origin(screen).y + layout.panel_height + 16.0).min(screen.y - height - 8.0);
 
+    draw_panel(x, y, width, height, t);
+    draw_rectangle_lines(x, y, width, height, 3.0, t.cursor);
+
+    let mut line_y = y + 28.0;
     draw_text(
-        "SETTINGS",
-        menu_x + 20.0,
-        menu_y + 40.0,
-        36.0,
+        step.item.display_name(),
+        x + padding,
+        line_y,
+        24.0,
         t.cursor,
     );
+    let counter = format!("{}/{}", tour.index + 1, tour.steps.len());
+    let counter_width = measure_text(&counter, None, 16, 1.0).width;
+    draw_text(
+        &counter,
+        x + width - padding - counter_width,
+        line_y,
+        16.0,
+        t.text_secondary,
+    );
+
+    for line in &lines {
+        line_y += 22.0;
+        draw_text(line, x + padding, line_y, 18.0, t.text);
+    }
+    if !step.hotkeys.is_empty() {
+        line_y += 24.0;
+        draw_text(step.hotkeys, x + padding, line_y, 16.0, t.success);
+    }
+
+    let hint = if tour.is_last() {
+        "A: Done • B: Back"
+    } else {
+        "A: Next • B: Back • X: Skip Tour"
+    };
+    draw_text(hint, x + padding, y + height - 12.0, 16.0, t.text_secondary);
+}
+
+fn render_settings_screen(state: &OverlayState) {
+    let t = theme(state);
+    let layout = SETTINGS_LAYOUT;
+    let screen = vec2(screen_width(), screen_height());
+    let menu_width = layout.panel_width;
+    let menu_height = layout.panel_height;
+    let menu_x = layout.origin(screen).x;
+    let menu_y = layout.origin(screen).y;
+
+    draw_rectangle(menu_x, menu_y, menu_width, menu_height, t.panel_background);
+    draw_rectangle_lines(menu_x, menu_y, menu_width, menu_height, 3.0, t.panel_border);
 
-    let options = ["Menu Customization", "Theme Selection"];
-    let option_start_y = menu_y + 100.0;
-    let option_height = 50.0;
-    const MAX_VISIBLE: usize = 6;
+    draw_text("SETTINGS", menu_x + 20.0, menu_y + 40.0, 36.0, t.cursor);
+
+    let stream_label = match &state.stream {
+        Some(stream) if stream.is_enabled() => "Stream Mode: ON",
+        _ => "Stream Mode: OFF",
+    };
+    let account_label = match &state.ra_user {
+        Some(user) => format!("RetroAchievements: {}", user),
+        None => "RetroAchievements: Log In".to_string(),
+    };
+    let auto_pause = state.auto_pause.as_ref().map(|a| a.config());
+    let auto_pause_label = match auto_pause {
+        Some(config) if config.enabled => "Auto-Pause: ON",
+        _ => "Auto-Pause: OFF",
+    };
+    let cart_id = state
+        .playtime
+        .current_session
+        .as_ref()
+        .map(|s| s.cart_id.as_str());
+    let game_pause_label = match (auto_pause, cart_id) {
+        (Some(config), Some(cart_id)) => {
+            match config.profiles.get(cart_id).and_then(|p| p.auto_pause) {
+                Some(true) => "Auto-Pause (This Game): ON",
+                Some(false) => "Auto-Pause (This Game): OFF",
+                None => "Auto-Pause (This Game): Default",
+            }
+        }
+        _ => "Auto-Pause (This Game): No Game",
+    };
+    let haptics = state.haptics.as_ref().map(|h| h.config());
+    let haptics_label = match haptics {
+        Some(config) if config.enabled => "Rumble: ON",
+        _ => "Rumble: OFF",
+    };
+    let intensity_label = format!(
+        "Rumble Intensity: {}%",
+        haptics.map(|c| c.intensity).unwrap_or(0)
+    );
+    let power = state.power.as_ref().map(|p| p.config());
+    let power_label = format!(
+        "Power Mode: {}",
+        power.map_or("Performance", |c| c.mode.name())
+    );
+    let auto_switch_label = match power {
+        Some(config) if config.auto_switch => "Performance on AC: ON",
+        _ => "Performance on AC: OFF",
+    };
+    let snooze = &state.toasts.snooze;
+    let on_off = |on: bool| if on { "ON" } else { "OFF" };
+    let snooze_label = match state.toasts.snoozed_count() {
+        0 => format!("Snooze Notifications: {}", on_off(snooze.enabled)),
+        held => format!(
+            "Snooze Notifications: {} ({} held)",
+            on_off(snooze.enabled),
+            held
+        ),
+    };
+    let style_labels = snooze::STYLES.map(|style| {
+        let name = match style {
+            ToastStyle::Success => "Unlocks & Successes",
+            ToastStyle::Info => "Info",
+            ToastStyle::Warning => "Warnings",
+            ToastStyle::Error => "Errors",
+        };
+        format!("  Snooze {}: {}", name, on_off(snooze.style(style)))
+    });
+    let options = [
+        "Menu Customization",
+        "Theme Selection",
+        stream_label,
+        &account_label,
+        "Log Out",
+        auto_pause_label,
+        game_pause_label,
+        haptics_label,
+        &intensity_label,
+        "HUD Layout",
+        &power_label,
+        auto_switch_label,
+        "Quick Actions",
+        "Message Rules",
+        "Replay Tour",
+        &snooze_label,
+        &style_labels[0],
+        &style_labels[1],
+        &style_labels[2],
+        &style_labels[3],
+    ];
+    let option_start_y = menu_y + layout.first_row_y;
+    let option_height = layout.row_height;
+    const MAX_VISIBLE: usize = SETTINGS_VISIBLE_ROWS;
 
     // Use scroll offset from state
     let scroll_offset = state.settings_scroll_offset;
@@ -193,7 +536,27 @@ fn render_settings_screen(state: &OverlayState) {
         let option = options[item_idx];
         let y = option_start_y + (i as f32 * option_height);
         let is_selected = item_idx == state.settings_selected_option;
-        let color = if is_selected { t.cursor } else { t.text };
+        // Log Out does nothing without stored credentials, the game override
+        // without a game, the intensity with rumble off, nor the snoozed
+        // styles with snoozing off
+        let disabled = (item_idx == 4 && state.ra_user.is_none())
+            || (item_idx == 6 && cart_id.is_none())
+            || (item_idx == 8 && !haptics.is_some_and(|c| c.enabled))
+            || (item_idx >= 16 && !snooze.enabled);
+        let color = if disabled {
+            t.text_disabled
+        } else if is_selected {
+            t.cursor
+        } else {
+            t.text
+        };
+
+        draw_pointer_highlight(
+            state,
+            PointerTarget::Settings(item_idx),
+            layout.row_rect(screen, i),
+            t,
+        );
 
         // Selection indicator
         if is_selected {
@@ -205,10 +568,22 @@ fn render_settings_screen(state: &OverlayState) {
 
     // Scroll indicators (only show if needed)
     if scroll_offset > 0 {
-        draw_text("▲", menu_x + menu_width - 25.0, option_start_y + 10.0, 16.0, t.text_secondary);
+        draw_text(
+            "▲",
+            menu_x + menu_width - 25.0,
+            option_start_y + 10.0,
+            16.0,
+            t.text_secondary,
+        );
     }
     if scroll_offset + MAX_VISIBLE < visible_count {
-        draw_text("▼", menu_x + menu_width - 25.0, option_start_y + (MAX_VISIBLE as f32 * option_height) - 15.0, 16.0, t.text_secondary);
+        draw_text(
+            "▼",
+            menu_x + menu_width - 25.0,
+            option_start_y + (MAX_VISIBLE as f32 * option_height) - 15.0,
+            16.0,
+            t.text_secondary,
+        );
     }
 
     draw_text(
@@ -220,811 +595,1367 @@ fn render_settings_screen(state: &OverlayState) {
     );
 }
 
-/// Get color for achievement rarity display
-fn get_rarity_color(rarity_percent: f32) -> Color {
-    if rarity_percent < 5.0 {
-        GOLD  // Ultra rare
-    } else if rarity_percent < 10.0 {
-        Color::new(0.9, 0.4, 0.9, 1.0)  // Purple - very rare
-    } else if rarity_percent < 25.0 {
-        Color::new(0.3, 0.6, 1.0, 1.0)  // Blue - rare
-    } else if rarity_percent < 50.0 {
-        GREEN  // Uncommon
-    } else {
-        GRAY  // Common
-    }
-}
-
-fn render_achievements_screen(state: &OverlayState) {
+fn render_login_screen(state: &OverlayState) {
     let t = theme(state);
-    let menu_width = 600.0;
-    let menu_height = 340.0;
+    let menu_width = 620.0;
+    let menu_height = 520.0;
     let menu_x = (screen_width() - menu_width) / 2.0;
     let menu_y = (screen_height() - menu_height) / 2.0;
 
-    draw_panel(menu_x, menu_y, menu_width, menu_height, &t);
-
-    let tracker = &state.achievements;
+    draw_panel(menu_x, menu_y, menu_width, menu_height, t);
 
-    // Title with game name
-    let title = if tracker.game_title.is_empty() {
-        "ACHIEVEMENTS".to_string()
-    } else {
-        format!("🏆 {}", tracker.game_title)
+    let Some(login) = &state.login else {
+        return;
     };
-    draw_text(&title, menu_x + 15.0, menu_y + 28.0, 24.0, t.cursor);
-
-    // Progress bar and stats
-    if !tracker.achievements.is_empty() {
-        let earned = tracker.progress.earned;
-        let total = tracker.progress.total;
-        let progress_pct = tracker.get_progress_percent();
 
-        // Progress text
-        let progress_text = format!("{}/{} ({:.0}%)",
-            earned, total, progress_pct);
-        draw_text(&progress_text, menu_x + 15.0, menu_y + 50.0, 16.0, t.text_secondary);
+    draw_text(
+        "RETROACHIEVEMENTS LOGIN",
+        menu_x + 20.0,
+        menu_y + 40.0,
+        32.0,
+        t.cursor,
+    );
+    draw_text(
+        "Your API key is at retroachievements.org/settings",
+        menu_x + 20.0,
+        menu_y + 64.0,
+        16.0,
+        t.text_secondary,
+    );
 
-        // Progress bar
-        let bar_x = menu_x + 15.0;
-        let bar_y = menu_y + 58.0;
-        let bar_width = menu_width - 30.0;
-        let bar_height = 6.0;
+    // Form rows
+    let masked_key = login.masked_api_key();
+    let rows = [
+        (LoginField::Username, "Username", login.username.as_str()),
+        (LoginField::ApiKey, "API Key", masked_key.as_str()),
+    ];
+    let field_x = menu_x + 150.0;
+    let field_width = menu_width - 170.0;
+    for (i, (field, label, value)) in rows.iter().enumerate() {
+        let y = menu_y + 90.0 + i as f32 * 44.0;
+        let is_selected = login.selected_field() == *field;
+        let border = if is_selected {
+            t.cursor
+        } else {
+            t.panel_border
+        };
 
-        // Bar background
-        draw_rectangle(bar_x, bar_y, bar_width, bar_height, Color::new(0.2, 0.2, 0.2, 1.0));
-        // Bar fill
-        let progress = progress_pct / 100.0;
-        let fill_color = if progress >= 1.0 { GOLD } else { GREEN };
-        draw_rectangle(bar_x, bar_y, bar_width * progress, bar_height, fill_color);
+        draw_text(
+            label,
+            menu_x + 20.0,
+            y + 24.0,
+            20.0,
+            if is_selected { t.cursor } else { t.text },
+        );
+        draw_rectangle(field_x, y, field_width, 34.0, t.background_overlay);
+        draw_rectangle_lines(field_x, y, field_width, 34.0, 2.0, border);
 
-        // Achievement list
-        let list_y = menu_y + 75.0;
-        let item_height = 40.0;
-        let max_visible = 6;
+        // Show a caret on the field being typed into
+        let editing = is_selected && login.keyboard.is_some();
+        let text = if editing {
+            format!("{}_", value)
+        } else {
+            value.to_string()
+        };
+        draw_text(&text, field_x + 10.0, y + 24.0, 20.0, t.text);
+    }
 
-        // Clamp scroll offset
-        let total_items = tracker.achievements.len();
-        let max_scroll = total_items.saturating_sub(max_visible);
-        let scroll = state.achievements_scroll_offset.min(max_scroll);
+    // Submit button
+    let button_y = menu_y + 190.0;
+    let button_selected = login.selected_field() == LoginField::Submit && login.keyboard.is_none();
+    let button_label = if login.verifying {
+        "Verifying..."
+    } else {
+        "Log In"
+    };
+    let button_width = 160.0;
+    let button_x = menu_x + (menu_width - button_width) / 2.0;
+    draw_rectangle(
+        button_x,
+        button_y,
+        button_width,
+        36.0,
+        if button_selected {
+            t.cursor
+        } else {
+            t.background_overlay
+        },
+    );
+    draw_rectangle_lines(button_x, button_y, button_width, 36.0, 2.0, t.panel_border);
+    let label_width = measure_text(button_label, None, 22, 1.0).width;
+    draw_text(
+        button_label,
+        button_x + (button_width - label_width) / 2.0,
+        button_y + 25.0,
+        22.0,
+        if button_selected {
+            t.panel_background
+        } else {
+            t.text
+        },
+    );
 
-        // Scroll indicators
-        if scroll > 0 {
-            draw_text("▲", menu_x + menu_width - 25.0, list_y - 10.0, 14.0, t.text_secondary);
-        }
-        if scroll < max_scroll {
-            draw_text("▼", menu_x + menu_width - 25.0, list_y + (max_visible as f32 * item_height) + 5.0, 14.0, t.text_secondary);
-        }
+    if let Some(error) = &login.error {
+        draw_text(error, menu_x + 20.0, button_y + 62.0, 18.0, t.error);
+    }
 
-        for i in 0..max_visible {
-            let item_idx = scroll + i;
-            if item_idx >= total_items {
-                break;  // No more achievements to display
-            }
+    // On-screen keyboard
+    if let Some(keyboard) = &login.keyboard {
+        draw_keyboard(keyboard, menu_x + menu_width / 2.0, button_y + 80.0, t);
+    }
 
-            let achievement = &tracker.achievements[item_idx];
-            let y = list_y + (i as f32 * item_height);
+    // Controls hint
+    let controls = if login.keyboard.is_some() {
+        "A: Type • X: Delete • LB: Shift • RB/B: Done"
+    } else {
+        "D-Pad: Navigate • A: Edit/Log In • B: Back"
+    };
+    draw_text(
+        controls,
+        menu_x + 20.0,
+        menu_y + menu_height - 14.0,
+        16.0,
+        t.text_secondary,
+    );
+}
 
-            // Earned indicator
-            let status_icon = if achievement.earned_hardcore {
-                "⭐" // Hardcore
-            } else if achievement.earned {
-                "✓"  // Normal
+/// Draw the on-screen keyboard horizontally centered on `center_x`
+fn draw_keyboard(
+    keyboard: &OnScreenKeyboard,
+    center_x: f32,
+    keyboard_y: f32,
+    t: &crate::themes::Theme,
+) {
+    let key_size = 40.0;
+    let key_gap = 4.0;
+    let keyboard_width = KEYBOARD_COLUMNS as f32 * (key_size + key_gap) - key_gap;
+    let keyboard_x = center_x - keyboard_width / 2.0;
+
+    let draw_key = |label: &str, x: f32, y: f32, w: f32, selected: bool| {
+        draw_rectangle(
+            x,
+            y,
+            w,
+            key_size - 8.0,
+            if selected {
+                t.cursor
             } else {
-                "○"  // Locked
-            };
-            let status_color = if achievement.earned { GREEN } else { GRAY };
-            draw_text(status_icon, menu_x + 18.0, y + 26.0, 22.0, status_color);
+                t.background_overlay
+            },
+        );
+        let label_width = measure_text(label, None, 18, 1.0).width;
+        draw_text(
+            label,
+            x + (w - label_width) / 2.0,
+            y + 22.0,
+            18.0,
+            if selected { t.panel_background } else { t.text },
+        );
+    };
 
-            // Achievement title
-            let title_color = if achievement.earned { t.text } else { t.text_disabled };
-            let title_text = if achievement.title.len() > 40 {
-                format!("{}...", &achievement.title[..37])
-            } else {
-                achievement.title.clone()
-            };
-            draw_text(&title_text, menu_x + 45.0, y + 22.0, 18.0, title_color);
+    for (row, chars) in KEYBOARD_ROWS.iter().enumerate() {
+        let y = keyboard_y + row as f32 * key_size;
+        for (col, _) in chars.chars().enumerate() {
+            let x = keyboard_x + col as f32 * (key_size + key_gap);
+            let selected = keyboard.row == row && keyboard.col == col;
+            let key = Key::Char(chars.chars().nth(col).unwrap_or(' '));
+            draw_key(&key.label(keyboard.shift), x, y, key_size, selected);
+        }
+    }
 
-            // Points (with rarity indicator if available)
-            let points_x = menu_x + menu_width - 130.0;
-            let points_text = format!("{} pts", achievement.points);
-            let points_color = if achievement.earned { GOLD } else { DARKGRAY };
-            draw_text(&points_text, points_x, y + 22.0, 16.0, points_color);
+    let action_y = keyboard_y + KEYBOARD_ROWS.len() as f32 * key_size;
+    for &(key, start, end) in ACTION_KEYS {
+        let x = keyboard_x + start as f32 * (key_size + key_gap);
+        let w = (end - start) as f32 * (key_size + key_gap) - key_gap;
+        let selected = keyboard.row == KEYBOARD_ROWS.len() && (start..end).contains(&keyboard.col);
+        let label = if key == Key::Shift && keyboard.shift {
+            "SHIFT ON".to_string()
+        } else {
+            key.label(false)
+        };
+        draw_key(&label, x, action_y, w, selected);
+    }
+}
 
-            // Rarity display (if available)
-            if let Some(rarity) = achievement.rarity_percent {
-                let rarity_color = get_rarity_color(rarity);
-                let rarity_text = format!("{:.1}%", rarity);
-                draw_text(&rarity_text, menu_x + menu_width - 65.0, y + 22.0, 14.0, rarity_color);
-            }
+fn render_search_screen(state: &OverlayState) {
+    let t = theme(state);
+    let menu_width = 620.0;
+    let menu_height = 560.0;
+    let menu_x = (screen_width() - menu_width) / 2.0;
+    let menu_y = (screen_height() - menu_height) / 2.0;
 
-            // Progress bar for multi-step achievements (on same line as description)
-            if let Some(progress) = &achievement.progress {
-                let prog_percent = progress.current as f32 / progress.target as f32;
-                let prog_text = format!("{}/{}", progress.current, progress.target);
+    draw_panel(menu_x, menu_y, menu_width, menu_height, t);
 
-                // Progress bar
-                let prog_bar_x = menu_x + 45.0;
-                let prog_bar_y = y + 28.0;
-                let prog_bar_width = 100.0;
-                let prog_bar_height = 4.0;
+    let Some(search) = &state.search else {
+        return;
+    };
 
-                // Background
-                draw_rectangle(prog_bar_x, prog_bar_y, prog_bar_width, prog_bar_height, Color::new(0.2, 0.2, 0.2, 1.0));
-                // Fill
-                let fill_width = prog_bar_width * prog_percent;
-                draw_rectangle(prog_bar_x, prog_bar_y, fill_width, prog_bar_height, GREEN);
+    draw_text("SEARCH", menu_x + 20.0, menu_y + 40.0, 32.0, t.cursor);
 
-                // Progress text
-                draw_text(&prog_text, prog_bar_x + prog_bar_width + 5.0, y + 32.0, 11.0, LIGHTGRAY);
-            }
+    // Query field
+    let field_x = menu_x + 20.0;
+    let field_y = menu_y + 56.0;
+    let field_width = menu_width - 40.0;
+    let typing = search.keyboard.is_some();
+    draw_rectangle(field_x, field_y, field_width, 34.0, t.background_overlay);
+    draw_rectangle_lines(
+        field_x,
+        field_y,
+        field_width,
+        34.0,
+        2.0,
+        if typing { t.cursor } else { t.panel_border },
+    );
+    let query = if typing {
+        format!("{}_", search.query)
+    } else {
+        search.query.clone()
+    };
+    draw_text(&query, field_x + 10.0, field_y + 24.0, 20.0, t.text);
+
+    // Results
+    let list_y = field_y + 44.0;
+    let row_height = 32.0;
+    if search.results.is_empty() {
+        let message = if search.query.trim().is_empty() {
+            "Type to search games, achievements and settings"
+        } else {
+            "No matches"
+        };
+        draw_text(message, field_x, list_y + 22.0, 18.0, t.text_secondary);
+    }
 
-            // Description removed for simplicity
-            // TODO: Add selection support for showing descriptions
+    let scroll = search.scroll_offset;
+    for (i, result) in search
+        .results
+        .iter()
+        .skip(scroll)
+        .take(SEARCH_VISIBLE_ROWS)
+        .enumerate()
+    {
+        let y = list_y + i as f32 * row_height;
+        let is_selected = !typing && scroll + i == search.selected;
+        if is_selected {
+            draw_rectangle(
+                field_x,
+                y,
+                field_width,
+                row_height - 2.0,
+                Color::new(t.accent.r * 0.2, t.accent.g * 0.2, t.accent.b * 0.2, 0.6),
+            );
         }
-
-        // Scroll indicators removed - TODO: Add scrolling support
-    } else {
-        // No achievements loaded
         draw_text(
-            "No RetroAchievements data",
-            menu_x + menu_width / 2.0 - 100.0,
-            menu_y + 120.0,
-            20.0,
-            GRAY,
+            result.category.display_name(),
+            field_x + 8.0,
+            y + 21.0,
+            12.0,
+            t.text_secondary,
         );
+        let label = if result.label.chars().count() > 45 {
+            format!("{}...", result.label.chars().take(42).collect::<String>())
+        } else {
+            result.label.clone()
+        };
         draw_text(
-            "Start a game with RA support to view achievements",
-            menu_x + 30.0,
-            menu_y + 160.0,
-            16.0,
-            DARKGRAY,
+            &label,
+            field_x + 110.0,
+            y + 22.0,
+            18.0,
+            if is_selected { t.cursor } else { t.text },
+        );
+    }
+    if scroll > 0 {
+        draw_text(
+            "▲",
+            menu_x + menu_width - 25.0,
+            list_y + 10.0,
+            14.0,
+            t.text_secondary,
+        );
+    }
+    if scroll + SEARCH_VISIBLE_ROWS < search.results.len() {
+        draw_text(
+            "▼",
+            menu_x + menu_width - 25.0,
+            list_y + SEARCH_VISIBLE_ROWS as f32 * row_height - 4.0,
+            14.0,
+            t.text_secondary,
         );
     }
 
-    // Controls hint
+    if let Some(keyboard) = &search.keyboard {
+        draw_keyboard(
+            keyboard,
+            menu_x + menu_width / 2.0,
+            list_y + SEARCH_VISIBLE_ROWS as f32 * row_height + 12.0,
+            t,
+        );
+    }
+
+    let controls = if typing {
+        "A: Type • X: Delete • LB: Shift • RB/B: Results"
+    } else {
+        "D-Pad: Navigate • A: Open • X: Edit Search • B: Back"
+    };
     draw_text(
-        "D-Pad: Navigate • LB/RB: Filter • LT/RT: Sort • B: Back",
-        menu_x + 15.0,
-        menu_y + menu_height - 12.0,
-        14.0,
-        LIGHTGRAY,
+        controls,
+        menu_x + 20.0,
+        menu_y + menu_height - 14.0,
+        16.0,
+        t.text_secondary,
     );
 }
 
-fn render_controllers_menu(state: &OverlayState) {
+/// Render an unlock banner off-screen and export it as a PNG for stream output
+pub fn export_stream_banner(state: &OverlayState, event: &StreamEvent, path: &Path) {
     let t = theme(state);
-    let menu_width = 600.0;
-    let menu_height = 380.0;
-    let menu_x = (screen_width() - menu_width) / 2.0;
-    let menu_y = (screen_height() - menu_height) / 2.0;
-
-    // Menu background
-    draw_rectangle(menu_x, menu_y, menu_width, menu_height, t.panel_background);
-    draw_rectangle_lines(menu_x, menu_y, menu_width, menu_height, 2.0, t.panel_border);
-
-    // Title
-    draw_text("🎮 CONTROLLERS", menu_x + 20.0, menu_y + 40.0, 32.0, t.cursor);
-
-    // Connected controller count
-    let controller_count = state.controllers.controllers.len();
-    let status_text = format!("{} controller(s) connected", controller_count);
-    draw_text(&status_text, menu_x + 20.0, menu_y + 70.0, 18.0, LIGHTGRAY);
-
-    // Menu options
-    let option_start_y = menu_y + 110.0;
-    let option_height = 45.0;
-    const MAX_VISIBLE: usize = 6;
-    let menu_len = CONTROLLER_MENU_OPTIONS.len();
-    let scroll_offset = 0; // Controllers menu is short, no scrolling needed for now
-
-    for i in 0..MAX_VISIBLE.min(menu_len.saturating_sub(scroll_offset)) {
-        let item_idx = scroll_offset + i;
-        if item_idx >= menu_len {
-            break;
-        }
-        let option = CONTROLLER_MENU_OPTIONS[item_idx];
-        let y = option_start_y + (i as f32 * option_height);
-        let is_selected = item_idx == state.controllers.selected_menu_item;
-        let color = if is_selected { t.cursor } else { t.text };
+    let (width, height) = (400.0, 70.0);
+    let target = render_target(width as u32, height as u32);
+    target.texture.set_filter(FilterMode::Linear);
 
-        // Selection indicator
-        if is_selected {
-            draw_rectangle(
-                menu_x + 15.0, y - 5.0,
-                menu_width - 30.0, option_height - 5.0,
-                Color::new(t.accent.r * 0.2, t.accent.g * 0.2, t.accent.b * 0.2, 0.6),
-            );
-            draw_text("►", menu_x + 25.0, y + 25.0, 24.0, t.cursor);
-        }
+    let mut camera = Camera2D::from_display_rect(Rect::new(0.0, 0.0, width, height));
+    camera.render_target = Some(target.clone());
+    set_camera(&camera);
 
-        draw_text(option, menu_x + 60.0, y + 25.0, 24.0, color);
+    clear_background(BLANK);
+    draw_rectangle(0.0, 0.0, width, height, t.panel_background);
+    draw_rectangle(0.0, 0.0, 6.0, height, t.success);
+    draw_rectangle_lines(0.0, 0.0, width, height, 2.0, t.panel_border);
 
-        // Show sub-info for some options
-        match i {
-            0 => {
-                // Bluetooth - show paired count if any
-                let paired = state.controllers.bluetooth_devices.iter().filter(|d| d.is_paired).count();
-                if paired > 0 {
-                    draw_text(&format!("{} paired", paired), menu_x + menu_width - 120.0, y + 25.0, 18.0, GRAY);
-                }
-            }
-            1 => {
-                // Assign - show assignment summary
-                let assigned = state.controllers.player_assignments.iter().filter(|a| a.is_some()).count();
-                draw_text(&format!("{}/4 assigned", assigned), menu_x + menu_width - 120.0, y + 25.0, 18.0, GRAY);
-            }
-            _ => {}
-        }
+    let title = match event.points {
+        Some(points) => format!("🏆 {} ({} pts)", event.title, points),
+        None => event.title.clone(),
+    };
+    draw_text(&title, 16.0, 28.0, 22.0, t.text);
+    if let Some(description) = &event.description {
+        draw_text(description, 16.0, 52.0, 16.0, t.text_secondary);
     }
 
-    // Success/error message
-    if let Some((msg, _)) = &state.controllers.success_message {
-        draw_text(msg, menu_x + 20.0, menu_y + menu_height - 50.0, 18.0, GREEN);
-    }
-    if let Some(msg) = &state.controllers.error_message {
-        draw_text(msg, menu_x + 20.0, menu_y + menu_height - 50.0, 18.0, RED);
+    set_default_camera();
+
+    // Render targets come back flipped vertically
+    let mut image = target.texture.get_texture_data();
+    let (w, h) = (image.width as usize, image.height as usize);
+    let mut flipped = Vec::with_capacity(image.bytes.len());
+    for row in (0..h).rev() {
+        flipped.extend_from_slice(&image.bytes[row * w * 4..(row + 1) * w * 4]);
     }
+    image.bytes = flipped;
 
-    // Controls hint
-    draw_text(
-        "D-Pad: Navigate • A: Select • B: Back",
-        menu_x + 20.0,
-        menu_y + menu_height - 20.0,
-        16.0,
-        LIGHTGRAY,
-    );
+    image.export_png(&path.to_string_lossy());
+    println!("[Stream] Banner written to {:?}", path);
 }
 
-fn render_bluetooth_screen(state: &OverlayState) {
+fn render_achievements_screen(state: &OverlayState) {
     let t = theme(state);
     let menu_width = 600.0;
-    let menu_height = 400.0;
+    let menu_height = 340.0;
     let menu_x = (screen_width() - menu_width) / 2.0;
     let menu_y = (screen_height() - menu_height) / 2.0;
 
-    draw_rectangle(menu_x, menu_y, menu_width, menu_height, t.panel_background);
-    draw_rectangle_lines(menu_x, menu_y, menu_width, menu_height, 2.0, t.panel_border);
+    draw_panel(menu_x, menu_y, menu_width, menu_height, &t);
 
-    // Title
-    draw_text("📶 BLUETOOTH CONTROLLERS", menu_x + 20.0, menu_y + 40.0, 28.0, t.cursor);
+    let tracker = &state.achievements;
 
-    // Scan state indicator
-    let scan_status = match &state.controllers.bluetooth_state {
-        BluetoothScanState::Idle => ("Press X to scan", GRAY),
-        BluetoothScanState::Scanning => ("Scanning...", YELLOW),
-        BluetoothScanState::Pairing(mac) => (&format!("Pairing: {}...", mac) as &str, ORANGE),
-        BluetoothScanState::Connecting(mac) => (&format!("Connecting: {}...", mac) as &str, BLUE),
-        BluetoothScanState::Error(err) => (err.as_str(), RED),
-    };
-    // Need to handle the lifetime issue differently
-    let (scan_text, scan_color) = scan_status;
-    draw_text(scan_text, menu_x + 20.0, menu_y + 70.0, 16.0, scan_color);
+    if state.network.is_offline() {
+        let banner = if tracker.achievements.is_empty() {
+            "Offline — no cached data for this game"
+        } else {
+            "Offline — showing cached data"
+        };
+        let width = measure_text(banner, None, 16, 1.0).width;
+        draw_rectangle(
+            menu_x,
+            menu_y - 30.0,
+            menu_width,
+            24.0,
+            Color::new(t.warning.r, t.warning.g, t.warning.b, 0.25),
+        );
+        draw_text(
+            banner,
+            menu_x + (menu_width - width) / 2.0,
+            menu_y - 13.0,
+            16.0,
+            t.warning,
+        );
+    }
 
-    // Device list
-    let list_y = menu_y + 100.0;
-    let item_height = 50.0;
-    let max_visible = 5;
+    // One tab per player in a shared session, above the panel
+    let player = state.selected_player();
+    if tracker.players.len() > 1 {
+        let tab_y = if state.network.is_offline() {
+            menu_y - 58.0
+        } else {
+            menu_y - 30.0
+        };
+        let mut tab_x = menu_x;
+        for (i, p) in tracker.players.iter().enumerate() {
+            let label = format!("P{} {}", p.slot, p.name);
+            let width = measure_text(&label, None, 16, 1.0).width + 20.0;
+            let selected = i == state.achievements_player;
+            let fill = if selected {
+                Color::new(t.accent.r, t.accent.g, t.accent.b, 0.35)
+            } else {
+                Color::new(0.1, 0.1, 0.1, 0.8)
+            };
+            draw_rectangle(tab_x, tab_y, width, 24.0, fill);
+            draw_text(
+                &label,
+                tab_x + 10.0,
+                tab_y + 17.0,
+                16.0,
+                if selected { t.text } else { t.text_secondary },
+            );
+            tab_x += width + 6.0;
+        }
+    }
 
-    if state.controllers.bluetooth_devices.is_empty() {
-        draw_text(
-            "No devices found",
-            menu_x + menu_width / 2.0 - 80.0,
-            menu_y + 180.0,
-            20.0,
-            t.text_disabled,
+    // Title with game name
+    let title = if tracker.game_title.is_empty() {
+        "ACHIEVEMENTS".to_string()
+    } else {
+        format!("🏆 {}", tracker.game_title)
+    };
+    let icon_size = 40.0;
+    let header_x = if draw_game_icon(state, menu_x + 15.0, menu_y + 12.0, icon_size) {
+        menu_x + 15.0 + icon_size + 10.0
+    } else {
+        menu_x + 15.0
+    };
+    draw_text(&title, header_x, menu_y + 28.0, 24.0, t.cursor);
+    if !tracker.console.is_empty() {
+        draw_console_badge(
+            &tracker.console,
+            menu_x + menu_width - 15.0,
+            menu_y + 10.0,
+            t,
         );
+    }
+
+    // Progress bar and stats
+    if !tracker.achievements.is_empty() {
+        let progress = tracker.player_progress(player);
+        let earned = progress.earned;
+        let total = progress.total;
+        let progress_pct = if total == 0 {
+            0.0
+        } else {
+            earned as f32 / total as f32 * 100.0
+        };
+
+        // Progress text
+        let progress_text = format!("{}/{} ({:.0}%)", earned, total, progress_pct);
         draw_text(
-            "Press X to scan for Bluetooth controllers",
-            menu_x + 60.0,
-            menu_y + 220.0,
+            &progress_text,
+            header_x,
+            menu_y + 50.0,
             16.0,
             t.text_secondary,
         );
-    } else {
-        let device_count = state.controllers.bluetooth_devices.len();
-        let scroll_offset = state.controllers.bt_scroll_offset;
 
-        for i in 0..max_visible.min(device_count.saturating_sub(scroll_offset)) {
-            let device_idx = scroll_offset + i;
-            if device_idx >= device_count {
-                break;
+        if let Some(difficulty) = tracker.difficulty {
+            let mut difficulty_text = format!("{} ({:.1})", difficulty.label(), difficulty.score);
+            if let Some(secs) = difficulty.expected_master_secs.filter(|_| earned < total) {
+                difficulty_text
+                    .push_str(&format!(" • ~{} to master", stats::format_duration(secs)));
             }
-            let device = &state.controllers.bluetooth_devices[device_idx];
+            let width = measure_text(&difficulty_text, None, 16, 1.0).width;
+            draw_text(
+                &difficulty_text,
+                menu_x + menu_width - 15.0 - width,
+                menu_y + 50.0,
+                16.0,
+                t.text_secondary,
+            );
+        }
+
+        // Progress bar
+        let bar_x = menu_x + 15.0;
+        let bar_y = menu_y + 58.0;
+        let bar_width = menu_width - 30.0;
+        let bar_height = 6.0;
+
+        // Bar background
+        draw_rectangle(
+            bar_x,
+            bar_y,
+            bar_width,
+            bar_height,
+            Color::new(0.2, 0.2, 0.2, 1.0),
+        );
+        // Bar fill
+        let progress = progress_pct / 100.0;
+        let fill_color = if progress >= 1.0 { GOLD } else { GREEN };
+        draw_rectangle(bar_x, bar_y, bar_width * progress, bar_height, fill_color);
+
+        // Achievement list
+        let list_y = menu_y + 75.0;
+        let item_height = 40.0;
+        let max_visible = ACHIEVEMENTS_VISIBLE_ROWS;
+
+        // Clamp scroll offset
+        let total_items = state.visible_achievement_count();
+        let max_scroll = total_items.saturating_sub(max_visible);
+        let scroll = state.achievements_scroll_offset.min(max_scroll);
+
+        // Scroll indicators
+        if scroll > 0 {
+            draw_text(
+                "▲",
+                menu_x + menu_width - 25.0,
+                list_y - 10.0,
+                14.0,
+                t.text_secondary,
+            );
+        }
+        if scroll < max_scroll {
+            draw_text(
+                "▼",
+                menu_x + menu_width - 25.0,
+                list_y + (max_visible as f32 * item_height) + 5.0,
+                14.0,
+                t.text_secondary,
+            );
+        }
+
+        for (i, row) in state.achievement_rows(scroll, max_visible).enumerate() {
+            let achievement = row.achievement;
             let y = list_y + (i as f32 * item_height);
-            let is_selected = device_idx == state.controllers.bt_selected_index;
 
-            // Selection background
-            if is_selected {
+            // Selection highlight
+            if row.selected {
                 draw_rectangle(
-                    menu_x + 15.0, y,
-                    menu_width - 30.0, item_height - 5.0,
-                    Color::new(0.3, 0.3, 0.4, 0.6),
+                    menu_x + 10.0,
+                    y + 2.0,
+                    menu_width - 20.0,
+                    item_height - 4.0,
+                    Color::new(t.accent.r * 0.2, t.accent.g * 0.2, t.accent.b * 0.2, 0.6),
                 );
             }
 
-            // Device icon based on state
-            let icon = if device.is_connected {
-                "🎮"
-            } else if device.is_paired {
-                "🔗"
+            // Earned indicator, for the player whose tab is open
+            let earned = row.earned;
+            let status_icon = if achievement.earned_hardcore && player.is_none_or(|p| p.account) {
+                "⭐" // Hardcore
+            } else if earned {
+                "✓" // Normal
             } else {
-                "📡"
+                "○" // Locked
             };
-            draw_text(icon, menu_x + 25.0, y + 30.0, 24.0, WHITE);
+            let status_color = if earned { GREEN } else { GRAY };
+            draw_text(status_icon, menu_x + 18.0, y + 26.0, 22.0, status_color);
 
-            // Device name
-            let name_color = if device.is_connected { t.success } else if device.is_paired { t.text } else { t.text_disabled };
-            draw_text(&device.name, menu_x + 60.0, y + 25.0, 20.0, name_color);
+            // Achievement title
+            let title_color = if earned { t.text } else { t.text_disabled };
+            let title_text = truncate_text(&achievement.title, 40);
+            draw_text(&title_text, menu_x + 45.0, y + 22.0, 18.0, title_color);
 
-            // MAC address (smaller)
-            draw_text(&device.mac_address, menu_x + 60.0, y + 42.0, 12.0, DARKGRAY);
+            // Tag unofficial achievements so they're not mistaken for the core set
+            if achievement.unofficial {
+                let title_width = measure_text(&title_text, None, 18, 1.0).width;
+                draw_text(
+                    "UNOFFICIAL",
+                    menu_x + 53.0 + title_width,
+                    y + 21.0,
+                    12.0,
+                    t.warning,
+                );
+            }
 
-            // Status
-            let status = if device.is_connected {
-                "Connected"
-            } else if device.is_paired {
-                "Paired"
-            } else {
-                "Available"
-            };
-            let status_color = if device.is_connected { GREEN } else if device.is_paired { YELLOW } else { GRAY };
-                draw_text(status, menu_x + menu_width - 110.0, y + 30.0, 16.0, status_color);
-        }
+            // Points (with rarity indicator if available)
+            let points_x = menu_x + menu_width - 130.0;
+            let points_text = format!("{} pts", achievement.points);
+            let points_color = if earned { GOLD } else { DARKGRAY };
+            draw_text(&points_text, points_x, y + 22.0, 16.0, points_color);
 
-        // Scroll indicators
-        if scroll_offset > 0 {
-            draw_text("▲", menu_x + menu_width - 25.0, list_y + 10.0, 16.0, t.text_secondary);
+            // Rarity display (if available)
+            if let Some(rarity) = achievement.rarity_percent {
+                let rarity_color = t.rarity_color(rarity);
+                let rarity_text = format!("{:.1}%", rarity);
+                draw_text(
+                    &rarity_text,
+                    menu_x + menu_width - 65.0,
+                    y + 22.0,
+                    14.0,
+                    rarity_color,
+                );
+            }
+
+            // Progress bar for multi-step achievements (on same line as description)
+            if let Some(progress) = &achievement.progress {
+                let prog_percent = progress.current as f32 / progress.target as f32;
+                let prog_text = format!("{}/{}", progress.current, progress.target);
+
+                // Progress bar
+                let prog_bar_x = menu_x + 45.0;
+                let prog_bar_y = y + 28.0;
+                let prog_bar_width = 100.0;
+                let prog_bar_height = 4.0;
+
+                // Background
+                draw_rectangle(
+                    prog_bar_x,
+                    prog_bar_y,
+                    prog_bar_width,
+                    prog_bar_height,
+                    Color::new(0.2, 0.2, 0.2, 1.0),
+                );
+                // Fill
+                let fill_width = prog_bar_width * prog_percent;
+                draw_rectangle(prog_bar_x, prog_bar_y, fill_width, prog_bar_height, GREEN);
+
+                // Progress text
+                draw_text(
+                    &prog_text,
+                    prog_bar_x + prog_bar_width + 5.0,
+                    y + 32.0,
+                    11.0,
+                    LIGHTGRAY,
+                );
+            }
+
+            // Description removed for simplicity
+            // TODO: Add selection support for showing descriptions
         }
-        if scroll_offset + max_visible < device_count {
-            draw_text("▼", menu_x + menu_width - 25.0, list_y + (max_visible as f32 * item_height) - 15.0, 16.0, t.text_secondary);
+
+        // Scroll indicators removed - TODO: Add scrolling support
+
+        if state.achievement_filter.is_active() {
+            let filter = &state.achievement_filter;
+            let filter_text = if filter.search_query.is_empty() {
+                format!(
+                    "Filter: {} ({} shown)",
+                    filter.filter.display_name(),
+                    total_items
+                )
+            } else if filter.filter == AchievementFilter::All {
+                format!(
+                    "Filter: \"{}\" ({} shown)",
+                    filter.search_query, total_items
+                )
+            } else {
+                format!(
+                    "Filter: {}, \"{}\" ({} shown)",
+                    filter.filter.display_name(),
+                    filter.search_query,
+                    total_items
+                )
+            };
+            let filter_width = measure_text(&filter_text, None, 14, 1.0).width;
+            draw_text(
+                &filter_text,
+                menu_x + menu_width - filter_width - 15.0,
+                menu_y + 50.0,
+                14.0,
+                t.accent,
+            );
+            if total_items == 0 {
+                draw_text(
+                    "No achievements match the filter",
+                    menu_x + 45.0,
+                    list_y + 22.0,
+                    18.0,
+                    t.text_secondary,
+                );
+            }
         }
+    } else {
+        // No achievements loaded
+        draw_text(
+            "No RetroAchievements data",
+            menu_x + menu_width / 2.0 - 100.0,
+            menu_y + 120.0,
+            20.0,
+            GRAY,
+        );
+        draw_text(
+            "Start a game with RA support to view achievements",
+            menu_x + 30.0,
+            menu_y + 160.0,
+            16.0,
+            DARKGRAY,
+        );
     }
 
     // Controls hint
+    let controls = if state.achievement_filter.is_active() {
+        "D-Pad: Navigate • A: Hints • X: Compare • LB: Filter • RB: Stats • B: Clear Filter"
+    } else if tracker.players.len() > 1 {
+        "D-Pad: Navigate • ◀▶: Player • A: Hints • X: Compare • LB: Filter • RB: Stats • B: Back"
+    } else {
+        "D-Pad: Navigate • A: Hints • X: Compare • LB: Filter • RB: Stats • B: Back"
+    };
     draw_text(
-        "D-Pad: Navigate • A: Pair/Connect • X: Scan • B: Back",
-        menu_x + 20.0,
-        menu_y + menu_height - 20.0,
+        controls,
+        menu_x + 15.0,
+        menu_y + menu_height - 12.0,
         14.0,
         LIGHTGRAY,
     );
 }
 
-fn render_assign_screen(state: &OverlayState) {
+/// Split text into lines that fit within max_width at the given font size
+fn wrap_text(text: &str, max_width: f32, font_size: f32) -> Vec<String> {
+    let mut lines = Vec::new();
+
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            let candidate = if line.is_empty() {
+                word.to_string()
+            } else {
+                format!("{} {}", line, word)
+            };
+            if !line.is_empty()
+                && measure_text(&candidate, None, font_size as u16, 1.0).width > max_width
+            {
+                lines.push(std::mem::replace(&mut line, word.to_string()));
+            } else {
+                line = candidate;
+            }
+        }
+        lines.push(line);
+    }
+
+    lines
+}
+
+fn render_hints_screen(state: &OverlayState) {
     let t = theme(state);
     let menu_width = 600.0;
-    let menu_height = 380.0;
+    let menu_height = 340.0;
     let menu_x = (screen_width() - menu_width) / 2.0;
     let menu_y = (screen_height() - menu_height) / 2.0;
 
-    draw_rectangle(menu_x, menu_y, menu_width, menu_height, t.panel_background);
-    draw_rectangle_lines(menu_x, menu_y, menu_width, menu_height, 2.0, t.panel_border);
-
-    // Title
-    draw_text("👥 ASSIGN CONTROLLERS", menu_x + 20.0, menu_y + 40.0, 28.0, t.cursor);
-    draw_text(
-        "Use Left/Right to change assignment",
-        menu_x + 20.0,
-        menu_y + 68.0,
-        16.0,
-        LIGHTGRAY,
-    );
+    draw_panel(menu_x, menu_y, menu_width, menu_height, t);
 
-    // Player assignment slots
-    let slot_start_y = menu_y + 100.0;
-    let slot_height = 60.0;
+    let Some(hints) = &state.hints else {
+        return;
+    };
 
-    for player in 0..MAX_PLAYERS {
-        let y = slot_start_y + (player as f32 * slot_height);
-        let is_selected = player == state.controllers.assign_selected_player;
+    let title = if hints.achievement_title.chars().count() > 40 {
+        format!(
+            "💡 {}...",
+            hints.achievement_title.chars().take(37).collect::<String>()
+        )
+    } else {
+        format!("💡 {}", hints.achievement_title)
+    };
+    draw_text(&title, menu_x + 15.0, menu_y + 28.0, 24.0, t.cursor);
 
-        // Selection background
-        if is_selected {
-            draw_rectangle(
-                menu_x + 15.0, y,
-                menu_width - 30.0, slot_height - 5.0,
-                Color::new(0.3, 0.3, 0.4, 0.6),
-            );
+    // Status line: where the hints came from
+    let status = if hints.loading {
+        "Fetching hints...".to_string()
+    } else if let Some(error) = &hints.error {
+        if hints.cached_at.is_some() {
+            "Offline - showing cached hints".to_string()
+        } else {
+            format!("Could not load hints: {}", error)
         }
+    } else if hints.hints.is_empty() {
+        String::new()
+    } else {
+        format!("Hint {}/{}", hints.selected + 1, hints.hints.len())
+    };
+    let status_color = if hints.error.is_some() {
+        t.warning
+    } else {
+        t.text_secondary
+    };
+    draw_text(&status, menu_x + 15.0, menu_y + 50.0, 16.0, status_color);
 
-        // Player label with color
-        let player_colors = [
-            Color::new(0.2, 0.6, 1.0, 1.0),  // P1: Blue
-            Color::new(1.0, 0.3, 0.3, 1.0),  // P2: Red
-            Color::new(0.3, 1.0, 0.3, 1.0),  // P3: Green
-            Color::new(1.0, 1.0, 0.3, 1.0),  // P4: Yellow
-        ];
-        let player_color = player_colors[player];
-        draw_text(&format!("P{}", player + 1), menu_x + 30.0, y + 35.0, 28.0, player_color);
-
-        // Assigned controller name
-        let controller_name = state.controllers.get_player_controller(player + 1)
-            .map(|c| c.name.clone())
-            .unwrap_or_else(|| "< Not Assigned >".to_string());
-        
-        let name_color = if state.controllers.player_assignments[player].is_some() {
-            t.text
+    let body_y = menu_y + 80.0;
+    if let Some(hint) = hints.current() {
+        let byline = if hint.submitted.is_empty() {
+            format!("by {}", hint.author)
         } else {
-            t.text_disabled
+            format!("by {} on {}", hint.author, hint.submitted)
         };
-        draw_text(&controller_name, menu_x + 100.0, y + 35.0, 20.0, name_color);
-
-        // Left/Right arrows if selected
-        if is_selected {
-            draw_text("◄", menu_x + menu_width - 80.0, y + 35.0, 24.0, t.cursor);
-            draw_text("►", menu_x + menu_width - 40.0, y + 35.0, 24.0, t.cursor);
+        draw_text(&byline, menu_x + 15.0, body_y, 16.0, t.accent);
+
+        // Hint text, clipped to the panel
+        let line_height = 20.0;
+        let max_lines = 9;
+        let lines = wrap_text(&hint.text, menu_width - 30.0, 18.0);
+        for (i, line) in lines.iter().take(max_lines).enumerate() {
+            let text = if i == max_lines - 1 && lines.len() > max_lines {
+                format!("{}...", line)
+            } else {
+                line.clone()
+            };
+            draw_text(
+                &text,
+                menu_x + 15.0,
+                body_y + 26.0 + i as f32 * line_height,
+                18.0,
+                t.text,
+            );
         }
-    }
-
-    // Available controllers summary
-    let unassigned_count = state.controllers.controllers.iter()
-        .filter(|c| c.assigned_player.is_none())
-        .count();
-    if unassigned_count > 0 {
+    } else if !hints.loading {
         draw_text(
-            &format!("{} unassigned controller(s) available", unassigned_count),
-            menu_x + 20.0,
-            menu_y + menu_height - 50.0,
-            16.0,
-            YELLOW,
+            "No community hints for this achievement yet",
+            menu_x + 15.0,
+            body_y + 20.0,
+            18.0,
+            t.text_disabled,
         );
     }
 
+    // Guide link for the full discussion
+    draw_text(
+        &format!("Guide: {}", crate::hints::guide_url(hints.achievement_id)),
+        menu_x + 15.0,
+        menu_y + menu_height - 34.0,
+        14.0,
+        t.text_secondary,
+    );
+
     // Controls hint
     draw_text(
-        "Up/Down: Select Player • Left/Right: Change • A: Quick Assign • B: Back",
-        menu_x + 20.0,
-        menu_y + menu_height - 20.0,
+        "◀/▶: Previous/Next Hint • B: Back",
+        menu_x + 15.0,
+        menu_y + menu_height - 12.0,
         14.0,
         LIGHTGRAY,
     );
 }
 
-fn render_gamepad_tester(state: &OverlayState) {
+fn render_achievement_stats(state: &OverlayState) {
     let t = theme(state);
-    let menu_width = 620.0;
-    let menu_height = 340.0;
+    let menu_width = 600.0;
+    let menu_height = 400.0;
     let menu_x = (screen_width() - menu_width) / 2.0;
     let menu_y = (screen_height() - menu_height) / 2.0;
 
-    draw_rectangle(menu_x, menu_y, menu_width, menu_height, t.panel_background);
-    draw_rectangle_lines(menu_x, menu_y, menu_width, menu_height, 2.0, t.panel_border);
+    draw_panel(menu_x, menu_y, menu_width, menu_height, t);
 
-    // Title with controller selector
-    draw_text("🕹️ GAMEPAD TESTER", menu_x + 20.0, menu_y + 35.0, 26.0, t.cursor);
+    let tracker = &state.achievements;
+    let title = if tracker.game_title.is_empty() {
+        "📊 Statistics".to_string()
+    } else {
+        format!("📊 {}", tracker.game_title)
+    };
+    draw_text(&title, menu_x + 15.0, menu_y + 28.0, 24.0, t.cursor);
 
-    if state.controllers.controllers.is_empty() {
+    // Loaded on entering the screen; computed here only for the first frame
+    let computed;
+    let stats = match state.resources.stats.get() {
+        Some(stats) => stats,
+        None => {
+            computed = state.achievement_stats();
+            &computed
+        }
+    };
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+
+    // Unlock heatmap: one column per week, today in the bottom right
+    let section_y = menu_y + 60.0;
+    draw_text(
+        &format!("Unlocks, last {} weeks", HEATMAP_WEEKS),
+        menu_x + 15.0,
+        section_y,
+        16.0,
+        t.text_secondary,
+    );
+    let cell = 14.0;
+    let gap = 3.0;
+    let grid_y = section_y + 10.0;
+    let busiest = stats.busiest_day().max(1) as f32;
+    for (i, &count) in stats.daily_unlocks.iter().enumerate() {
+        let x = menu_x + 15.0 + (i / 7) as f32 * (cell + gap);
+        let y = grid_y + (i % 7) as f32 * (cell + gap);
+        let color = if count == 0 {
+            Color::new(0.2, 0.2, 0.2, 1.0)
+        } else {
+            let strength = 0.35 + 0.65 * count as f32 / busiest;
+            Color::new(
+                t.success.r * strength,
+                t.success.g * strength,
+                t.success.b * strength,
+                1.0,
+            )
+        };
+        draw_rectangle(x, y, cell, cell, color);
+    }
+    let heatmap_bottom = grid_y + 7.0 * (cell + gap);
+
+    // Points per session, newest first
+    let sessions_x = menu_x + 15.0 + HEATMAP_WEEKS as f32 * (cell + gap) + 25.0;
+    draw_text(
+        "Recent sessions",
+        sessions_x,
+        section_y,
+        16.0,
+        t.text_secondary,
+    );
+    if stats.sessions.is_empty() {
         draw_text(
-            "No controllers connected",
-            menu_x + menu_width / 2.0 - 100.0,
-            menu_y + 150.0,
-            22.0,
-            GRAY,
+            "No unlocks recorded yet",
+            sessions_x,
+            section_y + 24.0,
+            16.0,
+            t.text_disabled,
         );
+    }
+    for (i, session) in stats.sessions.iter().rev().take(6).enumerate() {
+        let days_ago = now.saturating_sub(session.start) / (24 * 60 * 60);
+        let when = match days_ago {
+            0 => "Today".to_string(),
+            1 => "Yesterday".to_string(),
+            n => format!("{} days ago", n),
+        };
+        let unlocks = if session.unlocks == 1 {
+            "unlock"
+        } else {
+            "unlocks"
+        };
+        let y = section_y + 24.0 + i as f32 * 20.0;
+        draw_text(&when, sessions_x, y, 16.0, t.text);
         draw_text(
-            "Connect a controller to test",
-            menu_x + menu_width / 2.0 - 110.0,
-            menu_y + 190.0,
-            18.0,
-            DARKGRAY,
-        );
-    } else {
-        // Controller name with navigation
-        let controller_name = state.controllers.controllers
-            .get(state.controllers.tester_selected_controller)
-            .map(|c| c.name.as_str())
-            .unwrap_or("Unknown");
-        
-        let nav_text = format!("◄ {} ({}/{}) ►", 
-            controller_name,
-            state.controllers.tester_selected_controller + 1,
-            state.controllers.controllers.len()
+            &format!("{} {} • {} pts", session.unlocks, unlocks, session.points),
+            sessions_x + 110.0,
+            y,
+            16.0,
+            GOLD,
         );
-        draw_text(&nav_text, menu_x + 180.0, menu_y + 35.0, 18.0, LIGHTGRAY);
+    }
 
-        let btn = &state.controllers.tester_button_state;
-
-        // Layout constants
-        let left_x = menu_x + 80.0;
-        let right_x = menu_x + 420.0;
-        let center_y = menu_y + 170.0;
-
-        // === Left side: D-Pad and Left Stick ===
-        
-        // D-Pad
-        let dpad_x = left_x;
-        let dpad_y = center_y - 40.0;
-        let btn_size = 28.0;
-        
-        draw_text("D-PAD", dpad_x - 10.0, dpad_y - 45.0, 14.0, GRAY);
-        // Up
-        draw_button(dpad_x, dpad_y - btn_size, btn_size, btn.dpad_up, "▲");
-        // Down
-        draw_button(dpad_x, dpad_y + btn_size, btn_size, btn.dpad_down, "▼");
-        // Left
-        draw_button(dpad_x - btn_size, dpad_y, btn_size, btn.dpad_left, "◄");
-        // Right
-        draw_button(dpad_x + btn_size, dpad_y, btn_size, btn.dpad_right, "►");
-
-        // Left Stick
-        let ls_x = left_x + 100.0;
-        let ls_y = center_y + 40.0;
-        draw_text("L STICK", ls_x - 15.0, ls_y - 50.0, 14.0, GRAY);
-        draw_stick(ls_x, ls_y, 35.0, btn.left_stick_x, btn.left_stick_y, btn.ls_press);
-
-        // === Center: Triggers and special buttons ===
-        
-        let center_x = menu_x + menu_width / 2.0;
-        
-        // Triggers at top
-        let trigger_y = menu_y + 70.0;
-        draw_text("LT", center_x - 100.0, trigger_y, 14.0, GRAY);
-        draw_trigger(center_x - 80.0, trigger_y + 5.0, 60.0, 15.0, btn.lt);
-        draw_text("RT", center_x + 50.0, trigger_y, 14.0, GRAY);
-        draw_trigger(center_x + 20.0, trigger_y + 5.0, 60.0, 15.0, btn.rt);
-
-        // Bumpers
-        let bumper_y = trigger_y + 30.0;
-        draw_button(center_x - 80.0, bumper_y, 30.0, btn.lb, "LB");
-        draw_button(center_x + 50.0, bumper_y, 30.0, btn.rb, "RB");
-
-        // Select/Start/Guide
-        let special_y = center_y;
-        draw_button(center_x - 60.0, special_y, 25.0, btn.select, "⊡");
-        draw_button(center_x, special_y - 20.0, 30.0, btn.guide, "⬡");
-        draw_button(center_x + 35.0, special_y, 25.0, btn.start, "≡");
-        draw_text("SEL", center_x - 65.0, special_y + 35.0, 10.0, DARKGRAY);
-        draw_text("GUIDE", center_x - 15.0, special_y + 15.0, 10.0, DARKGRAY);
-        draw_text("START", center_x + 25.0, special_y + 35.0, 10.0, DARKGRAY);
-
-        // === Right side: Face buttons and Right Stick ===
-        
-        // Face buttons (ABXY)
-        let face_x = right_x;
-        let face_y = center_y - 40.0;
-        
-        draw_text("BUTTONS", face_x - 20.0, face_y - 45.0, 14.0, GRAY);
-        // A (bottom)
-        draw_button_colored(face_x, face_y + btn_size, btn_size, btn.a, "A", GREEN);
-        // B (right)
-        draw_button_colored(face_x + btn_size, face_y, btn_size, btn.b, "B", RED);
-        // X (left)
-        draw_button_colored(face_x - btn_size, face_y, btn_size, btn.x, "X", BLUE);
-        // Y (top)
-        draw_button_colored(face_x, face_y - btn_size, btn_size, btn.y, "Y", YELLOW);
-
-        // Right Stick
-        let rs_x = right_x - 100.0;
-        let rs_y = center_y + 40.0;
-        draw_text("R STICK", rs_x - 15.0, rs_y - 50.0, 14.0, GRAY);
-        draw_stick(rs_x, rs_y, 35.0, btn.right_stick_x, btn.right_stick_y, btn.rs_press);
+    // Rarity of what's left, as one segmented bar
+    let rarity_y = heatmap_bottom + 25.0;
+    draw_text(
+        &format!("Remaining: {}", stats.remaining),
+        menu_x + 15.0,
+        rarity_y,
+        16.0,
+        t.text_secondary,
+    );
+    let bar_x = menu_x + 15.0;
+    let bar_y = rarity_y + 10.0;
+    let bar_width = menu_width - 30.0;
+    let known: u32 = stats.remaining_by_rarity.iter().sum();
+    let total = (known + stats.remaining_unknown_rarity).max(1) as f32;
+    draw_rectangle(
+        bar_x,
+        bar_y,
+        bar_width,
+        10.0,
+        Color::new(0.2, 0.2, 0.2, 1.0),
+    );
+    let mut segment_x = bar_x;
+    let mut legend_x = bar_x;
+    for (i, &(_, label)) in RARITY_BUCKETS.iter().enumerate() {
+        let count = stats.remaining_by_rarity[i];
+        let color = t.rarity[i];
+        let width = bar_width * count as f32 / total;
+        draw_rectangle(segment_x, bar_y, width, 10.0, color);
+        segment_x += width;
+
+        let legend = format!("{} {}", label, count);
+        draw_text(&legend, legend_x, bar_y + 28.0, 14.0, color);
+        legend_x += measure_text(&legend, None, 14, 1.0).width + 14.0;
+    }
+    if stats.remaining_unknown_rarity > 0 {
+        draw_text(
+            &format!("Unknown {}", stats.remaining_unknown_rarity),
+            legend_x,
+            bar_y + 28.0,
+            14.0,
+            t.text_disabled,
+        );
+    }
 
-        // Last input indicator
-        let elapsed = state.controllers.tester_last_input_time.elapsed();
-        if elapsed.as_secs() < 2 {
-            draw_text("● Input detected", menu_x + 20.0, menu_y + menu_height - 50.0, 14.0, GREEN);
+    // Time to completion
+    let estimate = match stats.estimate {
+        _ if stats.remaining == 0 => "Set complete!".to_string(),
+        Some((secs, EstimateBasis::Playtime)) => {
+            format!(
+                "Estimated time to 100%: {} of play at your unlock rate",
+                stats::format_duration(secs)
+            )
         }
-    }
+        Some((secs, EstimateBasis::Calendar)) => {
+            format!(
+                "Estimated time to 100%: ~{} at your current pace",
+                stats::format_duration(secs)
+            )
+        }
+        None => "Not enough unlocks yet to estimate completion".to_string(),
+    };
+    draw_text(&estimate, menu_x + 15.0, bar_y + 60.0, 18.0, t.text);
 
     // Controls hint
     draw_text(
-        "Left/Right: Switch Controller • B: Back • Press buttons to test",
-        menu_x + 20.0,
-        menu_y + menu_height - 18.0,
+        "B: Back",
+        menu_x + 15.0,
+        menu_y + menu_height - 12.0,
         14.0,
         LIGHTGRAY,
     );
 }
 
-// Helper functions for gamepad tester rendering
-
-fn draw_button(x: f32, y: f32, size: f32, pressed: bool, label: &str) {
-    let color = if pressed { GREEN } else { Color::new(0.3, 0.3, 0.3, 1.0) };
-    let border = if pressed { WHITE } else { GRAY };
-    
-    draw_rectangle(x - size/2.0, y - size/2.0, size, size, color);
-    draw_rectangle_lines(x - size/2.0, y - size/2.0, size, size, 2.0, border);
-    
-    let label_size = if label.len() > 1 { 12.0 } else { 16.0 };
-    let dims = measure_text(label, None, label_size as u16, 1.0);
-    draw_text(label, x - dims.width/2.0, y + dims.height/4.0, label_size, WHITE);
-}
-
-fn draw_button_colored(x: f32, y: f32, size: f32, pressed: bool, label: &str, color: Color) {
-    let bg_color = if pressed { color } else { Color::new(0.2, 0.2, 0.2, 1.0) };
-    let border = if pressed { WHITE } else { Color::new(color.r * 0.5, color.g * 0.5, color.b * 0.5, 1.0) };
-    
-    draw_circle(x, y, size/2.0, bg_color);
-    draw_circle_lines(x, y, size/2.0, 2.0, border);
-    
-    let dims = measure_text(label, None, 14, 1.0);
-    draw_text(label, x - dims.width/2.0, y + dims.height/4.0, 14.0, WHITE);
-}
-
-fn draw_stick(x: f32, y: f32, radius: f32, stick_x: f32, stick_y: f32, pressed: bool) {
-    // Outer circle (deadzone area)
-    draw_circle_lines(x, y, radius, 2.0, GRAY);
-    
-    // Stick position
-    let stick_radius = radius * 0.4;
-    let pos_x = x + stick_x * (radius - stick_radius);
-    let pos_y = y - stick_y * (radius - stick_radius); // Invert Y for display
-    
-    let stick_color = if pressed { GREEN } else { Color::new(0.6, 0.6, 0.6, 1.0) };
-    draw_circle(pos_x, pos_y, stick_radius, stick_color);
-    draw_circle_lines(pos_x, pos_y, stick_radius, 2.0, WHITE);
-}
-
-fn draw_trigger(x: f32, y: f32, width: f32, height: f32, value: f32) {
-    // Background
-    draw_rectangle(x, y, width, height, Color::new(0.2, 0.2, 0.2, 1.0));
-    // Fill
-    let fill_color = if value > 0.1 { GREEN } else { Color::new(0.3, 0.3, 0.3, 1.0) };
-    draw_rectangle(x, y, width * value, height, fill_color);
-    // Border
-    draw_rectangle_lines(x, y, width, height, 1.0, GRAY);
-}
-
-fn render_hotkey_settings(_state: &OverlayState) {
-    let menu_width = 620.0;
+fn render_compare_screen(state: &OverlayState) {
+    let t = theme(state);
+    let menu_width = 600.0;
     let menu_height = 400.0;
     let menu_x = (screen_width() - menu_width) / 2.0;
     let menu_y = (screen_height() - menu_height) / 2.0;
 
-    draw_rectangle(menu_x, menu_y, menu_width, menu_height, Color::new(0.1, 0.1, 0.15, 0.98));
-    draw_rectangle_lines(menu_x, menu_y, menu_width, menu_height, 2.0, YELLOW);
+    draw_panel(menu_x, menu_y, menu_width, menu_height, t);
+    draw_text(
+        "👥 Compare with Friends",
+        menu_x + 15.0,
+        menu_y + 28.0,
+        24.0,
+        t.cursor,
+    );
 
-    // Title
-    draw_text("⌨️ HOTKEY SETTINGS", menu_x + 20.0, menu_y + 40.0, 28.0, YELLOW);
+    let Some(compare) = &state.compare else {
+        return;
+    };
 
-    // Coming soon message
-    draw_text(
-        "Configure custom hotkey bindings",
-        menu_x + 20.0,
-        menu_y + 80.0,
-        18.0,
-        LIGHTGRAY,
-    );
+    let mine_x = menu_x + menu_width - 170.0;
+    let theirs_x = menu_x + menu_width - 85.0;
+
+    // Friend selector
+    let friend_line = match compare.current_friend() {
+        Some(friend) => format!(
+            "◀ {} ({}/{}) ▶",
+            friend,
+            compare.friend_index + 1,
+            compare.friends.len()
+        ),
+        None if compare.loading => "Loading friends...".to_string(),
+        None => "You are not following anyone on RetroAchievements".to_string(),
+    };
+    draw_text(&friend_line, menu_x + 15.0, menu_y + 54.0, 18.0, t.accent);
 
-    let info_y = menu_y + 140.0;
-    let line_height = 30.0;
+    if let Some(error) = &compare.error {
+        draw_text(
+            &format!("Could not load progress: {}", error),
+            menu_x + 15.0,
+            menu_y + 76.0,
+            16.0,
+            t.warning,
+        );
+    } else if compare.loading && compare.current_friend().is_some() {
+        draw_text(
+            "Loading progress...",
+            menu_x + 15.0,
+            menu_y + 76.0,
+            16.0,
+            t.text_secondary,
+        );
+    }
 
-    // Show current default bindings
-    draw_text("Current Hotkeys:", menu_x + 20.0, info_y, 20.0, WHITE);
+    let Some(comparison) = compare.current() else {
+        return;
+    };
+
+    // Column headers with totals
+    let header_y = menu_y + 100.0;
+    draw_text(
+        "Achievement",
+        menu_x + 15.0,
+        header_y,
+        16.0,
+        t.text_secondary,
+    );
+    draw_text("You", mine_x, header_y, 16.0, t.text_secondary);
+    let friend_name: String = comparison.friend.chars().take(8).collect();
+    draw_text(&friend_name, theirs_x, header_y, 16.0, t.text_secondary);
+
+    let row_height = 24.0;
+    let rows_y = header_y + 22.0;
+    for (i, achievement) in comparison
+        .achievements
+        .iter()
+        .skip(compare.scroll_offset)
+        .take(COMPARE_VISIBLE_ROWS)
+        .enumerate()
+    {
+        let y = rows_y + i as f32 * row_height;
+        let title = if achievement.title.chars().count() > 40 {
+            format!(
+                "{}...",
+                achievement.title.chars().take(37).collect::<String>()
+            )
+        } else {
+            achievement.title.clone()
+        };
+        let title_color = if achievement.mine || achievement.theirs {
+            t.text
+        } else {
+            t.text_disabled
+        };
+        draw_text(&title, menu_x + 15.0, y, 16.0, title_color);
 
-    draw_text("• Toggle Overlay:", menu_x + 30.0, info_y + line_height, 16.0, LIGHTGRAY);
-    draw_text("F12, Ctrl+O, Guide Button", menu_x + 200.0, info_y + line_height, 16.0, GREEN);
+        for (earned, x) in [(achievement.mine, mine_x), (achievement.theirs, theirs_x)] {
+            let (mark, color) = if earned {
+                ("✓", t.success)
+            } else {
+                ("-", t.text_disabled)
+            };
+            draw_text(mark, x + 8.0, y, 18.0, color);
+        }
+    }
 
-    draw_text("• Performance HUD:", menu_x + 30.0, info_y + line_height * 2.0, 16.0, LIGHTGRAY);
-    draw_text("F3", menu_x + 200.0, info_y + line_height * 2.0, 16.0, GREEN);
+    // Totals
+    let totals_y = rows_y + COMPARE_VISIBLE_ROWS as f32 * row_height + 4.0;
+    let totals = format!(
+        "You: {}/{} ({} pts) • {}: {}/{} ({} pts)",
+        comparison.mine_earned(),
+        comparison.total(),
+        comparison.mine_points(),
+        comparison.friend,
+        comparison.theirs_earned(),
+        comparison.total(),
+        comparison.theirs_points()
+    );
+    draw_text(&totals, menu_x + 15.0, totals_y, 16.0, t.text);
 
-    draw_text("• Quick Save:", menu_x + 30.0, info_y + line_height * 3.0, 16.0, LIGHTGRAY);
-    draw_text("F5", menu_x + 200.0, info_y + line_height * 3.0, 16.0, GREEN);
+    // Controls hint
+    draw_text(
+        "◀/▶: Switch Friend • ▲/▼: Scroll • B: Back",
+        menu_x + 15.0,
+        menu_y + menu_height - 12.0,
+        14.0,
+        LIGHTGRAY,
+    );
+}
 
-    draw_text("• Quick Load:", menu_x + 30.0, info_y + line_height * 4.0, 16.0, LIGHTGRAY);
-    draw_text("F9", menu_x + 200.0, info_y + line_height * 4.0, 16.0, GREEN);
+fn render_challenges_screen(state: &OverlayState) {
+    let t = theme(state);
+    let menu_width = 600.0;
+    let menu_height = 400.0;
+    let menu_x = (screen_width() - menu_width) / 2.0;
+    let menu_y = (screen_height() - menu_height) / 2.0;
 
-    // Coming soon notice
+    draw_panel(menu_x, menu_y, menu_width, menu_height, t);
     draw_text(
-        "🚧 Custom hotkey configuration coming soon! 🚧",
-        menu_x + 80.0,
-        menu_y + menu_height - 60.0,
-        16.0,
-        ORANGE,
+        "🎯 Challenges",
+        menu_x + 15.0,
+        menu_y + 28.0,
+        24.0,
+        t.cursor,
     );
 
-    // Controls hint
+    if state.challenges.is_empty() {
+        draw_text(
+            "No challenges yet.",
+            menu_x + 15.0,
+            menu_y + 70.0,
+            18.0,
+            t.text_secondary,
+        );
+        draw_text(
+            "They are drawn from unearned achievements of games you've played.",
+            menu_x + 15.0,
+            menu_y + 94.0,
+            16.0,
+            t.text_disabled,
+        );
+    }
+
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let row_height = 40.0;
+    let rows_y = menu_y + 60.0;
+    let rows = state.challenges.iter().flat_map(|set| {
+        std::iter::once((set, None)).chain(set.challenges.iter().map(move |c| (set, Some(c))))
+    });
+    for (i, (set, challenge)) in rows
+        .skip(state.challenges_scroll)
+        .take(CHALLENGES_VISIBLE_ROWS)
+        .enumerate()
+    {
+        let y = rows_y + i as f32 * row_height;
+        let Some(challenge) = challenge else {
+            // Set header: progress, time left and streak
+            let done = set.challenges.iter().filter(|c| c.completed).count();
+            let left = set.ends_at.saturating_sub(now);
+            let period = set.period.to_uppercase();
+            let header = format!(
+                "{} {}/{} • ends in {}h {}m",
+                period,
+                done,
+                set.challenges.len(),
+                left / 3600,
+                left % 3600 / 60
+            );
+            draw_text(&header, menu_x + 15.0, y + 24.0, 18.0, t.accent);
+            let streak = format!("🔥 {} (best {})", set.streak, set.best_streak);
+            let streak_width = measure_text(&streak, None, 16, 1.0).width;
+            draw_text(
+                &streak,
+                menu_x + menu_width - 15.0 - streak_width,
+                y + 24.0,
+                16.0,
+                t.text,
+            );
+            continue;
+        };
+
+        let (mark, mark_color) = if challenge.completed {
+            ("✓", t.success)
+        } else {
+            ("○", t.text_disabled)
+        };
+        draw_text(mark, menu_x + 20.0, y + 18.0, 18.0, mark_color);
+        let title_color = if challenge.completed {
+            t.text_secondary
+        } else {
+            t.text
+        };
+        let title: String = challenge.title.chars().take(42).collect();
+        draw_text(&title, menu_x + 45.0, y + 16.0, 17.0, title_color);
+
+        let mut detail = format!("{} • {} pts", challenge.game_title, challenge.points);
+        if let Some(rarity) = challenge.rarity_percent {
+            detail.push_str(&format!(" • {:.1}% of players", rarity));
+        }
+        draw_text(&detail, menu_x + 45.0, y + 34.0, 14.0, t.text_disabled);
+        if let Some(rarity) = challenge.rarity_percent {
+            draw_circle(
+                menu_x + menu_width - 25.0,
+                y + 14.0,
+                5.0,
+                t.rarity_color(rarity),
+            );
+        }
+    }
+
     draw_text(
-        "B: Back to Controllers",
-        menu_x + 20.0,
-        menu_y + menu_height - 20.0,
-        16.0,
+        "▲/▼: Scroll • B: Back",
+        menu_x + 15.0,
+        menu_y + menu_height - 12.0,
+        14.0,
         LIGHTGRAY,
     );
 }
 
-fn render_menu_customization(state: &OverlayState) {
+fn render_controllers_menu(state: &OverlayState) {
     let t = theme(state);
-    let menu_width = 700.0;
-    let menu_height = 500.0;
+    let menu_width = 600.0;
+    let menu_height = 530.0;
     let menu_x = (screen_width() - menu_width) / 2.0;
     let menu_y = (screen_height() - menu_height) / 2.0;
 
+    // Menu background
     draw_rectangle(menu_x, menu_y, menu_width, menu_height, t.panel_background);
     draw_rectangle_lines(menu_x, menu_y, menu_width, menu_height, 2.0, t.panel_border);
 
     // Title
-    draw_text("⚙️ MENU CUSTOMIZATION", menu_x + 20.0, menu_y + 40.0, 28.0, t.cursor);
-
-    // Instructions
     draw_text(
-        "Left/Right: Toggle visibility • LB/RB: Move up/down",
+        "🎮 CONTROLLERS",
         menu_x + 20.0,
-        menu_y + 70.0,
-        16.0,
-        LIGHTGRAY,
+        menu_y + 40.0,
+        32.0,
+        t.cursor,
     );
 
-    // Menu items list
-    let all_items = state.menu_config.config().get_all_items();
-    let item_start_y = menu_y + 110.0;
-    let item_height = 45.0;
-    let max_visible = 8;
+    // Connected controller count
+    let controller_count = state.controllers.controllers.len();
+    let status_text = format!("{} controller(s) connected", controller_count);
+    draw_text(&status_text, menu_x + 20.0, menu_y + 70.0, 18.0, LIGHTGRAY);
 
-    // Use scroll offset from state
-    let scroll_offset = state.menu_customization_scroll_offset;
+    // Menu options
+    let option_start_y = menu_y + 110.0;
+    let option_height = 45.0;
+    const MAX_VISIBLE: usize = 8;
+    let menu_len = CONTROLLER_MENU_OPTIONS.len();
+    let scroll_offset = 0; // Controllers menu is short, no scrolling needed for now
 
-    for i in 0..max_visible.min(all_items.len() - scroll_offset) {
+    for i in 0..MAX_VISIBLE.min(menu_len.saturating_sub(scroll_offset)) {
         let item_idx = scroll_offset + i;
-        if item_idx >= all_items.len() {
+        if item_idx >= menu_len {
             break;
         }
-        let item_id = all_items[item_idx];
-        let y = item_start_y + (i as f32 * item_height);
-        let is_selected = item_idx == state.menu_customization_selected;
-        
-        // Get item config
-        let item_config = state.menu_config.config().items.iter()
-            .find(|item| item.id == item_id)
-            .unwrap();
+        let option = CONTROLLER_MENU_OPTIONS[item_idx];
+        let y = option_start_y + (i as f32 * option_height);
+        let is_selected = item_idx == state.controllers.selected_menu_item;
+        let color = if is_selected { t.cursor } else { t.text };
 
-        // Selection background
+        // Selection indicator
         if is_selected {
             draw_rectangle(
-                menu_x + 15.0, y,
-                menu_width - 30.0, item_height - 5.0,
-                Color::new(0.3, 0.3, 0.4, 0.6),
+                menu_x + 15.0,
+                y - 5.0,
+                menu_width - 30.0,
+                option_height - 5.0,
+                Color::new(t.accent.r * 0.2, t.accent.g * 0.2, t.accent.b * 0.2, 0.6),
             );
-            draw_text("►", menu_x + 25.0, y + 28.0, 24.0, t.cursor);
+            draw_text("►", menu_x + 25.0, y + 25.0, 24.0, t.cursor);
         }
 
-        // Visibility indicator
-        let visibility_icon = if item_config.visible { "✓" } else { "✗" };
-        let visibility_color = if item_config.visible { t.success } else { t.error };
-        draw_text(visibility_icon, menu_x + 60.0, y + 28.0, 20.0, visibility_color);
-
-        // Item name
-        let name_color = if item_config.visible { t.text } else { t.text_disabled };
-        draw_text(item_id.display_name(), menu_x + 100.0, y + 28.0, 22.0, name_color);
-
-        // Order indicator
-        let order_text = format!("#{:02}", item_config.order + 1);
-        draw_text(&order_text, menu_x + menu_width - 100.0, y + 28.0, 18.0, LIGHTGRAY);
+        draw_text(option, menu_x + 60.0, y + 25.0, 24.0, color);
 
-        // Move indicators
-        if is_selected {
-            if item_idx > 0 {
-                draw_text("▲", menu_x + menu_width - 60.0, y + 28.0, 18.0, LIGHTGRAY);
+        // Show sub-info for some options
+        match i {
+            0 => {
+                // Bluetooth - show paired count if any
+                let paired = state
+                    .controllers
+                    .bluetooth_devices
+                    .iter()
+                    .filter(|d| d.is_paired)
+                    .count();
+                if paired > 0 {
+                    draw_text(
+                        &format!("{} paired", paired),
+                        menu_x + menu_width - 120.0,
+                        y + 25.0,
+                        18.0,
+                        GRAY,
+                    );
+                }
             }
-            if item_idx < all_items.len() - 1 {
-                draw_text("▼", menu_x + menu_width - 40.0, y + 28.0, 18.0, LIGHTGRAY);
+            1 => {
+                // Assign - show assignment summary
+                let assigned = state
+                    .controllers
+                    .player_assignments
+                    .iter()
+                    .filter(|a| a.is_some())
+                    .count();
+                draw_text(
+                    &format!("{}/4 assigned", assigned),
+                    menu_x + menu_width - 120.0,
+                    y + 25.0,
+                    18.0,
+                    GRAY,
+                );
             }
+            _ => {}
         }
     }
 
-    // Scroll indicators
-    if scroll_offset > 0 {
-        draw_text("▲", menu_x + menu_width - 25.0, item_start_y + 10.0, 16.0, LIGHTGRAY);
+    // Success/error message
+    if let Some((msg, _)) = &state.controllers.success_message {
+        draw_text(msg, menu_x + 20.0, menu_y + menu_height - 50.0, 18.0, GREEN);
     }
-    if scroll_offset + max_visible < all_items.len() {
-        draw_text("▼", menu_x + menu_width - 25.0, item_start_y + (max_visible as f32 * item_height) - 15.0, 16.0, LIGHTGRAY);
+    if let Some(msg) = &state.controllers.error_message {
+        draw_text(msg, menu_x + 20.0, menu_y + menu_height - 50.0, 18.0, RED);
     }
 
     // Controls hint
     draw_text(
-        "D-Pad: Navigate • Left/Right: Toggle • LB/RB: Reorder • B: Back",
+        "D-Pad: Navigate • A: Select • B: Back",
         menu_x + 20.0,
         menu_y + menu_height - 20.0,
-        14.0,
+        16.0,
         LIGHTGRAY,
     );
 }
 
-fn render_theme_selection(state: &OverlayState) {
+fn render_bluetooth_screen(state: &OverlayState) {
     let t = theme(state);
-    let menu_width = 700.0;
-    let menu_height = 500.0;
+    let menu_width = 600.0;
+    let menu_height = 400.0;
     let menu_x = (screen_width() - menu_width) / 2.0;
     let menu_y = (screen_height() - menu_height) / 2.0;
 
@@ -1032,191 +1963,2936 @@ fn render_theme_selection(state: &OverlayState) {
     draw_rectangle_lines(menu_x, menu_y, menu_width, menu_height, 2.0, t.panel_border);
 
     // Title
-    draw_text("🎨 THEME SELECTION", menu_x + 20.0, menu_y + 40.0, 28.0, t.cursor);
-
-    // Instructions
     draw_text(
-        "Select a theme and press A to apply",
+        "📶 BLUETOOTH CONTROLLERS",
         menu_x + 20.0,
-        menu_y + 70.0,
-        16.0,
-        t.text_secondary,
+        menu_y + 40.0,
+        28.0,
+        t.cursor,
     );
 
-    // Theme list
-    let themes = crate::theme_config::ThemeConfigManager::available_themes();
-    let theme_start_y = menu_y + 110.0;
-    let theme_height = 60.0;
-    let max_visible = 6;
+    // Scan state indicator
+    let scan_status = match &state.controllers.bluetooth_state {
+        BluetoothScanState::Idle => ("Press X to scan", GRAY),
+        BluetoothScanState::Scanning => ("Scanning...", YELLOW),
+        BluetoothScanState::Pairing(mac) => (&format!("Pairing: {}...", mac) as &str, ORANGE),
+        BluetoothScanState::Connecting(mac) => (&format!("Connecting: {}...", mac) as &str, BLUE),
+        BluetoothScanState::Error(err) => (err.as_str(), RED),
+    };
+    // Need to handle the lifetime issue differently
+    let (scan_text, scan_color) = scan_status;
+    draw_text(scan_text, menu_x + 20.0, menu_y + 70.0, 16.0, scan_color);
 
-    // Use scroll offset from state
-    let scroll_offset = state.theme_selection_scroll_offset;
+    // Device list
+    let list_y = menu_y + 100.0;
+    let item_height = 50.0;
+    let max_visible = 5;
 
-    for i in 0..max_visible.min(themes.len() - scroll_offset) {
-        let theme_idx = scroll_offset + i;
-        if theme_idx >= themes.len() {
-            break;
-        }
-        let theme_name = &themes[theme_idx];
-        let y = theme_start_y + (i as f32 * theme_height);
-        let is_selected = theme_idx == state.theme_selected;
-        let is_current = theme_name == state.theme_config.theme_name();
+    if state.controllers.bluetooth_devices.is_empty() {
+        draw_text(
+            "No devices found",
+            menu_x + menu_width / 2.0 - 80.0,
+            menu_y + 180.0,
+            20.0,
+            t.text_disabled,
+        );
+        draw_text(
+            "Press X to scan for Bluetooth controllers",
+            menu_x + 60.0,
+            menu_y + 220.0,
+            16.0,
+            t.text_secondary,
+        );
+    } else {
+        let device_count = state.controllers.bluetooth_devices.len();
+        let scroll_offset = state.controllers.bt_scroll_offset;
 
-        // Get theme preview
-        let preview_theme = crate::themes::Theme::by_name(theme_name).unwrap_or_else(|| crate::themes::Theme::dark());
+        for i in 0..max_visible.min(device_count.saturating_sub(scroll_offset)) {
+            let device_idx = scroll_offset + i;
+            if device_idx >= device_count {
+                break;
+            }
+            let device = &state.controllers.bluetooth_devices[device_idx];
+            let y = list_y + (i as f32 * item_height);
+            let is_selected = device_idx == state.controllers.bt_selected_index;
 
-        // Selection background
-        if is_selected {
-            draw_rectangle(
-                menu_x + 15.0, y,
-                menu_width - 30.0, theme_height - 5.0,
-                Color::new(t.accent.r * 0.2, t.accent.g * 0.2, t.accent.b * 0.2, 0.6),
-            );
-            draw_text("►", menu_x + 25.0, y + 35.0, 24.0, t.cursor);
+            // Selection background
+            if is_selected {
+                draw_rectangle(
+                    menu_x + 15.0,
+                    y,
+                    menu_width - 30.0,
+                    item_height - 5.0,
+                    Color::new(0.3, 0.3, 0.4, 0.6),
+                );
+            }
+
+            // Device icon based on state
+            let icon = if device.is_connected {
+                "🎮"
+            } else if device.is_paired {
+                "🔗"
+            } else {
+                "📡"
+            };
+            draw_text(icon, menu_x + 25.0, y + 30.0, 24.0, WHITE);
+
+            // Device name
+            let name_color = if device.is_connected {
+                t.success
+            } else if device.is_paired {
+                t.text
+            } else {
+                t.text_disabled
+            };
+            draw_text(&device.name, menu_x + 60.0, y + 25.0, 20.0, name_color);
+
+            // MAC address (smaller)
+            draw_text(&device.mac_address, menu_x + 60.0, y + 42.0, 12.0, DARKGRAY);
+
+            // Status
+            let status = if device.is_connected {
+                "Connected"
+            } else if device.is_paired {
+                "Paired"
+            } else {
+                "Available"
+            };
+            let status_color = if device.is_connected {
+                GREEN
+            } else if device.is_paired {
+                YELLOW
+            } else {
+                GRAY
+            };
+            draw_text(
+                status,
+                menu_x + menu_width - 110.0,
+                y + 30.0,
+                16.0,
+                status_color,
+            );
         }
 
-        // Theme name
-        let name_color = if is_selected { t.cursor } else { t.text };
-        draw_text(theme_name, menu_x + 60.0, y + 25.0, 24.0, name_color);
+        // Scroll indicators
+        if scroll_offset > 0 {
+            draw_text(
+                "▲",
+                menu_x + menu_width - 25.0,
+                list_y + 10.0,
+                16.0,
+                t.text_secondary,
+            );
+        }
+        if scroll_offset + max_visible < device_count {
+            draw_text(
+                "▼",
+                menu_x + menu_width - 25.0,
+                list_y + (max_visible as f32 * item_height) - 15.0,
+                16.0,
+                t.text_secondary,
+            );
+        }
+    }
+
+    // Controls hint
+    draw_text(
+        "D-Pad: Navigate • A: Pair/Connect • X: Scan • B: Back",
+        menu_x + 20.0,
+        menu_y + menu_height - 20.0,
+        14.0,
+        LIGHTGRAY,
+    );
+}
+
+fn render_assign_screen(state: &OverlayState) {
+    let t = theme(state);
+    let menu_width = 600.0;
+    let menu_height = 380.0;
+    let menu_x = (screen_width() - menu_width) / 2.0;
+    let menu_y = (screen_height() - menu_height) / 2.0;
+
+    draw_rectangle(menu_x, menu_y, menu_width, menu_height, t.panel_background);
+    draw_rectangle_lines(menu_x, menu_y, menu_width, menu_height, 2.0, t.panel_border);
+
+    // Title
+    draw_text(
+        "👥 ASSIGN CONTROLLERS",
+        menu_x + 20.0,
+        menu_y + 40.0,
+        28.0,
+        t.cursor,
+    );
+    draw_text(
+        "Use Left/Right to change assignment",
+        menu_x + 20.0,
+        menu_y + 68.0,
+        16.0,
+        LIGHTGRAY,
+    );
+
+    // Player assignment slots
+    let slot_start_y = menu_y + 100.0;
+    let slot_height = 60.0;
+
+    for player in 0..MAX_PLAYERS {
+        let y = slot_start_y + (player as f32 * slot_height);
+        let is_selected = player == state.controllers.assign_selected_player;
 
-        // Current theme indicator
-        if is_current {
-            draw_text("(Current)", menu_x + 60.0, y + 45.0, 16.0, t.accent);
+        // Selection background
+        if is_selected {
+            draw_rectangle(
+                menu_x + 15.0,
+                y,
+                menu_width - 30.0,
+                slot_height - 5.0,
+                Color::new(0.3, 0.3, 0.4, 0.6),
+            );
         }
 
-        // Color preview swatches
-        let swatch_size = 20.0;
-        let swatch_y = y + 20.0;
-        let swatch_start_x = menu_x + menu_width - 200.0;
-        let swatch_spacing = 25.0;
+        // Player label with color
+        let player_colors = [
+            Color::new(0.2, 0.6, 1.0, 1.0), // P1: Blue
+            Color::new(1.0, 0.3, 0.3, 1.0), // P2: Red
+            Color::new(0.3, 1.0, 0.3, 1.0), // P3: Green
+            Color::new(1.0, 1.0, 0.3, 1.0), // P4: Yellow
+        ];
+        let player_color = player_colors[player];
+        draw_text(
+            &format!("P{}", player + 1),
+            menu_x + 30.0,
+            y + 35.0,
+            28.0,
+            player_color,
+        );
 
-        // Background
-        draw_rectangle(swatch_start_x, swatch_y, swatch_size, swatch_size, preview_theme.panel_background);
-        draw_rectangle_lines(swatch_start_x, swatch_y, swatch_size, swatch_size, 1.0, preview_theme.panel_border);
-        
-        // Text
-        draw_rectangle(swatch_start_x + swatch_spacing, swatch_y, swatch_size, swatch_size, preview_theme.text);
-        
-        // Cursor/Accent
-        draw_rectangle(swatch_start_x + swatch_spacing * 2.0, swatch_y, swatch_size, swatch_size, preview_theme.cursor);
-        
-        // Accent
-        draw_rectangle(swatch_start_x + swatch_spacing * 3.0, swatch_y, swatch_size, swatch_size, preview_theme.accent);
+        // Assigned controller name
+        let controller_name = state
+            .controllers
+            .get_player_controller(player + 1)
+            .map(|c| c.name.clone())
+            .unwrap_or_else(|| "< Not Assigned >".to_string());
+
+        let name_color = if state.controllers.player_assignments[player].is_some() {
+            t.text
+        } else {
+            t.text_disabled
+        };
+        draw_text(&controller_name, menu_x + 100.0, y + 35.0, 20.0, name_color);
+
+        // Left/Right arrows if selected
+        if is_selected {
+            draw_text("◄", menu_x + menu_width - 80.0, y + 35.0, 24.0, t.cursor);
+            draw_text("►", menu_x + menu_width - 40.0, y + 35.0, 24.0, t.cursor);
+        }
     }
 
-    // Scroll indicators
-    if scroll_offset > 0 {
-        draw_text("▲", menu_x + menu_width - 25.0, theme_start_y + 10.0, 16.0, t.text_secondary);
+    // Available controllers summary
+    let unassigned_count = state
+        .controllers
+        .controllers
+        .iter()
+        .filter(|c| c.assigned_player.is_none())
+        .count();
+    if unassigned_count > 0 {
+        draw_text(
+            &format!("{} unassigned controller(s) available", unassigned_count),
+            menu_x + 20.0,
+            menu_y + menu_height - 50.0,
+            16.0,
+            YELLOW,
+        );
     }
-    if scroll_offset + max_visible < themes.len() {
-        draw_text("▼", menu_x + menu_width - 25.0, theme_start_y + (max_visible as f32 * theme_height) - 15.0, 16.0, t.text_secondary);
+
+    // Controls hint
+    draw_text(
+        "Up/Down: Select Player • Left/Right: Change • A: Quick Assign • B: Back",
+        menu_x + 20.0,
+        menu_y + menu_height - 20.0,
+        14.0,
+        LIGHTGRAY,
+    );
+}
+
+fn render_gamepad_tester(state: &OverlayState) {
+    let t = theme(state);
+    let menu_width = gamepad_tester::WIDTH;
+    let menu_height = gamepad_tester::HEIGHT;
+    let menu_x = (screen_width() - menu_width) / 2.0;
+    let menu_y = (screen_height() - menu_height) / 2.0;
+
+    draw_rectangle(menu_x, menu_y, menu_width, menu_height, t.panel_background);
+    draw_rectangle_lines(menu_x, menu_y, menu_width, menu_height, 2.0, t.panel_border);
+
+    // Title with controller selector
+    draw_text(
+        "🕹️ GAMEPAD TESTER",
+        menu_x + 20.0,
+        menu_y + 35.0,
+        26.0,
+        t.cursor,
+    );
+
+    if state.controllers.controllers.is_empty() {
+        draw_text(
+            "No controllers connected",
+            menu_x + menu_width / 2.0 - 100.0,
+            menu_y + 150.0,
+            22.0,
+            GRAY,
+        );
+        draw_text(
+            "Connect a controller to test",
+            menu_x + menu_width / 2.0 - 110.0,
+            menu_y + 190.0,
+            18.0,
+            DARKGRAY,
+        );
+    } else {
+        // Controller name with navigation
+        let controller_name = state
+            .controllers
+            .controllers
+            .get(state.controllers.tester_selected_controller)
+            .map(|c| c.name.as_str())
+            .unwrap_or("Unknown");
+
+        let nav_text = format!(
+            "◄ {} ({}/{}) ►",
+            controller_name,
+            state.controllers.tester_selected_controller + 1,
+            state.controllers.controllers.len()
+        );
+        draw_text(&nav_text, menu_x + 180.0, menu_y + 35.0, 18.0, LIGHTGRAY);
+
+        state.controllers.tester.draw(menu_x, menu_y, 1.0);
+
+        // Last input indicator
+        if state.controllers.tester.recently_active() {
+            draw_text(
+                "● Input detected",
+                menu_x + 20.0,
+                menu_y + menu_height - 50.0,
+                14.0,
+                GREEN,
+            );
+        }
     }
 
     // Controls hint
     draw_text(
-        "D-Pad: Navigate • A: Apply Theme • B: Back",
-        menu_x + 20.0,
-        menu_y + menu_height - 20.0,
-        14.0,
+        "Left/Right: Switch Controller • B: Back • Press buttons to test",
+        menu_x + 20.0,
+        menu_y + menu_height - 18.0,
+        14.0,
+        LIGHTGRAY,
+    );
+}
+
+fn render_macros_screen(state: &OverlayState) {
+    let t = theme(state);
+    let menu_width = 600.0;
+    let menu_height = 440.0;
+    let menu_x = (screen_width() - menu_width) / 2.0;
+    let menu_y = (screen_height() - menu_height) / 2.0;
+    const MAX_VISIBLE: usize = 7;
+
+    draw_rectangle(menu_x, menu_y, menu_width, menu_height, t.panel_background);
+    draw_rectangle_lines(menu_x, menu_y, menu_width, menu_height, 2.0, t.panel_border);
+
+    draw_text(
+        "⏺ INPUT MACROS",
+        menu_x + 20.0,
+        menu_y + 40.0,
+        28.0,
+        t.cursor,
+    );
+    draw_text(
+        "Guide stops a recording or aborts playback",
+        menu_x + 20.0,
+        menu_y + 66.0,
+        16.0,
+        LIGHTGRAY,
+    );
+
+    let row_y = menu_y + 90.0;
+    let row_height = 42.0;
+    let rows = state.macros.len() + 1;
+    let scroll = (state.macros_selected + 1).saturating_sub(MAX_VISIBLE);
+    for (i, row) in (scroll..rows).take(MAX_VISIBLE).enumerate() {
+        let y = row_y + i as f32 * row_height;
+        let is_selected = row == state.macros_selected;
+        if is_selected {
+            draw_rectangle(
+                menu_x + 15.0,
+                y - 5.0,
+                menu_width - 30.0,
+                row_height - 5.0,
+                Color::new(t.accent.r * 0.2, t.accent.g * 0.2, t.accent.b * 0.2, 0.6),
+            );
+        }
+        let color = if is_selected { t.cursor } else { t.text };
+
+        match row.checked_sub(1).and_then(|i| state.macros.get(i)) {
+            None => {
+                draw_text("+ RECORD NEW MACRO", menu_x + 25.0, y + 22.0, 20.0, color);
+            }
+            Some(m) => {
+                draw_text(&m.name, menu_x + 25.0, y + 22.0, 20.0, color);
+                let info = format!(
+                    "{} steps • {:.1}s • ◄ {}x ►",
+                    m.steps,
+                    m.play_secs(),
+                    m.speed
+                );
+                let dims = measure_text(&info, None, 16, 1.0);
+                draw_text(
+                    &info,
+                    menu_x + menu_width - 25.0 - dims.width,
+                    y + 22.0,
+                    16.0,
+                    LIGHTGRAY,
+                );
+            }
+        }
+    }
+
+    if state.macros.is_empty() {
+        draw_text(
+            "No macros recorded yet",
+            menu_x + 25.0,
+            row_y + row_height + 22.0,
+            18.0,
+            GRAY,
+        );
+    }
+
+    draw_text(
+        "A: Play/Record • ◄►: Speed • X: Delete • B: Back",
+        menu_x + 20.0,
+        menu_y + menu_height - 20.0,
+        16.0,
+        LIGHTGRAY,
+    );
+}
+
+fn render_music_screen(state: &OverlayState) {
+    let t = theme(state);
+    let menu_width = 600.0;
+    let menu_height = 480.0;
+    let menu_x = (screen_width() - menu_width) / 2.0;
+    let menu_y = (screen_height() - menu_height) / 2.0;
+    const MAX_VISIBLE: usize = 8;
+
+    draw_rectangle(menu_x, menu_y, menu_width, menu_height, t.panel_background);
+    draw_rectangle_lines(menu_x, menu_y, menu_width, menu_height, 2.0, t.panel_border);
+
+    draw_text("🎵 MUSIC", menu_x + 20.0, menu_y + 40.0, 28.0, t.cursor);
+
+    let Some(music) = &state.music else {
+        draw_text(
+            "Music unavailable",
+            menu_x + menu_width / 2.0 - 80.0,
+            menu_y + 200.0,
+            22.0,
+            GRAY,
+        );
+        draw_text(
+            "B: Back",
+            menu_x + 20.0,
+            menu_y + menu_height - 20.0,
+            16.0,
+            LIGHTGRAY,
+        );
+        return;
+    };
+
+    let source = match &state.music_source {
+        MusicSource::AllTracks => "◄ All Tracks ►".to_string(),
+        MusicSource::Playlist(name) => format!("◄ {} ►", name),
+    };
+    let dims = measure_text(&source, None, 18, 1.0);
+    draw_text(
+        &source,
+        menu_x + menu_width - 25.0 - dims.width,
+        menu_y + 38.0,
+        18.0,
+        t.text,
+    );
+
+    let status = music.status();
+    let now_playing = match &status.track {
+        Some(track) if status.paused => format!("⏸ {}", music::track_title(track)),
+        Some(track) => format!("▶ {}", music::track_title(track)),
+        None => "Not playing".to_string(),
+    };
+    draw_text(&now_playing, menu_x + 20.0, menu_y + 66.0, 16.0, LIGHTGRAY);
+
+    let tracks = state.music_tracks();
+    let row_y = menu_y + 90.0;
+    let row_height = 36.0;
+    let rows = MUSIC_SETTING_ROWS + tracks.len();
+    let scroll = (state.music_selected + 1).saturating_sub(MAX_VISIBLE);
+    for (i, row) in (scroll..rows).take(MAX_VISIBLE).enumerate() {
+        let y = row_y + i as f32 * row_height;
+        let is_selected = row == state.music_selected;
+        if is_selected {
+            draw_rectangle(
+                menu_x + 15.0,
+                y - 5.0,
+                menu_width - 30.0,
+                row_height - 5.0,
+                Color::new(t.accent.r * 0.2, t.accent.g * 0.2, t.accent.b * 0.2, 0.6),
+            );
+        }
+        let color = if is_selected { t.cursor } else { t.text };
+
+        let (label, value) = match row {
+            0 => (
+                "Volume".to_string(),
+                Some(format!("◄ {:.0}% ►", music.volume() * 100.0)),
+            ),
+            1 => (
+                "Duck while playing".to_string(),
+                Some(match music.config().duck_percent {
+                    0 => "◄ Off ►".to_string(),
+                    percent => format!("◄ -{}% ►", percent),
+                }),
+            ),
+            _ => {
+                let track = &tracks[row - MUSIC_SETTING_ROWS];
+                let playing = status.track.as_ref() == Some(track);
+                (music::track_title(track), playing.then(|| "♪".to_string()))
+            }
+        };
+        draw_text(&label, menu_x + 25.0, y + 18.0, 18.0, color);
+        if let Some(value) = value {
+            let dims = measure_text(&value, None, 16, 1.0);
+            draw_text(
+                &value,
+                menu_x + menu_width - 25.0 - dims.width,
+                y + 18.0,
+                16.0,
+                LIGHTGRAY,
+            );
+        }
+    }
+
+    if tracks.is_empty() {
+        let message = match state.music_source {
+            MusicSource::AllTracks => "Add music to kazeta-plus/music",
+            MusicSource::Playlist(_) => "Add tracks with X from All Tracks",
+        };
+        draw_text(
+            message,
+            menu_x + 25.0,
+            row_y + MUSIC_SETTING_ROWS as f32 * row_height + 18.0,
+            18.0,
+            GRAY,
+        );
+    }
+
+    draw_text(
+        "A: Play • X: Playlist • RT: Pause • LT: Stop • ◄►: Skip • LB/RB: List",
+        menu_x + 20.0,
+        menu_y + menu_height - 20.0,
+        16.0,
+        LIGHTGRAY,
+    );
+}
+
+fn render_controller_settings(state: &OverlayState) {
+    let t = theme(state);
+    let menu_width = 660.0;
+    let menu_height = 420.0;
+    let menu_x = (screen_width() - menu_width) / 2.0;
+    let menu_y = (screen_height() - menu_height) / 2.0;
+
+    draw_rectangle(menu_x, menu_y, menu_width, menu_height, t.panel_background);
+    draw_rectangle_lines(menu_x, menu_y, menu_width, menu_height, 2.0, t.panel_border);
+
+    draw_text(
+        "🎛️ CONTROLLER SETTINGS",
+        menu_x + 20.0,
+        menu_y + 35.0,
+        26.0,
+        t.cursor,
+    );
+
+    let Some((guid, calibration)) = state.selected_calibration() else {
+        draw_text(
+            "No controllers connected",
+            menu_x + menu_width / 2.0 - 100.0,
+            menu_y + 200.0,
+            22.0,
+            GRAY,
+        );
+        draw_text(
+            "B: Back",
+            menu_x + 20.0,
+            menu_y + menu_height - 20.0,
+            16.0,
+            LIGHTGRAY,
+        );
+        return;
+    };
+    let controllers = &state.controllers;
+    let controller = &controllers.controllers[controllers.settings_selected_controller];
+    draw_text(&guid, menu_x + 20.0, menu_y + 58.0, 14.0, GRAY);
+
+    // Rows
+    let row_y = menu_y + 90.0;
+    let row_height = 40.0;
+    for (i, label) in CONTROLLER_SETTINGS_ROWS.iter().enumerate() {
+        let y = row_y + i as f32 * row_height;
+        let is_selected = i == controllers.settings_selected_row;
+        if is_selected {
+            draw_rectangle(
+                menu_x + 15.0,
+                y - 5.0,
+                360.0,
+                row_height - 5.0,
+                Color::new(t.accent.r * 0.2, t.accent.g * 0.2, t.accent.b * 0.2, 0.6),
+            );
+        }
+        let color = if is_selected { t.cursor } else { t.text };
+        draw_text(label, menu_x + 25.0, y + 14.0, 18.0, color);
+
+        let value = match i {
+            0 => Some(format!("◄ {} ►", controller.name)),
+            1 => Some(format!("◄ {:.0}% ►", calibration.left_deadzone * 100.0)),
+            2 => Some(format!("◄ {:.0}% ►", calibration.right_deadzone * 100.0)),
+            3 => Some(format!("◄ {:.0}% ►", calibration.trigger_threshold * 100.0)),
+            _ => None,
+        };
+        if let Some(value) = value {
+            draw_text(&value, menu_x + 25.0, y + 31.0, 15.0, LIGHTGRAY);
+        }
+    }
+
+    // Live sticks: gray is the raw reading, the cursor color what games get
+    let [lx, ly, rx, ry] = controllers.settings_axes;
+    let sticks = [
+        ("LEFT", Stick::Left, lx, ly, calibration.left_deadzone),
+        ("RIGHT", Stick::Right, rx, ry, calibration.right_deadzone),
+    ];
+    let radius = 55.0;
+    for (i, (label, stick, x, y, deadzone)) in sticks.into_iter().enumerate() {
+        let cx = menu_x + 440.0 + i as f32 * 140.0;
+        let cy = menu_y + 150.0;
+        draw_circle_lines(cx, cy, radius, 2.0, GRAY);
+        draw_circle(
+            cx,
+            cy,
+            radius * deadzone,
+            Color::new(t.accent.r, t.accent.g, t.accent.b, 0.25),
+        );
+        draw_circle(cx + x * radius, cy - y * radius, 4.0, GRAY);
+        let (ax, ay) = calibration.apply_stick(stick, x, y);
+        draw_circle(cx + ax * radius, cy - ay * radius, 6.0, t.cursor);
+        let dims = measure_text(label, None, 14, 1.0);
+        draw_text(
+            label,
+            cx - dims.width / 2.0,
+            cy + radius + 20.0,
+            14.0,
+            LIGHTGRAY,
+        );
+    }
+
+    // Triggers, with the threshold marked
+    for (i, value) in controllers.settings_triggers.into_iter().enumerate() {
+        let x = menu_x + 400.0;
+        let y = menu_y + 250.0 + i as f32 * 30.0;
+        let width = 220.0;
+        draw_trigger(x, y, width, 14.0, value);
+        let threshold_x = x + width * calibration.trigger_threshold;
+        draw_line(threshold_x, y - 3.0, threshold_x, y + 17.0, 2.0, t.cursor);
+        let label = if i == 0 { "LT" } else { "RT" };
+        let color = if calibration.trigger_pressed(value) {
+            GREEN
+        } else {
+            LIGHTGRAY
+        };
+        draw_text(label, x + width + 10.0, y + 12.0, 14.0, color);
+    }
+
+    if let Some(wizard) = &controllers.settings_wizard {
+        let (w, h) = (460.0, 170.0);
+        let (x, y) = ((screen_width() - w) / 2.0, (screen_height() - h) / 2.0);
+        draw_rectangle(x, y, w, h, t.panel_background);
+        draw_rectangle_lines(x, y, w, h, 2.0, t.cursor);
+        draw_text(
+            "STICK DRIFT CALIBRATION",
+            x + 20.0,
+            y + 35.0,
+            22.0,
+            t.cursor,
+        );
+        match wizard {
+            DriftWizard::Prompt => {
+                draw_text(
+                    "Let go of both sticks and lay the",
+                    x + 20.0,
+                    y + 75.0,
+                    18.0,
+                    t.text,
+                );
+                draw_text(
+                    "controller down, then press A.",
+                    x + 20.0,
+                    y + 100.0,
+                    18.0,
+                    t.text,
+                );
+                draw_text(
+                    "A: Start • B: Cancel",
+                    x + 20.0,
+                    y + h - 20.0,
+                    16.0,
+                    LIGHTGRAY,
+                );
+            }
+            DriftWizard::Measuring { .. } => {
+                draw_text(
+                    "Measuring... don't touch the sticks",
+                    x + 20.0,
+                    y + 75.0,
+                    18.0,
+                    t.text,
+                );
+                let progress = wizard.progress(std::time::Instant::now());
+                draw_rectangle(x + 20.0, y + 95.0, (w - 40.0) * progress, 16.0, t.accent);
+                draw_rectangle_lines(x + 20.0, y + 95.0, w - 40.0, 16.0, 1.0, GRAY);
+                draw_text("B: Cancel", x + 20.0, y + h - 20.0, 16.0, LIGHTGRAY);
+            }
+            DriftWizard::Done(m) => {
+                let left = format!(
+                    "Left offset  {:+.3}, {:+.3}",
+                    m.left_offset[0], m.left_offset[1]
+                );
+                let right = format!(
+                    "Right offset {:+.3}, {:+.3}",
+                    m.right_offset[0], m.right_offset[1]
+                );
+                draw_text(&left, x + 20.0, y + 75.0, 18.0, t.text);
+                draw_text(&right, x + 20.0, y + 100.0, 18.0, t.text);
+                draw_text("Saved. A: Done", x + 20.0, y + h - 20.0, 16.0, LIGHTGRAY);
+            }
+        }
+        return;
+    }
+
+    draw_text(
+        "D-Pad: Navigate • ◄/►: Change • A: Select • B: Back",
+        menu_x + 20.0,
+        menu_y + menu_height - 20.0,
+        16.0,
+        LIGHTGRAY,
+    );
+}
+
+fn render_hotkey_settings(_state: &OverlayState) {
+    let menu_width = 620.0;
+    let menu_height = 400.0;
+    let menu_x = (screen_width() - menu_width) / 2.0;
+    let menu_y = (screen_height() - menu_height) / 2.0;
+
+    draw_rectangle(
+        menu_x,
+        menu_y,
+        menu_width,
+        menu_height,
+        Color::new(0.1, 0.1, 0.15, 0.98),
+    );
+    draw_rectangle_lines(menu_x, menu_y, menu_width, menu_height, 2.0, YELLOW);
+
+    // Title
+    draw_text(
+        "⌨️ HOTKEY SETTINGS",
+        menu_x + 20.0,
+        menu_y + 40.0,
+        28.0,
+        YELLOW,
+    );
+
+    // Coming soon message
+    draw_text(
+        "Configure custom hotkey bindings",
+        menu_x + 20.0,
+        menu_y + 80.0,
+        18.0,
+        LIGHTGRAY,
+    );
+
+    let info_y = menu_y + 140.0;
+    let line_height = 30.0;
+
+    // Show current default bindings
+    draw_text("Current Hotkeys:", menu_x + 20.0, info_y, 20.0, WHITE);
+
+    draw_text(
+        "• Toggle Overlay:",
+        menu_x + 30.0,
+        info_y + line_height,
+        16.0,
+        LIGHTGRAY,
+    );
+    draw_text(
+        "F12, Ctrl+O, Guide Button",
+        menu_x + 200.0,
+        info_y + line_height,
+        16.0,
+        GREEN,
+    );
+
+    draw_text(
+        "• Performance HUD:",
+        menu_x + 30.0,
+        info_y + line_height * 2.0,
+        16.0,
+        LIGHTGRAY,
+    );
+    draw_text(
+        "F3",
+        menu_x + 200.0,
+        info_y + line_height * 2.0,
+        16.0,
+        GREEN,
+    );
+
+    draw_text(
+        "• Quick Save:",
+        menu_x + 30.0,
+        info_y + line_height * 3.0,
+        16.0,
+        LIGHTGRAY,
+    );
+    draw_text(
+        "F5",
+        menu_x + 200.0,
+        info_y + line_height * 3.0,
+        16.0,
+        GREEN,
+    );
+
+    draw_text(
+        "• Quick Load:",
+        menu_x + 30.0,
+        info_y + line_height * 4.0,
+        16.0,
+        LIGHTGRAY,
+    );
+    draw_text(
+        "F9",
+        menu_x + 200.0,
+        info_y + line_height * 4.0,
+        16.0,
+        GREEN,
+    );
+
+    draw_text(
+        "• Speedrun Timer:",
+        menu_x + 30.0,
+        info_y + line_height * 5.0,
+        16.0,
+        LIGHTGRAY,
+    );
+    draw_text(
+        "F6 Start/Pause, F7 Split, F8 Reset",
+        menu_x + 200.0,
+        info_y + line_height * 5.0,
+        16.0,
+        GREEN,
+    );
+
+    // Coming soon notice
+    draw_text(
+        "🚧 Custom hotkey configuration coming soon! 🚧",
+        menu_x + 80.0,
+        menu_y + menu_height - 60.0,
+        16.0,
+        ORANGE,
+    );
+
+    // Controls hint
+    draw_text(
+        "B: Back to Controllers",
+        menu_x + 20.0,
+        menu_y + menu_height - 20.0,
+        16.0,
+        LIGHTGRAY,
+    );
+}
+
+/// Radial palette: the actions around a ring, the highlighted one named in
+/// the middle
+fn render_quick_actions(state: &OverlayState) {
+    let t = theme(state);
+    let actions = &state.menu_config.config().quick_actions;
+    let cx = screen_width() / 2.0;
+    let cy = screen_height() / 2.0;
+    let radius = 170.0;
+    let slot_radius = 48.0;
+
+    draw_circle(cx, cy, radius + slot_radius + 20.0, t.panel_background);
+    draw_circle_lines(cx, cy, radius + slot_radius + 20.0, 2.0, t.panel_border);
+
+    for (i, action) in actions.iter().enumerate() {
+        let (dx, dy) = quick_actions::slot_offset(i, actions.len(), radius);
+        let (x, y) = (cx + dx, cy + dy);
+        let is_selected = i == state.quick_actions_selected;
+
+        if is_selected {
+            draw_circle(x, y, slot_radius, Color::new(0.3, 0.3, 0.4, 0.8));
+            draw_circle_lines(x, y, slot_radius, 3.0, t.cursor);
+        } else {
+            draw_circle_lines(x, y, slot_radius, 2.0, t.panel_border);
+        }
+
+        let icon_dims = measure_text(action.icon(), None, 36, 1.0);
+        draw_text(
+            action.icon(),
+            x - icon_dims.width / 2.0,
+            y + 12.0,
+            36.0,
+            t.text,
+        );
+    }
+
+    if let Some(action) = actions.get(state.quick_actions_selected) {
+        let name = action.display_name();
+        let dims = measure_text(name, None, 28, 1.0);
+        draw_text(name, cx - dims.width / 2.0, cy + 10.0, 28.0, t.cursor);
+    }
+
+    let hint = "Stick/D-Pad: Choose • A: Run • B: Close";
+    let dims = measure_text(hint, None, 16, 1.0);
+    draw_text(
+        hint,
+        cx - dims.width / 2.0,
+        cy + radius + slot_radius + 50.0,
+        16.0,
+        LIGHTGRAY,
+    );
+}
+
+fn render_quick_actions_settings(state: &OverlayState) {
+    let t = theme(state);
+    let menu_width = 700.0;
+    let menu_height = 500.0;
+    let menu_x = (screen_width() - menu_width) / 2.0;
+    let menu_y = (screen_height() - menu_height) / 2.0;
+
+    draw_rectangle(menu_x, menu_y, menu_width, menu_height, t.panel_background);
+    draw_rectangle_lines(menu_x, menu_y, menu_width, menu_height, 2.0, t.panel_border);
+
+    draw_text(
+        "QUICK ACTIONS",
+        menu_x + 20.0,
+        menu_y + 40.0,
+        28.0,
+        t.cursor,
+    );
+    draw_text(
+        "Hold Guide to open the palette • Slots go clockwise from the top",
+        menu_x + 20.0,
+        menu_y + 70.0,
+        16.0,
+        LIGHTGRAY,
+    );
+
+    let palette = &state.menu_config.config().quick_actions;
+    let item_start_y = menu_y + 95.0;
+    let item_height = 38.0;
+
+    for (i, action) in QuickAction::all().iter().enumerate() {
+        let y = item_start_y + i as f32 * item_height;
+        let is_selected = i == state.quick_actions_settings_selected;
+        let slot = palette.iter().position(|a| a == action);
+
+        if is_selected {
+            draw_rectangle(
+                menu_x + 15.0,
+                y,
+                menu_width - 30.0,
+                item_height - 4.0,
+                Color::new(0.3, 0.3, 0.4, 0.6),
+            );
+            draw_text("►", menu_x + 25.0, y + 25.0, 22.0, t.cursor);
+        }
+
+        let (mark, mark_color) = if slot.is_some() {
+            ("✓", t.success)
+        } else {
+            ("✗", t.error)
+        };
+        draw_text(mark, menu_x + 60.0, y + 25.0, 20.0, mark_color);
+        draw_text(action.icon(), menu_x + 95.0, y + 25.0, 22.0, t.text);
+
+        let name_color = if slot.is_some() {
+            t.text
+        } else {
+            t.text_disabled
+        };
+        draw_text(
+            action.display_name(),
+            menu_x + 135.0,
+            y + 25.0,
+            22.0,
+            name_color,
+        );
+
+        if let Some(slot) = slot {
+            draw_text(
+                &format!("#{}", slot + 1),
+                menu_x + menu_width - 80.0,
+                y + 25.0,
+                18.0,
+                LIGHTGRAY,
+            );
+        }
+    }
+
+    draw_text(
+        "A/Left/Right: Add or remove • LB/RB: Move • B: Back",
+        menu_x + 20.0,
+        menu_y + menu_height - 20.0,
+        16.0,
+        LIGHTGRAY,
+    );
+}
+
+/// Cut `text` to `max` characters, marking the cut with "..."
+fn truncate_text(text: &str, max: usize) -> String {
+    if text.chars().count() > max {
+        format!(
+            "{}...",
+            text.chars().take(max.saturating_sub(3)).collect::<String>()
+        )
+    } else {
+        text.to_string()
+    }
+}
+
+fn render_ipc_rules(state: &OverlayState) {
+    let t = theme(state);
+    let menu_width = 700.0;
+    let menu_height = 500.0;
+    let menu_x = (screen_width() - menu_width) / 2.0;
+    let menu_y = (screen_height() - menu_height) / 2.0;
+
+    draw_rectangle(menu_x, menu_y, menu_width, menu_height, t.panel_background);
+    draw_rectangle_lines(menu_x, menu_y, menu_width, menu_height, 2.0, t.panel_border);
+
+    draw_text(
+        "MESSAGE RULES",
+        menu_x + 20.0,
+        menu_y + 40.0,
+        28.0,
+        t.cursor,
+    );
+
+    let Some(rules) = &state.ipc_rules else {
+        draw_text(
+            "Message rules are unavailable",
+            menu_x + 20.0,
+            menu_y + 90.0,
+            20.0,
+            t.text_disabled,
+        );
+        draw_text(
+            "B: Back",
+            menu_x + 20.0,
+            menu_y + menu_height - 20.0,
+            16.0,
+            LIGHTGRAY,
+        );
+        return;
+    };
+
+    draw_text(
+        &rules.path().display().to_string(),
+        menu_x + 20.0,
+        menu_y + 65.0,
+        14.0,
+        LIGHTGRAY,
+    );
+
+    let mut y = menu_y + 95.0;
+    if let Some(error) = rules.error() {
+        draw_text(&truncate_text(error, 80), menu_x + 20.0, y, 16.0, t.error);
+        y += 24.0;
+    }
+
+    // Loaded rules, in the order they're checked
+    let loaded: Vec<_> = rules.rules().collect();
+    if loaded.is_empty() {
+        draw_text("No rules loaded", menu_x + 30.0, y, 18.0, t.text_disabled);
+        y += 24.0;
+    }
+    for (rule, hits) in loaded.iter().take(6) {
+        draw_text(
+            &format!("[{}]", rule.action.label()),
+            menu_x + 30.0,
+            y,
+            18.0,
+            t.cursor,
+        );
+        draw_text(
+            &truncate_text(&rule.label(), 50),
+            menu_x + 110.0,
+            y,
+            18.0,
+            t.text,
+        );
+        draw_text(
+            &format!("{} hits", hits),
+            menu_x + menu_width - 110.0,
+            y,
+            16.0,
+            LIGHTGRAY,
+        );
+        y += 24.0;
+    }
+
+    // What the rules matched recently
+    y += 10.0;
+    draw_text("RECENT MATCHES", menu_x + 20.0, y, 18.0, t.text_secondary);
+    y += 24.0;
+    for matched in rules.recent().take(5) {
+        let source = matched.source.as_deref().unwrap_or("-");
+        let line = format!(
+            "{}s ago  {} from {} → {} ({})",
+            matched.at.elapsed().as_secs(),
+            matched.kind,
+            source,
+            matched.action.label(),
+            matched.rule
+        );
+        draw_text(&truncate_text(&line, 80), menu_x + 30.0, y, 16.0, t.text);
+        y += 22.0;
+    }
+
+    // Notifications kept out of sight by `quiet` rules still land here
+    y += 10.0;
+    draw_text(
+        "NOTIFICATION HISTORY",
+        menu_x + 20.0,
+        y,
+        18.0,
+        t.text_secondary,
+    );
+    y += 24.0;
+    for toast in state.toasts.history().take(4) {
+        let line = format!(
+            "{}s ago  {}",
+            toast.created_at.elapsed().as_secs(),
+            toast.message
+        );
+        draw_text(&truncate_text(&line, 80), menu_x + 30.0, y, 16.0, t.text);
+        y += 22.0;
+    }
+
+    let controls = if state.debug_console.is_some() {
+        "A: Reload rules • X: Debug console • B: Back"
+    } else {
+        "A: Reload rules • B: Back"
+    };
+    draw_text(
+        controls,
+        menu_x + 20.0,
+        menu_y + menu_height - 20.0,
+        16.0,
+        LIGHTGRAY,
+    );
+}
+
+fn render_debug_console(state: &OverlayState) {
+    let t = theme(state);
+    let menu_width = 900.0;
+    let menu_height = 560.0;
+    let menu_x = (screen_width() - menu_width) / 2.0;
+    let menu_y = (screen_height() - menu_height) / 2.0;
+
+    draw_rectangle(menu_x, menu_y, menu_width, menu_height, t.panel_background);
+    draw_rectangle_lines(menu_x, menu_y, menu_width, menu_height, 2.0, t.panel_border);
+
+    draw_text(
+        "DEBUG CONSOLE",
+        menu_x + 20.0,
+        menu_y + 40.0,
+        28.0,
+        t.cursor,
+    );
+
+    let Some(console) = &state.debug_console else {
+        return;
+    };
+
+    // State dump
+    let game = state.current_cart_id().unwrap_or("-");
+    let dump = [
+        format!("screen: {:?}", state.current_screen),
+        format!("visible: {}", state.visible),
+        format!("game: {}", game),
+        format!(
+            "achievements: {} ({} unlocked)",
+            state.achievements.achievements.len(),
+            state.achievements.unlocks.len()
+        ),
+        format!(
+            "ipc: {} received, {} dropped, {} parse errors",
+            state.ipc_stats.received, state.ipc_stats.dropped, state.ipc_stats.parse_errors
+        ),
+    ];
+    let mut y = menu_y + 75.0;
+    for line in &dump {
+        draw_text(&truncate_text(line, 30), menu_x + 20.0, y, 16.0, t.text);
+        y += 20.0;
+    }
+
+    // Test messages to inject
+    y += 15.0;
+    draw_text("INJECT", menu_x + 20.0, y, 18.0, t.text_secondary);
+    y += 24.0;
+    for (index, (label, _)) in TEST_MESSAGES.iter().enumerate() {
+        let color = if index == console.selected {
+            t.cursor
+        } else {
+            t.text
+        };
+        if index == console.selected {
+            draw_text(">", menu_x + 20.0, y, 18.0, t.cursor);
+        }
+        draw_text(label, menu_x + 40.0, y, 18.0, color);
+        y += 24.0;
+    }
+
+    // Message log, newest first
+    let log_x = menu_x + 300.0;
+    draw_line(
+        log_x - 15.0,
+        menu_y + 60.0,
+        log_x - 15.0,
+        menu_y + menu_height - 40.0,
+        1.0,
+        t.panel_border,
+    );
+    draw_text(
+        &format!("IPC LOG ({}/{})", console.len(), LOG_CAPACITY),
+        log_x,
+        menu_y + 75.0,
+        18.0,
+        t.text_secondary,
+    );
+    let mut y = menu_y + 100.0;
+    if console.len() == 0 {
+        draw_text("No messages received yet", log_x, y, 16.0, t.text_disabled);
+    }
+    for message in console
+        .log()
+        .skip(console.scroll)
+        .take(DEBUG_LOG_VISIBLE_ROWS)
+    {
+        let at = console.timestamp(message);
+        let source = message.source.as_deref().unwrap_or("-");
+        let header = format!(
+            "{:>4}.{:03}s  {} from {} → {}",
+            at.as_secs(),
+            at.subsec_millis(),
+            message.kind,
+            source,
+            message.action.label()
+        );
+        let color = if message.action == RuleAction::Allow {
+            t.text
+        } else {
+            t.text_disabled
+        };
+        draw_text(&truncate_text(&header, 70), log_x, y, 16.0, color);
+        draw_text(
+            &truncate_text(&message.summary, 80),
+            log_x + 20.0,
+            y + 18.0,
+            14.0,
+            LIGHTGRAY,
+        );
+        y += 42.0;
+    }
+
+    draw_text(
+        "A: Inject • LB/RB: Scroll log • X: Clear log • B: Back",
+        menu_x + 20.0,
+        menu_y + menu_height - 20.0,
+        16.0,
+        LIGHTGRAY,
+    );
+}
+
+fn render_menu_customization(state: &OverlayState) {
+    let t = theme(state);
+    let menu_width = 700.0;
+    let menu_height = 500.0;
+    let menu_x = (screen_width() - menu_width) / 2.0;
+    let menu_y = (screen_height() - menu_height) / 2.0;
+
+    draw_rectangle(menu_x, menu_y, menu_width, menu_height, t.panel_background);
+    draw_rectangle_lines(menu_x, menu_y, menu_width, menu_height, 2.0, t.panel_border);
+
+    // Title
+    draw_text(
+        "⚙️ MENU CUSTOMIZATION",
+        menu_x + 20.0,
+        menu_y + 40.0,
+        28.0,
+        t.cursor,
+    );
+
+    // Instructions
+    draw_text(
+        "Left/Right: Toggle visibility • LB/RB: Move up/down",
+        menu_x + 20.0,
+        menu_y + 70.0,
+        16.0,
+        LIGHTGRAY,
+    );
+
+    // Menu items list
+    let all_items = state.menu_config.config().get_all_items();
+    let item_start_y = menu_y + 110.0;
+    let item_height = 45.0;
+    let max_visible = 8;
+
+    // Use scroll offset from state
+    let scroll_offset = state.menu_customization_scroll_offset;
+
+    for i in 0..max_visible.min(all_items.len() - scroll_offset) {
+        let item_idx = scroll_offset + i;
+        if item_idx >= all_items.len() {
+            break;
+        }
+        let item_id = all_items[item_idx];
+        let y = item_start_y + (i as f32 * item_height);
+        let is_selected = item_idx == state.menu_customization_selected;
+
+        // Get item config
+        let item_config = state
+            .menu_config
+            .config()
+            .items
+            .iter()
+            .find(|item| item.id == item_id)
+            .unwrap();
+
+        // Selection background
+        if is_selected {
+            draw_rectangle(
+                menu_x + 15.0,
+                y,
+                menu_width - 30.0,
+                item_height - 5.0,
+                Color::new(0.3, 0.3, 0.4, 0.6),
+            );
+            draw_text("►", menu_x + 25.0, y + 28.0, 24.0, t.cursor);
+        }
+
+        // Visibility indicator
+        let visibility_icon = if item_config.visible { "✓" } else { "✗" };
+        let visibility_color = if item_config.visible {
+            t.success
+        } else {
+            t.error
+        };
+        draw_text(
+            visibility_icon,
+            menu_x + 60.0,
+            y + 28.0,
+            20.0,
+            visibility_color,
+        );
+
+        // Item name
+        let name_color = if item_config.visible {
+            t.text
+        } else {
+            t.text_disabled
+        };
+        draw_text(
+            item_id.display_name(),
+            menu_x + 100.0,
+            y + 28.0,
+            22.0,
+            name_color,
+        );
+
+        // Order indicator
+        let order_text = format!("#{:02}", item_config.order + 1);
+        draw_text(
+            &order_text,
+            menu_x + menu_width - 100.0,
+            y + 28.0,
+            18.0,
+            LIGHTGRAY,
+        );
+
+        // Move indicators
+        if is_selected {
+            if item_idx > 0 {
+                draw_text("▲", menu_x + menu_width - 60.0, y + 28.0, 18.0, LIGHTGRAY);
+            }
+            if item_idx < all_items.len() - 1 {
+                draw_text("▼", menu_x + menu_width - 40.0, y + 28.0, 18.0, LIGHTGRAY);
+            }
+        }
+    }
+
+    // Scroll indicators
+    if scroll_offset > 0 {
+        draw_text(
+            "▲",
+            menu_x + menu_width - 25.0,
+            item_start_y + 10.0,
+            16.0,
+            LIGHTGRAY,
+        );
+    }
+    if scroll_offset + max_visible < all_items.len() {
+        draw_text(
+            "▼",
+            menu_x + menu_width - 25.0,
+            item_start_y + (max_visible as f32 * item_height) - 15.0,
+            16.0,
+            LIGHTGRAY,
+        );
+    }
+
+    // Controls hint
+    draw_text(
+        "D-Pad: Navigate • Left/Right: Toggle • LB/RB: Reorder • B: Back",
+        menu_x + 20.0,
+        menu_y + menu_height - 20.0,
+        14.0,
+        LIGHTGRAY,
+    );
+}
+
+fn render_theme_selection(state: &OverlayState) {
+    let t = theme(state);
+    let menu_width = 700.0;
+    let menu_height = 500.0;
+    let menu_x = (screen_width() - menu_width) / 2.0;
+    let menu_y = (screen_height() - menu_height) / 2.0;
+
+    draw_rectangle(menu_x, menu_y, menu_width, menu_height, t.panel_background);
+    draw_rectangle_lines(menu_x, menu_y, menu_width, menu_height, 2.0, t.panel_border);
+
+    // Title
+    draw_text(
+        "🎨 THEME SELECTION",
+        menu_x + 20.0,
+        menu_y + 40.0,
+        28.0,
+        t.cursor,
+    );
+
+    // Instructions
+    draw_text(
+        "Select a theme and press A to apply",
+        menu_x + 20.0,
+        menu_y + 70.0,
+        16.0,
+        t.text_secondary,
+    );
+
+    // Theme list
+    let themes = state.theme_config.available_themes();
+    let theme_start_y = menu_y + 110.0;
+    let theme_height = 60.0;
+    let max_visible = 6;
+
+    // Use scroll offset from state
+    let scroll_offset = state.theme_selection_scroll_offset;
+
+    for i in 0..max_visible.min(themes.len() - scroll_offset) {
+        let theme_idx = scroll_offset + i;
+        if theme_idx >= themes.len() {
+            break;
+        }
+        let theme_name = &themes[theme_idx];
+        let y = theme_start_y + (i as f32 * theme_height);
+        let is_selected = theme_idx == state.theme_selected;
+        let is_current = theme_name == state.theme_config.theme_name();
+        let is_custom = state.theme_config.is_user_theme(theme_name);
+
+        // Get theme preview
+        let preview_theme = state
+            .theme_config
+            .find(theme_name)
+            .unwrap_or_else(crate::themes::Theme::dark);
+
+        // Selection background
+        if is_selected {
+            draw_rectangle(
+                menu_x + 15.0,
+                y,
+                menu_width - 30.0,
+                theme_height - 5.0,
+                Color::new(t.accent.r * 0.2, t.accent.g * 0.2, t.accent.b * 0.2, 0.6),
+            );
+            draw_text("►", menu_x + 25.0, y + 35.0, 24.0, t.cursor);
+        }
+
+        // Theme name
+        let name_color = if is_selected { t.cursor } else { t.text };
+        draw_text(theme_name, menu_x + 60.0, y + 25.0, 24.0, name_color);
+
+        // Current theme and custom theme indicators
+        let tags: Vec<&str> = [(is_current, "(Current)"), (is_custom, "(Custom)")]
+            .iter()
+            .filter(|(shown, _)| *shown)
+            .map(|(_, tag)| *tag)
+            .collect();
+        if !tags.is_empty() {
+            draw_text(&tags.join(" "), menu_x + 60.0, y + 45.0, 16.0, t.accent);
+        }
+
+        // Color preview swatches
+        let swatch_size = 20.0;
+        let swatch_y = y + 20.0;
+        let swatch_start_x = menu_x + menu_width - 200.0;
+        let swatch_spacing = 25.0;
+
+        // Background
+        draw_rectangle(
+            swatch_start_x,
+            swatch_y,
+            swatch_size,
+            swatch_size,
+            preview_theme.panel_background,
+        );
+        draw_rectangle_lines(
+            swatch_start_x,
+            swatch_y,
+            swatch_size,
+            swatch_size,
+            1.0,
+            preview_theme.panel_border,
+        );
+
+        // Text
+        draw_rectangle(
+            swatch_start_x + swatch_spacing,
+            swatch_y,
+            swatch_size,
+            swatch_size,
+            preview_theme.text,
+        );
+
+        // Cursor/Accent
+        draw_rectangle(
+            swatch_start_x + swatch_spacing * 2.0,
+            swatch_y,
+            swatch_size,
+            swatch_size,
+            preview_theme.cursor,
+        );
+
+        // Accent
+        draw_rectangle(
+            swatch_start_x + swatch_spacing * 3.0,
+            swatch_y,
+            swatch_size,
+            swatch_size,
+            preview_theme.accent,
+        );
+    }
+
+    // Scroll indicators
+    if scroll_offset > 0 {
+        draw_text(
+            "▲",
+            menu_x + menu_width - 25.0,
+            theme_start_y + 10.0,
+            16.0,
+            t.text_secondary,
+        );
+    }
+    if scroll_offset + max_visible < themes.len() {
+        draw_text(
+            "▼",
+            menu_x + menu_width - 25.0,
+            theme_start_y + (max_visible as f32 * theme_height) - 15.0,
+            16.0,
+            t.text_secondary,
+        );
+    }
+
+    // Controls hint
+    draw_text(
+        "D-Pad: Navigate • A: Preview • X: Edit • LB: Export • RB: Import • B: Back",
+        menu_x + 20.0,
+        menu_y + menu_height - 20.0,
+        14.0,
+        t.text_secondary,
+    );
+}
+
+/// The theme on the preview screen, with the options and backdrop being tried
+fn preview_theme(state: &OverlayState) -> crate::themes::Theme {
+    let themes = state.theme_config.all_themes();
+    let name = themes
+        .get(state.theme_selected)
+        .map_or("Dark", |theme| theme.name.as_str());
+    let t = state
+        .theme_config
+        .variant(
+            name,
+            state.theme_preview_color_vision,
+            state.theme_preview_high_contrast,
+        )
+        .unwrap_or_else(crate::themes::Theme::dark);
+    if state.theme_preview_high_contrast {
+        t
+    } else {
+        t.with_backdrop(&state.theme_preview_backdrop)
+    }
+}
+
+/// Dimming drawn over the game, following the preview while it's open and
+/// darker when blur was asked for but the compositor isn't doing it
+fn backdrop_color(state: &OverlayState) -> Color {
+    let (mut color, backdrop) = match (&state.current_screen, &state.theme_editor) {
+        (OverlayScreen::ThemePreview, _) => (
+            preview_theme(state).background_overlay,
+            state.theme_preview_backdrop,
+        ),
+        (OverlayScreen::ThemeEditor, Some(editor)) => (
+            editor.theme.background_overlay,
+            state.theme_config.backdrop(),
+        ),
+        _ => (
+            theme(state).background_overlay,
+            state.theme_config.backdrop(),
+        ),
+    };
+    color.a = Backdrop {
+        opacity: color.a,
+        ..backdrop
+    }
+    .effective_opacity(state.backdrop_blur);
+    color
+}
+
+fn render_theme_preview(state: &OverlayState) {
+    let t = preview_theme(state);
+    let name = t.name.clone();
+    // Draw the samples at the previewed font size
+    FONT_SCALE.set(t.font_scale);
+
+    let menu_width = 700.0;
+    let menu_height = 520.0;
+    let menu_x = (screen_width() - menu_width) / 2.0;
+    let menu_y = (screen_height() - menu_height) / 2.0;
+
+    draw_rectangle(menu_x, menu_y, menu_width, menu_height, t.panel_background);
+    draw_rectangle_lines(menu_x, menu_y, menu_width, menu_height, 2.0, t.panel_border);
+    draw_text(
+        &format!("PREVIEW: {}", name),
+        menu_x + 20.0,
+        menu_y + 40.0,
+        28.0,
+        t.cursor,
+    );
+
+    // Options
+    let contrast = if state.theme_preview_high_contrast {
+        "On"
+    } else {
+        "Off"
+    };
+    draw_text(
+        &format!("High contrast: {}", contrast),
+        menu_x + menu_width - 200.0,
+        menu_y + 40.0,
+        18.0,
+        t.text,
+    );
+    let backdrop = &state.theme_preview_backdrop;
+    let blur = match (backdrop.blur, state.backdrop_blur) {
+        (false, _) => "Off".to_string(),
+        (true, true) => "On".to_string(),
+        (true, false) => "On (dimmed, no compositor blur)".to_string(),
+    };
+    let rows = [
+        format!("Colors: {}", state.theme_preview_color_vision.label()),
+        format!("Dimming: {:.0}%", backdrop.opacity * 100.0),
+        format!("Panels: {:.0}%", backdrop.panel_opacity * 100.0),
+        format!("Blur: {}", blur),
+    ];
+    for (i, row) in rows.iter().enumerate() {
+        let x = menu_x + 20.0 + (i % 2) as f32 * 330.0;
+        let y = menu_y + 72.0 + (i / 2) as f32 * 24.0;
+        let selected = i == state.theme_preview_row;
+        let label = if selected {
+            format!("◄ {} ►", row)
+        } else {
+            row.clone()
+        };
+        draw_text(&label, x, y, 18.0, if selected { t.cursor } else { t.text });
+    }
+
+    // Sample achievement row
+    let row_y = menu_y + 120.0;
+    draw_rectangle(
+        menu_x + 15.0,
+        row_y,
+        menu_width - 30.0,
+        60.0,
+        Color::new(t.accent.r * 0.2, t.accent.g * 0.2, t.accent.b * 0.2, 0.6),
+    );
+    draw_rectangle(menu_x + 25.0, row_y + 8.0, 44.0, 44.0, t.success);
+    draw_text("First Steps", menu_x + 80.0, row_y + 24.0, 20.0, t.text);
+    draw_text(
+        "Clear the first stage",
+        menu_x + 80.0,
+        row_y + 46.0,
+        16.0,
+        t.text_secondary,
+    );
+    draw_text(
+        "10 pts",
+        menu_x + menu_width - 150.0,
+        row_y + 24.0,
+        16.0,
+        t.accent,
+    );
+    draw_text(
+        "3.2%",
+        menu_x + menu_width - 70.0,
+        row_y + 24.0,
+        16.0,
+        t.rarity_color(3.2),
+    );
+    draw_text(
+        "Locked",
+        menu_x + menu_width - 150.0,
+        row_y + 46.0,
+        16.0,
+        t.text_disabled,
+    );
+
+    // Rarity tiers
+    let mut legend_x = menu_x + 20.0;
+    let legend_y = row_y + 90.0;
+    for (i, &(_, label)) in RARITY_BUCKETS.iter().enumerate() {
+        draw_rectangle(legend_x, legend_y - 12.0, 14.0, 14.0, t.rarity[i]);
+        draw_text(label, legend_x + 20.0, legend_y, 16.0, t.rarity[i]);
+        legend_x += measure_text(label, None, 16, 1.0).width + 40.0;
+    }
+
+    // Status colors
+    let status_y = legend_y + 30.0;
+    let statuses = [
+        ("Success", t.success),
+        ("Warning", t.warning),
+        ("Error", t.error),
+        ("Info", t.info),
+    ];
+    for (i, (label, color)) in statuses.iter().enumerate() {
+        draw_text(
+            label,
+            menu_x + 20.0 + i as f32 * 120.0,
+            status_y,
+            18.0,
+            *color,
+        );
+    }
+
+    // Sample toasts
+    let toasts = [
+        ("Achievement unlocked!", ToastStyle::Success),
+        ("Connected to RetroAchievements", ToastStyle::Info),
+        ("Battery low", ToastStyle::Warning),
+        ("Save failed", ToastStyle::Error),
+    ];
+    let toast_width = (menu_width - 50.0) / 2.0;
+    for (i, (message, style)) in toasts.iter().enumerate() {
+        let x = menu_x + 20.0 + (i % 2) as f32 * (toast_width + 10.0);
+        let y = status_y + 20.0 + (i / 2) as f32 * 70.0;
+        draw_toast(Rect::new(x, y, toast_width, 60.0), message, *style, 1.0, &t);
+    }
+
+    draw_text(
+        "Up/Down: Option • Left/Right: Change • X: High Contrast • A: Apply • B: Back",
+        menu_x + 20.0,
+        menu_y + menu_height - 20.0,
+        14.0,
+        t.text_secondary,
+    );
+    FONT_SCALE.set(theme(state).font_scale);
+}
+
+/// Theme editor, drawn in the theme being edited
+fn render_theme_editor(state: &OverlayState) {
+    use crate::theme_editor::{Channel, EDITOR_VISIBLE_ROWS};
+    use crate::themes::{color_to_hex, hsv_to_color, EDITABLE_COLORS};
+
+    let Some(editor) = &state.theme_editor else {
+        return;
+    };
+    let t = editor.preview();
+
+    let menu_width = 760.0;
+    let menu_height = 560.0;
+    let menu_x = (screen_width() - menu_width) / 2.0;
+    let menu_y = (screen_height() - menu_height) / 2.0;
+
+    draw_panel(menu_x, menu_y, menu_width, menu_height, &t);
+    draw_text(
+        &format!("EDIT THEME: {}", editor.theme.name),
+        menu_x + 20.0,
+        menu_y + 40.0,
+        28.0,
+        t.cursor,
+    );
+
+    if let Some(keyboard) = &editor.keyboard {
+        draw_text(
+            "Save as",
+            menu_x + 20.0,
+            menu_y + 80.0,
+            18.0,
+            t.text_secondary,
+        );
+        let field_x = menu_x + 20.0;
+        let field_y = menu_y + 92.0;
+        let field_width = menu_width - 40.0;
+        draw_rectangle(field_x, field_y, field_width, 34.0, t.background_overlay);
+        draw_rectangle_lines(field_x, field_y, field_width, 34.0, 2.0, t.cursor);
+        draw_text(
+            &format!("{}_", editor.name),
+            field_x + 10.0,
+            field_y + 24.0,
+            20.0,
+            t.text,
+        );
+        if editor
+            .replacing
+            .as_deref()
+            .is_some_and(|old| old == editor.name.trim())
+        {
+            draw_text(
+                "Replaces the saved theme",
+                field_x,
+                field_y + 56.0,
+                16.0,
+                t.warning,
+            );
+        }
+        draw_keyboard(keyboard, menu_x + menu_width / 2.0, field_y + 80.0, &t);
+        draw_text(
+            "A: Type • X: Delete • LB: Shift • DONE: Save • B: Back to colors",
+            menu_x + 20.0,
+            menu_y + menu_height - 20.0,
+            14.0,
+            t.text_secondary,
+        );
+        return;
+    }
+
+    // Color list
+    let list_x = menu_x + 20.0;
+    let list_y = menu_y + 60.0;
+    let row_height = 40.0;
+    let list_width = 330.0;
+    for (i, (key, label)) in EDITABLE_COLORS
+        .iter()
+        .enumerate()
+        .skip(editor.scroll_offset)
+        .take(EDITOR_VISIBLE_ROWS)
+    {
+        let y = list_y + (i - editor.scroll_offset) as f32 * row_height;
+        let selected = i == editor.selected;
+        if selected {
+            draw_rectangle(
+                list_x - 5.0,
+                y,
+                list_width,
+                row_height - 4.0,
+                Color::new(t.accent.r * 0.2, t.accent.g * 0.2, t.accent.b * 0.2, 0.6),
+            );
+        }
+        let color = editor.theme.color(key).unwrap_or(t.text);
+        draw_rectangle(list_x, y + 6.0, 24.0, 24.0, color);
+        draw_rectangle_lines(list_x, y + 6.0, 24.0, 24.0, 1.0, t.panel_border);
+        draw_text(
+            label,
+            list_x + 34.0,
+            y + 24.0,
+            18.0,
+            if selected { t.cursor } else { t.text },
+        );
+        draw_text(
+            &color_to_hex(color),
+            list_x + 210.0,
+            y + 24.0,
+            14.0,
+            t.text_secondary,
+        );
+    }
+    if editor.scroll_offset > 0 {
+        draw_text(
+            "▲",
+            list_x + list_width - 20.0,
+            list_y + 12.0,
+            16.0,
+            t.text_secondary,
+        );
+    }
+    if editor.scroll_offset + EDITOR_VISIBLE_ROWS < EDITABLE_COLORS.len() {
+        draw_text(
+            "▼",
+            list_x + list_width - 20.0,
+            list_y + EDITOR_VISIBLE_ROWS as f32 * row_height - 8.0,
+            16.0,
+            t.text_secondary,
+        );
+    }
+
+    // Channel sliders for the selected color, each a gradient over its range
+    let picker_x = menu_x + 380.0;
+    let picker_width = menu_width - 400.0;
+    let [hue, saturation, value, alpha] = editor.hsva;
+    draw_text(
+        EDITABLE_COLORS[editor.selected].1,
+        picker_x,
+        list_y + 20.0,
+        22.0,
+        t.text,
+    );
+    let steps = 36;
+    for (i, channel) in Channel::ALL.iter().enumerate() {
+        let y = list_y + 44.0 + i as f32 * 56.0;
+        let selected = *channel == editor.selected_channel();
+        let amount = editor.hsva[i];
+        let readout = match channel {
+            Channel::Hue => format!("{}: {:.0}°", channel.label(), amount),
+            _ => format!("{}: {:.0}%", channel.label(), amount * 100.0),
+        };
+        let readout = if selected {
+            format!("◄ {} ►", readout)
+        } else {
+            readout
+        };
+        draw_text(
+            &readout,
+            picker_x,
+            y + 14.0,
+            16.0,
+            if selected { t.cursor } else { t.text_secondary },
+        );
+
+        let bar_y = y + 22.0;
+        let step_width = picker_width / steps as f32;
+        for step in 0..steps {
+            let f = step as f32 / (steps - 1) as f32;
+            let color = match channel {
+                Channel::Hue => hsv_to_color(f * 360.0, 1.0, 1.0, 1.0),
+                Channel::Saturation => hsv_to_color(hue, f, value, 1.0),
+                Channel::Value => hsv_to_color(hue, saturation, f, 1.0),
+                Channel::Alpha => hsv_to_color(hue, saturation, value, f),
+            };
+            draw_rectangle(
+                picker_x + step as f32 * step_width,
+                bar_y,
+                step_width + 0.5,
+                14.0,
+                color,
+            );
+        }
+        draw_rectangle_lines(
+            picker_x,
+            bar_y,
+            picker_width,
+            14.0,
+            if selected { 2.0 } else { 1.0 },
+            if selected { t.cursor } else { t.panel_border },
+        );
+        let fraction = if *channel == Channel::Hue {
+            amount / 360.0
+        } else {
+            amount
+        };
+        let marker_x = picker_x + fraction * picker_width;
+        draw_rectangle(marker_x - 2.0, bar_y - 3.0, 4.0, 20.0, t.text);
+    }
+    let swatch_y = list_y + 44.0 + 4.0 * 56.0;
+    draw_rectangle(
+        picker_x,
+        swatch_y,
+        picker_width,
+        40.0,
+        hsv_to_color(hue, saturation, value, alpha),
+    );
+    draw_rectangle_lines(picker_x, swatch_y, picker_width, 40.0, 1.0, t.panel_border);
+
+    // Samples of the colors in use
+    let sample_y = menu_y + menu_height - 110.0;
+    draw_rectangle(
+        menu_x + 15.0,
+        sample_y,
+        menu_width - 30.0,
+        56.0,
+        Color::new(t.accent.r * 0.2, t.accent.g * 0.2, t.accent.b * 0.2, 0.6),
+    );
+    draw_text(
+        "Achievement title",
+        menu_x + 25.0,
+        sample_y + 22.0,
+        20.0,
+        t.text,
+    );
+    draw_text(
+        "Description",
+        menu_x + 25.0,
+        sample_y + 44.0,
+        16.0,
+        t.text_secondary,
+    );
+    draw_text(
+        "Locked",
+        menu_x + 200.0,
+        sample_y + 44.0,
+        16.0,
+        t.text_disabled,
+    );
+    draw_text("10 pts", menu_x + 280.0, sample_y + 22.0, 16.0, t.accent);
+    draw_text(
+        "Hardcore",
+        menu_x + 280.0,
+        sample_y + 44.0,
+        16.0,
+        t.accent_secondary,
+    );
+    let statuses = [
+        ("Success", t.success),
+        ("Warning", t.warning),
+        ("Error", t.error),
+        ("Info", t.info),
+    ];
+    for (i, (label, color)) in statuses.iter().enumerate() {
+        draw_text(
+            label,
+            menu_x + 400.0 + (i % 2) as f32 * 150.0,
+            sample_y + 22.0 + (i / 2) as f32 * 22.0,
+            16.0,
+            *color,
+        );
+    }
+
+    draw_text(
+        "Up/Down: Color • LB/RB: Channel • Left/Right: Adjust (LT/RT: Fast) • X: Reset • A: Save • B: Cancel",
+        menu_x + 20.0,
+        menu_y + menu_height - 20.0,
+        14.0,
+        t.text_secondary,
+    );
+}
+
+fn render_performance(state: &OverlayState) {
+    let t = theme(state);
+    let panel_width = 600.0;
+    let panel_height = 400.0;
+    let panel_x = (screen_width() - panel_width) / 2.0;
+    let panel_y = (screen_height() - panel_height) / 2.0;
+
+    draw_panel(panel_x, panel_y, panel_width, panel_height, &t);
+
+    // Title
+    draw_text(
+        "PERFORMANCE",
+        panel_x + 20.0,
+        panel_y + 40.0,
+        32.0,
+        t.cursor,
+    );
+
+    // Performance stats
+    let stats_y = panel_y + 80.0;
+    let cpu = state.performance.get_cpu_usage();
+    let ram = state.performance.get_ram_usage();
+    let fps = state.performance.get_average_fps();
+
+    draw_text(
+        &format!("CPU: {:.1}%", cpu),
+        panel_x + 40.0,
+        stats_y,
+        24.0,
+        t.text,
+    );
+    draw_text(
+        &format!("RAM: {:.1}%", ram),
+        panel_x + 40.0,
+        stats_y + 40.0,
+        24.0,
+        t.text,
+    );
+    draw_text(
+        &format!("FPS: {:.0}", fps),
+        panel_x + 40.0,
+        stats_y + 80.0,
+        24.0,
+        t.text,
+    );
+
+    // IPC health: dropped messages mean the queue overflowed during a burst
+    let ipc = state.ipc_stats;
+    draw_text(
+        &format!(
+            "IPC: {} msgs, {} dropped, {} invalid (peak queue {})",
+            ipc.received, ipc.dropped, ipc.parse_errors, ipc.peak_queue_len
+        ),
+        panel_x + 40.0,
+        stats_y + 120.0,
+        20.0,
+        if ipc.dropped > 0 {
+            t.warning
+        } else {
+            t.text_secondary
+        },
+    );
+    draw_text(
+        &format!(
+            "IPC data: {} KB, largest {} KB, {} deferred frames",
+            ipc.bytes_received / 1024,
+            ipc.largest_message / 1024,
+            ipc.deferred_frames
+        ),
+        panel_x + 40.0,
+        stats_y + 145.0,
+        20.0,
+        t.text_secondary,
+    );
+
+    // Back hint
+    draw_text(
+        "Press B to go back",
+        panel_x + 20.0,
+        panel_y + panel_height - 20.0,
+        16.0,
+        t.text_secondary,
+    );
+}
+
+fn render_playtime(state: &OverlayState) {
+    let t = theme(state);
+    let panel_width = 600.0;
+    let panel_height = 400.0;
+    let panel_x = (screen_width() - panel_width) / 2.0;
+    let panel_y = (screen_height() - panel_height) / 2.0;
+
+    // Panel background
+    draw_rectangle(
+        panel_x,
+        panel_y,
+        panel_width,
+        panel_height,
+        t.panel_background,
+    );
+    draw_rectangle_lines(
+        panel_x,
+        panel_y,
+        panel_width,
+        panel_height,
+        3.0,
+        t.panel_border,
+    );
+
+    // Title
+    draw_text("PLAYTIME", panel_x + 20.0, panel_y + 40.0, 32.0, t.cursor);
+
+    // Current session
+    if let Some(session_time) = state.playtime.get_current_session_duration() {
+        let hours = session_time.as_secs() / 3600;
+        let minutes = (session_time.as_secs() % 3600) / 60;
+        let seconds = session_time.as_secs() % 60;
+
+        draw_text(
+            &format!(
+                "Current Session: {:02}:{:02}:{:02}",
+                hours, minutes, seconds
+            ),
+            panel_x + 40.0,
+            panel_y + 100.0,
+            24.0,
+            t.text,
+        );
+    } else {
+        draw_text(
+            "No active session",
+            panel_x + 40.0,
+            panel_y + 100.0,
+            24.0,
+            t.text_secondary,
+        );
+    }
+
+    // Game picked from search
+    if let Some(game) = &state.playtime_focus {
+        draw_text(&game.name, panel_x + 40.0, panel_y + 160.0, 24.0, t.cursor);
+        let summary = match state.playtime.get_playtime(&game.cart_id) {
+            Some(entry) => {
+                let hours = entry.total_seconds / 3600;
+                let minutes = (entry.total_seconds % 3600) / 60;
+                format!(
+                    "Total: {}h {}m • {} plays",
+                    hours, minutes, entry.play_count
+                )
+            }
+            None => "Not played yet".to_string(),
+        };
+        draw_text(&summary, panel_x + 40.0, panel_y + 195.0, 20.0, t.text);
+    }
+
+    // Back hint
+    draw_text(
+        "Press B to go back",
+        panel_x + 20.0,
+        panel_y + panel_height - 20.0,
+        16.0,
+        t.text_secondary,
+    );
+}
+
+/// Colour of a run's timer: running, paused, or finished ahead of/behind the best
+fn speedrun_time_color(state: RunState, ahead: bool, t: &crate::themes::Theme) -> Color {
+    match state {
+        RunState::Running => t.text,
+        RunState::Idle | RunState::Paused => t.text_secondary,
+        RunState::Finished if ahead => t.success,
+        RunState::Finished => t.error,
+    }
+}
+
+fn render_speedrun(state: &OverlayState) {
+    let t = theme(state);
+    let panel_width = 600.0;
+    let panel_height = 400.0;
+    let panel_x = (screen_width() - panel_width) / 2.0;
+    let panel_y = (screen_height() - panel_height) / 2.0;
+
+    draw_panel(panel_x, panel_y, panel_width, panel_height, t);
+    draw_text(
+        "SPEEDRUN TIMER",
+        panel_x + 20.0,
+        panel_y + 40.0,
+        32.0,
+        t.cursor,
+    );
+
+    let Some(speedrun) = &state.speedrun else {
+        draw_text(
+            "Speedrun timer unavailable",
+            panel_x + 40.0,
+            panel_y + 100.0,
+            24.0,
+            t.text_secondary,
+        );
+        return;
+    };
+    let run = speedrun.run();
+    let best = speedrun.best();
+    let elapsed = run.elapsed(std::time::Instant::now());
+    let ahead = best.is_none_or(|b| (elapsed.as_millis() as u64) < b.total_ms);
+
+    // Main timer
+    let time = speedrun::format_time(elapsed);
+    draw_text(
+        &time,
+        panel_x + 40.0,
+        panel_y + 100.0,
+        48.0,
+        speedrun_time_color(run.state(), ahead, t),
+    );
+    let best_text = match best {
+        Some(b) => format!(
+            "Best: {}",
+            speedrun::format_time(std::time::Duration::from_millis(b.total_ms))
+        ),
+        None => "Best: --".to_string(),
+    };
+    draw_text(
+        &best_text,
+        panel_x + 40.0,
+        panel_y + 130.0,
+        20.0,
+        t.text_secondary,
+    );
+
+    // Splits (the last few, so the current one stays in view)
+    const MAX_SPLITS: usize = 6;
+    let current = run.splits().len();
+    let first = (current + 1).saturating_sub(MAX_SPLITS);
+    for (row, (i, name)) in run
+        .names()
+        .iter()
+        .enumerate()
+        .skip(first)
+        .take(MAX_SPLITS)
+        .enumerate()
+    {
+        let y = panel_y + 170.0 + row as f32 * 26.0;
+        let color = if i == current { t.cursor } else { t.text };
+        draw_text(name, panel_x + 40.0, y, 20.0, color);
+
+        if let Some(&split) = run.splits().get(i) {
+            draw_text(
+                &speedrun::format_time(split),
+                panel_x + 300.0,
+                y,
+                20.0,
+                t.text,
+            );
+            if let Some(&best_ms) = best.and_then(|b| b.splits_ms.get(i)) {
+                let delta_color = if (split.as_millis() as u64) < best_ms {
+                    t.success
+                } else {
+                    t.error
+                };
+                draw_text(
+                    &speedrun::format_delta(split, best_ms),
+                    panel_x + 420.0,
+                    y,
+                    20.0,
+                    delta_color,
+                );
+            }
+        } else if let Some(&best_ms) = best.and_then(|b| b.splits_ms.get(i)) {
+            draw_text(
+                &speedrun::format_time(std::time::Duration::from_millis(best_ms)),
+                panel_x + 300.0,
+                y,
+                20.0,
+                t.text_secondary,
+            );
+        }
+    }
+
+    // Options
+    let toggle_label = if run.state() == RunState::Running {
+        "Pause"
+    } else {
+        "Start"
+    };
+    let widget_label = format!(
+        "Widget: {}",
+        if speedrun.config().widget {
+            "On"
+        } else {
+            "Off"
+        }
+    );
+    let options = [toggle_label, "Split", "Reset", widget_label.as_str()];
+    let option_x = panel_x + 40.0;
+    for (i, option) in options.iter().enumerate() {
+        let x = option_x + i as f32 * 130.0;
+        let y = panel_y + panel_height - 50.0;
+        let selected = i == state.speedrun_selected;
+        if selected {
+            draw_text("►", x - 18.0, y, 18.0, t.cursor);
+        }
+        draw_text(option, x, y, 20.0, if selected { t.cursor } else { t.text });
+    }
+
+    draw_text(
+        "F6 Start/Pause  F7 Split  F8 Reset  •  B: Back",
+        panel_x + 20.0,
+        panel_y + panel_height - 20.0,
+        16.0,
+        t.text_secondary,
+    );
+}
+
+fn render_cheats(state: &OverlayState) {
+    let t = theme(state);
+    let panel_width = 600.0;
+    let panel_height = 400.0;
+    let panel_x = (screen_width() - panel_width) / 2.0;
+    let panel_y = (screen_height() - panel_height) / 2.0;
+
+    draw_panel(panel_x, panel_y, panel_width, panel_height, t);
+    draw_text("CHEATS", panel_x + 20.0, panel_y + 40.0, 32.0, t.cursor);
+
+    let Some(cheats) = state.cheats.as_ref().filter(|c| !c.cheats.is_empty()) else {
+        draw_text(
+            "No cheats found for this game",
+            panel_x + 40.0,
+            panel_y + 100.0,
+            24.0,
+            t.text_secondary,
+        );
+        draw_text(
+            "Add a .cht file under overlay/cheats/<cart id>.cht",
+            panel_x + 40.0,
+            panel_y + 130.0,
+            18.0,
+            t.text_secondary,
+        );
+        draw_text(
+            "B: Back",
+            panel_x + 20.0,
+            panel_y + panel_height - 20.0,
+            16.0,
+            t.text_secondary,
+        );
+        return;
+    };
+
+    const VISIBLE_ROWS: usize = 9;
+    let first = (cheats.selected + 1).saturating_sub(VISIBLE_ROWS);
+    for (row, (i, cheat)) in cheats
+        .cheats
+        .iter()
+        .enumerate()
+        .skip(first)
+        .take(VISIBLE_ROWS)
+        .enumerate()
+    {
+        let y = panel_y + 85.0 + row as f32 * 28.0;
+        let selected = i == cheats.selected;
+        if selected {
+            draw_text("►", panel_x + 22.0, y, 18.0, t.cursor);
+        }
+        let check = if cheat.enabled { "[x]" } else { "[ ]" };
+        let check_color = if cheat.enabled {
+            t.success
+        } else {
+            t.text_secondary
+        };
+        draw_text(check, panel_x + 40.0, y, 20.0, check_color);
+        let description: String = cheat.description.chars().take(44).collect();
+        draw_text(
+            &description,
+            panel_x + 80.0,
+            y,
+            20.0,
+            if selected { t.cursor } else { t.text },
+        );
+    }
+
+    let sources: Vec<String> = cheats
+        .sources
+        .iter()
+        .filter_map(|p| p.file_name().map(|n| n.to_string_lossy().into_owned()))
+        .collect();
+    draw_text(
+        &format!("From: {}", sources.join(", ")),
+        panel_x + 20.0,
+        panel_y + panel_height - 70.0,
+        14.0,
+        t.text_secondary,
+    );
+
+    let warning = if cheats.hardcore {
+        "Enabling a cheat turns hardcore off until the game restarts"
+    } else {
+        "Softcore: cheats reset when the game stops"
+    };
+    draw_text(
+        warning,
+        panel_x + 20.0,
+        panel_y + panel_height - 45.0,
+        16.0,
+        if cheats.hardcore {
+            t.error
+        } else {
+            t.text_secondary
+        },
+    );
+    draw_text(
+        "A: Toggle  •  B: Back",
+        panel_x + 20.0,
+        panel_y + panel_height - 20.0,
+        16.0,
+        t.text_secondary,
+    );
+}
+
+fn render_game_controls(state: &OverlayState) {
+    let t = theme(state);
+    let panel_width = 900.0;
+    let panel_height = 540.0;
+    let panel_x = (screen_width() - panel_width) / 2.0;
+    let panel_y = (screen_height() - panel_height) / 2.0;
+
+    draw_panel(panel_x, panel_y, panel_width, panel_height, t);
+    draw_text(
+        "GAME CONTROLS",
+        panel_x + 20.0,
+        panel_y + 40.0,
+        32.0,
+        t.cursor,
+    );
+    draw_text(
+        "B: Back",
+        panel_x + 20.0,
+        panel_y + panel_height - 20.0,
+        16.0,
+        t.text_secondary,
+    );
+
+    if state.game_controls.is_empty() {
+        draw_text(
+            "No control hints for this game",
+            panel_x + 40.0,
+            panel_y + 100.0,
+            24.0,
+            t.text_secondary,
+        );
+        draw_text(
+            "Carts can ship a controls.toml next to their .kzi",
+            panel_x + 40.0,
+            panel_y + 130.0,
+            18.0,
+            t.text_secondary,
+        );
+        return;
+    }
+
+    let layout = ControlsLayout::new(&state.game_controls);
+    let cx = panel_x + panel_width / 2.0;
+    let cy = panel_y + 250.0;
+    let w = 150.0;
+    let at = |button: Button| {
+        let (x, y) = button.position();
+        vec2(cx + x * w, cy + y * w)
+    };
+
+    // Controller body, shoulders and the unmapped buttons
+    let body = Color {
+        a: 0.35,
+        ..t.text_secondary
+    };
+    let part = Color {
+        a: 0.8,
+        ..t.text_secondary
+    };
+    draw_rectangle(cx - w, cy - 0.55 * w, 2.0 * w, 0.8 * w, body);
+    draw_circle(cx - 0.7 * w, cy + 0.3 * w, 0.38 * w, body);
+    draw_circle(cx + 0.7 * w, cy + 0.3 * w, 0.38 * w, body);
+    for shoulder in [Button::LT, Button::LB, Button::RT, Button::RB] {
+        let p = at(shoulder);
+        draw_rectangle(p.x - 0.22 * w, p.y - 0.06 * w, 0.44 * w, 0.12 * w, part);
+    }
+    for stick in [Button::LeftStick, Button::RightStick] {
+        let p = at(stick);
+        draw_circle_lines(p.x, p.y, 0.14 * w, 3.0, part);
+    }
+    let dpad = at(Button::DPad);
+    draw_rectangle(
+        dpad.x - 0.05 * w,
+        dpad.y - 0.16 * w,
+        0.1 * w,
+        0.32 * w,
+        part,
+    );
+    draw_rectangle(
+        dpad.x - 0.16 * w,
+        dpad.y - 0.05 * w,
+        0.32 * w,
+        0.1 * w,
+        part,
+    );
+    for face in [Button::A, Button::B, Button::X, Button::Y] {
+        let p = at(face);
+        draw_circle_lines(p.x, p.y, 0.08 * w, 2.0, part);
+        draw_text(face.display_name(), p.x - 5.0, p.y + 5.0, 16.0, t.text);
+    }
+    for small in [Button::Select, Button::Start, Button::Guide] {
+        let p = at(small);
+        draw_circle_lines(p.x, p.y, 0.05 * w, 2.0, part);
+    }
+
+    // Mapped buttons, with a line out to their label in the column beside the diagram
+    for left in [true, false] {
+        let side = layout.side(left);
+        let row_height = (1.7 * w / side.len().max(1) as f32).min(30.0);
+        let top = cy - 0.85 * w;
+        for (row, (button, label)) in side.iter().enumerate() {
+            let text = format!("{}: {}", button.display_name(), label);
+            let text: String = if text.chars().count() > 26 {
+                format!("{}...", text.chars().take(23).collect::<String>())
+            } else {
+                text
+            };
+            let y = top + row as f32 * row_height;
+            let width = measure_text(&text, None, 18, 1.0).width;
+            let (text_x, line_end) = if left {
+                (cx - w - 40.0 - width, cx - w - 32.0)
+            } else {
+                (cx + w + 40.0, cx + w + 32.0)
+            };
+            let p = at(*button);
+            draw_line(p.x, p.y, line_end, y - 5.0, 1.5, t.accent);
+            draw_circle(p.x, p.y, 0.05 * w, t.cursor);
+            draw_text(&text, text_x, y, 18.0, t.text);
+        }
+    }
+
+    if !layout.other.is_empty() {
+        let other: Vec<String> = layout
+            .other
+            .iter()
+            .map(|hint| format!("{}: {}", hint.button, hint.label))
+            .collect();
+        let text = other.join("  •  ");
+        let text: String = if text.chars().count() > 90 {
+            format!("{}...", text.chars().take(87).collect::<String>())
+        } else {
+            text
+        };
+        draw_text(
+            "Also",
+            panel_x + 20.0,
+            panel_y + panel_height - 70.0,
+            16.0,
+            t.text_secondary,
+        );
+        draw_text(
+            &text,
+            panel_x + 20.0,
+            panel_y + panel_height - 48.0,
+            18.0,
+            t.text,
+        );
+    }
+}
+
+fn render_speedrun_widget(state: &OverlayState, rect: Rect) {
+    let t = theme(state);
+    let s = rect.w / HudWidget::Timer.base_size().x;
+    draw_rectangle(
+        rect.x,
+        rect.y,
+        rect.w,
+        rect.h,
+        Color::new(0.0, 0.0, 0.0, 0.75),
+    );
+    draw_rectangle_lines(
+        rect.x,
+        rect.y,
+        rect.w,
+        rect.h,
+        1.5,
+        Color::new(0.5, 0.5, 0.5, 0.9),
+    );
+
+    // The layout editor previews the widget before any run exists
+    let Some(speedrun) = &state.speedrun else {
+        draw_text(
+            "Split 1",
+            rect.x + 8.0 * s,
+            rect.y + 16.0 * s,
+            14.0 * s,
+            t.text_secondary,
+        );
+        draw_text(
+            "0:00.000",
+            rect.x + 8.0 * s,
+            rect.y + 36.0 * s,
+            22.0 * s,
+            t.text,
+        );
+        return;
+    };
+    let run = speedrun.run();
+    let elapsed = run.elapsed(std::time::Instant::now());
+    let ahead = speedrun
+        .best()
+        .is_none_or(|b| (elapsed.as_millis() as u64) < b.total_ms);
+
+    let current = run
+        .names()
+        .get(run.splits().len())
+        .map(String::as_str)
+        .unwrap_or("Done");
+    draw_text(
+        current,
+        rect.x + 8.0 * s,
+        rect.y + 16.0 * s,
+        14.0 * s,
         t.text_secondary,
     );
+    draw_text(
+        &speedrun::format_time(elapsed),
+        rect.x + 8.0 * s,
+        rect.y + 36.0 * s,
+        22.0 * s,
+        speedrun_time_color(run.state(), ahead, t),
+    );
 }
 
-fn render_performance(state: &OverlayState) {
+/// Unearned achievements with measured progress and their progress bars
+fn render_pinned_achievements(state: &OverlayState, rect: Rect) {
     let t = theme(state);
-    let panel_width = 600.0;
-    let panel_height = 400.0;
-    let panel_x = (screen_width() - panel_width) / 2.0;
-    let panel_y = (screen_height() - panel_height) / 2.0;
-
-    draw_panel(panel_x, panel_y, panel_width, panel_height, &t);
+    let s = rect.w / HudWidget::Achievements.base_size().x;
+    draw_rectangle(
+        rect.x,
+        rect.y,
+        rect.w,
+        rect.h,
+        Color::new(0.0, 0.0, 0.0, 0.75),
+    );
+    draw_rectangle_lines(
+        rect.x,
+        rect.y,
+        rect.w,
+        rect.h,
+        1.5,
+        Color::new(0.5, 0.5, 0.5, 0.9),
+    );
 
-    // Title
-    draw_text("PERFORMANCE", panel_x + 20.0, panel_y + 40.0, 32.0, t.cursor);
+    let pinned = state.achievements.pinned();
+    if pinned.is_empty() {
+        draw_text(
+            "No achievements in progress",
+            rect.x + 8.0 * s,
+            rect.y + 20.0 * s,
+            14.0 * s,
+            t.text_secondary,
+        );
+        return;
+    }
 
-    // Performance stats
-    let stats_y = panel_y + 80.0;
-    let cpu = state.performance.get_cpu_usage();
-    let ram = state.performance.get_ram_usage();
-    let fps = state.performance.get_average_fps();
+    let row_height = 30.0 * s;
+    for (i, achievement) in pinned.iter().enumerate() {
+        let Some(progress) = &achievement.progress else {
+            continue;
+        };
+        let y = rect.y + 4.0 * s + i as f32 * row_height;
+        let title = if achievement.title.chars().count() > 22 {
+            format!(
+                "{}...",
+                achievement.title.chars().take(19).collect::<String>()
+            )
+        } else {
+            achievement.title.clone()
+        };
+        let label = format!("{} {}/{}", title, progress.current, progress.target);
+        draw_text(&label, rect.x + 8.0 * s, y + 14.0 * s, 14.0 * s, t.text);
+
+        let bar_width = rect.w - 16.0 * s;
+        let fraction = progress.current as f32 / progress.target as f32;
+        draw_rectangle(
+            rect.x + 8.0 * s,
+            y + 19.0 * s,
+            bar_width,
+            5.0 * s,
+            Color::new(1.0, 1.0, 1.0, 0.15),
+        );
+        draw_rectangle(
+            rect.x + 8.0 * s,
+            y + 19.0 * s,
+            bar_width * fraction,
+            5.0 * s,
+            t.accent,
+        );
+    }
+}
 
-    draw_text(
-        &format!("CPU: {:.1}%", cpu),
-        panel_x + 40.0,
-        stats_y,
-        24.0,
-        t.text,
+fn render_battery_widget(state: &OverlayState, rect: Rect) {
+    let t = theme(state);
+    let s = rect.w / HudWidget::Battery.base_size().x;
+    draw_rectangle(
+        rect.x,
+        rect.y,
+        rect.w,
+        rect.h,
+        Color::new(0.0, 0.0, 0.0, 0.75),
     );
-    draw_text(
-        &format!("RAM: {:.1}%", ram),
-        panel_x + 40.0,
-        stats_y + 40.0,
-        24.0,
-        t.text,
+    draw_rectangle_lines(
+        rect.x,
+        rect.y,
+        rect.w,
+        rect.h,
+        1.5,
+        Color::new(0.5, 0.5, 0.5, 0.9),
     );
-    draw_text(
-        &format!("FPS: {:.0}", fps),
-        panel_x + 40.0,
-        stats_y + 80.0,
-        24.0,
-        t.text,
+
+    let status = state.battery.status();
+    let percent = status.map_or(0, |b| b.percent);
+    let color = match status {
+        Some(b) if b.charging => t.success,
+        Some(b) if b.percent <= 15 => t.error,
+        Some(b) if b.percent <= 30 => t.warning,
+        _ => t.text,
+    };
+
+    // Battery outline with a fill for the charge level
+    let (icon_x, icon_y, icon_w, icon_h) = (rect.x + 8.0 * s, rect.y + 8.0 * s, 22.0 * s, 12.0 * s);
+    draw_rectangle_lines(icon_x, icon_y, icon_w, icon_h, 1.5, color);
+    draw_rectangle(icon_x + icon_w, icon_y + 3.0 * s, 2.0 * s, 6.0 * s, color);
+    draw_rectangle(
+        icon_x + 2.0 * s,
+        icon_y + 2.0 * s,
+        (icon_w - 4.0 * s) * percent as f32 / 100.0,
+        icon_h - 4.0 * s,
+        color,
     );
 
-    // Back hint
+    let label = match status {
+        Some(b) => format!("{}%{}", b.percent, if b.charging { "+" } else { "" }),
+        None => "--%".to_string(),
+    };
     draw_text(
-        "Press B to go back",
-        panel_x + 20.0,
-        panel_y + panel_height - 20.0,
-        16.0,
-        t.text_secondary,
+        &label,
+        icon_x + icon_w + 8.0 * s,
+        rect.y + 19.0 * s,
+        16.0 * s,
+        color,
     );
 }
 
-fn render_playtime(state: &OverlayState) {
-    let t = theme(state);
-    let panel_width = 600.0;
-    let panel_height = 400.0;
-    let panel_x = (screen_width() - panel_width) / 2.0;
-    let panel_y = (screen_height() - panel_height) / 2.0;
+/// HUD widgets at their layout positions. The performance HUD stays up while
+/// the menu is open; the others only show during play.
+fn render_hud(state: &OverlayState) {
+    // The layout editor draws its own previews
+    if state.visible && state.current_screen == OverlayScreen::HudLayout {
+        return;
+    }
 
-    // Panel background
-    draw_rectangle(panel_x, panel_y, panel_width, panel_height, t.panel_background);
-    draw_rectangle_lines(panel_x, panel_y, panel_width, panel_height, 3.0, t.panel_border);
+    let layout = state.hud_layout();
+    let screen = vec2(screen_width(), screen_height());
+    for widget in HudWidget::ALL {
+        if (state.visible && widget != HudWidget::Performance) || !state.hud_widget_active(widget) {
+            continue;
+        }
+        draw_hud_widget(state, widget, layout.get(widget).rect(widget, screen));
+    }
+}
 
-    // Title
-    draw_text("PLAYTIME", panel_x + 20.0, panel_y + 40.0, 32.0, t.cursor);
+fn draw_hud_widget(state: &OverlayState, widget: HudWidget, rect: Rect) {
+    match widget {
+        HudWidget::Performance => render_performance_hud(state, rect),
+        HudWidget::Achievements => render_pinned_achievements(state, rect),
+        HudWidget::Timer => render_speedrun_widget(state, rect),
+        HudWidget::Battery => render_battery_widget(state, rect),
+    }
+}
 
-    // Current session
-    if let Some(session_time) = state.playtime.get_current_session_duration() {
-        let hours = session_time.as_secs() / 3600;
-        let minutes = (session_time.as_secs() % 3600) / 60;
-        let seconds = session_time.as_secs() % 60;
+/// Every widget at its position, hidden ones as dimmed outlines, with the
+/// selected one highlighted
+fn render_hud_editor(state: &OverlayState) {
+    let t = theme(state);
+    let layout = state.hud_layout();
+    let screen = vec2(screen_width(), screen_height());
+    let selected = HudWidget::ALL[state.hud_editor_selected % HudWidget::ALL.len()];
+
+    for widget in HudWidget::ALL {
+        let placement = layout.get(widget);
+        let rect = placement.rect(widget, screen);
+        if placement.visible {
+            draw_hud_widget(state, widget, rect);
+        } else {
+            draw_rectangle(
+                rect.x,
+                rect.y,
+                rect.w,
+                rect.h,
+                Color::new(0.0, 0.0, 0.0, 0.4),
+            );
+            draw_text("Hidden", rect.x + 8.0, rect.y + 18.0, 14.0, t.text_disabled);
+        }
 
-        draw_text(
-            &format!("Current Session: {:02}:{:02}:{:02}", hours, minutes, seconds),
-            panel_x + 40.0,
-            panel_y + 100.0,
-            24.0,
-            t.text,
-        );
-    } else {
-        draw_text(
-            "No active session",
-            panel_x + 40.0,
-            panel_y + 100.0,
-            24.0,
-            t.text_secondary,
+        let (color, thickness) = if widget == selected {
+            (t.cursor, 3.0)
+        } else {
+            (t.text_secondary, 1.0)
+        };
+        draw_rectangle_lines(
+            rect.x - 3.0,
+            rect.y - 3.0,
+            rect.w + 6.0,
+            rect.h + 6.0,
+            thickness,
+            color,
         );
+        if widget == selected {
+            // Label on whichever side of the widget has room
+            let label_y = if rect.y > 30.0 {
+                rect.y - 8.0
+            } else {
+                rect.bottom() + 20.0
+            };
+            draw_text(widget.display_name(), rect.x, label_y, 18.0, t.cursor);
+        }
     }
 
-    // Back hint
+    let title = match state.current_cart_id() {
+        Some(_) if !state.achievements.game_title.is_empty() => {
+            format!("HUD LAYOUT: {}", state.achievements.game_title)
+        }
+        Some(cart_id) => format!("HUD LAYOUT: {}", cart_id),
+        None => "HUD LAYOUT: Default".to_string(),
+    };
+    let help = "LB/RB: Widget   D-Pad: Move   LT/RT: Size   A: Show/Hide   X: Snap   B: Save";
+    let help_width = measure_text(help, None, 16, 1.0).width;
+    let panel_w = help_width + 40.0;
+    let panel_x = (screen.x - panel_w) / 2.0;
+    let panel_y = screen.y / 2.0 - 40.0;
+    draw_panel(panel_x, panel_y, panel_w, 80.0, t);
+    let title_width = measure_text(&title, None, 24, 1.0).width;
     draw_text(
-        "Press B to go back",
-        panel_x + 20.0,
-        panel_y + panel_height - 20.0,
-        16.0,
-        t.text_secondary,
+        &title,
+        (screen.x - title_width) / 2.0,
+        panel_y + 32.0,
+        24.0,
+        t.cursor,
+    );
+    draw_text(help, panel_x + 20.0, panel_y + 60.0, 16.0, t.text_secondary);
+}
+
+/// Highlight a row the pointer is over, stronger while it is pressed
+fn draw_pointer_highlight(
+    state: &OverlayState,
+    target: PointerTarget,
+    rect: Rect,
+    t: &crate::themes::Theme,
+) {
+    let alpha = if state.pointer.pressed == Some(target) {
+        0.3
+    } else if state.pointer.hovered == Some(target) {
+        0.12
+    } else {
+        return;
+    };
+    draw_rectangle(
+        rect.x,
+        rect.y,
+        rect.w,
+        rect.h,
+        Color::new(t.cursor.r, t.cursor.g, t.cursor.b, alpha),
+    );
+}
+
+fn render_pointer_cursor(state: &OverlayState) {
+    let Some(position) = state.pointer.position else {
+        return;
+    };
+    let t = theme(state);
+    let radius = if state.pointer.pressed.is_some() {
+        6.0
+    } else {
+        8.0
+    };
+    draw_circle(
+        position.x,
+        position.y,
+        radius,
+        Color::new(0.0, 0.0, 0.0, 0.5),
     );
+    draw_circle_lines(position.x, position.y, radius, 2.0, t.cursor);
 }
 
 fn draw_vignette() {
@@ -1224,11 +4900,7 @@ fn draw_vignette() {
     let h = screen_height();
     let fade = |alpha: f32| Color::new(0.0, 0.0, 0.0, alpha);
 
-    let bands = [
-        (0.04, fade(0.20)),
-        (0.07, fade(0.12)),
-        (0.10, fade(0.08)),
-    ];
+    let bands = [(0.04, fade(0.20)), (0.07, fade(0.12)), (0.10, fade(0.08))];
 
     for (thickness_ratio, color) in bands {
         let t = h * thickness_ratio;
@@ -1244,6 +4916,57 @@ fn draw_vignette() {
     }
 }
 
+/// The running game's icon, once it has loaded. Returns whether it was drawn.
+fn draw_game_icon(state: &OverlayState, x: f32, y: f32, size: f32) -> bool {
+    let Some(texture) = state.achievements.icon.texture() else {
+        return false;
+    };
+    draw_texture_ex(
+        texture,
+        x,
+        y,
+        WHITE,
+        DrawTextureParams {
+            dest_size: Some(vec2(size, size)),
+            ..Default::default()
+        },
+    );
+    true
+}
+
+/// The console name in an outlined pill whose right edge is at `right`
+fn draw_console_badge(console: &str, right: f32, y: f32, t: &crate::themes::Theme) {
+    let font_size = 14.0;
+    let width = measure_text(console, None, font_size as u16, 1.0).width + 12.0;
+    let x = right - width;
+    draw_rectangle(
+        x,
+        y,
+        width,
+        20.0,
+        Color::new(t.cursor.r, t.cursor.g, t.cursor.b, 0.2),
+    );
+    draw_rectangle_lines(x, y, width, 20.0, 1.0, t.cursor);
+    draw_text(console, x + 6.0, y + 15.0, font_size, t.text);
+}
+
+/// A small pill ending at `right`; returns where it starts
+fn draw_header_chip(text: &str, right: f32, y: f32, color: Color, t: &crate::themes::Theme) -> f32 {
+    let font_size = 16.0;
+    let width = measure_text(text, None, font_size as u16, 1.0).width + 16.0;
+    let x = right - width;
+    draw_rectangle(
+        x,
+        y,
+        width,
+        22.0,
+        Color::new(color.r, color.g, color.b, 0.2),
+    );
+    draw_rectangle_lines(x, y, width, 22.0, 1.0, color);
+    draw_text(text, x + 8.0, y + 16.0, font_size, t.text);
+    x
+}
+
 fn draw_panel(x: f32, y: f32, w: f32, h: f32, t: &crate::themes::Theme) {
     let shadow = Color::new(0.0, 0.0, 0.0, 0.18);
     draw_rectangle(x - 8.0, y - 8.0, w + 16.0, h + 16.0, shadow);
@@ -1251,6 +4974,50 @@ fn draw_panel(x: f32, y: f32, w: f32, h: f32, t: &crate::themes::Theme) {
     draw_rectangle_lines(x, y, w, h, 2.0, t.panel_border);
 }
 
+/// Spinner shown while the game saves before it's closed
+fn render_quit_saving(state: &OverlayState) {
+    let t = theme(state);
+    let dialog_width = 360.0;
+    let dialog_height = 160.0;
+    let dialog_x = (screen_width() - dialog_width) / 2.0;
+    let dialog_y = (screen_height() - dialog_height) / 2.0;
+    draw_panel(dialog_x, dialog_y, dialog_width, dialog_height, t);
+
+    let Some(quit) = &state.quit else { return };
+    let (title, detail) = match quit.phase {
+        crate::quit::QuitPhase::Saving => (
+            "Saving...",
+            format!("Waiting for the game ({}s)", quit.seconds_left()),
+        ),
+        _ => ("Closing game...", "Almost done".to_string()),
+    };
+
+    // Eight dots chasing each other around a circle
+    let center = vec2(dialog_x + 50.0, dialog_y + dialog_height / 2.0);
+    let head = (quit.elapsed_secs() * 10.0) as usize % 8;
+    for i in 0..8 {
+        let angle = i as f32 / 8.0 * std::f32::consts::TAU;
+        let age = (head + 8 - i) % 8;
+        let mut color = t.cursor;
+        color.a = 1.0 - age as f32 / 8.0;
+        draw_circle(
+            center.x + angle.cos() * 18.0,
+            center.y + angle.sin() * 18.0,
+            4.0,
+            color,
+        );
+    }
+
+    draw_text(title, dialog_x + 95.0, center.y - 6.0, 28.0, t.text);
+    draw_text(
+        &detail,
+        dialog_x + 95.0,
+        center.y + 22.0,
+        16.0,
+        t.text_secondary,
+    );
+}
+
 fn render_quit_confirm(state: &OverlayState) {
     let t = theme(state);
     let dialog_width = 450.0;
@@ -1259,11 +5026,30 @@ fn render_quit_confirm(state: &OverlayState) {
     let dialog_y = (screen_height() - dialog_height) / 2.0;
 
     // Dialog background with red tint
-    draw_rectangle(dialog_x, dialog_y, dialog_width, dialog_height, Color::new(t.error.r * 0.15, t.error.g * 0.08, t.error.b * 0.08, 0.98));
-    draw_rectangle_lines(dialog_x, dialog_y, dialog_width, dialog_height, 3.0, t.error);
+    draw_rectangle(
+        dialog_x,
+        dialog_y,
+        dialog_width,
+        dialog_height,
+        Color::new(t.error.r * 0.15, t.error.g * 0.08, t.error.b * 0.08, 0.98),
+    );
+    draw_rectangle_lines(
+        dialog_x,
+        dialog_y,
+        dialog_width,
+        dialog_height,
+        3.0,
+        t.error,
+    );
 
     // Warning icon and title
-    draw_text("⚠️ QUIT GAME?", dialog_x + 130.0, dialog_y + 50.0, 32.0, t.error);
+    draw_text(
+        "⚠️ QUIT GAME?",
+        dialog_x + 130.0,
+        dialog_y + 50.0,
+        32.0,
+        t.error,
+    );
 
     // Message
     draw_text(
@@ -1295,7 +5081,14 @@ fn render_quit_confirm(state: &OverlayState) {
     let cancel_border = if cancel_selected { t.cursor } else { GRAY };
 
     draw_rectangle(dialog_x + 50.0, button_y, 150.0, 35.0, cancel_bg);
-    draw_rectangle_lines(dialog_x + 50.0, button_y, 150.0, 35.0, if cancel_selected { 3.0 } else { 2.0 }, cancel_border);
+    draw_rectangle_lines(
+        dialog_x + 50.0,
+        button_y,
+        150.0,
+        35.0,
+        if cancel_selected { 3.0 } else { 2.0 },
+        cancel_border,
+    );
 
     // Selection indicator for Cancel
     if cancel_selected {
@@ -1310,14 +5103,31 @@ fn render_quit_confirm(state: &OverlayState) {
     } else {
         Color::new(0.6, 0.2, 0.2, 1.0)
     };
-    let quit_border = if quit_selected { Color::new(1.0, 0.4, 0.4, 1.0) } else { RED };
+    let quit_border = if quit_selected {
+        Color::new(1.0, 0.4, 0.4, 1.0)
+    } else {
+        RED
+    };
 
     draw_rectangle(dialog_x + 250.0, button_y, 150.0, 35.0, quit_bg);
-    draw_rectangle_lines(dialog_x + 250.0, button_y, 150.0, 35.0, if quit_selected { 3.0 } else { 2.0 }, quit_border);
+    draw_rectangle_lines(
+        dialog_x + 250.0,
+        button_y,
+        150.0,
+        35.0,
+        if quit_selected { 3.0 } else { 2.0 },
+        quit_border,
+    );
 
     // Selection indicator for Quit
     if quit_selected {
-        draw_text("►", dialog_x + 225.0, button_y + 25.0, 24.0, Color::new(1.0, 0.4, 0.4, 1.0));
+        draw_text(
+            "►",
+            dialog_x + 225.0,
+            button_y + 25.0,
+            24.0,
+            Color::new(1.0, 0.4, 0.4, 1.0),
+        );
     }
 
     draw_text("QUIT", dialog_x + 290.0, button_y + 25.0, 20.0, WHITE);
@@ -1332,7 +5142,90 @@ fn render_quit_confirm(state: &OverlayState) {
     );
 }
 
+/// Full-screen mastery celebration: confetti over the game title, points and
+/// completion time
+fn render_celebration(state: &OverlayState, celebration: &Celebration) {
+    let t = theme(state);
+    let alpha = celebration.alpha();
+    let with_alpha = |c: Color, a: f32| Color::new(c.r, c.g, c.b, c.a * a);
+
+    draw_rectangle(
+        0.0,
+        0.0,
+        screen_width(),
+        screen_height(),
+        Color::new(0.0, 0.0, 0.0, 0.7 * alpha),
+    );
+
+    let palette = [t.accent, t.success, t.warning, t.cursor, t.error, GOLD];
+    let confetti = if state.power_mode().effects() {
+        celebration.confetti(vec2(screen_width(), screen_height()))
+    } else {
+        Vec::new()
+    };
+    for piece in confetti {
+        draw_rectangle_ex(
+            piece.position.x,
+            piece.position.y,
+            piece.size.x,
+            piece.size.y,
+            DrawRectangleParams {
+                offset: vec2(0.5, 0.5),
+                rotation: piece.rotation,
+                color: with_alpha(palette[piece.color % palette.len()], alpha),
+            },
+        );
+    }
+
+    let center_x = screen_width() / 2.0;
+    let center_y = screen_height() / 2.0;
+    let centered = |text: &str, y: f32, size: f32, color: Color| {
+        let width = measure_text(text, None, size as u16, 1.0).width;
+        draw_text(
+            text,
+            center_x - width / 2.0,
+            y,
+            size,
+            with_alpha(color, alpha),
+        );
+    };
+
+    let heading = if celebration.hardcore {
+        "HARDCORE MASTERY"
+    } else {
+        "SET COMPLETE"
+    };
+    centered(heading, center_y - 70.0, 56.0, GOLD);
+    centered(&celebration.game_title, center_y - 15.0, 36.0, t.text);
+    centered(
+        &format!(
+            "{} achievements • {} points",
+            celebration.total_achievements, celebration.points
+        ),
+        center_y + 30.0,
+        24.0,
+        t.text_secondary,
+    );
+    if let Some(secs) = celebration.completion_secs {
+        centered(
+            &format!("Completed in {}", celebration::format_completion_time(secs)),
+            center_y + 62.0,
+            22.0,
+            t.text_secondary,
+        );
+    }
+    if state.visible {
+        centered(
+            "Press any button to continue",
+            screen_height() - 40.0,
+            18.0,
+            t.text_secondary,
+        );
+    }
+}
+
 fn render_toasts(state: &OverlayState) {
+    let t = theme(state);
     let toasts = state.toasts.get_visible_toasts();
     if toasts.is_empty() {
         return;
@@ -1351,51 +5244,51 @@ fn render_toasts(state: &OverlayState) {
         use std::time::Instant;
         let elapsed = Instant::now().duration_since(toast.created_at);
         let remaining = toast.duration.saturating_sub(elapsed);
-        let alpha = if remaining < std::time::Duration::from_millis(500) {
-            remaining.as_millis() as f32 / 500.0
-        } else {
-            1.0
-        };
-
-        // Background color based on style
-        let bg_color = match toast.style {
-            ToastStyle::Info => Color::new(0.2, 0.4, 0.8, 0.95 * alpha),
-            ToastStyle::Success => Color::new(0.2, 0.8, 0.4, 0.95 * alpha),
-            ToastStyle::Warning => Color::new(0.9, 0.7, 0.2, 0.95 * alpha),
-            ToastStyle::Error => Color::new(0.9, 0.2, 0.2, 0.95 * alpha),
-        };
-
-        // Draw toast background
-        draw_rectangle(base_x, y, toast_width, toast_height, bg_color);
-        draw_rectangle_lines(
-            base_x,
-            y,
-            toast_width,
-            toast_height,
-            2.0,
-            Color::new(1.0, 1.0, 1.0, alpha),
-        );
-
-        // Draw message (word wrap if needed)
-        let text_x = base_x + 15.0;
-        let text_y = y + toast_height / 2.0 + 5.0;
+        let alpha =
+            if remaining < std::time::Duration::from_millis(500) && state.power_mode().effects() {
+                remaining.as_millis() as f32 / 500.0
+            } else {
+                1.0
+            };
 
-        draw_text(
+        draw_toast(
+            Rect::new(base_x, y, toast_width, toast_height),
             &toast.message,
-            text_x,
-            text_y,
-            22.0,
-            Color::new(1.0, 1.0, 1.0, alpha),
+            toast.style,
+            alpha,
+            t,
         );
     }
 }
 
-fn render_performance_hud(state: &OverlayState) {
-    let hud_width = 200.0;
-    let hud_height = 110.0;
-    let hud_x = 10.0;
-    let hud_y = 10.0;
-    let padding = 8.0;
+fn draw_toast(rect: Rect, message: &str, style: ToastStyle, alpha: f32, t: &crate::themes::Theme) {
+    draw_rectangle(rect.x, rect.y, rect.w, rect.h, t.toast_color(style, alpha));
+    draw_rectangle_lines(
+        rect.x,
+        rect.y,
+        rect.w,
+        rect.h,
+        2.0,
+        Color::new(1.0, 1.0, 1.0, alpha),
+    );
+
+    // Draw message (word wrap if needed)
+    draw_text(
+        message,
+        rect.x + 15.0,
+        rect.y + rect.h / 2.0 + 5.0,
+        22.0,
+        Color::new(1.0, 1.0, 1.0, alpha),
+    );
+}
+
+fn render_performance_hud(state: &OverlayState, rect: Rect) {
+    let s = rect.w / HudWidget::Performance.base_size().x;
+    let hud_width = rect.w;
+    let hud_height = rect.h;
+    let hud_x = rect.x;
+    let hud_y = rect.y;
+    let padding = 8.0 * s;
 
     // Semi-transparent background
     draw_rectangle(
@@ -1417,12 +5310,12 @@ fn render_performance_hud(state: &OverlayState) {
     );
 
     let text_x = hud_x + padding;
-    let mut text_y = hud_y + padding + 14.0;
-    let line_height = 18.0;
+    let mut text_y = hud_y + padding + 14.0 * s;
+    let line_height = 18.0 * s;
 
     // Title
-    draw_text("PERFORMANCE", text_x, text_y, 14.0, YELLOW);
-    text_y += line_height + 2.0;
+    draw_text("PERFORMANCE", text_x, text_y, 14.0 * s, YELLOW);
+    text_y += line_height + 2.0 * s;
 
     // FPS
     let fps = state.performance.fps();
@@ -1437,7 +5330,7 @@ fn render_performance_hud(state: &OverlayState) {
         &format!("FPS: {:.1}", fps),
         text_x,
         text_y,
-        16.0,
+        16.0 * s,
         fps_color,
     );
     text_y += line_height;
@@ -1448,7 +5341,7 @@ fn render_performance_hud(state: &OverlayState) {
         &format!("Frame: {:.2}ms", frame_time),
         text_x,
         text_y,
-        16.0,
+        16.0 * s,
         WHITE,
     );
     text_y += line_height;
@@ -1466,7 +5359,7 @@ fn render_performance_hud(state: &OverlayState) {
         &format!("CPU: {:.1}%", cpu_usage),
         text_x,
         text_y,
-        16.0,
+        16.0 * s,
         cpu_color,
     );
     text_y += line_height;
@@ -1486,16 +5379,32 @@ fn render_performance_hud(state: &OverlayState) {
         &format!("MEM: {:.0}/{:.0}MB", mem_used, mem_total),
         text_x,
         text_y,
-        16.0,
+        16.0 * s,
         mem_color,
     );
+    text_y += line_height;
+
+    // Demand-loaded overlay resources against their budget
+    let usage = state.memory_usage();
+    draw_text(
+        &format!(
+            "RES: {:.1}/{:.0}MB ({})",
+            usage.bytes as f32 / 1024.0 / 1024.0,
+            usage.budget as f32 / 1024.0 / 1024.0,
+            usage.loaded
+        ),
+        text_x,
+        text_y,
+        16.0 * s,
+        if usage.over_budget() { RED } else { WHITE },
+    );
 
     // Hint at bottom
     draw_text(
         "F3: Toggle",
-        hud_x + hud_width - 65.0,
-        hud_y + hud_height - 5.0,
-        10.0,
+        hud_x + hud_width - 65.0 * s,
+        hud_y + hud_height - 5.0 * s,
+        10.0 * s,
         DARKGRAY,
     );
 }
@@ -0,0 +1,210 @@
+//! Demand-loaded resources for overlay screens
+//!
+//! Textures and derived data that only some screens use are loaded when one
+//! of those screens is entered and dropped once none of them has been shown
+//! for `UNLOAD_AFTER`. If the loaded total goes over `MEMORY_BUDGET`, the
+//! least recently used resources the current screen doesn't need go first.
+//! The totals are shown in the performance HUD.
+
+use crate::ipc::OverlayScreen;
+use crate::stats::AchievementStats;
+use std::time::{Duration, Instant};
+
+/// Resources unused for this long are unloaded
+pub const UNLOAD_AFTER: Duration = Duration::from_secs(5 * 60);
+
+/// Approximate bytes the demand-loaded resources may hold at once
+pub const MEMORY_BUDGET: usize = 16 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resource {
+    /// Box art texture for the running game
+    GameIcon,
+    /// Heatmap, sessions and estimate for the statistics screen
+    AchievementStats,
+}
+
+impl Resource {
+    /// Resources a screen draws from
+    pub fn used_by(screen: OverlayScreen) -> &'static [Resource] {
+        match screen {
+            OverlayScreen::Main | OverlayScreen::Achievements => &[Resource::GameIcon],
+            OverlayScreen::AchievementStats => &[Resource::AchievementStats],
+            _ => &[],
+        }
+    }
+}
+
+/// A value that is loaded on demand and can be dropped again
+pub struct Lazy<T> {
+    value: Option<T>,
+    /// Approximate size of `value`
+    bytes: usize,
+    last_used: Option<Instant>,
+}
+
+impl<T> Default for Lazy<T> {
+    fn default() -> Self {
+        Self {
+            value: None,
+            bytes: 0,
+            last_used: None,
+        }
+    }
+}
+
+impl<T> Lazy<T> {
+    pub fn get(&self) -> Option<&T> {
+        self.value.as_ref()
+    }
+
+    pub fn is_loaded(&self) -> bool {
+        self.value.is_some()
+    }
+
+    pub fn bytes(&self) -> usize {
+        self.bytes
+    }
+
+    pub fn last_used(&self) -> Option<Instant> {
+        self.last_used
+    }
+
+    /// Mark the value as used, loading it first if needed. `load` returns
+    /// the value and its approximate size in bytes, or None if it can't be
+    /// loaded right now.
+    pub fn load_with(&mut self, now: Instant, load: impl FnOnce() -> Option<(T, usize)>) {
+        self.last_used = Some(now);
+        if self.value.is_none() {
+            if let Some((value, bytes)) = load() {
+                self.value = Some(value);
+                self.bytes = bytes;
+            }
+        }
+    }
+
+    /// Drop the value; the next `load_with` loads it again
+    pub fn unload(&mut self) {
+        self.value = None;
+        self.bytes = 0;
+    }
+}
+
+/// Demand-loaded state owned by the overlay, apart from the game icon which
+/// lives with the achievement tracker
+#[derive(Default)]
+pub struct ScreenResources {
+    /// Screen open on the last update; None while hidden
+    pub screen: Option<OverlayScreen>,
+    pub stats: Lazy<AchievementStats>,
+    /// (achievements, earned, unlocks) the stats were computed from
+    pub stats_source: (usize, usize, usize),
+}
+
+/// Loaded resources against the budget, for the performance HUD
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MemoryUsage {
+    pub loaded: usize,
+    pub bytes: usize,
+    pub budget: usize,
+}
+
+impl MemoryUsage {
+    pub fn over_budget(&self) -> bool {
+        self.bytes > self.budget
+    }
+}
+
+/// Which loaded resources to drop: any unused for `UNLOAD_AFTER`, then the
+/// least recently used ones not `in_use` until the rest fits the budget.
+/// `loaded` holds (resource, bytes, last used).
+pub fn plan_unloads(
+    loaded: &[(Resource, usize, Option<Instant>)],
+    in_use: &[Resource],
+    now: Instant,
+    budget: usize,
+) -> Vec<Resource> {
+    let stale = |last_used: Option<Instant>| {
+        last_used.is_none_or(|t| now.duration_since(t) >= UNLOAD_AFTER)
+    };
+
+    let mut unload: Vec<Resource> = loaded
+        .iter()
+        .filter(|(resource, _, last_used)| !in_use.contains(resource) && stale(*last_used))
+        .map(|(resource, _, _)| *resource)
+        .collect();
+
+    let mut total: usize = loaded
+        .iter()
+        .filter(|(r, _, _)| !unload.contains(r))
+        .map(|(_, bytes, _)| bytes)
+        .sum();
+    let mut candidates: Vec<_> = loaded
+        .iter()
+        .filter(|(resource, _, _)| !in_use.contains(resource) && !unload.contains(resource))
+        .collect();
+    candidates.sort_by_key(|(_, _, last_used)| *last_used);
+    for (resource, bytes, _) in candidates {
+        if total <= budget {
+            break;
+        }
+        total -= bytes;
+        unload.push(*resource);
+    }
+    unload
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plan_unloads() {
+        let now = Instant::now() + UNLOAD_AFTER * 2;
+        let fresh = Some(now - Duration::from_secs(10));
+        let stale = Some(now - UNLOAD_AFTER);
+        let loaded = [
+            (Resource::GameIcon, 1000, stale),
+            (Resource::AchievementStats, 100, fresh),
+        ];
+
+        // Stale and not on screen
+        assert_eq!(
+            plan_unloads(&loaded, &[], now, MEMORY_BUDGET),
+            vec![Resource::GameIcon]
+        );
+        // On screen, so kept however old
+        assert!(plan_unloads(&loaded, &[Resource::GameIcon], now, MEMORY_BUDGET).is_empty());
+
+        // Over budget: the older resource goes even though it's recent
+        let loaded = [
+            (
+                Resource::GameIcon,
+                1000,
+                Some(now - Duration::from_secs(20)),
+            ),
+            (Resource::AchievementStats, 100, fresh),
+        ];
+        assert_eq!(
+            plan_unloads(&loaded, &[], now, 500),
+            vec![Resource::GameIcon]
+        );
+        assert_eq!(
+            plan_unloads(&loaded, &[Resource::GameIcon], now, 500),
+            vec![Resource::AchievementStats]
+        );
+    }
+
+    #[test]
+    fn test_lazy_loads_once() {
+        let now = Instant::now();
+        let mut lazy: Lazy<u32> = Lazy::default();
+        lazy.load_with(now, || Some((7, 4)));
+        lazy.load_with(now, || panic!("already loaded"));
+        assert_eq!((lazy.get(), lazy.bytes()), (Some(&7), 4));
+
+        lazy.unload();
+        assert!(!lazy.is_loaded());
+        assert_eq!(lazy.last_used(), Some(now));
+    }
+}
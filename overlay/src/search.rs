@@ -0,0 +1,392 @@
+//! Global search across games, achievements and settings
+//!
+//! The index is built when the search screen opens from the visible menu
+//! items, the settings screen, the achievements of the current game and the
+//! game list the BIOS sends over IPC. Results update as the query is typed on
+//! the on-screen keyboard; picking one jumps straight to its screen.
+
+use crate::input::ControllerInput;
+use crate::ipc::{AchievementInfo, GameEntry};
+use crate::login::OnScreenKeyboard;
+use crate::menu_config::MenuItemId;
+
+/// Number of result rows visible at once
+pub const SEARCH_VISIBLE_ROWS: usize = 6;
+
+/// Results beyond this many are dropped
+const MAX_RESULTS: usize = 50;
+
+/// Searchable rows of the settings screen, in the order it lists them
+const SETTINGS_ITEMS: &[&str] = &[
+    "Menu Customization",
+    "Theme Selection",
+    "Stream Mode",
+    "RetroAchievements Login",
+    "Log Out",
+];
+
+/// Index of "Log Out" in `SETTINGS_ITEMS`, only offered while logged in
+const LOGOUT_SETTING: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchCategory {
+    Menu,
+    Setting,
+    Achievement,
+    Game,
+}
+
+impl SearchCategory {
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            SearchCategory::Menu => "MENU",
+            SearchCategory::Setting => "SETTING",
+            SearchCategory::Achievement => "ACHIEVEMENT",
+            SearchCategory::Game => "GAME",
+        }
+    }
+}
+
+/// Where a search result leads
+#[derive(Debug, Clone, PartialEq)]
+pub enum SearchTarget {
+    Menu(MenuItemId),
+    /// Row of the settings screen
+    Setting(usize),
+    /// Index into the tracker's achievement list
+    Achievement(usize),
+    /// Show only the achievements containing the query
+    FilterAchievements(String),
+    Game(GameEntry),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchEntry {
+    pub label: String,
+    /// Secondary text, also searched (achievement descriptions)
+    pub detail: String,
+    pub category: SearchCategory,
+    pub target: SearchTarget,
+}
+
+/// Score how well `query` matches `candidate`, or `None` if it doesn't.
+///
+/// Every query character must appear in order (case-insensitive, whitespace in
+/// the query is ignored). Consecutive characters, word starts and a plain
+/// substring match score higher.
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<u32> {
+    let query: Vec<char> = query
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .flat_map(char::to_lowercase)
+        .collect();
+    if query.is_empty() {
+        return Some(0);
+    }
+
+    let candidate: Vec<char> = candidate.chars().flat_map(char::to_lowercase).collect();
+    let mut score = 0;
+    let mut next = 0;
+    let mut last_match: Option<usize> = None;
+
+    for (i, &c) in candidate.iter().enumerate() {
+        if next == query.len() {
+            break;
+        }
+        if c != query[next] {
+            continue;
+        }
+
+        score += 1;
+        if last_match.is_some_and(|last| last + 1 == i) {
+            score += 5;
+        }
+        if i == 0 || !candidate[i - 1].is_alphanumeric() {
+            score += 8;
+        }
+        last_match = Some(i);
+        next += 1;
+    }
+
+    if next < query.len() {
+        return None;
+    }
+
+    let query: String = query.into_iter().collect();
+    let candidate: String = candidate.into_iter().collect();
+    if candidate.contains(&query) {
+        score += 20;
+    }
+    Some(score)
+}
+
+/// Build the searchable entries
+pub fn build_index(
+    menu_items: &[MenuItemId],
+    logged_in: bool,
+    achievements: &[AchievementInfo],
+    games: &[GameEntry],
+) -> Vec<SearchEntry> {
+    let mut entries = Vec::new();
+
+    for &id in menu_items {
+        entries.push(SearchEntry {
+            label: id.display_name().to_string(),
+            detail: String::new(),
+            category: SearchCategory::Menu,
+            target: SearchTarget::Menu(id),
+        });
+    }
+
+    for (i, &label) in SETTINGS_ITEMS.iter().enumerate() {
+        if i == LOGOUT_SETTING && !logged_in {
+            continue;
+        }
+        entries.push(SearchEntry {
+            label: label.to_string(),
+            detail: String::new(),
+            category: SearchCategory::Setting,
+            target: SearchTarget::Setting(i),
+        });
+    }
+
+    for (i, achievement) in achievements.iter().enumerate() {
+        entries.push(SearchEntry {
+            label: achievement.title.clone(),
+            detail: achievement.description.clone(),
+            category: SearchCategory::Achievement,
+            target: SearchTarget::Achievement(i),
+        });
+    }
+
+    for game in games {
+        entries.push(SearchEntry {
+            label: game.name.clone(),
+            detail: String::new(),
+            category: SearchCategory::Game,
+            target: SearchTarget::Game(game.clone()),
+        });
+    }
+
+    entries
+}
+
+/// What the state should do after a search input
+#[derive(Debug, Clone, PartialEq)]
+pub enum SearchAction {
+    None,
+    Close,
+    Open(SearchTarget),
+}
+
+/// State of the search screen
+pub struct SearchView {
+    pub query: String,
+    /// Open while typing; closed to browse the results
+    pub keyboard: Option<OnScreenKeyboard>,
+    pub results: Vec<SearchEntry>,
+    pub selected: usize,
+    pub scroll_offset: usize,
+    entries: Vec<SearchEntry>,
+}
+
+impl SearchView {
+    pub fn open(entries: Vec<SearchEntry>) -> Self {
+        Self {
+            query: String::new(),
+            keyboard: Some(OnScreenKeyboard::new()),
+            results: Vec::new(),
+            selected: 0,
+            scroll_offset: 0,
+            entries,
+        }
+    }
+
+    /// Re-rank the index against the current query
+    fn update_results(&mut self) {
+        self.selected = 0;
+        self.scroll_offset = 0;
+        self.results.clear();
+        if self.query.trim().is_empty() {
+            return;
+        }
+
+        let mut scored: Vec<(u32, &SearchEntry)> = self
+            .entries
+            .iter()
+            .filter_map(|entry| {
+                let label = fuzzy_score(&self.query, &entry.label);
+                // Description matches rank below title matches
+                let detail = fuzzy_score(&self.query, &entry.detail).map(|s| s / 2);
+                label.max(detail).map(|score| (score, entry))
+            })
+            .collect();
+        scored.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.label.len().cmp(&b.1.label.len())));
+
+        // Offer the plain-text filter the achievements screen applies
+        let query = self.query.trim().to_lowercase();
+        let filter_matches = self
+            .entries
+            .iter()
+            .filter(|e| e.category == SearchCategory::Achievement)
+            .filter(|e| {
+                e.label.to_lowercase().contains(&query) || e.detail.to_lowercase().contains(&query)
+            })
+            .count();
+        if filter_matches > 0 {
+            self.results.push(SearchEntry {
+                label: format!(
+                    "Achievements containing \"{}\" ({})",
+                    self.query.trim(),
+                    filter_matches
+                ),
+                detail: String::new(),
+                category: SearchCategory::Achievement,
+                target: SearchTarget::FilterAchievements(self.query.trim().to_string()),
+            });
+        }
+
+        self.results
+            .extend(scored.into_iter().map(|(_, entry)| entry.clone()));
+        self.results.truncate(MAX_RESULTS);
+    }
+
+    pub fn selected_result(&self) -> Option<&SearchEntry> {
+        self.results.get(self.selected)
+    }
+
+    /// Apply one input
+    pub fn handle_input(&mut self, input: ControllerInput) -> SearchAction {
+        if let Some(keyboard) = &mut self.keyboard {
+            let before = self.query.clone();
+            if keyboard.handle_input(input, &mut self.query) {
+                self.keyboard = None;
+            }
+            if self.query != before {
+                self.update_results();
+            }
+            return SearchAction::None;
+        }
+
+        match input {
+            ControllerInput::Up => self.selected = self.selected.saturating_sub(1),
+            ControllerInput::Down if self.selected + 1 < self.results.len() => self.selected += 1,
+            ControllerInput::Select => {
+                if let Some(result) = self.selected_result() {
+                    return SearchAction::Open(result.target.clone());
+                }
+            }
+            ControllerInput::Secondary => self.keyboard = Some(OnScreenKeyboard::new()),
+            ControllerInput::Back => return SearchAction::Close,
+            _ => {}
+        }
+
+        if self.selected < self.scroll_offset {
+            self.scroll_offset = self.selected;
+        } else if self.selected >= self.scroll_offset + SEARCH_VISIBLE_ROWS {
+            self.scroll_offset = self.selected + 1 - SEARCH_VISIBLE_ROWS;
+        }
+        SearchAction::None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn achievement(title: &str, description: &str) -> AchievementInfo {
+        AchievementInfo {
+            id: 1,
+            title: title.to_string(),
+            description: description.to_string(),
+            points: 10,
+            earned: false,
+            earned_hardcore: false,
+            rarity_percent: None,
+            earned_at: None,
+            progress: None,
+            unofficial: false,
+        }
+    }
+
+    fn view_with_query(query: &str) -> SearchView {
+        let games = vec![GameEntry {
+            cart_id: "celeste".to_string(),
+            name: "Celeste".to_string(),
+        }];
+        let achievements = vec![achievement(
+            "Strawberry Thief",
+            "Collect a strawberry in chapter 1",
+        )];
+        let mut view = SearchView::open(build_index(
+            &MenuItemId::all(),
+            false,
+            &achievements,
+            &games,
+        ));
+        view.query = query.to_string();
+        view.update_results();
+        view
+    }
+
+    #[test]
+    fn test_fuzzy_score() {
+        assert!(fuzzy_score("thm", "Theme Selection").is_some());
+        assert!(fuzzy_score("xyz", "Theme Selection").is_none());
+        assert_eq!(fuzzy_score("", "anything"), Some(0));
+
+        // Substrings and word starts beat scattered letters
+        let substring = fuzzy_score("play", "Playtime").unwrap();
+        let scattered = fuzzy_score("play", "Performance Display").unwrap();
+        assert!(substring > scattered);
+        assert!(fuzzy_score("qs", "Quick Save") > fuzzy_score("qs", "Quests"));
+    }
+
+    #[test]
+    fn test_results_across_categories() {
+        let view = view_with_query("cel");
+        assert_eq!(view.results[0].category, SearchCategory::Game);
+
+        // Logged out, so Log Out isn't offered
+        let view = view_with_query("log out");
+        assert!(view
+            .results
+            .iter()
+            .all(|r| r.target != SearchTarget::Setting(LOGOUT_SETTING)));
+
+        // Achievement matches come with a filter entry first
+        let view = view_with_query("straw");
+        assert_eq!(
+            view.results[0].target,
+            SearchTarget::FilterAchievements("straw".to_string())
+        );
+        assert!(view
+            .results
+            .iter()
+            .any(|r| r.target == SearchTarget::Achievement(0)));
+    }
+
+    #[test]
+    fn test_typing_then_opening_a_result() {
+        let mut view = view_with_query("");
+        assert!(view.results.is_empty());
+
+        // Keyboard starts on the "q" row; type "q" to match Quick Save and Quit
+        view.handle_input(ControllerInput::Select);
+        assert_eq!(view.query, "q");
+        assert!(!view.results.is_empty());
+
+        // Closing the keyboard hands the controls to the result list
+        view.handle_input(ControllerInput::RB);
+        assert!(view.keyboard.is_none());
+        let first = view.results[0].target.clone();
+        assert_eq!(
+            view.handle_input(ControllerInput::Select),
+            SearchAction::Open(first)
+        );
+        assert_eq!(
+            view.handle_input(ControllerInput::Back),
+            SearchAction::Close
+        );
+    }
+}
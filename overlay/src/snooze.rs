@@ -0,0 +1,175 @@
+//! Snoozed notifications
+//!
+//! While snoozed, toasts of the chosen styles (by default Success, which
+//! achievement unlocks use) don't pop up over the game. They're held back
+//! and shown as a single summary toast the next time the overlay opens.
+//! They still go to the notification history as they arrive.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+use crate::ipc::ToastStyle;
+
+/// Messages named in a summary before it says "and N more"
+const SUMMARY_MESSAGES: usize = 2;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct SnoozeConfig {
+    pub enabled: bool,
+    /// Styles held back while snoozed
+    pub info: bool,
+    pub success: bool,
+    pub warning: bool,
+    pub error: bool,
+    pub version: u32,
+}
+
+impl Default for SnoozeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            info: false,
+            success: true,
+            warning: false,
+            error: false,
+            version: 1,
+        }
+    }
+}
+
+impl SnoozeConfig {
+    pub fn load() -> Result<Self> {
+        let path = Self::config_path()?;
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let contents = fs::read_to_string(&path).context("Failed to read snooze config file")?;
+        serde_json::from_str(&contents).context("Failed to parse snooze config JSON")
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = Self::config_path()?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).context("Failed to create overlay config directory")?;
+        }
+        let json =
+            serde_json::to_string_pretty(self).context("Failed to serialize snooze config")?;
+        fs::write(&path, json).context("Failed to write snooze config file")?;
+        Ok(())
+    }
+
+    fn config_path() -> Result<PathBuf> {
+        let data_dir =
+            dirs::data_local_dir().context("Could not determine local data directory")?;
+        Ok(data_dir
+            .join("kazeta-plus")
+            .join("overlay")
+            .join("snooze.json"))
+    }
+
+    /// Whether a toast of this style is held back right now
+    pub fn snoozes(&self, style: ToastStyle) -> bool {
+        self.enabled && self.style(style)
+    }
+
+    /// Whether the style is one snoozing holds back
+    pub fn style(&self, style: ToastStyle) -> bool {
+        match style {
+            ToastStyle::Info => self.info,
+            ToastStyle::Success => self.success,
+            ToastStyle::Warning => self.warning,
+            ToastStyle::Error => self.error,
+        }
+    }
+
+    pub fn toggle_style(&mut self, style: ToastStyle) {
+        let flag = match style {
+            ToastStyle::Info => &mut self.info,
+            ToastStyle::Success => &mut self.success,
+            ToastStyle::Warning => &mut self.warning,
+            ToastStyle::Error => &mut self.error,
+        };
+        *flag = !*flag;
+    }
+}
+
+/// Styles in the order the settings screen lists them
+pub const STYLES: [ToastStyle; 4] = [
+    ToastStyle::Success,
+    ToastStyle::Info,
+    ToastStyle::Warning,
+    ToastStyle::Error,
+];
+
+/// One toast for everything held back: the message itself when there's
+/// only one, else a count and the first few. Takes the most severe style.
+pub fn summary(held: &[(String, ToastStyle)]) -> Option<(String, ToastStyle)> {
+    let severity = |style: &ToastStyle| match style {
+        ToastStyle::Info => 0,
+        ToastStyle::Success => 1,
+        ToastStyle::Warning => 2,
+        ToastStyle::Error => 3,
+    };
+    let style = held.iter().map(|(_, style)| *style).max_by_key(severity)?;
+    if let [(message, _)] = held {
+        return Some((message.clone(), style));
+    }
+
+    let named: Vec<&str> = held
+        .iter()
+        .take(SUMMARY_MESSAGES)
+        .map(|(message, _)| message.as_str())
+        .collect();
+    let mut message = format!("{} while snoozed: {}", held.len(), named.join(" · "));
+    if held.len() > SUMMARY_MESSAGES {
+        message.push_str(&format!(" and {} more", held.len() - SUMMARY_MESSAGES));
+    }
+    Some((message, style))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_snoozes_only_chosen_styles_while_enabled() {
+        let mut config = SnoozeConfig::default();
+        assert!(!config.snoozes(ToastStyle::Success));
+
+        config.enabled = true;
+        assert!(config.snoozes(ToastStyle::Success));
+        assert!(!config.snoozes(ToastStyle::Error));
+
+        config.toggle_style(ToastStyle::Error);
+        config.toggle_style(ToastStyle::Success);
+        assert!(config.snoozes(ToastStyle::Error));
+        assert!(!config.snoozes(ToastStyle::Success));
+    }
+
+    #[test]
+    fn test_summary() {
+        assert_eq!(summary(&[]), None);
+
+        let one = vec![("🏆 First Blood (5 points)".to_string(), ToastStyle::Success)];
+        assert_eq!(
+            summary(&one),
+            Some(("🏆 First Blood (5 points)".to_string(), ToastStyle::Success))
+        );
+
+        let many = vec![
+            ("🏆 A (5 points)".to_string(), ToastStyle::Success),
+            ("Battery low".to_string(), ToastStyle::Warning),
+            ("🏆 B (10 points)".to_string(), ToastStyle::Success),
+        ];
+        assert_eq!(
+            summary(&many),
+            Some((
+                "3 while snoozed: 🏆 A (5 points) · Battery low and 1 more".to_string(),
+                ToastStyle::Warning
+            ))
+        );
+    }
+}
@@ -0,0 +1,440 @@
+//! Speedrun timer with splits and per-game best times
+//!
+//! Split names come from `speedrun/<cart id>.txt` in the overlay data
+//! directory, one per line (blank lines and `#` comments are ignored). Without
+//! that file a run has a single "Finish" split. Hitting the last split ends
+//! the run, and its time is kept as the game's best if it beats the previous
+//! one, along with the time of each split.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Split used when a game has no split file
+const DEFAULT_SPLIT: &str = "Finish";
+
+/// Best run of a game
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct BestRun {
+    pub total_ms: u64,
+    /// Time at each split, from the start of the run
+    #[serde(default)]
+    pub splits_ms: Vec<u64>,
+}
+
+/// Speedrun timer configuration
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SpeedrunConfig {
+    /// Keep a compact timer on screen while the overlay is closed
+    pub widget: bool,
+    /// Best runs keyed by cart id
+    #[serde(default)]
+    pub best: HashMap<String, BestRun>,
+    pub version: u32,
+}
+
+impl Default for SpeedrunConfig {
+    fn default() -> Self {
+        Self {
+            widget: true,
+            best: HashMap::new(),
+            version: 1,
+        }
+    }
+}
+
+impl SpeedrunConfig {
+    /// Keep a finished run if it is the game's first or fastest.
+    /// Returns true if it became the new best.
+    pub fn record(&mut self, cart_id: &str, total: Duration, splits: &[Duration]) -> bool {
+        let total_ms = total.as_millis() as u64;
+        if self
+            .best
+            .get(cart_id)
+            .is_some_and(|best| best.total_ms <= total_ms)
+        {
+            return false;
+        }
+        self.best.insert(
+            cart_id.to_string(),
+            BestRun {
+                total_ms,
+                splits_ms: splits.iter().map(|s| s.as_millis() as u64).collect(),
+            },
+        );
+        true
+    }
+}
+
+/// A pausable stopwatch
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Stopwatch {
+    started: Option<Instant>,
+    banked: Duration,
+}
+
+impl Stopwatch {
+    pub fn is_running(&self) -> bool {
+        self.started.is_some()
+    }
+
+    pub fn start(&mut self, now: Instant) {
+        if self.started.is_none() {
+            self.started = Some(now);
+        }
+    }
+
+    pub fn pause(&mut self, now: Instant) {
+        if let Some(started) = self.started.take() {
+            self.banked += now.saturating_duration_since(started);
+        }
+    }
+
+    pub fn elapsed(&self, now: Instant) -> Duration {
+        self.banked
+            + self
+                .started
+                .map_or(Duration::ZERO, |s| now.saturating_duration_since(s))
+    }
+}
+
+/// Where a run is at
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RunState {
+    Idle,
+    Running,
+    Paused,
+    Finished,
+}
+
+/// One attempt through a game's splits
+#[derive(Debug, Clone)]
+pub struct Run {
+    names: Vec<String>,
+    splits: Vec<Duration>,
+    stopwatch: Stopwatch,
+}
+
+impl Run {
+    pub fn new(names: Vec<String>) -> Self {
+        let names = if names.is_empty() {
+            vec![DEFAULT_SPLIT.to_string()]
+        } else {
+            names
+        };
+        Self {
+            names,
+            splits: Vec::new(),
+            stopwatch: Stopwatch::default(),
+        }
+    }
+
+    pub fn state(&self) -> RunState {
+        if self.splits.len() == self.names.len() {
+            RunState::Finished
+        } else if self.stopwatch.is_running() {
+            RunState::Running
+        } else if self.stopwatch.banked > Duration::ZERO {
+            RunState::Paused
+        } else {
+            RunState::Idle
+        }
+    }
+
+    /// Start or pause the timer. Does nothing once the run is finished.
+    pub fn toggle(&mut self, now: Instant) {
+        match self.state() {
+            RunState::Running => self.stopwatch.pause(now),
+            RunState::Idle | RunState::Paused => self.stopwatch.start(now),
+            RunState::Finished => {}
+        }
+    }
+
+    /// Record the current split while the timer runs. Returns the final time
+    /// when this was the last split.
+    pub fn split(&mut self, now: Instant) -> Option<Duration> {
+        if self.state() != RunState::Running {
+            return None;
+        }
+        let time = self.stopwatch.elapsed(now);
+        self.splits.push(time);
+        if self.splits.len() == self.names.len() {
+            self.stopwatch.pause(now);
+            return Some(time);
+        }
+        None
+    }
+
+    pub fn reset(&mut self) {
+        self.splits.clear();
+        self.stopwatch = Stopwatch::default();
+    }
+
+    pub fn elapsed(&self, now: Instant) -> Duration {
+        self.stopwatch.elapsed(now)
+    }
+
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    pub fn splits(&self) -> &[Duration] {
+        &self.splits
+    }
+}
+
+fn parse_split_names(contents: &str) -> Vec<String> {
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Split names for a game, or none if it has no split file
+fn load_split_names(dir: &Path, cart_id: &str) -> Vec<String> {
+    fs::read_to_string(dir.join(format!("{}.txt", cart_id)))
+        .map(|contents| parse_split_names(&contents))
+        .unwrap_or_default()
+}
+
+/// Formats a time as `M:SS.cc`, or `H:MM:SS.cc` past an hour
+pub fn format_time(time: Duration) -> String {
+    let centis = time.as_millis() / 10;
+    let (hours, minutes, seconds) = (centis / 360_000, centis / 6000 % 60, centis / 100 % 60);
+    if hours > 0 {
+        format!(
+            "{}:{:02}:{:02}.{:02}",
+            hours,
+            minutes,
+            seconds,
+            centis % 100
+        )
+    } else {
+        format!("{}:{:02}.{:02}", minutes, seconds, centis % 100)
+    }
+}
+
+/// Formats the difference to a best time as `+S.cc` / `-S.cc`
+pub fn format_delta(time: Duration, best_ms: u64) -> String {
+    let time_ms = time.as_millis() as u64;
+    let (sign, diff) = if time_ms >= best_ms {
+        ('+', time_ms - best_ms)
+    } else {
+        ('-', best_ms - time_ms)
+    };
+    format!("{}{}.{:02}", sign, diff / 1000, diff % 1000 / 10)
+}
+
+/// The speedrun timer for the running game
+pub struct Speedrun {
+    config: SpeedrunConfig,
+    config_path: PathBuf,
+    splits_dir: PathBuf,
+    cart_id: Option<String>,
+    run: Run,
+}
+
+impl Speedrun {
+    /// Create new Speedrun with config loaded from disk
+    pub fn new() -> Result<Self> {
+        let config_path = Self::get_config_path()?;
+
+        let config = if config_path.exists() {
+            println!("[Speedrun] Loading config from {:?}", config_path);
+            Self::load_config(&config_path)?
+        } else {
+            let default_config = SpeedrunConfig::default();
+            if let Err(e) = Self::save_config(&config_path, &default_config) {
+                eprintln!("[Speedrun] Failed to save default config: {}", e);
+            }
+            default_config
+        };
+
+        let splits_dir = config_path.with_file_name("speedrun");
+
+        Ok(Self {
+            config,
+            config_path,
+            splits_dir,
+            cart_id: None,
+            run: Run::new(Vec::new()),
+        })
+    }
+
+    /// Get the configuration file path
+    fn get_config_path() -> Result<PathBuf> {
+        let data_dir =
+            dirs::data_local_dir().context("Could not determine local data directory")?;
+
+        let overlay_dir = data_dir.join("kazeta-plus").join("overlay");
+        if !overlay_dir.exists() {
+            fs::create_dir_all(&overlay_dir)
+                .context("Failed to create overlay config directory")?;
+        }
+
+        Ok(overlay_dir.join("speedrun.json"))
+    }
+
+    fn load_config(path: &PathBuf) -> Result<SpeedrunConfig> {
+        let contents = fs::read_to_string(path).context("Failed to read speedrun config file")?;
+        serde_json::from_str(&contents).context("Failed to parse speedrun config JSON")
+    }
+
+    fn save_config(path: &PathBuf, config: &SpeedrunConfig) -> Result<()> {
+        let json =
+            serde_json::to_string_pretty(config).context("Failed to serialize speedrun config")?;
+        fs::write(path, json).context("Failed to write speedrun config file")?;
+        Ok(())
+    }
+
+    /// Save current configuration to disk
+    pub fn save(&self) -> Result<()> {
+        Self::save_config(&self.config_path, &self.config)
+    }
+
+    pub fn config(&self) -> &SpeedrunConfig {
+        &self.config
+    }
+
+    pub fn config_mut(&mut self) -> &mut SpeedrunConfig {
+        &mut self.config
+    }
+
+    /// Load the game's splits and start from a fresh run
+    pub fn game_started(&mut self, cart_id: &str) {
+        let names = load_split_names(&self.splits_dir, cart_id);
+        if !names.is_empty() {
+            println!("[Speedrun] Loaded {} splits for {}", names.len(), cart_id);
+        }
+        self.cart_id = Some(cart_id.to_string());
+        self.run = Run::new(names);
+    }
+
+    pub fn game_stopped(&mut self) {
+        self.cart_id = None;
+        self.run = Run::new(Vec::new());
+    }
+
+    pub fn run(&self) -> &Run {
+        &self.run
+    }
+
+    /// Best run of the current game
+    pub fn best(&self) -> Option<&BestRun> {
+        self.cart_id
+            .as_ref()
+            .and_then(|id| self.config.best.get(id))
+    }
+
+    pub fn toggle(&mut self) {
+        self.run.toggle(Instant::now());
+    }
+
+    /// Record a split. Returns true when it finished the run with a new best.
+    pub fn split(&mut self) -> bool {
+        let Some(total) = self.run.split(Instant::now()) else {
+            return false;
+        };
+        let Some(cart_id) = &self.cart_id else {
+            return false;
+        };
+
+        println!("[Speedrun] Finished {} in {}", cart_id, format_time(total));
+        if !self.config.record(cart_id, total, self.run.splits()) {
+            return false;
+        }
+        if let Err(e) = self.save() {
+            eprintln!("[Speedrun] Failed to save best time: {}", e);
+        }
+        true
+    }
+
+    pub fn reset(&mut self) {
+        self.run.reset();
+    }
+
+    /// Whether the compact timer should be drawn while the overlay is closed
+    pub fn widget_visible(&self) -> bool {
+        self.config.widget && self.run.state() != RunState::Idle
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stopwatch_excludes_paused_time() {
+        let start = Instant::now();
+        let mut run = Run::new(Vec::new());
+        assert_eq!(run.state(), RunState::Idle);
+
+        run.toggle(start);
+        run.toggle(start + Duration::from_secs(2));
+        assert_eq!(
+            run.elapsed(start + Duration::from_secs(60)),
+            Duration::from_secs(2)
+        );
+
+        run.toggle(start + Duration::from_secs(60));
+        assert_eq!(
+            run.elapsed(start + Duration::from_secs(61)),
+            Duration::from_secs(3)
+        );
+
+        run.reset();
+        assert_eq!(run.state(), RunState::Idle);
+        assert_eq!(run.elapsed(start + Duration::from_secs(61)), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_last_split_finishes_run() {
+        let names = parse_split_names("# Any%\nForest\n\n  Castle \n");
+        assert_eq!(names, vec!["Forest", "Castle"]);
+
+        let start = Instant::now();
+        let mut run = Run::new(names);
+        // Splits only count while the timer runs
+        assert_eq!(run.split(start), None);
+
+        run.toggle(start);
+        assert_eq!(run.split(start + Duration::from_secs(30)), None);
+        assert_eq!(
+            run.split(start + Duration::from_secs(75)),
+            Some(Duration::from_secs(75))
+        );
+        assert_eq!(run.state(), RunState::Finished);
+        assert_eq!(
+            run.splits(),
+            &[Duration::from_secs(30), Duration::from_secs(75)]
+        );
+
+        // A finished run stays stopped until it is reset
+        run.toggle(start + Duration::from_secs(80));
+        assert_eq!(run.state(), RunState::Finished);
+    }
+
+    #[test]
+    fn test_only_faster_runs_become_best() {
+        let mut config = SpeedrunConfig::default();
+        let splits = [Duration::from_secs(40), Duration::from_secs(90)];
+        assert!(config.record("celeste", Duration::from_secs(90), &splits));
+        assert!(!config.record("celeste", Duration::from_secs(95), &splits));
+        assert!(config.record("celeste", Duration::from_millis(89_500), &splits));
+        assert_eq!(config.best["celeste"].total_ms, 89_500);
+        assert_eq!(config.best["celeste"].splits_ms, vec![40_000, 90_000]);
+    }
+
+    #[test]
+    fn test_time_formatting() {
+        assert_eq!(format_time(Duration::from_millis(75_430)), "1:15.43");
+        assert_eq!(format_time(Duration::from_millis(3_723_010)), "1:02:03.01");
+        assert_eq!(format_delta(Duration::from_millis(31_250), 30_000), "+1.25");
+        assert_eq!(format_delta(Duration::from_millis(29_000), 30_000), "-1.00");
+    }
+}
@@ -1,15 +1,91 @@
-use crate::ipc::{AchievementInfo, OverlayMessage, OverlayScreen, ToastStyle};
+use crate::backdrop::{self, Backdrop};
+use crate::battery::BatteryMonitor;
+use crate::calibration::{self, CalibrationManager, ControllerCalibration, DriftWizard};
+use crate::celebration::Celebration;
+use crate::cheats::CheatList;
+use crate::compare::CompareView;
+use crate::controllers::{
+    ControllerState, InputDevice, LaunchPlayers, CONTROLLER_MENU_OPTIONS, CONTROLLER_SETTINGS_ROWS,
+    LAUNCH_PLAYERS_FILE, MAX_PLAYERS,
+};
+use crate::controls::ControlsCache;
+use crate::debug_console::DebugConsole;
+use crate::game_icon::{self, GameIcon};
+use crate::haptics::{self, HapticPattern, Haptics};
+use crate::hints::HintsView;
+use crate::hotkeys::HotkeyAction;
+use crate::hud_layout::{HudLayout, HudLayoutManager, HudWidget, DEFAULT_LAYOUT};
 use crate::input::ControllerInput;
-use crate::controllers::{ControllerState, CONTROLLER_MENU_OPTIONS, MAX_PLAYERS};
-use crate::menu_config::{MenuConfigManager, MenuItemId};
+use crate::ipc::{
+    self, AchievementInfo, ChallengeSetInfo, ControlHint, GameEntry, IncomingMessage, IpcStats,
+    OverlayMessage, OverlayScreen, PointerAction, SessionPlayer, ToastStyle, UnlockRecord,
+};
+use crate::ipc_rules::{IpcRules, RuleAction};
+use crate::login::{self, LoginView};
+use crate::macros::{self, MacroSummary};
+use crate::menu_config::{
+    MenuConfigManager, MenuItemId, QuickAction, MAX_QUICK_ACTIONS, MIN_QUICK_ACTIONS,
+};
+use crate::music::{self, Jukebox, MusicSource};
+use crate::network::NetworkMonitor;
+use crate::onboarding::{self, Onboarding, Tour};
+use crate::pause::{self, AutoPause};
 use crate::performance::PerformanceStats;
 use crate::playtime::PlaytimeTracker;
+use crate::pointer::{Pointer, PointerTarget, MAIN_MENU_LAYOUT, SETTINGS_LAYOUT};
+use crate::power::{PowerManager, PowerMode};
+use crate::quick_actions;
+use crate::quit::{GracefulQuit, QuitAction, SaveOutcome};
+use crate::recovery::{Recovery, SessionSnapshot};
+use crate::resources::{self, MemoryUsage, Resource, ScreenResources};
+use crate::search::{self, SearchAction, SearchTarget, SearchView};
+use crate::snooze::{self, SnoozeConfig};
+use crate::speedrun::{self, Speedrun};
+use crate::stats::AchievementStats;
+use crate::stream_output::StreamOutput;
 use crate::theme_config::ThemeConfigManager;
-use macroquad::prelude::*;
-use std::time::{Duration, Instant};
-use std::collections::{VecDeque, HashSet};
-use kazeta_ra::{CredentialManager, RAClient};
+use crate::theme_editor::{ThemeEditor, ThemeEditorAction};
+use crate::themes::ColorVision;
+use crate::watchdog::ProcessWatchdog;
+use kazeta_ra::cache::RACache;
+use kazeta_ra::session::GameSession;
+use kazeta_ra::translations::Translations;
 use kazeta_ra::types::GameInfoAndProgress;
+use kazeta_ra::{CredentialManager, RAClient};
+use macroquad::prelude::*;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Number of achievement rows visible at once on the achievements screen
+pub const ACHIEVEMENTS_VISIBLE_ROWS: usize = 6;
+
+/// Number of options on the settings screen
+pub const SETTINGS_OPTIONS: usize = 20;
+
+/// Rows of the theme preview: colors, dimming, panel opacity, blur
+pub const THEME_PREVIEW_ROWS: usize = 4;
+
+/// Achievements shown by the pinned achievements HUD widget
+pub const PINNED_ACHIEVEMENTS: usize = 3;
+
+/// Number of options visible at once on the settings screen
+pub const SETTINGS_VISIBLE_ROWS: usize = 7;
+
+/// IPC log lines shown at once on the debug console
+pub const DEBUG_LOG_VISIBLE_ROWS: usize = 10;
+
+/// Options on the speedrun screen: Start/Pause, Split, Reset, Widget
+pub const SPEEDRUN_OPTIONS: usize = 4;
+
+/// Number of achievement rows visible at once on the friend comparison screen
+pub const COMPARE_VISIBLE_ROWS: usize = 10;
+
+/// Number of challenge rows visible at once on the challenges screen
+pub const CHALLENGES_VISIBLE_ROWS: usize = 7;
+
+/// Rows above the track list on the music screen: Volume, Ducking
+pub const MUSIC_SETTING_ROWS: usize = 2;
 
 /// Represents the achievement completion state
 #[derive(Debug, Clone)]
@@ -18,14 +94,57 @@ pub struct AchievementProgress {
     pub earned: u32,
 }
 
+/// kazeta-ra's difficulty estimate for the current game
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Difficulty {
+    pub score: f32,
+    pub expected_master_secs: Option<u64>,
+}
+
+impl Difficulty {
+    pub fn label(&self) -> &'static str {
+        kazeta_ra::difficulty::difficulty_label(self.score)
+    }
+}
+
 /// Stores achievement data
 pub struct AchievementTracker {
     pub game_id: Option<u32>,
     pub game_title: String,
     pub game_hash: Option<String>,
     pub console: String,
+    /// RA icon path or URL from `ra_game_start`
+    pub icon_url: Option<String>,
+    pub icon: GameIcon,
     pub achievements: Vec<AchievementInfo>,
     pub progress: AchievementProgress,
+    /// Unlock history from `ra_unlock_history`, plus unlocks this session
+    pub unlocks: Vec<UnlockRecord>,
+    pub difficulty: Option<Difficulty>,
+    /// Players of a shared multiplayer session, by slot; empty when playing alone
+    pub players: Vec<SessionPlayer>,
+    /// What each player other than the logged-in account earned this session
+    pub player_earned: HashMap<u8, HashSet<u32>>,
+    /// Position of each achievement in `achievements`, by ID, so single
+    /// updates don't search the list
+    index: HashMap<u32, usize>,
+}
+
+/// One listed row of the achievements screen
+pub struct AchievementRow<'a> {
+    pub achievement: &'a AchievementInfo,
+    pub selected: bool,
+    /// Earned by the player whose tab is open
+    pub earned: bool,
+}
+
+/// How a new achievement list was taken in
+#[derive(Debug, PartialEq)]
+pub enum ListUpdate {
+    /// A different set: everything was replaced
+    Replaced,
+    /// The same achievements in the same order: only these positions changed
+    Patched(Vec<usize>),
 }
 
 impl AchievementTracker {
@@ -35,54 +154,150 @@ impl AchievementTracker {
             game_title: String::new(),
             game_hash: None,
             console: String::new(),
+            icon_url: None,
+            icon: GameIcon::default(),
             achievements: Vec::new(),
             progress: AchievementProgress {
                 total: 0,
                 earned: 0,
             },
+            unlocks: Vec::new(),
+            difficulty: None,
+            players: Vec::new(),
+            player_earned: HashMap::new(),
+            index: HashMap::new(),
         }
     }
 
-    pub fn set_game_info(&mut self, game_id: u32, title: String, console: String) {
-        self.game_id = Some(game_id);
-        self.game_title = title;
-        self.game_hash = None;
-        self.console = console;
-        println!(
-            "[Achievements] Game info set: {} ({}) - ID: {}",
-            self.game_title, self.console, game_id
-        );
-    }
-
-    pub fn set_achievements(&mut self, achievements: Vec<AchievementInfo>) {
-        let earned = achievements.iter().filter(|a| a.earned).count() as u32;
-        let total = achievements.len() as u32;
-
-        self.achievements = achievements;
+    /// Take in a full list. The same set sent again (e.g. after a resync)
+    /// only patches the entries that changed, so the filter, selection and
+    /// scroll position don't have to be rebuilt.
+    pub fn set_achievements(&mut self, achievements: Vec<AchievementInfo>) -> ListUpdate {
+        // Unofficial achievements are listed but don't count towards completion
+        let official = achievements.iter().filter(|a| !a.unofficial);
+        let earned = official.clone().filter(|a| a.earned).count() as u32;
+        let total = official.count() as u32;
         self.progress.total = total;
         self.progress.earned = earned;
 
+        let same_set = achievements.len() == self.achievements.len()
+            && achievements
+                .iter()
+                .zip(&self.achievements)
+                .all(|(new, old)| new.id == old.id);
+        if same_set {
+            let mut changed = Vec::new();
+            for (i, (new, old)) in achievements
+                .into_iter()
+                .zip(self.achievements.iter_mut())
+                .enumerate()
+            {
+                if new != *old {
+                    *old = new;
+                    changed.push(i);
+                }
+            }
+            println!(
+                "[Achievements] Updated {} of {} achievements ({} earned)",
+                changed.len(),
+                total,
+                earned
+            );
+            return ListUpdate::Patched(changed);
+        }
+
+        self.index = achievements
+            .iter()
+            .enumerate()
+            .map(|(i, a)| (a.id, i))
+            .collect();
+        self.achievements = achievements;
         println!(
             "[Achievements] Set {} achievements ({} earned)",
             total, earned
         );
+        ListUpdate::Replaced
     }
 
-    pub fn unlock_achievement(&mut self, achievement_id: u32) {
-        if let Some(achievement) = self
-            .achievements
-            .iter_mut()
-            .find(|a| a.id == achievement_id)
-        {
-            if !achievement.earned {
-                achievement.earned = true;
-                self.progress.earned += 1;
-                println!(
-                    "[Achievements] Unlocked: {} ({} points)",
-                    achievement.title, achievement.points
-                );
+    /// Where an achievement is in the list
+    pub fn position(&self, achievement_id: u32) -> Option<usize> {
+        self.index.get(&achievement_id).copied()
+    }
+
+    /// Mark an achievement earned. Returns its position if that changed it.
+    pub fn unlock_achievement(&mut self, achievement_id: u32) -> Option<usize> {
+        let position = self.position(achievement_id)?;
+        let achievement = &mut self.achievements[position];
+        if achievement.earned {
+            return None;
+        }
+        achievement.earned = true;
+        if !achievement.unofficial {
+            self.progress.earned += 1;
+        }
+        println!(
+            "[Achievements] Unlocked: {} ({} points)",
+            achievement.title, achievement.points
+        );
+        Some(position)
+    }
+
+    /// Replace the session's players, keeping what the ones still playing earned
+    pub fn set_players(&mut self, mut players: Vec<SessionPlayer>) {
+        players.sort_by_key(|p| p.slot);
+        players.dedup_by_key(|p| p.slot);
+        self.player_earned
+            .retain(|slot, _| players.iter().any(|p| p.slot == *slot && !p.account));
+        self.players = players;
+    }
+
+    /// Record an unlock by a session player. The account's go to the list
+    /// itself; everyone else's are kept apart. Returns the position of a
+    /// list entry that changed, like `unlock_achievement`.
+    pub fn player_unlock(&mut self, player: &SessionPlayer, achievement_id: u32) -> Option<usize> {
+        match self.players.iter_mut().find(|p| p.slot == player.slot) {
+            Some(known) => *known = player.clone(),
+            None => {
+                self.players.push(player.clone());
+                self.players.sort_by_key(|p| p.slot);
             }
         }
+
+        if player.account {
+            self.unlock_achievement(achievement_id)
+        } else {
+            self.player_earned
+                .entry(player.slot)
+                .or_default()
+                .insert(achievement_id);
+            None
+        }
+    }
+
+    /// Whether a player earned an achievement. None is the logged-in account.
+    pub fn earned_by(&self, achievement: &AchievementInfo, player: Option<&SessionPlayer>) -> bool {
+        match player {
+            Some(p) if !p.account => self
+                .player_earned
+                .get(&p.slot)
+                .is_some_and(|ids| ids.contains(&achievement.id)),
+            _ => achievement.earned,
+        }
+    }
+
+    /// Earned/total for a player, counted like `progress`
+    pub fn player_progress(&self, player: Option<&SessionPlayer>) -> AchievementProgress {
+        match player {
+            Some(p) if !p.account => AchievementProgress {
+                earned: self
+                    .achievements
+                    .iter()
+                    .filter(|a| !a.unofficial && self.earned_by(a, player))
+                    .count() as u32,
+                total: self.progress.total,
+            },
+            _ => self.progress.clone(),
+        }
     }
 
     pub fn update_progress(&mut self, earned: u32, total: u32) {
@@ -91,28 +306,72 @@ impl AchievementTracker {
         println!("[Achievements] Progress updated: {}/{}", earned, total);
     }
 
+    /// Set the measured progress of one achievement. A target of 0 clears it.
+    ///
+    /// Returns false if the achievement isn't in the current list.
+    pub fn set_measured_progress(
+        &mut self,
+        achievement_id: u32,
+        current: u32,
+        target: u32,
+    ) -> bool {
+        let Some(achievement) = self
+            .position(achievement_id)
+            .map(|i| &mut self.achievements[i])
+        else {
+            return false;
+        };
+
+        achievement.progress = if target == 0 {
+            None
+        } else {
+            Some(ipc::AchievementProgress {
+                current: current.min(target),
+                target,
+            })
+        };
+        true
+    }
+
+    /// Unearned achievements with measured progress, closest to done first,
+    /// for the pinned achievements HUD widget
+    pub fn pinned(&self) -> Vec<&AchievementInfo> {
+        let fraction = |a: &AchievementInfo| {
+            a.progress
+                .as_ref()
+                .map_or(0.0, |p| p.current as f32 / p.target as f32)
+        };
+        let mut pinned: Vec<&AchievementInfo> = self
+            .achievements
+            .iter()
+            .filter(|a| !a.earned && a.progress.is_some())
+            .collect();
+        pinned.sort_by(|a, b| fraction(b).total_cmp(&fraction(a)));
+        pinned.truncate(PINNED_ACHIEVEMENTS);
+        pinned
+    }
+
     pub fn clear(&mut self) {
         self.game_id = None;
         self.game_title.clear();
         self.game_hash = None;
         self.console.clear();
+        self.icon_url = None;
+        self.icon.set_path(None);
         self.achievements.clear();
+        self.index.clear();
         self.progress.earned = 0;
         self.progress.total = 0;
+        self.unlocks.clear();
+        self.difficulty = None;
+        self.players.clear();
+        self.player_earned.clear();
         println!("[Achievements] Cleared");
     }
 
     pub fn has_game(&self) -> bool {
         self.game_id.is_some()
     }
-
-    pub fn get_progress_percent(&self) -> f32 {
-        if self.progress.total == 0 {
-            0.0
-        } else {
-            (self.progress.earned as f32 / self.progress.total as f32) * 100.0
-        }
-    }
 }
 
 /// Achievement filter options
@@ -166,30 +425,58 @@ impl AchievementFilterState {
         self.scroll_offset = 0;
     }
 
+    /// Step to the next of All / Earned / Unearned
+    pub fn cycle_filter(&mut self) {
+        let filters = AchievementFilter::all_filters();
+        self.selected_filter = (self.selected_filter + 1) % filters.len();
+        self.set_filter(filters[self.selected_filter]);
+    }
+
     pub fn apply_filter(&mut self, achievements: &[AchievementInfo]) {
         self.filtered_indices.clear();
 
+        let query = self.search_query.to_lowercase();
         for (i, achievement) in achievements.iter().enumerate() {
-            let matches_filter = match self.filter {
-                AchievementFilter::All => true,
-                AchievementFilter::Earned => achievement.earned,
-                AchievementFilter::Unearned => !achievement.earned,
-            };
-
-            let matches_search = if self.search_query.is_empty() {
-                true
-            } else {
-                let query = self.search_query.to_lowercase();
-                achievement.title.to_lowercase().contains(&query)
-                    || achievement.description.to_lowercase().contains(&query)
-            };
-
-            if matches_filter && matches_search {
+            if self.matches(achievement, &query) {
                 self.filtered_indices.push(i);
             }
         }
     }
 
+    /// Re-check one entry after it changed, instead of the whole list
+    pub fn update_entry(&mut self, achievements: &[AchievementInfo], index: usize) {
+        let Some(achievement) = achievements.get(index) else {
+            return;
+        };
+        let matches = self.matches(achievement, &self.search_query.to_lowercase());
+        match (self.filtered_indices.binary_search(&index), matches) {
+            (Err(at), true) => self.filtered_indices.insert(at, index),
+            (Ok(at), false) => {
+                self.filtered_indices.remove(at);
+            }
+            _ => {}
+        }
+    }
+
+    /// `query` is the search query, lowercased
+    fn matches(&self, achievement: &AchievementInfo, query: &str) -> bool {
+        let matches_filter = match self.filter {
+            AchievementFilter::All => true,
+            AchievementFilter::Earned => achievement.earned,
+            AchievementFilter::Unearned => !achievement.earned,
+        };
+
+        matches_filter
+            && (query.is_empty()
+                || achievement.title.to_lowercase().contains(query)
+                || achievement.description.to_lowercase().contains(query))
+    }
+
+    /// Whether the list is narrowed down at all
+    pub fn is_active(&self) -> bool {
+        self.filter != AchievementFilter::All || !self.search_query.is_empty()
+    }
+
     pub fn update_search(&mut self, query: String) {
         self.search_query = query;
         self.scroll_offset = 0;
@@ -221,10 +508,28 @@ pub struct OverlayState {
     pub settings_scroll_offset: usize,
     pub menu_customization_selected: usize,
     pub menu_customization_scroll_offset: usize,
+    /// Highlighted slot of the quick actions palette
+    pub quick_actions_selected: usize,
+    /// Row of the quick actions settings list, over QuickAction::all()
+    pub quick_actions_settings_selected: usize,
     pub theme_selected: usize,
     pub theme_selection_scroll_offset: usize,
+    /// Options being tried on the theme preview screen
+    pub theme_preview_color_vision: ColorVision,
+    pub theme_preview_high_contrast: bool,
+    pub theme_preview_backdrop: Backdrop,
+    /// Preview row being adjusted: colors, dimming, panels, blur
+    pub theme_preview_row: usize,
+    /// Whether the compositor is blurring the game behind the overlay
+    pub backdrop_blur: bool,
+    /// Whether the compositor can blur, checked the first time it's needed
+    blur_supported: Option<bool>,
     pub quit_confirm_selected: usize, // 0 = Cancel, 1 = Quit
     pub toasts: ToastManager,
+    /// Unlocks while the overlay was closed, shown in the main menu header
+    /// until it next opens
+    pub recent_unlocks: u32,
+    unlocks_while_closed: u32,
     pub achievements: AchievementTracker,
     pub controllers: ControllerState,
     pub performance: PerformanceStats,
@@ -233,6 +538,71 @@ pub struct OverlayState {
     pub theme_config: ThemeConfigManager,
     pub ra_poller: Option<RaPoller>,
     pub achievements_scroll_offset: usize,
+    pub achievements_selected: usize,
+    /// Player tab of the achievements screen in a shared session
+    pub achievements_player: usize,
+    pub hints: Option<HintsView>,
+    pub compare: Option<CompareView>,
+    pub login: Option<LoginView>,
+    pub ra_user: Option<String>,
+    pub stream: Option<StreamOutput>,
+    pub ipc_stats: IpcStats,
+    pub resources: ScreenResources,
+    /// Quit in progress, waiting for the game to save
+    pub quit: Option<GracefulQuit>,
+    /// Set by a `shutdown` message; the main loop exits once it's seen
+    pub shutdown_requested: bool,
+    pub search: Option<SearchView>,
+    pub theme_editor: Option<ThemeEditor>,
+    /// Installed games, as last reported by the BIOS
+    pub games: Vec<GameEntry>,
+    pub achievement_filter: AchievementFilterState,
+    /// Game picked from search, shown on the playtime screen
+    pub playtime_focus: Option<GameEntry>,
+    /// Daily/weekly challenge sets from `ra_challenges`
+    pub challenges: Vec<ChallengeSetInfo>,
+    pub challenges_scroll: usize,
+    pub auto_pause: Option<AutoPause>,
+    pub haptics: Option<Haptics>,
+    /// User rules applied to incoming IPC messages
+    pub ipc_rules: Option<IpcRules>,
+    /// Hidden IPC debug console, when enabled
+    pub debug_console: Option<DebugConsole>,
+    pub power: Option<PowerManager>,
+    pub recovery: Option<Recovery>,
+    /// Set while a game that reported its PID is running
+    pub watchdog: Option<ProcessWatchdog>,
+    pub calibration: Option<CalibrationManager>,
+    pub speedrun: Option<Speedrun>,
+    pub speedrun_selected: usize,
+    pub pointer: Pointer,
+    pub network: NetworkMonitor,
+    /// Mastery takeover, shown over everything until it times out
+    pub celebration: Option<Celebration>,
+    pub hud_layouts: Option<HudLayoutManager>,
+    /// Widget picked in the HUD layout editor, an index into `HudWidget::ALL`
+    pub hud_editor_selected: usize,
+    pub battery: BatteryMonitor,
+    /// Cheats for the running game, loaded when it starts
+    pub cheats: Option<CheatList>,
+    /// Button mapping of the running game, empty when its cart has none
+    pub game_controls: Vec<ControlHint>,
+    pub controls_cache: Option<ControlsCache>,
+    /// Macros saved by kazeta-input, reloaded when the Macros screen opens
+    pub macros: Vec<MacroSummary>,
+    /// Selected row on the Macros screen; 0 is "record new"
+    pub macros_selected: usize,
+    pub music: Option<Jukebox>,
+    /// Track list shown on the Music screen
+    pub music_source: MusicSource,
+    /// Playlist X adds tracks to from the full track list
+    pub music_target_playlist: Option<String>,
+    /// Selected row on the Music screen; the settings rows come first
+    pub music_selected: usize,
+    /// Which profiles have seen the first-run tour
+    pub onboarding: Option<Onboarding>,
+    /// First-run tour over the main menu, while it's running
+    pub tour: Option<Tour>,
 }
 
 struct RaPoller {
@@ -268,6 +638,109 @@ impl OverlayState {
             ThemeConfigManager::new().expect("Failed to create theme config")
         });
 
+        // Initialize stream output (OBS mirror); optional since it is off by default
+        let stream = match StreamOutput::new() {
+            Ok(stream) => Some(stream),
+            Err(e) => {
+                eprintln!("[State] Failed to initialize stream output: {}", e);
+                None
+            }
+        };
+
+        let auto_pause = match AutoPause::new() {
+            Ok(auto_pause) => Some(auto_pause),
+            Err(e) => {
+                eprintln!("[State] Failed to initialize auto-pause: {}", e);
+                None
+            }
+        };
+
+        let mut toasts = ToastManager::new();
+        match SnoozeConfig::load() {
+            Ok(config) => toasts.snooze = config,
+            Err(e) => eprintln!("[State] Failed to load snooze settings: {}", e),
+        }
+
+        let haptics = match Haptics::new() {
+            Ok(haptics) => Some(haptics),
+            Err(e) => {
+                eprintln!("[State] Failed to initialize haptics: {}", e);
+                None
+            }
+        };
+
+        let ipc_rules = match IpcRules::new() {
+            Ok(rules) => Some(rules),
+            Err(e) => {
+                eprintln!("[State] Failed to initialize message rules: {}", e);
+                None
+            }
+        };
+
+        let power = match PowerManager::new() {
+            Ok(power) => Some(power),
+            Err(e) => {
+                eprintln!("[State] Failed to initialize power modes: {}", e);
+                None
+            }
+        };
+
+        let recovery = match Recovery::new() {
+            Ok(recovery) => Some(recovery),
+            Err(e) => {
+                eprintln!("[State] Failed to initialize crash recovery: {}", e);
+                None
+            }
+        };
+
+        let calibration = match CalibrationManager::new() {
+            Ok(calibration) => Some(calibration),
+            Err(e) => {
+                eprintln!("[State] Failed to load controller calibration: {}", e);
+                None
+            }
+        };
+
+        let speedrun = match Speedrun::new() {
+            Ok(speedrun) => Some(speedrun),
+            Err(e) => {
+                eprintln!("[State] Failed to initialize speedrun timer: {}", e);
+                None
+            }
+        };
+
+        let music = match Jukebox::new() {
+            Ok(music) => Some(music),
+            Err(e) => {
+                eprintln!("[State] Failed to initialize jukebox: {}", e);
+                None
+            }
+        };
+
+        let onboarding = match Onboarding::new() {
+            Ok(onboarding) => Some(onboarding),
+            Err(e) => {
+                eprintln!("[State] Failed to initialize onboarding: {}", e);
+                None
+            }
+        };
+
+        let controls_cache = match ControlsCache::new() {
+            Ok(cache) => Some(cache),
+            Err(e) => {
+                eprintln!("[State] Failed to initialize controls cache: {}", e);
+                None
+            }
+        };
+
+        let hud_layouts = match HudLayoutManager::new() {
+            Ok(hud_layouts) => Some(hud_layouts),
+            Err(e) => {
+                eprintln!("[State] Failed to initialize HUD layout: {}", e);
+                None
+            }
+        };
+
         Self {
             visible: false,
             current_screen: OverlayScreen::Main,
@@ -277,10 +750,20 @@ impl OverlayState {
             settings_scroll_offset: 0,
             menu_customization_selected: 0,
             menu_customization_scroll_offset: 0,
+            quick_actions_selected: 0,
+            quick_actions_settings_selected: 0,
             theme_selected: 0,
             theme_selection_scroll_offset: 0,
+            theme_preview_color_vision: ColorVision::Normal,
+            theme_preview_high_contrast: false,
+            theme_preview_backdrop: crate::themes::Theme::dark().default_backdrop(),
+            theme_preview_row: 0,
+            backdrop_blur: false,
+            blur_supported: None,
             quit_confirm_selected: 0, // Default to Cancel button
-            toasts: ToastManager::new(),
+            toasts,
+            recent_unlocks: 0,
+            unlocks_while_closed: 0,
             achievements: AchievementTracker::new(),
             controllers: ControllerState::new(),
             performance: PerformanceStats::new(),
@@ -289,6 +772,51 @@ impl OverlayState {
             theme_config,
             ra_poller: None,
             achievements_scroll_offset: 0,
+            achievements_selected: 0,
+            achievements_player: 0,
+            hints: None,
+            compare: None,
+            login: None,
+            ra_user: login::stored_username(),
+            stream,
+            ipc_stats: IpcStats::default(),
+            resources: ScreenResources::default(),
+            quit: None,
+            shutdown_requested: false,
+            search: None,
+            theme_editor: None,
+            games: Vec::new(),
+            achievement_filter: AchievementFilterState::new(),
+            playtime_focus: None,
+            challenges: Vec::new(),
+            challenges_scroll: 0,
+            auto_pause,
+            haptics,
+            ipc_rules,
+            debug_console: DebugConsole::new(),
+            power,
+            recovery,
+            watchdog: None,
+            calibration,
+            speedrun,
+            speedrun_selected: 0,
+            pointer: Pointer::new(),
+            network: NetworkMonitor::start(),
+            celebration: None,
+            hud_layouts,
+            hud_editor_selected: 0,
+            battery: BatteryMonitor::default(),
+            cheats: None,
+            game_controls: Vec::new(),
+            controls_cache,
+            macros: Vec::new(),
+            macros_selected: 0,
+            music,
+            music_source: MusicSource::AllTracks,
+            music_target_playlist: None,
+            music_selected: 0,
+            onboarding,
+            tour: None,
         }
     }
 
@@ -298,6 +826,39 @@ impl OverlayState {
             // Reset to main menu when opening
             self.current_screen = OverlayScreen::Main;
             self.selected_option = 0;
+            self.opened();
+            self.start_tour_if_new();
+        } else {
+            self.pointer.clear();
+        }
+    }
+
+    /// Catch up on what happened while the overlay was closed: the unlock
+    /// count for the header and whatever the snooze held back
+    fn opened(&mut self) {
+        self.recent_unlocks = std::mem::take(&mut self.unlocks_while_closed);
+        self.toasts.flush_snoozed();
+    }
+
+    /// Open the quick actions palette, over the game or the menu
+    pub fn open_quick_actions(&mut self) {
+        if !self.visible {
+            self.opened();
+        }
+        self.visible = true;
+        self.current_screen = OverlayScreen::QuickActions;
+        self.quick_actions_selected = 0;
+        println!("[State] Opened quick actions");
+    }
+
+    /// Highlight the palette slot the left stick points at
+    pub fn point_quick_actions(&mut self, x: f32, y: f32) {
+        let count = self.menu_config.config().quick_actions.len();
+        if let Some(slot) = quick_actions::slot_for_stick(x, y, count) {
+            if slot != self.quick_actions_selected {
+                self.quick_actions_selected = slot;
+                self.request_haptic(HapticPattern::Tick);
+            }
         }
     }
 
@@ -306,1164 +867,4555 @@ impl OverlayState {
     }
 
     pub fn should_render(&self) -> bool {
-        self.visible || !self.toasts.is_empty()
+        self.visible
+            || !self.toasts.is_empty()
+            || self.celebration.is_some()
+            || self.hud_widgets_active()
+    }
+
+    /// Cart id of the running game
+    pub fn current_cart_id(&self) -> Option<&str> {
+        self.playtime
+            .current_session
+            .as_ref()
+            .map(|s| s.cart_id.as_str())
+    }
+
+    /// HUD layout for the running game, falling back to the default
+    pub fn hud_layout(&self) -> &HudLayout {
+        match &self.hud_layouts {
+            Some(hud_layouts) => hud_layouts.config().layout(self.current_cart_id()),
+            None => &DEFAULT_LAYOUT,
+        }
+    }
+
+    /// Whether a widget shown while the menu is hidden has something to show.
+    /// The performance HUD has its own toggle and is checked separately.
+    pub fn hud_widget_active(&self, widget: HudWidget) -> bool {
+        self.hud_layout().is_visible(widget)
+            && match widget {
+                HudWidget::Performance => self.performance.is_visible(),
+                HudWidget::Achievements => !self.achievements.pinned().is_empty(),
+                HudWidget::Timer => self.speedrun.as_ref().is_some_and(|s| s.widget_visible()),
+                HudWidget::Battery => self.battery.status().is_some(),
+            }
+    }
+
+    fn hud_widgets_active(&self) -> bool {
+        [
+            HudWidget::Achievements,
+            HudWidget::Timer,
+            HudWidget::Battery,
+        ]
+        .into_iter()
+        .any(|widget| self.hud_widget_active(widget))
+    }
+
+    /// Power mode in effect, Performance when the config couldn't be loaded
+    pub fn power_mode(&self) -> PowerMode {
+        self.power.as_ref().map_or(PowerMode::Performance, |p| {
+            p.config().active_mode(self.battery.on_ac())
+        })
     }
 
     pub fn update(&mut self) {
         self.toasts.update();
+        if let Some(rules) = &mut self.ipc_rules {
+            rules.reload_if_changed();
+        }
         self.performance.update();
         self.playtime.update_current_session();
+        self.update_network();
         self.update_ra_polling();
+        self.update_stream();
+        if self.hud_layout().is_visible(HudWidget::Battery)
+            || self.power.as_ref().is_some_and(|p| p.config().auto_switch)
+        {
+            self.battery.update();
+        }
+        self.toasts.duration_scale = self.power_mode().toast_scale();
+        if self.celebration.as_ref().is_some_and(|c| c.is_finished()) {
+            self.celebration = None;
+        }
+        if let Some(hints) = &mut self.hints {
+            hints.poll();
+        }
+        if let Some(compare) = &mut self.compare {
+            compare.poll();
+        }
+        if let Some(username) = self.login.as_mut().and_then(|l| l.poll()) {
+            self.finish_login(username);
+        }
+        self.update_resources();
+        self.update_quit();
+        if let Some(music) = &mut self.music {
+            music.set_ducked(!self.visible);
+        }
+        self.update_watchdog();
+        self.update_recovery();
     }
 
-    pub fn handle_message(&mut self, message: OverlayMessage) {
-        match message {
-            OverlayMessage::ShowOverlay { screen } => {
-                self.visible = true;
-                self.current_screen = screen;
-                println!("[State] Showing overlay screen: {:?}", screen);
+    /// Stop the game ourselves if its process exited without telling us
+    fn update_watchdog(&mut self) {
+        let Some(watchdog) = self.watchdog.as_mut() else {
+            return;
+        };
+        if !watchdog.poll() {
+            return;
+        }
+        println!(
+            "[Watchdog] PID {} exited without game_stopped",
+            watchdog.pid
+        );
+        let cart_id = watchdog.cart_id.clone();
+        self.handle_message(OverlayMessage::GameStopped { cart_id });
+    }
+
+    /// Write the recovery snapshot when it's due, or drop it once no game runs
+    fn update_recovery(&mut self) {
+        if !self.recovery.as_ref().is_some_and(|r| r.is_due()) {
+            return;
+        }
+        let snapshot = self.session_snapshot();
+        let Some(recovery) = self.recovery.as_mut() else {
+            return;
+        };
+        match snapshot {
+            Some(snapshot) => {
+                if let Err(e) = recovery.save(&snapshot) {
+                    eprintln!("[Recovery] {}", e);
+                }
             }
-            OverlayMessage::HideOverlay => {
-                self.visible = false;
-                println!("[State] Hiding overlay");
+            None => recovery.clear(),
+        }
+    }
+
+    /// The running game's state, for the recovery snapshot
+    fn session_snapshot(&self) -> Option<SessionSnapshot> {
+        let session = self.playtime.current_session.as_ref()?;
+        let now = unix_now();
+        Some(SessionSnapshot {
+            saved_at: now,
+            cart_id: session.cart_id.clone(),
+            session_started_at: now.saturating_sub(session.start_time.elapsed().as_secs()),
+            game_id: self.achievements.game_id,
+            game_title: self.achievements.game_title.clone(),
+            game_hash: self.achievements.game_hash.clone(),
+            console: self.achievements.console.clone(),
+            icon_url: self.achievements.icon_url.clone(),
+            achievements: self.achievements.achievements.clone(),
+            unlocks: self.achievements.unlocks.clone(),
+            pid: self.watchdog.as_ref().map(|w| w.pid),
+        })
+    }
+
+    /// Restore the session a crashed overlay left behind, falling back to
+    /// kazeta-ra's session file for the achievements
+    pub fn restore_session(&mut self) {
+        let snapshot = self
+            .recovery
+            .as_ref()
+            .and_then(|r| r.load_fresh(unix_now()));
+        if let Some(snapshot) = snapshot {
+            self.apply_snapshot(snapshot);
+        }
+        if !self.achievements.has_game() {
+            self.restore_ra_session();
+        }
+    }
+
+    fn apply_snapshot(&mut self, snapshot: SessionSnapshot) {
+        println!("[State] Recovering session for {}", snapshot.cart_id);
+        let elapsed = Duration::from_secs(unix_now().saturating_sub(snapshot.session_started_at));
+        self.playtime
+            .resume_session(snapshot.cart_id.clone(), elapsed);
+        self.game_controls = self
+            .controls_cache
+            .as_ref()
+            .map(|c| c.load(&snapshot.cart_id))
+            .unwrap_or_default();
+        self.watchdog = snapshot
+            .pid
+            .map(|pid| ProcessWatchdog::new(pid, snapshot.cart_id.clone()));
+
+        let name = if snapshot.game_title.is_empty() {
+            snapshot.cart_id
+        } else {
+            snapshot.game_title.clone()
+        };
+        self.achievements.game_title = snapshot.game_title;
+        self.achievements.game_hash = snapshot.game_hash;
+        self.achievements.console = snapshot.console;
+        self.achievements.icon.set_path(
+            snapshot
+                .icon_url
+                .as_deref()
+                .and_then(game_icon::cached_icon_path),
+        );
+        self.achievements.icon_url = snapshot.icon_url;
+        self.achievements.set_achievements(snapshot.achievements);
+        self.achievements.unlocks = snapshot.unlocks;
+        self.achievements.game_id = snapshot.game_id;
+        if let Some(id) = snapshot.game_id {
+            self.start_ra_poller(id);
+        }
+
+        self.toasts.add_toast(
+            format!("Recovered session: {}", name),
+            None,
+            ToastStyle::Info,
+            4000,
+        );
+    }
+
+    /// Step the quit handshake and close the overlay once the game is gone
+    fn update_quit(&mut self) {
+        let Some(quit) = self.quit.as_mut() else {
+            return;
+        };
+        match quit.poll() {
+            QuitAction::None => return,
+            QuitAction::Terminate => {
+                // Stopped emulators only see SIGTERM once continued
+                pause::signal_emulators("-CONT");
+                pause::signal_emulators("-TERM");
+                return;
             }
-            OverlayMessage::ShowToast {
-                message,
-                icon,
-                style,
-                duration_ms,
-            } => {
-                self.toasts.add_toast(message, icon, style, duration_ms);
+            QuitAction::Kill => pause::signal_emulators("-KILL"),
+            QuitAction::Finished => {}
+        }
+
+        let (message, style) = match self.quit.take().and_then(|q| q.outcome) {
+            Some(SaveOutcome::Confirmed) => (
+                "Game saved. Returning to BIOS...".to_string(),
+                ToastStyle::Success,
+            ),
+            Some(SaveOutcome::Failed(reason)) => {
+                (format!("Save failed: {}", reason), ToastStyle::Error)
             }
-            OverlayMessage::GameStarted {
-                cart_id,
-                game_name,
-                runtime,
-            } => {
-                println!(
-                    "[State] Game started: {} ({}) - runtime: {}",
-                    game_name, cart_id, runtime
-                );
-                self.playtime.start_session(cart_id);
+            Some(SaveOutcome::TimedOut) => (
+                "Save not confirmed in time".to_string(),
+                ToastStyle::Warning,
+            ),
+            Some(SaveOutcome::Unconfirmed) | None => {
+                ("Returning to BIOS...".to_string(), ToastStyle::Info)
             }
-        OverlayMessage::RaGameStart {
-            game_title,
-            game_id,
-            game_icon: _,
-            total_achievements,
-            earned_achievements,
-        } => {
-            println!("[State] RA Game started: {} ({}/{})", game_title, earned_achievements, total_achievements);
-            self.achievements.game_title = game_title.clone();
-            self.achievements.game_id = None;
-            if let Some(id) = game_id {
-                self.achievements.game_id = Some(id);
-                self.start_ra_poller(id);
-            }
-            self.achievements.update_progress(earned_achievements, total_achievements);
-        }
-        OverlayMessage::RaAchievementList { game_title, game_hash, achievements } => {
-            if !game_title.is_empty() {
-                self.achievements.game_title = game_title;
-            }
-            self.achievements.game_hash = Some(game_hash);
-            self.achievements.set_achievements(achievements);
-            if let Some(poller) = &mut self.ra_poller {
-                poller.earned = self.achievements.achievements
-                    .iter()
-                    .filter(|a| a.earned)
-                    .map(|a| a.id)
-                    .collect();
+        };
+        self.toasts.add_toast(message, None, style, 3000);
+        self.current_screen = OverlayScreen::Main;
+        self.visible = false;
+    }
+
+    /// Load what the open screen shows and drop what hasn't been shown in a while
+    fn update_resources(&mut self) {
+        let now = Instant::now();
+        let screen = self.visible.then_some(self.current_screen);
+        let in_use = screen.map_or(&[][..], Resource::used_by);
+
+        // Entering the stats screen recomputes them with the latest playtime
+        if screen != self.resources.screen {
+            self.resources.screen = screen;
+            if in_use.contains(&Resource::AchievementStats) {
+                self.resources.stats.unload();
             }
         }
-            OverlayMessage::RaProgressUpdate { earned, total } => {
-                self.achievements.update_progress(earned, total);
+
+        for resource in in_use {
+            match resource {
+                Resource::GameIcon => self.achievements.icon.load_pending(now),
+                Resource::AchievementStats => {
+                    let achievements = &self.achievements.achievements;
+                    let source = (
+                        achievements.len(),
+                        achievements.iter().filter(|a| a.earned).count(),
+                        self.achievements.unlocks.len(),
+                    );
+                    if source != self.resources.stats_source {
+                        self.resources.stats_source = source;
+                        self.resources.stats.unload();
+                    }
+                    let stats =
+                        (!self.resources.stats.is_loaded()).then(|| self.achievement_stats());
+                    self.resources.stats.load_with(now, || {
+                        stats.map(|stats| {
+                            let bytes = std::mem::size_of_val(&stats)
+                                + stats.daily_unlocks.len() * std::mem::size_of::<u32>()
+                                + std::mem::size_of_val(stats.sessions.as_slice());
+                            (stats, bytes)
+                        })
+                    });
+                }
             }
-            OverlayMessage::RaAchievementUnlocked {
-                achievement_id,
-                title,
-                description,
-                points,
-                icon_url: _,
-                is_hardcore: _,
-            } => {
-                self.achievements.unlock_achievement(achievement_id);
-                let desc_text = description.unwrap_or_default();
-                self.toasts.add_toast(
-                    format!("🏆 {} ({} points)", title, points),
-                    None,
-                    ToastStyle::Success,
-                    5000,
-                );
-                println!("[State] Achievement unlocked: {} - {}", title, desc_text);
-            }
-            OverlayMessage::SetTheme { font_color, cursor_color } => {
-                // Note: SetTheme in IPC currently just sets colors, not a full theme
-                // For now, just log it
-                println!("[State] SetTheme called with font_color={}, cursor_color={}", font_color, cursor_color);
-                // TODO: Apply custom colors to theme
+        }
+
+        let icon = &self.achievements.icon.texture;
+        let loaded: Vec<_> = [
+            (
+                Resource::GameIcon,
+                icon.is_loaded(),
+                icon.bytes(),
+                icon.last_used(),
+            ),
+            (
+                Resource::AchievementStats,
+                self.resources.stats.is_loaded(),
+                self.resources.stats.bytes(),
+                self.resources.stats.last_used(),
+            ),
+        ]
+        .into_iter()
+        .filter(|(_, is_loaded, _, _)| *is_loaded)
+        .map(|(resource, _, bytes, last_used)| (resource, bytes, last_used))
+        .collect();
+
+        for resource in resources::plan_unloads(&loaded, in_use, now, resources::MEMORY_BUDGET) {
+            println!("[Resources] Unloading {:?}", resource);
+            match resource {
+                Resource::GameIcon => self.achievements.icon.texture.unload(),
+                Resource::AchievementStats => self.resources.stats.unload(),
             }
-        OverlayMessage::GameStopped { cart_id } => {
-            println!("[State] Game stopped: {}", cart_id);
-                self.playtime.end_session();
-                // Clear achievement data when game stops
-                self.achievements.clear();
+        }
+    }
+
+    /// Demand-loaded resources against the budget, for the performance HUD
+    pub fn memory_usage(&self) -> MemoryUsage {
+        let icon = &self.achievements.icon.texture;
+        let stats = &self.resources.stats;
+        MemoryUsage {
+            loaded: [icon.is_loaded(), stats.is_loaded()]
+                .iter()
+                .filter(|l| **l)
+                .count(),
+            bytes: icon.bytes() + stats.bytes(),
+            budget: resources::MEMORY_BUDGET,
+        }
+    }
+
+    fn finish_login(&mut self, username: String) {
+        self.toasts.add_toast(
+            format!("Logged in to RetroAchievements as {}", username),
+            None,
+            ToastStyle::Success,
+            3000,
+        );
+        self.ra_user = Some(username);
+        self.login = None;
+        if self.current_screen == OverlayScreen::RaLogin {
+            self.current_screen = OverlayScreen::Settings;
+        }
+        // Pick up progress for a game that was started while logged out
+        if let Some(game_id) = self.achievements.game_id {
+            self.start_ra_poller(game_id);
+        }
+    }
+
+    fn logout(&mut self) {
+        match login::logout() {
+            Ok(()) => {
+                self.ra_user = None;
                 self.ra_poller = None;
-                self.achievements_scroll_offset = 0;
-            }
-            OverlayMessage::QuitGame => {
-                // This is handled in main.rs - trigger quit signal
-                println!("[State] Quit game requested");
-            }
-            OverlayMessage::QuitGameAck => {
-                println!("[State] Quit game acknowledged");
                 self.toasts.add_toast(
-                    "Returning to BIOS...".to_string(),
+                    "Logged out of RetroAchievements".to_string(),
                     None,
                     ToastStyle::Info,
-                    2000,
+                    3000,
                 );
             }
-            OverlayMessage::UnlockAchievement { cart_id, achievement_id, timestamp } => {
-                println!("[State] Achievement unlocked: cart={}, id={}, time={}", cart_id, achievement_id, timestamp);
-                // This is handled by RaAchievementUnlocked for RetroAchievements
-            }
-            OverlayMessage::GetStatus => {
-                // Status query - could be used for IPC health checks
-                println!("[State] Status requested");
-            }
-            OverlayMessage::ToggleOverlay => {
-                self.toggle_visibility();
-                println!("[State] Toggled overlay via IPC message");
-            }
-            OverlayMessage::HideOverlay => {
-                self.visible = false;
-                println!("[State] Hiding overlay via IPC message");
+            Err(e) => {
+                eprintln!("[State] Logout failed: {}", e);
+                self.toasts.add_toast(
+                    format!("Logout failed: {}", e),
+                    None,
+                    ToastStyle::Error,
+                    3000,
+                );
             }
         }
     }
 
-    fn start_ra_poller(&mut self, game_id: u32) {
-        let earned: HashSet<u32> = self
-            .achievements
-            .achievements
-            .iter()
-            .filter(|a| a.earned)
-            .map(|a| a.id)
-            .collect();
+    fn update_stream(&mut self) {
+        let Some(stream) = self.stream.as_mut() else {
+            return;
+        };
 
-        self.ra_poller = Some(RaPoller {
-            game_id,
-            earned,
-            last_poll: Instant::now(),
-            interval: Duration::from_secs(20),
-            backoff: None,
-        });
+        let session_seconds = self
+            .playtime
+            .get_current_session_duration()
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let total_seconds = self
+            .playtime
+            .current_session
+            .as_ref()
+            .and_then(|s| self.playtime.get_playtime(&s.cart_id))
+            .map(|e| e.total_seconds)
+            .unwrap_or(0)
+            + session_seconds;
+
+        stream.update(session_seconds, total_seconds);
     }
 
-    fn update_ra_polling(&mut self) {
-        let Some(poller) = self.ra_poller.as_mut() else { return; };
-
-        let interval = poller.backoff.unwrap_or(poller.interval);
-        if poller.last_poll.elapsed() < interval {
+    /// Pause the game while the overlay is open, resume when it closes
+    pub fn sync_game_pause(&mut self, visible: bool) {
+        let Some(auto_pause) = self.auto_pause.as_mut() else {
             return;
+        };
+
+        match (&self.playtime.current_session, visible) {
+            (Some(session), true) => auto_pause.pause(&session.cart_id),
+            _ => auto_pause.resume(),
         }
-        poller.last_poll = Instant::now();
+    }
 
-        let cred_mgr = match CredentialManager::new() {
-            Ok(c) => c,
-            Err(e) => {
-                eprintln!("[RA] Credential manager error: {}", e);
-                poller.backoff = Some(interval * 2);
-                return;
+    /// Whether the game should be kept from the gamepads, so typing on the
+    /// on-screen keyboard doesn't also move things in the game
+    pub fn wants_exclusive_input(&self) -> bool {
+        self.visible
+            && match self.current_screen {
+                OverlayScreen::RaLogin => self
+                    .login
+                    .as_ref()
+                    .is_some_and(|login| login.keyboard.is_some()),
+                OverlayScreen::ThemeEditor => self
+                    .theme_editor
+                    .as_ref()
+                    .is_some_and(|editor| editor.keyboard.is_some()),
+                _ => false,
             }
-        };
+    }
 
-        let credentials = match cred_mgr.load() {
-            Ok(Some(c)) => c,
-            Ok(None) => {
-                eprintln!("[RA] No credentials found");
-                poller.backoff = Some(interval * 2);
-                return;
-            }
-            Err(e) => {
-                eprintln!("[RA] Failed to load credentials: {}", e);
-                poller.backoff = Some(interval * 2);
-                return;
-            }
+    /// Have the compositor blur the game while the menu is open, if the
+    /// backdrop being shown asks for it
+    pub fn sync_backdrop_blur(&mut self, visible: bool) {
+        let backdrop = if self.current_screen == OverlayScreen::ThemePreview {
+            self.theme_preview_backdrop
+        } else {
+            self.theme_config.backdrop()
         };
-
-        let client = RAClient::new(credentials);
-        let game_id = poller.game_id;
-        match client.get_game_info_and_progress(game_id) {
-            Ok(info) => {
-                poller.backoff = None;
-                drop(poller); // Drop the mutable borrow before calling apply_ra_poll
-                self.apply_ra_poll(info);
-            }
-            Err(e) => {
-                eprintln!("[RA] Poll failed: {}", e);
-                poller.backoff = Some(interval * 2);
-            }
+        let wanted = visible
+            && backdrop.blur
+            && *self
+                .blur_supported
+                .get_or_insert_with(backdrop::compositor_blur_supported);
+        if wanted != self.backdrop_blur {
+            self.backdrop_blur = backdrop::set_compositor_blur(wanted);
         }
     }
 
-    fn apply_ra_poll(&mut self, info: GameInfoAndProgress) {
-        let Some(poller) = self.ra_poller.as_mut() else { return; };
-        let Some(achievements_map) = info.achievements.as_ref() else { return; };
+    fn request_haptic(&mut self, pattern: HapticPattern) {
+        if let Some(haptics) = &mut self.haptics {
+            haptics.request(pattern);
+        }
+    }
 
-        let mut earned_now: HashSet<u32> = HashSet::new();
-        let mut newly_unlocked = Vec::new();
+    /// Toggle rumble feedback from the settings screen
+    fn toggle_haptics(&mut self) {
+        let Some(haptics) = self.haptics.as_mut() else {
+            return;
+        };
 
-        for achievement in achievements_map.values() {
-            if achievement.is_earned() {
-                earned_now.insert(achievement.id);
-                if poller.earned.insert(achievement.id) {
-                    newly_unlocked.push((achievement.id, achievement.title.clone(), achievement.points));
-                }
-            }
+        let enabled = !haptics.config().enabled;
+        haptics.config_mut().enabled = enabled;
+        if let Err(e) = haptics.save() {
+            eprintln!("[State] Failed to save haptics config: {}", e);
         }
+        // Let the player feel that it's back on
+        self.request_haptic(HapticPattern::Tick);
+    }
 
-        poller.earned = earned_now;
-        self.achievements
-            .update_progress(poller.earned.len() as u32, info.num_achievements);
-
-        for (id, title, points) in newly_unlocked {
-            self.achievements.unlock_achievement(id);
+    /// Snooze notifications, or show what was held back and stop snoozing
+    fn toggle_snooze(&mut self) {
+        let enabled = !self.toasts.snooze.enabled;
+        if enabled {
+            // Shown before snoozing so it isn't held back itself
             self.toasts.add_toast(
-                format!("🏆 {} ({} pts)", title, points),
+                "Notifications snoozed: a summary shows when the overlay opens".to_string(),
                 None,
-                ToastStyle::Success,
-                5000,
+                ToastStyle::Info,
+                3000,
             );
-            println!("[RA] Detected unlock via polling: {} ({})", title, id);
+            self.toasts.snooze.enabled = true;
+        } else {
+            self.toasts.snooze.enabled = false;
+            self.toasts.flush_snoozed();
+        }
+        println!(
+            "[State] Notification snooze {}",
+            if enabled { "on" } else { "off" }
+        );
+        if let Err(e) = self.toasts.snooze.save() {
+            eprintln!("[State] Failed to save snooze settings: {}", e);
         }
     }
 
-    pub fn handle_input(&mut self, input: ControllerInput) {
-        if !self.visible {
-            return;
+    /// Choose whether snoozing holds back a style, from the settings screen
+    fn toggle_snooze_style(&mut self, style: ToastStyle) {
+        self.toasts.snooze.toggle_style(style);
+        if let Err(e) = self.toasts.snooze.save() {
+            eprintln!("[State] Failed to save snooze settings: {}", e);
         }
+    }
 
-        match self.current_screen {
-            OverlayScreen::Main => self.handle_main_menu_input(input),
-            OverlayScreen::Achievements => self.handle_achievements_input(input),
-            OverlayScreen::Performance => self.handle_performance_input(input),
-            OverlayScreen::Settings => self.handle_settings_input(input),
-            OverlayScreen::Controllers => self.handle_controllers_input(input),
-            OverlayScreen::GamepadTester => self.handle_gamepad_tester_input(input),
-            OverlayScreen::Playtime => self.handle_playtime_input(input),
-            OverlayScreen::HotkeySettings => self.handle_hotkey_settings_input(input),
-            OverlayScreen::MenuCustomization => self.handle_menu_customization_input(input),
-            OverlayScreen::ThemeSelection => self.handle_theme_selection_input(input),
-            OverlayScreen::QuitConfirm => self.handle_quit_confirm_input(input),
-            OverlayScreen::BluetoothPairing => self.handle_bluetooth_pairing_input(input),
-            OverlayScreen::ControllerAssign => self.handle_controller_assign_input(input),
+    /// Step the rumble intensity from the settings screen, with a preview
+    fn cycle_haptic_intensity(&mut self) {
+        let Some(haptics) = self.haptics.as_mut() else {
+            return;
+        };
+
+        let intensity = haptics::next_intensity(haptics.config().intensity);
+        haptics.config_mut().intensity = intensity;
+        if let Err(e) = haptics.save() {
+            eprintln!("[State] Failed to save haptics config: {}", e);
         }
+        self.request_haptic(HapticPattern::Tick);
     }
 
-    /// Helper to adjust scroll offset to keep selected item visible
-    fn adjust_scroll_offset(selected: usize, scroll_offset: &mut usize, max_visible: usize, total_items: usize) {
-        if selected < *scroll_offset {
-            // Selected item is above visible area, scroll up
-            *scroll_offset = selected;
-        } else if selected >= *scroll_offset + max_visible {
-            // Selected item is below visible area, scroll down
-            *scroll_offset = selected - max_visible + 1;
-        }
+    /// Step the power mode from the settings screen
+    fn cycle_power_mode(&mut self) {
+        let Some(power) = self.power.as_mut() else {
+            return;
+        };
 
-        // Ensure scroll offset doesn't go past the end
-        let max_scroll = total_items.saturating_sub(max_visible);
-        if *scroll_offset > max_scroll {
-            *scroll_offset = max_scroll;
+        let mode = power.config().mode.next();
+        power.config_mut().mode = mode;
+        if let Err(e) = power.save() {
+            eprintln!("[State] Failed to save power config: {}", e);
         }
+        println!("[State] Power mode: {}", mode.name());
     }
 
-    fn handle_main_menu_input(&mut self, input: ControllerInput) {
-        let visible_items = self.menu_config.config().get_visible_items();
-        let item_count = visible_items.len();
+    /// Toggle switching to Performance on AC power from the settings screen
+    fn toggle_power_auto_switch(&mut self) {
+        let Some(power) = self.power.as_mut() else {
+            return;
+        };
 
-        match input {
-            ControllerInput::Up => {
-                if self.selected_option > 0 {
-                    self.selected_option -= 1;
-                    Self::adjust_scroll_offset(
-                        self.selected_option,
-                        &mut self.main_menu_scroll_offset,
-                        6,
-                        item_count,
-                    );
-                }
-            }
-            ControllerInput::Down => {
-                if self.selected_option < item_count.saturating_sub(1) {
-                    self.selected_option += 1;
-                    Self::adjust_scroll_offset(
-                        self.selected_option,
-                        &mut self.main_menu_scroll_offset,
-                        6,
-                        item_count,
-                    );
-                }
-            }
-            ControllerInput::Select => {
-                if self.selected_option < visible_items.len() {
-                    let menu_item_id = visible_items[self.selected_option];
-                    match menu_item_id {
-                        MenuItemId::Achievements => {
-                            self.current_screen = OverlayScreen::Achievements;
-                            println!("[State] Switched to Achievements screen");
-                        }
-                        MenuItemId::Performance => {
-                            self.current_screen = OverlayScreen::Performance;
-                            println!("[State] Switched to Performance screen");
-                        }
-                        MenuItemId::Settings => {
-                            self.current_screen = OverlayScreen::Settings;
-                            self.settings_selected_option = 0;
-                            println!("[State] Switched to Settings screen");
-                        }
-                        MenuItemId::Controllers => {
-                            self.current_screen = OverlayScreen::Controllers;
-                            self.controllers.selected_menu_item = 0;
-                            println!("[State] Switched to Controllers screen");
-                        }
-                        MenuItemId::Playtime => {
-                            self.current_screen = OverlayScreen::Playtime;
-                            println!("[State] Switched to Playtime screen");
-                        }
-                        MenuItemId::QuickSave => {
-                            // TODO: Implement quick save
-                            println!("[State] Quick save requested (not implemented)");
-                        }
-                        MenuItemId::Resume => {
-                            println!("[State] Resuming game");
-                            self.visible = false;
-                        }
-                        MenuItemId::Quit => {
-                            self.current_screen = OverlayScreen::QuitConfirm;
-                            self.quit_confirm_selected = 0; // Default to Cancel button
-                            println!("[State] Showing quit confirmation");
-                        }
-                    }
-                }
-            }
-            ControllerInput::Back | ControllerInput::Guide => {
-                // Close overlay
-                self.visible = false;
-                println!("[State] Overlay closed");
-            }
-            _ => {}
+        let auto_switch = !power.config().auto_switch;
+        power.config_mut().auto_switch = auto_switch;
+        if let Err(e) = power.save() {
+            eprintln!("[State] Failed to save power config: {}", e);
         }
     }
 
-    fn handle_achievements_input(&mut self, input: ControllerInput) {
-        match input {
-            ControllerInput::Back => {
-                self.current_screen = OverlayScreen::Main;
-                println!("[State] Returning to main menu");
-            }
-            _ => {}
+    /// Toggle the global auto-pause switch from the settings screen
+    fn toggle_auto_pause(&mut self) {
+        let Some(auto_pause) = self.auto_pause.as_mut() else {
+            return;
+        };
+
+        let enabled = !auto_pause.config().enabled;
+        auto_pause.config_mut().enabled = enabled;
+        if let Err(e) = auto_pause.save() {
+            eprintln!("[State] Failed to save auto-pause config: {}", e);
         }
+        let message = if enabled {
+            "Auto-pause enabled"
+        } else {
+            "Auto-pause disabled"
+        };
+        self.toasts
+            .add_toast(message.to_string(), None, ToastStyle::Info, 2000);
     }
 
-    fn handle_performance_input(&mut self, input: ControllerInput) {
-        match input {
-            ControllerInput::Back => {
-                self.current_screen = OverlayScreen::Main;
-                println!("[State] Returning to main menu");
-            }
-            _ => {}
+    /// Cycle the running game's auto-pause override from the settings screen
+    fn cycle_game_auto_pause(&mut self) {
+        let Some(cart_id) = self
+            .playtime
+            .current_session
+            .as_ref()
+            .map(|s| s.cart_id.clone())
+        else {
+            return;
+        };
+        let Some(auto_pause) = self.auto_pause.as_mut() else {
+            return;
+        };
+
+        let value = auto_pause.config_mut().cycle_override(&cart_id);
+        if let Err(e) = auto_pause.save() {
+            eprintln!("[State] Failed to save auto-pause config: {}", e);
         }
+        println!("[State] Auto-pause override for {}: {:?}", cart_id, value);
     }
 
-    fn handle_settings_input(&mut self, input: ControllerInput) {
-        const SETTINGS_OPTIONS: usize = 2;
+    /// Toggle OBS stream output from the settings screen
+    fn toggle_stream_mode(&mut self) {
+        let Some(stream) = self.stream.as_mut() else {
+            self.toasts.add_toast(
+                "Stream output unavailable".to_string(),
+                None,
+                ToastStyle::Error,
+                2000,
+            );
+            return;
+        };
 
-        match input {
-            ControllerInput::Up => {
-                if self.settings_selected_option > 0 {
-                    self.settings_selected_option -= 1;
+        let enabled = !stream.is_enabled();
+        if let Err(e) = stream.set_enabled(enabled) {
+            eprintln!("[State] Failed to save stream config: {}", e);
+        }
+        let message = if enabled {
+            "Stream mode enabled"
+        } else {
+            "Stream mode disabled"
+        };
+        self.toasts
+            .add_toast(message.to_string(), None, ToastStyle::Info, 2000);
+    }
+
+    /// Run an incoming message past the message rules before handling it
+    pub fn route_message(&mut self, incoming: IncomingMessage) {
+        let screen = self.visible.then_some(self.current_screen);
+        let action = match &mut self.ipc_rules {
+            Some(rules) => rules.evaluate(&incoming.kind, incoming.source.as_deref(), screen),
+            None => RuleAction::Allow,
+        };
+        if let Some(console) = &mut self.debug_console {
+            console.record(&incoming, action);
+        }
+        match action {
+            RuleAction::Allow => self.handle_message(incoming.message),
+            RuleAction::Drop => println!("[Rules] Dropped {} message", incoming.kind),
+            RuleAction::Quiet => {
+                self.toasts.quiet = true;
+                self.handle_message(incoming.message);
+                self.toasts.quiet = false;
+            }
+        }
+    }
+
+    pub fn handle_message(&mut self, message: OverlayMessage) {
+        if let Some(recovery) = &mut self.recovery {
+            recovery.mark_dirty();
+        }
+        match message {
+            OverlayMessage::ShowOverlay { screen } => {
+                if !self.visible {
+                    self.opened();
+                }
+                self.visible = true;
+                self.current_screen = screen;
+                println!("[State] Showing overlay screen: {:?}", screen);
+                if screen == OverlayScreen::Main {
+                    self.start_tour_if_new();
                 }
             }
-            ControllerInput::Down => {
-                if self.settings_selected_option < SETTINGS_OPTIONS - 1 {
-                    self.settings_selected_option += 1;
+            OverlayMessage::HideOverlay => {
+                self.visible = false;
+                println!("[State] Hiding overlay");
+            }
+            OverlayMessage::ShowToast {
+                message,
+                icon,
+                style,
+                duration_ms,
+            } => {
+                if let Some(stream) = &mut self.stream {
+                    stream.toast(&message);
                 }
+                self.toasts.add_toast(message, icon, style, duration_ms);
             }
-            ControllerInput::Select => {
-                match self.settings_selected_option {
-                    0 => {
-                        // Menu Customization
-                        self.current_screen = OverlayScreen::MenuCustomization;
-                        self.menu_customization_selected = 0;
-                        println!("[State] Switched to Menu Customization");
+            OverlayMessage::GameStarted {
+                cart_id,
+                game_name,
+                runtime,
+                pid,
+            } => {
+                println!(
+                    "[State] Game started: {} ({}) - runtime: {}",
+                    game_name, cart_id, runtime
+                );
+                self.watchdog = pid.map(|pid| ProcessWatchdog::new(pid, cart_id.clone()));
+                if let Some(stream) = &mut self.stream {
+                    stream.game_started(&cart_id, &game_name);
+                }
+                if let Some(speedrun) = &mut self.speedrun {
+                    speedrun.game_started(&cart_id);
+                }
+                if let Some(launch) = LaunchPlayers::load(Path::new(LAUNCH_PLAYERS_FILE), &cart_id)
+                {
+                    self.controllers.apply_launch_players(&launch);
+                }
+                self.cheats = Some(CheatList::load(&cart_id, &game_name));
+                // Until the BIOS sends this launch's mapping
+                self.game_controls = self
+                    .controls_cache
+                    .as_ref()
+                    .map(|c| c.load(&cart_id))
+                    .unwrap_or_default();
+                self.playtime.start_session(cart_id);
+            }
+            OverlayMessage::GameControls { cart_id, controls } => {
+                println!("[State] {} control hints for {}", controls.len(), cart_id);
+                if let Some(cache) = &self.controls_cache {
+                    if let Err(e) = cache.store(&cart_id, &controls) {
+                        eprintln!("[State] Failed to cache controls: {:#}", e);
                     }
-                    1 => {
-                        // Theme Selection
-                        self.current_screen = OverlayScreen::ThemeSelection;
-                        self.theme_selected = 0;
-                        println!("[State] Switched to Theme Selection");
+                }
+                self.game_controls = controls;
+            }
+            OverlayMessage::RaGameStart {
+                game_title,
+                game_id,
+                game_icon,
+                total_achievements,
+                earned_achievements,
+                console,
+                icon_url,
+                icon_path,
+                difficulty,
+                expected_master_secs,
+            } => {
+                println!(
+                    "[State] RA Game started: {} ({}/{})",
+                    game_title, earned_achievements, total_achievements
+                );
+                self.achievements.difficulty = difficulty.map(|score| Difficulty {
+                    score,
+                    expected_master_secs,
+                });
+                self.achievements.game_title = game_title.clone();
+                if let Some(console) = console {
+                    self.achievements.console = console;
+                }
+                // Older senders only set game_icon, and none send the cached file
+                let icon_url = icon_url.or(game_icon).filter(|url| !url.is_empty());
+                let icon_path = icon_path
+                    .map(PathBuf::from)
+                    .or_else(|| icon_url.as_deref().and_then(game_icon::cached_icon_path));
+                self.achievements.icon_url = icon_url;
+                self.achievements.icon.set_path(icon_path);
+                self.achievements.game_id = None;
+                if let Some(id) = game_id {
+                    self.achievements.game_id = Some(id);
+                    self.start_ra_poller(id);
+                }
+                self.achievements
+                    .update_progress(earned_achievements, total_achievements);
+                if self.network.is_offline() {
+                    self.load_cached_achievements();
+                }
+                if let Some(stream) = &mut self.stream {
+                    stream.set_game_title(&game_title);
+                    stream.set_progress(earned_achievements, total_achievements);
+                }
+            }
+            OverlayMessage::RaAchievementList {
+                game_title,
+                game_hash,
+                achievements,
+            } => {
+                if !game_title.is_empty() {
+                    self.achievements.game_title = game_title;
+                }
+                self.achievements.game_hash = Some(game_hash);
+                let update = self.achievements.set_achievements(achievements);
+                if self.achievement_filter.is_active() {
+                    match update {
+                        ListUpdate::Replaced => self
+                            .achievement_filter
+                            .apply_filter(&self.achievements.achievements),
+                        ListUpdate::Patched(changed) => {
+                            for index in changed {
+                                self.achievement_filter
+                                    .update_entry(&self.achievements.achievements, index);
+                            }
+                        }
                     }
-                    _ => {}
+                }
+                if let Some(poller) = &mut self.ra_poller {
+                    poller.earned = self
+                        .achievements
+                        .achievements
+                        .iter()
+                        .filter(|a| a.earned)
+                        .map(|a| a.id)
+                        .collect();
                 }
             }
-            ControllerInput::Back => {
-                self.current_screen = OverlayScreen::Main;
-                println!("[State] Returning to main menu");
+            OverlayMessage::RaChallenges { sets } => {
+                println!("[State] Received {} challenge set(s)", sets.len());
+                self.challenges = sets;
             }
-            _ => {}
-        }
-    }
-
-    fn handle_controllers_input(&mut self, input: ControllerInput) {
-        match input {
-            ControllerInput::Up => {
-                if self.controllers.selected_menu_item > 0 {
-                    self.controllers.selected_menu_item -= 1;
+            OverlayMessage::RaUnlockHistory { game_hash, unlocks } => {
+                if self
+                    .achievements
+                    .game_hash
+                    .as_ref()
+                    .is_some_and(|hash| *hash != game_hash)
+                {
+                    println!(
+                        "[State] Ignoring unlock history for another game: {}",
+                        game_hash
+                    );
+                } else {
+                    println!("[State] Received {} unlocks of history", unlocks.len());
+                    self.achievements.unlocks = unlocks;
                 }
             }
-            ControllerInput::Down => {
-                if self.controllers.selected_menu_item < CONTROLLER_MENU_OPTIONS.len() - 1 {
-                    self.controllers.selected_menu_item += 1;
+            OverlayMessage::RaProgressUpdate { earned, total } => {
+                self.achievements.update_progress(earned, total);
+                if let Some(stream) = &mut self.stream {
+                    stream.set_progress(earned, total);
                 }
             }
-            ControllerInput::Select => {
-                match self.controllers.selected_menu_item {
-                    0 => {
-                        // View connected controllers (already on this screen)
-                        println!("[State] Viewing connected controllers");
-                    }
-                    1 => {
-                        // Gamepad Tester
-                        self.current_screen = OverlayScreen::GamepadTester;
-                        println!("[State] Switched to Gamepad Tester");
-                    }
-                    2 => {
-                        // Controller Settings
-                        println!("[State] Controller Settings (TODO)");
-                    }
-                    3 => {
-                        // Hotkey Settings
-                        self.current_screen = OverlayScreen::HotkeySettings;
-                        println!("[State] Switched to Hotkey Settings");
+            OverlayMessage::RaProgressMeasure {
+                achievement_id,
+                current,
+                target,
+            } => {
+                // Sent often while the value changes, so only log unknown IDs
+                if !self
+                    .achievements
+                    .set_measured_progress(achievement_id, current, target)
+                {
+                    println!(
+                        "[State] Progress for unknown achievement {}",
+                        achievement_id
+                    );
+                }
+            }
+            OverlayMessage::RaAchievementUnlocked {
+                achievement_id,
+                title,
+                description,
+                points,
+                icon_url: _,
+                is_hardcore,
+                player,
+            } => {
+                // In a shared session only the logged-in account's unlocks
+                // count towards its progress and history
+                let for_account = player.as_ref().is_none_or(|p| p.account);
+                let changed = match &player {
+                    Some(player) => self.achievements.player_unlock(player, achievement_id),
+                    None => self.achievements.unlock_achievement(achievement_id),
+                };
+                self.refilter_achievement(changed);
+                if !self.visible {
+                    self.unlocks_while_closed += 1;
+                }
+                if for_account {
+                    self.achievements.unlocks.push(UnlockRecord {
+                        achievement_id,
+                        earned_at: unix_now(),
+                        hardcore: is_hardcore,
+                        points,
+                    });
+                    if let Some(stream) = &mut self.stream {
+                        stream.achievement_unlocked(&title, description.clone(), points);
+                        stream.set_progress(
+                            self.achievements.progress.earned,
+                            self.achievements.progress.total,
+                        );
                     }
-                    _ => {}
+                }
+                let desc_text = description.unwrap_or_default();
+                self.request_haptic(HapticPattern::Unlock);
+                let message = match &player {
+                    Some(player) => format!("🏆 {}: {} ({} points)", player.name, title, points),
+                    None => format!("🏆 {} ({} points)", title, points),
+                };
+                self.toasts
+                    .add_toast(message, None, ToastStyle::Success, 5000);
+                match &player {
+                    Some(player) => println!(
+                        "[State] Achievement unlocked by {}: {} - {}",
+                        player.name, title, desc_text
+                    ),
+                    None => println!("[State] Achievement unlocked: {} - {}", title, desc_text),
                 }
             }
-            ControllerInput::Back => {
-                self.current_screen = OverlayScreen::Main;
-                self.selected_option = 3; // Keep Controllers selected
-                println!("[State] Returning to main menu");
+            OverlayMessage::RaSessionPlayers { players } => {
+                println!("[State] Shared session with {} player(s)", players.len());
+                self.achievements.set_players(players);
+                self.achievements_player = 0;
+            }
+            OverlayMessage::RaMastery {
+                game_title,
+                hardcore,
+                total_achievements,
+                points,
+                completion_secs,
+            } => {
+                println!(
+                    "[State] Mastered {} ({} achievements, {} points{})",
+                    game_title,
+                    total_achievements,
+                    points,
+                    if hardcore { ", hardcore" } else { "" }
+                );
+                self.achievements
+                    .update_progress(total_achievements, total_achievements);
+                self.request_haptic(HapticPattern::Unlock);
+                self.celebration = Some(Celebration::new(
+                    game_title,
+                    hardcore,
+                    total_achievements,
+                    points,
+                    completion_secs,
+                ));
+            }
+            OverlayMessage::SetTheme {
+                font_color,
+                cursor_color,
+            } => {
+                // Note: SetTheme in IPC currently just sets colors, not a full theme
+                // For now, just log it
+                println!(
+                    "[State] SetTheme called with font_color={}, cursor_color={}",
+                    font_color, cursor_color
+                );
+                // TODO: Apply custom colors to theme
+            }
+            OverlayMessage::GameStopped { cart_id } => {
+                println!("[State] Game stopped: {}", cart_id);
+                self.watchdog = None;
+                // Never leave a frozen process behind
+                if let Some(auto_pause) = &mut self.auto_pause {
+                    auto_pause.resume();
+                }
+                self.playtime.end_session();
+                // Clear achievement data when game stops
+                self.achievements.clear();
+                self.ra_poller = None;
+                self.achievements_scroll_offset = 0;
+                self.achievements_selected = 0;
+                self.achievements_player = 0;
+                self.achievement_filter.clear();
+                self.hints = None;
+                self.compare = None;
+                if matches!(
+                    self.current_screen,
+                    OverlayScreen::AchievementHints
+                        | OverlayScreen::FriendCompare
+                        | OverlayScreen::AchievementStats
+                ) {
+                    self.current_screen = OverlayScreen::Achievements;
+                }
+                if self.cheats.take().is_some() {
+                    CheatList::clear();
+                }
+                self.game_controls.clear();
+                if matches!(
+                    self.current_screen,
+                    OverlayScreen::Cheats | OverlayScreen::GameControls
+                ) {
+                    self.current_screen = OverlayScreen::Main;
+                }
+                if let Some(stream) = &mut self.stream {
+                    stream.game_stopped();
+                }
+                if let Some(speedrun) = &mut self.speedrun {
+                    speedrun.game_stopped();
+                }
+                // The BIOS goes back to its own menu music
+                if let Some(music) = &self.music {
+                    music.stop();
+                }
+            }
+            OverlayMessage::QuitGame => {
+                // This is handled in main.rs - trigger quit signal
+                println!("[State] Quit game requested");
+            }
+            OverlayMessage::QuitGameAck => {
+                println!("[State] Quit game acknowledged");
+                self.toasts.add_toast(
+                    "Returning to BIOS...".to_string(),
+                    None,
+                    ToastStyle::Info,
+                    2000,
+                );
+            }
+            OverlayMessage::UnlockAchievement {
+                cart_id,
+                achievement_id,
+                timestamp,
+            } => {
+                println!(
+                    "[State] Achievement unlocked: cart={}, id={}, time={}",
+                    cart_id, achievement_id, timestamp
+                );
+                // This is handled by RaAchievementUnlocked for RetroAchievements
+            }
+            OverlayMessage::GetStatus => {
+                // Status query - could be used for IPC health checks
+                println!("[State] Status requested");
+            }
+            OverlayMessage::ToggleOverlay => {
+                self.toggle_visibility();
+                println!("[State] Toggled overlay via IPC message");
+            }
+            OverlayMessage::HideOverlay => {
+                self.visible = false;
+                self.pointer.clear();
+                println!("[State] Hiding overlay via IPC message");
+            }
+            OverlayMessage::Shutdown => {
+                println!("[State] Shutdown requested, saving state");
+                if let Some(auto_pause) = &mut self.auto_pause {
+                    auto_pause.resume();
+                }
+                self.playtime.end_session();
+                self.shutdown_requested = true;
+            }
+            OverlayMessage::Pointer { x, y, action } => {
+                let screen = vec2(screen_width(), screen_height());
+                self.handle_pointer(vec2(x * screen.x, y * screen.y), screen, action);
+            }
+            OverlayMessage::ControllerConnected {
+                controller_id,
+                name,
+                vendor_id,
+                product_id,
+            } => {
+                println!("[State] Controller connected: {} ({})", name, controller_id);
+                self.controllers.input_device_connected(InputDevice {
+                    controller_id,
+                    name,
+                    vendor_id,
+                    product_id,
+                });
+            }
+            OverlayMessage::ControllerDisconnected { controller_id } => {
+                println!("[State] Controller disconnected: {}", controller_id);
+                self.controllers.input_device_disconnected(&controller_id);
+            }
+            OverlayMessage::GameList { games } => {
+                println!("[State] Received {} installed games", games.len());
+                self.games = games;
             }
-            _ => {}
         }
     }
 
-    fn handle_gamepad_tester_input(&mut self, input: ControllerInput) {
-        match input {
-            ControllerInput::Back => {
-                self.current_screen = OverlayScreen::Controllers;
-                self.controllers.selected_menu_item = 1; // Keep Gamepad Tester selected
-                println!("[State] Returning to Controllers menu");
+    /// React to connectivity changes from the network monitor
+    fn update_network(&mut self) {
+        let was_known = self.network.status().is_some();
+        let Some(online) = self.network.poll() else {
+            return;
+        };
+
+        if online {
+            // Catch up on unlocks that happened while offline
+            if let Some(poller) = &mut self.ra_poller {
+                poller.backoff = None;
+                poller.last_poll = Instant::now() - poller.interval;
             }
-            _ => {
-                // All other inputs are tracked by the tester
+            if was_known {
+                self.toasts
+                    .add_toast("Back online".to_string(), None, ToastStyle::Info, 3000);
+            }
+        } else {
+            self.load_cached_achievements();
+            if was_known {
+                self.toasts.add_toast(
+                    "Offline - achievements will sync when reconnected".to_string(),
+                    None,
+                    ToastStyle::Warning,
+                    4000,
+                );
             }
         }
     }
 
-    fn handle_playtime_input(&mut self, input: ControllerInput) {
-        match input {
-            ControllerInput::Back => {
-                self.current_screen = OverlayScreen::Main;
-                println!("[State] Returning to main menu");
+    /// Fill an empty achievement list from the local RA cache, so the
+    /// achievements screen isn't blank while offline
+    fn load_cached_achievements(&mut self) {
+        if !self.achievements.achievements.is_empty() {
+            return;
+        }
+        let Some(game_id) = self.achievements.game_id else {
+            return;
+        };
+
+        let cache = match RACache::new() {
+            Ok(cache) => cache,
+            Err(e) => {
+                eprintln!("[RA] Cache unavailable: {}", e);
+                return;
+            }
+        };
+        let hash = match &self.achievements.game_hash {
+            Some(hash) => Some(hash.clone()),
+            None => cache.get_game_hash(game_id).unwrap_or(None),
+        };
+        let Some(hash) = hash else {
+            return;
+        };
+
+        let cached = match cache.get_achievements(&hash) {
+            Ok(cached) if !cached.is_empty() => cached,
+            Ok(_) => return,
+            Err(e) => {
+                eprintln!("[RA] Failed to read cached achievements: {}", e);
+                return;
+            }
+        };
+
+        println!("[RA] Offline, showing {} cached achievements", cached.len());
+        let translations = Translations::load_or_default(game_id);
+        if self.achievements.game_title.is_empty() {
+            if let Ok(Some(title)) = cache.get_game_title(&hash) {
+                self.achievements.game_title = translations.game_title(&title).to_string();
+            }
+        }
+        if self.achievements.difficulty.is_none() {
+            if let Ok(Some(estimate)) = cache.get_difficulty(&hash) {
+                self.achievements.difficulty = Some(Difficulty {
+                    score: estimate.score,
+                    expected_master_secs: estimate.expected_master_secs,
+                });
             }
-            _ => {}
         }
+        self.achievements.game_hash = Some(hash);
+        let achievements = cached
+            .into_iter()
+            .map(|a| AchievementInfo {
+                earned: a.is_earned(),
+                earned_hardcore: a.is_earned_hardcore(),
+                id: a.id,
+                title: translations.achievement_title(a.id, &a.title).to_string(),
+                description: translations
+                    .achievement_description(a.id, a.description.as_deref().unwrap_or_default())
+                    .to_string(),
+                points: a.points,
+                rarity_percent: None,
+                earned_at: None,
+                progress: None,
+                unofficial: false,
+            })
+            .collect();
+        self.achievements.set_achievements(achievements);
     }
 
-    fn handle_menu_customization_input(&mut self, input: ControllerInput) {
-        let all_items = MenuItemId::all();
-        let item_count = all_items.len();
-        const MAX_VISIBLE: usize = 6;
+    /// Pick the running game back up from kazeta-ra's session file, for when
+    /// the overlay is restarted mid-game
+    pub fn restore_ra_session(&mut self) {
+        let session = match GameSession::load() {
+            Ok(Some(session)) => session,
+            Ok(None) => return,
+            Err(e) => {
+                eprintln!("[RA] Failed to read session file: {}", e);
+                return;
+            }
+        };
 
-        match input {
-            ControllerInput::Up => {
-                if self.menu_customization_selected > 0 {
-                    self.menu_customization_selected -= 1;
-                    Self::adjust_scroll_offset(
-                        self.menu_customization_selected,
-                        &mut self.menu_customization_scroll_offset,
-                        MAX_VISIBLE,
-                        item_count,
-                    );
-                }
-            }
-            ControllerInput::Down => {
-                if self.menu_customization_selected < item_count.saturating_sub(1) {
-                    self.menu_customization_selected += 1;
-                    Self::adjust_scroll_offset(
-                        self.menu_customization_selected,
-                        &mut self.menu_customization_scroll_offset,
-                        MAX_VISIBLE,
-                        item_count,
-                    );
-                }
+        println!("[State] Recovering RA session for game {}", session.game_id);
+        self.achievements.game_id = Some(session.game_id);
+        self.achievements.game_hash = Some(session.hash);
+        if !session.title.is_empty() {
+            self.achievements.game_title = session.title;
+        }
+        if !session.players.is_empty() {
+            let account = CredentialManager::new()
+                .ok()
+                .and_then(|m| m.load().ok().flatten())
+                .map(|c| c.username)
+                .unwrap_or_default();
+            self.achievements.set_players(
+                session
+                    .players
+                    .iter()
+                    .map(|p| SessionPlayer {
+                        slot: p.slot,
+                        name: p.username.clone(),
+                        account: p.is_account(&account),
+                    })
+                    .collect(),
+            );
+        }
+        self.load_cached_achievements();
+        self.start_ra_poller(session.game_id);
+    }
+
+    fn start_ra_poller(&mut self, game_id: u32) {
+        let earned: HashSet<u32> = self
+            .achievements
+            .achievements
+            .iter()
+            .filter(|a| a.earned)
+            .map(|a| a.id)
+            .collect();
+
+        self.ra_poller = Some(RaPoller {
+            game_id,
+            earned,
+            last_poll: Instant::now(),
+            interval: Duration::from_secs(20),
+            backoff: None,
+        });
+    }
+
+    fn update_ra_polling(&mut self) {
+        // Polling would only fail and back off; resumes when back online
+        if self.network.is_offline() {
+            return;
+        }
+        let Some(poller) = self.ra_poller.as_mut() else {
+            return;
+        };
+
+        let interval = poller.backoff.unwrap_or(poller.interval);
+        if poller.last_poll.elapsed() < interval {
+            return;
+        }
+        poller.last_poll = Instant::now();
+
+        let cred_mgr = match CredentialManager::new() {
+            Ok(c) => c,
+            Err(e) => {
+                eprintln!("[RA] Credential manager error: {}", e);
+                poller.backoff = Some(interval * 2);
+                return;
             }
-            ControllerInput::Left | ControllerInput::Right | ControllerInput::Select => {
-                // Toggle visibility of selected menu item
-                if self.menu_customization_selected < all_items.len() {
-                    let item_id = all_items[self.menu_customization_selected];
-                    self.menu_config.config_mut().toggle_visibility(item_id);
+        };
 
-                    // Save configuration
-                    if let Err(e) = self.menu_config.save() {
-                        eprintln!("[State] Failed to save menu config: {}", e);
-                        self.toasts.add_toast(
-                            format!("Failed to save menu config: {}", e),
-                            None,
-                            ToastStyle::Error,
-                            3000,
-                        );
-                    } else {
-                        let is_visible = self.menu_config.config().items.iter()
-                            .find(|i| i.id == item_id)
-                            .map(|i| i.visible)
-                            .unwrap_or(false);
+        let credentials = match cred_mgr.load() {
+            Ok(Some(c)) => c,
+            Ok(None) => {
+                eprintln!("[RA] No credentials found");
+                poller.backoff = Some(interval * 2);
+                return;
+            }
+            Err(e) => {
+                eprintln!("[RA] Failed to load credentials: {}", e);
+                poller.backoff = Some(interval * 2);
+                return;
+            }
+        };
 
-                        let status = if is_visible { "shown" } else { "hidden" };
-                        self.toasts.add_toast(
-                            format!("{} is now {}", item_id.display_name(), status),
-                            None,
-                            ToastStyle::Info,
-                            2000,
-                        );
-                        println!("[State] Toggled visibility for {:?}: {}", item_id, is_visible);
-                    }
-                }
+        let client = RAClient::new(credentials);
+        let game_id = poller.game_id;
+        match client.get_game_info_and_progress(game_id) {
+            Ok(info) => {
+                poller.backoff = None;
+                drop(poller); // Drop the mutable borrow before calling apply_ra_poll
+                self.apply_ra_poll(info);
             }
-            ControllerInput::LB => {
-                // Move selected item up in order
-                if self.menu_customization_selected < all_items.len() {
-                    let item_id = all_items[self.menu_customization_selected];
-                    self.menu_config.config_mut().move_up(item_id);
+            Err(e) => {
+                eprintln!("[RA] Poll failed: {}", e);
+                poller.backoff = Some(interval * 2);
+            }
+        }
+    }
 
-                    // Save configuration
-                    if let Err(e) = self.menu_config.save() {
-                        eprintln!("[State] Failed to save menu config: {}", e);
-                    } else {
-                        self.toasts.add_toast(
-                            format!("Moved {} up", item_id.display_name()),
-                            None,
-                            ToastStyle::Info,
-                            1500,
-                        );
-                        println!("[State] Moved {:?} up", item_id);
-                    }
+    fn apply_ra_poll(&mut self, info: GameInfoAndProgress) {
+        let Some(poller) = self.ra_poller.as_mut() else {
+            return;
+        };
+        let Some(achievements_map) = info.achievements.as_ref() else {
+            return;
+        };
+
+        let mut earned_now: HashSet<u32> = HashSet::new();
+        let mut newly_unlocked = Vec::new();
+
+        for achievement in achievements_map.values() {
+            if achievement.is_earned() {
+                earned_now.insert(achievement.id);
+                if poller.earned.insert(achievement.id) {
+                    newly_unlocked.push((
+                        achievement.id,
+                        achievement.title.clone(),
+                        achievement.points,
+                    ));
                 }
             }
-            ControllerInput::RB => {
-                // Move selected item down in order
-                if self.menu_customization_selected < all_items.len() {
-                    let item_id = all_items[self.menu_customization_selected];
-                    self.menu_config.config_mut().move_down(item_id);
+        }
 
-                    // Save configuration
-                    if let Err(e) = self.menu_config.save() {
-                        eprintln!("[State] Failed to save menu config: {}", e);
-                    } else {
-                        self.toasts.add_toast(
-                            format!("Moved {} down", item_id.display_name()),
-                            None,
-                            ToastStyle::Info,
-                            1500,
-                        );
-                        println!("[State] Moved {:?} down", item_id);
-                    }
-                }
+        poller.earned = earned_now;
+        self.achievements
+            .update_progress(poller.earned.len() as u32, info.num_achievements);
+
+        if let Some(stream) = &mut self.stream {
+            stream.set_progress(
+                self.achievements.progress.earned,
+                self.achievements.progress.total,
+            );
+        }
+
+        for (id, title, points) in newly_unlocked {
+            let changed = self.achievements.unlock_achievement(id);
+            self.refilter_achievement(changed);
+            if let Some(stream) = &mut self.stream {
+                stream.achievement_unlocked(&title, None, points);
             }
-            ControllerInput::Back => {
-                self.current_screen = OverlayScreen::Settings;
-                self.settings_selected_option = 0;
-                println!("[State] Returning to Settings");
+            self.request_haptic(HapticPattern::Unlock);
+            self.toasts.add_toast(
+                format!("🏆 {} ({} pts)", title, points),
+                None,
+                ToastStyle::Success,
+                5000,
+            );
+            println!("[RA] Detected unlock via polling: {} ({})", title, id);
+        }
+    }
+
+    pub fn handle_input(&mut self, input: ControllerInput) {
+        if !self.visible {
+            return;
+        }
+
+        // Any button dismisses the celebration before it times out
+        if self.celebration.take().is_some() {
+            return;
+        }
+
+        // The tour takes over the main menu until it's finished or skipped
+        if self.tour.is_some() && self.current_screen == OverlayScreen::Main {
+            self.handle_tour_input(input);
+            return;
+        }
+
+        if matches!(
+            input,
+            ControllerInput::Up
+                | ControllerInput::Down
+                | ControllerInput::Left
+                | ControllerInput::Right
+        ) {
+            self.request_haptic(HapticPattern::Tick);
+        }
+
+        match self.current_screen {
+            OverlayScreen::Main => self.handle_main_menu_input(input),
+            OverlayScreen::Achievements => self.handle_achievements_input(input),
+            OverlayScreen::Challenges => self.handle_challenges_input(input),
+            OverlayScreen::AchievementHints => self.handle_hints_input(input),
+            OverlayScreen::FriendCompare => self.handle_compare_input(input),
+            OverlayScreen::AchievementStats => self.handle_achievement_stats_input(input),
+            OverlayScreen::Performance => self.handle_performance_input(input),
+            OverlayScreen::Settings => self.handle_settings_input(input),
+            OverlayScreen::RaLogin => self.handle_login_input(input),
+            OverlayScreen::Controllers => self.handle_controllers_input(input),
+            OverlayScreen::GamepadTester => self.handle_gamepad_tester_input(input),
+            OverlayScreen::ControllerSettings => self.handle_controller_settings_input(input),
+            OverlayScreen::Macros => self.handle_macros_input(input),
+            OverlayScreen::Music => self.handle_music_input(input),
+            OverlayScreen::Playtime => self.handle_playtime_input(input),
+            OverlayScreen::Speedrun => self.handle_speedrun_input(input),
+            OverlayScreen::Cheats => self.handle_cheats_input(input),
+            OverlayScreen::GameControls => self.handle_game_controls_input(input),
+            OverlayScreen::HotkeySettings => self.handle_hotkey_settings_input(input),
+            OverlayScreen::MenuCustomization => self.handle_menu_customization_input(input),
+            OverlayScreen::QuickActions => self.handle_quick_actions_input(input),
+            OverlayScreen::QuickActionsSettings => self.handle_quick_actions_settings_input(input),
+            OverlayScreen::IpcRules => self.handle_ipc_rules_input(input),
+            OverlayScreen::DebugConsole => self.handle_debug_console_input(input),
+            OverlayScreen::ThemeSelection => self.handle_theme_selection_input(input),
+            OverlayScreen::ThemePreview => self.handle_theme_preview_input(input),
+            OverlayScreen::ThemeEditor => self.handle_theme_editor_input(input),
+            OverlayScreen::QuitConfirm => self.handle_quit_confirm_input(input),
+            // Nothing to do but wait for the save
+            OverlayScreen::QuitSaving => {}
+            OverlayScreen::BluetoothPairing => self.handle_bluetooth_pairing_input(input),
+            OverlayScreen::ControllerAssign => self.handle_controller_assign_input(input),
+            OverlayScreen::Search => self.handle_search_input(input),
+            OverlayScreen::HudLayout => {
+                self.edit_hud_layout(input, vec2(screen_width(), screen_height()))
             }
-            _ => {}
         }
     }
 
-    fn handle_theme_selection_input(&mut self, input: ControllerInput) {
-        use crate::themes::Theme;
-        let themes = Theme::all_presets();
-        let theme_count = themes.len();
-        const MAX_VISIBLE: usize = 5;
+    /// HUD layout editor: LB/RB pick a widget, the D-Pad moves it, LT/RT
+    /// resize it, A shows or hides it and X snaps it to the nearest corner.
+    /// B saves and leaves. A running game gets its own layout.
+    fn edit_hud_layout(&mut self, input: ControllerInput, screen: Vec2) {
+        const STEP: f32 = 10.0;
+        const SCALE_STEP: f32 = 0.1;
+
+        let count = HudWidget::ALL.len();
+        let widget = HudWidget::ALL[self.hud_editor_selected % count];
+        let cart_id = self.current_cart_id().map(str::to_string);
+        let Some(hud_layouts) = self.hud_layouts.as_mut() else {
+            self.current_screen = OverlayScreen::Settings;
+            return;
+        };
+        let layout = hud_layouts
+            .config_mut()
+            .layout_mut(cart_id.as_deref())
+            .get_mut(widget);
 
         match input {
-            ControllerInput::Up => {
-                if self.theme_selected > 0 {
-                    self.theme_selected -= 1;
-                    Self::adjust_scroll_offset(
-                        self.theme_selected,
-                        &mut self.theme_selection_scroll_offset,
-                        MAX_VISIBLE,
-                        theme_count,
-                    );
-                }
-            }
-            ControllerInput::Down => {
-                if self.theme_selected < theme_count.saturating_sub(1) {
-                    self.theme_selected += 1;
-                    Self::adjust_scroll_offset(
-                        self.theme_selected,
-                        &mut self.theme_selection_scroll_offset,
-                        MAX_VISIBLE,
-                        theme_count,
-                    );
-                }
+            ControllerInput::Up => layout.nudge(widget, vec2(0.0, -STEP), screen),
+            ControllerInput::Down => layout.nudge(widget, vec2(0.0, STEP), screen),
+            ControllerInput::Left => layout.nudge(widget, vec2(-STEP, 0.0), screen),
+            ControllerInput::Right => layout.nudge(widget, vec2(STEP, 0.0), screen),
+            ControllerInput::LT => layout.resize(widget, -SCALE_STEP, screen),
+            ControllerInput::RT => layout.resize(widget, SCALE_STEP, screen),
+            ControllerInput::Select => layout.visible = !layout.visible,
+            ControllerInput::Secondary => layout.snap(widget, screen),
+            ControllerInput::LB => {
+                self.hud_editor_selected = (self.hud_editor_selected + count - 1) % count
             }
-            ControllerInput::Select => {
-                if self.theme_selected < themes.len() {
-                    let theme = &themes[self.theme_selected];
-                    if let Err(e) = self.theme_config.set_theme(&theme.name) {
-                        eprintln!("[State] Failed to set theme: {}", e);
-                        self.toasts.add_toast(
-                            format!("Failed to set theme: {}", e),
-                            None,
-                            ToastStyle::Error,
-                            3000,
-                        );
-                    } else {
-                        self.toasts.add_toast(
-                            format!("Theme set to: {}", theme.name),
-                            None,
-                            ToastStyle::Info,
-                            2000,
-                        );
-                        println!("[State] Theme changed to: {}", theme.name);
-                    }
-                }
+            ControllerInput::RB => {
+                self.hud_editor_selected = (self.hud_editor_selected + 1) % count
             }
             ControllerInput::Back => {
+                if let Err(e) = hud_layouts.save() {
+                    eprintln!("[State] Failed to save HUD layout: {}", e);
+                }
                 self.current_screen = OverlayScreen::Settings;
-                self.settings_selected_option = 1;
-                println!("[State] Returning to Settings");
+                println!("[State] Saved HUD layout");
+            }
+            ControllerInput::Guide => {}
+        }
+    }
+
+    /// Handle a pointer event at a position in screen pixels. Hovering a row
+    /// selects it; a press and release on the same row activates it.
+    pub fn handle_pointer(&mut self, position: Vec2, screen: Vec2, action: PointerAction) {
+        if !self.visible {
+            return;
+        }
+
+        self.pointer.moved_to(position);
+        let target = self.pointer_target_at(position, screen);
+        if target != self.pointer.hovered {
+            self.pointer.hovered = target;
+            if let Some(target) = target {
+                self.select_pointer_target(target);
+                self.request_haptic(HapticPattern::Tick);
+            }
+        }
+
+        match action {
+            PointerAction::Move => {}
+            PointerAction::Press => self.pointer.pressed = target,
+            PointerAction::Release => {
+                if target.is_some() && self.pointer.pressed.take() == target {
+                    self.handle_input(ControllerInput::Select);
+                }
+            }
+            PointerAction::Back => self.handle_input(ControllerInput::Back),
+        }
+    }
+
+    /// The menu row under a position on the current screen
+    fn pointer_target_at(&self, position: Vec2, screen: Vec2) -> Option<PointerTarget> {
+        match self.current_screen {
+            // The tour highlights rows itself
+            OverlayScreen::Main if self.tour.is_some() => None,
+            OverlayScreen::Main => {
+                let count = self.menu_config.config().get_visible_items().len();
+                MAIN_MENU_LAYOUT
+                    .row_at(screen, position)
+                    .map(|row| self.main_menu_scroll_offset + row)
+                    .filter(|&index| index < count)
+                    .map(PointerTarget::MainMenu)
+            }
+            OverlayScreen::Settings => SETTINGS_LAYOUT
+                .row_at(screen, position)
+                .map(|row| self.settings_scroll_offset + row)
+                .filter(|&index| index < SETTINGS_OPTIONS)
+                .map(PointerTarget::Settings),
+            _ => None,
+        }
+    }
+
+    fn select_pointer_target(&mut self, target: PointerTarget) {
+        // Targets are always on screen, so the scroll offsets stay put
+        match target {
+            PointerTarget::MainMenu(index) => self.selected_option = index,
+            PointerTarget::Settings(index) => self.settings_selected_option = index,
+        }
+    }
+
+    /// Helper to adjust scroll offset to keep selected item visible
+    fn adjust_scroll_offset(
+        selected: usize,
+        scroll_offset: &mut usize,
+        max_visible: usize,
+        total_items: usize,
+    ) {
+        if selected < *scroll_offset {
+            // Selected item is above visible area, scroll up
+            *scroll_offset = selected;
+        } else if selected >= *scroll_offset + max_visible {
+            // Selected item is below visible area, scroll down
+            *scroll_offset = selected - max_visible + 1;
+        }
+
+        // Ensure scroll offset doesn't go past the end
+        let max_scroll = total_items.saturating_sub(max_visible);
+        if *scroll_offset > max_scroll {
+            *scroll_offset = max_scroll;
+        }
+    }
+
+    fn handle_main_menu_input(&mut self, input: ControllerInput) {
+        let visible_items = self.menu_config.config().get_visible_items();
+        let item_count = visible_items.len();
+
+        match input {
+            ControllerInput::Up => {
+                if self.selected_option > 0 {
+                    self.selected_option -= 1;
+                    Self::adjust_scroll_offset(
+                        self.selected_option,
+                        &mut self.main_menu_scroll_offset,
+                        6,
+                        item_count,
+                    );
+                }
+            }
+            ControllerInput::Down => {
+                if self.selected_option < item_count.saturating_sub(1) {
+                    self.selected_option += 1;
+                    Self::adjust_scroll_offset(
+                        self.selected_option,
+                        &mut self.main_menu_scroll_offset,
+                        6,
+                        item_count,
+                    );
+                }
+            }
+            ControllerInput::Select => {
+                if let Some(&id) = visible_items.get(self.selected_option) {
+                    self.open_menu_item(id);
+                }
+            }
+            ControllerInput::Secondary => self.open_search(),
+            ControllerInput::Back | ControllerInput::Guide => {
+                // Close overlay
+                self.visible = false;
+                println!("[State] Overlay closed");
+            }
+            _ => {}
+        }
+    }
+
+    /// Start the tour the first time the overlay opens for this profile
+    fn start_tour_if_new(&mut self) {
+        let profile = onboarding::profile_name(self.ra_user.as_deref());
+        let seen = self.onboarding.as_ref().is_none_or(|o| o.has_seen(profile));
+        if !seen && self.tour.is_none() {
+            self.start_tour();
+        }
+    }
+
+    fn start_tour(&mut self) {
+        let items = self.menu_config.config().get_visible_items();
+        if items.is_empty() {
+            return;
+        }
+        self.tour = Some(Tour::new(&items));
+        self.highlight_tour_step();
+        println!("[State] Started onboarding tour");
+    }
+
+    /// Select and scroll to the menu item the tour is describing
+    fn highlight_tour_step(&mut self) {
+        let Some(step) = self.tour.as_ref().and_then(|t| t.current()) else {
+            return;
+        };
+        let visible_items = self.menu_config.config().get_visible_items();
+        if let Some(index) = visible_items.iter().position(|&id| id == step.item) {
+            self.selected_option = index;
+            Self::adjust_scroll_offset(
+                index,
+                &mut self.main_menu_scroll_offset,
+                MAIN_MENU_LAYOUT.visible_rows,
+                visible_items.len(),
+            );
+        }
+    }
+
+    /// A moves on, B goes back, X skips the rest
+    fn handle_tour_input(&mut self, input: ControllerInput) {
+        let Some(tour) = &mut self.tour else { return };
+        match input {
+            ControllerInput::Select => {
+                if tour.next() {
+                    self.highlight_tour_step();
+                } else {
+                    self.finish_tour();
+                }
+            }
+            ControllerInput::Back => {
+                tour.back();
+                self.highlight_tour_step();
+            }
+            ControllerInput::Secondary => self.finish_tour(),
+            // Closing keeps the tour's place for next time
+            ControllerInput::Guide => {
+                self.visible = false;
+                println!("[State] Overlay closed");
+            }
+            _ => {}
+        }
+    }
+
+    /// End the tour and don't show it to this profile again
+    fn finish_tour(&mut self) {
+        self.tour = None;
+        self.selected_option = 0;
+        self.main_menu_scroll_offset = 0;
+        let profile = onboarding::profile_name(self.ra_user.as_deref()).to_string();
+        if let Some(onboarding) = &mut self.onboarding {
+            if let Err(e) = onboarding.mark_seen(&profile) {
+                eprintln!("[State] Failed to record onboarding: {}", e);
+            }
+        }
+        println!("[State] Finished onboarding tour for {}", profile);
+    }
+
+    fn open_menu_item(&mut self, id: MenuItemId) {
+        match id {
+            MenuItemId::Achievements => {
+                self.current_screen = OverlayScreen::Achievements;
+                println!("[State] Switched to Achievements screen");
+            }
+            MenuItemId::Challenges => {
+                self.current_screen = OverlayScreen::Challenges;
+                self.challenges_scroll = 0;
+                println!("[State] Switched to Challenges screen");
+            }
+            MenuItemId::Performance => {
+                self.current_screen = OverlayScreen::Performance;
+                println!("[State] Switched to Performance screen");
+            }
+            MenuItemId::Settings => {
+                self.current_screen = OverlayScreen::Settings;
+                self.settings_selected_option = 0;
+                println!("[State] Switched to Settings screen");
+            }
+            MenuItemId::Controllers => {
+                self.current_screen = OverlayScreen::Controllers;
+                self.controllers.selected_menu_item = 0;
+                println!("[State] Switched to Controllers screen");
+            }
+            MenuItemId::Playtime => {
+                self.current_screen = OverlayScreen::Playtime;
+                println!("[State] Switched to Playtime screen");
+            }
+            MenuItemId::Speedrun => {
+                self.current_screen = OverlayScreen::Speedrun;
+                self.speedrun_selected = 0;
+                println!("[State] Switched to Speedrun screen");
+            }
+            MenuItemId::Cheats => {
+                self.current_screen = OverlayScreen::Cheats;
+                println!("[State] Switched to Cheats screen");
+            }
+            MenuItemId::Controls => {
+                self.current_screen = OverlayScreen::GameControls;
+                println!("[State] Switched to Game Controls screen");
+            }
+            MenuItemId::Music => {
+                if let Some(music) = &mut self.music {
+                    music.rescan();
+                }
+                self.current_screen = OverlayScreen::Music;
+                self.music_selected = 0;
+                println!("[State] Switched to Music screen");
+            }
+            MenuItemId::QuickSave => {
+                // TODO: Implement quick save
+                println!("[State] Quick save requested (not implemented)");
+            }
+            MenuItemId::Resume => {
+                println!("[State] Resuming game");
+                self.visible = false;
+            }
+            MenuItemId::Quit => {
+                self.current_screen = OverlayScreen::QuitConfirm;
+                self.quit_confirm_selected = 0; // Default to Cancel button
+                println!("[State] Showing quit confirmation");
+            }
+        }
+    }
+
+    fn open_search(&mut self) {
+        let entries = search::build_index(
+            &self.menu_config.config().get_visible_items(),
+            self.ra_user.is_some(),
+            &self.achievements.achievements,
+            &self.games,
+        );
+        self.search = Some(SearchView::open(entries));
+        self.current_screen = OverlayScreen::Search;
+        println!("[State] Switched to Search screen");
+    }
+
+    fn handle_search_input(&mut self, input: ControllerInput) {
+        let Some(search) = &mut self.search else {
+            self.current_screen = OverlayScreen::Main;
+            return;
+        };
+
+        match search.handle_input(input) {
+            SearchAction::None => {}
+            SearchAction::Close => {
+                self.search = None;
+                self.current_screen = OverlayScreen::Main;
+                println!("[State] Returning to main menu");
+            }
+            SearchAction::Open(target) => {
+                self.search = None;
+                self.open_search_target(target);
+            }
+        }
+    }
+
+    /// Jump to the screen a search result points at
+    fn open_search_target(&mut self, target: SearchTarget) {
+        match target {
+            SearchTarget::Menu(id) => {
+                self.current_screen = OverlayScreen::Main;
+                self.open_menu_item(id);
+            }
+            SearchTarget::Setting(index) => {
+                self.current_screen = OverlayScreen::Settings;
+                self.settings_selected_option = index;
+                println!("[State] Switched to Settings screen");
+            }
+            SearchTarget::Achievement(index) => {
+                self.achievement_filter.clear();
+                self.current_screen = OverlayScreen::Achievements;
+                self.achievements_selected = index;
+                Self::adjust_scroll_offset(
+                    index,
+                    &mut self.achievements_scroll_offset,
+                    ACHIEVEMENTS_VISIBLE_ROWS,
+                    self.achievements.achievements.len(),
+                );
+                println!("[State] Switched to Achievements screen");
+            }
+            SearchTarget::FilterAchievements(query) => {
+                self.achievement_filter.update_search(query);
+                self.achievement_filter
+                    .apply_filter(&self.achievements.achievements);
+                self.current_screen = OverlayScreen::Achievements;
+                self.achievements_selected = 0;
+                self.achievements_scroll_offset = 0;
+                println!(
+                    "[State] Filtering achievements by \"{}\"",
+                    self.achievement_filter.search_query
+                );
+            }
+            SearchTarget::Game(game) => {
+                println!("[State] Showing playtime for {}", game.name);
+                self.playtime_focus = Some(game);
+                self.current_screen = OverlayScreen::Playtime;
+            }
+        }
+    }
+
+    /// The player whose tab is open on the achievements screen; None when
+    /// playing alone
+    pub fn selected_player(&self) -> Option<&SessionPlayer> {
+        self.achievements.players.get(self.achievements_player)
+    }
+
+    /// Number of achievements listed on the achievements screen
+    pub fn visible_achievement_count(&self) -> usize {
+        if self.achievement_filter.is_active() {
+            self.achievement_filter.get_filtered_count()
+        } else {
+            self.achievements.achievements.len()
+        }
+    }
+
+    /// Index into the achievement list of the entry listed at `position`
+    pub fn visible_achievement_index(&self, position: usize) -> Option<usize> {
+        if self.achievement_filter.is_active() {
+            self.achievement_filter.get_achievement_at(position)
+        } else {
+            (position < self.achievements.achievements.len()).then_some(position)
+        }
+    }
+
+    /// Render data for the `rows` listed entries from `scroll` on. Only
+    /// what's on screen is built, so large sets scroll as cheaply as small
+    /// ones.
+    pub fn achievement_rows(
+        &self,
+        scroll: usize,
+        rows: usize,
+    ) -> impl Iterator<Item = AchievementRow<'_>> {
+        let player = self.selected_player();
+        (scroll..scroll + rows).map_while(move |position| {
+            let achievement =
+                &self.achievements.achievements[self.visible_achievement_index(position)?];
+            Some(AchievementRow {
+                achievement,
+                selected: position == self.achievements_selected,
+                earned: self.achievements.earned_by(achievement, player),
+            })
+        })
+    }
+
+    /// Re-check one changed entry against the achievement filter
+    fn refilter_achievement(&mut self, changed: Option<usize>) {
+        if let Some(index) = changed.filter(|_| self.achievement_filter.is_active()) {
+            self.achievement_filter
+                .update_entry(&self.achievements.achievements, index);
+        }
+    }
+
+    #[cfg(test)]
+    fn visible_achievement_indices(&self) -> Vec<usize> {
+        (0..self.visible_achievement_count())
+            .filter_map(|position| self.visible_achievement_index(position))
+            .collect()
+    }
+
+    fn handle_achievements_input(&mut self, input: ControllerInput) {
+        let total = self.visible_achievement_count();
+
+        match input {
+            ControllerInput::Up => {
+                self.achievements_selected = self.achievements_selected.saturating_sub(1);
+            }
+            ControllerInput::Down if self.achievements_selected + 1 < total => {
+                self.achievements_selected += 1;
+            }
+            ControllerInput::Select => {
+                let index = self.visible_achievement_index(self.achievements_selected);
+                if let Some(achievement) = index.and_then(|i| self.achievements.achievements.get(i))
+                {
+                    // Reuse the open view (and its in-flight fetch) when reopening the same achievement
+                    let reuse = self
+                        .hints
+                        .as_ref()
+                        .is_some_and(|h| h.achievement_id == achievement.id);
+                    if !reuse {
+                        self.hints =
+                            Some(HintsView::open(achievement.id, achievement.title.clone()));
+                    }
+                    self.current_screen = OverlayScreen::AchievementHints;
+                    println!("[State] Showing hints for achievement {}", achievement.id);
+                }
+            }
+            ControllerInput::Secondary => {
+                if let Some(game_id) = self.achievements.game_id {
+                    let reuse = self.compare.as_ref().is_some_and(|c| c.game_id == game_id);
+                    if !reuse {
+                        self.compare = Some(CompareView::open(game_id));
+                    }
+                    self.current_screen = OverlayScreen::FriendCompare;
+                    println!(
+                        "[State] Comparing progress with friends for game {}",
+                        game_id
+                    );
+                }
+            }
+            ControllerInput::Left | ControllerInput::Right
+                if self.achievements.players.len() > 1 =>
+            {
+                let count = self.achievements.players.len();
+                self.achievements_player = if input == ControllerInput::Left {
+                    (self.achievements_player + count - 1) % count
+                } else {
+                    (self.achievements_player + 1) % count
+                };
+                if let Some(player) = self.selected_player() {
+                    println!(
+                        "[State] Showing achievements of player {} ({})",
+                        player.slot, player.name
+                    );
+                }
+            }
+            ControllerInput::RB if !self.achievements.achievements.is_empty() => {
+                self.current_screen = OverlayScreen::AchievementStats;
+                println!("[State] Showing achievement statistics");
+            }
+            ControllerInput::LB if !self.achievements.achievements.is_empty() => {
+                self.achievement_filter.cycle_filter();
+                self.achievement_filter
+                    .apply_filter(&self.achievements.achievements);
+                self.achievements_selected = 0;
+                self.achievements_scroll_offset = 0;
+                println!(
+                    "[State] Showing {} achievements",
+                    self.achievement_filter.filter.display_name()
+                );
+                return;
+            }
+            ControllerInput::Back if self.achievement_filter.is_active() => {
+                // Drop the filter first, keeping the selected achievement in view
+                self.achievements_selected = self
+                    .visible_achievement_index(self.achievements_selected)
+                    .unwrap_or(0);
+                self.achievement_filter.clear();
+                println!("[State] Cleared achievement filter");
+                Self::adjust_scroll_offset(
+                    self.achievements_selected,
+                    &mut self.achievements_scroll_offset,
+                    ACHIEVEMENTS_VISIBLE_ROWS,
+                    self.achievements.achievements.len(),
+                );
+                return;
+            }
+            ControllerInput::Back => {
+                self.current_screen = OverlayScreen::Main;
+                println!("[State] Returning to main menu");
+            }
+            _ => {}
+        }
+
+        Self::adjust_scroll_offset(
+            self.achievements_selected,
+            &mut self.achievements_scroll_offset,
+            ACHIEVEMENTS_VISIBLE_ROWS,
+            total,
+        );
+    }
+
+    fn handle_hints_input(&mut self, input: ControllerInput) {
+        match input {
+            ControllerInput::Left | ControllerInput::Up | ControllerInput::LB => {
+                if let Some(hints) = &mut self.hints {
+                    hints.prev();
+                }
+            }
+            ControllerInput::Right | ControllerInput::Down | ControllerInput::RB => {
+                if let Some(hints) = &mut self.hints {
+                    hints.next();
+                }
+            }
+            ControllerInput::Back => {
+                self.current_screen = OverlayScreen::Achievements;
+                println!("[State] Returning to achievements");
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_compare_input(&mut self, input: ControllerInput) {
+        let Some(compare) = &mut self.compare else {
+            self.current_screen = OverlayScreen::Achievements;
+            return;
+        };
+
+        match input {
+            ControllerInput::Left | ControllerInput::LB => compare.prev_friend(),
+            ControllerInput::Right | ControllerInput::RB => compare.next_friend(),
+            ControllerInput::Up => compare.scroll_up(),
+            ControllerInput::Down => compare.scroll_down(COMPARE_VISIBLE_ROWS),
+            ControllerInput::Back => {
+                self.current_screen = OverlayScreen::Achievements;
+                println!("[State] Returning to achievements");
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_achievement_stats_input(&mut self, input: ControllerInput) {
+        if input == ControllerInput::Back {
+            self.current_screen = OverlayScreen::Achievements;
+            println!("[State] Returning to achievements");
+        }
+    }
+
+    /// Statistics for the achievements screen, using the running game's
+    /// tracked playtime for the completion estimate
+    pub fn achievement_stats(&self) -> AchievementStats {
+        let playtime = self.current_cart_id().map(|cart_id| {
+            let stored = self
+                .playtime
+                .get_playtime(cart_id)
+                .map_or(0, |entry| entry.total_seconds);
+            stored
+                + self
+                    .playtime
+                    .get_current_session_duration()
+                    .map_or(0, |d| d.as_secs())
+        });
+        AchievementStats::compute(
+            &self.achievements.achievements,
+            &self.achievements.unlocks,
+            playtime,
+            unix_now(),
+        )
+    }
+
+    fn handle_performance_input(&mut self, input: ControllerInput) {
+        match input {
+            ControllerInput::Back => {
+                self.current_screen = OverlayScreen::Main;
+                println!("[State] Returning to main menu");
             }
             _ => {}
         }
     }
 
-    fn handle_quit_confirm_input(&mut self, input: ControllerInput) {
-        match input {
-            ControllerInput::Up | ControllerInput::Down |
-            ControllerInput::Left | ControllerInput::Right => {
-                // Toggle between Cancel (0) and Quit (1)
-                self.quit_confirm_selected = if self.quit_confirm_selected == 0 { 1 } else { 0 };
-            }
-            ControllerInput::Select => {
-                // Execute selected action
-                if self.quit_confirm_selected == 1 {
-                    // Quit selected
-                    println!("[State] Quit confirmed - triggering game exit");
-                    self.toasts.add_toast(
-                        "Returning to BIOS...".to_string(),
-                        None,
-                        ToastStyle::Info,
-                        2000,
-                    );
+    fn handle_settings_input(&mut self, input: ControllerInput) {
+        match input {
+            ControllerInput::Up => {
+                if self.settings_selected_option > 0 {
+                    self.settings_selected_option -= 1;
+                    if self.settings_selected_option < self.settings_scroll_offset {
+                        self.settings_scroll_offset = self.settings_selected_option;
+                    }
+                }
+            }
+            ControllerInput::Down => {
+                if self.settings_selected_option < SETTINGS_OPTIONS - 1 {
+                    self.settings_selected_option += 1;
+                    if self.settings_selected_option
+                        >= self.settings_scroll_offset + SETTINGS_VISIBLE_ROWS
+                    {
+                        self.settings_scroll_offset =
+                            self.settings_selected_option + 1 - SETTINGS_VISIBLE_ROWS;
+                    }
+                }
+            }
+            ControllerInput::Select => {
+                match self.settings_selected_option {
+                    0 => {
+                        // Menu Customization
+                        self.current_screen = OverlayScreen::MenuCustomization;
+                        self.menu_customization_selected = 0;
+                        println!("[State] Switched to Menu Customization");
+                    }
+                    1 => {
+                        // Theme Selection
+                        self.current_screen = OverlayScreen::ThemeSelection;
+                        self.theme_selected = 0;
+                        println!("[State] Switched to Theme Selection");
+                    }
+                    2 => {
+                        // Stream Mode (OBS output)
+                        self.toggle_stream_mode();
+                    }
+                    3 => {
+                        // RetroAchievements login (or switch account)
+                        self.login = Some(LoginView::open(self.ra_user.clone()));
+                        self.current_screen = OverlayScreen::RaLogin;
+                        println!("[State] Switched to RetroAchievements login");
+                    }
+                    4 if self.ra_user.is_some() => {
+                        // Log out
+                        self.logout();
+                    }
+                    5 => {
+                        // Auto-pause (all games)
+                        self.toggle_auto_pause();
+                    }
+                    6 => {
+                        // Auto-pause override for the running game
+                        self.cycle_game_auto_pause();
+                    }
+                    7 => {
+                        // Rumble on/off
+                        self.toggle_haptics();
+                    }
+                    8 => {
+                        // Rumble intensity
+                        self.cycle_haptic_intensity();
+                    }
+                    9 => {
+                        // HUD widget layout editor
+                        self.hud_editor_selected = 0;
+                        self.current_screen = OverlayScreen::HudLayout;
+                        println!("[State] Switched to HUD layout editor");
+                    }
+                    10 => {
+                        // Power mode
+                        self.cycle_power_mode();
+                    }
+                    11 => {
+                        // Performance on AC power
+                        self.toggle_power_auto_switch();
+                    }
+                    12 => {
+                        // Quick actions palette
+                        self.current_screen = OverlayScreen::QuickActionsSettings;
+                        self.quick_actions_settings_selected = 0;
+                        println!("[State] Switched to Quick Actions settings");
+                    }
+                    13 => {
+                        // Message rules debug view
+                        self.current_screen = OverlayScreen::IpcRules;
+                        println!("[State] Switched to Message Rules");
+                    }
+                    14 => {
+                        // Replay the first-run tour
+                        self.current_screen = OverlayScreen::Main;
+                        self.start_tour();
+                    }
+                    15 => {
+                        // Snooze notifications
+                        self.toggle_snooze();
+                    }
+                    index @ 16..=19 if self.toasts.snooze.enabled => {
+                        // Styles the snooze holds back
+                        self.toggle_snooze_style(snooze::STYLES[index - 16]);
+                    }
+                    _ => {}
+                }
+            }
+            ControllerInput::Back => {
+                self.current_screen = OverlayScreen::Main;
+                println!("[State] Returning to main menu");
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_login_input(&mut self, input: ControllerInput) {
+        let Some(login) = &mut self.login else {
+            self.current_screen = OverlayScreen::Settings;
+            return;
+        };
+
+        if login.handle_input(input) {
+            self.login = None;
+            self.current_screen = OverlayScreen::Settings;
+            println!("[State] Returning to settings");
+        }
+    }
+
+    fn handle_controllers_input(&mut self, input: ControllerInput) {
+        match input {
+            ControllerInput::Up => {
+                if self.controllers.selected_menu_item > 0 {
+                    self.controllers.selected_menu_item -= 1;
+                }
+            }
+            ControllerInput::Down => {
+                if self.controllers.selected_menu_item < CONTROLLER_MENU_OPTIONS.len() - 1 {
+                    self.controllers.selected_menu_item += 1;
+                }
+            }
+            ControllerInput::Select => {
+                match CONTROLLER_MENU_OPTIONS.get(self.controllers.selected_menu_item) {
+                    Some(&"GAMEPAD TESTER") => {
+                        self.current_screen = OverlayScreen::GamepadTester;
+                        println!("[State] Switched to Gamepad Tester");
+                    }
+                    Some(&"CONTROLLER SETTINGS") => {
+                        self.controllers.settings_selected_row = 0;
+                        self.controllers.settings_wizard = None;
+                        self.current_screen = OverlayScreen::ControllerSettings;
+                        println!("[State] Switched to Controller Settings");
+                    }
+                    Some(&"HOTKEY SETTINGS") => {
+                        self.current_screen = OverlayScreen::HotkeySettings;
+                        println!("[State] Switched to Hotkey Settings");
+                    }
+                    Some(&"INPUT MACROS") => {
+                        self.macros = macros::load();
+                        self.macros_selected = 0;
+                        self.current_screen = OverlayScreen::Macros;
+                        println!("[State] Switched to Input Macros");
+                    }
+                    _ => {}
+                }
+            }
+            ControllerInput::Back => {
+                self.current_screen = OverlayScreen::Main;
+                self.selected_option = 3; // Keep Controllers selected
+                println!("[State] Returning to main menu");
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_gamepad_tester_input(&mut self, input: ControllerInput) {
+        match input {
+            ControllerInput::Back => {
+                self.current_screen = OverlayScreen::Controllers;
+                self.select_controller_menu_item("GAMEPAD TESTER");
+                println!("[State] Returning to Controllers menu");
+            }
+            _ => {
+                // All other inputs are tracked by the tester
+            }
+        }
+    }
+
+    /// Highlight a Controllers menu entry when returning from its screen
+    fn select_controller_menu_item(&mut self, label: &str) {
+        if let Some(index) = CONTROLLER_MENU_OPTIONS.iter().position(|o| *o == label) {
+            self.controllers.selected_menu_item = index;
+        }
+    }
+
+    /// GUID and saved calibration of the controller being configured
+    pub fn selected_calibration(&self) -> Option<(String, ControllerCalibration)> {
+        let controller = self
+            .controllers
+            .controllers
+            .get(self.controllers.settings_selected_controller)?;
+        let calibration = self
+            .calibration
+            .as_ref()
+            .map(|c| c.get(&controller.guid))
+            .unwrap_or_default();
+        Some((controller.guid.clone(), calibration))
+    }
+
+    fn save_calibration(&mut self, guid: &str, calibration: ControllerCalibration) {
+        let Some(manager) = self.calibration.as_mut() else {
+            return;
+        };
+        if let Err(e) = manager.set(guid, calibration) {
+            eprintln!("[State] Failed to save controller calibration: {}", e);
+            self.toasts.add_toast(
+                format!("Failed to save calibration: {}", e),
+                None,
+                ToastStyle::Error,
+                3000,
+            );
+        }
+    }
+
+    /// Controller settings: D-Pad picks a row, Left/Right changes it, A runs
+    /// the drift calibration or reset
+    fn handle_controller_settings_input(&mut self, input: ControllerInput) {
+        if let Some(wizard) = &mut self.controllers.settings_wizard {
+            match (input, &*wizard) {
+                (ControllerInput::Select, DriftWizard::Prompt) => {
+                    wizard.start_measuring(Instant::now())
+                }
+                (ControllerInput::Select | ControllerInput::Back, DriftWizard::Done(_))
+                | (ControllerInput::Back, _) => self.controllers.settings_wizard = None,
+                _ => {}
+            }
+            return;
+        }
+
+        let rows = CONTROLLER_SETTINGS_ROWS.len();
+        let selected = self.selected_calibration();
+        match input {
+            ControllerInput::Up => {
+                self.controllers.settings_selected_row =
+                    (self.controllers.settings_selected_row + rows - 1) % rows;
+            }
+            ControllerInput::Down => {
+                self.controllers.settings_selected_row =
+                    (self.controllers.settings_selected_row + 1) % rows;
+            }
+            ControllerInput::Left | ControllerInput::Right => {
+                let steps = if input == ControllerInput::Right {
+                    1
+                } else {
+                    -1
+                };
+                let Some((guid, mut calibration)) = selected else {
+                    return;
+                };
+                match self.controllers.settings_selected_row {
+                    0 => {
+                        let count = self.controllers.controllers.len();
+                        let current = self.controllers.settings_selected_controller;
+                        self.controllers.settings_selected_controller =
+                            (current as i32 + steps).rem_euclid(count as i32) as usize;
+                        return;
+                    }
+                    1 => {
+                        calibration.left_deadzone = calibration::adjust(
+                            calibration.left_deadzone,
+                            steps,
+                            calibration::MAX_DEADZONE,
+                        )
+                    }
+                    2 => {
+                        calibration.right_deadzone = calibration::adjust(
+                            calibration.right_deadzone,
+                            steps,
+                            calibration::MAX_DEADZONE,
+                        )
+                    }
+                    3 => {
+                        calibration.trigger_threshold =
+                            calibration::adjust(calibration.trigger_threshold, steps, 0.9)
+                    }
+                    _ => return,
+                }
+                self.save_calibration(&guid, calibration);
+            }
+            ControllerInput::Select => {
+                let Some((guid, _)) = selected else { return };
+                match self.controllers.settings_selected_row {
+                    4 => self.controllers.settings_wizard = Some(DriftWizard::Prompt),
+                    5 => {
+                        self.save_calibration(&guid, ControllerCalibration::default());
+                        self.toasts.add_toast(
+                            "Calibration reset".to_string(),
+                            None,
+                            ToastStyle::Info,
+                            2000,
+                        );
+                    }
+                    _ => {}
+                }
+            }
+            ControllerInput::Back => {
+                self.current_screen = OverlayScreen::Controllers;
+                self.select_controller_menu_item("CONTROLLER SETTINGS");
+                println!("[State] Returning to Controllers menu");
+            }
+            _ => {}
+        }
+    }
+
+    /// Feed the latest stick reading to the drift calibration, saving the
+    /// offsets once it finishes
+    pub fn sample_drift_wizard(&mut self) {
+        let axes = self.controllers.settings_axes;
+        let Some(wizard) = &mut self.controllers.settings_wizard else {
+            return;
+        };
+        let Some(measurement) = wizard.sample(Instant::now(), axes) else {
+            return;
+        };
+        let Some((guid, calibration)) = self.selected_calibration() else {
+            return;
+        };
+
+        println!(
+            "[State] Stick drift measured for {}: {:?}",
+            guid, measurement
+        );
+        self.save_calibration(&guid, measurement.apply_to(calibration));
+    }
+
+    /// Macros: A records a new macro (first row) or plays the selected one,
+    /// Left/Right changes its speed, X deletes it. Recording and playback hide
+    /// the overlay so the input goes to the game.
+    fn handle_macros_input(&mut self, input: ControllerInput) {
+        let rows = self.macros.len() + 1;
+        let selected = self
+            .macros_selected
+            .checked_sub(1)
+            .and_then(|i| self.macros.get(i))
+            .cloned();
+        match input {
+            ControllerInput::Up => {
+                self.macros_selected = (self.macros_selected + rows - 1) % rows;
+            }
+            ControllerInput::Down => {
+                self.macros_selected = (self.macros_selected + 1) % rows;
+            }
+            ControllerInput::Left | ControllerInput::Right => {
+                let Some(selected) = selected else { return };
+                let steps = if input == ControllerInput::Right {
+                    1
+                } else {
+                    -1
+                };
+                let speed = macros::step_speed(selected.speed, steps);
+                macros::set_speed(&selected.name, speed);
+                self.macros[self.macros_selected - 1].speed = speed;
+            }
+            ControllerInput::Select => {
+                match selected {
+                    Some(selected) => {
+                        println!("[State] Playing macro '{}'", selected.name);
+                        macros::play(&selected.name);
+                    }
+                    None => {
+                        let name = macros::next_name(&self.macros);
+                        println!("[State] Recording macro '{}'", name);
+                        macros::record(&name);
+                        self.toasts.add_toast(
+                            format!("Recording {} - press Guide to finish", name),
+                            None,
+                            ToastStyle::Info,
+                            4000,
+                        );
+                    }
+                }
+                self.visible = false;
+                self.pointer.clear();
+            }
+            ControllerInput::Secondary => {
+                let Some(selected) = selected else { return };
+                macros::delete(&selected.name);
+                self.macros.retain(|m| m.name != selected.name);
+                self.macros_selected = self.macros_selected.min(self.macros.len());
+                self.toasts.add_toast(
+                    format!("Deleted {}", selected.name),
+                    None,
+                    ToastStyle::Info,
+                    2000,
+                );
+            }
+            ControllerInput::Back => {
+                self.current_screen = OverlayScreen::Controllers;
+                self.select_controller_menu_item("INPUT MACROS");
+                println!("[State] Returning to Controllers menu");
+            }
+            _ => {}
+        }
+    }
+
+    /// Tracks listed on the Music screen
+    pub fn music_tracks(&self) -> Vec<PathBuf> {
+        self.music
+            .as_ref()
+            .map(|m| m.tracks(&self.music_source))
+            .unwrap_or_default()
+    }
+
+    fn handle_music_input(&mut self, input: ControllerInput) {
+        let tracks = self.music_tracks();
+        let Some(music) = self.music.as_mut() else {
+            if input == ControllerInput::Back {
+                self.current_screen = OverlayScreen::Main;
+            }
+            return;
+        };
+        let rows = MUSIC_SETTING_ROWS + tracks.len();
+        let track = self
+            .music_selected
+            .checked_sub(MUSIC_SETTING_ROWS)
+            .and_then(|i| tracks.get(i));
+        match input {
+            ControllerInput::Up => {
+                self.music_selected = (self.music_selected + rows - 1) % rows;
+            }
+            ControllerInput::Down => {
+                self.music_selected = (self.music_selected + 1) % rows;
+            }
+            ControllerInput::Left | ControllerInput::Right => {
+                let forward = input == ControllerInput::Right;
+                match self.music_selected {
+                    0 => music.adjust_volume(if forward {
+                        music::VOLUME_STEP
+                    } else {
+                        -music::VOLUME_STEP
+                    }),
+                    1 => music.cycle_duck(forward),
+                    _ => music.skip(if forward { 1 } else { -1 }),
+                }
+            }
+            ControllerInput::Select if track.is_some() => {
+                music.play(tracks.clone(), self.music_selected - MUSIC_SETTING_ROWS);
+            }
+            ControllerInput::Secondary => {
+                let Some(track) = track else { return };
+                let playlist = match &self.music_source {
+                    MusicSource::Playlist(name) => name.clone(),
+                    MusicSource::AllTracks => self
+                        .music_target_playlist
+                        .get_or_insert_with(|| music.config().next_playlist_name())
+                        .clone(),
+                };
+                let added = music.toggle_in_playlist(&playlist, track);
+                let message = if added {
+                    format!("Added {} to {}", music::track_title(track), playlist)
+                } else {
+                    format!("Removed {} from {}", music::track_title(track), playlist)
+                };
+                self.toasts.add_toast(message, None, ToastStyle::Info, 2000);
+                // Keep the selection on the list as it shrinks
+                if !added && self.music_source != MusicSource::AllTracks {
+                    self.music_selected = self.music_selected.min(rows - 2);
+                }
+            }
+            ControllerInput::RT => music.toggle_pause(),
+            ControllerInput::LT => music.stop(),
+            ControllerInput::LB | ControllerInput::RB => {
+                self.cycle_music_source(input == ControllerInput::RB);
+            }
+            ControllerInput::Back => {
+                self.current_screen = OverlayScreen::Main;
+                println!("[State] Returning to main menu");
+            }
+            _ => {}
+        }
+    }
+
+    /// Step through all tracks, each playlist and a new empty playlist
+    fn cycle_music_source(&mut self, forward: bool) {
+        let Some(music) = self.music.as_ref() else {
+            return;
+        };
+        let mut sources = music.sources();
+        // A new playlist isn't saved until it has a track
+        if !sources.contains(&self.music_source) {
+            sources.push(self.music_source.clone());
+        }
+        let count = sources.len() + 1;
+        let index = sources
+            .iter()
+            .position(|s| *s == self.music_source)
+            .unwrap_or(0);
+        let new_index = if forward {
+            (index + 1) % count
+        } else {
+            (index + count - 1) % count
+        };
+
+        self.music_source = match sources.get(new_index) {
+            Some(source) => source.clone(),
+            None => MusicSource::Playlist(music.config().next_playlist_name()),
+        };
+        if let MusicSource::Playlist(name) = &self.music_source {
+            self.music_target_playlist = Some(name.clone());
+        }
+        self.music_selected = 0;
+    }
+
+    /// Play/pause or skip from the music hotkeys, whichever screen is open
+    pub fn handle_music_hotkey(&mut self, action: HotkeyAction) {
+        let tracks = self.music_tracks();
+        let Some(music) = self.music.as_mut() else {
+            return;
+        };
+        match action {
+            HotkeyAction::MusicPlayPause if music.is_playing() => music.toggle_pause(),
+            HotkeyAction::MusicPlayPause => music.play(tracks, 0),
+            HotkeyAction::MusicNext => music.skip(1),
+            _ => {}
+        }
+    }
+
+    fn handle_challenges_input(&mut self, input: ControllerInput) {
+        // A header row per set, then its challenges
+        let rows: usize = self
+            .challenges
+            .iter()
+            .map(|set| set.challenges.len() + 1)
+            .sum();
+        match input {
+            ControllerInput::Up => {
+                self.challenges_scroll = self.challenges_scroll.saturating_sub(1);
+            }
+            ControllerInput::Down => {
+                self.challenges_scroll =
+                    (self.challenges_scroll + 1).min(rows.saturating_sub(CHALLENGES_VISIBLE_ROWS));
+            }
+            ControllerInput::Back => {
+                self.current_screen = OverlayScreen::Main;
+                println!("[State] Returning to main menu");
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_playtime_input(&mut self, input: ControllerInput) {
+        match input {
+            ControllerInput::Back => {
+                self.playtime_focus = None;
+                self.current_screen = OverlayScreen::Main;
+                println!("[State] Returning to main menu");
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_speedrun_input(&mut self, input: ControllerInput) {
+        match input {
+            ControllerInput::Up => {
+                self.speedrun_selected = self.speedrun_selected.saturating_sub(1);
+            }
+            ControllerInput::Down => {
+                self.speedrun_selected = (self.speedrun_selected + 1).min(SPEEDRUN_OPTIONS - 1);
+            }
+            ControllerInput::Select => match self.speedrun_selected {
+                0 => self.handle_speedrun_hotkey(HotkeyAction::SpeedrunStartPause),
+                1 => self.handle_speedrun_hotkey(HotkeyAction::SpeedrunSplit),
+                2 => self.handle_speedrun_hotkey(HotkeyAction::SpeedrunReset),
+                3 => self.toggle_speedrun_widget(),
+                _ => {}
+            },
+            ControllerInput::Back => {
+                self.current_screen = OverlayScreen::Main;
+                println!("[State] Returning to main menu");
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_game_controls_input(&mut self, input: ControllerInput) {
+        if input == ControllerInput::Back {
+            self.current_screen = OverlayScreen::Main;
+            println!("[State] Returning to main menu");
+        }
+    }
+
+    fn handle_cheats_input(&mut self, input: ControllerInput) {
+        if input == ControllerInput::Back {
+            self.current_screen = OverlayScreen::Main;
+            println!("[State] Returning to main menu");
+            return;
+        }
+        let Some(cheats) = self.cheats.as_mut() else {
+            return;
+        };
+
+        match input {
+            ControllerInput::Up => cheats.selected = cheats.selected.saturating_sub(1),
+            ControllerInput::Down => {
+                cheats.selected = (cheats.selected + 1).min(cheats.cheats.len().saturating_sub(1));
+            }
+            ControllerInput::Select => match cheats.toggle_selected() {
+                Ok(true) => self.toasts.add_toast(
+                    "Hardcore disabled until the game restarts".to_string(),
+                    None,
+                    ToastStyle::Warning,
+                    4000,
+                ),
+                Ok(false) => {}
+                Err(e) => {
+                    eprintln!("[Cheats] {:#}", e);
+                    self.toasts.add_toast(
+                        "Failed to apply cheats".to_string(),
+                        None,
+                        ToastStyle::Error,
+                        3000,
+                    );
+                }
+            },
+            _ => {}
+        }
+    }
+
+    /// Start/pause, split or reset the speedrun timer, from its hotkeys or screen
+    pub fn handle_speedrun_hotkey(&mut self, action: HotkeyAction) {
+        let Some(speedrun) = self.speedrun.as_mut() else {
+            return;
+        };
+
+        match action {
+            HotkeyAction::SpeedrunStartPause => speedrun.toggle(),
+            HotkeyAction::SpeedrunSplit => {
+                let new_best = speedrun.split();
+                if new_best {
+                    let time = speedrun
+                        .best()
+                        .map(|b| Duration::from_millis(b.total_ms))
+                        .unwrap_or_default();
+                    self.toasts.add_toast(
+                        format!("New best time: {}", speedrun::format_time(time)),
+                        None,
+                        ToastStyle::Success,
+                        4000,
+                    );
+                }
+            }
+            HotkeyAction::SpeedrunReset => speedrun.reset(),
+            _ => {}
+        }
+    }
+
+    fn toggle_speedrun_widget(&mut self) {
+        let Some(speedrun) = self.speedrun.as_mut() else {
+            return;
+        };
+
+        let widget = !speedrun.config().widget;
+        speedrun.config_mut().widget = widget;
+        if let Err(e) = speedrun.save() {
+            eprintln!("[State] Failed to save speedrun config: {}", e);
+        }
+    }
+
+    fn handle_menu_customization_input(&mut self, input: ControllerInput) {
+        let all_items = MenuItemId::all();
+        let item_count = all_items.len();
+        const MAX_VISIBLE: usize = 6;
+
+        match input {
+            ControllerInput::Up => {
+                if self.menu_customization_selected > 0 {
+                    self.menu_customization_selected -= 1;
+                    Self::adjust_scroll_offset(
+                        self.menu_customization_selected,
+                        &mut self.menu_customization_scroll_offset,
+                        MAX_VISIBLE,
+                        item_count,
+                    );
+                }
+            }
+            ControllerInput::Down => {
+                if self.menu_customization_selected < item_count.saturating_sub(1) {
+                    self.menu_customization_selected += 1;
+                    Self::adjust_scroll_offset(
+                        self.menu_customization_selected,
+                        &mut self.menu_customization_scroll_offset,
+                        MAX_VISIBLE,
+                        item_count,
+                    );
+                }
+            }
+            ControllerInput::Left | ControllerInput::Right | ControllerInput::Select => {
+                // Toggle visibility of selected menu item
+                if self.menu_customization_selected < all_items.len() {
+                    let item_id = all_items[self.menu_customization_selected];
+                    self.menu_config.config_mut().toggle_visibility(item_id);
+
+                    // Save configuration
+                    if let Err(e) = self.menu_config.save() {
+                        eprintln!("[State] Failed to save menu config: {}", e);
+                        self.toasts.add_toast(
+                            format!("Failed to save menu config: {}", e),
+                            None,
+                            ToastStyle::Error,
+                            3000,
+                        );
+                    } else {
+                        let is_visible = self
+                            .menu_config
+                            .config()
+                            .items
+                            .iter()
+                            .find(|i| i.id == item_id)
+                            .map(|i| i.visible)
+                            .unwrap_or(false);
+
+                        let status = if is_visible { "shown" } else { "hidden" };
+                        self.toasts.add_toast(
+                            format!("{} is now {}", item_id.display_name(), status),
+                            None,
+                            ToastStyle::Info,
+                            2000,
+                        );
+                        println!(
+                            "[State] Toggled visibility for {:?}: {}",
+                            item_id, is_visible
+                        );
+                    }
+                }
+            }
+            ControllerInput::LB => {
+                // Move selected item up in order
+                if self.menu_customization_selected < all_items.len() {
+                    let item_id = all_items[self.menu_customization_selected];
+                    self.menu_config.config_mut().move_up(item_id);
+
+                    // Save configuration
+                    if let Err(e) = self.menu_config.save() {
+                        eprintln!("[State] Failed to save menu config: {}", e);
+                    } else {
+                        self.toasts.add_toast(
+                            format!("Moved {} up", item_id.display_name()),
+                            None,
+                            ToastStyle::Info,
+                            1500,
+                        );
+                        println!("[State] Moved {:?} up", item_id);
+                    }
+                }
+            }
+            ControllerInput::RB => {
+                // Move selected item down in order
+                if self.menu_customization_selected < all_items.len() {
+                    let item_id = all_items[self.menu_customization_selected];
+                    self.menu_config.config_mut().move_down(item_id);
+
+                    // Save configuration
+                    if let Err(e) = self.menu_config.save() {
+                        eprintln!("[State] Failed to save menu config: {}", e);
+                    } else {
+                        self.toasts.add_toast(
+                            format!("Moved {} down", item_id.display_name()),
+                            None,
+                            ToastStyle::Info,
+                            1500,
+                        );
+                        println!("[State] Moved {:?} down", item_id);
+                    }
+                }
+            }
+            ControllerInput::Back => {
+                self.current_screen = OverlayScreen::Settings;
+                self.settings_selected_option = 0;
+                println!("[State] Returning to Settings");
+            }
+            _ => {}
+        }
+    }
+
+    /// Palette: the D-Pad steps around the circle, A runs the highlighted
+    /// action, B or Guide closes it
+    fn handle_quick_actions_input(&mut self, input: ControllerInput) {
+        let actions = self.menu_config.config().quick_actions.clone();
+        let count = actions.len();
+        if count == 0 {
+            self.visible = false;
+            return;
+        }
+
+        match input {
+            ControllerInput::Left | ControllerInput::Up => {
+                self.quick_actions_selected = (self.quick_actions_selected + count - 1) % count;
+            }
+            ControllerInput::Right | ControllerInput::Down => {
+                self.quick_actions_selected = (self.quick_actions_selected + 1) % count;
+            }
+            ControllerInput::Select => {
+                let action = actions[self.quick_actions_selected.min(count - 1)];
+                self.run_quick_action(action);
+            }
+            ControllerInput::Back | ControllerInput::Guide => {
+                self.visible = false;
+                println!("[State] Closed quick actions");
+            }
+            _ => {}
+        }
+    }
+
+    /// Screenshots and save states are sent to the runtime wrapper through
+    /// the emulator command file, like pause and quit
+    fn run_quick_action(&mut self, action: QuickAction) {
+        println!("[State] Quick action: {:?}", action);
+        match action {
+            QuickAction::Screenshot | QuickAction::QuickSave | QuickAction::QuickLoad => {
+                let command = match action {
+                    QuickAction::Screenshot => "screenshot",
+                    QuickAction::QuickSave => "save_state",
+                    _ => "load_state",
+                };
+                // Close first so a screenshot doesn't catch the palette
+                self.visible = false;
+                if !pause::wrapper_handles_commands() {
+                    self.toasts.add_toast(
+                        format!("{} isn't supported by this game", action.display_name()),
+                        None,
+                        ToastStyle::Warning,
+                        3000,
+                    );
+                } else if let Err(e) = pause::write_command(command) {
+                    eprintln!("[State] Failed to send {}: {}", command, e);
+                    self.toasts.add_toast(
+                        format!("{} failed", action.display_name()),
+                        None,
+                        ToastStyle::Error,
+                        3000,
+                    );
+                }
+            }
+            QuickAction::ToggleHud => {
+                self.performance.toggle_visibility();
+                self.visible = false;
+            }
+            QuickAction::Mute => {
+                let result = std::process::Command::new("wpctl")
+                    .args(["set-mute", "@DEFAULT_AUDIO_SINK@", "toggle"])
+                    .status();
+                match result {
+                    Ok(status) if status.success() => {
+                        self.toasts.add_toast(
+                            "Audio mute toggled".to_string(),
+                            None,
+                            ToastStyle::Info,
+                            2000,
+                        );
+                    }
+                    _ => {
+                        self.toasts.add_toast(
+                            "Failed to toggle mute".to_string(),
+                            None,
+                            ToastStyle::Error,
+                            3000,
+                        );
+                    }
+                }
+                self.visible = false;
+            }
+            QuickAction::MusicPlayPause => {
+                self.handle_music_hotkey(HotkeyAction::MusicPlayPause);
+                self.visible = false;
+            }
+            QuickAction::Achievements => {
+                self.current_screen = OverlayScreen::Achievements;
+                println!("[State] Switched to Achievements screen");
+            }
+            QuickAction::Resume => {
+                self.visible = false;
+            }
+            QuickAction::Snooze => {
+                self.toggle_snooze();
+                self.visible = false;
+            }
+            QuickAction::Quit => {
+                self.current_screen = OverlayScreen::QuitConfirm;
+                self.quit_confirm_selected = 0; // Default to Cancel button
+                println!("[State] Showing quit confirmation");
+            }
+        }
+    }
+
+    /// Palette settings: A/Left/Right add or remove the selected action,
+    /// LB/RB move it around the circle
+    fn handle_ipc_rules_input(&mut self, input: ControllerInput) {
+        match input {
+            ControllerInput::Select => {
+                let Some(rules) = &mut self.ipc_rules else {
+                    return;
+                };
+                rules.reload();
+                match rules.error() {
+                    Some(_) => self.toasts.add_toast(
+                        "Message rules have errors".to_string(),
+                        None,
+                        ToastStyle::Error,
+                        2000,
+                    ),
+                    None => {
+                        let count = rules.rules().count();
+                        self.toasts.add_toast(
+                            format!("Loaded {} message rule(s)", count),
+                            None,
+                            ToastStyle::Success,
+                            2000,
+                        );
+                    }
+                }
+            }
+            ControllerInput::Secondary if self.debug_console.is_some() => {
+                self.current_screen = OverlayScreen::DebugConsole;
+                println!("[State] Switched to debug console");
+            }
+            ControllerInput::Back => {
+                self.current_screen = OverlayScreen::Settings;
+                println!("[State] Returning to settings");
+            }
+            _ => {}
+        }
+    }
+
+    /// Debug console: Up/Down pick a test message and A injects it through
+    /// the message rules like a client's; LB/RB scroll the log, X clears it
+    fn handle_debug_console_input(&mut self, input: ControllerInput) {
+        let Some(console) = &mut self.debug_console else {
+            self.current_screen = OverlayScreen::IpcRules;
+            return;
+        };
+
+        match input {
+            ControllerInput::Up => console.select_prev(),
+            ControllerInput::Down => console.select_next(),
+            ControllerInput::LB => console.scroll_newer(DEBUG_LOG_VISIBLE_ROWS),
+            ControllerInput::RB => console.scroll_older(DEBUG_LOG_VISIBLE_ROWS),
+            ControllerInput::Secondary => console.clear(),
+            ControllerInput::Select => match console.test_message() {
+                Ok(incoming) => {
+                    println!("[Debug] Injecting {} message", incoming.kind);
+                    self.route_message(incoming);
+                }
+                Err(e) => eprintln!("[Debug] {:#}", e),
+            },
+            ControllerInput::Back => {
+                self.current_screen = OverlayScreen::IpcRules;
+                println!("[State] Returning to message rules");
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_quick_actions_settings_input(&mut self, input: ControllerInput) {
+        let all_actions = QuickAction::all();
+        let Some(&action) = all_actions.get(self.quick_actions_settings_selected) else {
+            self.quick_actions_settings_selected = 0;
+            return;
+        };
+
+        match input {
+            ControllerInput::Up => {
+                self.quick_actions_settings_selected =
+                    self.quick_actions_settings_selected.saturating_sub(1);
+            }
+            ControllerInput::Down => {
+                self.quick_actions_settings_selected =
+                    (self.quick_actions_settings_selected + 1).min(all_actions.len() - 1);
+            }
+            ControllerInput::Left | ControllerInput::Right | ControllerInput::Select => {
+                if !self.menu_config.config_mut().toggle_quick_action(action) {
+                    self.toasts.add_toast(
+                        format!(
+                            "The palette holds {} to {} actions",
+                            MIN_QUICK_ACTIONS, MAX_QUICK_ACTIONS
+                        ),
+                        None,
+                        ToastStyle::Warning,
+                        2000,
+                    );
+                    return;
+                }
+                self.quick_actions_selected = 0;
+                self.save_quick_actions();
+            }
+            ControllerInput::LB | ControllerInput::RB => {
+                self.menu_config
+                    .config_mut()
+                    .move_quick_action(action, input == ControllerInput::LB);
+                self.save_quick_actions();
+            }
+            ControllerInput::Back => {
+                self.current_screen = OverlayScreen::Settings;
+                self.settings_selected_option = 12;
+                println!("[State] Returning to Settings");
+            }
+            _ => {}
+        }
+    }
+
+    fn save_quick_actions(&mut self) {
+        if let Err(e) = self.menu_config.save() {
+            eprintln!("[State] Failed to save menu config: {}", e);
+            self.toasts.add_toast(
+                format!("Failed to save menu config: {}", e),
+                None,
+                ToastStyle::Error,
+                3000,
+            );
+        }
+    }
+
+    fn handle_theme_selection_input(&mut self, input: ControllerInput) {
+        let themes = self.theme_config.all_themes();
+        let theme_count = themes.len();
+        const MAX_VISIBLE: usize = 5;
+
+        match input {
+            ControllerInput::Up => {
+                if self.theme_selected > 0 {
+                    self.theme_selected -= 1;
+                    Self::adjust_scroll_offset(
+                        self.theme_selected,
+                        &mut self.theme_selection_scroll_offset,
+                        MAX_VISIBLE,
+                        theme_count,
+                    );
+                }
+            }
+            ControllerInput::Down => {
+                if self.theme_selected < theme_count.saturating_sub(1) {
+                    self.theme_selected += 1;
+                    Self::adjust_scroll_offset(
+                        self.theme_selected,
+                        &mut self.theme_selection_scroll_offset,
+                        MAX_VISIBLE,
+                        theme_count,
+                    );
+                }
+            }
+            ControllerInput::Select => {
+                if self.theme_selected < themes.len() {
+                    self.theme_preview_color_vision = self.theme_config.color_vision();
+                    self.theme_preview_high_contrast = self.theme_config.high_contrast();
+                    self.theme_preview_backdrop = self
+                        .theme_config
+                        .backdrop_for(&themes[self.theme_selected].name);
+                    self.theme_preview_row = 0;
+                    self.current_screen = OverlayScreen::ThemePreview;
+                    println!(
+                        "[State] Previewing theme: {}",
+                        themes[self.theme_selected].name
+                    );
+                }
+            }
+            ControllerInput::Secondary => {
+                if let Some(theme) = themes.get(self.theme_selected) {
+                    let is_user_theme = self.theme_config.is_user_theme(&theme.name);
+                    self.theme_editor = Some(ThemeEditor::open(theme.clone(), is_user_theme));
+                    self.current_screen = OverlayScreen::ThemeEditor;
+                    println!("[State] Editing theme: {}", theme.name);
+                }
+            }
+            ControllerInput::LB => {
+                let Some(theme) = themes.get(self.theme_selected) else {
+                    return;
+                };
+                match self.theme_config.export_theme(&theme.name) {
+                    Ok(path) => self.toasts.add_toast(
+                        format!("Exported {} to {}", theme.name, path.display()),
+                        None,
+                        ToastStyle::Success,
+                        4000,
+                    ),
+                    Err(e) => {
+                        eprintln!("[State] Failed to export theme: {:#}", e);
+                        self.toasts.add_toast(
+                            format!("Export failed: {}", e),
+                            None,
+                            ToastStyle::Error,
+                            3000,
+                        );
+                    }
+                }
+            }
+            ControllerInput::RB => match self.theme_config.import_themes() {
+                Ok(names) if names.is_empty() => self.toasts.add_toast(
+                    format!(
+                        "No new themes in {}",
+                        self.theme_config.shared_dir().display()
+                    ),
+                    None,
+                    ToastStyle::Info,
+                    3000,
+                ),
+                Ok(names) => self.toasts.add_toast(
+                    format!("Imported: {}", names.join(", ")),
+                    None,
+                    ToastStyle::Success,
+                    3000,
+                ),
+                Err(e) => {
+                    eprintln!("[State] Failed to import themes: {:#}", e);
+                    self.toasts.add_toast(
+                        format!("Import failed: {}", e),
+                        None,
+                        ToastStyle::Error,
+                        3000,
+                    );
+                }
+            },
+            ControllerInput::Back => {
+                self.current_screen = OverlayScreen::Settings;
+                self.settings_selected_option = 1;
+                println!("[State] Returning to Settings");
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_theme_editor_input(&mut self, input: ControllerInput) {
+        let Some(editor) = &mut self.theme_editor else {
+            self.current_screen = OverlayScreen::ThemeSelection;
+            return;
+        };
+
+        match editor.handle_input(input) {
+            ThemeEditorAction::None => {}
+            ThemeEditorAction::Close => {
+                self.theme_editor = None;
+                self.current_screen = OverlayScreen::ThemeSelection;
+                println!("[State] Returning to theme selection");
+            }
+            ThemeEditorAction::Save => {
+                let theme = editor.named_theme();
+                let replacing = editor.replacing.clone();
+                match self
+                    .theme_config
+                    .save_user_theme(&theme, replacing.as_deref())
+                {
+                    Ok(name) => {
+                        self.toasts.add_toast(
+                            format!("Saved theme: {}", name),
+                            None,
+                            ToastStyle::Success,
+                            2000,
+                        );
+                        self.theme_editor = None;
+                        self.current_screen = OverlayScreen::ThemeSelection;
+                        // Land on the saved theme in the list
+                        let themes = self.theme_config.all_themes();
+                        self.theme_selected =
+                            themes.iter().position(|t| t.name == name).unwrap_or(0);
+                        Self::adjust_scroll_offset(
+                            self.theme_selected,
+                            &mut self.theme_selection_scroll_offset,
+                            5,
+                            themes.len(),
+                        );
+                    }
+                    Err(e) => {
+                        eprintln!("[State] Failed to save theme: {:#}", e);
+                        self.toasts.add_toast(
+                            format!("Failed to save theme: {}", e),
+                            None,
+                            ToastStyle::Error,
+                            3000,
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    fn handle_theme_preview_input(&mut self, input: ControllerInput) {
+        let themes = self.theme_config.all_themes();
+        let Some(theme) = themes.get(self.theme_selected) else {
+            self.current_screen = OverlayScreen::ThemeSelection;
+            return;
+        };
+
+        match input {
+            ControllerInput::Up => {
+                self.theme_preview_row = self.theme_preview_row.saturating_sub(1);
+            }
+            ControllerInput::Down => {
+                self.theme_preview_row = (self.theme_preview_row + 1).min(THEME_PREVIEW_ROWS - 1);
+            }
+            ControllerInput::Left | ControllerInput::Right => {
+                let forward = input == ControllerInput::Right;
+                let steps = if forward { 1 } else { -1 };
+                match self.theme_preview_row {
+                    0 => {
+                        self.theme_preview_color_vision =
+                            self.theme_preview_color_vision.cycle(forward)
+                    }
+                    1 => self.theme_preview_backdrop.adjust_opacity(steps),
+                    2 => self.theme_preview_backdrop.adjust_panel_opacity(steps),
+                    _ => {
+                        self.theme_preview_backdrop.blur = !self.theme_preview_backdrop.blur;
+                        self.sync_backdrop_blur(self.visible);
+                    }
+                }
+            }
+            ControllerInput::Secondary => {
+                self.theme_preview_high_contrast = !self.theme_preview_high_contrast;
+            }
+            ControllerInput::Select => {
+                let result = self.theme_config.set_variant(
+                    &theme.name,
+                    self.theme_preview_color_vision,
+                    self.theme_preview_high_contrast,
+                    self.theme_preview_backdrop,
+                );
+                if let Err(e) = result {
+                    eprintln!("[State] Failed to set theme: {}", e);
+                    self.toasts.add_toast(
+                        format!("Failed to set theme: {}", e),
+                        None,
+                        ToastStyle::Error,
+                        3000,
+                    );
+                } else {
+                    self.toasts.add_toast(
+                        format!("Theme set to: {}", theme.name),
+                        None,
+                        ToastStyle::Info,
+                        2000,
+                    );
+                    println!("[State] Theme changed to: {}", theme.name);
+                    self.current_screen = OverlayScreen::ThemeSelection;
+                    self.sync_backdrop_blur(self.visible);
+                }
+            }
+            ControllerInput::Back => {
+                // Drop the previewed blur along with the rest of the preview
+                self.current_screen = OverlayScreen::ThemeSelection;
+                self.sync_backdrop_blur(self.visible);
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_quit_confirm_input(&mut self, input: ControllerInput) {
+        match input {
+            ControllerInput::Up
+            | ControllerInput::Down
+            | ControllerInput::Left
+            | ControllerInput::Right => {
+                // Toggle between Cancel (0) and Quit (1)
+                self.quit_confirm_selected = if self.quit_confirm_selected == 0 {
+                    1
+                } else {
+                    0
+                };
+            }
+            ControllerInput::Select => {
+                // Execute selected action
+                if self.quit_confirm_selected == 1 {
+                    // Quit selected
+                    println!("[State] Quit confirmed - saving before exit");
+                    // A paused emulator can't save; the quit command replaces
+                    // the resume on the command file
+                    if let Some(auto_pause) = self.auto_pause.as_mut() {
+                        auto_pause.resume();
+                    }
+                    self.quit = Some(GracefulQuit::start());
+                    self.current_screen = OverlayScreen::QuitSaving;
+                } else {
+                    // Cancel selected
+                    self.current_screen = OverlayScreen::Main;
+                    self.selected_option = 5; // Keep quit option selected in main menu
+                    println!("[State] Quit cancelled");
+                }
+            }
+            ControllerInput::Back => {
+                // Back button always cancels
+                self.current_screen = OverlayScreen::Main;
+                self.selected_option = 5; // Keep quit selected
+                println!("[State] Quit cancelled");
+            }
+            _ => {}
+        }
+    }
+
+    fn handle_hotkey_settings_input(&mut self, input: ControllerInput) {
+        match input {
+            ControllerInput::Back => {
+                // Return to Controllers menu
+                self.current_screen = OverlayScreen::Controllers;
+                self.select_controller_menu_item("HOTKEY SETTINGS");
+                println!("[State] Returning to Controllers menu");
+            }
+            _ => {
+                // TODO: Implement hotkey configuration UI
+            }
+        }
+    }
+
+    fn handle_bluetooth_pairing_input(&mut self, input: ControllerInput) {
+        match input {
+            ControllerInput::Back => {
+                // Return to Controllers menu
+                self.current_screen = OverlayScreen::Controllers;
+                println!("[State] Returning to Controllers menu");
+            }
+            _ => {
+                // TODO: Implement Bluetooth pairing UI
+            }
+        }
+    }
+
+    fn handle_controller_assign_input(&mut self, input: ControllerInput) {
+        match input {
+            ControllerInput::Back => {
+                // Return to Controllers menu
+                self.current_screen = OverlayScreen::Controllers;
+                println!("[State] Returning to Controllers menu");
+            }
+            _ => {
+                // TODO: Implement controller assignment UI
+            }
+        }
+    }
+}
+
+/// Current Unix time in seconds
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+#[derive(Clone)]
+pub struct Toast {
+    pub message: String,
+    pub icon: Option<String>,
+    pub style: ToastStyle,
+    pub created_at: Instant,
+    pub duration: Duration,
+}
+
+/// Notifications kept in the history, newest first
+const TOAST_HISTORY_LEN: usize = 50;
+/// How long the summary of snoozed notifications stays up
+const SNOOZE_SUMMARY_MS: u32 = 6000;
+
+pub struct ToastManager {
+    queue: VecDeque<Toast>,
+    max_visible: usize,
+    /// Applied to the duration of toasts added from now on, from the power mode
+    pub duration_scale: f32,
+    /// Every toast added, shown or not, newest first
+    history: VecDeque<Toast>,
+    /// While set, new toasts only go to the history (message rules' `quiet`)
+    pub quiet: bool,
+    /// Which styles are held back until the overlay next opens
+    pub snooze: SnoozeConfig,
+    /// Held back by the snooze, oldest first
+    snoozed: Vec<Toast>,
+}
+
+impl ToastManager {
+    pub fn new() -> Self {
+        Self {
+            queue: VecDeque::new(),
+            max_visible: 3,
+            duration_scale: 1.0,
+            history: VecDeque::with_capacity(TOAST_HISTORY_LEN),
+            quiet: false,
+            snooze: SnoozeConfig::default(),
+            snoozed: Vec::new(),
+        }
+    }
+
+    pub fn add_toast(
+        &mut self,
+        message: String,
+        icon: Option<String>,
+        style: ToastStyle,
+        duration_ms: u32,
+    ) {
+        let toast = Toast {
+            message,
+            icon,
+            style,
+            created_at: Instant::now(),
+            duration: Duration::from_millis((duration_ms as f32 * self.duration_scale) as u64),
+        };
+        if self.history.len() >= TOAST_HISTORY_LEN {
+            self.history.pop_back();
+        }
+        self.history.push_front(toast.clone());
+        if self.quiet {
+            println!("[Toast] Quieted: {} ({:?})", toast.message, toast.style);
+            return;
+        }
+        if self.snooze.snoozes(toast.style) {
+            println!("[Toast] Snoozed: {} ({:?})", toast.message, toast.style);
+            self.snoozed.push(toast);
+            return;
+        }
+        println!("[Toast] Added: {} ({:?})", toast.message, toast.style);
+        self.queue.push_back(toast);
+    }
+
+    /// Show everything the snooze held back as one toast
+    pub fn flush_snoozed(&mut self) {
+        let held: Vec<(String, ToastStyle)> = self
+            .snoozed
+            .drain(..)
+            .map(|toast| (toast.message, toast.style))
+            .collect();
+        let Some((message, style)) = snooze::summary(&held) else {
+            return;
+        };
+        println!("[Toast] Showing {} snoozed notification(s)", held.len());
+        self.queue.push_back(Toast {
+            message,
+            icon: None,
+            style,
+            created_at: Instant::now(),
+            duration: Duration::from_millis(
+                (SNOOZE_SUMMARY_MS as f32 * self.duration_scale) as u64,
+            ),
+        });
+    }
+
+    pub fn snoozed_count(&self) -> usize {
+        self.snoozed.len()
+    }
+
+    /// Notification history, newest first
+    pub fn history(&self) -> impl Iterator<Item = &Toast> {
+        self.history.iter()
+    }
+
+    pub fn update(&mut self) {
+        let now = Instant::now();
+        self.queue
+            .retain(|toast| now.duration_since(toast.created_at) < toast.duration);
+    }
+
+    pub fn get_visible_toasts(&self) -> Vec<&Toast> {
+        self.queue.iter().take(self.max_visible).collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Helper to create a test achievement
+    fn create_test_achievement(id: u32, title: &str, earned: bool) -> AchievementInfo {
+        AchievementInfo {
+            id,
+            title: title.to_string(),
+            description: format!("{} description", title),
+            points: 10,
+            earned,
+            earned_hardcore: false,
+            rarity_percent: None,
+            earned_at: None,
+            progress: None,
+            unofficial: false,
+        }
+    }
+
+    #[test]
+    fn test_toast_manager_add_and_remove() {
+        let mut manager = ToastManager::new();
+
+        // Add toasts
+        manager.add_toast("Toast 1".to_string(), None, ToastStyle::Info, 1000);
+        manager.add_toast("Toast 2".to_string(), None, ToastStyle::Success, 1000);
+
+        assert!(!manager.is_empty());
+        assert_eq!(manager.get_visible_toasts().len(), 2);
+
+        // Wait for expiration
+        std::thread::sleep(Duration::from_millis(1100));
+        manager.update();
+
+        assert!(manager.is_empty());
+        assert_eq!(manager.get_visible_toasts().len(), 0);
+    }
+
+    #[test]
+    fn test_quiet_toasts_only_reach_history() {
+        let mut manager = ToastManager::new();
+        manager.add_toast("Shown".to_string(), None, ToastStyle::Info, 1000);
+        manager.quiet = true;
+        manager.add_toast("Hidden".to_string(), None, ToastStyle::Info, 1000);
+
+        assert_eq!(manager.get_visible_toasts().len(), 1);
+        let history: Vec<&str> = manager.history().map(|t| t.message.as_str()).collect();
+        assert_eq!(history, vec!["Hidden", "Shown"]);
+    }
+
+    #[test]
+    fn test_toast_duration_follows_power_mode() {
+        let mut manager = ToastManager::new();
+        manager.duration_scale = PowerMode::Battery.toast_scale();
+        manager.add_toast("Short".to_string(), None, ToastStyle::Info, 1000);
+        assert_eq!(
+            manager.get_visible_toasts()[0].duration,
+            Duration::from_millis(600)
+        );
+    }
+
+    #[test]
+    fn test_toast_manager_max_visible() {
+        let mut manager = ToastManager::new();
+
+        // Add more toasts than max_visible
+        for i in 0..5 {
+            manager.add_toast(format!("Toast {}", i), None, ToastStyle::Info, 5000);
+        }
+
+        // Should only show max_visible (3)
+        assert_eq!(manager.get_visible_toasts().len(), 3);
+
+        // But all 5 should be in the queue
+        assert_eq!(manager.queue.len(), 5);
+    }
+
+    #[test]
+    fn test_achievement_tracker_basic() {
+        let mut tracker = AchievementTracker::new();
+
+        assert!(!tracker.has_game());
+        assert_eq!(tracker.progress.total, 0);
+        assert_eq!(tracker.progress.earned, 0);
+
+        tracker.game_id = Some(1234);
+        assert!(tracker.has_game());
+    }
+
+    #[test]
+    fn test_achievement_tracker_progress() {
+        let mut tracker = AchievementTracker::new();
+
+        let achievements = vec![
+            create_test_achievement(1, "First", true),
+            create_test_achievement(2, "Second", false),
+            create_test_achievement(3, "Third", true),
+        ];
+
+        tracker.set_achievements(achievements);
+
+        assert_eq!(tracker.progress.total, 3);
+        assert_eq!(tracker.progress.earned, 2);
+    }
+
+    #[test]
+    fn test_unofficial_achievements_excluded_from_progress() {
+        let mut tracker = AchievementTracker::new();
+
+        let mut bonus = create_test_achievement(3, "Bonus", true);
+        bonus.unofficial = true;
+        let mut locked_bonus = create_test_achievement(4, "Locked Bonus", false);
+        locked_bonus.unofficial = true;
+
+        tracker.set_achievements(vec![
+            create_test_achievement(1, "First", true),
+            create_test_achievement(2, "Second", false),
+            bonus,
+            locked_bonus,
+        ]);
+
+        assert_eq!(tracker.achievements.len(), 4);
+        assert_eq!(tracker.progress.total, 2);
+        assert_eq!(tracker.progress.earned, 1);
+
+        tracker.unlock_achievement(4);
+        assert!(tracker.achievements[3].earned);
+        assert_eq!(tracker.progress.earned, 1);
+    }
+
+    #[test]
+    fn test_achievement_tracker_unlock() {
+        let mut tracker = AchievementTracker::new();
+
+        let achievements = vec![
+            create_test_achievement(1, "Achievement 1", false),
+            create_test_achievement(2, "Achievement 2", false),
+        ];
+
+        tracker.set_achievements(achievements);
+        assert_eq!(tracker.progress.earned, 0);
+
+        // Unlock achievement
+        tracker.unlock_achievement(1);
+        assert_eq!(tracker.progress.earned, 1);
+        assert!(tracker.achievements[0].earned);
+        assert!(!tracker.achievements[1].earned);
+
+        // Unlock same achievement again (should not double-count)
+        tracker.unlock_achievement(1);
+        assert_eq!(tracker.progress.earned, 1);
+    }
+
+    #[test]
+    fn test_achievement_tracker_measured_progress() {
+        let mut tracker = AchievementTracker::new();
+        tracker.set_achievements(vec![create_test_achievement(1, "Rings", false)]);
+
+        assert!(tracker.set_measured_progress(1, 45, 100));
+        let progress = tracker.achievements[0].progress.as_ref().unwrap();
+        assert_eq!((progress.current, progress.target), (45, 100));
+
+        // Clamped to the target, and cleared by a zero target
+        tracker.set_measured_progress(1, 150, 100);
+        assert_eq!(
+            tracker.achievements[0].progress.as_ref().unwrap().current,
+            100
+        );
+        tracker.set_measured_progress(1, 0, 0);
+        assert!(tracker.achievements[0].progress.is_none());
+
+        assert!(!tracker.set_measured_progress(99, 1, 2));
+    }
+
+    #[test]
+    fn test_pinned_achievements_closest_first() {
+        let mut tracker = AchievementTracker::new();
+        tracker.set_achievements(
+            (1..=5)
+                .map(|id| create_test_achievement(id, "Rings", id == 5))
+                .collect(),
+        );
+        tracker.set_measured_progress(1, 10, 100);
+        tracker.set_measured_progress(2, 90, 100);
+        tracker.set_measured_progress(3, 1, 2);
+        tracker.set_measured_progress(4, 0, 100);
+        tracker.set_measured_progress(5, 99, 100);
+
+        let ids: Vec<u32> = tracker.pinned().iter().map(|a| a.id).collect();
+        assert_eq!(ids, [2, 3, 1]);
+    }
+
+    #[test]
+    fn test_achievement_tracker_clear() {
+        let mut tracker = AchievementTracker::new();
+
+        tracker.game_id = Some(100);
+        tracker.set_achievements(vec![create_test_achievement(1, "Test", false)]);
+
+        tracker.clear();
+
+        assert!(!tracker.has_game());
+        assert_eq!(tracker.achievements.len(), 0);
+        assert_eq!(tracker.progress.total, 0);
+        assert_eq!(tracker.progress.earned, 0);
+    }
+
+    #[test]
+    fn test_achievement_filter_all() {
+        let mut filter = AchievementFilterState::new();
+        let achievements = vec![
+            create_test_achievement(1, "Earned", true),
+            create_test_achievement(2, "Unearned", false),
+            create_test_achievement(3, "Another Earned", true),
+        ];
+
+        filter.set_filter(AchievementFilter::All);
+        filter.apply_filter(&achievements);
+
+        assert_eq!(filter.get_filtered_count(), 3);
+    }
+
+    #[test]
+    fn test_achievement_filter_earned() {
+        let mut filter = AchievementFilterState::new();
+        let achievements = vec![
+            create_test_achievement(1, "Earned", true),
+            create_test_achievement(2, "Unearned", false),
+            create_test_achievement(3, "Another Earned", true),
+        ];
+
+        filter.set_filter(AchievementFilter::Earned);
+        filter.apply_filter(&achievements);
+
+        assert_eq!(filter.get_filtered_count(), 2);
+        assert_eq!(filter.get_achievement_at(0), Some(0));
+        assert_eq!(filter.get_achievement_at(1), Some(2));
+    }
+
+    #[test]
+    fn test_achievement_filter_unearned() {
+        let mut filter = AchievementFilterState::new();
+        let achievements = vec![
+            create_test_achievement(1, "Earned", true),
+            create_test_achievement(2, "Unearned", false),
+            create_test_achievement(3, "Another Earned", true),
+        ];
+
+        filter.set_filter(AchievementFilter::Unearned);
+        filter.apply_filter(&achievements);
+
+        assert_eq!(filter.get_filtered_count(), 1);
+        assert_eq!(filter.get_achievement_at(0), Some(1));
+    }
+
+    #[test]
+    fn test_achievement_search() {
+        let mut filter = AchievementFilterState::new();
+        let achievements = vec![
+            create_test_achievement(1, "First Achievement", false),
+            create_test_achievement(2, "Second Achievement", false),
+            create_test_achievement(3, "Special Badge", false),
+        ];
 
-                    // Signal the game to quit by writing to quit file
-                    if let Err(e) = signal_game_quit() {
-                        eprintln!("[State] Failed to signal quit: {}", e);
-                        self.toasts.add_toast(
-                            format!("Failed to quit: {}", e),
-                            None,
-                            ToastStyle::Error,
-                            3000,
-                        );
-                    }
+        filter.update_search("achievement".to_string());
+        filter.apply_filter(&achievements);
 
-                    self.visible = false;
-                } else {
-                    // Cancel selected
-                    self.current_screen = OverlayScreen::Main;
-                    self.selected_option = 5; // Keep quit option selected in main menu
-                    println!("[State] Quit cancelled");
-                }
-            }
-            ControllerInput::Back => {
-                // Back button always cancels
-                self.current_screen = OverlayScreen::Main;
-                self.selected_option = 5; // Keep quit selected
-                println!("[State] Quit cancelled");
-            }
-            _ => {}
-        }
+        assert_eq!(filter.get_filtered_count(), 2);
+        assert_eq!(filter.get_achievement_at(0), Some(0));
+        assert_eq!(filter.get_achievement_at(1), Some(1));
     }
 
-    fn handle_hotkey_settings_input(&mut self, input: ControllerInput) {
-        match input {
-            ControllerInput::Back => {
-                // Return to Controllers menu
-                self.current_screen = OverlayScreen::Controllers;
-                self.controllers.selected_menu_item = 3; // Keep Hotkey Settings selected
-                println!("[State] Returning to Controllers menu");
-            }
-            _ => {
-                // TODO: Implement hotkey configuration UI
-            }
-        }
+    #[test]
+    fn test_achievement_search_case_insensitive() {
+        let mut filter = AchievementFilterState::new();
+        let achievements = vec![
+            create_test_achievement(1, "UPPERCASE", false),
+            create_test_achievement(2, "lowercase", false),
+        ];
+
+        filter.update_search("case".to_string());
+        filter.apply_filter(&achievements);
+
+        assert_eq!(filter.get_filtered_count(), 2);
     }
 
-    fn handle_bluetooth_pairing_input(&mut self, input: ControllerInput) {
-        match input {
-            ControllerInput::Back => {
-                // Return to Controllers menu
-                self.current_screen = OverlayScreen::Controllers;
-                println!("[State] Returning to Controllers menu");
-            }
-            _ => {
-                // TODO: Implement Bluetooth pairing UI
-            }
+    /// Build a visible OverlayState on the given screen without touching async init
+    fn test_state(screen: OverlayScreen) -> OverlayState {
+        OverlayState {
+            visible: true,
+            current_screen: screen,
+            selected_option: 0,
+            main_menu_scroll_offset: 0,
+            settings_selected_option: 0,
+            settings_scroll_offset: 0,
+            menu_customization_selected: 0,
+            menu_customization_scroll_offset: 0,
+            quick_actions_selected: 0,
+            quick_actions_settings_selected: 0,
+            theme_selected: 0,
+            theme_selection_scroll_offset: 0,
+            theme_preview_color_vision: ColorVision::Normal,
+            theme_preview_high_contrast: false,
+            theme_preview_backdrop: crate::themes::Theme::dark().default_backdrop(),
+            theme_preview_row: 0,
+            backdrop_blur: false,
+            blur_supported: None,
+            quit_confirm_selected: 0,
+            toasts: ToastManager::new(),
+            recent_unlocks: 0,
+            unlocks_while_closed: 0,
+            achievements: AchievementTracker::new(),
+            controllers: ControllerState::new(),
+            performance: PerformanceStats::new(),
+            playtime: PlaytimeTracker::new().unwrap(),
+            menu_config: MenuConfigManager::new().unwrap(),
+            theme_config: ThemeConfigManager::new().unwrap(),
+            ra_poller: None,
+            achievements_scroll_offset: 0,
+            achievements_selected: 0,
+            achievements_player: 0,
+            hints: None,
+            compare: None,
+            login: None,
+            ra_user: None,
+            stream: None,
+            ipc_stats: IpcStats::default(),
+            resources: ScreenResources::default(),
+            quit: None,
+            shutdown_requested: false,
+            search: None,
+            theme_editor: None,
+            games: Vec::new(),
+            achievement_filter: AchievementFilterState::new(),
+            playtime_focus: None,
+            challenges: Vec::new(),
+            challenges_scroll: 0,
+            auto_pause: None,
+            haptics: None,
+            ipc_rules: None,
+            debug_console: None,
+            power: None,
+            recovery: None,
+            watchdog: None,
+            calibration: None,
+            speedrun: None,
+            speedrun_selected: 0,
+            pointer: Pointer::new(),
+            network: NetworkMonitor::default(),
+            celebration: None,
+            hud_layouts: None,
+            hud_editor_selected: 0,
+            battery: BatteryMonitor::default(),
+            cheats: None,
+            game_controls: Vec::new(),
+            controls_cache: None,
+            macros: Vec::new(),
+            macros_selected: 0,
+            music: None,
+            music_source: MusicSource::AllTracks,
+            music_target_playlist: None,
+            music_selected: 0,
+            onboarding: None,
+            tour: None,
         }
     }
 
-    fn handle_controller_assign_input(&mut self, input: ControllerInput) {
-        match input {
-            ControllerInput::Back => {
-                // Return to Controllers menu
-                self.current_screen = OverlayScreen::Controllers;
-                println!("[State] Returning to Controllers menu");
-            }
-            _ => {
-                // TODO: Implement controller assignment UI
-            }
-        }
+    #[test]
+    fn test_tour_runs_once_per_profile() {
+        let dir = std::env::temp_dir().join(format!("kazeta-tour-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let mut state = test_state(OverlayScreen::Main);
+        state.visible = false;
+        state.onboarding = Some(Onboarding::with_path(dir.join("onboarding.json")));
+        let items = state.menu_config.config().get_visible_items();
+
+        state.toggle_visibility();
+        assert_eq!(
+            state.tour.as_ref().map(|t| t.steps.len()),
+            Some(items.len())
+        );
+
+        // A moves the highlight along the menu, B goes back
+        state.handle_input(ControllerInput::Select);
+        assert_eq!(state.tour.as_ref().unwrap().index, 1);
+        assert_eq!(state.selected_option, 1);
+        assert_eq!(state.current_screen, OverlayScreen::Main);
+        state.handle_input(ControllerInput::Back);
+        assert_eq!(state.selected_option, 0);
+        assert!(state.visible);
+
+        // X skips, and the profile doesn't see it again
+        state.handle_input(ControllerInput::Secondary);
+        assert!(state.tour.is_none());
+        state.toggle_visibility();
+        state.toggle_visibility();
+        assert!(state.tour.is_none());
+
+        // Another profile still gets it
+        state.ra_user = Some("alice".to_string());
+        state.toggle_visibility();
+        state.toggle_visibility();
+        assert!(state.tour.is_some());
+
+        // Settings > Replay Tour restarts it
+        state.tour = None;
+        state.current_screen = OverlayScreen::Settings;
+        state.settings_selected_option = 14;
+        state.handle_input(ControllerInput::Select);
+        assert_eq!(state.current_screen, OverlayScreen::Main);
+        assert!(state.tour.is_some());
+
+        let _ = std::fs::remove_dir_all(&dir);
     }
-}
 
-/// Signal the running game to quit
-/// Creates a quit file that runtime wrappers check
-fn signal_game_quit() -> std::io::Result<()> {
-    use std::fs;
-    use std::io::Write;
+    #[test]
+    fn test_quit_confirm_selection() {
+        let mut state = test_state(OverlayScreen::QuitConfirm);
+
+        // Default should be Cancel (0)
+        assert_eq!(state.quit_confirm_selected, 0);
 
-    const QUIT_SIGNAL_FILE: &str = "/tmp/kazeta-quit-game";
+        // Navigate to Quit
+        state.handle_input(ControllerInput::Right);
+        assert_eq!(state.quit_confirm_selected, 1);
 
-    // Create the quit signal file
-    let mut file = fs::File::create(QUIT_SIGNAL_FILE)?;
-    writeln!(file, "quit")?;
+        // Navigate back to Cancel
+        state.handle_input(ControllerInput::Left);
+        assert_eq!(state.quit_confirm_selected, 0);
 
-    println!("[Quit] Created quit signal file: {}", QUIT_SIGNAL_FILE);
+        // Any direction should toggle
+        state.handle_input(ControllerInput::Up);
+        assert_eq!(state.quit_confirm_selected, 1);
 
-    // Also try to send SIGTERM to game processes
-    // This is a backup in case the wrapper doesn't see the file
-    #[cfg(target_os = "linux")]
-    {
-        // Try to find and signal common emulator processes
-        let emulators = ["mgba-qt", "vbam", "visualboyadvance-m", "retroarch", "dolphin-emu"];
-        for emu in emulators {
-            let _ = std::process::Command::new("pkill")
-                .args(["-TERM", emu])
-                .spawn();
-        }
+        state.handle_input(ControllerInput::Down);
+        assert_eq!(state.quit_confirm_selected, 0);
     }
 
-    Ok(())
-}
-
-pub struct Toast {
-    pub message: String,
-    pub icon: Option<String>,
-    pub style: ToastStyle,
-    pub created_at: Instant,
-    pub duration: Duration,
-}
+    #[test]
+    fn test_quit_confirm_cancel() {
+        let mut state = test_state(OverlayScreen::QuitConfirm);
 
-pub struct ToastManager {
-    queue: VecDeque<Toast>,
-    max_visible: usize,
-}
+        // Select Cancel
+        state.handle_input(ControllerInput::Select);
 
-impl ToastManager {
-    pub fn new() -> Self {
-        Self {
-            queue: VecDeque::new(),
-            max_visible: 3,
-        }
+        // Should return to main menu
+        assert_eq!(state.current_screen, OverlayScreen::Main);
     }
 
-    pub fn add_toast(&mut self, message: String, icon: Option<String>, style: ToastStyle, duration_ms: u32) {
-        println!("[Toast] Added: {} ({:?})", message, style);
-        let toast = Toast {
-            message,
-            icon,
-            style,
-            created_at: Instant::now(),
-            duration: Duration::from_millis(duration_ms as u64),
+    #[test]
+    fn test_quit_confirm_back_button() {
+        let mut state = OverlayState {
+            quit_confirm_selected: 1, // Quit selected
+            ..test_state(OverlayScreen::QuitConfirm)
         };
-        self.queue.push_back(toast);
-    }
 
-    pub fn update(&mut self) {
-        let now = Instant::now();
-        self.queue.retain(|toast| {
-            now.duration_since(toast.created_at) < toast.duration
-        });
-    }
+        // Press Back button - should always cancel even if Quit is selected
+        state.handle_input(ControllerInput::Back);
 
-    pub fn get_visible_toasts(&self) -> Vec<&Toast> {
-        self.queue.iter().take(self.max_visible).collect()
+        // Should return to main menu
+        assert_eq!(state.current_screen, OverlayScreen::Main);
     }
 
-    pub fn is_empty(&self) -> bool {
-        self.queue.is_empty()
-    }
-}
+    #[test]
+    fn test_settings_scroll_follows_selection() {
+        let mut state = test_state(OverlayScreen::Settings);
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        for _ in 0..8 {
+            state.handle_input(ControllerInput::Down);
+        }
+        assert_eq!(state.settings_selected_option, 8);
+        assert_eq!(state.settings_scroll_offset, 8 + 1 - SETTINGS_VISIBLE_ROWS);
 
-    // Helper to create a test achievement
-    fn create_test_achievement(id: u32, title: &str, earned: bool) -> AchievementInfo {
-        AchievementInfo {
-            id,
-            title: title.to_string(),
-            description: format!("{} description", title),
-            points: 10,
-            earned,
-            earned_hardcore: false,
-            rarity_percent: None,
-            earned_at: None,
-            progress: None,
+        for _ in 0..8 {
+            state.handle_input(ControllerInput::Up);
         }
+        assert_eq!(state.settings_selected_option, 0);
+        assert_eq!(state.settings_scroll_offset, 0);
     }
 
     #[test]
-    fn test_toast_manager_add_and_remove() {
-        let mut manager = ToastManager::new();
-
-        // Add toasts
-        manager.add_toast("Toast 1".to_string(), None, ToastStyle::Info, 1000);
-        manager.add_toast("Toast 2".to_string(), None, ToastStyle::Success, 1000);
-
-        assert!(!manager.is_empty());
-        assert_eq!(manager.get_visible_toasts().len(), 2);
+    fn test_theme_preview_backdrop_sliders() {
+        let mut state = test_state(OverlayScreen::ThemeSelection);
+        let saved = state.theme_config.backdrop_for("Dark");
+        state.theme_selected = 0;
+        state.handle_input(ControllerInput::Select);
+        assert_eq!(state.current_screen, OverlayScreen::ThemePreview);
 
-        // Wait for expiration
-        std::thread::sleep(Duration::from_millis(1100));
-        manager.update();
+        // Dimming is the second row
+        state.handle_input(ControllerInput::Down);
+        state.handle_input(ControllerInput::Left);
+        assert!(state.theme_preview_backdrop.opacity < saved.opacity);
+        assert_eq!(
+            state.theme_preview_backdrop.panel_opacity,
+            saved.panel_opacity
+        );
 
-        assert!(manager.is_empty());
-        assert_eq!(manager.get_visible_toasts().len(), 0);
+        // Backing out leaves the saved backdrop alone
+        state.handle_input(ControllerInput::Back);
+        assert_eq!(state.current_screen, OverlayScreen::ThemeSelection);
+        assert_eq!(state.theme_config.backdrop_for("Dark"), saved);
     }
 
     #[test]
-    fn test_toast_manager_max_visible() {
-        let mut manager = ToastManager::new();
-
-        // Add more toasts than max_visible
-        for i in 0..5 {
-            manager.add_toast(
-                format!("Toast {}", i),
-                None,
-                ToastStyle::Info,
-                5000,
-            );
-        }
+    fn test_exclusive_input_only_while_typing() {
+        let mut state = test_state(OverlayScreen::RaLogin);
+        state.visible = true;
+        state.login = Some(LoginView::open(None));
+        assert!(!state.wants_exclusive_input());
 
-        // Should only show max_visible (3)
-        assert_eq!(manager.get_visible_toasts().len(), 3);
+        // A on the username field opens the on-screen keyboard
+        state.handle_input(ControllerInput::Select);
+        assert!(state.wants_exclusive_input());
 
-        // But all 5 should be in the queue
-        assert_eq!(manager.queue.len(), 5);
+        state.visible = false;
+        assert!(!state.wants_exclusive_input());
     }
 
     #[test]
-    fn test_achievement_tracker_basic() {
-        let mut tracker = AchievementTracker::new();
+    fn test_quick_actions_palette() {
+        let mut state = test_state(OverlayScreen::Main);
+        state.visible = false;
+        state.menu_config.config_mut().quick_actions = vec![
+            QuickAction::Screenshot,
+            QuickAction::ToggleHud,
+            QuickAction::Achievements,
+            QuickAction::Quit,
+        ];
 
-        assert!(!tracker.has_game());
-        assert_eq!(tracker.progress.total, 0);
-        assert_eq!(tracker.progress.earned, 0);
+        state.open_quick_actions();
+        assert!(state.visible);
+        assert_eq!(state.current_screen, OverlayScreen::QuickActions);
+
+        // Stick right points at slot 1, the D-Pad steps around the circle
+        state.point_quick_actions(1.0, 0.0);
+        assert_eq!(state.quick_actions_selected, 1);
+        state.point_quick_actions(0.1, 0.1);
+        assert_eq!(state.quick_actions_selected, 1);
+        state.handle_input(ControllerInput::Left);
+        state.handle_input(ControllerInput::Left);
+        assert_eq!(state.quick_actions_selected, 3);
+
+        state.handle_input(ControllerInput::Select);
+        assert_eq!(state.current_screen, OverlayScreen::QuitConfirm);
+
+        let hud_visible = state.performance.is_visible();
+        state.open_quick_actions();
+        state.handle_input(ControllerInput::Down);
+        state.handle_input(ControllerInput::Select);
+        assert_ne!(state.performance.is_visible(), hud_visible);
+        assert!(!state.visible);
 
-        // Set game info
-        tracker.set_game_info(1234, "Test Game".to_string(), "GBA".to_string());
-        assert!(tracker.has_game());
-        assert_eq!(tracker.game_id, Some(1234));
-        assert_eq!(tracker.game_title, "Test Game");
+        state.open_quick_actions();
+        state.handle_input(ControllerInput::Back);
+        assert!(!state.visible);
     }
 
     #[test]
-    fn test_achievement_tracker_progress() {
-        let mut tracker = AchievementTracker::new();
-
-        let achievements = vec![
-            create_test_achievement(1, "First", true),
-            create_test_achievement(2, "Second", false),
-            create_test_achievement(3, "Third", true),
-        ];
+    fn test_screen_navigation() {
+        let mut state = test_state(OverlayScreen::Main);
 
-        tracker.set_achievements(achievements);
+        // Navigate to achievements screen
+        state.selected_option = 2; // Achievements is typically at index 2
+        state.handle_input(ControllerInput::Select);
+        assert_eq!(state.current_screen, OverlayScreen::Achievements);
 
-        assert_eq!(tracker.progress.total, 3);
-        assert_eq!(tracker.progress.earned, 2);
-        assert_eq!(tracker.get_progress_percent(), 66.666664); // 2/3 * 100
+        // Go back to main menu
+        state.handle_input(ControllerInput::Back);
+        assert_eq!(state.current_screen, OverlayScreen::Main);
     }
 
     #[test]
-    fn test_achievement_tracker_unlock() {
-        let mut tracker = AchievementTracker::new();
-
-        let achievements = vec![
-            create_test_achievement(1, "Achievement 1", false),
-            create_test_achievement(2, "Achievement 2", false),
-        ];
-
-        tracker.set_achievements(achievements);
-        assert_eq!(tracker.progress.earned, 0);
+    fn test_mastery_celebration_dismissed_by_input() {
+        let mut state = test_state(OverlayScreen::Main);
+        state.visible = false;
+        state.handle_message(OverlayMessage::RaMastery {
+            game_title: "Celeste".to_string(),
+            hardcore: true,
+            total_achievements: 30,
+            points: 400,
+            completion_secs: Some(3600),
+        });
+        assert!(state.should_render());
+        assert_eq!(state.achievements.progress.earned, 30);
 
-        // Unlock achievement
-        tracker.unlock_achievement(1);
-        assert_eq!(tracker.progress.earned, 1);
-        assert!(tracker.achievements[0].earned);
-        assert!(!tracker.achievements[1].earned);
+        // Input only reaches the overlay while it is shown
+        state.handle_input(ControllerInput::Select);
+        assert!(state.celebration.is_some());
 
-        // Unlock same achievement again (should not double-count)
-        tracker.unlock_achievement(1);
-        assert_eq!(tracker.progress.earned, 1);
+        state.visible = true;
+        state.handle_input(ControllerInput::Select);
+        assert!(state.celebration.is_none());
+        assert_eq!(state.current_screen, OverlayScreen::Main);
     }
 
     #[test]
-    fn test_achievement_tracker_clear() {
-        let mut tracker = AchievementTracker::new();
+    fn test_search_filters_achievements() {
+        let mut state = test_state(OverlayScreen::Main);
+        state.achievements.set_achievements(vec![
+            create_test_achievement(1, "Golden Key", true),
+            create_test_achievement(2, "Silver Key", false),
+            create_test_achievement(3, "Boss Rush", false),
+        ]);
+        state.handle_message(OverlayMessage::GameList {
+            games: vec![GameEntry {
+                cart_id: "celeste".to_string(),
+                name: "Celeste".to_string(),
+            }],
+        });
 
-        tracker.set_game_info(100, "Game".to_string(), "Console".to_string());
-        tracker.set_achievements(vec![create_test_achievement(1, "Test", false)]);
+        state.handle_input(ControllerInput::Secondary);
+        assert_eq!(state.current_screen, OverlayScreen::Search);
 
-        tracker.clear();
+        state.open_search_target(SearchTarget::FilterAchievements("key".to_string()));
+        assert_eq!(state.current_screen, OverlayScreen::Achievements);
+        assert_eq!(state.visible_achievement_indices(), vec![0, 1]);
 
-        assert!(!tracker.has_game());
-        assert_eq!(tracker.achievements.len(), 0);
-        assert_eq!(tracker.progress.total, 0);
-        assert_eq!(tracker.progress.earned, 0);
+        // Back clears the filter before leaving the screen, keeping the selection
+        state.handle_input(ControllerInput::Down);
+        state.handle_input(ControllerInput::Back);
+        assert_eq!(state.current_screen, OverlayScreen::Achievements);
+        assert_eq!(state.visible_achievement_indices(), vec![0, 1, 2]);
+        assert_eq!(state.achievements_selected, 1);
+
+        state.open_search_target(SearchTarget::Game(state.games[0].clone()));
+        assert_eq!(state.current_screen, OverlayScreen::Playtime);
+        state.handle_input(ControllerInput::Back);
+        assert!(state.playtime_focus.is_none());
     }
 
     #[test]
-    fn test_achievement_filter_all() {
-        let mut filter = AchievementFilterState::new();
-        let achievements = vec![
+    fn test_lb_cycles_the_achievement_filter() {
+        let mut state = test_state(OverlayScreen::Achievements);
+        state.achievements.set_achievements(vec![
             create_test_achievement(1, "Earned", true),
             create_test_achievement(2, "Unearned", false),
             create_test_achievement(3, "Another Earned", true),
-        ];
+        ]);
+        state.handle_input(ControllerInput::Down);
 
-        filter.set_filter(AchievementFilter::All);
-        filter.apply_filter(&achievements);
+        state.handle_input(ControllerInput::LB);
+        assert_eq!(state.achievement_filter.filter, AchievementFilter::Earned);
+        assert_eq!(state.visible_achievement_indices(), vec![0, 2]);
+        assert_eq!(state.achievements_selected, 0);
 
-        assert_eq!(filter.get_filtered_count(), 3);
+        state.handle_input(ControllerInput::LB);
+        assert_eq!(state.achievement_filter.filter, AchievementFilter::Unearned);
+        assert_eq!(state.visible_achievement_indices(), vec![1]);
+
+        // Round past All to Earned again; Back drops the filter, not the screen
+        state.handle_input(ControllerInput::LB);
+        state.handle_input(ControllerInput::LB);
+        assert_eq!(state.achievement_filter.filter, AchievementFilter::Earned);
+        state.handle_input(ControllerInput::Back);
+        assert_eq!(state.current_screen, OverlayScreen::Achievements);
+        assert_eq!(state.achievement_filter.filter, AchievementFilter::All);
+        assert_eq!(state.visible_achievement_indices(), vec![0, 1, 2]);
     }
 
     #[test]
-    fn test_achievement_filter_earned() {
-        let mut filter = AchievementFilterState::new();
-        let achievements = vec![
-            create_test_achievement(1, "Earned", true),
-            create_test_achievement(2, "Unearned", false),
-            create_test_achievement(3, "Another Earned", true),
-        ];
+    fn test_unlock_patches_achievement_list() {
+        let mut state = test_state(OverlayScreen::Achievements);
+        let set: Vec<_> = (1..=5)
+            .map(|id| create_test_achievement(id, "Rings", false))
+            .collect();
+        state.achievements.set_achievements(set.clone());
+        state.achievement_filter.filter = AchievementFilter::Unearned;
+        state
+            .achievement_filter
+            .apply_filter(&state.achievements.achievements);
+        state.handle_input(ControllerInput::Down);
 
-        filter.set_filter(AchievementFilter::Earned);
-        filter.apply_filter(&achievements);
+        // A single unlock drops just that entry from the Unearned filter
+        state.handle_message(OverlayMessage::RaAchievementUnlocked {
+            achievement_id: 4,
+            title: "Rings".to_string(),
+            description: None,
+            points: 10,
+            icon_url: None,
+            is_hardcore: false,
+            player: None,
+        });
+        assert_eq!(state.visible_achievement_indices(), vec![0, 1, 2, 4]);
+        assert_eq!(state.achievements_selected, 1);
+
+        // The same set sent again only patches what changed
+        let mut resync = set;
+        resync[3].earned = true;
+        resync[0].earned = true;
+        state.handle_message(OverlayMessage::RaAchievementList {
+            game_title: String::new(),
+            game_hash: "abc".to_string(),
+            achievements: resync.clone(),
+        });
+        assert_eq!(state.visible_achievement_indices(), vec![1, 2, 4]);
+        assert_eq!(state.achievements.progress.earned, 2);
+        assert_eq!(
+            state.achievements.set_achievements(resync),
+            ListUpdate::Patched(vec![])
+        );
 
-        assert_eq!(filter.get_filtered_count(), 2);
-        assert_eq!(filter.get_achievement_at(0), Some(0));
-        assert_eq!(filter.get_achievement_at(1), Some(2));
+        // A different set replaces the list
+        let other = vec![create_test_achievement(9, "Other", false)];
+        assert_eq!(
+            state.achievements.set_achievements(other),
+            ListUpdate::Replaced
+        );
+        assert_eq!(state.achievements.position(9), Some(0));
+        assert_eq!(state.achievements.position(4), None);
     }
 
     #[test]
-    fn test_achievement_filter_unearned() {
-        let mut filter = AchievementFilterState::new();
-        let achievements = vec![
-            create_test_achievement(1, "Earned", true),
-            create_test_achievement(2, "Unearned", false),
-            create_test_achievement(3, "Another Earned", true),
-        ];
+    fn test_snoozed_unlocks_summarised_when_overlay_opens() {
+        let mut state = test_state(OverlayScreen::Main);
+        state.visible = false;
+        state.toasts.snooze.enabled = true;
+        let unlock = |id: u32, title: &str| OverlayMessage::RaAchievementUnlocked {
+            achievement_id: id,
+            title: title.to_string(),
+            description: None,
+            points: 5,
+            icon_url: None,
+            is_hardcore: false,
+            player: None,
+        };
 
-        filter.set_filter(AchievementFilter::Unearned);
-        filter.apply_filter(&achievements);
+        state.handle_message(unlock(1, "First"));
+        state.handle_message(unlock(2, "Second"));
+        state
+            .toasts
+            .add_toast("Save failed".to_string(), None, ToastStyle::Error, 3000);
+        // Only the error isn't a snoozed style
+        assert_eq!(state.toasts.get_visible_toasts().len(), 1);
+        assert_eq!(state.toasts.snoozed_count(), 2);
+        assert_eq!(state.toasts.history().count(), 3);
+
+        state.toggle_visibility();
+        assert_eq!(state.recent_unlocks, 2);
+        assert_eq!(state.toasts.snoozed_count(), 0);
+        let toasts = state.toasts.get_visible_toasts();
+        assert_eq!(toasts.len(), 2);
+        assert_eq!(
+            toasts[1].message,
+            "2 while snoozed: 🏆 First (5 points) · 🏆 Second (5 points)"
+        );
 
-        assert_eq!(filter.get_filtered_count(), 1);
-        assert_eq!(filter.get_achievement_at(0), Some(1));
+        // Seen now; the next open starts from zero
+        state.toggle_visibility();
+        state.toggle_visibility();
+        assert_eq!(state.recent_unlocks, 0);
     }
 
-    #[test]
-    fn test_achievement_search() {
-        let mut filter = AchievementFilterState::new();
-        let achievements = vec![
-            create_test_achievement(1, "First Achievement", false),
-            create_test_achievement(2, "Second Achievement", false),
-            create_test_achievement(3, "Special Badge", false),
-        ];
-
-        filter.update_search("achievement".to_string());
-        filter.apply_filter(&achievements);
+    /// Size of the achievement set in the large-set test and benchmarks
+    const LARGE_SET_SIZE: usize = 1000;
 
-        assert_eq!(filter.get_filtered_count(), 2);
-        assert_eq!(filter.get_achievement_at(0), Some(0));
-        assert_eq!(filter.get_achievement_at(1), Some(1));
+    fn large_set() -> Vec<AchievementInfo> {
+        (1..=LARGE_SET_SIZE as u32)
+            .map(|id| create_test_achievement(id, "Collect every ring in the zone", id % 3 == 0))
+            .collect()
     }
 
     #[test]
-    fn test_achievement_search_case_insensitive() {
-        let mut filter = AchievementFilterState::new();
-        let achievements = vec![
-            create_test_achievement(1, "UPPERCASE", false),
-            create_test_achievement(2, "lowercase", false),
-        ];
+    fn test_large_achievement_set_scrolls_smoothly() {
+        let mut state = test_state(OverlayScreen::Achievements);
+        state.achievements.set_achievements(large_set());
+
+        // Scroll to the bottom and back, building each frame's rows the way
+        // the renderer does
+        let mut rows_built = 0;
+        for input in [ControllerInput::Down, ControllerInput::Up] {
+            for _ in 0..LARGE_SET_SIZE {
+                state.handle_input(input);
+                rows_built += state
+                    .achievement_rows(state.achievements_scroll_offset, ACHIEVEMENTS_VISIBLE_ROWS)
+                    .count();
+            }
+        }
 
-        filter.update_search("case".to_string());
-        filter.apply_filter(&achievements);
+        // Only the visible rows are built, whatever the set size
+        assert_eq!(rows_built, LARGE_SET_SIZE * 2 * ACHIEVEMENTS_VISIBLE_ROWS);
+        assert_eq!(state.achievements_selected, 0);
 
-        assert_eq!(filter.get_filtered_count(), 2);
+        for id in 1..=LARGE_SET_SIZE as u32 {
+            state.achievements.unlock_achievement(id);
+        }
+        assert_eq!(state.achievements.progress.earned, LARGE_SET_SIZE as u32);
     }
 
-    #[test]
-    fn test_quit_confirm_selection() {
-        let mut state_builder = || {
-            OverlayState {
-                visible: true,
-                current_screen: OverlayScreen::QuitConfirm,
-                selected_option: 0,
-                main_menu_scroll_offset: 0,
-                settings_selected_option: 0,
-                settings_scroll_offset: 0,
-                menu_customization_selected: 0,
-                menu_customization_scroll_offset: 0,
-                theme_selected: 0,
-                theme_selection_scroll_offset: 0,
-                quit_confirm_selected: 0,
-                toasts: ToastManager::new(),
-                achievements: AchievementTracker::new(),
-                controllers: ControllerState::new(),
-                performance: PerformanceStats::new(),
-                playtime: PlaytimeTracker::new().unwrap(),
-                menu_config: MenuConfigManager::new().unwrap(),
-                theme_config: ThemeConfigManager::new().unwrap(),
-            }
-        };
-
-        let mut state = state_builder();
+    /// Timings for the large-set test: `cargo +nightly bench --features daemon,bench`
+    #[cfg(feature = "bench")]
+    mod benches {
+        use super::*;
+        use test::{black_box, Bencher};
+
+        /// One frame of scrolling: the input, then the rows the renderer draws
+        #[bench]
+        fn bench_large_achievement_set_scroll_frame(b: &mut Bencher) {
+            let mut state = test_state(OverlayScreen::Achievements);
+            state.achievements.set_achievements(large_set());
+            let mut input = ControllerInput::Down;
+            b.iter(|| {
+                match state.achievements_selected {
+                    0 => input = ControllerInput::Down,
+                    selected if selected + 1 == LARGE_SET_SIZE => input = ControllerInput::Up,
+                    _ => {}
+                }
+                state.handle_input(input);
+                black_box(
+                    state
+                        .achievement_rows(
+                            state.achievements_scroll_offset,
+                            ACHIEVEMENTS_VISIBLE_ROWS,
+                        )
+                        .count(),
+                )
+            });
+        }
 
-        // Default should be Cancel (0)
-        assert_eq!(state.quit_confirm_selected, 0);
+        /// Loading the set and unlocking all of it, one achievement at a time
+        #[bench]
+        fn bench_large_achievement_set_unlocks(b: &mut Bencher) {
+            let achievements = large_set();
+            let mut tracker = AchievementTracker::new();
+            b.iter(|| {
+                tracker.set_achievements(achievements.clone());
+                for id in 1..=LARGE_SET_SIZE as u32 {
+                    black_box(tracker.unlock_achievement(id));
+                }
+            });
+        }
+    }
 
-        // Navigate to Quit
-        state.handle_input(ControllerInput::Right);
-        assert_eq!(state.quit_confirm_selected, 1);
+    #[test]
+    fn test_game_controls_cached_per_cart() {
+        let dir =
+            std::env::temp_dir().join(format!("kazeta-state-controls-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let mut state = test_state(OverlayScreen::Main);
+        state.controls_cache = Some(ControlsCache::with_dir(dir.clone()));
+        let started = r#"{"type":"game_started","cart_id":"celeste","game_name":"Celeste","runtime":"linux"}"#;
+
+        state.handle_message(serde_json::from_str(started).unwrap());
+        assert!(state.game_controls.is_empty());
+        let controls = r#"{"type":"game_controls","cart_id":"celeste","controls":[{"button":"a","label":"Jump"}]}"#;
+        state.handle_message(serde_json::from_str(controls).unwrap());
+        assert_eq!(state.game_controls.len(), 1);
+
+        state.open_menu_item(MenuItemId::Controls);
+        assert_eq!(state.current_screen, OverlayScreen::GameControls);
+        state.handle_message(OverlayMessage::GameStopped {
+            cart_id: "celeste".to_string(),
+        });
+        assert!(state.game_controls.is_empty());
+        assert_eq!(state.current_screen, OverlayScreen::Main);
 
-        // Navigate back to Cancel
-        state.handle_input(ControllerInput::Left);
-        assert_eq!(state.quit_confirm_selected, 0);
+        // The next launch has the mapping before the BIOS sends it again
+        state.handle_message(serde_json::from_str(started).unwrap());
+        assert_eq!(state.game_controls[0].label, "Jump");
 
-        // Any direction should toggle
-        state.handle_input(ControllerInput::Up);
-        assert_eq!(state.quit_confirm_selected, 1);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
 
-        state.handle_input(ControllerInput::Down);
-        assert_eq!(state.quit_confirm_selected, 0);
+    #[test]
+    fn test_ra_game_start_metadata() {
+        let mut state = test_state(OverlayScreen::Main);
+
+        // Senders from before console and icon were added still work
+        let legacy = r#"{"type":"ra_game_start","game_title":"Celeste","total_achievements":30,"earned_achievements":4}"#;
+        state.handle_message(serde_json::from_str(legacy).unwrap());
+        assert_eq!(state.achievements.game_title, "Celeste");
+        assert!(state.achievements.icon.path.is_none());
+        assert!(state.achievements.difficulty.is_none());
+
+        let full = r#"{"type":"ra_game_start","game_title":"Celeste","total_achievements":30,"earned_achievements":4,
+            "console":"Game Boy Advance","icon_url":"/Images/000001.png","icon_path":"/tmp/000001.png",
+            "difficulty":6.5,"expected_master_secs":36000}"#;
+        state.handle_message(serde_json::from_str(full).unwrap());
+        assert_eq!(state.achievements.console, "Game Boy Advance");
+        assert_eq!(
+            state.achievements.icon_url.as_deref(),
+            Some("/Images/000001.png")
+        );
+        assert_eq!(
+            state.achievements.icon.path.as_deref(),
+            Some(Path::new("/tmp/000001.png"))
+        );
+        let difficulty = state.achievements.difficulty.unwrap();
+        assert_eq!(difficulty.label(), "Very Hard");
+        assert_eq!(difficulty.expected_master_secs, Some(36000));
+
+        state.handle_message(OverlayMessage::GameStopped {
+            cart_id: "celeste".to_string(),
+        });
+        assert!(state.achievements.console.is_empty());
+        assert!(state.achievements.icon.path.is_none());
+        assert!(state.achievements.difficulty.is_none());
     }
 
     #[test]
-    fn test_quit_confirm_cancel() {
-        let mut state = OverlayState {
-            visible: true,
-            current_screen: OverlayScreen::QuitConfirm,
-            selected_option: 0,
-            main_menu_scroll_offset: 0,
-            settings_selected_option: 0,
-            settings_scroll_offset: 0,
-            menu_customization_selected: 0,
-            menu_customization_scroll_offset: 0,
-            theme_selected: 0,
-            theme_selection_scroll_offset: 0,
-            quit_confirm_selected: 0, // Cancel selected
-            toasts: ToastManager::new(),
-            achievements: AchievementTracker::new(),
-            controllers: ControllerState::new(),
-            performance: PerformanceStats::new(),
-            playtime: PlaytimeTracker::new().unwrap(),
-            menu_config: MenuConfigManager::new().unwrap(),
-            theme_config: ThemeConfigManager::new().unwrap(),
-        };
+    fn test_session_snapshot_restores_game() {
+        let mut state = test_state(OverlayScreen::Main);
+        assert!(state.session_snapshot().is_none());
+
+        state
+            .playtime
+            .resume_session("celeste".to_string(), Duration::from_secs(600));
+        state.achievements.game_id = Some(1234);
+        state.achievements.game_title = "Celeste".to_string();
+        let snapshot = state.session_snapshot().unwrap();
+        assert_eq!(snapshot.saved_at - snapshot.session_started_at, 600);
+        state.playtime.current_session = None;
+
+        let mut restored = test_state(OverlayScreen::Main);
+        restored.apply_snapshot(snapshot);
+        let session = restored.playtime.current_session.as_ref().unwrap();
+        assert_eq!(session.cart_id, "celeste");
+        assert!(session.start_time.elapsed() >= Duration::from_secs(600));
+        assert_eq!(restored.achievements.game_id, Some(1234));
+        assert_eq!(restored.achievements.game_title, "Celeste");
+        assert!(restored.ra_poller.is_some());
+        assert_eq!(
+            restored.toasts.get_visible_toasts()[0].message,
+            "Recovered session: Celeste"
+        );
+        restored.playtime.current_session = None;
+    }
 
-        // Select Cancel
-        state.handle_input(ControllerInput::Select);
+    #[test]
+    fn test_watchdog_stops_crashed_game() {
+        let mut state = test_state(OverlayScreen::Main);
+        let started = r#"{"type":"game_started","cart_id":"celeste","game_name":"Celeste","runtime":"linux","pid":4294967295}"#;
+        state.handle_message(serde_json::from_str(started).unwrap());
+        state.achievements.game_id = Some(1234);
+        state.achievements.game_title = "Celeste".to_string();
+        assert!(state.watchdog.is_some());
+
+        // The process doesn't exist, so the first check stops the game
+        state.update_watchdog();
+        assert!(state.watchdog.is_none());
+        assert!(state.playtime.current_session.is_none());
+        assert!(!state.achievements.has_game());
+
+        // Without a PID there's nothing to watch
+        let started = r#"{"type":"game_started","cart_id":"celeste","game_name":"Celeste","runtime":"linux"}"#;
+        state.handle_message(serde_json::from_str(started).unwrap());
+        assert!(state.watchdog.is_none());
+        state.handle_message(OverlayMessage::GameStopped {
+            cart_id: "celeste".to_string(),
+        });
+    }
 
-        // Should return to main menu
+    #[test]
+    fn test_challenges_screen() {
+        let mut state = test_state(OverlayScreen::Main);
+        let sets = r#"{"type":"ra_challenges","sets":[
+            {"period":"daily","ends_at":1000,"streak":2,"best_streak":4,"challenges":[
+                {"achievement_id":1,"title":"One","game_title":"Celeste","points":5,"completed":true},
+                {"achievement_id":2,"title":"Two","game_title":"Celeste","points":10,"rarity_percent":3.5}]},
+            {"period":"weekly","ends_at":5000,"challenges":[
+                {"achievement_id":3,"title":"Three","game_title":"Tetris"}]}]}"#;
+        state.handle_message(serde_json::from_str(sets).unwrap());
+        assert_eq!(state.challenges.len(), 2);
+        assert!(state.challenges[0].challenges[0].completed);
+        assert_eq!(state.challenges[1].streak, 0);
+
+        state.open_menu_item(MenuItemId::Challenges);
+        assert_eq!(state.current_screen, OverlayScreen::Challenges);
+
+        // Five rows fit, so there is nothing to scroll
+        state.handle_input(ControllerInput::Down);
+        assert_eq!(state.challenges_scroll, 0);
+        state.handle_input(ControllerInput::Back);
         assert_eq!(state.current_screen, OverlayScreen::Main);
     }
 
     #[test]
-    fn test_quit_confirm_back_button() {
-        let mut state = OverlayState {
-            visible: true,
-            current_screen: OverlayScreen::QuitConfirm,
-            selected_option: 0,
-            main_menu_scroll_offset: 0,
-            settings_selected_option: 0,
-            settings_scroll_offset: 0,
-            menu_customization_selected: 0,
-            menu_customization_scroll_offset: 0,
-            theme_selected: 0,
-            theme_selection_scroll_offset: 0,
-            quit_confirm_selected: 1, // Quit selected
-            toasts: ToastManager::new(),
-            achievements: AchievementTracker::new(),
-            controllers: ControllerState::new(),
-            performance: PerformanceStats::new(),
-            playtime: PlaytimeTracker::new().unwrap(),
-            menu_config: MenuConfigManager::new().unwrap(),
-            theme_config: ThemeConfigManager::new().unwrap(),
+    fn test_unlock_history_feeds_stats() {
+        let mut state = test_state(OverlayScreen::Achievements);
+        let list = r#"{"type":"ra_achievement_list","game_title":"Celeste","game_hash":"abc","achievements":[
+            {"id":1,"title":"One","description":"","points":5,"earned":true,"rarity_percent":40.0,"earned_at":1000},
+            {"id":2,"title":"Two","description":"","points":10,"earned":false,"rarity_percent":3.5}]}"#;
+        state.handle_message(serde_json::from_str(list).unwrap());
+
+        // History for a different game is dropped
+        let other = r#"{"type":"ra_unlock_history","game_hash":"zzz","unlocks":[{"achievement_id":9,"earned_at":5}]}"#;
+        state.handle_message(serde_json::from_str(other).unwrap());
+        assert!(state.achievements.unlocks.is_empty());
+
+        let history = r#"{"type":"ra_unlock_history","game_hash":"abc","unlocks":[{"achievement_id":1,"earned_at":1000,"points":5}]}"#;
+        state.handle_message(serde_json::from_str(history).unwrap());
+        assert_eq!(state.achievements.unlocks.len(), 1);
+
+        // Live unlocks join the history
+        state.handle_message(OverlayMessage::RaAchievementUnlocked {
+            achievement_id: 2,
+            title: "Two".to_string(),
+            description: None,
+            points: 10,
+            icon_url: None,
+            is_hardcore: true,
+            player: None,
+        });
+        assert_eq!(state.achievements.unlocks.len(), 2);
+        assert!(state.achievements.unlocks[1].hardcore);
+        let stats = state.achievement_stats();
+        assert_eq!(stats.remaining, 0);
+        assert_eq!(stats.sessions.iter().map(|s| s.points).sum::<u32>(), 15);
+
+        state.handle_input(ControllerInput::RB);
+        assert_eq!(state.current_screen, OverlayScreen::AchievementStats);
+        state.handle_input(ControllerInput::Back);
+        assert_eq!(state.current_screen, OverlayScreen::Achievements);
+    }
+
+    #[test]
+    fn test_shared_session_tracks_each_player() {
+        let mut state = test_state(OverlayScreen::Achievements);
+        let list = r#"{"type":"ra_achievement_list","game_title":"Pokemon","game_hash":"abc","achievements":[
+            {"id":1,"title":"One","description":"","points":5,"earned":false},
+            {"id":2,"title":"Two","description":"","points":10,"earned":false}]}"#;
+        state.handle_message(serde_json::from_str(list).unwrap());
+
+        let alice = SessionPlayer {
+            slot: 1,
+            name: "alice".to_string(),
+            account: true,
+        };
+        let bob = SessionPlayer {
+            slot: 2,
+            name: "bob".to_string(),
+            account: false,
+        };
+        state.handle_message(OverlayMessage::RaSessionPlayers {
+            players: vec![bob.clone(), alice.clone()],
+        });
+        assert_eq!(state.selected_player(), Some(&alice));
+
+        let unlock = |id: u32, player: &SessionPlayer| OverlayMessage::RaAchievementUnlocked {
+            achievement_id: id,
+            title: format!("#{}", id),
+            description: None,
+            points: 5,
+            icon_url: None,
+            is_hardcore: false,
+            player: Some(player.clone()),
         };
 
-        // Press Back button - should always cancel even if Quit is selected
-        state.handle_input(ControllerInput::Back);
+        // Bob's unlock stays off the account's progress and history
+        state.handle_message(unlock(2, &bob));
+        assert_eq!(state.achievements.progress.earned, 0);
+        assert!(state.achievements.unlocks.is_empty());
+        assert!(state
+            .toasts
+            .history()
+            .next()
+            .unwrap()
+            .message
+            .contains("bob"));
+
+        state.handle_message(unlock(1, &alice));
+        assert_eq!(state.achievements.progress.earned, 1);
+        assert_eq!(state.achievements.unlocks.len(), 1);
+
+        // Tabs flip between players
+        let two = state.achievements.achievements[1].clone();
+        assert!(!state.achievements.earned_by(&two, state.selected_player()));
+        state.handle_input(ControllerInput::Right);
+        assert_eq!(state.selected_player(), Some(&bob));
+        assert!(state.achievements.earned_by(&two, state.selected_player()));
+        assert_eq!(
+            state
+                .achievements
+                .player_progress(state.selected_player())
+                .earned,
+            1
+        );
+        state.handle_input(ControllerInput::Right);
+        assert_eq!(state.selected_player(), Some(&alice));
 
-        // Should return to main menu
-        assert_eq!(state.current_screen, OverlayScreen::Main);
+        state.handle_message(OverlayMessage::GameStopped {
+            cart_id: "pokemon".to_string(),
+        });
+        assert!(state.achievements.players.is_empty());
+        assert!(state.selected_player().is_none());
     }
 
     #[test]
-    fn test_screen_navigation() {
-        let mut state = OverlayState {
-            visible: true,
-            current_screen: OverlayScreen::Main,
-            selected_option: 0,
-            main_menu_scroll_offset: 0,
-            settings_selected_option: 0,
-            settings_scroll_offset: 0,
-            menu_customization_selected: 0,
-            menu_customization_scroll_offset: 0,
-            theme_selected: 0,
-            theme_selection_scroll_offset: 0,
-            quit_confirm_selected: 0,
-            toasts: ToastManager::new(),
-            achievements: AchievementTracker::new(),
-            controllers: ControllerState::new(),
-            performance: PerformanceStats::new(),
-            playtime: PlaytimeTracker::new().unwrap(),
-            menu_config: MenuConfigManager::new().unwrap(),
-            theme_config: ThemeConfigManager::new().unwrap(),
-        };
+    fn test_debug_console_logs_and_injects() {
+        let mut state = test_state(OverlayScreen::IpcRules);
 
-        // Navigate to achievements screen
-        state.selected_option = 2; // Achievements is typically at index 2
+        // Hidden unless enabled
+        state.handle_input(ControllerInput::Secondary);
+        assert_eq!(state.current_screen, OverlayScreen::IpcRules);
+
+        state.debug_console = Some(DebugConsole::default());
+        state.handle_input(ControllerInput::Secondary);
+        assert_eq!(state.current_screen, OverlayScreen::DebugConsole);
+
+        // The first test message is an info toast, handled like a client's
         state.handle_input(ControllerInput::Select);
-        assert_eq!(state.current_screen, OverlayScreen::Achievements);
+        assert_eq!(state.toasts.history().next().unwrap().message, "Test toast");
+
+        let console = state.debug_console.as_ref().unwrap();
+        let logged = console.log().next().unwrap();
+        assert_eq!(logged.kind, "show_toast");
+        assert_eq!(
+            logged.source.as_deref(),
+            Some(crate::debug_console::INJECT_SOURCE)
+        );
+        assert_eq!(logged.action, RuleAction::Allow);
 
-        // Go back to main menu
+        state.handle_input(ControllerInput::Secondary);
+        assert_eq!(state.debug_console.as_ref().unwrap().len(), 0);
         state.handle_input(ControllerInput::Back);
-        assert_eq!(state.current_screen, OverlayScreen::Main);
+        assert_eq!(state.current_screen, OverlayScreen::IpcRules);
     }
 }
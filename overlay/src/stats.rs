@@ -0,0 +1,267 @@
+//! Statistics for the current game's achievements
+//!
+//! Built from the achievement list plus `ra_unlock_history`, the unlock times
+//! kazeta-ra keeps in its cache. The history doesn't record play sessions, so
+//! unlocks less than `SESSION_GAP_SECS` apart are counted as one session.
+
+use crate::ipc::{AchievementInfo, UnlockRecord};
+use std::collections::HashSet;
+
+/// Unlocks further apart than this start a new session
+pub const SESSION_GAP_SECS: u64 = 2 * 60 * 60;
+
+/// Weeks covered by the unlock heatmap
+pub const HEATMAP_WEEKS: usize = 12;
+
+const DAY_SECS: u64 = 24 * 60 * 60;
+
+/// Rarity buckets for remaining achievements, matching the colors on the
+/// achievements screen: (upper bound in percent, label)
+pub const RARITY_BUCKETS: [(f32, &str); 5] = [
+    (5.0, "Ultra rare"),
+    (10.0, "Very rare"),
+    (25.0, "Rare"),
+    (50.0, "Uncommon"),
+    (f32::INFINITY, "Common"),
+];
+
+/// Achievements unlocked in one play session
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionSummary {
+    /// Unix timestamp of the session's first unlock
+    pub start: u64,
+    pub unlocks: u32,
+    pub points: u32,
+}
+
+/// How the time to completion was estimated
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum EstimateBasis {
+    /// Tracked playtime divided by achievements earned
+    Playtime,
+    /// Calendar time between the first and last unlock
+    Calendar,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AchievementStats {
+    /// Unlocks per day (UTC) for the last `HEATMAP_WEEKS` weeks, oldest
+    /// first; the last entry is today
+    pub daily_unlocks: Vec<u32>,
+    /// Oldest first
+    pub sessions: Vec<SessionSummary>,
+    /// Unearned official achievements per `RARITY_BUCKETS` entry
+    pub remaining_by_rarity: [u32; RARITY_BUCKETS.len()],
+    /// Unearned official achievements without rarity data
+    pub remaining_unknown_rarity: u32,
+    pub remaining: u32,
+    /// Seconds left at the current unlock rate
+    pub estimate: Option<(u64, EstimateBasis)>,
+}
+
+impl AchievementStats {
+    /// `playtime_secs` is the total tracked playtime for the game, if any
+    pub fn compute(
+        achievements: &[AchievementInfo],
+        unlocks: &[UnlockRecord],
+        playtime_secs: Option<u64>,
+        now: u64,
+    ) -> Self {
+        // Softcore and hardcore unlocks of one achievement count once, at the
+        // earlier time
+        let mut sorted: Vec<&UnlockRecord> = unlocks.iter().collect();
+        sorted.sort_by_key(|u| u.earned_at);
+        let mut seen = HashSet::new();
+        sorted.retain(|u| seen.insert(u.achievement_id));
+
+        let days = HEATMAP_WEEKS * 7;
+        let today = now / DAY_SECS;
+        let mut daily_unlocks = vec![0; days];
+        for unlock in &sorted {
+            let age = today.saturating_sub(unlock.earned_at / DAY_SECS) as usize;
+            if age < days {
+                daily_unlocks[days - 1 - age] += 1;
+            }
+        }
+
+        let mut sessions: Vec<SessionSummary> = Vec::new();
+        let mut last_unlock = None;
+        for unlock in &sorted {
+            let continues =
+                last_unlock.is_some_and(|last| unlock.earned_at - last <= SESSION_GAP_SECS);
+            match sessions.last_mut() {
+                Some(session) if continues => {
+                    session.unlocks += 1;
+                    session.points += unlock.points;
+                }
+                _ => sessions.push(SessionSummary {
+                    start: unlock.earned_at,
+                    unlocks: 1,
+                    points: unlock.points,
+                }),
+            }
+            last_unlock = Some(unlock.earned_at);
+        }
+
+        let mut remaining_by_rarity = [0; RARITY_BUCKETS.len()];
+        let mut remaining_unknown_rarity = 0;
+        let official = achievements.iter().filter(|a| !a.unofficial);
+        for achievement in official.clone().filter(|a| !a.earned) {
+            match achievement.rarity_percent {
+                Some(rarity) => {
+                    let bucket = RARITY_BUCKETS
+                        .iter()
+                        .position(|(max, _)| rarity < *max)
+                        .unwrap_or(RARITY_BUCKETS.len() - 1);
+                    remaining_by_rarity[bucket] += 1;
+                }
+                None => remaining_unknown_rarity += 1,
+            }
+        }
+        let earned = official.clone().filter(|a| a.earned).count() as u64;
+        let remaining = official.filter(|a| !a.earned).count() as u32;
+
+        let estimate = if remaining == 0 {
+            None
+        } else if let Some(playtime) = playtime_secs.filter(|&p| p > 0 && earned > 0) {
+            Some((
+                playtime / earned * remaining as u64,
+                EstimateBasis::Playtime,
+            ))
+        } else if let (Some(first), Some(last)) = (sorted.first(), sorted.last()) {
+            let intervals = sorted.len() as u64 - 1;
+            (intervals > 0).then(|| {
+                let per_unlock = (last.earned_at - first.earned_at) / intervals;
+                (per_unlock * remaining as u64, EstimateBasis::Calendar)
+            })
+        } else {
+            None
+        };
+
+        Self {
+            daily_unlocks,
+            sessions,
+            remaining_by_rarity,
+            remaining_unknown_rarity,
+            remaining,
+            estimate,
+        }
+    }
+
+    /// Most unlocks on a single heatmap day
+    pub fn busiest_day(&self) -> u32 {
+        self.daily_unlocks.iter().copied().max().unwrap_or(0)
+    }
+}
+
+/// "3h 20m", "4d 2h" and so on, for the completion estimate
+pub fn format_duration(secs: u64) -> String {
+    let minutes = secs / 60;
+    let hours = minutes / 60;
+    let days = hours / 24;
+    if days > 0 {
+        format!("{}d {}h", days, hours % 24)
+    } else if hours > 0 {
+        format!("{}h {}m", hours, minutes % 60)
+    } else {
+        format!("{}m", minutes.max(1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn achievement(id: u32, earned: bool, rarity: Option<f32>) -> AchievementInfo {
+        AchievementInfo {
+            id,
+            title: format!("Achievement {}", id),
+            description: String::new(),
+            points: 10,
+            earned,
+            earned_hardcore: false,
+            rarity_percent: rarity,
+            earned_at: None,
+            progress: None,
+            unofficial: false,
+        }
+    }
+
+    fn unlock(achievement_id: u32, earned_at: u64, hardcore: bool) -> UnlockRecord {
+        UnlockRecord {
+            achievement_id,
+            earned_at,
+            hardcore,
+            points: 10,
+        }
+    }
+
+    #[test]
+    fn test_sessions_heatmap_and_rarity() {
+        let now = 100 * DAY_SECS + 3600;
+        let achievements = vec![
+            achievement(1, true, Some(80.0)),
+            achievement(2, true, Some(40.0)),
+            achievement(3, true, None),
+            achievement(4, false, Some(2.0)),
+            achievement(5, false, Some(60.0)),
+            achievement(6, false, None),
+        ];
+        let unlocks = vec![
+            // Two unlocks an hour apart on day 90, one more today
+            unlock(1, 90 * DAY_SECS, false),
+            unlock(2, 90 * DAY_SECS + 3600, false),
+            unlock(3, 100 * DAY_SECS, false),
+            // Hardcore re-earn of #1 doesn't count twice
+            unlock(1, 99 * DAY_SECS, true),
+        ];
+
+        let stats = AchievementStats::compute(&achievements, &unlocks, None, now);
+        assert_eq!(stats.daily_unlocks.len(), HEATMAP_WEEKS * 7);
+        assert_eq!(*stats.daily_unlocks.last().unwrap(), 1);
+        assert_eq!(stats.daily_unlocks[stats.daily_unlocks.len() - 11], 2);
+        assert_eq!(stats.busiest_day(), 2);
+
+        assert_eq!(stats.sessions.len(), 2);
+        assert_eq!(
+            (stats.sessions[0].unlocks, stats.sessions[0].points),
+            (2, 20)
+        );
+        assert_eq!(stats.sessions[1].start, 100 * DAY_SECS);
+
+        assert_eq!(stats.remaining, 3);
+        assert_eq!(stats.remaining_by_rarity, [1, 0, 0, 0, 1]);
+        assert_eq!(stats.remaining_unknown_rarity, 1);
+
+        // 10 days across 2 intervals, 3 left
+        assert_eq!(
+            stats.estimate,
+            Some((15 * DAY_SECS, EstimateBasis::Calendar))
+        );
+    }
+
+    #[test]
+    fn test_estimate_prefers_playtime() {
+        let achievements = vec![
+            achievement(1, true, None),
+            achievement(2, true, None),
+            achievement(3, false, None),
+        ];
+        let stats = AchievementStats::compute(&achievements, &[], Some(2 * 3600), DAY_SECS);
+        assert_eq!(stats.estimate, Some((3600, EstimateBasis::Playtime)));
+
+        // Nothing to go on yet
+        let stats =
+            AchievementStats::compute(&achievements, &[unlock(1, 0, false)], None, DAY_SECS);
+        assert_eq!(stats.estimate, None);
+
+        // Complete
+        let done = vec![achievement(1, true, None)];
+        assert_eq!(
+            AchievementStats::compute(&done, &[], Some(3600), DAY_SECS).estimate,
+            None
+        );
+        assert_eq!(format_duration(3 * 3600 + 20 * 60), "3h 20m");
+        assert_eq!(format_duration(4 * DAY_SECS + 2 * 3600), "4d 2h");
+    }
+}
@@ -0,0 +1,642 @@
+//! Spectator/stream output for OBS and similar tools
+//!
+//! Mirrors the current game, unlock events and session stats to plain files
+//! (`state.json` + `banner.png`) and, optionally, a tiny local HTTP endpoint
+//! that OBS can consume as a browser or image source. This lets streamers
+//! show achievement popups without capturing the overlay window itself.
+//!
+//! HTTP is served on its own thread, like IPC (see `ipc_thread`), so a client
+//! that connects and sends nothing never holds up the render loop. The main
+//! loop hands over each new snapshot through a mutex.
+
+use crate::wake;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::os::unix::io::AsRawFd;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Maximum number of events kept in the rolling feed
+const MAX_EVENTS: usize = 20;
+
+/// Minimum time between state.json rewrites when nothing notable happened
+const WRITE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How often the HTTP thread checks for shutdown while no client connects
+const HTTP_POLL_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// How long the HTTP thread waits for a client to send its request
+const HTTP_READ_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Stream output configuration
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct StreamConfig {
+    /// Master switch for stream mode
+    pub enabled: bool,
+    /// Serve the output over HTTP on 127.0.0.1 when set
+    pub http_port: Option<u16>,
+    /// Override for the output directory (defaults to overlay/stream)
+    pub output_dir: Option<PathBuf>,
+    /// Render a PNG banner for each unlock
+    pub render_banner: bool,
+    pub version: u32,
+}
+
+impl Default for StreamConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            http_port: Some(8787),
+            output_dir: None,
+            render_banner: true,
+            version: 1,
+        }
+    }
+}
+
+/// Kind of event in the stream feed
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum StreamEventKind {
+    GameStarted,
+    GameStopped,
+    Achievement,
+    Toast,
+}
+
+/// A single entry in the stream event feed
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct StreamEvent {
+    /// Monotonic id so browser sources can detect new events
+    pub id: u64,
+    pub kind: StreamEventKind,
+    pub title: String,
+    pub description: Option<String>,
+    pub points: Option<u32>,
+    pub timestamp: u64,
+}
+
+/// Snapshot written to state.json
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct StreamSnapshot {
+    pub game_title: Option<String>,
+    pub cart_id: Option<String>,
+    pub achievements_earned: u32,
+    pub achievements_total: u32,
+    pub session_seconds: u64,
+    pub total_playtime_seconds: u64,
+    pub events: Vec<StreamEvent>,
+    pub updated_at: u64,
+}
+
+/// Writes stream state to disk and serves it over HTTP
+pub struct StreamOutput {
+    config: StreamConfig,
+    config_path: PathBuf,
+    output_dir: PathBuf,
+    http: Option<HttpThread>,
+    snapshot: StreamSnapshot,
+    next_event_id: u64,
+    dirty: bool,
+    last_write: Option<Instant>,
+    banner_pending: Option<StreamEvent>,
+}
+
+impl StreamOutput {
+    /// Create new StreamOutput with config loaded from disk
+    pub fn new() -> Result<Self> {
+        let config_path = Self::get_config_path()?;
+
+        let config = if config_path.exists() {
+            println!("[Stream] Loading config from {:?}", config_path);
+            Self::load_config(&config_path)?
+        } else {
+            let default_config = StreamConfig::default();
+            if let Err(e) = Self::save_config(&config_path, &default_config) {
+                eprintln!("[Stream] Failed to save default config: {}", e);
+            }
+            default_config
+        };
+
+        let output_dir = match &config.output_dir {
+            Some(dir) => dir.clone(),
+            None => config_path
+                .parent()
+                .map(|p| p.join("stream"))
+                .context("Invalid stream config path")?,
+        };
+
+        let mut output = Self {
+            config,
+            config_path,
+            output_dir,
+            http: None,
+            snapshot: StreamSnapshot::default(),
+            next_event_id: 1,
+            dirty: true,
+            last_write: None,
+            banner_pending: None,
+        };
+
+        if output.config.enabled {
+            output.start();
+        }
+
+        Ok(output)
+    }
+
+    /// Get the configuration file path
+    fn get_config_path() -> Result<PathBuf> {
+        let data_dir =
+            dirs::data_local_dir().context("Could not determine local data directory")?;
+
+        let overlay_dir = data_dir.join("kazeta-plus").join("overlay");
+
+        if !overlay_dir.exists() {
+            fs::create_dir_all(&overlay_dir)
+                .context("Failed to create overlay config directory")?;
+        }
+
+        Ok(overlay_dir.join("stream.json"))
+    }
+
+    /// Load configuration from file
+    fn load_config(path: &PathBuf) -> Result<StreamConfig> {
+        let contents = fs::read_to_string(path).context("Failed to read stream config file")?;
+
+        let config: StreamConfig =
+            serde_json::from_str(&contents).context("Failed to parse stream config JSON")?;
+
+        Ok(config)
+    }
+
+    /// Save configuration to file
+    fn save_config(path: &PathBuf, config: &StreamConfig) -> Result<()> {
+        let json =
+            serde_json::to_string_pretty(config).context("Failed to serialize stream config")?;
+
+        fs::write(path, json).context("Failed to write stream config file")?;
+
+        println!("[Stream] Config saved to {:?}", path);
+        Ok(())
+    }
+
+    /// Create the output directory and bind the HTTP listener
+    fn start(&mut self) {
+        if let Err(e) = fs::create_dir_all(&self.output_dir) {
+            eprintln!(
+                "[Stream] Failed to create output directory {:?}: {}",
+                self.output_dir, e
+            );
+        }
+
+        if let Some(port) = self.config.http_port {
+            match HttpThread::spawn(port, self.banner_path()) {
+                Ok(http) => {
+                    println!(
+                        "[Stream] Serving stream output on http://127.0.0.1:{}/",
+                        port
+                    );
+                    self.http = Some(http);
+                }
+                Err(e) => eprintln!("[Stream] Failed to serve HTTP on port {}: {:#}", port, e),
+            }
+        }
+
+        println!("[Stream] Writing stream output to {:?}", self.output_dir);
+        self.dirty = true;
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.config.enabled
+    }
+
+    /// Enable or disable stream mode and persist the choice
+    pub fn set_enabled(&mut self, enabled: bool) -> Result<()> {
+        if self.config.enabled == enabled {
+            return Ok(());
+        }
+
+        self.config.enabled = enabled;
+        if enabled {
+            self.start();
+        } else {
+            self.http = None;
+            println!("[Stream] Stream mode disabled");
+        }
+
+        Self::save_config(&self.config_path, &self.config)
+    }
+
+    /// Path of the rendered unlock banner
+    pub fn banner_path(&self) -> PathBuf {
+        self.output_dir.join("banner.png")
+    }
+
+    /// Record a new game session
+    pub fn game_started(&mut self, cart_id: &str, title: &str) {
+        self.snapshot.cart_id = Some(cart_id.to_string());
+        self.snapshot.game_title = Some(title.to_string());
+        self.snapshot.achievements_earned = 0;
+        self.snapshot.achievements_total = 0;
+        self.push_event(StreamEventKind::GameStarted, title.to_string(), None, None);
+    }
+
+    /// Update the title once RetroAchievements resolves it
+    pub fn set_game_title(&mut self, title: &str) {
+        if title.is_empty() {
+            return;
+        }
+        self.snapshot.game_title = Some(title.to_string());
+        self.dirty = true;
+    }
+
+    /// Record the end of a game session
+    pub fn game_stopped(&mut self) {
+        let title = self.snapshot.game_title.take().unwrap_or_default();
+        self.snapshot.cart_id = None;
+        self.snapshot.achievements_earned = 0;
+        self.snapshot.achievements_total = 0;
+        self.snapshot.session_seconds = 0;
+        self.push_event(StreamEventKind::GameStopped, title, None, None);
+    }
+
+    pub fn set_progress(&mut self, earned: u32, total: u32) {
+        if self.snapshot.achievements_earned != earned || self.snapshot.achievements_total != total
+        {
+            self.snapshot.achievements_earned = earned;
+            self.snapshot.achievements_total = total;
+            self.dirty = true;
+        }
+    }
+
+    /// Record an achievement unlock and queue a banner render
+    pub fn achievement_unlocked(&mut self, title: &str, description: Option<String>, points: u32) {
+        let event = self.push_event(
+            StreamEventKind::Achievement,
+            title.to_string(),
+            description,
+            Some(points),
+        );
+        if self.config.enabled && self.config.render_banner {
+            self.banner_pending = Some(event);
+        }
+    }
+
+    /// Mirror a toast into the feed
+    pub fn toast(&mut self, message: &str) {
+        self.push_event(StreamEventKind::Toast, message.to_string(), None, None);
+    }
+
+    fn push_event(
+        &mut self,
+        kind: StreamEventKind,
+        title: String,
+        description: Option<String>,
+        points: Option<u32>,
+    ) -> StreamEvent {
+        let event = StreamEvent {
+            id: self.next_event_id,
+            kind,
+            title,
+            description,
+            points,
+            timestamp: current_timestamp(),
+        };
+        self.next_event_id += 1;
+
+        self.snapshot.events.insert(0, event.clone());
+        self.snapshot.events.truncate(MAX_EVENTS);
+        self.dirty = true;
+        event
+    }
+
+    /// Take the unlock that still needs a banner rendered (called from the render loop)
+    pub fn take_banner_request(&mut self) -> Option<StreamEvent> {
+        self.banner_pending.take()
+    }
+
+    /// Flush state to disk and hand it to the HTTP thread (called from main loop)
+    pub fn update(&mut self, session_seconds: u64, total_playtime_seconds: u64) {
+        if !self.config.enabled {
+            return;
+        }
+
+        if self.snapshot.session_seconds != session_seconds {
+            self.snapshot.session_seconds = session_seconds;
+            self.snapshot.total_playtime_seconds = total_playtime_seconds;
+            // Session counters alone only need the periodic write
+            if self
+                .last_write
+                .is_none_or(|t| t.elapsed() >= WRITE_INTERVAL)
+            {
+                self.dirty = true;
+            }
+        }
+
+        if self.dirty {
+            self.snapshot.updated_at = current_timestamp();
+            if let Err(e) = self.write_state() {
+                eprintln!("[Stream] Failed to write state: {}", e);
+            }
+            self.dirty = false;
+            self.last_write = Some(Instant::now());
+        }
+    }
+
+    fn write_state(&self) -> Result<()> {
+        if let Some(http) = &self.http {
+            let json =
+                serde_json::to_vec(&self.snapshot).context("Failed to serialize stream state")?;
+            *http.state.lock().unwrap() = json;
+        }
+
+        let json = serde_json::to_string_pretty(&self.snapshot)
+            .context("Failed to serialize stream state")?;
+
+        // Write to a temp file and rename so OBS never reads a partial file
+        let path = self.output_dir.join("state.json");
+        let tmp_path = self.output_dir.join("state.json.tmp");
+        fs::write(&tmp_path, json).context("Failed to write stream state")?;
+        fs::rename(&tmp_path, &path).context("Failed to replace stream state")?;
+        Ok(())
+    }
+}
+
+/// Serves the stream output to OBS until dropped
+struct HttpThread {
+    /// Latest snapshot as JSON, replaced by the main loop on each write
+    state: Arc<Mutex<Vec<u8>>>,
+    running: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl HttpThread {
+    /// Bind on the caller's thread so a busy port is reported right away,
+    /// then serve from a new thread
+    fn spawn(port: u16, banner_path: PathBuf) -> Result<Self> {
+        let listener =
+            TcpListener::bind(("127.0.0.1", port)).context("Failed to bind HTTP port")?;
+        listener
+            .set_nonblocking(true)
+            .context("Failed to set HTTP listener non-blocking")?;
+
+        let state = Arc::new(Mutex::new(b"{}".to_vec()));
+        let running = Arc::new(AtomicBool::new(true));
+        let handle = {
+            let state = state.clone();
+            let running = running.clone();
+            thread::Builder::new()
+                .name("stream-http".to_string())
+                .spawn(move || run_http(listener, state, banner_path, running))
+                .context("Failed to start stream HTTP thread")?
+        };
+
+        Ok(Self {
+            state,
+            running,
+            handle: Some(handle),
+        })
+    }
+}
+
+impl Drop for HttpThread {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+fn run_http(
+    listener: TcpListener,
+    state: Arc<Mutex<Vec<u8>>>,
+    banner_path: PathBuf,
+    running: Arc<AtomicBool>,
+) {
+    while running.load(Ordering::Relaxed) {
+        match listener.accept() {
+            Ok((stream, _)) => {
+                if let Err(e) = serve(stream, &state, &banner_path) {
+                    eprintln!("[Stream] HTTP request failed: {:#}", e);
+                }
+            }
+            Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                wake::wait_readable(&[listener.as_raw_fd()], HTTP_POLL_TIMEOUT);
+            }
+            Err(e) => {
+                eprintln!("[Stream] HTTP accept error: {}", e);
+                thread::sleep(HTTP_POLL_TIMEOUT);
+            }
+        }
+    }
+}
+
+fn serve(mut stream: TcpStream, state: &Mutex<Vec<u8>>, banner_path: &Path) -> Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(HTTP_READ_TIMEOUT))?;
+
+    let mut buf = [0u8; 1024];
+    let n = stream
+        .read(&mut buf)
+        .context("Failed to read HTTP request")?;
+    let request = String::from_utf8_lossy(&buf[..n]);
+    let path = parse_request_path(&request).unwrap_or("/");
+
+    let (status, content_type, body) = match path {
+        "/" | "/index.html" => (
+            "200 OK",
+            "text/html; charset=utf-8",
+            BROWSER_SOURCE_HTML.as_bytes().to_vec(),
+        ),
+        "/state.json" => ("200 OK", "application/json", state.lock().unwrap().clone()),
+        "/banner.png" => match read_file(banner_path) {
+            Some(bytes) => ("200 OK", "image/png", bytes),
+            None => ("404 Not Found", "text/plain", b"no banner yet".to_vec()),
+        },
+        _ => ("404 Not Found", "text/plain", b"not found".to_vec()),
+    };
+
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nCache-Control: no-store\r\nAccess-Control-Allow-Origin: *\r\nConnection: close\r\n\r\n",
+        status,
+        content_type,
+        body.len()
+    )?;
+    stream.write_all(&body)?;
+    stream.flush()?;
+    Ok(())
+}
+
+/// Extract the path from an HTTP request line, dropping any query string
+fn parse_request_path(request: &str) -> Option<&str> {
+    let line = request.lines().next()?;
+    let mut parts = line.split_whitespace();
+    if parts.next()? != "GET" {
+        return None;
+    }
+    let target = parts.next()?;
+    Some(target.split('?').next().unwrap_or(target))
+}
+
+fn read_file(path: &Path) -> Option<Vec<u8>> {
+    fs::read(path).ok()
+}
+
+/// Get current Unix timestamp
+fn current_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Minimal browser source: polls state.json and pops a banner for new unlocks
+const BROWSER_SOURCE_HTML: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<style>
+  body { margin: 0; background: transparent; font-family: sans-serif; color: #fff; }
+  #popup { position: absolute; top: 16px; right: 16px; width: 400px; padding: 12px 16px;
+           background: rgba(20, 20, 30, 0.9); border-left: 6px solid #4caf50; border-radius: 6px;
+           opacity: 0; transition: opacity 0.4s; }
+  #popup.show { opacity: 1; }
+  #title { font-size: 20px; font-weight: bold; }
+  #desc { font-size: 14px; color: #ccc; margin-top: 4px; }
+</style>
+</head>
+<body>
+<div id="popup"><div id="title"></div><div id="desc"></div></div>
+<script>
+  let lastId = null;
+  async function poll() {
+    try {
+      const state = await (await fetch('/state.json', { cache: 'no-store' })).json();
+      const latest = state.events.find(e => e.kind === 'achievement');
+      if (latest && lastId !== null && latest.id > lastId) {
+        document.getElementById('title').textContent = latest.title + (latest.points ? ' (' + latest.points + ' pts)' : '');
+        document.getElementById('desc').textContent = latest.description || '';
+        const popup = document.getElementById('popup');
+        popup.classList.add('show');
+        setTimeout(() => popup.classList.remove('show'), 5000);
+      }
+      if (latest) lastId = Math.max(lastId || 0, latest.id);
+      else if (lastId === null) lastId = 0;
+    } catch (e) {}
+    setTimeout(poll, 1000);
+  }
+  poll();
+</script>
+</body>
+</html>
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_output() -> StreamOutput {
+        StreamOutput {
+            config: StreamConfig::default(),
+            config_path: PathBuf::from("/tmp/kazeta-stream-test.json"),
+            output_dir: PathBuf::from("/tmp/kazeta-stream-test"),
+            http: None,
+            snapshot: StreamSnapshot::default(),
+            next_event_id: 1,
+            dirty: false,
+            last_write: None,
+            banner_pending: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_request_path() {
+        assert_eq!(
+            parse_request_path("GET /state.json HTTP/1.1\r\n"),
+            Some("/state.json")
+        );
+        assert_eq!(
+            parse_request_path("GET /state.json?t=1 HTTP/1.1\r\n"),
+            Some("/state.json")
+        );
+        assert_eq!(parse_request_path("POST / HTTP/1.1\r\n"), None);
+        assert_eq!(parse_request_path(""), None);
+    }
+
+    #[test]
+    fn test_event_feed_is_bounded_and_newest_first() {
+        let mut output = test_output();
+        for i in 0..(MAX_EVENTS + 5) {
+            output.toast(&format!("toast {}", i));
+        }
+
+        assert_eq!(output.snapshot.events.len(), MAX_EVENTS);
+        assert_eq!(
+            output.snapshot.events[0].title,
+            format!("toast {}", MAX_EVENTS + 4)
+        );
+        assert!(output.snapshot.events[0].id > output.snapshot.events[1].id);
+    }
+
+    #[test]
+    fn test_idle_client_does_not_block_updates() {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).unwrap();
+        let port = listener.local_addr().unwrap().port();
+        drop(listener);
+
+        let mut output = test_output();
+        output.config.enabled = true;
+        output.output_dir =
+            std::env::temp_dir().join(format!("kazeta-stream-test-{}", std::process::id()));
+        fs::create_dir_all(&output.output_dir).unwrap();
+        output.http = Some(HttpThread::spawn(port, output.banner_path()).unwrap());
+
+        // Connects and never sends a request
+        let _idle = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        let start = Instant::now();
+        output.game_started("celeste", "Celeste");
+        output.update(1, 1);
+        assert!(
+            start.elapsed() < HTTP_READ_TIMEOUT,
+            "update waited on the HTTP client"
+        );
+
+        // Served once the idle client times out
+        let mut client = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        client
+            .write_all(b"GET /state.json HTTP/1.1\r\n\r\n")
+            .unwrap();
+        let mut response = String::new();
+        client.read_to_string(&mut response).unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.contains("\"game_title\":\"Celeste\""));
+
+        drop(output);
+        let _ = fs::remove_dir_all(
+            std::env::temp_dir().join(format!("kazeta-stream-test-{}", std::process::id())),
+        );
+    }
+
+    #[test]
+    fn test_banner_only_queued_when_enabled() {
+        let mut output = test_output();
+        output.achievement_unlocked("First Blood", None, 10);
+        assert!(output.take_banner_request().is_none());
+
+        output.config.enabled = true;
+        output.achievement_unlocked("Second Wind", Some("Recover".to_string()), 5);
+        let event = output.take_banner_request().unwrap();
+        assert_eq!(event.title, "Second Wind");
+        assert_eq!(event.points, Some(5));
+        assert!(output.take_banner_request().is_none());
+    }
+}
@@ -1,14 +1,28 @@
-use anyhow::{Context, Result};
+use crate::backdrop::Backdrop;
+use crate::themes::{ColorVision, Theme, ThemeFile};
+use anyhow::{bail, Context, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
-use crate::themes::Theme;
+use std::path::{Path, PathBuf};
+
+/// Longest custom theme name accepted
+pub const MAX_THEME_NAME_LEN: usize = 24;
 
 /// Theme configuration
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ThemeConfig {
     pub theme_name: String,
     pub version: u32,
+    /// Semantic colors adjusted for a color vision deficiency
+    #[serde(default)]
+    pub color_vision: ColorVision,
+    /// Opaque panels, white text and larger fonts
+    #[serde(default)]
+    pub high_contrast: bool,
+    /// Backdrop per theme name; themes without one use their defaults
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub backdrops: HashMap<String, Backdrop>,
 }
 
 impl Default for ThemeConfig {
@@ -16,15 +30,24 @@ impl Default for ThemeConfig {
         Self {
             theme_name: "Dark".to_string(),
             version: 1,
+            color_vision: ColorVision::Normal,
+            high_contrast: false,
+            backdrops: HashMap::new(),
         }
     }
 }
 
 /// Manages theme configuration
+///
+/// Custom themes made in the theme editor live as one JSON file each in
+/// `themes/` next to the config and are listed after the presets. Exports
+/// go to `themes/shared/`, and theme files dropped there are picked up by
+/// an import.
 pub struct ThemeConfigManager {
     config: ThemeConfig,
     config_path: PathBuf,
     current_theme: Theme,
+    user_themes: Vec<Theme>,
 }
 
 impl ThemeConfigManager {
@@ -47,10 +70,15 @@ impl ThemeConfigManager {
             default_config
         };
 
+        let user_themes = Self::load_user_themes(&Self::themes_dir(&config_path));
+
         // Load the theme
-        let current_theme = Theme::by_name(&config.theme_name)
+        let current_theme = Self::build_theme(&config, &user_themes, &config.theme_name)
             .unwrap_or_else(|| {
-                eprintln!("[ThemeConfig] Theme '{}' not found, using Dark", config.theme_name);
+                eprintln!(
+                    "[ThemeConfig] Theme '{}' not found, using Dark",
+                    config.theme_name
+                );
                 Theme::dark()
             });
 
@@ -58,13 +86,93 @@ impl ThemeConfigManager {
             config,
             config_path,
             current_theme,
+            user_themes,
         })
     }
 
+    /// Directory of the custom theme files
+    fn themes_dir(config_path: &Path) -> PathBuf {
+        config_path.with_file_name("themes")
+    }
+
+    /// Where themes are exported to and imported from
+    pub fn shared_dir(&self) -> PathBuf {
+        Self::themes_dir(&self.config_path).join("shared")
+    }
+
+    /// Custom themes, sorted by name. Unreadable files and names that
+    /// clash with a preset or an earlier file are skipped.
+    fn load_user_themes(dir: &Path) -> Vec<Theme> {
+        let Ok(entries) = fs::read_dir(dir) else {
+            return Vec::new();
+        };
+        let mut paths: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .collect();
+        paths.sort();
+
+        let mut themes: Vec<Theme> = Vec::new();
+        for path in paths {
+            match Self::read_theme_file(&path) {
+                Ok(theme)
+                    if Theme::by_name(&theme.name).is_none()
+                        && !themes
+                            .iter()
+                            .any(|t| t.name.eq_ignore_ascii_case(&theme.name)) =>
+                {
+                    themes.push(theme)
+                }
+                Ok(theme) => eprintln!(
+                    "[ThemeConfig] Skipping {:?}: theme '{}' already exists",
+                    path, theme.name
+                ),
+                Err(e) => eprintln!("[ThemeConfig] Skipping {:?}: {:#}", path, e),
+            }
+        }
+        themes.sort_by_key(|t| t.name.to_lowercase());
+        if !themes.is_empty() {
+            println!("[ThemeConfig] Loaded {} custom theme(s)", themes.len());
+        }
+        themes
+    }
+
+    fn read_theme_file(path: &Path) -> Result<Theme> {
+        let contents = fs::read_to_string(path).context("Failed to read theme file")?;
+        let file: ThemeFile =
+            serde_json::from_str(&contents).context("Failed to parse theme file JSON")?;
+        file.to_theme()
+    }
+
+    fn write_theme_file(path: &Path, theme: &Theme) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).context("Failed to create themes directory")?;
+        }
+        let json = serde_json::to_string_pretty(&ThemeFile::from_theme(theme))
+            .context("Failed to serialize theme")?;
+        fs::write(path, json).context("Failed to write theme file")
+    }
+
+    /// File name for a theme: its name lowercased, with anything but letters
+    /// and digits as dashes
+    fn theme_file_name(name: &str) -> String {
+        let slug: String = name
+            .to_lowercase()
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+            .collect();
+        let slug = slug
+            .split('-')
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join("-");
+        format!("{}.json", if slug.is_empty() { "theme" } else { &slug })
+    }
+
     /// Get the configuration file path
     fn get_config_path() -> Result<PathBuf> {
-        let data_dir = dirs::data_local_dir()
-            .context("Could not determine local data directory")?;
+        let data_dir =
+            dirs::data_local_dir().context("Could not determine local data directory")?;
 
         let overlay_dir = data_dir.join("kazeta-plus").join("overlay");
 
@@ -79,22 +187,20 @@ impl ThemeConfigManager {
 
     /// Load configuration from file
     fn load_config(path: &PathBuf) -> Result<ThemeConfig> {
-        let contents = fs::read_to_string(path)
-            .context("Failed to read theme config file")?;
+        let contents = fs::read_to_string(path).context("Failed to read theme config file")?;
 
-        let config: ThemeConfig = serde_json::from_str(&contents)
-            .context("Failed to parse theme config JSON")?;
+        let config: ThemeConfig =
+            serde_json::from_str(&contents).context("Failed to parse theme config JSON")?;
 
         Ok(config)
     }
 
     /// Save configuration to file
     fn save_config(path: &PathBuf, config: &ThemeConfig) -> Result<()> {
-        let json = serde_json::to_string_pretty(config)
-            .context("Failed to serialize theme config")?;
+        let json =
+            serde_json::to_string_pretty(config).context("Failed to serialize theme config")?;
 
-        fs::write(path, json)
-            .context("Failed to write theme config file")?;
+        fs::write(path, json).context("Failed to write theme config file")?;
 
         println!("[ThemeConfig] Config saved to {:?}", path);
         Ok(())
@@ -110,12 +216,108 @@ impl ThemeConfigManager {
         &self.config.theme_name
     }
 
-    /// Set the theme by name
-    pub fn set_theme(&mut self, theme_name: &str) -> Result<()> {
-        let theme = Theme::by_name(theme_name)
+    pub fn color_vision(&self) -> ColorVision {
+        self.config.color_vision
+    }
+
+    pub fn high_contrast(&self) -> bool {
+        self.config.high_contrast
+    }
+
+    /// A preset or custom theme by name
+    fn find_in(user_themes: &[Theme], name: &str) -> Option<Theme> {
+        Theme::by_name(name).or_else(|| user_themes.iter().find(|t| t.name == name).cloned())
+    }
+
+    /// A theme with the configured options and its backdrop. High contrast
+    /// keeps its opaque panels whatever the backdrop says.
+    fn build_theme(config: &ThemeConfig, user_themes: &[Theme], theme_name: &str) -> Option<Theme> {
+        let theme = Self::variant_in(
+            user_themes,
+            theme_name,
+            config.color_vision,
+            config.high_contrast,
+        )?;
+        match config.backdrops.get(theme_name) {
+            Some(backdrop) if !config.high_contrast => Some(theme.with_backdrop(backdrop)),
+            _ => Some(theme),
+        }
+    }
+
+    /// A preset or custom theme by name, as designed
+    pub fn find(&self, name: &str) -> Option<Theme> {
+        Self::find_in(&self.user_themes, name)
+    }
+
+    fn variant_in(
+        user_themes: &[Theme],
+        name: &str,
+        color_vision: ColorVision,
+        high_contrast: bool,
+    ) -> Option<Theme> {
+        Theme::variant(name, color_vision, high_contrast).or_else(|| {
+            let theme = user_themes.iter().find(|t| t.name == name)?.clone();
+            Some(theme.with_options(color_vision, high_contrast))
+        })
+    }
+
+    /// A preset or custom theme with the accessibility options applied
+    pub fn variant(
+        &self,
+        name: &str,
+        color_vision: ColorVision,
+        high_contrast: bool,
+    ) -> Option<Theme> {
+        Self::variant_in(&self.user_themes, name, color_vision, high_contrast)
+    }
+
+    /// Presets followed by custom themes
+    pub fn all_themes(&self) -> Vec<Theme> {
+        let mut themes = Theme::all_presets();
+        themes.extend(self.user_themes.iter().cloned());
+        themes
+    }
+
+    pub fn is_user_theme(&self, name: &str) -> bool {
+        self.user_themes.iter().any(|t| t.name == name)
+    }
+
+    /// Backdrop of a theme: the saved one or the theme's own
+    pub fn backdrop_for(&self, theme_name: &str) -> Backdrop {
+        self.config
+            .backdrops
+            .get(theme_name)
+            .copied()
+            .unwrap_or_else(|| {
+                self.find(theme_name)
+                    .unwrap_or_else(Theme::dark)
+                    .default_backdrop()
+            })
+    }
+
+    /// Backdrop of the current theme
+    pub fn backdrop(&self) -> Backdrop {
+        self.backdrop_for(&self.config.theme_name)
+    }
+
+    /// Set the theme along with its color vision and contrast options and
+    /// its backdrop
+    pub fn set_variant(
+        &mut self,
+        theme_name: &str,
+        color_vision: ColorVision,
+        high_contrast: bool,
+        backdrop: Backdrop,
+    ) -> Result<()> {
+        let mut config = self.config.clone();
+        config.color_vision = color_vision;
+        config.high_contrast = high_contrast;
+        config.theme_name = theme_name.to_string();
+        config.backdrops.insert(theme_name.to_string(), backdrop);
+        let theme = Self::build_theme(&config, &self.user_themes, theme_name)
             .ok_or_else(|| anyhow::anyhow!("Theme '{}' not found", theme_name))?;
 
-        self.config.theme_name = theme_name.to_string();
+        self.config = config;
         self.current_theme = theme;
         self.save()?;
 
@@ -124,11 +326,136 @@ impl ThemeConfigManager {
     }
 
     /// Get all available theme names
-    pub fn available_themes() -> Vec<String> {
-        Theme::all_presets()
-            .into_iter()
-            .map(|t| t.name)
-            .collect()
+    pub fn available_themes(&self) -> Vec<String> {
+        self.all_themes().into_iter().map(|t| t.name).collect()
+    }
+
+    /// Check a custom theme name: trimmed, not empty, and not taken
+    /// (case-insensitively) by a preset or by a custom theme other than
+    /// `replacing`
+    fn check_theme_name(&self, name: &str, replacing: Option<&str>) -> Result<String> {
+        let name = name.trim();
+        if name.is_empty() {
+            bail!("Theme name can't be empty");
+        }
+        if name.chars().count() > MAX_THEME_NAME_LEN {
+            bail!(
+                "Theme name can't be longer than {} characters",
+                MAX_THEME_NAME_LEN
+            );
+        }
+        if Theme::all_presets()
+            .iter()
+            .any(|t| t.name.eq_ignore_ascii_case(name))
+        {
+            bail!("{} is a built-in theme", name);
+        }
+        let taken = self
+            .user_themes
+            .iter()
+            .any(|t| t.name.eq_ignore_ascii_case(name) && Some(t.name.as_str()) != replacing);
+        if taken {
+            bail!("There's already a theme called {}", name);
+        }
+        Ok(name.to_string())
+    }
+
+    /// Save a custom theme from the editor, replacing the custom theme it
+    /// was edited from if any. The current theme is rebuilt if it was the
+    /// one edited. Returns the name as stored.
+    pub fn save_user_theme(&mut self, theme: &Theme, replacing: Option<&str>) -> Result<String> {
+        let replacing = replacing.filter(|name| self.is_user_theme(name));
+        let name = self.check_theme_name(&theme.name, replacing)?;
+        let mut theme = theme.clone().with_status_toasts();
+        theme.name = name.clone();
+
+        let dir = Self::themes_dir(&self.config_path);
+        if let Some(old) = replacing {
+            let old_path = dir.join(Self::theme_file_name(old));
+            if old != name && old_path.exists() {
+                fs::remove_file(&old_path).context("Failed to remove the old theme file")?;
+            }
+        }
+        Self::write_theme_file(&dir.join(Self::theme_file_name(&name)), &theme)?;
+
+        self.user_themes
+            .retain(|t| Some(t.name.as_str()) != replacing && t.name != name);
+        self.user_themes.push(theme);
+        self.user_themes.sort_by_key(|t| t.name.to_lowercase());
+
+        // Follow a rename of the current theme and its backdrop
+        if let Some(old) = replacing.filter(|old| *old != name) {
+            if let Some(backdrop) = self.config.backdrops.remove(old) {
+                self.config.backdrops.insert(name.clone(), backdrop);
+            }
+            if self.config.theme_name == old {
+                self.config.theme_name = name.clone();
+            }
+            self.save()?;
+        }
+        if self.config.theme_name == name {
+            if let Some(theme) = Self::build_theme(&self.config, &self.user_themes, &name) {
+                self.current_theme = theme;
+            }
+        }
+
+        println!("[ThemeConfig] Saved custom theme: {}", name);
+        Ok(name)
+    }
+
+    /// Write a theme to the shared directory for copying to another
+    /// device. Returns the file written.
+    pub fn export_theme(&self, name: &str) -> Result<PathBuf> {
+        let theme = self
+            .find(name)
+            .with_context(|| format!("Theme '{}' not found", name))?;
+        let path = self.shared_dir().join(Self::theme_file_name(name));
+        Self::write_theme_file(&path, &theme)?;
+        println!("[ThemeConfig] Exported {} to {:?}", name, path);
+        Ok(path)
+    }
+
+    /// Add the theme files in the shared directory as custom themes.
+    /// Names already taken get a number appended. Returns the names
+    /// imported.
+    pub fn import_themes(&mut self) -> Result<Vec<String>> {
+        let dir = self.shared_dir();
+        let entries =
+            fs::read_dir(&dir).with_context(|| format!("No theme files in {}", dir.display()))?;
+        let mut paths: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .collect();
+        paths.sort();
+
+        let mut imported = Vec::new();
+        for path in paths {
+            let mut theme = match Self::read_theme_file(&path) {
+                Ok(theme) => theme,
+                Err(e) => {
+                    eprintln!("[ThemeConfig] Skipping {:?}: {:#}", path, e);
+                    continue;
+                }
+            };
+            // Re-importing a theme that's already here unchanged is a no-op
+            let same = self.find(&theme.name).is_some_and(|existing| {
+                ThemeFile::from_theme(&existing).colors == ThemeFile::from_theme(&theme).colors
+            });
+            if same {
+                continue;
+            }
+            let base: String = theme.name.chars().take(MAX_THEME_NAME_LEN - 3).collect();
+            let mut suffix = 2;
+            while self.check_theme_name(&theme.name, None).is_err() && suffix < 100 {
+                theme.name = format!("{} {}", base.trim(), suffix);
+                suffix += 1;
+            }
+            match self.save_user_theme(&theme, None) {
+                Ok(name) => imported.push(name),
+                Err(e) => eprintln!("[ThemeConfig] Skipping {:?}: {:#}", path, e),
+            }
+        }
+        Ok(imported)
     }
 
     /// Save current configuration to disk
@@ -136,4 +463,3 @@ impl ThemeConfigManager {
         Self::save_config(&self.config_path, &self.config)
     }
 }
-
@@ -0,0 +1,270 @@
+//! Theme editor: custom themes from per-color HSV pickers
+//!
+//! Starts from the theme selected on the theme screen. Up/Down pick a color,
+//! LB/RB the channel (hue, saturation, value, alpha) and Left/Right nudge it
+//! with LT/RT for big steps; the screen is drawn in the theme being edited.
+//! Saving asks for a name on the on-screen keyboard and stores the theme
+//! with `ThemeConfigManager::save_user_theme`.
+
+use crate::input::ControllerInput;
+use crate::login::OnScreenKeyboard;
+use crate::theme_config::MAX_THEME_NAME_LEN;
+use crate::themes::{color_to_hsv, hsv_to_color, Theme, EDITABLE_COLORS};
+
+/// Color rows shown at once
+pub const EDITOR_VISIBLE_ROWS: usize = 8;
+
+/// Hue step in degrees; the other channels step by 5%
+const HUE_STEP: f32 = 5.0;
+const CHANNEL_STEP: f32 = 0.05;
+/// LT/RT move this many steps at once
+const COARSE_STEPS: f32 = 6.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Channel {
+    Hue,
+    Saturation,
+    Value,
+    Alpha,
+}
+
+impl Channel {
+    pub const ALL: [Channel; 4] = [
+        Channel::Hue,
+        Channel::Saturation,
+        Channel::Value,
+        Channel::Alpha,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Channel::Hue => "Hue",
+            Channel::Saturation => "Saturation",
+            Channel::Value => "Brightness",
+            Channel::Alpha => "Opacity",
+        }
+    }
+}
+
+/// What the state should do after an editor input
+#[derive(Debug, Clone, PartialEq)]
+pub enum ThemeEditorAction {
+    None,
+    Close,
+    /// Store the theme under the typed name
+    Save,
+}
+
+/// State of the theme editor screen
+pub struct ThemeEditor {
+    /// The theme as edited so far
+    pub theme: Theme,
+    /// The theme the editor started from, for resetting colors
+    original: Theme,
+    /// The custom theme being edited, replaced on save; None for presets
+    pub replacing: Option<String>,
+    /// Index into `EDITABLE_COLORS`
+    pub selected: usize,
+    pub scroll_offset: usize,
+    pub channel: usize,
+    /// Hue, saturation, value and alpha of the selected color. Kept apart
+    /// from the color so the hue survives dragging saturation or value to
+    /// zero.
+    pub hsva: [f32; 4],
+    /// Name to save under
+    pub name: String,
+    /// Open while naming the theme before saving
+    pub keyboard: Option<OnScreenKeyboard>,
+}
+
+impl ThemeEditor {
+    /// Edit `theme`. `is_user_theme` says whether saving replaces it or
+    /// makes a new custom theme from a preset.
+    pub fn open(theme: Theme, is_user_theme: bool) -> Self {
+        let (name, replacing) = if is_user_theme {
+            (theme.name.clone(), Some(theme.name.clone()))
+        } else {
+            let base: String = theme.name.chars().take(MAX_THEME_NAME_LEN - 7).collect();
+            (format!("{} Custom", base), None)
+        };
+        let mut editor = Self {
+            original: theme.clone(),
+            theme,
+            replacing,
+            selected: 0,
+            scroll_offset: 0,
+            channel: 0,
+            hsva: [0.0; 4],
+            name,
+            keyboard: None,
+        };
+        editor.load_selected();
+        editor
+    }
+
+    pub fn selected_key(&self) -> &'static str {
+        EDITABLE_COLORS[self.selected].0
+    }
+
+    pub fn selected_channel(&self) -> Channel {
+        Channel::ALL[self.channel]
+    }
+
+    /// The theme to draw with, toasts following the edited status colors
+    pub fn preview(&self) -> Theme {
+        self.theme.clone().with_status_toasts()
+    }
+
+    /// The edited theme under the typed name, ready to save
+    pub fn named_theme(&self) -> Theme {
+        let mut theme = self.preview();
+        theme.name = self.name.trim().to_string();
+        theme
+    }
+
+    fn load_selected(&mut self) {
+        let color = self
+            .theme
+            .color(self.selected_key())
+            .unwrap_or(self.theme.text);
+        let (hue, saturation, value) = color_to_hsv(color);
+        self.hsva = [hue, saturation, value, color.a];
+    }
+
+    fn store_selected(&mut self) {
+        let [hue, saturation, value, alpha] = self.hsva;
+        let key = self.selected_key();
+        if let Some(color) = self.theme.color_mut(key) {
+            *color = hsv_to_color(hue, saturation, value, alpha);
+        }
+    }
+
+    /// Move the selected channel by `steps` steps
+    fn adjust(&mut self, steps: f32) {
+        let value = &mut self.hsva[self.channel];
+        *value = match Channel::ALL[self.channel] {
+            Channel::Hue => (*value + steps * HUE_STEP).rem_euclid(360.0),
+            _ => (*value + steps * CHANNEL_STEP).clamp(0.0, 1.0),
+        };
+        self.store_selected();
+    }
+
+    fn select(&mut self, index: usize) {
+        self.selected = index;
+        if self.selected < self.scroll_offset {
+            self.scroll_offset = self.selected;
+        } else if self.selected >= self.scroll_offset + EDITOR_VISIBLE_ROWS {
+            self.scroll_offset = self.selected + 1 - EDITOR_VISIBLE_ROWS;
+        }
+        self.load_selected();
+    }
+
+    /// Apply one input
+    pub fn handle_input(&mut self, input: ControllerInput) -> ThemeEditorAction {
+        if let Some(keyboard) = &mut self.keyboard {
+            if keyboard.handle_input(input, &mut self.name) {
+                self.keyboard = None;
+                // Done saves; B and RB go back to editing
+                if input == ControllerInput::Select {
+                    return ThemeEditorAction::Save;
+                }
+            }
+            return ThemeEditorAction::None;
+        }
+
+        let count = EDITABLE_COLORS.len();
+        match input {
+            ControllerInput::Up => self.select((self.selected + count - 1) % count),
+            ControllerInput::Down => self.select((self.selected + 1) % count),
+            ControllerInput::LB => {
+                self.channel = (self.channel + Channel::ALL.len() - 1) % Channel::ALL.len()
+            }
+            ControllerInput::RB => self.channel = (self.channel + 1) % Channel::ALL.len(),
+            ControllerInput::Left => self.adjust(-1.0),
+            ControllerInput::Right => self.adjust(1.0),
+            ControllerInput::LT => self.adjust(-COARSE_STEPS),
+            ControllerInput::RT => self.adjust(COARSE_STEPS),
+            ControllerInput::Secondary => {
+                let key = self.selected_key();
+                if let (Some(color), Some(original)) =
+                    (self.theme.color_mut(key), self.original.color(key))
+                {
+                    *color = original;
+                }
+                self.load_selected();
+            }
+            ControllerInput::Select => {
+                self.name.truncate(
+                    self.name
+                        .char_indices()
+                        .nth(MAX_THEME_NAME_LEN)
+                        .map_or(self.name.len(), |(i, _)| i),
+                );
+                self.keyboard = Some(OnScreenKeyboard::new());
+            }
+            ControllerInput::Back => return ThemeEditorAction::Close,
+            _ => {}
+        }
+        ThemeEditorAction::None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::themes::color_to_hex;
+
+    #[test]
+    fn test_editor_adjusts_and_resets_colors() {
+        let mut editor = ThemeEditor::open(Theme::dark(), false);
+        assert_eq!(editor.name, "Dark Custom");
+        assert_eq!(editor.replacing, None);
+
+        // Accent, value channel, all the way down and back up by one step
+        let accent = EDITABLE_COLORS
+            .iter()
+            .position(|(key, _)| *key == "accent")
+            .unwrap();
+        while editor.selected != accent {
+            editor.handle_input(ControllerInput::Down);
+        }
+        editor.handle_input(ControllerInput::RB);
+        editor.handle_input(ControllerInput::RB);
+        assert_eq!(editor.selected_channel(), Channel::Value);
+        let hue = editor.hsva[0];
+        for _ in 0..5 {
+            editor.handle_input(ControllerInput::LT);
+        }
+        assert_eq!(color_to_hex(editor.theme.accent), "#000000ff");
+        editor.handle_input(ControllerInput::Right);
+        assert!((editor.hsva[2] - CHANNEL_STEP).abs() < 1e-4);
+        assert_eq!(editor.hsva[0], hue, "hue kept through black");
+
+        // Other colors are untouched, and X puts the accent back
+        assert_eq!(editor.theme.cursor, Theme::dark().cursor);
+        editor.handle_input(ControllerInput::Secondary);
+        assert_eq!(editor.theme.accent, Theme::dark().accent);
+
+        // A names the theme; Done saves it
+        assert_eq!(
+            editor.handle_input(ControllerInput::Select),
+            ThemeEditorAction::None
+        );
+        assert!(editor.keyboard.is_some());
+        editor.handle_input(ControllerInput::Back);
+        assert!(editor.keyboard.is_none());
+        editor.handle_input(ControllerInput::Select);
+        let keyboard = editor.keyboard.as_mut().unwrap();
+        keyboard.row = crate::login::KEYBOARD_ROWS.len();
+        keyboard.col = crate::login::KEYBOARD_COLUMNS - 1;
+        assert_eq!(
+            editor.handle_input(ControllerInput::Select),
+            ThemeEditorAction::Save
+        );
+        assert_eq!(editor.named_theme().name, "Dark Custom");
+        assert_eq!(
+            editor.handle_input(ControllerInput::Back),
+            ThemeEditorAction::Close
+        );
+    }
+}
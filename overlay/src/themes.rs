@@ -1,12 +1,16 @@
+use crate::backdrop::Backdrop;
+use crate::ipc::ToastStyle;
+use anyhow::{bail, Result};
 use macroquad::prelude::*;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 /// Complete theme definition for overlay UI
 #[derive(Debug, Clone)]
 pub struct Theme {
     pub name: String,
     pub background: Color,
-    pub background_overlay: Color,  // Semi-transparent overlay
+    pub background_overlay: Color, // Semi-transparent overlay
     pub panel_background: Color,
     pub panel_border: Color,
     pub text: Color,
@@ -19,6 +23,88 @@ pub struct Theme {
     pub warning: Color,
     pub error: Color,
     pub info: Color,
+    /// Achievement rarity colors, one per `stats::RARITY_BUCKETS` entry
+    pub rarity: [Color; 5],
+    /// Toast backgrounds: info, success, warning, error
+    pub toast: [Color; 4],
+    /// Multiplier for font sizes (larger in high contrast mode)
+    pub font_scale: f32,
+}
+
+/// Color vision deficiency the semantic colors are adjusted for
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ColorVision {
+    #[default]
+    Normal,
+    Deuteranopia,
+    Protanopia,
+    Tritanopia,
+}
+
+impl ColorVision {
+    pub const ALL: [ColorVision; 4] = [
+        ColorVision::Normal,
+        ColorVision::Deuteranopia,
+        ColorVision::Protanopia,
+        ColorVision::Tritanopia,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ColorVision::Normal => "Standard colors",
+            ColorVision::Deuteranopia => "Deuteranopia (green-weak)",
+            ColorVision::Protanopia => "Protanopia (red-weak)",
+            ColorVision::Tritanopia => "Tritanopia (blue-weak)",
+        }
+    }
+
+    /// The next (or previous) option, wrapping around
+    pub fn cycle(self, forward: bool) -> Self {
+        let index = Self::ALL.iter().position(|&cv| cv == self).unwrap_or(0);
+        let len = Self::ALL.len();
+        Self::ALL[if forward {
+            (index + 1) % len
+        } else {
+            (index + len - 1) % len
+        }]
+    }
+
+    /// Success, warning, error, info and rarity colors that stay apart for
+    /// this kind of color vision. Based on the Okabe-Ito palette.
+    fn palette(self) -> Option<([Color; 4], [Color; 5])> {
+        let yellow = Color::new(0.94, 0.89, 0.26, 1.0);
+        let orange = Color::new(0.9, 0.62, 0.0, 1.0);
+        let vermillion = Color::new(0.84, 0.37, 0.0, 1.0);
+        let purple = Color::new(0.8, 0.47, 0.65, 1.0);
+        let blue = Color::new(0.0, 0.45, 0.7, 1.0);
+        let sky = Color::new(0.34, 0.71, 0.91, 1.0);
+        let common = Color::new(0.6, 0.6, 0.6, 1.0);
+
+        match self {
+            ColorVision::Normal => None,
+            // Red and green merge: success in blue, errors in vermillion
+            ColorVision::Deuteranopia => Some((
+                [blue, yellow, vermillion, sky],
+                [yellow, vermillion, purple, sky, common],
+            )),
+            // Reds look dark, so errors use the brighter orange
+            ColorVision::Protanopia => Some((
+                [blue, yellow, orange, sky],
+                [yellow, orange, purple, sky, common],
+            )),
+            // Blue and yellow merge: lean on red against teal
+            ColorVision::Tritanopia => {
+                let red = Color::new(0.86, 0.15, 0.3, 1.0);
+                let pink = Color::new(0.95, 0.55, 0.7, 1.0);
+                let teal = Color::new(0.0, 0.6, 0.55, 1.0);
+                Some((
+                    [teal, pink, red, Color::new(0.3, 0.75, 0.85, 1.0)],
+                    [red, pink, teal, Color::new(0.6, 0.9, 0.85, 1.0), common],
+                ))
+            }
+        }
+    }
 }
 
 impl Theme {
@@ -56,8 +142,140 @@ impl Theme {
             warning,
             error,
             info,
+            rarity: [
+                GOLD,                           // Ultra rare
+                Color::new(0.9, 0.4, 0.9, 1.0), // Very rare
+                Color::new(0.3, 0.6, 1.0, 1.0), // Rare
+                GREEN,                          // Uncommon
+                GRAY,                           // Common
+            ],
+            toast: [
+                Color::new(0.2, 0.4, 0.8, 1.0),
+                Color::new(0.2, 0.8, 0.4, 1.0),
+                Color::new(0.9, 0.7, 0.2, 1.0),
+                Color::new(0.9, 0.2, 0.2, 1.0),
+            ],
+            font_scale: 1.0,
+        }
+    }
+
+    /// A color by its `EDITABLE_COLORS` key
+    pub fn color(&self, key: &str) -> Option<Color> {
+        let mut theme = self.clone();
+        theme.color_mut(key).map(|c| *c)
+    }
+
+    pub fn color_mut(&mut self, key: &str) -> Option<&mut Color> {
+        Some(match key {
+            "background" => &mut self.background,
+            "background_overlay" => &mut self.background_overlay,
+            "panel_background" => &mut self.panel_background,
+            "panel_border" => &mut self.panel_border,
+            "text" => &mut self.text,
+            "text_secondary" => &mut self.text_secondary,
+            "text_disabled" => &mut self.text_disabled,
+            "cursor" => &mut self.cursor,
+            "accent" => &mut self.accent,
+            "accent_secondary" => &mut self.accent_secondary,
+            "success" => &mut self.success,
+            "warning" => &mut self.warning,
+            "error" => &mut self.error,
+            "info" => &mut self.info,
+            _ => return None,
+        })
+    }
+
+    /// Toast backgrounds shaded from the status colors, as user themes
+    /// don't set them separately
+    pub fn with_status_toasts(mut self) -> Self {
+        let shade = |c: Color| Color::new(c.r * 0.85, c.g * 0.85, c.b * 0.85, 1.0);
+        self.toast = [
+            shade(self.info),
+            shade(self.success),
+            shade(self.warning),
+            shade(self.error),
+        ];
+        self
+    }
+
+    /// The theme with its semantic colors remapped for `color_vision`
+    pub fn with_color_vision(mut self, color_vision: ColorVision) -> Self {
+        if let Some(([success, warning, error, info], rarity)) = color_vision.palette() {
+            self.success = success;
+            self.warning = warning;
+            self.error = error;
+            self.info = info;
+            self.rarity = rarity;
+            // Slightly darker than the text colors so white text stays legible
+            let shade = |c: Color| Color::new(c.r * 0.85, c.g * 0.85, c.b * 0.85, 1.0);
+            self.toast = [shade(info), shade(success), shade(warning), shade(error)];
+        }
+        self
+    }
+
+    /// Opaque black panels, white text and borders, and larger fonts
+    pub fn with_high_contrast(mut self) -> Self {
+        self.background_overlay = Color::new(0.0, 0.0, 0.0, 0.9);
+        self.panel_background = BLACK;
+        self.panel_border = WHITE;
+        self.text = WHITE;
+        self.text_secondary = Color::new(0.9, 0.9, 0.9, 1.0);
+        self.text_disabled = Color::new(0.65, 0.65, 0.65, 1.0);
+        self.cursor = Color::new(1.0, 0.85, 0.0, 1.0);
+        self.font_scale = 1.25;
+        self
+    }
+
+    /// The theme's own backdrop, used until the user changes it
+    pub fn default_backdrop(&self) -> Backdrop {
+        Backdrop {
+            opacity: self.background_overlay.a,
+            panel_opacity: self.panel_background.a,
+            blur: false,
+        }
+    }
+
+    /// The theme with the dimming and panel alpha of `backdrop`
+    pub fn with_backdrop(mut self, backdrop: &Backdrop) -> Self {
+        self.background_overlay.a = backdrop.opacity;
+        self.panel_background.a = backdrop.panel_opacity;
+        self
+    }
+
+    /// A preset with the accessibility options applied
+    pub fn variant(name: &str, color_vision: ColorVision, high_contrast: bool) -> Option<Self> {
+        Some(Self::by_name(name)?.with_options(color_vision, high_contrast))
+    }
+
+    /// The theme with the accessibility options applied
+    pub fn with_options(self, color_vision: ColorVision, high_contrast: bool) -> Self {
+        let theme = self.with_color_vision(color_vision);
+        if high_contrast {
+            theme.with_high_contrast()
+        } else {
+            theme
         }
     }
+
+    /// Color for an achievement unlocked by `rarity_percent` of players
+    pub fn rarity_color(&self, rarity_percent: f32) -> Color {
+        let bucket = crate::stats::RARITY_BUCKETS
+            .iter()
+            .position(|&(max, _)| rarity_percent < max)
+            .unwrap_or(self.rarity.len() - 1);
+        self.rarity[bucket]
+    }
+
+    /// Toast background for `style`, faded to `alpha`
+    pub fn toast_color(&self, style: ToastStyle, alpha: f32) -> Color {
+        let c = match style {
+            ToastStyle::Info => self.toast[0],
+            ToastStyle::Success => self.toast[1],
+            ToastStyle::Warning => self.toast[2],
+            ToastStyle::Error => self.toast[3],
+        };
+        Color::new(c.r, c.g, c.b, 0.95 * alpha)
+    }
 }
 
 /// Preset themes
@@ -67,19 +285,19 @@ impl Theme {
         Self::new(
             "Dark".to_string(),
             Color::new(0.05, 0.05, 0.07, 1.0),
-            Color::new(0.0, 0.0, 0.0, 0.65),   // Background dim
+            Color::new(0.0, 0.0, 0.0, 0.65),    // Background dim
             Color::new(0.12, 0.12, 0.14, 0.92), // Glassy panel
-            Color::new(0.16, 0.16, 0.2, 1.0),  // Soft border
-            Color::new(0.93, 0.95, 0.98, 1.0), // Primary text
-            Color::new(0.72, 0.76, 0.82, 1.0), // Secondary text
-            Color::new(0.45, 0.48, 0.52, 1.0), // Disabled text
-            Color::new(0.25, 0.82, 0.69, 1.0), // Cursor/primary accent
-            Color::new(0.23, 0.7, 0.94, 1.0),  // Accent
-            Color::new(0.28, 0.82, 0.75, 1.0), // Accent secondary
-            Color::new(0.16, 0.75, 0.38, 1.0), // Success
-            Color::new(0.98, 0.75, 0.22, 1.0), // Warning
-            Color::new(0.94, 0.31, 0.31, 1.0), // Error
-            Color::new(0.34, 0.55, 0.94, 1.0), // Info
+            Color::new(0.16, 0.16, 0.2, 1.0),   // Soft border
+            Color::new(0.93, 0.95, 0.98, 1.0),  // Primary text
+            Color::new(0.72, 0.76, 0.82, 1.0),  // Secondary text
+            Color::new(0.45, 0.48, 0.52, 1.0),  // Disabled text
+            Color::new(0.25, 0.82, 0.69, 1.0),  // Cursor/primary accent
+            Color::new(0.23, 0.7, 0.94, 1.0),   // Accent
+            Color::new(0.28, 0.82, 0.75, 1.0),  // Accent secondary
+            Color::new(0.16, 0.75, 0.38, 1.0),  // Success
+            Color::new(0.98, 0.75, 0.22, 1.0),  // Warning
+            Color::new(0.94, 0.31, 0.31, 1.0),  // Error
+            Color::new(0.34, 0.55, 0.94, 1.0),  // Info
         )
     }
 
@@ -94,13 +312,13 @@ impl Theme {
             Color::new(0.1, 0.1, 0.1, 1.0),
             Color::new(0.3, 0.3, 0.3, 1.0),
             Color::new(0.6, 0.6, 0.6, 1.0),
-            Color::new(0.0, 0.4, 0.8, 1.0),  // Blue cursor
-            Color::new(0.0, 0.5, 1.0, 1.0),  // Blue accent
-            Color::new(0.2, 0.6, 1.0, 1.0),  // Light blue
-            Color::new(0.0, 0.7, 0.0, 1.0),  // Green
-            Color::new(1.0, 0.6, 0.0, 1.0),  // Orange
-            Color::new(0.9, 0.2, 0.2, 1.0),  // Red
-            Color::new(0.0, 0.5, 0.9, 1.0),  // Blue info
+            Color::new(0.0, 0.4, 0.8, 1.0), // Blue cursor
+            Color::new(0.0, 0.5, 1.0, 1.0), // Blue accent
+            Color::new(0.2, 0.6, 1.0, 1.0), // Light blue
+            Color::new(0.0, 0.7, 0.0, 1.0), // Green
+            Color::new(1.0, 0.6, 0.0, 1.0), // Orange
+            Color::new(0.9, 0.2, 0.2, 1.0), // Red
+            Color::new(0.0, 0.5, 0.9, 1.0), // Blue info
         )
     }
 
@@ -115,13 +333,13 @@ impl Theme {
             Color::new(0.0, 1.0, 0.0, 1.0),
             Color::new(0.0, 0.8, 0.0, 1.0),
             Color::new(0.0, 0.5, 0.0, 1.0),
-            Color::new(0.0, 1.0, 0.5, 1.0),  // Cyan-green cursor
-            Color::new(0.0, 1.0, 0.0, 1.0),  // Green accent
-            Color::new(0.0, 0.8, 0.5, 1.0),  // Light green
-            Color::new(0.0, 1.0, 0.0, 1.0),  // Green
-            Color::new(1.0, 1.0, 0.0, 1.0),  // Yellow warning
-            Color::new(1.0, 0.0, 0.0, 1.0),  // Red
-            Color::new(0.0, 0.8, 1.0, 1.0),  // Cyan info
+            Color::new(0.0, 1.0, 0.5, 1.0), // Cyan-green cursor
+            Color::new(0.0, 1.0, 0.0, 1.0), // Green accent
+            Color::new(0.0, 0.8, 0.5, 1.0), // Light green
+            Color::new(0.0, 1.0, 0.0, 1.0), // Green
+            Color::new(1.0, 1.0, 0.0, 1.0), // Yellow warning
+            Color::new(1.0, 0.0, 0.0, 1.0), // Red
+            Color::new(0.0, 0.8, 1.0, 1.0), // Cyan info
         )
     }
 
@@ -132,17 +350,17 @@ impl Theme {
             Color::new(0.05, 0.05, 0.15, 1.0),
             Color::new(0.0, 0.0, 0.0, 0.8),
             Color::new(0.1, 0.1, 0.2, 0.98),
-            Color::new(0.0, 0.4, 1.0, 1.0),  // PS Blue
+            Color::new(0.0, 0.4, 1.0, 1.0), // PS Blue
             WHITE,
             LIGHTGRAY,
             GRAY,
-            Color::new(0.0, 0.6, 1.0, 1.0),  // PS Blue cursor
-            Color::new(0.0, 0.4, 1.0, 1.0),  // PS Blue accent
-            Color::new(0.3, 0.5, 1.0, 1.0),  // Light blue
-            Color::new(0.0, 0.8, 0.4, 1.0),  // Green
-            Color::new(1.0, 0.7, 0.0, 1.0),  // Orange
-            Color::new(1.0, 0.2, 0.2, 1.0),  // Red
-            Color::new(0.0, 0.5, 1.0, 1.0),  // Blue info
+            Color::new(0.0, 0.6, 1.0, 1.0), // PS Blue cursor
+            Color::new(0.0, 0.4, 1.0, 1.0), // PS Blue accent
+            Color::new(0.3, 0.5, 1.0, 1.0), // Light blue
+            Color::new(0.0, 0.8, 0.4, 1.0), // Green
+            Color::new(1.0, 0.7, 0.0, 1.0), // Orange
+            Color::new(1.0, 0.2, 0.2, 1.0), // Red
+            Color::new(0.0, 0.5, 1.0, 1.0), // Blue info
         )
     }
 
@@ -153,17 +371,17 @@ impl Theme {
             Color::new(0.1, 0.1, 0.1, 1.0),
             Color::new(0.0, 0.0, 0.0, 0.75),
             Color::new(0.15, 0.15, 0.15, 0.98),
-            Color::new(0.2, 0.8, 0.2, 1.0),  // Xbox Green
+            Color::new(0.2, 0.8, 0.2, 1.0), // Xbox Green
             WHITE,
             LIGHTGRAY,
             GRAY,
-            Color::new(0.2, 0.8, 0.2, 1.0),  // Xbox Green cursor
-            Color::new(0.2, 0.8, 0.2, 1.0),  // Xbox Green accent
-            Color::new(0.4, 0.9, 0.4, 1.0),  // Light green
-            Color::new(0.2, 0.8, 0.2, 1.0),  // Green
-            Color::new(1.0, 0.7, 0.0, 1.0),  // Orange
-            Color::new(1.0, 0.2, 0.2, 1.0),  // Red
-            Color::new(0.2, 0.6, 0.9, 1.0),  // Blue info
+            Color::new(0.2, 0.8, 0.2, 1.0), // Xbox Green cursor
+            Color::new(0.2, 0.8, 0.2, 1.0), // Xbox Green accent
+            Color::new(0.4, 0.9, 0.4, 1.0), // Light green
+            Color::new(0.2, 0.8, 0.2, 1.0), // Green
+            Color::new(1.0, 0.7, 0.0, 1.0), // Orange
+            Color::new(1.0, 0.2, 0.2, 1.0), // Red
+            Color::new(0.2, 0.6, 0.9, 1.0), // Blue info
         )
     }
 
@@ -186,9 +404,135 @@ impl Theme {
     }
 }
 
-// Note: Color serialization is handled via serde's skip_serializing for Theme
-// Themes are defined as code presets, not serialized from config
-// Only the theme name is stored in config
+/// Colors the theme editor changes, as (key, label), in editor order. The
+/// keys name the colors in theme files.
+pub const EDITABLE_COLORS: [(&str, &str); 14] = [
+    ("background", "Background"),
+    ("background_overlay", "Backdrop dimming"),
+    ("panel_background", "Panel"),
+    ("panel_border", "Panel border"),
+    ("text", "Text"),
+    ("text_secondary", "Secondary text"),
+    ("text_disabled", "Disabled text"),
+    ("cursor", "Cursor"),
+    ("accent", "Accent"),
+    ("accent_secondary", "Second accent"),
+    ("success", "Success"),
+    ("warning", "Warning"),
+    ("error", "Error"),
+    ("info", "Info"),
+];
+
+/// Hue in degrees, saturation and value of a color
+pub fn color_to_hsv(c: Color) -> (f32, f32, f32) {
+    let max = c.r.max(c.g).max(c.b);
+    let min = c.r.min(c.g).min(c.b);
+    let delta = max - min;
+    let hue = if delta <= f32::EPSILON {
+        0.0
+    } else if max == c.r {
+        60.0 * ((c.g - c.b) / delta).rem_euclid(6.0)
+    } else if max == c.g {
+        60.0 * ((c.b - c.r) / delta + 2.0)
+    } else {
+        60.0 * ((c.r - c.g) / delta + 4.0)
+    };
+    let saturation = if max <= f32::EPSILON {
+        0.0
+    } else {
+        delta / max
+    };
+    (hue, saturation, max)
+}
+
+pub fn hsv_to_color(hue: f32, saturation: f32, value: f32, alpha: f32) -> Color {
+    let chroma = value * saturation;
+    let sector = hue.rem_euclid(360.0) / 60.0;
+    let x = chroma * (1.0 - (sector % 2.0 - 1.0).abs());
+    let (r, g, b) = match sector as u32 {
+        0 => (chroma, x, 0.0),
+        1 => (x, chroma, 0.0),
+        2 => (0.0, chroma, x),
+        3 => (0.0, x, chroma),
+        4 => (x, 0.0, chroma),
+        _ => (chroma, 0.0, x),
+    };
+    let m = value - chroma;
+    Color::new(r + m, g + m, b + m, alpha)
+}
+
+/// `#rrggbbaa`
+pub fn color_to_hex(c: Color) -> String {
+    let byte = |v: f32| (v.clamp(0.0, 1.0) * 255.0).round() as u8;
+    format!(
+        "#{:02x}{:02x}{:02x}{:02x}",
+        byte(c.r),
+        byte(c.g),
+        byte(c.b),
+        byte(c.a)
+    )
+}
+
+/// `#rrggbb` or `#rrggbbaa`
+pub fn parse_hex(hex: &str) -> Option<Color> {
+    let digits = hex.trim().strip_prefix('#')?;
+    if !matches!(digits.len(), 6 | 8) || !digits.is_ascii() {
+        return None;
+    }
+    let channel = |i: usize| u8::from_str_radix(&digits[i..i + 2], 16).ok();
+    let alpha = if digits.len() == 8 { channel(6)? } else { 255 };
+    Some(Color::from_rgba(
+        channel(0)?,
+        channel(2)?,
+        channel(4)?,
+        alpha,
+    ))
+}
+
+/// A user theme as saved and shared: its name and hex colors by
+/// `EDITABLE_COLORS` key. Colors it leaves out come from Dark.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ThemeFile {
+    pub name: String,
+    #[serde(default = "theme_file_version")]
+    pub version: u32,
+    pub colors: BTreeMap<String, String>,
+}
+
+fn theme_file_version() -> u32 {
+    1
+}
+
+impl ThemeFile {
+    pub fn from_theme(theme: &Theme) -> Self {
+        let colors = EDITABLE_COLORS
+            .iter()
+            .filter_map(|(key, _)| Some((key.to_string(), color_to_hex(theme.color(key)?))))
+            .collect();
+        Self {
+            name: theme.name.clone(),
+            version: theme_file_version(),
+            colors,
+        }
+    }
+
+    pub fn to_theme(&self) -> Result<Theme> {
+        let name = self.name.trim();
+        if name.is_empty() {
+            bail!("Theme has no name");
+        }
+        let mut theme = Theme::dark();
+        theme.name = name.to_string();
+        for (key, hex) in &self.colors {
+            let Some(color) = theme.color_mut(key) else {
+                bail!("Unknown color '{}'", key);
+            };
+            *color = parse_hex(hex)
+                .ok_or_else(|| anyhow::anyhow!("Invalid color for '{}': {}", key, hex))?;
+        }
+        Ok(theme.with_status_toasts())
+    }
+}
 
 #[cfg(test)]
 mod tests {
@@ -198,10 +542,10 @@ mod tests {
     fn test_theme_presets() {
         let dark = Theme::dark();
         assert_eq!(dark.name, "Dark");
-        
+
         let light = Theme::light();
         assert_eq!(light.name, "Light");
-        
+
         let all = Theme::all_presets();
         assert_eq!(all.len(), 5);
     }
@@ -213,4 +557,65 @@ mod tests {
         assert!(Theme::by_name("DARK").is_some());
         assert!(Theme::by_name("Invalid").is_none());
     }
+
+    #[test]
+    fn test_color_vision_variants() {
+        let dark = Theme::dark();
+        assert_eq!(dark.rarity_color(3.0), GOLD);
+        assert_eq!(dark.rarity_color(80.0), GRAY);
+
+        // Success and error must not both be green and red
+        let deut = Theme::variant("Dark", ColorVision::Deuteranopia, false).unwrap();
+        assert_ne!(deut.success, dark.success);
+        assert_ne!(deut.rarity_color(3.0), deut.rarity_color(7.0));
+        assert_eq!(deut.panel_background, dark.panel_background);
+
+        let hc = Theme::variant("Dark", ColorVision::Normal, true).unwrap();
+        assert!(hc.font_scale > 1.0);
+        assert_eq!(hc.success, dark.success);
+
+        assert_eq!(ColorVision::Normal.cycle(false), ColorVision::Tritanopia);
+        assert_eq!(ColorVision::Tritanopia.cycle(true), ColorVision::Normal);
+    }
+
+    #[test]
+    fn test_hsv_and_hex_round_trip() {
+        let orange = Color::from_rgba(250, 160, 20, 200);
+        let (h, s, v) = color_to_hsv(orange);
+        assert!((h - 36.5).abs() < 0.5, "hue {}", h);
+        let back = hsv_to_color(h, s, v, orange.a);
+        assert_eq!(color_to_hex(back), color_to_hex(orange));
+        assert_eq!(color_to_hex(orange), "#faa014c8");
+
+        assert_eq!(parse_hex("#faa014c8"), Some(orange));
+        assert_eq!(parse_hex("#faa014").map(|c| c.a), Some(1.0));
+        assert_eq!(parse_hex("faa014"), None);
+        assert_eq!(parse_hex("#faa01"), None);
+    }
+
+    #[test]
+    fn test_theme_file_round_trip() {
+        let mut theme = Theme::xbox();
+        theme.name = "Night Owl".to_string();
+        theme.accent = Color::from_rgba(255, 0, 128, 255);
+        let file = ThemeFile::from_theme(&theme);
+        assert_eq!(file.colors.len(), EDITABLE_COLORS.len());
+        assert_eq!(file.colors["accent"], "#ff0080ff");
+
+        let json = serde_json::to_string(&file).unwrap();
+        let loaded: ThemeFile = serde_json::from_str(&json).unwrap();
+        let restored = loaded.to_theme().unwrap();
+        assert_eq!(restored.name, "Night Owl");
+        assert_eq!(restored.accent, theme.accent);
+
+        // Missing colors come from Dark; unknown or malformed ones are refused
+        let partial: ThemeFile =
+            serde_json::from_str(r##"{"name":"Mint","colors":{"cursor":"#00ffaa"}}"##).unwrap();
+        let mint = partial.to_theme().unwrap();
+        assert_eq!(mint.panel_background, Theme::dark().panel_background);
+        assert_eq!(color_to_hex(mint.cursor), "#00ffaaff");
+        let bad: ThemeFile =
+            serde_json::from_str(r##"{"name":"Bad","colors":{"sparkle":"#ffffff"}}"##).unwrap();
+        assert!(bad.to_theme().is_err());
+    }
 }
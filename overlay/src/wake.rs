@@ -0,0 +1,134 @@
+//! Idle waiting for the main loop
+//!
+//! While nothing is on screen the main loop blocks in `poll(2)` on a wake
+//! pipe instead of spinning at a fixed frame rate. The IPC thread (see
+//! `ipc_thread`) wakes it as soon as a message is parsed, including hotkeys
+//! forwarded by kazeta-input-daemon; other background threads call [`wake`]
+//! when they have something for the main loop. The wait is bounded so gilrs/keyboard hotkeys, which have no
+//! pollable descriptor, are still picked up with a short delay.
+
+use anyhow::{Context, Result};
+use std::io::{Read, Write};
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::os::unix::net::UnixStream;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+/// Write end of the wake pipe, shared with background threads
+static WAKER: OnceLock<UnixStream> = OnceLock::new();
+
+/// Wake the main loop if it is blocked waiting for events. Safe to call from
+/// any thread, and a no-op before the waiter exists.
+pub fn wake() {
+    if let Some(stream) = WAKER.get() {
+        // A full pipe already means a wake-up is pending
+        let _ = (&*stream).write(&[1]);
+    }
+}
+
+pub struct IdleWaiter {
+    wake_rx: UnixStream,
+}
+
+impl IdleWaiter {
+    pub fn new() -> Result<Self> {
+        let (wake_tx, wake_rx) = UnixStream::pair().context("Failed to create wake pipe")?;
+        wake_tx
+            .set_nonblocking(true)
+            .context("Failed to set wake pipe non-blocking")?;
+        wake_rx
+            .set_nonblocking(true)
+            .context("Failed to set wake pipe non-blocking")?;
+
+        if WAKER.set(wake_tx).is_err() {
+            anyhow::bail!("Idle waiter already initialized");
+        }
+
+        Ok(Self { wake_rx })
+    }
+
+    /// Block until one of `fds` is readable, [`wake`] is called, or `timeout`
+    /// passes. Returns true if woken by an event rather than the timeout.
+    pub fn wait(&mut self, fds: &[RawFd], timeout: Duration) -> bool {
+        let fds: Vec<RawFd> = fds
+            .iter()
+            .copied()
+            .chain(std::iter::once(self.wake_rx.as_raw_fd()))
+            .collect();
+        let ready = wait_readable(&fds, timeout);
+
+        // Drain pending wake-ups so the next wait blocks again
+        let mut buf = [0u8; 64];
+        while matches!((&self.wake_rx).read(&mut buf), Ok(n) if n > 0) {}
+
+        ready
+    }
+}
+
+/// Block until one of `fds` is readable or `timeout` passes. Returns true if
+/// a descriptor became readable.
+pub fn wait_readable(fds: &[RawFd], timeout: Duration) -> bool {
+    let mut pollfds: Vec<libc::pollfd> = fds
+        .iter()
+        .map(|&fd| libc::pollfd {
+            fd,
+            events: libc::POLLIN,
+            revents: 0,
+        })
+        .collect();
+
+    let timeout_ms = timeout.as_millis().min(i32::MAX as u128) as i32;
+    // SAFETY: pollfds is a valid, initialized array for the duration of the call
+    let ready = unsafe {
+        libc::poll(
+            pollfds.as_mut_ptr(),
+            pollfds.len() as libc::nfds_t,
+            timeout_ms,
+        )
+    };
+
+    if ready < 0 {
+        let err = std::io::Error::last_os_error();
+        if err.kind() != std::io::ErrorKind::Interrupted {
+            eprintln!("[Wake] poll failed: {}", err);
+        }
+        return false;
+    }
+    ready > 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    #[test]
+    fn test_wait_returns_on_readable_fd_and_timeout() {
+        let (_wake_tx, wake_rx) = UnixStream::pair().unwrap();
+        wake_rx.set_nonblocking(true).unwrap();
+        let mut waiter = IdleWaiter { wake_rx };
+        let (mut tx, rx) = UnixStream::pair().unwrap();
+
+        let start = Instant::now();
+        assert!(!waiter.wait(&[rx.as_raw_fd()], Duration::from_millis(20)));
+        assert!(start.elapsed() >= Duration::from_millis(15));
+
+        tx.write_all(b"{}\n").unwrap();
+        let start = Instant::now();
+        assert!(waiter.wait(&[rx.as_raw_fd()], Duration::from_secs(5)));
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_wake_pipe_is_drained() {
+        let (tx, rx) = UnixStream::pair().unwrap();
+        tx.set_nonblocking(true).unwrap();
+        rx.set_nonblocking(true).unwrap();
+        let mut waiter = IdleWaiter { wake_rx: rx };
+
+        (&tx).write_all(&[1, 1, 1]).unwrap();
+        assert!(waiter.wait(&[], Duration::from_secs(5)));
+        // Pending wake-ups were consumed, so this one times out
+        assert!(!waiter.wait(&[], Duration::from_millis(10)));
+    }
+}
@@ -0,0 +1,125 @@
+//! Notices when the game's process is gone
+//!
+//! A game that crashes never sends `game_stopped`, which would leave its
+//! playtime session running and its achievements on screen until the next
+//! game. When `game_started` carries the game's PID, the overlay checks every
+//! `POLL_INTERVAL` that the process still exists and stops the game itself
+//! once it doesn't.
+//!
+//! The process's start time is remembered alongside the PID, so a new process
+//! that is handed the same PID after the game exits isn't mistaken for it.
+
+use std::fs;
+use std::time::{Duration, Instant};
+
+/// How often the process is looked for
+pub const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Watches the running game's process
+#[derive(Debug)]
+pub struct ProcessWatchdog {
+    pub pid: u32,
+    pub cart_id: String,
+    /// Start time from /proc/<pid>/stat, in clock ticks after boot
+    start_time: Option<u64>,
+    /// None until the first check, which happens on the next poll
+    last_check: Option<Instant>,
+}
+
+impl ProcessWatchdog {
+    pub fn new(pid: u32, cart_id: String) -> Self {
+        println!("[Watchdog] Watching PID {} for {}", pid, cart_id);
+        Self {
+            pid,
+            cart_id,
+            start_time: read_stat(pid).and_then(|stat| stat.start_time),
+            last_check: None,
+        }
+    }
+
+    /// Check on the process when it's due. Returns true once it has exited.
+    pub fn poll(&mut self) -> bool {
+        if self.last_check.is_some_and(|t| t.elapsed() < POLL_INTERVAL) {
+            return false;
+        }
+        self.last_check = Some(Instant::now());
+        !self.is_alive()
+    }
+
+    pub fn is_alive(&self) -> bool {
+        match read_stat(self.pid) {
+            Some(stat) => stat.is_alive_as(self.start_time),
+            None => false,
+        }
+    }
+}
+
+/// The fields of /proc/<pid>/stat the watchdog needs
+#[derive(Debug, Clone, PartialEq)]
+struct ProcStat {
+    state: char,
+    start_time: Option<u64>,
+}
+
+impl ProcStat {
+    /// Zombies have exited and are only waiting to be reaped
+    fn is_alive_as(&self, start_time: Option<u64>) -> bool {
+        self.state != 'Z'
+            && self.state != 'X'
+            && (start_time.is_none() || self.start_time == start_time)
+    }
+}
+
+fn read_stat(pid: u32) -> Option<ProcStat> {
+    parse_stat(&fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?)
+}
+
+/// The command name is in parentheses and may itself contain spaces or
+/// parentheses, so fields are counted from the last `)`
+fn parse_stat(contents: &str) -> Option<ProcStat> {
+    let rest = &contents[contents.rfind(')')? + 1..];
+    let mut fields = rest.split_whitespace();
+    let state = fields.next()?.chars().next()?;
+    // starttime is field 22; state was field 3
+    let start_time = fields.nth(18).and_then(|f| f.parse().ok());
+    Some(ProcStat { state, start_time })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_stat() {
+        let stat = "4242 (mgba (qt) x) S 1 4242 4242 0 -1 4194560 1000 0 0 0 50 10 0 0 20 0 8 0 123456 0 0";
+        let parsed = parse_stat(stat).unwrap();
+        assert_eq!(
+            parsed,
+            ProcStat {
+                state: 'S',
+                start_time: Some(123456)
+            }
+        );
+        assert!(parsed.is_alive_as(Some(123456)));
+        // Same PID, different process
+        assert!(!parsed.is_alive_as(Some(99)));
+
+        let zombie = ProcStat {
+            state: 'Z',
+            ..parsed
+        };
+        assert!(!zombie.is_alive_as(Some(123456)));
+        assert_eq!(parse_stat("garbage"), None);
+    }
+
+    #[test]
+    fn test_watchdog_sees_own_process() {
+        let mut watchdog = ProcessWatchdog::new(std::process::id(), "test".to_string());
+        assert!(!watchdog.poll());
+        // Not due again yet
+        assert!(!watchdog.poll());
+
+        let gone = ProcessWatchdog::new(u32::MAX, "test".to_string());
+        assert!(!gone.is_alive());
+    }
+}
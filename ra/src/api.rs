@@ -1,14 +1,133 @@
-use anyhow::{bail, Context, Result};
 use crate::auth::Credentials;
+use crate::compare::ProgressComparison;
+use crate::endpoints::{self, CircuitState, EndpointReport, Endpoints};
+use crate::http_cache::HttpCache;
+use crate::settings::RASettings;
 use crate::types::*;
+use anyhow::{bail, Context, Result};
 use serde::Deserialize;
+use std::sync::Mutex;
 
+/// Request URLs are written against the default endpoint and rebased onto
+/// the one in use; see `endpoints`
 const RA_API_BASE: &str = "https://retroachievements.org/API";
 
+/// Connect API, used by emulators. Logins and unlocks go through here and
+/// authenticate with a session token rather than the web API key.
+const RA_CONNECT_URL: &str = "https://retroachievements.org/dorequest.php";
+
+/// What a Connect API login proves the user's identity with
+enum LoginWith<'a> {
+    Password(&'a str),
+    /// Re-validates an existing token, returning the current one
+    Token(&'a str),
+}
+
+impl LoginWith<'_> {
+    fn param(&self) -> (&'static str, &str) {
+        match self {
+            LoginWith::Password(password) => ("p", password),
+            LoginWith::Token(token) => ("t", token),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct LoginResponse {
+    #[serde(rename = "Success")]
+    success: bool,
+    #[serde(rename = "Token")]
+    token: Option<String>,
+    #[serde(rename = "Error")]
+    error: Option<String>,
+}
+
+/// Pull the token out of a `login2` response. Failed logins come back as
+/// 401s with an error in the body, so the body is checked, not the status.
+fn parse_login_response(body: &str) -> Result<String> {
+    let login: LoginResponse =
+        serde_json::from_str(body).context("Failed to parse login response")?;
+
+    if !login.success {
+        bail!("Login failed: {}", login.error.unwrap_or_default());
+    }
+
+    login
+        .token
+        .filter(|t| !t.is_empty())
+        .context("No token in login response")
+}
+
+#[derive(Deserialize)]
+struct ConnectResponse {
+    #[serde(rename = "Success")]
+    success: bool,
+    #[serde(rename = "Error")]
+    error: Option<String>,
+}
+
+/// Check a Connect API reply that carries no data, like `ping`'s
+fn parse_connect_response(body: &str) -> Result<()> {
+    let response: ConnectResponse =
+        serde_json::from_str(body).context("Failed to parse RA response")?;
+
+    if !response.success {
+        bail!("RA request failed: {}", response.error.unwrap_or_default());
+    }
+    Ok(())
+}
+
+/// Form fields of a `startsession` request
+fn start_session_params(game_id: &str, hardcore: bool) -> Vec<(&'static str, &str)> {
+    vec![
+        ("r", "startsession"),
+        ("g", game_id),
+        ("h", if hardcore { "1" } else { "0" }),
+    ]
+}
+
+/// Form fields of a `ping` request; `m` is the rich presence text
+fn ping_params<'a>(
+    game_id: &'a str,
+    rich_presence: Option<&'a str>,
+) -> Vec<(&'static str, &'a str)> {
+    let mut params = vec![("r", "ping"), ("g", game_id)];
+    if let Some(text) = rich_presence {
+        params.push(("m", text));
+    }
+    params
+}
+
+/// Seconds cached game data is used without asking RA; None always asks
+fn http_cache_max_age(settings: &RASettings) -> Option<u64> {
+    Some(settings.http_cache_max_age).filter(|age| *age > 0)
+}
+
+fn header_value(
+    response_headers: &reqwest::header::HeaderMap,
+    name: reqwest::header::HeaderName,
+) -> Option<String> {
+    response_headers
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string)
+}
+
+const NO_TOKEN: &str =
+    "No session token available. Run 'kazeta-ra token --refresh --password <password>' first.";
+
 /// RetroAchievements API client
 pub struct RAClient {
     client: reqwest::blocking::Client,
     credentials: Credentials,
+    /// Session token, replaced when the client re-authenticates
+    token: Mutex<Option<String>>,
+    /// None if the cache directory can't be created
+    http_cache: Option<HttpCache>,
+    /// How long game-level responses are used without asking RA
+    max_age: Option<u64>,
+    /// Where requests go, in failover order
+    endpoints: Endpoints,
 }
 
 impl RAClient {
@@ -18,7 +137,162 @@ impl RAClient {
             .build()
             .expect("Failed to create HTTP client");
 
-        Self { client, credentials }
+        let token = Mutex::new(credentials.token.clone());
+        let http_cache = HttpCache::new().ok();
+        let settings = RASettings::load().unwrap_or_default();
+        let max_age = http_cache_max_age(&settings);
+        let endpoints = Endpoints::new(settings.endpoints);
+        Self {
+            client,
+            credentials,
+            token,
+            http_cache,
+            max_age,
+            endpoints,
+        }
+    }
+
+    /// Send a request to the first endpoint that's up, failing over to the
+    /// next on a connection error or a 5xx. The last endpoint's 5xx is
+    /// returned for the caller to report. Errors leave out the URL, which
+    /// carries the API key.
+    fn send(
+        &self,
+        request: reqwest::blocking::RequestBuilder,
+    ) -> Result<reqwest::blocking::Response> {
+        let request = request
+            .build()
+            .map_err(|e| e.without_url())
+            .context("Failed to build RA request")?;
+        let now = chrono::Utc::now().timestamp();
+        let order = self.endpoints.attempt_order(now);
+        if order.is_empty() {
+            return Err(self.endpoints.all_down(now));
+        }
+
+        let mut last_error = None;
+        for (i, (base, state)) in order.iter().enumerate() {
+            let started = std::time::Instant::now();
+            if *state == CircuitState::HalfOpen {
+                match endpoints::probe_request(&self.client, base).send() {
+                    Ok(response) if !response.status().is_server_error() => {
+                        self.endpoints
+                            .record_success(base, endpoints::elapsed_ms(started), now);
+                    }
+                    Ok(response) => {
+                        self.endpoints.record_failure(
+                            base,
+                            &format!("Probe returned {}", response.status()),
+                            now,
+                        );
+                        continue;
+                    }
+                    Err(e) => {
+                        self.endpoints
+                            .record_failure(base, &e.without_url().to_string(), now);
+                        continue;
+                    }
+                }
+            }
+
+            let mut attempt = request.try_clone().context("RA request can't be retried")?;
+            *attempt.url_mut() = endpoints::rebase(request.url(), base)?;
+            let started = std::time::Instant::now();
+            match self.client.execute(attempt) {
+                Ok(response) if response.status().is_server_error() => {
+                    self.endpoints.record_failure(
+                        base,
+                        &format!("HTTP {}", response.status()),
+                        now,
+                    );
+                    if i + 1 == order.len() {
+                        return Ok(response);
+                    }
+                }
+                Ok(response) => {
+                    self.endpoints
+                        .record_success(base, endpoints::elapsed_ms(started), now);
+                    return Ok(response);
+                }
+                Err(e) => {
+                    let e = e.without_url();
+                    self.endpoints.record_failure(base, &e.to_string(), now);
+                    last_error = Some(anyhow::Error::new(e));
+                }
+            }
+        }
+        Err(last_error.unwrap_or_else(|| self.endpoints.all_down(now)))
+    }
+
+    /// Probe every configured endpoint now, whatever its circuit says, and
+    /// report their health
+    pub fn probe_endpoints(&self) -> Vec<EndpointReport> {
+        let now = chrono::Utc::now().timestamp();
+        for base in self.endpoints.bases() {
+            let started = std::time::Instant::now();
+            match endpoints::probe_request(&self.client, base).send() {
+                Ok(response) if !response.status().is_server_error() => {
+                    self.endpoints
+                        .record_success(base, endpoints::elapsed_ms(started), now);
+                }
+                Ok(response) => self.endpoints.record_failure(
+                    base,
+                    &format!("Probe returned {}", response.status()),
+                    now,
+                ),
+                Err(e) => self
+                    .endpoints
+                    .record_failure(base, &e.without_url().to_string(), now),
+            }
+        }
+        self.endpoints.report(now)
+    }
+
+    /// Health of the configured endpoints as last seen
+    pub fn endpoint_report(&self) -> Vec<EndpointReport> {
+        self.endpoints.report(chrono::Utc::now().timestamp())
+    }
+
+    /// GET a web API URL through the HTTP cache. `max_age` of None always
+    /// checks with RA, which answers 304 if the stored body is current.
+    fn get_cached(&self, url: &str, max_age: Option<u64>) -> Result<String> {
+        let now = chrono::Utc::now().timestamp();
+        let Some(cache) = &self.http_cache else {
+            let response = self
+                .send(self.client.get(url))
+                .context("Failed to send request to RA API")?;
+            if !response.status().is_success() {
+                bail!("RA API returned error: {}", response.status());
+            }
+            return response.text().context("Failed to read RA API response");
+        };
+
+        if let Some(entry) = cache.fresh(url, max_age, now) {
+            return Ok(entry.body);
+        }
+        let stored = cache.get(url);
+        let mut request = self.client.get(url);
+        for (name, value) in stored.iter().flat_map(|entry| entry.validators()) {
+            request = request.header(name, value);
+        }
+
+        let response = self
+            .send(request)
+            .context("Failed to send request to RA API")?;
+        if response.status() == reqwest::StatusCode::NOT_MODIFIED {
+            if let Some(entry) = stored {
+                return Ok(cache.revalidated(url, entry, now));
+            }
+        }
+        if !response.status().is_success() {
+            bail!("RA API returned error: {}", response.status());
+        }
+
+        let etag = header_value(response.headers(), reqwest::header::ETAG);
+        let last_modified = header_value(response.headers(), reqwest::header::LAST_MODIFIED);
+        let body = response.text().context("Failed to read RA API response")?;
+        cache.store(url, etag, last_modified, &body, now);
+        Ok(body)
     }
 
     /// Get user summary (profile info)
@@ -28,16 +302,15 @@ impl RAClient {
             RA_API_BASE, self.credentials.username, self.credentials.api_key
         );
 
-        let response = self.client.get(&url)
-            .send()
+        let response = self
+            .send(self.client.get(&url))
             .context("Failed to send request to RA API")?;
 
         if !response.status().is_success() {
             bail!("RA API returned error: {}", response.status());
         }
 
-        let summary: UserSummary = response.json()
-            .context("Failed to parse user summary")?;
+        let summary: UserSummary = response.json().context("Failed to parse user summary")?;
 
         Ok(summary)
     }
@@ -49,8 +322,8 @@ impl RAClient {
             RA_API_BASE, hash, self.credentials.api_key
         );
 
-        let response = self.client.get(&url)
-            .send()
+        let response = self
+            .send(self.client.get(&url))
             .context("Failed to send request to RA API")?;
 
         if !response.status().is_success() {
@@ -62,121 +335,250 @@ impl RAClient {
         }
 
         let text = response.text()?;
-        
+
         // RA API returns empty object {} or error for unknown hash
         if text == "{}" || text.is_empty() || text.contains("\"ID\":0") {
             return Ok(None);
         }
 
-        let lookup: GameInfoAndProgress = serde_json::from_str(&text)
-            .context("Failed to parse game lookup response")?;
+        let lookup: GameInfoAndProgress =
+            serde_json::from_str(&text).context("Failed to parse game lookup response")?;
 
         Ok(Some(lookup.id))
     }
 
     /// Get game info and user's achievement progress
     pub fn get_game_info_and_progress(&self, game_id: u32) -> Result<GameInfoAndProgress> {
+        self.get_user_game_progress(game_id, &self.credentials.username)
+    }
+
+    /// Get game info and progress, with the game's unofficial achievements
+    /// added when `include_unofficial` is set
+    pub fn get_game_info_with_unofficial(
+        &self,
+        game_id: u32,
+        include_unofficial: bool,
+    ) -> Result<GameInfoAndProgress> {
+        let mut info = self.get_game_info_and_progress(game_id)?;
+        if include_unofficial {
+            let unofficial = self.get_unofficial_achievements(game_id)?;
+            info.merge_unofficial(unofficial);
+        }
+        Ok(info)
+    }
+
+    /// Get a game's unofficial achievements (not part of the core set)
+    pub fn get_unofficial_achievements(&self, game_id: u32) -> Result<Vec<Achievement>> {
+        let url = format!(
+            "{}/API_GetGameExtended.php?i={}&f={}&y={}",
+            RA_API_BASE, game_id, ACHIEVEMENT_FLAG_UNOFFICIAL, self.credentials.api_key
+        );
+
+        let body = self.get_cached(&url, self.max_age)?;
+        let game: GameExtended =
+            serde_json::from_str(&body).context("Failed to parse unofficial achievements")?;
+
+        Ok(game.achievements.into_values().collect())
+    }
+
+    /// Get game info and another user's achievement progress
+    pub fn get_user_game_progress(
+        &self,
+        game_id: u32,
+        username: &str,
+    ) -> Result<GameInfoAndProgress> {
         let url = format!(
             "{}/API_GetGameInfoAndUserProgress.php?g={}&u={}&y={}",
-            RA_API_BASE, game_id, self.credentials.username, self.credentials.api_key
+            RA_API_BASE, game_id, username, self.credentials.api_key
         );
 
-        let response = self.client.get(&url)
-            .send()
-            .context("Failed to send request to RA API")?;
+        let body = self.get_cached(&url, None)?;
+        let info: GameInfoAndProgress =
+            serde_json::from_str(&body).context("Failed to parse game info")?;
+
+        Ok(info)
+    }
+
+    /// Award an achievement (unlock)
+    /// Note: This requires a session token, not the web API key
+    pub fn award_achievement(
+        &self,
+        achievement_id: u32,
+        hardcore: bool,
+    ) -> Result<AwardAchievementResponse> {
+        let response = self.send_with_token(|token| {
+            let url = format!(
+                "{}/API_AwardAchievement.php?u={}&t={}&a={}&h={}",
+                RA_API_BASE,
+                self.credentials.username,
+                token,
+                achievement_id,
+                if hardcore { 1 } else { 0 }
+            );
+            self.client.post(url)
+        })?;
 
         if !response.status().is_success() {
             bail!("RA API returned error: {}", response.status());
         }
 
-        let info: GameInfoAndProgress = response.json()
-            .context("Failed to parse game info")?;
+        let result: AwardAchievementResponse =
+            response.json().context("Failed to parse award response")?;
 
-        Ok(info)
+        Ok(result)
     }
 
-    /// Award an achievement (unlock)
-    /// Note: This requires a session token, not the web API key
-    pub fn award_achievement(&self, achievement_id: u32, hardcore: bool) -> Result<AwardAchievementResponse> {
-        let token = self.credentials.token.as_ref()
-            .context("No session token available. Login required.")?;
+    /// Tell RA a game session has started, so the user's profile shows the game
+    pub fn start_session(&self, game_id: u32, hardcore: bool) -> Result<()> {
+        let game_id = game_id.to_string();
+        self.connect_request(&start_session_params(&game_id, hardcore))
+    }
+
+    /// Keep the "now playing" status alive; RA expects one every few minutes
+    pub fn ping(&self, game_id: u32, rich_presence: Option<&str>) -> Result<()> {
+        let game_id = game_id.to_string();
+        self.connect_request(&ping_params(&game_id, rich_presence))
+    }
+
+    /// Send a Connect API request signed with the session token
+    fn connect_request(&self, params: &[(&str, &str)]) -> Result<()> {
+        let response = self.send_with_token(|token| {
+            let auth = [("u", self.credentials.username.as_str()), ("t", token)];
+            self.client
+                .post(RA_CONNECT_URL)
+                .form(&[params, &auth].concat())
+        })?;
+        let body = response.text().context("Failed to read RA response")?;
+        parse_connect_response(&body)
+    }
+
+    /// Send a request signed with the session token. A 401 means the server
+    /// no longer accepts the token, so re-authenticate and retry once.
+    fn send_with_token(
+        &self,
+        request: impl Fn(&str) -> reqwest::blocking::RequestBuilder,
+    ) -> Result<reqwest::blocking::Response> {
+        let token = self.token().context(NO_TOKEN)?;
+        let response = self
+            .send(request(&token))
+            .context("Failed to send request to RA API")?;
 
+        if response.status() != reqwest::StatusCode::UNAUTHORIZED {
+            return Ok(response);
+        }
+
+        eprintln!("[RA] Session token rejected, re-authenticating");
+        let token = self.refresh_token()?;
+        self.send(request(&token))
+            .context("Failed to send request to RA API")
+    }
+
+    /// Login to get a session token (required for awarding achievements)
+    /// Note: This uses the user's password, not API key. The caller saves
+    /// the token with `CredentialManager::update_token`.
+    pub fn login(&self, password: &str) -> Result<String> {
+        self.connect_login(LoginWith::Password(password))
+    }
+
+    /// Re-validate the current session token, picking up a replacement if
+    /// the server issued one
+    pub fn refresh_token(&self) -> Result<String> {
+        let current = self.token().context(NO_TOKEN)?;
+        self.connect_login(LoginWith::Token(&current))
+    }
+
+    fn connect_login(&self, with: LoginWith<'_>) -> Result<String> {
+        let (key, value) = with.param();
+        let request = self.client.post(RA_CONNECT_URL).form(&[
+            ("r", "login2"),
+            ("u", self.credentials.username.as_str()),
+            (key, value),
+        ]);
+        let body = self
+            .send(request)
+            .context("Failed to send login request")?
+            .text()
+            .context("Failed to read login response")?;
+
+        let token = parse_login_response(&body)?;
+        *self.token.lock().unwrap() = Some(token.clone());
+        Ok(token)
+    }
+
+    /// The current session token, including one from a re-authentication
+    pub fn token(&self) -> Option<String> {
+        self.token.lock().unwrap().clone()
+    }
+
+    /// Get community comments (hints/solutions) posted on an achievement
+    pub fn get_achievement_comments(
+        &self,
+        achievement_id: u32,
+        count: u32,
+    ) -> Result<Vec<AchievementComment>> {
         let url = format!(
-            "{}/API_AwardAchievement.php?u={}&t={}&a={}&h={}",
-            RA_API_BASE,
-            self.credentials.username,
-            token,
-            achievement_id,
-            if hardcore { 1 } else { 0 }
+            "{}/API_GetComments.php?i={}&t=2&c={}&y={}",
+            RA_API_BASE, achievement_id, count, self.credentials.api_key
         );
 
-        let response = self.client.post(&url)
-            .send()
-            .context("Failed to send award request to RA API")?;
+        let response = self
+            .send(self.client.get(&url))
+            .context("Failed to send request to RA API")?;
 
         if !response.status().is_success() {
             bail!("RA API returned error: {}", response.status());
         }
 
-        let result: AwardAchievementResponse = response.json()
-            .context("Failed to parse award response")?;
+        let comments: CommentsResponse = response
+            .json()
+            .context("Failed to parse achievement comments")?;
 
-        Ok(result)
+        Ok(comments.results)
     }
 
-    /// Login to get a session token (required for awarding achievements)
-    /// Note: This uses the user's password, not API key
-    pub fn login(&self, password: &str) -> Result<String> {
+    /// Get the users this account follows (RA "friends")
+    pub fn get_followed_users(&self) -> Result<Vec<FollowedUser>> {
         let url = format!(
-            "{}/API_Login.php?u={}&p={}",
-            RA_API_BASE, self.credentials.username, password
+            "{}/API_GetUsersIFollow.php?c=500&y={}",
+            RA_API_BASE, self.credentials.api_key
         );
 
-        let response = self.client.post(&url)
-            .send()
-            .context("Failed to send login request")?;
+        let response = self
+            .send(self.client.get(&url))
+            .context("Failed to send request to RA API")?;
 
         if !response.status().is_success() {
-            bail!("Login failed: {}", response.status());
+            bail!("RA API returned error: {}", response.status());
         }
 
-        #[derive(Deserialize)]
-        struct LoginResponse {
-            #[serde(rename = "Success")]
-            success: bool,
-            #[serde(rename = "Token")]
-            token: Option<String>,
-            #[serde(rename = "Error")]
-            error: Option<String>,
-        }
+        let followed: FollowedUsersResponse =
+            response.json().context("Failed to parse followed users")?;
 
-        let login: LoginResponse = response.json()
-            .context("Failed to parse login response")?;
+        Ok(followed.results)
+    }
 
-        if !login.success {
-            bail!("Login failed: {}", login.error.unwrap_or_default());
-        }
+    /// Compare our progress in a game against another user's
+    pub fn compare_progress(&self, game_id: u32, friend: &str) -> Result<ProgressComparison> {
+        let mine = self.get_game_info_and_progress(game_id)?;
+        let theirs = self
+            .get_user_game_progress(game_id, friend)
+            .with_context(|| format!("Failed to get progress for {}", friend))?;
 
-        login.token.context("No token in login response")
+        Ok(ProgressComparison::new(&mine, &theirs, friend))
     }
 
     /// Get list of games for a console
     pub fn get_game_list(&self, console_id: ConsoleId) -> Result<Vec<GameListEntry>> {
         let url = format!(
             "{}/API_GetGameList.php?c={}&y={}",
-            RA_API_BASE, console_id.as_u32(), self.credentials.api_key
+            RA_API_BASE,
+            console_id.as_u32(),
+            self.credentials.api_key
         );
 
-        let response = self.client.get(&url)
-            .send()
-            .context("Failed to send request to RA API")?;
-
-        if !response.status().is_success() {
-            bail!("RA API returned error: {}", response.status());
-        }
-
-        let games: Vec<GameListEntry> = response.json()
-            .context("Failed to parse game list")?;
+        let body = self.get_cached(&url, self.max_age)?;
+        let games: Vec<GameListEntry> =
+            serde_json::from_str(&body).context("Failed to parse game list")?;
 
         Ok(games)
     }
@@ -189,6 +591,26 @@ impl RAClient {
         }
     }
 
+    /// Like `verify_credentials`, but a server that can't be reached (or is
+    /// failing) is an error rather than a rejection, so the caller can fall
+    /// back to what it already knows
+    pub fn check_credentials(&self) -> Result<bool> {
+        let url = format!(
+            "{}/API_GetUserSummary.php?u={}&y={}&g=0&a=0",
+            RA_API_BASE, self.credentials.username, self.credentials.api_key
+        );
+
+        let response = self
+            .send(self.client.get(&url))
+            .context("Failed to send request to RA API")?;
+
+        if response.status().is_server_error() {
+            bail!("RA API returned error: {}", response.status());
+        }
+
+        Ok(response.status().is_success() && response.json::<UserSummary>().is_ok())
+    }
+
     /// Get the username
     pub fn username(&self) -> &str {
         &self.credentials.username
@@ -204,6 +626,14 @@ impl RAClient {
 pub struct AsyncRAClient {
     client: reqwest::Client,
     credentials: Credentials,
+    /// Session token, replaced when the client re-authenticates
+    token: Mutex<Option<String>>,
+    /// None if the cache directory can't be created
+    http_cache: Option<HttpCache>,
+    /// How long game-level responses are used without asking RA
+    max_age: Option<u64>,
+    /// Where requests go, in failover order
+    endpoints: Endpoints,
 }
 
 impl AsyncRAClient {
@@ -213,7 +643,138 @@ impl AsyncRAClient {
             .build()
             .expect("Failed to create HTTP client");
 
-        Self { client, credentials }
+        let token = Mutex::new(credentials.token.clone());
+        let http_cache = HttpCache::new().ok();
+        let settings = RASettings::load().unwrap_or_default();
+        let max_age = http_cache_max_age(&settings);
+        let endpoints = Endpoints::new(settings.endpoints);
+        Self {
+            client,
+            credentials,
+            token,
+            http_cache,
+            max_age,
+            endpoints,
+        }
+    }
+
+    /// Send a request to the first endpoint that's up; see `RAClient::send`
+    async fn send(&self, request: reqwest::RequestBuilder) -> Result<reqwest::Response> {
+        let request = request
+            .build()
+            .map_err(|e| e.without_url())
+            .context("Failed to build RA request")?;
+        let now = chrono::Utc::now().timestamp();
+        let order = self.endpoints.attempt_order(now);
+        if order.is_empty() {
+            return Err(self.endpoints.all_down(now));
+        }
+
+        let mut last_error = None;
+        for (i, (base, state)) in order.iter().enumerate() {
+            let started = std::time::Instant::now();
+            if *state == CircuitState::HalfOpen {
+                match endpoints::probe_request_async(&self.client, base)
+                    .send()
+                    .await
+                {
+                    Ok(response) if !response.status().is_server_error() => {
+                        self.endpoints
+                            .record_success(base, endpoints::elapsed_ms(started), now);
+                    }
+                    Ok(response) => {
+                        self.endpoints.record_failure(
+                            base,
+                            &format!("Probe returned {}", response.status()),
+                            now,
+                        );
+                        continue;
+                    }
+                    Err(e) => {
+                        self.endpoints
+                            .record_failure(base, &e.without_url().to_string(), now);
+                        continue;
+                    }
+                }
+            }
+
+            let mut attempt = request.try_clone().context("RA request can't be retried")?;
+            *attempt.url_mut() = endpoints::rebase(request.url(), base)?;
+            let started = std::time::Instant::now();
+            match self.client.execute(attempt).await {
+                Ok(response) if response.status().is_server_error() => {
+                    self.endpoints.record_failure(
+                        base,
+                        &format!("HTTP {}", response.status()),
+                        now,
+                    );
+                    if i + 1 == order.len() {
+                        return Ok(response);
+                    }
+                }
+                Ok(response) => {
+                    self.endpoints
+                        .record_success(base, endpoints::elapsed_ms(started), now);
+                    return Ok(response);
+                }
+                Err(e) => {
+                    let e = e.without_url();
+                    self.endpoints.record_failure(base, &e.to_string(), now);
+                    last_error = Some(anyhow::Error::new(e));
+                }
+            }
+        }
+        Err(last_error.unwrap_or_else(|| self.endpoints.all_down(now)))
+    }
+
+    /// GET a web API URL through the HTTP cache. `max_age` of None always
+    /// checks with RA, which answers 304 if the stored body is current.
+    async fn get_cached(&self, url: &str, max_age: Option<u64>) -> Result<String> {
+        let now = chrono::Utc::now().timestamp();
+        let Some(cache) = &self.http_cache else {
+            let response = self
+                .send(self.client.get(url))
+                .await
+                .context("Failed to send request to RA API")?;
+            if !response.status().is_success() {
+                bail!("RA API returned error: {}", response.status());
+            }
+            return response
+                .text()
+                .await
+                .context("Failed to read RA API response");
+        };
+
+        if let Some(entry) = cache.fresh(url, max_age, now) {
+            return Ok(entry.body);
+        }
+        let stored = cache.get(url);
+        let mut request = self.client.get(url);
+        for (name, value) in stored.iter().flat_map(|entry| entry.validators()) {
+            request = request.header(name, value);
+        }
+
+        let response = self
+            .send(request)
+            .await
+            .context("Failed to send request to RA API")?;
+        if response.status() == reqwest::StatusCode::NOT_MODIFIED {
+            if let Some(entry) = stored {
+                return Ok(cache.revalidated(url, entry, now));
+            }
+        }
+        if !response.status().is_success() {
+            bail!("RA API returned error: {}", response.status());
+        }
+
+        let etag = header_value(response.headers(), reqwest::header::ETAG);
+        let last_modified = header_value(response.headers(), reqwest::header::LAST_MODIFIED);
+        let body = response
+            .text()
+            .await
+            .context("Failed to read RA API response")?;
+        cache.store(url, etag, last_modified, &body, now);
+        Ok(body)
     }
 
     /// Get user summary (profile info)
@@ -223,8 +784,8 @@ impl AsyncRAClient {
             RA_API_BASE, self.credentials.username, self.credentials.api_key
         );
 
-        let response = self.client.get(&url)
-            .send()
+        let response = self
+            .send(self.client.get(&url))
             .await
             .context("Failed to send request to RA API")?;
 
@@ -232,7 +793,8 @@ impl AsyncRAClient {
             bail!("RA API returned error: {}", response.status());
         }
 
-        let summary: UserSummary = response.json()
+        let summary: UserSummary = response
+            .json()
             .await
             .context("Failed to parse user summary")?;
 
@@ -246,8 +808,8 @@ impl AsyncRAClient {
             RA_API_BASE, hash, self.credentials.api_key
         );
 
-        let response = self.client.get(&url)
-            .send()
+        let response = self
+            .send(self.client.get(&url))
             .await
             .context("Failed to send request to RA API")?;
 
@@ -266,113 +828,228 @@ impl AsyncRAClient {
             return Ok(None);
         }
 
-        let lookup: GameInfoAndProgress = serde_json::from_str(&text)
-            .context("Failed to parse game lookup response")?;
+        let lookup: GameInfoAndProgress =
+            serde_json::from_str(&text).context("Failed to parse game lookup response")?;
 
         Ok(Some(lookup.id))
     }
 
     /// Get game info and user's achievement progress
     pub async fn get_game_info_and_progress(&self, game_id: u32) -> Result<GameInfoAndProgress> {
+        self.get_user_game_progress(game_id, &self.credentials.username)
+            .await
+    }
+
+    /// Get game info and progress, with the game's unofficial achievements
+    /// added when `include_unofficial` is set
+    pub async fn get_game_info_with_unofficial(
+        &self,
+        game_id: u32,
+        include_unofficial: bool,
+    ) -> Result<GameInfoAndProgress> {
+        let mut info = self.get_game_info_and_progress(game_id).await?;
+        if include_unofficial {
+            let unofficial = self.get_unofficial_achievements(game_id).await?;
+            info.merge_unofficial(unofficial);
+        }
+        Ok(info)
+    }
+
+    /// Get a game's unofficial achievements (not part of the core set)
+    pub async fn get_unofficial_achievements(&self, game_id: u32) -> Result<Vec<Achievement>> {
         let url = format!(
-            "{}/API_GetGameInfoAndUserProgress.php?g={}&u={}&y={}",
-            RA_API_BASE, game_id, self.credentials.username, self.credentials.api_key
+            "{}/API_GetGameExtended.php?i={}&f={}&y={}",
+            RA_API_BASE, game_id, ACHIEVEMENT_FLAG_UNOFFICIAL, self.credentials.api_key
         );
 
-        let response = self.client.get(&url)
-            .send()
-            .await
-            .context("Failed to send request to RA API")?;
+        let body = self.get_cached(&url, self.max_age).await?;
+        let game: GameExtended =
+            serde_json::from_str(&body).context("Failed to parse unofficial achievements")?;
 
-        if !response.status().is_success() {
-            bail!("RA API returned error: {}", response.status());
-        }
+        Ok(game.achievements.into_values().collect())
+    }
 
-        let info: GameInfoAndProgress = response.json()
-            .await
-            .context("Failed to parse game info")?;
+    /// Get game info and another user's achievement progress
+    pub async fn get_user_game_progress(
+        &self,
+        game_id: u32,
+        username: &str,
+    ) -> Result<GameInfoAndProgress> {
+        let url = format!(
+            "{}/API_GetGameInfoAndUserProgress.php?g={}&u={}&y={}",
+            RA_API_BASE, game_id, username, self.credentials.api_key
+        );
+
+        let body = self.get_cached(&url, None).await?;
+        let info: GameInfoAndProgress =
+            serde_json::from_str(&body).context("Failed to parse game info")?;
 
         Ok(info)
     }
 
     /// Award an achievement (unlock)
     /// Note: This requires a session token, not the web API key
-    pub async fn award_achievement(&self, achievement_id: u32, hardcore: bool) -> Result<AwardAchievementResponse> {
-        let token = self.credentials.token.as_ref()
-            .context("No session token available. Login required.")?;
-
-        let url = format!(
-            "{}/API_AwardAchievement.php?u={}&t={}&a={}&h={}",
-            RA_API_BASE,
-            self.credentials.username,
-            token,
-            achievement_id,
-            if hardcore { 1 } else { 0 }
-        );
-
-        let response = self.client.post(&url)
-            .send()
-            .await
-            .context("Failed to send award request to RA API")?;
+    pub async fn award_achievement(
+        &self,
+        achievement_id: u32,
+        hardcore: bool,
+    ) -> Result<AwardAchievementResponse> {
+        let response = self
+            .send_with_token(|token| {
+                let url = format!(
+                    "{}/API_AwardAchievement.php?u={}&t={}&a={}&h={}",
+                    RA_API_BASE,
+                    self.credentials.username,
+                    token,
+                    achievement_id,
+                    if hardcore { 1 } else { 0 }
+                );
+                self.client.post(url)
+            })
+            .await?;
 
         if !response.status().is_success() {
             bail!("RA API returned error: {}", response.status());
         }
 
-        let result: AwardAchievementResponse = response.json()
+        let result: AwardAchievementResponse = response
+            .json()
             .await
             .context("Failed to parse award response")?;
 
         Ok(result)
     }
 
+    /// Tell RA a game session has started, so the user's profile shows the game
+    pub async fn start_session(&self, game_id: u32, hardcore: bool) -> Result<()> {
+        let game_id = game_id.to_string();
+        self.connect_request(&start_session_params(&game_id, hardcore))
+            .await
+    }
+
+    /// Keep the "now playing" status alive; RA expects one every few minutes
+    pub async fn ping(&self, game_id: u32, rich_presence: Option<&str>) -> Result<()> {
+        let game_id = game_id.to_string();
+        self.connect_request(&ping_params(&game_id, rich_presence))
+            .await
+    }
+
+    /// Send a Connect API request signed with the session token
+    async fn connect_request(&self, params: &[(&str, &str)]) -> Result<()> {
+        let response = self
+            .send_with_token(|token| {
+                let auth = [("u", self.credentials.username.as_str()), ("t", token)];
+                self.client
+                    .post(RA_CONNECT_URL)
+                    .form(&[params, &auth].concat())
+            })
+            .await?;
+        let body = response
+            .text()
+            .await
+            .context("Failed to read RA response")?;
+        parse_connect_response(&body)
+    }
+
+    /// Send a request signed with the session token. A 401 means the server
+    /// no longer accepts the token, so re-authenticate and retry once.
+    async fn send_with_token(
+        &self,
+        request: impl Fn(&str) -> reqwest::RequestBuilder,
+    ) -> Result<reqwest::Response> {
+        let token = self.token().context(NO_TOKEN)?;
+        let response = self
+            .send(request(&token))
+            .await
+            .context("Failed to send request to RA API")?;
+
+        if response.status() != reqwest::StatusCode::UNAUTHORIZED {
+            return Ok(response);
+        }
+
+        eprintln!("[RA] Session token rejected, re-authenticating");
+        let token = self.refresh_token().await?;
+        self.send(request(&token))
+            .await
+            .context("Failed to send request to RA API")
+    }
+
     /// Login to get a session token (required for awarding achievements)
-    /// Note: This uses the user's password, not API key
+    /// Note: This uses the user's password, not API key. The caller saves
+    /// the token with `CredentialManager::update_token`.
     pub async fn login(&self, password: &str) -> Result<String> {
+        self.connect_login(LoginWith::Password(password)).await
+    }
+
+    /// Re-validate the current session token, picking up a replacement if
+    /// the server issued one
+    pub async fn refresh_token(&self) -> Result<String> {
+        let current = self.token().context(NO_TOKEN)?;
+        self.connect_login(LoginWith::Token(&current)).await
+    }
+
+    async fn connect_login(&self, with: LoginWith<'_>) -> Result<String> {
+        let (key, value) = with.param();
+        let request = self.client.post(RA_CONNECT_URL).form(&[
+            ("r", "login2"),
+            ("u", self.credentials.username.as_str()),
+            (key, value),
+        ]);
+        let body = self
+            .send(request)
+            .await
+            .context("Failed to send login request")?
+            .text()
+            .await
+            .context("Failed to read login response")?;
+
+        let token = parse_login_response(&body)?;
+        *self.token.lock().unwrap() = Some(token.clone());
+        Ok(token)
+    }
+
+    /// The current session token, including one from a re-authentication
+    pub fn token(&self) -> Option<String> {
+        self.token.lock().unwrap().clone()
+    }
+
+    /// Get community comments (hints/solutions) posted on an achievement
+    pub async fn get_achievement_comments(
+        &self,
+        achievement_id: u32,
+        count: u32,
+    ) -> Result<Vec<AchievementComment>> {
         let url = format!(
-            "{}/API_Login.php?u={}&p={}",
-            RA_API_BASE, self.credentials.username, password
+            "{}/API_GetComments.php?i={}&t=2&c={}&y={}",
+            RA_API_BASE, achievement_id, count, self.credentials.api_key
         );
 
-        let response = self.client.post(&url)
-            .send()
+        let response = self
+            .send(self.client.get(&url))
             .await
-            .context("Failed to send login request")?;
+            .context("Failed to send request to RA API")?;
 
         if !response.status().is_success() {
-            bail!("Login failed: {}", response.status());
-        }
-
-        #[derive(Deserialize)]
-        struct LoginResponse {
-            #[serde(rename = "Success")]
-            success: bool,
-            #[serde(rename = "Token")]
-            token: Option<String>,
-            #[serde(rename = "Error")]
-            error: Option<String>,
+            bail!("RA API returned error: {}", response.status());
         }
 
-        let login: LoginResponse = response.json()
+        let comments: CommentsResponse = response
+            .json()
             .await
-            .context("Failed to parse login response")?;
-
-        if !login.success {
-            bail!("Login failed: {}", login.error.unwrap_or_default());
-        }
+            .context("Failed to parse achievement comments")?;
 
-        login.token.context("No token in login response")
+        Ok(comments.results)
     }
 
-    /// Get list of games for a console
-    pub async fn get_game_list(&self, console_id: ConsoleId) -> Result<Vec<GameListEntry>> {
+    /// Get the users this account follows (RA "friends")
+    pub async fn get_followed_users(&self) -> Result<Vec<FollowedUser>> {
         let url = format!(
-            "{}/API_GetGameList.php?c={}&y={}",
-            RA_API_BASE, console_id.as_u32(), self.credentials.api_key
+            "{}/API_GetUsersIFollow.php?c=500&y={}",
+            RA_API_BASE, self.credentials.api_key
         );
 
-        let response = self.client.get(&url)
-            .send()
+        let response = self
+            .send(self.client.get(&url))
             .await
             .context("Failed to send request to RA API")?;
 
@@ -380,9 +1057,37 @@ impl AsyncRAClient {
             bail!("RA API returned error: {}", response.status());
         }
 
-        let games: Vec<GameListEntry> = response.json()
+        let followed: FollowedUsersResponse = response
+            .json()
+            .await
+            .context("Failed to parse followed users")?;
+
+        Ok(followed.results)
+    }
+
+    /// Compare our progress in a game against another user's
+    pub async fn compare_progress(&self, game_id: u32, friend: &str) -> Result<ProgressComparison> {
+        let mine = self.get_game_info_and_progress(game_id).await?;
+        let theirs = self
+            .get_user_game_progress(game_id, friend)
             .await
-            .context("Failed to parse game list")?;
+            .with_context(|| format!("Failed to get progress for {}", friend))?;
+
+        Ok(ProgressComparison::new(&mine, &theirs, friend))
+    }
+
+    /// Get list of games for a console
+    pub async fn get_game_list(&self, console_id: ConsoleId) -> Result<Vec<GameListEntry>> {
+        let url = format!(
+            "{}/API_GetGameList.php?c={}&y={}",
+            RA_API_BASE,
+            console_id.as_u32(),
+            self.credentials.api_key
+        );
+
+        let body = self.get_cached(&url, self.max_age).await?;
+        let games: Vec<GameListEntry> =
+            serde_json::from_str(&body).context("Failed to parse game list")?;
 
         Ok(games)
     }
@@ -406,3 +1111,37 @@ impl AsyncRAClient {
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_login_response() {
+        let ok = r#"{"Success":true,"User":"user","Token":"abc123","Score":100}"#;
+        assert_eq!(parse_login_response(ok).unwrap(), "abc123");
+
+        let expired = r#"{"Success":false,"Status":401,"Code":"expired_token","Error":"The access token has expired. Please log in again."}"#;
+        let err = parse_login_response(expired).unwrap_err().to_string();
+        assert!(err.contains("expired"));
+
+        assert!(parse_login_response(r#"{"Success":true,"Token":""}"#).is_err());
+    }
+
+    #[test]
+    fn test_connect_session_requests() {
+        assert!(parse_connect_response(r#"{"Success":true}"#).is_ok());
+        let err =
+            parse_connect_response(r#"{"Success":false,"Error":"Unknown game"}"#).unwrap_err();
+        assert!(err.to_string().contains("Unknown game"));
+
+        assert_eq!(
+            start_session_params("1234", true),
+            [("r", "startsession"), ("g", "1234"), ("h", "1")]
+        );
+        assert_eq!(ping_params("1234", None), [("r", "ping"), ("g", "1234")]);
+        assert_eq!(
+            ping_params("1234", Some("Level 2")).last(),
+            Some(&("m", "Level 2"))
+        );
+    }
+}
@@ -1,7 +1,37 @@
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+/// How long a session token is trusted before it is re-validated with the
+/// server. RA doesn't publish an expiry, so this errs on the short side.
+pub const TOKEN_LIFETIME: chrono::Duration = chrono::Duration::days(14);
+
+/// How long `kazeta-ra status` trusts its last credential check before asking
+/// the server again
+pub const STATUS_TTL: chrono::Duration = chrono::Duration::hours(1);
+
+/// Present while hardcore is switched off for the running game, e.g. because
+/// a cheat was enabled. Holds the reason; cleared when the next game starts.
+pub const SOFTCORE_SESSION_FILE: &str = "/tmp/kazeta-ra-softcore";
+
+/// Turn hardcore off until the next game starts
+pub fn suspend_hardcore_for_session(reason: &str) -> Result<()> {
+    fs::write(SOFTCORE_SESSION_FILE, format!("{}\n", reason))
+        .context("Failed to write softcore session marker")
+}
+
+/// Start a new session with the hardcore setting from the credentials
+pub fn clear_session_softcore() {
+    let _ = fs::remove_file(SOFTCORE_SESSION_FILE);
+}
+
+/// Why hardcore is off for this session, if it is
+pub fn session_softcore_reason() -> Option<String> {
+    fs::read_to_string(SOFTCORE_SESSION_FILE)
+        .ok()
+        .map(|r| r.trim().to_string())
+}
 
 /// RetroAchievements credentials
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -10,6 +40,9 @@ pub struct Credentials {
     pub api_key: String,
     #[serde(default)]
     pub token: Option<String>,
+    /// When the token was last issued or re-validated (RFC 3339)
+    #[serde(default)]
+    pub token_acquired: Option<String>,
     #[serde(default)]
     pub hardcore: bool,
     #[serde(default)]
@@ -22,15 +55,69 @@ impl Credentials {
             username,
             api_key,
             token: None,
+            token_acquired: None,
             hardcore: false,
             last_sync: None,
         }
     }
+
+    /// Store a freshly issued or re-validated session token
+    pub fn set_token(&mut self, token: String) {
+        self.token = Some(token);
+        self.token_acquired = Some(chrono::Utc::now().to_rfc3339());
+    }
+
+    /// When the token should be re-validated, if its age is known
+    pub fn token_expires_at(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        let acquired =
+            chrono::DateTime::parse_from_rfc3339(self.token_acquired.as_deref()?).ok()?;
+        Some(acquired.with_timezone(&chrono::Utc) + TOKEN_LIFETIME)
+    }
+
+    /// Hardcore for the running game: the saved setting, unless it has been
+    /// suspended for the session
+    pub fn session_hardcore(&self) -> bool {
+        self.hardcore && !Path::new(SOFTCORE_SESSION_FILE).exists()
+    }
+
+    /// Whether there is a token that doesn't need refreshing yet.
+    /// Tokens saved before their age was tracked count as expired.
+    pub fn token_is_fresh(&self, now: chrono::DateTime<chrono::Utc>) -> bool {
+        self.token.is_some() && self.token_expires_at().is_some_and(|expires| now < expires)
+    }
+}
+
+/// The outcome of the last credential check against the API, so `status`
+/// doesn't make a request on every game launch
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StatusCheck {
+    pub username: String,
+    pub valid: bool,
+    /// Unix timestamp of the check
+    pub checked_at: i64,
+}
+
+impl StatusCheck {
+    pub fn new(username: &str, valid: bool, now: i64) -> Self {
+        Self {
+            username: username.to_string(),
+            valid,
+            checked_at: now,
+        }
+    }
+
+    /// Whether the check still stands for `username`, `ttl` after it was made
+    pub fn is_fresh(&self, username: &str, now: i64, ttl: chrono::Duration) -> bool {
+        self.username == username
+            && now >= self.checked_at
+            && now - self.checked_at < ttl.num_seconds()
+    }
 }
 
 /// Manages RetroAchievements credentials storage
 pub struct CredentialManager {
     credentials_path: PathBuf,
+    status_path: PathBuf,
 }
 
 impl CredentialManager {
@@ -39,12 +126,15 @@ impl CredentialManager {
             .context("Could not find home directory")?
             .join(".local/share/kazeta-plus");
 
-        fs::create_dir_all(&data_dir)
-            .context("Failed to create kazeta data directory")?;
+        fs::create_dir_all(&data_dir).context("Failed to create kazeta data directory")?;
 
         let credentials_path = data_dir.join("ra_credentials.json");
+        let status_path = data_dir.join("ra_status.json");
 
-        Ok(Self { credentials_path })
+        Ok(Self {
+            credentials_path,
+            status_path,
+        })
     }
 
     /// Check if credentials are stored
@@ -61,12 +151,26 @@ impl CredentialManager {
     /// Load stored credentials
     /// Checks BIOS config.toml first, then falls back to ra_credentials.json
     pub fn load(&self) -> Result<Option<Credentials>> {
+        let stored = self.load_from_json()?;
+
         // First, try to load from BIOS config.toml
-        if let Some(creds) = Self::load_from_bios_config()? {
+        if let Some(mut creds) = Self::load_from_bios_config()? {
+            // The BIOS config has no session token, so keep the one saved
+            // for the same account
+            if let Some(stored) = stored.filter(|s| s.username == creds.username) {
+                creds.token = stored.token;
+                creds.token_acquired = stored.token_acquired;
+                creds.last_sync = stored.last_sync;
+            }
             return Ok(Some(creds));
         }
 
         // Fall back to JSON file
+        Ok(stored)
+    }
+
+    /// Load credentials from ra_credentials.json
+    fn load_from_json(&self) -> Result<Option<Credentials>> {
         if !self.credentials_path.exists() {
             return Ok(None);
         }
@@ -74,8 +178,8 @@ impl CredentialManager {
         let content = fs::read_to_string(&self.credentials_path)
             .context("Failed to read credentials file")?;
 
-        let creds: Credentials = serde_json::from_str(&content)
-            .context("Failed to parse credentials")?;
+        let creds: Credentials =
+            serde_json::from_str(&content).context("Failed to parse credentials")?;
 
         Ok(Some(creds))
     }
@@ -90,18 +194,20 @@ impl CredentialManager {
             return Ok(None);
         }
 
-        let content = fs::read_to_string(&config_path)
-            .context("Failed to read BIOS config file")?;
+        let content =
+            fs::read_to_string(&config_path).context("Failed to read BIOS config file")?;
 
         // Parse TOML manually to extract retroachievements section
-        let config: toml::Value = toml::from_str(&content)
-            .context("Failed to parse BIOS config TOML")?;
+        let config: toml::Value =
+            toml::from_str(&content).context("Failed to parse BIOS config TOML")?;
 
         if let Some(ra_section) = config.get("retroachievements") {
-            let username = ra_section.get("username")
+            let username = ra_section
+                .get("username")
                 .and_then(|v| v.as_str())
                 .map(|s| s.to_string());
-            let api_key = ra_section.get("api_key")
+            let api_key = ra_section
+                .get("api_key")
                 .and_then(|v| v.as_str())
                 .map(|s| s.to_string());
 
@@ -117,11 +223,10 @@ impl CredentialManager {
 
     /// Save credentials to storage
     pub fn save(&self, credentials: &Credentials) -> Result<()> {
-        let json = serde_json::to_string_pretty(credentials)
-            .context("Failed to serialize credentials")?;
+        let json =
+            serde_json::to_string_pretty(credentials).context("Failed to serialize credentials")?;
 
-        fs::write(&self.credentials_path, json)
-            .context("Failed to write credentials file")?;
+        fs::write(&self.credentials_path, json).context("Failed to write credentials file")?;
 
         // Set restrictive permissions (Unix only)
         #[cfg(unix)]
@@ -138,16 +243,34 @@ impl CredentialManager {
     /// Delete stored credentials
     pub fn delete(&self) -> Result<()> {
         if self.credentials_path.exists() {
-            fs::remove_file(&self.credentials_path)
-                .context("Failed to delete credentials file")?;
+            fs::remove_file(&self.credentials_path).context("Failed to delete credentials file")?;
         }
+        self.clear_status_check();
         Ok(())
     }
 
+    /// The last credential check, if one was recorded
+    pub fn load_status_check(&self) -> Option<StatusCheck> {
+        let content = fs::read_to_string(&self.status_path).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    /// Record a credential check for `status` to reuse
+    pub fn save_status_check(&self, check: &StatusCheck) -> Result<()> {
+        let json =
+            serde_json::to_string_pretty(check).context("Failed to serialize status check")?;
+        fs::write(&self.status_path, json).context("Failed to write status file")
+    }
+
+    /// Forget the last credential check, so the next `status` asks the server
+    pub fn clear_status_check(&self) {
+        let _ = fs::remove_file(&self.status_path);
+    }
+
     /// Update the token in stored credentials
     pub fn update_token(&self, token: String) -> Result<()> {
         if let Some(mut creds) = self.load()? {
-            creds.token = Some(token);
+            creds.set_token(token);
             creds.last_sync = Some(chrono::Utc::now().to_rfc3339());
             self.save(&creds)?;
         }
@@ -175,3 +298,35 @@ impl Default for CredentialManager {
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_expiry() {
+        let mut creds = Credentials::new("user".to_string(), "key".to_string());
+        let now = chrono::Utc::now();
+        assert!(!creds.token_is_fresh(now));
+
+        // A token from before ages were tracked is refreshed
+        creds.token = Some("abc".to_string());
+        assert!(!creds.token_is_fresh(now));
+
+        creds.set_token("def".to_string());
+        assert!(creds.token_is_fresh(now));
+        assert!(!creds.token_is_fresh(now + TOKEN_LIFETIME + chrono::Duration::minutes(1)));
+    }
+
+    #[test]
+    fn test_status_check_freshness() {
+        let check = StatusCheck::new("user", true, 1_000);
+        assert!(check.is_fresh("user", 1_000, STATUS_TTL));
+        assert!(check.is_fresh("user", 1_000 + 3_599, STATUS_TTL));
+        assert!(!check.is_fresh("user", 1_000 + 3_600, STATUS_TTL));
+        // Another account needs its own check
+        assert!(!check.is_fresh("other", 1_000, STATUS_TTL));
+        // A clock that went backwards doesn't make an old check fresh forever
+        assert!(!check.is_fresh("user", 500, STATUS_TTL));
+        assert!(!check.is_fresh("user", 1_000, chrono::Duration::zero()));
+    }
+}
@@ -1,7 +1,30 @@
-use anyhow::{Context, Result};
-use rusqlite::{Connection, params};
-use std::path::PathBuf;
+use crate::difficulty::{self, AchievementSample, DifficultyEstimate};
+use crate::local::LocalUnlock;
 use crate::types::*;
+use anyhow::{bail, Context, Result};
+use rusqlite::{params, Connection};
+use std::collections::HashMap;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+/// Host serving game icons and achievement badges
+const RA_MEDIA_BASE: &str = "https://media.retroachievements.org";
+
+/// Default size limit for the shared image cache (50 MB)
+pub const DEFAULT_IMAGE_CACHE_BYTES: u64 = 50 * 1024 * 1024;
+
+/// Largest single image the cache downloads (badges and icons are a few KB)
+const MAX_IMAGE_BYTES: u64 = 4 * 1024 * 1024;
+
+/// Current cache schema, stored in `PRAGMA user_version`.
+/// Version 1 had games, achievements and user_progress; version 2 adds the
+/// unlock history and per-game sync state; version 3 stores player and
+/// unlock counts so rarity is known offline; version 4 caches difficulty
+/// estimates; version 5 records the achievements of native carts.
+const SCHEMA_VERSION: i32 = 5;
 
 /// Local cache for RetroAchievements data
 /// Reduces API calls and enables offline viewing
@@ -11,26 +34,36 @@ pub struct RACache {
 
 impl RACache {
     pub fn new() -> Result<Self> {
-        let cache_dir = dirs::home_dir()
-            .context("Could not find home directory")?
-            .join(".local/share/kazeta-plus/ra_cache");
+        let path = Self::default_path()?;
+        if let Some(cache_dir) = path.parent() {
+            std::fs::create_dir_all(cache_dir).context("Failed to create cache directory")?;
+        }
 
-        std::fs::create_dir_all(&cache_dir)
-            .context("Failed to create cache directory")?;
+        Self::open(&path)
+    }
 
-        let db_path = cache_dir.join("achievements.db");
-        let conn = Connection::open(&db_path)
-            .context("Failed to open cache database")?;
+    /// Where `new` keeps the database
+    pub fn default_path() -> Result<PathBuf> {
+        Ok(dirs::home_dir()
+            .context("Could not find home directory")?
+            .join(".local/share/kazeta-plus/ra_cache/achievements.db"))
+    }
+
+    /// Open (and migrate if needed) the cache database at `path`
+    pub fn open(path: &Path) -> Result<Self> {
+        let conn = Connection::open(path).context("Failed to open cache database")?;
 
         let cache = Self { conn };
         cache.init_tables()?;
+        cache.migrate()?;
 
         Ok(cache)
     }
 
     fn init_tables(&self) -> Result<()> {
-        self.conn.execute_batch(
-            r#"
+        self.conn
+            .execute_batch(
+                r#"
             CREATE TABLE IF NOT EXISTS games (
                 hash TEXT PRIMARY KEY,
                 game_id INTEGER NOT NULL,
@@ -39,7 +72,8 @@ impl RACache {
                 console_name TEXT,
                 icon_url TEXT,
                 num_achievements INTEGER DEFAULT 0,
-                last_updated TEXT NOT NULL
+                last_updated TEXT NOT NULL,
+                num_players INTEGER
             );
 
             CREATE TABLE IF NOT EXISTS achievements (
@@ -50,6 +84,7 @@ impl RACache {
                 points INTEGER DEFAULT 0,
                 badge_name TEXT,
                 display_order INTEGER DEFAULT 0,
+                num_awarded INTEGER,
                 FOREIGN KEY (game_hash) REFERENCES games(hash)
             );
 
@@ -61,20 +96,159 @@ impl RACache {
             );
 
             CREATE INDEX IF NOT EXISTS idx_achievements_game ON achievements(game_hash);
-            "#
-        ).context("Failed to create cache tables")?;
+
+            CREATE TABLE IF NOT EXISTS unlock_events (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                achievement_id INTEGER NOT NULL,
+                game_hash TEXT NOT NULL,
+                hardcore INTEGER NOT NULL,
+                earned_at INTEGER NOT NULL,
+                UNIQUE (achievement_id, hardcore),
+                FOREIGN KEY (achievement_id) REFERENCES achievements(id)
+            );
+
+            CREATE TABLE IF NOT EXISTS sync_state (
+                game_hash TEXT PRIMARY KEY,
+                last_synced INTEGER NOT NULL,
+                FOREIGN KEY (game_hash) REFERENCES games(hash)
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_unlock_events_time ON unlock_events(earned_at);
+
+            CREATE TABLE IF NOT EXISTS difficulty (
+                game_hash TEXT PRIMARY KEY,
+                score REAL NOT NULL,
+                remaining INTEGER NOT NULL,
+                pace_secs INTEGER,
+                expected_master_secs INTEGER,
+                computed_at INTEGER NOT NULL,
+                FOREIGN KEY (game_hash) REFERENCES games(hash)
+            );
+
+            CREATE TABLE IF NOT EXISTS local_unlocks (
+                cart_id TEXT NOT NULL,
+                achievement_id TEXT NOT NULL,
+                unlocked_at INTEGER NOT NULL,
+                PRIMARY KEY (cart_id, achievement_id)
+            );
+            "#,
+            )
+            .context("Failed to create cache tables")?;
 
         Ok(())
     }
 
+    /// Bring caches written by older versions up to SCHEMA_VERSION
+    fn migrate(&self) -> Result<()> {
+        let version: i32 = self
+            .conn
+            .query_row("PRAGMA user_version", [], |row| row.get(0))?;
+        if version >= SCHEMA_VERSION {
+            return Ok(());
+        }
+
+        // Version 1 only kept the latest unlock dates; seed the history from them
+        if version < 2 {
+            let imported = self.import_unlock_history()?;
+            if imported > 0 {
+                println!("Imported {} unlocks into the achievement history", imported);
+            }
+        }
+
+        // Version 3 added the rarity counts. They fill in as games are re-synced.
+        self.add_column_if_missing("games", "num_players", "INTEGER")?;
+        self.add_column_if_missing("achievements", "num_awarded", "INTEGER")?;
+
+        self.conn
+            .execute_batch(&format!("PRAGMA user_version = {}", SCHEMA_VERSION))
+            .context("Failed to update cache schema version")?;
+        Ok(())
+    }
+
+    /// `ALTER TABLE ... ADD COLUMN` unless the table already has the column
+    fn add_column_if_missing(&self, table: &str, column: &str, kind: &str) -> Result<()> {
+        let exists: bool = self.conn.query_row(
+            &format!(
+                "SELECT COUNT(*) > 0 FROM pragma_table_info('{}') WHERE name = ?1",
+                table
+            ),
+            params![column],
+            |row| row.get(0),
+        )?;
+        if !exists {
+            self.conn
+                .execute_batch(&format!(
+                    "ALTER TABLE {} ADD COLUMN {} {}",
+                    table, column, kind
+                ))
+                .with_context(|| format!("Failed to add {}.{}", table, column))?;
+        }
+        Ok(())
+    }
+
+    /// Record an unlock event for every earned achievement in user_progress.
+    /// Events that already exist are left alone.
+    fn import_unlock_history(&self) -> Result<usize> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT p.achievement_id, a.game_hash, p.date_earned, p.date_earned_hardcore
+            FROM user_progress p
+            JOIN achievements a ON a.id = p.achievement_id
+            WHERE p.date_earned IS NOT NULL OR p.date_earned_hardcore IS NOT NULL
+            "#,
+        )?;
+
+        let rows: Vec<(u32, String, Option<String>, Option<String>)> = stmt
+            .query_map([], |row| {
+                Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+            })?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        let mut imported = 0;
+        for (achievement_id, game_hash, softcore, hardcore) in rows {
+            for (date, is_hardcore) in [(softcore, false), (hardcore, true)] {
+                let Some(earned_at) = date.as_deref().and_then(parse_timestamp) else {
+                    continue;
+                };
+                imported +=
+                    self.record_unlock(achievement_id, &game_hash, is_hardcore, earned_at)?;
+            }
+        }
+
+        Ok(imported)
+    }
+
+    /// Insert an unlock event, returning 1 if it was new
+    fn record_unlock(
+        &self,
+        achievement_id: u32,
+        game_hash: &str,
+        hardcore: bool,
+        earned_at: i64,
+    ) -> Result<usize> {
+        let inserted = self
+            .conn
+            .execute(
+                r#"
+            INSERT OR IGNORE INTO unlock_events (achievement_id, game_hash, hardcore, earned_at)
+            VALUES (?1, ?2, ?3, ?4)
+            "#,
+                params![achievement_id, game_hash, hardcore, earned_at],
+            )
+            .context("Failed to record unlock event")?;
+
+        Ok(inserted)
+    }
+
     /// Store game info in cache
     pub fn cache_game(&self, hash: &str, info: &GameInfoAndProgress) -> Result<()> {
         let now = chrono::Utc::now().to_rfc3339();
 
         self.conn.execute(
             r#"
-            INSERT OR REPLACE INTO games (hash, game_id, title, console_id, console_name, icon_url, num_achievements, last_updated)
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
+            INSERT OR REPLACE INTO games (hash, game_id, title, console_id, console_name, icon_url, num_achievements, last_updated, num_players)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)
             "#,
             params![
                 hash,
@@ -85,16 +259,24 @@ impl RACache {
                 info.image_icon,
                 info.num_achievements,
                 now,
+                info.num_players_casual,
             ],
         ).context("Failed to cache game info")?;
 
-        // Cache achievements
+        // Cache achievements (the cache only tracks the core set)
         if let Some(ref achievements) = info.achievements {
-            for achievement in achievements.values() {
+            for achievement in achievements.values().filter(|a| !a.is_unofficial()) {
                 self.cache_achievement(hash, achievement)?;
             }
         }
 
+        self.conn
+            .execute(
+                "INSERT OR REPLACE INTO sync_state (game_hash, last_synced) VALUES (?1, ?2)",
+                params![hash, chrono::Utc::now().timestamp()],
+            )
+            .context("Failed to update sync state")?;
+
         Ok(())
     }
 
@@ -102,8 +284,8 @@ impl RACache {
     fn cache_achievement(&self, game_hash: &str, achievement: &Achievement) -> Result<()> {
         self.conn.execute(
             r#"
-            INSERT OR REPLACE INTO achievements (id, game_hash, title, description, points, badge_name, display_order)
-            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+            INSERT OR REPLACE INTO achievements (id, game_hash, title, description, points, badge_name, display_order, num_awarded)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)
             "#,
             params![
                 achievement.id,
@@ -113,34 +295,45 @@ impl RACache {
                 achievement.points,
                 achievement.badge_name,
                 achievement.display_order,
+                achievement.num_awarded,
             ],
         ).context("Failed to cache achievement")?;
 
         // Store user progress
-        self.conn.execute(
-            r#"
+        self.conn
+            .execute(
+                r#"
             INSERT OR REPLACE INTO user_progress (achievement_id, date_earned, date_earned_hardcore)
             VALUES (?1, ?2, ?3)
             "#,
-            params![
-                achievement.id,
-                achievement.date_earned,
-                achievement.date_earned_hardcore,
-            ],
-        ).context("Failed to cache user progress")?;
+                params![
+                    achievement.id,
+                    achievement.date_earned,
+                    achievement.date_earned_hardcore,
+                ],
+            )
+            .context("Failed to cache user progress")?;
+
+        // Unlocks made on other devices show up here first
+        for (date, hardcore) in [
+            (&achievement.date_earned, false),
+            (&achievement.date_earned_hardcore, true),
+        ] {
+            if let Some(earned_at) = date.as_deref().and_then(parse_timestamp) {
+                self.record_unlock(achievement.id, game_hash, hardcore, earned_at)?;
+            }
+        }
 
         Ok(())
     }
 
     /// Get cached game ID for a ROM hash
     pub fn get_game_id(&self, hash: &str) -> Result<Option<u32>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT game_id FROM games WHERE hash = ?1"
-        )?;
+        let mut stmt = self
+            .conn
+            .prepare("SELECT game_id FROM games WHERE hash = ?1")?;
 
-        let result = stmt.query_row(params![hash], |row| {
-            row.get::<_, u32>(0)
-        });
+        let result = stmt.query_row(params![hash], |row| row.get::<_, u32>(0));
 
         match result {
             Ok(id) => Ok(Some(id)),
@@ -149,15 +342,29 @@ impl RACache {
         }
     }
 
+    /// Most recently cached ROM hash for a game ID, for looking up a game
+    /// whose hash isn't known (e.g. started while offline)
+    pub fn get_game_hash(&self, game_id: u32) -> Result<Option<String>> {
+        let result = self.conn.query_row(
+            "SELECT hash FROM games WHERE game_id = ?1 ORDER BY last_updated DESC LIMIT 1",
+            params![game_id],
+            |row| row.get::<_, String>(0),
+        );
+
+        match result {
+            Ok(hash) => Ok(Some(hash)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
     /// Get cached game title for a ROM hash
     pub fn get_game_title(&self, hash: &str) -> Result<Option<String>> {
-        let mut stmt = self.conn.prepare(
-            "SELECT title FROM games WHERE hash = ?1"
-        )?;
+        let mut stmt = self
+            .conn
+            .prepare("SELECT title FROM games WHERE hash = ?1")?;
 
-        let result = stmt.query_row(params![hash], |row| {
-            row.get::<_, String>(0)
-        });
+        let result = stmt.query_row(params![hash], |row| row.get::<_, String>(0));
 
         match result {
             Ok(title) => Ok(Some(title)),
@@ -176,30 +383,62 @@ impl RACache {
             LEFT JOIN user_progress p ON a.id = p.achievement_id
             WHERE a.game_hash = ?1
             ORDER BY a.display_order
-            "#
+            "#,
         )?;
 
-        let achievements = stmt.query_map(params![hash], |row| {
-            Ok(CachedAchievement {
-                id: row.get(0)?,
-                title: row.get(1)?,
-                description: row.get(2)?,
-                points: row.get(3)?,
-                badge_name: row.get(4)?,
-                display_order: row.get(5)?,
-                date_earned: row.get(6)?,
-                date_earned_hardcore: row.get(7)?,
-            })
-        })?
-        .filter_map(|r| r.ok())
-        .collect();
+        let achievements = stmt
+            .query_map(params![hash], |row| {
+                Ok(CachedAchievement {
+                    id: row.get(0)?,
+                    title: row.get(1)?,
+                    description: row.get(2)?,
+                    points: row.get(3)?,
+                    badge_name: row.get(4)?,
+                    display_order: row.get(5)?,
+                    date_earned: row.get(6)?,
+                    date_earned_hardcore: row.get(7)?,
+                })
+            })?
+            .filter_map(|r| r.ok())
+            .collect();
 
         Ok(achievements)
     }
 
+    /// Hash and title of the game an achievement belongs to
+    pub fn get_achievement_game(&self, achievement_id: u32) -> Result<Option<(String, String)>> {
+        let result = self.conn.query_row(
+            r#"
+            SELECT a.game_hash, g.title
+            FROM achievements a
+            JOIN games g ON g.hash = a.game_hash
+            WHERE a.id = ?1
+            "#,
+            params![achievement_id],
+            |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)),
+        );
+
+        match result {
+            Ok(game) => Ok(Some(game)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
     /// Mark an achievement as earned in the cache
     pub fn mark_earned(&self, achievement_id: u32, hardcore: bool) -> Result<()> {
-        let now = chrono::Utc::now().to_rfc3339();
+        let timestamp = chrono::Utc::now();
+        let now = timestamp.to_rfc3339();
+
+        self.conn
+            .execute(
+                r#"
+            INSERT OR IGNORE INTO unlock_events (achievement_id, game_hash, hardcore, earned_at)
+            SELECT id, game_hash, ?2, ?3 FROM achievements WHERE id = ?1
+            "#,
+                params![achievement_id, hardcore, timestamp.timestamp()],
+            )
+            .context("Failed to record unlock event")?;
 
         if hardcore {
             self.conn.execute(
@@ -236,14 +475,520 @@ impl RACache {
         Ok(result)
     }
 
-    /// Clear all cached data
+    /// The completed set for a game, if every achievement has been earned.
+    /// A set only counts as hardcore mastered when each unlock was hardcore;
+    /// the completion time then only covers hardcore unlocks.
+    pub fn get_mastery(&self, hash: &str) -> Result<Option<Mastery>> {
+        let result = self.conn.query_row(
+            r#"
+            SELECT g.title,
+                   COUNT(a.id) AS total,
+                   SUM(CASE WHEN p.date_earned IS NOT NULL OR p.date_earned_hardcore IS NOT NULL THEN 1 ELSE 0 END) AS earned,
+                   SUM(CASE WHEN p.date_earned_hardcore IS NOT NULL THEN 1 ELSE 0 END) AS earned_hardcore,
+                   COALESCE(SUM(a.points), 0) AS points
+            FROM games g
+            JOIN achievements a ON a.game_hash = g.hash
+            LEFT JOIN user_progress p ON p.achievement_id = a.id
+            WHERE g.hash = ?1
+            GROUP BY g.hash
+            "#,
+            params![hash],
+            |row| Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, u32>(1)?,
+                row.get::<_, u32>(2)?,
+                row.get::<_, u32>(3)?,
+                row.get::<_, u32>(4)?,
+            )),
+        );
+
+        let (game_title, total, earned, earned_hardcore, points) = match result {
+            Ok(row) => row,
+            Err(rusqlite::Error::QueryReturnedNoRows) => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+        if total == 0 || earned < total {
+            return Ok(None);
+        }
+
+        let hardcore = earned_hardcore == total;
+        let (first_unlock, completed_at) = self.conn.query_row(
+            r#"
+            SELECT MIN(earned_at), MAX(earned_at) FROM unlock_events
+            WHERE game_hash = ?1 AND (hardcore = 1 OR ?2 = 0)
+            "#,
+            params![hash, hardcore],
+            |row| Ok((row.get::<_, Option<i64>>(0)?, row.get::<_, Option<i64>>(1)?)),
+        )?;
+
+        Ok(Some(Mastery {
+            game_hash: hash.to_string(),
+            game_title,
+            hardcore,
+            total,
+            points,
+            first_unlock,
+            completed_at,
+        }))
+    }
+
+    /// Get the cached icon path and badge names for a game
+    pub fn get_game_images(&self, hash: &str) -> Result<Option<CachedGameImages>> {
+        let icon = self.conn.query_row(
+            "SELECT icon_url FROM games WHERE hash = ?1",
+            params![hash],
+            |row| row.get::<_, Option<String>>(0),
+        );
+
+        let icon = match icon {
+            Ok(icon) => icon,
+            Err(rusqlite::Error::QueryReturnedNoRows) => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut stmt = self.conn.prepare(
+            "SELECT id, badge_name FROM achievements WHERE game_hash = ?1 AND badge_name IS NOT NULL ORDER BY display_order"
+        )?;
+
+        let badges = stmt
+            .query_map(params![hash], |row| {
+                Ok((row.get::<_, u32>(0)?, row.get::<_, String>(1)?))
+            })?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(Some(CachedGameImages {
+            icon_url: icon,
+            badges,
+        }))
+    }
+
+    /// Unlocks earned in the last `days` days, newest first
+    pub fn recent_unlocks(&self, days: u32) -> Result<Vec<UnlockEvent>> {
+        let since = chrono::Utc::now().timestamp() - i64::from(days) * 86_400;
+
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT e.achievement_id, e.game_hash, g.title, a.title, a.points, e.hardcore, e.earned_at
+            FROM unlock_events e
+            JOIN achievements a ON a.id = e.achievement_id
+            LEFT JOIN games g ON g.hash = e.game_hash
+            WHERE e.earned_at >= ?1
+            ORDER BY e.earned_at DESC, e.id DESC
+            "#
+        )?;
+
+        let events = stmt
+            .query_map(params![since], |row| {
+                Ok(UnlockEvent {
+                    achievement_id: row.get(0)?,
+                    game_hash: row.get(1)?,
+                    game_title: row.get(2)?,
+                    title: row.get(3)?,
+                    points: row.get(4)?,
+                    hardcore: row.get(5)?,
+                    earned_at: row.get(6)?,
+                })
+            })?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(events)
+    }
+
+    /// Every unlock recorded for a game, oldest first
+    pub fn unlock_history(&self, hash: &str) -> Result<Vec<UnlockEvent>> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT e.achievement_id, e.game_hash, g.title, a.title, a.points, e.hardcore, e.earned_at
+            FROM unlock_events e
+            JOIN achievements a ON a.id = e.achievement_id
+            LEFT JOIN games g ON g.hash = e.game_hash
+            WHERE e.game_hash = ?1
+            ORDER BY e.earned_at, e.id
+            "#
+        )?;
+
+        let events = stmt
+            .query_map(params![hash], |row| {
+                Ok(UnlockEvent {
+                    achievement_id: row.get(0)?,
+                    game_hash: row.get(1)?,
+                    game_title: row.get(2)?,
+                    title: row.get(3)?,
+                    points: row.get(4)?,
+                    hardcore: row.get(5)?,
+                    earned_at: row.get(6)?,
+                })
+            })?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(events)
+    }
+
+    /// Games with some but not all achievements earned, most recently played first
+    pub fn games_in_progress(&self) -> Result<Vec<GameProgress>> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT g.hash, g.game_id, g.title,
+                   SUM(CASE WHEN p.date_earned IS NOT NULL OR p.date_earned_hardcore IS NOT NULL THEN 1 ELSE 0 END) AS earned,
+                   COUNT(a.id) AS total,
+                   (SELECT MAX(earned_at) FROM unlock_events e WHERE e.game_hash = g.hash) AS last_unlock,
+                   s.last_synced
+            FROM games g
+            JOIN achievements a ON a.game_hash = g.hash
+            LEFT JOIN user_progress p ON p.achievement_id = a.id
+            LEFT JOIN sync_state s ON s.game_hash = g.hash
+            GROUP BY g.hash
+            HAVING earned > 0 AND earned < total
+            ORDER BY COALESCE(last_unlock, s.last_synced, 0) DESC
+            "#
+        )?;
+
+        let games = stmt
+            .query_map([], |row| {
+                Ok(GameProgress {
+                    hash: row.get(0)?,
+                    game_id: row.get(1)?,
+                    title: row.get(2)?,
+                    earned: row.get(3)?,
+                    total: row.get(4)?,
+                    last_unlock: row.get(5)?,
+                    last_synced: row.get(6)?,
+                })
+            })?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(games)
+    }
+
+    /// Completion of every cached game with achievements, most recently
+    /// unlocked first, for the trophy room
+    pub fn library_progress(&self) -> Result<Vec<LibraryGame>> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT g.hash, g.game_id, g.title, g.console_name, g.icon_url,
+                   SUM(CASE WHEN p.date_earned IS NOT NULL OR p.date_earned_hardcore IS NOT NULL THEN 1 ELSE 0 END) AS earned,
+                   SUM(CASE WHEN p.date_earned_hardcore IS NOT NULL THEN 1 ELSE 0 END) AS earned_hardcore,
+                   COUNT(a.id) AS total,
+                   COALESCE(SUM(CASE WHEN p.date_earned IS NOT NULL OR p.date_earned_hardcore IS NOT NULL THEN a.points ELSE 0 END), 0) AS points_earned,
+                   COALESCE(SUM(a.points), 0) AS points_total,
+                   (SELECT MAX(earned_at) FROM unlock_events e WHERE e.game_hash = g.hash) AS last_unlock
+            FROM games g
+            JOIN achievements a ON a.game_hash = g.hash
+            LEFT JOIN user_progress p ON p.achievement_id = a.id
+            GROUP BY g.hash
+            ORDER BY COALESCE(last_unlock, 0) DESC, g.title
+            "#
+        )?;
+
+        let games = stmt
+            .query_map([], |row| {
+                Ok(LibraryGame {
+                    hash: row.get(0)?,
+                    game_id: row.get(1)?,
+                    title: row.get(2)?,
+                    console_name: row.get(3)?,
+                    icon_url: row.get(4)?,
+                    earned: row.get(5)?,
+                    earned_hardcore: row.get(6)?,
+                    total: row.get(7)?,
+                    points_earned: row.get(8)?,
+                    points_total: row.get(9)?,
+                    last_unlock: row.get(10)?,
+                })
+            })?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(games)
+    }
+
+    /// Every unearned achievement across the cached library, with its rarity
+    /// and when its game was last played (latest unlock or sync)
+    pub fn unearned_achievements(&self) -> Result<Vec<UnearnedAchievement>> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT a.id, a.game_hash, g.title, a.title, a.description, a.points, a.badge_name,
+                   a.num_awarded, g.num_players,
+                   COALESCE((SELECT MAX(earned_at) FROM unlock_events e WHERE e.game_hash = g.hash), s.last_synced)
+            FROM achievements a
+            JOIN games g ON g.hash = a.game_hash
+            LEFT JOIN user_progress p ON p.achievement_id = a.id
+            LEFT JOIN sync_state s ON s.game_hash = g.hash
+            WHERE p.date_earned IS NULL AND p.date_earned_hardcore IS NULL
+            ORDER BY a.id
+            "#
+        )?;
+
+        let achievements = stmt
+            .query_map([], |row| {
+                let num_awarded: Option<u32> = row.get(7)?;
+                let num_players: Option<u32> = row.get(8)?;
+                Ok(UnearnedAchievement {
+                    id: row.get(0)?,
+                    game_hash: row.get(1)?,
+                    game_title: row.get(2)?,
+                    title: row.get(3)?,
+                    description: row.get(4)?,
+                    points: row.get(5)?,
+                    badge_name: row.get(6)?,
+                    rarity_percent: match (num_awarded, num_players) {
+                        (Some(awarded), Some(players)) if players > 0 => {
+                            Some((awarded as f32 / players as f32 * 100.0).min(100.0))
+                        }
+                        _ => None,
+                    },
+                    last_played: row.get(9)?,
+                })
+            })?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(achievements)
+    }
+
+    /// First time an achievement was unlocked in either mode (unix seconds)
+    pub fn first_unlock(&self, achievement_id: u32) -> Result<Option<i64>> {
+        let time = self.conn.query_row(
+            "SELECT MIN(earned_at) FROM unlock_events WHERE achievement_id = ?1",
+            params![achievement_id],
+            |row| row.get::<_, Option<i64>>(0),
+        )?;
+        Ok(time)
+    }
+
+    /// When a game's data was last fetched from RetroAchievements (unix seconds)
+    pub fn last_synced(&self, hash: &str) -> Result<Option<i64>> {
+        let result = self.conn.query_row(
+            "SELECT last_synced FROM sync_state WHERE game_hash = ?1",
+            params![hash],
+            |row| row.get::<_, i64>(0),
+        );
+
+        match result {
+            Ok(time) => Ok(Some(time)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Recompute a game's difficulty estimate from its cached rarity and
+    /// unlock history and store it. None until rarity counts are cached.
+    pub fn update_difficulty(&self, hash: &str) -> Result<Option<DifficultyEstimate>> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT a.num_awarded, g.num_players,
+                   p.date_earned IS NOT NULL OR p.date_earned_hardcore IS NOT NULL
+            FROM achievements a
+            JOIN games g ON g.hash = a.game_hash
+            LEFT JOIN user_progress p ON p.achievement_id = a.id
+            WHERE a.game_hash = ?1
+            "#,
+        )?;
+        let samples: Vec<AchievementSample> = stmt
+            .query_map(params![hash], |row| {
+                let num_awarded: Option<u32> = row.get(0)?;
+                let num_players: Option<u32> = row.get(1)?;
+                Ok(AchievementSample {
+                    rarity_percent: match (num_awarded, num_players) {
+                        (Some(awarded), Some(players)) if players > 0 => {
+                            Some((awarded as f32 / players as f32 * 100.0).min(100.0))
+                        }
+                        _ => None,
+                    },
+                    earned: row.get(2)?,
+                })
+            })?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        // One time per achievement: softcore and hardcore unlocks count once
+        let mut stmt = self.conn.prepare(
+            "SELECT MIN(earned_at) FROM unlock_events WHERE game_hash = ?1 GROUP BY achievement_id",
+        )?;
+        let unlock_times: Vec<i64> = stmt
+            .query_map(params![hash], |row| row.get(0))?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        let Some(estimate) =
+            difficulty::estimate(&samples, &unlock_times, chrono::Utc::now().timestamp())
+        else {
+            return Ok(None);
+        };
+        self.conn
+            .execute(
+                r#"
+            INSERT OR REPLACE INTO difficulty
+                (game_hash, score, remaining, pace_secs, expected_master_secs, computed_at)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+            "#,
+                params![
+                    hash,
+                    estimate.score,
+                    estimate.remaining,
+                    estimate.pace_secs.map(|s| s as i64),
+                    estimate.expected_master_secs.map(|s| s as i64),
+                    estimate.computed_at,
+                ],
+            )
+            .context("Failed to cache difficulty estimate")?;
+
+        Ok(Some(estimate))
+    }
+
+    /// The difficulty estimate cached at the game's last sync
+    pub fn get_difficulty(&self, hash: &str) -> Result<Option<DifficultyEstimate>> {
+        let result = self.conn.query_row(
+            r#"
+            SELECT score, remaining, pace_secs, expected_master_secs, computed_at
+            FROM difficulty WHERE game_hash = ?1
+            "#,
+            params![hash],
+            |row| {
+                Ok(DifficultyEstimate {
+                    score: row.get(0)?,
+                    remaining: row.get(1)?,
+                    pace_secs: row.get::<_, Option<i64>>(2)?.map(|s| s as u64),
+                    expected_master_secs: row.get::<_, Option<i64>>(3)?.map(|s| s as u64),
+                    computed_at: row.get(4)?,
+                })
+            },
+        );
+
+        match result {
+            Ok(estimate) => Ok(Some(estimate)),
+            Err(rusqlite::Error::QueryReturnedNoRows) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Record that a native cart's achievement was unlocked. Returns whether
+    /// it's new; the first unlock time is kept.
+    pub fn record_local_unlock(
+        &self,
+        cart_id: &str,
+        achievement_id: &str,
+        unlocked_at: i64,
+    ) -> Result<bool> {
+        let inserted = self
+            .conn
+            .execute(
+                r#"
+            INSERT OR IGNORE INTO local_unlocks (cart_id, achievement_id, unlocked_at)
+            VALUES (?1, ?2, ?3)
+            "#,
+                params![cart_id, achievement_id, unlocked_at],
+            )
+            .context("Failed to record local unlock")?;
+
+        Ok(inserted > 0)
+    }
+
+    /// A native cart's unlocks, oldest first
+    pub fn local_unlocks(&self, cart_id: &str) -> Result<Vec<LocalUnlock>> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT achievement_id, unlocked_at FROM local_unlocks
+            WHERE cart_id = ?1
+            ORDER BY unlocked_at, achievement_id
+            "#,
+        )?;
+
+        let unlocks = stmt
+            .query_map(params![cart_id], |row| {
+                Ok(LocalUnlock {
+                    achievement_id: row.get(0)?,
+                    unlocked_at: row.get(1)?,
+                })
+            })?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(unlocks)
+    }
+
+    /// What SQLite's `quick_check` (or the slower, more thorough
+    /// `integrity_check`) reports wrong with the database. Empty when sound.
+    pub fn integrity_problems(&self, full: bool) -> Result<Vec<String>> {
+        let pragma = if full {
+            "PRAGMA integrity_check"
+        } else {
+            "PRAGMA quick_check"
+        };
+        let mut stmt = self.conn.prepare(pragma)?;
+        let problems = stmt
+            .query_map([], |row| row.get::<_, String>(0))?
+            .filter_map(|r| r.ok())
+            .filter(|line| line != "ok")
+            .collect();
+        Ok(problems)
+    }
+
+    /// Delete rows left pointing at games or achievements that aren't cached,
+    /// e.g. by a write cut short. Returns how many were removed.
+    pub fn remove_orphans(&self) -> Result<usize> {
+        let statements = [
+            // Children before parents, for the foreign keys
+            "DELETE FROM user_progress WHERE achievement_id NOT IN
+                (SELECT id FROM achievements WHERE game_hash IN (SELECT hash FROM games))",
+            "DELETE FROM unlock_events WHERE achievement_id NOT IN
+                (SELECT id FROM achievements WHERE game_hash IN (SELECT hash FROM games))",
+            "DELETE FROM achievements WHERE game_hash NOT IN (SELECT hash FROM games)",
+            "DELETE FROM sync_state WHERE game_hash NOT IN (SELECT hash FROM games)",
+            "DELETE FROM difficulty WHERE game_hash NOT IN (SELECT hash FROM games)",
+        ];
+        let mut removed = 0;
+        for sql in statements {
+            removed += self
+                .conn
+                .execute(sql, [])
+                .context("Failed to remove orphaned cache rows")?;
+        }
+        Ok(removed)
+    }
+
+    /// Achievements whose cached earned flags (softcore or hardcore) differ
+    /// from what the API returned for the game
+    pub fn earned_mismatches(&self, hash: &str, info: &GameInfoAndProgress) -> Result<Vec<u32>> {
+        let cached: std::collections::HashMap<u32, CachedAchievement> = self
+            .get_achievements(hash)?
+            .into_iter()
+            .map(|a| (a.id, a))
+            .collect();
+
+        let mut mismatches: Vec<u32> = info
+            .achievements
+            .iter()
+            .flat_map(|achievements| achievements.values())
+            .filter(|a| !a.is_unofficial())
+            .filter(|a| {
+                let earned = (a.date_earned.is_some(), a.date_earned_hardcore.is_some());
+                cached
+                    .get(&a.id)
+                    .is_none_or(|c| (c.date_earned.is_some(), c.is_earned_hardcore()) != earned)
+            })
+            .map(|a| a.id)
+            .collect();
+        mismatches.sort_unstable();
+        Ok(mismatches)
+    }
+
+    /// Clear all cached data. Local unlocks stay: unlike the RA data there
+    /// is nowhere to sync them back from.
     pub fn clear(&self) -> Result<()> {
         self.conn.execute_batch(
             r#"
+            DELETE FROM difficulty;
+            DELETE FROM unlock_events;
+            DELETE FROM sync_state;
             DELETE FROM user_progress;
             DELETE FROM achievements;
             DELETE FROM games;
-            "#
+            "#,
         )?;
         Ok(())
     }
@@ -262,6 +1007,119 @@ pub struct CachedAchievement {
     pub date_earned_hardcore: Option<String>,
 }
 
+/// Image references for a cached game
+#[derive(Debug, Clone)]
+pub struct CachedGameImages {
+    pub icon_url: Option<String>,
+    pub badges: Vec<(u32, String)>, // (achievement_id, badge_name)
+}
+
+/// An achievement unlock from the history
+#[derive(Debug, Clone)]
+pub struct UnlockEvent {
+    pub achievement_id: u32,
+    pub game_hash: String,
+    pub game_title: Option<String>,
+    pub title: String,
+    pub points: u32,
+    pub hardcore: bool,
+    /// Unix timestamp (seconds)
+    pub earned_at: i64,
+}
+
+/// An achievement not yet earned, as a challenge candidate
+#[derive(Debug, Clone)]
+pub struct UnearnedAchievement {
+    pub id: u32,
+    pub game_hash: String,
+    pub game_title: String,
+    pub title: String,
+    pub description: Option<String>,
+    pub points: u32,
+    pub badge_name: Option<String>,
+    /// Share of the game's players who have earned it (0-100), once known
+    pub rarity_percent: Option<f32>,
+    /// Unix timestamp of the game's latest unlock or sync
+    pub last_played: Option<i64>,
+}
+
+/// A partially completed game
+#[derive(Debug, Clone)]
+pub struct GameProgress {
+    pub hash: String,
+    pub game_id: u32,
+    pub title: String,
+    pub earned: u32,
+    pub total: u32,
+    /// Unix timestamp of the latest unlock, if any is in the history
+    pub last_unlock: Option<i64>,
+    /// Unix timestamp of the last fetch from RetroAchievements
+    pub last_synced: Option<i64>,
+}
+
+/// A cached game's completion, whatever its progress
+#[derive(Debug, Clone)]
+pub struct LibraryGame {
+    pub hash: String,
+    pub game_id: u32,
+    pub title: String,
+    pub console_name: Option<String>,
+    pub icon_url: Option<String>,
+    pub earned: u32,
+    pub earned_hardcore: u32,
+    pub total: u32,
+    pub points_earned: u32,
+    pub points_total: u32,
+    /// Unix timestamp of the latest unlock, if any is in the history
+    pub last_unlock: Option<i64>,
+}
+
+impl LibraryGame {
+    pub fn is_mastered(&self) -> bool {
+        self.total > 0 && self.earned == self.total
+    }
+
+    /// Mastered with every unlock in hardcore mode
+    pub fn is_mastered_hardcore(&self) -> bool {
+        self.total > 0 && self.earned_hardcore == self.total
+    }
+}
+
+/// A game with every achievement earned
+#[derive(Debug, Clone)]
+pub struct Mastery {
+    pub game_hash: String,
+    pub game_title: String,
+    /// Every achievement was earned in hardcore mode
+    pub hardcore: bool,
+    pub total: u32,
+    /// Points across the whole set
+    pub points: u32,
+    /// Unix timestamps of the first and last unlock in the history
+    pub first_unlock: Option<i64>,
+    pub completed_at: Option<i64>,
+}
+
+impl Mastery {
+    /// Seconds from the first unlock to the last one
+    pub fn completion_secs(&self) -> Option<u64> {
+        let (first, last) = (self.first_unlock?, self.completed_at?);
+        Some(last.saturating_sub(first).max(0) as u64)
+    }
+}
+
+/// Parse an unlock date as written by this cache (RFC 3339) or returned by
+/// the RA API (`2024-01-31 18:04:05`, UTC)
+fn parse_timestamp(date: &str) -> Option<i64> {
+    chrono::DateTime::parse_from_rfc3339(date)
+        .map(|d| d.timestamp())
+        .or_else(|_| {
+            chrono::NaiveDateTime::parse_from_str(date, "%Y-%m-%d %H:%M:%S")
+                .map(|d| d.and_utc().timestamp())
+        })
+        .ok()
+}
+
 impl CachedAchievement {
     pub fn is_earned(&self) -> bool {
         self.date_earned.is_some() || self.date_earned_hardcore.is_some()
@@ -272,3 +1130,651 @@ impl CachedAchievement {
     }
 }
 
+/// On-disk cache of game icons and achievement badges shared by BIOS and overlay
+/// Files are named after their URL path so lookups don't need the database,
+/// and the least recently used files are evicted once the size limit is hit.
+pub struct ImageCache {
+    dir: PathBuf,
+    max_bytes: u64,
+    client: reqwest::blocking::Client,
+    /// Read from disk the first time sizes are needed, then kept up to date
+    /// so a download doesn't rescan the directory
+    index: Mutex<Option<ImageIndex>>,
+}
+
+/// Cached files with their size and last use
+#[derive(Default)]
+struct ImageIndex {
+    files: HashMap<PathBuf, (u64, SystemTime)>,
+    total: u64,
+}
+
+impl ImageIndex {
+    fn insert(&mut self, path: PathBuf, len: u64, used: SystemTime) {
+        if let Some((old, _)) = self.files.insert(path, (len, used)) {
+            self.total -= old;
+        }
+        self.total += len;
+    }
+
+    fn remove(&mut self, path: &Path) {
+        if let Some((len, _)) = self.files.remove(path) {
+            self.total -= len;
+        }
+    }
+}
+
+impl ImageCache {
+    pub fn new() -> Result<Self> {
+        Self::with_dir(Self::default_dir()?, DEFAULT_IMAGE_CACHE_BYTES)
+    }
+
+    /// Where `new` keeps the images
+    pub fn default_dir() -> Result<PathBuf> {
+        Ok(dirs::home_dir()
+            .context("Could not find home directory")?
+            .join(".local/share/kazeta-plus/ra_cache/images"))
+    }
+
+    /// Create an image cache in a specific directory with a size limit
+    pub fn with_dir(dir: PathBuf, max_bytes: u64) -> Result<Self> {
+        fs::create_dir_all(&dir).context("Failed to create image cache directory")?;
+
+        let client = reqwest::blocking::Client::builder()
+            .timeout(std::time::Duration::from_secs(30))
+            .build()
+            .context("Failed to create HTTP client")?;
+
+        Ok(Self {
+            dir,
+            max_bytes,
+            client,
+            index: Mutex::new(None),
+        })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Full URL for a game icon path (e.g. "/Images/000001.png")
+    pub fn icon_url(image_icon: &str) -> String {
+        if image_icon.starts_with("http://") || image_icon.starts_with("https://") {
+            image_icon.to_string()
+        } else {
+            format!("{}/{}", RA_MEDIA_BASE, image_icon.trim_start_matches('/'))
+        }
+    }
+
+    /// Full URL for an achievement badge (locked badges use the "_lock" variant)
+    pub fn badge_url(badge_name: &str, locked: bool) -> String {
+        let suffix = if locked { "_lock" } else { "" };
+        format!("{}/Badge/{}{}.png", RA_MEDIA_BASE, badge_name, suffix)
+    }
+
+    /// Local file path an image URL is cached under
+    pub fn local_path(&self, url: &str) -> PathBuf {
+        let without_scheme = url.split("://").nth(1).unwrap_or(url);
+        let path = without_scheme
+            .split_once('/')
+            .map(|(_, p)| p)
+            .unwrap_or(without_scheme);
+        let name: String = path
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '.' || c == '-' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        self.dir.join(name)
+    }
+
+    /// Get a cached image without downloading it
+    pub fn get(&self, url: &str) -> Option<PathBuf> {
+        let path = self.local_path(url);
+        if path.exists() {
+            touch(&path);
+            if let Some(index) = self.index.lock().unwrap().as_mut() {
+                if let Ok(metadata) = fs::metadata(&path) {
+                    index.insert(path.clone(), metadata.len(), SystemTime::now());
+                }
+            }
+            Some(path)
+        } else {
+            None
+        }
+    }
+
+    /// Return the local path for an image, downloading it first if needed
+    pub fn fetch(&self, url: &str) -> Result<PathBuf> {
+        if let Some(path) = self.get(url) {
+            return Ok(path);
+        }
+
+        let response = self
+            .client
+            .get(url)
+            .send()
+            .context("Failed to download image")?;
+
+        if !response.status().is_success() {
+            bail!("Image download returned error: {}", response.status());
+        }
+
+        if response
+            .content_length()
+            .is_some_and(|len| len > MAX_IMAGE_BYTES)
+        {
+            bail!("Image is larger than {} bytes", MAX_IMAGE_BYTES);
+        }
+        let bytes = read_capped(response, MAX_IMAGE_BYTES)?;
+
+        let path = self.local_path(url);
+        let tmp_path = path.with_extension("part");
+        fs::write(&tmp_path, &bytes).context("Failed to write cached image")?;
+        fs::rename(&tmp_path, &path).context("Failed to store cached image")?;
+
+        self.with_index(|index| index.insert(path.clone(), bytes.len() as u64, SystemTime::now()))?;
+        self.evict()?;
+        Ok(path)
+    }
+
+    /// Total size of all cached images in bytes
+    pub fn size(&self) -> Result<u64> {
+        self.with_index(|index| index.total)
+    }
+
+    /// Delete least recently used images until the cache fits its size limit
+    pub fn evict(&self) -> Result<usize> {
+        self.with_index(|index| {
+            if index.total <= self.max_bytes {
+                return 0;
+            }
+
+            let mut entries: Vec<(PathBuf, SystemTime)> = index
+                .files
+                .iter()
+                .map(|(path, (_, used))| (path.clone(), *used))
+                .collect();
+            entries.sort_by_key(|(_, used)| *used);
+
+            let mut removed = 0;
+            for (path, _) in entries {
+                if index.total <= self.max_bytes {
+                    break;
+                }
+                // Already gone (e.g. cleared by another process) counts as removed
+                match fs::remove_file(&path) {
+                    Ok(()) => removed += 1,
+                    Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                    Err(_) => continue,
+                }
+                index.remove(&path);
+            }
+            removed
+        })
+    }
+
+    /// Remove every cached image
+    pub fn clear(&self) -> Result<()> {
+        Self::clear_dir(&self.dir)?;
+        *self.index.lock().unwrap() = Some(ImageIndex::default());
+        Ok(())
+    }
+
+    /// Remove every cached image in `dir`, without creating it if there's no cache yet
+    pub fn clear_dir(dir: &Path) -> Result<()> {
+        if !dir.exists() {
+            return Ok(());
+        }
+        for (path, _, _) in Self::entries(dir)? {
+            fs::remove_file(&path).ok();
+        }
+        Ok(())
+    }
+
+    /// Run `f` on the index, reading it from disk first if this is the first use
+    fn with_index<T>(&self, f: impl FnOnce(&mut ImageIndex) -> T) -> Result<T> {
+        let mut index = self.index.lock().unwrap();
+        if index.is_none() {
+            let mut scanned = ImageIndex::default();
+            for (path, len, modified) in Self::entries(&self.dir)? {
+                scanned.insert(path, len, modified);
+            }
+            *index = Some(scanned);
+        }
+        Ok(f(index.as_mut().unwrap()))
+    }
+
+    fn entries(dir: &Path) -> Result<Vec<(PathBuf, u64, SystemTime)>> {
+        let mut entries = Vec::new();
+        for entry in fs::read_dir(dir).context("Failed to read image cache directory")? {
+            let entry = entry?;
+            let metadata = entry.metadata()?;
+            if metadata.is_file() {
+                let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+                entries.push((entry.path(), metadata.len(), modified));
+            }
+        }
+        Ok(entries)
+    }
+}
+
+/// Bump a file's mtime so eviction treats it as recently used
+fn touch(path: &Path) {
+    if let Ok(file) = fs::File::options().write(true).open(path) {
+        let _ = file.set_modified(SystemTime::now());
+    }
+}
+
+/// Read a whole body, failing instead of buffering more than `limit` bytes
+fn read_capped(reader: impl Read, limit: u64) -> Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    reader
+        .take(limit + 1)
+        .read_to_end(&mut bytes)
+        .context("Failed to read image data")?;
+    if bytes.len() as u64 > limit {
+        bail!("Image is larger than {} bytes", limit);
+    }
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tempfile::TempDir;
+
+    fn game(id: u32, title: &str, achievements: &[(u32, Option<&str>)]) -> GameInfoAndProgress {
+        let achievements: serde_json::Map<String, serde_json::Value> = achievements
+            .iter()
+            .map(|(aid, earned)| {
+                (
+                    aid.to_string(),
+                    serde_json::json!({
+                        "ID": aid, "Title": format!("Achievement {}", aid), "Description": "",
+                        "Points": 5, "BadgeName": "0", "DisplayOrder": aid,
+                        "DateEarned": earned, "DateEarnedHardcore": null,
+                    }),
+                )
+            })
+            .collect();
+
+        serde_json::from_value(serde_json::json!({
+            "ID": id, "Title": title, "ConsoleID": 7, "ConsoleName": "NES", "ImageIcon": "",
+            "ImageTitle": null, "ImageIngame": null, "ImageBoxArt": null,
+            "NumAchievements": achievements.len(), "NumDistinctPlayersCasual": 0,
+            "NumDistinctPlayersHardcore": 0, "Achievements": achievements,
+            "NumAwardedToUser": null, "NumAwardedToUserHardcore": null,
+            "UserCompletion": null, "UserCompletionHardcore": null,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_parse_timestamp_formats() {
+        assert_eq!(parse_timestamp("2024-01-31 18:04:05"), Some(1706724245));
+        assert_eq!(
+            parse_timestamp("2024-01-31T18:04:05+00:00"),
+            Some(1706724245)
+        );
+        assert_eq!(parse_timestamp("yesterday"), None);
+    }
+
+    #[test]
+    fn test_recent_unlocks_and_games_in_progress() {
+        let tmp = TempDir::new().unwrap();
+        let cache = RACache::open(&tmp.path().join("achievements.db")).unwrap();
+
+        cache
+            .cache_game(
+                "aaa",
+                &game(
+                    1,
+                    "Old Game",
+                    &[(10, Some("2001-01-01 00:00:00")), (11, None)],
+                ),
+            )
+            .unwrap();
+        cache
+            .cache_game(
+                "bbb",
+                &game(2, "Finished", &[(20, Some("2001-01-01 00:00:00"))]),
+            )
+            .unwrap();
+        cache
+            .cache_game("ccc", &game(3, "Untouched", &[(30, None)]))
+            .unwrap();
+        cache.mark_earned(30, true).unwrap();
+
+        let recent = cache.recent_unlocks(30).unwrap();
+        assert_eq!(recent.len(), 1);
+        assert_eq!(recent[0].achievement_id, 30);
+        assert_eq!(recent[0].game_title.as_deref(), Some("Untouched"));
+        assert!(recent[0].hardcore);
+        assert_eq!(cache.recent_unlocks(100 * 365).unwrap().len(), 3);
+
+        // "Finished" is complete; "Untouched" only has progress in the history
+        let in_progress = cache.games_in_progress().unwrap();
+        let titles: Vec<&str> = in_progress.iter().map(|g| g.title.as_str()).collect();
+        assert_eq!(titles, ["Old Game"]);
+        assert_eq!((in_progress[0].earned, in_progress[0].total), (1, 2));
+        assert!(cache.last_synced("aaa").unwrap().is_some());
+        assert_eq!(cache.get_game_hash(2).unwrap().as_deref(), Some("bbb"));
+        assert_eq!(cache.get_game_hash(99).unwrap(), None);
+    }
+
+    #[test]
+    fn test_library_progress() {
+        let tmp = TempDir::new().unwrap();
+        let cache = RACache::open(&tmp.path().join("achievements.db")).unwrap();
+
+        cache
+            .cache_game(
+                "aaa",
+                &game(
+                    1,
+                    "Half Done",
+                    &[(10, Some("2001-01-01 00:00:00")), (11, None)],
+                ),
+            )
+            .unwrap();
+        cache
+            .cache_game("bbb", &game(2, "Mastered", &[(20, None)]))
+            .unwrap();
+        cache
+            .cache_game("ccc", &game(3, "Untouched", &[(30, None)]))
+            .unwrap();
+        cache.mark_earned(20, true).unwrap();
+
+        let games = cache.library_progress().unwrap();
+        let titles: Vec<&str> = games.iter().map(|g| g.title.as_str()).collect();
+        // Latest unlock first, games without unlocks last
+        assert_eq!(titles, ["Mastered", "Half Done", "Untouched"]);
+
+        assert!(games[0].is_mastered() && games[0].is_mastered_hardcore());
+        assert_eq!((games[1].earned, games[1].total), (1, 2));
+        assert_eq!((games[1].points_earned, games[1].points_total), (5, 10));
+        assert!(!games[1].is_mastered());
+        assert_eq!(games[2].earned, 0);
+    }
+
+    #[test]
+    fn test_unlock_history_is_per_game_and_ordered() {
+        let tmp = TempDir::new().unwrap();
+        let cache = RACache::open(&tmp.path().join("achievements.db")).unwrap();
+
+        cache
+            .cache_game(
+                "aaa",
+                &game(
+                    1,
+                    "First",
+                    &[
+                        (10, Some("2001-01-02 00:00:00")),
+                        (11, Some("2001-01-01 00:00:00")),
+                        (12, None),
+                    ],
+                ),
+            )
+            .unwrap();
+        cache
+            .cache_game(
+                "bbb",
+                &game(2, "Other", &[(20, Some("2001-01-01 00:00:00"))]),
+            )
+            .unwrap();
+
+        let history = cache.unlock_history("aaa").unwrap();
+        let ids: Vec<u32> = history.iter().map(|e| e.achievement_id).collect();
+        assert_eq!(ids, [11, 10]);
+        assert!(history[0].earned_at < history[1].earned_at);
+        assert!(cache.unlock_history("missing").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_mastery_is_hardcore_aware() {
+        let tmp = TempDir::new().unwrap();
+        let cache = RACache::open(&tmp.path().join("achievements.db")).unwrap();
+
+        cache
+            .cache_game(
+                "aaa",
+                &game(
+                    1,
+                    "Nearly",
+                    &[(10, Some("2001-01-01 00:00:00")), (11, None)],
+                ),
+            )
+            .unwrap();
+        assert!(cache.get_mastery("aaa").unwrap().is_none());
+        assert!(cache.get_mastery("missing").unwrap().is_none());
+
+        cache
+            .cache_game(
+                "bbb",
+                &game(
+                    2,
+                    "Done",
+                    &[
+                        (20, Some("2001-01-01 00:00:00")),
+                        (21, Some("2001-01-01 02:00:00")),
+                    ],
+                ),
+            )
+            .unwrap();
+        let mastery = cache.get_mastery("bbb").unwrap().unwrap();
+        assert_eq!(mastery.game_title, "Done");
+        assert!(!mastery.hardcore);
+        assert_eq!((mastery.total, mastery.points), (2, 10));
+        assert_eq!(mastery.completion_secs(), Some(7200));
+
+        // Finishing the set in hardcore only times the hardcore unlocks
+        cache.mark_earned(10, true).unwrap();
+        cache.mark_earned(11, true).unwrap();
+        let mastery = cache.get_mastery("aaa").unwrap().unwrap();
+        assert!(mastery.hardcore);
+        assert!(mastery.completion_secs().unwrap() < 60);
+    }
+
+    #[test]
+    fn test_unearned_achievements_across_library() {
+        let tmp = TempDir::new().unwrap();
+        let cache = RACache::open(&tmp.path().join("achievements.db")).unwrap();
+
+        cache
+            .cache_game(
+                "aaa",
+                &game(1, "First", &[(10, Some("2001-01-01 00:00:00")), (11, None)]),
+            )
+            .unwrap();
+        cache
+            .cache_game("bbb", &game(2, "Second", &[(20, None)]))
+            .unwrap();
+
+        let unearned = cache.unearned_achievements().unwrap();
+        let ids: Vec<u32> = unearned.iter().map(|a| a.id).collect();
+        assert_eq!(ids, [11, 20]);
+        assert_eq!(unearned[0].game_title, "First");
+        // No players counted, so no rarity; the sync counts as playing
+        assert_eq!(unearned[1].rarity_percent, None);
+        assert!(unearned[1].last_played.is_some());
+
+        assert_eq!(cache.first_unlock(10).unwrap(), Some(978307200));
+        assert_eq!(cache.first_unlock(11).unwrap(), None);
+        cache.mark_earned(11, false).unwrap();
+        assert!(cache.first_unlock(11).unwrap().is_some());
+        assert_eq!(cache.unearned_achievements().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_difficulty_is_cached_per_game() {
+        let tmp = TempDir::new().unwrap();
+        let cache = RACache::open(&tmp.path().join("achievements.db")).unwrap();
+
+        // Without player counts there's no rarity to go on
+        cache
+            .cache_game(
+                "aaa",
+                &game(1, "First", &[(10, Some("2001-01-01 00:00:00")), (11, None)]),
+            )
+            .unwrap();
+        assert_eq!(cache.update_difficulty("aaa").unwrap(), None);
+        assert_eq!(cache.get_difficulty("aaa").unwrap(), None);
+
+        let mut info = game(
+            1,
+            "First",
+            &[
+                (10, Some("2001-01-01 00:00:00")),
+                (11, Some("2001-01-01 00:10:00")),
+                (12, None),
+            ],
+        );
+        info.num_players_casual = 100;
+        for achievement in info.achievements.as_mut().unwrap().values_mut() {
+            achievement.num_awarded = Some(10);
+        }
+        cache.cache_game("aaa", &info).unwrap();
+
+        let estimate = cache.update_difficulty("aaa").unwrap().unwrap();
+        assert_eq!(estimate.label(), "Hard");
+        assert_eq!(estimate.remaining, 1);
+        assert_eq!(estimate.pace_secs, Some(600));
+        assert_eq!(estimate.expected_master_secs, Some(600));
+        assert_eq!(cache.get_difficulty("aaa").unwrap(), Some(estimate));
+    }
+
+    #[test]
+    fn test_version_1_cache_is_imported() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("achievements.db");
+        {
+            let conn = Connection::open(&path).unwrap();
+            conn.execute_batch(
+                r#"
+                CREATE TABLE games (hash TEXT PRIMARY KEY, game_id INTEGER NOT NULL, title TEXT NOT NULL,
+                    console_id INTEGER NOT NULL, console_name TEXT, icon_url TEXT,
+                    num_achievements INTEGER DEFAULT 0, last_updated TEXT NOT NULL);
+                CREATE TABLE achievements (id INTEGER PRIMARY KEY, game_hash TEXT NOT NULL, title TEXT NOT NULL,
+                    description TEXT, points INTEGER DEFAULT 0, badge_name TEXT, display_order INTEGER DEFAULT 0);
+                CREATE TABLE user_progress (achievement_id INTEGER PRIMARY KEY, date_earned TEXT,
+                    date_earned_hardcore TEXT);
+                INSERT INTO games VALUES ('aaa', 1, 'Old Game', 7, 'NES', NULL, 2, '2001-01-01T00:00:00+00:00');
+                INSERT INTO achievements VALUES (10, 'aaa', 'First', NULL, 5, NULL, 0);
+                INSERT INTO achievements VALUES (11, 'aaa', 'Second', NULL, 5, NULL, 1);
+                INSERT INTO user_progress VALUES (10, '2001-01-01 00:00:00', '2001-01-02T00:00:00+00:00');
+                INSERT INTO user_progress VALUES (11, NULL, NULL);
+                "#,
+            ).unwrap();
+        }
+
+        let cache = RACache::open(&path).unwrap();
+        let history = cache.recent_unlocks(100 * 365).unwrap();
+        assert_eq!(history.len(), 2);
+        assert!(history[0].hardcore);
+        assert_eq!(cache.games_in_progress().unwrap().len(), 1);
+        // The rarity columns were added
+        assert_eq!(cache.unearned_achievements().unwrap().len(), 1);
+
+        // Reopening doesn't import twice
+        drop(cache);
+        let cache = RACache::open(&path).unwrap();
+        assert_eq!(cache.recent_unlocks(100 * 365).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_local_path_is_flat_and_stable() {
+        let tmp = TempDir::new().unwrap();
+        let cache =
+            ImageCache::with_dir(tmp.path().to_path_buf(), DEFAULT_IMAGE_CACHE_BYTES).unwrap();
+
+        let icon = cache.local_path(&ImageCache::icon_url("/Images/000123.png"));
+        assert_eq!(icon, tmp.path().join("Images_000123.png"));
+
+        let badge = cache.local_path(&ImageCache::badge_url("12345", true));
+        assert_eq!(badge, tmp.path().join("Badge_12345_lock.png"));
+    }
+
+    #[test]
+    fn test_evict_removes_least_recently_used() {
+        let tmp = TempDir::new().unwrap();
+        let cache = ImageCache::with_dir(tmp.path().to_path_buf(), 10).unwrap();
+
+        let old = tmp.path().join("old.png");
+        let new = tmp.path().join("new.png");
+        fs::write(&old, [0u8; 8]).unwrap();
+        fs::write(&new, [0u8; 8]).unwrap();
+        let file = fs::File::options().write(true).open(&old).unwrap();
+        file.set_modified(SystemTime::now() - Duration::from_secs(3600))
+            .unwrap();
+
+        assert_eq!(cache.evict().unwrap(), 1);
+        assert!(!old.exists());
+        assert!(new.exists());
+        assert_eq!(cache.size().unwrap(), 8);
+    }
+
+    #[test]
+    fn test_read_capped_stops_at_the_limit() {
+        assert_eq!(read_capped(&[7u8; 16][..], 16).unwrap(), vec![7u8; 16]);
+        assert!(read_capped(&[7u8; 17][..], 16).is_err());
+        // Only one byte past the limit is ever read from an endless body
+        assert!(read_capped(std::io::repeat(0), 1024).is_err());
+    }
+
+    #[test]
+    fn test_size_is_tracked_after_the_first_scan() {
+        let tmp = TempDir::new().unwrap();
+        let cache = ImageCache::with_dir(tmp.path().to_path_buf(), 10).unwrap();
+        fs::write(tmp.path().join("a.png"), [0u8; 4]).unwrap();
+        assert_eq!(cache.size().unwrap(), 4);
+
+        // Files written behind the cache's back aren't seen until the next start
+        fs::write(tmp.path().join("b.png"), [0u8; 4]).unwrap();
+        assert_eq!(cache.size().unwrap(), 4);
+        assert_eq!(
+            ImageCache::with_dir(tmp.path().to_path_buf(), 10)
+                .unwrap()
+                .size()
+                .unwrap(),
+            8
+        );
+
+        // Looking an image up marks it used and counts it
+        let url = format!("{}/b.png", RA_MEDIA_BASE);
+        assert_eq!(cache.get(&url), Some(tmp.path().join("b.png")));
+        assert_eq!(cache.size().unwrap(), 8);
+
+        // Images gone from disk still leave the index when evicted
+        cache
+            .with_index(|index| {
+                index.insert(tmp.path().join("gone.png"), 6, SystemTime::UNIX_EPOCH)
+            })
+            .unwrap();
+        assert_eq!(cache.evict().unwrap(), 0);
+        assert_eq!(cache.size().unwrap(), 8);
+
+        cache.clear().unwrap();
+        assert_eq!(cache.size().unwrap(), 0);
+        assert_eq!(fs::read_dir(tmp.path()).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_clear_dir_does_not_create_the_cache() {
+        let tmp = TempDir::new().unwrap();
+        let dir = tmp.path().join("images");
+        ImageCache::clear_dir(&dir).unwrap();
+        assert!(!dir.exists());
+
+        fs::create_dir(&dir).unwrap();
+        fs::write(dir.join("a.png"), [0u8; 4]).unwrap();
+        ImageCache::clear_dir(&dir).unwrap();
+        assert!(dir.exists());
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 0);
+    }
+}
@@ -0,0 +1,548 @@
+//! Daily and weekly challenges
+//!
+//! A challenge set is a handful of unearned achievements drawn from every
+//! game in the local cache. Rarer achievements and games played recently are
+//! more likely to be drawn, and no game gets more than `MAX_PER_GAME` picks.
+//! Sets run until the end of their UTC day or ISO week. Finishing every
+//! challenge of a set before it ends adds one to that period's streak; a
+//! period that ends without a finished set resets it.
+//!
+//! The active sets and streaks are kept in `ra_challenges.json` next to the
+//! other kazeta-ra settings.
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+use crate::cache::UnearnedAchievement;
+
+const DAY_SECS: i64 = 86_400;
+
+/// Most challenges one game can get in a set
+pub const MAX_PER_GAME: usize = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChallengePeriod {
+    Daily,
+    Weekly,
+}
+
+impl ChallengePeriod {
+    pub const ALL: [ChallengePeriod; 2] = [ChallengePeriod::Daily, ChallengePeriod::Weekly];
+
+    pub fn parse(name: &str) -> Result<Self> {
+        match name.to_lowercase().as_str() {
+            "daily" | "day" => Ok(ChallengePeriod::Daily),
+            "weekly" | "week" => Ok(ChallengePeriod::Weekly),
+            _ => bail!(
+                "Unknown challenge period '{}'. Use 'daily' or 'weekly'.",
+                name
+            ),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ChallengePeriod::Daily => "daily",
+            ChallengePeriod::Weekly => "weekly",
+        }
+    }
+
+    /// Number of the day or ISO week containing `now` (unix seconds)
+    pub fn index(&self, now: i64) -> i64 {
+        let day = now.div_euclid(DAY_SECS);
+        match self {
+            ChallengePeriod::Daily => day,
+            // 1970-01-01 was a Thursday; weeks start on Monday
+            ChallengePeriod::Weekly => (day + 3).div_euclid(7),
+        }
+    }
+
+    /// Start and end (unix seconds) of period number `index`
+    pub fn bounds(&self, index: i64) -> (i64, i64) {
+        match self {
+            ChallengePeriod::Daily => (index * DAY_SECS, (index + 1) * DAY_SECS),
+            ChallengePeriod::Weekly => {
+                let start = (index * 7 - 3) * DAY_SECS;
+                (start, start + 7 * DAY_SECS)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Challenge {
+    pub achievement_id: u32,
+    pub game_hash: String,
+    pub game_title: String,
+    pub title: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    pub points: u32,
+    #[serde(default)]
+    pub badge_name: Option<String>,
+    #[serde(default)]
+    pub rarity_percent: Option<f32>,
+    /// When it was unlocked, if within the set's period
+    #[serde(default)]
+    pub completed_at: Option<i64>,
+}
+
+impl From<&UnearnedAchievement> for Challenge {
+    fn from(a: &UnearnedAchievement) -> Self {
+        Self {
+            achievement_id: a.id,
+            game_hash: a.game_hash.clone(),
+            game_title: a.game_title.clone(),
+            title: a.title.clone(),
+            description: a.description.clone(),
+            points: a.points,
+            badge_name: a.badge_name.clone(),
+            rarity_percent: a.rarity_percent,
+            completed_at: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChallengeSet {
+    /// Day or week number, see `ChallengePeriod::index`
+    pub index: i64,
+    pub starts_at: i64,
+    pub ends_at: i64,
+    /// How often this period's set was redrawn, so a reroll draws differently
+    #[serde(default)]
+    pub rerolls: u32,
+    pub challenges: Vec<Challenge>,
+}
+
+impl ChallengeSet {
+    pub fn completed(&self) -> usize {
+        self.challenges
+            .iter()
+            .filter(|c| c.completed_at.is_some())
+            .count()
+    }
+
+    pub fn is_complete(&self) -> bool {
+        !self.challenges.is_empty() && self.completed() == self.challenges.len()
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Streak {
+    pub current: u32,
+    pub best: u32,
+    /// Period index of the last finished set
+    #[serde(default)]
+    pub last_index: Option<i64>,
+}
+
+impl Streak {
+    /// Count a finished set. Returns false if it was already counted.
+    fn record(&mut self, index: i64) -> bool {
+        if self.last_index == Some(index) {
+            return false;
+        }
+        self.current = if self.last_index == Some(index - 1) {
+            self.current + 1
+        } else {
+            1
+        };
+        self.best = self.best.max(self.current);
+        self.last_index = Some(index);
+        true
+    }
+
+    /// Break the streak if neither this period nor the one before was finished
+    fn expire(&mut self, index: i64) {
+        if self.last_index.is_none_or(|last| last < index - 1) {
+            self.current = 0;
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PeriodState {
+    #[serde(default)]
+    pub set: Option<ChallengeSet>,
+    #[serde(default)]
+    pub streak: Streak,
+}
+
+/// What changed during a refresh
+#[derive(Debug, Default, PartialEq)]
+pub struct RefreshOutcome {
+    /// Challenges unlocked since the last refresh
+    pub completed: Vec<(ChallengePeriod, Challenge)>,
+    /// Periods whose whole set was just finished
+    pub finished_sets: Vec<ChallengePeriod>,
+    /// Periods that got a new set
+    pub new_sets: Vec<ChallengePeriod>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ChallengeState {
+    #[serde(default)]
+    pub daily: PeriodState,
+    #[serde(default)]
+    pub weekly: PeriodState,
+}
+
+impl ChallengeState {
+    /// Load the saved challenges, or an empty state if there are none
+    pub fn load() -> Result<Self> {
+        let path = Self::get_path()?;
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(&path).context("Failed to read challenges file")?;
+        serde_json::from_str(&content).context("Failed to parse challenges JSON")
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = Self::get_path()?;
+        let json = serde_json::to_string_pretty(self).context("Failed to serialize challenges")?;
+        fs::write(&path, json).context("Failed to write challenges file")?;
+        Ok(())
+    }
+
+    fn get_path() -> Result<PathBuf> {
+        let data_dir = dirs::home_dir()
+            .context("Could not find home directory")?
+            .join(".local/share/kazeta-plus");
+
+        fs::create_dir_all(&data_dir).context("Failed to create kazeta data directory")?;
+
+        Ok(data_dir.join("ra_challenges.json"))
+    }
+
+    pub fn period(&self, period: ChallengePeriod) -> &PeriodState {
+        match period {
+            ChallengePeriod::Daily => &self.daily,
+            ChallengePeriod::Weekly => &self.weekly,
+        }
+    }
+
+    fn period_mut(&mut self, period: ChallengePeriod) -> &mut PeriodState {
+        match period {
+            ChallengePeriod::Daily => &mut self.daily,
+            ChallengePeriod::Weekly => &mut self.weekly,
+        }
+    }
+
+    /// Bring a period up to date: mark unlocked challenges (`first_unlock`
+    /// gives an achievement's unlock time), count a finished set towards the
+    /// streak, and replace an expired set with a new draw of `count`
+    /// challenges. A count of 0 turns the period off.
+    pub fn refresh(
+        &mut self,
+        period: ChallengePeriod,
+        now: i64,
+        count: usize,
+        candidates: &[UnearnedAchievement],
+        first_unlock: impl Fn(u32) -> Option<i64>,
+        outcome: &mut RefreshOutcome,
+    ) {
+        let index = period.index(now);
+        let state = self.period_mut(period);
+
+        if let Some(set) = &mut state.set {
+            for challenge in set
+                .challenges
+                .iter_mut()
+                .filter(|c| c.completed_at.is_none())
+            {
+                let unlocked = first_unlock(challenge.achievement_id)
+                    .filter(|&at| at >= set.starts_at && at < set.ends_at);
+                if let Some(at) = unlocked {
+                    challenge.completed_at = Some(at);
+                    outcome.completed.push((period, challenge.clone()));
+                }
+            }
+            if set.is_complete() && state.streak.record(set.index) {
+                outcome.finished_sets.push(period);
+            }
+            if set.index != index {
+                state.set = None;
+            }
+        }
+        state.streak.expire(index);
+
+        if count == 0 {
+            state.set = None;
+        } else if state.set.is_none() {
+            state.set = Some(draw(period, index, 0, count, candidates, now));
+            outcome.new_sets.push(period);
+        }
+    }
+
+    /// Replace the current set with a new draw. The streak is untouched.
+    pub fn reroll(
+        &mut self,
+        period: ChallengePeriod,
+        now: i64,
+        count: usize,
+        candidates: &[UnearnedAchievement],
+    ) {
+        let index = period.index(now);
+        let state = self.period_mut(period);
+        let rerolls = state
+            .set
+            .as_ref()
+            .filter(|set| set.index == index)
+            .map_or(0, |set| set.rerolls + 1);
+        state.set = Some(draw(period, index, rerolls, count, candidates, now));
+    }
+}
+
+/// How likely an achievement is to be drawn. Rarity counts from 0.5 (everyone
+/// has it) to 1.5 (nobody does); a game's pull halves over two weeks away.
+pub fn weight(candidate: &UnearnedAchievement, now: i64) -> f64 {
+    let rarity = candidate.rarity_percent.map_or(1.0, |percent| {
+        1.5 - (percent as f64 / 100.0).clamp(0.0, 1.0)
+    });
+    let recency = candidate.last_played.map_or(0.25, |played| {
+        let days = (now - played).max(0) as f64 / DAY_SECS as f64;
+        1.0 / (1.0 + days / 14.0)
+    });
+    rarity * recency
+}
+
+/// Weighted draw without replacement. The seed comes from the period, so a
+/// set is reproducible until it is rerolled.
+fn draw(
+    period: ChallengePeriod,
+    index: i64,
+    rerolls: u32,
+    count: usize,
+    candidates: &[UnearnedAchievement],
+    now: i64,
+) -> ChallengeSet {
+    let period_tag = match period {
+        ChallengePeriod::Daily => 1,
+        ChallengePeriod::Weekly => 2,
+    };
+    let mut rng = SplitMix64((index as u64) << 8 ^ (rerolls as u64) << 2 ^ period_tag);
+
+    // Efraimidis-Spirakis: the largest u^(1/w) keys are a weighted sample
+    let mut keyed: Vec<(f64, &UnearnedAchievement)> = candidates
+        .iter()
+        .map(|c| (rng.next_f64().powf(1.0 / weight(c, now).max(1e-6)), c))
+        .collect();
+    keyed.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+    let mut challenges: Vec<Challenge> = Vec::new();
+    for (_, candidate) in keyed {
+        if challenges.len() == count {
+            break;
+        }
+        let from_game = challenges
+            .iter()
+            .filter(|c| c.game_hash == candidate.game_hash)
+            .count();
+        if from_game < MAX_PER_GAME {
+            challenges.push(Challenge::from(candidate));
+        }
+    }
+
+    let (starts_at, ends_at) = period.bounds(index);
+    ChallengeSet {
+        index,
+        starts_at,
+        ends_at,
+        rerolls,
+        challenges,
+    }
+}
+
+/// Small deterministic generator for the draw
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform in (0, 1]
+    fn next_f64(&mut self) -> f64 {
+        ((self.next_u64() >> 11) + 1) as f64 / (1u64 << 53) as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(
+        id: u32,
+        game: &str,
+        rarity: Option<f32>,
+        last_played: Option<i64>,
+    ) -> UnearnedAchievement {
+        UnearnedAchievement {
+            id,
+            game_hash: game.to_string(),
+            game_title: game.to_uppercase(),
+            title: format!("Achievement {}", id),
+            description: None,
+            points: 10,
+            badge_name: None,
+            rarity_percent: rarity,
+            last_played,
+        }
+    }
+
+    #[test]
+    fn test_period_bounds() {
+        // Wednesday 2024-01-31 18:04:05 UTC
+        let now = 1706724245;
+        let (start, end) = ChallengePeriod::Daily.bounds(ChallengePeriod::Daily.index(now));
+        assert_eq!((start, end), (1706659200, 1706745600));
+
+        // The week runs from Monday 2024-01-29 to Monday 2024-02-05
+        let (start, end) = ChallengePeriod::Weekly.bounds(ChallengePeriod::Weekly.index(now));
+        assert_eq!((start, end), (1706486400, 1707091200));
+        assert_eq!(
+            ChallengePeriod::Weekly.index(start),
+            ChallengePeriod::Weekly.index(end - 1)
+        );
+        assert_ne!(
+            ChallengePeriod::Weekly.index(start),
+            ChallengePeriod::Weekly.index(end)
+        );
+
+        assert_eq!(
+            ChallengePeriod::parse("Weekly").unwrap(),
+            ChallengePeriod::Weekly
+        );
+        assert!(ChallengePeriod::parse("monthly").is_err());
+    }
+
+    #[test]
+    fn test_weight_prefers_rare_and_recent() {
+        let now = 100 * DAY_SECS;
+        let common = candidate(1, "a", Some(90.0), Some(now));
+        let rare = candidate(2, "a", Some(2.0), Some(now));
+        let stale = candidate(3, "b", Some(2.0), Some(now - 60 * DAY_SECS));
+        assert!(weight(&rare, now) > weight(&common, now));
+        assert!(weight(&rare, now) > weight(&stale, now));
+        assert!(weight(&candidate(4, "c", None, None), now) > 0.0);
+    }
+
+    #[test]
+    fn test_draw_is_reproducible_and_spread_across_games() {
+        let now = 100 * DAY_SECS;
+        let candidates: Vec<_> = (0..10)
+            .map(|i| candidate(i, if i < 8 { "big" } else { "small" }, None, Some(now)))
+            .collect();
+
+        let set = draw(ChallengePeriod::Daily, 100, 0, 3, &candidates, now);
+        assert_eq!(
+            set,
+            draw(ChallengePeriod::Daily, 100, 0, 3, &candidates, now)
+        );
+        assert_eq!(set.challenges.len(), 3);
+        let from_big = set
+            .challenges
+            .iter()
+            .filter(|c| c.game_hash == "big")
+            .count();
+        assert_eq!(from_big, MAX_PER_GAME);
+
+        // Fewer candidates than asked for
+        assert_eq!(
+            draw(ChallengePeriod::Weekly, 1, 0, 5, &candidates[8..], now)
+                .challenges
+                .len(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_streak_across_periods() {
+        let day = ChallengePeriod::Daily;
+        let candidates = vec![
+            candidate(1, "a", None, Some(0)),
+            candidate(2, "b", None, Some(0)),
+        ];
+        let mut state = ChallengeState::default();
+        let mut outcome = RefreshOutcome::default();
+
+        // Day 10: draw both, unlock both
+        let now = 10 * DAY_SECS + 100;
+        state.refresh(day, now, 2, &candidates, |_| None, &mut outcome);
+        assert_eq!(outcome.new_sets, [day]);
+        state.refresh(
+            day,
+            now + 50,
+            2,
+            &candidates,
+            |_| Some(now + 10),
+            &mut outcome,
+        );
+        assert_eq!(outcome.completed.len(), 2);
+        assert_eq!(outcome.finished_sets, [day]);
+        assert_eq!(state.daily.streak.current, 1);
+
+        // Refreshing again doesn't count the set twice
+        state.refresh(
+            day,
+            now + 60,
+            2,
+            &candidates,
+            |_| Some(now + 10),
+            &mut outcome,
+        );
+        assert_eq!(state.daily.streak.current, 1);
+
+        // Day 11: finished too
+        let now = 11 * DAY_SECS + 100;
+        state.refresh(day, now, 2, &candidates, |_| None, &mut outcome);
+        assert_eq!(state.daily.set.as_ref().unwrap().index, 11);
+        state.refresh(
+            day,
+            now + 5,
+            2,
+            &candidates,
+            |_| Some(now + 1),
+            &mut outcome,
+        );
+        assert_eq!(
+            (state.daily.streak.current, state.daily.streak.best),
+            (2, 2)
+        );
+
+        // Day 12 isn't finished, so by day 13 the streak is gone
+        state.refresh(day, 12 * DAY_SECS, 2, &candidates, |_| None, &mut outcome);
+        assert_eq!(state.daily.streak.current, 2);
+        state.refresh(day, 13 * DAY_SECS, 2, &candidates, |_| None, &mut outcome);
+        assert_eq!(
+            (state.daily.streak.current, state.daily.streak.best),
+            (0, 2)
+        );
+
+        // An unlock from before the set started doesn't count
+        state.refresh(
+            day,
+            13 * DAY_SECS + 9,
+            2,
+            &candidates,
+            |_| Some(12 * DAY_SECS),
+            &mut outcome,
+        );
+        assert_eq!(state.daily.set.as_ref().unwrap().completed(), 0);
+
+        // A reroll draws again for the same day
+        state.reroll(day, 13 * DAY_SECS + 10, 1, &candidates);
+        let set = state.daily.set.as_ref().unwrap();
+        assert_eq!((set.index, set.rerolls, set.challenges.len()), (13, 1, 1));
+    }
+}
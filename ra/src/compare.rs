@@ -0,0 +1,173 @@
+// Side-by-side achievement comparison against another user
+
+use crate::types::GameInfoAndProgress;
+use serde::{Deserialize, Serialize};
+
+/// One achievement with both players' unlock state
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AchievementComparison {
+    pub id: u32,
+    pub title: String,
+    pub points: u32,
+    pub mine: bool,
+    pub theirs: bool,
+}
+
+/// Our progress in a game next to a friend's
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProgressComparison {
+    pub game_id: u32,
+    pub game_title: String,
+    pub friend: String,
+    pub achievements: Vec<AchievementComparison>,
+}
+
+impl ProgressComparison {
+    /// Build a comparison from two progress lookups of the same game
+    pub fn new(mine: &GameInfoAndProgress, theirs: &GameInfoAndProgress, friend: &str) -> Self {
+        let their_map = theirs.achievements.as_ref();
+
+        let mut achievements: Vec<(u32, AchievementComparison)> = mine
+            .achievements
+            .as_ref()
+            .map(|map| {
+                map.values()
+                    .map(|a| {
+                        let theirs = their_map
+                            .and_then(|m| m.get(&a.id.to_string()))
+                            .is_some_and(|t| t.is_earned());
+                        (
+                            a.display_order,
+                            AchievementComparison {
+                                id: a.id,
+                                title: a.title.clone(),
+                                points: a.points,
+                                mine: a.is_earned(),
+                                theirs,
+                            },
+                        )
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        achievements.sort_by_key(|(order, a)| (*order, a.id));
+
+        Self {
+            game_id: mine.id,
+            game_title: mine.title.clone(),
+            friend: friend.to_string(),
+            achievements: achievements.into_iter().map(|(_, a)| a).collect(),
+        }
+    }
+
+    pub fn total(&self) -> usize {
+        self.achievements.len()
+    }
+
+    pub fn mine_earned(&self) -> usize {
+        self.achievements.iter().filter(|a| a.mine).count()
+    }
+
+    pub fn theirs_earned(&self) -> usize {
+        self.achievements.iter().filter(|a| a.theirs).count()
+    }
+
+    pub fn mine_points(&self) -> u32 {
+        self.achievements
+            .iter()
+            .filter(|a| a.mine)
+            .map(|a| a.points)
+            .sum()
+    }
+
+    pub fn theirs_points(&self) -> u32 {
+        self.achievements
+            .iter()
+            .filter(|a| a.theirs)
+            .map(|a| a.points)
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Achievement;
+    use std::collections::HashMap;
+
+    fn achievement(id: u32, order: u32, points: u32, earned: bool) -> Achievement {
+        Achievement {
+            id,
+            title: format!("Achievement {}", id),
+            description: String::new(),
+            points,
+            badge_name: String::new(),
+            display_order: order,
+            date_earned: earned.then(|| "2024-01-01 00:00:00".to_string()),
+            date_earned_hardcore: None,
+            flags: None,
+            num_awarded: None,
+        }
+    }
+
+    fn progress(achievements: Vec<Achievement>) -> GameInfoAndProgress {
+        GameInfoAndProgress {
+            id: 7,
+            title: "Test Game".to_string(),
+            console_id: 5,
+            console_name: "Game Boy Advance".to_string(),
+            image_icon: String::new(),
+            image_title: None,
+            image_ingame: None,
+            image_boxart: None,
+            num_achievements: achievements.len() as u32,
+            num_players_casual: 0,
+            num_players_hardcore: 0,
+            achievements: Some(
+                achievements
+                    .into_iter()
+                    .map(|a| (a.id.to_string(), a))
+                    .collect::<HashMap<_, _>>(),
+            ),
+            num_awarded_to_user: None,
+            num_awarded_to_user_hardcore: None,
+            user_completion: None,
+            user_completion_hardcore: None,
+        }
+    }
+
+    #[test]
+    fn test_comparison_merges_unlocks_in_display_order() {
+        let mine = progress(vec![
+            achievement(10, 2, 5, true),
+            achievement(11, 1, 10, false),
+            achievement(12, 3, 25, true),
+        ]);
+        let theirs = progress(vec![
+            achievement(10, 2, 5, false),
+            achievement(11, 1, 10, true),
+            achievement(12, 3, 25, true),
+        ]);
+
+        let comparison = ProgressComparison::new(&mine, &theirs, "friend");
+
+        let ids: Vec<u32> = comparison.achievements.iter().map(|a| a.id).collect();
+        assert_eq!(ids, vec![11, 10, 12]);
+        assert_eq!(comparison.mine_earned(), 2);
+        assert_eq!(comparison.theirs_earned(), 2);
+        assert_eq!(comparison.mine_points(), 30);
+        assert_eq!(comparison.theirs_points(), 35);
+    }
+
+    #[test]
+    fn test_friend_without_progress() {
+        let mine = progress(vec![achievement(1, 1, 5, true)]);
+        let mut theirs = progress(Vec::new());
+        theirs.achievements = None;
+
+        let comparison = ProgressComparison::new(&mine, &theirs, "friend");
+        assert_eq!(comparison.total(), 1);
+        assert_eq!(comparison.theirs_earned(), 0);
+    }
+}
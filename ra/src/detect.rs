@@ -0,0 +1,476 @@
+use crate::types::ConsoleId;
+use anyhow::{bail, Context, Result};
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+/// Bytes read from the start of the file; covers the SNES HiROM header
+/// behind a 512-byte copier header
+const SCAN_BYTES: u64 = 0x10200;
+
+/// Largest CD image; bigger PlayStation discs must be DVDs (PS2)
+const CD_MAX_BYTES: u64 = 900 * 1024 * 1024;
+
+/// Score given to an extension that only one console uses
+const EXTENSION_SCORE: u32 = 40;
+
+/// Score given to generic extensions like .bin or .iso
+const WEAK_EXTENSION_SCORE: u32 = 10;
+
+/// Nintendo logo in Game Boy / Game Boy Color headers (0x104-0x133)
+const GB_LOGO: [u8; 48] = [
+    0xCE, 0xED, 0x66, 0x66, 0xCC, 0x0D, 0x00, 0x0B, 0x03, 0x73, 0x00, 0x83, 0x00, 0x0C, 0x00, 0x0D,
+    0x00, 0x08, 0x11, 0x1F, 0x88, 0x89, 0x00, 0x0E, 0xDC, 0xCC, 0x6E, 0xE6, 0xDD, 0xDD, 0xD9, 0x99,
+    0xBB, 0xBB, 0x67, 0x63, 0x6E, 0x0E, 0xEC, 0xCC, 0xDD, 0xDC, 0x99, 0x9F, 0xBB, 0xB9, 0x33, 0x3E,
+];
+
+/// First bytes of the compressed Nintendo logo in GBA (0x04) and DS (0xC0) headers
+const GBA_LOGO_PREFIX: [u8; 8] = [0x24, 0xFF, 0xAE, 0x51, 0x69, 0x9A, 0xA2, 0x21];
+
+/// Result of console detection, with the evidence behind it
+#[derive(Debug, Clone)]
+pub struct Detection {
+    pub console: ConsoleId,
+    /// 0-100
+    pub confidence: u8,
+    /// Why this console was chosen
+    pub reasons: Vec<String>,
+    /// Other consoles that matched, best first, with their confidence
+    pub alternatives: Vec<(ConsoleId, u8)>,
+}
+
+struct Candidate {
+    console: ConsoleId,
+    score: u32,
+    reasons: Vec<String>,
+}
+
+#[derive(Default)]
+struct Evidence {
+    candidates: Vec<Candidate>,
+}
+
+impl Evidence {
+    fn add(&mut self, console: ConsoleId, score: u32, reason: impl Into<String>) {
+        let reason = format!("+{} {}", score, reason.into());
+        match self.candidates.iter_mut().find(|c| c.console == console) {
+            Some(candidate) => {
+                candidate.score += score;
+                candidate.reasons.push(reason);
+            }
+            None => self.candidates.push(Candidate {
+                console,
+                score,
+                reasons: vec![reason],
+            }),
+        }
+    }
+}
+
+fn confidence(score: u32) -> u8 {
+    score.min(100) as u8
+}
+
+/// Auto-detect console type from a ROM file
+pub fn detect_console(path: &Path) -> Result<ConsoleId> {
+    Ok(detect_console_explained(path)?.console)
+}
+
+/// Auto-detect console type from a ROM file's contents and extension.
+///
+/// Header signatures count for more than the extension, so a `.bin` or
+/// `.rom` file (or a misnamed one) is still recognized.
+pub fn detect_console_explained(path: &Path) -> Result<Detection> {
+    let file = File::open(path).context("Failed to open ROM file")?;
+    let file_size = file
+        .metadata()
+        .context("Failed to get file metadata")?
+        .len();
+
+    let mut data = Vec::new();
+    file.take(SCAN_BYTES)
+        .read_to_end(&mut data)
+        .context("Failed to read ROM file")?;
+
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_lowercase());
+
+    detect_from_data(&data, file_size, extension.as_deref())
+}
+
+fn detect_from_data(data: &[u8], file_size: u64, extension: Option<&str>) -> Result<Detection> {
+    let mut evidence = Evidence::default();
+
+    check_nes(data, &mut evidence);
+    check_n64(data, &mut evidence);
+    check_snes(data, file_size, &mut evidence);
+    check_game_boy(data, &mut evidence);
+    check_gba(data, &mut evidence);
+    check_nds(data, &mut evidence);
+    check_sega(data, &mut evidence);
+    check_playstation(data, file_size, &mut evidence);
+
+    if let Some(ext) = extension {
+        if let Some(console) = detect_from_extension(ext) {
+            evidence.add(console, EXTENSION_SCORE, format!("extension .{}", ext));
+        } else if let Some(console) = detect_from_weak_extension(ext) {
+            evidence.add(
+                console,
+                WEAK_EXTENSION_SCORE,
+                format!("generic extension .{}", ext),
+            );
+        }
+    }
+
+    // Stable sort keeps content matches ahead of extension-only ties
+    let mut candidates = evidence.candidates;
+    candidates.sort_by_key(|c| std::cmp::Reverse(c.score));
+
+    let mut candidates = candidates.into_iter();
+    let Some(best) = candidates.next() else {
+        bail!("Could not detect console type from file. Please specify --console manually.");
+    };
+
+    Ok(Detection {
+        console: best.console,
+        confidence: confidence(best.score),
+        reasons: best.reasons,
+        alternatives: candidates
+            .map(|c| (c.console, confidence(c.score)))
+            .collect(),
+    })
+}
+
+/// Detect console from an extension only one console uses
+fn detect_from_extension(ext: &str) -> Option<ConsoleId> {
+    match ext {
+        "gba" => Some(ConsoleId::GameBoyAdvance),
+        "gb" => Some(ConsoleId::GameBoy),
+        "gbc" => Some(ConsoleId::GameBoyColor),
+        "nes" | "fds" => Some(ConsoleId::NES),
+        "snes" | "sfc" | "smc" => Some(ConsoleId::SNES),
+        "n64" | "z64" | "v64" | "u64" => Some(ConsoleId::Nintendo64),
+        "nds" => Some(ConsoleId::NintendoDS),
+        "psx" | "ps1" | "cue" => Some(ConsoleId::PlayStation),
+        "ps2" => Some(ConsoleId::PlayStation2),
+        "gen" | "md" | "smd" => Some(ConsoleId::MegaDrive),
+        "sms" => Some(ConsoleId::MasterSystem),
+        "a26" => Some(ConsoleId::Atari2600),
+        "vb" => Some(ConsoleId::VirtualBoy),
+        _ => None,
+    }
+}
+
+/// Generic extensions, used only as a tie-breaker for the most likely console
+fn detect_from_weak_extension(ext: &str) -> Option<ConsoleId> {
+    match ext {
+        "bin" | "img" => Some(ConsoleId::PlayStation),
+        "iso" => Some(ConsoleId::PlayStation2),
+        _ => None,
+    }
+}
+
+fn bytes_at(data: &[u8], offset: usize, len: usize) -> Option<&[u8]> {
+    data.get(offset..offset + len)
+}
+
+fn check_nes(data: &[u8], evidence: &mut Evidence) {
+    if bytes_at(data, 0, 4) == Some(b"NES\x1a") {
+        let format = if data.get(7).is_some_and(|b| b & 0x0C == 0x08) {
+            "NES 2.0"
+        } else {
+            "iNES"
+        };
+        evidence.add(
+            ConsoleId::NES,
+            90,
+            format!("{} header magic \"NES\\x1a\" at 0x0000", format),
+        );
+    } else if bytes_at(data, 0, 4) == Some(b"FDS\x1a") {
+        evidence.add(
+            ConsoleId::NES,
+            90,
+            "fwNES FDS header magic \"FDS\\x1a\" at 0x0000",
+        );
+    } else if bytes_at(data, 0, 15) == Some(b"\x01*NINTENDO-HVC*") {
+        evidence.add(
+            ConsoleId::NES,
+            90,
+            "Famicom Disk System disk info block at 0x0000",
+        );
+    }
+}
+
+fn check_n64(data: &[u8], evidence: &mut Evidence) {
+    let order = match bytes_at(data, 0, 4) {
+        Some([0x80, 0x37, 0x12, 0x40]) => "big-endian (z64)",
+        Some([0x40, 0x12, 0x37, 0x80]) => "little-endian (n64)",
+        Some([0x37, 0x80, 0x40, 0x12]) => "byte-swapped (v64)",
+        _ => return,
+    };
+    evidence.add(
+        ConsoleId::Nintendo64,
+        90,
+        format!("N64 {} magic at 0x0000", order),
+    );
+}
+
+/// Scores the internal header at 0x7FC0 (LoROM) or 0xFFC0 (HiROM), after a
+/// 512-byte copier header if the size says there is one
+fn check_snes(data: &[u8], file_size: u64, evidence: &mut Evidence) {
+    let copier = if file_size % 1024 == 512 { 512 } else { 0 };
+
+    let best = [(0x7FC0, "LoROM"), (0xFFC0, "HiROM")]
+        .into_iter()
+        .filter_map(|(base, layout)| {
+            let header = bytes_at(data, copier + base, 0x20)?;
+            let mut score = 0;
+            let mut found = Vec::new();
+
+            let complement = u16::from_le_bytes([header[0x1C], header[0x1D]]);
+            let checksum = u16::from_le_bytes([header[0x1E], header[0x1F]]);
+            if complement ^ checksum == 0xFFFF {
+                score += 50;
+                found.push("checksum and complement agree");
+            }
+
+            // Map mode: 0x2X, with bit 0 set for HiROM layouts
+            let map_mode = header[0x15];
+            if map_mode & 0xE0 == 0x20 && (map_mode & 0x01 == 1) == (base == 0xFFC0) {
+                score += 20;
+                found.push("map mode matches");
+            }
+
+            if header[..21].iter().all(|&b| (0x20..0x7F).contains(&b)) {
+                score += 15;
+                found.push("printable title");
+            }
+
+            (score >= 50).then(|| {
+                (
+                    score,
+                    format!(
+                        "SNES {} header at 0x{:04X}: {}",
+                        layout,
+                        copier + base,
+                        found.join(", ")
+                    ),
+                )
+            })
+        })
+        .max_by_key(|(score, _)| *score);
+
+    if let Some((score, reason)) = best {
+        evidence.add(ConsoleId::SNES, score, reason);
+        if copier > 0 {
+            evidence.add(ConsoleId::SNES, 5, "512-byte copier header");
+        }
+    }
+}
+
+fn check_game_boy(data: &[u8], evidence: &mut Evidence) {
+    if bytes_at(data, 0x104, GB_LOGO.len()) != Some(&GB_LOGO[..]) {
+        return;
+    }
+
+    // 0x80: also runs on the original Game Boy, 0xC0: Game Boy Color only
+    let console = match data.get(0x143) {
+        Some(0x80) | Some(0xC0) => ConsoleId::GameBoyColor,
+        _ => ConsoleId::GameBoy,
+    };
+    let flag = if console == ConsoleId::GameBoyColor {
+        "Game Boy Color flag set"
+    } else {
+        "no Game Boy Color flag"
+    };
+    evidence.add(
+        console,
+        60,
+        format!("Nintendo logo at 0x0104, {} at 0x0143", flag),
+    );
+
+    if let Some(header) = bytes_at(data, 0x134, 0x1A) {
+        let sum = header[..0x19]
+            .iter()
+            .fold(0u8, |x, &b| x.wrapping_sub(b).wrapping_sub(1));
+        if sum == header[0x19] {
+            evidence.add(console, 30, "header checksum at 0x014D matches");
+        }
+    }
+}
+
+fn check_gba(data: &[u8], evidence: &mut Evidence) {
+    let Some(header) = bytes_at(data, 0, 0xC0) else {
+        return;
+    };
+
+    if header[0x04..0x0C] == GBA_LOGO_PREFIX {
+        evidence.add(ConsoleId::GameBoyAdvance, 30, "Nintendo logo at 0x0004");
+    }
+    if header[0xB2] == 0x96 {
+        evidence.add(ConsoleId::GameBoyAdvance, 20, "fixed value 0x96 at 0x00B2");
+    }
+    let sum = header[0xA0..0xBD]
+        .iter()
+        .fold(0u8, |x, &b| x.wrapping_sub(b))
+        .wrapping_sub(0x19);
+    if sum == header[0xBD] && header[0xB2] == 0x96 {
+        evidence.add(
+            ConsoleId::GameBoyAdvance,
+            40,
+            "header checksum at 0x00BD matches",
+        );
+    }
+}
+
+fn check_nds(data: &[u8], evidence: &mut Evidence) {
+    if bytes_at(data, 0xC0, GBA_LOGO_PREFIX.len()) == Some(&GBA_LOGO_PREFIX[..]) {
+        evidence.add(ConsoleId::NintendoDS, 30, "Nintendo logo at 0x00C0");
+    }
+    if bytes_at(data, 0x15C, 2) == Some(&[0x56, 0xCF]) {
+        evidence.add(ConsoleId::NintendoDS, 50, "logo CRC 0xCF56 at 0x015C");
+    }
+}
+
+fn check_sega(data: &[u8], evidence: &mut Evidence) {
+    if let Some(system) = bytes_at(data, 0x100, 16) {
+        if system.starts_with(b"SEGA") || system.starts_with(b" SEGA") {
+            let name = String::from_utf8_lossy(system).trim().to_string();
+            evidence.add(
+                ConsoleId::MegaDrive,
+                80,
+                format!("\"{}\" system name at 0x0100", name),
+            );
+        }
+    }
+
+    for offset in [0x7FF0, 0x3FF0, 0x1FF0] {
+        if bytes_at(data, offset, 8) == Some(b"TMR SEGA") {
+            evidence.add(
+                ConsoleId::MasterSystem,
+                85,
+                format!("\"TMR SEGA\" header at 0x{:04X}", offset),
+            );
+            break;
+        }
+    }
+}
+
+/// Looks for an ISO 9660 volume descriptor with a PLAYSTATION system ID, in
+/// either 2048-byte (.iso) or raw 2352-byte (.bin) sectors
+fn check_playstation(data: &[u8], file_size: u64, evidence: &mut Evidence) {
+    let (descriptor, raw) = if bytes_at(data, 0x8001, 5) == Some(b"CD001") {
+        (0x8000, false)
+    } else if bytes_at(data, 0x9319, 5) == Some(b"CD001") {
+        (0x9318, true)
+    } else {
+        return;
+    };
+
+    if bytes_at(data, descriptor + 8, 11) != Some(b"PLAYSTATION") {
+        return;
+    }
+
+    let reason = format!(
+        "PLAYSTATION system ID in the ISO 9660 volume descriptor at 0x{:04X}",
+        descriptor + 8
+    );
+    if file_size > CD_MAX_BYTES {
+        evidence.add(ConsoleId::PlayStation2, 60, reason);
+        evidence.add(
+            ConsoleId::PlayStation2,
+            30,
+            format!("{} MB is too large for a CD", file_size / (1024 * 1024)),
+        );
+    } else if raw {
+        evidence.add(ConsoleId::PlayStation, 60, reason);
+        evidence.add(ConsoleId::PlayStation, 20, "raw 2352-byte CD sectors");
+    } else {
+        // PS2 CDs exist too, so the size alone leans only slightly towards PS1
+        evidence.add(ConsoleId::PlayStation, 60, reason.clone());
+        evidence.add(ConsoleId::PlayStation2, 50, reason);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::Builder;
+
+    fn gb_rom(cgb_flag: u8) -> Vec<u8> {
+        let mut rom = vec![0u8; 0x8000];
+        rom[0x104..0x134].copy_from_slice(&GB_LOGO);
+        rom[0x134..0x13A].copy_from_slice(b"TETRIS");
+        rom[0x143] = cgb_flag;
+        rom[0x14D] = rom[0x134..0x14D]
+            .iter()
+            .fold(0u8, |x, &b| x.wrapping_sub(b).wrapping_sub(1));
+        rom
+    }
+
+    #[test]
+    fn test_content_beats_extension() {
+        let mut rom = b"NES\x1a\x02\x01\x00\x00".to_vec();
+        rom.resize(0x4010, 0);
+
+        // A .bin would otherwise be guessed as PlayStation
+        let detection = detect_from_data(&rom, rom.len() as u64, Some("bin")).unwrap();
+        assert_eq!(detection.console, ConsoleId::NES);
+        assert!(detection.confidence >= 90);
+        assert!(detection.reasons[0].contains("iNES"));
+        assert_eq!(detection.alternatives, vec![(ConsoleId::PlayStation, 10)]);
+    }
+
+    #[test]
+    fn test_game_boy_header() {
+        let rom = gb_rom(0x00);
+        let detection = detect_from_data(&rom, rom.len() as u64, Some("rom")).unwrap();
+        assert_eq!(detection.console, ConsoleId::GameBoy);
+        assert_eq!(detection.confidence, 90);
+
+        let rom = gb_rom(0xC0);
+        let detection = detect_from_data(&rom, rom.len() as u64, None).unwrap();
+        assert_eq!(detection.console, ConsoleId::GameBoyColor);
+    }
+
+    #[test]
+    fn test_snes_and_genesis_headers() {
+        let mut rom = vec![0u8; 0x8000];
+        rom[0x7FC0..0x7FD5].copy_from_slice(b"SUPER MARIOWORLD     ");
+        rom[0x7FD5] = 0x20;
+        rom[0x7FDC..0x7FE0].copy_from_slice(&[0x34, 0x12, 0xCB, 0xED]);
+        let detection = detect_from_data(&rom, rom.len() as u64, Some("bin")).unwrap();
+        assert_eq!(detection.console, ConsoleId::SNES);
+        assert_eq!(detection.confidence, 85);
+
+        let mut rom = vec![0u8; 0x200];
+        rom[0x100..0x110].copy_from_slice(b"SEGA MEGA DRIVE ");
+        let detection = detect_from_data(&rom, rom.len() as u64, Some("bin")).unwrap();
+        assert_eq!(detection.console, ConsoleId::MegaDrive);
+    }
+
+    #[test]
+    fn test_extension_fallback_and_unknown() {
+        let rom = vec![0xFFu8; 0x1000];
+        let detection = detect_from_data(&rom, rom.len() as u64, Some("a26")).unwrap();
+        assert_eq!(detection.console, ConsoleId::Atari2600);
+        assert_eq!(detection.confidence, 40);
+
+        assert!(detect_from_data(&rom, rom.len() as u64, Some("rom")).is_err());
+    }
+
+    #[test]
+    fn test_detect_console_from_file() {
+        let mut file = Builder::new().suffix(".rom").tempfile().unwrap();
+        file.write_all(&gb_rom(0x80)).unwrap();
+        file.flush().unwrap();
+
+        assert_eq!(
+            detect_console(file.path()).unwrap(),
+            ConsoleId::GameBoyColor
+        );
+    }
+}
@@ -0,0 +1,242 @@
+//! Per-game difficulty and time-to-master estimates
+//!
+//! Difficulty is read from the set's rarity: each achievement scores
+//! `-log10(share of players who have it)`, so one everybody earns scores 0
+//! and a 1% achievement scores 2. The game's score is the mean of those,
+//! scaled to 0-10.
+//!
+//! The time to master comes from the player's own pace: the active time
+//! between their unlocks (gaps longer than a session don't count), divided
+//! by the unlocks. Each remaining achievement is then expected to take that
+//! pace, scaled by how hard it is compared to the ones already earned.
+//!
+//! Estimates are computed when a game is synced and cached alongside it.
+
+use serde::{Deserialize, Serialize};
+
+/// Unlocks further apart than this are in different play sessions
+pub const SESSION_GAP_SECS: i64 = 2 * 60 * 60;
+
+/// Rarity below this is treated as this, so one unearned-by-anyone
+/// achievement doesn't dominate the score
+const MIN_RARITY_PERCENT: f32 = 0.1;
+
+/// Mean hardness that maps to a score of 10
+const MAX_HARDNESS: f32 = 2.0;
+
+/// Score thresholds for the labels: (upper bound, label)
+const LABELS: [(f32, &str); 5] = [
+    (2.0, "Easy"),
+    (4.0, "Moderate"),
+    (6.0, "Hard"),
+    (8.0, "Very Hard"),
+    (f32::INFINITY, "Extreme"),
+];
+
+/// An official achievement as seen by the estimate
+#[derive(Debug, Clone, PartialEq)]
+pub struct AchievementSample {
+    pub rarity_percent: Option<f32>,
+    pub earned: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DifficultyEstimate {
+    /// 0 (everyone gets everything) to 10
+    pub score: f32,
+    /// Unearned official achievements
+    pub remaining: u32,
+    /// Active seconds per unlock, from the player's history
+    pub pace_secs: Option<u64>,
+    /// Seconds of play left to earn everything at that pace
+    pub expected_master_secs: Option<u64>,
+    /// Unix timestamp of the computation
+    pub computed_at: i64,
+}
+
+impl DifficultyEstimate {
+    pub fn label(&self) -> &'static str {
+        difficulty_label(self.score)
+    }
+}
+
+/// "Easy" through "Extreme" for a 0-10 score
+pub fn difficulty_label(score: f32) -> &'static str {
+    LABELS
+        .iter()
+        .find(|(max, _)| score < *max)
+        .map_or(LABELS[LABELS.len() - 1].1, |(_, label)| label)
+}
+
+/// "3h 20m", "4d 2h" and so on, for the expected time to master
+pub fn format_duration(secs: u64) -> String {
+    let minutes = secs / 60;
+    let hours = minutes / 60;
+    let days = hours / 24;
+    if days > 0 {
+        format!("{}d {}h", days, hours % 24)
+    } else if hours > 0 {
+        format!("{}h {}m", hours, minutes % 60)
+    } else {
+        format!("{}m", minutes.max(1))
+    }
+}
+
+fn hardness(rarity_percent: f32) -> f32 {
+    -(rarity_percent.clamp(MIN_RARITY_PERCENT, 100.0) / 100.0).log10()
+}
+
+fn mean(values: impl Iterator<Item = f32>) -> Option<f32> {
+    let (sum, count) = values.fold((0.0, 0), |(sum, count), v| (sum + v, count + 1));
+    (count > 0).then(|| sum / count as f32)
+}
+
+/// Active seconds per unlock. `unlock_times` holds one time per achievement.
+pub fn unlock_pace(unlock_times: &[i64]) -> Option<u64> {
+    let mut sorted = unlock_times.to_vec();
+    sorted.sort_unstable();
+    let intervals: Vec<i64> = sorted
+        .windows(2)
+        .map(|w| w[1] - w[0])
+        .filter(|gap| *gap <= SESSION_GAP_SECS)
+        .collect();
+    (!intervals.is_empty()).then(|| (intervals.iter().sum::<i64>() / intervals.len() as i64) as u64)
+}
+
+/// Estimate a game's difficulty. Returns None when no rarity is known yet.
+pub fn estimate(
+    achievements: &[AchievementSample],
+    unlock_times: &[i64],
+    now: i64,
+) -> Option<DifficultyEstimate> {
+    let mean_hardness = mean(
+        achievements
+            .iter()
+            .filter_map(|a| a.rarity_percent)
+            .map(hardness),
+    )?;
+    let score = (mean_hardness / MAX_HARDNESS * 10.0).min(10.0);
+
+    let remaining: Vec<&AchievementSample> = achievements.iter().filter(|a| !a.earned).collect();
+    let pace_secs = unlock_pace(unlock_times);
+
+    // What the pace was measured on; the whole set if nothing earned has rarity yet
+    let earned_hardness = mean(
+        achievements
+            .iter()
+            .filter(|a| a.earned)
+            .filter_map(|a| a.rarity_percent)
+            .map(hardness),
+    )
+    .unwrap_or(mean_hardness);
+
+    let expected_master_secs = pace_secs.map(|pace| {
+        remaining
+            .iter()
+            .map(|a| {
+                let weight = a
+                    .rarity_percent
+                    .map_or(1.0, |r| (1.0 + hardness(r)) / (1.0 + earned_hardness));
+                pace as f32 * weight
+            })
+            .sum::<f32>() as u64
+    });
+
+    Some(DifficultyEstimate {
+        score,
+        remaining: remaining.len() as u32,
+        pace_secs,
+        expected_master_secs,
+        computed_at: now,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(rarity_percent: Option<f32>, earned: bool) -> AchievementSample {
+        AchievementSample {
+            rarity_percent,
+            earned,
+        }
+    }
+
+    #[test]
+    fn test_score_follows_rarity() {
+        let common = estimate(
+            &[sample(Some(100.0), false), sample(Some(80.0), false)],
+            &[],
+            0,
+        )
+        .unwrap();
+        assert!(common.score < 1.0);
+        assert_eq!(common.label(), "Easy");
+
+        let rare = estimate(
+            &[sample(Some(1.0), false), sample(Some(10.0), false)],
+            &[],
+            0,
+        )
+        .unwrap();
+        assert!((rare.score - 7.5).abs() < 0.01);
+        assert_eq!(rare.label(), "Very Hard");
+
+        // Nobody has it: clamped rather than infinite
+        let impossible = estimate(&[sample(Some(0.0), false)], &[], 0).unwrap();
+        assert_eq!(impossible.score, 10.0);
+        assert_eq!(impossible.label(), "Extreme");
+
+        assert_eq!(estimate(&[sample(None, false)], &[], 0), None);
+    }
+
+    #[test]
+    fn test_pace_ignores_gaps_between_sessions() {
+        // Two sessions of 10 minute unlocks, a week apart
+        let week = 7 * 24 * 3600;
+        let times = [0, 600, 1200, week, week + 600];
+        assert_eq!(unlock_pace(&times), Some(600));
+        assert_eq!(unlock_pace(&[0]), None);
+        assert_eq!(unlock_pace(&[0, week]), None);
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!(format_duration(30), "1m");
+        assert_eq!(format_duration(3 * 3600 + 20 * 60), "3h 20m");
+        assert_eq!(format_duration(4 * 86400 + 2 * 3600), "4d 2h");
+    }
+
+    #[test]
+    fn test_expected_time_scales_with_remaining_rarity() {
+        let earned = [sample(Some(10.0), true), sample(Some(10.0), true)];
+        let times = [0, 600];
+
+        // Remaining achievements as rare as the earned ones take the pace each
+        let same = estimate(
+            &[
+                &earned[..],
+                &[sample(Some(10.0), false), sample(None, false)],
+            ]
+            .concat(),
+            &times,
+            0,
+        )
+        .unwrap();
+        assert_eq!(same.remaining, 2);
+        assert_eq!(same.pace_secs, Some(600));
+        assert_eq!(same.expected_master_secs, Some(1200));
+
+        // A 1% achievement is (1 + 2) / (1 + 1) times the work
+        let rarer = estimate(
+            &[&earned[..], &[sample(Some(1.0), false)]].concat(),
+            &times,
+            0,
+        )
+        .unwrap();
+        assert_eq!(rarer.expected_master_secs, Some(900));
+
+        let mastered = estimate(&earned, &times, 0).unwrap();
+        assert_eq!(mastered.expected_master_secs, Some(0));
+    }
+}
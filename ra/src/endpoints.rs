@@ -0,0 +1,428 @@
+//! RA endpoint failover and health
+//!
+//! Requests are built against the default site and rebased onto each
+//! configured endpoint (`kazeta-ra config endpoints`) in turn: a connection
+//! error or a 5xx moves on to the next one. Each endpoint has a circuit
+//! breaker. After `FAILURE_THRESHOLD` failures in a row it opens, and the
+//! endpoint is skipped for a cooldown that doubles each time it opens again.
+//! Once the cooldown is over the endpoint is half-open: a quick probe has to
+//! succeed before real requests (which can take the full 30s timeout to
+//! fail) go back to it.
+//!
+//! The CLI runs once per command, so health is kept in `ra_endpoints.json`
+//! for the next run and for `kazeta-ra status`.
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// The RetroAchievements site; request URLs are written against it
+pub const DEFAULT_ENDPOINT: &str = "https://retroachievements.org";
+
+/// Failures in a row that open an endpoint's circuit
+const FAILURE_THRESHOLD: u32 = 3;
+/// Seconds an endpoint is skipped the first time its circuit opens
+const BASE_COOLDOWN_SECS: i64 = 60;
+const MAX_COOLDOWN_SECS: i64 = 15 * 60;
+/// How often a healthy endpoint's last success is written back to disk
+const SUCCESS_SAVE_INTERVAL_SECS: i64 = 5 * 60;
+
+/// Timeout of a probe, well under the request timeout
+pub const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+    /// In use
+    Closed,
+    /// Skipped until its cooldown is over
+    Open,
+    /// Cooldown over; probed before it's used again
+    HalfOpen,
+}
+
+impl CircuitState {
+    pub fn label(self) -> &'static str {
+        match self {
+            CircuitState::Closed => "healthy",
+            CircuitState::Open => "down",
+            CircuitState::HalfOpen => "recovering",
+        }
+    }
+}
+
+/// What's known about one endpoint
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EndpointHealth {
+    /// Failures since the last success
+    #[serde(default)]
+    pub failures: u32,
+    /// Unix timestamp the open circuit's cooldown ends
+    #[serde(default)]
+    pub open_until: Option<i64>,
+    /// Length of the current cooldown, doubled each time it reopens
+    #[serde(default)]
+    pub cooldown_secs: i64,
+    #[serde(default)]
+    pub last_success: Option<i64>,
+    #[serde(default)]
+    pub last_failure: Option<i64>,
+    #[serde(default)]
+    pub last_error: Option<String>,
+    /// Response time of the last success
+    #[serde(default)]
+    pub latency_ms: Option<u64>,
+}
+
+impl EndpointHealth {
+    pub fn state(&self, now: i64) -> CircuitState {
+        match self.open_until {
+            Some(until) if now < until => CircuitState::Open,
+            Some(_) => CircuitState::HalfOpen,
+            None => CircuitState::Closed,
+        }
+    }
+
+    fn succeeded(&mut self, latency_ms: u64, now: i64) {
+        self.failures = 0;
+        self.open_until = None;
+        self.cooldown_secs = 0;
+        self.last_success = Some(now);
+        self.latency_ms = Some(latency_ms);
+    }
+
+    fn failed(&mut self, error: &str, now: i64) {
+        self.failures += 1;
+        self.last_failure = Some(now);
+        self.last_error = Some(error.to_string());
+        let cooldown = match self.state(now) {
+            // A failed probe reopens it for longer
+            CircuitState::HalfOpen => {
+                (self.cooldown_secs * 2).clamp(BASE_COOLDOWN_SECS, MAX_COOLDOWN_SECS)
+            }
+            CircuitState::Closed if self.failures >= FAILURE_THRESHOLD => BASE_COOLDOWN_SECS,
+            _ => return,
+        };
+        self.cooldown_secs = cooldown;
+        self.open_until = Some(now + cooldown);
+    }
+}
+
+/// An endpoint's health as `kazeta-ra status` reports it
+#[derive(Debug, Clone, Serialize)]
+pub struct EndpointReport {
+    pub url: String,
+    pub state: CircuitState,
+    /// The endpoint requests go to first
+    pub current: bool,
+    #[serde(flatten)]
+    pub health: EndpointHealth,
+}
+
+/// The configured endpoints and their circuit breakers
+pub struct Endpoints {
+    bases: Vec<String>,
+    health: Mutex<HashMap<String, EndpointHealth>>,
+    /// None keeps health in memory only
+    path: Option<PathBuf>,
+}
+
+fn health_path() -> Option<PathBuf> {
+    dirs::home_dir().map(|home| home.join(".local/share/kazeta-plus/ra_endpoints.json"))
+}
+
+impl Endpoints {
+    /// The configured endpoints with their health from earlier runs
+    pub fn new(bases: Vec<String>) -> Self {
+        Self::with_path(bases, health_path())
+    }
+
+    pub fn with_path(bases: Vec<String>, path: Option<PathBuf>) -> Self {
+        let bases = if bases.is_empty() {
+            vec![DEFAULT_ENDPOINT.to_string()]
+        } else {
+            bases
+        };
+        let health = path
+            .as_ref()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        Self {
+            bases,
+            health: Mutex::new(health),
+            path,
+        }
+    }
+
+    pub fn bases(&self) -> &[String] {
+        &self.bases
+    }
+
+    fn state_of(
+        &self,
+        health: &HashMap<String, EndpointHealth>,
+        base: &str,
+        now: i64,
+    ) -> CircuitState {
+        health
+            .get(base)
+            .map_or(CircuitState::Closed, |h| h.state(now))
+    }
+
+    /// Endpoints to try, in order: closed ones as configured, then half-open
+    /// ones. Open ones are left out.
+    pub fn attempt_order(&self, now: i64) -> Vec<(String, CircuitState)> {
+        let health = self.health.lock().unwrap();
+        let mut order: Vec<(String, CircuitState)> = self
+            .bases
+            .iter()
+            .map(|base| (base.clone(), self.state_of(&health, base, now)))
+            .filter(|(_, state)| *state != CircuitState::Open)
+            .collect();
+        order.sort_by_key(|(_, state)| *state == CircuitState::HalfOpen);
+        order
+    }
+
+    /// The endpoint requests go to first, if any is usable
+    pub fn current(&self, now: i64) -> Option<String> {
+        self.attempt_order(now)
+            .into_iter()
+            .next()
+            .map(|(base, _)| base)
+    }
+
+    /// Error for when every endpoint's circuit is open
+    pub fn all_down(&self, now: i64) -> anyhow::Error {
+        let health = self.health.lock().unwrap();
+        let retry = self
+            .bases
+            .iter()
+            .filter_map(|base| health.get(base)?.open_until)
+            .min()
+            .map_or(0, |until| (until - now).max(0));
+        anyhow::anyhow!("All RA endpoints are failing; next retry in {}s", retry)
+    }
+
+    pub fn record_success(&self, base: &str, latency_ms: u64, now: i64) {
+        let mut health = self.health.lock().unwrap();
+        let entry = health.entry(base.to_string()).or_default();
+        // Keep writes to recoveries and the odd refresh of a healthy endpoint
+        let worth_saving = entry.failures > 0
+            || entry.open_until.is_some()
+            || entry
+                .last_success
+                .is_none_or(|at| now - at >= SUCCESS_SAVE_INTERVAL_SECS);
+        if entry.failures > 0 {
+            eprintln!("[RA] {} is answering again", base);
+        }
+        entry.succeeded(latency_ms, now);
+        if worth_saving {
+            self.save(&health);
+        }
+    }
+
+    pub fn record_failure(&self, base: &str, error: &str, now: i64) {
+        let mut health = self.health.lock().unwrap();
+        let entry = health.entry(base.to_string()).or_default();
+        let was_open = entry.open_until.is_some();
+        entry.failed(error, now);
+        if let (false, Some(until)) = (was_open, entry.open_until) {
+            eprintln!(
+                "[RA] {} failed {} times in a row; skipping it for {}s",
+                base,
+                entry.failures,
+                until - now
+            );
+        }
+        self.save(&health);
+    }
+
+    /// Health of every configured endpoint, in configured order
+    pub fn report(&self, now: i64) -> Vec<EndpointReport> {
+        let current = self.current(now);
+        let health = self.health.lock().unwrap();
+        self.bases
+            .iter()
+            .map(|base| EndpointReport {
+                url: base.clone(),
+                state: self.state_of(&health, base, now),
+                current: current.as_deref() == Some(base.as_str()),
+                health: health.get(base).cloned().unwrap_or_default(),
+            })
+            .collect()
+    }
+
+    /// Write health back, dropping endpoints no longer configured
+    fn save(&self, health: &HashMap<String, EndpointHealth>) {
+        let Some(path) = &self.path else { return };
+        let kept: HashMap<&String, &EndpointHealth> = health
+            .iter()
+            .filter(|(base, _)| self.bases.contains(base))
+            .collect();
+        let result = serde_json::to_string_pretty(&kept)
+            .context("Failed to serialize endpoint health")
+            .and_then(|json| fs::write(path, json).context("Failed to write endpoint health"));
+        if let Err(e) = result {
+            eprintln!("[RA] {:#}", e);
+        }
+    }
+}
+
+/// A configured endpoint as stored: an http(s) URL without a trailing slash
+pub fn normalize(base: &str) -> Result<String> {
+    let base = base.trim().trim_end_matches('/');
+    let url =
+        reqwest::Url::parse(base).with_context(|| format!("Invalid endpoint URL '{}'", base))?;
+    if !matches!(url.scheme(), "http" | "https") || url.host_str().is_none() {
+        bail!("Endpoint must be an http(s) URL, got '{}'", base);
+    }
+    if url.query().is_some() || url.fragment().is_some() {
+        bail!("Endpoint can't have a query or fragment, got '{}'", base);
+    }
+    Ok(base.to_string())
+}
+
+/// A request URL moved from the default site onto `base`. URLs elsewhere,
+/// like the media server, are left alone.
+pub fn rebase(url: &reqwest::Url, base: &str) -> Result<reqwest::Url> {
+    let Some(rest) = url.as_str().strip_prefix(DEFAULT_ENDPOINT) else {
+        return Ok(url.clone());
+    };
+    if base == DEFAULT_ENDPOINT {
+        return Ok(url.clone());
+    }
+    reqwest::Url::parse(&format!("{}{}", base, rest))
+        .with_context(|| format!("Invalid endpoint URL '{}'", base))
+}
+
+/// A quick request showing whether an endpoint answers at all. Anything
+/// but a 5xx counts, since the site root needs no credentials.
+pub fn probe_request(
+    client: &reqwest::blocking::Client,
+    base: &str,
+) -> reqwest::blocking::RequestBuilder {
+    client.head(format!("{}/", base)).timeout(PROBE_TIMEOUT)
+}
+
+pub fn probe_request_async(client: &reqwest::Client, base: &str) -> reqwest::RequestBuilder {
+    client.head(format!("{}/", base)).timeout(PROBE_TIMEOUT)
+}
+
+/// Milliseconds since `started`, for recording latency
+pub fn elapsed_ms(started: Instant) -> u64 {
+    started.elapsed().as_millis().min(u64::MAX as u128) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn endpoints(bases: &[&str]) -> Endpoints {
+        Endpoints::with_path(bases.iter().map(|b| b.to_string()).collect(), None)
+    }
+
+    #[test]
+    fn test_circuit_opens_after_repeated_failures() {
+        let endpoints = endpoints(&[DEFAULT_ENDPOINT, "https://mirror.example"]);
+        let now = 1_000;
+        assert_eq!(endpoints.current(now).as_deref(), Some(DEFAULT_ENDPOINT));
+
+        endpoints.record_failure(DEFAULT_ENDPOINT, "timed out", now);
+        endpoints.record_failure(DEFAULT_ENDPOINT, "timed out", now);
+        assert_eq!(endpoints.attempt_order(now)[0].1, CircuitState::Closed);
+        endpoints.record_failure(DEFAULT_ENDPOINT, "timed out", now);
+
+        // Open: skipped, and the mirror takes over
+        let order = endpoints.attempt_order(now + 1);
+        assert_eq!(
+            order,
+            vec![("https://mirror.example".to_string(), CircuitState::Closed)]
+        );
+        let report = endpoints.report(now + 1);
+        assert_eq!(report[0].state, CircuitState::Open);
+        assert_eq!(report[0].health.last_error.as_deref(), Some("timed out"));
+        assert!(report[1].current);
+
+        // Half-open after the cooldown, tried after healthy endpoints
+        let later = now + BASE_COOLDOWN_SECS;
+        let order = endpoints.attempt_order(later);
+        assert_eq!(order[0].0, "https://mirror.example");
+        assert_eq!(
+            order[1],
+            (DEFAULT_ENDPOINT.to_string(), CircuitState::HalfOpen)
+        );
+
+        // A failed probe reopens it for twice as long
+        endpoints.record_failure(DEFAULT_ENDPOINT, "HTTP 503", later);
+        assert_eq!(
+            endpoints.report(later)[0].health.open_until,
+            Some(later + 2 * BASE_COOLDOWN_SECS)
+        );
+
+        // A success closes it and resets the cooldown
+        let recovered = later + 2 * BASE_COOLDOWN_SECS;
+        endpoints.record_success(DEFAULT_ENDPOINT, 120, recovered);
+        let report = endpoints.report(recovered);
+        assert_eq!(report[0].state, CircuitState::Closed);
+        assert_eq!(report[0].health.failures, 0);
+        assert_eq!(report[0].health.cooldown_secs, 0);
+        assert!(report[0].current);
+    }
+
+    #[test]
+    fn test_all_endpoints_down() {
+        let endpoints = endpoints(&[]);
+        assert_eq!(endpoints.bases(), [DEFAULT_ENDPOINT]);
+        for _ in 0..FAILURE_THRESHOLD {
+            endpoints.record_failure(DEFAULT_ENDPOINT, "connection refused", 0);
+        }
+        assert!(endpoints.attempt_order(10).is_empty());
+        assert_eq!(endpoints.current(10), None);
+        assert!(endpoints
+            .all_down(10)
+            .to_string()
+            .contains("next retry in 50s"));
+    }
+
+    #[test]
+    fn test_health_persists_between_runs() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ra_endpoints.json");
+        let bases = vec![DEFAULT_ENDPOINT.to_string()];
+
+        let first = Endpoints::with_path(bases.clone(), Some(path.clone()));
+        for _ in 0..FAILURE_THRESHOLD {
+            first.record_failure(DEFAULT_ENDPOINT, "timed out", 0);
+        }
+        let second = Endpoints::with_path(bases, Some(path));
+        assert_eq!(second.report(1)[0].state, CircuitState::Open);
+    }
+
+    #[test]
+    fn test_normalize_and_rebase() {
+        assert_eq!(
+            normalize(" https://ra.example/mirror/ ").unwrap(),
+            "https://ra.example/mirror"
+        );
+        assert!(normalize("ftp://ra.example").is_err());
+        assert!(normalize("not a url").is_err());
+        assert!(normalize("https://ra.example/?x=1").is_err());
+
+        let url =
+            reqwest::Url::parse("https://retroachievements.org/API/API_GetUserSummary.php?u=me")
+                .unwrap();
+        assert_eq!(
+            rebase(&url, "https://ra.example/mirror").unwrap().as_str(),
+            "https://ra.example/mirror/API/API_GetUserSummary.php?u=me"
+        );
+        assert_eq!(rebase(&url, DEFAULT_ENDPOINT).unwrap(), url);
+
+        let media = reqwest::Url::parse("https://media.retroachievements.org/Badge/1.png").unwrap();
+        assert_eq!(rebase(&media, "https://ra.example").unwrap(), media);
+    }
+}
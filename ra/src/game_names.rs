@@ -1,6 +1,7 @@
+use crate::types::ConsoleId;
 use anyhow::{bail, Context, Result};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
 
@@ -10,6 +11,11 @@ pub struct GameNameMapping {
     /// Map of ROM hash to custom game name
     #[serde(default)]
     pub games: HashMap<String, GameNameEntry>,
+    /// Map of cartridge file path to custom game name. Checked before the
+    /// hash mapping, so one cart can be renamed without touching other carts
+    /// that share its ROM.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub carts: HashMap<String, GameNameEntry>,
 }
 
 /// Entry for a single game name mapping
@@ -20,22 +26,45 @@ pub struct GameNameEntry {
     /// Optional console ID (for reference)
     #[serde(default)]
     pub console: Option<String>,
+    /// Where the name came from; manual names are written without it
+    #[serde(default, skip_serializing_if = "NameSource::is_manual")]
+    pub source: NameSource,
+}
+
+/// Origin of a custom game name
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum NameSource {
+    /// Set with `set-game-name`; always wins
+    #[default]
+    Manual,
+    /// Read from the ROM header by `game-start` for a game RetroAchievements
+    /// doesn't know. Only used when there's no RA title.
+    Header,
+    /// The cart's `ra_game_name`, passed to `game-start` as `--name-hint`.
+    /// Reported in its output; never stored.
+    Cartridge,
+}
+
+impl NameSource {
+    fn is_manual(&self) -> bool {
+        *self == NameSource::Manual
+    }
 }
 
 impl GameNameMapping {
     /// Load game name mappings from disk
     pub fn load() -> Result<Self> {
         let path = Self::get_config_path()?;
-        
+
         if !path.exists() {
             return Ok(Self::default());
         }
 
-        let content = fs::read_to_string(&path)
-            .context("Failed to read game names config file")?;
+        let content = fs::read_to_string(&path).context("Failed to read game names config file")?;
 
-        let mapping: GameNameMapping = serde_json::from_str(&content)
-            .context("Failed to parse game names config JSON")?;
+        let mapping: GameNameMapping =
+            serde_json::from_str(&content).context("Failed to parse game names config JSON")?;
 
         Ok(mapping)
     }
@@ -43,46 +72,132 @@ impl GameNameMapping {
     /// Save game name mappings to disk
     pub fn save(&self) -> Result<()> {
         let path = Self::get_config_path()?;
-        
+
         // Create directory if it doesn't exist
         if let Some(parent) = path.parent() {
-            fs::create_dir_all(parent)
-                .context("Failed to create config directory")?;
+            fs::create_dir_all(parent).context("Failed to create config directory")?;
         }
 
-        let json = serde_json::to_string_pretty(self)
-            .context("Failed to serialize game names config")?;
+        let json =
+            serde_json::to_string_pretty(self).context("Failed to serialize game names config")?;
 
-        fs::write(&path, json)
-            .context("Failed to write game names config file")?;
+        fs::write(&path, json).context("Failed to write game names config file")?;
 
         Ok(())
     }
 
     /// Get custom name for a ROM hash
-    /// Checks cartridge TOML first (if cart_path provided), then JSON mapping
+    /// Checks the cart-scoped override and the cartridge's own `ra_game_name`
+    /// first (if cart_path provided), then the JSON hash mapping.
+    /// Header suggestions are left out; see `get_suggested_name`.
     pub fn get_name(&self, hash: &str, cart_path: Option<&Path>) -> Option<String> {
-        // First, try to get from cartridge TOML if path is provided
         if let Some(path) = cart_path {
+            if let Some(name) = self.get_cart_name(path) {
+                return Some(name);
+            }
             if let Ok(name) = Self::get_name_from_cartridge(path) {
                 return Some(name);
             }
         }
 
         // Fall back to JSON mapping
-        self.games.get(hash).map(|e| e.name.clone())
+        self.games
+            .get(hash)
+            .filter(|e| e.source == NameSource::Manual)
+            .map(|e| e.name.clone())
+    }
+
+    /// Name read from the ROM header earlier, if no manual name replaced it
+    pub fn get_suggested_name(&self, hash: &str) -> Option<String> {
+        self.games
+            .get(hash)
+            .filter(|e| e.source == NameSource::Header)
+            .map(|e| e.name.clone())
+    }
+
+    /// Remember a name read from the ROM header. Never replaces an existing
+    /// entry, so manual names always win. Returns whether it was stored.
+    pub fn suggest_name(
+        &mut self,
+        hash: &str,
+        name: String,
+        console: Option<String>,
+    ) -> Result<bool> {
+        if self.games.contains_key(hash) {
+            return Ok(false);
+        }
+        self.games.insert(
+            hash.to_string(),
+            GameNameEntry {
+                name,
+                console,
+                source: NameSource::Header,
+            },
+        );
+        self.save()?;
+        Ok(true)
+    }
+
+    /// Override set for one cartridge file with `set-game-name --cart`
+    pub fn get_cart_name(&self, cart_path: &Path) -> Option<String> {
+        self.carts
+            .get(&Self::cart_key(cart_path))
+            .map(|e| e.name.clone())
     }
 
-    /// Get game name from cartridge TOML file
+    /// Set a custom name for one cartridge file
+    pub fn set_cart_name(
+        &mut self,
+        cart_path: &Path,
+        name: String,
+        console: Option<String>,
+    ) -> Result<()> {
+        self.carts.insert(
+            Self::cart_key(cart_path),
+            GameNameEntry {
+                name,
+                console,
+                source: NameSource::Manual,
+            },
+        );
+        self.save()
+    }
+
+    /// Remove a cartridge's custom name. Returns whether it had one.
+    pub fn remove_cart_name(&mut self, cart_path: &Path) -> Result<bool> {
+        let removed = self.carts.remove(&Self::cart_key(cart_path)).is_some();
+        if removed {
+            self.save()?;
+        }
+        Ok(removed)
+    }
+
+    /// Cart paths are stored canonicalized, so relative and absolute paths
+    /// to the same file match
+    fn cart_key(cart_path: &Path) -> String {
+        cart_path
+            .canonicalize()
+            .unwrap_or_else(|_| cart_path.to_path_buf())
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    /// Get game name from the cartridge: a plain `.kzi` with an
+    /// `RA_Game_Name=` line, or a packaged cart with a cartridge.toml
     fn get_name_from_cartridge(cart_path: &Path) -> Result<String> {
-        use std::io::Read;
         use flate2::read::GzDecoder;
+        use std::io::Read;
         use tar::Archive;
 
-        // Open the .kzi file (which is a gzip-compressed tar archive)
-        let file = fs::File::open(cart_path)
-            .context("Failed to open cartridge file")?;
-        let decoder = GzDecoder::new(file);
+        let bytes = fs::read(cart_path).context("Failed to open cartridge file")?;
+        // Not gzip: a plain key=value .kzi
+        if !bytes.starts_with(&[0x1f, 0x8b]) {
+            return kzi_ra_game_name(&String::from_utf8_lossy(&bytes))
+                .context("No ra_game_name found in cartridge");
+        }
+
+        // Packaged carts are gzip-compressed tar archives
+        let decoder = GzDecoder::new(bytes.as_slice());
         let mut archive = Archive::new(decoder);
 
         // Find and read the cartridge.toml file from the archive
@@ -94,10 +209,10 @@ impl GameNameMapping {
 
             if path.file_name().and_then(|n| n.to_str()) == Some("cartridge.toml") {
                 entry.read_to_string(&mut content)?;
-                
+
                 // Parse the TOML content
-                let toml_value: toml::Value = toml::from_str(&content)
-                    .context("Failed to parse cartridge.toml")?;
+                let toml_value: toml::Value =
+                    toml::from_str(&content).context("Failed to parse cartridge.toml")?;
 
                 // Extract ra_game_name field
                 if let Some(name) = toml_value.get("ra_game_name").and_then(|v| v.as_str()) {
@@ -112,7 +227,14 @@ impl GameNameMapping {
 
     /// Set custom name for a ROM hash
     pub fn set_name(&mut self, hash: String, name: String, console: Option<String>) -> Result<()> {
-        self.games.insert(hash, GameNameEntry { name, console });
+        self.games.insert(
+            hash,
+            GameNameEntry {
+                name,
+                console,
+                source: NameSource::Manual,
+            },
+        );
         self.save()?;
         Ok(())
     }
@@ -130,10 +252,414 @@ impl GameNameMapping {
             .context("Could not find home directory")?
             .join(".local/share/kazeta-plus");
 
-        fs::create_dir_all(&data_dir)
-            .context("Failed to create kazeta data directory")?;
+        fs::create_dir_all(&data_dir).context("Failed to create kazeta data directory")?;
 
         Ok(data_dir.join("ra_game_names.json"))
     }
 }
 
+/// Columns of the CSV written by `export-game-names --csv`
+pub const CSV_COLUMNS: [&str; 4] = ["hash", "console", "name", "cart"];
+
+/// One manual name from a CSV file: keyed by ROM hash or by cartridge path
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NameRow {
+    pub hash: Option<String>,
+    pub console: Option<String>,
+    pub name: String,
+    pub cart: Option<String>,
+}
+
+/// A CSV row that failed validation; `line` is where the row starts
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RowError {
+    pub line: usize,
+    pub error: String,
+}
+
+/// What importing rows changed (or would change, for a dry run)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct ImportSummary {
+    pub added: usize,
+    pub updated: usize,
+    pub unchanged: usize,
+}
+
+impl GameNameMapping {
+    /// Manual names as CSV, hash rows first, each group sorted. Names read
+    /// from ROM headers are left out; `game-start` finds them again.
+    pub fn to_csv(&self) -> String {
+        let mut games: Vec<_> = self
+            .games
+            .iter()
+            .filter(|(_, e)| e.source == NameSource::Manual)
+            .collect();
+        games.sort_by(|a, b| a.0.cmp(b.0));
+        let mut carts: Vec<_> = self.carts.iter().collect();
+        carts.sort_by(|a, b| a.0.cmp(b.0));
+
+        let mut csv = CSV_COLUMNS.join(",") + "\n";
+        for (hash, entry) in games {
+            csv += &csv_record(&[
+                hash,
+                entry.console.as_deref().unwrap_or(""),
+                &entry.name,
+                "",
+            ]);
+        }
+        for (cart, entry) in carts {
+            csv += &csv_record(&[
+                "",
+                entry.console.as_deref().unwrap_or(""),
+                &entry.name,
+                cart,
+            ]);
+        }
+        csv
+    }
+
+    /// Validate a CSV file with a `hash,console,name,cart` header (columns in
+    /// any order). Every row is checked, so all problems are reported at once.
+    pub fn parse_csv(content: &str) -> (Vec<NameRow>, Vec<RowError>) {
+        let mut records = parse_csv_records(content).into_iter();
+        let mut errors = Vec::new();
+
+        let Some((header_line, header)) = records.next() else {
+            errors.push(RowError {
+                line: 1,
+                error: "File is empty".to_string(),
+            });
+            return (Vec::new(), errors);
+        };
+        let header: Vec<String> = header.iter().map(|h| h.trim().to_lowercase()).collect();
+        let mut columns = [0; 4];
+        for (i, name) in CSV_COLUMNS.iter().enumerate() {
+            match header.iter().position(|h| h == name) {
+                Some(index) => columns[i] = index,
+                None => errors.push(RowError {
+                    line: header_line,
+                    error: format!("Header is missing the '{}' column", name),
+                }),
+            }
+        }
+        if !errors.is_empty() {
+            return (Vec::new(), errors);
+        }
+
+        let mut rows = Vec::new();
+        let mut seen = HashSet::new();
+        for (line, record) in records {
+            if record.iter().all(|field| field.trim().is_empty()) {
+                continue;
+            }
+            if record.len() != header.len() {
+                errors.push(RowError {
+                    line,
+                    error: format!("Expected {} columns, found {}", header.len(), record.len()),
+                });
+                continue;
+            }
+            let field =
+                |column: usize| Some(record[columns[column]].trim()).filter(|f| !f.is_empty());
+            match Self::validate_row(field(0), field(1), field(2), field(3)) {
+                Ok(row) => {
+                    let key = row
+                        .hash
+                        .clone()
+                        .or_else(|| row.cart.as_deref().map(|c| Self::cart_key(Path::new(c))));
+                    if !seen.insert(key) {
+                        errors.push(RowError {
+                            line,
+                            error: "Duplicate of an earlier row".to_string(),
+                        });
+                        continue;
+                    }
+                    rows.push(row);
+                }
+                Err(error) => errors.push(RowError { line, error }),
+            }
+        }
+        (rows, errors)
+    }
+
+    fn validate_row(
+        hash: Option<&str>,
+        console: Option<&str>,
+        name: Option<&str>,
+        cart: Option<&str>,
+    ) -> Result<NameRow, String> {
+        let name = name.ok_or("Name is empty")?;
+        let hash = match (hash, cart) {
+            (Some(_), Some(_)) => return Err("Give either a hash or a cart, not both".to_string()),
+            (None, None) => return Err("Row needs a hash or a cart".to_string()),
+            (Some(hash), None)
+                if hash.len() != 32 || !hash.chars().all(|c| c.is_ascii_hexdigit()) =>
+            {
+                return Err(format!("Not a ROM hash: {}", hash));
+            }
+            (hash, _) => hash.map(str::to_lowercase),
+        };
+        let console = console
+            .map(|c| {
+                ConsoleId::from_str(c)
+                    .map(|id| id.to_string())
+                    .ok_or(format!("Unknown console: {}", c))
+            })
+            .transpose()?;
+        Ok(NameRow {
+            hash,
+            console,
+            name: name.to_string(),
+            cart: cart.map(str::to_string),
+        })
+    }
+
+    /// Store rows as manual names, replacing what was there. Doesn't save.
+    pub fn import_rows(&mut self, rows: &[NameRow]) -> ImportSummary {
+        let mut summary = ImportSummary::default();
+        for row in rows {
+            let (map, key) = match (&row.hash, &row.cart) {
+                (Some(hash), _) => (&mut self.games, hash.clone()),
+                (None, Some(cart)) => (&mut self.carts, Self::cart_key(Path::new(cart))),
+                (None, None) => continue,
+            };
+            let entry = GameNameEntry {
+                name: row.name.clone(),
+                console: row.console.clone(),
+                source: NameSource::Manual,
+            };
+            match map.get(&key) {
+                Some(old)
+                    if old.name == entry.name
+                        && old.console == entry.console
+                        && old.source == entry.source =>
+                {
+                    summary.unchanged += 1;
+                    continue;
+                }
+                Some(_) => summary.updated += 1,
+                None => summary.added += 1,
+            }
+            map.insert(key, entry);
+        }
+        summary
+    }
+}
+
+/// One CSV record, quoting fields that need it
+fn csv_record(fields: &[&str]) -> String {
+    let fields: Vec<String> = fields
+        .iter()
+        .map(|field| {
+            if field.contains([',', '"', '\n', '\r']) {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                field.to_string()
+            }
+        })
+        .collect();
+    fields.join(",") + "\n"
+}
+
+/// Split CSV text into records, each with the line it starts on. Quoted
+/// fields may hold commas, doubled quotes and line breaks.
+fn parse_csv_records(content: &str) -> Vec<(usize, Vec<String>)> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut line = 1;
+    let mut record_line = 1;
+    let mut chars = content.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            '"' if in_quotes => in_quotes = false,
+            '"' if field.is_empty() => in_quotes = true,
+            ',' if !in_quotes => record.push(std::mem::take(&mut field)),
+            '\r' if !in_quotes => {}
+            '\n' if !in_quotes => {
+                record.push(std::mem::take(&mut field));
+                records.push((record_line, std::mem::take(&mut record)));
+                line += 1;
+                record_line = line;
+            }
+            '\n' => {
+                field.push(c);
+                line += 1;
+            }
+            _ => field.push(c),
+        }
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push((record_line, record));
+    }
+    records
+}
+
+/// The `ra_game_name` value from a plain `.kzi`, read the way the BIOS reads
+/// it: case-insensitive keys, optional quotes, `#`/`;` comments
+fn kzi_ra_game_name(content: &str) -> Option<String> {
+    content.lines().find_map(|line| {
+        let (key, value) = line.trim().split_once('=')?;
+        let key = key.trim().to_lowercase();
+        if !matches!(
+            key.as_str(),
+            "ra_game_name" | "ra-game-name" | "ra game name"
+        ) {
+            return None;
+        }
+        let value = value.trim();
+        let value = value
+            .strip_prefix('"')
+            .and_then(|v| v.strip_suffix('"'))
+            .unwrap_or(value);
+        (!value.is_empty()).then(|| value.to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn entry(name: &str, source: NameSource) -> GameNameEntry {
+        GameNameEntry {
+            name: name.to_string(),
+            console: None,
+            source,
+        }
+    }
+
+    #[test]
+    fn test_cart_scoped_names_win() {
+        let mut kzi = tempfile::Builder::new().suffix(".kzi").tempfile().unwrap();
+        writeln!(
+            kzi,
+            "Name=Shared Rom\nExec=game.gba\n# comment\nRA_Game_Name = \"From The Cart\""
+        )
+        .unwrap();
+
+        let mut mapping = GameNameMapping::default();
+        mapping
+            .games
+            .insert("abc".to_string(), entry("By Hash", NameSource::Manual));
+
+        // The cart's own ra_game_name beats the hash mapping
+        assert_eq!(
+            mapping.get_name("abc", Some(kzi.path())).as_deref(),
+            Some("From The Cart")
+        );
+        assert_eq!(mapping.get_name("abc", None).as_deref(), Some("By Hash"));
+
+        // A cart-scoped override beats both
+        mapping.carts.insert(
+            GameNameMapping::cart_key(kzi.path()),
+            entry("Override", NameSource::Manual),
+        );
+        assert_eq!(
+            mapping.get_name("abc", Some(kzi.path())).as_deref(),
+            Some("Override")
+        );
+        assert_eq!(mapping.get_cart_name(Path::new("/elsewhere.kzi")), None);
+
+        // Header suggestions only come from get_suggested_name
+        mapping
+            .games
+            .insert("def".to_string(), entry("From Header", NameSource::Header));
+        assert_eq!(mapping.get_name("def", None), None);
+        assert_eq!(
+            mapping.get_suggested_name("def").as_deref(),
+            Some("From Header")
+        );
+    }
+
+    #[test]
+    fn test_csv_round_trip() {
+        let mut mapping = GameNameMapping::default();
+        let hash = "0123456789abcdef0123456789abcdef";
+        mapping.games.insert(
+            hash.to_string(),
+            GameNameEntry {
+                name: "Zelda, \"Link's\" Awakening".to_string(),
+                console: Some("gb".to_string()),
+                source: NameSource::Manual,
+            },
+        );
+        mapping.games.insert(
+            "ffffffffffffffffffffffffffffffff".to_string(),
+            entry("From Header", NameSource::Header),
+        );
+        mapping.carts.insert(
+            "/media/cart.kzi".to_string(),
+            entry("My Cart", NameSource::Manual),
+        );
+
+        let csv = mapping.to_csv();
+        assert!(!csv.contains("From Header"));
+        let (rows, errors) = GameNameMapping::parse_csv(&csv);
+        assert!(errors.is_empty(), "{:?}", errors);
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].name, "Zelda, \"Link's\" Awakening");
+        assert_eq!(rows[1].cart.as_deref(), Some("/media/cart.kzi"));
+
+        // Importing its own export changes nothing
+        let mut copy = mapping.clone();
+        let summary = copy.import_rows(&rows);
+        assert_eq!(
+            summary,
+            ImportSummary {
+                added: 0,
+                updated: 0,
+                unchanged: 2
+            }
+        );
+
+        let summary = GameNameMapping::default().import_rows(&rows);
+        assert_eq!(summary.added, 2);
+    }
+
+    #[test]
+    fn test_csv_errors_are_reported_per_row() {
+        let csv = "name,hash,console,cart\n\
+            Good,0123456789ABCDEF0123456789ABCDEF,GBA,\n\
+            No Key,,gba,\n\
+            Bad Hash,xyz,,\n\
+            \"Multi\nLine\",,wii,/a.kzi\n\
+            Dupe,0123456789abcdef0123456789abcdef,,\n\
+            Short,abc\n";
+        let (rows, errors) = GameNameMapping::parse_csv(csv);
+
+        assert_eq!(rows.len(), 1);
+        assert_eq!(
+            rows[0].hash.as_deref(),
+            Some("0123456789abcdef0123456789abcdef")
+        );
+        assert_eq!(rows[0].console.as_deref(), Some("gba"));
+        let lines: Vec<usize> = errors.iter().map(|e| e.line).collect();
+        assert_eq!(lines, vec![3, 4, 5, 7, 8]);
+        assert!(errors[2].error.contains("wii"));
+
+        let (_, errors) = GameNameMapping::parse_csv("hash,name\n");
+        assert_eq!(errors.len(), 2);
+    }
+
+    #[test]
+    fn test_kzi_ra_game_name() {
+        assert_eq!(
+            kzi_ra_game_name("name=X\nra-game-name=Y").as_deref(),
+            Some("Y")
+        );
+        assert_eq!(kzi_ra_game_name("RA_Game_Name=\n"), None);
+        assert_eq!(kzi_ra_game_name("name=X"), None);
+
+        // Files without carts still load
+        let mapping: GameNameMapping = serde_json::from_str(r#"{"games":{}}"#).unwrap();
+        assert!(mapping.carts.is_empty());
+    }
+}
@@ -1,20 +1,36 @@
-use anyhow::{Context, Result, bail};
-use md5::{Md5, Digest};
-use std::fs::File;
-use std::io::{BufReader, Read, Seek, SeekFrom};
-use std::path::Path;
 use crate::types::ConsoleId;
+use anyhow::{Context, Result};
+use md5::{Digest, Md5};
+use rayon::prelude::*;
+use std::fs::File;
+use std::io::{self, BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
 
 /// Hash a ROM file for RetroAchievements identification
 /// Different consoles may require different hashing methods
 /// Uses streaming to avoid loading entire file into memory
 pub fn hash_rom(path: &Path, console_id: ConsoleId) -> Result<String> {
-    let file = File::open(path)
-        .context("Failed to open ROM file")?;
+    hash_rom_with_progress(path, console_id, |_, _| {})
+}
 
-    let metadata = file.metadata()
-        .context("Failed to get file metadata")?;
+/// `hash_rom`, calling `progress(bytes_read, file_size)` as each chunk
+/// (about 1 MB) is read
+pub fn hash_rom_with_progress(
+    path: &Path,
+    console_id: ConsoleId,
+    progress: impl FnMut(u64, u64),
+) -> Result<String> {
+    let file = File::open(path).context("Failed to open ROM file")?;
+
+    let metadata = file.metadata().context("Failed to get file metadata")?;
     let file_size = metadata.len() as usize;
+    let file = ProgressReader {
+        inner: file,
+        read: 0,
+        total: metadata.len(),
+        progress,
+    };
 
     // Stream hash based on console type
     let hasher = match console_id {
@@ -29,7 +45,7 @@ pub fn hash_rom(path: &Path, console_id: ConsoleId) -> Result<String> {
 }
 
 /// Hash NES ROM with streaming (strip 16-byte header if present)
-fn hash_nes_rom(mut file: File) -> Result<Md5> {
+fn hash_nes_rom(mut file: impl Read) -> Result<Md5> {
     let mut header = [0u8; 16];
     file.read_exact(&mut header)
         .context("Failed to read NES header")?;
@@ -42,8 +58,7 @@ fn hash_nes_rom(mut file: File) -> Result<Md5> {
         let mut reader = BufReader::with_capacity(1024 * 1024, file);
         let mut chunk = [0u8; 8192];
         loop {
-            let bytes_read = reader.read(&mut chunk)
-                .context("Failed to read ROM data")?;
+            let bytes_read = reader.read(&mut chunk).context("Failed to read ROM data")?;
             if bytes_read == 0 {
                 break;
             }
@@ -55,8 +70,7 @@ fn hash_nes_rom(mut file: File) -> Result<Md5> {
         let mut reader = BufReader::with_capacity(1024 * 1024, file);
         let mut chunk = [0u8; 8192];
         loop {
-            let bytes_read = reader.read(&mut chunk)
-                .context("Failed to read ROM data")?;
+            let bytes_read = reader.read(&mut chunk).context("Failed to read ROM data")?;
             if bytes_read == 0 {
                 break;
             }
@@ -68,7 +82,7 @@ fn hash_nes_rom(mut file: File) -> Result<Md5> {
 }
 
 /// Hash SNES ROM with streaming (strip 512-byte header if present)
-fn hash_snes_rom(mut file: File, file_size: usize) -> Result<Md5> {
+fn hash_snes_rom(mut file: impl Read, file_size: usize) -> Result<Md5> {
     let mut hasher = Md5::new();
 
     // Check if file has 512-byte copier header
@@ -84,8 +98,7 @@ fn hash_snes_rom(mut file: File, file_size: usize) -> Result<Md5> {
     let mut reader = BufReader::with_capacity(1024 * 1024, file);
     let mut chunk = [0u8; 8192];
     loop {
-        let bytes_read = reader.read(&mut chunk)
-            .context("Failed to read ROM data")?;
+        let bytes_read = reader.read(&mut chunk).context("Failed to read ROM data")?;
         if bytes_read == 0 {
             break;
         }
@@ -96,7 +109,7 @@ fn hash_snes_rom(mut file: File, file_size: usize) -> Result<Md5> {
 }
 
 /// Hash N64 ROM with streaming (byteswap if needed)
-fn hash_n64_rom(mut file: File) -> Result<Md5> {
+fn hash_n64_rom(mut file: impl Read) -> Result<Md5> {
     // Read first 4 bytes to determine byte order
     let mut magic = [0u8; 4];
     file.read_exact(&mut magic)
@@ -111,8 +124,7 @@ fn hash_n64_rom(mut file: File) -> Result<Md5> {
             let mut reader = BufReader::with_capacity(1024 * 1024, file);
             let mut chunk = [0u8; 8192];
             loop {
-                let bytes_read = reader.read(&mut chunk)
-                    .context("Failed to read ROM data")?;
+                let bytes_read = reader.read(&mut chunk).context("Failed to read ROM data")?;
                 if bytes_read == 0 {
                     break;
                 }
@@ -129,8 +141,7 @@ fn hash_n64_rom(mut file: File) -> Result<Md5> {
             let mut reader = BufReader::with_capacity(1024 * 1024, file);
             let mut chunk = [0u8; 8192];
             loop {
-                let bytes_read = reader.read(&mut chunk)
-                    .context("Failed to read ROM data")?;
+                let bytes_read = reader.read(&mut chunk).context("Failed to read ROM data")?;
                 if bytes_read == 0 {
                     break;
                 }
@@ -165,8 +176,7 @@ fn hash_n64_rom(mut file: File) -> Result<Md5> {
             let mut reader = BufReader::with_capacity(1024 * 1024, file);
             let mut chunk = [0u8; 8192];
             loop {
-                let bytes_read = reader.read(&mut chunk)
-                    .context("Failed to read ROM data")?;
+                let bytes_read = reader.read(&mut chunk).context("Failed to read ROM data")?;
                 if bytes_read == 0 {
                     break;
                 }
@@ -191,8 +201,7 @@ fn hash_n64_rom(mut file: File) -> Result<Md5> {
             let mut reader = BufReader::with_capacity(1024 * 1024, file);
             let mut chunk = [0u8; 8192];
             loop {
-                let bytes_read = reader.read(&mut chunk)
-                    .context("Failed to read ROM data")?;
+                let bytes_read = reader.read(&mut chunk).context("Failed to read ROM data")?;
                 if bytes_read == 0 {
                     break;
                 }
@@ -205,14 +214,13 @@ fn hash_n64_rom(mut file: File) -> Result<Md5> {
 }
 
 /// Hash generic ROM with streaming (no preprocessing)
-fn hash_generic_rom(file: File) -> Result<Md5> {
+fn hash_generic_rom(file: impl Read) -> Result<Md5> {
     let mut hasher = Md5::new();
     let mut reader = BufReader::with_capacity(1024 * 1024, file);
     let mut chunk = [0u8; 8192];
 
     loop {
-        let bytes_read = reader.read(&mut chunk)
-            .context("Failed to read ROM data")?;
+        let bytes_read = reader.read(&mut chunk).context("Failed to read ROM data")?;
         if bytes_read == 0 {
             break;
         }
@@ -222,171 +230,60 @@ fn hash_generic_rom(file: File) -> Result<Md5> {
     Ok(hasher)
 }
 
-/// Get the hash type name for a console
-pub fn hash_type_name(console_id: ConsoleId) -> &'static str {
-    match console_id {
-        ConsoleId::NES => "MD5 (headerless)",
-        ConsoleId::SNES => "MD5 (headerless)",
-        ConsoleId::Nintendo64 => "MD5 (big-endian)",
-        _ => "MD5",
-    }
+/// Counts the bytes read for `hash_rom_with_progress`
+struct ProgressReader<R, F> {
+    inner: R,
+    read: u64,
+    total: u64,
+    progress: F,
 }
 
-/// Auto-detect console type from ROM file
-/// Checks file extension first, then verifies with magic bytes
-pub fn detect_console(path: &Path) -> Result<ConsoleId> {
-    // First, try to detect from file extension
-    if let Some(ext) = path.extension() {
-        if let Some(ext_str) = ext.to_str() {
-            let ext_lower = ext_str.to_lowercase();
-            if let Some(console) = detect_from_extension(&ext_lower) {
-                // Verify with magic bytes if possible
-                if let Ok(verified) = verify_with_magic_bytes(path, console) {
-                    if verified {
-                        return Ok(console);
-                    }
-                }
-                // If verification fails but extension is clear, still use it
-                // (some ROMs might have unusual headers)
-                return Ok(console);
-            }
+impl<R: Read, F: FnMut(u64, u64)> Read for ProgressReader<R, F> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        if n > 0 {
+            self.read += n as u64;
+            (self.progress)(self.read, self.total);
         }
-    }
-
-    // If extension didn't work, try magic bytes only
-    detect_from_magic_bytes(path)
-}
-
-/// Detect console from file extension
-fn detect_from_extension(ext: &str) -> Option<ConsoleId> {
-    match ext {
-        "gba" => Some(ConsoleId::GameBoyAdvance),
-        "gb" => Some(ConsoleId::GameBoy),
-        "gbc" => Some(ConsoleId::GameBoyColor),
-        "nes" | "fds" => Some(ConsoleId::NES),
-        "snes" | "sfc" | "smc" => Some(ConsoleId::SNES),
-        "n64" | "z64" | "v64" | "u64" => Some(ConsoleId::Nintendo64),
-        "nds" => Some(ConsoleId::NintendoDS),
-        "psx" | "ps1" | "bin" | "cue" | "img" => Some(ConsoleId::PlayStation),
-        "ps2" | "iso" => Some(ConsoleId::PlayStation2),
-        "gen" | "md" | "smd" => Some(ConsoleId::MegaDrive),
-        "sms" => Some(ConsoleId::MasterSystem),
-        "a26" => Some(ConsoleId::Atari2600),
-        "vb" => Some(ConsoleId::VirtualBoy),
-        _ => None,
+        Ok(n)
     }
 }
 
-/// Verify console type using magic bytes
-fn verify_with_magic_bytes(path: &Path, console: ConsoleId) -> Result<bool> {
-    let mut file = File::open(path)
-        .context("Failed to open ROM file")?;
-    
-    let mut buffer = vec![0u8; 16]; // Read first 16 bytes for magic detection
-    let bytes_read = file.read(&mut buffer)
-        .context("Failed to read ROM file")?;
-
-    if bytes_read < 4 {
-        return Ok(false);
-    }
-
-    let verified = match console {
-        ConsoleId::NES => &buffer[0..4] == b"NES\x1a",
-        ConsoleId::SNES => {
-            // SNES ROMs can have headers, check for common patterns
-            // Check at offset 0 or 512 (header offset)
-            // SNES ROMs often start with specific patterns, but it's not always reliable
-            // For now, just check if it's not obviously wrong
-            // SNES detection is less reliable, so we trust extension
-            let _check_offset = if bytes_read > 512 && buffer.len() > 512 {
-                &buffer[512..512+4]
-            } else {
-                &buffer[0..4]
-            };
-            true
-        },
-        ConsoleId::Nintendo64 => {
-            // N64 ROMs have specific magic bytes
-            matches!(&buffer[0..4], 
-                [0x80, 0x37, 0x12, 0x40] | // Big-endian (z64)
-                [0x40, 0x12, 0x37, 0x80] | // Little-endian (n64)
-                [0x37, 0x80, 0x40, 0x12]   // Byte-swapped (v64)
-            )
-        },
-        ConsoleId::GameBoyAdvance | ConsoleId::GameBoy | ConsoleId::GameBoyColor => {
-            // Game Boy ROMs have a Nintendo logo at specific offsets
-            // GBA: 0x04-0x9F, GB/GBC: 0x104-0x133
-            if bytes_read >= 0xA0 {
-                // Check for Nintendo logo pattern (simplified check)
-                // Real check would verify the exact logo bytes
-                buffer[0x04] == 0x24 || buffer[0x04] == 0xCE // Common first bytes
-            } else {
-                true // Can't verify, trust extension
-            }
-        },
-        ConsoleId::NintendoDS => {
-            // NDS ROMs have "Nintendo DS" string at offset 0x0C
-            bytes_read >= 0x10 && 
-            buffer[0x0C..0x0C+12].iter().any(|&b| b != 0)
-        },
-        ConsoleId::PlayStation => {
-            // PSX discs have "PLAYSTATION" or "PS-X EXE" markers
-            // This is complex for disc images, so we'll trust extension
-            true
-        },
-        _ => true, // For other consoles, trust extension
-    };
-
-    Ok(verified)
+/// Hash many ROMs in parallel, one file per thread, for batch jobs over a
+/// whole library. `progress(bytes_read, total_bytes)` covers every file and
+/// may be called from any of the threads. Results keep the order of `roms`.
+pub fn hash_roms(
+    roms: &[(PathBuf, ConsoleId)],
+    progress: impl Fn(u64, u64) + Sync,
+) -> Vec<Result<String>> {
+    let total: u64 = roms
+        .iter()
+        .filter_map(|(path, _)| path.metadata().ok())
+        .map(|metadata| metadata.len())
+        .sum();
+    let done = AtomicU64::new(0);
+
+    roms.par_iter()
+        .map(|(path, console_id)| {
+            let mut last = 0;
+            hash_rom_with_progress(path, *console_id, |read, _| {
+                let read_total = done.fetch_add(read - last, Ordering::Relaxed) + read - last;
+                last = read;
+                progress(read_total, total);
+            })
+            .with_context(|| format!("Failed to hash {}", path.display()))
+        })
+        .collect()
 }
 
-/// Detect console from magic bytes only (fallback when extension fails)
-fn detect_from_magic_bytes(path: &Path) -> Result<ConsoleId> {
-    let mut file = File::open(path)
-        .context("Failed to open ROM file")?;
-    
-    let mut buffer = vec![0u8; 16];
-    let bytes_read = file.read(&mut buffer)
-        .context("Failed to read ROM file")?;
-
-    if bytes_read < 4 {
-        bail!("File too small to detect console type");
-    }
-
-    // Check for known magic bytes
-    if &buffer[0..4] == b"NES\x1a" {
-        return Ok(ConsoleId::NES);
-    }
-
-    if matches!(&buffer[0..4], 
-        [0x80, 0x37, 0x12, 0x40] | 
-        [0x40, 0x12, 0x37, 0x80] | 
-        [0x37, 0x80, 0x40, 0x12]
-    ) {
-        return Ok(ConsoleId::Nintendo64);
-    }
-
-    // Check for SNES header (512 bytes offset)
-    if bytes_read > 512 {
-        let mut header_buffer = vec![0u8; 4];
-        file.seek(SeekFrom::Start(512))?;
-        if file.read_exact(&mut header_buffer).is_ok() {
-            // SNES ROMs have specific patterns, but detection is complex
-            // We'll need extension for SNES
-        }
-    }
-
-    // Check for Game Boy family
-    if bytes_read >= 0xA0 {
-        // Check Nintendo logo area
-        if buffer[0x04] == 0x24 || buffer[0x04] == 0xCE {
-            // Could be GB/GBC/GBA, but need more info
-            // For now, default to GBA as most common
-            return Ok(ConsoleId::GameBoyAdvance);
-        }
+/// Get the hash type name for a console
+pub fn hash_type_name(console_id: ConsoleId) -> &'static str {
+    match console_id {
+        ConsoleId::NES => "MD5 (headerless)",
+        ConsoleId::SNES => "MD5 (headerless)",
+        ConsoleId::Nintendo64 => "MD5 (big-endian)",
+        _ => "MD5",
     }
-
-    bail!("Could not detect console type from file. Please specify --console manually.")
 }
 
 #[cfg(test)]
@@ -426,5 +323,38 @@ mod tests {
         // Verify it produces a valid MD5 hash
         assert_eq!(hash.len(), 32);
     }
-}
 
+    #[test]
+    fn test_progress_and_parallel_hashing() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut roms = Vec::new();
+        for i in 0..4 {
+            let path = dir.path().join(format!("rom{}.gba", i));
+            std::fs::write(&path, vec![i as u8; 3 * 1024 * 1024 + i]).unwrap();
+            roms.push((path, ConsoleId::GameBoyAdvance));
+        }
+        roms.push((dir.path().join("missing.gba"), ConsoleId::GameBoyAdvance));
+
+        let mut reports = Vec::new();
+        let single =
+            hash_rom_with_progress(&roms[0].0, ConsoleId::GameBoyAdvance, |read, total| {
+                reports.push((read, total));
+            })
+            .unwrap();
+        assert!(reports.len() > 1);
+        assert_eq!(reports.last(), Some(&(3 * 1024 * 1024, 3 * 1024 * 1024)));
+
+        let max = AtomicU64::new(0);
+        let results = hash_roms(&roms, |read, total| {
+            assert!(read <= total);
+            max.fetch_max(read, Ordering::Relaxed);
+        });
+        assert_eq!(results.len(), 5);
+        assert_eq!(results[0].as_ref().unwrap(), &single);
+        for (result, (path, console)) in results.iter().zip(&roms).take(4) {
+            assert_eq!(result.as_ref().unwrap(), &hash_rom(path, *console).unwrap());
+        }
+        assert!(results[4].is_err());
+        assert_eq!(max.load(Ordering::Relaxed), 4 * 3 * 1024 * 1024 + 6);
+    }
+}
@@ -0,0 +1,196 @@
+//! Game titles from ROM headers, for games RetroAchievements doesn't know
+//!
+//! GBA, SNES and Genesis carts carry an internal name; NES ROMs don't, so
+//! their filename is used instead (as it is for any console without a
+//! readable header). Titles are cleaned up before use: padding and control
+//! bytes dropped, all-caps names title-cased, and dump tags like `(USA)` or
+//! `[!]` stripped from filenames.
+
+use crate::types::ConsoleId;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+/// Bytes read from the start of the file; covers the SNES HiROM header
+/// behind a 512-byte copier header
+const SCAN_BYTES: u64 = 0x10200;
+
+/// Suggested title for a ROM: its internal name if the console has one,
+/// otherwise the cleaned-up filename
+pub fn suggest_name(path: &Path, console: ConsoleId) -> Option<String> {
+    let mut data = Vec::new();
+    if let Ok(file) = File::open(path) {
+        let _ = file.take(SCAN_BYTES).read_to_end(&mut data);
+    }
+    let file_size = path
+        .metadata()
+        .map(|m| m.len())
+        .unwrap_or(data.len() as u64);
+
+    header_title(&data, file_size, console)
+        .and_then(|raw| normalize_title(&raw))
+        .or_else(|| title_from_filename(path))
+}
+
+/// The raw internal name, if the console stores one and it's readable
+fn header_title(data: &[u8], file_size: u64, console: ConsoleId) -> Option<String> {
+    match console {
+        ConsoleId::GameBoyAdvance => ascii_field(data, 0xA0, 12),
+        ConsoleId::SNES => snes_title(data, file_size),
+        // Overseas name first; the domestic one is often in Japanese
+        ConsoleId::MegaDrive => {
+            let system = data.get(0x100..0x110)?;
+            if !(system.starts_with(b"SEGA") || system.starts_with(b" SEGA")) {
+                return None;
+            }
+            ascii_field(data, 0x150, 48).or_else(|| ascii_field(data, 0x120, 48))
+        }
+        _ => None,
+    }
+}
+
+/// The 21-byte title of whichever SNES header (LoROM or HiROM) has a valid
+/// checksum, skipping a 512-byte copier header
+fn snes_title(data: &[u8], file_size: u64) -> Option<String> {
+    let copier = if file_size % 1024 == 512 { 512 } else { 0 };
+    [0x7FC0, 0xFFC0].into_iter().find_map(|base| {
+        let header = data.get(copier + base..copier + base + 0x20)?;
+        let complement = u16::from_le_bytes([header[0x1C], header[0x1D]]);
+        let checksum = u16::from_le_bytes([header[0x1E], header[0x1F]]);
+        if complement ^ checksum != 0xFFFF {
+            return None;
+        }
+        ascii_field(data, copier + base, 21)
+    })
+}
+
+/// A fixed-width text field; None if it holds anything but printable ASCII
+/// and padding
+fn ascii_field(data: &[u8], offset: usize, len: usize) -> Option<String> {
+    let field = data.get(offset..offset + len)?;
+    let text: &[u8] = match field.iter().position(|&b| b == 0) {
+        Some(end) => &field[..end],
+        None => field,
+    };
+    if !text.iter().all(|b| (0x20..0x7F).contains(b)) {
+        return None;
+    }
+    Some(String::from_utf8_lossy(text).into_owned())
+}
+
+/// Collapse whitespace and title-case all-caps names. None if fewer than two
+/// letters or digits are left.
+pub fn normalize_title(raw: &str) -> Option<String> {
+    let words: Vec<&str> = raw
+        .split(|c: char| c.is_whitespace() || c == '_' || c.is_control())
+        .filter(|w| !w.is_empty())
+        .collect();
+    let title = words.join(" ");
+    if title.chars().filter(|c| c.is_alphanumeric()).count() < 2 {
+        return None;
+    }
+
+    if title.chars().any(|c| c.is_lowercase()) {
+        return Some(title);
+    }
+    Some(
+        words
+            .iter()
+            .map(|w| title_case_word(w))
+            .collect::<Vec<_>>()
+            .join(" "),
+    )
+}
+
+fn title_case_word(word: &str) -> String {
+    // Sequel numbers stay as they are
+    if word.chars().all(|c| matches!(c, 'I' | 'V' | 'X')) {
+        return word.to_string();
+    }
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first
+            .to_uppercase()
+            .chain(chars.flat_map(|c| c.to_lowercase()))
+            .collect(),
+        None => String::new(),
+    }
+}
+
+/// The file stem without dump tags like `(USA)`, `(Rev 1)` or `[!]`
+fn title_from_filename(path: &Path) -> Option<String> {
+    let stem = path.file_stem()?.to_str()?;
+    let mut title = String::new();
+    let mut depth = 0;
+    for c in stem.chars() {
+        match c {
+            '(' | '[' => depth += 1,
+            ')' | ']' => depth = (depth - 1).max(0),
+            _ if depth == 0 => title.push(c),
+            _ => {}
+        }
+    }
+    normalize_title(&title)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_header_titles() {
+        let mut gba = vec![0u8; 0xC0];
+        gba[0xA0..0xAA].copy_from_slice(b"POKEMON EM");
+        assert_eq!(
+            header_title(&gba, 0xC0, ConsoleId::GameBoyAdvance).as_deref(),
+            Some("POKEMON EM")
+        );
+
+        // LoROM header with a valid checksum, behind a copier header
+        let mut snes = vec![0u8; 0x8200];
+        let base = 512 + 0x7FC0;
+        snes[base..base + 21].copy_from_slice(b"SUPER MARIOWORLD     ");
+        snes[base + 0x1C..base + 0x20].copy_from_slice(&[0x5F, 0x5E, 0xA0, 0xA1]);
+        assert_eq!(
+            header_title(&snes, 0x8200, ConsoleId::SNES).as_deref(),
+            Some("SUPER MARIOWORLD     ")
+        );
+        // Same data without a valid checksum
+        snes[base + 0x1E] = 0;
+        assert_eq!(header_title(&snes, 0x8200, ConsoleId::SNES), None);
+
+        let mut genesis = vec![b' '; 0x200];
+        genesis[0x100..0x110].copy_from_slice(b"SEGA MEGA DRIVE ");
+        genesis[0x150..0x163].copy_from_slice(b"SONIC THE HEDGEHOG ");
+        let title = header_title(&genesis, 0x200, ConsoleId::MegaDrive).unwrap();
+        assert_eq!(
+            normalize_title(&title).as_deref(),
+            Some("Sonic The Hedgehog")
+        );
+
+        assert_eq!(header_title(&gba, 0xC0, ConsoleId::NES), None);
+    }
+
+    #[test]
+    fn test_normalize_and_filename() {
+        assert_eq!(
+            normalize_title("  FINAL FANTASY III ").as_deref(),
+            Some("Final Fantasy III")
+        );
+        assert_eq!(
+            normalize_title("Already Mixed  Case").as_deref(),
+            Some("Already Mixed Case")
+        );
+        assert_eq!(normalize_title(" - "), None);
+
+        let path = Path::new("/roms/Legend_of_Zelda, The (USA) (Rev 1) [!].nes");
+        assert_eq!(
+            title_from_filename(path).as_deref(),
+            Some("Legend of Zelda, The")
+        );
+        assert_eq!(
+            suggest_name(Path::new("/missing/Metroid (Europe).nes"), ConsoleId::NES).as_deref(),
+            Some("Metroid")
+        );
+    }
+}
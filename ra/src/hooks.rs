@@ -0,0 +1,443 @@
+//! User hooks for RetroAchievements events
+//!
+//! `~/.config/kazeta/ra-hooks.toml` lists commands to run or webhooks to POST
+//! to when an achievement unlocks, a game starts or a set is mastered:
+//!
+//! ```toml
+//! timeout_secs = 10
+//!
+//! [[hook]]
+//! event = "unlock"
+//! command = "notify-send \"$KAZETA_EVENT\""
+//!
+//! [[hook]]
+//! event = "all"
+//! url = "https://example.com/kazeta"
+//! timeout_secs = 3
+//! ```
+//!
+//! Commands run through `sh -c` with the JSON payload on stdin and the event
+//! name in `KAZETA_EVENT`; webhooks receive the same payload as the POST body.
+//! Hooks run concurrently on their own threads, so the event is handled
+//! without waiting on them, and are killed or abandoned once their timeout
+//! passes. Commands wait for them with `wait_pending` before exiting.
+//! Failures are appended to `logs/ra-hooks.log` in the data directory.
+
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+/// Timeout used when the config doesn't set one
+const DEFAULT_TIMEOUT_SECS: u64 = 10;
+
+/// Hooks fired by this process that may still be running
+static PENDING: Mutex<Vec<JoinHandle<usize>>> = Mutex::new(Vec::new());
+
+/// Events hooks can subscribe to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HookEvent {
+    Unlock,
+    GameStart,
+    Mastery,
+    /// Subscribes to every event
+    All,
+}
+
+impl HookEvent {
+    pub fn name(&self) -> &'static str {
+        match self {
+            HookEvent::Unlock => "unlock",
+            HookEvent::GameStart => "game_start",
+            HookEvent::Mastery => "mastery",
+            HookEvent::All => "all",
+        }
+    }
+
+    fn matches(&self, event: HookEvent) -> bool {
+        *self == HookEvent::All || *self == event
+    }
+}
+
+/// A single `[[hook]]` entry
+#[derive(Debug, Clone, Deserialize)]
+pub struct Hook {
+    pub event: HookEvent,
+    /// Shell command to run
+    #[serde(default)]
+    pub command: Option<String>,
+    /// URL to POST the payload to
+    #[serde(default)]
+    pub url: Option<String>,
+    /// Overrides the global timeout
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+}
+
+impl Hook {
+    fn describe(&self) -> String {
+        match (&self.command, &self.url) {
+            (Some(command), _) => format!("command `{}`", command),
+            (None, Some(url)) => format!("webhook {}", url),
+            (None, None) => "empty hook".to_string(),
+        }
+    }
+}
+
+/// Contents of ra-hooks.toml
+#[derive(Debug, Clone, Deserialize)]
+pub struct HooksConfig {
+    #[serde(default = "default_timeout")]
+    pub timeout_secs: u64,
+    #[serde(default, rename = "hook")]
+    pub hooks: Vec<Hook>,
+}
+
+fn default_timeout() -> u64 {
+    DEFAULT_TIMEOUT_SECS
+}
+
+impl Default for HooksConfig {
+    fn default() -> Self {
+        Self {
+            timeout_secs: DEFAULT_TIMEOUT_SECS,
+            hooks: Vec::new(),
+        }
+    }
+}
+
+impl HooksConfig {
+    /// Load hooks from ~/.config/kazeta/ra-hooks.toml, or none if it doesn't exist
+    pub fn load() -> Result<Self> {
+        let path = dirs::config_dir()
+            .context("Could not determine config directory")?
+            .join("kazeta")
+            .join("ra-hooks.toml");
+
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(&path).context("Failed to read RA hooks file")?;
+        Self::parse(&content)
+    }
+
+    pub fn parse(content: &str) -> Result<Self> {
+        let config: HooksConfig =
+            toml::from_str(content).context("Failed to parse RA hooks TOML")?;
+
+        for hook in &config.hooks {
+            if hook.command.is_some() == hook.url.is_some() {
+                bail!(
+                    "Each hook needs exactly one of `command` or `url` ({:?} hook)",
+                    hook.event.name()
+                );
+            }
+        }
+        Ok(config)
+    }
+}
+
+/// Runs the hooks subscribed to an event and records failures
+pub struct HookRunner {
+    config: HooksConfig,
+    log_path: Option<PathBuf>,
+}
+
+impl HookRunner {
+    pub fn new(config: HooksConfig, log_path: Option<PathBuf>) -> Self {
+        Self { config, log_path }
+    }
+
+    /// Runner for the user's hooks, logging to the diagnostics directory
+    pub fn load() -> Result<Self> {
+        let log_path =
+            dirs::home_dir().map(|home| home.join(".local/share/kazeta-plus/logs/ra-hooks.log"));
+        Ok(Self::new(HooksConfig::load()?, log_path))
+    }
+
+    /// Run every hook subscribed to `event` and wait for them to finish or
+    /// time out. Returns the number of hooks that failed.
+    pub fn run(&self, event: HookEvent, payload: &serde_json::Value) -> usize {
+        let mut payload = payload.clone();
+        if let Some(object) = payload.as_object_mut() {
+            object.insert("event".to_string(), event.name().into());
+            object.insert(
+                "timestamp".to_string(),
+                chrono::Utc::now().timestamp().into(),
+            );
+        }
+        let body = payload.to_string();
+
+        let handles: Vec<_> = self
+            .config
+            .hooks
+            .iter()
+            .filter(|hook| hook.event.matches(event))
+            .cloned()
+            .map(|hook| {
+                let body = body.clone();
+                let timeout =
+                    Duration::from_secs(hook.timeout_secs.unwrap_or(self.config.timeout_secs));
+                thread::spawn(move || {
+                    let result = match (&hook.command, &hook.url) {
+                        (Some(command), _) => run_command(command, event, &body, timeout),
+                        (None, Some(url)) => post_webhook(url, &body, timeout),
+                        (None, None) => Ok(()),
+                    };
+                    result.map_err(|e| format!("{} failed: {:#}", hook.describe(), e))
+                })
+            })
+            .collect();
+
+        let mut failed = 0;
+        for handle in handles {
+            let error = match handle.join() {
+                Ok(Ok(())) => continue,
+                Ok(Err(e)) => e,
+                Err(_) => "hook thread panicked".to_string(),
+            };
+            failed += 1;
+            self.log_failure(event, &error);
+        }
+        failed
+    }
+
+    /// Run the hooks for `event` on a new thread, like `run`, without
+    /// waiting. The handle yields the number of hooks that failed.
+    pub fn spawn(self, event: HookEvent, payload: serde_json::Value) -> JoinHandle<usize> {
+        thread::spawn(move || self.run(event, &payload))
+    }
+
+    fn has_hooks(&self, event: HookEvent) -> bool {
+        self.config
+            .hooks
+            .iter()
+            .any(|hook| hook.event.matches(event))
+    }
+
+    fn log_failure(&self, event: HookEvent, error: &str) {
+        let line = format!(
+            "{} [{}] {}",
+            chrono::Local::now().format("%Y-%m-%d %H:%M:%S"),
+            event.name(),
+            error
+        );
+        eprintln!("Hook error: {}", line);
+
+        let Some(path) = &self.log_path else { return };
+        if let Err(e) = append_line(path, &line) {
+            eprintln!("Failed to write hook log {}: {}", path.display(), e);
+        }
+    }
+}
+
+/// Start the hooks for an event in the background, ignoring a broken hooks
+/// file beyond reporting it
+pub fn fire(event: HookEvent, payload: serde_json::Value) {
+    match HookRunner::load() {
+        Ok(runner) if runner.has_hooks(event) => {
+            let handle = runner.spawn(event, payload);
+            PENDING
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .push(handle);
+        }
+        Ok(_) => {}
+        Err(e) => eprintln!("Skipping RA hooks: {:#}", e),
+    }
+}
+
+/// Wait for hooks started with `fire` to finish or time out, so a command
+/// doesn't exit under them. Call once the event has been handled.
+pub fn wait_pending() {
+    let handles = std::mem::take(&mut *PENDING.lock().unwrap_or_else(|e| e.into_inner()));
+    for handle in handles {
+        let _ = handle.join();
+    }
+}
+
+fn run_command(command: &str, event: HookEvent, body: &str, timeout: Duration) -> Result<()> {
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .env("KAZETA_EVENT", event.name())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .context("Failed to start")?;
+
+    if let Some(mut stdin) = child.stdin.take() {
+        // On its own thread: a command that never reads stdin would block a
+        // large write before the timeout starts. It may also exit without
+        // reading it, which only fails the write.
+        let body = body.to_string();
+        thread::spawn(move || {
+            let _ = stdin.write_all(body.as_bytes());
+        });
+    }
+
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(status) = child.try_wait().context("Failed to wait for command")? {
+            if status.success() {
+                return Ok(());
+            }
+            bail!("exited with {}", status);
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            bail!("timed out after {}s", timeout.as_secs());
+        }
+        thread::sleep(Duration::from_millis(20));
+    }
+}
+
+fn post_webhook(url: &str, body: &str, timeout: Duration) -> Result<()> {
+    let client = reqwest::blocking::Client::builder()
+        .timeout(timeout)
+        .build()
+        .context("Failed to create HTTP client")?;
+
+    client
+        .post(url)
+        .header("Content-Type", "application/json")
+        .body(body.to_string())
+        .send()
+        .context("Request failed")?
+        .error_for_status()
+        .context("Webhook returned an error")?;
+    Ok(())
+}
+
+fn append_line(path: &Path, line: &str) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", line)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_hooks() {
+        let config = HooksConfig::parse(
+            r#"
+            [[hook]]
+            event = "unlock"
+            command = "true"
+
+            [[hook]]
+            event = "all"
+            url = "http://localhost/hook"
+            timeout_secs = 2
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.timeout_secs, DEFAULT_TIMEOUT_SECS);
+        assert_eq!(config.hooks.len(), 2);
+        assert!(config.hooks[1].event.matches(HookEvent::Mastery));
+        assert!(!config.hooks[0].event.matches(HookEvent::GameStart));
+
+        assert!(HooksConfig::parse("[[hook]]\nevent = \"unlock\"\n").is_err());
+        assert!(HooksConfig::parse("[[hook]]\nevent = \"levelup\"\ncommand = \"true\"\n").is_err());
+    }
+
+    #[test]
+    fn test_command_receives_payload() {
+        let tmp = TempDir::new().unwrap();
+        let out = tmp.path().join("out.json");
+        let event_out = tmp.path().join("event.txt");
+        let config = HooksConfig::parse(&format!(
+            "[[hook]]\nevent = \"game_start\"\ncommand = \"cat > '{}'; echo $KAZETA_EVENT > '{}'\"\n",
+            out.display(),
+            event_out.display()
+        ))
+        .unwrap();
+        let runner = HookRunner::new(config, None);
+
+        // Other events don't run the hook
+        assert_eq!(runner.run(HookEvent::Unlock, &serde_json::json!({})), 0);
+        assert!(!out.exists());
+
+        assert_eq!(
+            runner.run(HookEvent::GameStart, &serde_json::json!({ "game_id": 7 })),
+            0
+        );
+        let payload: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&out).unwrap()).unwrap();
+        assert_eq!(payload["game_id"], 7);
+        assert_eq!(payload["event"], "game_start");
+        assert_eq!(fs::read_to_string(&event_out).unwrap().trim(), "game_start");
+    }
+
+    #[test]
+    fn test_failures_and_timeouts_are_logged() {
+        let tmp = TempDir::new().unwrap();
+        let log = tmp.path().join("logs").join("ra-hooks.log");
+        let config = HooksConfig::parse(
+            r#"
+            timeout_secs = 1
+
+            [[hook]]
+            event = "mastery"
+            command = "exit 3"
+
+            [[hook]]
+            event = "mastery"
+            command = "sleep 5"
+            "#,
+        )
+        .unwrap();
+        let runner = HookRunner::new(config, Some(log.clone()));
+
+        let started = Instant::now();
+        assert_eq!(runner.run(HookEvent::Mastery, &serde_json::json!({})), 2);
+        assert!(started.elapsed() < Duration::from_secs(4));
+
+        let logged = fs::read_to_string(&log).unwrap();
+        assert!(logged.contains("[mastery] command `exit 3` failed"));
+        assert!(logged.contains("timed out after 1s"));
+    }
+
+    #[test]
+    fn test_spawn_does_not_wait_for_hooks() {
+        let config =
+            HooksConfig::parse("[[hook]]\nevent = \"unlock\"\ncommand = \"sleep 1\"\n").unwrap();
+        let runner = HookRunner::new(config, None);
+
+        let started = Instant::now();
+        let handle = runner.spawn(HookEvent::Unlock, serde_json::json!({}));
+        assert!(started.elapsed() < Duration::from_millis(500));
+        assert_eq!(handle.join().unwrap(), 0);
+        assert!(started.elapsed() >= Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_timeout_covers_unread_stdin() {
+        // Bigger than a pipe buffer, to a command that never reads it
+        let config = HooksConfig::parse(
+            "timeout_secs = 1\n[[hook]]\nevent = \"unlock\"\ncommand = \"sleep 5\"\n",
+        )
+        .unwrap();
+        let runner = HookRunner::new(config, None);
+        let payload = serde_json::json!({ "padding": "x".repeat(1 << 20) });
+
+        let started = Instant::now();
+        assert_eq!(runner.run(HookEvent::Unlock, &payload), 1);
+        assert!(started.elapsed() < Duration::from_secs(4));
+    }
+}
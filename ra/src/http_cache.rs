@@ -0,0 +1,281 @@
+//! HTTP-level cache for RA web API responses
+//!
+//! Responses are kept on disk with their `ETag` and `Last-Modified`
+//! validators. Game-level data (a game's achievement set, the console game
+//! lists) is reused without asking the server for `http-cache-max-age`
+//! seconds; after that, and always for anything holding a user's progress,
+//! the request is made conditional so an unchanged response comes back as a
+//! bodyless 304.
+//!
+//! Entries are keyed by the URL without the API key, so a new key doesn't
+//! empty the cache and the key never lands on disk.
+
+use anyhow::{Context, Result};
+use md5::{Digest, Md5};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+/// Query parameters left out of cache keys
+const SECRET_PARAMS: &[&str] = &["y", "t"];
+
+const STATS_FILE: &str = "stats.json";
+
+/// A stored response and its validators
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CachedResponse {
+    /// Request URL without the API key
+    pub url: String,
+    #[serde(default)]
+    pub etag: Option<String>,
+    #[serde(default)]
+    pub last_modified: Option<String>,
+    /// Unix timestamp the server last confirmed the body
+    pub fetched_at: i64,
+    pub body: String,
+}
+
+impl CachedResponse {
+    /// Whether the body can be used without asking the server.
+    /// `max_age` of None always asks.
+    pub fn is_fresh(&self, max_age: Option<u64>, now: i64) -> bool {
+        max_age.is_some_and(|age| now - self.fetched_at < age as i64)
+    }
+
+    /// Headers that turn a request for this URL into a conditional one
+    pub fn validators(&self) -> Vec<(reqwest::header::HeaderName, &str)> {
+        let mut headers = Vec::new();
+        if let Some(etag) = &self.etag {
+            headers.push((reqwest::header::IF_NONE_MATCH, etag.as_str()));
+        }
+        if let Some(modified) = &self.last_modified {
+            headers.push((reqwest::header::IF_MODIFIED_SINCE, modified.as_str()));
+        }
+        headers
+    }
+}
+
+/// How requests were answered, across runs
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CacheStats {
+    /// Served from the cache without a request
+    pub hits: u64,
+    /// Confirmed unchanged by the server (304)
+    pub revalidated: u64,
+    /// Fetched in full
+    pub misses: u64,
+}
+
+impl CacheStats {
+    /// Share of lookups that didn't download a body, 0-100
+    pub fn hit_rate(&self) -> f64 {
+        let total = self.hits + self.revalidated + self.misses;
+        if total == 0 {
+            0.0
+        } else {
+            (self.hits + self.revalidated) as f64 * 100.0 / total as f64
+        }
+    }
+}
+
+/// What's on disk, for `kazeta-ra diagnostics`
+#[derive(Debug, Clone, Default)]
+pub struct CacheSummary {
+    pub entries: usize,
+    pub bytes: u64,
+    pub stats: CacheStats,
+}
+
+pub struct HttpCache {
+    dir: PathBuf,
+}
+
+impl HttpCache {
+    pub fn new() -> Result<Self> {
+        let dir = dirs::home_dir()
+            .context("Could not find home directory")?
+            .join(".local/share/kazeta-plus/ra_cache/http");
+
+        Self::with_dir(dir)
+    }
+
+    pub fn with_dir(dir: PathBuf) -> Result<Self> {
+        fs::create_dir_all(&dir).context("Failed to create HTTP cache directory")?;
+        Ok(Self { dir })
+    }
+
+    fn entry_path(&self, url: &str) -> PathBuf {
+        let digest = Md5::digest(cache_url(url).as_bytes());
+        let name: String = digest.iter().map(|b| format!("{:02x}", b)).collect();
+        self.dir.join(format!("{}.json", name))
+    }
+
+    /// The stored response for a URL, fresh or not
+    pub fn get(&self, url: &str) -> Option<CachedResponse> {
+        let contents = fs::read_to_string(self.entry_path(url)).ok()?;
+        serde_json::from_str(&contents).ok()
+    }
+
+    /// The stored response if it can be used without a request, counting the hit
+    pub fn fresh(&self, url: &str, max_age: Option<u64>, now: i64) -> Option<CachedResponse> {
+        let entry = self.get(url).filter(|e| e.is_fresh(max_age, now))?;
+        self.count(|stats| stats.hits += 1);
+        Some(entry)
+    }
+
+    /// Keep a full response, counting the miss
+    pub fn store(
+        &self,
+        url: &str,
+        etag: Option<String>,
+        last_modified: Option<String>,
+        body: &str,
+        now: i64,
+    ) {
+        self.count(|stats| stats.misses += 1);
+        let entry = CachedResponse {
+            url: cache_url(url),
+            etag,
+            last_modified,
+            fetched_at: now,
+            body: body.to_string(),
+        };
+        self.write(url, &entry);
+    }
+
+    /// The server answered 304: the stored body is current again
+    pub fn revalidated(&self, url: &str, mut entry: CachedResponse, now: i64) -> String {
+        self.count(|stats| stats.revalidated += 1);
+        entry.fetched_at = now;
+        self.write(url, &entry);
+        entry.body
+    }
+
+    // A cache that can't be written only costs a refetch
+    fn write(&self, url: &str, entry: &CachedResponse) {
+        if let Ok(json) = serde_json::to_string(entry) {
+            let _ = fs::write(self.entry_path(url), json);
+        }
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        fs::read_to_string(self.dir.join(STATS_FILE))
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    fn count(&self, update: impl FnOnce(&mut CacheStats)) {
+        let mut stats = self.stats();
+        update(&mut stats);
+        if let Ok(json) = serde_json::to_string(&stats) {
+            let _ = fs::write(self.dir.join(STATS_FILE), json);
+        }
+    }
+
+    pub fn summary(&self) -> CacheSummary {
+        let mut summary = CacheSummary {
+            stats: self.stats(),
+            ..Default::default()
+        };
+        for entry in fs::read_dir(&self.dir).into_iter().flatten().flatten() {
+            if entry.file_name() == STATS_FILE {
+                continue;
+            }
+            summary.entries += 1;
+            summary.bytes += entry.metadata().map(|m| m.len()).unwrap_or(0);
+        }
+        summary
+    }
+
+    /// Remove every stored response and reset the statistics
+    pub fn clear(&self) -> Result<()> {
+        for entry in fs::read_dir(&self.dir)
+            .context("Failed to read HTTP cache directory")?
+            .flatten()
+        {
+            fs::remove_file(entry.path()).context("Failed to remove HTTP cache entry")?;
+        }
+        Ok(())
+    }
+}
+
+/// A URL with its secrets removed
+fn cache_url(url: &str) -> String {
+    let Some((base, query)) = url.split_once('?') else {
+        return url.to_string();
+    };
+    let kept: Vec<&str> = query
+        .split('&')
+        .filter(|param| {
+            let name = param.split('=').next().unwrap_or("");
+            !SECRET_PARAMS.contains(&name)
+        })
+        .collect();
+    format!("{}?{}", base, kept.join("&"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_cache(name: &str) -> HttpCache {
+        let dir =
+            std::env::temp_dir().join(format!("kazeta-ra-http-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        HttpCache::with_dir(dir).unwrap()
+    }
+
+    #[test]
+    fn test_cache_url_drops_api_key() {
+        assert_eq!(
+            cache_url("https://ra.org/API/API_GetGameExtended.php?i=1&y=secret&f=5"),
+            "https://ra.org/API/API_GetGameExtended.php?i=1&f=5"
+        );
+        assert_eq!(cache_url("https://ra.org/"), "https://ra.org/");
+    }
+
+    #[test]
+    fn test_freshness_and_stats() {
+        let cache = test_cache("fresh");
+        let url = "https://ra.org/API/API_GetGameList.php?c=5&y=key1";
+
+        assert!(cache.fresh(url, Some(60), 1000).is_none());
+        cache.store(url, Some("\"abc\"".to_string()), None, "[]", 1000);
+
+        // The same request with another API key finds the entry
+        let other_key = "https://ra.org/API/API_GetGameList.php?c=5&y=key2";
+        assert_eq!(cache.fresh(other_key, Some(60), 1030).unwrap().body, "[]");
+        assert!(cache.fresh(url, Some(60), 1060).is_none());
+        assert!(cache.fresh(url, None, 1001).is_none());
+
+        let stale = cache.get(url).unwrap();
+        assert_eq!(
+            stale.validators(),
+            vec![(reqwest::header::IF_NONE_MATCH, "\"abc\"")]
+        );
+        assert!(!fs::read_to_string(cache.entry_path(url))
+            .unwrap()
+            .contains("key1"));
+
+        assert_eq!(cache.revalidated(url, stale, 1100), "[]");
+        assert!(cache.fresh(url, Some(60), 1130).is_some());
+
+        let summary = cache.summary();
+        assert_eq!(summary.entries, 1);
+        assert_eq!(
+            summary.stats,
+            CacheStats {
+                hits: 2,
+                revalidated: 1,
+                misses: 1
+            }
+        );
+        assert_eq!(summary.stats.hit_rate(), 75.0);
+
+        cache.clear().unwrap();
+        assert_eq!(cache.summary().entries, 0);
+        assert_eq!(cache.stats(), CacheStats::default());
+        let _ = fs::remove_dir_all(&cache.dir);
+    }
+}
@@ -4,13 +4,30 @@
 pub mod api;
 pub mod auth;
 pub mod cache;
+pub mod challenge;
+pub mod compare;
+pub mod detect;
+pub mod difficulty;
+pub mod endpoints;
 pub mod game_names;
 pub mod hash;
+pub mod header;
+pub mod hooks;
+pub mod http_cache;
+pub mod local;
+pub mod output;
+pub mod overlay_socket;
+pub mod session;
+pub mod settings;
+pub mod translations;
 pub mod types;
+pub mod verify;
 
-pub use api::{RAClient, AsyncRAClient};
-pub use auth::{Credentials, CredentialManager};
-pub use game_names::{GameNameEntry, GameNameMapping};
-pub use hash::{hash_rom, detect_console};
+pub use api::{AsyncRAClient, RAClient};
+pub use auth::{CredentialManager, Credentials};
+pub use compare::{AchievementComparison, ProgressComparison};
+pub use detect::{detect_console, detect_console_explained, Detection};
+pub use game_names::{GameNameEntry, GameNameMapping, NameSource};
+pub use hash::hash_rom;
+pub use settings::RASettings;
 pub use types::*;
-
@@ -0,0 +1,269 @@
+//! Achievements for native Kazeta carts
+//!
+//! Homebrew and native games have no RetroAchievements set, so a cart can
+//! define its own in an `achievements.toml`:
+//!
+//! ```toml
+//! [[achievement]]
+//! id = "first-jump"
+//! title = "Leap of Faith"
+//! description = "Jump for the first time"
+//! points = 5
+//! ```
+//!
+//! The game unlocks them with `kazeta-ra local award <id>` or `award`. Unlocks
+//! are kept in the local cache next to, but never mixed with, the RA data:
+//! local points don't count towards RA points, mastery or challenges, and
+//! `clear-cache` leaves them alone since there is nothing to re-sync them from.
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::cache::RACache;
+
+/// Name of the definition file inside a cart
+pub const DEFINITION_FILE: &str = "achievements.toml";
+
+/// One achievement a cart defines
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LocalAchievement {
+    pub id: String,
+    pub title: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub points: u32,
+}
+
+/// A cart's achievements.toml
+#[derive(Debug, Deserialize)]
+struct DefinitionFile {
+    #[serde(default, rename = "achievement")]
+    achievements: Vec<LocalAchievement>,
+}
+
+/// The achievements of one cart
+#[derive(Debug, Clone)]
+pub struct LocalSet {
+    /// The cart's `id`, which unlocks are recorded under
+    pub cart_id: String,
+    pub achievements: Vec<LocalAchievement>,
+}
+
+/// A recorded unlock
+#[derive(Debug, Clone, Serialize)]
+pub struct LocalUnlock {
+    pub achievement_id: String,
+    pub unlocked_at: i64,
+}
+
+/// Result of `award`
+#[derive(Debug, Clone, Serialize)]
+pub struct Award {
+    pub achievement: LocalAchievement,
+    /// False when it was already unlocked
+    pub newly_unlocked: bool,
+    pub earned: usize,
+    pub total: usize,
+}
+
+impl LocalSet {
+    /// Load the set of the cart at `cart`: the cart's directory, its `.kzi`,
+    /// or the achievements.toml itself
+    pub fn load(cart: &Path) -> Result<Self> {
+        let dir = if cart.is_dir() {
+            cart.to_path_buf()
+        } else {
+            cart.parent().map(Path::to_path_buf).unwrap_or_default()
+        };
+        let kzi = if cart.extension().and_then(|e| e.to_str()) == Some("kzi") {
+            Some(cart.to_path_buf())
+        } else {
+            find_kzi(&dir)
+        };
+
+        let path = dir.join(DEFINITION_FILE);
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("No {} in {}", DEFINITION_FILE, dir.display()))?;
+        let cart_id = kzi
+            .and_then(|kzi| fs::read_to_string(kzi).ok())
+            .and_then(|content| kzi_id(&content))
+            .or_else(|| dir.file_name().map(|n| n.to_string_lossy().into_owned()))
+            .context("Could not determine the cart's id")?;
+
+        Self::parse(cart_id, &content)
+    }
+
+    /// Parse achievements.toml, rejecting ids that are empty or repeated
+    pub fn parse(cart_id: String, content: &str) -> Result<Self> {
+        let file: DefinitionFile = toml::from_str(content)
+            .with_context(|| format!("Failed to parse {}", DEFINITION_FILE))?;
+
+        let mut seen = HashSet::new();
+        for achievement in &file.achievements {
+            if achievement.id.trim().is_empty() {
+                bail!("Achievement '{}' has no id", achievement.title);
+            }
+            if !seen.insert(achievement.id.as_str()) {
+                bail!("Achievement id '{}' is defined twice", achievement.id);
+            }
+        }
+
+        Ok(Self {
+            cart_id,
+            achievements: file.achievements,
+        })
+    }
+
+    pub fn get(&self, id: &str) -> Option<&LocalAchievement> {
+        self.achievements.iter().find(|a| a.id == id)
+    }
+
+    pub fn total_points(&self) -> u32 {
+        self.achievements.iter().map(|a| a.points).sum()
+    }
+}
+
+/// Unlock achievement `id` of `set`, telling the overlay if it's new
+pub fn award(cache: &RACache, set: &LocalSet, id: &str) -> Result<Award> {
+    let achievement = set
+        .get(id)
+        .with_context(|| format!("{} has no achievement '{}'", set.cart_id, id))?
+        .clone();
+
+    let newly_unlocked = cache.record_local_unlock(
+        &set.cart_id,
+        &achievement.id,
+        chrono::Utc::now().timestamp(),
+    )?;
+    let unlocked: HashSet<String> = cache
+        .local_unlocks(&set.cart_id)?
+        .into_iter()
+        .map(|u| u.achievement_id)
+        .collect();
+    let earned = set
+        .achievements
+        .iter()
+        .filter(|a| unlocked.contains(&a.id))
+        .count();
+
+    if newly_unlocked {
+        notify_overlay(&achievement);
+    }
+
+    Ok(Award {
+        achievement,
+        newly_unlocked,
+        earned,
+        total: set.achievements.len(),
+    })
+}
+
+/// Toast for a local unlock, worded so it isn't mistaken for an RA one
+fn notify_overlay(achievement: &LocalAchievement) {
+    use std::io::Write;
+    use std::os::unix::net::UnixStream;
+
+    let socket_path = "/tmp/kazeta-overlay.sock";
+    if !Path::new(socket_path).exists() {
+        return; // Overlay not running, skip
+    }
+
+    let message = serde_json::json!({
+        "type": "show_toast",
+        "message": format!("🎮 Cart Achievement: {} ({} pts)", achievement.title, achievement.points),
+        "style": "info",
+        "duration_ms": 5000,
+    });
+
+    if let Ok(mut stream) = UnixStream::connect(socket_path) {
+        let _ = writeln!(stream, "{}", message);
+    }
+}
+
+fn find_kzi(dir: &Path) -> Option<PathBuf> {
+    fs::read_dir(dir)
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .find(|path| path.extension().and_then(|e| e.to_str()) == Some("kzi"))
+}
+
+/// The `Id=` line of a .kzi
+fn kzi_id(content: &str) -> Option<String> {
+    content.lines().find_map(|line| {
+        let (key, value) = line.trim().split_once('=')?;
+        if !key.trim().eq_ignore_ascii_case("id") {
+            return None;
+        }
+        let value = value.trim();
+        let value = value
+            .strip_prefix('"')
+            .and_then(|v| v.strip_suffix('"'))
+            .unwrap_or(value);
+        (!value.is_empty()).then(|| value.to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const DEFINITIONS: &str = r#"
+        [[achievement]]
+        id = "first-jump"
+        title = "Leap of Faith"
+        description = "Jump for the first time"
+        points = 5
+
+        [[achievement]]
+        id = "finish"
+        title = "The End"
+        points = 25
+    "#;
+
+    #[test]
+    fn test_load_from_cart_dir() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(
+            tmp.path().join("game.kzi"),
+            "Name=My Game\nId=my-game\nExec=game\n",
+        )
+        .unwrap();
+        fs::write(tmp.path().join(DEFINITION_FILE), DEFINITIONS).unwrap();
+
+        let set = LocalSet::load(tmp.path()).unwrap();
+        assert_eq!(set.cart_id, "my-game");
+        assert_eq!(set.achievements.len(), 2);
+        assert_eq!(set.total_points(), 30);
+        assert_eq!(set.get("finish").unwrap().description, "");
+
+        let duplicate = format!(
+            "{}\n[[achievement]]\nid = \"finish\"\ntitle = \"Again\"\n",
+            DEFINITIONS
+        );
+        assert!(LocalSet::parse("my-game".to_string(), &duplicate).is_err());
+    }
+
+    #[test]
+    fn test_award_is_recorded_once() {
+        let tmp = TempDir::new().unwrap();
+        let cache = RACache::open(&tmp.path().join("achievements.db")).unwrap();
+        let set = LocalSet::parse("my-game".to_string(), DEFINITIONS).unwrap();
+
+        let award1 = award(&cache, &set, "first-jump").unwrap();
+        assert!(award1.newly_unlocked);
+        assert_eq!((award1.earned, award1.total), (1, 2));
+        assert!(!award(&cache, &set, "first-jump").unwrap().newly_unlocked);
+        assert!(award(&cache, &set, "missing").is_err());
+
+        // Not part of the RA data
+        assert!(cache.recent_unlocks(1).unwrap().is_empty());
+        cache.clear().unwrap();
+        assert_eq!(cache.local_unlocks("my-game").unwrap().len(), 1);
+    }
+}
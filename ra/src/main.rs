@@ -1,14 +1,28 @@
 use anyhow::{bail, Context, Result};
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand, ValueEnum};
 use kazeta_ra::{
     api::RAClient,
-    auth::{CredentialManager, Credentials},
-    cache::RACache,
-    game_names::GameNameMapping,
-    hash::{hash_rom, detect_console},
-    types::ConsoleId,
+    auth::{self, CredentialManager, Credentials, StatusCheck},
+    cache::{ImageCache, Mastery, RACache},
+    challenge::{ChallengePeriod, ChallengeState, RefreshOutcome},
+    detect::{detect_console, detect_console_explained},
+    difficulty::{self, DifficultyEstimate},
+    endpoints::CircuitState,
+    game_names::{GameNameMapping, NameSource},
+    hash::{hash_rom, hash_rom_with_progress, hash_roms, hash_type_name},
+    header,
+    hooks::{self, HookEvent},
+    http_cache::HttpCache,
+    local::{self, LocalSet},
+    output::{self, CodedError, ErrorCode},
+    overlay_socket::{self, OverlaySocket, SendOptions},
+    session::{GameSession, SessionPlayer},
+    settings::{RASettings, SETTING_KEYS},
+    translations::Translations,
+    types::{ConsoleId, GameInfoAndProgress},
+    verify::{self, CacheVerifier, Outcome},
 };
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 #[derive(Parser)]
 #[command(name = "kazeta-ra")]
@@ -16,6 +30,40 @@ use std::path::PathBuf;
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+
+    /// Output format. Defaults to json for the commands runtime wrappers
+    /// call (game-start, status, notify-*, ...) and plain for the rest.
+    #[arg(long, global = true, value_enum)]
+    output: Option<OutputFormat>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum OutputFormat {
+    /// One JSON object per command, errors included (see `kazeta_ra::output`)
+    Json,
+    /// Human-readable text
+    Plain,
+}
+
+/// How the commands that notify the overlay deliver their messages
+#[derive(Args, Clone, Copy)]
+struct NotifyArgs {
+    /// Print the JSON that would be sent to the overlay (on stderr) instead
+    /// of sending it. Everything else the command does still happens.
+    #[arg(long)]
+    dry_run: bool,
+    /// Log connecting to the overlay socket and each write, with timings
+    #[arg(long)]
+    trace: bool,
+}
+
+impl NotifyArgs {
+    fn socket(&self) -> OverlaySocket {
+        OverlaySocket::new(SendOptions {
+            dry_run: self.dry_run,
+            trace: self.trace,
+        })
+    }
 }
 
 #[derive(Subcommand)]
@@ -35,11 +83,25 @@ enum Commands {
 
     /// Get stored credentials (for runtime wrappers)
     GetCredentials {
-        /// Output format: json, env
+        /// Output format: json, env (--output plain also gives env)
         #[arg(short, long, default_value = "json")]
         format: String,
     },
 
+    /// Print or refresh the session token used to award achievements
+    Token {
+        /// Print only the token, refreshing it first if it has expired (for runtime wrappers)
+        #[arg(long)]
+        print: bool,
+        /// Refresh the token even if it hasn't expired
+        #[arg(long)]
+        refresh: bool,
+        /// RetroAchievements password, needed when there is no token yet
+        /// or it has been revoked (defaults to $RA_PASSWORD)
+        #[arg(long)]
+        password: Option<String>,
+    },
+
     /// Set hardcore mode on/off
     SetHardcore {
         /// Enable hardcore mode
@@ -50,6 +112,19 @@ enum Commands {
     /// Get user profile/summary
     Profile,
 
+    /// List the users you follow on RetroAchievements
+    Friends,
+
+    /// Compare your achievements in a game against a friend's
+    Compare {
+        /// RetroAchievements game ID
+        #[arg(short, long)]
+        game_id: u32,
+        /// Username to compare against
+        #[arg(short, long)]
+        user: String,
+    },
+
     /// Hash a ROM file for RA identification
     HashRom {
         /// Path to ROM file
@@ -58,6 +133,27 @@ enum Commands {
         /// Console type (gba, nes, snes, etc.) - auto-detected if not specified
         #[arg(short, long)]
         console: Option<String>,
+        /// Show why the console was chosen before the hash
+        #[arg(long)]
+        explain: bool,
+        /// Print the percentage hashed to stderr
+        #[arg(long)]
+        progress: bool,
+    },
+
+    /// Hash every ROM under a directory, several files at a time
+    HashDir {
+        /// Directory to scan, subdirectories included
+        dir: PathBuf,
+        /// Console type for every file - auto-detected per file if not specified
+        #[arg(short, long)]
+        console: Option<String>,
+        /// Files hashed at once (default: one per CPU)
+        #[arg(short, long)]
+        jobs: Option<usize>,
+        /// Print the percentage of the whole library hashed to stderr
+        #[arg(long)]
+        progress: bool,
     },
 
     /// Get game info and achievements for a ROM
@@ -71,6 +167,9 @@ enum Commands {
         /// Console type (auto-detected from path if not specified)
         #[arg(short, long)]
         console: Option<String>,
+        /// Include unofficial achievements (default: the show-unofficial setting)
+        #[arg(long)]
+        unofficial: bool,
     },
 
     /// Notify that a game has started (sends to overlay)
@@ -84,9 +183,18 @@ enum Commands {
         /// Path to ROM file (alternative to --hash, auto-detects console)
         #[arg(short, long)]
         path: Option<PathBuf>,
+        /// Cartridge file, for cart-scoped names (found from --path if left out)
+        #[arg(long)]
+        cart: Option<PathBuf>,
+        /// Name from the cartridge's ra_game_name, used when RetroAchievements
+        /// doesn't know the ROM and no custom name is set
+        #[arg(long)]
+        name_hint: Option<String>,
         /// Also notify the overlay daemon
         #[arg(long)]
         notify_overlay: bool,
+        #[command(flatten)]
+        notify: NotifyArgs,
     },
 
     /// Notify that an achievement was unlocked
@@ -97,14 +205,109 @@ enum Commands {
         /// Achievement title (optional, for display)
         #[arg(short, long)]
         title: Option<String>,
+        /// Player slot that earned it, in a session started with --player
+        #[arg(short, long)]
+        player: Option<u8>,
+        #[command(flatten)]
+        notify: NotifyArgs,
+    },
+
+    /// Report measured progress of an achievement (e.g. 45/100 rings) to the overlay
+    NotifyProgress {
+        /// Achievement ID
+        #[arg(short, long)]
+        id: u32,
+        /// Current value
+        #[arg(short, long)]
+        current: u32,
+        /// Value that unlocks the achievement (0 clears the progress bar)
+        #[arg(short, long)]
+        target: u32,
+        #[command(flatten)]
+        notify: NotifyArgs,
+    },
+
+    /// Track the running game's session for wrappers: "now playing" on RA
+    /// and the session file the overlay reads
+    Session {
+        #[command(subcommand)]
+        action: SessionAction,
     },
 
-    /// Check if RA is configured and enabled
-    Status,
+    /// Achievements a native cart defines in its achievements.toml. They
+    /// are recorded locally and earn no RetroAchievements points.
+    Local {
+        #[command(subcommand)]
+        action: LocalAction,
+    },
+
+    /// Check if RA is configured and enabled. The credential check is
+    /// cached, so this only asks the server once per `--ttl`.
+    Status {
+        /// Check the credentials with the server even if the cached result
+        /// is fresh, and probe every configured endpoint
+        #[arg(long, conflicts_with = "offline")]
+        refresh: bool,
+
+        /// Never ask the server: trust the stored credentials unless the
+        /// last check rejected them
+        #[arg(long)]
+        offline: bool,
+
+        /// Seconds a cached check is trusted
+        #[arg(long, default_value_t = auth::STATUS_TTL.num_seconds() as u64)]
+        ttl: u64,
+    },
 
     /// Clear local achievement cache
     ClearCache,
 
+    /// Check the local cache for damage and repair what can be
+    Cache {
+        #[command(subcommand)]
+        action: CacheAction,
+    },
+
+    /// Show cache statistics for troubleshooting
+    Diagnostics,
+
+    /// List achievements unlocked recently, from the local cache
+    RecentUnlocks {
+        /// How many days back to look
+        #[arg(short, long, default_value_t = 30)]
+        days: u32,
+    },
+
+    /// List cached games with some but not all achievements earned
+    InProgress,
+
+    /// Completion, points and mastered sets across every cached game, plus
+    /// the latest unlocks. Reads only the local cache, so it works offline.
+    Trophies {
+        /// How many recent unlocks to include
+        #[arg(long, default_value = "10")]
+        recent: usize,
+    },
+
+    /// Daily and weekly challenges drawn from your unearned achievements
+    Challenge {
+        #[command(subcommand)]
+        action: Option<ChallengeAction>,
+    },
+
+    /// Download a game's icon and achievement badges into the local image cache
+    FetchImages {
+        /// ROM hash (game must have been looked up with game-info or game-start first)
+        #[arg(short = 'H', long)]
+        hash: String,
+        /// Also fetch the greyed-out "locked" badge variants
+        #[arg(long)]
+        locked: bool,
+        /// Only fetch the game icon
+        #[arg(long)]
+        icon_only: bool,
+    },
+
     /// Send achievement list to overlay daemon
     SendAchievementsToOverlay {
         /// ROM hash
@@ -116,6 +319,11 @@ enum Commands {
         /// Console type (required with --hash, auto-detected with --path)
         #[arg(short, long)]
         console: Option<String>,
+        /// Include unofficial achievements (default: the show-unofficial setting)
+        #[arg(long)]
+        unofficial: bool,
+        #[command(flatten)]
+        notify: NotifyArgs,
     },
 
     /// Set a custom game name for a ROM (when auto-detection fails)
@@ -129,6 +337,9 @@ enum Commands {
         /// Console type (required with --hash, auto-detected with --path)
         #[arg(short, long)]
         console: Option<String>,
+        /// Name only this cartridge file (alternative to hash and path)
+        #[arg(long)]
+        cart: Option<PathBuf>,
         /// Custom game name to use
         #[arg(short, long)]
         name: String,
@@ -145,149 +356,903 @@ enum Commands {
         /// Console type (required with --hash, auto-detected with --path)
         #[arg(short, long)]
         console: Option<String>,
+        /// Remove this cartridge file's name instead (alternative to hash and path)
+        #[arg(long)]
+        cart: Option<PathBuf>,
     },
 
     /// List all custom game name mappings
     ListGameNames,
+
+    /// Write the custom game names to a file, for editing or another device
+    ExportGameNames {
+        /// Write CSV with hash, console, name and cart columns (default: the
+        /// names file's JSON)
+        #[arg(long)]
+        csv: bool,
+        /// File to write (default: stdout)
+        file: Option<PathBuf>,
+    },
+
+    /// Add or replace custom game names from a CSV written by
+    /// export-game-names --csv. Nothing is written if any row is invalid.
+    ImportGameNames {
+        /// CSV file to read
+        file: PathBuf,
+        /// Check the file and report what would change without saving
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// View or change kazeta-ra settings
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+}
+
+#[derive(Subcommand)]
+enum ChallengeAction {
+    /// Show the active challenges, drawing new sets when a period has ended (default)
+    Show,
+    /// Throw away the current set and draw a new one
+    Reroll {
+        /// daily or weekly
+        period: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum CacheAction {
+    /// Check the database, JSON files and images, and compare earned
+    /// achievements with RetroAchievements. Broken cache files are moved to
+    /// ra_cache/quarantine and fetched again.
+    Verify {
+        /// Skip the comparison with RetroAchievements
+        #[arg(long)]
+        offline: bool,
+        /// Only the quick database check game-start runs
+        #[arg(long)]
+        quick: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum SessionAction {
+    /// Start a session for a game and write the session file
+    Start {
+        /// ROM hash (alternative to --path)
+        #[arg(short = 'H', long)]
+        hash: Option<String>,
+        /// Path to ROM file (alternative to --hash, auto-detects console)
+        #[arg(short, long)]
+        path: Option<PathBuf>,
+        /// Console type (required with --hash, auto-detected with --path)
+        #[arg(short, long)]
+        console: Option<String>,
+        /// Map a player slot to a RetroAchievements user for a shared
+        /// session, e.g. --player 2=bob (repeatable). Player 1 defaults to
+        /// the logged-in account.
+        #[arg(long = "player", value_name = "SLOT=USERNAME")]
+        players: Vec<String>,
+        #[command(flatten)]
+        notify: NotifyArgs,
+    },
+    /// Send a heartbeat for the running session
+    Ping {
+        /// Rich presence text to show on the user's profile
+        #[arg(short, long)]
+        rich_presence: Option<String>,
+        /// Keep pinging every this many seconds until the session is stopped
+        /// (RA expects a ping every couple of minutes, e.g. 120)
+        #[arg(long)]
+        interval: Option<u64>,
+    },
+    /// End the session and remove the session file
+    Stop,
+}
+
+#[derive(Subcommand)]
+enum LocalAction {
+    /// Unlock one of the cart's achievements
+    Award {
+        /// Achievement id from achievements.toml
+        id: String,
+        /// Cart directory or .kzi (defaults to the current directory)
+        #[arg(long)]
+        cart: Option<PathBuf>,
+    },
+    /// List the cart's achievements and which are unlocked
+    List {
+        /// Cart directory or .kzi (defaults to the current directory)
+        #[arg(long)]
+        cart: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+enum ConfigAction {
+    /// Change a setting (e.g. `config set show-unofficial true`)
+    Set { key: String, value: String },
+    /// Print a setting
+    Get { key: String },
+    /// Print all settings
+    List,
+}
+
+impl Commands {
+    /// The format used without `--output`: JSON for the commands that have
+    /// always printed it
+    fn default_output(&self) -> OutputFormat {
+        match self {
+            Commands::GetCredentials { format } if format == "env" => OutputFormat::Plain,
+            Commands::GetCredentials { .. }
+            | Commands::GameStart { .. }
+            | Commands::NotifyAchievement { .. }
+            | Commands::NotifyProgress { .. }
+            | Commands::Session { .. }
+            | Commands::Local { .. }
+            | Commands::Status { .. }
+            | Commands::FetchImages { .. }
+            | Commands::Trophies { .. }
+            | Commands::SendAchievementsToOverlay { .. } => OutputFormat::Json,
+            _ => OutputFormat::Plain,
+        }
+    }
 }
 
-fn main() -> Result<()> {
+fn main() {
     let cli = Cli::parse();
+    let out = cli.output.unwrap_or_else(|| cli.command.default_output());
 
-    match cli.command {
-        Commands::Login { username, api_key } => cmd_login(username, api_key),
-        Commands::Logout => cmd_logout(),
-        Commands::GetCredentials { format } => cmd_get_credentials(&format),
-        Commands::SetHardcore { enabled } => cmd_set_hardcore(enabled),
-        Commands::Profile => cmd_profile(),
-        Commands::HashRom { path, console } => cmd_hash_rom(&path, console.as_deref()),
-        Commands::GameInfo { hash, path, console } => cmd_game_info(hash, path, console.as_deref()),
-        Commands::GameStart { hash, console, path, notify_overlay } => {
-            cmd_game_start(hash.as_deref(), console.as_deref(), path.as_ref(), notify_overlay)
+    let result = run(cli.command, out);
+    // Hooks fired while handling the command run alongside it
+    hooks::wait_pending();
+
+    if let Err(e) = result {
+        match out {
+            OutputFormat::Json => println!("{}", output::error_json(&e)),
+            OutputFormat::Plain => eprintln!("Error: {:?}", e),
+        }
+        std::process::exit(output::error_code(&e).exit_status());
+    }
+}
+
+fn run(command: Commands, out: OutputFormat) -> Result<()> {
+    match command {
+        Commands::Login { username, api_key } => cmd_login(username, api_key, out),
+        Commands::Logout => cmd_logout(out),
+        Commands::GetCredentials { format } => match format.as_str() {
+            "json" | "env" => cmd_get_credentials(out),
+            _ => Err(CodedError::new(
+                ErrorCode::InvalidArgument,
+                format!("Unknown format: {}. Use 'json' or 'env'.", format),
+            )
+            .into()),
+        },
+        Commands::Token {
+            print,
+            refresh,
+            password,
+        } => cmd_token(print, refresh, password, out),
+        Commands::SetHardcore { enabled } => cmd_set_hardcore(enabled, out),
+        Commands::Profile => cmd_profile(out),
+        Commands::Friends => cmd_friends(out),
+        Commands::Compare { game_id, user } => cmd_compare(game_id, &user, out),
+        Commands::HashRom {
+            path,
+            console,
+            explain,
+            progress,
+        } => cmd_hash_rom(&path, console.as_deref(), explain, progress, out),
+        Commands::HashDir {
+            dir,
+            console,
+            jobs,
+            progress,
+        } => cmd_hash_dir(&dir, console.as_deref(), jobs, progress, out),
+        Commands::GameInfo {
+            hash,
+            path,
+            console,
+            unofficial,
+        } => cmd_game_info(hash, path, console.as_deref(), unofficial, out),
+        Commands::GameStart {
+            hash,
+            console,
+            path,
+            cart,
+            name_hint,
+            notify_overlay,
+            notify,
+        } => {
+            let name_hint = name_hint.filter(|n| !n.trim().is_empty());
+            let overlay = notify_overlay.then(|| notify.socket());
+            cmd_game_start(
+                hash.as_deref(),
+                console.as_deref(),
+                path.as_ref(),
+                cart,
+                name_hint,
+                overlay.as_ref(),
+                out,
+            )
         }
-        Commands::NotifyAchievement { id, title } => cmd_notify_achievement(id, title),
-        Commands::Status => cmd_status(),
-        Commands::ClearCache => cmd_clear_cache(),
-        Commands::SendAchievementsToOverlay { hash, path, console } => {
-            cmd_send_achievements_to_overlay(hash.as_ref().map(|s| s.as_str()), path.as_ref(), console.as_deref())
+        Commands::NotifyAchievement {
+            id,
+            title,
+            player,
+            notify,
+        } => cmd_notify_achievement(id, title, player, &notify.socket(), out),
+        Commands::NotifyProgress {
+            id,
+            current,
+            target,
+            notify,
+        } => cmd_notify_progress(id, current, target, &notify.socket(), out),
+        Commands::Session { action } => cmd_session(action, out),
+        Commands::Local { action } => cmd_local(action, out),
+        Commands::Status {
+            refresh,
+            offline,
+            ttl,
+        } => cmd_status(refresh, offline, ttl, out),
+        Commands::ClearCache => cmd_clear_cache(out),
+        Commands::Cache {
+            action: CacheAction::Verify { offline, quick },
+        } => cmd_cache_verify(offline, quick, out),
+        Commands::Diagnostics => cmd_diagnostics(out),
+        Commands::RecentUnlocks { days } => cmd_recent_unlocks(days, out),
+        Commands::InProgress => cmd_in_progress(out),
+        Commands::Trophies { recent } => cmd_trophies(recent, out),
+        Commands::Challenge { action } => {
+            cmd_challenge(action.unwrap_or(ChallengeAction::Show), out)
         }
-        Commands::SetGameName { hash, path, console, name } => {
-            cmd_set_game_name(hash.as_ref().map(|s| s.as_str()), path.as_ref(), console.as_deref(), &name)
+        Commands::FetchImages {
+            hash,
+            locked,
+            icon_only,
+        } => cmd_fetch_images(&hash, locked, icon_only, out),
+        Commands::SendAchievementsToOverlay {
+            hash,
+            path,
+            console,
+            unofficial,
+            notify,
+        } => {
+            let overlay = notify.socket();
+            cmd_send_achievements_to_overlay(
+                hash.as_deref(),
+                path.as_ref(),
+                console.as_deref(),
+                unofficial,
+                &overlay,
+                out,
+            )
         }
-        Commands::RemoveGameName { hash, path, console } => {
-            cmd_remove_game_name(hash.as_ref().map(|s| s.as_str()), path.as_ref(), console.as_deref())
+        Commands::SetGameName {
+            hash,
+            path,
+            console,
+            cart: Some(cart),
+            name,
+        } if hash.is_none() && path.is_none() => {
+            cmd_set_cart_name(&cart, console.as_deref(), &name, out)
         }
-        Commands::ListGameNames => cmd_list_game_names(),
+        Commands::SetGameName {
+            hash,
+            path,
+            console,
+            name,
+            ..
+        } => cmd_set_game_name(
+            hash.as_deref(),
+            path.as_ref(),
+            console.as_deref(),
+            &name,
+            out,
+        ),
+        Commands::RemoveGameName {
+            hash: None,
+            path: None,
+            cart: Some(cart),
+            ..
+        } => cmd_remove_cart_name(&cart, out),
+        Commands::RemoveGameName {
+            hash,
+            path,
+            console,
+            ..
+        } => cmd_remove_game_name(hash.as_deref(), path.as_ref(), console.as_deref(), out),
+        Commands::ListGameNames => cmd_list_game_names(out),
+        Commands::ExportGameNames { csv, file } => cmd_export_game_names(csv, file.as_deref(), out),
+        Commands::ImportGameNames { file, dry_run } => cmd_import_game_names(&file, dry_run, out),
+        Commands::Config { action } => cmd_config(action, out),
+    }
+}
+
+/// Print a command's result: its fields as JSON, or the human-readable text
+fn emit(out: OutputFormat, fields: serde_json::Value, plain: impl FnOnce()) {
+    match out {
+        OutputFormat::Json => println!("{}", output::success(fields)),
+        OutputFormat::Plain => plain(),
+    }
+}
+
+fn load_credentials(cred_manager: &CredentialManager) -> Result<Credentials> {
+    Ok(cred_manager.load()?.ok_or_else(CodedError::not_logged_in)?)
+}
+
+fn parse_console(console: &str) -> Result<ConsoleId> {
+    ConsoleId::from_str(console).ok_or_else(|| {
+        CodedError::new(
+            ErrorCode::InvalidArgument,
+            format!("Unknown console: {}", console),
+        )
+        .into()
+    })
+}
+
+/// The ROM hash and console from `--hash`/`--console`, or by hashing `--path`
+fn rom_hash_and_console(
+    hash: Option<&str>,
+    path: Option<&PathBuf>,
+    console: Option<&str>,
+) -> Result<(String, ConsoleId)> {
+    if let Some(h) = hash {
+        // Hash provided, console is required
+        let c = console.ok_or_else(|| {
+            CodedError::new(
+                ErrorCode::InvalidArgument,
+                "--console is required when using --hash",
+            )
+        })?;
+        Ok((h.to_string(), parse_console(c)?))
+    } else if let Some(p) = path {
+        // Path provided, auto-detect console if not specified
+        let detected_console = match console {
+            Some(c) => parse_console(c)?,
+            None => detect_console(p)?,
+        };
+        let hash = hash_rom(p, detected_console)?;
+        Ok((hash, detected_console))
+    } else {
+        Err(CodedError::new(
+            ErrorCode::InvalidArgument,
+            "Either --hash or --path is required",
+        )
+        .into())
+    }
+}
+
+/// Name for a ROM RetroAchievements doesn't know: the one read from its
+/// header before, or one read now and remembered in the name mapping
+fn header_name(hash: &str, path: Option<&PathBuf>, console: ConsoleId) -> Option<String> {
+    let mut mapping = GameNameMapping::load().ok()?;
+    if let Some(name) = mapping.get_suggested_name(hash) {
+        return Some(name);
     }
+
+    let name = header::suggest_name(path?, console)?;
+    // Diagnostics go to stderr so JSON output stays a single object
+    match mapping.suggest_name(hash, name.clone(), Some(console.to_string())) {
+        Ok(true) => eprintln!(
+            "Using \"{}\" from the ROM header; set-game-name overrides it",
+            name
+        ),
+        Ok(false) => {}
+        Err(e) => eprintln!("Failed to remember header name: {:#}", e),
+    }
+    Some(name)
 }
 
-fn cmd_login(username: String, api_key: String) -> Result<()> {
+fn cmd_login(username: String, api_key: String, out: OutputFormat) -> Result<()> {
     let cred_manager = CredentialManager::new()?;
     let credentials = Credentials::new(username.clone(), api_key);
 
     // Verify credentials work
     let client = RAClient::new(credentials.clone());
     if !client.verify_credentials()? {
-        bail!("Invalid credentials. Please check your username and API key.");
+        return Err(CodedError::new(
+            ErrorCode::NotLoggedIn,
+            "Invalid credentials. Please check your username and API key.",
+        )
+        .into());
     }
 
     // Save credentials
     cred_manager.save(&credentials)?;
+    let check = StatusCheck::new(&username, true, chrono::Utc::now().timestamp());
+    if let Err(e) = cred_manager.save_status_check(&check) {
+        eprintln!("Failed to record credential check: {:#}", e);
+    }
 
-    println!("✓ Logged in as: {}", username);
-    println!("✓ Credentials saved to: {}", cred_manager.credentials_path().display());
+    let path = cred_manager.credentials_path();
+    emit(
+        out,
+        serde_json::json!({ "username": username, "credentials_path": path }),
+        || {
+            println!("✓ Logged in as: {}", username);
+            println!("✓ Credentials saved to: {}", path.display());
+        },
+    );
     Ok(())
 }
 
-fn cmd_logout() -> Result<()> {
+fn cmd_logout(out: OutputFormat) -> Result<()> {
     let cred_manager = CredentialManager::new()?;
     cred_manager.delete()?;
-    println!("✓ Logged out. Credentials removed.");
+    emit(out, serde_json::Value::Null, || {
+        println!("✓ Logged out. Credentials removed.")
+    });
     Ok(())
 }
 
-fn cmd_get_credentials(format: &str) -> Result<()> {
+fn cmd_get_credentials(out: OutputFormat) -> Result<()> {
     let cred_manager = CredentialManager::new()?;
-    let credentials = cred_manager.load()?
-        .context("No credentials stored. Run 'kazeta-ra login' first.")?;
-
-    match format {
-        "json" => {
-            println!("{}", serde_json::to_string(&credentials)?);
-        }
-        "env" => {
-            println!("RA_USERNAME={}", credentials.username);
-            println!("RA_API_KEY={}", credentials.api_key);
-            if let Some(token) = &credentials.token {
-                println!("RA_TOKEN={}", token);
-            }
-            println!("RA_HARDCORE={}", if credentials.hardcore { "1" } else { "0" });
-        }
-        _ => {
-            bail!("Unknown format: {}. Use 'json' or 'env'.", format);
+    let credentials = load_credentials(&cred_manager)?;
+
+    // The env form is the plain output, for `eval`/`source` in wrappers
+    emit(out, serde_json::to_value(&credentials)?, || {
+        println!("RA_USERNAME={}", credentials.username);
+        println!("RA_API_KEY={}", credentials.api_key);
+        if let Some(token) = &credentials.token {
+            println!("RA_TOKEN={}", token);
         }
+        println!(
+            "RA_HARDCORE={}",
+            if credentials.session_hardcore() {
+                "1"
+            } else {
+                "0"
+            }
+        );
+    });
+
+    Ok(())
+}
+
+fn cmd_token(
+    print: bool,
+    refresh: bool,
+    password: Option<String>,
+    out: OutputFormat,
+) -> Result<()> {
+    let cred_manager = CredentialManager::new()?;
+    let mut credentials = load_credentials(&cred_manager)?;
+    let password = password.or_else(|| std::env::var("RA_PASSWORD").ok());
+    let stale = !credentials.token_is_fresh(chrono::Utc::now());
+
+    let refreshed = refresh || (print && stale);
+    if refreshed {
+        let client = RAClient::new(credentials.clone());
+        let token = match (&password, &credentials.token) {
+            (Some(password), _) => client.login(password)?,
+            (None, Some(_)) => client.refresh_token()?,
+            (None, None) => {
+                return Err(CodedError::new(
+                    ErrorCode::InvalidArgument,
+                    "No session token yet. Pass --password (or set RA_PASSWORD) to log in.",
+                )
+                .into())
+            }
+        };
+        cred_manager.update_token(token.clone())?;
+        credentials.set_token(token);
+        // stderr, so `--print` output stays just the token
+        eprintln!("✓ Session token refreshed");
     }
 
+    if print && credentials.token.is_none() {
+        return Err(CodedError::new(ErrorCode::NotFound, "No session token").into());
+    }
+
+    let valid = credentials.token_is_fresh(chrono::Utc::now());
+    let fields = serde_json::json!({
+        // Only handed out when asked for, like the plain output
+        "token": credentials.token.as_ref().filter(|_| print),
+        "expires_at": credentials.token_expires_at().map(|t| t.to_rfc3339()),
+        "valid": valid,
+        "refreshed": refreshed,
+    });
+    emit(out, fields, || {
+        if print {
+            println!("{}", credentials.token.as_deref().unwrap_or_default());
+        } else if credentials.token.is_none() {
+            println!("No session token. Run 'kazeta-ra token --refresh --password <password>'.");
+        } else if let Some(expires) = credentials.token_expires_at().filter(|_| valid) {
+            println!(
+                "Session token valid until {}",
+                expires.format("%Y-%m-%d %H:%M UTC")
+            );
+        } else {
+            println!("Session token expired. Run 'kazeta-ra token --refresh'.");
+        }
+    });
     Ok(())
 }
 
-fn cmd_set_hardcore(enabled: bool) -> Result<()> {
+fn cmd_set_hardcore(enabled: bool, out: OutputFormat) -> Result<()> {
     let cred_manager = CredentialManager::new()?;
     cred_manager.set_hardcore(enabled)?;
-    println!("✓ Hardcore mode: {}", if enabled { "enabled" } else { "disabled" });
+    emit(out, serde_json::json!({ "hardcore": enabled }), || {
+        println!(
+            "✓ Hardcore mode: {}",
+            if enabled { "enabled" } else { "disabled" }
+        );
+    });
     Ok(())
 }
 
-fn cmd_profile() -> Result<()> {
+fn cmd_profile(out: OutputFormat) -> Result<()> {
     let cred_manager = CredentialManager::new()?;
-    let credentials = cred_manager.load()?
-        .context("No credentials stored. Run 'kazeta-ra login' first.")?;
+    let credentials = load_credentials(&cred_manager)?;
 
     let client = RAClient::new(credentials);
     let summary = client.get_user_summary()?;
+    let recent = summary.recently_played.as_deref().unwrap_or_default();
+
+    let fields = serde_json::json!({
+        "user": summary.user,
+        "points": summary.total_points,
+        "softcore_points": summary.total_softcore_points,
+        "true_points": summary.total_true_points,
+        "rank": summary.rank,
+        "recently_played": recent.iter().map(|game| serde_json::json!({
+            "game_id": game.game_id,
+            "title": game.title,
+            "console": game.console_name,
+        })).collect::<Vec<_>>(),
+    });
+    emit(out, fields, || {
+        println!("╔════════════════════════════════════════╗");
+        println!("║  RetroAchievements Profile             ║");
+        println!("╠════════════════════════════════════════╣");
+        println!("║  User: {:<30} ║", summary.user);
+        println!("║  Points: {:<28} ║", summary.total_points);
+        println!(
+            "║  Softcore Points: {:<19} ║",
+            summary.total_softcore_points
+        );
+        println!("║  True Points: {:<23} ║", summary.total_true_points);
+        if let Some(rank) = summary.rank {
+            println!("║  Rank: #{:<28} ║", rank);
+        }
+        println!("╚════════════════════════════════════════╝");
 
-    println!("╔════════════════════════════════════════╗");
-    println!("║  RetroAchievements Profile             ║");
-    println!("╠════════════════════════════════════════╣");
-    println!("║  User: {:<30} ║", summary.user);
-    println!("║  Points: {:<28} ║", summary.total_points);
-    println!("║  Softcore Points: {:<19} ║", summary.total_softcore_points);
-    println!("║  True Points: {:<23} ║", summary.total_true_points);
-    if let Some(rank) = summary.rank {
-        println!("║  Rank: #{:<28} ║", rank);
-    }
-    println!("╚════════════════════════════════════════╝");
-
-    if let Some(recent) = summary.recently_played {
         if !recent.is_empty() {
             println!("\nRecently Played:");
             for game in recent.iter().take(5) {
                 println!("  • {} ({})", game.title, game.console_name);
             }
         }
-    }
+    });
+
+    Ok(())
+}
+
+fn cmd_friends(out: OutputFormat) -> Result<()> {
+    let cred_manager = CredentialManager::new()?;
+    let credentials = load_credentials(&cred_manager)?;
+
+    let client = RAClient::new(credentials);
+    let friends = client.get_followed_users()?;
+
+    let fields = serde_json::json!({
+        "friends": friends.iter().map(|friend| serde_json::json!({
+            "user": friend.user,
+            "points": friend.points,
+            "softcore_points": friend.points_softcore,
+            "follows_you": friend.is_following_me,
+        })).collect::<Vec<_>>(),
+    });
+    emit(out, fields, || {
+        if friends.is_empty() {
+            println!("You are not following anyone on RetroAchievements.");
+            return;
+        }
+
+        println!("Following {} users:", friends.len());
+        for friend in &friends {
+            println!(
+                "  • {:<20} {:>7} pts{}",
+                friend.user,
+                friend.points,
+                if friend.is_following_me {
+                    "  (follows you)"
+                } else {
+                    ""
+                }
+            );
+        }
+    });
+
+    Ok(())
+}
+
+fn cmd_compare(game_id: u32, user: &str, out: OutputFormat) -> Result<()> {
+    let cred_manager = CredentialManager::new()?;
+    let credentials = load_credentials(&cred_manager)?;
+
+    let client = RAClient::new(credentials);
+    let me = client.username().to_string();
+    let comparison = client.compare_progress(game_id, user)?;
+
+    let fields = serde_json::json!({
+        "game_id": comparison.game_id,
+        "game_title": comparison.game_title,
+        "user": me,
+        "friend": comparison.friend,
+        "achievements": comparison.achievements,
+        "total": comparison.total(),
+        "mine_earned": comparison.mine_earned(),
+        "mine_points": comparison.mine_points(),
+        "theirs_earned": comparison.theirs_earned(),
+        "theirs_points": comparison.theirs_points(),
+    });
+    emit(out, fields, || {
+        println!("{} (ID: {})", comparison.game_title, comparison.game_id);
+        println!();
+        println!(
+            "  {:<40} {:>6} {:>6}",
+            "Achievement",
+            truncate_name(&me),
+            truncate_name(user)
+        );
+        for achievement in &comparison.achievements {
+            let title = if achievement.title.chars().count() > 40 {
+                format!(
+                    "{}...",
+                    achievement.title.chars().take(37).collect::<String>()
+                )
+            } else {
+                achievement.title.clone()
+            };
+            println!(
+                "  {:<40} {:>6} {:>6}",
+                title,
+                if achievement.mine { "✓" } else { "·" },
+                if achievement.theirs { "✓" } else { "·" }
+            );
+        }
+        println!();
+        println!(
+            "  {}: {}/{} ({} pts)   {}: {}/{} ({} pts)",
+            me,
+            comparison.mine_earned(),
+            comparison.total(),
+            comparison.mine_points(),
+            user,
+            comparison.theirs_earned(),
+            comparison.total(),
+            comparison.theirs_points()
+        );
+    });
 
     Ok(())
 }
 
-fn cmd_hash_rom(path: &PathBuf, console: Option<&str>) -> Result<()> {
-    let console_id = if let Some(c) = console {
-        ConsoleId::from_str(c)
-            .context(format!("Unknown console: {}", c))?
+/// Shorten a username to fit a 6-character table column
+fn truncate_name(name: &str) -> String {
+    if name.chars().count() > 6 {
+        name.chars().take(6).collect()
+    } else {
+        name.to_string()
+    }
+}
+
+/// Percentage on one stderr line, redrawn only when it changes. Safe to
+/// report to from several hashing threads.
+struct ProgressLine {
+    label: String,
+    last: std::sync::Mutex<Option<u64>>,
+}
+
+impl ProgressLine {
+    fn new(label: impl Into<String>) -> Self {
+        Self {
+            label: label.into(),
+            last: std::sync::Mutex::new(None),
+        }
+    }
+
+    fn report(&self, done: u64, total: u64) {
+        let percent = (done * 100).checked_div(total).unwrap_or(100);
+        let mut last = self.last.lock().unwrap_or_else(|e| e.into_inner());
+        if *last != Some(percent) {
+            *last = Some(percent);
+            eprint!("\r{}: {:3}%", self.label, percent);
+        }
+    }
+
+    fn finish(&self) {
+        if self.last.lock().map(|last| last.is_some()).unwrap_or(false) {
+            eprintln!();
+        }
+    }
+}
+
+fn cmd_hash_rom(
+    path: &Path,
+    console: Option<&str>,
+    explain: bool,
+    progress: bool,
+    out: OutputFormat,
+) -> Result<()> {
+    let (console_id, detection) = if let Some(c) = console {
+        (parse_console(c)?, None)
+    } else if explain {
+        let detection = detect_console_explained(path)?;
+        (detection.console, Some(detection))
     } else {
         // Auto-detect console from file
-        detect_console(path)?
+        (detect_console(path)?, None)
+    };
+
+    let hash = if progress {
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        let line = ProgressLine::new(format!("Hashing {}", name));
+        let hash = hash_rom_with_progress(path, console_id, |read, total| line.report(read, total));
+        line.finish();
+        hash?
+    } else {
+        hash_rom(path, console_id)?
+    };
+
+    let mut fields = serde_json::json!({
+        "hash": hash,
+        "console": console_id.to_string(),
+        "hash_method": hash_type_name(console_id),
+    });
+    if let Some(detection) = &detection {
+        fields["detection"] = serde_json::json!({
+            "confidence": detection.confidence,
+            "reasons": detection.reasons,
+            "alternatives": detection.alternatives.iter().map(|(console, confidence)| serde_json::json!({
+                "console": console.to_string(),
+                "confidence": confidence,
+            })).collect::<Vec<_>>(),
+        });
+    }
+    emit(out, fields, || {
+        if explain {
+            match &detection {
+                Some(detection) => {
+                    println!(
+                        "Console: {} ({}% confidence)",
+                        detection.console.to_string(),
+                        detection.confidence
+                    );
+                    for reason in &detection.reasons {
+                        println!("  {}", reason);
+                    }
+                    for (console, confidence) in &detection.alternatives {
+                        println!("  Also matched: {} ({}%)", console.to_string(), confidence);
+                    }
+                }
+                None => println!("Console: {} (given with --console)", console_id.to_string()),
+            }
+            println!("Hash method: {}", hash_type_name(console_id));
+        }
+        println!("{}", hash);
+    });
+    Ok(())
+}
+
+fn cmd_hash_dir(
+    dir: &Path,
+    console: Option<&str>,
+    jobs: Option<usize>,
+    progress: bool,
+    out: OutputFormat,
+) -> Result<()> {
+    let console = console.map(parse_console).transpose()?;
+    let mut files = Vec::new();
+    collect_files(dir, &mut files).with_context(|| format!("Failed to read {}", dir.display()))?;
+    files.sort();
+
+    // Files no console claims aren't ROMs (saves, covers, cartridge files...)
+    let mut skipped = 0;
+    let mut roms = Vec::new();
+    for path in files {
+        match console {
+            Some(console_id) => roms.push((path, console_id)),
+            None => match detect_console(&path) {
+                Ok(console_id) => roms.push((path, console_id)),
+                Err(_) => skipped += 1,
+            },
+        }
+    }
+
+    let line = ProgressLine::new(format!("Hashing {} ROMs", roms.len()));
+    let hash_all = || {
+        hash_roms(&roms, |read, total| {
+            if progress {
+                line.report(read, total);
+            }
+        })
+    };
+    let results = match jobs {
+        Some(jobs) => rayon::ThreadPoolBuilder::new()
+            .num_threads(jobs)
+            .build()
+            .context("Failed to start hashing threads")?
+            .install(hash_all),
+        None => hash_all(),
     };
+    line.finish();
+
+    let mut hashed = Vec::new();
+    let mut failed = Vec::new();
+    for ((path, console_id), result) in roms.iter().zip(results) {
+        match result {
+            Ok(hash) => hashed.push(serde_json::json!({
+                "path": path,
+                "console": console_id.to_string(),
+                "hash": hash,
+            })),
+            Err(e) => failed.push(serde_json::json!({
+                "path": path,
+                "error": format!("{:#}", e),
+            })),
+        }
+    }
+
+    let fields = serde_json::json!({
+        "roms": hashed,
+        "failed": failed,
+        "skipped": skipped,
+    });
+    emit(out, fields, || {
+        for rom in &hashed {
+            println!(
+                "{}  {:<12} {}",
+                rom["hash"].as_str().unwrap_or(""),
+                rom["console"].as_str().unwrap_or(""),
+                rom["path"].as_str().unwrap_or("")
+            );
+        }
+        for failure in &failed {
+            eprintln!("{}", failure["error"].as_str().unwrap_or(""));
+        }
+        eprintln!(
+            "{} hashed, {} failed, {} skipped",
+            hashed.len(),
+            failed.len(),
+            skipped
+        );
+    });
+    Ok(())
+}
 
-    let hash = hash_rom(path, console_id)?;
-    println!("{}", hash);
+/// Every file under `dir`, following subdirectories but skipping hidden entries
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path
+            .file_name()
+            .is_some_and(|n| n.to_string_lossy().starts_with('.'))
+        {
+            continue;
+        }
+        if path.is_dir() {
+            collect_files(&path, files)?;
+        } else {
+            files.push(path);
+        }
+    }
     Ok(())
 }
 
-fn cmd_game_info(hash: Option<String>, path: Option<PathBuf>, console: Option<&str>) -> Result<()> {
+fn cmd_game_info(
+    hash: Option<String>,
+    path: Option<PathBuf>,
+    console: Option<&str>,
+    unofficial: bool,
+    out: OutputFormat,
+) -> Result<()> {
     let cred_manager = CredentialManager::new()?;
-    let credentials = cred_manager.load()?
-        .context("No credentials stored. Run 'kazeta-ra login' first.")?;
+    let credentials = load_credentials(&cred_manager)?;
 
     // Save path for cartridge lookup before it's moved
     let path_for_cart = path.as_ref().cloned();
@@ -299,16 +1264,18 @@ fn cmd_game_info(hash: Option<String>, path: Option<PathBuf>, console: Option<&s
         (h, ConsoleId::GameBoyAdvance)
     } else if let Some(p) = path {
         // Auto-detect console if not provided
-        let detected_console = if let Some(c) = console {
-            ConsoleId::from_str(c)
-                .context(format!("Unknown console: {}", c))?
-        } else {
-            detect_console(&p)?
+        let detected_console = match console {
+            Some(c) => parse_console(c)?,
+            None => detect_console(&p)?,
         };
         let hash = hash_rom(&p, detected_console)?;
         (hash, detected_console)
     } else {
-        bail!("Either --hash or --path is required");
+        return Err(CodedError::new(
+            ErrorCode::InvalidArgument,
+            "Either --hash or --path is required",
+        )
+        .into());
     };
 
     let client = RAClient::new(credentials);
@@ -317,152 +1284,238 @@ fn cmd_game_info(hash: Option<String>, path: Option<PathBuf>, console: Option<&s
     // Check for custom game name
     let game_name_mapping = GameNameMapping::load().ok();
     // Try to find cartridge path from ROM path if provided
-    let cart_path = path_for_cart.as_ref().and_then(|p| find_cartridge_for_rom(p).ok());
-    let custom_name = game_name_mapping.as_ref()
+    let cart_path = path_for_cart
+        .as_ref()
+        .and_then(|p| find_cartridge_for_rom(p).ok());
+    let custom_name = game_name_mapping
+        .as_ref()
         .and_then(|m| m.get_name(&rom_hash, cart_path.as_deref()));
 
     // Try to get game ID from hash
     let game_id = match client.get_game_id(&rom_hash, console_id)? {
         Some(id) => id,
         None => {
-            // Game not found - show custom name if available
-            if let Some(name) = custom_name {
+            // Not an error: the ROM just has no set
+            if out == OutputFormat::Json {
+                let mut result = output::failure(
+                    ErrorCode::NotFound,
+                    "No RetroAchievements found for this ROM",
+                );
+                result["hash"] = serde_json::json!(rom_hash);
+                result["custom_name"] = serde_json::json!(custom_name);
+                println!("{}", result);
+            } else if let Some(name) = custom_name {
+                // Game not found - show custom name if available
                 println!("╔════════════════════════════════════════════════════════╗");
                 println!("║  {} (Custom Name)", name);
                 println!("╠════════════════════════════════════════════════════════╣");
                 println!("║  Hash: {}", rom_hash);
                 println!("║  No RetroAchievements found for this ROM.");
                 println!("╚════════════════════════════════════════════════════════╝");
-                return Ok(());
             } else {
                 println!("No RetroAchievements found for this ROM.");
                 println!("Hash: {}", rom_hash);
-                return Ok(());
             }
+            return Ok(());
         }
     };
 
     // Get full game info
-    let info = client.get_game_info_and_progress(game_id)?;
+    let include_unofficial = unofficial || RASettings::load()?.show_unofficial;
+    let info = client.get_game_info_with_unofficial(game_id, include_unofficial)?;
 
     // Cache it
     cache.cache_game(&rom_hash, &info)?;
+    let difficulty = cache.update_difficulty(&rom_hash).unwrap_or_else(|e| {
+        eprintln!("Warning: failed to estimate difficulty: {}", e);
+        None
+    });
 
-    // Use custom name if available, otherwise use API title
-    let display_title = custom_name.as_deref().unwrap_or(&info.title);
-
-    // Display
-    println!("╔════════════════════════════════════════════════════════╗");
-    println!("║  {} ", display_title);
-    println!("╠════════════════════════════════════════════════════════╣");
-    println!("║  Console: {}", info.console_name);
-    println!("║  Game ID: {}", info.id);
-    println!("║  Hash: {}", rom_hash);
-    println!("║  Achievements: {}", info.num_achievements);
-    
-    if let Some(earned) = info.num_awarded_to_user {
-        let total = info.num_achievements;
-        let pct = if total > 0 { earned * 100 / total } else { 0 };
-        println!("║  Progress: {}/{} ({}%)", earned, total, pct);
-    }
-    
-    println!("╚════════════════════════════════════════════════════════╝");
-
-    // List achievements
-    if let Some(ref achievements) = info.achievements {
-        println!("\nAchievements:");
-        let mut sorted: Vec<_> = achievements.values().collect();
-        sorted.sort_by_key(|a| a.display_order);
-
-        for achievement in sorted {
-            let status = if achievement.is_earned_hardcore() {
-                "★"
-            } else if achievement.is_earned() {
-                "✓"
-            } else {
-                " "
-            };
-            println!(
-                "  [{}] {} ({} pts) - {}",
-                status, achievement.title, achievement.points, achievement.description
-            );
+    // Use custom name if available, otherwise the API title in the system
+    // language
+    let translations = Translations::load_or_default(info.id);
+    let display_title = custom_name
+        .as_deref()
+        .unwrap_or(translations.game_title(&info.title));
+
+    let mut sorted: Vec<_> = info.achievements.iter().flat_map(|a| a.values()).collect();
+    sorted.sort_by_key(|a| a.display_order);
+
+    let fields = serde_json::json!({
+        "game_id": info.id,
+        "title": display_title,
+        "console": info.console_name,
+        "hash": rom_hash,
+        "language": translations.language,
+        "achievements_total": info.num_achievements,
+        "achievements_earned": info.num_awarded_to_user,
+        "difficulty": difficulty.as_ref().map(|d| serde_json::json!({
+            "score": d.score,
+            "label": d.label(),
+            "pace_secs": d.pace_secs,
+            "expected_master_secs": d.expected_master_secs,
+        })),
+        "achievements": sorted.iter().map(|a| serde_json::json!({
+            "id": a.id,
+            "title": translations.achievement_title(a.id, &a.title),
+            "description": translations.achievement_description(a.id, &a.description),
+            "points": a.points,
+            "earned": a.is_earned(),
+            "earned_hardcore": a.is_earned_hardcore(),
+            "unofficial": a.is_unofficial(),
+        })).collect::<Vec<_>>(),
+    });
+    emit(out, fields, || {
+        println!("╔════════════════════════════════════════════════════════╗");
+        println!("║  {} ", display_title);
+        println!("╠════════════════════════════════════════════════════════╣");
+        println!("║  Console: {}", info.console_name);
+        println!("║  Game ID: {}", info.id);
+        println!("║  Hash: {}", rom_hash);
+        println!("║  Achievements: {}", info.num_achievements);
+
+        if let Some(earned) = info.num_awarded_to_user {
+            let total = info.num_achievements;
+            let pct = (earned * 100).checked_div(total).unwrap_or(0);
+            println!("║  Progress: {}/{} ({}%)", earned, total, pct);
+        }
+
+        if let Some(d) = &difficulty {
+            println!("║  Difficulty: {} ({:.1}/10)", d.label(), d.score);
+            match d.expected_master_secs {
+                Some(0) => {}
+                Some(secs) => println!(
+                    "║  Expected time to master: ~{}",
+                    difficulty::format_duration(secs)
+                ),
+                None => println!("║  Expected time to master: unknown until you unlock a few"),
+            }
         }
-    }
+
+        println!("╚════════════════════════════════════════════════════════╝");
+
+        // List achievements
+        if info.achievements.is_some() {
+            println!("\nAchievements:");
+            for achievement in &sorted {
+                let status = if achievement.is_earned_hardcore() {
+                    "★"
+                } else if achievement.is_earned() {
+                    "✓"
+                } else {
+                    " "
+                };
+                println!(
+                    "  [{}] {} ({} pts) - {}{}",
+                    status,
+                    translations.achievement_title(achievement.id, &achievement.title),
+                    achievement.points,
+                    translations.achievement_description(achievement.id, &achievement.description),
+                    if achievement.is_unofficial() {
+                        " [Unofficial]"
+                    } else {
+                        ""
+                    }
+                );
+            }
+        }
+    });
 
     Ok(())
 }
 
-fn cmd_game_start(hash: Option<&str>, console: Option<&str>, path: Option<&PathBuf>, notify_overlay: bool) -> Result<()> {
+fn cmd_game_start(
+    hash: Option<&str>,
+    console: Option<&str>,
+    path: Option<&PathBuf>,
+    cart: Option<PathBuf>,
+    name_hint: Option<String>,
+    overlay: Option<&OverlaySocket>,
+    out: OutputFormat,
+) -> Result<()> {
+    // A new game starts in the saved mode, whatever the last one was switched to
+    auth::clear_session_softcore();
+
     let cred_manager = CredentialManager::new()?;
-    let credentials = cred_manager.load()?
-        .context("No credentials stored. Run 'kazeta-ra login' first.")?;
+    let credentials = load_credentials(&cred_manager)?;
 
     // Determine hash and console
-    let (rom_hash, console_id) = if let Some(h) = hash {
-        // Hash provided, console is required
-        let c = console.ok_or_else(|| anyhow::anyhow!("--console is required when using --hash"))?;
-        let console_id = ConsoleId::from_str(c)
-            .context(format!("Unknown console: {}", c))?;
-        (h.to_string(), console_id)
-    } else if let Some(p) = path {
-        // Path provided, auto-detect console if not specified
-        let detected_console = if let Some(c) = console {
-            ConsoleId::from_str(c)
-                .context(format!("Unknown console: {}", c))?
-        } else {
-            detect_console(p)?
-        };
-        let hash = hash_rom(p, detected_console)?;
-        (hash, detected_console)
-    } else {
-        bail!("Either --hash or --path is required");
-    };
+    let (rom_hash, console_id) = rom_hash_and_console(hash, path, console)?;
+
+    // Catch a database damaged by a power cut before reading from it. The
+    // findings go to stderr so the JSON on stdout stays a single object.
+    if let Ok(verifier) = CacheVerifier::new() {
+        for finding in verifier.quick().findings {
+            eprintln!("Cache check: {}: {}", finding.item, finding.detail);
+        }
+    }
 
     let client = RAClient::new(credentials);
     let cache = RACache::new()?;
 
     // Check for custom game name first
     let game_name_mapping = GameNameMapping::load().ok();
-    // Try to find cartridge path from ROM path if provided
-    let cart_path = path.and_then(|p| find_cartridge_for_rom(p).ok());
-    let custom_name = game_name_mapping.as_ref()
+    // Use the given cartridge, or try to find it from the ROM path
+    let cart_path = cart.or_else(|| path.and_then(|p| find_cartridge_for_rom(p).ok()));
+    let custom_name = game_name_mapping
+        .as_ref()
         .and_then(|m| m.get_name(&rom_hash, cart_path.as_deref()));
 
     // Get game info
     let game_id = match client.get_game_id(&rom_hash, console_id)? {
         Some(id) => id,
         None => {
-            // Game not found - use custom name if available
+            // Game not found - use custom name if available, then the
+            // cartridge's hint, else the ROM's own
+            let (custom_name, source) = match (custom_name, name_hint) {
+                (Some(name), _) => (Some(name), NameSource::Manual),
+                (None, Some(hint)) => (Some(hint), NameSource::Cartridge),
+                (None, None) => (header_name(&rom_hash, path, console_id), NameSource::Header),
+            };
             if let Some(name) = custom_name {
-                let output = serde_json::json!({
-                    "success": true,
+                let fields = serde_json::json!({
                     "game_id": 0,
                     "title": name,
                     "custom_name": true,
+                    "name_source": source,
+                    "achievements_total": 0,
+                    "achievements_earned": 0,
+                    // Older names for the same fields
                     "total_achievements": 0,
                     "earned_achievements": 0,
                 });
-                println!("{}", serde_json::to_string(&output)?);
-                return Ok(());
+                emit(out, fields, || {
+                    println!("Started: {} (no achievements)", name)
+                });
             } else {
-                println!("{{\"success\": false, \"error\": \"Game not found in RetroAchievements\"}}");
-                return Ok(());
+                // Not an error for the wrapper: the game runs without achievements
+                report_failure(
+                    out,
+                    ErrorCode::NotFound,
+                    "Game not found in RetroAchievements",
+                );
             }
+            return Ok(());
         }
     };
 
     let info = client.get_game_info_and_progress(game_id)?;
     cache.cache_game(&rom_hash, &info)?;
+    let difficulty = cache.update_difficulty(&rom_hash).unwrap_or(None);
 
     let earned = info.num_awarded_to_user.unwrap_or(0);
     let total = info.num_achievements;
 
-    // Use custom name if available, otherwise use API title
-    let game_title = custom_name.unwrap_or_else(|| info.title.clone());
+    // Use custom name if available, otherwise the API title in the system
+    // language
+    let game_title = custom_name.unwrap_or_else(|| {
+        Translations::load_or_default(info.id)
+            .game_title(&info.title)
+            .to_string()
+    });
 
     // Output game info as JSON for runtime wrapper
-    let output = serde_json::json!({
-        "success": true,
+    let fields = serde_json::json!({
         "game_id": info.id,
         "title": game_title,
         "console": info.console_name,
@@ -470,99 +1523,1076 @@ fn cmd_game_start(hash: Option<&str>, console: Option<&str>, path: Option<&PathB
         "achievements_earned": earned,
         "icon_url": info.image_icon,
     });
-    println!("{}", serde_json::to_string(&output)?);
 
-    // Notify overlay if requested
-    if notify_overlay {
-        notify_overlay_game_start(info.id, &game_title, earned, total)?;
+    // Notify overlay if requested. The game has started either way, so the
+    // hooks run before a failed delivery is reported.
+    let delivered = match overlay {
+        Some(overlay) => notify_overlay_game_start(
+            overlay,
+            &info,
+            &game_title,
+            earned,
+            total,
+            difficulty.as_ref(),
+        )
+        .and_then(|()| {
+            // New games in the cache can be drawn from once the next set is due
+            let mut challenges = ChallengeState::load().unwrap_or_default();
+            match refresh_challenges(&cache, &mut challenges) {
+                Ok(_) => notify_overlay_challenges(overlay, &challenges),
+                Err(_) => Ok(()),
+            }
+        }),
+        None => Ok(()),
+    };
+
+    hooks::fire(
+        HookEvent::GameStart,
+        serde_json::json!({
+            "game_id": info.id,
+            "title": game_title,
+            "console": info.console_name,
+            "hash": rom_hash,
+            "achievements_total": total,
+            "achievements_earned": earned,
+        }),
+    );
+    delivered?;
+
+    emit(out, fields, || {
+        println!(
+            "Started: {} ({}/{} achievements)",
+            game_title, earned, total
+        );
+    });
+    Ok(())
+}
+
+fn cmd_session(action: SessionAction, out: OutputFormat) -> Result<()> {
+    match action {
+        SessionAction::Start {
+            hash,
+            path,
+            console,
+            players,
+            notify,
+        } => cmd_session_start(
+            hash.as_deref(),
+            path.as_ref(),
+            console.as_deref(),
+            &players,
+            &notify.socket(),
+            out,
+        ),
+        SessionAction::Ping {
+            rich_presence,
+            interval,
+        } => cmd_session_ping(rich_presence.as_deref(), interval, out),
+        SessionAction::Stop => {
+            let session = GameSession::clear()?;
+            emit(
+                out,
+                serde_json::json!({ "stopped": session.is_some() }),
+                || match &session {
+                    Some(session) => println!("Session for game {} stopped", session.game_id),
+                    None => println!("No session running"),
+                },
+            );
+            Ok(())
+        }
     }
+}
+
+fn cmd_session_start(
+    hash: Option<&str>,
+    path: Option<&PathBuf>,
+    console: Option<&str>,
+    players: &[String],
+    overlay: &OverlaySocket,
+    out: OutputFormat,
+) -> Result<()> {
+    let cred_manager = CredentialManager::new()?;
+    let credentials = load_credentials(&cred_manager)?;
+    let hardcore = credentials.session_hardcore();
+    let account = credentials.username.clone();
+    let players = players
+        .iter()
+        .map(|p| SessionPlayer::parse(p))
+        .collect::<Result<Vec<_>>>()
+        .map_err(|e| CodedError::new(ErrorCode::InvalidArgument, e.to_string()))?;
+    let (rom_hash, console_id) = rom_hash_and_console(hash, path, console)?;
 
+    let cache = RACache::new()?;
+    let client = RAClient::new(credentials);
+    let game_id = match cache.get_game_id(&rom_hash)? {
+        Some(id) => id,
+        None => client.get_game_id(&rom_hash, console_id)?.ok_or_else(|| {
+            CodedError::new(ErrorCode::NotFound, "Game not found in RetroAchievements")
+        })?,
+    };
+    let title = cache.get_game_title(&rom_hash)?.unwrap_or_default();
+
+    let now = chrono::Utc::now().timestamp();
+    let mut session = GameSession::new(game_id, rom_hash, title, hardcore, now);
+    session
+        .set_players(players, &account)
+        .map_err(|e| CodedError::new(ErrorCode::InvalidArgument, e.to_string()))?;
+    // Offline play still gets a session file; the next ping tries RA again
+    let connected = match client.start_session(game_id, hardcore) {
+        Ok(()) => {
+            session.last_ping = Some(now);
+            true
+        }
+        Err(e) => {
+            eprintln!("[RA] Failed to start RA session: {:#}", e);
+            false
+        }
+    };
+    session.save()?;
+    if !session.players.is_empty() {
+        notify_overlay_session_players(overlay, &session.players, &account)?;
+    }
+
+    let fields = serde_json::json!({ "session": session, "connected": connected });
+    emit(out, fields, || {
+        println!(
+            "Session started for game {} ({})",
+            session.game_id,
+            if hardcore { "hardcore" } else { "softcore" }
+        );
+        for player in &session.players {
+            println!("  Player {}: {}", player.slot, player.username);
+        }
+        if !connected {
+            println!("RetroAchievements couldn't be reached; pings will retry.");
+        }
+    });
+    Ok(())
+}
+
+fn cmd_session_ping(
+    rich_presence: Option<&str>,
+    interval: Option<u64>,
+    out: OutputFormat,
+) -> Result<()> {
+    let credentials = load_credentials(&CredentialManager::new()?)?;
+    let client = RAClient::new(credentials);
+
+    loop {
+        let mut session = GameSession::load()?
+            .ok_or_else(|| CodedError::new(ErrorCode::NotFound, "No session running"))?;
+        let result = client.ping(session.game_id, rich_presence);
+        if result.is_ok() {
+            session.last_ping = Some(chrono::Utc::now().timestamp());
+            session.rich_presence = rich_presence.map(str::to_string);
+            // Stopped while the ping was in flight
+            if GameSession::load()?.is_none() {
+                break;
+            }
+            session.save()?;
+        }
+
+        let Some(secs) = interval else {
+            result?;
+            emit(out, serde_json::json!({ "session": session }), || {
+                println!("Pinged game {}", session.game_id);
+            });
+            return Ok(());
+        };
+        if let Err(e) = result {
+            eprintln!("[RA] Ping failed: {:#}", e);
+        }
+        std::thread::sleep(std::time::Duration::from_secs(secs.max(1)));
+    }
+
+    emit(out, serde_json::json!({ "stopped": true }), || {
+        println!("Session stopped")
+    });
+    Ok(())
+}
+
+fn cmd_local(action: LocalAction, out: OutputFormat) -> Result<()> {
+    let (LocalAction::Award { cart, .. } | LocalAction::List { cart }) = &action;
+    let cart = cart.clone().unwrap_or_else(|| PathBuf::from("."));
+    let set = LocalSet::load(&cart)
+        .map_err(|e| CodedError::new(ErrorCode::NotFound, format!("{:#}", e)))?;
+    let cache = RACache::new()?;
+
+    match action {
+        LocalAction::Award { id, .. } => {
+            if set.get(&id).is_none() {
+                return Err(CodedError::new(
+                    ErrorCode::InvalidArgument,
+                    format!("{} has no achievement '{}'", set.cart_id, id),
+                )
+                .into());
+            }
+            let award = local::award(&cache, &set, &id)?;
+            let fields = serde_json::json!({ "cart_id": set.cart_id, "award": award });
+            emit(out, fields, || {
+                if award.newly_unlocked {
+                    println!(
+                        "✓ {} ({} pts)",
+                        award.achievement.title, award.achievement.points
+                    );
+                } else {
+                    println!("{} was already unlocked", award.achievement.title);
+                }
+                println!("{}/{} cart achievements", award.earned, award.total);
+            });
+        }
+        LocalAction::List { .. } => {
+            let unlocks = cache.local_unlocks(&set.cart_id)?;
+            let unlocked_at = |id: &str| {
+                unlocks
+                    .iter()
+                    .find(|u| u.achievement_id == id)
+                    .map(|u| u.unlocked_at)
+            };
+            let achievements: Vec<serde_json::Value> = set
+                .achievements
+                .iter()
+                .map(|a| {
+                    serde_json::json!({
+                        "id": a.id,
+                        "title": a.title,
+                        "description": a.description,
+                        "points": a.points,
+                        "unlocked_at": unlocked_at(&a.id),
+                    })
+                })
+                .collect();
+            let fields = serde_json::json!({
+                "cart_id": set.cart_id,
+                "achievements": achievements,
+                "total_points": set.total_points(),
+            });
+            emit(out, fields, || {
+                println!(
+                    "{} - cart achievements (not RetroAchievements)",
+                    set.cart_id
+                );
+                for a in &set.achievements {
+                    let mark = if unlocked_at(&a.id).is_some() {
+                        "✓"
+                    } else {
+                        " "
+                    };
+                    println!("  [{}] {} ({} pts)", mark, a.title, a.points);
+                    if !a.description.is_empty() {
+                        println!("      {}", a.description);
+                    }
+                }
+            });
+        }
+    }
     Ok(())
 }
 
-fn cmd_notify_achievement(id: u32, title: Option<String>) -> Result<()> {
+fn cmd_notify_achievement(
+    id: u32,
+    title: Option<String>,
+    player: Option<u8>,
+    overlay: &OverlaySocket,
+    out: OutputFormat,
+) -> Result<()> {
     let cache = RACache::new()?;
 
     // Try to get achievement info from cache
     let achievement_title = title.unwrap_or_else(|| format!("Achievement #{}", id));
 
-    // Notify overlay
-    notify_overlay_achievement(&achievement_title)?;
+    // The mode the player is logged in with (softcore if hardcore was
+    // suspended for this session)
+    let credentials = CredentialManager::new()
+        .ok()
+        .and_then(|m| m.load().ok().flatten());
+    let hardcore = credentials.as_ref().is_some_and(|c| c.session_hardcore());
+    let account = credentials.as_ref().map_or("", |c| c.username.as_str());
+
+    // In a shared session, whoever plays in the slot earned it
+    let player = match player {
+        Some(slot) => {
+            let session = GameSession::load()?;
+            let player = session
+                .as_ref()
+                .and_then(|s| s.player(slot))
+                .cloned()
+                .ok_or_else(|| {
+                    CodedError::new(
+                        ErrorCode::InvalidArgument,
+                        format!("Player {} isn't mapped in the running session", slot),
+                    )
+                })?;
+            Some(player)
+        }
+        None => None,
+    };
+    let for_account = player.as_ref().is_none_or(|p| p.is_account(account));
+
+    // Notify overlay. The unlock is recorded even if that fails; the
+    // failure is reported once everything else is done.
+    let mut delivered = match &player {
+        Some(player) => notify_overlay_player_achievement(
+            overlay,
+            &cache,
+            id,
+            &achievement_title,
+            player,
+            account,
+            hardcore,
+        ),
+        None => notify_overlay_achievement(overlay, &achievement_title),
+    };
+
+    // The cache and challenges follow the logged-in account only
+    if for_account {
+        let _ = cache.mark_earned(id, hardcore);
 
-    // Mark in cache
-    let _ = cache.mark_earned(id, false);
+        let mut challenges = ChallengeState::load().unwrap_or_default();
+        if let Ok(outcome) = refresh_challenges(&cache, &mut challenges) {
+            if !outcome.completed.is_empty() && delivered.is_ok() {
+                delivered = notify_overlay_challenge_progress(overlay, &outcome, &challenges)
+                    .and_then(|()| notify_overlay_challenges(overlay, &challenges));
+            }
+        }
+    }
 
-    println!("{{\"success\": true, \"achievement_id\": {}}}", id);
+    let player_name = player.as_ref().map(|p| p.username.as_str());
+    let game = cache.get_achievement_game(id).ok().flatten();
+    hooks::fire(
+        HookEvent::Unlock,
+        serde_json::json!({
+            "achievement_id": id,
+            "title": achievement_title,
+            "game_hash": game.as_ref().map(|(hash, _)| hash),
+            "game_title": game.as_ref().map(|(_, title)| title),
+            "player": player_name,
+        }),
+    );
+
+    // The last achievement of the set completes the game. A hardcore unlock
+    // on a set already finished in softcore only counts once it is all hardcore.
+    let mastery = game
+        .filter(|_| for_account)
+        .and_then(|(hash, _)| cache.get_mastery(&hash).ok().flatten())
+        .filter(|m| m.hardcore || !hardcore);
+    if let Some(mastery) = mastery {
+        if delivered.is_ok() {
+            delivered = notify_overlay_mastery(overlay, &mastery);
+        }
+        hooks::fire(
+            HookEvent::Mastery,
+            serde_json::json!({
+                "game_hash": mastery.game_hash,
+                "game_title": mastery.game_title,
+                "achievements_total": mastery.total,
+                "points": mastery.points,
+                "hardcore": mastery.hardcore,
+                "completion_secs": mastery.completion_secs(),
+            }),
+        );
+    }
+    delivered?;
+
+    emit(
+        out,
+        serde_json::json!({ "achievement_id": id, "player": player_name }),
+        || match player_name {
+            Some(name) => println!("✓ {} ({})", achievement_title, name),
+            None => println!("✓ {}", achievement_title),
+        },
+    );
+    Ok(())
+}
+
+fn cmd_notify_progress(
+    id: u32,
+    current: u32,
+    target: u32,
+    overlay: &OverlaySocket,
+    out: OutputFormat,
+) -> Result<()> {
+    if current > target && target > 0 {
+        return Err(CodedError::new(
+            ErrorCode::InvalidArgument,
+            format!(
+                "Current value {} is greater than the target {}",
+                current, target
+            ),
+        )
+        .into());
+    }
+
+    notify_overlay_progress(overlay, id, current, target)?;
+
+    let fields = serde_json::json!({ "achievement_id": id, "current": current, "target": target });
+    emit(out, fields, || {
+        println!("✓ Achievement #{}: {}/{}", id, current, target)
+    });
     Ok(())
 }
 
-fn cmd_status() -> Result<()> {
+fn cmd_status(refresh: bool, offline: bool, ttl: u64, out: OutputFormat) -> Result<()> {
     let cred_manager = CredentialManager::new()?;
 
     if !cred_manager.has_credentials() {
-        println!("{{\"enabled\": false, \"reason\": \"Not logged in\"}}");
+        emit(
+            out,
+            serde_json::json!({ "enabled": false, "reason": "Not logged in" }),
+            || {
+                println!("RetroAchievements: not logged in");
+            },
+        );
         return Ok(());
     }
 
-    let credentials = cred_manager.load()?
-        .context("Failed to load credentials")?;
+    let credentials = cred_manager.load()?.context("Failed to load credentials")?;
+
+    // Reuse the last check while it's fresh; asking the server costs seconds
+    // on every launch, and much longer without a network
+    let now = chrono::Utc::now().timestamp();
+    let ttl = chrono::Duration::seconds(ttl.min(i64::MAX as u64) as i64);
+    let last_check = cred_manager
+        .load_status_check()
+        .filter(|check| check.username == credentials.username);
+    // Without a usable answer, trust the credentials unless they were rejected
+    let trusted = last_check.as_ref().is_none_or(|check| check.valid);
+    let last_checked_at = last_check.as_ref().map(|check| check.checked_at);
 
     let client = RAClient::new(credentials.clone());
-    let valid = client.verify_credentials().unwrap_or(false);
+    let (valid, source, checked_at) = match &last_check {
+        Some(check) if !refresh && check.is_fresh(&credentials.username, now, ttl) => {
+            (check.valid, "cached", Some(check.checked_at))
+        }
+        _ if offline => (trusted, "offline", last_checked_at),
+        _ => match client.check_credentials() {
+            Ok(valid) => {
+                if let Err(e) = cred_manager.save_status_check(&StatusCheck::new(
+                    &credentials.username,
+                    valid,
+                    now,
+                )) {
+                    eprintln!("Failed to record credential check: {:#}", e);
+                }
+                (valid, "server", Some(now))
+            }
+            Err(e) => {
+                eprintln!(
+                    "Couldn't reach RetroAchievements, using stored credentials: {:#}",
+                    e
+                );
+                (trusted, "offline", last_checked_at)
+            }
+        },
+    };
+
+    // A refresh probes every endpoint; otherwise report what requests last saw
+    let endpoints = if refresh {
+        client.probe_endpoints()
+    } else {
+        client.endpoint_report()
+    };
+    let current_endpoint = endpoints.iter().find(|e| e.current);
 
-    let output = serde_json::json!({
+    let softcore_reason = auth::session_softcore_reason();
+    let session = GameSession::load().unwrap_or(None);
+    let stale = session
+        .as_ref()
+        .is_some_and(|s| s.is_stale(chrono::Utc::now().timestamp()));
+
+    let fields = serde_json::json!({
         "enabled": valid,
         "username": credentials.username,
-        "hardcore": credentials.hardcore,
+        "hardcore": credentials.session_hardcore(),
+        "softcore_reason": softcore_reason,
         "valid_credentials": valid,
+        "checked_by": source,
+        "checked_at": checked_at,
+        "session": session,
+        "session_stale": stale,
+        "endpoint": current_endpoint.map(|e| &e.url),
+        "endpoints": endpoints,
+    });
+    emit(out, fields, || {
+        println!("RetroAchievements: logged in as {}", credentials.username);
+        if !valid {
+            println!("  Credentials were rejected. Run 'kazeta-ra login' again.");
+        }
+        match (source, checked_at) {
+            ("server", _) => {}
+            (_, Some(at)) => println!(
+                "  Last checked {} min ago (--refresh to check now)",
+                (now - at).max(0) / 60
+            ),
+            (_, None) => println!("  Not checked with the server yet"),
+        }
+        match (credentials.session_hardcore(), &softcore_reason) {
+            (true, _) => println!("  Hardcore: on"),
+            (false, Some(reason)) => println!("  Hardcore: off for this session ({})", reason),
+            (false, None) => println!("  Hardcore: off"),
+        }
+        if let Some(session) = &session {
+            let note = if stale { " (no recent ping)" } else { "" };
+            println!("  Playing: game {}{}", session.game_id, note);
+        }
+        match current_endpoint {
+            Some(endpoint) => {
+                let latency = endpoint
+                    .health
+                    .latency_ms
+                    .map(|ms| format!(", {} ms", ms))
+                    .unwrap_or_default();
+                println!(
+                    "  Endpoint: {} ({}{})",
+                    endpoint.url,
+                    endpoint.state.label(),
+                    latency
+                );
+            }
+            None => println!("  Endpoint: none available"),
+        }
+        for endpoint in endpoints
+            .iter()
+            .filter(|e| !e.current && e.state != CircuitState::Closed)
+        {
+            let retry = endpoint
+                .health
+                .open_until
+                .filter(|until| *until > now)
+                .map(|until| format!(", retry in {}s", until - now))
+                .unwrap_or_default();
+            let error = endpoint
+                .health
+                .last_error
+                .as_deref()
+                .unwrap_or("unknown error");
+            println!(
+                "  {}: {}{} ({})",
+                endpoint.url,
+                endpoint.state.label(),
+                retry,
+                error
+            );
+        }
     });
-    println!("{}", serde_json::to_string(&output)?);
 
     Ok(())
 }
 
-fn cmd_clear_cache() -> Result<()> {
+fn cmd_clear_cache(out: OutputFormat) -> Result<()> {
     let cache = RACache::new()?;
     cache.clear()?;
-    println!("✓ Achievement cache cleared.");
+    ImageCache::clear_dir(&ImageCache::default_dir()?)?;
+    HttpCache::new()?.clear()?;
+    emit(out, serde_json::Value::Null, || {
+        println!("✓ Achievement cache cleared.")
+    });
     Ok(())
 }
 
-// Overlay notification helpers
+fn cmd_cache_verify(offline: bool, quick: bool, out: OutputFormat) -> Result<()> {
+    let verifier = CacheVerifier::new()?;
+    let mut report = if quick {
+        verifier.quick()
+    } else {
+        let (mut report, cache) = verifier.full();
+        let credentials = CredentialManager::new()?.load().ok().flatten();
+        if let (false, Some(cache), Some(credentials)) = (offline, cache, credentials) {
+            let games: Vec<(String, u32)> = cache
+                .library_progress()?
+                .into_iter()
+                .map(|game| (game.hash, game.game_id))
+                .collect();
+            let client = RAClient::new(credentials);
+            verify::cross_check(
+                &cache,
+                &games,
+                |game_id| client.get_game_info_and_progress(game_id),
+                &mut report,
+            );
+        }
+        report
+    };
+    report
+        .findings
+        .sort_by_key(|finding| finding.outcome == Outcome::Failed);
+
+    let fields = serde_json::json!({
+        "checked": report.checked,
+        "repaired": report.count(Outcome::Repaired),
+        "quarantined": report.count(Outcome::Quarantined),
+        "failed": report.count(Outcome::Failed),
+        "findings": report.findings,
+    });
+    emit(out, fields, || {
+        for finding in &report.findings {
+            let mark = match finding.outcome {
+                Outcome::Repaired => "✓ Repaired",
+                Outcome::Quarantined => "✓ Quarantined",
+                Outcome::Failed => "✗ Failed",
+            };
+            println!("{} {}: {}", mark, finding.item, finding.detail);
+        }
+        if report.is_clean() {
+            println!("✓ {}, no problems found.", report.summary());
+        } else {
+            println!("{}", report.summary());
+        }
+    });
+    Ok(())
+}
+
+fn cmd_diagnostics(out: OutputFormat) -> Result<()> {
+    let max_age = RASettings::load().unwrap_or_default().http_cache_max_age;
+    let summary = HttpCache::new()?.summary();
+    let stats = &summary.stats;
+
+    let fields = serde_json::json!({
+        "http_cache": {
+            "entries": summary.entries,
+            "bytes": summary.bytes,
+            "hits": stats.hits,
+            "revalidated": stats.revalidated,
+            "misses": stats.misses,
+            "hit_rate": stats.hit_rate(),
+            "max_age_secs": max_age,
+        },
+    });
+    emit(out, fields, || {
+        println!("HTTP cache");
+        println!(
+            "  Entries:      {} ({:.1} KB)",
+            summary.entries,
+            summary.bytes as f64 / 1024.0
+        );
+        println!(
+            "  Max age:      {}",
+            if max_age == 0 {
+                "always revalidate".to_string()
+            } else {
+                format!("{}s", max_age)
+            }
+        );
+        println!("  Hits:         {}", stats.hits);
+        println!("  Revalidated:  {} (304 Not Modified)", stats.revalidated);
+        println!("  Misses:       {}", stats.misses);
+        println!("  Hit rate:     {:.0}%", stats.hit_rate());
+    });
+    Ok(())
+}
 
-fn notify_overlay_game_start(game_id: u32, title: &str, earned: u32, total: u32) -> Result<()> {
-    use std::io::Write;
-    use std::os::unix::net::UnixStream;
+fn cmd_recent_unlocks(days: u32, out: OutputFormat) -> Result<()> {
+    let cache = RACache::new()?;
+    let unlocks = cache.recent_unlocks(days)?;
+
+    let fields = serde_json::json!({
+        "days": days,
+        "unlocks": unlocks.iter().map(|unlock| serde_json::json!({
+            "achievement_id": unlock.achievement_id,
+            "title": unlock.title,
+            "points": unlock.points,
+            "hardcore": unlock.hardcore,
+            "game_hash": unlock.game_hash,
+            "game_title": unlock.game_title,
+            "earned_at": unlock.earned_at,
+        })).collect::<Vec<_>>(),
+    });
+    emit(out, fields, || {
+        if unlocks.is_empty() {
+            println!("No achievements unlocked in the last {} days.", days);
+            return;
+        }
 
-    let socket_path = "/tmp/kazeta-overlay.sock";
-    if !std::path::Path::new(socket_path).exists() {
-        return Ok(()); // Overlay not running, skip
-    }
-    
-    let message = serde_json::json!({
-        "type": "ra_game_start",
-        "game_title": title,
-        "game_id": game_id,
-        "total_achievements": total,
-        "earned_achievements": earned,
+        println!("Unlocked in the last {} days:", days);
+        println!("{:-<80}", "");
+        for unlock in &unlocks {
+            let date = chrono::DateTime::from_timestamp(unlock.earned_at, 0)
+                .map(|d| d.format("%Y-%m-%d %H:%M").to_string())
+                .unwrap_or_default();
+            let game = unlock.game_title.as_deref().unwrap_or(&unlock.game_hash);
+            let mode = if unlock.hardcore { " [HC]" } else { "" };
+            println!(
+                "  {}  {} - {} ({} pts){}",
+                date, game, unlock.title, unlock.points, mode
+            );
+        }
+    });
+
+    Ok(())
+}
+
+fn cmd_in_progress(out: OutputFormat) -> Result<()> {
+    let cache = RACache::new()?;
+    let games = cache.games_in_progress()?;
+
+    let fields = serde_json::json!({
+        "games": games.iter().map(|game| serde_json::json!({
+            "hash": game.hash,
+            "game_id": game.game_id,
+            "title": game.title,
+            "earned": game.earned,
+            "total": game.total,
+            "last_unlock": game.last_unlock,
+            "last_synced": game.last_synced,
+        })).collect::<Vec<_>>(),
+    });
+    emit(out, fields, || {
+        if games.is_empty() {
+            println!("No games in progress.");
+            return;
+        }
+
+        println!("Games in progress:");
+        println!("{:-<80}", "");
+        for game in &games {
+            let percent = game.earned * 100 / game.total.max(1);
+            println!(
+                "  {} ({}/{}, {}%)",
+                game.title, game.earned, game.total, percent
+            );
+        }
+    });
+
+    Ok(())
+}
+
+fn cmd_trophies(recent: usize, out: OutputFormat) -> Result<()> {
+    let cache = RACache::new()?;
+    let games = cache.library_progress()?;
+    // Icons are only shown if an earlier fetch-images left them on disk
+    let images = ImageCache::new().ok();
+    let unlocks: Vec<_> = cache
+        .recent_unlocks(100 * 365)?
+        .into_iter()
+        .take(recent)
+        .collect();
+
+    let points_earned: u32 = games.iter().map(|g| g.points_earned).sum();
+    let points_total: u32 = games.iter().map(|g| g.points_total).sum();
+    let mastered = games.iter().filter(|g| g.is_mastered()).count();
+
+    let fields = serde_json::json!({
+        "points_earned": points_earned,
+        "points_total": points_total,
+        "mastered": mastered,
+        "games": games.iter().map(|game| serde_json::json!({
+            "hash": game.hash,
+            "game_id": game.game_id,
+            "title": game.title,
+            "console": game.console_name,
+            "earned": game.earned,
+            "total": game.total,
+            "points_earned": game.points_earned,
+            "points_total": game.points_total,
+            "mastered": game.is_mastered(),
+            "hardcore": game.is_mastered_hardcore(),
+            "last_unlock": game.last_unlock,
+            "icon_path": game.icon_url.as_deref()
+                .filter(|icon| !icon.is_empty())
+                .and_then(|icon| images.as_ref()?.get(&ImageCache::icon_url(icon))),
+        })).collect::<Vec<_>>(),
+        "recent": unlocks.iter().map(|unlock| serde_json::json!({
+            "achievement_id": unlock.achievement_id,
+            "title": unlock.title,
+            "points": unlock.points,
+            "hardcore": unlock.hardcore,
+            "game_title": unlock.game_title,
+            "earned_at": unlock.earned_at,
+        })).collect::<Vec<_>>(),
+    });
+    emit(out, fields, || {
+        if games.is_empty() {
+            println!("No games in the cache yet.");
+            return;
+        }
+
+        println!(
+            "{} / {} points, {} of {} games mastered",
+            points_earned,
+            points_total,
+            mastered,
+            games.len()
+        );
+        println!("{:-<80}", "");
+        for game in &games {
+            let percent = game.earned * 100 / game.total.max(1);
+            let badge = if game.is_mastered_hardcore() {
+                " [MASTERED HC]"
+            } else if game.is_mastered() {
+                " [MASTERED]"
+            } else {
+                ""
+            };
+            println!(
+                "  {} ({}/{}, {}%){}",
+                game.title, game.earned, game.total, percent, badge
+            );
+        }
+        if !unlocks.is_empty() {
+            println!();
+            println!("Recent unlocks:");
+            for unlock in &unlocks {
+                let game = unlock.game_title.as_deref().unwrap_or(&unlock.game_hash);
+                println!("  {} - {} ({} pts)", game, unlock.title, unlock.points);
+            }
+        }
     });
 
-    if let Ok(mut stream) = UnixStream::connect(socket_path) {
-        let _ = writeln!(stream, "{}", message);
+    Ok(())
+}
+
+fn cmd_challenge(action: ChallengeAction, out: OutputFormat) -> Result<()> {
+    let cache = RACache::new()?;
+    let mut state = ChallengeState::load()?;
+
+    if let ChallengeAction::Reroll { period } = &action {
+        let period = ChallengePeriod::parse(period).map_err(invalid_argument)?;
+        let settings = RASettings::load()?;
+        let candidates = cache.unearned_achievements()?;
+        state.reroll(
+            period,
+            chrono::Utc::now().timestamp(),
+            challenge_count(&settings, period),
+            &candidates,
+        );
+    }
+    refresh_challenges(&cache, &mut state)?;
+    // Only mirrored to the overlay's Challenges screen if it's up
+    if let Err(e) = notify_overlay_challenges(&OverlaySocket::new(SendOptions::default()), &state) {
+        if !overlay_socket::is_not_running(&e) {
+            eprintln!("[RA] {:#}", e);
+        }
     }
 
+    let now = chrono::Utc::now().timestamp();
+    emit(
+        out,
+        serde_json::json!({ "sets": challenge_sets_json(&state) }),
+        || {
+            let mut any = false;
+            for period in ChallengePeriod::ALL {
+                let period_state = state.period(period);
+                let Some(set) = &period_state.set else {
+                    continue;
+                };
+                any = true;
+
+                let left = (set.ends_at - now).max(0);
+                println!(
+                    "{} challenges ({}/{} done, ends in {}h {}m), streak {} (best {}):",
+                    capitalize(period.name()),
+                    set.completed(),
+                    set.challenges.len(),
+                    left / 3600,
+                    left % 3600 / 60,
+                    period_state.streak.current,
+                    period_state.streak.best,
+                );
+                println!("{:-<80}", "");
+                for challenge in &set.challenges {
+                    let mark = if challenge.completed_at.is_some() {
+                        "✓"
+                    } else {
+                        " "
+                    };
+                    let rarity = challenge
+                        .rarity_percent
+                        .map(|r| format!(", {:.1}% of players", r))
+                        .unwrap_or_default();
+                    println!(
+                        "  [{}] {} - {} ({} pts{})",
+                        mark, challenge.game_title, challenge.title, challenge.points, rarity
+                    );
+                }
+                println!();
+            }
+            if !any {
+                println!(
+                    "No challenges. Play a game with achievements first, or turn them on with"
+                );
+                println!("`kazeta-ra config set daily-challenges 3`.");
+            }
+        },
+    );
+
     Ok(())
 }
 
-fn notify_overlay_achievement(title: &str) -> Result<()> {
-    use std::io::Write;
-    use std::os::unix::net::UnixStream;
+fn challenge_count(settings: &RASettings, period: ChallengePeriod) -> usize {
+    match period {
+        ChallengePeriod::Daily => settings.daily_challenges,
+        ChallengePeriod::Weekly => settings.weekly_challenges,
+    }
+}
 
-    let socket_path = "/tmp/kazeta-overlay.sock";
-    if !std::path::Path::new(socket_path).exists() {
-        return Ok(()); // Overlay not running, skip
+/// Mark unlocked challenges, update streaks and draw sets for new periods
+fn refresh_challenges(cache: &RACache, state: &mut ChallengeState) -> Result<RefreshOutcome> {
+    let settings = RASettings::load()?;
+    let candidates = cache.unearned_achievements()?;
+    let now = chrono::Utc::now().timestamp();
+
+    let mut outcome = RefreshOutcome::default();
+    for period in ChallengePeriod::ALL {
+        let first_unlock = |id| cache.first_unlock(id).ok().flatten();
+        state.refresh(
+            period,
+            now,
+            challenge_count(&settings, period),
+            &candidates,
+            first_unlock,
+            &mut outcome,
+        );
     }
+    state.save()?;
+    Ok(outcome)
+}
+
+fn challenge_sets_json(state: &ChallengeState) -> Vec<serde_json::Value> {
+    ChallengePeriod::ALL
+        .iter()
+        .filter_map(|&period| {
+            let period_state = state.period(period);
+            let set = period_state.set.as_ref()?;
+            Some(serde_json::json!({
+                "period": period.name(),
+                "starts_at": set.starts_at,
+                "ends_at": set.ends_at,
+                "streak": period_state.streak.current,
+                "best_streak": period_state.streak.best,
+                "challenges": set.challenges.iter().map(|c| serde_json::json!({
+                    "achievement_id": c.achievement_id,
+                    "title": c.title,
+                    "description": c.description,
+                    "game_title": c.game_title,
+                    "points": c.points,
+                    "rarity_percent": c.rarity_percent,
+                    "completed": c.completed_at.is_some(),
+                })).collect::<Vec<_>>(),
+            }))
+        })
+        .collect()
+}
 
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    chars
+        .next()
+        .map(|c| c.to_uppercase().chain(chars).collect())
+        .unwrap_or_default()
+}
+
+fn cmd_fetch_images(hash: &str, locked: bool, icon_only: bool, out: OutputFormat) -> Result<()> {
+    let cache = RACache::new()?;
+    let images = ImageCache::new()?;
+
+    let game_images = cache.get_game_images(hash)?.ok_or_else(|| {
+        CodedError::new(
+            ErrorCode::NotFound,
+            "Game not in cache. Run 'kazeta-ra game-info' or 'game-start' first.",
+        )
+    })?;
+
+    let mut failed = 0;
+
+    let icon_path = match game_images.icon_url.filter(|i| !i.is_empty()) {
+        Some(icon) => match images.fetch(&ImageCache::icon_url(&icon)) {
+            Ok(path) => Some(path),
+            Err(e) => {
+                eprintln!("Failed to fetch game icon: {}", e);
+                failed += 1;
+                None
+            }
+        },
+        None => None,
+    };
+
+    let mut badge_paths = serde_json::Map::new();
+    if !icon_only {
+        for (id, badge_name) in game_images.badges {
+            let mut entry = serde_json::Map::new();
+            match images.fetch(&ImageCache::badge_url(&badge_name, false)) {
+                Ok(path) => {
+                    entry.insert("unlocked".to_string(), serde_json::json!(path));
+                }
+                Err(e) => {
+                    eprintln!("Failed to fetch badge {}: {}", badge_name, e);
+                    failed += 1;
+                }
+            }
+            if locked {
+                match images.fetch(&ImageCache::badge_url(&badge_name, true)) {
+                    Ok(path) => {
+                        entry.insert("locked".to_string(), serde_json::json!(path));
+                    }
+                    Err(e) => {
+                        eprintln!("Failed to fetch locked badge {}: {}", badge_name, e);
+                        failed += 1;
+                    }
+                }
+            }
+            badge_paths.insert(id.to_string(), serde_json::Value::Object(entry));
+        }
+    }
+
+    match out {
+        // Partial downloads still list what was fetched, with success false
+        OutputFormat::Json => println!(
+            "{}",
+            serde_json::json!({
+                "success": failed == 0,
+                "hash": hash,
+                "icon": icon_path,
+                "badges": badge_paths,
+                "failed": failed,
+                "cache_dir": images.dir(),
+            })
+        ),
+        OutputFormat::Plain => {
+            println!(
+                "✓ Fetched {} badges into {}",
+                badge_paths.len(),
+                images.dir().display()
+            );
+            if failed > 0 {
+                println!("  {} images failed to download", failed);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// Overlay notification helpers
+
+fn notify_overlay_game_start(
+    overlay: &OverlaySocket,
+    info: &GameInfoAndProgress,
+    title: &str,
+    earned: u32,
+    total: u32,
+    difficulty: Option<&DifficultyEstimate>,
+) -> Result<()> {
+    let icon = info.image_icon.as_str();
+    let message = serde_json::json!({
+        "type": "ra_game_start",
+        "game_title": title,
+        "game_id": info.id,
+        "total_achievements": total,
+        "earned_achievements": earned,
+        "console": info.console_name,
+        "icon_url": icon,
+        // Only there once `fetch-images` has downloaded it
+        "icon_path": ImageCache::new().ok()
+            .filter(|_| !icon.is_empty())
+            .and_then(|images| images.get(&ImageCache::icon_url(icon))),
+        "difficulty": difficulty.map(|d| d.score),
+        "expected_master_secs": difficulty.and_then(|d| d.expected_master_secs),
+    });
+
+    overlay.send(&message)
+}
+
+fn notify_overlay_achievement(overlay: &OverlaySocket, title: &str) -> Result<()> {
     let message = serde_json::json!({
         "type": "show_toast",
         "message": format!("🏆 Achievement Unlocked: {}", title),
@@ -570,44 +2600,156 @@ fn notify_overlay_achievement(title: &str) -> Result<()> {
         "duration_ms": 5000,
     });
 
-    if let Ok(mut stream) = UnixStream::connect(socket_path) {
-        let _ = writeln!(stream, "{}", message);
+    overlay.send(&message)
+}
+
+/// Tell the overlay which player is in which slot of a shared session
+fn notify_overlay_session_players(
+    overlay: &OverlaySocket,
+    players: &[SessionPlayer],
+    account: &str,
+) -> Result<()> {
+    let players: Vec<_> = players.iter().map(|p| player_json(p, account)).collect();
+    let message = serde_json::json!({
+        "type": "ra_session_players",
+        "players": players,
+    });
+
+    overlay.send(&message)
+}
+
+/// An unlock in a shared session, naming the player who earned it
+fn notify_overlay_player_achievement(
+    overlay: &OverlaySocket,
+    cache: &RACache,
+    id: u32,
+    title: &str,
+    player: &SessionPlayer,
+    account: &str,
+    hardcore: bool,
+) -> Result<()> {
+    let cached = cache
+        .get_achievement_game(id)
+        .ok()
+        .flatten()
+        .and_then(|(hash, _)| cache.get_achievements(&hash).ok())
+        .and_then(|achievements| achievements.into_iter().find(|a| a.id == id));
+    let message = serde_json::json!({
+        "type": "ra_achievement_unlocked",
+        "achievement_id": id,
+        "title": title,
+        "description": cached.as_ref().and_then(|a| a.description.clone()),
+        "points": cached.as_ref().map_or(0, |a| a.points),
+        "is_hardcore": hardcore,
+        "player": player_json(player, account),
+    });
+
+    overlay.send(&message)
+}
+
+fn player_json(player: &SessionPlayer, account: &str) -> serde_json::Value {
+    serde_json::json!({
+        "slot": player.slot,
+        "name": player.username,
+        "account": player.is_account(account),
+    })
+}
+
+fn notify_overlay_mastery(overlay: &OverlaySocket, mastery: &Mastery) -> Result<()> {
+    let message = serde_json::json!({
+        "type": "ra_mastery",
+        "game_title": mastery.game_title,
+        "hardcore": mastery.hardcore,
+        "total_achievements": mastery.total,
+        "points": mastery.points,
+        "completion_secs": mastery.completion_secs(),
+    });
+
+    overlay.send(&message)
+}
+
+/// Send the active challenge sets for the overlay's Challenges screen
+fn notify_overlay_challenges(overlay: &OverlaySocket, state: &ChallengeState) -> Result<()> {
+    let message = serde_json::json!({
+        "type": "ra_challenges",
+        "sets": challenge_sets_json(state),
+    });
+
+    overlay.send(&message)
+}
+
+/// Toast each challenge an unlock completed, and any set it finished
+fn notify_overlay_challenge_progress(
+    overlay: &OverlaySocket,
+    outcome: &RefreshOutcome,
+    state: &ChallengeState,
+) -> Result<()> {
+    let mut toasts = Vec::new();
+    for (period, challenge) in &outcome.completed {
+        toasts.push(format!(
+            "🎯 {} challenge complete: {}",
+            capitalize(period.name()),
+            challenge.title
+        ));
+    }
+    for period in &outcome.finished_sets {
+        let streak = state.period(*period).streak.current;
+        toasts.push(format!(
+            "🔥 All {} challenges done! Streak: {}",
+            period.name(),
+            streak
+        ));
     }
 
-    Ok(())
+    let messages: Vec<_> = toasts
+        .into_iter()
+        .map(|toast| {
+            serde_json::json!({
+                "type": "show_toast",
+                "message": toast,
+                "style": "success",
+                "duration_ms": 5000,
+            })
+        })
+        .collect();
+    overlay.send_all(&messages)
 }
 
-fn cmd_send_achievements_to_overlay(hash: Option<&str>, path: Option<&PathBuf>, console: Option<&str>) -> Result<()> {
-    use std::io::Write;
-    use std::os::unix::net::UnixStream;
+fn notify_overlay_progress(
+    overlay: &OverlaySocket,
+    achievement_id: u32,
+    current: u32,
+    target: u32,
+) -> Result<()> {
+    let message = serde_json::json!({
+        "type": "ra_progress_measure",
+        "achievement_id": achievement_id,
+        "current": current,
+        "target": target,
+    });
+
+    overlay.send(&message)
+}
 
+fn cmd_send_achievements_to_overlay(
+    hash: Option<&str>,
+    path: Option<&PathBuf>,
+    console: Option<&str>,
+    unofficial: bool,
+    overlay: &OverlaySocket,
+    out: OutputFormat,
+) -> Result<()> {
     let cred_manager = CredentialManager::new()?;
-    let credentials = cred_manager.load()?
-        .context("No credentials stored. Run 'kazeta-ra login' first.")?;
+    let credentials = load_credentials(&cred_manager)?;
 
     // Save path for cartridge lookup
     let path_for_cart = path.cloned();
 
     // Determine hash and console
-    let (rom_hash, console_id) = if let Some(h) = hash {
-        // Hash provided, console is required
-        let c = console.ok_or_else(|| anyhow::anyhow!("--console is required when using --hash"))?;
-        let console_id = ConsoleId::from_str(c)
-            .context(format!("Unknown console: {}", c))?;
-        (h.to_string(), console_id)
-    } else if let Some(p) = path {
-        // Path provided, auto-detect console if not specified
-        let detected_console = if let Some(c) = console {
-            ConsoleId::from_str(c)
-                .context(format!("Unknown console: {}", c))?
-        } else {
-            detect_console(p)?
-        };
-        let hash = hash_rom(p, detected_console)?;
-        (hash, detected_console)
-    } else {
-        bail!("Either --hash or --path is required");
-    };
+    let (rom_hash, console_id) = rom_hash_and_console(hash, path, console)?;
+
+    // Don't ask the server for a list nobody will see
+    overlay.check()?;
 
     let client = RAClient::new(credentials);
     let cache = RACache::new()?;
@@ -615,58 +2757,72 @@ fn cmd_send_achievements_to_overlay(hash: Option<&str>, path: Option<&PathBuf>,
     // Check for custom game name
     let game_name_mapping = GameNameMapping::load().ok();
     // Try to find cartridge path from ROM path if provided
-    let cart_path = path_for_cart.as_ref().and_then(|p| find_cartridge_for_rom(p).ok());
-    let custom_name = game_name_mapping.as_ref()
+    let cart_path = path_for_cart
+        .as_ref()
+        .and_then(|p| find_cartridge_for_rom(p).ok());
+    let custom_name = game_name_mapping
+        .as_ref()
         .and_then(|m| m.get_name(&rom_hash, cart_path.as_deref()));
 
     // Get game ID from hash
     let game_id = match client.get_game_id(&rom_hash, console_id)? {
         Some(id) => id,
         None => {
-            println!("{{\"success\": false, \"error\": \"Game not found\"}}");
+            report_failure(out, ErrorCode::NotFound, "Game not found");
             return Ok(());
         }
     };
 
     // Get full game info with achievements
-    let info = client.get_game_info_and_progress(game_id)?;
-    
+    let include_unofficial = unofficial || RASettings::load()?.show_unofficial;
+    let info = client.get_game_info_with_unofficial(game_id, include_unofficial)?;
+
     // Cache it
     cache.cache_game(&rom_hash, &info)?;
 
-    // Use custom name if available, otherwise use API title
-    let game_title = custom_name.unwrap_or_else(|| info.title.clone());
+    // Use custom name if available, otherwise the API title in the system
+    // language
+    let translations = Translations::load_or_default(info.id);
+    let game_title =
+        custom_name.unwrap_or_else(|| translations.game_title(&info.title).to_string());
+
+    // Unlock times come from the cache's history, which outlives the API's
+    // single latest date per mode
+    let history = cache.unlock_history(&rom_hash)?;
+    let first_unlock = |id: u32| {
+        history
+            .iter()
+            .find(|e| e.achievement_id == id)
+            .map(|e| e.earned_at)
+    };
 
     // Build achievement list for overlay
-    let achievements: Vec<serde_json::Value> = info.achievements
-        .as_ref()
-        .map(|achs| {
-            let mut list: Vec<_> = achs.values()
+    let achievements: Vec<serde_json::Value> =
+        info.achievements
+            .as_ref()
+            .map(|achs| {
+                let mut list: Vec<_> = achs.values()
                 .map(|a| serde_json::json!({
                     "id": a.id,
-                    "title": a.title,
-                    "description": a.description,
+                    "title": translations.achievement_title(a.id, &a.title),
+                    "description": translations.achievement_description(a.id, &a.description),
                     "points": a.points,
                     "earned": a.date_earned.is_some() || a.date_earned_hardcore.is_some(),
                     "earned_hardcore": a.date_earned_hardcore.is_some(),
+                    "unofficial": a.is_unofficial(),
+                    "rarity_percent": a.rarity_percent(info.num_players_casual),
+                    "earned_at": first_unlock(a.id),
                 }))
                 .collect();
-            // Sort by display order (using id as fallback)
-            list.sort_by(|a, b| {
-                let a_id = a["id"].as_u64().unwrap_or(0);
-                let b_id = b["id"].as_u64().unwrap_or(0);
-                a_id.cmp(&b_id)
-            });
-            list
-        })
-        .unwrap_or_default();
-
-    // Send to overlay
-    let socket_path = "/tmp/kazeta-overlay.sock";
-    if !std::path::Path::new(socket_path).exists() {
-        println!("{{\"success\": false, \"error\": \"Overlay not running\"}}");
-        return Ok(());
-    }
+                // Sort by display order (using id as fallback)
+                list.sort_by(|a, b| {
+                    let a_id = a["id"].as_u64().unwrap_or(0);
+                    let b_id = b["id"].as_u64().unwrap_or(0);
+                    a_id.cmp(&b_id)
+                });
+                list
+            })
+            .unwrap_or_default();
 
     let message = serde_json::json!({
         "type": "ra_achievement_list",
@@ -675,22 +2831,57 @@ fn cmd_send_achievements_to_overlay(hash: Option<&str>, path: Option<&PathBuf>,
         "achievements": achievements,
     });
 
-    if let Ok(mut stream) = UnixStream::connect(socket_path) {
-        let _ = writeln!(stream, "{}", message);
-        println!("{{\"success\": true, \"achievements_sent\": {}}}", achievements.len());
-    } else {
-        println!("{{\"success\": false, \"error\": \"Failed to connect to overlay\"}}");
-    }
+    // Every unlock, for the overlay's statistics screen
+    let unlocks: Vec<serde_json::Value> = history
+        .iter()
+        .map(|e| {
+            serde_json::json!({
+                "achievement_id": e.achievement_id,
+                "earned_at": e.earned_at,
+                "hardcore": e.hardcore,
+                "points": e.points,
+            })
+        })
+        .collect();
+    let history_message = serde_json::json!({
+        "type": "ra_unlock_history",
+        "game_hash": rom_hash,
+        "unlocks": unlocks,
+    });
 
+    overlay.send_all(&[message, history_message])?;
+    let dry_run = overlay.options().dry_run;
+    let fields = serde_json::json!({
+        "achievements_sent": achievements.len(),
+        "unlocks_sent": unlocks.len(),
+        "dry_run": dry_run,
+    });
+    emit(out, fields, || {
+        let verb = if dry_run { "Would send" } else { "✓ Sent" };
+        println!(
+            "{} {} achievements to the overlay",
+            verb,
+            achievements.len()
+        );
+    });
     Ok(())
 }
 
+/// Report a miss that isn't worth failing the command over
+fn report_failure(out: OutputFormat, code: ErrorCode, message: &str) {
+    match out {
+        OutputFormat::Json => println!("{}", output::failure(code, message)),
+        OutputFormat::Plain => println!("{}", message),
+    }
+}
+
 /// Try to find a cartridge (.kzi) file that contains the given ROM path
 /// This is a best-effort search - may not always find the cartridge
 fn find_cartridge_for_rom(rom_path: &PathBuf) -> Result<PathBuf> {
     // Check if ROM path is inside a cartridge directory structure
     // Cartridges are typically in ~/.local/share/kazeta-plus/cartridges/ or similar
-    let rom_path = rom_path.canonicalize()
+    let rom_path = rom_path
+        .canonicalize()
         .context("Failed to canonicalize ROM path")?;
 
     // Walk up the directory tree looking for a .kzi file
@@ -711,82 +2902,294 @@ fn find_cartridge_for_rom(rom_path: &PathBuf) -> Result<PathBuf> {
     bail!("Could not find cartridge file for ROM")
 }
 
-fn cmd_set_game_name(hash: Option<&str>, path: Option<&PathBuf>, console: Option<&str>, name: &str) -> Result<()> {
+fn cmd_set_game_name(
+    hash: Option<&str>,
+    path: Option<&PathBuf>,
+    console: Option<&str>,
+    name: &str,
+    out: OutputFormat,
+) -> Result<()> {
     // Determine hash and console
-    let (rom_hash, console_id) = if let Some(h) = hash {
-        // Hash provided, console is required
-        let c = console.ok_or_else(|| anyhow::anyhow!("--console is required when using --hash"))?;
-        let console_id = ConsoleId::from_str(c)
-            .context(format!("Unknown console: {}", c))?;
-        (h.to_string(), console_id)
-    } else if let Some(p) = path {
-        // Path provided, auto-detect console if not specified
-        let detected_console = if let Some(c) = console {
-            ConsoleId::from_str(c)
-                .context(format!("Unknown console: {}", c))?
-        } else {
-            detect_console(p)?
-        };
-        let hash = hash_rom(p, detected_console)?;
-        (hash, detected_console)
-    } else {
-        bail!("Either --hash or --path is required");
-    };
+    let (rom_hash, console_id) = rom_hash_and_console(hash, path, console)?;
 
     let mut mapping = GameNameMapping::load()?;
     let console_str = console_id.to_string();
-    mapping.set_name(rom_hash.clone(), name.to_string(), Some(console_str))?;
+    mapping.set_name(
+        rom_hash.clone(),
+        name.to_string(),
+        Some(console_str.clone()),
+    )?;
+
+    emit(
+        out,
+        serde_json::json!({ "hash": rom_hash, "name": name, "console": console_str }),
+        || {
+            println!("✓ Set custom name for hash {}: {}", rom_hash, name);
+        },
+    );
+    Ok(())
+}
+
+fn cmd_set_cart_name(
+    cart: &Path,
+    console: Option<&str>,
+    name: &str,
+    out: OutputFormat,
+) -> Result<()> {
+    if !cart.exists() {
+        return Err(CodedError::new(
+            ErrorCode::InvalidArgument,
+            format!("Cartridge not found: {}", cart.display()),
+        )
+        .into());
+    }
+
+    let mut mapping = GameNameMapping::load()?;
+    mapping.set_cart_name(cart, name.to_string(), console.map(str::to_string))?;
+
+    emit(
+        out,
+        serde_json::json!({ "cart": cart, "name": name, "console": console }),
+        || {
+            println!(
+                "✓ Set custom name for cartridge {}: {}",
+                cart.display(),
+                name
+            );
+        },
+    );
+    Ok(())
+}
 
-    println!("✓ Set custom name for hash {}: {}", rom_hash, name);
+fn cmd_remove_cart_name(cart: &Path, out: OutputFormat) -> Result<()> {
+    let mut mapping = GameNameMapping::load()?;
+    let removed = mapping.remove_cart_name(cart)?;
+
+    emit(
+        out,
+        serde_json::json!({ "cart": cart, "removed": removed }),
+        || {
+            if removed {
+                println!("✓ Removed custom name for cartridge {}", cart.display());
+            } else {
+                println!("Cartridge {} has no custom name", cart.display());
+            }
+        },
+    );
     Ok(())
 }
 
-fn cmd_remove_game_name(hash: Option<&str>, path: Option<&PathBuf>, console: Option<&str>) -> Result<()> {
+fn cmd_remove_game_name(
+    hash: Option<&str>,
+    path: Option<&PathBuf>,
+    console: Option<&str>,
+    out: OutputFormat,
+) -> Result<()> {
     // Determine hash and console
-    let (rom_hash, _console_id) = if let Some(h) = hash {
-        // Hash provided, console is required
-        let c = console.ok_or_else(|| anyhow::anyhow!("--console is required when using --hash"))?;
-        let console_id = ConsoleId::from_str(c)
-            .context(format!("Unknown console: {}", c))?;
-        (h.to_string(), console_id)
-    } else if let Some(p) = path {
-        // Path provided, auto-detect console if not specified
-        let detected_console = if let Some(c) = console {
-            ConsoleId::from_str(c)
-                .context(format!("Unknown console: {}", c))?
-        } else {
-            detect_console(p)?
-        };
-        let hash = hash_rom(p, detected_console)?;
-        (hash, detected_console)
-    } else {
-        bail!("Either --hash or --path is required");
-    };
+    let (rom_hash, _console_id) = rom_hash_and_console(hash, path, console)?;
 
     let mut mapping = GameNameMapping::load()?;
     mapping.remove_name(&rom_hash)?;
 
-    println!("✓ Removed custom name for hash {}", rom_hash);
+    emit(out, serde_json::json!({ "hash": rom_hash }), || {
+        println!("✓ Removed custom name for hash {}", rom_hash);
+    });
     Ok(())
 }
 
-fn cmd_list_game_names() -> Result<()> {
+fn cmd_export_game_names(csv: bool, file: Option<&Path>, out: OutputFormat) -> Result<()> {
     let mapping = GameNameMapping::load()?;
+    let content = if csv {
+        mapping.to_csv()
+    } else {
+        serde_json::to_string_pretty(&mapping).context("Failed to serialize game names")? + "\n"
+    };
 
-    if mapping.games.is_empty() {
-        println!("No custom game names configured.");
+    // Without a file the export itself is the output
+    let Some(file) = file else {
+        print!("{}", content);
         return Ok(());
+    };
+    std::fs::write(file, &content)
+        .with_context(|| format!("Failed to write {}", file.display()))?;
+
+    let count = mapping
+        .games
+        .values()
+        .filter(|e| e.source == NameSource::Manual)
+        .count()
+        + mapping.carts.len();
+    emit(
+        out,
+        serde_json::json!({ "file": file, "names": count }),
+        || {
+            println!("✓ Exported {} custom name(s) to {}", count, file.display());
+        },
+    );
+    Ok(())
+}
+
+fn cmd_import_game_names(file: &Path, dry_run: bool, out: OutputFormat) -> Result<()> {
+    let content = std::fs::read_to_string(file).map_err(|e| {
+        CodedError::new(
+            ErrorCode::InvalidArgument,
+            format!("Failed to read {}: {}", file.display(), e),
+        )
+    })?;
+
+    let (rows, errors) = GameNameMapping::parse_csv(&content);
+    if !errors.is_empty() {
+        // Plain output lists the rows like compiler errors; JSON carries them
+        // in the error message
+        let detail = match out {
+            OutputFormat::Plain => {
+                for error in &errors {
+                    eprintln!("{}:{}: {}", file.display(), error.line, error.error);
+                }
+                String::new()
+            }
+            OutputFormat::Json => {
+                let lines: Vec<String> = errors
+                    .iter()
+                    .map(|e| format!("line {}: {}", e.line, e.error))
+                    .collect();
+                format!(": {}", lines.join("; "))
+            }
+        };
+        return Err(CodedError::new(
+            ErrorCode::InvalidArgument,
+            format!(
+                "{} invalid row(s), nothing imported{}",
+                errors.len(),
+                detail
+            ),
+        )
+        .into());
     }
 
-    println!("Custom Game Names:");
-    println!("{:-<80}", "");
-    for (hash, entry) in &mapping.games {
-        if let Some(ref console) = entry.console {
-            println!("  {} [{}] -> {}", hash, console, entry.name);
-        } else {
-            println!("  {} -> {}", hash, entry.name);
+    let mut mapping = GameNameMapping::load()?;
+    let summary = mapping.import_rows(&rows);
+    if !dry_run {
+        mapping.save()?;
+    }
+
+    emit(
+        out,
+        serde_json::json!({
+            "dry_run": dry_run,
+            "added": summary.added,
+            "updated": summary.updated,
+            "unchanged": summary.unchanged,
+        }),
+        || {
+            let verb = if dry_run {
+                "Would import"
+            } else {
+                "✓ Imported"
+            };
+            println!(
+                "{} {} name(s): {} added, {} updated, {} unchanged",
+                verb,
+                rows.len(),
+                summary.added,
+                summary.updated,
+                summary.unchanged
+            );
+        },
+    );
+    Ok(())
+}
+
+fn cmd_config(action: ConfigAction, out: OutputFormat) -> Result<()> {
+    let mut settings = RASettings::load()?;
+
+    match action {
+        ConfigAction::Set { key, value } => {
+            settings.set(&key, &value).map_err(invalid_argument)?;
+            settings.save()?;
+            let value = settings.get(&key)?;
+            emit(
+                out,
+                serde_json::json!({ "key": key, "value": value }),
+                || {
+                    println!("✓ {} = {}", key, value);
+                },
+            );
+        }
+        ConfigAction::Get { key } => {
+            let value = settings.get(&key).map_err(invalid_argument)?;
+            emit(
+                out,
+                serde_json::json!({ "key": key, "value": value }),
+                || println!("{}", value),
+            );
+        }
+        ConfigAction::List => {
+            let mut values = Vec::new();
+            for key in SETTING_KEYS {
+                values.push((*key, settings.get(key)?));
+            }
+            let fields = serde_json::json!({
+                "settings": values.iter()
+            .map(|(key, value)| (key.to_string(), serde_json::json!(value)))
+            .collect::<serde_json::Map<_, _>>(),
+            });
+            emit(out, fields, || {
+                for (key, value) in &values {
+                    println!("{} = {}", key, value);
+                }
+            });
         }
     }
 
     Ok(())
 }
+
+/// An unknown setting or a value it can't take
+fn invalid_argument(err: anyhow::Error) -> anyhow::Error {
+    CodedError::new(ErrorCode::InvalidArgument, format!("{:#}", err)).into()
+}
+
+fn cmd_list_game_names(out: OutputFormat) -> Result<()> {
+    let mapping = GameNameMapping::load()?;
+
+    let fields = serde_json::json!({
+        "games": mapping.games.iter().map(|(hash, entry)| serde_json::json!({
+            "hash": hash,
+            "name": entry.name,
+            "console": entry.console,
+            "source": entry.source,
+        })).collect::<Vec<_>>(),
+        "carts": mapping.carts.iter().map(|(cart, entry)| serde_json::json!({
+            "cart": cart,
+            "name": entry.name,
+            "console": entry.console,
+        })).collect::<Vec<_>>(),
+    });
+    emit(out, fields, || {
+        if mapping.games.is_empty() && mapping.carts.is_empty() {
+            println!("No custom game names configured.");
+            return;
+        }
+
+        println!("Custom Game Names:");
+        println!("{:-<80}", "");
+        for (hash, entry) in &mapping.games {
+            let suffix = if entry.source == NameSource::Header {
+                " (from ROM header)"
+            } else {
+                ""
+            };
+            if let Some(ref console) = entry.console {
+                println!("  {} [{}] -> {}{}", hash, console, entry.name, suffix);
+            } else {
+                println!("  {} -> {}{}", hash, entry.name, suffix);
+            }
+        }
+        for (cart, entry) in &mapping.carts {
+            println!("  {} (cartridge) -> {}", cart, entry.name);
+        }
+    });
+
+    Ok(())
+}
@@ -0,0 +1,187 @@
+//! Machine-readable results for `kazeta-ra --output json`
+//!
+//! In JSON mode every command prints exactly one object on stdout. Successes
+//! carry `"success": true` next to the command's fields; failures look like
+//! `{"success": false, "code": "not_logged_in", "error": "<message>"}` and
+//! exit with status 1, except that an overlay notification that didn't
+//! arrive exits with 3 (`overlay_not_running`) or 4 (`send_failed`) in either
+//! output mode. `error` stays a plain string so wrappers written against the
+//! older output keep working.
+
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::fmt;
+
+/// Stable error codes for scripts to branch on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    /// No credentials stored
+    NotLoggedIn,
+    /// A bad flag value, e.g. an unknown console
+    InvalidArgument,
+    /// The game or setting doesn't exist
+    NotFound,
+    /// The RetroAchievements API couldn't be reached or refused the request
+    Network,
+    /// The local achievement cache failed
+    Cache,
+    /// Reading or writing a file failed
+    Io,
+    /// Nothing is listening on the overlay socket
+    OverlayNotRunning,
+    /// The overlay is there but a notification couldn't be delivered
+    SendFailed,
+    /// Anything else
+    Internal,
+}
+
+impl ErrorCode {
+    /// Process exit status for a command that failed with this code
+    pub fn exit_status(&self) -> i32 {
+        match self {
+            Self::OverlayNotRunning => 3,
+            Self::SendFailed => 4,
+            _ => 1,
+        }
+    }
+}
+
+/// An error that knows its code. Use it where the cause is known up front;
+/// anything else is classified from the error chain by `error_code`.
+#[derive(Debug)]
+pub struct CodedError {
+    pub code: ErrorCode,
+    pub message: String,
+}
+
+impl CodedError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+
+    pub fn not_logged_in() -> Self {
+        Self::new(
+            ErrorCode::NotLoggedIn,
+            "No credentials stored. Run 'kazeta-ra login' first.",
+        )
+    }
+}
+
+impl fmt::Display for CodedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for CodedError {}
+
+/// The code for an error: the outermost `CodedError` in the chain, else a
+/// guess from the underlying error type
+pub fn error_code(err: &anyhow::Error) -> ErrorCode {
+    if let Some(coded) = err.chain().find_map(|e| e.downcast_ref::<CodedError>()) {
+        return coded.code;
+    }
+    for cause in err.chain() {
+        if cause.is::<reqwest::Error>() {
+            return ErrorCode::Network;
+        }
+        if cause.is::<rusqlite::Error>() {
+            return ErrorCode::Cache;
+        }
+        if cause.is::<std::io::Error>() {
+            return ErrorCode::Io;
+        }
+    }
+    ErrorCode::Internal
+}
+
+/// `{"success": false, "code": ..., "error": ...}` for a failed command
+pub fn error_json(err: &anyhow::Error) -> Value {
+    failure(error_code(err), &format!("{:#}", err))
+}
+
+/// A failure result, for commands that report a miss without failing
+pub fn failure(code: ErrorCode, message: &str) -> Value {
+    json!({
+        "success": false,
+        "code": code,
+        "error": message,
+    })
+}
+
+/// A successful result: the command's fields plus `"success": true`
+pub fn success(fields: Value) -> Value {
+    let mut object = match fields {
+        Value::Object(map) => map,
+        Value::Null => serde_json::Map::new(),
+        other => {
+            let mut map = serde_json::Map::new();
+            map.insert("result".to_string(), other);
+            map
+        }
+    };
+    object.insert("success".to_string(), Value::Bool(true));
+    Value::Object(object)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    #[test]
+    fn test_error_code_from_chain() {
+        let coded: anyhow::Error = CodedError::not_logged_in().into();
+        assert_eq!(error_code(&coded), ErrorCode::NotLoggedIn);
+
+        // Context added on top doesn't hide the code
+        let wrapped = Err::<(), _>(CodedError::new(
+            ErrorCode::InvalidArgument,
+            "Unknown console: n64",
+        ))
+        .context("Failed to hash ROM")
+        .unwrap_err();
+        assert_eq!(error_code(&wrapped), ErrorCode::InvalidArgument);
+
+        let io = Err::<(), _>(std::io::Error::from(std::io::ErrorKind::NotFound))
+            .context("Failed to read ROM file")
+            .unwrap_err();
+        assert_eq!(error_code(&io), ErrorCode::Io);
+
+        assert_eq!(
+            error_code(&anyhow::anyhow!("something else")),
+            ErrorCode::Internal
+        );
+    }
+
+    #[test]
+    fn test_result_shapes() {
+        let err =
+            anyhow::Error::from(CodedError::not_logged_in()).context("Failed to load profile");
+        let value = error_json(&err);
+        assert_eq!(value["success"], false);
+        assert_eq!(value["code"], "not_logged_in");
+        assert_eq!(
+            value["error"],
+            "Failed to load profile: No credentials stored. Run 'kazeta-ra login' first."
+        );
+        assert_eq!(
+            serde_json::to_value(ErrorCode::InvalidArgument).unwrap(),
+            "invalid_argument"
+        );
+        assert_eq!(
+            serde_json::to_value(ErrorCode::OverlayNotRunning).unwrap(),
+            "overlay_not_running"
+        );
+        assert_eq!(ErrorCode::SendFailed.exit_status(), 4);
+        assert_eq!(ErrorCode::Network.exit_status(), 1);
+
+        let value = success(json!({"hash": "abc"}));
+        assert_eq!(value, json!({"success": true, "hash": "abc"}));
+        assert_eq!(success(Value::Null), json!({"success": true}));
+    }
+}
@@ -0,0 +1,210 @@
+//! Delivery of notifications to the overlay daemon
+//!
+//! Messages go to the overlay's socket as JSON lines. With `--dry-run` they're
+//! printed to stderr exactly as they'd be written instead, and `--trace` logs
+//! how long connecting and each write took. A socket that's missing or that
+//! nobody listens on is `overlay_not_running`; any other failure to connect
+//! or write is `send_failed`, so callers can tell "start the overlay" apart
+//! from "something broke".
+
+use crate::output::{CodedError, ErrorCode};
+use anyhow::Result;
+use serde_json::Value;
+use std::io::{ErrorKind, Write};
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+pub const SOCKET_PATH: &str = "/tmp/kazeta-overlay.sock";
+
+/// How notifications are delivered, from a command's `--dry-run` and `--trace`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SendOptions {
+    /// Print the messages instead of writing them
+    pub dry_run: bool,
+    /// Log connect and write timing to stderr
+    pub trace: bool,
+}
+
+pub struct OverlaySocket {
+    path: PathBuf,
+    options: SendOptions,
+}
+
+impl OverlaySocket {
+    pub fn new(options: SendOptions) -> Self {
+        Self::with_path(SOCKET_PATH, options)
+    }
+
+    pub fn with_path(path: impl Into<PathBuf>, options: SendOptions) -> Self {
+        Self {
+            path: path.into(),
+            options,
+        }
+    }
+
+    pub fn options(&self) -> SendOptions {
+        self.options
+    }
+
+    /// Fail early when the overlay isn't there, e.g. before fetching what
+    /// to send it. Dry runs always pass.
+    pub fn check(&self) -> Result<()> {
+        if self.options.dry_run || self.path.exists() {
+            Ok(())
+        } else {
+            Err(not_running(&self.path).into())
+        }
+    }
+
+    /// Send one message
+    pub fn send(&self, message: &Value) -> Result<()> {
+        self.send_all(std::slice::from_ref(message))
+    }
+
+    /// Send messages in order over a single connection
+    pub fn send_all(&self, messages: &[Value]) -> Result<()> {
+        if self.options.dry_run {
+            for message in messages {
+                eprintln!("{}", message);
+            }
+            return Ok(());
+        }
+
+        let started = Instant::now();
+        let mut stream = self.connect()?;
+        self.trace(format_args!(
+            "connected to {} in {:.2?}",
+            self.path.display(),
+            started.elapsed()
+        ));
+
+        for message in messages {
+            let line = format!("{}\n", message);
+            let started = Instant::now();
+            stream.write_all(line.as_bytes()).map_err(|e| {
+                CodedError::new(
+                    ErrorCode::SendFailed,
+                    format!("Failed to write to the overlay: {}", e),
+                )
+            })?;
+            let kind = message.get("type").and_then(Value::as_str).unwrap_or("?");
+            self.trace(format_args!(
+                "wrote {} ({} bytes) in {:.2?}",
+                kind,
+                line.len(),
+                started.elapsed()
+            ));
+        }
+        Ok(())
+    }
+
+    fn connect(&self) -> Result<UnixStream> {
+        if !self.path.exists() {
+            self.trace(format_args!("{} doesn't exist", self.path.display()));
+            return Err(not_running(&self.path).into());
+        }
+        UnixStream::connect(&self.path).map_err(|e| {
+            self.trace(format_args!(
+                "connecting to {} failed: {}",
+                self.path.display(),
+                e
+            ));
+            match e.kind() {
+                // A socket file left behind by an overlay that's gone
+                ErrorKind::ConnectionRefused | ErrorKind::NotFound => {
+                    not_running(&self.path).into()
+                }
+                _ => CodedError::new(
+                    ErrorCode::SendFailed,
+                    format!("Failed to connect to the overlay: {}", e),
+                )
+                .into(),
+            }
+        })
+    }
+
+    fn trace(&self, message: std::fmt::Arguments) {
+        if self.options.trace {
+            eprintln!("[trace] {}", message);
+        }
+    }
+}
+
+fn not_running(path: &Path) -> CodedError {
+    CodedError::new(
+        ErrorCode::OverlayNotRunning,
+        format!("Overlay not running ({})", path.display()),
+    )
+}
+
+/// Whether an error only means the overlay isn't there to notify
+pub fn is_not_running(err: &anyhow::Error) -> bool {
+    crate::output::error_code(err) == ErrorCode::OverlayNotRunning
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::io::{BufRead, BufReader};
+    use std::os::unix::net::UnixListener;
+
+    fn socket_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "kazeta-ra-overlay-{}-{}.sock",
+            name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn test_not_running() {
+        let path = socket_path("missing");
+        let socket = OverlaySocket::with_path(&path, SendOptions::default());
+        assert!(is_not_running(&socket.check().unwrap_err()));
+        let err = socket.send(&json!({"type": "hide_overlay"})).unwrap_err();
+        assert!(is_not_running(&err));
+        assert_eq!(ErrorCode::OverlayNotRunning.exit_status(), 3);
+
+        // A stale socket file with nobody listening
+        drop(UnixListener::bind(&path).unwrap());
+        let err = socket.send(&json!({"type": "hide_overlay"})).unwrap_err();
+        assert!(is_not_running(&err));
+        let _ = std::fs::remove_file(&path);
+
+        // Dry runs don't need the overlay
+        let dry_run = OverlaySocket::with_path(
+            &path,
+            SendOptions {
+                dry_run: true,
+                trace: false,
+            },
+        );
+        assert!(dry_run.check().is_ok());
+        assert!(dry_run.send(&json!({"type": "hide_overlay"})).is_ok());
+    }
+
+    #[test]
+    fn test_send_all_writes_lines() {
+        let path = socket_path("lines");
+        let listener = UnixListener::bind(&path).unwrap();
+        let socket = OverlaySocket::with_path(
+            &path,
+            SendOptions {
+                dry_run: false,
+                trace: true,
+            },
+        );
+        socket
+            .send_all(&[json!({"type": "a"}), json!({"type": "b"})])
+            .unwrap();
+
+        let (stream, _) = listener.accept().unwrap();
+        let lines: Vec<String> = BufReader::new(stream).lines().map(|l| l.unwrap()).collect();
+        assert_eq!(lines, vec![r#"{"type":"a"}"#, r#"{"type":"b"}"#]);
+        let _ = std::fs::remove_file(&path);
+    }
+}
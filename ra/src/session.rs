@@ -0,0 +1,244 @@
+//! The running game's RetroAchievements session
+//!
+//! `kazeta-ra session start` writes `SESSION_FILE` for the game a wrapper
+//! launched, `session ping` keeps RA's "now playing" status alive and
+//! records the heartbeat, and `session stop` removes the file. Anything else
+//! that wants to know what's being played reads the file; the overlay uses
+//! it to pick the game back up after it crashed and restarted.
+//!
+//! A shared multiplayer session (e.g. a GBA link session) can map its player
+//! slots to RetroAchievements users, so unlocks are attributed to whoever
+//! earned them. Only the logged-in account's unlocks reach RA and the local
+//! progress cache; the others are tracked by the overlay for the session.
+
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+/// Lives under /run so a reboot never leaves a session behind
+pub const SESSION_FILE: &str = "/run/kazeta/ra-session.json";
+
+/// A session without a heartbeat for this long was left by a wrapper that
+/// died without stopping it
+pub const STALE_AFTER_SECS: i64 = 10 * 60;
+
+/// Most player slots a session maps, as many as a GBA link cable takes
+pub const MAX_PLAYERS: u8 = 4;
+
+/// The RetroAchievements user playing in one slot of a shared session
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SessionPlayer {
+    /// Player number, from 1
+    pub slot: u8,
+    pub username: String,
+}
+
+impl SessionPlayer {
+    /// Parse `SLOT=USERNAME`, as given to `session start --player`
+    pub fn parse(arg: &str) -> Result<Self> {
+        let Some((slot, username)) = arg.split_once('=') else {
+            bail!("Expected SLOT=USERNAME, got '{}'", arg);
+        };
+        let slot: u8 = slot
+            .trim()
+            .parse()
+            .with_context(|| format!("Invalid player slot '{}'", slot))?;
+        if slot == 0 || slot > MAX_PLAYERS {
+            bail!("Player slot must be between 1 and {}", MAX_PLAYERS);
+        }
+        let username = username.trim();
+        if username.is_empty() {
+            bail!("No username given for player {}", slot);
+        }
+        Ok(Self {
+            slot,
+            username: username.to_string(),
+        })
+    }
+
+    /// Whether this player is the account kazeta-ra is logged in with
+    pub fn is_account(&self, account: &str) -> bool {
+        self.username.eq_ignore_ascii_case(account)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GameSession {
+    pub game_id: u32,
+    pub hash: String,
+    #[serde(default)]
+    pub title: String,
+    /// Unix timestamp
+    pub started_at: i64,
+    pub hardcore: bool,
+    /// Unix timestamp of the last successful ping
+    #[serde(default)]
+    pub last_ping: Option<i64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rich_presence: Option<String>,
+    /// Player slots of a shared session, by slot; empty for single player
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub players: Vec<SessionPlayer>,
+}
+
+impl GameSession {
+    pub fn new(game_id: u32, hash: String, title: String, hardcore: bool, now: i64) -> Self {
+        Self {
+            game_id,
+            hash,
+            title,
+            started_at: now,
+            hardcore,
+            last_ping: None,
+            rich_presence: None,
+            players: Vec::new(),
+        }
+    }
+
+    /// Map player slots to users. The logged-in account takes slot 1 if
+    /// it isn't given a slot and slot 1 is free.
+    pub fn set_players(&mut self, mut players: Vec<SessionPlayer>, account: &str) -> Result<()> {
+        players.sort_by_key(|p| p.slot);
+        if let Some(pair) = players.windows(2).find(|pair| pair[0].slot == pair[1].slot) {
+            bail!("Player {} is given twice", pair[0].slot);
+        }
+        let has_account = players.iter().any(|p| p.is_account(account));
+        if !players.is_empty() && !has_account && players[0].slot != 1 {
+            players.insert(
+                0,
+                SessionPlayer {
+                    slot: 1,
+                    username: account.to_string(),
+                },
+            );
+        }
+        self.players = players;
+        Ok(())
+    }
+
+    pub fn player(&self, slot: u8) -> Option<&SessionPlayer> {
+        self.players.iter().find(|p| p.slot == slot)
+    }
+
+    /// Whether the wrapper that owns the session has stopped pinging
+    pub fn is_stale(&self, now: i64) -> bool {
+        now - self.last_ping.unwrap_or(self.started_at) > STALE_AFTER_SECS
+    }
+
+    /// The current session, if a game is running
+    pub fn load() -> Result<Option<Self>> {
+        Self::load_from(Path::new(SESSION_FILE))
+    }
+
+    pub fn load_from(path: &Path) -> Result<Option<Self>> {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(e).context("Failed to read session file"),
+        };
+        serde_json::from_str(&contents)
+            .map(Some)
+            .context("Failed to parse session file")
+    }
+
+    pub fn save(&self) -> Result<()> {
+        self.save_to(Path::new(SESSION_FILE))
+    }
+
+    /// Written under a temporary name first so readers never see half a file
+    pub fn save_to(&self, path: &Path) -> Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).context("Failed to create session directory")?;
+        }
+        let json = serde_json::to_string_pretty(self).context("Failed to serialize session")?;
+        let partial = path.with_extension("json.partial");
+        fs::write(&partial, json).context("Failed to write session file")?;
+        fs::rename(&partial, path).context("Failed to write session file")
+    }
+
+    /// End the session. Returns the session that was running, if any.
+    pub fn clear() -> Result<Option<Self>> {
+        Self::clear_at(Path::new(SESSION_FILE))
+    }
+
+    pub fn clear_at(path: &Path) -> Result<Option<Self>> {
+        let session = Self::load_from(path).unwrap_or(None);
+        match fs::remove_file(path) {
+            Ok(()) => Ok(session),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e).context("Failed to remove session file"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_file_round_trip() {
+        let dir = std::env::temp_dir().join(format!("kazeta-ra-session-{}", std::process::id()));
+        let path = dir.join("ra-session.json");
+        let _ = fs::remove_dir_all(&dir);
+
+        assert_eq!(GameSession::load_from(&path).unwrap(), None);
+
+        let mut session =
+            GameSession::new(1234, "abc".to_string(), "Celeste".to_string(), true, 1000);
+        session.save_to(&path).unwrap();
+        session.last_ping = Some(1100);
+        session.save_to(&path).unwrap();
+        assert_eq!(
+            GameSession::load_from(&path).unwrap(),
+            Some(session.clone())
+        );
+
+        assert_eq!(GameSession::clear_at(&path).unwrap(), Some(session));
+        assert!(!path.exists());
+        assert_eq!(GameSession::clear_at(&path).unwrap(), None);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_stale_without_heartbeat() {
+        let mut session = GameSession::new(1, "abc".to_string(), String::new(), false, 0);
+        assert!(!session.is_stale(STALE_AFTER_SECS));
+        assert!(session.is_stale(STALE_AFTER_SECS + 1));
+
+        session.last_ping = Some(STALE_AFTER_SECS);
+        assert!(!session.is_stale(STALE_AFTER_SECS + 1));
+    }
+
+    #[test]
+    fn test_session_players() {
+        assert_eq!(
+            SessionPlayer::parse("2=bob").unwrap(),
+            SessionPlayer {
+                slot: 2,
+                username: "bob".to_string()
+            }
+        );
+        assert!(SessionPlayer::parse("bob").is_err());
+        assert!(SessionPlayer::parse("0=bob").is_err());
+        assert!(SessionPlayer::parse("5=bob").is_err());
+        assert!(SessionPlayer::parse("2=").is_err());
+
+        let mut session = GameSession::new(1, "abc".to_string(), String::new(), false, 0);
+        let bob = SessionPlayer::parse("2=bob").unwrap();
+        session.set_players(vec![bob.clone()], "alice").unwrap();
+        assert_eq!(session.players.len(), 2);
+        assert!(session.player(1).unwrap().is_account("Alice"));
+        assert_eq!(session.player(2), Some(&bob));
+
+        // The account playing as player 2 leaves slot 1 alone
+        session
+            .set_players(vec![SessionPlayer::parse("2=alice").unwrap()], "alice")
+            .unwrap();
+        assert_eq!(session.players.len(), 1);
+
+        assert!(session
+            .set_players(vec![bob.clone(), bob], "alice")
+            .is_err());
+    }
+}
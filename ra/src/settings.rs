@@ -0,0 +1,217 @@
+use crate::endpoints::{self, DEFAULT_ENDPOINT};
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+/// Keys accepted by `kazeta-ra config`
+pub const SETTING_KEYS: &[&str] = &[
+    "show-unofficial",
+    "daily-challenges",
+    "weekly-challenges",
+    "http-cache-max-age",
+    "endpoints",
+];
+
+/// User preferences for the RetroAchievements integration
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RASettings {
+    /// Include unofficial (flag 5) achievements in game info and the overlay list
+    #[serde(default)]
+    pub show_unofficial: bool,
+    /// Achievements in each daily challenge set (0 turns them off)
+    #[serde(default = "default_daily_challenges")]
+    pub daily_challenges: usize,
+    /// Achievements in each weekly challenge set (0 turns them off)
+    #[serde(default = "default_weekly_challenges")]
+    pub weekly_challenges: usize,
+    /// Seconds cached game data is used without asking RA (0 always asks)
+    #[serde(default = "default_http_cache_max_age")]
+    pub http_cache_max_age: u64,
+    /// RA sites to use, in failover order
+    #[serde(default = "default_endpoints")]
+    pub endpoints: Vec<String>,
+}
+
+fn default_daily_challenges() -> usize {
+    3
+}
+
+fn default_weekly_challenges() -> usize {
+    5
+}
+
+fn default_http_cache_max_age() -> u64 {
+    24 * 60 * 60
+}
+
+fn default_endpoints() -> Vec<String> {
+    vec![DEFAULT_ENDPOINT.to_string()]
+}
+
+impl Default for RASettings {
+    fn default() -> Self {
+        Self {
+            show_unofficial: false,
+            daily_challenges: default_daily_challenges(),
+            weekly_challenges: default_weekly_challenges(),
+            http_cache_max_age: default_http_cache_max_age(),
+            endpoints: default_endpoints(),
+        }
+    }
+}
+
+impl RASettings {
+    /// Load settings from disk, or the defaults if none are stored
+    pub fn load() -> Result<Self> {
+        let path = Self::get_config_path()?;
+
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(&path).context("Failed to read RA settings file")?;
+
+        let settings: RASettings =
+            serde_json::from_str(&content).context("Failed to parse RA settings JSON")?;
+
+        Ok(settings)
+    }
+
+    /// Save settings to disk
+    pub fn save(&self) -> Result<()> {
+        let path = Self::get_config_path()?;
+
+        let json = serde_json::to_string_pretty(self).context("Failed to serialize RA settings")?;
+
+        fs::write(&path, json).context("Failed to write RA settings file")?;
+
+        Ok(())
+    }
+
+    /// Get a setting by its CLI key
+    pub fn get(&self, key: &str) -> Result<String> {
+        match key {
+            "show-unofficial" => Ok(self.show_unofficial.to_string()),
+            "daily-challenges" => Ok(self.daily_challenges.to_string()),
+            "weekly-challenges" => Ok(self.weekly_challenges.to_string()),
+            "http-cache-max-age" => Ok(self.http_cache_max_age.to_string()),
+            "endpoints" => Ok(self.endpoints.join(",")),
+            _ => bail!(
+                "Unknown setting: {}. Available: {}",
+                key,
+                SETTING_KEYS.join(", ")
+            ),
+        }
+    }
+
+    /// Set a setting by its CLI key
+    pub fn set(&mut self, key: &str, value: &str) -> Result<()> {
+        match key {
+            "show-unofficial" => self.show_unofficial = parse_bool(value)?,
+            "daily-challenges" => self.daily_challenges = parse_count(value)?,
+            "weekly-challenges" => self.weekly_challenges = parse_count(value)?,
+            "http-cache-max-age" => self.http_cache_max_age = parse_seconds(value)?,
+            "endpoints" => self.endpoints = parse_endpoints(value)?,
+            _ => bail!(
+                "Unknown setting: {}. Available: {}",
+                key,
+                SETTING_KEYS.join(", ")
+            ),
+        }
+        Ok(())
+    }
+
+    fn get_config_path() -> Result<PathBuf> {
+        let data_dir = dirs::home_dir()
+            .context("Could not find home directory")?
+            .join(".local/share/kazeta-plus");
+
+        fs::create_dir_all(&data_dir).context("Failed to create kazeta data directory")?;
+
+        Ok(data_dir.join("ra_settings.json"))
+    }
+}
+
+fn parse_bool(value: &str) -> Result<bool> {
+    match value.to_lowercase().as_str() {
+        "true" | "on" | "yes" | "1" => Ok(true),
+        "false" | "off" | "no" | "0" => Ok(false),
+        _ => bail!("Expected true or false, got '{}'", value),
+    }
+}
+
+fn parse_count(value: &str) -> Result<usize> {
+    match value.parse::<usize>() {
+        Ok(count) if count <= 20 => Ok(count),
+        _ => bail!("Expected a number from 0 to 20, got '{}'", value),
+    }
+}
+
+fn parse_seconds(value: &str) -> Result<u64> {
+    match value.parse::<u64>() {
+        Ok(seconds) => Ok(seconds),
+        Err(_) => bail!("Expected a number of seconds, got '{}'", value),
+    }
+}
+
+/// Comma-separated endpoint URLs, or `default`
+fn parse_endpoints(value: &str) -> Result<Vec<String>> {
+    if value.trim().eq_ignore_ascii_case("default") {
+        return Ok(default_endpoints());
+    }
+    let mut list = Vec::new();
+    for base in value.split(',').filter(|base| !base.trim().is_empty()) {
+        let base = endpoints::normalize(base)?;
+        if !list.contains(&base) {
+            list.push(base);
+        }
+    }
+    if list.is_empty() {
+        bail!(
+            "Expected comma-separated endpoint URLs or 'default', got '{}'",
+            value
+        );
+    }
+    Ok(list)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_and_get() {
+        let mut settings = RASettings::default();
+        assert_eq!(settings.get("show-unofficial").unwrap(), "false");
+
+        settings.set("show-unofficial", "on").unwrap();
+        assert!(settings.show_unofficial);
+        assert_eq!(settings.get("show-unofficial").unwrap(), "true");
+
+        assert!(settings.set("show-unofficial", "maybe").is_err());
+        assert!(settings.set("unknown", "true").is_err());
+
+        settings.set("daily-challenges", "0").unwrap();
+        assert_eq!(settings.get("daily-challenges").unwrap(), "0");
+        assert!(settings.set("weekly-challenges", "many").is_err());
+        assert!(settings.get("unknown").is_err());
+
+        settings.set("http-cache-max-age", "3600").unwrap();
+        assert_eq!(settings.http_cache_max_age, 3600);
+        assert!(settings.set("http-cache-max-age", "-1").is_err());
+
+        assert_eq!(settings.get("endpoints").unwrap(), DEFAULT_ENDPOINT);
+        settings
+            .set(
+                "endpoints",
+                "https://ra.example/, https://retroachievements.org",
+            )
+            .unwrap();
+        assert_eq!(settings.endpoints, ["https://ra.example", DEFAULT_ENDPOINT]);
+        assert!(settings.set("endpoints", " , ").is_err());
+        assert!(settings.set("endpoints", "ra.example").is_err());
+        settings.set("endpoints", "default").unwrap();
+        assert_eq!(settings.endpoints, [DEFAULT_ENDPOINT]);
+    }
+}
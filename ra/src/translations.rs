@@ -0,0 +1,222 @@
+//! Community translations of achievement text
+//!
+//! RetroAchievements serves titles and descriptions in English only, so
+//! translations come from per-game override files at
+//! `~/.local/share/kazeta-plus/ra_translations/<game_id>.toml`, one table per
+//! language:
+//!
+//! ```toml
+//! [de]
+//! title = "Sonic the Hedgehog"
+//!
+//! [de.achievements.1234]
+//! title = "Ringsammler"
+//! description = "Sammle 100 Ringe"
+//! ```
+//!
+//! The language is the system's (`LC_ALL`, `LC_MESSAGES`, then `LANG`). A
+//! table for the full locale (`pt_BR`) wins over one for the language
+//! (`pt`). Anything not translated keeps its English text.
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// One language's overrides for a game
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct LanguageTable {
+    /// Translated game title
+    #[serde(default)]
+    pub title: Option<String>,
+    /// Translated achievement text, by achievement ID
+    #[serde(default)]
+    pub achievements: HashMap<String, AchievementText>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct AchievementText {
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+/// The overrides for one game in the system language
+#[derive(Debug, Clone, Default)]
+pub struct Translations {
+    /// Language of the table in use, e.g. "de"
+    pub language: Option<String>,
+    table: LanguageTable,
+}
+
+impl Translations {
+    /// Translations of a game for the system language. Missing files and
+    /// English systems give no overrides.
+    pub fn load(game_id: u32) -> Result<Self> {
+        let Some(locale) = system_locale() else {
+            return Ok(Self::default());
+        };
+        Self::load_from(&Self::get_file_path(game_id)?, &locale)
+    }
+
+    /// Like `load`, for display paths where a broken file shouldn't stop
+    /// anything: the error goes to stderr and the English text is used
+    pub fn load_or_default(game_id: u32) -> Self {
+        Self::load(game_id).unwrap_or_else(|e| {
+            eprintln!(
+                "Warning: ignoring translations for game {}: {:#}",
+                game_id, e
+            );
+            Self::default()
+        })
+    }
+
+    fn load_from(path: &Path, locale: &str) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let mut tables: HashMap<String, LanguageTable> = toml::from_str(&content)
+            .with_context(|| format!("Failed to parse {}", path.display()))?;
+
+        let language = locale.split('_').next().unwrap_or(locale);
+        for key in [locale, language] {
+            if let Some(table) = tables.remove(key) {
+                return Ok(Self {
+                    language: Some(key.to_string()),
+                    table,
+                });
+            }
+        }
+        Ok(Self::default())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.language.is_none()
+    }
+
+    /// The game title, translated if there is one
+    pub fn game_title<'a>(&'a self, english: &'a str) -> &'a str {
+        self.table.title.as_deref().unwrap_or(english)
+    }
+
+    /// An achievement's title, translated if there is one
+    pub fn achievement_title<'a>(&'a self, id: u32, english: &'a str) -> &'a str {
+        self.achievement(id)
+            .and_then(|a| a.title.as_deref())
+            .unwrap_or(english)
+    }
+
+    /// An achievement's description, translated if there is one
+    pub fn achievement_description<'a>(&'a self, id: u32, english: &'a str) -> &'a str {
+        self.achievement(id)
+            .and_then(|a| a.description.as_deref())
+            .unwrap_or(english)
+    }
+
+    fn achievement(&self, id: u32) -> Option<&AchievementText> {
+        self.table.achievements.get(&id.to_string())
+    }
+
+    /// Directory holding the per-game translation files
+    pub fn get_dir() -> Result<PathBuf> {
+        Ok(dirs::home_dir()
+            .context("Could not find home directory")?
+            .join(".local/share/kazeta-plus/ra_translations"))
+    }
+
+    fn get_file_path(game_id: u32) -> Result<PathBuf> {
+        Ok(Self::get_dir()?.join(format!("{}.toml", game_id)))
+    }
+}
+
+/// The system locale without encoding or modifier, e.g. "pt_BR". None for
+/// English and the C/POSIX locale, which need no translation.
+pub fn system_locale() -> Option<String> {
+    ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|value| !value.is_empty())
+        .and_then(|value| normalize_locale(&value))
+}
+
+fn normalize_locale(value: &str) -> Option<String> {
+    let locale = value.split(['.', '@']).next().unwrap_or(value);
+    if locale.is_empty()
+        || locale == "C"
+        || locale == "POSIX"
+        || locale == "en"
+        || locale.starts_with("en_")
+    {
+        None
+    } else {
+        Some(locale.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FILE: &str = r#"
+        [pt]
+        title = "Jogo"
+
+        [pt.achievements.10]
+        title = "Coletor"
+
+        [pt_BR.achievements.10]
+        title = "Colecionador"
+        description = "Colete 100 anéis"
+    "#;
+
+    #[test]
+    fn test_normalize_locale() {
+        assert_eq!(normalize_locale("de_DE.UTF-8").as_deref(), Some("de_DE"));
+        assert_eq!(normalize_locale("sr_RS@latin").as_deref(), Some("sr_RS"));
+        assert_eq!(normalize_locale("en_US.UTF-8"), None);
+        assert_eq!(normalize_locale("C.UTF-8"), None);
+        assert_eq!(normalize_locale("POSIX"), None);
+    }
+
+    #[test]
+    fn test_locale_then_language_then_english() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("1.toml");
+        fs::write(&path, FILE).unwrap();
+
+        let brazil = Translations::load_from(&path, "pt_BR").unwrap();
+        assert_eq!(brazil.language.as_deref(), Some("pt_BR"));
+        assert_eq!(brazil.achievement_title(10, "Collector"), "Colecionador");
+        assert_eq!(
+            brazil.achievement_description(10, "Collect 100 rings"),
+            "Colete 100 anéis"
+        );
+        // Only the first matching table is used
+        assert_eq!(brazil.game_title("Game"), "Game");
+
+        let portugal = Translations::load_from(&path, "pt_PT").unwrap();
+        assert_eq!(portugal.language.as_deref(), Some("pt"));
+        assert_eq!(portugal.game_title("Game"), "Jogo");
+        assert_eq!(portugal.achievement_title(10, "Collector"), "Coletor");
+        assert_eq!(
+            portugal.achievement_description(10, "Collect 100 rings"),
+            "Collect 100 rings"
+        );
+        assert_eq!(portugal.achievement_title(11, "Other"), "Other");
+
+        let german = Translations::load_from(&path, "de_DE").unwrap();
+        assert!(german.is_empty());
+        assert!(
+            Translations::load_from(&tmp.path().join("missing.toml"), "pt")
+                .unwrap()
+                .is_empty()
+        );
+
+        fs::write(&path, "[pt\n").unwrap();
+        assert!(Translations::load_from(&path, "pt").is_err());
+    }
+}
@@ -130,6 +130,20 @@ pub struct GameInfoAndProgress {
     pub user_completion_hardcore: Option<String>,
 }
 
+impl GameInfoAndProgress {
+    /// Add unofficial achievements to the list. They're tagged with the
+    /// unofficial flag and never change the official counts.
+    pub fn merge_unofficial(&mut self, unofficial: Vec<Achievement>) {
+        let achievements = self.achievements.get_or_insert_with(Default::default);
+        for mut achievement in unofficial {
+            achievement.flags = Some(ACHIEVEMENT_FLAG_UNOFFICIAL);
+            achievements
+                .entry(achievement.id.to_string())
+                .or_insert(achievement);
+        }
+    }
+}
+
 /// Individual achievement
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Achievement {
@@ -149,9 +163,26 @@ pub struct Achievement {
     pub date_earned: Option<String>,
     #[serde(rename = "DateEarnedHardcore")]
     pub date_earned_hardcore: Option<String>,
+    /// 3 = core set, 5 = unofficial
+    #[serde(rename = "Flags", default)]
+    pub flags: Option<u32>,
+    /// Players who have earned it, in either mode
+    #[serde(rename = "NumAwarded", default)]
+    pub num_awarded: Option<u32>,
 }
 
+/// Achievement flag for the core (official) set
+pub const ACHIEVEMENT_FLAG_CORE: u32 = 3;
+
+/// Achievement flag for unofficial/bonus achievements
+pub const ACHIEVEMENT_FLAG_UNOFFICIAL: u32 = 5;
+
 impl Achievement {
+    /// Unofficial achievements don't count towards completion
+    pub fn is_unofficial(&self) -> bool {
+        self.flags == Some(ACHIEVEMENT_FLAG_UNOFFICIAL)
+    }
+
     pub fn is_earned(&self) -> bool {
         self.date_earned.is_some() || self.date_earned_hardcore.is_some()
     }
@@ -160,6 +191,12 @@ impl Achievement {
         self.date_earned_hardcore.is_some()
     }
 
+    /// Share of the game's players who have earned it (0-100)
+    pub fn rarity_percent(&self, num_players: u32) -> Option<f32> {
+        let awarded = self.num_awarded?;
+        (num_players > 0).then(|| (awarded as f32 / num_players as f32 * 100.0).min(100.0))
+    }
+
     pub fn badge_url(&self) -> String {
         format!(
             "https://media.retroachievements.org/Badge/{}.png",
@@ -168,6 +205,15 @@ impl Achievement {
     }
 }
 
+/// Response from API_GetGameExtended (used to fetch unofficial achievements)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameExtended {
+    #[serde(rename = "ID")]
+    pub id: u32,
+    #[serde(rename = "Achievements", default)]
+    pub achievements: std::collections::HashMap<String, Achievement>,
+}
+
 /// Game ID lookup result
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GameIdLookup {
@@ -209,3 +255,48 @@ pub struct GameListEntry {
     pub points: u32,
 }
 
+/// A user comment on an achievement page (community hints/solutions)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AchievementComment {
+    #[serde(rename = "User")]
+    pub user: String,
+    #[serde(rename = "Submitted", default)]
+    pub submitted: String,
+    #[serde(rename = "CommentText")]
+    pub text: String,
+}
+
+/// Response from API_GetComments
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommentsResponse {
+    #[serde(rename = "Count", default)]
+    pub count: u32,
+    #[serde(rename = "Total", default)]
+    pub total: u32,
+    #[serde(rename = "Results", default)]
+    pub results: Vec<AchievementComment>,
+}
+
+/// A user followed by the logged-in account
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FollowedUser {
+    #[serde(rename = "User")]
+    pub user: String,
+    #[serde(rename = "Points", default)]
+    pub points: u32,
+    #[serde(rename = "PointsSoftcore", default)]
+    pub points_softcore: u32,
+    #[serde(rename = "IsFollowingMe", default)]
+    pub is_following_me: bool,
+}
+
+/// Response from API_GetUsersIFollow
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FollowedUsersResponse {
+    #[serde(rename = "Count", default)]
+    pub count: u32,
+    #[serde(rename = "Total", default)]
+    pub total: u32,
+    #[serde(rename = "Results", default)]
+    pub results: Vec<FollowedUser>,
+}
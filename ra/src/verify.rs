@@ -0,0 +1,491 @@
+//! Integrity check for the local cache
+//!
+//! Power loss in the middle of a write can leave the achievement database,
+//! a JSON file or an image half written. `kazeta-ra cache verify` checks them
+//! all and repairs what it can:
+//!
+//! - the database gets SQLite's `integrity_check` (`quick_check` at
+//!   `game-start`), and rows orphaned by a cut-short write are deleted
+//! - JSON files must parse; broken cache files are moved to
+//!   `ra_cache/quarantine/` and rebuilt on the next fetch, while broken
+//!   settings, game names and credentials are only reported, since they
+//!   can't be fetched again
+//! - images must be non-empty and look like an image, or they're deleted
+//!   and downloaded again
+//! - when logged in, each cached game's earned flags are compared with the
+//!   API and the game is re-cached if they differ
+//!
+//! A database SQLite can't read is quarantined whole. That loses the local
+//! unlocks of native carts along with it, so the summary says so.
+
+use crate::cache::RACache;
+use crate::types::GameInfoAndProgress;
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Cache files that are rebuilt from the server when they go missing
+const REBUILDABLE_FILES: &[&str] = &["ra_challenges.json", "ra_status.json"];
+/// Files the user wrote or that can't be fetched again; never moved
+const USER_FILES: &[&str] = &[
+    "ra_settings.json",
+    "ra_game_names.json",
+    "ra_credentials.json",
+];
+
+/// What happened to something that failed its check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Outcome {
+    /// Fixed in place
+    Repaired,
+    /// Moved aside to be rebuilt
+    Quarantined,
+    /// Still broken, needs a person
+    Failed,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Finding {
+    /// The file, or "database"
+    pub item: String,
+    pub outcome: Outcome,
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct VerifyReport {
+    /// Files, database checks and games looked at
+    pub checked: usize,
+    /// Only what failed a check; a clean cache has none
+    pub findings: Vec<Finding>,
+}
+
+impl VerifyReport {
+    pub fn count(&self, outcome: Outcome) -> usize {
+        self.findings
+            .iter()
+            .filter(|f| f.outcome == outcome)
+            .count()
+    }
+
+    pub fn is_clean(&self) -> bool {
+        self.findings.is_empty()
+    }
+
+    fn add(&mut self, item: impl Into<String>, outcome: Outcome, detail: impl Into<String>) {
+        self.findings.push(Finding {
+            item: item.into(),
+            outcome,
+            detail: detail.into(),
+        });
+    }
+
+    /// One line, e.g. "Checked 42 items: 1 repaired, 0 quarantined, 0 failed"
+    pub fn summary(&self) -> String {
+        format!(
+            "Checked {} items: {} repaired, {} quarantined, {} failed",
+            self.checked,
+            self.count(Outcome::Repaired),
+            self.count(Outcome::Quarantined),
+            self.count(Outcome::Failed),
+        )
+    }
+}
+
+pub struct CacheVerifier {
+    /// `~/.local/share/kazeta-plus`
+    data_dir: PathBuf,
+}
+
+impl CacheVerifier {
+    pub fn new() -> Result<Self> {
+        let data_dir = dirs::home_dir()
+            .context("Could not find home directory")?
+            .join(".local/share/kazeta-plus");
+        Ok(Self::with_dir(data_dir))
+    }
+
+    pub fn with_dir(data_dir: PathBuf) -> Self {
+        Self { data_dir }
+    }
+
+    fn cache_dir(&self) -> PathBuf {
+        self.data_dir.join("ra_cache")
+    }
+
+    fn quarantine_dir(&self) -> PathBuf {
+        self.cache_dir().join("quarantine")
+    }
+
+    /// The database checks `game-start` runs before using the cache
+    pub fn quick(&self) -> VerifyReport {
+        let mut report = VerifyReport::default();
+        self.verify_database(false, &mut report);
+        report
+    }
+
+    /// Every local check. Returns the cache, if it could be opened, for
+    /// `cross_check`.
+    pub fn full(&self) -> (VerifyReport, Option<RACache>) {
+        let mut report = VerifyReport::default();
+        let cache = self.verify_database(true, &mut report);
+        self.verify_json(&mut report);
+        self.verify_images(&mut report);
+        (report, cache)
+    }
+
+    fn verify_database(&self, full: bool, report: &mut VerifyReport) -> Option<RACache> {
+        let path = self.cache_dir().join("achievements.db");
+        if !path.exists() {
+            return None;
+        }
+        report.checked += 1;
+
+        let problems = RACache::open(&path).and_then(|cache| {
+            let problems = cache.integrity_problems(full)?;
+            Ok((cache, problems))
+        });
+        let problem = match problems {
+            Ok((cache, problems)) if problems.is_empty() => {
+                match cache.remove_orphans() {
+                    Ok(0) => {}
+                    Ok(removed) => report.add(
+                        "database",
+                        Outcome::Repaired,
+                        format!("Removed {} orphaned rows", removed),
+                    ),
+                    Err(e) => report.add("database", Outcome::Failed, format!("{:#}", e)),
+                }
+                return Some(cache);
+            }
+            Ok((_, problems)) => problems.into_iter().take(3).collect::<Vec<_>>().join("; "),
+            Err(e) => format!("{:#}", e),
+        };
+
+        // SQLite keeps recent writes in these until they're checkpointed
+        for suffix in ["-wal", "-shm", "-journal"] {
+            let side = PathBuf::from(format!("{}{}", path.display(), suffix));
+            if side.exists() {
+                let _ = self.quarantine(&side);
+            }
+        }
+        match self.quarantine(&path) {
+            Ok(moved) => report.add(
+                "database",
+                Outcome::Quarantined,
+                format!(
+                    "{} (moved to {}; RA data is fetched again, local cart unlocks are only in the moved file)",
+                    problem,
+                    moved.display()
+                ),
+            ),
+            Err(e) => report.add("database", Outcome::Failed, format!("{}; {:#}", problem, e)),
+        }
+        RACache::open(&path).ok()
+    }
+
+    fn verify_json(&self, report: &mut VerifyReport) {
+        let http_dir = self.cache_dir().join("http");
+        let mut rebuildable: Vec<PathBuf> = fs::read_dir(&http_dir)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .collect();
+        rebuildable.extend(
+            REBUILDABLE_FILES
+                .iter()
+                .map(|name| self.data_dir.join(name)),
+        );
+
+        for path in rebuildable.iter().filter(|path| path.exists()) {
+            report.checked += 1;
+            let Err(problem) = check_json(path) else {
+                continue;
+            };
+            match self.quarantine(path) {
+                Ok(_) => report.add(display_name(path), Outcome::Quarantined, problem),
+                Err(e) => report.add(
+                    display_name(path),
+                    Outcome::Failed,
+                    format!("{}; {:#}", problem, e),
+                ),
+            }
+        }
+
+        for path in USER_FILES
+            .iter()
+            .map(|name| self.data_dir.join(name))
+            .filter(|path| path.exists())
+        {
+            report.checked += 1;
+            if let Err(problem) = check_json(&path) {
+                report.add(
+                    display_name(&path),
+                    Outcome::Failed,
+                    format!("{} (left in place, fix or remove it)", problem),
+                );
+            }
+        }
+    }
+
+    fn verify_images(&self, report: &mut VerifyReport) {
+        let images = fs::read_dir(self.cache_dir().join("images"))
+            .into_iter()
+            .flatten()
+            .flatten();
+        for entry in images.filter(|entry| entry.path().is_file()) {
+            report.checked += 1;
+            let path = entry.path();
+            let Err(problem) = check_image(&path) else {
+                continue;
+            };
+            match fs::remove_file(&path) {
+                Ok(()) => report.add(
+                    display_name(&path),
+                    Outcome::Repaired,
+                    format!("{}, removed to be downloaded again", problem),
+                ),
+                Err(e) => report.add(
+                    display_name(&path),
+                    Outcome::Failed,
+                    format!("{}; {}", problem, e),
+                ),
+            }
+        }
+    }
+
+    /// Move a file into the quarantine directory, stamped with the time so
+    /// repeated runs don't overwrite each other
+    fn quarantine(&self, path: &Path) -> Result<PathBuf> {
+        let dir = self.quarantine_dir();
+        fs::create_dir_all(&dir).context("Failed to create quarantine directory")?;
+        let name = path
+            .file_name()
+            .context("Nothing to quarantine")?
+            .to_string_lossy();
+        let target = dir.join(format!(
+            "{}.{}",
+            name,
+            chrono::Utc::now().format("%Y%m%d%H%M%S")
+        ));
+        fs::rename(path, &target)
+            .with_context(|| format!("Failed to move {} to quarantine", path.display()))?;
+        Ok(target)
+    }
+}
+
+/// Compare each cached game's earned flags with the API and re-cache the
+/// ones that differ. `fetch` gets a game's info and progress by game ID; the
+/// first error stops the check, since the rest would most likely fail too.
+pub fn cross_check(
+    cache: &RACache,
+    games: &[(String, u32)],
+    mut fetch: impl FnMut(u32) -> Result<GameInfoAndProgress>,
+    report: &mut VerifyReport,
+) {
+    for (hash, game_id) in games {
+        report.checked += 1;
+        let info = match fetch(*game_id) {
+            Ok(info) => info,
+            Err(e) => {
+                report.add(
+                    format!("game {}", game_id),
+                    Outcome::Failed,
+                    format!("Couldn't check against RetroAchievements: {:#}", e),
+                );
+                return;
+            }
+        };
+        let mismatches = match cache.earned_mismatches(hash, &info) {
+            Ok(mismatches) if mismatches.is_empty() => continue,
+            Ok(mismatches) => mismatches,
+            Err(e) => {
+                report.add(info.title.clone(), Outcome::Failed, format!("{:#}", e));
+                continue;
+            }
+        };
+        match cache.cache_game(hash, &info) {
+            Ok(()) => report.add(
+                info.title.clone(),
+                Outcome::Repaired,
+                format!(
+                    "{} earned flags updated from RetroAchievements",
+                    mismatches.len()
+                ),
+            ),
+            Err(e) => report.add(info.title.clone(), Outcome::Failed, format!("{:#}", e)),
+        }
+    }
+}
+
+fn check_json(path: &Path) -> Result<(), String> {
+    let contents = fs::read(path).map_err(|e| e.to_string())?;
+    serde_json::from_slice::<serde_json::Value>(&contents)
+        .map(|_| ())
+        .map_err(|e| format!("Invalid JSON: {}", e))
+}
+
+/// Every image the cache downloads is a PNG, JPEG, GIF or WebP
+fn check_image(path: &Path) -> Result<(), String> {
+    let contents = fs::read(path).map_err(|e| e.to_string())?;
+    if contents.is_empty() {
+        return Err("Empty file".to_string());
+    }
+    let known = contents.starts_with(b"\x89PNG")
+        || contents.starts_with(b"\xFF\xD8")
+        || contents.starts_with(b"GIF8")
+        || (contents.starts_with(b"RIFF") && contents.get(8..12) == Some(b"WEBP"));
+    if known {
+        Ok(())
+    } else {
+        Err("Not an image".to_string())
+    }
+}
+
+fn display_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn game(id: u32, earned: Option<&str>) -> GameInfoAndProgress {
+        serde_json::from_value(serde_json::json!({
+            "ID": id, "Title": "Test Game", "ConsoleID": 7, "ConsoleName": "NES", "ImageIcon": "",
+            "ImageTitle": null, "ImageIngame": null, "ImageBoxArt": null,
+            "NumAchievements": 1, "NumDistinctPlayersCasual": 0, "NumDistinctPlayersHardcore": 0,
+            "Achievements": { "10": {
+                "ID": 10, "Title": "First", "Description": "", "Points": 5, "BadgeName": "0",
+                "DisplayOrder": 1, "DateEarned": earned, "DateEarnedHardcore": null,
+            }},
+            "NumAwardedToUser": null, "NumAwardedToUserHardcore": null,
+            "UserCompletion": null, "UserCompletionHardcore": null,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_unreadable_database_is_quarantined() {
+        let tmp = TempDir::new().unwrap();
+        let verifier = CacheVerifier::with_dir(tmp.path().to_path_buf());
+        fs::create_dir_all(verifier.cache_dir()).unwrap();
+        fs::write(
+            verifier.cache_dir().join("achievements.db"),
+            b"not a database, power went out",
+        )
+        .unwrap();
+
+        let (report, cache) = verifier.full();
+        assert_eq!(report.count(Outcome::Quarantined), 1);
+        assert_eq!(report.findings[0].item, "database");
+        assert_eq!(fs::read_dir(verifier.quarantine_dir()).unwrap().count(), 1);
+
+        // A fresh database takes its place
+        assert!(cache.unwrap().integrity_problems(true).unwrap().is_empty());
+        assert!(verifier.quick().is_clean());
+    }
+
+    #[test]
+    fn test_orphans_json_and_images() {
+        let tmp = TempDir::new().unwrap();
+        let verifier = CacheVerifier::with_dir(tmp.path().to_path_buf());
+        let cache_dir = verifier.cache_dir();
+        fs::create_dir_all(cache_dir.join("http")).unwrap();
+        fs::create_dir_all(cache_dir.join("images")).unwrap();
+
+        let db = cache_dir.join("achievements.db");
+        RACache::open(&db)
+            .unwrap()
+            .cache_game("aaa", &game(1, None))
+            .unwrap();
+        // An achievement whose game row never made it to disk
+        rusqlite::Connection::open(&db)
+            .unwrap()
+            .execute_batch(
+                "PRAGMA foreign_keys = OFF;
+                 INSERT INTO achievements (id, game_hash, title) VALUES (20, 'gone', 'Lost');
+                 INSERT INTO user_progress (achievement_id, date_earned) VALUES (20, '2024-01-31 18:04:05');",
+            )
+            .unwrap();
+
+        fs::write(cache_dir.join("http/good.json"), "{}").unwrap();
+        fs::write(cache_dir.join("http/torn.json"), "{\"url\": \"https://").unwrap();
+        fs::write(tmp.path().join("ra_settings.json"), "{").unwrap();
+        fs::write(cache_dir.join("images/ok.png"), b"\x89PNG\r\n\x1a\n").unwrap();
+        fs::write(cache_dir.join("images/empty.png"), b"").unwrap();
+
+        let (report, _) = verifier.full();
+        assert_eq!(
+            report
+                .findings
+                .iter()
+                .find(|f| f.item == "database")
+                .unwrap()
+                .detail,
+            "Removed 2 orphaned rows"
+        );
+        let outcome = |item: &str| {
+            report
+                .findings
+                .iter()
+                .find(|f| f.item == item)
+                .map(|f| f.outcome)
+        };
+        assert_eq!(outcome("torn.json"), Some(Outcome::Quarantined));
+        assert_eq!(outcome("ra_settings.json"), Some(Outcome::Failed));
+        assert_eq!(outcome("empty.png"), Some(Outcome::Repaired));
+        assert_eq!(outcome("good.json"), None);
+        assert_eq!(outcome("ok.png"), None);
+        assert!(tmp.path().join("ra_settings.json").exists());
+        assert!(!cache_dir.join("images/empty.png").exists());
+
+        // Nothing left to repair but the settings file
+        let (again, _) = verifier.full();
+        assert_eq!(again.findings.len(), 1);
+        assert_eq!(
+            again.summary(),
+            format!(
+                "Checked {} items: 0 repaired, 0 quarantined, 1 failed",
+                again.checked
+            )
+        );
+    }
+
+    #[test]
+    fn test_cross_check_repairs_earned_flags() {
+        let tmp = TempDir::new().unwrap();
+        let cache = RACache::open(&tmp.path().join("achievements.db")).unwrap();
+        cache.cache_game("aaa", &game(1, None)).unwrap();
+        let games = vec![("aaa".to_string(), 1)];
+
+        let mut report = VerifyReport::default();
+        cross_check(
+            &cache,
+            &games,
+            |_| Ok(game(1, Some("2024-01-31 18:04:05"))),
+            &mut report,
+        );
+        assert_eq!(report.count(Outcome::Repaired), 1);
+        assert!(cache.get_achievements("aaa").unwrap()[0].is_earned());
+
+        let mut report = VerifyReport::default();
+        cross_check(
+            &cache,
+            &games,
+            |_| Ok(game(1, Some("2024-01-31 18:04:05"))),
+            &mut report,
+        );
+        assert!(report.is_clean());
+
+        cross_check(&cache, &games, |_| anyhow::bail!("offline"), &mut report);
+        assert_eq!(report.count(Outcome::Failed), 1);
+    }
+}
@@ -48,6 +48,7 @@ PACKAGE_MARKER_FILE="$DEPLOYMENT_DIR/.kazeta-installed-packages"
 CURRENT_PACKAGES=(
     "brightnessctl" "keyd" "rsync" "xxhash" "iwd" "networkmanager"
     "ffmpeg" "unzip" "bluez" "bluez-utils"
+    "cifs-utils" "nfs-utils"
     "base-devel" "dkms" "linux-headers"
     "noto-fonts" "ttf-dejavu" "ttf-liberation" "noto-fonts-emoji"
     "pipewire-alsa" "alsa-utils"
@@ -129,6 +129,7 @@ pacman -Syy
 PACKAGES_TO_INSTALL=(
     "brightnessctl" "keyd" "rsync" "xxhash" "iwd" "networkmanager"
     "ffmpeg" "unzip" "bluez" "bluez-utils"
+    "cifs-utils" "nfs-utils"
     "base-devel" "dkms" "linux-headers"
     "noto-fonts" "ttf-dejavu" "ttf-liberation" "noto-fonts-emoji"
     "pipewire-alsa" "alsa-utils"